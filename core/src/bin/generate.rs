@@ -0,0 +1,169 @@
+//! Generate random NPCs and monsters from the command line, e.g.
+//!
+//! ```text
+//! generate monster --party-level 3 --type humanoid
+//! generate npc --species nat20_core::species.dragonborn --class nat20_core::class.wizard --level 5
+//! ```
+
+use std::{process::ExitCode, str::FromStr};
+
+use hecs::{Entity, World};
+use nat20_core::{
+    components::{
+        health::hit_points::HitPoints,
+        id::{ClassId, Name, SpeciesId},
+        items::inventory::{Inventory, ItemContainer},
+        level::{ChallengeRating, CharacterLevels, Level},
+        personality::Personality,
+        species::{CreatureSize, CreatureType},
+    },
+    systems::{
+        self,
+        generator::{MonsterFilter, NpcFilter},
+        time::RestKind,
+    },
+};
+
+const USAGE: &str = "\
+Usage:
+    generate monster [--party-level <level>] [--min-cr <cr>] [--max-cr <cr>]
+                     [--type <creature type>] [--size <size>] [--count <count>]
+    generate npc [--species <species id>] [--class <class id>] [--level <level>]
+                 [--name <name>] [--count <count>]";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let Some((command, options)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let result = parse_options(options).and_then(|options| match command.as_str() {
+        "monster" => generate_monsters(&options),
+        "npc" => generate_npcs(&options),
+        _ => Err(format!("Unknown command '{}'", command)),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Pairs of `--option value`
+fn parse_options(args: &[String]) -> Result<Vec<(String, String)>, String> {
+    args.chunks(2)
+        .map(|pair| match pair {
+            [option, value] if option.starts_with("--") => {
+                Ok((option.trim_start_matches("--").to_string(), value.clone()))
+            }
+            [option, _] => Err(format!("Expected an option, found '{}'", option)),
+            [option] => Err(format!("Missing value for '{}'", option)),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn option<T: FromStr>(options: &[(String, String)], name: &str) -> Result<Option<T>, String> {
+    options
+        .iter()
+        .find(|(option, _)| option == name)
+        .map(|(_, value)| {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid value '{}' for --{}", value, name))
+        })
+        .transpose()
+}
+
+fn serde_option<T: serde::de::DeserializeOwned>(
+    options: &[(String, String)],
+    name: &str,
+) -> Result<Option<T>, String> {
+    options
+        .iter()
+        .find(|(option, _)| option == name)
+        .map(|(_, value)| {
+            serde_plain::from_str(value)
+                .map_err(|_| format!("Invalid value '{}' for --{}", value, name))
+        })
+        .transpose()
+}
+
+fn generate_monsters(options: &[(String, String)]) -> Result<(), String> {
+    let mut filter = match option::<u8>(options, "party-level")? {
+        Some(party_level) => MonsterFilter::for_party_level(party_level),
+        None => MonsterFilter::default(),
+    };
+    if let Some(min) = option::<u8>(options, "min-cr")? {
+        filter.min_challenge_rating = Some(min);
+    }
+    if let Some(max) = option::<u8>(options, "max-cr")? {
+        filter.max_challenge_rating = Some(max);
+    }
+    filter.creature_type = serde_option::<CreatureType>(options, "type")?;
+    filter.size = serde_option::<CreatureSize>(options, "size")?;
+
+    let mut world = World::new();
+    for _ in 0..option::<usize>(options, "count")?.unwrap_or(1) {
+        let monster = systems::generator::generate_monster(&mut world, &filter)
+            .ok_or_else(|| format!("No monster templates match {:?}", filter))?;
+        print_creature(&mut world, monster.id());
+    }
+    Ok(())
+}
+
+fn generate_npcs(options: &[(String, String)]) -> Result<(), String> {
+    let filter = NpcFilter {
+        species: option::<SpeciesId>(options, "species")?,
+        class: option::<ClassId>(options, "class")?,
+        level: option::<u8>(options, "level")?.unwrap_or(1),
+    };
+    let name = Name::new(option::<String>(options, "name")?.unwrap_or("NPC".to_string()));
+
+    let mut world = World::new();
+    for _ in 0..option::<usize>(options, "count")?.unwrap_or(1) {
+        let npc = systems::generator::generate_npc(&mut world, name.clone(), &filter)
+            .map_err(|error| format!("Failed to generate NPC: {:?}", error))?;
+        print_creature(&mut world, npc.id());
+    }
+    Ok(())
+}
+
+fn print_creature(world: &mut World, entity: Entity) {
+    systems::time::on_rest_end(world, &[entity], &RestKind::Long);
+
+    println!(
+        "{}",
+        systems::helpers::get_component::<Name>(world, entity).as_str()
+    );
+    if let Ok(species) = world.get::<&SpeciesId>(entity) {
+        println!("  Species: {}", *species);
+    }
+    if let Ok(levels) = world.get::<&CharacterLevels>(entity) {
+        println!("  Level: {}", levels.total_level());
+        for (class, progression) in levels.all_classes() {
+            println!("    {}: {}", class, progression.level());
+        }
+    }
+    if let Ok(challenge_rating) = world.get::<&ChallengeRating>(entity) {
+        println!("  Challenge rating: {}", challenge_rating.total_level());
+    }
+    println!(
+        "  Hit points: {}",
+        systems::helpers::get_component::<HitPoints>(world, entity).max()
+    );
+    if let Ok(personality) = world.get::<&Personality>(entity) {
+        println!("  Personality: {}", *personality);
+    }
+    let inventory = systems::helpers::get_component::<Inventory>(world, entity);
+    if !inventory.items().is_empty() {
+        println!("  Inventory:");
+        for item in inventory.items() {
+            println!("    {}", item.item().name);
+        }
+    }
+}
@@ -2,24 +2,40 @@ pub mod ability;
 pub mod actions;
 pub mod ai;
 pub mod background;
+pub mod biography;
+pub mod campaign;
 pub mod class;
+pub mod control;
 pub mod d20;
 pub mod damage;
 pub mod dice;
+pub mod disease;
 pub mod effects;
 pub mod faction;
+pub mod familiar;
+pub mod fear;
 pub mod feat;
 pub mod health;
+pub mod hidden;
 pub mod id;
+pub mod illusion;
+pub mod images;
 pub mod items;
 pub mod level;
 pub mod level_up;
+pub mod lever;
+pub mod lock;
 pub mod modifier;
+pub mod personality;
 pub mod proficiency;
 pub mod resource;
 pub mod saving_throw;
+pub mod shapechange;
 pub mod skill;
 pub mod species;
 pub mod speed;
 pub mod spells;
+pub mod surface;
+pub mod sustenance;
 pub mod time;
+pub mod travel;
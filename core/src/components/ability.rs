@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt,
     hash::Hash,
     str::FromStr,
@@ -83,20 +83,37 @@ impl From<Ability> for String {
     }
 }
 
+/// The highest an ability score can normally be raised to, e.g. by an Ability
+/// Score Improvement
+pub const ABILITY_SCORE_MAX: i32 = 20;
+
+/// How an effect changes an ability score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbilityScoreOperation {
+    /// Add to the score, e.g. +2 from a Ring of Strength
+    Modify(i32),
+    /// Set the score to a fixed value unless it's already higher, e.g. 19 from
+    /// a Headband of Intellect
+    Set(i32),
+    /// Raise the highest value the score can be increased to, e.g. 30 from an
+    /// Epic Boon
+    RaiseMaximum(i32),
+}
+
 #[derive(Debug, Clone)]
 pub struct AbilityScore {
     pub ability: Ability,
     pub base: i32,
     pub modifiers: ModifierSet,
+    /// Fixed values the score is set to if they're higher than the score itself
+    pub overrides: BTreeMap<ModifierSource, i32>,
+    /// Sources which raise the highest value the score can be increased to
+    pub maximums: BTreeMap<ModifierSource, i32>,
 }
 
 impl AbilityScore {
     pub fn default(ability: Ability) -> Self {
-        Self {
-            ability,
-            base: 10,
-            modifiers: ModifierSet::new(),
-        }
+        Self::new(ability, 10)
     }
 
     pub fn new(ability: Ability, base: i32) -> Self {
@@ -104,10 +121,15 @@ impl AbilityScore {
             ability,
             base,
             modifiers: ModifierSet::new(),
+            overrides: BTreeMap::new(),
+            maximums: BTreeMap::new(),
         }
     }
 
     pub fn ability_modifier(&self) -> ModifierSet {
+        if let Some((source, value)) = self.active_override() {
+            return ModifierSet::from(source.clone(), (value - 10) / 2);
+        }
         let mut ability_modifiers = self.modifiers.clone();
         ability_modifiers.scale_modifiers(0.5);
         let base_modifier = (self.base - 10) / 2;
@@ -116,8 +138,53 @@ impl AbilityScore {
     }
 
     pub fn total(&self) -> i32 {
+        match self.active_override() {
+            Some((_, value)) => *value,
+            None => self.unset_total(),
+        }
+    }
+
+    /// The score without any of the overrides, i.e. what it goes back to when
+    /// e.g. a Headband of Intellect is taken off. Improvements apply to this
+    /// score rather than the overridden one.
+    pub fn unset_total(&self) -> i32 {
         self.base + self.modifiers.total()
     }
+
+    /// The highest override, if it's higher than the score itself
+    pub fn active_override(&self) -> Option<(&ModifierSource, &i32)> {
+        self.overrides
+            .iter()
+            .max_by_key(|(_, value)| **value)
+            .filter(|(_, value)| **value > self.unset_total())
+    }
+
+    /// The highest value the score can be increased to, which is the default
+    /// unless something raises it
+    pub fn max_score(&self, default: i32) -> i32 {
+        self.maximums.values().copied().fold(default, i32::max)
+    }
+
+    pub fn apply(&mut self, source: ModifierSource, operation: AbilityScoreOperation) {
+        match operation {
+            AbilityScoreOperation::Modify(delta) => {
+                self.modifiers.add_modifier(source, delta);
+            }
+            AbilityScoreOperation::Set(value) => {
+                self.overrides.insert(source, value);
+            }
+            AbilityScoreOperation::RaiseMaximum(value) => {
+                self.maximums.insert(source, value);
+            }
+        }
+    }
+
+    /// Remove everything the source does to the score
+    pub fn remove(&mut self, source: &ModifierSource) {
+        self.modifiers.remove_modifier(source);
+        self.overrides.remove(source);
+        self.maximums.remove(source);
+    }
 }
 
 impl fmt::Display for AbilityScore {
@@ -132,6 +199,10 @@ impl fmt::Display for AbilityScore {
             sign,
             modifier.abs()
         )?;
+        if let Some((source, _)) = self.active_override() {
+            write!(f, " (set by {})", source)?;
+            return Ok(());
+        }
         if self.modifiers.is_empty() {
             return Ok(());
         }
@@ -177,6 +248,15 @@ impl AbilityScoreMap {
         self.get(ability).ability_modifier()
     }
 
+    pub fn apply(
+        &mut self,
+        ability: &Ability,
+        source: ModifierSource,
+        operation: AbilityScoreOperation,
+    ) {
+        self.get_mut(ability).apply(source, operation);
+    }
+
     pub fn get_max_score(&self, abilities: &[Ability]) -> (Ability, i32) {
         let mut max_ability = abilities[0];
         let mut max_score = self.get(&max_ability).base;
@@ -200,7 +280,7 @@ impl KeyedModifiable<Ability> for AbilityScoreMap {
     }
 
     fn remove_modifier(&mut self, ability: &Ability, source: &ModifierSource) {
-        self.get_mut(ability).modifiers.remove_modifier(source);
+        self.get_mut(ability).remove(source);
     }
 
     fn total(&self, ability: &Ability) -> i32 {
@@ -332,4 +412,55 @@ mod tests {
         );
         assert_eq!(ability_scores.total(&Ability::Dexterity), 15);
     }
+
+    #[test]
+    fn ability_score_override() {
+        let headband =
+            ModifierSource::Item(ItemId::new("nat20_core", "item.headband_of_intellect"));
+        let mut ability_scores = AbilityScoreMap::new();
+        ability_scores.set(
+            Ability::Intelligence,
+            AbilityScore::new(Ability::Intelligence, 12),
+        );
+        ability_scores.apply(
+            &Ability::Intelligence,
+            headband.clone(),
+            AbilityScoreOperation::Set(19),
+        );
+        assert_eq!(ability_scores.total(&Ability::Intelligence), 19);
+        assert_eq!(
+            ability_scores
+                .ability_modifier(&Ability::Intelligence)
+                .total(),
+            4
+        );
+
+        // The override does nothing if the score is already higher
+        ability_scores.add_modifier(
+            &Ability::Intelligence,
+            ModifierSource::Custom("Tome".to_string()),
+            8,
+        );
+        assert_eq!(ability_scores.total(&Ability::Intelligence), 20);
+
+        ability_scores.remove_modifier(
+            &Ability::Intelligence,
+            &ModifierSource::Custom("Tome".to_string()),
+        );
+        ability_scores.remove_modifier(&Ability::Intelligence, &headband);
+        assert_eq!(ability_scores.total(&Ability::Intelligence), 12);
+    }
+
+    #[test]
+    fn ability_score_maximum() {
+        let boon = ModifierSource::Custom("Epic Boon".to_string());
+        let mut ability_score = AbilityScore::new(Ability::Strength, 20);
+        assert_eq!(ability_score.max_score(ABILITY_SCORE_MAX), 20);
+
+        ability_score.apply(boon.clone(), AbilityScoreOperation::RaiseMaximum(30));
+        assert_eq!(ability_score.max_score(ABILITY_SCORE_MAX), 30);
+
+        ability_score.remove(&boon);
+        assert_eq!(ability_score.max_score(ABILITY_SCORE_MAX), 20);
+    }
 }
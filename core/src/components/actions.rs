@@ -1,2 +1,7 @@
 pub mod action;
+pub mod constraint;
+pub mod custom;
+pub mod modification;
+pub mod preview;
+pub mod resolution;
 pub mod targeting;
@@ -5,29 +5,45 @@ use std::{
 };
 
 use hecs::{Entity, World};
-use serde::Deserialize;
+use parry3d::na::Point3;
+use serde::{Deserialize, Serialize};
+use tracing::error;
 
 use crate::{
     components::{
-        actions::targeting::{TargetInstance, TargetingContext},
+        actions::{
+            constraint::ActionConstraint,
+            custom::CustomActionResult,
+            modification::{EventModification, ModificationDelta},
+            resolution::TargetResolution,
+            targeting::{TargetInstance, TargetingContext},
+        },
         d20::D20CheckResult,
         damage::{
             AttackRoll, AttackRollResult, DamageMitigationResult, DamageRoll, DamageRollResult,
         },
         dice::{DiceSetRoll, DiceSetRollResult},
-        effects::effect::EffectInstanceTemplate,
-        health::life_state::LifeState,
-        id::{ActionId, EffectId, EntityIdentifier, IdProvider, ScriptId, SpellId},
+        effects::effect::{EffectInstanceTemplate, EffectTag},
+        health::{healing::HealingResult, life_state::LifeState, resurrection::Resurrection},
+        id::{
+            ActionId, CustomActionId, EffectId, EntityIdentifier, IdProvider, ItemId, MonsterId,
+            ScriptId, SpellId,
+        },
+        illusion::IllusionTemplate,
         items::equipment::{armor::ArmorClass, slots::EquipmentSlot},
+        lock::UnlockMethod,
+        modifier::ModifierSource,
         resource::{RechargeRule, ResourceAmountMap},
         saving_throw::SavingThrowDC,
-        spells::spellbook::SpellSource,
+        skill::{SkillCheckDC, SkillContest},
+        spells::{metamagic::Metamagic, spellbook::SpellSource},
+        surface::SurfaceTemplate,
     },
     engine::{
-        event::{ActionData, Event},
+        event::{ActionData, Event, EventId},
         game_state::GameState,
     },
-    registry::{registry::ActionsRegistry, serialize::action::ActionDefinition},
+    registry::{self, registry::ActionsRegistry, serialize::action::ActionDefinition},
     systems::{self},
 };
 
@@ -49,22 +65,81 @@ pub enum ActionContext {
         /// most spells have different effects based on the level at which they are cast.
         /// For example, Fireball deals more damage when cast at a higher level.
         level: u8,
+        /// Metamagic options applied to the spell, e.g. Twinned Spell
+        metamagic: Vec<Metamagic>,
+    },
+    /// Actions with a variable cost, e.g. Lay on Hands, are performed with the
+    /// amount the actor chose to spend, on top of the context they'd otherwise
+    /// be performed in
+    Variable {
+        amount: u8,
+        context: Box<ActionContext>,
     },
     // TODO: Not sure if Other is needed
     Other,
 }
 
+impl ActionContext {
+    /// The context the action is performed in, looking past the amount chosen
+    /// for a variable cost
+    pub fn base(&self) -> &ActionContext {
+        match self {
+            ActionContext::Variable { context, .. } => context.base(),
+            _ => self,
+        }
+    }
+
+    pub fn base_mut(&mut self) -> &mut ActionContext {
+        match self {
+            ActionContext::Variable { context, .. } => context.base_mut(),
+            other => other,
+        }
+    }
+
+    /// The amount chosen for an action with a variable cost
+    pub fn amount(&self) -> Option<u8> {
+        match self {
+            ActionContext::Variable { amount, .. } => Some(*amount),
+            _ => None,
+        }
+    }
+}
+
 pub type DamageFunction = dyn Fn(&World, Entity, &ActionContext) -> DamageRoll + Send + Sync;
 pub type AttackRollFunction =
     dyn Fn(&World, Entity, Entity, &ActionContext) -> AttackRoll + Send + Sync;
 pub type SavingThrowFunction =
     dyn Fn(&World, Entity, &ActionContext) -> SavingThrowDC + Send + Sync;
+/// The damage of an action against one of its targets, in the situation the
+/// target was resolved in, see `TargetResolution`
+pub type TargetDamageFunction =
+    dyn Fn(&World, Entity, &ActionContext, &TargetResolution) -> DamageRoll + Send + Sync;
+/// The saving throw a target of an action has to make, in the situation the
+/// target was resolved in, see `TargetResolution`
+pub type TargetSavingThrowFunction =
+    dyn Fn(&World, Entity, &ActionContext, &TargetResolution) -> SavingThrowDC + Send + Sync;
+pub type SkillCheckFunction = dyn Fn(&World, Entity, &ActionContext) -> SkillCheckDC + Send + Sync;
 pub type HealFunction = dyn Fn(&World, Entity, &ActionContext) -> DiceSetRoll + Send + Sync;
+pub type UtilityFunction =
+    dyn Fn(&mut GameState, &ActionData, Entity) -> UtilityOutcome + Send + Sync;
 
 #[derive(Clone)]
 pub enum DamageOnFailure {
     Half,
-    Custom(Arc<DamageFunction>),
+    Custom(Arc<TargetDamageFunction>),
+}
+
+/// How the damage of an action is rolled when it affects several targets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageRollPolicy {
+    /// Every target gets its own damage roll, e.g. each ray of Scorching Ray
+    #[default]
+    PerTarget,
+    /// The damage is rolled once and every target takes the same roll, halved
+    /// or not depending on its own saving throw, e.g. Fireball. Critical hits
+    /// don't add any dice to the shared roll.
+    Once,
 }
 
 #[derive(Clone)]
@@ -75,16 +150,50 @@ pub enum ActionCondition {
         damage_on_miss: Option<DamageOnFailure>,
     },
     SavingThrow {
-        saving_throw: Arc<SavingThrowFunction>,
+        saving_throw: Arc<TargetSavingThrowFunction>,
         damage_on_save: Option<DamageOnFailure>,
     },
+    /// The performer has to succeed on a skill check for the payload to apply,
+    /// e.g. a Medicine check to stabilize a dying creature
+    SkillCheck {
+        skill_check: Arc<SkillCheckFunction>,
+    },
+    /// The performer and the target make opposing skill checks, and the
+    /// payload applies if the performer wins, e.g. when trying to disarm
+    Contest {
+        contest: SkillContest,
+    },
 }
 
 #[derive(Clone)]
 pub struct ActionPayload {
-    damage: Option<Arc<DamageFunction>>,
+    damage: Option<Arc<TargetDamageFunction>>,
     effect: Option<EffectInstanceTemplate>,
     healing: Option<Arc<HealFunction>>,
+    /// Whether the payload stabilizes a dying target
+    stabilize: bool,
+    /// Brings a dead target back to life
+    revive: Option<Resurrection>,
+    /// Ends the effects on the target with any of these tags, e.g. curses
+    cures: Vec<EffectTag>,
+    /// Items which appear in the inventory of the target, e.g. Goodberry
+    conjures: Vec<ItemId>,
+    /// Whether the target searches its surroundings for hidden features
+    search: bool,
+    /// Whether the target drops the weapon it's holding, or the targeted
+    /// weapon if the action targets an item
+    disarm: bool,
+    /// Whether the damage is dealt to the targeted item instead of its owner
+    sunder: bool,
+    /// Whether the actor picks up the targeted item lying on the ground
+    pick_up: bool,
+    /// Whether the owner of the targeted item puts it down on the ground
+    drop_item: bool,
+    /// Whether the weapon used for the attack leaves the hand of the actor and
+    /// lands next to the target
+    throw: bool,
+    /// Whether the actor studies the target to see if it's an illusion
+    investigate: bool,
 }
 
 #[derive(Debug)]
@@ -94,14 +203,36 @@ pub enum ActionPayloadError {
 
 impl ActionPayload {
     pub fn new(
-        damage: Option<Arc<DamageFunction>>,
+        damage: Option<Arc<TargetDamageFunction>>,
         effect: Option<EffectInstanceTemplate>,
         healing: Option<Arc<HealFunction>>,
+        stabilize: bool,
+        revive: Option<Resurrection>,
+        cures: Vec<EffectTag>,
+        conjures: Vec<ItemId>,
+        search: bool,
+        disarm: bool,
+        sunder: bool,
+        pick_up: bool,
+        drop_item: bool,
+        throw: bool,
+        investigate: bool,
     ) -> Result<Self, ActionPayloadError> {
         let payload = ActionPayload {
             damage,
             effect,
             healing,
+            stabilize,
+            revive,
+            cures,
+            conjures,
+            search,
+            disarm,
+            sunder,
+            pick_up,
+            drop_item,
+            throw,
+            investigate,
         };
 
         if payload.is_empty() {
@@ -112,14 +243,36 @@ impl ActionPayload {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.damage.is_none() && self.effect.is_none() && self.healing.is_none()
+        self.damage.is_none()
+            && self.effect.is_none()
+            && self.healing.is_none()
+            && !self.stabilize
+            && self.revive.is_none()
+            && self.cures.is_empty()
+            && self.conjures.is_empty()
+            && !self.search
+            && !self.disarm
+            && !self.pick_up
+            && !self.drop_item
+            && !self.investigate
     }
 
-    pub fn with_damage(damage: Arc<DamageFunction>) -> Self {
+    pub fn with_damage(damage: Arc<TargetDamageFunction>) -> Self {
         Self {
             damage: Some(damage),
             effect: None,
             healing: None,
+            stabilize: false,
+            revive: None,
+            cures: Vec::new(),
+            conjures: Vec::new(),
+            search: false,
+            disarm: false,
+            sunder: false,
+            pick_up: false,
+            drop_item: false,
+            throw: false,
+            investigate: false,
         }
     }
 
@@ -128,6 +281,17 @@ impl ActionPayload {
             damage: None,
             effect: Some(effect),
             healing: None,
+            stabilize: false,
+            revive: None,
+            cures: Vec::new(),
+            conjures: Vec::new(),
+            search: false,
+            disarm: false,
+            sunder: false,
+            pick_up: false,
+            drop_item: false,
+            throw: false,
+            investigate: false,
         }
     }
 
@@ -136,10 +300,21 @@ impl ActionPayload {
             damage: None,
             effect: None,
             healing: Some(healing),
+            stabilize: false,
+            revive: None,
+            cures: Vec::new(),
+            conjures: Vec::new(),
+            search: false,
+            disarm: false,
+            sunder: false,
+            pick_up: false,
+            drop_item: false,
+            throw: false,
+            investigate: false,
         }
     }
 
-    pub fn damage(&self) -> Option<&Arc<DamageFunction>> {
+    pub fn damage(&self) -> Option<&Arc<TargetDamageFunction>> {
         self.damage.as_ref()
     }
 
@@ -150,6 +325,50 @@ impl ActionPayload {
     pub fn healing(&self) -> Option<&Arc<HealFunction>> {
         self.healing.as_ref()
     }
+
+    pub fn stabilize(&self) -> bool {
+        self.stabilize
+    }
+
+    pub fn revive(&self) -> Option<&Resurrection> {
+        self.revive.as_ref()
+    }
+
+    pub fn cures(&self) -> &[EffectTag] {
+        &self.cures
+    }
+
+    pub fn conjures(&self) -> &[ItemId] {
+        &self.conjures
+    }
+
+    pub fn search(&self) -> bool {
+        self.search
+    }
+
+    pub fn disarm(&self) -> bool {
+        self.disarm
+    }
+
+    pub fn sunder(&self) -> bool {
+        self.sunder
+    }
+
+    pub fn pick_up(&self) -> bool {
+        self.pick_up
+    }
+
+    pub fn drop_item(&self) -> bool {
+        self.drop_item
+    }
+
+    pub fn throw(&self) -> bool {
+        self.throw
+    }
+
+    pub fn investigate(&self) -> bool {
+        self.investigate
+    }
 }
 
 #[derive(Clone)]
@@ -159,15 +378,67 @@ pub enum ActionKind {
         payload: ActionPayload,
     },
     Composite {
-        actions: Vec<ActionKind>,
+        actions: Vec<CompositeStep>,
     },
     Variant {
         variants: Vec<ActionId>,
     },
+    /// Interacting with the world rather than the creatures in it, e.g. picking
+    /// a lock or pulling a lever. The function changes the target directly and
+    /// reports what happened to it.
+    Utility {
+        utility: Arc<UtilityFunction>,
+    },
     Reaction {
         reaction: ScriptId,
     },
-    Custom(Arc<dyn Fn(&World, Entity, &ActionContext) + Send + Sync>),
+    /// Image created at each targeted point, e.g. Minor Image
+    Illusion {
+        illusion: IllusionTemplate,
+    },
+    /// Performed by the [`CustomAction`](super::custom::CustomAction) registered
+    /// under the id
+    Custom {
+        action: CustomActionId,
+    },
+}
+
+/// One of the sub-actions of a composite action. The steps are performed in
+/// order, and each step can depend on how the earlier steps went for a target.
+#[derive(Debug, Clone)]
+pub struct CompositeStep {
+    pub kind: ActionKind,
+    pub only_if: CompositeStepCondition,
+    /// What the step costs on top of the rest of the action
+    pub resource_cost: ResourceAmountMap,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeStepCondition {
+    #[default]
+    Always,
+    /// The last step performed on the target succeeded, e.g. the attack hit
+    PreviousSucceeded,
+    /// The last step performed on the target failed, e.g. the target made its
+    /// saving throw
+    PreviousFailed,
+}
+
+impl CompositeStepCondition {
+    /// Whether a step with the condition is performed on a target, given the
+    /// result of the last step performed on it
+    pub fn is_met(&self, previous: Option<&ActionKindResult>) -> bool {
+        match self {
+            CompositeStepCondition::Always => true,
+            CompositeStepCondition::PreviousSucceeded => {
+                previous.is_some_and(ActionKindResult::succeeded)
+            }
+            CompositeStepCondition::PreviousFailed => {
+                previous.is_some_and(|previous| !previous.succeeded())
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -242,6 +513,15 @@ impl DamageOutcome {
     }
 }
 
+/// The saving throw a target made against an action, whether or not the action
+/// deals any damage
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavingThrowOutcome {
+    pub dc: SavingThrowDC,
+    pub result: D20CheckResult,
+    pub success: bool,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct EffectOutcome {
     pub effect: EffectId,
@@ -261,29 +541,135 @@ pub enum EffectApplyRule {
 #[derive(Debug, Clone, PartialEq)]
 pub struct HealingOutcome {
     pub healing: DiceSetRollResult,
+    /// The healing actually received after the target's healing modifiers
+    pub received: HealingResult,
     pub new_life_state: Option<LifeState>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ActionOutcomeBundle {
     pub damage: Option<DamageOutcome>,
+    /// The saving throw the target made, if the action calls for one
+    pub saving_throw: Option<SavingThrowOutcome>,
     pub effect: Option<EffectOutcome>,
     pub healing: Option<HealingOutcome>,
+    /// The new life state of a target that was stabilized, if it was dying
+    pub stabilized: Option<LifeState>,
+    /// The new life state of a target that was brought back to life
+    pub revived: Option<LifeState>,
+    /// The effects which were ended on the target, e.g. by Remove Curse
+    pub cured: Vec<EffectId>,
+    /// The items which appeared in the inventory of the target
+    pub conjured: Vec<ItemId>,
+    /// The weapon the target dropped
+    pub disarmed: Option<ItemId>,
+    /// The damage dealt to the targeted item
+    pub sundered: Option<ItemDamageOutcome>,
+}
+
+impl ActionOutcomeBundle {
+    /// Whether the action got through to the target, i.e. the attack hit or the
+    /// target failed its saving throw. Actions without either succeed if they
+    /// did anything at all, since a failed skill check leaves nothing behind.
+    pub fn succeeded(&self) -> bool {
+        if let Some(saving_throw) = &self.saving_throw {
+            return !saving_throw.success;
+        }
+        if let Some(DamageOutcome {
+            kind:
+                DamageResolutionKind::AttackRoll {
+                    attack_roll,
+                    armor_class,
+                },
+            ..
+        }) = &self.damage
+        {
+            return attack_roll.hits(armor_class);
+        }
+        self.damage.is_some()
+            || self.effect.as_ref().is_some_and(|effect| effect.applied)
+            || self.healing.is_some()
+            || self.stabilized.is_some()
+            || self.revived.is_some()
+            || !self.cured.is_empty()
+            || !self.conjured.is_empty()
+            || self.disarmed.is_some()
+            || self.sundered.is_some()
+    }
+}
+
+/// What a utility action did to its target
+#[derive(Debug, Clone, PartialEq)]
+pub enum UtilityOutcome {
+    Unlocked(UnlockMethod),
+    /// The lock held, e.g. the check to pick it failed
+    StillLocked,
+    LeverPulled {
+        active: bool,
+    },
+    /// A companion of the form appeared next to the target, e.g. a familiar
+    Summoned(MonsterId),
+    /// The target took the form of the creature, e.g. with Wild Shape
+    Shapechanged(MonsterId),
+    /// There was nothing to do with the target, e.g. it had no lock to open
+    NoEffect,
+}
+
+impl UtilityOutcome {
+    pub fn succeeded(&self) -> bool {
+        match self {
+            UtilityOutcome::Unlocked(_)
+            | UtilityOutcome::LeverPulled { .. }
+            | UtilityOutcome::Summoned(_)
+            | UtilityOutcome::Shapechanged(_) => true,
+            UtilityOutcome::StillLocked | UtilityOutcome::NoEffect => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemDamageOutcome {
+    pub item: ItemId,
+    pub damage: u32,
+    pub remaining_hit_points: u32,
+    /// Broken items are destroyed
+    pub broken: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ActionKindResult {
     Standard(ActionOutcomeBundle),
-    Utility,
+    Utility(UtilityOutcome),
     Composite { actions: Vec<ActionKindResult> },
     Reaction { result: ReactionResult },
-    Custom {/* ... */},
+    Custom(CustomActionResult),
+}
+
+impl ActionKindResult {
+    /// Whether the action got through to the target. Composite actions succeed
+    /// if any of their steps did.
+    pub fn succeeded(&self) -> bool {
+        match self {
+            ActionKindResult::Standard(outcome) => outcome.succeeded(),
+            ActionKindResult::Composite { actions } => {
+                actions.iter().any(ActionKindResult::succeeded)
+            }
+            ActionKindResult::Utility(outcome) => outcome.succeeded(),
+            ActionKindResult::Custom(result) => result.success,
+            ActionKindResult::Reaction { .. } => true,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub enum ReactionResult {
+    /// Change the pending event before it's resolved, e.g. Shield raising the
+    /// AC against the triggering attack
     ModifyEvent {
-        modification: Arc<dyn Fn(&World, &mut Event) + Send + Sync>,
+        event: EventId,
+        modification: EventModification,
+        /// The change the modification made, for the log
+        delta: ModificationDelta,
     },
     CancelEvent {
         event: Box<Event>,
@@ -295,7 +681,16 @@ pub enum ReactionResult {
 impl Debug for ReactionResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ReactionResult::ModifyEvent { .. } => write!(f, "ModifyEvent"),
+            ReactionResult::ModifyEvent {
+                event,
+                modification,
+                delta,
+            } => f
+                .debug_struct("ModifyEvent")
+                .field("event", event)
+                .field("modification", modification)
+                .field("delta", delta)
+                .finish(),
             ReactionResult::CancelEvent {
                 event,
                 resources_refunded,
@@ -312,7 +707,18 @@ impl Debug for ReactionResult {
 impl PartialEq for ReactionResult {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (ReactionResult::ModifyEvent { .. }, ReactionResult::ModifyEvent { .. }) => true,
+            (
+                ReactionResult::ModifyEvent {
+                    event: e1,
+                    modification: m1,
+                    ..
+                },
+                ReactionResult::ModifyEvent {
+                    event: e2,
+                    modification: m2,
+                    ..
+                },
+            ) => e1 == e2 && m1 == m2,
             (
                 ReactionResult::CancelEvent { event: e1, .. },
                 ReactionResult::CancelEvent { event: e2, .. },
@@ -339,6 +745,12 @@ pub struct Action {
     pub cooldown: Option<RechargeRule>,
     /// If the action is a reaction, this will describe what triggers the reaction.
     pub reaction_trigger: Option<ScriptId>,
+    /// Surface left behind in the area of the action, e.g. the Grease spell
+    pub surface: Option<SurfaceTemplate>,
+    /// Whether the damage is rolled once for all targets or once per target
+    pub damage_roll: DamageRollPolicy,
+    /// Restrictions on when the action can be used, e.g. only while raging
+    pub constraints: Vec<ActionConstraint>,
 }
 
 /// Represents the result of performing an action on a single target. For actions
@@ -347,6 +759,9 @@ pub struct Action {
 pub struct ActionResult {
     pub performer: EntityIdentifier,
     pub target: TargetInstance,
+    /// The point the action was aimed at to affect the target, if it wasn't
+    /// aimed at the target itself, e.g. the center of a Fireball
+    pub point: Option<Point3<f32>>,
     pub kind: ActionKindResult,
 }
 
@@ -369,29 +784,50 @@ impl ActionKind {
         game_state: &mut GameState,
         action_data: &ActionData,
         targets: &[Entity],
+        damage_roll: DamageRollPolicy,
     ) {
         match self {
-            ActionKind::Standard { .. } => {
-                for target in targets {
+            ActionKind::Standard { payload, .. } => {
+                // Every target is resolved up front, so performing the action
+                // on one of them can't change how it's performed on the rest
+                let mut resolutions =
+                    systems::actions::resolve_targets(game_state, action_data, targets);
+                let shared_damage = match damage_roll {
+                    DamageRollPolicy::PerTarget => None,
+                    DamageRollPolicy::Once => resolutions.first().and_then(|resolution| {
+                        systems::actions::roll_shared_damage(
+                            &game_state.world,
+                            action_data,
+                            payload,
+                            resolution,
+                        )
+                    }),
+                };
+                for resolution in &mut resolutions {
+                    resolution.shared_damage = shared_damage.clone();
+                }
+                for resolution in &resolutions {
                     systems::actions::perform_standard_action(
                         game_state,
                         self,
                         action_data,
-                        *target,
+                        resolution,
                     );
                 }
             }
 
             ActionKind::Composite { actions } => {
-                for action in actions {
-                    match action {
-                        ActionKind::Reaction { .. } => {
-                            // Assume this is being performed as part of a reaction
-                            // TODO: Also seems like a bit of a hack
-                            continue;
-                        }
-                        _ => action.perform(game_state, action_data, targets),
-                    }
+                systems::composite::perform(game_state, actions, action_data, targets, damage_roll);
+            }
+
+            ActionKind::Utility { utility } => {
+                for target in targets {
+                    let outcome = utility(game_state, action_data, *target);
+                    let _ = game_state.process_event(Event::action_performed_event(
+                        game_state,
+                        action_data,
+                        vec![(*target, ActionKindResult::Utility(outcome))],
+                    ));
                 }
             }
 
@@ -407,12 +843,58 @@ impl ActionKind {
                 );
             }
 
-            ActionKind::Custom(custom) => {
-                // custom(game_state.world, target, context)
-                todo!("Custom actions are not yet implemented");
+            ActionKind::Illusion { illusion } => {
+                systems::illusions::create(game_state, illusion, action_data);
+            }
+
+            ActionKind::Custom { action } => {
+                let Some(custom) = registry::custom_action::get(action) else {
+                    error!(
+                        "Custom action {} used by {} has not been registered",
+                        action, action_data.action_id
+                    );
+                    return;
+                };
+                for target in targets {
+                    let result = custom.perform(game_state, action_data, *target);
+                    let _ = game_state.process_event(Event::action_performed_event(
+                        game_state,
+                        action_data,
+                        vec![(*target, ActionKindResult::Custom(result))],
+                    ));
+                }
             }
         }
     }
+
+    /// Whether performing the action involves making an attack roll
+    pub fn is_attack(&self) -> bool {
+        match self {
+            ActionKind::Standard { condition, .. } => {
+                matches!(condition, ActionCondition::AttackRoll { .. })
+            }
+            ActionKind::Composite { actions } => actions.iter().any(|step| step.kind.is_attack()),
+            _ => false,
+        }
+    }
+
+    /// The full cost of an action of this kind, i.e. the cost of the action
+    /// itself along with the cost of every step of a composite action
+    pub fn total_cost(&self, mut cost: ResourceAmountMap) -> ResourceAmountMap {
+        if let ActionKind::Composite { actions } = self {
+            for step in actions {
+                for (resource, amount) in step.kind.total_cost(step.resource_cost.clone()) {
+                    match cost.get_mut(&resource) {
+                        Some(total) => *total += amount,
+                        None => {
+                            cost.insert(resource, amount);
+                        }
+                    }
+                }
+            }
+        }
+        cost
+    }
 }
 
 impl Debug for ActionKind {
@@ -421,8 +903,10 @@ impl Debug for ActionKind {
             ActionKind::Standard { .. } => write!(f, "Standard"),
             ActionKind::Composite { actions } => write!(f, "Composite({:?})", actions),
             ActionKind::Variant { variants } => write!(f, "Variants({:?})", variants),
+            ActionKind::Utility { .. } => write!(f, "Utility"),
             ActionKind::Reaction { .. } => write!(f, "Reaction"),
-            ActionKind::Custom(_) => write!(f, "CustomAction"),
+            ActionKind::Illusion { illusion } => write!(f, "Illusion({})", illusion.name),
+            ActionKind::Custom { action } => write!(f, "Custom({})", action),
         }
     }
 }
@@ -440,7 +924,7 @@ impl Action {
         // TODO: Not a fan of having to clone to avoid borrowing issues, but
         // hopefully since most of the effect just have a no-op as their
         // on_action component it'll be cheap to clone
-        let hooks: Vec<_> = systems::effects::effects(&game_state.world, action_data.actor)
+        let hooks: Vec<_> = systems::effects::active_effects(&game_state.world, action_data.actor)
             .iter()
             .filter_map(|effect| Some(effect.effect().on_action.clone()))
             .collect();
@@ -449,7 +933,8 @@ impl Action {
             hook(&mut game_state.world, action_data);
         }
 
-        self.kind.perform(game_state, action_data, targets);
+        self.kind
+            .perform(game_state, action_data, targets, self.damage_roll);
     }
 
     pub fn id(&self) -> &ActionId {
@@ -473,6 +958,10 @@ impl Action {
     pub fn resource_cost_mut(&mut self) -> &mut ResourceAmountMap {
         &mut self.resource_cost
     }
+
+    pub fn constraints(&self) -> &[ActionConstraint] {
+        &self.constraints
+    }
 }
 
 impl IdProvider for Action {
@@ -502,10 +991,17 @@ impl PartialEq for Action {
 }
 
 impl ActionResult {
-    pub fn new(world: &World, performer: Entity, target: Entity, kind: ActionKindResult) -> Self {
+    pub fn new(
+        world: &World,
+        performer: Entity,
+        target: Entity,
+        point: Option<Point3<f32>>,
+        kind: ActionKindResult,
+    ) -> Self {
         ActionResult {
             performer: EntityIdentifier::from_world(world, performer),
-            target: TargetInstance::Entity(target),
+            target: TargetInstance::from_entity(world, target),
+            point,
             kind,
         }
     }
@@ -516,12 +1012,34 @@ pub type ActionMap = HashMap<ActionId, Vec<(ActionContext, ResourceAmountMap)>>;
 
 pub type ActionCooldownMap = HashMap<ActionId, RechargeRule>;
 
+/// Actions which can only be used once per turn that the entity has used during
+/// the current turn, whoever's turn it is
+pub type ActionsUsedThisTurn = HashSet<ActionId>;
+
+/// Actions the entity can take while something lasts, e.g. the extra action
+/// granted by Haste, along with what granted them. An action granted more than
+/// once is only revoked when all of its grants are.
+pub type GrantedActionMap = HashMap<ActionId, Vec<ModifierSource>>;
+
 pub type ReactionSet = HashSet<ActionId>;
 
 // TODO: Not sure if this is the best solution
 pub fn default_actions() -> ActionMap {
     let mut actions = ActionMap::new();
-    for action in [ActionId::new("nat20_core", "action.dash")] {
+    for action in [
+        ActionId::new("nat20_core", "action.dash"),
+        ActionId::new("nat20_core", "action.drop_item"),
+        ActionId::new("nat20_core", "action.force_lock"),
+        ActionId::new("nat20_core", "action.grapple"),
+        ActionId::new("nat20_core", "action.investigate"),
+        ActionId::new("nat20_core", "action.open_lock"),
+        ActionId::new("nat20_core", "action.pick_lock"),
+        ActionId::new("nat20_core", "action.pick_up"),
+        ActionId::new("nat20_core", "action.pull_lever"),
+        ActionId::new("nat20_core", "action.search"),
+        ActionId::new("nat20_core", "action.shove"),
+        ActionId::new("nat20_core", "action.stabilize"),
+    ] {
         let resource_cost = ActionsRegistry::get(&action).unwrap().resource_cost.clone();
         actions.insert(action.clone(), vec![(ActionContext::Other, resource_cost)]);
     }
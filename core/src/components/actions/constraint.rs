@@ -0,0 +1,115 @@
+use std::fmt;
+
+use hecs::{Entity, World};
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    components::{
+        actions::{
+            action::{ActionContext, ActionsUsedThisTurn},
+            targeting::TargetInstance,
+        },
+        id::{ActionId, EffectId},
+        items::equipment::{slots::EquipmentSlot, weapon::WeaponKind},
+    },
+    systems,
+};
+
+/// A restriction on when an action can be used, declared alongside the action in
+/// the registry, e.g. "only while raging" or "requires a free hand".
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionConstraint {
+    /// The action can only be used once during each turn, e.g. once on the
+    /// actor's own turn and once more as a reaction on someone else's
+    OncePerTurn,
+    /// The actor has to be hidden, e.g. after taking the Hide action
+    Hidden,
+    /// The actor has to be affected by the effect, e.g. only while raging
+    RequiresEffect(EffectId),
+    /// The actor can't be affected by the effect, e.g. no spellcasting while raging
+    ForbidsEffect(EffectId),
+    /// The actor needs a hand which isn't holding anything
+    FreeHand,
+    /// The action has to be performed with a weapon of the kind, e.g. melee only
+    Weapon(WeaponKind),
+    /// Every targeted creature or object has to be within the distance of the actor
+    TargetWithin(Length),
+}
+
+impl ActionConstraint {
+    /// Whether the actor meets the constraint, regardless of what it's targeting.
+    /// Constraints on the targets are always met here, see [`Self::allows_target`].
+    pub fn is_met(
+        &self,
+        world: &World,
+        actor: Entity,
+        action_id: &ActionId,
+        context: &ActionContext,
+    ) -> bool {
+        match self {
+            ActionConstraint::OncePerTurn => !world
+                .get::<&ActionsUsedThisTurn>(actor)
+                .is_ok_and(|used| used.contains(action_id)),
+            ActionConstraint::Hidden => systems::visibility::is_hiding(world, actor),
+            ActionConstraint::RequiresEffect(effect_id) => {
+                systems::effects::has_effect(world, actor, effect_id)
+            }
+            ActionConstraint::ForbidsEffect(effect_id) => {
+                !systems::effects::has_effect(world, actor, effect_id)
+            }
+            ActionConstraint::FreeHand => systems::loadout::loadout(world, actor).has_free_hand(),
+            ActionConstraint::Weapon(kind) => match context.base() {
+                ActionContext::Weapon { slot } => match slot {
+                    EquipmentSlot::MeleeMainHand | EquipmentSlot::MeleeOffHand => {
+                        *kind == WeaponKind::Melee
+                    }
+                    EquipmentSlot::RangedMainHand | EquipmentSlot::RangedOffHand => {
+                        *kind == WeaponKind::Ranged
+                    }
+                    _ => false,
+                },
+                _ => false,
+            },
+            ActionConstraint::TargetWithin(_) => true,
+        }
+    }
+
+    /// Whether the target is allowed by the constraint. Constraints on the actor
+    /// always allow the target, see [`Self::is_met`].
+    pub fn allows_target(&self, world: &World, actor: Entity, target: &TargetInstance) -> bool {
+        match self {
+            ActionConstraint::TargetWithin(distance) => match target {
+                TargetInstance::Entity(entity)
+                | TargetInstance::Object(entity)
+                | TargetInstance::Item { owner: entity, .. } => {
+                    systems::geometry::distance_between_entities(world, actor, *entity)
+                        .is_some_and(|between| between <= *distance)
+                }
+                TargetInstance::Point(point) => systems::geometry::get_foot_position(world, actor)
+                    .is_some_and(|position| {
+                        Length::new::<meter>((point - position).magnitude()) <= *distance
+                    }),
+            },
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for ActionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionConstraint::OncePerTurn => write!(f, "Once per turn"),
+            ActionConstraint::Hidden => write!(f, "Requires being hidden"),
+            ActionConstraint::RequiresEffect(effect_id) => write!(f, "Requires {}", effect_id),
+            ActionConstraint::ForbidsEffect(effect_id) => write!(f, "Not while {}", effect_id),
+            ActionConstraint::FreeHand => write!(f, "Requires a free hand"),
+            ActionConstraint::Weapon(kind) => write!(f, "{} weapons only", kind),
+            ActionConstraint::TargetWithin(distance) => {
+                write!(f, "Target within {} ft", distance.get::<foot>())
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::id::CustomActionId,
+    engine::{event::ActionData, game_state::GameState},
+};
+
+/// Mechanics which don't fit any of the built-in action kinds, e.g. homebrew
+/// rules. Implementations are registered under an id with
+/// [`crate::registry::custom_action::register`], and actions refer to them by
+/// that id through `ActionKind::Custom`.
+pub trait CustomAction: Send + Sync + 'static {
+    /// Perform the action on a single target, returning what happened to it
+    fn perform(
+        &self,
+        game_state: &mut GameState,
+        action_data: &ActionData,
+        target: Entity,
+    ) -> CustomActionResult;
+}
+
+/// What a custom action did to its target. Since the engine doesn't know what
+/// the action does, the details are left as arbitrary data for whoever renders
+/// the result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomActionResult {
+    /// The custom action which produced the result
+    pub action: CustomActionId,
+    /// Whether the action got through to the target, e.g. so a composite action
+    /// can decide whether to perform its next step
+    pub success: bool,
+    /// What happened to the target, e.g. "was turned into a newt", for when
+    /// there's no dedicated way of rendering the result
+    pub summary: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+impl CustomActionResult {
+    pub fn new(action: CustomActionId, success: bool, summary: impl Into<String>) -> Self {
+        Self {
+            action,
+            success,
+            summary: summary.into(),
+            payload: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_payload(mut self, payload: serde_json::Value) -> Self {
+        self.payload = payload;
+        self
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+
+use crate::{
+    components::{
+        damage::DamageComponentResult,
+        modifier::{DiceModifierRoll, Modifiable, ModifierSource},
+    },
+    engine::event::{Event, EventKind},
+    systems::d20::{D20CheckDCKind, D20ResultKind},
+};
+
+/// A change a reaction makes to an event before it's resolved, e.g. Shield
+/// raising the AC against the triggering attack or Cutting Words subtracting
+/// from a roll. Any dice are rolled when the reaction is performed, so applying
+/// the modification always has the same outcome.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventModification {
+    /// Flat bonus to a d20 roll, or a penalty if it's negative
+    D20Bonus { source: ModifierSource, bonus: i32 },
+    /// Dice rolled and added to or subtracted from a d20 roll, e.g. Cutting Words
+    D20Dice {
+        source: ModifierSource,
+        roll: DiceModifierRoll,
+    },
+    /// Bonus to the DC of a check or the AC against an attack roll, but only for
+    /// the modified check
+    DCBonus { source: ModifierSource, bonus: i32 },
+    /// Replace the d20 roll with a new one, unless the old one was better and
+    /// the new one doesn't have to be used
+    RerollD20 {
+        new_roll: D20ResultKind,
+        force_use_new: bool,
+    },
+    /// Extra damage added to a damage roll, e.g. Divine Smite
+    AddDamage {
+        components: Vec<DamageComponentResult>,
+    },
+    /// Damage subtracted from a damage roll, spread over its components in
+    /// order without reducing any of them below zero
+    ReduceDamage { source: ModifierSource, amount: i32 },
+}
+
+/// The value a modification changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifiedValue {
+    AttackRoll,
+    SavingThrow,
+    SkillCheck,
+    ArmorClass,
+    DifficultyClass,
+    Damage,
+}
+
+impl fmt::Display for ModifiedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModifiedValue::AttackRoll => write!(f, "Attack roll"),
+            ModifiedValue::SavingThrow => write!(f, "Saving throw"),
+            ModifiedValue::SkillCheck => write!(f, "Skill check"),
+            ModifiedValue::ArmorClass => write!(f, "AC"),
+            ModifiedValue::DifficultyClass => write!(f, "DC"),
+            ModifiedValue::Damage => write!(f, "Damage"),
+        }
+    }
+}
+
+/// How a modification changed the event it was applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModificationDelta {
+    pub value: ModifiedValue,
+    pub before: i32,
+    pub after: i32,
+}
+
+impl EventModification {
+    /// Apply the modification to the event, returning how it changed the event.
+    /// Returns `None` and leaves the event untouched if the modification doesn't
+    /// apply to this kind of event.
+    pub fn apply(&self, event: &mut Event) -> Option<ModificationDelta> {
+        match (self, &mut event.kind) {
+            (
+                EventModification::D20Bonus { source, bonus },
+                EventKind::D20CheckPerformed(_, result, _),
+            ) => Some(modify_roll(result, |result| {
+                result.d20_result_mut().add_bonus(source.clone(), *bonus);
+            })),
+
+            (
+                EventModification::D20Dice { source, roll },
+                EventKind::D20CheckPerformed(_, result, _),
+            ) => Some(modify_roll(result, |result| {
+                result
+                    .d20_result_mut()
+                    .dice_rolls
+                    .insert(source.clone(), roll.clone());
+            })),
+
+            (
+                EventModification::RerollD20 {
+                    new_roll,
+                    force_use_new,
+                },
+                EventKind::D20CheckPerformed(_, result, _),
+            ) => Some(modify_roll(result, |result| {
+                if *force_use_new || new_roll.d20_result().total() > result.d20_result().total() {
+                    *result = new_roll.clone();
+                }
+            })),
+
+            (
+                EventModification::DCBonus { source, bonus },
+                EventKind::D20CheckPerformed(_, _, dc_kind),
+            ) => {
+                let (value, before) = dc_total(dc_kind);
+                match dc_kind {
+                    D20CheckDCKind::SavingThrow(dc) => dc.dc.add_modifier(source.clone(), *bonus),
+                    D20CheckDCKind::Skill(dc) => dc.dc.add_modifier(source.clone(), *bonus),
+                    D20CheckDCKind::AttackRoll(_, armor_class) => {
+                        armor_class.add_modifier(source.clone(), *bonus)
+                    }
+                }
+                Some(ModificationDelta {
+                    value,
+                    before,
+                    after: dc_total(dc_kind).1,
+                })
+            }
+
+            (
+                EventModification::AddDamage { components },
+                EventKind::DamageRollPerformed(_, damage_roll, _),
+            ) => {
+                let before = damage_roll.total;
+                for component in components {
+                    damage_roll.add_component(component.clone());
+                }
+                Some(ModificationDelta {
+                    value: ModifiedValue::Damage,
+                    before,
+                    after: damage_roll.total,
+                })
+            }
+
+            (
+                EventModification::ReduceDamage { source, amount },
+                EventKind::DamageRollPerformed(_, damage_roll, _),
+            ) => {
+                let before = damage_roll.total;
+                let mut remaining = *amount;
+                for component in &mut damage_roll.components {
+                    let reduction = remaining.min(component.result.subtotal.max(0));
+                    if reduction <= 0 {
+                        continue;
+                    }
+                    component
+                        .result
+                        .modifiers
+                        .add_modifier(source.clone(), -reduction);
+                    component.result.subtotal -= reduction;
+                    damage_roll.total -= reduction;
+                    remaining -= reduction;
+                }
+                Some(ModificationDelta {
+                    value: ModifiedValue::Damage,
+                    before,
+                    after: damage_roll.total,
+                })
+            }
+
+            _ => None,
+        }
+    }
+
+    /// How the modification would change the event, without changing it
+    pub fn preview(&self, event: &Event) -> Option<ModificationDelta> {
+        self.apply(&mut event.clone())
+    }
+}
+
+fn modify_roll(
+    result: &mut D20ResultKind,
+    modify: impl FnOnce(&mut D20ResultKind),
+) -> ModificationDelta {
+    let before = result.d20_result().total() as i32;
+    modify(result);
+    ModificationDelta {
+        value: roll_value(result),
+        before,
+        after: result.d20_result().total() as i32,
+    }
+}
+
+fn roll_value(result: &D20ResultKind) -> ModifiedValue {
+    match result {
+        D20ResultKind::SavingThrow { .. } => ModifiedValue::SavingThrow,
+        D20ResultKind::Skill { .. } => ModifiedValue::SkillCheck,
+        D20ResultKind::AttackRoll { .. } => ModifiedValue::AttackRoll,
+    }
+}
+
+fn dc_total(dc_kind: &D20CheckDCKind) -> (ModifiedValue, i32) {
+    match dc_kind {
+        D20CheckDCKind::SavingThrow(dc) => (ModifiedValue::DifficultyClass, dc.dc.total()),
+        D20CheckDCKind::Skill(dc) => (ModifiedValue::DifficultyClass, dc.dc.total()),
+        D20CheckDCKind::AttackRoll(_, armor_class) => {
+            (ModifiedValue::ArmorClass, armor_class.total())
+        }
+    }
+}
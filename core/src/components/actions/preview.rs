@@ -0,0 +1,65 @@
+use hecs::Entity;
+
+use crate::components::{
+    damage::AttackRoll,
+    items::equipment::armor::ArmorClass,
+    saving_throw::SavingThrowDC,
+    skill::{Skill, SkillCheckDC, SkillContest},
+};
+
+/// The d20 check that will be made when the action is performed, with all the
+/// modifiers from both the actor and the target already applied
+#[derive(Debug, Clone)]
+pub enum CheckPreview {
+    AttackRoll {
+        attack_roll: AttackRoll,
+        armor_class: ArmorClass,
+        hit_chance: f64,
+        crit_chance: f64,
+    },
+    SavingThrow {
+        saving_throw_dc: SavingThrowDC,
+        /// The chance that the target succeeds on its saving throw
+        save_chance: f64,
+    },
+    SkillCheck {
+        skill_check_dc: SkillCheckDC,
+        success_chance: f64,
+    },
+    Contest {
+        contest: SkillContest,
+        /// The skill the target resists with
+        defense: Skill,
+        /// The chance that the performer wins the contest
+        success_chance: f64,
+    },
+}
+
+/// The lowest and highest amount of damage the target can take, after its
+/// resistances have been applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamagePreview {
+    /// Damage when the action succeeds, i.e. the attack hits or the target
+    /// fails its saving throw
+    pub full: DamageRange,
+    /// Damage on a critical hit, if the action uses an attack roll
+    pub critical: Option<DamageRange>,
+    /// Damage on a miss or a successful saving throw, if any
+    pub reduced: Option<DamageRange>,
+}
+
+/// What's expected to happen to a target if the action is performed
+#[derive(Debug, Clone)]
+pub struct ActionPreview {
+    pub target: Entity,
+    pub check: Option<CheckPreview>,
+    /// The chance that the payload of the action applies in full
+    pub success_chance: f64,
+    pub damage: Option<DamagePreview>,
+}
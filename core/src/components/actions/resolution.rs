@@ -0,0 +1,92 @@
+use std::fmt;
+
+use hecs::Entity;
+use parry3d::na::Point3;
+
+use crate::components::{
+    ability::Ability, damage::DamageRollResult, modifier::ModifierSource,
+    saving_throw::SavingThrowKind,
+};
+
+/// How much of a target is hidden behind obstacles from where an action comes
+/// from. There's no total cover, since a target behind total cover can't be
+/// targeted in the first place.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cover {
+    #[default]
+    None,
+    Half,
+    ThreeQuarters,
+}
+
+impl Cover {
+    /// Bonus to AC and Dexterity saving throws
+    pub fn bonus(&self) -> i32 {
+        match self {
+            Cover::None => 0,
+            Cover::Half => 2,
+            Cover::ThreeQuarters => 5,
+        }
+    }
+
+    pub fn modifier_source(&self) -> ModifierSource {
+        ModifierSource::Custom(self.to_string())
+    }
+
+    /// The cover a modifier was given for, if any
+    pub fn from_modifier_source(source: &ModifierSource) -> Option<Self> {
+        [Cover::Half, Cover::ThreeQuarters]
+            .into_iter()
+            .find(|cover| cover.modifier_source() == *source)
+    }
+}
+
+impl fmt::Display for Cover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cover::None => write!(f, "No Cover"),
+            Cover::Half => write!(f, "Half Cover"),
+            Cover::ThreeQuarters => write!(f, "Three-Quarters Cover"),
+        }
+    }
+}
+
+/// The situation of a single target of an action, settled for all targets
+/// before the action is resolved against any of them. Every target gets its own
+/// context, so two targets of the same action can be in different situations,
+/// e.g. only one of them behind cover, and resolving the action against one
+/// target doesn't change how it's resolved against the next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetResolution {
+    pub target: Entity,
+    /// Where the action comes from as far as the target is concerned, e.g. the
+    /// center of a Fireball or the eyes of an archer
+    pub origin: Option<Point3<f32>>,
+    pub cover: Cover,
+    /// The damage rolled once for all targets, see `DamageRollPolicy::Once`
+    pub shared_damage: Option<DamageRollResult>,
+}
+
+impl TargetResolution {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            origin: None,
+            cover: Cover::None,
+            shared_damage: None,
+        }
+    }
+
+    /// Bonus to the AC of the target against an attack roll, if any
+    pub fn armor_class_bonus(&self) -> Option<(ModifierSource, i32)> {
+        (self.cover != Cover::None).then(|| (self.cover.modifier_source(), self.cover.bonus()))
+    }
+
+    /// Bonus to the saving throw the target makes against the action, if any
+    pub fn saving_throw_bonus(&self, kind: &SavingThrowKind) -> Option<(ModifierSource, i32)> {
+        if *kind != SavingThrowKind::Ability(Ability::Dexterity) {
+            return None;
+        }
+        self.armor_class_bonus()
+    }
+}
@@ -1,23 +1,31 @@
-use std::{collections::HashSet, fmt, str::FromStr};
+use std::{collections::HashSet, f32::consts::PI, fmt, str::FromStr};
 
 use hecs::{Entity, World};
 use parry3d::{
-    na::{Isometry3, Point3},
+    na::{Isometry3, Point3, Translation3, UnitQuaternion, Vector3},
     shape::Shape,
 };
 use serde::{Deserialize, Serialize};
 use uom::{
     Conversion,
     si::{
+        angle::radian,
         f32::{Angle, Length},
         length::{Unit, meter},
     },
 };
 
 use crate::{
-    components::health::life_state::LifeState,
+    components::{
+        health::life_state::LifeState,
+        items::{equipment::slots::EquipmentSlot, inventory::ItemContainer},
+        lever::Lever,
+        species::{CreatureSize, CreatureType},
+    },
     engine::geometry::WorldGeometry,
-    entities::{character::CharacterTag, monster::MonsterTag},
+    entities::{
+        character::CharacterTag, ground_item::GroundItemTag, monster::MonsterTag, object::ObjectTag,
+    },
     systems,
 };
 
@@ -61,22 +69,41 @@ impl AreaShape {
             target_point.coords
         };
 
+        // Direction from the actor towards the target point, used to orient
+        // shapes that originate at the actor
+        let direction = (target_point.coords - actor_position)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::x);
+
         match self {
-            AreaShape::Arc { .. } => {
-                todo!("Parry3D does not have a built-in arc shape");
+            AreaShape::Arc { angle, length } => {
+                // Parry3D cones point along the positive y-axis with the apex at
+                // half height, so the cone is flipped to put the apex at the actor
+                let half_height = length.get::<meter>() / 2.0;
+                let radius = length.get::<meter>() * (angle.get::<radian>() / 2.0).tan();
+                (
+                    Box::new(parry3d::shape::Cone::new(half_height, radius)),
+                    Isometry3::from_parts(
+                        Translation3::from(actor_position + direction * half_height),
+                        UnitQuaternion::rotation_between(&Vector3::y(), &-direction)
+                            .unwrap_or_else(|| {
+                                UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI)
+                            }),
+                    ),
+                )
             }
             AreaShape::Sphere { radius } => (
                 Box::new(parry3d::shape::Ball::new(radius.get::<meter>())),
-                Isometry3::new(translation, parry3d::na::Vector3::zeros()),
+                Isometry3::new(translation, Vector3::zeros()),
             ),
             AreaShape::Cube { side_length } => {
                 let half_size = side_length.get::<meter>() / 2.0;
                 (
-                    Box::new(parry3d::shape::Cuboid::new(parry3d::na::Vector3::new(
+                    Box::new(parry3d::shape::Cuboid::new(Vector3::new(
                         half_size, half_size, half_size,
                     ))),
                     // TODO: Cube rotation?
-                    Isometry3::new(translation, parry3d::na::Vector3::zeros()),
+                    Isometry3::new(translation, Vector3::zeros()),
                 )
             }
             AreaShape::Cylinder { radius, height } => (
@@ -84,27 +111,26 @@ impl AreaShape {
                     height.get::<meter>(),
                     radius.get::<meter>(),
                 )),
-                Isometry3::new(translation, parry3d::na::Vector3::zeros()),
+                Isometry3::new(translation, Vector3::zeros()),
             ),
             AreaShape::Line { length, width } => {
                 let half_length = length.get::<meter>() / 2.0;
                 let half_width = width.get::<meter>() / 2.0;
-                let mut rotation = parry3d::na::Vector3::zeros();
+                let mut rotation = UnitQuaternion::identity();
                 if fixed_on_actor {
-                    // Line starts at the actor's position
-                    translation.x += half_length;
-                    // Rotate around Y axis to point towards target point
-                    let direction = (target_point.coords - actor_position).normalize();
-                    let yaw = direction.y.atan2(direction.x);
-                    rotation = parry3d::na::Vector3::new(0.0, yaw, 0.0);
+                    // Line starts at the actor's position and points towards
+                    // the target point
+                    translation = actor_position + direction * half_length;
+                    rotation = UnitQuaternion::rotation_between(&Vector3::x(), &direction)
+                        .unwrap_or_else(|| UnitQuaternion::from_axis_angle(&Vector3::y_axis(), PI));
                 }
                 (
-                    Box::new(parry3d::shape::Cuboid::new(parry3d::na::Vector3::new(
+                    Box::new(parry3d::shape::Cuboid::new(Vector3::new(
                         half_length,
                         half_width,
                         half_width,
                     ))),
-                    Isometry3::new(translation, rotation),
+                    Isometry3::from_parts(Translation3::from(translation), rotation),
                 )
             }
         }
@@ -116,9 +142,21 @@ pub enum EntityFilter {
     All,
     Characters,
     Monsters,
+    Objects,
     Specific(HashSet<Entity>),
     LifeStates(HashSet<LifeState>),
     NotLifeStates(HashSet<LifeState>),
+    /// Creatures of the given size or smaller, e.g. for grappling
+    MaxSize(CreatureSize),
+    /// Living creatures of the given type, e.g. Humanoids for Charm Person
+    CreatureType(CreatureType),
+    /// Creatures at 0 hit points which are making death saving throws
+    Dying,
+    /// Items lying on the ground, e.g. for picking them up
+    GroundItems,
+    /// Doors and containers which are locked, e.g. for picking their locks
+    Locked,
+    Levers,
 }
 
 impl EntityFilter {
@@ -131,6 +169,7 @@ impl EntityFilter {
             EntityFilter::All => true,
             EntityFilter::Characters => world.get::<&CharacterTag>(*entity).is_ok(),
             EntityFilter::Monsters => world.get::<&MonsterTag>(*entity).is_ok(),
+            EntityFilter::Objects => world.get::<&ObjectTag>(*entity).is_ok(),
             EntityFilter::Specific(entities) => entities.contains(entity),
             EntityFilter::LifeStates(states) => {
                 if let Ok(life_state) = world.get::<&LifeState>(*entity) {
@@ -146,6 +185,63 @@ impl EntityFilter {
                     true
                 }
             }
+            EntityFilter::MaxSize(max_size) => world
+                .get::<&CreatureSize>(*entity)
+                .is_ok_and(|size| *size <= *max_size),
+            EntityFilter::CreatureType(creature_type) => {
+                EntityFilter::not_dead().matches(world, entity)
+                    && world
+                        .get::<&CreatureType>(*entity)
+                        .is_ok_and(|other| *other == *creature_type)
+            }
+            EntityFilter::Dying => world
+                .get::<&LifeState>(*entity)
+                .is_ok_and(|life_state| matches!(*life_state, LifeState::Unconscious(_))),
+            EntityFilter::GroundItems => world.get::<&GroundItemTag>(*entity).is_ok(),
+            EntityFilter::Locked => systems::locks::is_locked(world, *entity),
+            EntityFilter::Levers => world.get::<&Lever>(*entity).is_ok(),
+        }
+    }
+}
+
+/// An item carried by a creature, either equipped or in its inventory
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemTarget {
+    Equipped(EquipmentSlot),
+    /// Index of the item in the owner's inventory
+    Inventory(usize),
+}
+
+/// Which of a creature's items an action can target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemFilter {
+    /// Any item the creature carries, e.g. Mending
+    Any,
+    /// Items worn or held by the creature
+    Equipped,
+    /// Metal items worn or held by the creature, e.g. Heat Metal
+    Metal,
+    /// Weapons held by the creature, e.g. disarming it
+    Weapons,
+    /// The armor worn by the creature
+    Armor,
+}
+
+impl ItemFilter {
+    pub fn matches(&self, world: &World, owner: Entity, item: &ItemTarget) -> bool {
+        let Some(instance) = systems::inventory::target_item(world, owner, item) else {
+            return false;
+        };
+        match (self, item) {
+            (ItemFilter::Any, _) => true,
+            (ItemFilter::Equipped, ItemTarget::Equipped(_)) => true,
+            (ItemFilter::Metal, ItemTarget::Equipped(_)) => instance.item().material.is_metal(),
+            (ItemFilter::Weapons, ItemTarget::Equipped(slot)) => {
+                EquipmentSlot::weapon_slots().contains(slot)
+            }
+            (ItemFilter::Armor, ItemTarget::Equipped(slot)) => *slot == EquipmentSlot::Armor,
+            _ => false,
         }
     }
 }
@@ -153,7 +249,38 @@ impl EntityFilter {
 #[derive(Debug, Clone, PartialEq)]
 pub enum TargetInstance {
     Entity(Entity),
+    /// Destructible objects, e.g. doors and crates
+    Object(Entity),
     Point(Point3<f32>),
+    /// An item carried by a creature, e.g. the armor targeted by Heat Metal.
+    /// Range and line of sight are measured to the owner.
+    Item {
+        owner: Entity,
+        item: ItemTarget,
+    },
+}
+
+impl TargetInstance {
+    /// Target the given entity, using `TargetInstance::Object` if the entity is
+    /// an object and `TargetInstance::Entity` otherwise.
+    pub fn from_entity(world: &World, entity: Entity) -> Self {
+        if world.get::<&ObjectTag>(entity).is_ok() {
+            TargetInstance::Object(entity)
+        } else {
+            TargetInstance::Entity(entity)
+        }
+    }
+
+    /// The targeted entity, if the target is not a point. For items this is the
+    /// creature carrying the item.
+    pub fn entity(&self) -> Option<Entity> {
+        match self {
+            TargetInstance::Entity(entity)
+            | TargetInstance::Object(entity)
+            | TargetInstance::Item { owner: entity, .. } => Some(*entity),
+            TargetInstance::Point(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -167,6 +294,14 @@ pub enum TargetingError {
     NoLineOfSight {
         target: TargetInstance,
     },
+    NoLineOfEffect {
+        target: TargetInstance,
+    },
+    /// The target can't be seen, e.g. because it's invisible. The actor has to
+    /// guess which square it's in and target that instead.
+    NotVisible {
+        target: TargetInstance,
+    },
     InvalidTarget {
         target: TargetInstance,
     },
@@ -297,7 +432,14 @@ pub struct TargetingContext {
     pub kind: TargetingKind,
     pub range: TargetingRange,
     pub require_line_of_sight: bool,
+    /// Whether the path to the target has to be unobstructed. Seeing the target
+    /// through a window is enough for line of sight, but not for line of effect.
+    pub require_line_of_effect: bool,
     pub allowed_targets: EntityFilter,
+    /// Actions which target items can only target items matching the filter,
+    /// and the owners of the items have to match `allowed_targets`. Actions
+    /// without an item filter can't target items at all.
+    pub allowed_items: Option<ItemFilter>,
 }
 
 impl TargetingContext {
@@ -311,16 +453,25 @@ impl TargetingContext {
             kind,
             range,
             require_line_of_sight,
+            require_line_of_effect: false,
             allowed_targets,
+            allowed_items: None,
         }
     }
 
+    pub fn with_allowed_items(mut self, allowed_items: ItemFilter) -> Self {
+        self.allowed_items = Some(allowed_items);
+        self
+    }
+
     pub fn self_target() -> Self {
         TargetingContext {
             kind: TargetingKind::SelfTarget,
             range: TargetingRange::new::<meter>(0.0),
             require_line_of_sight: false,
+            require_line_of_effect: false,
             allowed_targets: EntityFilter::All,
+            allowed_items: None,
         }
     }
 
@@ -336,7 +487,9 @@ impl TargetingContext {
             let actor_position = systems::geometry::get_foot_position(world, actor).unwrap();
 
             let distance = match target {
-                TargetInstance::Entity(entity) => {
+                TargetInstance::Entity(entity)
+                | TargetInstance::Object(entity)
+                | TargetInstance::Item { owner: entity, .. } => {
                     systems::geometry::distance_between_entities(world, actor, *entity).unwrap()
                 }
 
@@ -353,27 +506,78 @@ impl TargetingContext {
                 });
             }
 
+            // Creatures which can't be seen can't be targeted directly
+            if let Some(entity) = target.entity()
+                && !systems::visibility::can_see(world, actor, entity)
+            {
+                return Err(TargetingError::NotVisible {
+                    target: target.clone(),
+                });
+            }
+
             // Check line of sight
             if self.require_line_of_sight {
-                let line_of_sight_result = match target {
-                    TargetInstance::Entity(entity) => {
-                        systems::geometry::line_of_sight_entity_entity(
+                let has_line_of_sight = |viewer: Entity| {
+                    match target {
+                        TargetInstance::Entity(entity)
+                        | TargetInstance::Object(entity)
+                        | TargetInstance::Item { owner: entity, .. } => {
+                            systems::geometry::line_of_sight_entity_entity(
+                                world,
+                                world_geometry,
+                                viewer,
+                                *entity,
+                            )
+                        }
+                        TargetInstance::Point(point) => {
+                            systems::geometry::line_of_sight_entity_point(
+                                world,
+                                world_geometry,
+                                viewer,
+                                *point,
+                            )
+                        }
+                    }
+                    .has_line_of_sight
+                };
+
+                // The actor can also see the target through the eyes of its
+                // familiar if they're sharing senses
+                if !has_line_of_sight(actor)
+                    && !systems::familiar::shared_senses(world, actor)
+                        .is_some_and(|familiar| has_line_of_sight(familiar))
+                {
+                    return Err(TargetingError::NoLineOfSight {
+                        target: target.clone(),
+                    });
+                }
+            }
+
+            // Check line of effect. Unlike sight, it can't be shared with a
+            // familiar, since the effect still originates from the actor
+            if self.require_line_of_effect {
+                let has_line_of_effect = match target {
+                    TargetInstance::Entity(entity)
+                    | TargetInstance::Object(entity)
+                    | TargetInstance::Item { owner: entity, .. } => {
+                        systems::geometry::line_of_effect_entity_entity(
                             world,
                             world_geometry,
                             actor,
                             *entity,
                         )
                     }
-                    TargetInstance::Point(point) => systems::geometry::line_of_sight_entity_point(
+                    TargetInstance::Point(point) => systems::geometry::line_of_effect_entity_point(
                         world,
                         world_geometry,
                         actor,
                         *point,
                     ),
-                };
+                }
+                .has_line_of_effect;
 
-                if !line_of_sight_result.has_line_of_sight {
-                    return Err(TargetingError::NoLineOfSight {
+                if !has_line_of_effect {
+                    return Err(TargetingError::NoLineOfEffect {
                         target: target.clone(),
                     });
                 }
@@ -381,8 +585,9 @@ impl TargetingContext {
 
             // Check allowed targets
             match target {
-                TargetInstance::Entity(entity) => {
-                    if !self.allowed_targets.matches(world, entity) {
+                TargetInstance::Entity(entity) | TargetInstance::Object(entity) => {
+                    if self.allowed_items.is_some() || !self.allowed_targets.matches(world, entity)
+                    {
                         return Err(TargetingError::InvalidTarget {
                             target: target.clone(),
                         });
@@ -391,6 +596,18 @@ impl TargetingContext {
                 TargetInstance::Point(_) => {
                     // Points are always allowed
                 }
+                TargetInstance::Item { owner, item } => {
+                    if !self
+                        .allowed_items
+                        .as_ref()
+                        .is_some_and(|filter| filter.matches(world, *owner, item))
+                        || !self.allowed_targets.matches(world, owner)
+                    {
+                        return Err(TargetingError::InvalidTarget {
+                            target: target.clone(),
+                        });
+                    }
+                }
             }
         }
 
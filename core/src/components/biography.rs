@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Freeform descriptions of a character written by its player, or of a
+/// creature written by the GM. They have no mechanical effect, but are saved
+/// along with the creatures of an encounter preset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Biography {
+    pub appearance: String,
+    pub backstory: String,
+    /// A small keepsake with no real value, e.g. a lucky coin
+    pub trinket: String,
+    pub notes: String,
+}
+
+impl Biography {
+    pub fn is_empty(&self) -> bool {
+        *self == Biography::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn biography_missing_fields_default() {
+        let biography: Biography =
+            serde_json::from_str(r#"{ "backstory": "Raised by wolves" }"#).unwrap();
+        assert_eq!(biography.backstory, "Raised by wolves");
+        assert!(biography.notes.is_empty());
+        assert!(!biography.is_empty());
+    }
+}
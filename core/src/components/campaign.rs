@@ -0,0 +1,159 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        id::{AdventureId, IdProvider},
+        items::loot::LootTable,
+        travel::WorldMap,
+    },
+    systems::{
+        mapgen::MapGenConfig,
+        preset::{EncounterPreset, PresetCreature},
+        scene::Scene,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdventureMap {
+    pub name: String,
+    pub config: MapGenConfig,
+}
+
+/// Something the party has to do for a quest. It's done once the flag has
+/// been set, e.g. by a scene or by the GM.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestObjective {
+    pub description: String,
+    pub flag: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quest {
+    /// Unique within the adventure
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Flags which have to be set before the quest shows up. Quests without
+    /// any requirements are available from the start.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    pub objectives: Vec<QuestObjective>,
+    /// Handed out to the party when the quest is completed
+    #[serde(default)]
+    pub rewards: LootTable,
+    /// Name of the adventure map where the quest takes place, which is loaded
+    /// when the quest starts
+    #[serde(default)]
+    pub map: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuestStatus {
+    Locked,
+    Active,
+    Completed,
+}
+
+/// Everything needed to play an adventure, bundled into a single registry
+/// entry: the maps and the world they're part of, the encounters on them, the
+/// NPCs to talk to, the scenes with their dialogue, the quests and the loot to
+/// hand out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Adventure {
+    pub id: AdventureId,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub maps: Vec<AdventureMap>,
+    /// The locations the party can travel between
+    #[serde(default)]
+    pub world_map: WorldMap,
+    #[serde(default)]
+    pub encounters: Vec<EncounterPreset>,
+    /// Spawned when the adventure starts
+    #[serde(default)]
+    pub npcs: Vec<PresetCreature>,
+    #[serde(default)]
+    pub scenes: Vec<Scene>,
+    #[serde(default)]
+    pub quests: Vec<Quest>,
+    /// Named loot tables, e.g. for the chests in a dungeon
+    #[serde(default)]
+    pub loot: HashMap<String, LootTable>,
+}
+
+impl Adventure {
+    pub fn map(&self, name: &str) -> Option<&AdventureMap> {
+        self.maps.iter().find(|map| map.name == name)
+    }
+
+    pub fn encounter(&self, name: &str) -> Option<&EncounterPreset> {
+        self.encounters
+            .iter()
+            .find(|encounter| encounter.name == name)
+    }
+
+    pub fn scene(&self, name: &str) -> Option<&Scene> {
+        self.scenes.iter().find(|scene| scene.name == name)
+    }
+
+    pub fn quest(&self, id: &str) -> Option<&Quest> {
+        self.quests.iter().find(|quest| quest.id == id)
+    }
+}
+
+impl IdProvider for Adventure {
+    type Id = AdventureId;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+}
+
+/// How far the party has come in an adventure. Progress is tracked as a set of
+/// flags, which the quests and content of the adventure are keyed on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CampaignState {
+    pub adventure: AdventureId,
+    #[serde(default)]
+    pub flags: BTreeSet<String>,
+    /// Where the party is on the world map
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Map of a quest which has just started, waiting to be loaded
+    #[serde(default)]
+    pub pending_map: Option<String>,
+}
+
+impl CampaignState {
+    pub fn new(adventure: AdventureId) -> Self {
+        Self {
+            adventure,
+            flags: BTreeSet::new(),
+            location: None,
+            pending_map: None,
+        }
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+
+    pub fn quest_status(&self, quest: &Quest) -> QuestStatus {
+        if quest
+            .objectives
+            .iter()
+            .all(|objective| self.has_flag(&objective.flag))
+        {
+            QuestStatus::Completed
+        } else if quest.requires.iter().all(|flag| self.has_flag(flag)) {
+            QuestStatus::Active
+        } else {
+            QuestStatus::Locked
+        }
+    }
+}
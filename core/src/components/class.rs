@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    fmt::Display,
     hash::Hash,
 };
 
@@ -7,16 +8,21 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
-        ability::{Ability, AbilityScoreDistribution},
+        ability::{Ability, AbilityScoreDistribution, AbilityScoreMap},
         dice::DieSize,
-        id::{ActionId, ClassId, EffectId, IdProvider, ResourceId, SpellId, SubclassId},
+        id::{
+            ActionId, ClassId, EffectId, IdProvider, ResourceId, SpellId, SpellListId, SubclassId,
+        },
         items::equipment::{armor::ArmorType, weapon::WeaponCategory},
         level_up::{ChoiceItem, ChoiceSpec, LevelUpPrompt},
         modifier::ModifierSource,
         resource::ResourceBudgetKind,
         skill::Skill,
     },
-    registry::{registry::SubclassesRegistry, serialize::class::ClassDefinition},
+    registry::{
+        registry::{SpellListsRegistry, SubclassesRegistry},
+        serialize::class::ClassDefinition,
+    },
 };
 
 /// Classes and subclasses share a lot of common properties, so we define a base struct
@@ -52,6 +58,15 @@ pub struct ClassBase {
     /// Actions that are available at each level.
     #[serde(default)]
     pub actions_by_level: HashMap<u8, Vec<ActionId>>,
+    /// Spells which are always prepared from each level, e.g. Circle spells
+    /// for a Druid subclass. Requires the class to be a spellcaster.
+    #[serde(default)]
+    pub spells_by_level: HashMap<u8, Vec<SpellId>>,
+    /// Spells which are added to the class' spell list, e.g. the expanded spell
+    /// list of a Warlock patron. Unlike `spells_by_level` they still have to be
+    /// learned or prepared like any other spell on the list.
+    #[serde(default)]
+    pub expanded_spell_list: HashSet<SpellId>,
 }
 
 /// How a class gets access to spells (i.e., what the "known pool" means).
@@ -106,8 +121,38 @@ pub struct SpellcastingRules {
     pub cantrips_per_level: HashMap<u8, usize>,
     pub prepared_spells_per_level: HashMap<u8, usize>,
     pub spell_replacement_model: SpellReplacementModel,
-    /// The universe of spells this class can ever touch.
-    pub spell_list: HashSet<SpellId>,
+    /// The universe of spells this class can ever touch. Spell lists can be
+    /// shared between classes, e.g. Wizards and Sorcerers share most spells.
+    pub spell_list: SpellListId,
+}
+
+/// Minimum ability score needed to multiclass into or out of a class. The
+/// prerequisite is met if any of the abilities reaches the minimum, e.g. a
+/// Fighter needs either Strength or Dexterity 13.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MulticlassPrerequisite {
+    pub any_of: Vec<Ability>,
+    pub minimum: u8,
+}
+
+impl MulticlassPrerequisite {
+    pub fn is_met(&self, ability_scores: &AbilityScoreMap) -> bool {
+        self.any_of
+            .iter()
+            .any(|ability| ability_scores.get(ability).total() >= self.minimum as i32)
+    }
+}
+
+impl Display for MulticlassPrerequisite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let abilities = self
+            .any_of
+            .iter()
+            .map(|ability| ability.to_string())
+            .collect::<Vec<_>>()
+            .join(" or ");
+        write!(f, "{} {}", abilities, self.minimum)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -122,6 +167,10 @@ pub struct Class {
     /// Saving throw proficiencies granted at level 1 (e.g. STR + CON for Fighter)
     pub saving_throw_proficiencies: [Ability; 2],
 
+    /// Ability scores needed to multiclass into this class, and to multiclass
+    /// out of it into another class. All of the prerequisites must be met.
+    pub multiclass_prerequisites: Vec<MulticlassPrerequisite>,
+
     pub subclasses: HashSet<SubclassId>,
 
     /// The levels at which the class can pick a new feat.
@@ -137,6 +186,7 @@ impl Class {
         hp_per_level: u8,
         default_abilities: AbilityScoreDistribution,
         saving_throw_proficiencies: [Ability; 2],
+        multiclass_prerequisites: Vec<MulticlassPrerequisite>,
         subclass_level: u8,
         subclasses: HashSet<SubclassId>,
         feat_levels: HashSet<u8>,
@@ -149,6 +199,8 @@ impl Class {
         resources_by_level: HashMap<u8, Vec<(ResourceId, ResourceBudgetKind, bool)>>,
         mut prompts_by_level: HashMap<u8, Vec<LevelUpPrompt>>,
         actions_by_level: HashMap<u8, Vec<ActionId>>,
+        spells_by_level: HashMap<u8, Vec<SpellId>>,
+        expanded_spell_list: HashSet<SpellId>,
     ) -> Self {
         // Add skill proficiencies
         prompts_by_level
@@ -184,6 +236,7 @@ impl Class {
             hp_per_level,
             default_abilities,
             saving_throw_proficiencies,
+            multiclass_prerequisites,
             subclasses,
             feat_levels,
             base: ClassBase {
@@ -196,6 +249,8 @@ impl Class {
                 resources_by_level,
                 prompts_by_level,
                 actions_by_level,
+                spells_by_level,
+                expanded_spell_list,
             },
         }
     }
@@ -222,6 +277,26 @@ impl Class {
         self.base.spellcasting.as_ref()
     }
 
+    /// All the spells on the spell list of the class, including the ones added
+    /// by the class and subclass' expanded spell lists. Empty if the class
+    /// isn't a spellcaster.
+    pub fn spell_list(&self, subclass_id: &Option<SubclassId>) -> HashSet<SpellId> {
+        let Some(rules) = self.spellcasting_rules(subclass_id) else {
+            return HashSet::new();
+        };
+
+        let mut spells = SpellListsRegistry::get(&rules.spell_list)
+            .map(|spell_list| spell_list.spells.clone())
+            .unwrap_or_default();
+        spells.extend(self.base.expanded_spell_list.iter().cloned());
+        if let Some(subclass_id) = subclass_id
+            && let Some(subclass) = self.subclass(subclass_id)
+        {
+            spells.extend(subclass.base.expanded_spell_list.iter().cloned());
+        }
+        spells
+    }
+
     pub fn base(&self) -> &ClassBase {
         &self.base
     }
@@ -0,0 +1,53 @@
+use std::collections::HashSet;
+
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    ability::Ability,
+    id::{ActionId, EffectId},
+    saving_throw::SavingThrowDC,
+};
+
+/// Control over the turns of a creature granted by an effect, e.g. Dominate
+/// Person or Command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlTemplate {
+    /// The only actions the controlled creature can take. If not specified it
+    /// can take any of its actions.
+    #[serde(default)]
+    pub actions: Option<HashSet<ActionId>>,
+    /// The controlled creature can't move, e.g. Command's "Halt"
+    #[serde(default)]
+    pub immobile: bool,
+    /// The controlled creature spends its turns moving away from the
+    /// controller, e.g. Command's "Flee"
+    #[serde(default)]
+    pub flee: bool,
+    /// Saving throw the controlled creature makes to break free each time it
+    /// takes damage, against the spell save DC of the controller, or a
+    /// Charisma based DC if the control doesn't come from a spell
+    #[serde(default)]
+    pub save_on_damage: Option<Ability>,
+}
+
+/// A creature whose turns are decided by someone else. The control lasts as
+/// long as the effect granting it, see [`ControlTemplate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Controlled {
+    pub controller: Entity,
+    pub effect: EffectId,
+    pub actions: Option<HashSet<ActionId>>,
+    pub immobile: bool,
+    pub flee: bool,
+    /// The saving throw made to break free when taking damage, if any
+    pub save_on_damage: Option<SavingThrowDC>,
+}
+
+impl Controlled {
+    pub fn allows(&self, action_id: &ActionId) -> bool {
+        self.actions
+            .as_ref()
+            .is_none_or(|actions| actions.contains(action_id))
+    }
+}
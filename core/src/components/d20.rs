@@ -1,4 +1,9 @@
-use std::{cmp::max, collections::HashMap, fmt, hash::Hash};
+use std::{
+    cmp::max,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::Hash,
+};
 
 use hecs::{Entity, World};
 use rand::Rng;
@@ -8,8 +13,12 @@ use strum::IntoEnumIterator;
 use crate::{
     components::{
         ability::{Ability, AbilityScoreMap},
+        dice::RollPolicy,
         effects::hooks::D20CheckHooks,
-        modifier::{KeyedModifiable, Modifiable, ModifierSet, ModifierSource},
+        modifier::{
+            DiceModifier, DiceModifierRoll, KeyedModifiable, Modifiable, ModifierSet,
+            ModifierSource,
+        },
         proficiency::{Proficiency, ProficiencyLevel},
     },
     systems,
@@ -73,6 +82,36 @@ impl AdvantageTracker {
     }
 }
 
+/// Some states force the outcome of a check regardless of the roll, e.g. an
+/// unconscious creature automatically fails Strength and Dexterity saving throws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum D20CheckOverride {
+    AutoSuccess,
+    AutoFailure,
+}
+
+impl fmt::Display for D20CheckOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            D20CheckOverride::AutoSuccess => write!(f, "Automatic Success"),
+            D20CheckOverride::AutoFailure => write!(f, "Automatic Failure"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct D20CheckOverrideSource {
+    pub kind: D20CheckOverride,
+    pub source: ModifierSource,
+}
+
+impl fmt::Display for D20CheckOverrideSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.kind, self.source)
+    }
+}
+
 pub static D20_CRITICAL_SUCCESS: u8 = 20;
 pub static D20_CRITICAL_FAILURE: u8 = 1;
 
@@ -81,6 +120,7 @@ pub struct D20Check {
     modifiers: ModifierSet,
     proficiency: Proficiency,
     advantage_tracker: AdvantageTracker,
+    overrides: Vec<D20CheckOverrideSource>,
 }
 
 impl D20Check {
@@ -89,6 +129,7 @@ impl D20Check {
             modifiers: ModifierSet::new(),
             proficiency,
             advantage_tracker: AdvantageTracker::new(),
+            overrides: Vec::new(),
         }
     }
 
@@ -116,17 +157,87 @@ impl D20Check {
         self.proficiency = proficiency;
     }
 
+    /// Grant proficiency from another source without touching the proficiency
+    /// granted by the existing ones, e.g. a feat on top of a class
+    pub fn add_proficiency(&mut self, level: ProficiencyLevel, source: ModifierSource) {
+        self.proficiency.add_source(level, source);
+    }
+
+    pub fn remove_proficiency(&mut self, source: &ModifierSource) {
+        self.proficiency.remove_source(source);
+    }
+
+    pub fn add_override(&mut self, kind: D20CheckOverride, source: ModifierSource) {
+        self.overrides.push(D20CheckOverrideSource { kind, source });
+    }
+
+    /// Add a modifier which is rolled along with the d20, e.g. +1d4 from Bless
+    pub fn add_dice_modifier(&mut self, source: ModifierSource, modifier: DiceModifier) {
+        self.modifiers.add_dice_modifier(source, modifier);
+    }
+
+    pub fn remove_override(&mut self, source: &ModifierSource) {
+        self.overrides.retain(|o| &o.source != source);
+    }
+
+    /// The override that decides the outcome of the check, if any. If there are
+    /// conflicting overrides, automatic failure takes precedence.
+    pub fn active_override(&self) -> Option<&D20CheckOverrideSource> {
+        self.overrides
+            .iter()
+            .find(|o| o.kind == D20CheckOverride::AutoFailure)
+            .or_else(|| self.overrides.first())
+    }
+
     pub fn roll(&self, proficiency_bonus: u8) -> D20CheckResult {
+        let mut rng = rand::rng();
+        // Technically inefficient to always roll two dice, but it's probably not a big deal
+        let roll1 = rng.random_range(1..=20) as u8;
+        let roll2 = rng.random_range(1..=20) as u8;
+        self.resolve(proficiency_bonus, roll1, roll2, &RollPolicy::RANDOM)
+    }
+
+    /// Roll the check according to the roll policy of the creature making it,
+    /// e.g. NPCs taking 10 instead of rolling. Advantage and disadvantage don't
+    /// matter when both dice come up the same.
+    pub fn roll_with_policy(&self, proficiency_bonus: u8, policy: &RollPolicy) -> D20CheckResult {
+        match policy.fixed_d20 {
+            Some(value) => {
+                let value = value.clamp(1, 20);
+                self.resolve(proficiency_bonus, value, value, policy)
+            }
+            None => self.roll(proficiency_bonus),
+        }
+    }
+
+    /// Everything that goes into the check, without rolling it
+    pub fn explain(&self, proficiency_bonus: u8) -> D20CheckExplanation {
+        D20CheckExplanation {
+            modifiers: self.modifiers_with_proficiency(proficiency_bonus),
+            proficiency: self.proficiency.clone(),
+            advantage_tracker: self.advantage_tracker.clone(),
+            forced_outcome: self.active_override().cloned(),
+        }
+    }
+
+    fn modifiers_with_proficiency(&self, proficiency_bonus: u8) -> ModifierSet {
         let mut modifiers = self.modifiers.clone();
         modifiers.add_modifier(
             ModifierSource::Proficiency(self.proficiency.level().clone()),
             self.proficiency.bonus(proficiency_bonus) as i32,
         );
+        modifiers
+    }
 
-        let mut rng = rand::rng();
-        // Technically inefficient to always roll two dice, but it's probably not a big deal
-        let roll1 = rng.random_range(1..=20) as u8;
-        let roll2 = rng.random_range(1..=20) as u8;
+    fn resolve(
+        &self,
+        proficiency_bonus: u8,
+        roll1: u8,
+        roll2: u8,
+        policy: &RollPolicy,
+    ) -> D20CheckResult {
+        let mut modifiers = self.modifiers_with_proficiency(proficiency_bonus);
+        let dice_rolls = modifiers.roll_dice_with_policy(policy);
 
         let roll_mode = self.advantage_tracker.roll_mode();
         let rolls = match roll_mode {
@@ -144,16 +255,24 @@ impl D20Check {
 
         let is_crit = selected_roll == D20_CRITICAL_SUCCESS;
 
-        D20CheckResult {
+        let mut result = D20CheckResult {
             advantage_tracker: self.advantage_tracker.clone(),
             rolls,
             selected_roll,
             modifier_breakdown: modifiers.clone(),
+            dice_rolls,
             is_crit,
             is_crit_fail: selected_roll == D20_CRITICAL_FAILURE,
             // We can already now say the check is a success if it's a crit
             success: is_crit,
+            forced_outcome: None,
+        };
+
+        if let Some(active_override) = self.active_override() {
+            result.force_outcome(active_override.kind, active_override.source.clone());
         }
+
+        result
     }
 
     pub fn roll_hooks(
@@ -167,10 +286,12 @@ impl D20Check {
             (hook.check_hook)(world, entity, &mut check);
         }
 
+        // Entities without a level (e.g. objects) are never proficient
         let proficiency_bonus = systems::helpers::level(world, entity)
-            .unwrap()
-            .proficiency_bonus();
-        let mut result = check.roll(proficiency_bonus);
+            .map(|level| level.proficiency_bonus())
+            .unwrap_or(0);
+        let policy = systems::helpers::roll_policy(world, entity);
+        let mut result = check.roll_with_policy(proficiency_bonus, &policy);
 
         for hook in hooks {
             (hook.result_hook)(world, entity, &mut result);
@@ -180,21 +301,37 @@ impl D20Check {
     }
 
     pub fn success_probability(&self, target_dc: u32, proficiency_bonus: u8) -> f64 {
+        if let Some(active_override) = self.active_override() {
+            return match active_override.kind {
+                D20CheckOverride::AutoSuccess => 1.0,
+                D20CheckOverride::AutoFailure => 0.0,
+            };
+        }
+
         let mut total_modifier = self.modifiers.total();
         total_modifier += self.proficiency.bonus(proficiency_bonus) as i32;
 
         let roll_mode = self.advantage_tracker.roll_mode();
 
-        // Needed raw roll
-        let needed_roll = (target_dc as i32 - total_modifier).clamp(2, 20);
-
-        let single_roll_p = (21 - needed_roll) as f64 / 20.0;
-
-        match roll_mode {
-            RollMode::Normal => single_roll_p,
-            RollMode::Advantage => 1.0 - (1.0 - single_roll_p).powi(2),
-            RollMode::Disadvantage => single_roll_p.powi(2),
-        }
+        // Dice modifiers, e.g. Bless, are rolled along with the d20, so every
+        // value they can come up with is weighed by its chance
+        self.modifiers
+            .dice_distribution()
+            .into_iter()
+            .map(|(dice_value, chance)| {
+                // Needed raw roll
+                let needed_roll = (target_dc as i32 - total_modifier - dice_value).clamp(2, 20);
+
+                let single_roll_p = (21 - needed_roll) as f64 / 20.0;
+
+                let p = match roll_mode {
+                    RollMode::Normal => single_roll_p,
+                    RollMode::Advantage => 1.0 - (1.0 - single_roll_p).powi(2),
+                    RollMode::Disadvantage => single_roll_p.powi(2),
+                };
+                chance * p
+            })
+            .sum()
     }
 }
 
@@ -229,20 +366,52 @@ impl fmt::Display for D20Check {
     }
 }
 
+/// The would-be breakdown of a check, e.g. to show the bonus of a skill
+/// without rolling it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct D20CheckExplanation {
+    /// Every modifier to the roll, including the ability modifier and the
+    /// proficiency bonus
+    pub modifiers: ModifierSet,
+    pub proficiency: Proficiency,
+    pub advantage_tracker: AdvantageTracker,
+    /// If set, the outcome of the check is decided regardless of the roll
+    pub forced_outcome: Option<D20CheckOverrideSource>,
+}
+
+impl D20CheckExplanation {
+    pub fn total_modifier(&self) -> i32 {
+        self.modifiers.total()
+    }
+
+    pub fn roll_mode(&self) -> RollMode {
+        self.advantage_tracker.roll_mode()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct D20CheckResult {
     pub advantage_tracker: AdvantageTracker,
     pub rolls: Vec<u8>,
     pub selected_roll: u8,
     pub modifier_breakdown: ModifierSet,
+    /// Modifiers which were rolled along with the d20, e.g. +1d4 from Bless
+    pub dice_rolls: BTreeMap<ModifierSource, DiceModifierRoll>,
     pub is_crit: bool,
     pub is_crit_fail: bool,
     pub success: bool,
+    /// If set, the outcome of the check was decided regardless of the roll
+    pub forced_outcome: Option<D20CheckOverrideSource>,
 }
 
 impl D20CheckResult {
     pub fn total_modifier(&self) -> i32 {
         self.modifier_breakdown.total()
+            + self
+                .dice_rolls
+                .values()
+                .map(|roll| roll.value())
+                .sum::<i32>()
     }
 
     pub fn total(&self) -> u32 {
@@ -253,9 +422,17 @@ impl D20CheckResult {
     where
         T: IntoEnumIterator + Copy + Eq + Hash,
     {
+        if let Some(forced_outcome) = &self.forced_outcome {
+            return forced_outcome.kind == D20CheckOverride::AutoSuccess;
+        }
         self.is_crit || (!self.is_crit_fail && self.total() >= dc.dc.total() as u32)
     }
 
+    pub fn force_outcome(&mut self, kind: D20CheckOverride, source: ModifierSource) {
+        self.success = kind == D20CheckOverride::AutoSuccess;
+        self.forced_outcome = Some(D20CheckOverrideSource { kind, source });
+    }
+
     pub fn add_bonus(&mut self, source: ModifierSource, value: i32) {
         self.modifier_breakdown.add_modifier(source, value);
     }
@@ -282,7 +459,13 @@ impl fmt::Display for D20CheckResult {
         if !self.modifier_breakdown.is_empty() {
             write!(f, " {}", self.modifier_breakdown)?;
         }
+        for (source, roll) in &self.dice_rolls {
+            write!(f, " {} ({})", roll, source)?;
+        }
         write!(f, " = {}", self.total())?;
+        if let Some(forced_outcome) = &self.forced_outcome {
+            write!(f, " ({})", forced_outcome)?;
+        }
         Ok(())
     }
 }
@@ -339,6 +522,14 @@ where
         self.get_mut(key).set_proficiency(proficiency);
     }
 
+    pub fn add_proficiency(&mut self, key: &K, level: ProficiencyLevel, source: ModifierSource) {
+        self.get_mut(key).add_proficiency(level, source);
+    }
+
+    pub fn remove_proficiency(&mut self, key: &K, source: &ModifierSource) {
+        self.get_mut(key).remove_proficiency(source);
+    }
+
     pub fn add_advantage(&mut self, key: &K, kind: AdvantageType, source: ModifierSource) {
         self.get_mut(key).advantage_tracker_mut().add(kind, source);
     }
@@ -347,7 +538,21 @@ where
         self.get_mut(key).advantage_tracker_mut().remove(source);
     }
 
-    pub fn check(&self, key: &K, world: &World, entity: Entity) -> D20CheckResult {
+    pub fn add_override(&mut self, key: &K, kind: D20CheckOverride, source: ModifierSource) {
+        self.get_mut(key).add_override(kind, source);
+    }
+
+    pub fn add_dice_modifier(&mut self, key: &K, source: ModifierSource, modifier: DiceModifier) {
+        self.get_mut(key).add_dice_modifier(source, modifier);
+    }
+
+    pub fn remove_override(&mut self, key: &K, source: &ModifierSource) {
+        self.get_mut(key).remove_override(source);
+    }
+
+    /// The check for the given key, including the modifier of the ability it's
+    /// based on
+    fn with_ability_modifier(&self, key: &K, world: &World, entity: Entity) -> D20Check {
         let mut d20 = self.get(key).clone();
         if let Some(ability) = (self.ability_mapper)(key) {
             let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
@@ -356,17 +561,69 @@ where
                 ability_scores.ability_modifier(&ability).total(),
             );
         }
+        d20
+    }
 
-        d20.roll_hooks(world, entity, &(self.get_hooks)(key, world, entity))
+    pub fn check(&self, key: &K, world: &World, entity: Entity) -> D20CheckResult {
+        self.with_ability_modifier(key, world, entity).roll_hooks(
+            world,
+            entity,
+            &(self.get_hooks)(key, world, entity),
+        )
     }
 
     pub fn check_dc(&self, dc: &D20CheckDC<K>, world: &World, entity: Entity) -> D20CheckResult {
         let mut result = self.check(&dc.key, world, entity);
+        if result.forced_outcome.is_some() {
+            return result;
+        }
         result.success |= result.total() >= dc.dc.total() as u32;
         result.success &= !result.is_crit_fail; // Critical failure cannot be a success
 
         result
     }
+
+    /// The check as it would be rolled, with the ability modifier and the hooks
+    /// applied, along with the proficiency bonus of the entity
+    fn prepare(&self, key: &K, world: &World, entity: Entity) -> (D20Check, u8) {
+        let mut d20 = self.with_ability_modifier(key, world, entity);
+        for hook in (self.get_hooks)(key, world, entity) {
+            (hook.check_hook)(world, entity, &mut d20);
+        }
+
+        let proficiency_bonus = systems::helpers::level(world, entity)
+            .map(|level| level.proficiency_bonus())
+            .unwrap_or(0);
+        (d20, proficiency_bonus)
+    }
+
+    /// The chance that the entity succeeds on the check against the DC, without
+    /// actually rolling it
+    pub fn success_probability(&self, dc: &D20CheckDC<K>, world: &World, entity: Entity) -> f64 {
+        let (d20, proficiency_bonus) = self.prepare(&dc.key, world, entity);
+        d20.success_probability(dc.dc.total() as u32, proficiency_bonus)
+    }
+
+    /// Everything that goes into the check for the entity, without rolling it
+    pub fn explain(&self, key: &K, world: &World, entity: Entity) -> D20CheckExplanation {
+        let (d20, proficiency_bonus) = self.prepare(key, world, entity);
+        d20.explain(proficiency_bonus)
+    }
+
+    /// The passive score for the check, e.g. passive Perception, which is what
+    /// the entity would get on an average roll: 10 plus all its modifiers, with
+    /// +5 for advantage and -5 for disadvantage
+    pub fn passive_score(&self, key: &K, world: &World, entity: Entity) -> i32 {
+        let (d20, proficiency_bonus) = self.prepare(key, world, entity);
+        let roll_mode_bonus = match d20.advantage_tracker().roll_mode() {
+            RollMode::Normal => 0,
+            RollMode::Advantage => 5,
+            RollMode::Disadvantage => -5,
+        };
+        10 + d20.modifiers().total()
+            + d20.proficiency().bonus(proficiency_bonus) as i32
+            + roll_mode_bonus
+    }
 }
 
 impl<K> KeyedModifiable<K> for D20CheckSet<K>
@@ -515,6 +772,80 @@ mod tests {
         println!("Result: {}", result);
     }
 
+    #[test]
+    fn d20_check_explain() {
+        let mut check = D20Check::new(Proficiency::new(
+            ProficiencyLevel::Expertise,
+            ModifierSource::Custom("Training".to_string()),
+        ));
+        check.add_modifier(ModifierSource::Ability(Ability::Dexterity), 3);
+        check.advantage_tracker.add(
+            AdvantageType::Advantage,
+            ModifierSource::Item(ItemId::new("nat20_core", "item.lucky_charm")),
+        );
+        check.add_override(
+            D20CheckOverride::AutoFailure,
+            ModifierSource::Custom("Paralyzed".to_string()),
+        );
+
+        let explanation = check.explain(2);
+        // +3 from Dexterity and +4 from expertise
+        assert_eq!(explanation.total_modifier(), 7);
+        assert_eq!(
+            explanation
+                .modifiers
+                .get(&ModifierSource::Proficiency(ProficiencyLevel::Expertise)),
+            Some(4)
+        );
+        assert_eq!(explanation.roll_mode(), RollMode::Advantage);
+        assert_eq!(
+            explanation.forced_outcome.map(|outcome| outcome.kind),
+            Some(D20CheckOverride::AutoFailure)
+        );
+    }
+
+    #[test]
+    fn d20_check_override() {
+        let mut check = D20Check::new(Proficiency::new(
+            ProficiencyLevel::None,
+            ModifierSource::None,
+        ));
+        check.add_modifier(ModifierSource::Custom("Huge Bonus".to_string()), 100);
+        check.add_override(
+            D20CheckOverride::AutoFailure,
+            ModifierSource::Custom("Unconscious".to_string()),
+        );
+        check.add_override(
+            D20CheckOverride::AutoSuccess,
+            ModifierSource::Custom("Blessing".to_string()),
+        );
+
+        let dc = D20CheckDC {
+            key: Ability::Strength,
+            dc: ModifierSet::from(ModifierSource::Base, 10),
+        };
+
+        // Automatic failure takes precedence, regardless of the roll
+        let result = check.roll(0);
+        assert!(!result.success);
+        assert!(!result.is_success(&dc));
+        assert_eq!(
+            result.forced_outcome,
+            Some(D20CheckOverrideSource {
+                kind: D20CheckOverride::AutoFailure,
+                source: ModifierSource::Custom("Unconscious".to_string()),
+            })
+        );
+
+        check.remove_override(&ModifierSource::Custom("Unconscious".to_string()));
+        let result = check.roll(0);
+        assert!(result.is_success(&dc));
+        assert_eq!(
+            result.forced_outcome.unwrap().kind,
+            D20CheckOverride::AutoSuccess
+        );
+    }
+
     #[test]
     fn d20_check_critical_success() {
         let mut check = D20Check::new(Proficiency::new(
@@ -535,4 +866,24 @@ mod tests {
         assert!(result.is_crit);
         println!("Result: {}", result);
     }
+
+    #[test]
+    fn d20_check_with_dice_modifier() {
+        let mut check = D20Check::new(Proficiency::new(
+            ProficiencyLevel::None,
+            ModifierSource::None,
+        ));
+        check.add_dice_modifier(
+            ModifierSource::Custom("Bless".to_string()),
+            DiceModifier::bonus("1d4".parse().unwrap()),
+        );
+
+        // Needing a 14, 13, 12 or 11 on the d20, depending on the d4
+        let probability = check.success_probability(15, 0);
+        assert!((probability - (7.0 + 8.0 + 9.0 + 10.0) / 80.0).abs() < 1e-9);
+
+        // With a fixed d20 the d4 comes up with its average, rounded down
+        let result = check.roll_with_policy(0, &RollPolicy::STATIC);
+        assert_eq!(result.total(), 12);
+    }
 }
@@ -1,29 +1,32 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fmt::{self, Display},
     str::FromStr,
 };
 
 use hecs::{Entity, World};
 use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
 
 use crate::{
     components::{
         actions::action::ActionContext,
-        d20::{D20Check, D20CheckResult},
-        dice::{DiceSet, DiceSetRoll, DiceSetRollResult},
+        d20::{D20Check, D20CheckOverride, D20CheckResult, RollMode},
+        dice::{DiceSet, DiceSetRoll, DiceSetRollResult, RollPolicy},
         id::{ActionId, SpellId},
         items::equipment::{
+            armor::ArmorClass,
             slots::EquipmentSlot,
             weapon::{Weapon, WeaponKind},
         },
         modifier::{ModifierSet, ModifierSource},
+        proficiency::{Proficiency, ProficiencyLevel},
         spells::spell,
     },
     systems::{self},
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter)]
 #[serde(rename_all = "snake_case")]
 pub enum DamageType {
     Acid,
@@ -47,12 +50,40 @@ impl fmt::Display for DamageType {
     }
 }
 
+impl FromStr for DamageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DamageType::iter()
+            .find(|damage_type| damage_type.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("Unknown damage type: {}", s))
+    }
+}
+
+/// Provenance of a damage component. Some resistances only apply to damage that
+/// lacks a certain tag, e.g. "resistant to nonmagical bludgeoning damage".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageTag {
+    Magical,
+    Silvered,
+    Adamantine,
+}
+
+impl fmt::Display for DamageTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 /// --- DAMAGE APPLICATION ---
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DamageComponent {
     pub dice_roll: DiceSetRoll,
     pub damage_type: DamageType,
+    #[serde(default)]
+    pub tags: BTreeSet<DamageTag>,
 }
 
 impl DamageComponent {
@@ -60,6 +91,7 @@ impl DamageComponent {
         Self {
             dice_roll: DiceSetRoll::new(dice, ModifierSet::new()),
             damage_type,
+            tags: BTreeSet::new(),
         }
     }
 }
@@ -74,6 +106,7 @@ impl fmt::Display for DamageComponent {
 pub struct DamageComponentResult {
     pub result: DiceSetRollResult,
     pub damage_type: DamageType,
+    pub tags: BTreeSet<DamageTag>,
 }
 
 impl fmt::Display for DamageComponentResult {
@@ -98,6 +131,7 @@ impl Default for DamageComponentResult {
         Self {
             result: DiceSetRollResult::default(),
             damage_type: DamageType::Slashing,
+            tags: BTreeSet::new(),
         }
     }
 }
@@ -109,6 +143,8 @@ impl Default for DamageComponentResult {
 pub enum DamageSource {
     Weapon(WeaponKind),
     Spell(SpellId),
+    /// Hazards in the world, e.g. a creature standing in a fire
+    Environment,
 }
 
 impl From<&Weapon> for DamageSource {
@@ -119,7 +155,7 @@ impl From<&Weapon> for DamageSource {
 
 impl From<&ActionContext> for DamageSource {
     fn from(action_context: &ActionContext) -> Self {
-        match action_context {
+        match action_context.base() {
             ActionContext::Spell { id, .. } => DamageSource::Spell(id.clone()),
             ActionContext::Weapon { slot } => match slot {
                 EquipmentSlot::MeleeMainHand => DamageSource::Weapon(WeaponKind::Melee),
@@ -143,6 +179,7 @@ impl TryFrom<String> for DamageSource {
         match value.to_ascii_lowercase().as_str() {
             "melee" => Ok(DamageSource::Weapon(WeaponKind::Melee)),
             "ranged" => Ok(DamageSource::Weapon(WeaponKind::Ranged)),
+            "environment" => Ok(DamageSource::Environment),
             _ => Err(format!("Unknown DamageSource: {}", value)),
         }
     }
@@ -165,6 +202,7 @@ impl Display for DamageSource {
         match self {
             DamageSource::Weapon(kind) => write!(f, "{:?}", kind),
             DamageSource::Spell(spell_id) => write!(f, "{}", spell_id),
+            DamageSource::Environment => write!(f, "Environment"),
         }
     }
 }
@@ -191,15 +229,52 @@ impl DamageRoll {
         self.bonus.push(DamageComponent::new(dice, damage_type));
     }
 
+    /// Tag every component of the roll, e.g. all damage dealt by a +1 weapon is
+    /// magical.
+    pub fn add_tag(&mut self, tag: DamageTag) {
+        self.primary.tags.insert(tag);
+        for component in &mut self.bonus {
+            component.tags.insert(tag);
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: DamageTag) {
+        self.primary.tags.remove(&tag);
+        for component in &mut self.bonus {
+            component.tags.remove(&tag);
+        }
+    }
+
     pub fn roll(&self, crit: bool) -> DamageRollResult {
-        if crit {
-            self.roll_internal(2)
+        self.roll_internal(if crit { 2 } else { 1 }, DiceSetRoll::roll)
+    }
+
+    /// Roll the damage according to the roll policy of the creature dealing it,
+    /// e.g. NPCs dealing average damage
+    pub fn roll_with_policy(&self, crit: bool, policy: &RollPolicy) -> DamageRollResult {
+        if policy.average_damage {
+            self.roll_internal(if crit { 2 } else { 1 }, DiceSetRoll::average_roll)
         } else {
-            self.roll_internal(1)
+            self.roll(crit)
         }
     }
 
-    fn roll_internal(&self, repeat: u32) -> DamageRollResult {
+    /// The lowest and highest possible results of the roll
+    pub fn min_max_results(&self, crit: bool) -> (DamageRollResult, DamageRollResult) {
+        let repeat = if crit { 2 } else { 1 };
+        (
+            self.roll_internal(repeat, |dice_roll| dice_roll.fixed_roll(1)),
+            self.roll_internal(repeat, |dice_roll| {
+                dice_roll.fixed_roll(dice_roll.dice.die_size as u32)
+            }),
+        )
+    }
+
+    fn roll_internal(
+        &self,
+        repeat: u32,
+        roll: impl Fn(&DiceSetRoll) -> DiceSetRollResult,
+    ) -> DamageRollResult {
         let mut results = Vec::new();
         let mut total = 0;
 
@@ -209,11 +284,17 @@ impl DamageRoll {
         for component in damage_components {
             let mut component_dice_roll = component.dice_roll.clone();
             component_dice_roll.dice.num_dice *= repeat;
-            let result = component_dice_roll.roll();
+            let result = roll(&component_dice_roll);
             total += result.subtotal;
+            let mut tags = component.tags.clone();
+            // Damage from spells is always magical
+            if matches!(self.source, DamageSource::Spell(_)) {
+                tags.insert(DamageTag::Magical);
+            }
             results.push(DamageComponentResult {
                 damage_type: component.damage_type,
                 result,
+                tags,
             });
         }
 
@@ -222,6 +303,7 @@ impl DamageRoll {
             total,
             source: self.source.clone(),
             action: None,
+            crit: repeat > 1,
         }
     }
 
@@ -261,6 +343,9 @@ pub struct DamageRollResult {
     // TODO: I don't think a full `ActionData` is necessary here, so let's just
     // store the actor and action id for now
     pub action: Option<(Entity, ActionId)>,
+    /// The dice were doubled for a critical hit, which also goes for any dice
+    /// added to the roll afterwards, e.g. a smite
+    pub crit: bool,
 }
 
 impl DamageRollResult {
@@ -298,6 +383,7 @@ impl Default for DamageRollResult {
             total: 0,
             source: DamageSource::Weapon(WeaponKind::Melee),
             action: None,
+            crit: false,
         }
     }
 }
@@ -395,6 +481,23 @@ impl From<MitigationOperation> for String {
 pub struct DamageMitigationEffect {
     pub source: ModifierSource,
     pub operation: MitigationOperation,
+    /// The effect doesn't apply to damage with any of these tags, e.g. resistance
+    /// to nonmagical slashing damage is bypassed by magical damage
+    pub bypassed_by: BTreeSet<DamageTag>,
+}
+
+impl DamageMitigationEffect {
+    pub fn new(source: ModifierSource, operation: MitigationOperation) -> Self {
+        Self {
+            source,
+            operation,
+            bypassed_by: BTreeSet::new(),
+        }
+    }
+
+    pub fn applies_to(&self, component: &DamageComponentResult) -> bool {
+        self.bypassed_by.is_disjoint(&component.tags)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -445,7 +548,11 @@ impl DamageResistances {
 
             if let Some(effects) = self.effects.get(&damage_type) {
                 // Sort by priority
-                let mut sorted_effects = effects.clone();
+                let mut sorted_effects: Vec<_> = effects
+                    .iter()
+                    .filter(|effect| effect.applies_to(comp))
+                    .cloned()
+                    .collect();
                 sorted_effects.sort_by_key(|e| e.operation.priority());
 
                 for effect in sorted_effects {
@@ -479,6 +586,28 @@ impl DamageResistances {
     }
 }
 
+/// Objects (and some creatures) have a damage threshold. Damage from a single
+/// attack or effect that is lower than the threshold is ignored entirely, while
+/// damage equal to or above the threshold is taken in full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DamageThreshold(pub u32);
+
+impl DamageThreshold {
+    pub fn apply(&self, mitigation_result: &mut DamageMitigationResult) {
+        if self.0 == 0 || mitigation_result.total >= self.0 as i32 {
+            return;
+        }
+
+        for component in &mut mitigation_result.components {
+            component.modifiers.push(DamageMitigationEffect::new(
+                ModifierSource::Custom("Damage Threshold".to_string()),
+                MitigationOperation::Immunity,
+            ));
+        }
+        mitigation_result.recalculate_total();
+    }
+}
+
 impl Default for DamageResistances {
     fn default() -> Self {
         Self::new()
@@ -594,18 +723,56 @@ pub struct AttackRoll {
     pub d20_check: D20Check,
     pub source: DamageSource,
     crit_threshold: u8, // Default critical threshold is 20
+    /// If set, any hit is a critical hit, e.g. attacks against a paralyzed target
+    crit_on_hit: Option<ModifierSource>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AttackRollResult {
     pub roll_result: D20CheckResult,
     pub source: DamageSource,
+    pub crit_on_hit: Option<ModifierSource>,
+}
+
+impl AttackRollResult {
+    /// Should be called once the attack is known to hit. Turns the hit into a
+    /// critical hit if the attack roll has a crit on hit override.
+    pub fn confirm_hit(&mut self) {
+        if self.crit_on_hit.is_some() {
+            self.roll_result.is_crit = true;
+        }
+    }
+
+    /// Whether the attack hits a target with the given Armor Class
+    pub fn hits(&self, armor_class: &ArmorClass) -> bool {
+        let result = &self.roll_result;
+        if let Some(forced_outcome) = &result.forced_outcome {
+            return forced_outcome.kind == D20CheckOverride::AutoSuccess;
+        }
+        !result.is_crit_fail && (result.is_crit || result.total() >= armor_class.total() as u32)
+    }
 }
 
 impl fmt::Display for AttackRollResult {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // TODO: Include source information?
-        write!(f, "{}", self.roll_result)
+        write!(f, "{}", self.roll_result)?;
+        if let Some(source) = &self.crit_on_hit {
+            write!(f, " (Critical on Hit: {})", source)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for AttackRoll {
+    fn default() -> Self {
+        Self::new(
+            D20Check::new(Proficiency::new(
+                ProficiencyLevel::None,
+                ModifierSource::None,
+            )),
+            DamageSource::default(),
+        )
     }
 }
 
@@ -615,9 +782,14 @@ impl AttackRoll {
             d20_check,
             source,
             crit_threshold: 20,
+            crit_on_hit: None,
         }
     }
 
+    pub fn set_crit_on_hit(&mut self, source: ModifierSource) {
+        self.crit_on_hit = Some(source);
+    }
+
     // TODO: Track the source of the crit threshold reduction?
     pub fn reduce_crit_threshold(&mut self, amount: u8) {
         if amount > self.crit_threshold {
@@ -628,7 +800,15 @@ impl AttackRoll {
     }
 
     pub fn roll_raw(&self, proficiency_bonus: u8) -> AttackRollResult {
-        let mut roll_result = self.d20_check.roll(proficiency_bonus);
+        self.roll_raw_with_policy(proficiency_bonus, &RollPolicy::RANDOM)
+    }
+
+    pub fn roll_raw_with_policy(
+        &self,
+        proficiency_bonus: u8,
+        policy: &RollPolicy,
+    ) -> AttackRollResult {
+        let mut roll_result = self.d20_check.roll_with_policy(proficiency_bonus, policy);
         if roll_result.selected_roll >= self.crit_threshold {
             roll_result.is_crit = true;
         }
@@ -636,6 +816,7 @@ impl AttackRoll {
         AttackRollResult {
             roll_result,
             source: self.source.clone(),
+            crit_on_hit: self.crit_on_hit.clone(),
         }
     }
 
@@ -647,6 +828,26 @@ impl AttackRoll {
                 .proficiency_bonus(),
         )
     }
+
+    pub fn crit_chance(&self, world: &World, entity: Entity, target_ac: u32) -> f64 {
+        if self.crit_on_hit.is_some() {
+            return self.hit_chance(world, entity, target_ac);
+        }
+        if self
+            .d20_check
+            .active_override()
+            .is_some_and(|active_override| active_override.kind == D20CheckOverride::AutoFailure)
+        {
+            return 0.0;
+        }
+
+        let single_roll_p = (21 - self.crit_threshold as i32) as f64 / 20.0;
+        match self.d20_check.advantage_tracker().roll_mode() {
+            RollMode::Normal => single_roll_p,
+            RollMode::Advantage => 1.0 - (1.0 - single_roll_p).powi(2),
+            RollMode::Disadvantage => single_roll_p.powi(2),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -701,6 +902,7 @@ mod tests {
             vec![DamageMitigationEffect {
                 source: ModifierSource::Item(ItemId::new("nat20_core", "item.shield_of_resistance")),
                 operation: MitigationOperation::Resistance,
+                bypassed_by: BTreeSet::new(),
             }],
         );
 
@@ -721,6 +923,7 @@ mod tests {
             vec![DamageMitigationEffect {
                 source: ModifierSource::Item(ItemId::new("nat20_core", "item.ring_of_fire_immunity")),
                 operation: MitigationOperation::Immunity,
+                bypassed_by: BTreeSet::new(),
             }],
         );
 
@@ -743,6 +946,7 @@ mod tests {
                     "item.shield_of_vulnerability",
                 )),
                 operation: MitigationOperation::Vulnerability,
+                bypassed_by: BTreeSet::new(),
             }],
         );
 
@@ -765,6 +969,7 @@ mod tests {
                     "item.shield_of_flat_reduction",
                 )),
                 operation: MitigationOperation::FlatReduction(3),
+                bypassed_by: BTreeSet::new(),
             }],
         );
 
@@ -788,6 +993,7 @@ mod tests {
                         "item.shield_of_resistance",
                     )),
                     operation: MitigationOperation::Resistance,
+                    bypassed_by: BTreeSet::new(),
                 },
                 DamageMitigationEffect {
                     source: ModifierSource::Item(ItemId::new(
@@ -795,6 +1001,7 @@ mod tests {
                         "item.shield_of_flat_reduction",
                     )),
                     operation: MitigationOperation::FlatReduction(3),
+                    bypassed_by: BTreeSet::new(),
                 },
             ],
         );
@@ -816,6 +1023,7 @@ mod tests {
             vec![DamageMitigationEffect {
                 source: ModifierSource::Item(ItemId::new("nat20_core", "item.shield_of_resistance")),
                 operation: MitigationOperation::Resistance,
+                bypassed_by: BTreeSet::new(),
             }],
         );
         resistances.effects.insert(
@@ -823,6 +1031,7 @@ mod tests {
             vec![DamageMitigationEffect {
                 source: ModifierSource::Item(ItemId::new("nat20_core", "item.ring_of_fire_immunity")),
                 operation: MitigationOperation::Immunity,
+                bypassed_by: BTreeSet::new(),
             }],
         );
 
@@ -847,10 +1056,12 @@ mod tests {
                         "item.shield_of_resistance",
                     )),
                     operation: MitigationOperation::Resistance,
+                    bypassed_by: BTreeSet::new(),
                 },
                 DamageMitigationEffect {
                     source: ModifierSource::Effect(EffectId::new("nat20_core", "Curse of Slashing")),
                     operation: MitigationOperation::Vulnerability,
+                    bypassed_by: BTreeSet::new(),
                 },
                 DamageMitigationEffect {
                     source: ModifierSource::Item(ItemId::new(
@@ -858,6 +1069,7 @@ mod tests {
                         "item.ring_of_slashing_immunity",
                     )),
                     operation: MitigationOperation::Immunity,
+                    bypassed_by: BTreeSet::new(),
                 },
             ],
         );
@@ -885,6 +1097,7 @@ mod tests {
                         "item.shield_of_resistance",
                     )),
                     operation: MitigationOperation::Resistance,
+                    bypassed_by: BTreeSet::new(),
                 },
                 DamageMitigationEffect {
                     source: ModifierSource::Item(ItemId::new(
@@ -892,6 +1105,7 @@ mod tests {
                         "item.shield_of_flat_reduction",
                     )),
                     operation: MitigationOperation::FlatReduction(3),
+                    bypassed_by: BTreeSet::new(),
                 },
             ],
         );
@@ -903,6 +1117,73 @@ mod tests {
         println!("{}", mitigation_result);
     }
 
+    #[rstest]
+    fn damage_mitigation_bypassed_by_tag(mut damage_roll_result: DamageRollResult) {
+        let mut resistances = DamageResistances::new();
+        resistances.add_effect(
+            DamageType::Slashing,
+            DamageMitigationEffect {
+                source: ModifierSource::Custom("Nonmagical Resistance".to_string()),
+                operation: MitigationOperation::Resistance,
+                bypassed_by: BTreeSet::from([DamageTag::Magical, DamageTag::Silvered]),
+            },
+        );
+
+        // Nonmagical slashing is halved
+        let mitigation_result = resistances.apply(&damage_roll_result);
+        // 7 / 2 + 2 = 5
+        assert_eq!(mitigation_result.total, 5);
+
+        // Silvered slashing bypasses the resistance
+        damage_roll_result.components[0]
+            .tags
+            .insert(DamageTag::Silvered);
+        let mitigation_result = resistances.apply(&damage_roll_result);
+        // 7 + 2 = 9
+        assert_eq!(mitigation_result.total, 9);
+        assert!(mitigation_result.components[0].modifiers.is_empty());
+    }
+
+    #[rstest]
+    fn damage_threshold(damage_roll_result: DamageRollResult) {
+        let resistances = DamageResistances::new();
+
+        // 7 + 2 = 9, which doesn't meet the threshold
+        let mut mitigation_result = resistances.apply(&damage_roll_result);
+        DamageThreshold(10).apply(&mut mitigation_result);
+        assert_eq!(mitigation_result.total, 0);
+        assert!(
+            mitigation_result
+                .components
+                .iter()
+                .all(|component| component.after_mods == 0)
+        );
+
+        // Damage meeting the threshold is taken in full
+        let mut mitigation_result = resistances.apply(&damage_roll_result);
+        DamageThreshold(9).apply(&mut mitigation_result);
+        assert_eq!(mitigation_result.total, 9);
+    }
+
+    #[rstest]
+    fn damage_roll_tags(mut damage_roll: DamageRoll) {
+        damage_roll.add_tag(DamageTag::Magical);
+        let result = damage_roll.roll(false);
+        assert!(
+            result
+                .components
+                .iter()
+                .all(|component| component.tags.contains(&DamageTag::Magical))
+        );
+    }
+
+    #[rstest]
+    fn spell_damage_is_magical(mut damage_roll: DamageRoll) {
+        damage_roll.source = DamageSource::Spell(SpellId::new("nat20_core", "spell.fire_bolt"));
+        let result = damage_roll.roll(false);
+        assert!(result.components[0].tags.contains(&DamageTag::Magical));
+    }
+
     // TODO: Find a better way to test this
     // #[test]
     // fn attack_roll_crit_threshold() {
@@ -962,6 +1243,7 @@ mod tests {
                     modifiers,
                 ),
                 damage_type: DamageType::Slashing,
+                tags: BTreeSet::new(),
             },
             bonus: vec![DamageComponent {
                 dice_roll: DiceSetRoll::new(
@@ -972,6 +1254,7 @@ mod tests {
                     ModifierSet::new(),
                 ),
                 damage_type: DamageType::Fire,
+                tags: BTreeSet::new(),
             }],
             source: DamageSource::Weapon(WeaponKind::Melee),
         }
@@ -989,6 +1272,7 @@ mod tests {
                         modifiers: ModifierSet::new(),
                         subtotal: 7,
                     },
+                    tags: BTreeSet::new(),
                 },
                 DamageComponentResult {
                     damage_type: DamageType::Fire,
@@ -998,11 +1282,13 @@ mod tests {
                         modifiers: ModifierSet::new(),
                         subtotal: 2,
                     },
+                    tags: BTreeSet::new(),
                 },
             ],
             total: 9,
             source: DamageSource::Weapon(WeaponKind::Melee),
             action: None,
+            crit: false,
         }
     }
 }
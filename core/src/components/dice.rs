@@ -76,6 +76,40 @@ impl From<DiceSet> for String {
     }
 }
 
+/// The d20 result used when a creature "takes 10" instead of rolling
+pub const TAKE_10: u8 = 10;
+
+/// Replaces the dice rolled by a creature with static results. The GM can turn
+/// this on for the NPCs of an encounter to speed up large fights, see
+/// `GameState::set_roll_policy`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RollPolicy {
+    /// Damage dice always come up with their average, rounded down
+    #[serde(default)]
+    pub average_damage: bool,
+    /// d20 rolls always come up with this value instead of being rolled
+    #[serde(default)]
+    pub fixed_d20: Option<u8>,
+}
+
+impl RollPolicy {
+    /// Roll every die as usual
+    pub const RANDOM: Self = Self {
+        average_damage: false,
+        fixed_d20: None,
+    };
+
+    /// Average damage and take 10 on every d20 roll
+    pub const STATIC: Self = Self {
+        average_damage: true,
+        fixed_d20: Some(TAKE_10),
+    };
+
+    pub fn is_random(&self) -> bool {
+        *self == Self::RANDOM
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(try_from = "String", into = "String")]
 pub struct DiceSetRoll {
@@ -114,6 +148,38 @@ impl DiceSetRoll {
     pub fn max_roll(&self) -> i32 {
         (self.dice.num_dice as i32 * self.dice.die_size as i32) + self.modifiers.total()
     }
+
+    /// The result if the dice came up with their average, rounded down like the
+    /// damage listed in a monster's stat block. The rolls alternate between
+    /// rounding down and up, e.g. 2d6 comes up as a 3 and a 4.
+    pub fn average_roll(&self) -> DiceSetRollResult {
+        let sides = self.dice.die_size as u32 + 1;
+        let rolls: Vec<u32> = (0..self.dice.num_dice)
+            .map(|i| ((i + 1) * sides) / 2 - (i * sides) / 2)
+            .collect();
+        let subtotal = rolls.iter().sum::<u32>() as i32 + self.modifiers.total();
+
+        DiceSetRollResult {
+            die_size: self.dice.die_size,
+            rolls,
+            modifiers: self.modifiers.clone(),
+            subtotal,
+        }
+    }
+
+    /// The result if every die came up with the given value, e.g. to preview the
+    /// lowest and highest possible results
+    pub fn fixed_roll(&self, value: u32) -> DiceSetRollResult {
+        let rolls = vec![value.clamp(1, self.dice.die_size as u32); self.dice.num_dice as usize];
+        let subtotal = rolls.iter().sum::<u32>() as i32 + self.modifiers.total();
+
+        DiceSetRollResult {
+            die_size: self.dice.die_size,
+            rolls,
+            modifiers: self.modifiers.clone(),
+            subtotal,
+        }
+    }
 }
 
 impl Modifiable for DiceSetRoll {
@@ -231,7 +297,7 @@ impl fmt::Display for DiceSetRollResult {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CompositeRoll {
     pub groups: Vec<DiceSetRoll>,
 }
@@ -259,12 +325,57 @@ impl CompositeRoll {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CompositeRollResult {
     pub components: Vec<DiceSetRollResult>,
     pub total: i32,
 }
 
+impl fmt::Display for CompositeRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| group.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", groups.join(" + "))
+    }
+}
+
+impl FromStr for CompositeRoll {
+    type Err = String;
+
+    /// Parser for expressions like "2d6 + 1d4 + 3" or "d20-1". Flat bonuses
+    /// are added as modifiers to the first group of dice.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut groups = Vec::new();
+        let mut bonus = 0;
+        for term in parse_dice_terms(s)? {
+            match term {
+                DiceTerm::Dice { dice, keep: None } => {
+                    groups.push(DiceSetRoll::new(dice, ModifierSet::new()))
+                }
+                DiceTerm::Dice { keep: Some(_), .. } => {
+                    return Err(format!("Keeping dice is not supported in: {}", s));
+                }
+                DiceTerm::Flat(value) => bonus += value,
+            }
+        }
+
+        let Some(first) = groups.first_mut() else {
+            return Err(format!(
+                "Dice expression must contain at least one die: {}",
+                s
+            ));
+        };
+        if bonus != 0 {
+            first.add_modifier(ModifierSource::Base, bonus);
+        }
+
+        Ok(Self { groups })
+    }
+}
+
 impl fmt::Display for CompositeRollResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for comp in &self.components {
@@ -274,6 +385,197 @@ impl fmt::Display for CompositeRollResult {
     }
 }
 
+/// Which of the dice in a [`KeepRoll`] count towards the total
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepDice {
+    Highest(u32),
+    Lowest(u32),
+}
+
+/// Roll a number of dice and only keep some of them, e.g. "4d6kh3" for
+/// rolling ability scores or "2d20kl1" for disadvantage
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeepRoll {
+    pub dice: DiceSet,
+    pub keep: KeepDice,
+    pub bonus: i32,
+}
+
+impl KeepRoll {
+    pub fn roll(&self) -> KeepRollResult {
+        let mut rng = rand::rng();
+        let rolls: Vec<u32> = (0..self.dice.num_dice)
+            .map(|_| rng.random_range(1..=self.dice.die_size as u32))
+            .collect();
+
+        let mut sorted = rolls.clone();
+        let kept = match self.keep {
+            KeepDice::Highest(count) => {
+                sorted.sort_unstable_by(|a, b| b.cmp(a));
+                sorted.into_iter().take(count as usize).collect::<Vec<_>>()
+            }
+            KeepDice::Lowest(count) => {
+                sorted.sort_unstable();
+                sorted.into_iter().take(count as usize).collect::<Vec<_>>()
+            }
+        };
+        let total = kept.iter().sum::<u32>() as i32 + self.bonus;
+
+        KeepRollResult { rolls, kept, total }
+    }
+}
+
+impl fmt::Display for KeepRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.keep {
+            KeepDice::Highest(count) => write!(f, "{}kh{}", self.dice, count)?,
+            KeepDice::Lowest(count) => write!(f, "{}kl{}", self.dice, count)?,
+        }
+        match self.bonus {
+            0 => Ok(()),
+            bonus if bonus > 0 => write!(f, "+{}", bonus),
+            bonus => write!(f, "{}", bonus),
+        }
+    }
+}
+
+impl FromStr for KeepRoll {
+    type Err = String;
+
+    /// Parser for expressions like "4d6kh3", "2d20kl1+5" or "2d20kh", where
+    /// the number of kept dice defaults to one
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keep_roll = None;
+        let mut bonus = 0;
+        for term in parse_dice_terms(s)? {
+            match term {
+                DiceTerm::Dice { dice, keep } if keep_roll.is_none() => {
+                    let keep = keep.ok_or_else(|| format!("Missing 'kh' or 'kl' in: {}", s))?;
+                    keep_roll = Some((dice, keep));
+                }
+                DiceTerm::Dice { .. } => {
+                    return Err(format!("Only one set of dice can be kept in: {}", s));
+                }
+                DiceTerm::Flat(value) => bonus += value,
+            }
+        }
+
+        let Some((dice, keep)) = keep_roll else {
+            return Err(format!("Missing dice to keep in: {}", s));
+        };
+        Ok(Self { dice, keep, bonus })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeepRollResult {
+    pub rolls: Vec<u32>,
+    /// The dice that counted towards the total, from best to worst
+    pub kept: Vec<u32>,
+    pub total: i32,
+}
+
+impl fmt::Display for KeepRollResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} keep {:?} = {}", self.rolls, self.kept, self.total)
+    }
+}
+
+/// A single term of a dice expression, with the sign already applied to flat
+/// numbers
+enum DiceTerm {
+    Dice {
+        dice: DiceSet,
+        keep: Option<KeepDice>,
+    },
+    Flat(i32),
+}
+
+/// The grammar shared by [`CompositeRoll`] and [`KeepRoll`]: terms separated
+/// by '+' or '-', where each term is either a flat number or a set of dice
+/// with an optional "kh"/"kl" suffix, e.g. "2d6 + 1d4 - 1" or "4d6kh3 + 2".
+fn parse_dice_terms(s: &str) -> Result<Vec<DiceTerm>, String> {
+    let expression = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    if expression.is_empty() {
+        return Err("Empty dice expression".to_string());
+    }
+
+    let mut terms = Vec::new();
+    let mut sign = 1;
+    let mut term = String::new();
+    for (i, c) in expression.chars().enumerate() {
+        match c {
+            '+' | '-' => {
+                if term.is_empty() && i > 0 {
+                    return Err(format!("Missing term before '{}' in: {}", c, s));
+                }
+                if !term.is_empty() {
+                    terms.push(parse_dice_term(sign, &std::mem::take(&mut term))?);
+                }
+                sign = if c == '-' { -1 } else { 1 };
+            }
+            _ => term.push(c),
+        }
+    }
+    if term.is_empty() {
+        return Err(format!("Missing term at the end of: {}", s));
+    }
+    terms.push(parse_dice_term(sign, &term)?);
+
+    Ok(terms)
+}
+
+fn parse_dice_term(sign: i32, term: &str) -> Result<DiceTerm, String> {
+    if !term.contains('d') {
+        let value = term
+            .parse::<i32>()
+            .map_err(|_| format!("Invalid term: {}", term))?;
+        return Ok(DiceTerm::Flat(sign * value));
+    }
+    if sign < 0 {
+        return Err(format!("Subtracting dice is not supported: -{}", term));
+    }
+
+    let (dice, keep) = if let Some((dice, count)) = term.split_once("kh") {
+        (dice, Some((count, true)))
+    } else if let Some((dice, count)) = term.split_once("kl") {
+        (dice, Some((count, false)))
+    } else {
+        (term, None)
+    };
+    let dice: DiceSet = dice.parse()?;
+
+    let keep = match keep {
+        Some((count, highest)) => {
+            let count = if count.is_empty() {
+                1
+            } else {
+                count
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid number of dice to keep: {}", count))?
+            };
+            if count == 0 || count > dice.num_dice {
+                return Err(format!(
+                    "Can't keep {} out of {} dice in: {}",
+                    count, dice.num_dice, term
+                ));
+            }
+            Some(if highest {
+                KeepDice::Highest(count)
+            } else {
+                KeepDice::Lowest(count)
+            })
+        }
+        None => None,
+    };
+
+    Ok(DiceTerm::Dice { dice, keep })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::components::{ability::Ability, id::ItemId, modifier::ModifierSource};
@@ -307,6 +609,20 @@ mod tests {
         println!("Dice Roll Result:\n{}", result);
     }
 
+    #[test]
+    fn average_roll() {
+        let dice = DiceSetRoll::new(
+            "2d6".parse().unwrap(),
+            ModifierSet::from(ModifierSource::Base, 2),
+        );
+        let result = dice.average_roll();
+        assert_eq!(result.rolls, vec![3, 4]);
+        assert_eq!(result.subtotal, 9);
+
+        let dice = DiceSetRoll::new("3d8".parse().unwrap(), ModifierSet::new());
+        assert_eq!(dice.average_roll().subtotal, 13);
+    }
+
     #[test]
     fn composite_roll() {
         let mut modifiers = ModifierSet::new();
@@ -387,4 +703,73 @@ mod tests {
         assert_eq!(dice.num_dice, 1);
         assert_eq!(dice.die_size, DieSize::D100);
     }
+
+    #[test]
+    fn parse_composite_roll() {
+        let roll: CompositeRoll = "2d6 + 1d4 + 3".parse().unwrap();
+        assert_eq!(roll.groups.len(), 2);
+        assert_eq!(roll.groups[0].dice, DiceSet::new(2, DieSize::D6));
+        assert_eq!(roll.groups[1].dice, DiceSet::new(1, DieSize::D4));
+        assert_eq!(roll.min_roll(), 6);
+        assert_eq!(roll.max_roll(), 19);
+    }
+
+    #[test]
+    fn parse_composite_roll_with_negative_bonus() {
+        let roll: CompositeRoll = "D20-1".parse().unwrap();
+        assert_eq!(roll.groups.len(), 1);
+        assert_eq!(roll.min_roll(), 0);
+        assert_eq!(roll.max_roll(), 19);
+    }
+
+    #[test]
+    fn parse_invalid_composite_roll_errors() {
+        assert!(CompositeRoll::from_str("").is_err());
+        assert!(CompositeRoll::from_str("3").is_err());
+        assert!(CompositeRoll::from_str("2d6 +").is_err());
+        assert!(CompositeRoll::from_str("2d6 ++ 1").is_err());
+        assert!(CompositeRoll::from_str("1d20 - 1d4").is_err());
+        assert!(CompositeRoll::from_str("2d6 + x").is_err());
+        assert!(CompositeRoll::from_str("4d6kh3 + 1d4").is_err());
+    }
+
+    #[test]
+    fn parse_keep_roll() {
+        let roll: KeepRoll = "4d6kh3".parse().unwrap();
+        assert_eq!(roll.dice, DiceSet::new(4, DieSize::D6));
+        assert_eq!(roll.keep, KeepDice::Highest(3));
+        assert_eq!(roll.bonus, 0);
+
+        let roll: KeepRoll = "2d20 kl + 5".parse().unwrap();
+        assert_eq!(roll.dice, DiceSet::new(2, DieSize::D20));
+        assert_eq!(roll.keep, KeepDice::Lowest(1));
+        assert_eq!(roll.bonus, 5);
+        assert_eq!(roll.to_string(), "2d20kl1+5");
+    }
+
+    #[test]
+    fn parse_invalid_keep_roll_errors() {
+        assert!("4d6".parse::<KeepRoll>().is_err());
+        assert!("4d6kh5".parse::<KeepRoll>().is_err());
+        assert!("4d6kh0".parse::<KeepRoll>().is_err());
+        assert!("4d6khx".parse::<KeepRoll>().is_err());
+        assert!("4d6kh3 + 1d4".parse::<KeepRoll>().is_err());
+        assert!("4d6kh3 ++ 1".parse::<KeepRoll>().is_err());
+    }
+
+    #[test]
+    fn keep_roll_keeps_highest() {
+        let roll: KeepRoll = "4d6kh3-1".parse().unwrap();
+        for _ in 0..20 {
+            let result = roll.roll();
+            assert_eq!(result.rolls.len(), 4);
+            assert_eq!(result.kept.len(), 3);
+            let lowest = *result.rolls.iter().min().unwrap();
+            assert_eq!(
+                result.rolls.iter().sum::<u32>() - lowest,
+                result.kept.iter().sum::<u32>()
+            );
+            assert_eq!(result.total, result.kept.iter().sum::<u32>() as i32 - 1);
+        }
+    }
 }
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+use crate::components::{ability::Ability, id::EffectId, saving_throw::SavingThrowKind};
+
+/// Long-lasting afflictions which get worse over the days. Every dawn an
+/// infected creature makes a saving throw against the disease, and each failed
+/// save makes the symptoms worse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Disease {
+    #[strum(to_string = "Sight Rot")]
+    SightRot,
+    #[strum(to_string = "Sewer Plague")]
+    SewerPlague,
+}
+
+impl Disease {
+    /// The effect marking a creature as infected
+    pub fn effect(&self) -> EffectId {
+        match self {
+            Disease::SightRot => EffectId::new("nat20_core", "effect.disease.sight_rot"),
+            Disease::SewerPlague => EffectId::new("nat20_core", "effect.disease.sewer_plague"),
+        }
+    }
+
+    pub fn saving_throw(&self) -> SavingThrowKind {
+        SavingThrowKind::Ability(Ability::Constitution)
+    }
+
+    pub fn saving_throw_dc(&self) -> i32 {
+        match self {
+            Disease::SightRot => 15,
+            Disease::SewerPlague => 11,
+        }
+    }
+
+    /// The effects applied as the disease progresses, starting with the first
+    /// symptoms. Once the last stage is reached, every failed save applies it
+    /// again, e.g. another level of exhaustion.
+    pub fn stages(&self) -> Vec<EffectId> {
+        match self {
+            Disease::SightRot => (1..=5)
+                .map(|stage| {
+                    EffectId::new(
+                        "nat20_core",
+                        format!("effect.disease.sight_rot.symptoms_{}", stage),
+                    )
+                })
+                .collect(),
+            Disease::SewerPlague => {
+                vec![EffectId::new("nat20_core", "effect.condition.exhaustion")]
+            }
+        }
+    }
+
+    /// How many days in a row the creature has to finish a long rest and
+    /// succeed on its saving throw to recover from the disease
+    pub fn recovery_streak(&self) -> u32 {
+        match self {
+            Disease::SightRot => 3,
+            Disease::SewerPlague => 2,
+        }
+    }
+}
+
+/// How far a disease has progressed in a creature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiseaseProgress {
+    /// Index of the current stage in [`Disease::stages`]
+    pub stage: usize,
+    /// Days in a row with a long rest and a successful saving throw
+    pub streak: u32,
+    /// Whether the creature has finished a long rest since the last dawn
+    pub rested: bool,
+}
+
+/// The diseases a creature is suffering from. Added at the first dawn after the
+/// creature is infected, which is when the symptoms start to show.
+pub type Diseases = HashMap<Disease, DiseaseProgress>;
+
+/// What happened to a disease at dawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiseaseOutcome {
+    /// The disease has incubated and the first symptoms appear
+    SymptomsAppear,
+    /// The creature succeeded on its saving throw. The streak counts the days
+    /// in a row towards recovering.
+    Resisted { streak: u32 },
+    /// The creature failed its saving throw and the disease progressed
+    Worsened { stage: usize },
+    /// The creature has recovered from the disease
+    Recovered,
+}
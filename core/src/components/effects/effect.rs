@@ -1,4 +1,9 @@
-use std::{collections::HashMap, fmt::Debug, hash::Hash, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+    sync::Arc,
+};
 
 use hecs::{Entity, World};
 use serde::{Deserialize, Serialize};
@@ -6,15 +11,17 @@ use serde::{Deserialize, Serialize};
 use crate::{
     components::{
         actions::action::ActionContext,
+        control::ControlTemplate,
         damage::{
             AttackRoll, AttackRollResult, DamageMitigationResult, DamageRoll, DamageRollResult,
         },
         effects::hooks::{
             ActionHook, ApplyEffectHook, ArmorClassHook, AttackRollHook, AttackRollResultHook,
-            D20CheckHooks, DamageRollHook, DamageRollResultHook, DeathHook,
-            PostDamageMitigationHook, PreDamageMitigationHook, ResourceCostHook, UnapplyEffectHook,
+            D20CheckHooks, DamageRollHook, DamageRollResultHook, DeathHook, IncomingAttackRollHook,
+            PostDamageMitigationHook, PreDamageMitigationHook, ResourceCostHook, ThrowHook,
+            UnapplyEffectHook,
         },
-        id::{ActionId, EffectId, IdProvider},
+        id::{ActionId, EffectId, IdProvider, ItemId},
         items::equipment::armor::ArmorClass,
         modifier::ModifierSource,
         resource::ResourceAmountMap,
@@ -24,6 +31,7 @@ use crate::{
     },
     engine::event::ActionData,
     registry::{registry::EffectsRegistry, serialize::effect::EffectDefinition},
+    systems,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -38,6 +46,47 @@ pub enum EffectLifetime {
         duration: TimeDuration,
         remaining: TimeDuration,
     },
+
+    /// Expire at the Start/End of `entity`'s *next* turn, e.g. "until the end
+    /// of your next turn". An End boundary only counts once `entity` has
+    /// started a turn after the effect was applied, so an effect applied during
+    /// `entity`'s own turn outlasts that turn. Outside of combat the effect
+    /// lasts for `remaining`, which is one round.
+    UntilNextTurn {
+        entity: Entity,
+        boundary: TurnBoundary,
+        turn_started: bool,
+        remaining: TimeDuration,
+    },
+}
+
+impl EffectLifetime {
+    /// The entity whose turns drive the lifetime on the initiative timeline
+    pub fn anchor(&self) -> Option<Entity> {
+        match self {
+            EffectLifetime::Permanent => None,
+            EffectLifetime::AtTurnBoundary { entity, .. }
+            | EffectLifetime::UntilNextTurn { entity, .. } => Some(*entity),
+        }
+    }
+
+    /// Move the lifetime onto the turns of another entity, e.g. when the
+    /// original anchor is not taking part in the encounter.
+    pub fn set_anchor(&mut self, anchor: Entity) {
+        match self {
+            EffectLifetime::Permanent => {}
+            EffectLifetime::AtTurnBoundary { entity, .. }
+            | EffectLifetime::UntilNextTurn { entity, .. } => *entity = anchor,
+        }
+    }
+
+    pub fn remaining(&self) -> Option<&TimeDuration> {
+        match self {
+            EffectLifetime::Permanent => None,
+            EffectLifetime::AtTurnBoundary { remaining, .. }
+            | EffectLifetime::UntilNextTurn { remaining, .. } => Some(remaining),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -59,6 +108,22 @@ pub enum EffectLifetimeTemplate {
         boundary: TurnBoundary,
         duration: TimeDuration,
     },
+    UntilNextTurn {
+        entity: EffectLifetimeEntiy,
+        boundary: TurnBoundary,
+    },
+    /// A fixed duration (rounds, minutes, hours) counted from the start of the
+    /// applier's turn, so "1 minute" ends just before the applier's 11th turn.
+    Duration(TimeDuration),
+}
+
+impl EffectLifetimeEntiy {
+    fn resolve(&self, applier: Entity, target: Entity) -> Entity {
+        match self {
+            EffectLifetimeEntiy::Applier => applier,
+            EffectLifetimeEntiy::Target => target,
+        }
+    }
 }
 
 impl EffectLifetimeTemplate {
@@ -70,18 +135,28 @@ impl EffectLifetimeTemplate {
                 entity,
                 boundary,
                 duration,
-            } => {
-                let entity = match entity {
-                    EffectLifetimeEntiy::Applier => applier,
-                    EffectLifetimeEntiy::Target => target,
-                };
-                EffectLifetime::AtTurnBoundary {
-                    entity,
+            } => EffectLifetime::AtTurnBoundary {
+                entity: entity.resolve(applier, target),
+                boundary: *boundary,
+                duration: *duration,
+                remaining: *duration,
+            },
+
+            EffectLifetimeTemplate::UntilNextTurn { entity, boundary } => {
+                EffectLifetime::UntilNextTurn {
+                    entity: entity.resolve(applier, target),
                     boundary: *boundary,
-                    duration: *duration,
-                    remaining: *duration,
+                    turn_started: false,
+                    remaining: TimeDuration::from_rounds(1),
                 }
             }
+
+            EffectLifetimeTemplate::Duration(duration) => EffectLifetime::AtTurnBoundary {
+                entity: applier,
+                boundary: TurnBoundary::Start,
+                duration: *duration,
+                remaining: *duration,
+            },
         }
     }
 }
@@ -93,6 +168,78 @@ pub enum EffectKind {
     Debuff,
 }
 
+/// Categories of effects which other rules refer to, e.g. Remove Curse ends
+/// every curse on the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectTag {
+    /// Can only be ended by Remove Curse or similar magic. Items with a curse
+    /// among their effects can't be unequipped while the curse lasts.
+    Curse,
+    /// A disease or one of its symptoms, which can be ended by Lesser
+    /// Restoration or similar magic
+    Disease,
+    /// A poison or the Poisoned condition, which can be ended by Lay on Hands
+    /// or similar abilities
+    Poison,
+    /// Makes the entity Invisible, so it can't be seen or targeted directly by
+    /// creatures which aren't its allies
+    Invisible,
+    /// The entity can't harm the creature that applied the effect
+    Charmed,
+    /// The entity can't willingly move closer to the creature that applied the
+    /// effect, and has Disadvantage on attack rolls and ability checks while it
+    /// can see it
+    Frightened,
+    /// Reduces the hit point maximum of the entity until it finishes a long
+    /// rest, or until it's ended by Greater Restoration or similar magic
+    MaxHitPointReduction,
+    /// The entity can't take any actions, so its turns are skipped
+    Incapacitated,
+}
+
+/// Actions which end an effect on the entity performing them, e.g. the
+/// Invisibility spell ending when the invisible creature attacks or casts a
+/// spell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectEndTrigger {
+    Attack,
+    Spell,
+}
+
+impl EffectEndTrigger {
+    pub fn matches(&self, action_data: &ActionData) -> bool {
+        match self {
+            EffectEndTrigger::Attack => systems::actions::get_action(&action_data.action_id)
+                .is_some_and(|action| action.kind().is_attack()),
+            EffectEndTrigger::Spell => {
+                matches!(action_data.context.base(), ActionContext::Spell { .. })
+            }
+        }
+    }
+}
+
+/// What happens when an effect is applied to an entity which already has it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StackingPolicy {
+    /// Effects of the same name don't stack. The entity keeps a single
+    /// instance, which lasts for the longest of the applied durations.
+    #[default]
+    Unique,
+    /// Each application adds a stack to the existing instance, e.g. levels of
+    /// exhaustion. `on_apply` runs once for every stack, and flat modifiers
+    /// are multiplied by the number of stacks.
+    Stack {
+        max: Option<u32>,
+        /// The entity dies when the effect reaches its maximum number of
+        /// stacks, e.g. at the sixth level of exhaustion
+        #[serde(default)]
+        lethal: bool,
+    },
+}
+
 #[derive(Clone, Deserialize)]
 #[serde(from = "EffectDefinition")]
 pub struct Effect {
@@ -100,6 +247,18 @@ pub struct Effect {
     pub kind: EffectKind,
     pub description: String,
     pub replaces: Option<EffectId>,
+    pub stacking: StackingPolicy,
+    pub tags: HashSet<EffectTag>,
+    /// The effect ends when the entity performs one of these kinds of actions
+    pub end_on: HashSet<EffectEndTrigger>,
+    /// The effect ends when the applier or one of its allies damages the
+    /// entity, e.g. Charm Person
+    pub end_on_damage: bool,
+    /// Hands control of the entity's turns to the applier while the effect lasts
+    pub control: Option<ControlTemplate>,
+    /// Applied to the entity when the effect ends, e.g. the wave of lethargy
+    /// when Haste ends
+    pub followed_by: Option<EffectInstanceTemplate>,
 
     // on_turn_start: EffectHook,
     // TODO: Do we need to differentiate between when an effect explicitly expires and when
@@ -110,6 +269,8 @@ pub struct Effect {
     pub on_skill_check: HashMap<Skill, D20CheckHooks>,
     pub on_saving_throw: HashMap<SavingThrowKind, D20CheckHooks>,
     pub pre_attack_roll: AttackRollHook,
+    /// Applied to attack rolls made *against* the entity with the effect
+    pub pre_incoming_attack_roll: IncomingAttackRollHook,
     pub post_attack_roll: AttackRollResultHook,
     pub on_armor_class: ArmorClassHook,
     pub pre_damage_roll: DamageRollHook,
@@ -119,6 +280,7 @@ pub struct Effect {
     pub pre_damage_mitigation: PreDamageMitigationHook,
     pub post_damage_mitigation: PostDamageMitigationHook,
     pub on_death: DeathHook,
+    pub on_throw: ThrowHook,
 }
 
 impl Effect {
@@ -134,6 +296,9 @@ impl Effect {
             on_saving_throw: HashMap::new(),
             pre_attack_roll: Arc::new(|_: &World, _: Entity, _: &mut AttackRoll| {})
                 as AttackRollHook,
+            pre_incoming_attack_roll: Arc::new(
+                |_: &World, _: Entity, _: Entity, _: &mut AttackRoll| {},
+            ) as IncomingAttackRollHook,
             post_attack_roll: Arc::new(|_: &World, _: Entity, _: &mut AttackRollResult| {})
                 as AttackRollResultHook,
             on_armor_class: Arc::new(|_: &World, _: Entity, _: &mut ArmorClass| {})
@@ -162,13 +327,24 @@ impl Effect {
                  _killer: Option<Entity>,
                  _applier: Option<Entity>| {},
             ) as DeathHook,
+            on_throw: Arc::new(|_: &World, _: Entity, _: &ItemId| false) as ThrowHook,
             replaces: None,
+            stacking: StackingPolicy::Unique,
+            tags: HashSet::new(),
+            end_on: HashSet::new(),
+            end_on_damage: false,
+            control: None,
+            followed_by: None,
         }
     }
 
     pub fn id(&self) -> &EffectId {
         &self.id
     }
+
+    pub fn has_tag(&self, tag: &EffectTag) -> bool {
+        self.tags.contains(tag)
+    }
 }
 
 impl IdProvider for Effect {
@@ -185,6 +361,12 @@ pub struct EffectInstance {
     pub source: ModifierSource,
     pub applier: Option<Entity>,
     pub lifetime: EffectLifetime,
+    /// Number of times the effect has been applied, see `StackingPolicy::Stack`
+    pub stacks: u32,
+    /// Sources currently suppressing the effect, e.g. an antimagic field. A
+    /// suppressed effect is still there, and its duration keeps running, but
+    /// it doesn't do anything until all the suppressions are lifted.
+    pub suppressed_by: Vec<ModifierSource>,
 }
 
 impl EffectInstance {
@@ -194,6 +376,8 @@ impl EffectInstance {
             source,
             lifetime,
             applier: None,
+            stacks: 1,
+            suppressed_by: Vec::new(),
         }
     }
 
@@ -206,6 +390,22 @@ impl EffectInstance {
             .expect(format!("Effect definition not found for ID `{}`", self.effect_id).as_str())
     }
 
+    pub fn is_suppressed(&self) -> bool {
+        !self.suppressed_by.is_empty()
+    }
+
+    /// Whether this instance lasts longer than `other`. Permanent effects
+    /// outlast everything else.
+    pub fn outlasts(&self, other: &EffectInstance) -> bool {
+        match (self.lifetime.remaining(), other.lifetime.remaining()) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(remaining), Some(other_remaining)) => {
+                remaining.as_seconds() > other_remaining.as_seconds()
+            }
+        }
+    }
+
     pub fn advance_time(&mut self, time_step: TimeStep) {
         match self.lifetime {
             EffectLifetime::Permanent => { /* Do nothing */ }
@@ -231,15 +431,36 @@ impl EffectInstance {
                 }
                 remaining.decrement(&time_step);
             }
+
+            EffectLifetime::UntilNextTurn {
+                entity: lifetime_entity,
+                boundary: lifetime_boundary,
+                ref mut turn_started,
+                ref mut remaining,
+            } => match time_step {
+                TimeStep::TurnBoundary {
+                    entity: time_step_entity,
+                    boundary: time_step_boundary,
+                } => {
+                    if time_step_entity != lifetime_entity {
+                        return;
+                    }
+                    if time_step_boundary == TurnBoundary::Start {
+                        *turn_started = true;
+                    }
+                    if time_step_boundary == lifetime_boundary && *turn_started {
+                        *remaining = TimeDuration::from_seconds(0.0);
+                    }
+                }
+                TimeStep::RealTime { .. } => remaining.decrement(&time_step),
+            },
         }
     }
 
     pub fn is_expired(&self) -> bool {
-        match self.lifetime {
-            EffectLifetime::Permanent => false,
-
-            EffectLifetime::AtTurnBoundary { ref remaining, .. } => remaining.as_turns() == 0,
-        }
+        self.lifetime
+            .remaining()
+            .is_some_and(|remaining| remaining.as_turns() == 0)
     }
 }
 
@@ -257,10 +478,12 @@ impl EffectInstanceTemplate {
         source: ModifierSource,
     ) -> EffectInstance {
         EffectInstance {
-            effect_id: self.effect_id.clone(),
-            source,
-            lifetime: self.lifetime.instantiate(applier, target),
             applier: Some(applier),
+            ..EffectInstance::new(
+                self.effect_id.clone(),
+                source,
+                self.lifetime.instantiate(applier, target),
+            )
         }
     }
 
@@ -269,3 +492,156 @@ impl EffectInstanceTemplate {
             .expect(format!("Effect definition not found for ID `{}`", self.effect_id).as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(
+        lifetime: EffectLifetimeTemplate,
+        applier: Entity,
+        target: Entity,
+    ) -> EffectInstance {
+        EffectInstance::new(
+            EffectId::new("nat20_core", "effect.test"),
+            ModifierSource::Custom("Test".to_string()),
+            lifetime.instantiate(applier, target),
+        )
+    }
+
+    fn turn(effect: &mut EffectInstance, entity: Entity) {
+        for boundary in [TurnBoundary::Start, TurnBoundary::End] {
+            effect.advance_time(TimeStep::TurnBoundary { entity, boundary });
+        }
+    }
+
+    #[test]
+    fn until_start_of_next_turn() {
+        let mut world = World::new();
+        let applier = world.spawn(());
+        let target = world.spawn(());
+
+        let mut effect = instance(
+            EffectLifetimeTemplate::UntilNextTurn {
+                entity: EffectLifetimeEntiy::Applier,
+                boundary: TurnBoundary::Start,
+            },
+            applier,
+            target,
+        );
+
+        // Applied during the applier's turn, so the end of that turn doesn't count
+        effect.advance_time(TimeStep::TurnBoundary {
+            entity: applier,
+            boundary: TurnBoundary::End,
+        });
+        turn(&mut effect, target);
+        assert!(!effect.is_expired());
+
+        effect.advance_time(TimeStep::TurnBoundary {
+            entity: applier,
+            boundary: TurnBoundary::Start,
+        });
+        assert!(effect.is_expired());
+    }
+
+    #[test]
+    fn until_end_of_next_turn() {
+        let mut world = World::new();
+        let applier = world.spawn(());
+        let target = world.spawn(());
+
+        let mut effect = instance(
+            EffectLifetimeTemplate::UntilNextTurn {
+                entity: EffectLifetimeEntiy::Applier,
+                boundary: TurnBoundary::End,
+            },
+            applier,
+            target,
+        );
+
+        // The end of the turn the effect was applied in doesn't count
+        effect.advance_time(TimeStep::TurnBoundary {
+            entity: applier,
+            boundary: TurnBoundary::End,
+        });
+        turn(&mut effect, target);
+        assert!(!effect.is_expired());
+
+        effect.advance_time(TimeStep::TurnBoundary {
+            entity: applier,
+            boundary: TurnBoundary::Start,
+        });
+        assert!(!effect.is_expired());
+
+        effect.advance_time(TimeStep::TurnBoundary {
+            entity: applier,
+            boundary: TurnBoundary::End,
+        });
+        assert!(effect.is_expired());
+    }
+
+    #[test]
+    fn until_next_turn_out_of_combat_lasts_one_round() {
+        let mut world = World::new();
+        let applier = world.spawn(());
+
+        let mut effect = instance(
+            EffectLifetimeTemplate::UntilNextTurn {
+                entity: EffectLifetimeEntiy::Target,
+                boundary: TurnBoundary::End,
+            },
+            applier,
+            applier,
+        );
+
+        effect.advance_time(TimeStep::RealTime { delta_seconds: 5.0 });
+        assert!(!effect.is_expired());
+        effect.advance_time(TimeStep::RealTime { delta_seconds: 1.0 });
+        assert!(effect.is_expired());
+    }
+
+    #[test]
+    fn duration_in_rounds_counts_applier_turns() {
+        let mut world = World::new();
+        let applier = world.spawn(());
+        let target = world.spawn(());
+
+        let mut effect = instance(
+            EffectLifetimeTemplate::Duration(TimeDuration::from_rounds(2)),
+            applier,
+            target,
+        );
+        assert_eq!(effect.lifetime.anchor(), Some(applier));
+
+        turn(&mut effect, target);
+        turn(&mut effect, applier);
+        assert!(!effect.is_expired());
+
+        turn(&mut effect, target);
+        turn(&mut effect, applier);
+        assert!(effect.is_expired());
+    }
+
+    #[test]
+    fn lifetime_template_deserialize() {
+        let template: EffectLifetimeTemplate = serde_json::from_str(
+            r#"{ "until_next_turn": { "entity": "target", "boundary": "end" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            template,
+            EffectLifetimeTemplate::UntilNextTurn {
+                entity: EffectLifetimeEntiy::Target,
+                boundary: TurnBoundary::End,
+            }
+        );
+
+        let template: EffectLifetimeTemplate =
+            serde_json::from_str(r#"{ "duration": { "rounds": 10 } }"#).unwrap();
+        assert_eq!(
+            template,
+            EffectLifetimeTemplate::Duration(TimeDuration::from_rounds(10))
+        );
+    }
+}
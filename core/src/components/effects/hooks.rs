@@ -10,7 +10,7 @@ use crate::{
             AttackRoll, AttackRollResult, DamageMitigationResult, DamageRoll, DamageRollResult,
         },
         effects::effect::EffectInstance,
-        id::ActionId,
+        id::{ActionId, ItemId},
         items::equipment::armor::ArmorClass,
         resource::ResourceAmountMap,
     },
@@ -20,6 +20,9 @@ use crate::{
 pub type ApplyEffectHook = Arc<dyn Fn(&mut World, Entity, Option<&ActionContext>) + Send + Sync>;
 pub type UnapplyEffectHook = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
 pub type AttackRollHook = Arc<dyn Fn(&World, Entity, &mut AttackRoll) + Send + Sync>;
+// Entities in order: 1. target (the entity with the effect), 2. attacker
+pub type IncomingAttackRollHook =
+    Arc<dyn Fn(&World, Entity, Entity, &mut AttackRoll) + Send + Sync>;
 pub type AttackRollResultHook = Arc<dyn Fn(&World, Entity, &mut AttackRollResult) + Send + Sync>;
 pub type ArmorClassHook = Arc<dyn Fn(&World, Entity, &mut ArmorClass) + Send + Sync>;
 pub type D20CheckHook = Arc<dyn Fn(&World, Entity, &mut D20Check) + Send + Sync>;
@@ -35,6 +38,8 @@ pub type PostDamageMitigationHook =
     Arc<dyn Fn(&World, Entity, &mut DamageMitigationResult) + Send + Sync>;
 // Entitys in order: 1. victim, 2. killer (if any), 3. effect applier (if any)
 pub type DeathHook = Arc<dyn Fn(&mut World, Entity, Option<Entity>, Option<Entity>) + Send + Sync>;
+/// Whether the thrown item flies back to the hand of the thrower
+pub type ThrowHook = Arc<dyn Fn(&World, Entity, &ItemId) -> bool + Send + Sync>;
 
 #[derive(Clone)]
 pub struct D20CheckHooks {
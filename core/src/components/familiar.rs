@@ -0,0 +1,27 @@
+use hecs::Entity;
+
+/// Present on a creature serving as the familiar of another creature, e.g. from
+/// Find Familiar
+#[derive(Debug, Clone)]
+pub struct Familiar {
+    pub owner: Entity,
+    /// Dismissed familiars are kept in a pocket dimension, i.e. they don't have
+    /// a position in the world until they're recalled
+    pub dismissed: bool,
+}
+
+impl Familiar {
+    pub fn new(owner: Entity) -> Self {
+        Self {
+            owner,
+            dismissed: false,
+        }
+    }
+}
+
+/// Present on a creature serving as the steed of another creature, e.g. from
+/// Find Steed. Unlike familiars, steeds fight alongside their owner.
+#[derive(Debug, Clone)]
+pub struct Steed {
+    pub owner: Entity,
+}
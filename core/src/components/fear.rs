@@ -0,0 +1,13 @@
+use std::collections::HashSet;
+
+use hecs::Entity;
+
+/// The creatures a frightened creature is afraid of which were out of its line
+/// of sight when it last acted, e.g. because they were behind a wall. Line of
+/// sight needs the world geometry, which the d20 check hooks don't have, so
+/// it's worked out ahead of time by
+/// [`update_fear_sight`](crate::systems::conditions::update_fear_sight).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FearSight {
+    pub out_of_sight: HashSet<Entity>,
+}
@@ -1,2 +1,5 @@
+pub mod healing;
 pub mod hit_points;
 pub mod life_state;
+pub mod massive_damage;
+pub mod resurrection;
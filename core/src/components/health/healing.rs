@@ -0,0 +1,251 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::modifier::ModifierSource;
+
+/// --- HEALING MODIFIERS ---
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum HealingOperation {
+    Block,          // set to 0, e.g. Chill Touch
+    FlatBonus(i32), // add N
+    Double,         // multiply by 2
+    Half,           // divide by 2
+}
+
+impl HealingOperation {
+    fn apply(&self, value: u32) -> u32 {
+        match self {
+            HealingOperation::Block => 0,
+            HealingOperation::FlatBonus(amount) => (value as i32 + amount).max(0) as u32,
+            HealingOperation::Double => value * 2,
+            HealingOperation::Half => value / 2,
+        }
+    }
+
+    fn priority(&self) -> u8 {
+        match self {
+            HealingOperation::Block => 0,
+            HealingOperation::FlatBonus(_) => 1,
+            HealingOperation::Double => 2,
+            HealingOperation::Half => 3,
+        }
+    }
+}
+
+impl fmt::Display for HealingOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HealingOperation::Block => write!(f, "* 0"),
+            HealingOperation::FlatBonus(amount) => write!(f, "+ {}", amount),
+            HealingOperation::Double => write!(f, "* 2"),
+            HealingOperation::Half => write!(f, "/ 2"),
+        }
+    }
+}
+
+impl FromStr for HealingOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(HealingOperation::Block),
+            "double" => Ok(HealingOperation::Double),
+            "half" => Ok(HealingOperation::Half),
+            _ if s.starts_with("flat_bonus") => {
+                let start = s
+                    .find('(')
+                    .ok_or_else(|| format!("Invalid FlatBonus format, missing '(': {}", s))?;
+                let end = s
+                    .find(')')
+                    .ok_or_else(|| format!("Invalid FlatBonus format, missing ')': {}", s))?;
+                let amount_str = &s[start + 1..end];
+                let amount: i32 = amount_str
+                    .parse()
+                    .map_err(|_| format!("Invalid FlatBonus amount '{}'", amount_str))?;
+                Ok(HealingOperation::FlatBonus(amount))
+            }
+            _ => Err(format!("Unknown HealingOperation: {}", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for HealingOperation {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<HealingOperation> for String {
+    fn from(op: HealingOperation) -> Self {
+        match op {
+            HealingOperation::Block => "block".to_string(),
+            HealingOperation::FlatBonus(amount) => format!("flat_bonus({})", amount),
+            HealingOperation::Double => "double".to_string(),
+            HealingOperation::Half => "half".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HealingModifierEffect {
+    pub source: ModifierSource,
+    pub operation: HealingOperation,
+}
+
+/// Modifiers to the healing an entity receives. This is the healing equivalent
+/// of `DamageResistances`.
+#[derive(Debug, Clone, Default)]
+pub struct HealingModifiers {
+    pub effects: Vec<HealingModifierEffect>,
+}
+
+impl HealingModifiers {
+    pub fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn add_effect(&mut self, effect: HealingModifierEffect) {
+        self.effects.push(effect);
+    }
+
+    pub fn remove_effect(&mut self, effect: &HealingModifierEffect) {
+        if let Some(index) = self.effects.iter().position(|e| e == effect) {
+            self.effects.remove(index);
+        }
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|e| e.operation == HealingOperation::Block)
+    }
+
+    pub fn apply(&self, amount: u32) -> HealingResult {
+        let mut sorted_effects = self.effects.clone();
+        sorted_effects.sort_by_key(|e| e.operation.priority());
+
+        let mut value = amount;
+        let mut applied_mods = Vec::new();
+        for effect in sorted_effects {
+            value = effect.operation.apply(value);
+            applied_mods.push(effect);
+            if value == 0 {
+                break;
+            }
+        }
+
+        HealingResult {
+            original: amount,
+            amount: value,
+            modifiers: applied_mods,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealingResult {
+    pub original: u32,
+    pub amount: u32,
+    /// Sorted by priority
+    pub modifiers: Vec<HealingModifierEffect>,
+}
+
+impl HealingResult {
+    pub fn unmodified(amount: u32) -> Self {
+        Self {
+            original: amount,
+            amount,
+            modifiers: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for HealingResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.is_empty() {
+            return write!(f, "{}", self.amount);
+        }
+        write!(f, "{} ({}", self.amount, self.original)?;
+        for modifier in &self.modifiers {
+            write!(f, " {}", modifier.operation)?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::components::id::EffectId;
+
+    use super::*;
+
+    fn modifier(operation: HealingOperation) -> HealingModifierEffect {
+        HealingModifierEffect {
+            source: ModifierSource::Effect(EffectId::new("nat20_core", "effect.test")),
+            operation,
+        }
+    }
+
+    #[test]
+    fn no_modifiers() {
+        let modifiers = HealingModifiers::new();
+        let result = modifiers.apply(10);
+        assert_eq!(result, HealingResult::unmodified(10));
+    }
+
+    #[test]
+    fn block_takes_priority() {
+        let mut modifiers = HealingModifiers::new();
+        modifiers.add_effect(modifier(HealingOperation::Double));
+        modifiers.add_effect(modifier(HealingOperation::Block));
+        assert!(modifiers.is_blocked());
+
+        let result = modifiers.apply(10);
+        assert_eq!(result.amount, 0);
+        assert_eq!(result.modifiers.len(), 1);
+    }
+
+    #[test]
+    fn flat_bonus_then_half() {
+        let mut modifiers = HealingModifiers::new();
+        modifiers.add_effect(modifier(HealingOperation::Half));
+        modifiers.add_effect(modifier(HealingOperation::FlatBonus(3)));
+
+        // (7 + 3) / 2 = 5
+        let result = modifiers.apply(7);
+        assert_eq!(result.amount, 5);
+        assert_eq!(result.original, 7);
+    }
+
+    #[test]
+    fn remove_effect() {
+        let mut modifiers = HealingModifiers::new();
+        modifiers.add_effect(modifier(HealingOperation::Block));
+        modifiers.remove_effect(&modifier(HealingOperation::Block));
+        assert!(modifiers.is_empty());
+    }
+
+    #[test]
+    fn operation_roundtrip() {
+        for operation in [
+            HealingOperation::Block,
+            HealingOperation::FlatBonus(-2),
+            HealingOperation::Double,
+            HealingOperation::Half,
+        ] {
+            let string: String = operation.clone().into();
+            assert_eq!(HealingOperation::from_str(&string).unwrap(), operation);
+        }
+    }
+}
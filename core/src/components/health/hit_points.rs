@@ -37,6 +37,10 @@ pub struct HitPoints {
     current: u32,
     max: u32,
     temp: Option<TemporaryHitPoints>,
+    /// Reduction to the hit point maximum, e.g. from a Wight's Life Drain. This
+    /// is tracked separately from `max` so it can be restored by a long rest or
+    /// Greater Restoration without touching the underlying maximum.
+    max_reduction: u32,
 }
 
 impl HitPoints {
@@ -45,6 +49,7 @@ impl HitPoints {
             current: max,
             max,
             temp: None,
+            max_reduction: 0,
         }
     }
 
@@ -53,6 +58,7 @@ impl HitPoints {
             current,
             max,
             temp: None,
+            max_reduction: 0,
         }
     }
 
@@ -61,6 +67,7 @@ impl HitPoints {
             current,
             max,
             temp: Some(temp),
+            max_reduction: 0,
         }
     }
 
@@ -68,19 +75,40 @@ impl HitPoints {
         self.current
     }
 
+    /// The effective hit point maximum, i.e. after any reductions
     pub fn max(&self) -> u32 {
+        self.max.saturating_sub(self.max_reduction)
+    }
+
+    /// The hit point maximum before any reductions
+    pub fn base_max(&self) -> u32 {
         self.max
     }
 
+    pub fn max_reduction(&self) -> u32 {
+        self.max_reduction
+    }
+
     pub fn temp(&self) -> Option<&TemporaryHitPoints> {
         self.temp.as_ref()
     }
 
     pub fn update_max(&mut self, new_max: u32) {
-        if new_max < self.current {
-            self.current = new_max;
-        }
         self.max = new_max;
+        self.current = self.current.min(self.max());
+    }
+
+    pub(crate) fn reduce_max(&mut self, amount: u32) {
+        self.max_reduction = (self.max_reduction + amount).min(self.max);
+        self.current = self.current.min(self.max());
+    }
+
+    pub(crate) fn restore_max(&mut self) {
+        self.max_reduction = 0;
+    }
+
+    pub(crate) fn restore_max_by(&mut self, amount: u32) {
+        self.max_reduction = self.max_reduction.saturating_sub(amount);
     }
 
     /// Applies damage to the hit points. If the entity has temporary hit points,
@@ -112,15 +140,15 @@ impl HitPoints {
     }
 
     pub(crate) fn heal(&mut self, amount: u32) {
-        self.current = (self.current + amount).min(self.max);
+        self.current = (self.current + amount).min(self.max());
     }
 
     pub(crate) fn heal_full(&mut self) {
-        self.current = self.max;
+        self.current = self.max();
     }
 
     pub fn is_full(&self) -> bool {
-        self.current == self.max
+        self.current == self.max()
     }
 
     pub fn is_alive(&self) -> bool {
@@ -226,6 +254,47 @@ mod tests {
         assert_eq!(hp.current(), 7);
     }
 
+    #[test]
+    fn reduce_max_clamps_current() {
+        let mut hp = HitPoints::new(20);
+        hp.reduce_max(5);
+        assert_eq!(hp.max(), 15);
+        assert_eq!(hp.base_max(), 20);
+        assert_eq!(hp.current(), 15);
+
+        hp.heal(10);
+        assert_eq!(hp.current(), 15);
+        assert!(hp.is_full());
+    }
+
+    #[test]
+    fn reduce_max_does_not_go_below_zero() {
+        let mut hp = HitPoints::new(10);
+        hp.reduce_max(25);
+        assert_eq!(hp.max(), 0);
+        assert_eq!(hp.max_reduction(), 10);
+        assert_eq!(hp.current(), 0);
+    }
+
+    #[test]
+    fn restore_max_removes_reduction() {
+        let mut hp = HitPoints::new(20);
+        hp.reduce_max(8);
+        hp.restore_max();
+        assert_eq!(hp.max(), 20);
+        // Restoring the maximum does not heal
+        assert_eq!(hp.current(), 12);
+    }
+
+    #[test]
+    fn update_max_keeps_reduction() {
+        let mut hp = HitPoints::new(20);
+        hp.reduce_max(5);
+        hp.update_max(30);
+        assert_eq!(hp.max(), 25);
+        assert_eq!(hp.current(), 15);
+    }
+
     #[test]
     fn damage_exact_to_zero_with_temp() {
         let mut hp = HitPoints::with_temp(4, 4, temp_hp(2));
@@ -17,8 +17,19 @@ impl LifeState {
     pub fn unconscious() -> Self {
         Self::Unconscious(DeathSavingThrows::new())
     }
+
+    /// Stable creatures are still unconscious, they just don't make death saves
+    pub fn is_unconscious(&self) -> bool {
+        matches!(self, LifeState::Unconscious(_) | LifeState::Stable)
+    }
 }
 
+/// Declared by a creature whose melee attacks should knock its targets out
+/// rather than kill them. Creatures reduced to 0 hit points by such an attack
+/// fall unconscious and are stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonLethalIntent;
+
 pub static DEATH_SAVING_THROW_DC: u8 = 10;
 pub static DEATH_SAVING_THROW_SUCCESS_THRESHOLD: u8 = 3;
 pub static DEATH_SAVING_THROW_FAILURE_THRESHOLD: u8 = 3;
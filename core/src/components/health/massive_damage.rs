@@ -0,0 +1,65 @@
+use strum::Display;
+
+/// DC of the Constitution saving throw against a system shock
+pub const SYSTEM_SHOCK_SAVING_THROW_DC: i32 = 15;
+
+/// Results of the System Shock table, rolled with a d10 by a creature that
+/// fails its saving throw against massive damage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum SystemShock {
+    /// The creature drops to 0 hit points
+    #[strum(to_string = "drops to 0 hit points")]
+    DropToZero,
+    /// The creature drops to 0 hit points, but is stable
+    #[strum(to_string = "drops to 0 hit points, but is stable")]
+    DropToZeroStable,
+    /// The creature is stunned until the end of its next turn
+    #[strum(to_string = "is stunned")]
+    Stunned,
+    /// The creature can't take reactions and has Disadvantage on attack rolls
+    /// and ability checks until the end of its next turn
+    #[strum(to_string = "is reeling")]
+    Reeling,
+    /// The creature can't take reactions until the end of its next turn
+    #[strum(to_string = "can't take reactions")]
+    Dazed,
+}
+
+impl SystemShock {
+    pub fn from_roll(roll: u32) -> Self {
+        match roll {
+            0..=1 => SystemShock::DropToZero,
+            2..=3 => SystemShock::DropToZeroStable,
+            4..=5 => SystemShock::Stunned,
+            6..=7 => SystemShock::Reeling,
+            _ => SystemShock::Dazed,
+        }
+    }
+}
+
+/// What happened to a creature that took massive damage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MassiveDamageOutcome {
+    /// The damage left over after dropping the creature to 0 hit points was at
+    /// least its hit point maximum
+    InstantDeath,
+    /// The creature succeeded on its saving throw against the system shock
+    SystemShockResisted,
+    /// The creature failed its saving throw and rolled on the System Shock
+    /// table
+    SystemShock { roll: u32, result: SystemShock },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_shock_table() {
+        assert_eq!(SystemShock::from_roll(1), SystemShock::DropToZero);
+        assert_eq!(SystemShock::from_roll(3), SystemShock::DropToZeroStable);
+        assert_eq!(SystemShock::from_roll(4), SystemShock::Stunned);
+        assert_eq!(SystemShock::from_roll(7), SystemShock::Reeling);
+        assert_eq!(SystemShock::from_roll(10), SystemShock::Dazed);
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::time::TimeDuration;
+
+/// How many hit points a creature has when it returns to life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevivedHitPoints {
+    One,
+    Full,
+}
+
+/// Brings a dead creature back to life, e.g. Revivify or Raise Dead
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Resurrection {
+    /// How long the creature can have been dead for
+    pub time_limit: TimeDuration,
+    pub hit_points: RevivedHitPoints,
+    /// Whether coming back from death is an ordeal, leaving the creature with a
+    /// penalty to D20 Tests which wears off over a few long rests
+    #[serde(default)]
+    pub penalty: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResurrectionError {
+    NotDead,
+    /// The creature has been dead for too long
+    TimeLimitExceeded {
+        elapsed: TimeDuration,
+        limit: TimeDuration,
+    },
+    /// Death is permanent, e.g. because of the permadeath rule
+    Permanent,
+}
+
+/// How long a creature has been dead for. Added when it dies and removed when
+/// it returns to life.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeathTimer {
+    elapsed: TimeDuration,
+}
+
+impl DeathTimer {
+    pub fn new() -> Self {
+        Self {
+            elapsed: TimeDuration::from_seconds(0.0),
+        }
+    }
+
+    pub fn elapsed(&self) -> TimeDuration {
+        self.elapsed
+    }
+
+    pub fn advance(&mut self, duration: &TimeDuration) {
+        self.elapsed =
+            TimeDuration::from_seconds(self.elapsed.as_seconds() + duration.as_seconds());
+    }
+}
+
+/// Marks the remains of a creature whose death is permanent. Corpses are
+/// objects, so they can still be targeted, but they can't be brought back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Corpse;
+
+/// Effects for the penalty to D20 Tests after returning from death, ordered
+/// from the smallest penalty to the largest. Each long rest replaces the effect
+/// with the next smaller one.
+pub const RETURN_FROM_DEATH_EFFECTS: [&str; 4] = [
+    "effect.return_from_death.penalty_1",
+    "effect.return_from_death.penalty_2",
+    "effect.return_from_death.penalty_3",
+    "effect.return_from_death.penalty_4",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn death_timer_advance() {
+        let mut timer = DeathTimer::new();
+        timer.advance(&TimeDuration::from_rounds(10));
+        assert_eq!(timer.elapsed(), TimeDuration::from_minutes(1));
+    }
+}
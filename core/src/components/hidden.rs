@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::components::{damage::DamageType, dice::DiceSet, skill::Skill};
+
+/// The DC to spot a typical secret door
+pub const SECRET_DOOR_DC_DEFAULT: i32 = 15;
+/// The DC to spot a typical trap, and to avoid some of its damage once it goes
+/// off
+pub const TRAP_DC_DEFAULT: i32 = 13;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HiddenFeatureKind {
+    SecretDoor,
+    Lever,
+    /// Something which tells the party about the place, e.g. scratch marks on
+    /// the floor or a hidden note
+    Clue,
+    /// A pressure plate or tripwire which goes off when someone walks over it,
+    /// see [`Trap`]
+    Trap,
+}
+
+/// Something on the map which the party doesn't know about until they notice it,
+/// either with their passive Perception when they get close or by searching for
+/// it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HiddenFeature {
+    pub kind: HiddenFeatureKind,
+    pub dc: i32,
+    /// What the party learns when they find it, e.g. the contents of a clue
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub revealed: bool,
+}
+
+impl HiddenFeature {
+    pub fn new(kind: HiddenFeatureKind, dc: i32, description: impl Into<String>) -> Self {
+        Self {
+            kind,
+            dc,
+            description: description.into(),
+            revealed: false,
+        }
+    }
+
+    /// The skill used to find the feature. Doors and levers are spotted, while
+    /// clues have to be studied to make sense of them.
+    pub fn skill(&self) -> Skill {
+        match self.kind {
+            HiddenFeatureKind::SecretDoor | HiddenFeatureKind::Lever | HiddenFeatureKind::Trap => {
+                Skill::Perception
+            }
+            HiddenFeatureKind::Clue => Skill::Investigation,
+        }
+    }
+}
+
+/// What happens to whoever walks over a hidden trap. The creature makes a
+/// Dexterity saving throw against the DC of the trap, taking half damage on a
+/// success. Traps which have been found can be stepped around, so they only go
+/// off while they're hidden.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trap {
+    pub damage: (DiceSet, DamageType),
+    pub dc: i32,
+}
@@ -12,6 +12,22 @@ pub enum IdError {
     EmptyId,
 }
 
+/// The prefix every id of the type has to start with, e.g. `spell_list` for
+/// `SpellListId`
+fn id_prefix(type_name: &str) -> String {
+    let name = type_name.strip_suffix("Id").unwrap_or(type_name);
+    let mut prefix = String::new();
+    let mut previous_lowercase = false;
+    for c in name.chars() {
+        if c.is_uppercase() && previous_lowercase {
+            prefix.push('_');
+        }
+        previous_lowercase = c.is_lowercase();
+        prefix.push(c.to_ascii_lowercase());
+    }
+    prefix
+}
+
 macro_rules! id_newtypes {
     ($($name:ident),+) => {
         $(
@@ -47,7 +63,7 @@ macro_rules! id_newtypes {
                     if parts.len() != 2 {
                         return Err(IdError::MissingNamespace);
                     }
-                    let prefix = stringify!($name).to_lowercase().replace("id", "");
+                    let prefix = id_prefix(stringify!($name));
                     let id = parts[1];
                     if !id.starts_with(&prefix) {
                         return Err(IdError::InvalidPrefix {
@@ -100,7 +116,11 @@ id_newtypes!(
     SubspeciesId,
     AIControllerId,
     FactionId,
-    ScriptId
+    MonsterId,
+    ScriptId,
+    AdventureId,
+    SpellListId,
+    CustomActionId
 );
 
 impl Into<ActionId> for SpellId {
@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    species::CreatureSize,
+    time::{TimeDuration, TimeStep},
+};
+
+/// Image left behind by an action, e.g. the Minor Image spell, placed at each
+/// point the action targets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IllusionTemplate {
+    /// What the image looks like, e.g. "Crate" or "Goblin"
+    pub name: String,
+    pub size: CreatureSize,
+    /// If not specified the illusion uses the spell save DC of the caster
+    #[serde(default)]
+    pub dc: Option<i32>,
+    #[serde(default)]
+    pub duration: Option<TimeDuration>,
+}
+
+/// An image of an object or creature which looks real until someone sees
+/// through it, either by studying it with an Investigation check against `dc`
+/// or by physically interacting with it. Only the creatures which have seen
+/// through the illusion know it's fake, everyone else still treats it as real.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Illusion {
+    /// The creature that created the illusion, which always knows it's fake
+    pub caster: Option<Entity>,
+    pub dc: i32,
+    pub time_remaining: Option<TimeDuration>,
+    pub disbelievers: HashSet<Entity>,
+}
+
+impl Illusion {
+    pub fn new(caster: Option<Entity>, dc: i32, duration: Option<TimeDuration>) -> Self {
+        Self {
+            caster,
+            dc,
+            time_remaining: duration,
+            disbelievers: HashSet::new(),
+        }
+    }
+
+    /// Whether the observer knows the illusion is fake
+    pub fn is_disbelieved_by(&self, observer: Entity) -> bool {
+        self.caster == Some(observer) || self.disbelievers.contains(&observer)
+    }
+
+    pub fn advance_time(&mut self, time_step: &TimeStep) {
+        if let Some(time_remaining) = &mut self.time_remaining {
+            time_remaining.decrement(time_step);
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time_remaining
+            .is_some_and(|time_remaining| time_remaining.as_seconds() <= 0.0)
+    }
+}
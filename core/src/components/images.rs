@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Images used to show a creature in the GUI. Paths are relative to the
+/// directory the game is started from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CreatureImages {
+    /// Shown in the party panel and the character sheet
+    pub portrait: Option<PathBuf>,
+    /// Shown in the initiative tracker and on the battle map
+    pub token: Option<PathBuf>,
+}
+
+impl CreatureImages {
+    /// Creatures without a token use their portrait instead
+    pub fn token_or_portrait(&self) -> Option<&PathBuf> {
+        self.token.as_ref().or(self.portrait.as_ref())
+    }
+}
@@ -1,4 +1,10 @@
+pub mod coating;
 pub mod equipment;
 pub mod inventory;
 pub mod item;
+pub mod light;
+pub mod loot;
 pub mod money;
+pub mod spell_item;
+pub mod supply;
+pub mod tool;
@@ -0,0 +1,180 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    d20::D20CheckDC,
+    damage::{DamageComponentResult, DamageType},
+    dice::{DiceSet, DiceSetRoll},
+    effects::effect::EffectInstanceTemplate,
+    id::{IdProvider, ItemId},
+    items::item::Item,
+    modifier::{ModifierSet, ModifierSource},
+    saving_throw::{SavingThrowDC, SavingThrowKind},
+    time::{TimeDuration, TimeStep},
+};
+
+fn default_hits() -> u8 {
+    1
+}
+
+/// Saving throw the target of a coated weapon can make to shrug off the coating
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoatingSave {
+    pub saving_throw: SavingThrowKind,
+    pub dc: i32,
+    /// Whether a successful save halves the damage instead of negating it
+    #[serde(default)]
+    pub half_damage: bool,
+}
+
+/// Rider applied to hits made with a weapon coated in e.g. a poison or an oil
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coating {
+    #[serde(default)]
+    pub damage: Vec<(DiceSet, DamageType)>,
+    #[serde(default)]
+    pub effects: Vec<EffectInstanceTemplate>,
+    #[serde(default)]
+    pub save: Option<CoatingSave>,
+    /// Number of hits before the coating wears off
+    #[serde(default = "default_hits")]
+    pub hits: u8,
+    /// How long the coating lasts before it dries up, if it does at all
+    #[serde(default)]
+    pub duration: Option<TimeDuration>,
+}
+
+impl Coating {
+    pub fn saving_throw_dc(&self, source: &ItemId) -> Option<SavingThrowDC> {
+        self.save.as_ref().map(|save| D20CheckDC {
+            key: save.saving_throw,
+            dc: ModifierSet::from(ModifierSource::Item(source.clone()), save.dc),
+        })
+    }
+
+    /// Roll the damage of the coating. Unlike the on-hit riders of magic weapons
+    /// the damage isn't part of the attack, so it isn't doubled on a critical
+    /// hit.
+    pub fn roll_damage(&self) -> Vec<DamageComponentResult> {
+        self.damage
+            .iter()
+            .map(|(dice, damage_type)| DamageComponentResult {
+                damage_type: *damage_type,
+                result: DiceSetRoll::new(*dice, ModifierSet::new()).roll(),
+                tags: BTreeSet::new(),
+            })
+            .collect()
+    }
+}
+
+/// A consumable which can be applied to a weapon, e.g. a vial of Basic Poison
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoatingItem {
+    pub item: Item,
+    pub coating: Coating,
+}
+
+impl IdProvider for CoatingItem {
+    type Id = ItemId;
+
+    fn id(&self) -> &Self::Id {
+        &self.item.id
+    }
+}
+
+/// A coating that has been applied to a weapon, keeping track of how long it
+/// has left
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedCoating {
+    /// The item the coating came from
+    pub source: ItemId,
+    pub name: String,
+    pub coating: Coating,
+    pub hits_remaining: u8,
+    pub time_remaining: Option<TimeDuration>,
+}
+
+impl AppliedCoating {
+    pub fn new(item: &CoatingItem) -> Self {
+        Self {
+            source: item.item.id.clone(),
+            name: item.item.name.clone(),
+            coating: item.coating.clone(),
+            hits_remaining: item.coating.hits,
+            time_remaining: item.coating.duration,
+        }
+    }
+
+    pub fn spend_hit(&mut self) {
+        self.hits_remaining = self.hits_remaining.saturating_sub(1);
+    }
+
+    pub fn advance_time(&mut self, time_step: &TimeStep) {
+        if let Some(time_remaining) = &mut self.time_remaining {
+            time_remaining.decrement(time_step);
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.hits_remaining == 0
+            || self
+                .time_remaining
+                .is_some_and(|time_remaining| time_remaining.as_seconds() <= 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn basic_poison() -> CoatingItem {
+        CoatingItem {
+            item: Item {
+                id: ItemId::new("nat20_core", "item.basic_poison"),
+                name: "Basic Poison".to_string(),
+                ..Item::default()
+            },
+            coating: Coating {
+                damage: vec![(DiceSet::from_str("1d4").unwrap(), DamageType::Poison)],
+                effects: Vec::new(),
+                save: None,
+                hits: 2,
+                duration: Some(TimeDuration::from_rounds(10)),
+            },
+        }
+    }
+
+    #[test]
+    fn coating_expires_after_hits() {
+        let mut coating = AppliedCoating::new(&basic_poison());
+        coating.spend_hit();
+        assert!(!coating.is_expired());
+        coating.spend_hit();
+        assert!(coating.is_expired());
+    }
+
+    #[test]
+    fn coating_dries_up() {
+        let mut coating = AppliedCoating::new(&basic_poison());
+        coating.advance_time(&TimeStep::RealTime {
+            delta_seconds: 30.0,
+        });
+        assert!(!coating.is_expired());
+        coating.advance_time(&TimeStep::RealTime {
+            delta_seconds: 30.0,
+        });
+        assert!(coating.is_expired());
+    }
+
+    #[test]
+    fn coating_damage_is_not_magical() {
+        let components = basic_poison().coating.roll_damage();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].damage_type, DamageType::Poison);
+        assert!((1..=4).contains(&components[0].result.subtotal));
+        assert!(components[0].tags.is_empty());
+    }
+}
@@ -52,7 +52,7 @@ pub struct ArmorClass {
 }
 
 impl ArmorClass {
-    fn new(
+    pub(crate) fn new(
         base_value: i32,
         base_source: ModifierSource,
         dexterity_bonus: ArmorDexterityBonus,
@@ -1,13 +1,17 @@
-use std::fmt::Debug;
+use std::{collections::BTreeSet, fmt::Debug};
 
 use serde::{Deserialize, Serialize};
 
 use crate::components::{
+    damage::{DamageComponentResult, DamageTag, DamageType},
+    dice::{DiceSet, DiceSetRoll},
+    effects::effect::EffectInstanceTemplate,
     id::EffectId,
     items::{
         equipment::slots::{EquipmentSlot, SlotProvider},
         item::Item,
     },
+    modifier::ModifierSet,
 };
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -16,6 +20,7 @@ pub enum EquipmentKind {
     Headwear,
     Cloak,
     Gloves,
+    Belt,
     Boots,
     Amulet,
     Ring,
@@ -34,6 +39,7 @@ impl SlotProvider for EquipmentItem {
             EquipmentKind::Headwear => &[EquipmentSlot::Headwear],
             EquipmentKind::Cloak => &[EquipmentSlot::Cloak],
             EquipmentKind::Gloves => &[EquipmentSlot::Gloves],
+            EquipmentKind::Belt => &[EquipmentSlot::Belt],
             EquipmentKind::Boots => &[EquipmentSlot::Boots],
             EquipmentKind::Amulet => &[EquipmentSlot::Amulet],
             EquipmentKind::Ring => &[EquipmentSlot::Ring1, EquipmentSlot::Ring2],
@@ -41,6 +47,44 @@ impl SlotProvider for EquipmentItem {
     }
 }
 
+/// Riders that are applied when an attack made with a piece of equipment hits,
+/// e.g. the extra fire damage of a Flame Tongue. The damage is rolled as extra
+/// components on the attack's damage roll, so it goes through mitigation like
+/// the rest of the damage. Since riders come from magic items, their damage is
+/// always magical.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct OnHit {
+    #[serde(default)]
+    pub damage: Vec<(DiceSet, DamageType)>,
+    #[serde(default)]
+    pub effects: Vec<EffectInstanceTemplate>,
+}
+
+impl OnHit {
+    pub fn is_empty(&self) -> bool {
+        self.damage.is_empty() && self.effects.is_empty()
+    }
+
+    /// Roll the extra damage components. On a critical hit the dice are doubled,
+    /// same as for the rest of the damage roll.
+    pub fn roll_damage(&self, crit: bool) -> Vec<DamageComponentResult> {
+        self.damage
+            .iter()
+            .map(|(dice, damage_type)| {
+                let mut dice = *dice;
+                if crit {
+                    dice.num_dice *= 2;
+                }
+                DamageComponentResult {
+                    damage_type: *damage_type,
+                    result: DiceSetRoll::new(dice, ModifierSet::new()).roll(),
+                    tags: BTreeSet::from([DamageTag::Magical]),
+                }
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -76,4 +120,28 @@ mod tests {
         };
         assert_ne!(boots.valid_slots(), &[EquipmentSlot::Headwear]);
     }
+
+    #[test]
+    fn on_hit_roll_damage() {
+        let on_hit = OnHit {
+            damage: vec![(DiceSet::from_str("2d6").unwrap(), DamageType::Fire)],
+            effects: vec![],
+        };
+        assert!(!on_hit.is_empty());
+
+        let components = on_hit.roll_damage(false);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].damage_type, DamageType::Fire);
+        assert!((2..=12).contains(&components[0].result.subtotal));
+        assert!(components[0].tags.contains(&DamageTag::Magical));
+
+        let components = on_hit.roll_damage(true);
+        assert!((4..=24).contains(&components[0].result.subtotal));
+    }
+
+    #[test]
+    fn on_hit_default_is_empty() {
+        assert!(OnHit::default().is_empty());
+        assert!(OnHit::default().roll_damage(true).is_empty());
+    }
 }
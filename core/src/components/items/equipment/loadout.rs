@@ -6,14 +6,17 @@ use serde::Deserialize;
 use crate::{
     components::{
         ability::AbilityScoreMap,
-        actions::action::{ActionContext, ActionMap, ActionProvider},
+        actions::{
+            action::{ActionContext, ActionMap, ActionProvider},
+            targeting::TargetingRange,
+        },
         d20::AdvantageType,
         damage::{AttackRoll, AttackRollResult, DamageRoll},
         id::{EffectId, ItemId},
         items::{
             equipment::{
                 armor::{Armor, ArmorClass, ArmorDexterityBonus},
-                equipment::EquipmentItem,
+                equipment::{EquipmentItem, OnHit},
                 slots::{EquipmentSlot, SlotProvider},
                 weapon::{Weapon, WeaponKind, WeaponProficiencyMap, WeaponProperties},
             },
@@ -35,6 +38,8 @@ pub enum TryEquipError {
     SlotOccupied,
     NotProficient,
     WrongWeaponType,
+    /// The item currently in the slot is cursed and can't be removed
+    BoundByCurse(EquipmentInstance),
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -53,6 +58,15 @@ impl EquipmentInstance {
             EquipmentInstance::Equipment(equipment) => &equipment.effects,
         }
     }
+
+    /// Riders applied when an attack made with this piece of equipment hits.
+    /// Only weapons can carry on-hit riders for now.
+    pub fn on_hit(&self) -> Option<&OnHit> {
+        match self {
+            EquipmentInstance::Weapon(weapon) => Some(weapon.on_hit()),
+            _ => None,
+        }
+    }
 }
 
 impl SlotProvider for EquipmentInstance {
@@ -80,6 +94,14 @@ impl ItemContainer for EquipmentInstance {
             EquipmentInstance::Equipment(equipment) => &equipment.item,
         }
     }
+
+    fn item_mut(&mut self) -> &mut Item {
+        match self {
+            EquipmentInstance::Armor(armor) => &mut armor.item,
+            EquipmentInstance::Weapon(weapon) => weapon.item_mut(),
+            EquipmentInstance::Equipment(equipment) => &mut equipment.item,
+        }
+    }
 }
 
 macro_rules! impl_into_equipment_instance {
@@ -128,6 +150,10 @@ impl Loadout {
         self.equipment.get(slot)
     }
 
+    pub fn item_in_slot_mut(&mut self, slot: &EquipmentSlot) -> Option<&mut EquipmentInstance> {
+        self.equipment.get_mut(slot)
+    }
+
     pub fn unequip(&mut self, slot: &EquipmentSlot) -> Option<EquipmentInstance> {
         self.equipment.remove(slot)
     }
@@ -253,7 +279,7 @@ impl Loadout {
         if let Some(armor) = &self.armor() {
             let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
             let mut armor_class = armor.armor_class(&ability_scores);
-            for effect in systems::effects::effects(world, entity).iter() {
+            for effect in systems::effects::active_effects(world, entity).iter() {
                 (effect.effect().on_armor_class)(world, entity, &mut armor_class);
             }
             armor_class
@@ -292,10 +318,38 @@ impl Loadout {
         }
     }
 
+    pub fn weapon_in_hand_mut(&mut self, slot: &EquipmentSlot) -> Option<&mut Weapon> {
+        if !slot.is_weapon_slot() {
+            return None;
+        }
+        if let Some(EquipmentInstance::Weapon(weapon)) = self.equipment.get_mut(slot) {
+            Some(weapon)
+        } else {
+            None
+        }
+    }
+
+    /// All the equipped weapons along with the slot they're equipped in
+    pub fn weapons_mut(&mut self) -> impl Iterator<Item = (&EquipmentSlot, &mut Weapon)> {
+        self.equipment
+            .iter_mut()
+            .filter_map(|(slot, equipment)| match equipment {
+                EquipmentInstance::Weapon(weapon) => Some((slot, weapon)),
+                _ => None,
+            })
+    }
+
     pub fn has_weapon_in_hand(&self, slot: &EquipmentSlot) -> bool {
         self.weapon_in_hand(slot).is_some()
     }
 
+    /// Whether one of the hands isn't holding anything. Two-handed weapons only
+    /// take up the main hand slot, since one hand can let go of them for free.
+    pub fn has_free_hand(&self) -> bool {
+        self.item_in_slot(&EquipmentSlot::MeleeMainHand).is_none()
+            || self.item_in_slot(&EquipmentSlot::MeleeOffHand).is_none()
+    }
+
     pub fn is_wielding_weapon_with_both_hands(&self, weapon_kind: &WeaponKind) -> bool {
         let (main_hand_slot, off_hand_slot) = match weapon_kind {
             WeaponKind::Melee => (EquipmentSlot::MeleeMainHand, EquipmentSlot::MeleeOffHand),
@@ -332,17 +386,7 @@ impl Loadout {
             &systems::helpers::get_component::<WeaponProficiencyMap>(world, entity)
                 .proficiency(&weapon.category()),
         );
-        let range = weapon.range();
-        if range.normal() != range.max() {
-            let distance =
-                systems::geometry::distance_between_entities(world, entity, target).unwrap();
-            if distance > range.normal() {
-                attack_roll.d20_check.advantage_tracker_mut().add(
-                    AdvantageType::Disadvantage,
-                    ModifierSource::Custom("Target is outside normal range".to_string()),
-                );
-            }
-        }
+        add_range_disadvantage(world, entity, target, weapon.range(), &mut attack_roll);
         attack_roll
     }
 
@@ -355,6 +399,66 @@ impl Loadout {
             self.is_wielding_weapon_with_both_hands(weapon.kind()),
         )
     }
+
+    pub fn thrown_attack_roll(
+        &self,
+        world: &World,
+        entity: Entity,
+        target: Entity,
+        slot: &EquipmentSlot,
+    ) -> AttackRoll {
+        let weapon = self
+            .weapon_in_hand(slot)
+            .expect("No weapon equipped in the specified slot");
+        let mut attack_roll = weapon.thrown_attack_roll(
+            &systems::helpers::get_component::<AbilityScoreMap>(world, entity),
+            &systems::helpers::get_component::<WeaponProficiencyMap>(world, entity)
+                .proficiency(&weapon.category()),
+        );
+        add_range_disadvantage(
+            world,
+            entity,
+            target,
+            weapon.thrown_range(),
+            &mut attack_roll,
+        );
+        attack_roll
+    }
+
+    pub fn thrown_damage_roll(
+        &self,
+        world: &World,
+        entity: Entity,
+        slot: &EquipmentSlot,
+    ) -> DamageRoll {
+        let weapon = self
+            .weapon_in_hand(slot)
+            .expect("No weapon equipped in the specified slot");
+        weapon.thrown_damage_roll(&systems::helpers::get_component::<AbilityScoreMap>(
+            world, entity,
+        ))
+    }
+}
+
+/// Attacks against targets beyond the normal range of the weapon are made with
+/// disadvantage
+fn add_range_disadvantage(
+    world: &World,
+    entity: Entity,
+    target: Entity,
+    range: &TargetingRange,
+    attack_roll: &mut AttackRoll,
+) {
+    if range.normal() == range.max() {
+        return;
+    }
+    let distance = systems::geometry::distance_between_entities(world, entity, target).unwrap();
+    if distance > range.normal() {
+        attack_roll.d20_check.advantage_tracker_mut().add(
+            AdvantageType::Disadvantage,
+            ModifierSource::Custom("Target is outside normal range".to_string()),
+        );
+    }
 }
 
 impl ActionProvider for Loadout {
@@ -617,12 +721,16 @@ mod tests {
         }
 
         // Both melee and ranged attacks use the same ActionId, but their
-        // contexts are different
-        assert_eq!(actions.len(), 1);
+        // contexts are different. Only the dagger can be thrown.
+        assert_eq!(actions.len(), 2);
         assert_eq!(
             actions[&ActionId::new("nat20_core", "action.weapon_attack")].len(),
             2
         );
+        assert_eq!(
+            actions[&ActionId::new("nat20_core", "action.throw")].len(),
+            1
+        );
         for (_, data) in actions {
             for (context, ..) in data {
                 match context {
@@ -5,6 +5,7 @@ pub enum EquipmentSlot {
     Headwear,
     Cloak,
     Gloves,
+    Belt,
     Boots,
     Amulet,
     Ring1,
@@ -14,12 +14,16 @@ use crate::{
         ability::{Ability, AbilityScoreMap},
         actions::targeting::TargetingRange,
         d20::D20Check,
-        damage::{AttackRoll, DamageRoll, DamageSource, DamageType},
+        damage::{AttackRoll, DamageRoll, DamageSource, DamageTag, DamageType},
         dice::DiceSet,
         id::{ActionId, EffectId},
         items::{
-            equipment::slots::{EquipmentSlot, SlotProvider},
-            item::Item,
+            coating::AppliedCoating,
+            equipment::{
+                equipment::OnHit,
+                slots::{EquipmentSlot, SlotProvider},
+            },
+            item::{Item, ItemRarity},
         },
         modifier::{KeyedModifiable, Modifiable, ModifierSet, ModifierSource},
         proficiency::{Proficiency, ProficiencyLevel},
@@ -57,6 +61,10 @@ pub enum WeaponProperties {
     /// Damage if wielded with two hands
     Versatile(DiceSet),
     Enchantment(u32),
+    /// Damage dealt with the weapon counts as magical, even without an enchantment
+    Magical,
+    Silvered,
+    Adamantine,
 }
 
 impl Display for WeaponProperties {
@@ -85,6 +93,9 @@ impl FromStr for WeaponProperties {
                 "light" => Ok(WeaponProperties::Light),
                 "reach" => Ok(WeaponProperties::Reach),
                 "twohanded" | "two-handed" => Ok(WeaponProperties::TwoHanded),
+                "magical" => Ok(WeaponProperties::Magical),
+                "silvered" => Ok(WeaponProperties::Silvered),
+                "adamantine" => Ok(WeaponProperties::Adamantine),
                 _ => Err(format!("Invalid weapon property: {}", s)),
             }
         } else if parts.len() > 1 {
@@ -190,6 +201,12 @@ pub static MELEE_RANGE_DEFAULT: LazyLock<TargetingRange> =
     LazyLock::new(|| TargetingRange::new::<foot>(5.0));
 pub static MELEE_RANGE_REACH: LazyLock<TargetingRange> =
     LazyLock::new(|| TargetingRange::new::<foot>(10.0));
+/// Melee weapons without the thrown property can still be thrown, but they're
+/// treated as improvised weapons
+pub static IMPROVISED_THROW_RANGE: LazyLock<TargetingRange> =
+    LazyLock::new(|| TargetingRange::with_max::<foot>(20.0, 60.0));
+pub static IMPROVISED_THROW_DAMAGE: LazyLock<DiceSet> =
+    LazyLock::new(|| DiceSet::from_str("1d4").unwrap());
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(from = "WeaponDefinition")]
@@ -202,6 +219,8 @@ pub struct Weapon {
     ability: Ability,
     weapon_actions: Vec<ActionId>,
     effects: Vec<EffectId>,
+    on_hit: OnHit,
+    coating: Option<AppliedCoating>,
 }
 
 impl Weapon {
@@ -228,6 +247,10 @@ impl Weapon {
         };
 
         let mut weapon_actions = vec![ActionId::new("nat20_core", "action.weapon_attack")];
+        // Anything you can swing, you can also throw
+        if matches!(kind, WeaponKind::Melee) {
+            weapon_actions.push(ActionId::new("nat20_core", "action.throw"));
+        }
 
         if damage.is_empty() {
             panic!("Weapon must have at least one damage type");
@@ -240,6 +263,18 @@ impl Weapon {
             damage_roll.add_bonus(dice, damage_type);
         }
 
+        for property in &properties {
+            match property {
+                WeaponProperties::Enchantment(level) if *level > 0 => {
+                    damage_roll.add_tag(DamageTag::Magical)
+                }
+                WeaponProperties::Magical => damage_roll.add_tag(DamageTag::Magical),
+                WeaponProperties::Silvered => damage_roll.add_tag(DamageTag::Silvered),
+                WeaponProperties::Adamantine => damage_roll.add_tag(DamageTag::Adamantine),
+                _ => {}
+            }
+        }
+
         weapon_actions.extend(extra_weapon_actions);
 
         Self {
@@ -251,13 +286,57 @@ impl Weapon {
             ability,
             weapon_actions,
             effects,
+            on_hit: OnHit::default(),
+            coating: None,
         }
     }
 
+    pub fn with_on_hit(mut self, on_hit: OnHit) -> Self {
+        self.on_hit = on_hit;
+        self
+    }
+
+    /// Turn the weapon into a +N version of itself, e.g. a "Longsword +1"
+    pub fn with_enchantment(mut self, level: u32) -> Self {
+        let previous = self.enchantment();
+        self.properties
+            .retain(|property| !matches!(property, WeaponProperties::Enchantment(_)));
+        if level > 0 {
+            self.properties.insert(WeaponProperties::Enchantment(level));
+            self.damage_roll.add_tag(DamageTag::Magical);
+        }
+
+        let suffix = format!(" +{}", previous);
+        if previous > 0 && self.item.name.ends_with(&suffix) {
+            self.item.name.truncate(self.item.name.len() - suffix.len());
+        }
+        if level > 0 {
+            self.item.name = format!("{} +{}", self.item.name, level);
+        }
+        self
+    }
+
+    /// Add a minor property which deals extra damage on a hit, e.g. turning a
+    /// "Longsword +1" into a "Longsword +1 of Flames"
+    pub fn with_rider(mut self, name: &str, dice: DiceSet, damage_type: DamageType) -> Self {
+        self.on_hit.damage.push((dice, damage_type));
+        self.item.name = format!("{} of {}", self.item.name, name);
+        self
+    }
+
+    pub fn with_rarity(mut self, rarity: ItemRarity) -> Self {
+        self.item.rarity = rarity;
+        self
+    }
+
     pub fn item(&self) -> &Item {
         &self.item
     }
 
+    pub fn item_mut(&mut self) -> &mut Item {
+        &mut self.item
+    }
+
     pub fn category(&self) -> &WeaponCategory {
         &self.category
     }
@@ -292,6 +371,47 @@ impl Weapon {
         AttackRoll::new(attack_roll, DamageSource::from(self))
     }
 
+    /// The attack roll for throwing the weapon. Thrown attacks are ranged
+    /// attacks, and you aren't proficient with weapons thrown as improvised
+    /// weapons.
+    pub fn thrown_attack_roll(
+        &self,
+        ability_scores: &AbilityScoreMap,
+        weapon_proficiency: &Proficiency,
+    ) -> AttackRoll {
+        let mut attack_roll = if self.is_improvised_throw() {
+            self.attack_roll(
+                ability_scores,
+                &Proficiency::new(ProficiencyLevel::None, ModifierSource::None),
+            )
+        } else {
+            self.attack_roll(ability_scores, weapon_proficiency)
+        };
+        attack_roll.source = DamageSource::Weapon(WeaponKind::Ranged);
+        attack_roll
+    }
+
+    /// The damage roll for throwing the weapon. Weapons without the thrown
+    /// property deal 1d4 bludgeoning damage.
+    pub fn thrown_damage_roll(&self, ability_scores: &AbilityScoreMap) -> DamageRoll {
+        let source = DamageSource::Weapon(WeaponKind::Ranged);
+        if !self.is_improvised_throw() {
+            let mut damage_roll = self.damage_roll(ability_scores, false);
+            damage_roll.source = source;
+            return damage_roll;
+        }
+
+        let mut damage_roll = DamageRoll::new(
+            IMPROVISED_THROW_DAMAGE.clone(),
+            DamageType::Bludgeoning,
+            source,
+        );
+        // A magic sword is still magic when it's thrown at someone
+        damage_roll.primary.tags = self.damage_roll.primary.tags.clone();
+        self.add_ability_modifier(ability_scores, &mut damage_roll.primary.dice_roll.modifiers);
+        damage_roll
+    }
+
     pub fn damage_roll(
         &self,
         ability_scores: &AbilityScoreMap,
@@ -380,6 +500,27 @@ impl Weapon {
         return &MELEE_RANGE_DEFAULT;
     }
 
+    /// The range of the weapon when it's thrown. Weapons without the thrown
+    /// property use the range of an improvised weapon.
+    pub fn thrown_range(&self) -> &TargetingRange {
+        self.properties
+            .iter()
+            .find_map(|property| match property {
+                WeaponProperties::Thrown(range) => Some(range),
+                _ => None,
+            })
+            .unwrap_or(&IMPROVISED_THROW_RANGE)
+    }
+
+    /// Whether the weapon isn't meant to be thrown, in which case it's treated
+    /// as an improvised weapon when it is
+    pub fn is_improvised_throw(&self) -> bool {
+        !self
+            .properties
+            .iter()
+            .any(|property| matches!(property, WeaponProperties::Thrown(_)))
+    }
+
     pub fn effects(&self) -> &Vec<EffectId> {
         &self.effects
     }
@@ -387,6 +528,28 @@ impl Weapon {
     pub fn weapon_actions(&self) -> &Vec<ActionId> {
         &self.weapon_actions
     }
+
+    pub fn on_hit(&self) -> &OnHit {
+        &self.on_hit
+    }
+
+    /// The poison or oil the weapon is currently coated in, if any
+    pub fn coating(&self) -> Option<&AppliedCoating> {
+        self.coating.as_ref()
+    }
+
+    pub fn coating_mut(&mut self) -> Option<&mut AppliedCoating> {
+        self.coating.as_mut()
+    }
+
+    /// Coat the weapon, replacing any previous coating
+    pub fn coat(&mut self, coating: AppliedCoating) {
+        self.coating = Some(coating);
+    }
+
+    pub fn remove_coating(&mut self) -> Option<AppliedCoating> {
+        self.coating.take()
+    }
 }
 
 impl SlotProvider for Weapon {
@@ -414,15 +577,18 @@ impl SlotProvider for Weapon {
 #[cfg(test)]
 mod tests {
 
-    use std::str::FromStr;
+    use std::{collections::BTreeSet, str::FromStr};
 
     use uom::si::{f32::Mass, mass::pound};
 
-    use crate::components::{
-        ability::AbilityScore,
-        dice::DieSize,
-        id::ItemId,
-        items::{item::ItemRarity, money::MonetaryValue},
+    use crate::{
+        components::{
+            ability::AbilityScore,
+            dice::DieSize,
+            id::ItemId,
+            items::{item::ItemRarity, money::MonetaryValue},
+        },
+        entities::object::ObjectMaterial,
     };
 
     use super::*;
@@ -436,6 +602,8 @@ mod tests {
             weight: Mass::new::<pound>(5.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Steel,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item.clone(),
@@ -459,6 +627,45 @@ mod tests {
         println!("{:?}", weapon);
     }
 
+    #[test]
+    fn weapon_with_enchantment() {
+        let item = Item {
+            id: ItemId::new("nat20_core", "item.longsword"),
+            name: "Longsword".to_string(),
+            description: "A longsword".to_string(),
+            weight: Mass::new::<pound>(3.0),
+            value: MonetaryValue::from_str("15 GP").unwrap(),
+            rarity: ItemRarity::Common,
+            material: ObjectMaterial::Steel,
+            damage_taken: 0,
+        };
+        let weapon = Weapon::new(
+            item,
+            WeaponKind::Melee,
+            WeaponCategory::Martial,
+            HashSet::new(),
+            vec![("1d8".parse().unwrap(), DamageType::Slashing)],
+            vec![],
+            vec![],
+        )
+        .with_enchantment(1);
+
+        assert!(weapon.has_property(&WeaponProperties::Enchantment(1)));
+        assert!(
+            weapon
+                .damage_roll
+                .primary
+                .tags
+                .contains(&DamageTag::Magical)
+        );
+        assert_eq!(weapon.item().name, "Longsword +1");
+
+        let weapon = weapon.with_enchantment(2);
+        assert!(!weapon.has_property(&WeaponProperties::Enchantment(1)));
+        assert!(weapon.has_property(&WeaponProperties::Enchantment(2)));
+        assert_eq!(weapon.item().name, "Longsword +2");
+    }
+
     #[test]
     #[should_panic(expected = "Ranged weapons must have a range property")]
     fn ranged_weapon_without_range_panics() {
@@ -469,6 +676,8 @@ mod tests {
             weight: Mass::new::<pound>(2.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Wood,
+            damage_taken: 0,
         };
         Weapon::new(
             item,
@@ -490,6 +699,8 @@ mod tests {
             weight: Mass::new::<pound>(1.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Steel,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -514,6 +725,8 @@ mod tests {
             weight: Mass::new::<pound>(3.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Uncommon,
+            material: ObjectMaterial::Steel,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -525,6 +738,35 @@ mod tests {
             vec![],
         );
         assert_eq!(weapon.enchantment(), 2);
+        assert!(
+            weapon
+                .damage_roll
+                .primary
+                .tags
+                .contains(&DamageTag::Magical)
+        );
+    }
+
+    #[test]
+    fn weapon_material_properties() {
+        let weapon = Weapon::new(
+            Item::default(),
+            WeaponKind::Melee,
+            WeaponCategory::Martial,
+            HashSet::from([
+                WeaponProperties::from_str("silvered").unwrap(),
+                WeaponProperties::from_str("adamantine").unwrap(),
+            ]),
+            vec![
+                ("1d8".parse().unwrap(), DamageType::Slashing),
+                ("1d4".parse().unwrap(), DamageType::Fire),
+            ],
+            vec![],
+            vec![],
+        );
+        let tags = BTreeSet::from([DamageTag::Silvered, DamageTag::Adamantine]);
+        assert_eq!(weapon.damage_roll.primary.tags, tags);
+        assert_eq!(weapon.damage_roll.bonus[0].tags, tags);
     }
 
     #[test]
@@ -536,6 +778,8 @@ mod tests {
             weight: Mass::new::<pound>(2.5),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Steel,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -567,6 +811,8 @@ mod tests {
             weight: Mass::new::<pound>(8.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Rare,
+            material: ObjectMaterial::Steel,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -590,6 +836,8 @@ mod tests {
             weight: Mass::new::<pound>(2.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Wood,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -614,6 +862,8 @@ mod tests {
             weight: Mass::new::<pound>(2.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Wood,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -636,6 +886,8 @@ mod tests {
             weight: Mass::new::<pound>(3.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Uncommon,
+            material: ObjectMaterial::Cloth,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -658,6 +910,8 @@ mod tests {
             weight: Mass::new::<pound>(2.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Rare,
+            material: ObjectMaterial::Wood,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -685,6 +939,8 @@ mod tests {
             weight: Mass::new::<pound>(2.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Steel,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -709,6 +965,8 @@ mod tests {
             weight: Mass::new::<pound>(3.0),
             value: MonetaryValue::from_str("1 GP").unwrap(),
             rarity: ItemRarity::Uncommon,
+            material: ObjectMaterial::Wood,
+            damage_taken: 0,
         };
         let weapon = Weapon::new(
             item,
@@ -1,14 +1,30 @@
+use std::collections::HashMap;
+
+use hecs::{Entity, World};
 use serde::Deserialize;
 
-use crate::components::{
-    id::{IdProvider, ItemId},
-    items::{
-        equipment::{
-            armor::Armor, equipment::EquipmentItem, loadout::EquipmentInstance, weapon::Weapon,
+use crate::{
+    components::{
+        actions::action::{ActionContext, ActionMap, ActionProvider},
+        id::{ActionId, IdProvider, ItemId},
+        items::{
+            coating::CoatingItem,
+            equipment::{
+                armor::Armor, equipment::EquipmentItem, loadout::EquipmentInstance, weapon::Weapon,
+            },
+            item::Item,
+            light::LightItem,
+            money::{MonetaryValue, MonetaryValueError},
+            spell_item::{Charges, SpellItem},
+            supply::{SupplyItem, SupplyKind},
+            tool::ToolItem,
         },
-        item::Item,
-        money::{MonetaryValue, MonetaryValueError},
+        resource::RechargeRule,
+        spells::spellbook::{GrantedSpellSource, SpellSource},
+        time::TimeStep,
     },
+    registry::registry::{ActionsRegistry, SpellsRegistry},
+    systems,
 };
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -18,6 +34,11 @@ pub enum ItemInstance {
     Armor(Armor),
     Weapon(Weapon),
     Equipment(EquipmentItem),
+    Spell(SpellItem),
+    Coating(CoatingItem),
+    Tool(ToolItem),
+    Supply(SupplyItem),
+    Light(LightItem),
 }
 
 impl ItemInstance {
@@ -27,6 +48,24 @@ impl ItemInstance {
             ItemInstance::Armor(_) | ItemInstance::Weapon(_) | ItemInstance::Equipment(_)
         )
     }
+
+    pub fn charges(&self) -> Option<&Charges> {
+        match self {
+            ItemInstance::Spell(spell_item) => Some(&spell_item.charges),
+            ItemInstance::Tool(tool) => Some(&tool.charges),
+            ItemInstance::Supply(supply) => Some(&supply.charges),
+            _ => None,
+        }
+    }
+
+    fn charges_mut(&mut self) -> Option<&mut Charges> {
+        match self {
+            ItemInstance::Spell(spell_item) => Some(&mut spell_item.charges),
+            ItemInstance::Tool(tool) => Some(&mut tool.charges),
+            ItemInstance::Supply(supply) => Some(&mut supply.charges),
+            _ => None,
+        }
+    }
 }
 
 impl IdProvider for ItemInstance {
@@ -38,12 +77,19 @@ impl IdProvider for ItemInstance {
             ItemInstance::Armor(armor) => &armor.item.id,
             ItemInstance::Weapon(weapon) => &weapon.item().id,
             ItemInstance::Equipment(equipment) => &equipment.item.id,
+            ItemInstance::Spell(spell_item) => &spell_item.item.id,
+            ItemInstance::Coating(coating) => &coating.item.id,
+            ItemInstance::Tool(tool) => &tool.item.id,
+            ItemInstance::Supply(supply) => &supply.item.id,
+            ItemInstance::Light(light) => &light.item.id,
         }
     }
 }
 
 pub trait ItemContainer {
     fn item(&self) -> &Item;
+
+    fn item_mut(&mut self) -> &mut Item;
 }
 
 impl ItemContainer for ItemInstance {
@@ -53,6 +99,25 @@ impl ItemContainer for ItemInstance {
             ItemInstance::Armor(armor) => &armor.item,
             ItemInstance::Weapon(weapon) => weapon.item(),
             ItemInstance::Equipment(equipment) => &equipment.item,
+            ItemInstance::Spell(spell_item) => &spell_item.item,
+            ItemInstance::Coating(coating) => &coating.item,
+            ItemInstance::Tool(tool) => &tool.item,
+            ItemInstance::Supply(supply) => &supply.item,
+            ItemInstance::Light(light) => &light.item,
+        }
+    }
+
+    fn item_mut(&mut self) -> &mut Item {
+        match self {
+            ItemInstance::Item(item) => item,
+            ItemInstance::Armor(armor) => &mut armor.item,
+            ItemInstance::Weapon(weapon) => weapon.item_mut(),
+            ItemInstance::Equipment(equipment) => &mut equipment.item,
+            ItemInstance::Spell(spell_item) => &mut spell_item.item,
+            ItemInstance::Coating(coating) => &mut coating.item,
+            ItemInstance::Tool(tool) => &mut tool.item,
+            ItemInstance::Supply(supply) => &mut supply.item,
+            ItemInstance::Light(light) => &mut light.item,
         }
     }
 }
@@ -74,6 +139,11 @@ impl_into_item_instance! {
     Armor => Armor,
     Weapon => Weapon,
     EquipmentItem => Equipment,
+    SpellItem => Spell,
+    CoatingItem => Coating,
+    ToolItem => Tool,
+    SupplyItem => Supply,
+    LightItem => Light,
 }
 
 impl From<ItemInstance> for EquipmentInstance {
@@ -127,6 +197,10 @@ impl Inventory {
         &self.items
     }
 
+    pub fn item_mut(&mut self, index: usize) -> Option<&mut ItemInstance> {
+        self.items.get_mut(index)
+    }
+
     /// Optional: find by name
     pub fn find_by_name(&self, name: &str) -> Option<&ItemInstance> {
         self.items.iter().find(|i| i.item().name == name)
@@ -148,4 +222,202 @@ impl Inventory {
         }
         Ok(())
     }
+
+    /// Spend a charge of the first spell item or tool with the given id that
+    /// has any left. Consumable items are removed once they're spent. Returns
+    /// `false` if there was no such item.
+    pub fn spend_charge(&mut self, item_id: &ItemId) -> bool {
+        let Some(index) = self.items.iter().position(|item| {
+            item.id() == item_id && item.charges().is_some_and(|charges| !charges.is_empty())
+        }) else {
+            return false;
+        };
+
+        let charges = self.items[index].charges_mut().unwrap();
+        charges.spend();
+        if charges.consumable && charges.is_empty() {
+            self.items.remove(index);
+        }
+        true
+    }
+
+    /// The first tool with charges left which lets its holder perform the
+    /// action, if any
+    pub fn tool_for_action(&self, action_id: &ActionId) -> Option<&ToolItem> {
+        self.items.iter().find_map(|item| match item {
+            ItemInstance::Tool(tool)
+                if !tool.charges.is_empty() && tool.actions.contains(action_id) =>
+            {
+                Some(tool)
+            }
+            _ => None,
+        })
+    }
+
+    fn supplies(&self, kind: &SupplyKind) -> impl Iterator<Item = &SupplyItem> {
+        self.items.iter().filter_map(move |item| match item {
+            ItemInstance::Supply(supply) if supply.provides(kind) => Some(supply),
+            _ => None,
+        })
+    }
+
+    /// The first supply with charges left which provides the kind of supply,
+    /// if any
+    pub fn supply_for(&self, kind: &SupplyKind) -> Option<&SupplyItem> {
+        self.supplies(kind)
+            .find(|supply| !supply.charges.is_empty())
+    }
+
+    /// How many days' worth of the kind of supply are carried
+    pub fn supply_days(&self, kind: &SupplyKind) -> u32 {
+        self.supplies(kind)
+            .map(|supply| supply.charges.current as u32)
+            .sum()
+    }
+
+    /// Fill up the containers for the kind of supply, e.g. waterskins. Supplies
+    /// which are used up, e.g. rations, have to be bought again instead.
+    pub fn refill_supplies(&mut self, kind: &SupplyKind) {
+        for item in &mut self.items {
+            if let ItemInstance::Supply(supply) = item
+                && supply.provides(kind)
+                && !supply.charges.consumable
+            {
+                supply.charges.current = supply.charges.max;
+            }
+        }
+    }
+
+    /// The lights which are currently lit
+    pub fn lit_lights(&self) -> impl Iterator<Item = &LightItem> {
+        self.items.iter().filter_map(|item| match item {
+            ItemInstance::Light(light) if light.lit => Some(light),
+            _ => None,
+        })
+    }
+
+    pub fn light_mut(&mut self, index: usize) -> Option<&mut LightItem> {
+        match self.items.get_mut(index) {
+            Some(ItemInstance::Light(light)) => Some(light),
+            _ => None,
+        }
+    }
+
+    pub fn take_all_items(&mut self) -> Vec<ItemInstance> {
+        std::mem::take(&mut self.items)
+    }
+
+    /// Remove the first item with the given id, e.g. to use it up as fuel
+    pub fn take_item(&mut self, item_id: &ItemId) -> Option<ItemInstance> {
+        let index = self.items.iter().position(|item| item.id() == item_id)?;
+        self.remove_item(index)
+    }
+
+    /// Let the lit lights burn for a while. Lights which burn out are
+    /// refuelled from the inventory so they keep burning, or go out if there's
+    /// no fuel left. Lights without any fuel, e.g. torches, are used up.
+    pub fn burn_lights(&mut self, time_step: &TimeStep) {
+        let mut fuel_left: HashMap<ItemId, usize> = HashMap::new();
+        for item in &self.items {
+            *fuel_left.entry(item.id().clone()).or_default() += 1;
+        }
+
+        let mut fuel_used = Vec::new();
+        for item in &mut self.items {
+            let ItemInstance::Light(light) = item else {
+                continue;
+            };
+            if !light.lit {
+                continue;
+            }
+            light.advance_time(time_step);
+            if !light.is_burned_out() {
+                continue;
+            }
+            if let Some(fuel) = &light.fuel
+                && let Some(left) = fuel_left.get_mut(fuel)
+                && *left > 0
+            {
+                *left -= 1;
+                fuel_used.push(fuel.clone());
+                light.refuel();
+                light.lit = true;
+            }
+        }
+
+        for fuel in fuel_used {
+            self.take_item(&fuel);
+        }
+        self.items.retain(|item| {
+            !matches!(item, ItemInstance::Light(light) if light.fuel.is_none() && light.is_burned_out())
+        });
+    }
+
+    pub fn recharge_items(&mut self, rest_type: &RechargeRule) {
+        for item in &mut self.items {
+            if let Some(charges) = item.charges_mut() {
+                charges.recharge(rest_type);
+            }
+        }
+    }
+}
+
+impl ActionProvider for Inventory {
+    fn actions(&self, world: &World, entity: Entity) -> ActionMap {
+        let mut actions = ActionMap::new();
+
+        for item in &self.items {
+            if let ItemInstance::Tool(tool) = item
+                && !tool.charges.is_empty()
+            {
+                for action_id in &tool.actions {
+                    let Some(action) = ActionsRegistry::get(action_id) else {
+                        continue;
+                    };
+                    let entry = actions.entry(action_id.clone()).or_default();
+                    if !entry
+                        .iter()
+                        .any(|(existing, _)| *existing == ActionContext::Other)
+                    {
+                        entry.push((ActionContext::Other, action.resource_cost.clone()));
+                    }
+                }
+                continue;
+            }
+
+            let ItemInstance::Spell(spell_item) = item else {
+                continue;
+            };
+            if spell_item.charges.is_empty() {
+                continue;
+            }
+            let Some(spell) = SpellsRegistry::get(&spell_item.spell) else {
+                continue;
+            };
+            // Spell scrolls can only be read if the spell is on one of the
+            // reader's spell lists
+            if spell_item.charges.consumable
+                && systems::spells::classes_with_spell(world, entity, &spell_item.spell).is_empty()
+            {
+                continue;
+            }
+
+            let context = ActionContext::Spell {
+                id: spell_item.spell.clone(),
+                source: SpellSource::Granted {
+                    source: GrantedSpellSource::Item(spell_item.item.id.clone()),
+                    level: spell_item.level,
+                },
+                level: spell_item.level,
+                metamagic: Vec::new(),
+            };
+            let entry = actions.entry(spell.action().id().clone()).or_default();
+            // Several copies of the same item only need one context
+            if !entry.iter().any(|(existing, _)| *existing == context) {
+                entry.push((context, spell.action().resource_cost().clone()));
+            }
+        }
+
+        actions
+    }
 }
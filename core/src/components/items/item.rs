@@ -1,12 +1,18 @@
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
-use strum::Display;
-use uom::si::{f32::Mass, mass::kilogram};
+use strum::{Display, EnumIter};
+use uom::si::{
+    f32::Mass,
+    mass::{kilogram, pound},
+};
 
-use crate::components::{id::ItemId, items::money::MonetaryValue};
+use crate::{
+    components::{id::ItemId, items::money::MonetaryValue},
+    entities::object::ObjectMaterial,
+};
 
-#[derive(Debug, Clone, PartialEq, Display, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Display, EnumIter, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ItemRarity {
     Common,
@@ -24,6 +30,41 @@ pub struct Item {
     pub weight: Mass,
     pub value: MonetaryValue,
     pub rarity: ItemRarity,
+    /// What the item is mostly made of, e.g. steel for a longsword
+    pub material: ObjectMaterial,
+    /// Damage the item has taken, e.g. from being sundered. The item breaks
+    /// once it reaches its hit points.
+    #[serde(default)]
+    pub damage_taken: u32,
+}
+
+impl Item {
+    /// Items are treated as objects, so their hit points depend on their
+    /// size, which is estimated from their weight, and on whether their
+    /// material is fragile or resilient
+    pub fn hit_points(&self) -> u32 {
+        let pounds = self.weight.get::<pound>();
+        let (fragile, resilient) = if pounds < 5.0 {
+            (2, 5)
+        } else if pounds < 30.0 {
+            (3, 10)
+        } else {
+            (4, 18)
+        };
+        if self.material.is_fragile() {
+            fragile
+        } else {
+            resilient
+        }
+    }
+
+    pub fn armor_class(&self) -> i32 {
+        self.material.armor_class()
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.damage_taken >= self.hit_points()
+    }
 }
 
 impl Default for Item {
@@ -35,6 +76,8 @@ impl Default for Item {
             weight: Mass::new::<kilogram>(0.0),
             value: MonetaryValue::from_str("0 GP").unwrap(),
             rarity: ItemRarity::Common,
+            material: ObjectMaterial::Wood,
+            damage_taken: 0,
         }
     }
 }
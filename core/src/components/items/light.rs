@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use uom::si::{f32::Length, length::meter};
+
+use crate::components::{
+    id::{IdProvider, ItemId},
+    items::item::Item,
+    time::{TimeDuration, TimeStep},
+};
+
+/// Something which sheds light while it's lit, e.g. a torch or a lantern. Lights
+/// only burn for so long before they either need more fuel or are used up
+/// entirely.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightItem {
+    pub item: Item,
+    /// Radius of bright light. Dim light extends for the same distance beyond it.
+    pub bright_light: Length,
+    /// How long the light burns before it goes out
+    pub burn_time: TimeDuration,
+    /// Item which is used up to light it again once it has burned out, e.g. a
+    /// flask of oil for a lantern. Lights without any fuel burn themselves up,
+    /// like a torch.
+    #[serde(default)]
+    pub fuel: Option<ItemId>,
+    #[serde(default)]
+    pub lit: bool,
+    /// How long the light has left to burn. Unused lights have the full burn
+    /// time left.
+    #[serde(default)]
+    pub time_remaining: Option<TimeDuration>,
+}
+
+impl LightItem {
+    pub fn time_remaining(&self) -> TimeDuration {
+        self.time_remaining.unwrap_or(self.burn_time)
+    }
+
+    pub fn is_burned_out(&self) -> bool {
+        self.time_remaining().as_seconds() <= 0.0
+    }
+
+    /// Fill the light up with fuel, letting it burn for the full burn time again
+    pub fn refuel(&mut self) {
+        self.time_remaining = Some(self.burn_time);
+    }
+
+    /// Radius of dim light, measured from the light itself
+    pub fn dim_light(&self) -> Length {
+        Length::new::<meter>(self.bright_light.get::<meter>() * 2.0)
+    }
+
+    pub fn advance_time(&mut self, time_step: &TimeStep) {
+        if !self.lit {
+            return;
+        }
+        let mut time_remaining = self.time_remaining();
+        time_remaining.decrement(time_step);
+        self.time_remaining = Some(time_remaining);
+        if self.is_burned_out() {
+            self.lit = false;
+        }
+    }
+}
+
+impl IdProvider for LightItem {
+    type Id = ItemId;
+
+    fn id(&self) -> &Self::Id {
+        &self.item.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::length::foot;
+
+    use super::*;
+
+    fn torch() -> LightItem {
+        LightItem {
+            item: Item {
+                id: ItemId::new("nat20_core", "item.torch"),
+                name: "Torch".to_string(),
+                ..Item::default()
+            },
+            bright_light: Length::new::<foot>(20.0),
+            burn_time: TimeDuration::from_hours(1),
+            fuel: None,
+            lit: true,
+            time_remaining: None,
+        }
+    }
+
+    #[test]
+    fn light_burns_out() {
+        let mut torch = torch();
+        torch.advance_time(&TimeStep::RealTime {
+            delta_seconds: TimeDuration::from_minutes(30).as_seconds(),
+        });
+        assert!(torch.lit);
+        assert_eq!(torch.time_remaining(), TimeDuration::from_minutes(30));
+
+        torch.advance_time(&TimeStep::RealTime {
+            delta_seconds: TimeDuration::from_minutes(45).as_seconds(),
+        });
+        assert!(!torch.lit);
+        assert!(torch.is_burned_out());
+    }
+
+    #[test]
+    fn unlit_light_does_not_burn() {
+        let mut torch = torch();
+        torch.lit = false;
+        torch.advance_time(&TimeStep::RealTime {
+            delta_seconds: TimeDuration::from_hours(2).as_seconds(),
+        });
+        assert_eq!(torch.time_remaining(), TimeDuration::from_hours(1));
+    }
+
+    #[test]
+    fn dim_light_extends_beyond_bright_light() {
+        let torch = torch();
+        assert!((torch.dim_light().get::<foot>() - 40.0).abs() < 1e-3);
+    }
+}
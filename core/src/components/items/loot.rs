@@ -0,0 +1,121 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{id::ItemId, items::item::ItemRarity};
+
+fn default_chance() -> f32 {
+    1.0
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LootItem {
+    Item(ItemId),
+    /// A random magic item of the rarity, rolled on the magic item tables
+    MagicItem {
+        rarity: ItemRarity,
+    },
+}
+
+impl From<ItemId> for LootItem {
+    fn from(item: ItemId) -> Self {
+        LootItem::Item(item)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub item: LootItem,
+    /// Chance for the entry to drop, between 0 and 1
+    #[serde(default = "default_chance")]
+    pub chance: f32,
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+/// Every entry in a loot table is rolled independently, so a table can drop
+/// anything from nothing to every item in it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LootTable {
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    pub fn new(entries: Vec<LootEntry>) -> Self {
+        Self { entries }
+    }
+
+    pub fn roll(&self) -> Vec<LootItem> {
+        let mut rng = rand::rng();
+        let mut items = Vec::new();
+        for entry in &self.entries {
+            if rng.random::<f32>() < entry.chance {
+                for _ in 0..entry.count {
+                    items.push(entry.item.clone());
+                }
+            }
+        }
+        items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(item: &str, chance: f32, count: u32) -> LootEntry {
+        LootEntry {
+            item: ItemId::new("nat20_core", item).into(),
+            chance,
+            count,
+        }
+    }
+
+    #[test]
+    fn loot_table_roll() {
+        let table = LootTable::new(vec![
+            entry("item.dagger", 1.0, 2),
+            entry("item.longsword", 0.0, 1),
+        ]);
+
+        assert_eq!(
+            table.roll(),
+            vec![
+                LootItem::Item(ItemId::new("nat20_core", "item.dagger")),
+                LootItem::Item(ItemId::new("nat20_core", "item.dagger"))
+            ]
+        );
+    }
+
+    #[test]
+    fn loot_table_deserialize_defaults() {
+        let table: LootTable =
+            serde_json::from_str(r#"[{ "item": "nat20_core::item.dagger" }]"#).unwrap();
+        assert_eq!(table, LootTable::new(vec![entry("item.dagger", 1.0, 1)]));
+    }
+
+    #[test]
+    fn loot_table_deserialize_magic_item() {
+        let table: LootTable =
+            serde_json::from_str(r#"[{ "item": { "rarity": "rare" }, "chance": 0.1 }]"#).unwrap();
+        assert_eq!(
+            table,
+            LootTable::new(vec![LootEntry {
+                item: LootItem::MagicItem {
+                    rarity: ItemRarity::Rare
+                },
+                chance: 0.1,
+                count: 1,
+            }])
+        );
+    }
+}
@@ -90,6 +90,13 @@ impl MonetaryValue {
         Err(MonetaryValueError::InsufficientFunds)
     }
 
+    /// Whether there is enough of every currency to pay the cost
+    pub fn covers(&self, cost: &MonetaryValue) -> bool {
+        cost.values
+            .iter()
+            .all(|(currency, amount)| self.values.get(currency).copied().unwrap_or(0) >= *amount)
+    }
+
     pub fn total_in_gold(&self) -> f32 {
         self.values
             .iter()
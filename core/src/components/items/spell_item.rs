@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    id::{IdProvider, ItemId, SpellId},
+    items::item::Item,
+    resource::RechargeRule,
+};
+
+/// How many times an item can be used, e.g. the charges of a wand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Charges {
+    pub current: u8,
+    pub max: u8,
+    /// When the charges are restored, if ever
+    #[serde(default)]
+    pub recharge: Option<RechargeRule>,
+    /// Consumable items, e.g. scrolls, are destroyed when they run out of charges
+    #[serde(default)]
+    pub consumable: bool,
+}
+
+impl Charges {
+    pub fn new(max: u8, recharge: Option<RechargeRule>) -> Self {
+        Self {
+            current: max,
+            max,
+            recharge,
+            consumable: false,
+        }
+    }
+
+    pub fn consumable() -> Self {
+        Self {
+            current: 1,
+            max: 1,
+            recharge: None,
+            consumable: true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current == 0
+    }
+
+    /// Returns `false` if there were no charges left to spend
+    pub fn spend(&mut self) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        self.current -= 1;
+        true
+    }
+
+    pub fn recharge(&mut self, rest_type: &RechargeRule) {
+        if let Some(recharge) = &self.recharge
+            && recharge.is_recharged_by(rest_type)
+        {
+            self.current = self.max;
+        }
+    }
+}
+
+/// An item which lets its holder cast a spell from it, e.g. a Spell Scroll or a
+/// Wand of Magic Missiles. Each cast spends a charge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpellItem {
+    pub item: Item,
+    pub spell: SpellId,
+    /// The level the spell is cast at
+    pub level: u8,
+    pub charges: Charges,
+}
+
+impl IdProvider for SpellItem {
+    type Id = ItemId;
+
+    fn id(&self) -> &Self::Id {
+        &self.item.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consumable_charges() {
+        let mut charges = Charges::consumable();
+        assert!(charges.spend());
+        assert!(charges.is_empty());
+        assert!(!charges.spend());
+        charges.recharge(&RechargeRule::Daily);
+        assert!(charges.is_empty());
+    }
+
+    #[test]
+    fn recharge_charges() {
+        let mut charges = Charges::new(7, Some(RechargeRule::Daily));
+        for _ in 0..7 {
+            assert!(charges.spend());
+        }
+        assert!(charges.is_empty());
+
+        charges.recharge(&RechargeRule::Rest(crate::systems::time::RestKind::Short));
+        assert!(charges.is_empty());
+        charges.recharge(&RechargeRule::Daily);
+        assert_eq!(charges.current, 7);
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+use crate::components::{
+    id::{IdProvider, ItemId},
+    items::{item::Item, spell_item::Charges},
+};
+
+/// What a creature needs a day's worth of to keep going
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyKind {
+    Food,
+    Water,
+}
+
+/// Food and drink carried by the party, e.g. rations or a waterskin. Every
+/// charge sustains a single creature for a day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplyItem {
+    pub item: Item,
+    pub provides: Vec<SupplyKind>,
+    pub charges: Charges,
+    /// Hit points restored by a day's worth, e.g. a Goodberry
+    #[serde(default)]
+    pub healing: u32,
+}
+
+impl SupplyItem {
+    pub fn provides(&self, kind: &SupplyKind) -> bool {
+        self.provides.contains(kind)
+    }
+}
+
+impl IdProvider for SupplyItem {
+    type Id = ItemId;
+
+    fn id(&self) -> &Self::Id {
+        &self.item.id
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    id::{ActionId, IdProvider, ItemId},
+    items::{item::Item, spell_item::Charges},
+};
+
+/// Gear which is used up a little with every use, e.g. a Healer's Kit. Each use
+/// spends a charge and lets the holder perform one of the actions of the tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolItem {
+    pub item: Item,
+    pub charges: Charges,
+    pub actions: Vec<ActionId>,
+}
+
+impl IdProvider for ToolItem {
+    type Id = ItemId;
+
+    fn id(&self) -> &Self::Id {
+        &self.item.id
+    }
+}
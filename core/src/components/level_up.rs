@@ -10,20 +10,26 @@ use serde::{Deserialize, Serialize};
 use crate::{
     components::{
         ability::Ability,
-        class::ClassAndSubclass,
+        class::{ClassAndSubclass, SpellAccessModel},
         id::{
             ActionId, BackgroundId, ClassId, EffectId, FeatId, ItemId, SpeciesId, SpellId,
             SubclassId, SubspeciesId,
         },
+        items::{
+            equipment::weapon::WeaponCategory,
+            inventory::{ItemContainer, ItemInstance},
+            item::ItemRarity,
+        },
         modifier::ModifierSource,
         resource::ResourceMap,
         skill::Skill,
         spells::spellbook::{SpellSource, Spellbook},
     },
     registry::registry::{
-        BackgroundsRegistry, ClassesRegistry, FeatsRegistry, SpeciesRegistry, SpellsRegistry,
+        BackgroundsRegistry, ClassesRegistry, FeatsRegistry, ItemsRegistry, SpeciesRegistry,
+        SpellsRegistry,
     },
-    systems::{self},
+    systems::{self, feats::FeatError},
 };
 
 static ABILITY_SCORE_POINT_COST: LazyLock<HashMap<u8, u8>> = LazyLock::new(|| {
@@ -56,6 +62,10 @@ pub enum ChoiceItem {
     Equipment {
         items: Vec<(u8, ItemId)>,
         money: String, // e.g., "10 GP"
+        /// One weapon of each category is picked in a follow-up prompt, e.g.
+        /// "any martial weapon"
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        any_weapon: Vec<WeaponCategory>,
     },
 }
 
@@ -104,11 +114,21 @@ impl std::fmt::Display for ChoiceItem {
             ChoiceItem::Subclass(id) => write!(f, "{}", id),
             ChoiceItem::Species(id) => write!(f, "{}", id),
             ChoiceItem::Subspecies(id) => write!(f, "{}", id),
-            ChoiceItem::Equipment { items, money } => {
+            ChoiceItem::Equipment {
+                items,
+                money,
+                any_weapon,
+            } => {
                 let mut lines: Vec<String> = items
                     .iter()
                     .map(|(count, id)| format!("{} x {}", count, id.to_string()))
                     .collect();
+                for category in any_weapon {
+                    lines.push(format!(
+                        "1 x any {} weapon",
+                        category.to_string().to_lowercase()
+                    ));
+                }
                 if !money.is_empty() {
                     lines.push(money.to_string());
                 }
@@ -125,6 +145,9 @@ pub struct ChoiceSpec {
     pub options: Vec<ChoiceItem>,
     pub picks: u8,
     pub allow_duplicates: bool,
+    /// Options which are shown but can't be picked, along with the reason why
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<(ChoiceItem, String)>,
 }
 
 impl ChoiceSpec {
@@ -141,6 +164,7 @@ impl ChoiceSpec {
             options,
             picks: 1,
             allow_duplicates: false,
+            disabled: Vec::new(),
         }
     }
 
@@ -149,6 +173,49 @@ impl ChoiceSpec {
         self
     }
 
+    /// Disable the options the entity already has, i.e. actions it already
+    /// knows, effects which are already active, and feats it can't take again
+    pub fn disable_known_options(&mut self, world: &World, entity: Entity) {
+        let actions = systems::actions::all_actions(world, entity);
+        let effects = systems::effects::effects(world, entity)
+            .iter()
+            .map(|effect| effect.effect_id.clone())
+            .collect::<HashSet<_>>();
+
+        for option in &self.options {
+            if self.disabled_reason(option).is_some() {
+                continue;
+            }
+            let reason = match option {
+                ChoiceItem::Action(action_id) if actions.contains_key(action_id) => {
+                    Some("Already known")
+                }
+                ChoiceItem::Effect(effect_id) if effects.contains(effect_id) => {
+                    Some("Already active")
+                }
+                ChoiceItem::Feat(feat_id) => {
+                    match systems::feats::can_acquire_feat(world, entity, feat_id) {
+                        Err(FeatError::AlreadyHasUnrepeatableFeat { .. }) => Some("Already taken"),
+                        Err(FeatError::PrequisiteNotMet { .. }) => Some("Prerequisite not met"),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                self.disabled.push((option.clone(), reason.to_string()));
+            }
+        }
+    }
+
+    /// The reason the option can't be picked, if it's disabled
+    pub fn disabled_reason(&self, item: &ChoiceItem) -> Option<&str> {
+        self.disabled
+            .iter()
+            .find(|(disabled, _)| disabled == item)
+            .map(|(_, reason)| reason.as_str())
+    }
+
     pub fn priority(&self) -> u8 {
         self.options
             .iter()
@@ -202,14 +269,54 @@ impl LevelUpPrompt {
         ))
     }
 
-    pub fn class() -> Self {
-        LevelUpPrompt::Choice(ChoiceSpec::single(
+    pub fn class(world: &World, entity: Entity) -> Self {
+        let mut spec = ChoiceSpec::single(
             "Class",
             ClassesRegistry::keys()
                 .cloned()
                 .map(ChoiceItem::Class)
                 .collect(),
-        ))
+        );
+        // Classes whose multiclass prerequisites aren't met are still shown,
+        // so it's clear what's needed to pick them
+        spec.disabled = ClassesRegistry::keys()
+            .filter_map(|class_id| {
+                let error = systems::class::can_multiclass(world, entity, class_id).err()?;
+                Some((ChoiceItem::Class(class_id.clone()), error.to_string()))
+            })
+            .collect();
+        LevelUpPrompt::Choice(spec)
+    }
+
+    /// Follow-up prompt for an "any martial weapon" style pick in a starting
+    /// equipment package. Only mundane weapons are offered.
+    pub fn any_weapon(id: impl Into<String>, category: &WeaponCategory) -> Self {
+        let mut weapons = ItemsRegistry::values()
+            .filter_map(|item| match item {
+                ItemInstance::Weapon(weapon)
+                    if weapon.category() == category
+                        && weapon.item().rarity == ItemRarity::Common =>
+                {
+                    Some(weapon.item())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        weapons.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut spec = ChoiceSpec::single(
+            format!("Any {} Weapon", category),
+            weapons
+                .into_iter()
+                .map(|weapon| ChoiceItem::Equipment {
+                    items: vec![(1, weapon.id.clone())],
+                    money: String::new(),
+                    any_weapon: Vec::new(),
+                })
+                .collect(),
+        );
+        spec.with_id(id);
+        LevelUpPrompt::Choice(spec)
     }
 
     pub fn feats(world: &World, entity: Entity) -> Self {
@@ -263,15 +370,23 @@ impl LevelUpPrompt {
         {
             let spellbook = systems::helpers::get_component::<Spellbook>(world, entity);
             let resources = systems::helpers::get_component::<ResourceMap>(world, entity);
-            let known_spells = spellbook
-                .known_spells_for_class(class_and_subclass, &resources)
-                .unwrap();
+            // Classes with access to their entire spell list know every spell
+            // on it, so they pick among the ones they haven't prepared yet
+            let selected_spells = match spellcasting_rules.access_model {
+                SpellAccessModel::Learned => {
+                    spellbook.known_spells_for_class(class_and_subclass, &resources)
+                }
+                SpellAccessModel::EntireClassList => {
+                    spellbook.castable_spells_for_class(class_and_subclass, &resources)
+                }
+            }
+            .unwrap();
 
-            let options = spellcasting_rules
-                .spell_list
+            let options = spellbook
+                .spell_list(class_and_subclass)
                 .iter()
                 .filter_map(|spell_id| {
-                    if known_spells.contains(spell_id) {
+                    if selected_spells.contains(spell_id) {
                         return None;
                     }
                     let spell = SpellsRegistry::get(spell_id)?;
@@ -285,7 +400,7 @@ impl LevelUpPrompt {
                         None
                     }
                 })
-                .collect();
+                .collect::<Vec<_>>();
             let (id, label) = if cantrips {
                 (
                     "choice.cantrips".to_string(),
@@ -298,9 +413,11 @@ impl LevelUpPrompt {
             LevelUpPrompt::Choice(ChoiceSpec {
                 id,
                 label,
+                // There might not be enough spells on the list yet
+                picks: number_of_spells.min(options.len() as u8),
                 options,
-                picks: number_of_spells,
                 allow_duplicates: false,
+                disabled: Vec::new(),
             })
         } else {
             panic!(
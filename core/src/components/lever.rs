@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// A lever, switch or other mechanism which can be pulled back and forth, e.g.
+/// to raise a portcullis or drain a flooded room
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lever {
+    pub active: bool,
+}
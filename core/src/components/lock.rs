@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::components::id::ItemId;
+
+/// The DC of a typical lock, both to pick it and to force it open
+pub const LOCK_DC_DEFAULT: i32 = 15;
+
+/// How a lock was opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockMethod {
+    Key,
+    Picked,
+    Forced,
+    /// The door or container the lock was on was destroyed
+    Broken,
+}
+
+/// A lock on a door or container. Anyone carrying the key opens it right away,
+/// while everyone else has to pick it with thieves' tools, force it open or
+/// break whatever it's on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lock {
+    /// DC of the Dexterity (Sleight of Hand) check to pick the lock
+    pub dc: i32,
+    /// DC of the Strength (Athletics) check to force the lock open
+    pub force_dc: i32,
+    #[serde(default)]
+    pub key: Option<ItemId>,
+    pub locked: bool,
+}
+
+impl Lock {
+    pub fn new(dc: i32, force_dc: i32, key: Option<ItemId>) -> Self {
+        Self {
+            dc,
+            force_dc,
+            key,
+            locked: true,
+        }
+    }
+}
+
+impl Default for Lock {
+    fn default() -> Self {
+        Self::new(LOCK_DC_DEFAULT, LOCK_DC_DEFAULT, None)
+    }
+}
@@ -1,11 +1,16 @@
 use std::collections::BTreeMap;
 use std::fmt;
 
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::components::id::{
-    ActionId, BackgroundId, ClassId, EffectId, FeatId, ItemId, SpeciesId, SubclassId, SubspeciesId,
+use crate::components::{
+    dice::{DiceSet, RollPolicy},
+    id::{
+        ActionId, BackgroundId, ClassId, EffectId, FeatId, ItemId, SpeciesId, SubclassId,
+        SubspeciesId,
+    },
 };
 
 use super::{ability::Ability, proficiency::ProficiencyLevel};
@@ -57,9 +62,116 @@ impl fmt::Display for ModifierSource {
     }
 }
 
+/// A modifier which is rolled every time it's applied instead of being a flat
+/// value, e.g. +1d4 from Bless or -1d4 from Bane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiceModifier {
+    pub dice: DiceSet,
+    /// Whether the result is subtracted instead of added
+    pub penalty: bool,
+}
+
+impl DiceModifier {
+    pub fn bonus(dice: DiceSet) -> Self {
+        Self {
+            dice,
+            penalty: false,
+        }
+    }
+
+    pub fn penalty(dice: DiceSet) -> Self {
+        Self {
+            dice,
+            penalty: true,
+        }
+    }
+
+    pub fn roll(&self) -> DiceModifierRoll {
+        let mut rng = rand::rng();
+        DiceModifierRoll {
+            modifier: *self,
+            rolls: (0..self.dice.num_dice)
+                .map(|_| rng.random_range(1..=self.dice.die_size as u32))
+                .collect(),
+        }
+    }
+
+    /// Roll the modifier according to the roll policy of the creature it's
+    /// rolled for. The dice are rolled along with a d20, so when the d20 is
+    /// fixed they come up with their average, rounded down like damage dice.
+    pub fn roll_with_policy(&self, policy: &RollPolicy) -> DiceModifierRoll {
+        if policy.fixed_d20.is_none() {
+            return self.roll();
+        }
+        let sides = self.dice.die_size as u32 + 1;
+        DiceModifierRoll {
+            modifier: *self,
+            rolls: (0..self.dice.num_dice)
+                .map(|i| ((i + 1) * sides) / 2 - (i * sides) / 2)
+                .collect(),
+        }
+    }
+
+    /// The chance of each value of the modifier, e.g. 1 through 4 with 25%
+    /// each for +1d4
+    pub fn distribution(&self) -> BTreeMap<i32, f64> {
+        let die_size = self.dice.die_size as i32;
+        let mut distribution = BTreeMap::from([(0, 1.0)]);
+        for _ in 0..self.dice.num_dice {
+            let mut next = BTreeMap::new();
+            for (value, chance) in &distribution {
+                for face in 1..=die_size {
+                    let face = if self.penalty { -face } else { face };
+                    *next.entry(value + face).or_insert(0.0) += chance / die_size as f64;
+                }
+            }
+            distribution = next;
+        }
+        distribution
+    }
+
+    /// The average value of the modifier, e.g. 2.5 for +1d4
+    pub fn average(&self) -> f32 {
+        let average = self.dice.num_dice as f32 * (self.dice.die_size as u32 + 1) as f32 / 2.0;
+        if self.penalty { -average } else { average }
+    }
+}
+
+impl fmt::Display for DiceModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.penalty { "-" } else { "+" };
+        write!(f, "{}{}", sign, self.dice)
+    }
+}
+
+/// The rolled value of a [`DiceModifier`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceModifierRoll {
+    pub modifier: DiceModifier,
+    pub rolls: Vec<u32>,
+}
+
+impl DiceModifierRoll {
+    pub fn value(&self) -> i32 {
+        let sum = self.rolls.iter().sum::<u32>() as i32;
+        if self.modifier.penalty { -sum } else { sum }
+    }
+}
+
+impl fmt::Display for DiceModifierRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.modifier.penalty { "-" } else { "+" };
+        write!(f, "{}{} ({})", sign, self.value().abs(), self.modifier)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModifierSet {
     modifiers: BTreeMap<ModifierSource, i32>,
+    /// Modifiers which are rolled when the modifiers are applied. They're kept
+    /// apart from the flat modifiers, so they don't count towards the total
+    /// until they're rolled.
+    dice: BTreeMap<ModifierSource, DiceModifier>,
 }
 
 pub trait Modifiable {
@@ -74,13 +186,17 @@ impl ModifierSet {
     pub fn new() -> Self {
         Self {
             modifiers: BTreeMap::new(),
+            dice: BTreeMap::new(),
         }
     }
 
     pub fn from(source: ModifierSource, value: i32) -> Self {
         let mut modifiers = BTreeMap::new();
         modifiers.insert(source, value);
-        Self { modifiers }
+        Self {
+            modifiers,
+            dice: BTreeMap::new(),
+        }
     }
 
     pub fn from_iter<I>(iter: I) -> Self
@@ -88,7 +204,10 @@ impl ModifierSet {
         I: IntoIterator<Item = (ModifierSource, i32)>,
     {
         let modifiers = iter.into_iter().collect();
-        Self { modifiers }
+        Self {
+            modifiers,
+            dice: BTreeMap::new(),
+        }
     }
 
     pub fn add_modifier_set(&mut self, other: &ModifierSet) {
@@ -96,6 +215,57 @@ impl ModifierSet {
             let entry = self.modifiers.entry(source.clone()).or_insert(0);
             *entry += value;
         }
+        for (source, modifier) in &other.dice {
+            self.dice.insert(source.clone(), *modifier);
+        }
+    }
+
+    pub fn add_dice_modifier(&mut self, source: ModifierSource, modifier: DiceModifier) {
+        self.dice.insert(source, modifier);
+    }
+
+    pub fn dice_modifiers(&self) -> impl Iterator<Item = (&ModifierSource, &DiceModifier)> {
+        self.dice.iter()
+    }
+
+    /// The average of all the dice modifiers combined, e.g. to estimate the
+    /// chance of success of a check without rolling it
+    pub fn dice_average(&self) -> f32 {
+        self.dice.values().map(DiceModifier::average).sum()
+    }
+
+    /// The chance of each combined value of all the dice modifiers, e.g. to
+    /// work out the chance of success of a check without rolling it
+    pub fn dice_distribution(&self) -> BTreeMap<i32, f64> {
+        self.dice
+            .values()
+            .fold(BTreeMap::from([(0, 1.0)]), |distribution, modifier| {
+                let mut combined = BTreeMap::new();
+                for (value, chance) in &distribution {
+                    for (modifier_value, modifier_chance) in modifier.distribution() {
+                        *combined.entry(value + modifier_value).or_insert(0.0) +=
+                            chance * modifier_chance;
+                    }
+                }
+                combined
+            })
+    }
+
+    /// Remove the dice modifiers and roll them, leaving only the flat modifiers
+    pub fn roll_dice(&mut self) -> BTreeMap<ModifierSource, DiceModifierRoll> {
+        self.roll_dice_with_policy(&RollPolicy::RANDOM)
+    }
+
+    /// Remove the dice modifiers and roll them according to the roll policy,
+    /// see `DiceModifier::roll_with_policy`
+    pub fn roll_dice_with_policy(
+        &mut self,
+        policy: &RollPolicy,
+    ) -> BTreeMap<ModifierSource, DiceModifierRoll> {
+        std::mem::take(&mut self.dice)
+            .into_iter()
+            .map(|(source, modifier)| (source, modifier.roll_with_policy(policy)))
+            .collect()
     }
 
     pub fn get(&self, source: &ModifierSource) -> Option<i32> {
@@ -114,6 +284,9 @@ impl ModifierSet {
     }
 
     pub fn is_empty(&self) -> bool {
+        if !self.dice.is_empty() {
+            return false;
+        }
         if self.modifiers.is_empty() {
             return true;
         }
@@ -144,6 +317,7 @@ impl Modifiable for ModifierSet {
 
     fn remove_modifier(&mut self, source: &ModifierSource) {
         self.modifiers.remove(source);
+        self.dice.remove(source);
     }
 
     fn total(&self) -> i32 {
@@ -173,6 +347,12 @@ impl fmt::Display for ModifierSet {
             let sign = if *value >= 0 { "+" } else { "-" };
             s += &format!("{}{} ({})", sign, value.abs(), source);
         }
+        for (source, modifier) in &self.dice {
+            if !s.is_empty() {
+                s += " ";
+            }
+            s += &format!("{} ({})", modifier, source);
+        }
         write!(f, "{}", s)
     }
 }
@@ -204,4 +384,33 @@ mod tests {
         assert_eq!(modifiers.total(), 4);
         println!("Modifiers breakdown: {}", modifiers);
     }
+
+    #[test]
+    fn dice_modifiers() {
+        let bless = ModifierSource::Effect(EffectId::new("nat20_core", "effect.spell.bless"));
+        let bane = ModifierSource::Effect(EffectId::new("nat20_core", "effect.spell.bane"));
+        let mut modifiers = ModifierSet::from(ModifierSource::Base, 2);
+        modifiers.add_dice_modifier(bless.clone(), DiceModifier::bonus("1d4".parse().unwrap()));
+        modifiers.add_dice_modifier(bane.clone(), DiceModifier::penalty("1d6".parse().unwrap()));
+
+        // Dice don't count towards the total until they're rolled
+        assert_eq!(modifiers.total(), 2);
+        assert_eq!(modifiers.dice_average(), 2.5 - 3.5);
+        let distribution = modifiers.dice_distribution();
+        assert_eq!(distribution.len(), 9);
+        assert!((distribution[&(1 - 6)] - 1.0 / 24.0).abs() < 1e-9);
+        assert!((distribution.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        let breakdown = modifiers.to_string();
+        assert!(breakdown.starts_with("+2 (Base)"));
+        assert!(breakdown.contains("+1d4") && breakdown.contains("-1d6"));
+
+        let rolls = modifiers.roll_dice();
+        assert!((1..=4).contains(&rolls[&bless].value()));
+        assert!((-6..=-1).contains(&rolls[&bane].value()));
+        assert_eq!(modifiers.dice_modifiers().count(), 0);
+
+        modifiers.add_dice_modifier(bless.clone(), DiceModifier::bonus("1d4".parse().unwrap()));
+        modifiers.remove_modifier(&bless);
+        assert_eq!(modifiers.dice_modifiers().count(), 0);
+    }
 }
@@ -0,0 +1,26 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Short descriptive tags used to roleplay NPCs and monsters, e.g. "cowardly"
+/// or "greedy". They have no mechanical effect.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Personality {
+    pub tags: Vec<String>,
+}
+
+impl Personality {
+    pub fn new(tags: Vec<String>) -> Self {
+        Self { tags }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+impl fmt::Display for Personality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tags.join(", "))
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
 use serde::{Deserialize, Serialize};
 
@@ -13,10 +13,15 @@ pub enum ProficiencyLevel {
     Half, // Optional: for features like Bard’s Jack of All Trades
 }
 
+/// Used when nothing grants the proficiency
+static NO_SOURCE: ModifierSource = ModifierSource::None;
+
+/// Proficiency can be granted by several sources at once, e.g. a class and a
+/// feat. The highest level among them is used, and removing one source leaves
+/// the proficiency granted by the others intact.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Proficiency {
-    level: ProficiencyLevel,
-    source: ModifierSource,
+    sources: BTreeMap<ModifierSource, ProficiencyLevel>,
 }
 
 impl ProficiencyLevel {
@@ -42,19 +47,47 @@ impl fmt::Display for ProficiencyLevel {
 
 impl Proficiency {
     pub fn new(level: ProficiencyLevel, source: ModifierSource) -> Self {
-        Self { level, source }
+        Self {
+            sources: BTreeMap::from([(source, level)]),
+        }
     }
 
+    /// The highest level granted by any of the sources
     pub fn level(&self) -> &ProficiencyLevel {
-        &self.level
+        self.highest()
+            .map(|(_, level)| level)
+            .unwrap_or(&ProficiencyLevel::None)
     }
 
+    /// The source granting the highest level
     pub fn source(&self) -> &ModifierSource {
-        &self.source
+        self.highest()
+            .map(|(source, _)| source)
+            .unwrap_or(&NO_SOURCE)
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = (&ModifierSource, &ProficiencyLevel)> {
+        self.sources.iter()
+    }
+
+    /// Grant the proficiency from another source. If the source already grants
+    /// it, the level is replaced.
+    pub fn add_source(&mut self, level: ProficiencyLevel, source: ModifierSource) {
+        self.sources.insert(source, level);
+    }
+
+    pub fn remove_source(&mut self, source: &ModifierSource) {
+        self.sources.remove(source);
     }
 
     pub fn bonus(&self, proficiency_bonus: u8) -> u8 {
-        self.level.bonus(proficiency_bonus)
+        self.level().bonus(proficiency_bonus)
+    }
+
+    fn highest(&self) -> Option<(&ModifierSource, &ProficiencyLevel)> {
+        self.sources
+            .iter()
+            .max_by(|(_, a), (_, b)| a.multiplier().total_cmp(&b.multiplier()))
     }
 }
 
@@ -89,4 +122,26 @@ mod tests {
         assert_eq!(prof.bonus(2), 0);
         assert_eq!(prof.bonus(3), 0);
     }
+
+    #[test]
+    fn highest_source_wins() {
+        let feat = ModifierSource::Custom("Resilient".to_string());
+        let item = ModifierSource::Custom("Cloak".to_string());
+        let mut prof = Proficiency::new(ProficiencyLevel::None, ModifierSource::None);
+        prof.add_source(ProficiencyLevel::Proficient, feat.clone());
+        prof.add_source(ProficiencyLevel::Expertise, item.clone());
+        assert_eq!(prof.level(), &ProficiencyLevel::Expertise);
+        assert_eq!(prof.source(), &item);
+        assert_eq!(prof.bonus(2), 4);
+
+        // Removing one source leaves the proficiency from the other
+        prof.remove_source(&item);
+        assert_eq!(prof.level(), &ProficiencyLevel::Proficient);
+        assert_eq!(prof.source(), &feat);
+
+        prof.remove_source(&feat);
+        prof.remove_source(&ModifierSource::None);
+        assert_eq!(prof.level(), &ProficiencyLevel::None);
+        assert_eq!(prof.source(), &ModifierSource::None);
+    }
 }
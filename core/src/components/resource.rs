@@ -9,7 +9,7 @@ use std::{
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
-    components::id::{IdProvider, ResourceId},
+    components::id::{ClassId, IdProvider, ResourceId},
     systems::time::RestKind,
 };
 
@@ -268,6 +268,12 @@ pub enum ResourceError {
         needed: ResourceAmount,
         available: ResourceAmount,
     },
+    /// A variable amount has to be resolved to a flat amount before it's spent
+    UnresolvedAmount(ResourceAmount),
+    InvalidAmount {
+        amount: u8,
+        allowed: ResourceAmount,
+    },
 }
 
 macro_rules! impl_resource_amount_router {
@@ -293,6 +299,9 @@ macro_rules! impl_resource_amount_router {
                             })
                         }
                     }
+                    (_, ResourceAmount::Variable { .. }) => {
+                        Err(ResourceError::UnresolvedAmount(__arg.clone()))
+                    }
                     _ => Err(ResourceError::MistmatchAmountAndKind {
                         amount: __arg.clone(),
                         kind: self.clone(),
@@ -327,6 +336,9 @@ impl ResourceBudgetKind {
             (ResourceBudgetKind::Flat(budget), ResourceAmount::Flat(amt)) => {
                 budget.can_afford(*amt)
             }
+            (ResourceBudgetKind::Flat(budget), ResourceAmount::Variable { min, .. }) => {
+                budget.can_afford(*min)
+            }
             (ResourceBudgetKind::Tiered(budgets), ResourceAmount::Tiered { tier, amount }) => {
                 if let Some(budget) = budgets.get(tier) {
                     budget.can_afford(*amount)
@@ -385,6 +397,9 @@ impl From<ResourceAmount> for ResourceBudgetKind {
                 map.insert(tier, ResourceBudget::with_max_uses(amount).unwrap());
                 ResourceBudgetKind::Tiered(map)
             }
+            ResourceAmount::Variable { max, .. } => {
+                ResourceBudgetKind::Flat(ResourceBudget::with_max_uses(max).unwrap())
+            }
         }
     }
 }
@@ -394,6 +409,13 @@ impl From<ResourceAmount> for ResourceBudgetKind {
 pub enum ResourceDefinitionKind {
     Flat,
     Tiered,
+    /// A flat resource which grows with a class, e.g. the Lay on Hands pool of
+    /// 5 Hit Points per Paladin level. The pool is usually spent a variable
+    /// amount at a time.
+    Pool {
+        per_level: u8,
+        class: ClassId,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -415,7 +437,34 @@ impl IdProvider for Resource {
 #[serde(into = "String")]
 pub enum ResourceAmount {
     Flat(u8),
-    Tiered { tier: u8, amount: u8 },
+    Tiered {
+        tier: u8,
+        amount: u8,
+    },
+    /// The actor chooses how much of a flat resource to spend, e.g. how many
+    /// points from the Lay on Hands pool. Written as "1..5", or "1.." when the
+    /// actor can spend everything it has.
+    Variable {
+        min: u8,
+        max: u8,
+    },
+}
+
+impl ResourceAmount {
+    /// Resolve a variable amount to the amount chosen by the actor. Other
+    /// amounts are fixed, so they're only valid if they're already the amount.
+    pub fn resolve(&self, amount: u8) -> Result<ResourceAmount, ResourceError> {
+        match self {
+            ResourceAmount::Variable { min, max } if (*min..=*max).contains(&amount) => {
+                Ok(ResourceAmount::Flat(amount))
+            }
+            ResourceAmount::Flat(flat) if *flat == amount => Ok(self.clone()),
+            _ => Err(ResourceError::InvalidAmount {
+                amount,
+                allowed: self.clone(),
+            }),
+        }
+    }
 }
 
 impl Add for ResourceAmount {
@@ -501,6 +550,24 @@ impl FromStr for ResourceAmount {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((min, max)) = s.split_once("..") {
+            let min = min
+                .trim()
+                .parse::<u8>()
+                .map_err(|_| format!("Invalid ResourceAmount format: {}", s))?;
+            let max = if max.trim().is_empty() {
+                u8::MAX
+            } else {
+                max.trim()
+                    .parse::<u8>()
+                    .map_err(|_| format!("Invalid ResourceAmount format: {}", s))?
+            };
+            if min > max {
+                return Err(format!("Invalid ResourceAmount range: {}", s));
+            }
+            return Ok(ResourceAmount::Variable { min, max });
+        }
+
         let parts: Vec<&str> = s.split(':').collect();
         if parts.len() == 1 {
             let amount = parts[0]
@@ -546,6 +613,8 @@ impl From<ResourceAmount> for String {
         match amount {
             ResourceAmount::Flat(amt) => amt.to_string(),
             ResourceAmount::Tiered { tier, amount } => format!("{}:{}", tier, amount),
+            ResourceAmount::Variable { min, max } if max == u8::MAX => format!("{}..", min),
+            ResourceAmount::Variable { min, max } => format!("{}..{}", min, max),
         }
     }
 }
@@ -692,6 +761,14 @@ impl ResourceMap {
     }
 
     pub fn spend_all(&mut self, cost: &ResourceAmountMap) -> Result<(), ResourceError> {
+        // Check this up front so nothing is spent if the cost can't be paid
+        if let Some(amount) = cost
+            .values()
+            .find(|amount| matches!(amount, ResourceAmount::Variable { .. }))
+        {
+            return Err(ResourceError::UnresolvedAmount(amount.clone()));
+        }
+
         let (can_afford, lacking_id) = self.can_afford_all(cost);
         if !can_afford {
             let resource_id = lacking_id.unwrap();
@@ -755,6 +832,11 @@ impl Default for ResourceMap {
                 ResourceId::new("nat20_core", "resource.reaction").clone(),
                 ResourceBudgetKind::Flat(ResourceBudget::new(1, 1).unwrap()),
             ),
+            // Drawing a weapon, opening a door or picking something up
+            (
+                ResourceId::new("nat20_core", "resource.free_interaction").clone(),
+                ResourceBudgetKind::Flat(ResourceBudget::new(1, 1).unwrap()),
+            ),
         ]);
         map
     }
@@ -776,6 +858,47 @@ mod tests {
         ResourceBudgetKind::Tiered(map)
     }
 
+    #[test]
+    fn variable_amount_parsing() {
+        assert_eq!(
+            "1..5".parse::<ResourceAmount>(),
+            Ok(ResourceAmount::Variable { min: 1, max: 5 })
+        );
+        assert_eq!(
+            "1..".parse::<ResourceAmount>(),
+            Ok(ResourceAmount::Variable {
+                min: 1,
+                max: u8::MAX
+            })
+        );
+        assert!("5..1".parse::<ResourceAmount>().is_err());
+        assert_eq!(
+            String::from(ResourceAmount::Variable {
+                min: 1,
+                max: u8::MAX
+            }),
+            "1.."
+        );
+    }
+
+    #[test]
+    fn variable_amount_must_be_resolved() {
+        let mut res = flat_resource(3, 5);
+        let cost = ResourceAmount::Variable { min: 1, max: 5 };
+        assert!(res.can_afford(&cost));
+        assert_eq!(
+            res.spend(&cost),
+            Err(ResourceError::UnresolvedAmount(cost.clone()))
+        );
+
+        assert!(cost.resolve(0).is_err());
+        assert!(cost.resolve(6).is_err());
+        let resolved = cost.resolve(2).unwrap();
+        assert_eq!(resolved, ResourceAmount::Flat(2));
+        assert!(res.spend(&resolved).is_ok());
+        assert_eq!(res.current_uses()[0], ResourceAmount::Flat(1));
+    }
+
     #[test]
     fn flat_spend_success() {
         let mut res = flat_resource(3, 3);
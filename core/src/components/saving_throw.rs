@@ -7,8 +7,10 @@ use strum::IntoEnumIterator;
 use crate::{
     components::{
         ability::Ability,
-        d20::{D20CheckDC, D20CheckSet},
+        d20::{D20CheckDC, D20CheckOverride, D20CheckSet},
         effects::hooks::D20CheckHooks,
+        health::life_state::LifeState,
+        modifier::ModifierSource,
     },
     systems::{self},
 };
@@ -88,16 +90,38 @@ pub type SavingThrowSet = D20CheckSet<SavingThrowKind>;
 
 pub type SavingThrowDC = D20CheckDC<SavingThrowKind>;
 
+/// The base of save DCs which are derived from a creature, e.g. spell save DCs,
+/// before adding its ability modifier and proficiency bonus
+pub const BASE_SAVE_DC: i32 = 8;
+
 pub fn get_saving_throw_hooks(
     kind: &SavingThrowKind,
     world: &World,
     entity: Entity,
 ) -> Vec<D20CheckHooks> {
-    systems::effects::effects(world, entity)
+    let mut hooks: Vec<D20CheckHooks> = systems::effects::active_effects(world, entity)
         .iter()
         .filter_map(|e| e.effect().on_saving_throw.get(&kind))
         .cloned()
-        .collect()
+        .collect();
+
+    // Unconscious creatures automatically fail Strength and Dexterity saving throws
+    if matches!(
+        kind,
+        SavingThrowKind::Ability(Ability::Strength | Ability::Dexterity)
+    ) && world
+        .get::<&LifeState>(entity)
+        .is_ok_and(|life_state| life_state.is_unconscious())
+    {
+        hooks.push(D20CheckHooks::with_check_hook(|_, _, check| {
+            check.add_override(
+                D20CheckOverride::AutoFailure,
+                ModifierSource::Custom("Unconscious".to_string()),
+            );
+        }));
+    }
+
+    hooks
 }
 
 impl Default for SavingThrowSet {
@@ -0,0 +1,37 @@
+use uom::si::f32::Length;
+
+use crate::components::{
+    ability::Ability,
+    health::hit_points::HitPoints,
+    id::MonsterId,
+    modifier::ModifierSource,
+    species::{CreatureSize, CreatureType},
+};
+
+/// The physical abilities which are replaced by the ones of the new form. The
+/// mental abilities are always kept, e.g. for a druid in Wild Shape.
+pub const SHAPECHANGE_ABILITIES: [Ability; 3] =
+    [Ability::Strength, Ability::Dexterity, Ability::Constitution];
+
+/// The parts of the stat block that are swapped out while shapechanged, so they
+/// can be restored when the creature reverts to its normal form
+#[derive(Debug, Clone)]
+pub struct OriginalForm {
+    pub hit_points: HitPoints,
+    /// Base scores only, modifiers from e.g. effects stay on the creature
+    pub abilities: Vec<(Ability, i32)>,
+    pub speed: Length,
+    pub size: CreatureSize,
+    pub creature_type: CreatureType,
+}
+
+/// Present on a creature while it has taken the form of a creature from the
+/// monster registry, e.g. a druid using Wild Shape
+#[derive(Debug, Clone)]
+pub struct Shapechanged {
+    pub form: MonsterId,
+    /// What caused the shapechange. If it's an effect, the effect is removed
+    /// when the creature reverts.
+    pub source: ModifierSource,
+    pub original: OriginalForm,
+}
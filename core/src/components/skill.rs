@@ -97,12 +97,25 @@ pub type SkillSet = D20CheckSet<Skill>;
 
 pub type SkillCheckDC = D20CheckDC<Skill>;
 
+/// A contest between two creatures, e.g. trying to knock the weapon out of
+/// someone's hands. The defender uses whichever of its skills is best.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillContest {
+    pub skill: Skill,
+    pub opposed_by: Vec<Skill>,
+}
+
 pub fn get_skill_hooks(skill: &Skill, world: &World, entity: Entity) -> Vec<D20CheckHooks> {
-    systems::effects::effects(world, entity)
+    let mut hooks: Vec<D20CheckHooks> = systems::effects::active_effects(world, entity)
         .iter()
         .filter_map(|e| e.effect().on_skill_check.get(&skill))
         .cloned()
-        .collect()
+        .collect();
+    // Fear isn't tied to a specific skill, and depends on who applied it
+    hooks.push(D20CheckHooks::with_check_hook(
+        systems::conditions::apply_fear,
+    ));
+    hooks
 }
 
 impl Default for SkillSet {
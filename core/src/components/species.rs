@@ -1,7 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
-use strum::Display;
+use strum::{Display, EnumIter, IntoEnumIterator};
+use uom::si::{f32::Length, length::foot};
 
 use crate::{
     components::{
@@ -12,7 +13,9 @@ use crate::{
 };
 
 // TODO: Mutliple creature types? e.g. Undead Dragon
-#[derive(Debug, Clone, Display, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Display, EnumIter, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum CreatureType {
     Aberration,
@@ -31,7 +34,9 @@ pub enum CreatureType {
     Undead,
 }
 
-#[derive(Debug, Clone, Display, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Display, EnumIter, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum CreatureSize {
     Tiny,
@@ -42,6 +47,33 @@ pub enum CreatureSize {
     Gargantuan,
 }
 
+impl CreatureSize {
+    /// Width of the square of space the creature controls in combat
+    pub fn space(&self) -> Length {
+        let feet = match self {
+            CreatureSize::Tiny => 2.5,
+            CreatureSize::Small | CreatureSize::Medium => 5.0,
+            CreatureSize::Large => 10.0,
+            CreatureSize::Huge => 15.0,
+            CreatureSize::Gargantuan => 20.0,
+        };
+        Length::new::<foot>(feet)
+    }
+
+    pub fn larger(&self) -> Option<CreatureSize> {
+        CreatureSize::iter().skip_while(|size| size != self).nth(1)
+    }
+
+    pub fn smaller(&self) -> Option<CreatureSize> {
+        CreatureSize::iter().take_while(|size| size != self).last()
+    }
+
+    /// Number of sizes between the two sizes, positive if `other` is larger
+    pub fn difference(&self, other: &CreatureSize) -> i32 {
+        other.clone() as i32 - self.clone() as i32
+    }
+}
+
 // TODO: Do we need all these modes?
 // pub struct Speed {
 //     pub walk: u8,
@@ -26,6 +26,15 @@ impl Speed {
         }
     }
 
+    pub fn base(&self) -> Length {
+        Length::new::<meter>(*self.flat.get(&ModifierSource::Base).unwrap_or(&0.0))
+    }
+
+    /// Replace the base speed, keeping any modifiers, e.g. when shapechanging
+    pub fn set_base(&mut self, base: Length) {
+        self.flat.insert(ModifierSource::Base, base.get::<meter>());
+    }
+
     pub fn add_flat_modifier<T>(&mut self, source: ModifierSource, value: T)
     where
         T: Into<f32>,
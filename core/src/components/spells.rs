@@ -1,2 +1,5 @@
+pub mod innate;
+pub mod metamagic;
 pub mod spell;
+pub mod spell_list;
 pub mod spellbook;
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        ability::Ability,
+        id::SpellId,
+        resource::{RechargeRule, ResourceBudget},
+    },
+    systems::time::RestKind,
+};
+
+/// How often an innate spell can be cast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InnateFrequency {
+    AtWill,
+    PerDay(u8),
+}
+
+/// Spells a creature can cast without spell slots, e.g. a drow casting Darkness
+/// once per day. Stat blocks usually give the save DC and attack bonus of the
+/// spells directly, in which case they're used instead of the ones derived from
+/// the spellcasting ability.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InnateSpellcasting {
+    pub ability: Ability,
+    #[serde(default)]
+    pub dc: Option<i32>,
+    #[serde(default)]
+    pub attack_bonus: Option<i32>,
+    /// Innate spells are always cast at their base level
+    pub spells: HashMap<SpellId, InnateFrequency>,
+}
+
+/// Uses of innate spells are regained when the creature finishes a long rest
+pub const INNATE_RECHARGE: RechargeRule = RechargeRule::Rest(RestKind::Long);
+
+/// The innate spellcasting of a creature along with the uses it has left of
+/// each of its X/day spells
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InnateSpellcastingState {
+    pub spellcasting: InnateSpellcasting,
+    uses: HashMap<SpellId, ResourceBudget>,
+}
+
+impl InnateSpellcastingState {
+    pub fn new(spellcasting: InnateSpellcasting) -> Self {
+        let uses = spellcasting
+            .spells
+            .iter()
+            .filter_map(|(spell_id, frequency)| match frequency {
+                InnateFrequency::AtWill => None,
+                InnateFrequency::PerDay(per_day) => ResourceBudget::with_max_uses(*per_day)
+                    .ok()
+                    .map(|budget| (spell_id.clone(), budget)),
+            })
+            .collect();
+        Self { spellcasting, uses }
+    }
+
+    /// The uses left of an X/day spell, or `None` for at will spells
+    pub fn uses(&self, spell_id: &SpellId) -> Option<&ResourceBudget> {
+        self.uses.get(spell_id)
+    }
+
+    pub fn can_cast(&self, spell_id: &SpellId) -> bool {
+        match self.spellcasting.spells.get(spell_id) {
+            Some(InnateFrequency::AtWill) => true,
+            Some(InnateFrequency::PerDay(_)) => self
+                .uses
+                .get(spell_id)
+                .is_some_and(|budget| !budget.is_empty()),
+            None => false,
+        }
+    }
+
+    /// Returns `false` if the spell isn't innate or has no uses left. Casting an
+    /// at will spell always succeeds.
+    pub fn spend(&mut self, spell_id: &SpellId) -> bool {
+        match self.spellcasting.spells.get(spell_id) {
+            Some(InnateFrequency::AtWill) => true,
+            Some(InnateFrequency::PerDay(_)) => self
+                .uses
+                .get_mut(spell_id)
+                .is_some_and(|budget| budget.spend(1).is_ok()),
+            None => false,
+        }
+    }
+
+    pub fn recharge(&mut self, rest_type: &RechargeRule) {
+        if INNATE_RECHARGE.is_recharged_by(rest_type) {
+            for budget in self.uses.values_mut() {
+                budget.recharge_full();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn darkness() -> SpellId {
+        SpellId::new("nat20_core", "spell.darkness")
+    }
+
+    fn fire_bolt() -> SpellId {
+        SpellId::new("nat20_core", "spell.fire_bolt")
+    }
+
+    fn state() -> InnateSpellcastingState {
+        InnateSpellcastingState::new(
+            serde_json::from_str(
+                r#"{
+                    "ability": "charisma",
+                    "dc": 11,
+                    "spells": {
+                        "nat20_core::spell.fire_bolt": "at_will",
+                        "nat20_core::spell.darkness": { "per_day": 1 }
+                    }
+                }"#,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn per_day_spells_run_out() {
+        let mut state = state();
+        assert_eq!(state.spellcasting.dc, Some(11));
+        assert_eq!(state.spellcasting.attack_bonus, None);
+
+        assert!(state.spend(&darkness()));
+        assert!(!state.can_cast(&darkness()));
+        assert!(!state.spend(&darkness()));
+
+        for _ in 0..3 {
+            assert!(state.spend(&fire_bolt()));
+        }
+        assert!(state.can_cast(&fire_bolt()));
+        assert!(state.uses(&fire_bolt()).is_none());
+    }
+
+    #[test]
+    fn uses_recharge_on_long_rest() {
+        let mut state = state();
+        state.spend(&darkness());
+
+        state.recharge(&RechargeRule::Rest(RestKind::Short));
+        assert!(!state.can_cast(&darkness()));
+
+        state.recharge(&RechargeRule::Rest(RestKind::Long));
+        assert!(state.can_cast(&darkness()));
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+use uom::si::length::{foot, meter};
+
+use crate::components::{
+    actions::targeting::{TargetingContext, TargetingKind, TargetingRange},
+    id::ResourceId,
+    resource::{ResourceAmount, ResourceAmountMap},
+};
+
+/// Metamagic options which can be applied when casting a spell. The options
+/// are stored in the spell's `ActionContext`, so everything that depends on the
+/// context (targeting, validation, etc.) takes them into account.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Display,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Metamagic {
+    /// Double the range of the spell, or make a touch spell have a range of 30 ft.
+    Distant,
+    /// Cast a spell which costs an action as a bonus action instead
+    Quickened,
+    /// Target a second creature with a spell which only targets one creature
+    Twinned,
+}
+
+impl Metamagic {
+    pub fn sorcery_point_cost(&self, spell_level: u8) -> u8 {
+        match self {
+            Metamagic::Distant => 1,
+            Metamagic::Quickened => 2,
+            Metamagic::Twinned => spell_level.max(1),
+        }
+    }
+
+    /// Whether the metamagic option does anything for a spell with the given
+    /// targeting and cost
+    pub fn applicable(&self, targeting: &TargetingContext, cost: &ResourceAmountMap) -> bool {
+        match self {
+            Metamagic::Distant => {
+                !matches!(targeting.kind, TargetingKind::SelfTarget)
+                    && targeting.range.max().get::<meter>() > 0.0
+            }
+            Metamagic::Quickened => {
+                cost.contains_key(&ResourceId::new("nat20_core", "resource.action"))
+            }
+            Metamagic::Twinned => matches!(targeting.kind, TargetingKind::Single),
+        }
+    }
+
+    /// Adds the Sorcery Point cost of the metamagic option and applies any
+    /// changes to the action economy
+    pub fn apply_to_cost(&self, cost: &mut ResourceAmountMap, spell_level: u8) {
+        let sorcery_points = cost
+            .entry(sorcery_point_id())
+            .or_insert(ResourceAmount::Flat(0));
+        *sorcery_points =
+            sorcery_points.clone() + ResourceAmount::Flat(self.sorcery_point_cost(spell_level));
+
+        if *self == Metamagic::Quickened
+            && let Some(action) = cost.remove(&ResourceId::new("nat20_core", "resource.action"))
+        {
+            cost.insert(
+                ResourceId::new("nat20_core", "resource.bonus_action"),
+                action,
+            );
+        }
+    }
+
+    pub fn apply_to_targeting(&self, targeting: &mut TargetingContext) {
+        match self {
+            Metamagic::Distant => {
+                let normal = targeting.range.normal().get::<foot>();
+                let max = targeting.range.max().get::<foot>();
+                // Touch spells get a range of 30 ft. instead of being doubled
+                targeting.range = if max <= 5.0 {
+                    TargetingRange::new::<foot>(30.0)
+                } else {
+                    TargetingRange::with_max::<foot>(normal * 2.0, max * 2.0)
+                };
+            }
+            Metamagic::Quickened => {}
+            Metamagic::Twinned => {
+                if matches!(targeting.kind, TargetingKind::Single) {
+                    targeting.kind = TargetingKind::Multiple { max_targets: 2 };
+                }
+            }
+        }
+    }
+}
+
+pub fn sorcery_point_id() -> ResourceId {
+    ResourceId::new("nat20_core", "resource.sorcerer.sorcery_point")
+}
+
+/// The metamagic options known by a creature
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KnownMetamagic(pub BTreeSet<Metamagic>);
+
+#[cfg(test)]
+mod tests {
+    use crate::components::actions::targeting::EntityFilter;
+
+    use super::*;
+
+    fn single_target(range_ft: f32) -> TargetingContext {
+        TargetingContext::new(
+            TargetingKind::Single,
+            TargetingRange::new::<foot>(range_ft),
+            true,
+            EntityFilter::not_dead(),
+        )
+    }
+
+    #[test]
+    fn quickened_swaps_action_for_bonus_action() {
+        let mut cost = ResourceAmountMap::from([(
+            ResourceId::new("nat20_core", "resource.action"),
+            ResourceAmount::Flat(1),
+        )]);
+
+        Metamagic::Quickened.apply_to_cost(&mut cost, 1);
+
+        assert!(!cost.contains_key(&ResourceId::new("nat20_core", "resource.action")));
+        assert_eq!(
+            cost.get(&ResourceId::new("nat20_core", "resource.bonus_action")),
+            Some(&ResourceAmount::Flat(1))
+        );
+        assert_eq!(
+            cost.get(&sorcery_point_id()),
+            Some(&ResourceAmount::Flat(2))
+        );
+    }
+
+    #[test]
+    fn twinned_cost_scales_with_spell_level() {
+        assert_eq!(Metamagic::Twinned.sorcery_point_cost(0), 1);
+        assert_eq!(Metamagic::Twinned.sorcery_point_cost(3), 3);
+    }
+
+    #[test]
+    fn twinned_adds_second_target() {
+        let mut targeting = single_target(120.0);
+        assert!(Metamagic::Twinned.applicable(&targeting, &ResourceAmountMap::new()));
+
+        Metamagic::Twinned.apply_to_targeting(&mut targeting);
+
+        assert!(matches!(
+            targeting.kind,
+            TargetingKind::Multiple { max_targets: 2 }
+        ));
+        assert!(!Metamagic::Twinned.applicable(&targeting, &ResourceAmountMap::new()));
+    }
+
+    #[test]
+    fn distant_doubles_range() {
+        let mut targeting = single_target(120.0);
+        Metamagic::Distant.apply_to_targeting(&mut targeting);
+        assert!((targeting.range.max().get::<foot>() - 240.0).abs() < 0.1);
+
+        let mut touch = single_target(5.0);
+        Metamagic::Distant.apply_to_targeting(&mut touch);
+        assert!((touch.range.max().get::<foot>() - 30.0).abs() < 0.1);
+    }
+}
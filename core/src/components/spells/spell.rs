@@ -2,21 +2,23 @@ use std::{hash::Hash, sync::Arc};
 
 use hecs::{Entity, World};
 use serde::{Deserialize, Serialize};
-use strum::Display;
+use strum::{Display, EnumIter};
 
 use crate::{
     components::{
         ability::Ability,
-        actions::action::{Action, ActionKind, TargetingFunction},
+        actions::action::{Action, ActionKind, DamageRollPolicy, TargetingFunction},
         id::{EffectId, IdProvider, ScriptId, SpellId},
+        items::money::MonetaryValue,
         resource::ResourceAmountMap,
+        surface::SurfaceTemplate,
     },
     engine::event::ActionExecutionInstanceId,
     registry::serialize::spell::SpellDefinition,
     systems,
 };
 
-#[derive(Debug, Clone, Copy, Display, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Display, EnumIter, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MagicSchool {
     Abjuration,
@@ -35,6 +37,32 @@ pub enum SpellFlag {
     Concentration,
     Verbal,
     Somatic,
+    /// The spell can be cast as a ritual, taking 10 minutes longer to cast
+    /// but without expending a spell slot
+    Ritual,
+}
+
+/// Material components that cost money, e.g. the diamond Revivify is cast with.
+/// Components without a cost are assumed to be in the caster's component pouch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialComponent {
+    pub description: String,
+    pub cost: MonetaryValue,
+    #[serde(default)]
+    pub consumed: MaterialConsumption,
+}
+
+/// Whether, and when, a material component is used up by the spell
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaterialConsumption {
+    #[default]
+    Never,
+    /// Used up as soon as the spell is cast
+    OnCast,
+    /// Only used up if the spell brings its target back to life, so the
+    /// diamond of a resurrection spell isn't lost on a failed attempt
+    OnSuccess,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -44,6 +72,7 @@ pub struct Spell {
     base_level: u8,
     school: MagicSchool,
     flags: Vec<SpellFlag>,
+    material: Option<MaterialComponent>,
     action: Action,
     /// TODO: Is there a better way to represent this?
     ///
@@ -62,10 +91,13 @@ impl Spell {
         base_level: u8,
         school: MagicSchool,
         flags: Vec<SpellFlag>,
+        material: Option<MaterialComponent>,
         kind: ActionKind,
         resource_cost: ResourceAmountMap,
         targeting: Arc<TargetingFunction>,
         reaction_trigger: Option<ScriptId>,
+        surface: Option<SurfaceTemplate>,
+        damage_roll: DamageRollPolicy,
         granted_spells: Vec<(SpellId, u8)>,
     ) -> Self {
         let action_id = id.clone().into();
@@ -75,14 +107,18 @@ impl Spell {
             school,
             base_level,
             flags,
+            material,
             action: Action {
                 id: action_id,
                 description,
+                resource_cost: kind.total_cost(resource_cost),
                 kind,
-                resource_cost,
                 targeting,
                 cooldown: None,
                 reaction_trigger,
+                surface,
+                damage_roll,
+                constraints: Vec::new(),
             },
             granted_spells,
         }
@@ -116,6 +152,10 @@ impl Spell {
         self.flags.contains(&flag)
     }
 
+    pub fn material(&self) -> Option<&MaterialComponent> {
+        self.material.as_ref()
+    }
+
     pub fn granted_spells(&self) -> &Vec<(SpellId, u8)> {
         &self.granted_spells
     }
@@ -137,7 +177,15 @@ pub const CONCENTRATION_SAVING_THROW_DC_DEFAULT: i32 = 10;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ConcentrationInstance {
-    Effect { entity: Entity, effect: EffectId },
+    Effect {
+        entity: Entity,
+        effect: EffectId,
+    },
+    /// An entity which only exists while the caster concentrates, e.g. the
+    /// image of Major Image
+    Entity {
+        entity: Entity,
+    },
     // TODO: Environmental effects (e.g. web)
 }
 
@@ -147,6 +195,9 @@ impl ConcentrationInstance {
             ConcentrationInstance::Effect { entity, effect } => {
                 systems::effects::remove_effect(world, *entity, effect);
             }
+            ConcentrationInstance::Entity { entity } => {
+                let _ = world.despawn(*entity);
+            }
         }
     }
 }
@@ -176,7 +227,8 @@ impl ConcentrationTracker {
     /// then we need to be able to remove just that one instance.
     pub fn remove_instances_by_entity(&mut self, entity: Entity) {
         self.instances.retain(|instance| match instance {
-            ConcentrationInstance::Effect { entity: e, .. } => *e != entity,
+            ConcentrationInstance::Effect { entity: e, .. }
+            | ConcentrationInstance::Entity { entity: e } => *e != entity,
         });
         if self.instances.is_empty() {
             self.action_instance = None;
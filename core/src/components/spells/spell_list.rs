@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::id::{IdProvider, SpellId, SpellListId};
+
+/// The spells a class can learn or prepare, e.g. the Wizard spell list. Lists
+/// are shared between classes and subclasses through the registry, so e.g. an
+/// Eldritch Knight can use the Wizard spell list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpellList {
+    pub id: SpellListId,
+    pub spells: HashSet<SpellId>,
+    /// The spells from the list worth picking first, best first, e.g. for
+    /// characters which are built without making their own choices
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recommended: Vec<SpellId>,
+}
+
+impl SpellList {
+    pub fn contains(&self, spell_id: &SpellId) -> bool {
+        self.spells.contains(spell_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SpellId> {
+        self.spells.iter()
+    }
+}
+
+impl IdProvider for SpellList {
+    type Id = SpellListId;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+}
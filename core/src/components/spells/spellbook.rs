@@ -22,11 +22,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
+        ability::{Ability, AbilityScoreMap},
         actions::action::{ActionContext, ActionMap, ActionProvider},
         class::{CastingReadinessModel, ClassAndSubclass, SpellAccessModel, SpellcastingRules},
         id::{EffectId, FeatId, ItemId, ResourceId, SpeciesId, SpellId},
         resource::{ResourceAmount, ResourceAmountMap, ResourceBudgetKind, ResourceMap},
-        spells::spell::ConcentrationTracker,
+        spells::{
+            innate::{InnateSpellcasting, InnateSpellcastingState},
+            spell::{ConcentrationTracker, SPELL_CASTING_ABILITIES, SpellFlag},
+        },
     },
     registry::registry::{ClassesRegistry, SpellsRegistry},
     systems,
@@ -136,20 +140,65 @@ impl ClassSpellSelections {
     }
 }
 
+/// The ability a spell source casts its spells with, which decides the spell
+/// save DC and spell attack bonus of its spells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpellcastingAbility {
+    Fixed(Ability),
+    /// The highest of Intelligence, Wisdom and Charisma
+    Highest,
+}
+
+impl SpellcastingAbility {
+    /// Classes cast with the ability from their spellcasting rules, while spells
+    /// granted by items, feats etc. use the highest spellcasting ability.
+    pub fn default_for(source: &SpellSource) -> Self {
+        match source {
+            SpellSource::Class(class_and_subclass) => {
+                ClassesRegistry::get(&class_and_subclass.class)
+                    .and_then(|class| class.spellcasting_rules(&class_and_subclass.subclass))
+                    .map(|rules| SpellcastingAbility::Fixed(rules.spellcasting_ability))
+                    .unwrap_or(SpellcastingAbility::Highest)
+            }
+            SpellSource::Granted { .. } => SpellcastingAbility::Highest,
+        }
+    }
+
+    pub fn resolve(&self, ability_scores: &AbilityScoreMap) -> Ability {
+        match self {
+            SpellcastingAbility::Fixed(ability) => *ability,
+            SpellcastingAbility::Highest => ability_scores.get_max_score(SPELL_CASTING_ABILITIES).0,
+        }
+    }
+}
+
 /// The per-class caster state (rules + caps + level-derived info).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClassSpellcastingState {
     pub selections: ClassSpellSelections,
+    /// Starts out as the ability from the class' spellcasting rules, but can be
+    /// overridden by features
+    pub spellcasting_ability: SpellcastingAbility,
+    /// Spells added to the class' spell list by feats or magic items
+    #[serde(default)]
+    pub expanded_spells: HashSet<SpellId>,
 }
 
 impl ClassSpellcastingState {
-    pub fn new(max_cantrips: usize, max_learned_spells: usize, max_prepared_spells: usize) -> Self {
+    pub fn new(
+        spellcasting_ability: Ability,
+        max_cantrips: usize,
+        max_learned_spells: usize,
+        max_prepared_spells: usize,
+    ) -> Self {
         Self {
             selections: ClassSpellSelections::new(
                 max_cantrips,
                 max_learned_spells,
                 max_prepared_spells,
             ),
+            spellcasting_ability: SpellcastingAbility::Fixed(spellcasting_ability),
+            expanded_spells: HashSet::new(),
         }
     }
 
@@ -176,6 +225,8 @@ pub enum GrantedSpellSource {
     Species(SpeciesId),
     Effect(EffectId),
     ParentSpell(SpellId),
+    /// Spells the creature can cast without spell slots, see [`InnateSpellcasting`]
+    Innate,
 }
 
 /// A class-independent spell source (items/feats/race/boons).
@@ -186,12 +237,14 @@ pub struct GrantedSpellMap {
     /// These do not consume spell slots. The value is the level at which they are
     /// castable
     pub spells: HashMap<SpellId, u8>,
+    pub spellcasting_ability: SpellcastingAbility,
 }
 
 impl GrantedSpellMap {
     pub fn new() -> Self {
         Self {
             spells: HashMap::new(),
+            spellcasting_ability: SpellcastingAbility::Highest,
         }
     }
 }
@@ -227,6 +280,8 @@ pub struct Spellbook {
     class_states: HashMap<ClassAndSubclass, ClassSpellcastingState>,
     /// External sources (items/feats/race).
     granted: HashMap<GrantedSpellSource, GrantedSpellMap>,
+    /// Innate spells and their remaining uses, e.g. for monsters.
+    innate: Option<InnateSpellcastingState>,
     /// Concentration tracking state.
     concentration: ConcentrationTracker,
 }
@@ -236,6 +291,7 @@ impl Spellbook {
         Self {
             class_states: HashMap::new(),
             granted: HashMap::new(),
+            innate: None,
             concentration: ConcentrationTracker::default(),
         }
     }
@@ -259,6 +315,32 @@ impl Spellbook {
         self.granted.get_mut(source)
     }
 
+    /// Give the creature innate spells, replacing any it already had. The spells
+    /// are granted at their base level and are cast with the innate ability.
+    pub fn set_innate_spellcasting(&mut self, spellcasting: InnateSpellcasting) {
+        let mut spell_set = GrantedSpellMap::new();
+        spell_set.spellcasting_ability = SpellcastingAbility::Fixed(spellcasting.ability);
+        for spell_id in spellcasting.spells.keys() {
+            let Some(spell) = SpellsRegistry::get(spell_id) else {
+                continue;
+            };
+            spell_set
+                .spells
+                .insert(spell_id.clone(), spell.base_level());
+        }
+
+        self.granted.insert(GrantedSpellSource::Innate, spell_set);
+        self.innate = Some(InnateSpellcastingState::new(spellcasting));
+    }
+
+    pub fn innate_spellcasting(&self) -> Option<&InnateSpellcastingState> {
+        self.innate.as_ref()
+    }
+
+    pub fn innate_spellcasting_mut(&mut self) -> Option<&mut InnateSpellcastingState> {
+        self.innate.as_mut()
+    }
+
     pub fn insert_class_state(
         &mut self,
         class_and_subclass: ClassAndSubclass,
@@ -281,6 +363,99 @@ impl Spellbook {
         self.class_states.get_mut(class_and_subclass)
     }
 
+    /// The ability the spells from the source are cast with, or `None` if the
+    /// spellbook doesn't have the source
+    pub fn spellcasting_ability(&self, source: &SpellSource) -> Option<SpellcastingAbility> {
+        match source {
+            SpellSource::Class(class_and_subclass) => self
+                .class_states
+                .get(class_and_subclass)
+                .map(|state| state.spellcasting_ability),
+            SpellSource::Granted { source, .. } => self
+                .granted
+                .get(source)
+                .map(|granted| granted.spellcasting_ability),
+        }
+    }
+
+    /// Override the ability the spells from the source are cast with, e.g. a
+    /// feat which lets the character choose the ability for the spells it grants
+    pub fn set_spellcasting_ability(
+        &mut self,
+        source: &SpellSource,
+        spellcasting_ability: SpellcastingAbility,
+    ) -> Result<(), SpellbookError> {
+        match source {
+            SpellSource::Class(class_and_subclass) => {
+                self.class_states
+                    .get_mut(class_and_subclass)
+                    .ok_or(SpellbookError::ClassNotFound)?
+                    .spellcasting_ability = spellcasting_ability;
+            }
+            // The override can be set before the source has granted any spells
+            SpellSource::Granted { source, .. } => {
+                self.granted
+                    .entry(source.clone())
+                    .or_insert_with(GrantedSpellMap::new)
+                    .spellcasting_ability = spellcasting_ability;
+            }
+        }
+        Ok(())
+    }
+
+    /// The spells the class can learn and prepare, i.e. the class' spell list
+    /// along with any spells added to it by features
+    pub fn spell_list(&self, class_and_subclass: &ClassAndSubclass) -> HashSet<SpellId> {
+        let mut spell_list = ClassesRegistry::get(&class_and_subclass.class)
+            .map(|class| class.spell_list(&class_and_subclass.subclass))
+            .unwrap_or_default();
+        if let Some(state) = self.class_states.get(class_and_subclass) {
+            spell_list.extend(state.expanded_spells.iter().cloned());
+        }
+        spell_list
+    }
+
+    pub fn is_on_spell_list(
+        &self,
+        class_and_subclass: &ClassAndSubclass,
+        spell_id: &SpellId,
+    ) -> bool {
+        self.spell_list(class_and_subclass).contains(spell_id)
+    }
+
+    /// Add spells to the spell list of the class, e.g. a feat which lets a
+    /// Wizard learn spells from the Cleric spell list
+    pub fn expand_spell_list(
+        &mut self,
+        class_and_subclass: &ClassAndSubclass,
+        spells: impl IntoIterator<Item = SpellId>,
+    ) -> Result<(), SpellbookError> {
+        self.class_states
+            .get_mut(class_and_subclass)
+            .ok_or(SpellbookError::ClassNotFound)?
+            .expanded_spells
+            .extend(spells);
+        Ok(())
+    }
+
+    /// Add a spell which is always prepared for the class, e.g. Circle or
+    /// Patron spells granted by a subclass
+    pub fn add_always_prepared(
+        &mut self,
+        class_and_subclass: &ClassAndSubclass,
+        spell_id: &SpellId,
+    ) -> Result<(), SpellbookError> {
+        let state = self
+            .class_states
+            .get_mut(class_and_subclass)
+            .ok_or(SpellbookError::ClassNotFound)?;
+
+        if !state.selections.always_prepared.insert(spell_id.clone()) {
+            return Err(SpellbookError::AlreadyPresent);
+        }
+        Ok(())
+    }
+
     /// Computed known spells for a class:
     /// - Learned: learned_spells (+ cantrips, always_prepared)
     /// - EntireClassList: all spells from class list up to max_spell_level (+ cantrips, always_prepared)
@@ -315,7 +490,7 @@ impl Spellbook {
                 }
                 SpellAccessModel::EntireClassList => {
                     // Compute: all spells on the class list that are within max spell level.
-                    for spell_id in spellcasting_rules.spell_list.iter() {
+                    for spell_id in self.spell_list(class_and_subclass).iter() {
                         let spell = SpellsRegistry::get(spell_id)
                             .unwrap_or_else(|| panic!("Missing spell in registry: {}", spell_id));
 
@@ -383,17 +558,19 @@ impl Spellbook {
         class_and_subclass: &ClassAndSubclass,
         spell_id: &SpellId,
     ) -> Result<(), SpellbookError> {
+        let on_spell_list = self.is_on_spell_list(class_and_subclass, spell_id);
         let state = self
             .class_states
             .get_mut(class_and_subclass)
             .ok_or(SpellbookError::ClassNotFound)?;
 
         if let Some(class) = ClassesRegistry::get(&class_and_subclass.class)
-            && let Some(spellcasting_rules) = class.spellcasting_rules(&class_and_subclass.subclass)
+            && class
+                .spellcasting_rules(&class_and_subclass.subclass)
+                .is_some()
+            && !on_spell_list
         {
-            if !spellcasting_rules.spell_list.contains(spell_id) {
-                return Err(SpellbookError::SpellNotOnClassList);
-            }
+            return Err(SpellbookError::SpellNotOnClassList);
         }
 
         let spell = SpellsRegistry::get(spell_id)
@@ -417,6 +594,7 @@ impl Spellbook {
         spell_id: &SpellId,
         resources: &ResourceMap,
     ) -> Result<(), SpellbookError> {
+        let on_spell_list = self.is_on_spell_list(class_and_subclass, spell_id);
         let state = self
             .class_states
             .get_mut(class_and_subclass)
@@ -428,7 +606,7 @@ impl Spellbook {
             if spellcasting_rules.access_model != SpellAccessModel::Learned {
                 return Err(SpellbookError::CannotLearnForThisClass);
             }
-            if !spellcasting_rules.spell_list.contains(spell_id) {
+            if !on_spell_list {
                 return Err(SpellbookError::SpellNotOnClassList);
             }
 
@@ -459,6 +637,7 @@ impl Spellbook {
         spell_id: &SpellId,
         resources: &ResourceMap,
     ) -> Result<(), SpellbookError> {
+        let on_spell_list = self.is_on_spell_list(class_and_subclass, spell_id);
         let state = self
             .class_states
             .get_mut(class_and_subclass)
@@ -469,7 +648,7 @@ impl Spellbook {
             if spellcasting_rules.readiness_model != CastingReadinessModel::Prepared {
                 return Err(SpellbookError::CannotPrepareForThisClass);
             }
-            if !spellcasting_rules.spell_list.contains(spell_id) {
+            if !on_spell_list {
                 return Err(SpellbookError::SpellNotOnClassList);
             }
             // Must be "known" in the sense of your access model:
@@ -668,6 +847,15 @@ impl Spellbook {
         // Granted cantrips/spells
         for (source, granted_set) in self.granted.iter() {
             for (spell_id, level) in granted_set.spells.iter() {
+                // X/day innate spells can't be cast once they're used up
+                if *source == GrantedSpellSource::Innate
+                    && !self
+                        .innate
+                        .as_ref()
+                        .is_some_and(|innate| innate.can_cast(spell_id))
+                {
+                    continue;
+                }
                 castable.insert((
                     spell_id.clone(),
                     SpellSource::Granted {
@@ -733,6 +921,7 @@ impl ActionProvider for Spellbook {
                     id: spell_id.clone(),
                     source: source.clone(),
                     level: 0,
+                    metamagic: Vec::new(),
                 };
 
                 actions.insert(
@@ -748,6 +937,7 @@ impl ActionProvider for Spellbook {
                         id: spell_id.clone(),
                         source: source.clone(),
                         level: *level,
+                        metamagic: Vec::new(),
                     };
 
                     actions.insert(
@@ -787,6 +977,7 @@ impl ActionProvider for Spellbook {
                             id: spell_id.clone(),
                             source: source.clone(),
                             level: cast_level,
+                            metamagic: Vec::new(),
                         };
 
                         let mut resource_cost: ResourceAmountMap =
@@ -805,6 +996,22 @@ impl ActionProvider for Spellbook {
                             .or_insert_with(Vec::new)
                             .push((context, resource_cost));
                     }
+
+                    // Rituals can also be cast at their base level without
+                    // spending a spell slot, but take longer to cast
+                    if spell.has_flag(SpellFlag::Ritual) {
+                        let context = ActionContext::Spell {
+                            id: spell_id.clone(),
+                            source: source.clone(),
+                            level: spell.base_level(),
+                            metamagic: Vec::new(),
+                        };
+
+                        actions
+                            .entry(spell.action().id().clone())
+                            .or_insert_with(Vec::new)
+                            .push((context, spell.action().resource_cost().clone()));
+                    }
                 }
             }
         }
@@ -0,0 +1,242 @@
+use hecs::Entity;
+use parry3d::na::Point3;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+use uom::si::{f32::Length, length::meter};
+
+use crate::components::{
+    ability::Ability,
+    damage::DamageType,
+    dice::{DiceSet, DieSize},
+    saving_throw::SavingThrowKind,
+    skill::Skill,
+    time::{TimeDuration, TimeStep},
+};
+
+/// DC of surfaces which neither specify a DC nor get one from the action that
+/// created them
+pub const SURFACE_DC_DEFAULT: i32 = 10;
+
+/// Creatures more than this far above or below a surface aren't standing on it
+const SURFACE_HEIGHT_TOLERANCE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SurfaceKind {
+    Fire,
+    Ice,
+    Grease,
+    Acid,
+    /// Suppresses magic within it, see `systems::antimagic`
+    Antimagic,
+}
+
+/// The roll a creature makes to resist a surface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceCheck {
+    SavingThrow(SavingThrowKind),
+    Skill(Skill),
+}
+
+/// What happens to a creature that enters a surface or starts its turn in it.
+/// Damage is halved on a successful check, and creatures that fail the check on
+/// a slippery surface fall prone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceHazard {
+    pub damage: Option<(DiceSet, DamageType)>,
+    pub check: SurfaceCheck,
+    pub prone: bool,
+}
+
+/// What happens to an existing surface when a new surface is created on top of
+/// it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceInteraction {
+    None,
+    /// The existing surface turns into another kind, e.g. grease catching fire
+    Transform(SurfaceKind),
+    /// The existing surface is destroyed, e.g. ice melting in a fire
+    Remove,
+}
+
+impl SurfaceKind {
+    /// What the surface does to creatures entering it, if anything
+    pub fn hazard(&self) -> Option<SurfaceHazard> {
+        let dexterity_save =
+            SurfaceCheck::SavingThrow(SavingThrowKind::Ability(Ability::Dexterity));
+        let hazard = match self {
+            SurfaceKind::Fire => SurfaceHazard {
+                damage: Some((DiceSet::new(1, DieSize::D10), DamageType::Fire)),
+                check: dexterity_save,
+                prone: false,
+            },
+            SurfaceKind::Acid => SurfaceHazard {
+                damage: Some((DiceSet::new(2, DieSize::D4), DamageType::Acid)),
+                check: dexterity_save,
+                prone: false,
+            },
+            SurfaceKind::Grease => SurfaceHazard {
+                damage: None,
+                check: dexterity_save,
+                prone: true,
+            },
+            SurfaceKind::Ice => SurfaceHazard {
+                damage: None,
+                check: SurfaceCheck::Skill(Skill::Acrobatics),
+                prone: true,
+            },
+            SurfaceKind::Antimagic => return None,
+        };
+        Some(hazard)
+    }
+
+    /// Moving through difficult terrain costs twice as much movement
+    pub fn is_difficult_terrain(&self) -> bool {
+        matches!(self, SurfaceKind::Grease | SurfaceKind::Ice)
+    }
+
+    /// What this kind of surface does to an existing surface it's created on
+    /// top of
+    pub fn interact(&self, existing: &SurfaceKind) -> SurfaceInteraction {
+        match (self, existing) {
+            (SurfaceKind::Fire, SurfaceKind::Grease) => {
+                SurfaceInteraction::Transform(SurfaceKind::Fire)
+            }
+            (SurfaceKind::Fire, SurfaceKind::Ice) | (SurfaceKind::Ice, SurfaceKind::Fire) => {
+                SurfaceInteraction::Remove
+            }
+            _ => SurfaceInteraction::None,
+        }
+    }
+}
+
+/// A surface covering the ground in a circle around its center, e.g. a pool of
+/// acid or the slick left behind by the Grease spell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    pub kind: SurfaceKind,
+    pub center: Point3<f32>,
+    pub radius: Length,
+    pub dc: i32,
+    /// Surfaces without a duration last until something removes them
+    pub time_remaining: Option<TimeDuration>,
+    /// The creature whose action created the surface, if any
+    pub creator: Option<Entity>,
+}
+
+impl Surface {
+    pub fn new(
+        kind: SurfaceKind,
+        center: Point3<f32>,
+        radius: Length,
+        dc: i32,
+        duration: Option<TimeDuration>,
+        creator: Option<Entity>,
+    ) -> Self {
+        Self {
+            kind,
+            center,
+            radius,
+            dc,
+            time_remaining: duration,
+            creator,
+        }
+    }
+
+    pub fn contains(&self, point: &Point3<f32>) -> bool {
+        let horizontal_distance =
+            ((point.x - self.center.x).powi(2) + (point.z - self.center.z).powi(2)).sqrt();
+        horizontal_distance <= self.radius.get::<meter>()
+            && (point.y - self.center.y).abs() <= SURFACE_HEIGHT_TOLERANCE
+    }
+
+    /// Whether the two surfaces cover any of the same ground
+    pub fn overlaps(&self, other: &Surface) -> bool {
+        let horizontal_distance = ((other.center.x - self.center.x).powi(2)
+            + (other.center.z - self.center.z).powi(2))
+        .sqrt();
+        horizontal_distance <= (self.radius + other.radius).get::<meter>()
+            && (other.center.y - self.center.y).abs() <= SURFACE_HEIGHT_TOLERANCE
+    }
+
+    pub fn advance_time(&mut self, time_step: &TimeStep) {
+        if let Some(time_remaining) = &mut self.time_remaining {
+            time_remaining.decrement(time_step);
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time_remaining
+            .is_some_and(|time_remaining| time_remaining.as_seconds() <= 0.0)
+    }
+}
+
+/// Surface left behind by an action, covering the area of the action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceTemplate {
+    pub kind: SurfaceKind,
+    /// If not specified the surface uses the saving throw DC of the action, e.g.
+    /// the spell save DC of the caster
+    #[serde(default)]
+    pub dc: Option<i32>,
+    #[serde(default)]
+    pub duration: Option<TimeDuration>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(kind: SurfaceKind, x: f32, radius: f32) -> Surface {
+        Surface::new(
+            kind,
+            Point3::new(x, 0.0, 0.0),
+            Length::new::<meter>(radius),
+            SURFACE_DC_DEFAULT,
+            Some(TimeDuration::from_rounds(2)),
+            None,
+        )
+    }
+
+    #[test]
+    fn surface_contains_point() {
+        let grease = surface(SurfaceKind::Grease, 0.0, 1.5);
+        assert!(grease.contains(&Point3::new(1.0, 0.0, 1.0)));
+        assert!(!grease.contains(&Point3::new(2.0, 0.0, 0.0)));
+        // Flying over the grease doesn't count
+        assert!(!grease.contains(&Point3::new(0.0, 3.0, 0.0)));
+    }
+
+    #[test]
+    fn surface_overlaps() {
+        let grease = surface(SurfaceKind::Grease, 0.0, 1.5);
+        assert!(grease.overlaps(&surface(SurfaceKind::Fire, 2.5, 1.5)));
+        assert!(!grease.overlaps(&surface(SurfaceKind::Fire, 3.5, 1.5)));
+    }
+
+    #[test]
+    fn surface_expires() {
+        let mut fire = surface(SurfaceKind::Fire, 0.0, 1.5);
+        let step = TimeStep::RealTime { delta_seconds: 6.0 };
+        fire.advance_time(&step);
+        assert!(!fire.is_expired());
+        fire.advance_time(&step);
+        assert!(fire.is_expired());
+    }
+
+    #[test]
+    fn fire_ignites_grease() {
+        assert_eq!(
+            SurfaceKind::Fire.interact(&SurfaceKind::Grease),
+            SurfaceInteraction::Transform(SurfaceKind::Fire)
+        );
+        assert_eq!(
+            SurfaceKind::Fire.interact(&SurfaceKind::Ice),
+            SurfaceInteraction::Remove
+        );
+        assert_eq!(
+            SurfaceKind::Grease.interact(&SurfaceKind::Fire),
+            SurfaceInteraction::None
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::items::supply::SupplyKind;
+
+/// How well a creature has been eating and drinking. Only kept track of for
+/// the party while the supplies rule is enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sustenance {
+    /// Whether the creature has eaten since the last dawn
+    pub fed: bool,
+    /// Whether the creature has had enough to drink since the last dawn
+    pub watered: bool,
+    /// Days in a row the creature has gone without food
+    pub days_without_food: u32,
+}
+
+impl Sustenance {
+    pub fn has(&self, kind: &SupplyKind) -> bool {
+        match kind {
+            SupplyKind::Food => self.fed,
+            SupplyKind::Water => self.watered,
+        }
+    }
+
+    pub fn provide(&mut self, kind: &SupplyKind) {
+        match kind {
+            SupplyKind::Food => self.fed = true,
+            SupplyKind::Water => self.watered = true,
+        }
+    }
+}
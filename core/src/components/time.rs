@@ -1,9 +1,16 @@
+use std::fmt;
+
 use hecs::Entity;
 use serde::{Deserialize, Serialize};
 
 use crate::{engine::encounter::EncounterId, registry::serialize::effect::TimeDurationDefinition};
 
 pub const TURN_DURATION_SECONDS: f32 = 6.0;
+pub const SECONDS_PER_MINUTE: f32 = 60.0;
+pub const SECONDS_PER_HOUR: f32 = 60.0 * SECONDS_PER_MINUTE;
+pub const SECONDS_PER_DAY: f32 = 24.0 * SECONDS_PER_HOUR;
+/// The hour of the day at which daily resources are recharged
+pub const DAWN_HOUR: u32 = 6;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -49,6 +56,24 @@ impl TimeDuration {
         }
     }
 
+    /// A round is the time it takes for every participant in an encounter to
+    /// take a turn, which is the same as the duration of a single turn.
+    pub fn from_rounds(rounds: u32) -> Self {
+        Self::from_turns(rounds)
+    }
+
+    pub fn from_minutes(minutes: u32) -> Self {
+        Self::from_seconds(minutes as f32 * SECONDS_PER_MINUTE)
+    }
+
+    pub fn from_hours(hours: u32) -> Self {
+        Self::from_seconds(hours as f32 * SECONDS_PER_HOUR)
+    }
+
+    pub fn from_days(days: u32) -> Self {
+        Self::from_seconds(days as f32 * SECONDS_PER_DAY)
+    }
+
     pub fn as_seconds(&self) -> f32 {
         self.seconds
     }
@@ -57,6 +82,11 @@ impl TimeDuration {
         (self.seconds / TURN_DURATION_SECONDS).ceil() as u32
     }
 
+    /// Whole hours, rounded down
+    pub fn as_hours(&self) -> u32 {
+        (self.seconds / SECONDS_PER_HOUR).floor() as u32
+    }
+
     pub fn decrement(&mut self, step: &TimeStep) {
         match step {
             TimeStep::RealTime { delta_seconds } => {
@@ -115,12 +145,104 @@ impl EntityClock {
     }
 }
 
+/// The in-game time shared by everyone outside of combat. The calendar is kept
+/// simple: days are counted from the start of the campaign and every day is 24
+/// hours long. The day and the time of day are stored separately, and the time
+/// of day in double precision, so adding the frame time every frame doesn't
+/// make the clock drift over a long campaign.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WorldClock {
+    day: u32,
+    seconds_of_day: f64,
+}
+
+impl WorldClock {
+    /// The campaign starts at dawn on the first day
+    pub fn new() -> Self {
+        Self::at(1, DAWN_HOUR, 0)
+    }
+
+    pub fn at(day: u32, hour: u32, minute: u32) -> Self {
+        Self {
+            day,
+            seconds_of_day: hour as f64 * SECONDS_PER_HOUR as f64
+                + minute as f64 * SECONDS_PER_MINUTE as f64,
+        }
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u32 {
+        (self.seconds_of_day / SECONDS_PER_HOUR as f64) as u32
+    }
+
+    pub fn minute(&self) -> u32 {
+        ((self.seconds_of_day % SECONDS_PER_HOUR as f64) / SECONDS_PER_MINUTE as f64) as u32
+    }
+
+    pub fn second(&self) -> u32 {
+        (self.seconds_of_day % SECONDS_PER_MINUTE as f64) as u32
+    }
+
+    /// Advance the clock and return the number of dawns that passed
+    pub fn advance(&mut self, duration: &TimeDuration) -> u32 {
+        let seconds_per_day = SECONDS_PER_DAY as f64;
+        let dawn = DAWN_HOUR as f64 * SECONDS_PER_HOUR as f64;
+        let before = self.seconds_of_day;
+        let after = before + duration.as_seconds() as f64;
+
+        let days = (after / seconds_per_day).floor();
+        self.day += days as u32;
+        self.seconds_of_day = after - days * seconds_per_day;
+
+        let dawns = ((after - dawn) / seconds_per_day).floor()
+            - ((before - dawn) / seconds_per_day).floor();
+        dawns as u32
+    }
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for WorldClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {}, {:02}:{:02}:{:02}",
+            self.day,
+            self.hour(),
+            self.minute(),
+            self.second()
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use hecs::World;
 
     use super::*;
 
+    #[test]
+    fn world_clock_doesnt_drift_with_frame_time() {
+        let mut clock = WorldClock::at(1, 23, 0);
+        // An hour of frames at 60 frames per second
+        for _ in 0..60 * 60 * 60 {
+            clock.advance(&TimeDuration::from_seconds(1.0 / 60.0));
+        }
+        assert_eq!(clock.day(), 2);
+        assert_eq!(clock.hour(), 0);
+        assert_eq!(clock.minute(), 0);
+        // A frame time of 1/60 s is slightly more than that in single
+        // precision, but the clock is still within a millisecond of midnight
+        assert!(clock.seconds_of_day < 0.001);
+    }
+
     #[test]
     fn time_duration_as_seconds() {
         assert_eq!(TimeDuration::from_seconds(2.5).as_seconds(), 2.5);
@@ -217,4 +339,41 @@ mod tests {
         });
         assert_eq!(clock.local_time_seconds(), 1.0 + TURN_DURATION_SECONDS);
     }
+
+    #[test]
+    fn time_duration_calendar_units() {
+        assert_eq!(
+            TimeDuration::from_minutes(1).as_turns(),
+            TimeDuration::from_rounds(10).as_turns()
+        );
+        assert_eq!(TimeDuration::from_hours(1).as_seconds(), 3600.0);
+        assert_eq!(TimeDuration::from_days(1).as_seconds(), 86400.0);
+    }
+
+    #[test]
+    fn world_clock_advance() {
+        let mut clock = WorldClock::new();
+        assert_eq!(clock.to_string(), "Day 1, 06:00:00");
+
+        // Passing dawn exactly doesn't count the dawn we started at
+        assert_eq!(clock.advance(&TimeDuration::from_minutes(90)), 0);
+        assert_eq!(clock.to_string(), "Day 1, 07:30:00");
+
+        assert_eq!(clock.advance(&TimeDuration::from_hours(17)), 0);
+        assert_eq!(clock.to_string(), "Day 2, 00:30:00");
+
+        // A long rest through the night ends after dawn
+        assert_eq!(clock.advance(&TimeDuration::from_hours(8)), 1);
+        assert_eq!((clock.day(), clock.hour(), clock.minute()), (2, 8, 30));
+
+        assert_eq!(clock.advance(&TimeDuration::from_days(3)), 3);
+        assert_eq!(clock.day(), 5);
+
+        // Rounds of combat also move the clock
+        let mut clock = WorldClock::at(1, 5, 59);
+        for _ in 0..10 {
+            clock.advance(&TimeDuration::from_rounds(1));
+        }
+        assert_eq!((clock.hour(), clock.minute()), (6, 0));
+    }
 }
@@ -0,0 +1,209 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::components::{id::ItemId, items::money::MonetaryValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationKind {
+    Town,
+    Dungeon,
+    Wilderness,
+}
+
+/// Something the party can make use of at a location
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Service {
+    /// Sells the items at their listed value
+    Shop { stock: Vec<ItemId> },
+    /// A safe place for the whole party to take a long rest, for a price
+    Inn { price: MonetaryValue },
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncounterTableEntry {
+    /// Name of an encounter in the adventure
+    pub encounter: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+/// Rolled whenever the party arrives at the location, and for every hour the
+/// party spends camping there
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EncounterTable {
+    /// Chance for an encounter to happen on arrival, between 0 and 1
+    pub chance: f32,
+    /// Chance for an encounter to happen during each hour of rest
+    #[serde(default)]
+    pub rest_chance: f32,
+    pub entries: Vec<EncounterTableEntry>,
+}
+
+impl EncounterTable {
+    pub fn roll(&self) -> Option<&str> {
+        self.roll_with_chance(self.chance)
+    }
+
+    pub fn roll_during_rest(&self) -> Option<&str> {
+        self.roll_with_chance(self.rest_chance)
+    }
+
+    fn roll_with_chance(&self, chance: f32) -> Option<&str> {
+        let mut rng = rand::rng();
+        let total = self.entries.iter().map(|entry| entry.weight).sum::<u32>();
+        if total == 0 || rng.random::<f32>() >= chance {
+            return None;
+        }
+
+        let mut roll = rng.random_range(0..total);
+        for entry in &self.entries {
+            if roll < entry.weight {
+                return Some(&entry.encounter);
+            }
+            roll -= entry.weight;
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// Unique within the world map
+    pub id: String,
+    pub name: String,
+    pub kind: LocationKind,
+    #[serde(default)]
+    pub description: String,
+    /// Where the location is drawn on the overworld map
+    pub position: [f32; 2],
+    /// Name of the map in the adventure where the location is played out
+    #[serde(default)]
+    pub map: Option<String>,
+    #[serde(default)]
+    pub encounters: EncounterTable,
+    #[serde(default)]
+    pub services: Vec<Service>,
+}
+
+impl Location {
+    pub fn inn_price(&self) -> Option<&MonetaryValue> {
+        self.services.iter().find_map(|service| match service {
+            Service::Inn { price } => Some(price),
+            _ => None,
+        })
+    }
+
+    pub fn shop_stock(&self) -> impl Iterator<Item = &ItemId> {
+        self.services.iter().flat_map(|service| match service {
+            Service::Shop { stock } => stock.as_slice(),
+            _ => &[],
+        })
+    }
+}
+
+/// A road or path between two locations, which can be travelled both ways
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    pub from: String,
+    pub to: String,
+    pub hours: u32,
+}
+
+impl Route {
+    /// The other end of the route, if it starts or ends at the location
+    pub fn other_end(&self, location: &str) -> Option<&str> {
+        if self.from == location {
+            Some(&self.to)
+        } else if self.to == location {
+            Some(&self.from)
+        } else {
+            None
+        }
+    }
+}
+
+/// The locations of an adventure and the routes between them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorldMap {
+    /// Where the party starts. Defaults to the first location.
+    #[serde(default)]
+    pub start: Option<String>,
+    pub locations: Vec<Location>,
+    pub routes: Vec<Route>,
+}
+
+impl WorldMap {
+    pub fn location(&self, id: &str) -> Option<&Location> {
+        self.locations.iter().find(|location| location.id == id)
+    }
+
+    pub fn start(&self) -> Option<&Location> {
+        match &self.start {
+            Some(start) => self.location(start),
+            None => self.locations.first(),
+        }
+    }
+
+    /// The locations which can be reached directly from the location, along
+    /// with how long it takes to get there
+    pub fn neighbours(&self, location: &str) -> Vec<(&Location, u32)> {
+        self.routes
+            .iter()
+            .filter_map(|route| {
+                let other = self.location(route.other_end(location)?)?;
+                Some((other, route.hours))
+            })
+            .collect()
+    }
+
+    /// The quickest way from one location to another, as the locations passed
+    /// through on the way (not including the starting point) and how many
+    /// hours each leg takes
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<(&Location, u32)>> {
+        let mut best: HashMap<&str, (u32, Option<(&str, u32)>)> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        best.insert(from, (0, None));
+        queue.push(Reverse((0, from)));
+
+        while let Some(Reverse((hours, location))) = queue.pop() {
+            if location == to {
+                break;
+            }
+            if best.get(location).is_some_and(|(best, _)| hours > *best) {
+                continue;
+            }
+            for (neighbour, leg) in self.neighbours(location) {
+                let total = hours + leg;
+                if best
+                    .get(neighbour.id.as_str())
+                    .is_none_or(|(best, _)| total < *best)
+                {
+                    best.insert(neighbour.id.as_str(), (total, Some((location, leg))));
+                    queue.push(Reverse((total, neighbour.id.as_str())));
+                }
+            }
+        }
+
+        best.get(to)?;
+        let mut path = Vec::new();
+        let mut current = to;
+        while let Some((_, Some((previous, leg)))) = best.get(current) {
+            path.push((self.location(current)?, *leg));
+            current = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
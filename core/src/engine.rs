@@ -3,3 +3,4 @@ pub mod event;
 pub mod game_state;
 pub mod geometry;
 pub mod interaction;
+pub mod rules;
@@ -1,4 +1,7 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use hecs::{Entity, World};
 use uuid::Uuid;
@@ -6,12 +9,17 @@ use uuid::Uuid;
 use crate::{
     components::{
         actions::targeting::EntityFilter,
-        d20::{D20CheckDC, D20CheckResult},
+        d20::{D20Check, D20CheckDC, D20CheckResult},
+        dice::RollPolicy,
         health::life_state::{DEATH_SAVING_THROW_DC, LifeState},
+        id::MonsterId,
+        items::equipment::{loadout::Loadout, slots::EquipmentSlot, weapon::WeaponProperties},
         modifier::{ModifierSet, ModifierSource},
+        proficiency::{Proficiency, ProficiencyLevel},
         saving_throw::SavingThrowKind,
         skill::{Skill, SkillSet},
-        time::{TimeStep, TurnBoundary},
+        species::CreatureSize,
+        time::{TimeDuration, TimeStep, TurnBoundary},
     },
     engine::{
         event::{
@@ -20,13 +28,37 @@ use crate::{
         },
         game_state::GameState,
         interaction::InteractionScopeId,
+        rules::InitiativeMode,
+    },
+    entities::{
+        character::CharacterTag, ground_item::GroundItemTag, monster::MonsterTag, object::ObjectTag,
     },
-    entities::{character::CharacterTag, monster::MonsterTag},
     systems::{self, d20::D20CheckDCKind},
 };
 
 pub type EncounterId = Uuid;
 
+/// Groups of at least this many identical monsters fight as a single mob
+pub const MOB_SIZE_MIN: usize = 6;
+
+/// A group of identical monsters sharing a single initiative roll. The members
+/// take their turns back to back and act together, see `systems::mob`, but
+/// still keep track of their own hit points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mob {
+    pub monster: MonsterId,
+    pub members: Vec<Entity>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitiativeError {
+    /// Only popcorn initiative lets creatures pick who goes next
+    NotPopcorn,
+    NotParticipant(Entity),
+    /// The creature has already taken its turn this round
+    AlreadyActed(Entity),
+}
+
 #[derive(Debug)]
 pub struct Encounter {
     id: EncounterId,
@@ -34,6 +66,12 @@ pub struct Encounter {
     round: usize,
     turn_index: usize,
     initiative_order: Vec<(Entity, D20CheckResult)>,
+    mode: InitiativeMode,
+    /// The creature picked to take the next turn with popcorn initiative
+    next_actor: Option<Entity>,
+    /// How the NPCs of the encounter roll their dice
+    roll_policy: RollPolicy,
+    mobs: Vec<Mob>,
     event_log: EventLog,
 }
 
@@ -45,8 +83,15 @@ impl Encounter {
             round: 1,
             turn_index: 0,
             initiative_order: Vec::new(),
+            mode: game_state.initiative_mode,
+            next_actor: None,
+            roll_policy: game_state.npc_roll_policy,
+            mobs: Vec::new(),
             event_log: EventLog::new(),
         };
+        for entity in encounter.participants.clone() {
+            encounter.apply_roll_policy(&mut game_state.world, entity);
+        }
         encounter.roll_initiative(&game_state.world);
         encounter.start_turn(game_state);
         encounter
@@ -59,24 +104,125 @@ impl Encounter {
     }
 
     fn roll_initiative(&mut self, world: &World) {
-        let mut indexed_rolls: Vec<(Entity, D20CheckResult)> = self
-            .participants
-            .iter()
-            .map(|entity| {
-                let roll = systems::helpers::get_component::<SkillSet>(world, *entity).check(
-                    &Skill::Initiative,
-                    world,
-                    *entity,
-                );
-                (entity.clone(), roll)
-            })
-            .collect();
+        self.mobs = form_mobs(world, &self.participants);
+
+        let mut indexed_rolls: Vec<(Entity, D20CheckResult)> = Vec::new();
+        for entity in &self.participants {
+            let mob = self.mob(*entity);
+            // The whole mob rolls once, when its first member is reached
+            if mob.is_some_and(|mob| mob.members[0] != *entity) {
+                continue;
+            }
+
+            let roll = self.roll(world, *entity);
+            match mob {
+                Some(mob) => indexed_rolls.extend(
+                    mob.members
+                        .iter()
+                        .map(|member| (member.clone(), roll.clone())),
+                ),
+                None => indexed_rolls.push((entity.clone(), roll)),
+            }
+        }
 
         indexed_rolls.sort_by_key(|(_, roll)| -(roll.total() as i32));
-        self.initiative_order = indexed_rolls
-            .into_iter()
-            .map(|(i, roll)| (i, roll))
-            .collect();
+        if self.mode == InitiativeMode::Side {
+            indexed_rolls = order_by_side(world, indexed_rolls);
+        }
+        self.initiative_order = indexed_rolls;
+    }
+
+    /// Roll initiative for a single creature
+    fn roll(&self, world: &World, entity: Entity) -> D20CheckResult {
+        let mut roll = systems::helpers::get_component::<SkillSet>(world, entity).check(
+            &Skill::Initiative,
+            world,
+            entity,
+        );
+        if self.mode == InitiativeMode::SpeedFactor {
+            roll.add_bonus(
+                ModifierSource::Custom("Speed Factor".to_string()),
+                speed_factor(world, entity),
+            );
+        }
+        roll
+    }
+
+    /// Add a creature to an encounter which is already running. The creature
+    /// rolls initiative and is slotted into the turn order without changing
+    /// whose turn it currently is.
+    pub(crate) fn add_participant(&mut self, world: &World, entity: Entity) {
+        if !self.participants.insert(entity) {
+            return;
+        }
+
+        // Reinforcements join the mob of their kind, if there is one
+        if let Ok(monster) = world.get::<&MonsterId>(entity)
+            && let Some(mob) = self.mobs.iter_mut().find(|mob| mob.monster == *monster)
+        {
+            let last_member = *mob.members.last().unwrap();
+            mob.members.push(entity);
+            let (index, roll) = self
+                .initiative_order
+                .iter()
+                .position(|(other, _)| *other == last_member)
+                .map(|index| (index + 1, self.initiative_order[index].1.clone()))
+                .unwrap();
+            self.initiative_order.insert(index, (entity, roll));
+            if index <= self.turn_index {
+                self.turn_index += 1;
+            }
+            return;
+        }
+
+        let party = is_party(world, entity);
+        let last_of_side = self
+            .initiative_order
+            .iter()
+            .rposition(|(other, _)| is_party(world, *other) == party);
+        let (index, roll) = match last_of_side {
+            // Latecomers take their turn along with the rest of their side
+            Some(index) if self.mode == InitiativeMode::Side => {
+                (index + 1, self.initiative_order[index].1.clone())
+            }
+            _ => {
+                let roll = self.roll(world, entity);
+                let index = self
+                    .initiative_order
+                    .iter()
+                    .position(|(_, other)| other.total() < roll.total())
+                    .unwrap_or(self.initiative_order.len());
+                (index, roll)
+            }
+        };
+        self.initiative_order.insert(index, (entity, roll));
+
+        if index <= self.turn_index {
+            self.turn_index += 1;
+        }
+    }
+
+    pub fn roll_policy(&self) -> RollPolicy {
+        self.roll_policy
+    }
+
+    /// Change how the NPCs of the encounter roll their dice, see
+    /// `GameState::set_roll_policy`
+    pub(crate) fn set_roll_policy(&mut self, world: &mut World, policy: RollPolicy) {
+        self.roll_policy = policy;
+        for entity in self.participants.clone() {
+            self.apply_roll_policy(world, entity);
+        }
+    }
+
+    /// The party always rolls their own dice, so the roll policy only applies
+    /// to everyone else
+    pub(crate) fn apply_roll_policy(&self, world: &mut World, entity: Entity) {
+        if self.roll_policy.is_random() || is_party(world, entity) {
+            let _ = world.remove_one::<RollPolicy>(entity);
+        } else {
+            let _ = world.insert_one(entity, self.roll_policy);
+        }
     }
 
     pub fn id(&self) -> &EncounterId {
@@ -87,11 +233,58 @@ impl Encounter {
         &self.initiative_order
     }
 
+    pub fn initiative_mode(&self) -> InitiativeMode {
+        self.mode
+    }
+
+    /// Pick who takes the next turn with popcorn initiative. Only creatures
+    /// which haven't acted yet this round can be picked, except at the end of
+    /// the round, where anyone can be picked to start the next one. Members of
+    /// a mob are picked together.
+    pub fn choose_next(&mut self, entity: Entity) -> Result<(), InitiativeError> {
+        if self.mode != InitiativeMode::Popcorn {
+            return Err(InitiativeError::NotPopcorn);
+        }
+        let index = self
+            .initiative_order
+            .iter()
+            .position(|(other, _)| *other == entity)
+            .ok_or(InitiativeError::NotParticipant(entity))?;
+        let round_over = self.turn_index + 1 == self.initiative_order.len();
+        if !round_over && index <= self.turn_index {
+            return Err(InitiativeError::AlreadyActed(entity));
+        }
+        self.next_actor = Some(entity);
+        Ok(())
+    }
+
     pub fn current_entity(&self) -> Entity {
         let (idx, _) = self.initiative_order[self.turn_index];
         idx
     }
 
+    pub fn mobs(&self) -> &[Mob] {
+        &self.mobs
+    }
+
+    /// The mob the entity is a member of, if any
+    pub fn mob(&self, entity: Entity) -> Option<&Mob> {
+        self.mobs.iter().find(|mob| mob.members.contains(&entity))
+    }
+
+    /// The members of the mob of the current entity whose turns are up next,
+    /// starting with the current entity, in initiative order
+    pub fn mob_turns(&self) -> Vec<Entity> {
+        let Some(mob) = self.mob(self.current_entity()) else {
+            return Vec::new();
+        };
+        self.initiative_order[self.turn_index..]
+            .iter()
+            .map(|(entity, _)| *entity)
+            .take_while(|entity| mob.members.contains(entity))
+            .collect()
+    }
+
     pub fn participants(&self, world: &World, filter: EntityFilter) -> Vec<Entity> {
         match filter {
             EntityFilter::All => self.participants.iter().cloned().collect(),
@@ -108,6 +301,14 @@ impl Encounter {
                 .map(|(e, _)| e)
                 .collect(),
 
+            EntityFilter::Objects => world.query::<&ObjectTag>().iter().map(|(e, _)| e).collect(),
+
+            EntityFilter::GroundItems => world
+                .query::<&GroundItemTag>()
+                .iter()
+                .map(|(e, _)| e)
+                .collect(),
+
             EntityFilter::Specific(entities) => {
                 self.participants.intersection(&entities).cloned().collect()
             }
@@ -135,16 +336,29 @@ impl Encounter {
                     }
                 })
                 .collect(),
+
+            EntityFilter::MaxSize(_)
+            | EntityFilter::CreatureType(_)
+            | EntityFilter::Dying
+            | EntityFilter::Locked
+            | EntityFilter::Levers => self
+                .participants
+                .iter()
+                .filter(|entity| filter.matches(world, entity))
+                .cloned()
+                .collect(),
         }
     }
 
     fn start_turn(&mut self, game_state: &mut GameState) {
         self.advance_time(game_state, TurnBoundary::Start);
+        systems::surfaces::on_turn_start(game_state, self.current_entity());
 
         if self.should_skip_turn(game_state) {
             self.end_turn(game_state, self.current_entity());
             return;
         }
+        systems::control::on_turn_start(game_state, self.current_entity());
 
         let session = game_state
             .interaction_engine
@@ -182,9 +396,26 @@ impl Encounter {
 
         session.clear_prompts();
 
+        if let Some(next_actor) = self.next_actor.take() {
+            self.move_to_next_turn(next_actor);
+        }
+
         self.turn_index = (self.turn_index + 1) % self.participants.len();
         if self.turn_index == 0 {
+            if self.mode == InitiativeMode::SpeedFactor {
+                self.roll_initiative(&game_state.world);
+            }
             self.round += 1;
+            // Encounters happen side by side, so the world clock only moves
+            // when the encounter furthest along starts a new round
+            if game_state
+                .encounters
+                .iter()
+                .filter(|(id, _)| **id != self.id)
+                .all(|(_, encounter)| encounter.round() < self.round)
+            {
+                systems::time::advance_world_clock(game_state, &TimeDuration::from_rounds(1));
+            }
             self.event_log
                 .push(Event::encounter_event(EncounterEvent::NewRound(
                     self.id.clone(),
@@ -195,6 +426,31 @@ impl Encounter {
         self.start_turn(game_state);
     }
 
+    /// Move the creature (and the rest of its mob) so it takes the turn right
+    /// after the current one, or the first turn of the next round if this is
+    /// the last turn of the round
+    fn move_to_next_turn(&mut self, entity: Entity) {
+        let group = match self.mob(entity) {
+            Some(mob) => mob.members.clone(),
+            None => vec![entity],
+        };
+        let round_over = self.turn_index + 1 == self.initiative_order.len();
+
+        let (moved, rest): (Vec<_>, Vec<_>) = self
+            .initiative_order
+            .drain(..)
+            .partition(|(other, _)| group.contains(other));
+        self.initiative_order = rest;
+        if round_over {
+            self.initiative_order.splice(0..0, moved);
+            // The turn index wraps around to the start of the order
+            self.turn_index = self.initiative_order.len() - 1;
+        } else {
+            let index = self.turn_index + 1;
+            self.initiative_order.splice(index..index, moved);
+        }
+    }
+
     // TODO: Some of this feels like it should belong somewhere else?
     fn should_skip_turn(&mut self, game_state: &mut GameState) -> bool {
         let current_entity = self.current_entity();
@@ -222,32 +478,31 @@ impl Encounter {
                 Arc::new({
                     move |game_state, event| match &event.kind {
                         EventKind::D20CheckResolved(performer, result, dc) => {
-                            let mut life_state = systems::helpers::get_component_mut::<LifeState>(
-                                &mut game_state.world,
+                            let life_state = *systems::helpers::get_component::<LifeState>(
+                                &game_state.world,
                                 *performer,
                             );
 
-                            if let LifeState::Unconscious(ref mut death_saving_throws) = *life_state
-                            {
-                                death_saving_throws.update(result.d20_result());
-
-                                let next_state = death_saving_throws.next_state();
-
-                                if next_state != *life_state {
-                                    *life_state = next_state.clone();
+                            let LifeState::Unconscious(mut death_saving_throws) = life_state else {
+                                return CallbackResult::None;
+                            };
+                            death_saving_throws.update(result.d20_result());
 
-                                    return CallbackResult::Event(Event::new(
-                                        EventKind::LifeStateChanged {
-                                            entity: current_entity,
-                                            new_state: next_state,
-                                            actor: None,
-                                        },
-                                    ));
-                                } else {
-                                    return CallbackResult::None;
+                            match systems::health::set_life_state(
+                                &mut game_state.world,
+                                *performer,
+                                death_saving_throws.next_state(),
+                            ) {
+                                // Successes and failures accumulate while the
+                                // creature is still dying
+                                Some(LifeState::Unconscious(_)) | None => CallbackResult::None,
+                                Some(new_state) => {
+                                    CallbackResult::Event(Event::new(EventKind::LifeStateChanged {
+                                        entity: current_entity,
+                                        new_state,
+                                        actor: None,
+                                    }))
                                 }
-                            } else {
-                                return CallbackResult::None;
                             }
                         }
                         _ => panic!("Expected D20CheckResolved event"),
@@ -257,11 +512,12 @@ impl Encounter {
 
             return true;
         } else {
-            // Normal / other states => decide if they can act
+            // Normal / other states => decide if they can act. Incapacitated
+            // creatures, e.g. stunned ones, can't take actions either.
             return !matches!(
                 *systems::helpers::get_component::<LifeState>(&game_state.world, current_entity),
                 LifeState::Normal
-            );
+            ) || systems::conditions::is_incapacitated(&game_state.world, current_entity);
         };
     }
 
@@ -303,3 +559,101 @@ impl Encounter {
         std::mem::take(&mut self.event_log)
     }
 }
+
+/// The party and their allies are on one side, everyone else on the other
+fn is_party(world: &World, entity: Entity) -> bool {
+    systems::ai::is_player_controlled(world, entity) || world.get::<&CharacterTag>(entity).is_ok()
+}
+
+/// With side initiative each side rolls a single d20 without any modifiers. The
+/// members of a side keep the order of their own initiative rolls, and the
+/// party wins ties.
+fn order_by_side(
+    world: &World,
+    rolls: Vec<(Entity, D20CheckResult)>,
+) -> Vec<(Entity, D20CheckResult)> {
+    let side_roll = || {
+        D20Check::new(Proficiency::new(
+            ProficiencyLevel::None,
+            ModifierSource::None,
+        ))
+        .roll(0)
+    };
+    let party_roll = side_roll();
+    let enemy_roll = side_roll();
+
+    let (party, enemies): (Vec<_>, Vec<_>) = rolls
+        .into_iter()
+        .partition(|(entity, _)| is_party(world, *entity));
+    let party = party
+        .into_iter()
+        .map(|(entity, _)| (entity, party_roll.clone()));
+    let enemies = enemies
+        .into_iter()
+        .map(|(entity, _)| (entity, enemy_roll.clone()));
+
+    if party_roll.total() >= enemy_roll.total() {
+        party.chain(enemies).collect()
+    } else {
+        enemies.chain(party).collect()
+    }
+}
+
+/// Speed factor initiative favours small creatures and light weapons over large
+/// creatures and heavy weapons
+fn speed_factor(world: &World, entity: Entity) -> i32 {
+    let size = world
+        .get::<&CreatureSize>(entity)
+        .map(|size| match *size {
+            CreatureSize::Tiny => 5,
+            CreatureSize::Small => 2,
+            CreatureSize::Medium => 0,
+            CreatureSize::Large => -2,
+            CreatureSize::Huge => -5,
+            CreatureSize::Gargantuan => -8,
+        })
+        .unwrap_or(0);
+    let weapon = world
+        .get::<&Loadout>(entity)
+        .ok()
+        .and_then(|loadout| {
+            let weapon = loadout.weapon_in_hand(&EquipmentSlot::MeleeMainHand)?;
+            Some(if weapon.has_property(&WeaponProperties::Heavy) {
+                -2
+            } else if weapon.has_property(&WeaponProperties::Light)
+                || weapon.has_property(&WeaponProperties::Finesse)
+            {
+                2
+            } else {
+                0
+            })
+        })
+        .unwrap_or(0);
+    size + weapon
+}
+
+/// Group the participants into mobs of identical monsters. Monsters are
+/// identical if they're spawned from the same template, and player controlled
+/// creatures never join a mob.
+fn form_mobs(world: &World, participants: &HashSet<Entity>) -> Vec<Mob> {
+    let mut groups: HashMap<MonsterId, Vec<Entity>> = HashMap::new();
+    for entity in participants {
+        if systems::ai::is_player_controlled(world, *entity) {
+            continue;
+        }
+        if let Ok(monster) = world.get::<&MonsterId>(*entity) {
+            groups.entry(monster.clone()).or_default().push(*entity);
+        }
+    }
+
+    let mut mobs = groups
+        .into_iter()
+        .filter(|(_, members)| members.len() >= MOB_SIZE_MIN)
+        .map(|(monster, mut members)| {
+            members.sort();
+            Mob { monster, members }
+        })
+        .collect::<Vec<_>>();
+    mobs.sort_by_key(|mob| mob.members[0]);
+    mobs
+}
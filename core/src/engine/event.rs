@@ -3,7 +3,8 @@ use std::{
     sync::Arc,
 };
 
-use hecs::Entity;
+use hecs::{Entity, World};
+use parry3d::na::Point3;
 use uuid::Uuid;
 
 use crate::{
@@ -13,14 +14,18 @@ use crate::{
             targeting::TargetInstance,
         },
         damage::DamageRollResult,
-        health::life_state::LifeState,
-        id::ActionId,
+        health::{life_state::LifeState, massive_damage::MassiveDamageOutcome},
+        hidden::HiddenFeatureKind,
+        id::{ActionId, ItemId},
+        lock::UnlockMethod,
         resource::{ResourceAmountMap, ResourceError},
     },
-    engine::{encounter::EncounterId, game_state::GameState},
+    engine::{encounter::EncounterId, game_state::GameState, rules::OptionalRule},
     systems::{
+        self,
         actions::ActionUsabilityError,
         d20::{D20CheckDCKind, D20ResultKind},
+        mob::MobAttack,
         time::RestKind,
     },
 };
@@ -66,33 +71,29 @@ impl Event {
                     Some(*entity)
                 }
             }
+            EventKind::MassiveDamage { entity, .. } => Some(*entity),
             EventKind::D20CheckPerformed(entity, _, _) => Some(*entity),
             EventKind::D20CheckResolved(entity, _, _) => Some(*entity),
-            EventKind::DamageRollPerformed(entity, _) => Some(*entity),
+            EventKind::DamageRollPerformed(entity, _, _) => Some(*entity),
             EventKind::DamageRollResolved(entity, _) => Some(*entity),
             EventKind::Encounter(_) => None,
             // TODO: Same problem as ReactionTriggered
             EventKind::RestStarted { participants, .. } => Some(*participants.first()?),
             EventKind::RestFinished { participants, .. } => Some(*participants.first()?),
+            EventKind::RestInterrupted { participants, .. } => Some(*participants.first()?),
+            EventKind::LockOpened { target, actor, .. } => Some(actor.unwrap_or(*target)),
+            EventKind::HiddenFeatureRevealed { feature, by, .. } => Some(by.unwrap_or(*feature)),
+            EventKind::IllusionDisbelieved { by, .. } => Some(*by),
+            EventKind::ItemDropped { entity, .. } | EventKind::ItemPickedUp { entity, .. } => {
+                Some(*entity)
+            }
         }
     }
 
     pub fn target(&self) -> Option<Entity> {
         match &self.kind {
-            EventKind::ActionRequested { action } => {
-                if let Some(TargetInstance::Entity(target)) = action.targets.first() {
-                    Some(*target)
-                } else {
-                    None
-                }
-            }
-            EventKind::ActionPerformed { action, .. } => {
-                if let Some(TargetInstance::Entity(target)) = action.targets.first() {
-                    Some(*target)
-                } else {
-                    None
-                }
-            }
+            EventKind::ActionRequested { action } => action.targets.first()?.entity(),
+            EventKind::ActionPerformed { action, .. } => action.targets.first()?.entity(),
             _ => None,
         }
     }
@@ -105,7 +106,13 @@ impl Event {
         let results = results
             .into_iter()
             .map(|(entity, result)| {
-                ActionResult::new(&game_state.world, action_data.actor, entity, result)
+                ActionResult::new(
+                    &game_state.world,
+                    action_data.actor,
+                    entity,
+                    action_data.point_affecting(&game_state.world, entity),
+                    result,
+                )
             })
             .collect();
         Event::new(EventKind::ActionPerformed {
@@ -147,11 +154,19 @@ pub enum EventKind {
         /// The entity that caused the change, if any
         actor: Option<Entity>,
     },
+    /// The entity took enough damage at once to kill it outright or to send
+    /// it into shock
+    MassiveDamage {
+        entity: Entity,
+        outcome: MassiveDamageOutcome,
+    },
     /// The initial D20 roll which can be reacted to, e.g. with the Lucky feat.
     D20CheckPerformed(Entity, D20ResultKind, D20CheckDCKind),
     /// The final result of a D20 check after reactions have been applied.
     D20CheckResolved(Entity, D20ResultKind, D20CheckDCKind),
-    DamageRollPerformed(Entity, DamageRollResult),
+    /// Damage rolled by an entity against a target, which can still be reacted
+    /// to before it's applied, e.g. with Divine Smite after an attack hits.
+    DamageRollPerformed(Entity, DamageRollResult, Entity),
     DamageRollResolved(Entity, DamageRollResult),
 
     RestStarted {
@@ -162,6 +177,43 @@ pub enum EventKind {
         kind: RestKind,
         participants: Vec<Entity>,
     },
+    /// The participants were woken before their rest was over
+    RestInterrupted {
+        kind: RestKind,
+        participants: Vec<Entity>,
+        /// How long they managed to rest for
+        hours: u32,
+    },
+    /// The lock on a door or container was opened
+    LockOpened {
+        target: Entity,
+        method: UnlockMethod,
+        /// The entity that opened the lock, if any
+        actor: Option<Entity>,
+    },
+    /// A secret door, hidden lever or clue was found
+    HiddenFeatureRevealed {
+        feature: Entity,
+        kind: HiddenFeatureKind,
+        /// The entity that found it, if anyone
+        by: Option<Entity>,
+    },
+    /// A creature saw through an illusion, which is only fake to that creature
+    IllusionDisbelieved {
+        illusion: Entity,
+        by: Entity,
+    },
+    /// An item was put down on the ground
+    ItemDropped {
+        entity: Entity,
+        item: ItemId,
+        ground_item: Entity,
+    },
+    /// An item lying on the ground was picked up
+    ItemPickedUp {
+        entity: Entity,
+        item: ItemId,
+    },
 }
 
 impl EventKind {
@@ -173,12 +225,19 @@ impl EventKind {
             EventKind::ReactionTriggered { .. } => "ReactionTriggered",
             EventKind::ReactionRequested { .. } => "ReactionRequested",
             EventKind::LifeStateChanged { .. } => "LifeStateChanged",
+            EventKind::MassiveDamage { .. } => "MassiveDamage",
             EventKind::D20CheckPerformed(_, _, _) => "D20CheckPerformed",
             EventKind::D20CheckResolved(_, _, _) => "D20CheckResolved",
-            EventKind::DamageRollPerformed(_, _) => "DamageRollPerformed",
+            EventKind::DamageRollPerformed(_, _, _) => "DamageRollPerformed",
             EventKind::DamageRollResolved(_, _) => "DamageRollResolved",
             EventKind::RestStarted { .. } => "RestStarted",
             EventKind::RestFinished { .. } => "RestFinished",
+            EventKind::RestInterrupted { .. } => "RestInterrupted",
+            EventKind::LockOpened { .. } => "LockOpened",
+            EventKind::HiddenFeatureRevealed { .. } => "HiddenFeatureRevealed",
+            EventKind::IllusionDisbelieved { .. } => "IllusionDisbelieved",
+            EventKind::ItemDropped { .. } => "ItemDropped",
+            EventKind::ItemPickedUp { .. } => "ItemPickedUp",
         }
     }
 }
@@ -188,6 +247,7 @@ pub enum EncounterEvent {
     EncounterStarted(EncounterId),
     EncounterEnded(EncounterId, EventLog),
     NewRound(EncounterId, usize),
+    MobAttack(EncounterId, MobAttack),
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -262,15 +322,34 @@ impl ActionData {
     pub fn entity_targets(&self) -> Vec<Entity> {
         self.targets
             .iter()
-            .filter_map(|t| {
-                if let TargetInstance::Entity(e) = t {
-                    Some(*e)
-                } else {
-                    None
-                }
+            .filter_map(TargetInstance::entity)
+            .collect()
+    }
+
+    pub fn point_targets(&self) -> Vec<Point3<f32>> {
+        self.targets
+            .iter()
+            .filter_map(|target| match target {
+                TargetInstance::Point(point) => Some(*point),
+                _ => None,
             })
             .collect()
     }
+
+    /// The point the action was aimed at to affect the entity, unless the entity
+    /// was targeted directly. For actions aimed at several points, e.g. an area
+    /// placed more than once, this is the point closest to the entity.
+    pub fn point_affecting(&self, world: &World, entity: Entity) -> Option<Point3<f32>> {
+        if self.entity_targets().contains(&entity) {
+            return None;
+        }
+        let position = systems::geometry::get_foot_position(world, entity);
+        let distance =
+            |point: &Point3<f32>| position.map_or(0.0, |position| (position - point).norm());
+        self.point_targets()
+            .into_iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -331,9 +410,34 @@ pub type EventCallback =
 pub enum CallbackResult {
     Event(Event),
     EventWithCallback(Event, EventCallback),
+    /// Pause the resolution until an entity has made a decision. The callback
+    /// is called with the index of the chosen option.
+    Decision(DecisionRequest, DecisionCallback),
     None,
 }
 
+pub type DecisionCallback =
+    Arc<dyn Fn(&mut GameState, usize) -> CallbackResult + Send + Sync + 'static>;
+
+/// A choice an entity has to make in the middle of resolving an action, e.g.
+/// whether to spend a spell slot on a hit
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionRequest {
+    pub chooser: Entity,
+    pub description: String,
+    pub options: Vec<String>,
+}
+
+impl DecisionRequest {
+    pub fn new(chooser: Entity, description: impl Into<String>, options: Vec<String>) -> Self {
+        Self {
+            chooser,
+            description: description.into(),
+            options,
+        }
+    }
+}
+
 impl EventListener {
     pub fn new(trigger_id: EventId, callback: EventCallback) -> Self {
         Self {
@@ -357,15 +461,7 @@ impl EventListener {
 
     pub fn callback(&self, game_state: &mut GameState, event: &Event) {
         let result = (self.callback)(game_state, event);
-        match result {
-            CallbackResult::Event(event) => {
-                game_state.process_event(event);
-            }
-            CallbackResult::EventWithCallback(event, callback) => {
-                game_state.process_event_with_callback(event, callback);
-            }
-            CallbackResult::None => {}
-        }
+        game_state.process_callback_result(result);
     }
 }
 
@@ -389,6 +485,9 @@ pub enum ActionPromptKind {
         /// is reaction, and the value is their options
         options: HashMap<Entity, Vec<ReactionData>>,
     },
+    /// Prompt an entity to make a choice before the resolution of an action
+    /// can continue
+    Decision { request: DecisionRequest },
 }
 
 impl ActionPromptKind {
@@ -396,6 +495,7 @@ impl ActionPromptKind {
         match self {
             ActionPromptKind::Action { actor } => vec![*actor],
             ActionPromptKind::Reactions { options, .. } => options.keys().cloned().collect(),
+            ActionPromptKind::Decision { request } => vec![request.chooser],
         }
     }
 }
@@ -427,6 +527,11 @@ pub enum ActionDecisionKind {
         /// The chosen reaction. None if the entity chooses not to react
         choice: Option<ReactionData>,
     },
+    Decision {
+        chooser: Entity,
+        /// The index of the chosen option
+        option: usize,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -457,6 +562,11 @@ pub enum ActionError {
     },
     Usability(ActionUsabilityError),
     Resource(ResourceError),
+    /// The action belongs to an optional rule which isn't enabled
+    RuleDisabled(OptionalRule),
+    /// Rituals take ten minutes to cast, which can't happen while anyone is in
+    /// combat
+    RitualInCombat,
 }
 
 macro_rules! ensure_equal {
@@ -475,10 +585,7 @@ macro_rules! ensure_equal {
 
 impl ActionPrompt {
     pub fn actors(&self) -> Vec<Entity> {
-        match &self.kind {
-            ActionPromptKind::Action { actor, .. } => vec![*actor],
-            ActionPromptKind::Reactions { options, .. } => options.keys().cloned().collect(),
-        }
+        self.kind.actors()
     }
 
     pub fn is_valid_decision(&self, decision: &ActionDecision) -> Result<(), ActionError> {
@@ -551,6 +658,30 @@ impl ActionPrompt {
                 }
             }
 
+            (
+                ActionPromptKind::Decision { request },
+                ActionDecisionKind::Decision { chooser, option },
+            ) => {
+                ensure_equal!(
+                    request.chooser,
+                    *chooser,
+                    "chooser",
+                    FieldMismatch,
+                    self,
+                    decision
+                );
+
+                if *option >= request.options.len() {
+                    return Err(ActionError::FieldMismatch {
+                        field: "option",
+                        expected: format!("one of {:?}", request.options),
+                        actual: format!("{}", option),
+                        prompt: self.clone(),
+                        decision: decision.clone(),
+                    });
+                }
+            }
+
             _ => {
                 return Err(ActionError::PromptDecisionMismatch {
                     prompt: self.clone(),
@@ -567,6 +698,7 @@ impl ActionDecisionKind {
         match self {
             ActionDecisionKind::Action { action, .. } => action.actor,
             ActionDecisionKind::Reaction { reactor, .. } => *reactor,
+            ActionDecisionKind::Decision { chooser, .. } => *chooser,
         }
     }
 }
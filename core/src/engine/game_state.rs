@@ -1,33 +1,48 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::mpsc::{self, Receiver, Sender},
+};
 
 use hecs::{Entity, World};
 use parry3d::{na::Point3, shape::Ball};
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
 use crate::{
     components::{
         actions::{
-            action::{ActionKindResult, ReactionResult},
+            action::{ActionKindResult, ActionMap, ReactionResult},
+            resolution::Cover,
             targeting::EntityFilter,
         },
-        time::{EntityClock, TimeMode, TimeStep},
+        campaign::CampaignState,
+        dice::RollPolicy,
+        id::ActionId,
+        modifier::{Modifiable, ModifierSource},
+        spells::spell::MaterialConsumption,
+        surface::Surface,
+        time::{EntityClock, TimeDuration, TimeMode, TimeStep, WorldClock},
     },
     engine::{
         encounter::{Encounter, EncounterId},
         event::{
             ActionData, ActionDecision, ActionDecisionKind, ActionError, ActionPrompt,
-            ActionPromptId, ActionPromptKind, EncounterEvent, Event, EventCallback, EventId,
-            EventKind, EventListener, EventLog, ReactionData,
+            ActionPromptId, ActionPromptKind, CallbackResult, DecisionCallback, DecisionRequest,
+            EncounterEvent, Event, EventCallback, EventId, EventKind, EventListener, EventLog,
+            ReactionData,
         },
         game_state,
         geometry::WorldGeometry,
         interaction::{InteractionEngine, InteractionScopeId, InteractionSession},
+        rules::{InitiativeMode, OptionalRule},
     },
     systems::{
         self,
+        composite::CompositeResolution,
         d20::D20CheckDCKind,
+        mob::MobResolution,
         movement::{MovementError, PathResult},
-        time::RestKind,
+        plan::{PlanError, TurnPlan},
+        time::{RestKind, TimeActivity},
     },
 };
 
@@ -35,13 +50,33 @@ use crate::{
 pub struct GameState {
     pub world: World,
     pub geometry: WorldGeometry,
+    /// Surfaces covering the ground, e.g. fire or grease
+    pub surfaces: Vec<Surface>,
 
     pub encounters: HashMap<EncounterId, Encounter>,
     pub in_combat: HashMap<Entity, EncounterId>,
     pub resting: HashMap<Entity, RestKind>,
+    pub clock: WorldClock,
+    /// Progress through the adventure being played, if any
+    pub campaign: Option<CampaignState>,
+    pub optional_rules: HashSet<OptionalRule>,
+    /// How new encounters decide their turn order
+    pub initiative_mode: InitiativeMode,
+    /// How the NPCs of new encounters roll their dice
+    pub npc_roll_policy: RollPolicy,
     pub interaction_engine: InteractionEngine,
     pub event_log: EventLog,
     event_listeners: HashMap<EventId, EventListener>,
+    /// Continuations of actions which are waiting for a decision prompt to be
+    /// answered
+    decision_callbacks: HashMap<ActionPromptId, DecisionCallback>,
+    /// Composite actions which are still performing their steps, innermost last
+    pub(crate) composite_actions: Vec<CompositeResolution>,
+    /// The mob attack currently being made, if any
+    pub(crate) mob_attack: Option<MobResolution>,
+    /// Receive a copy of every event as it is logged, e.g. so the GUI can react
+    /// to combat events without digging through the logs every frame.
+    event_subscribers: Vec<Sender<Event>>,
 }
 
 impl GameState {
@@ -49,12 +84,22 @@ impl GameState {
         Self {
             world: World::new(),
             geometry,
+            surfaces: Vec::new(),
             encounters: HashMap::new(),
             in_combat: HashMap::new(),
             resting: HashMap::new(),
+            clock: WorldClock::new(),
+            campaign: None,
+            optional_rules: HashSet::new(),
+            initiative_mode: InitiativeMode::default(),
+            npc_roll_policy: RollPolicy::default(),
             interaction_engine: InteractionEngine::default(),
             event_log: EventLog::new(),
             event_listeners: HashMap::new(),
+            decision_callbacks: HashMap::new(),
+            composite_actions: Vec::new(),
+            mob_attack: None,
+            event_subscribers: Vec::new(),
         }
     }
 
@@ -72,6 +117,7 @@ impl GameState {
                     encounter_id: Some(encounter_id.clone()),
                 },
             );
+            systems::time::anchor_effects_to_encounter(&mut self.world, *entity, &participants);
         }
 
         self.event_log
@@ -89,6 +135,69 @@ impl GameState {
         self.start_encounter_with_id(participants, EncounterId::new_v4())
     }
 
+    /// Add an entity to an encounter which has already started, e.g. when
+    /// reinforcements arrive in the middle of a fight.
+    pub fn add_to_encounter(&mut self, encounter_id: &EncounterId, entity: Entity) {
+        let Some(encounter) = self.encounters.get_mut(encounter_id) else {
+            warn!(
+                "Cannot add {:?} to encounter {}: encounter not found",
+                entity, encounter_id
+            );
+            return;
+        };
+
+        encounter.apply_roll_policy(&mut self.world, entity);
+        encounter.add_participant(&self.world, entity);
+        let participants = encounter
+            .participants(&self.world, EntityFilter::All)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        self.in_combat.insert(entity, encounter_id.clone());
+        systems::time::set_time_mode(
+            &mut self.world,
+            entity,
+            TimeMode::TurnBased {
+                encounter_id: Some(encounter_id.clone()),
+            },
+        );
+        systems::time::anchor_effects_to_encounter(&mut self.world, entity, &participants);
+    }
+
+    pub fn rule_enabled(&self, rule: OptionalRule) -> bool {
+        self.optional_rules.contains(&rule)
+    }
+
+    pub fn set_rule(&mut self, rule: OptionalRule, enabled: bool) {
+        if enabled {
+            self.optional_rules.insert(rule);
+        } else {
+            self.optional_rules.remove(&rule);
+        }
+    }
+
+    /// Actions which belong to an optional rule, e.g. called shots, can only be
+    /// used while the rule is enabled
+    pub fn action_allowed(&self, action_id: &ActionId) -> bool {
+        systems::actions::required_rule(action_id).is_none_or(|rule| self.rule_enabled(rule))
+    }
+
+    /// All the actions of the entity, except the ones belonging to optional
+    /// rules which aren't enabled
+    pub fn actions(&self, entity: Entity) -> ActionMap {
+        let mut actions = systems::actions::all_actions(&self.world, entity);
+        actions.retain(|action_id, _| self.action_allowed(action_id));
+        actions
+    }
+
+    /// Let the NPCs of an encounter use static rolls instead of rolling their
+    /// dice, e.g. to speed up a large fight
+    pub fn set_roll_policy(&mut self, encounter_id: &EncounterId, policy: RollPolicy) {
+        if let Some(encounter) = self.encounters.get_mut(encounter_id) {
+            encounter.set_roll_policy(&mut self.world, policy);
+        }
+    }
+
     pub fn encounter(&self, encounter_id: &EncounterId) -> Option<&Encounter> {
         self.encounters.get(encounter_id)
     }
@@ -106,6 +215,7 @@ impl GameState {
             for entity in encounter.participants(&self.world, EntityFilter::All) {
                 self.in_combat.remove(&entity);
                 systems::time::set_time_mode(&mut self.world, entity, TimeMode::RealTime);
+                let _ = self.world.remove_one::<RollPolicy>(entity);
             }
             self.event_log
                 .push(Event::encounter_event(EncounterEvent::EncounterEnded(
@@ -138,6 +248,8 @@ impl GameState {
 
         if let Some(encounter) = encounter {
             encounter.end_turn(self, entity);
+            // Ending the turn clears the prompts, including any decisions
+            self.prune_decision_callbacks();
         }
     }
 
@@ -165,6 +277,12 @@ impl GameState {
         )
     }
 
+    /// Validate a plan for the turn of an entity as a whole, and execute it if
+    /// all of its steps are valid
+    pub fn submit_plan(&mut self, plan: TurnPlan) -> Result<(), Vec<PlanError>> {
+        systems::plan::execute(self, plan)
+    }
+
     fn scope_for_entity(&self, entity: Entity) -> InteractionScopeId {
         if let Some(id) = self.in_combat.get(&entity) {
             InteractionScopeId::Encounter(*id)
@@ -302,6 +420,13 @@ impl GameState {
                         }),
                     )?;
                 }
+
+                ActionDecisionKind::Decision { option, .. } => {
+                    if let Some(callback) = self.decision_callbacks.remove(&prompt_id) {
+                        let result = callback(self, *option);
+                        self.process_callback_result(result);
+                    }
+                }
             }
         }
 
@@ -333,6 +458,15 @@ impl GameState {
         scope: InteractionScopeId,
         event: Event,
     ) -> Result<(), ActionError> {
+        // The steps of composite actions are logged as part of the whole action
+        if systems::composite::absorb_result(self, &event) {
+            return Ok(());
+        }
+        // As are the attacks of the members of a mob
+        if systems::mob::absorb_result(self, &event) {
+            return Ok(());
+        }
+
         self.log_event(&scope, event.clone());
 
         if let Some(event_id) = event.response_to {
@@ -391,6 +525,10 @@ impl GameState {
                     }
                     *options = new_options;
                 }
+                ActionPromptKind::Decision { request } => {
+                    // Nobody is left to make the decision
+                    invalid = !self.world.contains(request.chooser);
+                }
                 ActionPromptKind::Action { .. } => { /* nothing */ }
             }
 
@@ -417,6 +555,21 @@ impl GameState {
                 }
             }
         }
+
+        self.prune_decision_callbacks();
+    }
+
+    /// Drop the callbacks of decisions whose prompts are no longer pending,
+    /// e.g. because they were cleared at the end of a turn
+    fn prune_decision_callbacks(&mut self) {
+        let pending: HashSet<ActionPromptId> = self
+            .interaction_engine
+            .sessions
+            .values()
+            .flat_map(|session| session.pending_prompts().iter().map(|prompt| prompt.id))
+            .collect();
+        self.decision_callbacks
+            .retain(|prompt_id, _| pending.contains(prompt_id));
     }
 
     fn resume_pending_events_if_ready(&mut self, scope: InteractionScopeId) {
@@ -428,9 +581,12 @@ impl GameState {
                 info!("No pending prompts; ready to resume pending events.");
                 true
             } else if let Some(front) = session.next_prompt()
-                && !matches!(front.kind, ActionPromptKind::Reactions { .. })
+                && !matches!(
+                    front.kind,
+                    ActionPromptKind::Reactions { .. } | ActionPromptKind::Decision { .. }
+                )
             {
-                info!("Next prompt is not a reaction; ready to resume pending events.");
+                info!("Next prompt is not a reaction or decision; ready to resume pending events.");
                 true
             } else {
                 false
@@ -516,6 +672,17 @@ impl GameState {
             targets,
         } = action;
 
+        if let Some(rule) = systems::actions::required_rule(action_id)
+            && !self.rule_enabled(rule)
+        {
+            return Err(ActionError::RuleDisabled(rule));
+        }
+
+        let ritual = systems::spells::is_ritual_casting(action_context, resource_cost);
+        if ritual && !self.in_combat.is_empty() {
+            return Err(ActionError::RitualInCombat);
+        }
+
         systems::actions::action_usable_on_targets(
             &self.world,
             &self.geometry,
@@ -528,8 +695,46 @@ impl GameState {
         .map_err(|error| ActionError::Usability(error))?;
 
         if spend_resources {
+            // Check this before spending, since the familiar can only deliver
+            // the spell if the actor can't reach the targets itself
+            let deliverer = systems::familiar::touch_spell_deliverer(
+                &self.world,
+                &self.geometry,
+                *actor,
+                action_id,
+                action_context,
+                targets,
+            );
+
             systems::resources::spend(&mut self.world, *actor, resource_cost)
                 .map_err(|error| ActionError::Resource(error))?;
+
+            systems::inventory::spend_item_charge(&mut self.world, *actor, action_context);
+            systems::spells::spend_innate_use(&mut self.world, *actor, action_context);
+            systems::inventory::spend_tool_charge(&mut self.world, *actor, action_id);
+            systems::spells::consume_material_component(
+                &mut self.world,
+                *actor,
+                action_context,
+                MaterialConsumption::OnCast,
+            );
+
+            if ritual {
+                let _ = systems::time::pass_time(
+                    self,
+                    TimeDuration::from_minutes(systems::spells::RITUAL_CASTING_MINUTES),
+                    TimeActivity::Downtime,
+                );
+            }
+
+            if let Some(familiar) = deliverer {
+                systems::resources::spend(
+                    &mut self.world,
+                    familiar,
+                    &systems::familiar::delivery_cost(),
+                )
+                .map_err(|error| ActionError::Resource(error))?;
+            }
         }
 
         Ok(())
@@ -586,17 +791,33 @@ impl GameState {
                                     }
                                 }
 
-                                // TODO: How to handle this properly?
-                                ReactionResult::ModifyEvent { modification } => {
+                                ReactionResult::ModifyEvent {
+                                    event: modified_event,
+                                    modification,
+                                    ..
+                                } => {
                                     info!(
                                         "Modifying event {:?} due to reaction by {:?}",
-                                        event.id,
+                                        modified_event,
                                         action_result.performer.id()
                                     );
-                                    (modification)(
-                                        &self.world,
-                                        &mut session.pending_events_mut().front_mut().unwrap(),
-                                    );
+                                    let Some(pending_event) = session
+                                        .pending_events_mut()
+                                        .iter_mut()
+                                        .find(|e| e.id == *modified_event)
+                                    else {
+                                        error!(
+                                            "Attempted to modify event which is not pending: {:?}",
+                                            modified_event
+                                        );
+                                        continue;
+                                    };
+                                    if modification.apply(pending_event).is_none() {
+                                        error!(
+                                            "Modification {:?} doesn't apply to event {:?}",
+                                            modification, pending_event
+                                        );
+                                    }
                                 }
 
                                 ReactionResult::NoEffect => { /* Do nothing */ }
@@ -612,9 +833,19 @@ impl GameState {
                 let dc = match dc_kind {
                     // TODO: Do we ever need to recalculate DCs for saving throws or skills?
                     D20CheckDCKind::SavingThrow(_) | D20CheckDCKind::Skill(_) => dc_kind.clone(),
-                    D20CheckDCKind::AttackRoll(target, _) => {
-                        // Recalculate AC in case it changed due to reactions
-                        let armor_class = systems::loadout::armor_class(&self.world, *target);
+                    D20CheckDCKind::AttackRoll(target, rolled_against) => {
+                        // Recalculate AC in case it changed due to reactions,
+                        // e.g. Shield, but keep any bonus a reaction or cover
+                        // gave against this attack only
+                        let mut armor_class = systems::loadout::armor_class(&self.world, *target);
+                        for (source, bonus) in rolled_against.modifiers.iter() {
+                            if (matches!(source, ModifierSource::Action(_))
+                                || Cover::from_modifier_source(source).is_some())
+                                && !armor_class.modifiers.contains_key(source)
+                            {
+                                armor_class.add_modifier(source.clone(), *bonus);
+                            }
+                        }
                         D20CheckDCKind::AttackRoll(*target, armor_class)
                     }
                 };
@@ -626,7 +857,7 @@ impl GameState {
                 );
             }
 
-            EventKind::DamageRollPerformed(entity, damage) => {
+            EventKind::DamageRollPerformed(entity, damage, _) => {
                 let _ = self.process_event_scoped(
                     self.scope_for_entity(*entity),
                     Event::new(EventKind::DamageRollResolved(*entity, damage.clone()))
@@ -649,6 +880,10 @@ impl GameState {
     }
 
     fn log_event(&mut self, scope: &InteractionScopeId, event: Event) {
+        // Subscribers whose receiver has been dropped are removed
+        self.event_subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+
         match scope {
             InteractionScopeId::Global => self.event_log.push(event),
             InteractionScopeId::Encounter(encounter_id) => {
@@ -662,6 +897,15 @@ impl GameState {
         }
     }
 
+    /// Subscribe to all events logged from now on. Unlike event listeners the
+    /// subscription doesn't affect how events are processed, and it lasts until
+    /// the receiver is dropped.
+    pub fn subscribe_events(&mut self) -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        self.event_subscribers.push(sender);
+        receiver
+    }
+
     pub fn add_event_listener(&mut self, event_listener: EventListener) {
         self.event_listeners
             .insert(event_listener.trigger_id(), event_listener);
@@ -683,7 +927,39 @@ impl GameState {
         }
     }
 
+    /// Continue the resolution of an action from the result of a callback
+    pub(crate) fn process_callback_result(&mut self, result: CallbackResult) {
+        match result {
+            CallbackResult::Event(event) => {
+                let _ = self.process_event(event);
+            }
+            CallbackResult::EventWithCallback(event, callback) => {
+                let _ = self.process_event_with_callback(event, callback);
+            }
+            CallbackResult::Decision(request, callback) => {
+                self.request_decision(request, callback);
+            }
+            CallbackResult::None => {}
+        }
+    }
+
+    /// Pause the resolution of an action until the chooser has answered the
+    /// request, after which the callback is called with the chosen option
+    pub fn request_decision(&mut self, request: DecisionRequest, callback: DecisionCallback) {
+        let scope = self.scope_for_entity(request.chooser);
+        let prompt = ActionPrompt::new(ActionPromptKind::Decision { request });
+        self.decision_callbacks.insert(prompt.id, callback);
+        self.interaction_engine
+            .session_mut(scope)
+            .queue_prompt(prompt, true);
+    }
+
     pub fn update(&mut self, delta_time: f32) {
+        // During encounters the world clock is advanced one round at a time
+        if self.encounters.is_empty() {
+            systems::time::advance_world_clock(self, &TimeDuration::from_seconds(delta_time));
+        }
+
         let time_step = TimeStep::RealTime {
             delta_seconds: delta_time,
         };
@@ -215,6 +215,10 @@ fn build_navmesh(
     (poly_navmesh, detail_navmesh, polyanya_mesh)
 }
 
+/// Distance between the points sampled along a path when checking what it
+/// passes through
+pub const PATH_SAMPLE_DISTANCE: f32 = 0.25;
+
 #[derive(Debug, Clone)]
 pub struct WorldPath {
     pub points: Vec<Point3<f32>>,
@@ -279,4 +283,23 @@ impl WorldPath {
     pub fn end(&self) -> Option<&Point3<f32>> {
         self.points.last()
     }
+
+    /// Points at most `PATH_SAMPLE_DISTANCE` apart along the path, not
+    /// including its start, each paired with the distance (in meters) travelled
+    /// along the path to reach it
+    pub fn samples(&self) -> Vec<(Point3<f32>, f32)> {
+        let mut samples = Vec::new();
+        let mut travelled = 0.0;
+        for (start, end) in self.points.windows(2).map(|window| (window[0], window[1])) {
+            let segment = end - start;
+            let length = segment.magnitude();
+            let steps = ((length / PATH_SAMPLE_DISTANCE).ceil() as usize).max(1);
+            for step in 1..=steps {
+                let t = step as f32 / steps as f32;
+                samples.push((start + segment * t, travelled + length * t));
+            }
+            travelled += length;
+        }
+        samples
+    }
 }
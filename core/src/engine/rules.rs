@@ -0,0 +1,44 @@
+use strum::{Display, EnumIter};
+
+/// Optional rules which can be toggled on and off for a game, e.g. the variant
+/// rules from the Dungeon Master's Guide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum OptionalRule {
+    /// Creatures taking damage equal to at least half their hit point maximum
+    /// from a single source have to make a saving throw or suffer a system
+    /// shock
+    MassiveDamage,
+    /// Death is permanent. Dead creatures can't be brought back, their
+    /// remains become corpse objects and characters leave the party.
+    Permadeath,
+    /// The party has to carry food and water, and suffers exhaustion when
+    /// they run out
+    Supplies,
+    /// Creatures can try to knock the weapons out of their enemies' hands or
+    /// break their equipment instead of attacking them directly
+    AttackManeuvers,
+    /// Attacks can be aimed at a specific part of the body, at a penalty to
+    /// hit, for an extra effect when they land
+    CalledShots,
+}
+
+/// How the turn order of an encounter is decided. Encounters keep the mode
+/// they were started with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Display, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum InitiativeMode {
+    /// Every creature rolls its own initiative
+    #[default]
+    Individual,
+    /// The party and their enemies each roll a single d20, and the side with
+    /// the highest roll takes all its turns before the other side
+    Side,
+    /// Whoever just took their turn picks who goes next among the creatures
+    /// which haven't acted yet this round. The last creature in a round picks
+    /// who starts the next one.
+    Popcorn,
+    /// Initiative is rolled again at the start of every round, modified by the
+    /// size of the creature and the weapon it's wielding
+    SpeedFactor,
+}
@@ -1,3 +1,5 @@
 pub mod character;
+pub mod ground_item;
 pub mod monster;
+pub mod object;
 pub mod utils;
@@ -7,11 +7,13 @@ use crate::{
         ability::AbilityScoreMap,
         actions::action::{ActionCooldownMap, ActionMap, default_actions},
         ai::PlayerControlledTag,
+        biography::Biography,
         damage::DamageResistances,
         effects::effect::EffectInstance,
         faction::FactionSet,
-        health::{hit_points::HitPoints, life_state::LifeState},
+        health::{healing::HealingModifiers, hit_points::HitPoints, life_state::LifeState},
         id::{AIControllerId, BackgroundId, FactionId, FeatId, Name, SpeciesId, SubspeciesId},
+        images::CreatureImages,
         items::{
             equipment::{armor::ArmorTrainingSet, loadout::Loadout, weapon::WeaponProficiencyMap},
             inventory::Inventory,
@@ -58,6 +60,7 @@ from_world!(
         pub skills: SkillSet,
         pub saving_throws: SavingThrowSet,
         pub resistances: DamageResistances,
+        pub healing_modifiers: HealingModifiers,
         pub weapon_proficiencies: WeaponProficiencyMap,
         pub armor_training: ArmorTrainingSet,
         pub inventory: Inventory,
@@ -69,6 +72,8 @@ from_world!(
         pub actions: ActionMap,
         pub cooldowns: ActionCooldownMap,
         pub factions: FactionSet,
+        pub biography: Biography,
+        pub images: CreatureImages,
     }
 );
 
@@ -95,6 +100,7 @@ impl Character {
             skills: SkillSet::default(),
             saving_throws: SavingThrowSet::default(),
             resistances: DamageResistances::new(),
+            healing_modifiers: HealingModifiers::new(),
             armor_training: ArmorTrainingSet::new(),
             weapon_proficiencies: WeaponProficiencyMap::new(),
             loadout: Loadout::new(),
@@ -106,6 +112,8 @@ impl Character {
             actions: default_actions(),
             cooldowns: HashMap::new(),
             factions: FactionSet::from([FactionId::new("nat20_core", "faction.players")]),
+            biography: Biography::default(),
+            images: CreatureImages::default(),
         }
     }
 }
@@ -0,0 +1,42 @@
+use hecs::Bundle;
+
+use crate::{
+    components::{
+        id::Name,
+        items::inventory::{ItemContainer, ItemInstance},
+        species::CreatureSize,
+    },
+    from_world,
+    systems::geometry::CreaturePose,
+};
+
+#[derive(Debug, Clone)]
+pub struct GroundItemTag;
+
+from_world!(
+    /// An item lying on the map, e.g. a weapon knocked out of someone's hands or
+    /// the belongings of a fallen creature. Anyone standing next to it can pick
+    /// it up.
+    #[derive(Bundle, Clone)]
+    pub struct GroundItem {
+        pub tag: GroundItemTag,
+        pub name: Name,
+        pub item: ItemInstance,
+        pub pose: CreaturePose,
+        /// Items are small enough to be tiny, which also keeps them from
+        /// getting in the way of anyone moving past them
+        pub size: CreatureSize,
+    }
+);
+
+impl GroundItem {
+    pub fn new(item: ItemInstance, pose: CreaturePose) -> Self {
+        Self {
+            tag: GroundItemTag,
+            name: Name::new(item.item().name.clone()),
+            item,
+            pose,
+            size: CreatureSize::Tiny,
+        }
+    }
+}
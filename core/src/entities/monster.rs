@@ -5,20 +5,25 @@ use crate::{
         ability::AbilityScoreMap,
         actions::action::{ActionCooldownMap, ActionMap, default_actions},
         damage::DamageResistances,
+        dice::DiceSetRoll,
         effects::effect::EffectInstance,
         faction::FactionSet,
-        health::{hit_points::HitPoints, life_state::LifeState},
-        id::{AIControllerId, Name},
-        items::equipment::{
-            armor::ArmorTrainingSet, loadout::Loadout, weapon::WeaponProficiencyMap,
+        health::{healing::HealingModifiers, hit_points::HitPoints, life_state::LifeState},
+        id::{AIControllerId, IdProvider, ItemId, MonsterId, Name},
+        images::CreatureImages,
+        items::{
+            equipment::{armor::ArmorTrainingSet, loadout::Loadout, weapon::WeaponProficiencyMap},
+            inventory::Inventory,
+            loot::LootTable,
         },
         level::ChallengeRating,
+        personality::Personality,
         resource::ResourceMap,
         saving_throw::SavingThrowSet,
         skill::SkillSet,
         species::{CreatureSize, CreatureType},
         speed::Speed,
-        spells::spellbook::Spellbook,
+        spells::{innate::InnateSpellcasting, spellbook::Spellbook},
         time::EntityClock,
     },
     from_world,
@@ -47,9 +52,11 @@ from_world!(
         pub skills: SkillSet,
         pub saving_throws: SavingThrowSet,
         pub resistances: DamageResistances,
+        pub healing_modifiers: HealingModifiers,
         // TODO: alignment?
         // TODO: ArmorClass or just Loadout?
         pub loadout: Loadout,
+        pub inventory: Inventory,
         pub spellbook: Spellbook,
         pub resources: ResourceMap,
         pub effects: Vec<EffectInstance>,
@@ -58,6 +65,8 @@ from_world!(
         pub weapon_proficiencies: WeaponProficiencyMap,
         pub armor_training: ArmorTrainingSet,
         pub factions: FactionSet,
+        pub personality: Personality,
+        pub images: CreatureImages,
     }
 );
 
@@ -89,7 +98,9 @@ impl Monster {
             skills: SkillSet::default(),
             saving_throws: SavingThrowSet::default(),
             resistances: DamageResistances::default(),
+            healing_modifiers: HealingModifiers::default(),
             loadout: Loadout::default(),
+            inventory: Inventory::new(),
             spellbook: Spellbook::new(),
             resources: ResourceMap::default(),
             effects: Vec::new(),
@@ -98,6 +109,42 @@ impl Monster {
             weapon_proficiencies: WeaponProficiencyMap::new(),
             armor_training: ArmorTrainingSet::default(),
             factions,
+            personality: Personality::default(),
+            images: CreatureImages::default(),
         }
     }
 }
+
+/// A stat block from the monster registry. Individual monsters are generated
+/// from it with `systems::generator`, which rolls the parts that vary between
+/// monsters of the same kind, e.g. hit points, names and loot.
+#[derive(Debug, Clone)]
+pub struct MonsterTemplate {
+    pub id: MonsterId,
+    pub name: Name,
+    pub challenge_rating: ChallengeRating,
+    pub hit_points: DiceSetRoll,
+    pub size: CreatureSize,
+    pub creature_type: CreatureType,
+    pub speed: Speed,
+    pub abilities: AbilityScoreMap,
+    pub factions: FactionSet,
+    /// Monsters are considered proficient with all their equipment
+    pub equipment: Vec<ItemId>,
+    /// Spells the monster casts without spell slots
+    pub innate_spellcasting: Option<InnateSpellcasting>,
+    /// Individual names to pick from, e.g. for humanoid NPCs
+    pub names: Vec<String>,
+    /// Personality tags to pick from
+    pub personality: Vec<String>,
+    pub loot: LootTable,
+    pub images: CreatureImages,
+}
+
+impl IdProvider for MonsterTemplate {
+    type Id = MonsterId;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+}
@@ -0,0 +1,157 @@
+use hecs::Bundle;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        ability::AbilityScoreMap,
+        damage::{
+            DamageMitigationEffect, DamageResistances, DamageThreshold, DamageType,
+            MitigationOperation,
+        },
+        effects::effect::EffectInstance,
+        health::{healing::HealingModifiers, hit_points::HitPoints, life_state::LifeState},
+        id::Name,
+        items::equipment::armor::{ArmorClass, ArmorDexterityBonus},
+        modifier::ModifierSource,
+        saving_throw::SavingThrowSet,
+        species::CreatureSize,
+        time::EntityClock,
+    },
+    from_world,
+    systems::geometry::CreaturePose,
+};
+
+#[derive(Debug, Clone)]
+pub struct ObjectTag;
+
+/// The material an object is made of determines its armor class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectMaterial {
+    Cloth, // also leather, paper and rope
+    Crystal,
+    Glass,
+    Ice,
+    Wood,
+    Bone,
+    Stone,
+    Iron,
+    Steel,
+    Mithral,
+    Adamantine,
+}
+
+impl ObjectMaterial {
+    pub fn armor_class(&self) -> i32 {
+        match self {
+            ObjectMaterial::Cloth => 11,
+            ObjectMaterial::Crystal | ObjectMaterial::Glass | ObjectMaterial::Ice => 13,
+            ObjectMaterial::Wood | ObjectMaterial::Bone => 15,
+            ObjectMaterial::Stone => 17,
+            ObjectMaterial::Iron | ObjectMaterial::Steel => 19,
+            ObjectMaterial::Mithral => 21,
+            ObjectMaterial::Adamantine => 23,
+        }
+    }
+
+    /// Manufactured metal objects, e.g. the ones affected by Heat Metal
+    pub fn is_metal(&self) -> bool {
+        matches!(
+            self,
+            ObjectMaterial::Iron
+                | ObjectMaterial::Steel
+                | ObjectMaterial::Mithral
+                | ObjectMaterial::Adamantine
+        )
+    }
+
+    /// Fragile objects, e.g. a glass bottle, have fewer hit points than
+    /// resilient ones of the same size
+    pub fn is_fragile(&self) -> bool {
+        matches!(
+            self,
+            ObjectMaterial::Cloth
+                | ObjectMaterial::Crystal
+                | ObjectMaterial::Glass
+                | ObjectMaterial::Ice
+        )
+    }
+
+    /// Transparent objects, such as windows, don't block line of sight, but
+    /// they still block line of effect.
+    pub fn is_transparent(&self) -> bool {
+        matches!(
+            self,
+            ObjectMaterial::Crystal | ObjectMaterial::Glass | ObjectMaterial::Ice
+        )
+    }
+}
+
+from_world!(
+    /// Destructible objects such as doors, crates and siege targets. Objects can
+    /// be targeted and damaged like creatures, but don't take turns or act.
+    #[derive(Bundle, Clone)]
+    pub struct Object {
+        pub tag: ObjectTag,
+        pub name: Name,
+        pub material: ObjectMaterial,
+        pub pose: CreaturePose,
+        pub size: CreatureSize,
+        pub hit_points: HitPoints,
+        pub life_state: LifeState,
+        pub time: EntityClock,
+        /// Unlike creatures, objects don't have a loadout, so their armor class
+        /// is stored directly on the entity
+        pub armor_class: ArmorClass,
+        pub damage_threshold: DamageThreshold,
+        pub resistances: DamageResistances,
+        pub healing_modifiers: HealingModifiers,
+        pub ability_scores: AbilityScoreMap,
+        pub saving_throws: SavingThrowSet,
+        pub effects: Vec<EffectInstance>,
+    }
+);
+
+impl Object {
+    pub fn new(
+        name: Name,
+        material: ObjectMaterial,
+        size: CreatureSize,
+        hit_points: HitPoints,
+        damage_threshold: DamageThreshold,
+    ) -> Self {
+        // Objects are immune to poison and psychic damage
+        let mut resistances = DamageResistances::new();
+        for damage_type in [DamageType::Poison, DamageType::Psychic] {
+            resistances.add_effect(
+                damage_type,
+                DamageMitigationEffect::new(
+                    ModifierSource::Custom("Object".to_string()),
+                    MitigationOperation::Immunity,
+                ),
+            );
+        }
+
+        Self {
+            tag: ObjectTag,
+            name,
+            material,
+            pose: CreaturePose::default(),
+            size,
+            hit_points,
+            life_state: LifeState::Normal,
+            time: EntityClock::new(),
+            armor_class: ArmorClass::new(
+                material.armor_class(),
+                ModifierSource::Base,
+                ArmorDexterityBonus::Limited(0),
+            ),
+            damage_threshold,
+            resistances,
+            healing_modifiers: HealingModifiers::new(),
+            ability_scores: AbilityScoreMap::new(),
+            saving_throws: SavingThrowSet::default(),
+            effects: Vec::new(),
+        }
+    }
+}
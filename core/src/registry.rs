@@ -1,4 +1,5 @@
 pub mod ai;
+pub mod custom_action;
 pub mod registry;
 pub mod registry_validation;
 pub mod serialize;
@@ -1,13 +1,20 @@
 use std::{collections::HashMap, sync::LazyLock};
 
 use hecs::Entity;
-use rand::seq::{IndexedRandom, IteratorRandom};
+use parry3d::na::Point3;
+use rand::{
+    Rng,
+    seq::{IndexedRandom, IteratorRandom},
+};
 
 use crate::{
     components::{
-        actions::targeting::{TargetInstance, TargetingKind},
+        actions::{
+            action::ActionKind,
+            targeting::{TargetInstance, TargetingKind},
+        },
         ai::{AIController, AIDecision},
-        id::AIControllerId,
+        id::{AIControllerId, ActionId},
     },
     engine::{
         event::{ActionData, ActionDecision, ActionDecisionKind, ActionPrompt, ActionPromptKind},
@@ -42,7 +49,8 @@ impl AIController for RandomController {
 
         match &prompt.kind {
             ActionPromptKind::Action { actor } => {
-                let actions = systems::actions::available_actions(&game_state.world, *actor);
+                let mut actions = systems::actions::available_actions(&game_state.world, *actor);
+                actions.retain(|action_id, _| game_state.action_allowed(action_id));
 
                 // Pick a random action
                 if actions.is_empty() {
@@ -53,12 +61,13 @@ impl AIController for RandomController {
                 if let Some(action_id) = actions.keys().choose(rng)
                     && let Some(contexts_and_costs) = actions.get(action_id)
                     && let Some((context, resource_cost)) = contexts_and_costs.choose(rng)
-                    && let Some(action) = systems::actions::get_action(action_id)
+                    && let action_id = pick_variant(action_id, rng)
+                    && let Some(action) = systems::actions::get_action(&action_id)
                 {
                     let targeting = systems::actions::targeting_context(
                         &game_state.world,
                         *actor,
-                        action_id,
+                        &action_id,
                         &context,
                     );
                     let mut targets = Vec::new();
@@ -68,7 +77,7 @@ impl AIController for RandomController {
                         && let Some(encounter) = game_state.encounters.get(encounter_id)
                     {
                         encounter
-                            .participants(&game_state.world, targeting.allowed_targets)
+                            .participants(&game_state.world, targeting.allowed_targets.clone())
                             .into_iter()
                             .filter(|target| {
                                 let target_attitude = systems::factions::mutual_attitude(
@@ -83,12 +92,37 @@ impl AIController for RandomController {
                                         &action.kind,
                                     )
                             })
+                            // Charmed creatures won't turn on their charmer
+                            .filter(|target| {
+                                systems::conditions::prevents_harm(
+                                    &game_state.world,
+                                    *actor,
+                                    *target,
+                                    &action.kind,
+                                )
+                                .is_none()
+                            })
+                            // The AI doesn't know where unseen creatures are
+                            .filter(|target| {
+                                systems::visibility::can_see(&game_state.world, *actor, *target)
+                            })
+                            // Actions targeting items need something to target
+                            .filter(|target| {
+                                targeting.allowed_items.as_ref().is_none_or(|filter| {
+                                    !systems::inventory::item_targets(
+                                        &game_state.world,
+                                        *target,
+                                        filter,
+                                    )
+                                    .is_empty()
+                                })
+                            })
                             .collect::<Vec<Entity>>()
                     } else {
                         return AIDecision::empty(*actor);
                     };
 
-                    match targeting.kind {
+                    match targeting.kind.clone() {
                         TargetingKind::SelfTarget => targets.push(*actor),
 
                         TargetingKind::Single => {
@@ -104,22 +138,40 @@ impl AIController for RandomController {
                             targets.extend(chosen_targets);
                         }
 
-                        TargetingKind::Area {
-                            shape,
-                            fixed_on_actor,
-                        } => todo!(),
+                        // Areas are aimed at a point once the action is known
+                        TargetingKind::Area { .. } => {}
                     }
 
-                    let action = ActionData::new(
+                    let mut action = ActionData::new(
                         *actor,
                         action_id.clone(),
                         context.clone(),
                         resource_cost.clone(),
                         targets
                             .iter()
-                            .map(|entity| TargetInstance::Entity(*entity))
+                            .filter_map(|entity| {
+                                systems::actions::target_for_entity(
+                                    &game_state.world,
+                                    &targeting,
+                                    *entity,
+                                )
+                            })
                             .collect(),
                     );
+                    if matches!(targeting.kind, TargetingKind::Area { .. }) {
+                        let Some(point) = aim_area(game_state, &action, &possible_targets) else {
+                            return AIDecision::empty(*actor);
+                        };
+                        action.targets = vec![TargetInstance::Point(point)];
+                    }
+                    // Spend as much as possible on actions with a variable cost
+                    if let Some((_, max)) = systems::actions::variable_amount_range(
+                        &game_state.world,
+                        *actor,
+                        resource_cost,
+                    ) {
+                        let _ = systems::actions::choose_amount(&mut action, max);
+                    }
 
                     let path = match systems::movement::path_to_target(game_state, &action, true) {
                         Ok(result) => match result {
@@ -174,6 +226,60 @@ impl AIController for RandomController {
                     return AIDecision::empty(actor);
                 }
             }
+
+            ActionPromptKind::Decision { request } => {
+                let Some(option) = (0..request.options.len()).choose(rng) else {
+                    return AIDecision::empty(actor);
+                };
+                AIDecision {
+                    actor,
+                    decision: Some(ActionDecision {
+                        response_to: prompt.id,
+                        kind: ActionDecisionKind::Decision {
+                            chooser: actor,
+                            option,
+                        },
+                    }),
+                    path: None,
+                }
+            }
         }
     }
 }
+
+/// Variants can't be performed on their own, so one of them is picked instead
+fn pick_variant<R: Rng + ?Sized>(action_id: &ActionId, rng: &mut R) -> ActionId {
+    match systems::actions::get_action(action_id).map(|action| &action.kind) {
+        Some(ActionKind::Variant { variants }) => variants
+            .choose(rng)
+            .cloned()
+            .unwrap_or_else(|| action_id.clone()),
+        _ => action_id.clone(),
+    }
+}
+
+/// Aim an area at one of the possible targets, picking the one which catches
+/// the most possible targets in the area without catching any allies
+fn aim_area(
+    game_state: &GameState,
+    action: &ActionData,
+    possible_targets: &[Entity],
+) -> Option<Point3<f32>> {
+    possible_targets
+        .iter()
+        .filter_map(|target| systems::geometry::get_foot_position(&game_state.world, *target))
+        .filter_map(|point| {
+            let mut aimed = action.clone();
+            aimed.targets = vec![TargetInstance::Point(point)];
+            if !systems::actions::friendly_fire(game_state, &aimed).is_empty() {
+                return None;
+            }
+            let caught = systems::actions::get_targeted_entities(game_state, &aimed)
+                .into_iter()
+                .filter(|entity| possible_targets.contains(entity))
+                .count();
+            (caught > 0).then_some((point, caught))
+        })
+        .max_by_key(|(_, caught)| *caught)
+        .map(|(point, _)| point)
+}
@@ -0,0 +1,24 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, RwLock},
+};
+
+use tracing::debug;
+
+use crate::components::{actions::custom::CustomAction, id::CustomActionId};
+
+/// Unlike the other registries, custom actions are implemented in code rather
+/// than loaded from files, so they're registered while the game is running
+static CUSTOM_ACTIONS: LazyLock<RwLock<HashMap<CustomActionId, Arc<dyn CustomAction>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Make the custom action available to any action referring to it by id.
+/// Registering an id again replaces the previous implementation.
+pub fn register(id: CustomActionId, action: impl CustomAction) {
+    debug!("Registering custom action {}", id);
+    CUSTOM_ACTIONS.write().unwrap().insert(id, Arc::new(action));
+}
+
+pub fn get(id: &CustomActionId) -> Option<Arc<dyn CustomAction>> {
+    CUSTOM_ACTIONS.read().unwrap().get(id).cloned()
+}
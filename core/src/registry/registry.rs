@@ -15,25 +15,29 @@ use crate::{
     components::{
         actions::action::Action,
         background::Background,
+        campaign::Adventure,
         class::{Class, Subclass},
         effects::effect::Effect,
         faction::Faction,
         feat::Feat,
         id::{
-            ActionId, BackgroundId, ClassId, EffectId, FactionId, FeatId, IdProvider, ItemId,
-            ResourceId, ScriptId, SpeciesId, SpellId, SubclassId, SubspeciesId,
+            ActionId, AdventureId, BackgroundId, ClassId, EffectId, FactionId, FeatId, IdProvider,
+            ItemId, MonsterId, ResourceId, ScriptId, SpeciesId, SpellId, SpellListId, SubclassId,
+            SubspeciesId,
         },
         items::inventory::ItemInstance,
         resource::Resource,
         species::{Species, Subspecies},
-        spells::spell::Spell,
+        spells::{spell::Spell, spell_list::SpellList},
     },
+    entities::monster::MonsterTemplate,
     registry::{
         registry_validation::{ReferenceCollector, RegistryReference, RegistryReferenceCollector},
         serialize::{
             action::ActionDefinition,
             class::ClassDefinition,
             effect::EffectDefinition,
+            monster::MonsterDefinition,
             species::{SpeciesDefinition, SubspeciesDefinition},
             spell::SpellDefinition,
         },
@@ -317,16 +321,19 @@ where
 
 pub struct RegistrySet {
     pub actions: Registry<ActionId, Action, ActionDefinition>,
+    pub adventures: Registry<AdventureId, Adventure, Adventure>,
     pub backgrounds: Registry<BackgroundId, Background, Background>,
     pub classes: Registry<ClassId, Class, ClassDefinition>,
     pub effects: Registry<EffectId, Effect, EffectDefinition>,
     pub factions: Registry<FactionId, Faction, Faction>,
     pub feats: Registry<FeatId, Feat, Feat>,
     pub items: Registry<ItemId, ItemInstance, ItemInstance>,
+    pub monsters: Registry<MonsterId, MonsterTemplate, MonsterDefinition>,
     pub resources: Registry<ResourceId, Resource, Resource>,
     pub scripts: Registry<ScriptId, Script, Script>,
     pub species: Registry<SpeciesId, Species, SpeciesDefinition>,
     pub spells: Registry<SpellId, Spell, SpellDefinition>,
+    pub spell_lists: Registry<SpellListId, SpellList, SpellList>,
     pub subclasses: Registry<SubclassId, Subclass, Subclass>,
     pub subspecies: Registry<SubspeciesId, Subspecies, SubspeciesDefinition>,
 }
@@ -338,29 +345,35 @@ impl RegistrySet {
         let root_directory = root_directory.as_ref();
 
         let actions_directory = root_directory.join("actions");
+        let adventures_directory = root_directory.join("adventures");
         let backgrounds_directory = root_directory.join("backgrounds");
         let classes_directory = root_directory.join("classes");
         let effects_directory = root_directory.join("effects");
         let factions_directory = root_directory.join("factions");
         let feats_directory = root_directory.join("feats");
         let items_directory = root_directory.join("items");
+        let monsters_directory = root_directory.join("monsters");
         let resources_directory = root_directory.join("resources");
         let species_directory = root_directory.join("species");
         let spells_directory = root_directory.join("spells");
+        let spell_lists_directory = root_directory.join("spell_lists");
         let subclasses_directory = root_directory.join("subclasses");
         let subspecies_directory = root_directory.join("subspecies");
 
         let all_directories: Vec<&Path> = vec![
             actions_directory.as_path(),
+            adventures_directory.as_path(),
             backgrounds_directory.as_path(),
             classes_directory.as_path(),
             effects_directory.as_path(),
             factions_directory.as_path(),
             feats_directory.as_path(),
             items_directory.as_path(),
+            monsters_directory.as_path(),
             resources_directory.as_path(),
             species_directory.as_path(),
             spells_directory.as_path(),
+            spell_lists_directory.as_path(),
             subclasses_directory.as_path(),
             subspecies_directory.as_path(),
         ];
@@ -371,15 +384,18 @@ impl RegistrySet {
         let scripts_map = Self::load_scripts_from_directories(&all_directories, &mut errors);
 
         let actions = Registry::load_registry(&actions_directory, &mut errors);
+        let adventures = Registry::load_registry(&adventures_directory, &mut errors);
         let backgrounds = Registry::load_registry(&backgrounds_directory, &mut errors);
         let classes = Registry::load_registry(&classes_directory, &mut errors);
         let effects = Registry::load_registry(&effects_directory, &mut errors);
         let factions = Registry::load_registry(&factions_directory, &mut errors);
         let feats = Registry::load_registry(&feats_directory, &mut errors);
         let items = Registry::load_registry(&items_directory, &mut errors);
+        let monsters = Registry::load_registry(&monsters_directory, &mut errors);
         let resources = Registry::load_registry(&resources_directory, &mut errors);
         let species = Registry::load_registry(&species_directory, &mut errors);
         let spells = Registry::load_registry(&spells_directory, &mut errors);
+        let spell_lists = Registry::load_registry(&spell_lists_directory, &mut errors);
         let subclasses = Registry::load_registry(&subclasses_directory, &mut errors);
         let subspecies = Registry::load_registry(&subspecies_directory, &mut errors);
 
@@ -390,33 +406,39 @@ impl RegistrySet {
 
         let set = Self {
             actions: actions.expect("validated"),
+            adventures: adventures.expect("validated"),
             backgrounds: backgrounds.expect("validated"),
             classes: classes.expect("validated"),
             effects: effects.expect("validated"),
             factions: factions.expect("validated"),
             feats: feats.expect("validated"),
             items: items.expect("validated"),
+            monsters: monsters.expect("validated"),
             resources: resources.expect("validated"),
             scripts: Registry {
                 entries: scripts_map,
             },
             species: species.expect("validated"),
             spells: spells.expect("validated"),
+            spell_lists: spell_lists.expect("validated"),
             subclasses: subclasses.expect("validated"),
             subspecies: subspecies.expect("validated"),
         };
 
         // Validate references now that all registries are loaded.
         Self::validate_registry_references(&mut errors, &set.actions, &set);
+        Self::validate_registry_references(&mut errors, &set.adventures, &set);
         Self::validate_registry_references(&mut errors, &set.backgrounds, &set);
         Self::validate_registry_references(&mut errors, &set.classes, &set);
         Self::validate_registry_references(&mut errors, &set.effects, &set);
         Self::validate_registry_references(&mut errors, &set.factions, &set);
         Self::validate_registry_references(&mut errors, &set.feats, &set);
         Self::validate_registry_references(&mut errors, &set.items, &set);
+        Self::validate_registry_references(&mut errors, &set.monsters, &set);
         Self::validate_registry_references(&mut errors, &set.resources, &set);
         Self::validate_registry_references(&mut errors, &set.species, &set);
         Self::validate_registry_references(&mut errors, &set.spells, &set);
+        Self::validate_registry_references(&mut errors, &set.spell_lists, &set);
         Self::validate_registry_references(&mut errors, &set.subclasses, &set);
         Self::validate_registry_references(&mut errors, &set.subspecies, &set);
 
@@ -555,11 +577,15 @@ impl RegistrySet {
                     RegistryReference::Faction(id) => registries.factions.entries.contains_key(id),
                     RegistryReference::Feat(id) => registries.feats.entries.contains_key(id),
                     RegistryReference::Item(id) => registries.items.entries.contains_key(id),
+                    RegistryReference::Monster(id) => registries.monsters.entries.contains_key(id),
                     RegistryReference::Resource(id) => {
                         registries.resources.entries.contains_key(id)
                     }
                     RegistryReference::Species(id) => registries.species.entries.contains_key(id),
                     RegistryReference::Spell(id) => registries.spells.entries.contains_key(id),
+                    RegistryReference::SpellList(id) => {
+                        registries.spell_lists.entries.contains_key(id)
+                    }
                     RegistryReference::Subclass(id) => {
                         registries.subclasses.entries.contains_key(id)
                     }
@@ -614,6 +640,9 @@ impl RegistrySet {
             }
             RegistryReference::Feat(id) => (id.to_string(), registries.feats.all_keys_strings()),
             RegistryReference::Item(id) => (id.to_string(), registries.items.all_keys_strings()),
+            RegistryReference::Monster(id) => {
+                (id.to_string(), registries.monsters.all_keys_strings())
+            }
             RegistryReference::Resource(id) => {
                 (id.to_string(), registries.resources.all_keys_strings())
             }
@@ -621,6 +650,9 @@ impl RegistrySet {
                 (id.to_string(), registries.species.all_keys_strings())
             }
             RegistryReference::Spell(id) => (id.to_string(), registries.spells.all_keys_strings()),
+            RegistryReference::SpellList(id) => {
+                (id.to_string(), registries.spell_lists.all_keys_strings())
+            }
             RegistryReference::Subclass(id) => {
                 (id.to_string(), registries.subclasses.all_keys_strings())
             }
@@ -674,15 +706,18 @@ macro_rules! define_registry {
 }
 
 define_registry!(ActionsRegistry, ActionId, Action, actions);
+define_registry!(AdventuresRegistry, AdventureId, Adventure, adventures);
 define_registry!(BackgroundsRegistry, BackgroundId, Background, backgrounds);
 define_registry!(ClassesRegistry, ClassId, Class, classes);
 define_registry!(EffectsRegistry, EffectId, Effect, effects);
 define_registry!(FactionsRegistry, FactionId, Faction, factions);
 define_registry!(FeatsRegistry, FeatId, Feat, feats);
 define_registry!(ItemsRegistry, ItemId, ItemInstance, items);
+define_registry!(MonstersRegistry, MonsterId, MonsterTemplate, monsters);
 define_registry!(ResourcesRegistry, ResourceId, Resource, resources);
 define_registry!(ScriptsRegistry, ScriptId, Script, scripts);
 define_registry!(SpeciesRegistry, SpeciesId, Species, species);
 define_registry!(SpellsRegistry, SpellId, Spell, spells);
+define_registry!(SpellListsRegistry, SpellListId, SpellList, spell_lists);
 define_registry!(SubclassesRegistry, SubclassId, Subclass, subclasses);
 define_registry!(SubspeciesRegistry, SubspeciesId, Subspecies, subspecies);
@@ -3,15 +3,19 @@ use std::{collections::HashMap, fmt::Display};
 use crate::{
     components::{
         background::Background,
+        campaign::Adventure,
         faction::Faction,
         feat::Feat,
         id::{
-            ActionId, BackgroundId, ClassId, EffectId, FactionId, FeatId, ItemId, ResourceId,
-            ScriptId, SpeciesId, SpellId, SubclassId, SubspeciesId,
+            ActionId, BackgroundId, ClassId, EffectId, FactionId, FeatId, ItemId, MonsterId,
+            ResourceId, ScriptId, SpeciesId, SpellId, SpellListId, SubclassId, SubspeciesId,
         },
+        items::loot::{LootItem, LootTable},
         resource::Resource,
+        spells::spell_list::SpellList,
     },
     scripts::script::ScriptFunction,
+    systems::scene::SceneStep,
 };
 
 #[derive(Debug, Clone)]
@@ -23,10 +27,12 @@ pub enum RegistryReference {
     Faction(FactionId),
     Feat(FeatId),
     Item(ItemId),
+    Monster(MonsterId),
     Resource(ResourceId),
     Script(ScriptId, ScriptFunction),
     Species(SpeciesId),
     Spell(SpellId),
+    SpellList(SpellListId),
     Subclass(SubclassId),
     Subspecies(SubspeciesId),
 }
@@ -41,12 +47,14 @@ impl Display for RegistryReference {
             RegistryReference::Faction(id) => write!(f, "Faction '{}'", id),
             RegistryReference::Feat(id) => write!(f, "Feat '{}'", id),
             RegistryReference::Item(id) => write!(f, "Item '{}'", id),
+            RegistryReference::Monster(id) => write!(f, "Monster '{}'", id),
             RegistryReference::Resource(id) => write!(f, "Resource '{}'", id),
             RegistryReference::Script(id, function) => {
                 write!(f, "Script '{}' (function: {:?})", id, function)
             }
             RegistryReference::Species(id) => write!(f, "Species '{}'", id),
             RegistryReference::Spell(id) => write!(f, "Spell '{}'", id),
+            RegistryReference::SpellList(id) => write!(f, "Spell list '{}'", id),
             RegistryReference::Subclass(id) => write!(f, "Subclass '{}'", id),
             RegistryReference::Subspecies(id) => write!(f, "Subspecies '{}'", id),
         }
@@ -138,3 +146,48 @@ impl RegistryReferenceCollector for Resource {
         // Resources currently have no registry references
     }
 }
+
+impl RegistryReferenceCollector for SpellList {
+    fn collect_registry_references(&self, collector: &mut ReferenceCollector) {
+        for spell in self.spells.iter().chain(&self.recommended) {
+            collector.add(RegistryReference::Spell(spell.clone()));
+        }
+    }
+}
+
+impl RegistryReferenceCollector for LootTable {
+    fn collect_registry_references(&self, collector: &mut ReferenceCollector) {
+        for entry in &self.entries {
+            if let LootItem::Item(item_id) = &entry.item {
+                collector.add(RegistryReference::Item(item_id.clone()));
+            }
+        }
+    }
+}
+
+impl RegistryReferenceCollector for Adventure {
+    fn collect_registry_references(&self, collector: &mut ReferenceCollector) {
+        let creatures = self
+            .encounters
+            .iter()
+            .flat_map(|encounter| &encounter.creatures)
+            .chain(&self.npcs);
+        for creature in creatures {
+            collector.add(RegistryReference::Monster(creature.template.clone()));
+        }
+        for step in self.scenes.iter().flat_map(|scene| &scene.steps) {
+            if let SceneStep::Spawn { template, .. } = step {
+                collector.add(RegistryReference::Monster(template.clone()));
+            }
+        }
+        for quest in &self.quests {
+            quest.rewards.collect_registry_references(collector);
+        }
+        self.loot.collect_registry_references(collector);
+        for location in &self.world_map.locations {
+            for item in location.shop_stock() {
+                collector.add(RegistryReference::Item(item.clone()));
+            }
+        }
+    }
+}
@@ -6,9 +6,11 @@ pub mod effect;
 pub mod feat;
 pub mod item;
 pub mod modifier;
+pub mod monster;
 pub mod parser;
 pub mod quantity;
 pub mod species;
 pub mod spell;
 pub mod targeting;
+pub mod utility;
 pub mod variables;
@@ -1,18 +1,35 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
+use uom::si::f32::Length;
 
 use crate::{
     components::{
-        actions::action::{Action, ActionCondition, ActionKind, ActionPayload, DamageOnFailure},
-        effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate},
-        id::{ActionId, EffectId, ScriptId},
+        actions::{
+            action::{
+                Action, ActionCondition, ActionKind, ActionPayload, CompositeStep,
+                CompositeStepCondition, DamageOnFailure, DamageRollPolicy, TargetDamageFunction,
+                TargetSavingThrowFunction,
+            },
+            constraint::ActionConstraint,
+        },
+        effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate, EffectTag},
+        health::resurrection::Resurrection,
+        id::{ActionId, CustomActionId, EffectId, ItemId, ScriptId},
+        illusion::IllusionTemplate,
+        items::equipment::weapon::WeaponKind,
         resource::{RechargeRule, ResourceAmountMap},
+        skill::SkillContest,
+        surface::SurfaceTemplate,
     },
     registry::{
         registry_validation::{ReferenceCollector, RegistryReference, RegistryReferenceCollector},
         serialize::{
-            d20::{AttackRollProvider, SavingThrowProvider},
+            d20::{AttackRollProvider, SavingThrowProvider, SkillCheckProvider},
             dice::{DamageEquation, HealEquation},
+            quantity::LengthExpressionDefinition,
             targeting::TargetingDefinition,
+            utility::UtilityProvider,
         },
     },
     scripts::script::ScriptFunction,
@@ -25,6 +42,29 @@ pub enum DamageOnFailureDefinition {
     Custom(DamageEquation),
 }
 
+impl From<DamageOnFailureDefinition> for DamageOnFailure {
+    fn from(spec: DamageOnFailureDefinition) -> Self {
+        match spec {
+            DamageOnFailureDefinition::Half => DamageOnFailure::Half,
+            DamageOnFailureDefinition::Custom(damage_equation) => {
+                DamageOnFailure::Custom(target_damage(damage_equation))
+            }
+        }
+    }
+}
+
+/// Damage equations are the same for every target of an action
+fn target_damage(damage_equation: DamageEquation) -> Arc<TargetDamageFunction> {
+    let function = damage_equation.function;
+    Arc::new(move |world, entity, context, _| function(world, entity, context))
+}
+
+/// Saving throw DCs are the same for every target of an action
+fn target_saving_throw(saving_throw: SavingThrowProvider) -> Arc<TargetSavingThrowFunction> {
+    let function = saving_throw.function;
+    Arc::new(move |world, entity, context, _| function(world, entity, context))
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ActionConditionDefinition {
@@ -38,6 +78,12 @@ pub enum ActionConditionDefinition {
         #[serde(default)]
         damage_on_save: Option<DamageOnFailureDefinition>,
     },
+    SkillCheck {
+        skill_check: SkillCheckProvider,
+    },
+    Contest {
+        contest: SkillContest,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +101,28 @@ pub struct ActionPayloadDefinition {
     pub healing: Option<HealEquation>,
     #[serde(default)]
     pub effect: Option<EffectInstanceTemplate>,
+    #[serde(default)]
+    pub stabilize: bool,
+    #[serde(default)]
+    pub revive: Option<Resurrection>,
+    #[serde(default)]
+    pub cures: Vec<EffectTag>,
+    #[serde(default)]
+    pub conjures: Vec<ItemId>,
+    #[serde(default)]
+    pub search: bool,
+    #[serde(default)]
+    pub disarm: bool,
+    #[serde(default)]
+    pub sunder: bool,
+    #[serde(default)]
+    pub pick_up: bool,
+    #[serde(default)]
+    pub drop_item: bool,
+    #[serde(default)]
+    pub throw: bool,
+    #[serde(default)]
+    pub investigate: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -65,13 +133,11 @@ pub enum ActionKindDefinition {
         condition: Option<ActionConditionDefinition>,
         payload: ActionPayloadDefinition,
     },
-
-    // Utility {
-    //     // most likely non-data / hand-written only,
-    //     // you can keep this variant out of the serializable enum if you want
-    // },
+    Utility {
+        utility: UtilityProvider,
+    },
     Composite {
-        actions: Vec<ActionKindDefinition>,
+        actions: Vec<CompositeStepDefinition>,
     },
     Variants {
         variants: Vec<ActionId>,
@@ -80,7 +146,37 @@ pub enum ActionKindDefinition {
     // for now, so they can live only on the runtime enum.
     Reaction {
         script: ScriptId,
-    }, // Custom(...)
+    },
+    Illusion {
+        image: IllusionTemplate,
+    },
+    /// Custom actions are registered in code, so they can't be checked when
+    /// the registries are loaded
+    Custom {
+        action: CustomActionId,
+    },
+}
+
+/// A step of a composite action. Steps without any of the extra fields can be
+/// written just like any other action kind.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompositeStepDefinition {
+    #[serde(flatten)]
+    pub kind: ActionKindDefinition,
+    #[serde(default)]
+    pub only_if: CompositeStepCondition,
+    #[serde(default)]
+    pub resource_cost: ResourceAmountMap,
+}
+
+impl From<CompositeStepDefinition> for CompositeStep {
+    fn from(spec: CompositeStepDefinition) -> Self {
+        CompositeStep {
+            kind: spec.kind.into(),
+            only_if: spec.only_if,
+            resource_cost: spec.resource_cost,
+        }
+    }
 }
 
 impl From<ActionKindDefinition> for ActionKind {
@@ -94,48 +190,61 @@ impl From<ActionKindDefinition> for ActionKind {
                             damage_on_miss,
                         } => ActionCondition::AttackRoll {
                             attack_roll: attack_roll.function,
-                            damage_on_miss: damage_on_miss.map(|damage_on_failure| {
-                                match damage_on_failure {
-                                    DamageOnFailureDefinition::Half => DamageOnFailure::Half,
-                                    DamageOnFailureDefinition::Custom(damage_equation) => {
-                                        DamageOnFailure::Custom(damage_equation.function)
-                                    }
-                                }
-                            }),
+                            damage_on_miss: damage_on_miss.map(DamageOnFailure::from),
                         },
                         ActionConditionDefinition::SavingThrow {
                             saving_throw,
                             damage_on_save,
                         } => ActionCondition::SavingThrow {
-                            saving_throw: saving_throw.function,
-                            damage_on_save: damage_on_save.map(|damage_on_failure| {
-                                match damage_on_failure {
-                                    DamageOnFailureDefinition::Half => DamageOnFailure::Half,
-                                    DamageOnFailureDefinition::Custom(damage_equation) => {
-                                        DamageOnFailure::Custom(damage_equation.function)
-                                    }
-                                }
-                            }),
+                            saving_throw: target_saving_throw(saving_throw),
+                            damage_on_save: damage_on_save.map(DamageOnFailure::from),
                         },
+                        ActionConditionDefinition::SkillCheck { skill_check } => {
+                            ActionCondition::SkillCheck {
+                                skill_check: skill_check.function,
+                            }
+                        }
+                        ActionConditionDefinition::Contest { contest } => {
+                            ActionCondition::Contest { contest }
+                        }
                     }
                 } else {
                     ActionCondition::None
                 },
                 payload: ActionPayload::new(
-                    payload.damage.map(|eq| eq.function),
+                    payload.damage.map(target_damage),
                     payload.effect,
                     payload.healing.map(|eq| eq.function),
+                    payload.stabilize,
+                    payload.revive,
+                    payload.cures,
+                    payload.conjures,
+                    payload.search,
+                    payload.disarm,
+                    payload.sunder,
+                    payload.pick_up,
+                    payload.drop_item,
+                    payload.throw,
+                    payload.investigate,
                 )
                 .unwrap(),
             },
 
+            ActionKindDefinition::Utility { utility } => ActionKind::Utility {
+                utility: utility.function,
+            },
+
             ActionKindDefinition::Composite { actions } => ActionKind::Composite {
-                actions: actions.into_iter().map(ActionKind::from).collect(),
+                actions: actions.into_iter().map(CompositeStep::from).collect(),
             },
 
             ActionKindDefinition::Variants { variants } => ActionKind::Variant { variants },
 
             ActionKindDefinition::Reaction { script } => ActionKind::Reaction { reaction: script },
+
+            ActionKindDefinition::Illusion { image } => ActionKind::Illusion { illusion: image },
+
+            ActionKindDefinition::Custom { action } => ActionKind::Custom { action },
         }
     }
 }
@@ -147,10 +256,19 @@ impl RegistryReferenceCollector for ActionKindDefinition {
                 if let Some(effect) = &payload.effect {
                     collector.add(RegistryReference::Effect(effect.effect_id.clone()));
                 }
+                for item in &payload.conjures {
+                    collector.add(RegistryReference::Item(item.clone()));
+                }
             }
+            ActionKindDefinition::Utility { .. }
+            | ActionKindDefinition::Illusion { .. }
+            | ActionKindDefinition::Custom { .. } => {}
             ActionKindDefinition::Composite { actions } => {
-                for action in actions {
-                    action.collect_registry_references(collector);
+                for step in actions {
+                    step.kind.collect_registry_references(collector);
+                    for resource in step.resource_cost.keys() {
+                        collector.add(RegistryReference::Resource(resource.clone()));
+                    }
                 }
             }
             ActionKindDefinition::Variants { .. } => {
@@ -169,6 +287,71 @@ impl RegistryReferenceCollector for ActionKindDefinition {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionConstraintDefinition {
+    OncePerTurn,
+    Hidden,
+    RequiresEffect(EffectId),
+    ForbidsEffect(EffectId),
+    FreeHand,
+    Weapon(WeaponKind),
+    TargetWithin(ConstantLength),
+}
+
+/// A distance which is known as soon as the registry is loaded, so it can't
+/// depend on any variables. Distances which do fail to load instead of failing
+/// when the action is used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    try_from = "LengthExpressionDefinition",
+    into = "LengthExpressionDefinition"
+)]
+pub struct ConstantLength {
+    definition: LengthExpressionDefinition,
+    length: Length,
+}
+
+impl TryFrom<LengthExpressionDefinition> for ConstantLength {
+    type Error = String;
+
+    fn try_from(definition: LengthExpressionDefinition) -> Result<Self, Self::Error> {
+        let length = definition.evaluate_without_variables().map_err(|error| {
+            format!(
+                "Distance '{}' can't depend on variables: {:?}",
+                definition, error
+            )
+        })?;
+        Ok(Self { definition, length })
+    }
+}
+
+impl From<ConstantLength> for LengthExpressionDefinition {
+    fn from(value: ConstantLength) -> Self {
+        value.definition
+    }
+}
+
+impl From<ActionConstraintDefinition> for ActionConstraint {
+    fn from(spec: ActionConstraintDefinition) -> Self {
+        match spec {
+            ActionConstraintDefinition::OncePerTurn => ActionConstraint::OncePerTurn,
+            ActionConstraintDefinition::Hidden => ActionConstraint::Hidden,
+            ActionConstraintDefinition::RequiresEffect(effect_id) => {
+                ActionConstraint::RequiresEffect(effect_id)
+            }
+            ActionConstraintDefinition::ForbidsEffect(effect_id) => {
+                ActionConstraint::ForbidsEffect(effect_id)
+            }
+            ActionConstraintDefinition::FreeHand => ActionConstraint::FreeHand,
+            ActionConstraintDefinition::Weapon(kind) => ActionConstraint::Weapon(kind),
+            ActionConstraintDefinition::TargetWithin(distance) => {
+                ActionConstraint::TargetWithin(distance.length)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ActionDefinition {
     pub id: ActionId,
@@ -183,6 +366,12 @@ pub struct ActionDefinition {
     // TODO: How to handle reaction triggers in serialization?
     #[serde(default)]
     pub reaction_trigger: Option<ScriptId>,
+    #[serde(default)]
+    pub surface: Option<SurfaceTemplate>,
+    #[serde(default)]
+    pub damage_roll: DamageRollPolicy,
+    #[serde(default)]
+    pub constraints: Vec<ActionConstraintDefinition>,
 }
 
 impl RegistryReferenceCollector for ActionDefinition {
@@ -197,19 +386,36 @@ impl RegistryReferenceCollector for ActionDefinition {
                 ScriptFunction::ReactionTrigger,
             ));
         }
+        for constraint in &self.constraints {
+            match constraint {
+                ActionConstraintDefinition::RequiresEffect(effect_id)
+                | ActionConstraintDefinition::ForbidsEffect(effect_id) => {
+                    collector.add(RegistryReference::Effect(effect_id.clone()));
+                }
+                _ => {}
+            }
+        }
     }
 }
 
 impl From<ActionDefinition> for Action {
     fn from(value: ActionDefinition) -> Self {
+        let kind: ActionKind = value.kind.into();
         Action {
             id: value.id,
             description: value.description,
-            kind: value.kind.into(),
-            resource_cost: value.resource_cost,
+            resource_cost: kind.total_cost(value.resource_cost),
+            kind,
             targeting: value.targeting.function(),
             cooldown: value.cooldown,
             reaction_trigger: value.reaction_trigger,
+            surface: value.surface,
+            damage_roll: value.damage_roll,
+            constraints: value
+                .constraints
+                .into_iter()
+                .map(ActionConstraint::from)
+                .collect(),
         }
     }
 }
@@ -5,9 +5,9 @@ use serde::{Deserialize, Serialize};
 use crate::{
     components::{
         ability::{Ability, AbilityScoreDistribution},
-        class::{Class, SpellcastingRules, Subclass},
+        class::{Class, MulticlassPrerequisite, SpellcastingRules, Subclass},
         dice::DieSize,
-        id::{ActionId, ClassId, EffectId, ResourceId, SubclassId},
+        id::{ActionId, ClassId, EffectId, ResourceId, SpellId, SubclassId},
         items::equipment::{armor::ArmorType, weapon::WeaponCategory},
         level_up::{ChoiceItem, ChoiceSpec, LevelUpPrompt},
         resource::{ResourceBudget, ResourceBudgetKind},
@@ -42,6 +42,8 @@ pub struct ClassDefinition {
     pub hp_per_level: u8,
     pub default_abilities: AbilityScoreDistribution,
     pub saving_throw_proficiencies: [Ability; 2],
+    #[serde(default)]
+    pub multiclass_prerequisites: Vec<MulticlassPrerequisite>,
     pub subclass_level: u8,
     pub subclasses: HashSet<SubclassId>,
     pub feat_levels: HashSet<u8>,
@@ -55,6 +57,10 @@ pub struct ClassDefinition {
     pub resources_by_level: HashMap<u8, Vec<ClassResourceDefinition>>,
     pub prompts_by_level: HashMap<u8, Vec<LevelUpPrompt>>,
     pub actions_by_level: HashMap<u8, Vec<ActionId>>,
+    #[serde(default)]
+    pub spells_by_level: HashMap<u8, Vec<SpellId>>,
+    #[serde(default)]
+    pub expanded_spell_list: HashSet<SpellId>,
 }
 
 impl From<ClassDefinition> for Class {
@@ -97,6 +103,7 @@ impl From<ClassDefinition> for Class {
             def.hp_per_level,
             def.default_abilities,
             def.saving_throw_proficiencies,
+            def.multiclass_prerequisites,
             def.subclass_level,
             def.subclasses,
             def.feat_levels,
@@ -109,6 +116,8 @@ impl From<ClassDefinition> for Class {
             resources_by_level,
             def.prompts_by_level,
             def.actions_by_level,
+            def.spells_by_level,
+            def.expanded_spell_list,
         )
     }
 }
@@ -130,6 +139,19 @@ impl RegistryReferenceCollector for ClassDefinition {
                 prompt.collect_registry_references(collector);
             }
         }
+        for spell_list in self.spells_by_level.values() {
+            for spell in spell_list {
+                collector.add(RegistryReference::Spell(spell.clone()));
+            }
+        }
+        for spell in &self.expanded_spell_list {
+            collector.add(RegistryReference::Spell(spell.clone()));
+        }
+        if let Some(spellcasting) = &self.spellcasting {
+            collector.add(RegistryReference::SpellList(
+                spellcasting.spell_list.clone(),
+            ));
+        }
     }
 }
 
@@ -200,5 +222,23 @@ impl RegistryReferenceCollector for Subclass {
                 collector.add(RegistryReference::Action(action.clone()));
             }
         }
+        for prompts in self.base.prompts_by_level.values() {
+            for prompt in prompts {
+                prompt.collect_registry_references(collector);
+            }
+        }
+        for spell_list in self.base.spells_by_level.values() {
+            for spell in spell_list {
+                collector.add(RegistryReference::Spell(spell.clone()));
+            }
+        }
+        for spell in &self.base.expanded_spell_list {
+            collector.add(RegistryReference::Spell(spell.clone()));
+        }
+        if let Some(spellcasting) = &self.base.spellcasting {
+            collector.add(RegistryReference::SpellList(
+                spellcasting.spell_list.clone(),
+            ));
+        }
     }
 }
@@ -6,22 +6,24 @@ use serde::{Deserialize, Serialize};
 use crate::{
     components::{
         ability::{Ability, AbilityScoreMap},
-        actions::action::{ActionContext, AttackRollFunction, SavingThrowFunction},
+        actions::action::{
+            ActionContext, AttackRollFunction, SavingThrowFunction, SkillCheckFunction,
+        },
         d20::{D20Check, D20CheckDC},
         damage::{AttackRoll, DamageSource},
         id::SpellId,
         modifier::{Modifiable, ModifierSet, ModifierSource},
         proficiency::{Proficiency, ProficiencyLevel},
-        saving_throw::{SavingThrowDC, SavingThrowKind},
-        spells::{
-            spell::SPELL_CASTING_ABILITIES,
-            spellbook::{SpellSource, Spellbook},
-        },
+        saving_throw::{BASE_SAVE_DC, SavingThrowDC, SavingThrowKind},
+        skill::Skill,
+        spells::spellbook::SpellSource,
     },
-    registry::registry::{ClassesRegistry, SpellsRegistry},
     systems,
 };
 
+/// Aiming at a specific part of the body makes an attack harder to land
+pub const CALLED_SHOT_PENALTY: i32 = -5;
+
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(try_from = "String", into = "String")]
 pub struct AttackRollProvider {
@@ -45,10 +47,28 @@ impl FromStr for AttackRollProvider {
                     weapon_attack_roll(world, entity, target, action_context)
                 },
             ) as Arc<AttackRollFunction>,
+            "called_shot_attack_roll" => Arc::new(
+                |world: &World, entity: Entity, target: Entity, action_context: &ActionContext| {
+                    let mut attack_roll = weapon_attack_roll(world, entity, target, action_context);
+                    attack_roll.d20_check.add_modifier(
+                        ModifierSource::Custom("Called Shot".to_string()),
+                        CALLED_SHOT_PENALTY,
+                    );
+                    attack_roll
+                },
+            ) as Arc<AttackRollFunction>,
+            "thrown_attack_roll" => Arc::new(
+                |world: &World, entity: Entity, target: Entity, action_context: &ActionContext| {
+                    if let ActionContext::Weapon { slot } = action_context.base() {
+                        return systems::loadout::thrown_attack_roll(world, entity, target, slot);
+                    }
+                    panic!("Action context must be Weapon");
+                },
+            ) as Arc<AttackRollFunction>,
             "spell_attack_roll" => Arc::new({
                 |world: &World, entity: Entity, target: Entity, action_context: &ActionContext| {
                     let (source, id) =
-                        if let ActionContext::Spell { source, id, .. } = action_context {
+                        if let ActionContext::Spell { source, id, .. } = action_context.base() {
                             (source, id)
                         } else {
                             panic!("Action context must be Spell for spell_attack_roll");
@@ -88,40 +108,12 @@ fn weapon_attack_roll(
     target: Entity,
     action_context: &ActionContext,
 ) -> AttackRoll {
-    if let ActionContext::Weapon { slot } = action_context {
+    if let ActionContext::Weapon { slot } = action_context.base() {
         return systems::loadout::weapon_attack_roll(world, entity, target, slot);
     }
     panic!("Action context must be Weapon");
 }
 
-fn get_spellcasting_ability_from_source(
-    world: &World,
-    caster: Entity,
-    source: &SpellSource,
-) -> Ability {
-    match source {
-        SpellSource::Class(class_and_subclass) => {
-            if let Some(class) = ClassesRegistry::get(&class_and_subclass.class)
-                && let Some(spellcasting_rules) =
-                    class.spellcasting_rules(&class_and_subclass.subclass)
-            {
-                spellcasting_rules.spellcasting_ability
-            } else {
-                panic!(
-                    "Class {:?} does not have spellcasting capabilities",
-                    class_and_subclass
-                );
-            }
-        }
-        SpellSource::Granted { .. } => {
-            // Use the highest spellcasting ability
-            systems::helpers::get_component::<AbilityScoreMap>(world, caster)
-                .get_max_score(SPELL_CASTING_ABILITIES)
-                .0
-        }
-    }
-}
-
 fn spell_attack_roll(
     world: &World,
     caster: Entity,
@@ -132,27 +124,15 @@ fn spell_attack_roll(
     source: &SpellSource,
     spell_id: &SpellId,
 ) -> AttackRoll {
-    let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, caster);
-    let spellcasting_ability = get_spellcasting_ability_from_source(world, caster, source);
-    let proficiency_bonus = systems::helpers::level(world, caster)
-        .unwrap()
-        .proficiency_bonus();
-
     let mut roll = D20Check::new(Proficiency::new(
         ProficiencyLevel::Proficient,
         ModifierSource::None,
     ));
-    let spellcasting_modifier = ability_scores
-        .ability_modifier(&spellcasting_ability)
-        .total();
-    roll.add_modifier(
-        ModifierSource::Ability(spellcasting_ability),
-        spellcasting_modifier,
-    );
-    roll.add_modifier(
-        ModifierSource::Proficiency(ProficiencyLevel::Proficient),
-        proficiency_bonus as i32,
-    );
+    for (modifier_source, value) in
+        systems::spells::spell_attack_bonus(world, caster, source).iter()
+    {
+        roll.add_modifier(modifier_source.clone(), *value);
+    }
 
     AttackRoll::new(roll, DamageSource::Spell(spell_id.clone()))
 }
@@ -184,16 +164,18 @@ impl FromStr for SavingThrowProvider {
         let ability: Ability = serde_plain::from_str(parts[1]).unwrap();
 
         let function = match parts[0] {
-            "weapon_save_dc" => Arc::new(
-                |world: &World, entity: Entity, action_context: &ActionContext| {
-                    weapon_save_dc(world, entity, action_context)
-                },
-            ) as Arc<SavingThrowFunction>,
+            "weapon_save_dc" => Arc::new({
+                let ability = ability.clone();
+                move |world: &World, entity: Entity, _: &ActionContext| {
+                    weapon_save_dc(world, entity, ability)
+                }
+            }) as Arc<SavingThrowFunction>,
 
             "spell_save_dc" => Arc::new({
                 let ability = ability.clone();
                 move |world: &World, entity: Entity, action_context: &ActionContext| {
-                    let source = if let ActionContext::Spell { source, .. } = action_context {
+                    let source = if let ActionContext::Spell { source, .. } = action_context.base()
+                    {
                         source
                     } else {
                         panic!("Action context must be Spell for spell_save_dc");
@@ -201,6 +183,14 @@ impl FromStr for SavingThrowProvider {
                     spell_save_dc(world, entity, ability, source)
                 }
             }) as Arc<SavingThrowFunction>,
+
+            "unarmed_strike_dc" => Arc::new({
+                let ability = ability.clone();
+                move |world: &World, entity: Entity, _: &ActionContext| {
+                    unarmed_strike_dc(world, entity, ability)
+                }
+            }) as Arc<SavingThrowFunction>,
+
             _ => {
                 return Err(format!("Unknown SavingThrowProvider: {}", s));
             }
@@ -227,52 +217,129 @@ impl From<SavingThrowProvider> for String {
     }
 }
 
-const BASE_SAVE_DC: i32 = 8;
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SkillCheckProvider {
+    pub raw: String,
+    pub function: Arc<SkillCheckFunction>,
+}
 
-fn weapon_save_dc(
-    world: &World,
-    entity: Entity,
-    action_context: &ActionContext,
-) -> D20CheckDC<SavingThrowKind> {
-    todo!("Implement weapon_save_dc");
-    // https://www.reddit.com/r/BaldursGate3/comments/16kynf6/how_is_the_save_dc_of_maneuvers_calculated/
-    // https://bg3.wiki/wiki/Dice_rolls#Weapon_action_DC
-    // if let ActionContext::Weapon { slot } = action_context {
-    //     return systems::combat::weapon_saving_throw_dc(world, entity, slot);
-    // }
-    // panic!("Action context must be Weapon");
+impl Display for SkillCheckProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
 }
 
-fn spell_save_dc(
+impl FromStr for SkillCheckProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Example format: "medicine;10"
+
+        let parts: Vec<&str> = s.split(';').collect();
+        if parts.len() != 2 {
+            return Err(format!("Invalid SkillCheckProvider format: {}", s));
+        }
+
+        let skill: Skill = serde_plain::from_str(parts[0])
+            .map_err(|_| format!("Unknown skill in SkillCheckProvider: {}", s))?;
+        let dc: i32 = parts[1]
+            .parse()
+            .map_err(|_| format!("Invalid DC in SkillCheckProvider: {}", s))?;
+
+        let function = Arc::new(move |_: &World, _: Entity, _: &ActionContext| D20CheckDC {
+            key: skill,
+            dc: ModifierSet::from(ModifierSource::Base, dc),
+        }) as Arc<SkillCheckFunction>;
+
+        Ok(Self {
+            raw: s.to_string(),
+            function,
+        })
+    }
+}
+
+impl TryFrom<String> for SkillCheckProvider {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<SkillCheckProvider> for String {
+    fn from(provider: SkillCheckProvider) -> Self {
+        provider.raw
+    }
+}
+
+/// DC of saving throws against weapon actions, e.g. knocking a creature down
+/// with a weapon, which uses the better of Strength and Dexterity like in BG3:
+/// https://bg3.wiki/wiki/Dice_rolls#Weapon_action_DC
+fn weapon_save_dc(world: &World, entity: Entity, saving_throw_ability: Ability) -> SavingThrowDC {
+    let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
+    // Entities without a level don't have a proficiency bonus to add
+    let proficiency_bonus =
+        systems::helpers::level(world, entity).map_or(0, |level| level.proficiency_bonus());
+
+    let ability = [Ability::Strength, Ability::Dexterity]
+        .into_iter()
+        .max_by_key(|ability| ability_scores.ability_modifier(ability).total())
+        .unwrap();
+
+    let mut dc = ModifierSet::new();
+    dc.add_modifier(ModifierSource::Base, BASE_SAVE_DC);
+    dc.add_modifier(
+        ModifierSource::Ability(ability),
+        ability_scores.ability_modifier(&ability).total(),
+    );
+    dc.add_modifier(
+        ModifierSource::Proficiency(ProficiencyLevel::Proficient),
+        proficiency_bonus as i32,
+    );
+
+    D20CheckDC {
+        key: SavingThrowKind::Ability(saving_throw_ability),
+        dc,
+    }
+}
+
+/// DC of the saving throw against the Grapple and Shove options of an Unarmed
+/// Strike, which is based on Strength
+fn unarmed_strike_dc(
     world: &World,
-    caster: Entity,
+    entity: Entity,
     saving_throw_ability: Ability,
-    source: &SpellSource,
 ) -> SavingThrowDC {
-    let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, caster);
-    let spellcasting_ability = get_spellcasting_ability_from_source(world, caster, source);
-    let proficiency_bonus = systems::helpers::level(world, caster)
-        .unwrap()
-        .proficiency_bonus();
-
-    let mut spell_save_dc = ModifierSet::new();
-    spell_save_dc.add_modifier(ModifierSource::Base, BASE_SAVE_DC);
-    let spellcasting_modifier = ability_scores
-        .ability_modifier(&spellcasting_ability)
-        .total();
-    spell_save_dc.add_modifier(
-        ModifierSource::Ability(spellcasting_ability),
-        spellcasting_modifier,
+    let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
+    let proficiency_bonus =
+        systems::helpers::level(world, entity).map_or(0, |level| level.proficiency_bonus());
+
+    let mut dc = ModifierSet::new();
+    dc.add_modifier(ModifierSource::Base, BASE_SAVE_DC);
+    dc.add_modifier(
+        ModifierSource::Ability(Ability::Strength),
+        ability_scores.ability_modifier(&Ability::Strength).total(),
     );
-    // TODO: Not sure if Proficiency is the correct modifier source here, since I don't think
-    // you can have e.g. Expertise in spell save DCs.
-    spell_save_dc.add_modifier(
+    dc.add_modifier(
         ModifierSource::Proficiency(ProficiencyLevel::Proficient),
         proficiency_bonus as i32,
     );
 
     D20CheckDC {
         key: SavingThrowKind::Ability(saving_throw_ability),
-        dc: spell_save_dc,
+        dc,
+    }
+}
+
+fn spell_save_dc(
+    world: &World,
+    caster: Entity,
+    saving_throw_ability: Ability,
+    source: &SpellSource,
+) -> SavingThrowDC {
+    D20CheckDC {
+        key: SavingThrowKind::Ability(saving_throw_ability),
+        dc: systems::spells::spell_dc(world, caster, source),
     }
 }
@@ -12,7 +12,7 @@ use crate::{
     components::{
         actions::action::{ActionContext, DamageFunction, HealFunction},
         damage::{DamageRoll, DamageSource, DamageType},
-        dice::{DiceSet, DiceSetRoll},
+        dice::{DiceSet, DiceSetRoll, DieSize},
         items::equipment::{slots::EquipmentSlot, weapon::WeaponKind},
         modifier::{Modifiable, ModifierSet, ModifierSource},
     },
@@ -24,17 +24,30 @@ use crate::{
 };
 
 static DAMAGE_DEFAULTS: LazyLock<HashMap<String, Arc<DamageFunction>>> = LazyLock::new(|| {
-    HashMap::from([(
-        "weapon_damage_roll".to_string(),
-        Arc::new(
-            |world: &World, entity: Entity, action_context: &ActionContext| {
-                if let ActionContext::Weapon { slot } = action_context {
-                    return systems::loadout::weapon_damage_roll(world, entity, slot);
-                }
-                panic!("Action context must be Weapon");
-            },
-        ) as Arc<DamageFunction>,
-    )])
+    HashMap::from([
+        (
+            "weapon_damage_roll".to_string(),
+            Arc::new(
+                |world: &World, entity: Entity, action_context: &ActionContext| {
+                    if let ActionContext::Weapon { slot } = action_context.base() {
+                        return systems::loadout::weapon_damage_roll(world, entity, slot);
+                    }
+                    panic!("Action context must be Weapon");
+                },
+            ) as Arc<DamageFunction>,
+        ),
+        (
+            "thrown_damage_roll".to_string(),
+            Arc::new(
+                |world: &World, entity: Entity, action_context: &ActionContext| {
+                    if let ActionContext::Weapon { slot } = action_context.base() {
+                        return systems::loadout::thrown_damage_roll(world, entity, slot);
+                    }
+                    panic!("Action context must be Weapon");
+                },
+            ) as Arc<DamageFunction>,
+        ),
+    ])
 });
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -158,6 +171,27 @@ impl FromStr for HealEquation {
             });
         }
 
+        // Flat amounts without any dice, e.g. "amount" for Lay on Hands
+        if let Ok(expression) = Parser::new(s).parse_int_expression() {
+            let function = Arc::new(
+                move |world: &World, entity: Entity, action_context: &ActionContext| {
+                    let amount = expression
+                        .evaluate(world, entity, action_context, &PARSER_VARIABLES)
+                        .unwrap();
+
+                    DiceSetRoll {
+                        dice: DiceSet::new(0, DieSize::D4),
+                        modifiers: ModifierSet::from(ModifierSource::Base, amount),
+                    }
+                },
+            );
+
+            return Ok(HealEquation {
+                raw: s.to_string(),
+                function,
+            });
+        }
+
         Err(format!("Unknown heal formula: {}", s))
     }
 }
@@ -1,30 +1,46 @@
 use hecs::{Entity, World};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+use tracing::warn;
+use uom::si::{f32::Length, length::foot};
 
 use crate::{
     components::{
         ability::AbilityScoreMap,
         actions::action::ActionContext,
+        control::ControlTemplate,
         d20::{D20CheckKey, D20CheckSet},
         damage::{
-            DamageMitigationEffect, DamageMitigationResult, DamageResistances, DamageRollResult,
+            AttackRoll, DamageMitigationEffect, DamageMitigationResult, DamageResistances,
+            DamageRollResult,
         },
         effects::{
-            effect::{Effect, EffectInstance, EffectKind},
+            effect::{
+                Effect, EffectEndTrigger, EffectInstance, EffectInstanceTemplate, EffectKind,
+                EffectTag, StackingPolicy,
+            },
             hooks::{
                 ActionHook, ArmorClassHook, AttackRollHook, DamageRollResultHook, DeathHook,
-                PostDamageMitigationHook, PreDamageMitigationHook, ResourceCostHook,
+                IncomingAttackRollHook, PostDamageMitigationHook, PreDamageMitigationHook,
+                ResourceCostHook, ThrowHook,
             },
         },
-        health::hit_points::{HitPoints, TemporaryHitPoints},
-        id::{ActionId, EffectId, ResourceId, ScriptId},
+        health::{
+            healing::{HealingModifierEffect, HealingModifiers, HealingOperation},
+            hit_points::{HitPoints, TemporaryHitPoints},
+        },
+        id::{ActionId, EffectId, ItemId, ResourceId, ScriptId},
         items::equipment::armor::ArmorClass,
         modifier::{KeyedModifiable, Modifiable, ModifierSource},
         resource::{ResourceAmount, ResourceAmountMap, ResourceMap},
         saving_throw::SavingThrowSet,
+        shapechange::Shapechanged,
         skill::SkillSet,
         speed::Speed,
+        spells::metamagic::{KnownMetamagic, Metamagic},
         time::TimeDuration,
     },
     engine::event::ActionData,
@@ -44,11 +60,12 @@ use crate::{
     scripts::{
         script::ScriptFunction,
         script_api::{
-            ScriptActionView, ScriptDamageMitigationResult, ScriptDamageRollResult,
-            ScriptEffectView, ScriptEntityView, ScriptOptionalEntityView, ScriptResourceCost,
+            ScriptActionView, ScriptAttackRoll, ScriptDamageMitigationResult,
+            ScriptDamageRollResult, ScriptEffectView, ScriptEntityView, ScriptOptionalEntityView,
+            ScriptResourceCost,
         },
     },
-    systems,
+    systems::{self, generator::MonsterFilter},
 };
 
 // TODO: Should this be it's own time module?
@@ -57,6 +74,7 @@ use crate::{
 pub enum TimeDurationDefinition {
     RealTime { time: TimeExpressionDefinition },
     Turns { turns: u32 },
+    Rounds { rounds: u32 },
 }
 
 // impl Evaluable for TimeDurationDefinition {
@@ -87,6 +105,7 @@ impl From<TimeDurationDefinition> for TimeDuration {
                 TimeDuration::from_seconds(time.evaluate_without_variables().unwrap().value)
             }
             TimeDurationDefinition::Turns { turns } => TimeDuration::from_turns(turns),
+            TimeDurationDefinition::Rounds { rounds } => TimeDuration::from_rounds(rounds),
         }
     }
 }
@@ -101,6 +120,34 @@ pub struct EffectDefinition {
     #[serde(default)]
     pub replaces: Option<EffectId>,
 
+    /// How the effect behaves when it's applied to an entity which already
+    /// has it. By default effects of the same name don't stack.
+    #[serde(default)]
+    pub stacking: StackingPolicy,
+
+    #[serde(default)]
+    pub tags: HashSet<EffectTag>,
+
+    /// Actions which end the effect when the entity with the effect performs
+    /// them, e.g. `["attack", "spell"]` for Invisibility
+    #[serde(default)]
+    pub end_on: HashSet<EffectEndTrigger>,
+
+    /// Whether the effect ends when the applier or one of its allies damages
+    /// the entity with the effect, e.g. Charm Person
+    #[serde(default)]
+    pub end_on_damage: bool,
+
+    /// The applier decides the turns of the entity with the effect, e.g.
+    /// Dominate Person
+    #[serde(default)]
+    pub control: Option<ControlTemplate>,
+
+    /// Applied to the entity when the effect ends, e.g. the lethargy after
+    /// Haste
+    #[serde(default)]
+    pub followed_by: Option<EffectInstanceTemplate>,
+
     /// Simple effect modifiers like:
     /// - Ability score changes
     /// - Skill modifiers
@@ -113,6 +160,9 @@ pub struct EffectDefinition {
     /// Other hooks can be either pattern-based or script-based
     #[serde(default)]
     pub pre_attack_roll: Vec<AttackRollHookDefinition>,
+    /// Hooks for attack rolls made against the entity with the effect
+    #[serde(default)]
+    pub pre_incoming_attack_roll: Vec<AttackRollHookDefinition>,
     // #[serde(default)]
     // pub post_attack_roll: Vec<AttackRollResultHookDef>,
     #[serde(default)]
@@ -132,6 +182,8 @@ pub struct EffectDefinition {
     pub on_resource_cost: Vec<ResourceCostHookDefinition>,
     #[serde(default)]
     pub on_death: Vec<DeathHookDefinition>,
+    #[serde(default)]
+    pub on_throw: Vec<ThrowHookDefinition>,
 }
 
 impl From<EffectDefinition> for Effect {
@@ -139,6 +191,13 @@ impl From<EffectDefinition> for Effect {
         let effect_id = definition.id.clone();
 
         let mut effect = Effect::new(effect_id.clone(), definition.kind, definition.description);
+        effect.replaces = definition.replaces;
+        effect.stacking = definition.stacking;
+        effect.tags = definition.tags;
+        effect.end_on = definition.end_on;
+        effect.end_on_damage = definition.end_on_damage;
+        effect.control = definition.control;
+        effect.followed_by = definition.followed_by;
 
         // 1. Simple persistent modifiers
         // Build on_apply from all modifiers
@@ -172,6 +231,12 @@ impl From<EffectDefinition> for Effect {
             effect.pre_attack_roll = AttackRollHookDefinition::combine_hooks(hooks);
         }
 
+        // Build pre_incoming_attack_roll hooks
+        {
+            let hooks = collect_effect_hooks(&definition.pre_incoming_attack_roll, &effect_id);
+            effect.pre_incoming_attack_roll = AttackRollHookDefinition::combine_hooks(hooks);
+        }
+
         // Build post_damage_roll hooks
         {
             let hooks = collect_effect_hooks(&definition.post_damage_roll, &effect_id);
@@ -214,6 +279,12 @@ impl From<EffectDefinition> for Effect {
             effect.on_death = DeathHookDefinition::combine_hooks(hooks);
         }
 
+        // Build on_throw hooks
+        {
+            let hooks = collect_effect_hooks(&definition.on_throw, &effect_id);
+            effect.on_throw = ThrowHookDefinition::combine_hooks(hooks);
+        }
+
         effect
     }
 }
@@ -223,11 +294,17 @@ impl RegistryReferenceCollector for EffectDefinition {
         if let Some(replaces) = &self.replaces {
             collector.add(RegistryReference::Effect(replaces.clone()));
         }
+        if let Some(followed_by) = &self.followed_by {
+            collector.add(RegistryReference::Effect(followed_by.effect_id.clone()));
+        }
         for modifier in &self.modifiers {
             match modifier {
                 EffectModifier::Resource { resource, .. } => {
                     collector.add(RegistryReference::Resource(resource.clone()));
                 }
+                EffectModifier::Action { action } => {
+                    collector.add(RegistryReference::Action(action.clone()));
+                }
                 _ => { /* No references to collect */ }
             }
         }
@@ -242,6 +319,17 @@ impl RegistryReferenceCollector for EffectDefinition {
                 _ => { /* No references to collect */ }
             }
         }
+        for hook in &self.pre_incoming_attack_roll {
+            match hook {
+                AttackRollHookDefinition::Script { script } => {
+                    collector.add(RegistryReference::Script(
+                        script.clone(),
+                        ScriptFunction::IncomingAttackRollHook,
+                    ));
+                }
+                _ => { /* No references to collect */ }
+            }
+        }
         for hook in &self.post_damage_roll {
             match hook {
                 DamageRollResultHookDefinition::Script { script } => {
@@ -283,6 +371,15 @@ impl RegistryReferenceCollector for EffectDefinition {
                 }
             }
         }
+        for hook in &self.on_throw {
+            match hook {
+                ThrowHookDefinition::Returning { returning } => {
+                    for item in returning {
+                        collector.add(RegistryReference::Item(item.clone()));
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -301,6 +398,9 @@ pub enum EffectModifier {
     DamageResistance {
         resistance: DamageResistanceProvider,
     },
+    Healing {
+        healing: HealingOperation,
+    },
     Resource {
         resource: ResourceId,
         amount: ResourceAmount,
@@ -311,6 +411,26 @@ pub enum EffectModifier {
     TemporaryHitPoints {
         temporary_hit_points: HealEquation,
     },
+    /// Reduces the hit point maximum while the effect lasts, e.g. a Wight's
+    /// Life Drain
+    MaxHitPointsReduction {
+        max_hit_points_reduction: u32,
+    },
+    /// Take the strongest form matching the filter, unless a form was chosen
+    /// already, e.g. for Wild Shape
+    Shapechange {
+        shapechange: MonsterFilter,
+    },
+    /// An action the entity can take while the effect lasts, e.g. the extra
+    /// action granted by Haste
+    Action {
+        action: ActionId,
+    },
+    /// A metamagic option the entity knows while the effect lasts, e.g. the
+    /// options picked by a Sorcerer
+    Metamagic {
+        metamagic: Metamagic,
+    },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -329,13 +449,16 @@ impl EffectModifier {
         context: Option<&ActionContext>,
     ) {
         let source = ModifierSource::Effect(effect_id.clone());
+        // Flat modifiers of stacking effects grow with every stack, e.g. the
+        // penalties of exhaustion
+        let stacks = systems::effects::stacks(world, entity, effect_id).max(1);
         match self {
             EffectModifier::Ability { ability: modifier } => {
                 let mut abilities =
                     systems::helpers::get_component_mut::<AbilityScoreMap>(world, entity);
                 match phase {
                     EffectPhase::Apply => {
-                        abilities.add_modifier(&modifier.ability, source, modifier.delta);
+                        abilities.apply(&modifier.ability, source, modifier.operation);
                     }
                     EffectPhase::Unapply => {
                         abilities.remove_modifier(&modifier.ability, &source);
@@ -345,7 +468,7 @@ impl EffectModifier {
 
             EffectModifier::Skill { skill: modifier } => {
                 let mut skills = systems::helpers::get_component_mut::<SkillSet>(world, entity);
-                Self::apply_d20_check_modifier(&mut *skills, modifier, source, phase);
+                Self::apply_d20_check_modifier(&mut *skills, modifier, source, phase, stacks);
             }
 
             EffectModifier::SavingThrow {
@@ -353,7 +476,7 @@ impl EffectModifier {
             } => {
                 let mut saves =
                     systems::helpers::get_component_mut::<SavingThrowSet>(world, entity);
-                Self::apply_d20_check_modifier(&mut *saves, modifier, source, phase);
+                Self::apply_d20_check_modifier(&mut *saves, modifier, source, phase, stacks);
             }
 
             EffectModifier::DamageResistance {
@@ -364,6 +487,7 @@ impl EffectModifier {
                 let mitigation_effect = DamageMitigationEffect {
                     source: source.clone(),
                     operation: modifier.operation.clone(),
+                    bypassed_by: modifier.bypassed_by.clone(),
                 };
                 match phase {
                     EffectPhase::Apply => {
@@ -375,15 +499,49 @@ impl EffectModifier {
                 }
             }
 
+            EffectModifier::Healing { healing } => {
+                let mut modifiers =
+                    systems::helpers::get_component_mut::<HealingModifiers>(world, entity);
+                let healing_effect = HealingModifierEffect {
+                    source: source.clone(),
+                    operation: healing.clone(),
+                };
+                match phase {
+                    EffectPhase::Apply => {
+                        modifiers.add_effect(healing_effect);
+                    }
+                    EffectPhase::Unapply => {
+                        modifiers.remove_effect(&healing_effect);
+                    }
+                }
+            }
+
             EffectModifier::Resource { resource, amount } => {
                 let mut resources =
                     systems::helpers::get_component_mut::<ResourceMap>(world, entity);
                 match phase {
                     EffectPhase::Apply => {
-                        resources.add_uses(resource, amount);
+                        // The effect can also give the entity a resource it
+                        // doesn't have yet, e.g. the Hasted Action
+                        if resources.get(resource).is_some() {
+                            resources.add_uses(resource, amount);
+                        } else {
+                            resources.add(resource.clone(), amount.clone().into(), true);
+                        }
                     }
                     EffectPhase::Unapply => {
                         resources.remove_uses(resource, amount);
+                        if resources.get(resource).is_some_and(|budget| {
+                            budget.max_uses().iter().all(|uses| {
+                                matches!(
+                                    uses,
+                                    ResourceAmount::Flat(0)
+                                        | ResourceAmount::Tiered { amount: 0, .. }
+                                )
+                            })
+                        }) {
+                            resources.remove(resource);
+                        }
                     }
                 }
             }
@@ -395,7 +553,7 @@ impl EffectModifier {
                         SpeedModifier::Flat(bonus) => {
                             speed.add_flat_modifier(
                                 source,
-                                bonus.evaluate_without_variables().unwrap().value,
+                                bonus.evaluate_without_variables().unwrap().value * stacks as f32,
                             );
                         }
                         SpeedModifier::Multiplier(multiplier) => {
@@ -433,6 +591,68 @@ impl EffectModifier {
                     }
                 }
             }
+
+            EffectModifier::MaxHitPointsReduction {
+                max_hit_points_reduction: amount,
+            } => match phase {
+                EffectPhase::Apply => {
+                    systems::health::reduce_max_hit_points(world, entity, *amount);
+                }
+                EffectPhase::Unapply => {
+                    systems::health::restore_max_hit_points_by(world, entity, *amount);
+                }
+            },
+
+            EffectModifier::Shapechange {
+                shapechange: filter,
+            } => {
+                let shapechanged_by_effect = world
+                    .get::<&Shapechanged>(entity)
+                    .is_ok_and(|shapechanged| shapechanged.source == source);
+                match phase {
+                    EffectPhase::Apply => {
+                        // The form might have been chosen already, e.g. with Wild
+                        // Shape, in which case the effect only keeps track of it
+                        if !shapechanged_by_effect
+                            && let Err(error) = systems::shapechange::shapechange_by_filter(
+                                world, entity, filter, source,
+                            )
+                        {
+                            warn!("Failed to shapechange {:?}: {:?}", entity, error);
+                        }
+                    }
+                    EffectPhase::Unapply => {
+                        if shapechanged_by_effect {
+                            systems::shapechange::restore_original_form(world, entity);
+                        }
+                    }
+                }
+            }
+
+            EffectModifier::Action { action } => match phase {
+                EffectPhase::Apply => {
+                    systems::actions::grant_action(world, entity, action, source);
+                }
+                EffectPhase::Unapply => {
+                    systems::actions::revoke_action(world, entity, action, &source);
+                }
+            },
+
+            EffectModifier::Metamagic { metamagic } => match phase {
+                EffectPhase::Apply => {
+                    if let Ok(mut known_metamagic) = world.get::<&mut KnownMetamagic>(entity) {
+                        known_metamagic.0.insert(*metamagic);
+                    } else {
+                        let _ =
+                            world.insert_one(entity, KnownMetamagic(BTreeSet::from([*metamagic])));
+                    }
+                }
+                EffectPhase::Unapply => {
+                    if let Ok(mut known_metamagic) = world.get::<&mut KnownMetamagic>(entity) {
+                        known_metamagic.0.remove(metamagic);
+                    }
+                }
+            },
         }
     }
 
@@ -441,6 +661,7 @@ impl EffectModifier {
         modifier: &D20CheckModifierProvider<K>,
         source: ModifierSource,
         phase: EffectPhase,
+        stacks: u32,
     ) where
         K: D20CheckKey + DeserializeOwned,
     {
@@ -448,7 +669,7 @@ impl EffectModifier {
             EffectPhase::Apply => {
                 if let Some(delta) = modifier.delta {
                     for kind in &modifier.kind {
-                        modifiable.add_modifier(kind, source.clone(), delta);
+                        modifiable.add_modifier(kind, source.clone(), delta * stacks as i32);
                     }
                 }
                 if let Some(advantage_type) = modifier.advantage {
@@ -456,11 +677,28 @@ impl EffectModifier {
                         modifiable.add_advantage(kind, advantage_type, source.clone());
                     }
                 }
+                if let Some(override_outcome) = modifier.override_outcome {
+                    for kind in &modifier.kind {
+                        modifiable.add_override(kind, override_outcome, source.clone());
+                    }
+                }
+                if let Some(dice) = modifier.dice {
+                    for kind in &modifier.kind {
+                        modifiable.add_dice_modifier(kind, source.clone(), dice);
+                    }
+                }
+                if let Some(proficiency) = modifier.proficiency {
+                    for kind in &modifier.kind {
+                        modifiable.add_proficiency(kind, proficiency, source.clone());
+                    }
+                }
             }
             EffectPhase::Unapply => {
                 for kind in &modifier.kind {
                     modifiable.remove_modifier(kind, &source);
                     modifiable.remove_advantage(kind, &source);
+                    modifiable.remove_override(kind, &source);
+                    modifiable.remove_proficiency(kind, &source);
                 }
             }
         }
@@ -505,7 +743,8 @@ impl HookEffect<AttackRollHook> for AttackRollHookDefinition {
                 let modifier_source = ModifierSource::Effect(effect.clone());
                 Arc::new({
                     let modifier = modifier.clone();
-                    move |_world, _entity, attack_roll| {
+                    let effect = effect.clone();
+                    move |world, entity, attack_roll| {
                         if let Some(damage_source) = &modifier.source
                             && *damage_source != attack_roll.source
                         {
@@ -516,9 +755,17 @@ impl HookEffect<AttackRollHook> for AttackRollHookDefinition {
                         if let Some(attack_modifier) = &modifier.modifier {
                             match attack_modifier {
                                 AttackRollModifier::FlatBonus(bonus) => {
+                                    let stacks =
+                                        systems::effects::stacks(world, entity, &effect).max(1);
+                                    attack_roll.d20_check.add_modifier(
+                                        modifier_source.clone(),
+                                        *bonus * stacks as i32,
+                                    );
+                                }
+                                AttackRollModifier::Dice(dice) => {
                                     attack_roll
                                         .d20_check
-                                        .add_modifier(modifier_source.clone(), *bonus);
+                                        .add_dice_modifier(modifier_source.clone(), *dice);
                                 }
                                 AttackRollModifier::Advantage(advantage) => {
                                     attack_roll
@@ -529,6 +776,13 @@ impl HookEffect<AttackRollHook> for AttackRollHookDefinition {
                                 AttackRollModifier::CritThreshold(threshold) => {
                                     attack_roll.reduce_crit_threshold(*threshold);
                                 }
+                                AttackRollModifier::CritOnHit(within) => {
+                                    // The target isn't known here, so distance
+                                    // requirements can't be checked
+                                    if within.is_none() {
+                                        attack_roll.set_crit_on_hit(modifier_source.clone());
+                                    }
+                                }
                             }
                         }
                     }
@@ -550,6 +804,102 @@ impl HookEffect<AttackRollHook> for AttackRollHookDefinition {
     }
 }
 
+impl HookEffect<IncomingAttackRollHook> for AttackRollHookDefinition {
+    fn build_hook(&self, effect: &EffectId) -> IncomingAttackRollHook {
+        match self {
+            AttackRollHookDefinition::Modifier { modifier } => {
+                let modifier_source = ModifierSource::Effect(effect.clone());
+                Arc::new({
+                    let modifier = modifier.clone();
+                    move |world, target, attacker, attack_roll| {
+                        if let Some(damage_source) = &modifier.source
+                            && *damage_source != attack_roll.source
+                        {
+                            // Only apply if the damage source matches
+                            return;
+                        }
+
+                        if let Some(attack_modifier) = &modifier.modifier {
+                            match attack_modifier {
+                                AttackRollModifier::FlatBonus(bonus) => {
+                                    attack_roll
+                                        .d20_check
+                                        .add_modifier(modifier_source.clone(), *bonus);
+                                }
+                                AttackRollModifier::Dice(dice) => {
+                                    attack_roll
+                                        .d20_check
+                                        .add_dice_modifier(modifier_source.clone(), *dice);
+                                }
+                                AttackRollModifier::Advantage(advantage) => {
+                                    attack_roll
+                                        .d20_check
+                                        .advantage_tracker_mut()
+                                        .add(*advantage, modifier_source.clone());
+                                }
+                                AttackRollModifier::CritThreshold(threshold) => {
+                                    attack_roll.reduce_crit_threshold(*threshold);
+                                }
+                                AttackRollModifier::CritOnHit(within) => {
+                                    let in_range = match within {
+                                        Some(distance) => {
+                                            systems::geometry::distance_between_entities(
+                                                world, attacker, target,
+                                            )
+                                            .is_some_and(|d| {
+                                                d <= Length::new::<foot>(*distance as f32)
+                                            })
+                                        }
+                                        None => true,
+                                    };
+                                    if in_range {
+                                        attack_roll.set_crit_on_hit(modifier_source.clone());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                })
+            }
+
+            AttackRollHookDefinition::Script { script } => {
+                let effect_id = effect.clone();
+                let script_id = script.clone();
+                Arc::new(
+                    move |world: &World,
+                          target: Entity,
+                          attacker: Entity,
+                          attack_roll: &mut AttackRoll| {
+                        let target_view = ScriptEntityView::new_from_world(world, target);
+                        let attacker_view = ScriptEntityView::new_from_world(world, attacker);
+                        let script_attack_roll = ScriptAttackRoll::take_from(
+                            attack_roll,
+                            ModifierSource::Effect(effect_id.clone()),
+                        );
+
+                        systems::scripts::evaluate_incoming_attack_roll_hook(
+                            &script_id,
+                            &target_view,
+                            &attacker_view,
+                            &script_attack_roll,
+                        );
+
+                        *attack_roll = script_attack_roll.into_inner();
+                    },
+                )
+            }
+        }
+    }
+
+    fn combine_hooks(hooks: Vec<IncomingAttackRollHook>) -> IncomingAttackRollHook {
+        Arc::new(move |world, target, attacker, attack_roll| {
+            for hook in &hooks {
+                hook(world, target, attacker, attack_roll);
+            }
+        })
+    }
+}
+
 pub enum DamageRollHookDefinition {
     // …
 }
@@ -908,6 +1258,33 @@ impl HookEffect<DeathHook> for DeathHookDefinition {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ThrowHookDefinition {
+    /// The listed items fly back to the hand of the thrower, e.g. a Dwarven
+    /// Thrower
+    Returning { returning: Vec<ItemId> },
+}
+
+impl HookEffect<ThrowHook> for ThrowHookDefinition {
+    fn build_hook(&self, _effect: &EffectId) -> ThrowHook {
+        match self {
+            ThrowHookDefinition::Returning { returning } => {
+                let returning = returning.clone();
+                Arc::new(move |_world: &World, _thrower: Entity, item: &ItemId| {
+                    returning.contains(item)
+                })
+            }
+        }
+    }
+
+    fn combine_hooks(hooks: Vec<ThrowHook>) -> ThrowHook {
+        Arc::new(move |world: &World, thrower: Entity, item: &ItemId| {
+            hooks.iter().any(|hook| hook(world, thrower, item))
+        })
+    }
+}
+
 fn take_entity_view_once(
     world: &mut World,
     taken: &mut HashMap<Entity, ScriptEntityView>,
@@ -10,7 +10,7 @@ use crate::{
         items::{
             equipment::{
                 armor::Armor,
-                equipment::EquipmentItem,
+                equipment::{EquipmentItem, OnHit},
                 weapon::{Weapon, WeaponCategory, WeaponKind, WeaponProperties},
             },
             inventory::ItemInstance,
@@ -31,6 +31,8 @@ pub struct WeaponDefinition {
     pub damage: Vec<(DiceSet, DamageType)>,
     pub extra_weapon_actions: Vec<ActionId>,
     pub effects: Vec<EffectId>,
+    #[serde(default)]
+    pub on_hit: OnHit,
 }
 
 impl From<WeaponDefinition> for Weapon {
@@ -44,6 +46,7 @@ impl From<WeaponDefinition> for Weapon {
             def.extra_weapon_actions,
             def.effects,
         )
+        .with_on_hit(def.on_hit)
     }
 }
 
@@ -63,6 +66,9 @@ impl RegistryReferenceCollector for Weapon {
         for effect in self.effects() {
             collector.add(RegistryReference::Effect(effect.clone()));
         }
+        for effect in &self.on_hit().effects {
+            collector.add(RegistryReference::Effect(effect.effect_id.clone()));
+        }
     }
 }
 
@@ -87,6 +93,25 @@ impl RegistryReferenceCollector for ItemInstance {
             ItemInstance::Equipment(equipment_item) => {
                 equipment_item.collect_registry_references(collector);
             }
+            ItemInstance::Spell(spell_item) => {
+                collector.add(RegistryReference::Spell(spell_item.spell.clone()));
+            }
+            ItemInstance::Coating(coating_item) => {
+                for effect in &coating_item.coating.effects {
+                    collector.add(RegistryReference::Effect(effect.effect_id.clone()));
+                }
+            }
+            ItemInstance::Tool(tool) => {
+                for action in &tool.actions {
+                    collector.add(RegistryReference::Action(action.clone()));
+                }
+            }
+            ItemInstance::Supply(_) => { /* No references to collect */ }
+            ItemInstance::Light(light) => {
+                if let Some(fuel) = &light.fuel {
+                    collector.add(RegistryReference::Item(fuel.clone()));
+                }
+            }
         }
     }
 }
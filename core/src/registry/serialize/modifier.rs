@@ -1,13 +1,16 @@
-use std::{fmt::Display, str::FromStr};
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use strum::IntoEnumIterator;
 
 use crate::{
     components::{
-        ability::Ability,
-        d20::AdvantageType,
-        damage::{DamageSource, DamageType, MitigationOperation},
+        ability::{Ability, AbilityScoreOperation},
+        d20::{AdvantageType, D20CheckOverride},
+        damage::{DamageSource, DamageTag, DamageType, MitigationOperation},
+        dice::DiceSet,
+        modifier::DiceModifier,
+        proficiency::ProficiencyLevel,
         saving_throw::SavingThrowKind,
         skill::Skill,
     },
@@ -66,6 +69,12 @@ pub struct D20CheckModifierProvider<T: Clone + DeserializeOwned + IntoEnumIterat
     pub delta: Option<i32>,
     #[serde(skip)]
     pub advantage: Option<AdvantageType>,
+    #[serde(skip)]
+    pub override_outcome: Option<D20CheckOverride>,
+    #[serde(skip)]
+    pub dice: Option<DiceModifier>,
+    #[serde(skip)]
+    pub proficiency: Option<ProficiencyLevel>,
     pub raw: String,
 }
 
@@ -81,6 +90,10 @@ where
         // "stealth-1"
         // "investigation+2"
         // "strength disadvantage"
+        // "dexterity auto_failure"
+        // "all+1d4"
+        // "constitution proficient"
+        // "stealth expertise"
 
         let normalized = normalize_spec_string(input);
 
@@ -88,25 +101,33 @@ where
             split_first_delimiter(&normalized, &[' ', '+', '-'], "D20CheckModifierProvider")?;
 
         if check_str.to_lowercase().eq("all") {
-            let (delta, advantage) = parse_delta_or_advantage(modifier_str, &normalized)?;
+            let (delta, advantage, override_outcome, dice, proficiency) =
+                parse_d20_check_modifier(modifier_str, &normalized)?;
 
             return Ok(D20CheckModifierProvider {
                 raw: normalized,
                 kind: T::iter().collect(),
                 delta,
                 advantage,
+                override_outcome,
+                dice,
+                proficiency,
             });
         }
 
         let kind: T = parse_plain_enum(check_str, "check kind", &normalized)?;
 
-        let (delta, advantage) = parse_delta_or_advantage(modifier_str, &normalized)?;
+        let (delta, advantage, override_outcome, dice, proficiency) =
+            parse_d20_check_modifier(modifier_str, &normalized)?;
 
         Ok(D20CheckModifierProvider {
             raw: normalized,
             kind: vec![kind],
             delta,
             advantage,
+            override_outcome,
+            dice,
+            proficiency,
         })
     }
 }
@@ -140,7 +161,7 @@ pub struct AbilityModifierProvider {
     #[serde(skip)]
     pub ability: Ability,
     #[serde(skip)]
-    pub delta: i32,
+    pub operation: AbilityScoreOperation,
     pub raw: String,
 }
 
@@ -151,20 +172,32 @@ impl FromStr for AbilityModifierProvider {
         // Examples:
         // "strength+2"
         // "intelligence-1"
+        // "intelligence=19"
+        // "strength max 30"
 
-        let (name, delta_part) =
-            split_first_delimiter(input, &['+', '-'], "AbilityModifierProvider")?;
+        let (name, operation_part) =
+            split_first_delimiter(input, &['+', '-', '=', ' '], "AbilityModifierProvider")?;
 
         let ability: Ability = parse_plain_enum(name, "ability", input)?;
 
-        let delta: i32 = delta_part
-            .parse()
-            .map_err(|_| format!("Invalid modifier in '{}'", input))?;
+        let parse_value = |value: &str| {
+            value
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid modifier in '{}'", input))
+        };
+        let operation = if let Some(value) = operation_part.strip_prefix('=') {
+            AbilityScoreOperation::Set(parse_value(value)?)
+        } else if let Some(value) = operation_part.strip_prefix("max") {
+            AbilityScoreOperation::RaiseMaximum(parse_value(value)?)
+        } else {
+            AbilityScoreOperation::Modify(parse_value(operation_part)?)
+        };
 
         Ok(AbilityModifierProvider {
             raw: input.to_string(),
             ability,
-            delta,
+            operation,
         })
     }
 }
@@ -178,6 +211,8 @@ pub struct DamageResistanceProvider {
     pub damage_type: DamageType,
     #[serde(skip)]
     pub operation: MitigationOperation,
+    #[serde(skip)]
+    pub bypassed_by: BTreeSet<DamageTag>,
     pub raw: String,
 }
 
@@ -189,13 +224,28 @@ impl FromStr for DamageResistanceProvider {
         // "fire resistance"
         // "cold immunity"
         // "force -2"
+        // "nonmagical slashing resistance"
+        // "nonmagical nonsilvered piercing resistance"
         let normalized = normalize_spec_string(input);
         let parts: Vec<&str> = normalized.split_whitespace().collect();
 
-        if parts.len() != 2 {
+        if parts.len() < 2 {
             return Err(format!("Invalid DamageResistanceProvider: {}", input));
         }
 
+        let (tag_parts, parts) = parts.split_at(parts.len() - 2);
+
+        let mut bypassed_by = BTreeSet::new();
+        for tag_part in tag_parts {
+            let Some(tag) = tag_part.strip_prefix("non") else {
+                return Err(format!(
+                    "Invalid damage tag '{}' in '{}', expected e.g. 'nonmagical'",
+                    tag_part, input
+                ));
+            };
+            bypassed_by.insert(parse_plain_enum(tag, "damage tag", input)?);
+        }
+
         let damage_type: DamageType = parse_plain_enum(parts[0].trim(), "damage type", input)?;
 
         let operation: MitigationOperation =
@@ -215,6 +265,7 @@ impl FromStr for DamageResistanceProvider {
             raw: input.to_string(),
             damage_type,
             operation,
+            bypassed_by,
         })
     }
 }
@@ -225,8 +276,14 @@ impl_string_backed_spec!(DamageResistanceProvider);
 #[serde(try_from = "String", into = "String")]
 pub enum AttackRollModifier {
     FlatBonus(i32),
+    /// A bonus or penalty rolled along with the attack, e.g. +1d4 from Bless
+    Dice(DiceModifier),
     Advantage(AdvantageType),
     CritThreshold(u8),
+    /// Any hit is a critical hit, optionally only if the attacker is within the
+    /// given distance (in feet) of the target, e.g. attacks against a paralyzed
+    /// creature. The distance is only checked for incoming attack rolls
+    CritOnHit(Option<u32>),
 }
 
 impl Display for AttackRollModifier {
@@ -239,12 +296,17 @@ impl Display for AttackRollModifier {
                     write!(f, "{}", bonus)
                 }
             }
+            AttackRollModifier::Dice(modifier) => write!(f, "{}", modifier),
             AttackRollModifier::Advantage(advantage) => {
                 write!(f, "{}", serde_plain::to_string(advantage).unwrap())
             }
             AttackRollModifier::CritThreshold(modifier) => {
                 write!(f, "crit(-{})", modifier)
             }
+            AttackRollModifier::CritOnHit(within) => match within {
+                Some(distance) => write!(f, "crit_on_hit({}ft)", distance),
+                None => write!(f, "crit_on_hit"),
+            },
         }
     }
 }
@@ -267,6 +329,27 @@ impl FromStr for AttackRollModifier {
             return Ok(AttackRollModifier::CritThreshold(threshold));
         }
 
+        if normalized == "crit_on_hit" {
+            return Ok(AttackRollModifier::CritOnHit(None));
+        }
+
+        if normalized.starts_with("crit_on_hit(") && normalized.ends_with(')') {
+            let inner = &normalized[12..normalized.len() - 1];
+            let distance: u32 = inner
+                .trim_end_matches("ft")
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid crit on hit distance in '{}'", input))?;
+            return Ok(AttackRollModifier::CritOnHit(Some(distance)));
+        }
+
+        if normalized.contains('d') {
+            return Ok(AttackRollModifier::Dice(parse_dice_modifier(
+                &normalized,
+                input,
+            )?));
+        }
+
         let bonus: i32 = normalized
             .parse()
             .map_err(|_| format!("Invalid flat bonus in '{}'", input))?;
@@ -389,24 +472,62 @@ where
     serde_plain::from_str(name).map_err(|_| format!("Unknown {} in '{}'", field_name, whole))
 }
 
-fn parse_delta_or_advantage(
+type D20CheckModifier = (
+    Option<i32>,
+    Option<AdvantageType>,
+    Option<D20CheckOverride>,
+    Option<DiceModifier>,
+    Option<ProficiencyLevel>,
+);
+
+fn parse_d20_check_modifier(
     modifier_str: &str,
     full_input: &str,
-) -> Result<(Option<i32>, Option<AdvantageType>), String> {
+) -> Result<D20CheckModifier, String> {
     let modifier_str = modifier_str.trim();
     match modifier_str {
-        "" => Ok((None, None)),
-        "advantage" => Ok((None, Some(AdvantageType::Advantage))),
-        "disadvantage" => Ok((None, Some(AdvantageType::Disadvantage))),
+        "" => Ok((None, None, None, None, None)),
+        "advantage" => Ok((None, Some(AdvantageType::Advantage), None, None, None)),
+        "disadvantage" => Ok((None, Some(AdvantageType::Disadvantage), None, None, None)),
+        "auto_success" => Ok((None, None, Some(D20CheckOverride::AutoSuccess), None, None)),
+        "auto_failure" => Ok((None, None, Some(D20CheckOverride::AutoFailure), None, None)),
+        "proficient" => Ok((None, None, None, None, Some(ProficiencyLevel::Proficient))),
+        "expertise" => Ok((None, None, None, None, Some(ProficiencyLevel::Expertise))),
+        "half_proficient" => Ok((None, None, None, None, Some(ProficiencyLevel::Half))),
+        _ if modifier_str.contains('d') => Ok((
+            None,
+            None,
+            None,
+            Some(parse_dice_modifier(modifier_str, full_input)?),
+            None,
+        )),
         _ => {
             let delta: i32 = modifier_str
                 .parse()
                 .map_err(|_| format!("Invalid modifier in '{}'", full_input))?;
-            Ok((Some(delta), None))
+            Ok((Some(delta), None, None, None, None))
         }
     }
 }
 
+/// Parse a signed dice modifier, e.g. "+1d4" or "-1d4"
+fn parse_dice_modifier(modifier_str: &str, full_input: &str) -> Result<DiceModifier, String> {
+    let (penalty, dice_str) = match modifier_str.split_at_checked(1) {
+        Some(("+", dice_str)) => (false, dice_str),
+        Some(("-", dice_str)) => (true, dice_str),
+        _ => (false, modifier_str),
+    };
+    let dice: DiceSet = dice_str
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid dice modifier in '{}'", full_input))?;
+    Ok(if penalty {
+        DiceModifier::penalty(dice)
+    } else {
+        DiceModifier::bonus(dice)
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(try_from = "String", into = "String")]
 pub enum SpeedModifier {
@@ -474,6 +595,8 @@ impl_string_backed_spec!(SpeedModifierProvider);
 
 #[cfg(test)]
 mod tests {
+    use crate::components::dice::DieSize;
+
     use super::*;
 
     #[test]
@@ -493,6 +616,11 @@ mod tests {
         let spec: SkillModifierProvider = "all disadvantage".parse().unwrap();
         assert_eq!(spec.kind.len(), Skill::iter().count());
         assert_eq!(spec.advantage, Some(AdvantageType::Disadvantage));
+
+        let spec: SkillModifierProvider = "stealth expertise".parse().unwrap();
+        assert_eq!(spec.kind[0], Skill::Stealth);
+        assert_eq!(spec.proficiency, Some(ProficiencyLevel::Expertise));
+        assert_eq!(spec.advantage, None);
     }
 
     #[test]
@@ -518,17 +646,55 @@ mod tests {
         let spec: SavingThrowModifierProvider = "all-1".parse().unwrap();
         assert_eq!(spec.kind.len(), SavingThrowKind::iter().count());
         assert_eq!(spec.delta, Some(-1));
+
+        let spec: SavingThrowModifierProvider = "all+1d4".parse().unwrap();
+        assert_eq!(spec.kind.len(), SavingThrowKind::iter().count());
+        assert_eq!(
+            spec.dice,
+            Some(DiceModifier::bonus(DiceSet::new(1, DieSize::D4)))
+        );
+        assert_eq!(spec.delta, None);
+
+        let spec: SavingThrowModifierProvider = "cha-1d4".parse().unwrap();
+        assert_eq!(
+            spec.dice,
+            Some(DiceModifier::penalty(DiceSet::new(1, DieSize::D4)))
+        );
+
+        let spec: SavingThrowModifierProvider = "con proficient".parse().unwrap();
+        assert_eq!(
+            spec.kind[0],
+            SavingThrowKind::Ability(Ability::Constitution)
+        );
+        assert_eq!(spec.proficiency, Some(ProficiencyLevel::Proficient));
+        assert_eq!(spec.delta, None);
+
+        let spec: SavingThrowModifierProvider = "strength auto_failure".parse().unwrap();
+        assert_eq!(spec.kind[0], SavingThrowKind::Ability(Ability::Strength));
+        assert_eq!(spec.override_outcome, Some(D20CheckOverride::AutoFailure));
+        assert_eq!(spec.delta, None);
+        assert_eq!(spec.advantage, None);
     }
 
     #[test]
     fn test_ability_modifier_provider_parsing() {
         let spec: AbilityModifierProvider = "strength+2".parse().unwrap();
         assert_eq!(spec.ability, Ability::Strength);
-        assert_eq!(spec.delta, 2);
+        assert_eq!(spec.operation, AbilityScoreOperation::Modify(2));
 
         let spec: AbilityModifierProvider = "intelligence-1".parse().unwrap();
         assert_eq!(spec.ability, Ability::Intelligence);
-        assert_eq!(spec.delta, -1);
+        assert_eq!(spec.operation, AbilityScoreOperation::Modify(-1));
+
+        let spec: AbilityModifierProvider = "intelligence=19".parse().unwrap();
+        assert_eq!(spec.ability, Ability::Intelligence);
+        assert_eq!(spec.operation, AbilityScoreOperation::Set(19));
+
+        let spec: AbilityModifierProvider = "strength max 30".parse().unwrap();
+        assert_eq!(spec.ability, Ability::Strength);
+        assert_eq!(spec.operation, AbilityScoreOperation::RaiseMaximum(30));
+
+        assert!("strength max".parse::<AbilityModifierProvider>().is_err());
     }
 
     #[test]
@@ -544,5 +710,42 @@ mod tests {
         let spec: DamageResistanceProvider = "force -2".parse().unwrap();
         assert_eq!(spec.damage_type, DamageType::Force);
         assert_eq!(spec.operation, MitigationOperation::FlatReduction(2));
+        assert!(spec.bypassed_by.is_empty());
+    }
+
+    #[test]
+    fn test_damage_resistance_provider_bypass_tags() {
+        let spec: DamageResistanceProvider = "nonmagical nonsilvered piercing resistance"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.damage_type, DamageType::Piercing);
+        assert_eq!(spec.operation, MitigationOperation::Resistance);
+        assert_eq!(
+            spec.bypassed_by,
+            BTreeSet::from([DamageTag::Magical, DamageTag::Silvered])
+        );
+
+        assert!(
+            "magical slashing resistance"
+                .parse::<DamageResistanceProvider>()
+                .is_err()
+        );
+        assert!(
+            "nonshiny slashing resistance"
+                .parse::<DamageResistanceProvider>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_attack_roll_modifier_crit_on_hit() {
+        let modifier: AttackRollModifier = "crit_on_hit".parse().unwrap();
+        assert!(matches!(modifier, AttackRollModifier::CritOnHit(None)));
+
+        let modifier: AttackRollModifier = "crit_on_hit(5ft)".parse().unwrap();
+        assert!(matches!(modifier, AttackRollModifier::CritOnHit(Some(5))));
+        assert_eq!(modifier.to_string(), "crit_on_hit(5ft)");
+
+        assert!("crit_on_hit(close)".parse::<AttackRollModifier>().is_err());
     }
 }
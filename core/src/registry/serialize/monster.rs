@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        ability::{Ability, AbilityScore, AbilityScoreMap},
+        dice::DiceSetRoll,
+        faction::FactionSet,
+        id::{ItemId, MonsterId, Name},
+        images::CreatureImages,
+        items::loot::LootTable,
+        level::ChallengeRating,
+        species::{CreatureSize, CreatureType},
+        speed::Speed,
+        spells::innate::InnateSpellcasting,
+    },
+    entities::monster::MonsterTemplate,
+    registry::{
+        registry_validation::{ReferenceCollector, RegistryReference, RegistryReferenceCollector},
+        serialize::quantity::LengthExpressionDefinition,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonsterDefinition {
+    pub id: MonsterId,
+    pub name: String,
+    pub challenge_rating: u8,
+    pub hit_points: DiceSetRoll,
+    pub size: CreatureSize,
+    pub creature_type: CreatureType,
+    pub speed: LengthExpressionDefinition,
+    pub abilities: HashMap<Ability, i32>,
+    #[serde(default)]
+    pub factions: FactionSet,
+    #[serde(default)]
+    pub equipment: Vec<ItemId>,
+    #[serde(default)]
+    pub innate_spellcasting: Option<InnateSpellcasting>,
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub personality: Vec<String>,
+    #[serde(default)]
+    pub loot: LootTable,
+    #[serde(default)]
+    pub images: CreatureImages,
+}
+
+impl From<MonsterDefinition> for MonsterTemplate {
+    fn from(value: MonsterDefinition) -> Self {
+        let mut abilities = AbilityScoreMap::new();
+        for (ability, score) in value.abilities {
+            abilities.set(ability, AbilityScore::new(ability, score));
+        }
+
+        MonsterTemplate {
+            id: value.id,
+            name: Name::new(value.name),
+            challenge_rating: ChallengeRating::new(value.challenge_rating),
+            hit_points: value.hit_points,
+            size: value.size,
+            creature_type: value.creature_type,
+            speed: Speed::new(value.speed.evaluate_without_variables().unwrap()),
+            abilities,
+            factions: value.factions,
+            equipment: value.equipment,
+            innate_spellcasting: value.innate_spellcasting,
+            names: value.names,
+            personality: value.personality,
+            loot: value.loot,
+            images: value.images,
+        }
+    }
+}
+
+impl RegistryReferenceCollector for MonsterDefinition {
+    fn collect_registry_references(&self, collector: &mut ReferenceCollector) {
+        for faction in &self.factions {
+            collector.add(RegistryReference::Faction(faction.clone()));
+        }
+        for item in &self.equipment {
+            collector.add(RegistryReference::Item(item.clone()));
+        }
+        if let Some(innate_spellcasting) = &self.innate_spellcasting {
+            for spell in innate_spellcasting.spells.keys() {
+                collector.add(RegistryReference::Spell(spell.clone()));
+            }
+        }
+        self.loot.collect_registry_references(collector);
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use uom::si::{
     f32::{Length, Time},
     length::{foot, meter},
-    time::{hour, minute, second},
+    time::{day, hour, minute, second},
 };
 
 use crate::{
@@ -52,6 +52,7 @@ impl QuantityDimension for TimeDim {
             "s" | "sec" | "second" | "seconds" => Ok(Time::new::<second>(value)),
             "min" | "minute" | "minutes" => Ok(Time::new::<minute>(value)),
             "hr" | "hour" | "hours" => Ok(Time::new::<hour>(value)),
+            "d" | "day" | "days" => Ok(Time::new::<day>(value)),
             other => Err(format!("Unknown time unit: '{}'", other)),
         }
     }
@@ -184,6 +185,7 @@ mod tests {
             }),
             level: 5,
             id: SpellId::new("nat20_core", "spell.test"),
+            metamagic: Vec::new(),
         };
 
         let length = expr
@@ -217,6 +219,7 @@ mod tests {
             }),
             level: 3,
             id: SpellId::new("nat20_core", "spell.test"),
+            metamagic: Vec::new(),
         };
 
         let time = expr
@@ -2,9 +2,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
+        actions::action::DamageRollPolicy,
         id::{ScriptId, SpellId},
         resource::ResourceAmountMap,
-        spells::spell::{MagicSchool, Spell, SpellFlag},
+        spells::spell::{MagicSchool, MaterialComponent, Spell, SpellFlag},
+        surface::SurfaceTemplate,
     },
     registry::{
         registry_validation::{ReferenceCollector, RegistryReference, RegistryReferenceCollector},
@@ -21,11 +23,19 @@ pub struct SpellDefinition {
     pub school: MagicSchool,
     #[serde(default)]
     pub flags: Vec<SpellFlag>,
+    #[serde(default)]
+    pub material: Option<MaterialComponent>,
     pub kind: ActionKindDefinition,
     pub resource_cost: ResourceAmountMap,
     pub targeting: TargetingDefinition,
     #[serde(default)]
     pub reaction_trigger: Option<ScriptId>,
+    /// Surface left behind in the area of the spell, e.g. Grease
+    #[serde(default)]
+    pub surface: Option<SurfaceTemplate>,
+    /// Whether the damage is rolled once for every target, e.g. Fireball
+    #[serde(default)]
+    pub damage_roll: DamageRollPolicy,
     /// TODO: Is there a better way to represent this?
     ///
     /// Some spells like Hex or Hunter's Mark grant an alternative version of themselves
@@ -45,10 +55,13 @@ impl From<SpellDefinition> for Spell {
             value.base_level,
             value.school,
             value.flags,
+            value.material,
             value.kind.into(),
             value.resource_cost,
             value.targeting.function(),
             value.reaction_trigger,
+            value.surface,
+            value.damage_roll,
             value.granted_spells,
         )
     }
@@ -7,16 +7,20 @@ use std::{
 
 use hecs::{Entity, World};
 use serde::{Deserialize, Serialize};
-use uom::si::length::meter;
+use uom::si::{angle::degree, f32::Angle, length::meter};
 
 use crate::{
     components::{
         actions::{
             action::{ActionContext, TargetingFunction},
-            targeting::{AreaShape, EntityFilter, TargetingContext, TargetingKind, TargetingRange},
+            targeting::{
+                AreaShape, EntityFilter, ItemFilter, TargetingContext, TargetingKind,
+                TargetingRange,
+            },
         },
         health::life_state::LifeState,
         items::equipment::loadout::Loadout,
+        species::{CreatureSize, CreatureType},
     },
     registry::serialize::{
         parser::{Evaluable, EvaluationError, IntExpression, Parser},
@@ -33,7 +37,7 @@ static TARGETING_DEFAULTS: LazyLock<HashMap<String, Arc<TargetingFunction>>> =
                 "weapon_targeting".to_string(),
                 Arc::new(
                     |world: &World, entity: Entity, action_context: &ActionContext| {
-                        if let ActionContext::Weapon { slot } = action_context {
+                        if let ActionContext::Weapon { slot } = action_context.base() {
                             TargetingContext {
                                 kind: TargetingKind::Single,
                                 range: systems::helpers::get_component::<Loadout>(world, entity)
@@ -42,7 +46,32 @@ static TARGETING_DEFAULTS: LazyLock<HashMap<String, Arc<TargetingFunction>>> =
                                     .range()
                                     .clone(),
                                 require_line_of_sight: true,
+                                require_line_of_effect: true,
                                 allowed_targets: EntityFilter::not_dead(),
+                                allowed_items: None,
+                            }
+                        } else {
+                            panic!("Action context must be Weapon");
+                        }
+                    },
+                ) as Arc<TargetingFunction>,
+            ),
+            (
+                "thrown_weapon_targeting".to_string(),
+                Arc::new(
+                    |world: &World, entity: Entity, action_context: &ActionContext| {
+                        if let ActionContext::Weapon { slot } = action_context.base() {
+                            TargetingContext {
+                                kind: TargetingKind::Single,
+                                range: systems::helpers::get_component::<Loadout>(world, entity)
+                                    .weapon_in_hand(slot)
+                                    .unwrap()
+                                    .thrown_range()
+                                    .clone(),
+                                require_line_of_sight: true,
+                                require_line_of_effect: true,
+                                allowed_targets: EntityFilter::not_dead(),
+                                allowed_items: None,
                             }
                         } else {
                             panic!("Action context must be Weapon");
@@ -105,10 +134,12 @@ pub enum AreaShapeDefinition {
     Sphere {
         radius: LengthExpressionDefinition,
     },
-    // Arc {
-    //     angle: IntExpressionDefinition,
-    //     length: LengthExpressionDefinition,
-    // },
+    /// Cones originate at the actor. The angle is in degrees, and a 5e cone
+    /// whose width matches its length is roughly 53 degrees.
+    Arc {
+        angle: IntExpressionDefinition,
+        length: LengthExpressionDefinition,
+    },
     Cube {
         side: LengthExpressionDefinition,
     },
@@ -133,13 +164,14 @@ impl Evaluable for AreaShapeDefinition {
         variables: &VariableMap,
     ) -> Result<AreaShape, EvaluationError> {
         match self {
-            // AreaShapeDefinition::Arc {
-            //     angle,
-            //     length: radius,
-            // } => Ok(AreaShape::Arc {
-            //     angle: angle.evaluate(world, entity, context, variables)?,
-            //     length: radius.evaluate(world, entity, context, variables)?,
-            // }),
+            AreaShapeDefinition::Arc { angle, length } => Ok(AreaShape::Arc {
+                angle: Angle::new::<degree>(
+                    angle
+                        .expression
+                        .evaluate(world, entity, context, variables)? as f32,
+                ),
+                length: length.evaluate(world, entity, context, variables)?,
+            }),
             AreaShapeDefinition::Sphere { radius } => Ok(AreaShape::Sphere {
                 radius: radius.evaluate(world, entity, context, variables)?,
             }),
@@ -213,22 +245,50 @@ pub enum EntityFilterDefinition {
     All,
     Characters,
     Monsters,
+    Objects,
     LifeStates(HashSet<LifeState>),
     NotLifeStates(HashSet<LifeState>),
     NotDead,
+    /// Creatures no more than one size larger than the actor, e.g. for grappling
+    /// and shoving
+    AtMostOneSizeLarger,
+    /// Living creatures of the given type, e.g. `{"creature_type": "humanoid"}`
+    /// for Charm Person
+    CreatureType(CreatureType),
+    /// Creatures at 0 hit points which are making death saving throws, e.g. for
+    /// stabilizing them
+    Dying,
+    /// Only the actor itself, e.g. for actions targeting its own items
+    Actor,
+    GroundItems,
+    Locked,
+    Levers,
 }
 
 impl EntityFilterDefinition {
-    pub fn evaluate(&self) -> EntityFilter {
+    pub fn evaluate(&self, world: &World, entity: Entity) -> EntityFilter {
         match self {
             EntityFilterDefinition::All => EntityFilter::All,
             EntityFilterDefinition::Characters => EntityFilter::Characters,
             EntityFilterDefinition::Monsters => EntityFilter::Monsters,
+            EntityFilterDefinition::Objects => EntityFilter::Objects,
             EntityFilterDefinition::LifeStates(states) => EntityFilter::LifeStates(states.clone()),
             EntityFilterDefinition::NotLifeStates(states) => {
                 EntityFilter::NotLifeStates(states.clone())
             }
             EntityFilterDefinition::NotDead => EntityFilter::not_dead(),
+            EntityFilterDefinition::AtMostOneSizeLarger => {
+                let size = systems::helpers::get_component::<CreatureSize>(world, entity);
+                EntityFilter::MaxSize(size.larger().unwrap_or(CreatureSize::Gargantuan))
+            }
+            EntityFilterDefinition::CreatureType(creature_type) => {
+                EntityFilter::CreatureType(creature_type.clone())
+            }
+            EntityFilterDefinition::Dying => EntityFilter::Dying,
+            EntityFilterDefinition::Actor => EntityFilter::Specific(HashSet::from([entity])),
+            EntityFilterDefinition::GroundItems => EntityFilter::GroundItems,
+            EntityFilterDefinition::Locked => EntityFilter::Locked,
+            EntityFilterDefinition::Levers => EntityFilter::Levers,
         }
     }
 }
@@ -238,7 +298,11 @@ pub struct TargetingContextDefinition {
     pub kind: TargetingKindDefinition,
     pub range: LengthExpressionDefinition,
     pub require_line_of_sight: bool,
+    #[serde(default)]
+    pub require_line_of_effect: bool,
     pub allowed_targets: EntityFilterDefinition,
+    #[serde(default)]
+    pub allowed_items: Option<ItemFilter>,
 }
 
 impl TargetingContextDefinition {
@@ -261,7 +325,9 @@ impl TargetingContextDefinition {
                     kind,
                     range,
                     require_line_of_sight: definition.require_line_of_sight,
-                    allowed_targets: definition.allowed_targets.evaluate(),
+                    require_line_of_effect: definition.require_line_of_effect,
+                    allowed_targets: definition.allowed_targets.evaluate(world, entity),
+                    allowed_items: definition.allowed_items.clone(),
                 }
             }
         })
@@ -0,0 +1,181 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use hecs::{Entity, World};
+use parry3d::na::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    components::{
+        actions::action::{UtilityFunction, UtilityOutcome},
+        id::MonsterId,
+        lock::UnlockMethod,
+    },
+    engine::{event::ActionData, game_state::GameState},
+    systems::{self, familiar::FamiliarError, locks::LockError},
+};
+
+type SummonFunction =
+    fn(&mut World, Entity, &MonsterId, &Point3<f32>) -> Result<Entity, FamiliarError>;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UtilityProvider {
+    pub raw: String,
+    pub function: Arc<UtilityFunction>,
+}
+
+impl Display for UtilityProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl FromStr for UtilityProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let function = match s {
+            "open_lock" => Arc::new(
+                |game_state: &mut GameState, action_data: &ActionData, target: Entity| {
+                    unlock(game_state, target, UnlockMethod::Key, |game_state| {
+                        systems::locks::open(game_state, action_data.actor, target)
+                    })
+                },
+            ) as Arc<UtilityFunction>,
+            "pick_lock" => Arc::new(
+                |game_state: &mut GameState, action_data: &ActionData, target: Entity| {
+                    unlock(game_state, target, UnlockMethod::Picked, |game_state| {
+                        systems::locks::pick(game_state, action_data.actor, target)
+                    })
+                },
+            ) as Arc<UtilityFunction>,
+            "force_lock" => Arc::new(
+                |game_state: &mut GameState, action_data: &ActionData, target: Entity| {
+                    unlock(game_state, target, UnlockMethod::Forced, |game_state| {
+                        systems::locks::force(game_state, action_data.actor, target)
+                    })
+                },
+            ) as Arc<UtilityFunction>,
+            "pull_lever" => Arc::new(
+                |game_state: &mut GameState, _: &ActionData, target: Entity| {
+                    match systems::levers::pull(&mut game_state.world, target) {
+                        Some(active) => UtilityOutcome::LeverPulled { active },
+                        None => UtilityOutcome::NoEffect,
+                    }
+                },
+            ) as Arc<UtilityFunction>,
+            _ => {
+                // Summons and shapechanges take the form of the creature, e.g.
+                // "summon_familiar nat20_core::monster.owl"
+                let Some((kind, form)) = s.split_once(' ') else {
+                    return Err(format!("Unknown UtilityProvider: {}", s));
+                };
+                let form = MonsterId::from_str(form.trim())
+                    .map_err(|_| format!("Invalid form in UtilityProvider: {}", s))?;
+                if kind == "wild_shape" {
+                    Arc::new(
+                        move |game_state: &mut GameState, _: &ActionData, target: Entity| {
+                            match systems::shapechange::wild_shape(
+                                &mut game_state.world,
+                                target,
+                                &form,
+                            ) {
+                                Ok(()) => UtilityOutcome::Shapechanged(form.clone()),
+                                Err(error) => {
+                                    warn!(
+                                        "{:?} failed to wild shape into {}: {:?}",
+                                        target, form, error
+                                    );
+                                    UtilityOutcome::NoEffect
+                                }
+                            }
+                        },
+                    ) as Arc<UtilityFunction>
+                } else {
+                    let summon: SummonFunction = match kind {
+                        "summon_familiar" => systems::familiar::summon,
+                        "summon_steed" => systems::familiar::summon_steed,
+                        _ => return Err(format!("Unknown UtilityProvider: {}", s)),
+                    };
+                    Arc::new(
+                        move |game_state: &mut GameState, _: &ActionData, target: Entity| {
+                            summon_next_to(game_state, target, &form, summon)
+                        },
+                    ) as Arc<UtilityFunction>
+                }
+            }
+        };
+
+        Ok(Self {
+            raw: s.to_string(),
+            function,
+        })
+    }
+}
+
+impl TryFrom<String> for UtilityProvider {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<UtilityProvider> for String {
+    fn from(provider: UtilityProvider) -> Self {
+        provider.raw
+    }
+}
+
+/// Summon a companion for the target in the closest spot next to it
+fn summon_next_to(
+    game_state: &mut GameState,
+    target: Entity,
+    form: &MonsterId,
+    summon: SummonFunction,
+) -> UtilityOutcome {
+    let Some(position) = systems::geometry::get_foot_position(&game_state.world, target) else {
+        return UtilityOutcome::NoEffect;
+    };
+    let beside = position + Vector3::new(Length::new::<foot>(5.0).get::<meter>(), 0.0, 0.0);
+    let beside =
+        systems::geometry::navmesh_nearest_point(&game_state.geometry, beside).unwrap_or(beside);
+
+    match summon(&mut game_state.world, target, form, &beside) {
+        Ok(_) => UtilityOutcome::Summoned(form.clone()),
+        Err(error) => {
+            warn!("{:?} failed to summon {}: {:?}", target, form, error);
+            UtilityOutcome::NoEffect
+        }
+    }
+}
+
+/// Try to open the lock on the target. Checks made against a lock resolve
+/// right away unless someone reacts to them, so whether the lock opened can be
+/// read straight off the target afterwards.
+fn unlock(
+    game_state: &mut GameState,
+    target: Entity,
+    method: UnlockMethod,
+    attempt: impl FnOnce(&mut GameState) -> Result<(), LockError>,
+) -> UtilityOutcome {
+    if !systems::locks::is_locked(&game_state.world, target) {
+        return UtilityOutcome::NoEffect;
+    }
+    match attempt(game_state) {
+        Ok(()) if !systems::locks::is_locked(&game_state.world, target) => {
+            UtilityOutcome::Unlocked(method)
+        }
+        Ok(()) | Err(LockError::Locked(_)) | Err(LockError::NoThievesTools) => {
+            UtilityOutcome::StillLocked
+        }
+        Err(LockError::NoLock(_))
+        | Err(LockError::NotLocked(_))
+        | Err(LockError::NotAContainer(_)) => UtilityOutcome::NoEffect,
+    }
+}
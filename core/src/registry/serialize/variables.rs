@@ -20,12 +20,18 @@ pub static PARSER_VARIABLES: LazyLock<VariableMap> = LazyLock::new(|| {
         (
             "spell_level".to_string(),
             Arc::new(|_world: &World, _entity: Entity, context: &ActionContext| {
-                if let ActionContext::Spell { level, .. } = context {
+                if let ActionContext::Spell { level, .. } = context.base() {
                     return *level as i32;
                 }
                 0
             }) as Arc<VariableFunction>,
         ),
+        (
+            "amount".to_string(),
+            Arc::new(|_world: &World, _entity: Entity, context: &ActionContext| {
+                context.amount().unwrap_or(0) as i32
+            }) as Arc<VariableFunction>,
+        ),
         (
             "caster_level".to_string(),
             Arc::new(|world: &World, entity: Entity, _context: &ActionContext| {
@@ -10,11 +10,11 @@ use crate::{
         script::{Script, ScriptError, ScriptFunction},
         script_api::{
             ScriptActionContext, ScriptActionKindResultView, ScriptActionOutcomeBundleView,
-            ScriptActionPerformedView, ScriptActionResultView, ScriptActionView,
+            ScriptActionPerformedView, ScriptActionResultView, ScriptActionView, ScriptAttackRoll,
             ScriptD20CheckDCKind, ScriptD20CheckView, ScriptD20Result,
             ScriptDamageMitigationResult, ScriptDamageOutcomeView, ScriptDamageResolutionKindView,
-            ScriptDamageRollResult, ScriptEffectView, ScriptEntity, ScriptEntityView,
-            ScriptEventView, ScriptLoadoutView, ScriptOptionalEntityView,
+            ScriptDamageRollResult, ScriptDamageRollView, ScriptEffectView, ScriptEntity,
+            ScriptEntityView, ScriptEventView, ScriptLoadoutView, ScriptOptionalEntityView,
             ScriptReactionBodyContext, ScriptReactionPlan, ScriptReactionTriggerContext,
             ScriptResourceCost, ScriptResourceView, ScriptSavingThrow,
         },
@@ -38,12 +38,14 @@ impl RhaiScriptEngine {
             .build_type::<ScriptActionKindResultView>()
             .build_type::<ScriptActionOutcomeBundleView>()
             .build_type::<ScriptActionPerformedView>()
+            .build_type::<ScriptAttackRoll>()
             .build_type::<ScriptD20CheckDCKind>()
             .build_type::<ScriptD20CheckView>()
             .build_type::<ScriptD20Result>()
             .build_type::<ScriptDamageMitigationResult>()
             .build_type::<ScriptDamageOutcomeView>()
             .build_type::<ScriptDamageRollResult>()
+            .build_type::<ScriptDamageRollView>()
             .build_type::<ScriptDamageResolutionKindView>()
             .build_type::<ScriptEffectView>()
             .build_type::<ScriptEntity>()
@@ -205,6 +207,27 @@ impl ScriptEngine for RhaiScriptEngine {
         Ok(modifier as i32)
     }
 
+    fn evaluate_incoming_attack_roll_hook(
+        &mut self,
+        script: &Script,
+        target: &ScriptEntityView,
+        attacker: &ScriptEntityView,
+        attack_roll: &ScriptAttackRoll,
+    ) -> Result<(), ScriptError> {
+        let ast = self.get_ast(script).cloned()?;
+        let mut scope = Scope::new();
+        self.engine
+            .call_fn::<()>(
+                &mut scope,
+                &ast,
+                ScriptFunction::IncomingAttackRollHook.fn_name(),
+                (target.clone(), attacker.clone(), attack_roll.clone()),
+            )
+            .map_err(|e| ScriptError::RuntimeError(format!("Rhai error: {}", e)))?;
+
+        Ok(())
+    }
+
     fn evaluate_damage_roll_result_hook(
         &mut self,
         script: &Script,
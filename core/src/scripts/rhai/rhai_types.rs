@@ -1,12 +1,14 @@
-use rhai::{Array, CustomType, TypeBuilder, plugin::*};
+use rhai::{Array, CustomType, EvalAltResult, TypeBuilder, plugin::*};
 
 use crate::{
-    components::id::ResourceId,
+    components::{d20::AdvantageType, id::ResourceId},
+    registry::serialize::parser::Parser,
     scripts::script_api::{
         ScriptActionContext, ScriptActionKindResultView, ScriptActionOutcomeBundleView,
-        ScriptActionPerformedView, ScriptActionResultView, ScriptActionView, ScriptD20CheckDCKind,
-        ScriptD20CheckView, ScriptD20Result, ScriptDamageMitigationResult, ScriptDamageOutcomeView,
-        ScriptDamageResolutionKindView, ScriptDamageRollResult, ScriptEffectView, ScriptEntity,
+        ScriptActionPerformedView, ScriptActionResultView, ScriptActionView, ScriptAttackRoll,
+        ScriptD20CheckDCKind, ScriptD20CheckView, ScriptD20Result, ScriptDamageMitigationResult,
+        ScriptDamageOutcomeView, ScriptDamageResolutionKindView, ScriptDamageRollResult,
+        ScriptDamageRollView, ScriptDiceRollBonus, ScriptEffectView, ScriptEntity,
         ScriptEntityView, ScriptEventRef, ScriptEventView, ScriptLoadoutView,
         ScriptOptionalEntityView, ScriptReactionBodyContext, ScriptReactionPlan,
         ScriptReactionTriggerContext, ScriptResourceCost, ScriptResourceView, ScriptSavingThrow,
@@ -30,6 +32,24 @@ impl CustomType for ScriptEffectView {
     }
 }
 
+impl CustomType for ScriptAttackRoll {
+    fn build(mut builder: TypeBuilder<Self>) {
+        builder
+            .with_name("AttackRoll")
+            .with_get("source", |s: &mut Self| s.source())
+            .with_fn("add_modifier", |s: &mut Self, modifier: i64| {
+                s.add_modifier(modifier as i32);
+            })
+            .with_fn("give_advantage", |s: &mut Self| {
+                s.add_advantage(AdvantageType::Advantage);
+            })
+            .with_fn("give_disadvantage", |s: &mut Self| {
+                s.add_advantage(AdvantageType::Disadvantage);
+            })
+            .with_fn("set_crit_on_hit", |s: &mut Self| s.set_crit_on_hit());
+    }
+}
+
 impl CustomType for ScriptDamageRollResult {
     fn build(mut builder: TypeBuilder<Self>) {
         builder
@@ -105,6 +125,16 @@ impl CustomType for ScriptD20CheckView {
     }
 }
 
+impl CustomType for ScriptDamageRollView {
+    fn build(mut builder: TypeBuilder<Self>) {
+        builder
+            .with_name("DamageRollPerformedView")
+            .with_get("performer", |s: &mut Self| s.performer.id)
+            .with_get("target", |s: &mut Self| s.target.id)
+            .with_get("damage_roll", |s: &mut Self| s.damage_roll.clone());
+    }
+}
+
 impl CustomType for ScriptSavingThrow {
     fn build(mut builder: TypeBuilder<Self>) {
         builder.with_name("SavingThrow");
@@ -267,6 +297,12 @@ impl CustomType for ScriptEventView {
             })
             .with_fn("as_action_performed", |s: &mut Self| {
                 s.as_action_performed().clone()
+            })
+            .with_fn("is_damage_roll_performed", |s: &mut Self| {
+                s.is_damage_roll_performed()
+            })
+            .with_fn("as_damage_roll_performed", |s: &mut Self| {
+                s.as_damage_roll_performed().clone()
             });
     }
 }
@@ -299,6 +335,12 @@ pub mod reaction_plan_module {
         }
     }
 
+    #[rhai_fn(return_raw)]
+    pub fn penalize_d20_result(penalty: String) -> Result<ScriptReactionPlan, Box<EvalAltResult>> {
+        let penalty = Parser::new(&penalty).parse_dice_expression()?;
+        Ok(ScriptReactionPlan::PenalizeD20Result { penalty })
+    }
+
     pub fn modify_d20_dc(modifier: String) -> ScriptReactionPlan {
         ScriptReactionPlan::ModifyD20DC {
             modifier: modifier.parse().unwrap(),
@@ -335,6 +377,37 @@ pub mod reaction_plan_module {
         }
     }
 
+    pub fn add_damage(damage: String) -> ScriptReactionPlan {
+        ScriptReactionPlan::AddDamage {
+            damage: damage.parse().unwrap(),
+            against: None,
+        }
+    }
+
+    pub fn add_damage_against(
+        damage: String,
+        creature_types: Array,
+        damage_against: String,
+    ) -> ScriptReactionPlan {
+        let creature_types = creature_types
+            .into_iter()
+            .map(|v| {
+                serde_plain::from_str(&v.cast::<String>()).expect("Failed to parse CreatureType")
+            })
+            .collect();
+
+        ScriptReactionPlan::AddDamage {
+            damage: damage.parse().unwrap(),
+            against: Some((creature_types, damage_against.parse().unwrap())),
+        }
+    }
+
+    #[rhai_fn(return_raw)]
+    pub fn reduce_damage(reduction: String) -> Result<ScriptReactionPlan, Box<EvalAltResult>> {
+        let reduction = reduction.parse::<ScriptDiceRollBonus>()?;
+        Ok(ScriptReactionPlan::ReduceDamage { reduction })
+    }
+
     pub fn cancel_trigger_event(resources_to_refund: Array) -> ScriptReactionPlan {
         let resources: Vec<ResourceId> = resources_to_refund
             .into_iter()
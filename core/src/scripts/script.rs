@@ -160,6 +160,7 @@ pub enum ScriptFunction {
     ActionHook,
     ArmorClassHook,
     AttackRollHook,
+    IncomingAttackRollHook,
     DamageRollResultHook,
     PreDamageMitigationHook,
     PostDamageMitigationHook,
@@ -175,6 +176,7 @@ impl ScriptFunction {
             ScriptFunction::ActionHook => "action_hook",
             ScriptFunction::ArmorClassHook => "armor_class_hook",
             ScriptFunction::AttackRollHook => "attack_roll_hook",
+            ScriptFunction::IncomingAttackRollHook => "incoming_attack_roll_hook",
             ScriptFunction::DamageRollResultHook => "damage_roll_result_hook",
             ScriptFunction::PreDamageMitigationHook => "pre_damage_mitigation_hook",
             ScriptFunction::PostDamageMitigationHook => "post_damage_mitigation_hook",
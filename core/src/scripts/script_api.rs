@@ -15,8 +15,9 @@ use crate::{
             },
             targeting::TargetInstance,
         },
+        d20::AdvantageType,
         damage::{
-            DamageComponentResult, DamageMitigationEffect, DamageMitigationResult,
+            AttackRoll, DamageComponentResult, DamageMitigationEffect, DamageMitigationResult,
             DamageRollResult, DamageType, MitigationOperation,
         },
         dice::{DiceSet, DiceSetRoll},
@@ -25,10 +26,12 @@ use crate::{
         items::equipment::loadout::Loadout,
         modifier::{Modifiable, ModifierSet, ModifierSource},
         resource::{ResourceAmount, ResourceAmountMap, ResourceBudgetKind, ResourceMap},
+        species::CreatureType,
     },
     engine::event::{ActionData, Event, EventKind, ReactionData},
     registry::serialize::{
         d20::SavingThrowProvider,
+        dice::DamageEquation,
         parser::{DiceExpression, Evaluable, IntExpression, Parser},
         variables::PARSER_VARIABLES,
     },
@@ -279,9 +282,16 @@ impl ScriptDamageRollResult {
                     modifiers: ModifierSet::from(ModifierSource::Base, modifier),
                 }
                 .roll();
+                // Bonus damage shares the provenance of the roll it's added to
+                let tags = inner
+                    .components
+                    .first()
+                    .map(|component| component.tags.clone())
+                    .unwrap_or_default();
                 inner.add_component(DamageComponentResult {
                     result,
                     damage_type,
+                    tags,
                 });
             }
         }
@@ -290,6 +300,50 @@ impl ScriptDamageRollResult {
 
 impl_script_shared_methods!(ScriptDamageRollResult, DamageRollResult);
 
+/// Script-facing attack roll which hasn't been rolled yet. Everything the
+/// script adds to it is attributed to `source`, i.e. the effect of the hook.
+#[derive(Clone)]
+pub struct ScriptAttackRoll {
+    inner: ScriptShared<AttackRoll>,
+    source: ModifierSource,
+}
+
+impl ScriptAttackRoll {
+    pub fn take_from(attack_roll: &mut AttackRoll, source: ModifierSource) -> Self {
+        Self {
+            inner: ScriptShared::take_from(attack_roll),
+            source,
+        }
+    }
+
+    pub fn into_inner(self) -> AttackRoll {
+        self.inner.into_inner()
+    }
+
+    pub fn source(&self) -> String {
+        self.inner.read().source.to_string()
+    }
+
+    pub fn add_modifier(&mut self, modifier: i32) {
+        let source = self.source.clone();
+        self.inner.write().d20_check.add_modifier(source, modifier);
+    }
+
+    pub fn add_advantage(&mut self, kind: AdvantageType) {
+        let source = self.source.clone();
+        self.inner
+            .write()
+            .d20_check
+            .advantage_tracker_mut()
+            .add(kind, source);
+    }
+
+    pub fn set_crit_on_hit(&mut self) {
+        let source = self.source.clone();
+        self.inner.write().set_crit_on_hit(source);
+    }
+}
+
 #[derive(Clone)]
 pub struct ScriptDamageMitigationResult {
     inner: ScriptShared<DamageMitigationResult>,
@@ -303,12 +357,10 @@ impl ScriptDamageMitigationResult {
     pub fn add_immunity(&mut self) {
         let mut inner = self.inner.write();
         for component in &mut inner.components {
-            component.modifiers.push(DamageMitigationEffect {
-                source: ModifierSource::Custom(
-                    "TODO: Figure out how to propagate the source".to_string(),
-                ),
-                operation: MitigationOperation::Immunity,
-            });
+            component.modifiers.push(DamageMitigationEffect::new(
+                ModifierSource::Custom("TODO: Figure out how to propagate the source".to_string()),
+                MitigationOperation::Immunity,
+            ));
         }
         inner.recalculate_total();
     }
@@ -416,6 +468,7 @@ pub enum ScriptEventView {
     ActionRequested(ScriptActionView),
     ActionPerformed(ScriptActionPerformedView),
     D20CheckPerformed(ScriptD20CheckView),
+    DamageRollPerformed(ScriptDamageRollView),
 }
 
 impl ScriptEventView {
@@ -427,6 +480,14 @@ impl ScriptEventView {
                 ))
             }
 
+            EventKind::DamageRollPerformed(performer, damage_roll, target) => {
+                Some(ScriptEventView::DamageRollPerformed(ScriptDamageRollView {
+                    performer: ScriptEntity::from(*performer),
+                    target: ScriptEntity::from(*target),
+                    damage_roll: ScriptDamageRollResult::new(damage_roll.clone()),
+                }))
+            }
+
             EventKind::ActionRequested { action } => Some(ScriptEventView::ActionRequested(
                 ScriptActionView::from(action),
             )),
@@ -443,7 +504,7 @@ impl ScriptEventView {
 
                 let mut script_results = Vec::new();
                 for result in results {
-                    if let TargetInstance::Entity(target_entity) = result.target {
+                    if let Some(target_entity) = result.target.entity() {
                         script_results.push(ScriptActionResultView::from_action_result(
                             action.actor,
                             target_entity,
@@ -490,6 +551,7 @@ impl_event_accessors!(ScriptEventView {
     is_d20_check_performed => as_d20_check_performed: D20CheckPerformed(ScriptD20CheckView),
     is_action_requested    => as_action_requested:    ActionRequested(ScriptActionView),
     is_action_performed    => as_action_performed:    ActionPerformed(ScriptActionPerformedView),
+    is_damage_roll_performed => as_damage_roll_performed: DamageRollPerformed(ScriptDamageRollView),
 });
 
 /// View of a "D20CheckPerformed" event.
@@ -512,6 +574,15 @@ impl ScriptD20CheckView {
     }
 }
 
+/// View of a "DamageRollPerformed" event, i.e. damage which has been rolled
+/// but not yet applied to the target.
+#[derive(Clone)]
+pub struct ScriptDamageRollView {
+    pub performer: ScriptEntity,
+    pub target: ScriptEntity,
+    pub damage_roll: ScriptDamageRollResult,
+}
+
 #[derive(Clone)]
 pub struct ScriptActionContext {
     pub inner: ActionContext,
@@ -519,11 +590,11 @@ pub struct ScriptActionContext {
 
 impl ScriptActionContext {
     pub fn is_spell(&self) -> bool {
-        matches!(self.inner, ActionContext::Spell { .. })
+        matches!(self.inner.base(), ActionContext::Spell { .. })
     }
 
     pub fn is_weapon_attack(&self) -> bool {
-        matches!(self.inner, ActionContext::Weapon { .. })
+        matches!(self.inner.base(), ActionContext::Weapon { .. })
     }
 }
 
@@ -609,7 +680,11 @@ impl From<&ActionData> for ScriptActionView {
                 .targets
                 .iter()
                 .filter_map(|t| match t {
-                    TargetInstance::Entity(entity) => Some(ScriptEntity::from(*entity)),
+                    TargetInstance::Entity(entity)
+                    | TargetInstance::Object(entity)
+                    | TargetInstance::Item { owner: entity, .. } => {
+                        Some(ScriptEntity::from(*entity))
+                    }
                     TargetInstance::Point(_) => None, // TODO: Handle point targets if needed
                 })
                 .collect(),
@@ -675,7 +750,7 @@ impl ScriptActionKindResultView {
             ActionKindResult::Standard(bundle) => {
                 ScriptActionKindResultView::Standard(ScriptActionOutcomeBundleView::from(bundle))
             }
-            ActionKindResult::Utility => ScriptActionKindResultView::Utility,
+            ActionKindResult::Utility(_) => ScriptActionKindResultView::Utility,
             ActionKindResult::Composite { actions } => ScriptActionKindResultView::Composite {
                 actions: actions
                     .iter()
@@ -927,6 +1002,10 @@ pub enum ScriptReactionPlan {
     /// Add a flat modifier to the most recent D20 roll for this event.
     ModifyD20Result { bonus: ScriptDiceRollBonus },
 
+    /// Roll the dice and subtract the result from the most recent D20 roll for
+    /// this event, e.g. Cutting Words.
+    PenalizeD20Result { penalty: DiceExpression },
+
     /// Add a flat modifier to the DC for this event.
     ModifyD20DC { modifier: ScriptDiceRollBonus },
 
@@ -946,6 +1025,18 @@ pub enum ScriptReactionPlan {
         on_failure: Box<ScriptReactionPlan>,
     },
 
+    /// Add damage to the damage roll of the trigger event before it's applied,
+    /// e.g. Divine Smite. If the target is one of the creature types in
+    /// `against`, the damage from there is added instead.
+    AddDamage {
+        damage: DamageEquation,
+        against: Option<(Vec<CreatureType>, DamageEquation)>,
+    },
+
+    /// Subtract from the damage roll of the trigger event before it's applied,
+    /// e.g. Cutting Words.
+    ReduceDamage { reduction: ScriptDiceRollBonus },
+
     /// Cancel a specific event (usually the trigger) and maybe refund resources.
     CancelEvent {
         event: ScriptEventRef,
@@ -9,9 +9,9 @@ use crate::scripts::{
     rhai::rhai_engine::RhaiScriptEngine,
     script::{Script, ScriptError, ScriptLanguage},
     script_api::{
-        ScriptActionView, ScriptDamageMitigationResult, ScriptDamageRollResult, ScriptEffectView,
-        ScriptEntityView, ScriptOptionalEntityView, ScriptReactionBodyContext, ScriptReactionPlan,
-        ScriptReactionTriggerContext,
+        ScriptActionView, ScriptAttackRoll, ScriptDamageMitigationResult, ScriptDamageRollResult,
+        ScriptEffectView, ScriptEntityView, ScriptOptionalEntityView, ScriptReactionBodyContext,
+        ScriptReactionPlan, ScriptReactionTriggerContext,
     },
 };
 
@@ -73,6 +73,16 @@ pub trait ScriptEngine {
         entity: &ScriptEntityView,
     ) -> Result<i32, ScriptError>;
 
+    /// Execute a hook for an attack roll made against the target, modifying the
+    /// attack roll in place.
+    fn evaluate_incoming_attack_roll_hook(
+        &mut self,
+        script: &Script,
+        target: &ScriptEntityView,
+        attacker: &ScriptEntityView,
+        attack_roll: &ScriptAttackRoll,
+    ) -> Result<(), ScriptError>;
+
     /// Execute a damage roll result hook, returning the modified damage roll result.
     fn evaluate_damage_roll_result_hook(
         &mut self,
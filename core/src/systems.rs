@@ -1,21 +1,52 @@
 pub mod actions;
 pub mod ai;
+pub mod antimagic;
 pub mod backgrounds;
+pub mod campaign;
 pub mod class;
+pub mod coating;
+pub mod composite;
+pub mod conditions;
+pub mod control;
+pub mod crafting;
 pub mod d20;
+pub mod discovery;
 pub mod damage;
+pub mod disease;
 pub mod effects;
 pub mod factions;
+pub mod familiar;
 pub mod feats;
+pub mod generator;
 pub mod geometry;
+pub mod gm;
 pub mod health;
 pub mod helpers;
+pub mod illusions;
 pub mod inventory;
 pub mod level_up;
+pub mod levers;
+pub mod light;
 pub mod loadout;
+pub mod locks;
+pub mod loot;
+pub mod mapgen;
+pub mod mob;
 pub mod movement;
+pub mod plan;
+pub mod preset;
+pub mod quick_build;
 pub mod resources;
+pub mod scene;
 pub mod scripts;
+pub mod shapechange;
+pub mod size;
 pub mod species;
 pub mod spells;
+pub mod supplies;
+pub mod surfaces;
 pub mod time;
+pub mod traps;
+pub mod travel;
+pub mod visibility;
+pub mod what_if;
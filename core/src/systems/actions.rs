@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use hecs::{Entity, World};
+use parry3d::na::Vector3;
 use tracing::{debug, warn};
 use uuid::Uuid;
 
@@ -10,32 +11,54 @@ use crate::{
             action::{
                 Action, ActionCondition, ActionContext, ActionCooldownMap, ActionKind,
                 ActionKindResult, ActionMap, ActionOutcomeBundle, ActionPayload, ActionProvider,
-                AttackRollFunction, DamageOnFailure, DamageOutcome, EffectApplyRule, EffectOutcome,
-                HealingOutcome, SavingThrowFunction,
+                ActionsUsedThisTurn, AttackRollFunction, DamageOnFailure, DamageOutcome,
+                EffectApplyRule, EffectOutcome, HealingOutcome, ItemDamageOutcome,
+                SavingThrowOutcome, SkillCheckFunction, TargetDamageFunction,
+                TargetSavingThrowFunction,
             },
+            constraint::ActionConstraint,
+            modification::EventModification,
+            preview::{ActionPreview, CheckPreview, DamagePreview, DamageRange},
+            resolution::{Cover, TargetResolution},
             targeting::{
-                AreaShape, TargetInstance, TargetingContext, TargetingError, TargetingKind,
+                AreaShape, ItemTarget, TargetInstance, TargetingContext, TargetingError,
+                TargetingKind,
             },
         },
-        damage::DamageRollResult,
+        damage::{AttackRollResult, DamageMitigationResult, DamageRollResult},
+        faction::Attitude,
         health::life_state::LifeState,
-        id::{ActionId, ResourceId, ScriptId},
-        items::equipment::loadout::Loadout,
+        id::{ActionId, FeatId, IdProvider, ItemId, ResourceId, ScriptId},
+        items::{
+            equipment::{
+                armor::{ArmorClass, ArmorDexterityBonus},
+                loadout::Loadout,
+            },
+            inventory::{Inventory, ItemContainer},
+        },
         modifier::{Modifiable, ModifierSource},
-        resource::{RechargeRule, ResourceAmountMap, ResourceMap},
+        resource::{
+            RechargeRule, ResourceAmount, ResourceAmountMap, ResourceBudgetKind, ResourceError,
+            ResourceMap,
+        },
+        saving_throw::SavingThrowSet,
+        skill::{SkillCheckDC, SkillContest, SkillSet},
         spells::{
-            spell::{ConcentrationInstance, SpellFlag},
+            metamagic::Metamagic,
+            spell::{ConcentrationInstance, MaterialComponent, MaterialConsumption, SpellFlag},
             spellbook::Spellbook,
         },
     },
     engine::{
         event::{
-            ActionData, ActionError, CallbackResult, Event, EventCallback, EventKind, ReactionData,
+            ActionData, ActionError, CallbackResult, DecisionRequest, Event, EventCallback,
+            EventKind, ReactionData,
         },
         game_state::GameState,
         geometry::WorldGeometry,
+        rules::OptionalRule,
     },
-    registry::registry::{ActionsRegistry, SpellsRegistry},
+    registry::registry::{ActionsRegistry, ItemsRegistry, SpellsRegistry},
     scripts::script_api::{
         ScriptEventView, ScriptReactionBodyContext, ScriptReactionTriggerContext,
     },
@@ -87,6 +110,54 @@ fn add_action_to_map(
         .or_insert(vec![(context, resource_cost.clone())]);
 }
 
+/// Let the entity take the action until the grant is revoked again, see
+/// `revoke_action`
+pub fn grant_action(
+    world: &mut World,
+    entity: Entity,
+    action_id: &ActionId,
+    source: ModifierSource,
+) {
+    debug!(
+        "Entity {:?} is granted action {:?} by {:?}",
+        entity, action_id, source
+    );
+    if let Ok(mut granted) = world.get::<&mut GrantedActionMap>(entity) {
+        granted.entry(action_id.clone()).or_default().push(source);
+        return;
+    }
+    let _ = world.insert_one(
+        entity,
+        GrantedActionMap::from([(action_id.clone(), vec![source])]),
+    );
+}
+
+/// Revoke a single grant of the action from the source. The entity can still
+/// take the action if it was also granted by something else, or if it knows
+/// the action itself.
+pub fn revoke_action(
+    world: &mut World,
+    entity: Entity,
+    action_id: &ActionId,
+    source: &ModifierSource,
+) {
+    debug!(
+        "Entity {:?} is no longer granted action {:?} by {:?}",
+        entity, action_id, source
+    );
+    let Ok(mut granted) = world.get::<&mut GrantedActionMap>(entity) else {
+        return;
+    };
+    if let Some(sources) = granted.get_mut(action_id)
+        && let Some(index) = sources.iter().position(|granted_by| granted_by == source)
+    {
+        sources.remove(index);
+        if sources.is_empty() {
+            granted.remove(action_id);
+        }
+    }
+}
+
 pub fn on_cooldown(world: &World, entity: Entity, action_id: &ActionId) -> Option<RechargeRule> {
     if let Some(cooldowns) = world.get::<&ActionCooldownMap>(entity).ok() {
         cooldowns.get(action_id).cloned()
@@ -105,22 +176,139 @@ pub fn set_cooldown(
     cooldowns.insert(action_id.clone(), cooldown);
 }
 
+/// Put the action on cooldown if it has one, and remember that it has been
+/// used this turn if it can only be used once per turn
+pub fn record_use(world: &mut World, entity: Entity, action_id: &ActionId, action: &Action) {
+    if let Some(cooldown) = action.cooldown {
+        set_cooldown(world, entity, action_id, cooldown);
+    }
+
+    if action
+        .constraints()
+        .contains(&ActionConstraint::OncePerTurn)
+    {
+        if let Ok(mut used) = world.get::<&mut ActionsUsedThisTurn>(entity) {
+            used.insert(action_id.clone());
+            return;
+        }
+        let _ = world.insert_one(entity, ActionsUsedThisTurn::from([action_id.clone()]));
+    }
+}
+
 pub fn all_actions(world: &World, entity: Entity) -> ActionMap {
     let mut actions = systems::helpers::get_component_clone::<ActionMap>(world, entity);
     actions
         .extend(systems::helpers::get_component::<Spellbook>(world, entity).actions(world, entity));
     actions
         .extend(systems::helpers::get_component::<Loadout>(world, entity).actions(world, entity));
+    // Spells cast from items and the actions of tools are added next to the
+    // ones the entity knows itself
+    if let Ok(inventory) = world.get::<&Inventory>(entity) {
+        for (action_id, contexts) in inventory.actions(world, entity) {
+            actions.entry(action_id).or_default().extend(contexts);
+        }
+    }
+    // Actions granted by effects, e.g. Haste, are only added if the entity
+    // can't already take them. Granted attacks which aren't spells are made
+    // with the same weapons as regular attacks.
+    let weapon_attacks = actions
+        .get(&ActionId::new("nat20_core", "action.weapon_attack"))
+        .cloned()
+        .unwrap_or_default();
+    if let Ok(granted) = world.get::<&GrantedActionMap>(entity) {
+        for action_id in granted.keys() {
+            if actions.contains_key(action_id) {
+                continue;
+            }
+            let Some(action) = get_action(action_id) else {
+                continue;
+            };
+            if action.kind().is_attack() && ActionsRegistry::get(action_id).is_some() {
+                for (context, _) in &weapon_attacks {
+                    add_action_to_map(&mut actions, action_id, action, context.clone());
+                }
+            } else {
+                add_action_to_map(&mut actions, action_id, action, ActionContext::Other);
+            }
+        }
+    }
+    // Maneuvers are made with the same weapons as regular attacks
+    if let Some(attacks) = actions
+        .get(&ActionId::new("nat20_core", "action.weapon_attack"))
+        .cloned()
+    {
+        for (maneuver, _) in weapon_maneuvers() {
+            if let Some(action) = get_action(&maneuver) {
+                for (context, _) in &attacks {
+                    add_action_to_map(&mut actions, &maneuver, action, context.clone());
+                }
+            }
+        }
+    }
     actions
 }
 
+/// Optional attack maneuvers which can be made with any weapon the entity can
+/// attack with, along with the rule that has to be enabled to use them
+pub fn weapon_maneuvers() -> Vec<(ActionId, OptionalRule)> {
+    vec![
+        (
+            ActionId::new("nat20_core", "action.disarm"),
+            OptionalRule::AttackManeuvers,
+        ),
+        (
+            ActionId::new("nat20_core", "action.sunder"),
+            OptionalRule::AttackManeuvers,
+        ),
+        (
+            ActionId::new("nat20_core", "action.topple"),
+            OptionalRule::AttackManeuvers,
+        ),
+        (
+            ActionId::new("nat20_core", "action.called_shot"),
+            OptionalRule::CalledShots,
+        ),
+    ]
+}
+
+/// The optional rule which has to be enabled for the action, or any of its
+/// variants, to be used
+pub fn required_rule(action_id: &ActionId) -> Option<OptionalRule> {
+    weapon_maneuvers().into_iter().find_map(|(maneuver, rule)| {
+        let is_variant = get_action(&maneuver).is_some_and(|action| {
+            matches!(&action.kind, ActionKind::Variant { variants } if variants.contains(action_id))
+        });
+        (maneuver == *action_id || is_variant).then_some(rule)
+    })
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ActionUsabilityError {
     EntityNotAlive(Entity),
+    /// The entity is a familiar which has been dismissed to a pocket dimension
+    Dismissed(Entity),
+    /// Familiars can't take the attack action
+    FamiliarCannotAttack,
     OnCooldown(RechargeRule),
     NotEnoughResources(ResourceAmountMap),
+    /// The caster can't pay for the costly material component of the spell
+    MissingMaterialComponent(MaterialComponent),
     ResourceNotFound(ResourceId),
     TargetingError(TargetingError),
+    /// Spells can't be cast inside an antimagic field
+    AntimagicField,
+    /// One of the constraints declared on the action isn't met
+    ConstraintNotMet(ActionConstraint),
+    /// Charmed creatures can't harm the creature that charmed them
+    Charmed(Entity),
+    /// The entity is controlled by another creature which doesn't allow it to
+    /// take the action
+    Controlled(Entity),
+    /// The metamagic option applied to the spell isn't known by the caster
+    UnknownMetamagic(Metamagic),
+    /// The cost of the spell doesn't include the Sorcery Points needed for the
+    /// metamagic applied to it
+    MetamagicNotPaid(u8),
 }
 
 pub fn action_usable(
@@ -135,10 +323,39 @@ pub fn action_usable(
         return Err(ActionUsabilityError::EntityNotAlive(entity));
     }
 
+    if systems::familiar::is_dismissed(world, entity) {
+        return Err(ActionUsabilityError::Dismissed(entity));
+    }
+
+    if !systems::familiar::can_take_action(world, entity, action_id) {
+        return Err(ActionUsabilityError::FamiliarCannotAttack);
+    }
+
+    if let Some(controller) = systems::control::restricts_action(world, entity, action_id) {
+        return Err(ActionUsabilityError::Controlled(controller));
+    }
+
     if let Some(cooldown) = on_cooldown(world, entity, action_id) {
         return Err(ActionUsabilityError::OnCooldown(cooldown));
     }
 
+    if let Some(action) = get_action(action_id)
+        && let Some(constraint) = action
+            .constraints()
+            .iter()
+            .find(|constraint| !constraint.is_met(world, entity, action_id, action_context))
+    {
+        return Err(ActionUsabilityError::ConstraintNotMet(constraint.clone()));
+    }
+
+    if matches!(action_context.base(), ActionContext::Spell { .. })
+        && systems::antimagic::in_antimagic_field(world, entity)
+    {
+        return Err(ActionUsabilityError::AntimagicField);
+    }
+
+    systems::spells::validate_metamagic(world, entity, action_context, resource_cost)?;
+
     let resources = systems::helpers::get_component::<ResourceMap>(world, entity);
     for (resource_id, amount) in resource_cost {
         if let Some(resource) = resources.get(resource_id) {
@@ -152,6 +369,12 @@ pub fn action_usable(
         }
     }
 
+    if let Some(material) =
+        systems::spells::missing_material_component(world, entity, action_context)
+    {
+        return Err(ActionUsabilityError::MissingMaterialComponent(material));
+    }
+
     Ok(())
 }
 
@@ -171,7 +394,40 @@ pub fn action_usable_on_targets(
     if let Err(targeting_error) =
         targeting_context.validate_targets(world, world_geometry, actor, targets)
     {
-        return Err(ActionUsabilityError::TargetingError(targeting_error));
+        // Touch spells can also be delivered by the familiar of the actor
+        let deliverer = systems::familiar::touch_spell_deliverer(
+            world,
+            world_geometry,
+            actor,
+            action_id,
+            context,
+            targets,
+        );
+        if deliverer.is_none() {
+            return Err(ActionUsabilityError::TargetingError(targeting_error));
+        }
+    }
+
+    if let Some(action) = get_action(action_id) {
+        for constraint in action.constraints() {
+            if !targets
+                .iter()
+                .all(|target| constraint.allows_target(world, actor, target))
+            {
+                return Err(ActionUsabilityError::ConstraintNotMet(constraint.clone()));
+            }
+        }
+
+        if let Some(charmer) =
+            targets
+                .iter()
+                .filter_map(TargetInstance::entity)
+                .find_map(|target| {
+                    systems::conditions::prevents_harm(world, actor, target, &action.kind)
+                })
+        {
+            return Err(ActionUsabilityError::Charmed(charmer));
+        }
     }
 
     Ok(())
@@ -182,7 +438,7 @@ pub fn available_actions(world: &World, entity: Entity) -> ActionMap {
 
     actions.retain(|action_id, action_data| {
         action_data.retain_mut(|(action_context, resource_cost)| {
-            for effect in systems::effects::effects(world, entity).iter() {
+            for effect in systems::effects::active_effects(world, entity).iter() {
                 (effect.effect().on_resource_cost)(
                     world,
                     entity,
@@ -200,30 +456,86 @@ pub fn available_actions(world: &World, entity: Entity) -> ActionMap {
     actions
 }
 
+/// The smallest and largest amount the entity can choose to spend on an action
+/// with a variable cost, e.g. how many points from its Lay on Hands pool it can
+/// use. Returns `None` if the cost is fixed, or if the entity can't even afford
+/// the smallest amount.
+pub fn variable_amount_range(
+    world: &World,
+    entity: Entity,
+    resource_cost: &ResourceAmountMap,
+) -> Option<(u8, u8)> {
+    let resources = systems::helpers::get_component::<ResourceMap>(world, entity);
+    let mut range: Option<(u8, u8)> = None;
+    for (resource_id, amount) in resource_cost {
+        let ResourceAmount::Variable { min, max } = amount else {
+            continue;
+        };
+        let Some(ResourceBudgetKind::Flat(budget)) = resources.get(resource_id) else {
+            return None;
+        };
+        let (lowest, highest) = range.unwrap_or((0, u8::MAX));
+        range = Some((lowest.max(*min), highest.min(*max).min(budget.current_uses)));
+    }
+    range.filter(|(min, max)| min <= max)
+}
+
+/// Choose how much to spend on an action with a variable cost. Every variable
+/// amount in the cost is resolved to the chosen amount, and the amount is added
+/// to the context of the action with [`ActionContext::Variable`] so the payload
+/// can scale with it.
+pub fn choose_amount(action_data: &mut ActionData, amount: u8) -> Result<(), ResourceError> {
+    for cost in action_data.resource_cost.values_mut() {
+        if matches!(cost, ResourceAmount::Variable { .. }) {
+            *cost = cost.resolve(amount)?;
+        }
+    }
+    let context = action_data.context.base().clone();
+    action_data.context = ActionContext::Variable {
+        amount,
+        context: Box::new(context),
+    };
+    Ok(())
+}
+
 pub fn perform_action(game_state: &mut GameState, action_data: &ActionData) {
     // TODO: Handle missing action
     let mut action = get_action(&action_data.action_id)
         .cloned()
         .expect("Action not found in character's actions or registry");
-    // Set the action on cooldown if applicable
-    if let Some(cooldown) = action.cooldown {
-        set_cooldown(
-            &mut game_state.world,
-            action_data.actor,
-            &action_data.action_id,
-            cooldown,
-        );
-    }
+    record_use(
+        &mut game_state.world,
+        action_data.actor,
+        &action_data.action_id,
+        &action,
+    );
+    systems::conditions::update_fear_sight(game_state, action_data.actor);
     // Determine which entities are being targeted
-    let entities = get_targeted_entities(game_state, action_data);
+    let mut entities = get_targeted_entities(game_state, action_data);
+    // Attacks pass right through illusions, which gives them away
+    if action.kind.is_attack() {
+        entities = systems::illusions::pass_through(game_state, action_data.actor, entities);
+    }
     debug!(
         "Performing action {:?} by entity {:?} on targets {:?}",
         action_data.action_id, action_data.actor, entities
     );
+    systems::surfaces::create_from_action(game_state, &action, action_data);
     action.perform(game_state, action_data, &entities);
+    // Actions aimed at a point are logged even if they don't affect anyone
+    if entities.is_empty() && !action_data.point_targets().is_empty() {
+        let _ = game_state.process_event(Event::action_performed_event(
+            game_state,
+            action_data,
+            Vec::new(),
+        ));
+    }
+    systems::effects::end_effects_triggered_by(&mut game_state.world, action_data);
 }
 
-fn get_targeted_entities(game_state: &mut GameState, action_data: &ActionData) -> Vec<Entity> {
+/// The entities affected by the action with its current targets. For area
+/// actions this is every valid target inside the area.
+pub fn get_targeted_entities(game_state: &GameState, action_data: &ActionData) -> Vec<Entity> {
     let mut entities = Vec::new();
     let targeting_context = targeting_context(
         &game_state.world,
@@ -235,10 +547,19 @@ fn get_targeted_entities(game_state: &mut GameState, action_data: &ActionData) -
         TargetingKind::SelfTarget | TargetingKind::Single | TargetingKind::Multiple { .. } => {
             for target in &action_data.targets {
                 match target {
-                    TargetInstance::Entity(entity) => entities.push(*entity),
+                    TargetInstance::Entity(entity)
+                    | TargetInstance::Object(entity)
+                    | TargetInstance::Item { owner: entity, .. } => entities.push(*entity),
                     TargetInstance::Point(point) => {
+                        // Unseen creatures are attacked by guessing their square
                         if let Some(entity) =
                             systems::geometry::get_entity_at_point(&game_state.world, *point)
+                                .or_else(|| {
+                                    systems::visibility::creature_in_square(
+                                        &game_state.world,
+                                        *point,
+                                    )
+                                })
                         {
                             entities.push(entity);
                         }
@@ -253,7 +574,9 @@ fn get_targeted_entities(game_state: &mut GameState, action_data: &ActionData) -
         } => {
             for target in &action_data.targets {
                 let point = match target {
-                    TargetInstance::Entity(entity) => {
+                    TargetInstance::Entity(entity)
+                    | TargetInstance::Object(entity)
+                    | TargetInstance::Item { owner: entity, .. } => {
                         &systems::geometry::get_foot_position(&game_state.world, *entity).unwrap()
                     }
 
@@ -273,11 +596,13 @@ fn get_targeted_entities(game_state: &mut GameState, action_data: &ActionData) -
                     &shape_pose,
                 );
 
-                // Only keep the entities that are valid targets
+                // Only keep the entities that are valid targets. Illusions
+                // aren't really there, so there's nothing to catch in the area.
                 entities_in_shape.retain(|entity| {
                     targeting_context
                         .allowed_targets
                         .matches(&game_state.world, entity)
+                        && !systems::illusions::is_illusion(&game_state.world, *entity)
                 });
 
                 // Check if any of the entities are behind cover and remove them
@@ -309,6 +634,191 @@ fn get_targeted_entities(game_state: &mut GameState, action_data: &ActionData) -
     entities
 }
 
+/// The allies of the actor, possibly including the actor itself, that would be
+/// caught by a harmful action, e.g. a Fireball exploding too close to the party
+pub fn friendly_fire(game_state: &GameState, action_data: &ActionData) -> Vec<Entity> {
+    let Some(action) = get_action(&action_data.action_id) else {
+        return Vec::new();
+    };
+    let harmful = systems::ai::recommeneded_target_attitude(
+        &game_state.world,
+        action_data.actor,
+        &action.kind,
+    ) == Attitude::Hostile;
+    if !harmful {
+        return Vec::new();
+    }
+
+    let targets = get_targeted_entities(game_state, action_data);
+    systems::factions::allies(&game_state.world, action_data.actor, &targets)
+}
+
+/// Preview the outcome of the action on each of the entities it targets
+pub fn preview(game_state: &GameState, action_data: &ActionData) -> Vec<ActionPreview> {
+    let targets = get_targeted_entities(game_state, action_data);
+    resolve_targets(game_state, action_data, &targets)
+        .iter()
+        .filter_map(|resolution| preview_resolution(&game_state.world, action_data, resolution))
+        .collect()
+}
+
+/// Preview the outcome of the action on a single target, without rolling
+/// anything. Returns `None` if the action isn't a standard action.
+pub fn preview_on_target(
+    game_state: &GameState,
+    action_data: &ActionData,
+    target: Entity,
+) -> Option<ActionPreview> {
+    let resolution = resolve_targets(game_state, action_data, &[target]).pop()?;
+    preview_resolution(&game_state.world, action_data, &resolution)
+}
+
+/// Preview the outcome of the action on a target in the situation it was
+/// resolved in, see `resolve_targets`
+fn preview_resolution(
+    world: &World,
+    action_data: &ActionData,
+    resolution: &TargetResolution,
+) -> Option<ActionPreview> {
+    let action = get_action(&action_data.action_id)?;
+    let ActionKind::Standard { condition, payload } = &action.kind else {
+        return None;
+    };
+    let actor = action_data.actor;
+    let context = &action_data.context;
+    let target = resolution.target;
+
+    let (check, success_chance, damage_on_failure) = match condition {
+        ActionCondition::None => (None, 1.0, None),
+
+        ActionCondition::AttackRoll {
+            attack_roll,
+            damage_on_miss,
+        } => {
+            let attack_roll = systems::damage::prepare_attack_roll(
+                attack_roll(world, actor, target, context),
+                world,
+                actor,
+                target,
+            );
+            let mut armor_class = attack_armor_class(world, action_data, target, payload);
+            if let Some((source, bonus)) = resolution.armor_class_bonus() {
+                armor_class.add_modifier(source, bonus);
+            }
+            let hit_chance = attack_roll.hit_chance(world, actor, armor_class.total() as u32);
+            let crit_chance = attack_roll.crit_chance(world, actor, armor_class.total() as u32);
+            (
+                Some(CheckPreview::AttackRoll {
+                    attack_roll,
+                    armor_class,
+                    hit_chance,
+                    crit_chance,
+                }),
+                hit_chance,
+                damage_on_miss.as_ref(),
+            )
+        }
+
+        ActionCondition::SavingThrow {
+            saving_throw,
+            damage_on_save,
+        } => {
+            let saving_throw_dc = saving_throw(world, actor, context, resolution);
+            // A bonus to the save is as good as a lower DC
+            let mut effective_dc = saving_throw_dc.clone();
+            if let Some((source, bonus)) = resolution.saving_throw_bonus(&saving_throw_dc.key) {
+                effective_dc.dc.add_modifier(source, -bonus);
+            }
+            let save_chance = systems::helpers::get_component::<SavingThrowSet>(world, target)
+                .success_probability(&effective_dc, world, target);
+            (
+                Some(CheckPreview::SavingThrow {
+                    saving_throw_dc,
+                    save_chance,
+                }),
+                1.0 - save_chance,
+                damage_on_save.as_ref(),
+            )
+        }
+
+        ActionCondition::SkillCheck { skill_check } => {
+            let skill_check_dc = skill_check(world, actor, context);
+            let success_chance = systems::helpers::get_component::<SkillSet>(world, actor)
+                .success_probability(&skill_check_dc, world, actor);
+            (
+                Some(CheckPreview::SkillCheck {
+                    skill_check_dc,
+                    success_chance,
+                }),
+                success_chance,
+                None,
+            )
+        }
+
+        ActionCondition::Contest { contest } => {
+            let success_chance =
+                systems::d20::contest_success_probability(world, actor, target, contest);
+            (
+                Some(CheckPreview::Contest {
+                    contest: contest.clone(),
+                    defense: systems::d20::contest_defense(world, target, contest),
+                    success_chance,
+                }),
+                success_chance,
+                None,
+            )
+        }
+    };
+
+    let damage = payload.damage().map(|damage_function| {
+        let range = |function: &TargetDamageFunction, crit: bool, half: bool| {
+            damage_range(world, action_data, resolution, function, crit, half)
+        };
+        DamagePreview {
+            full: range(damage_function.as_ref(), false, false),
+            critical: matches!(condition, ActionCondition::AttackRoll { .. })
+                .then(|| range(damage_function.as_ref(), true, false)),
+            reduced: damage_on_failure.map(|damage_on_failure| match damage_on_failure {
+                DamageOnFailure::Half => range(damage_function.as_ref(), false, true),
+                DamageOnFailure::Custom(function) => range(function.as_ref(), false, false),
+            }),
+        }
+    });
+
+    Some(ActionPreview {
+        target,
+        check,
+        success_chance,
+        damage,
+    })
+}
+
+fn damage_range(
+    world: &World,
+    action_data: &ActionData,
+    resolution: &TargetResolution,
+    damage_function: &TargetDamageFunction,
+    crit: bool,
+    half: bool,
+) -> DamageRange {
+    let damage_roll = systems::damage::prepare_damage_roll(
+        damage_function(world, action_data.actor, &action_data.context, resolution),
+        world,
+        action_data.actor,
+    );
+    let (min, max) = damage_roll.min_max_results(crit);
+    let [min, max] = [min, max].map(|mut damage_roll| {
+        damage_roll.action = Some((action_data.actor, action_data.action_id.clone()));
+        if half {
+            damage_roll = halve_damage(&damage_roll, "Half Damage".to_string());
+        }
+        systems::health::mitigate(world, resolution.target, &damage_roll)
+            .total
+            .max(0)
+    });
+    DamageRange { min, max }
+}
+
 pub fn targeting_context(
     world: &World,
     entity: Entity,
@@ -316,7 +826,36 @@ pub fn targeting_context(
     context: &ActionContext,
 ) -> TargetingContext {
     // TODO: Handle missing action
-    get_action(action_id).unwrap().targeting()(world, entity, context)
+    let mut targeting = get_action(action_id).unwrap().targeting()(world, entity, context);
+    if let ActionContext::Spell { metamagic, .. } = context.base() {
+        // Spells need a clear path to their targets, even if the caster can
+        // see them
+        targeting.require_line_of_effect = true;
+        for metamagic in metamagic {
+            metamagic.apply_to_targeting(&mut targeting);
+        }
+    }
+    targeting
+}
+
+/// The target for the entity when it's picked for the action. Actions which
+/// target items target the first item of the entity the action can target, if
+/// any.
+pub fn target_for_entity(
+    world: &World,
+    targeting: &TargetingContext,
+    entity: Entity,
+) -> Option<TargetInstance> {
+    match &targeting.allowed_items {
+        Some(filter) => systems::inventory::item_targets(world, entity, filter)
+            .into_iter()
+            .next()
+            .map(|item| TargetInstance::Item {
+                owner: entity,
+                item,
+            }),
+        None => Some(TargetInstance::from_entity(world, entity)),
+    }
 }
 
 pub fn available_reactions_to_event(
@@ -391,23 +930,31 @@ pub fn perform_reaction(game_state: &mut GameState, reaction_data: &ReactionData
         }
 
         ActionKind::Composite { actions } => {
-            for action in actions {
-                match action {
-                    ActionKind::Reaction { reaction } => {
-                        evaluate_and_apply_reaction(game_state, reaction, reaction_data);
-                    }
-
-                    _ => {
-                        perform_action(game_state, &ActionData::from(reaction_data));
-                    }
+            for step in actions {
+                if let ActionKind::Reaction { reaction } = &step.kind {
+                    evaluate_and_apply_reaction(game_state, reaction, reaction_data);
                 }
             }
+            // The rest of the steps are performed as a regular action, which
+            // skips the scripted steps
+            if actions
+                .iter()
+                .any(|step| !matches!(step.kind, ActionKind::Reaction { .. }))
+            {
+                perform_action(game_state, &ActionData::from(reaction_data));
+            }
         }
 
         _ => {
             perform_action(game_state, &ActionData::from(reaction_data));
         }
     }
+
+    // Scripted reactions like Shield don't go through `perform_action`
+    systems::effects::end_effects_triggered_by(
+        &mut game_state.world,
+        &ActionData::from(reaction_data),
+    );
 }
 
 fn evaluate_and_apply_reaction(
@@ -422,16 +969,97 @@ fn evaluate_and_apply_reaction(
     systems::scripts::apply_reaction_plan(game_state, reaction_data, plan);
 }
 
+/// Roll the damage of the payload a single time, for actions which deal the
+/// same damage to all of their targets, see `DamageRollPolicy::Once`. The
+/// damage is rolled for the first of the resolved targets.
+pub fn roll_shared_damage(
+    world: &World,
+    action_data: &ActionData,
+    payload: &ActionPayload,
+    resolution: &TargetResolution,
+) -> Option<DamageRollResult> {
+    let damage_function = payload.damage()?;
+    let mut damage_roll = systems::damage::damage_roll_fn(
+        damage_function.as_ref(),
+        world,
+        action_data.actor,
+        &action_data.context,
+        resolution,
+        false,
+    );
+    damage_roll.action = Some((action_data.actor, action_data.action_id.clone()));
+    Some(damage_roll)
+}
+
+/// Settle the situation of each target of the action before it's resolved
+/// against any of them, so resolving it against one target can't change how
+/// it's resolved against the next, see `TargetResolution`.
+pub fn resolve_targets(
+    game_state: &GameState,
+    action_data: &ActionData,
+    targets: &[Entity],
+) -> Vec<TargetResolution> {
+    // Areas centered on a point, e.g. a Fireball, come from that point, while
+    // everything else comes from the performer
+    let from_point = get_action(&action_data.action_id).is_some()
+        && matches!(
+            targeting_context(
+                &game_state.world,
+                action_data.actor,
+                &action_data.action_id,
+                &action_data.context,
+            )
+            .kind,
+            TargetingKind::Area {
+                shape: AreaShape::Sphere { .. },
+                fixed_on_actor: false,
+            }
+        );
+    let actor_eyes = systems::geometry::get_eye_position(&game_state.world, action_data.actor);
+
+    targets
+        .iter()
+        .map(|target| {
+            let origin = if from_point {
+                // The point is on the ground, so raise it a bit to keep the
+                // ground itself from covering the target
+                action_data
+                    .point_affecting(&game_state.world, *target)
+                    .map(|point| point + Vector3::y() * 0.1)
+            } else {
+                actor_eyes
+            };
+            let cover = match origin {
+                Some(origin) if *target != action_data.actor => systems::geometry::cover(
+                    &game_state.world,
+                    &game_state.geometry,
+                    origin,
+                    *target,
+                ),
+                _ => Cover::None,
+            };
+            TargetResolution {
+                target: *target,
+                origin,
+                cover,
+                shared_damage: None,
+            }
+        })
+        .collect()
+}
+
+/// Perform a standard action on a single target, as it was resolved before the
+/// action was performed on any of its targets, see `resolve_targets`.
 pub fn perform_standard_action(
     game_state: &mut GameState,
     action_kind: &ActionKind,
     action_data: &ActionData,
-    target: Entity,
+    resolution: &TargetResolution,
 ) -> Result<(), ActionError> {
     match action_kind {
         ActionKind::Standard { condition, payload } => match condition {
             ActionCondition::None => {
-                perform_unconditional(game_state, action_data, target, payload)
+                perform_unconditional(game_state, action_data, resolution, payload)
             }
             ActionCondition::AttackRoll {
                 attack_roll,
@@ -439,7 +1067,7 @@ pub fn perform_standard_action(
             } => perform_attack_roll(
                 game_state,
                 action_data,
-                target,
+                resolution,
                 attack_roll,
                 payload,
                 damage_on_miss,
@@ -450,11 +1078,17 @@ pub fn perform_standard_action(
             } => perform_saving_throw(
                 game_state,
                 action_data,
-                target,
+                resolution,
                 saving_throw,
                 payload,
                 damage_on_save,
             ),
+            ActionCondition::SkillCheck { skill_check } => {
+                perform_skill_check(game_state, action_data, resolution, skill_check, payload)
+            }
+            ActionCondition::Contest { contest } => {
+                perform_contest(game_state, action_data, resolution, contest, payload)
+            }
         },
 
         _ => {
@@ -467,9 +1101,10 @@ pub fn perform_standard_action(
 fn perform_unconditional(
     game_state: &mut GameState,
     action_data: &ActionData,
-    target: Entity,
+    resolution: &TargetResolution,
     payload: &ActionPayload,
 ) -> Result<(), ActionError> {
+    let target = resolution.target;
     // Apply effect immediately (no gating for unconditional).
     let effect_outcome: Option<EffectOutcome> = get_effect_outcome(
         &mut game_state.world,
@@ -483,18 +1118,40 @@ fn perform_unconditional(
     let healing_outcome: Option<HealingOutcome> = payload.healing().map(|healing_amount| {
         let healing_amount =
             healing_amount(&game_state.world, action_data.actor, &action_data.context).roll();
-        let new_life_state = systems::health::heal(
+        let (received, new_life_state) = systems::health::receive_healing(
             &mut game_state.world,
             target,
-            healing_amount.subtotal as u32,
+            healing_amount.subtotal.max(0) as u32,
         );
 
         HealingOutcome {
             healing: healing_amount,
+            received,
             new_life_state,
         }
     });
 
+    let stabilized = get_stabilize_outcome(&mut game_state.world, target, action_data, payload);
+    let revived = get_revive_outcome(game_state, target, action_data, payload);
+    let cured =
+        systems::effects::remove_effects_with_tags(&mut game_state.world, target, payload.cures());
+    let conjured = get_conjured(&mut game_state.world, target, payload);
+    if payload.search() {
+        systems::discovery::search(game_state, target);
+    }
+    if payload.investigate() {
+        systems::illusions::investigate(game_state, action_data.actor, target);
+    }
+    let disarmed = get_disarm_outcome(&mut game_state.world, target, action_data, payload);
+    if payload.pick_up() {
+        systems::inventory::pick_up(game_state, action_data.actor, target);
+    }
+    if payload.drop_item()
+        && let Some(item) = item_target(action_data, target)
+    {
+        systems::inventory::drop_target(game_state, target, &item);
+    }
+
     let Some(damage_roll) = get_damage_roll(
         &game_state.world,
         action_data.actor,
@@ -505,12 +1162,20 @@ fn perform_unconditional(
         &action_data.context,
         true,
         false,
+        resolution,
     ) else {
         // If there is no damage, we can emit the ActionPerformed immediately.
         let result: ActionKindResult = ActionKindResult::Standard(ActionOutcomeBundle {
             damage: None,
+            saving_throw: None,
             effect: effect_outcome,
             healing: healing_outcome,
+            stabilized,
+            revived,
+            cured,
+            conjured,
+            disarmed,
+            sundered: None,
         });
 
         return game_state.process_event(Event::action_performed_event(
@@ -524,16 +1189,24 @@ fn perform_unconditional(
     let damage_event = Event::new(EventKind::DamageRollPerformed(
         action_data.actor,
         damage_roll,
+        target,
     ));
 
     let callback: EventCallback = Arc::new({
         let action_data = action_data.clone();
+        let payload = payload.clone();
         let effect_result = effect_outcome.clone();
 
         move |game_state, event| match &event.kind {
             EventKind::DamageRollResolved(_, damage_roll_result) => {
-                let (damage_taken, new_life_state) =
-                    systems::health::damage(game_state, target, damage_roll_result, None);
+                let (damage_taken, new_life_state, sundered) = apply_damage(
+                    game_state,
+                    &action_data,
+                    target,
+                    &payload,
+                    damage_roll_result,
+                    None,
+                );
 
                 let damage_outcome = DamageOutcome::unconditional(
                     Some(damage_roll_result.clone()),
@@ -543,8 +1216,15 @@ fn perform_unconditional(
 
                 let result = ActionKindResult::Standard(ActionOutcomeBundle {
                     damage: Some(damage_outcome),
+                    saving_throw: None,
                     effect: effect_result.clone(),
                     healing: healing_outcome.clone(),
+                    stabilized,
+                    revived,
+                    cured: cured.clone(),
+                    conjured: conjured.clone(),
+                    disarmed: disarmed.clone(),
+                    sundered,
                 });
 
                 CallbackResult::Event(Event::action_performed_event(
@@ -566,11 +1246,12 @@ fn perform_unconditional(
 fn perform_attack_roll(
     game_state: &mut GameState,
     action_data: &ActionData,
-    target: Entity,
+    resolution: &TargetResolution,
     attack_roll_function: &Arc<AttackRollFunction>,
     payload: &ActionPayload,
     damage_on_miss: &Option<DamageOnFailure>,
 ) -> Result<(), ActionError> {
+    let target = resolution.target;
     let attack_roll = systems::damage::attack_roll_fn(
         attack_roll_function.as_ref(),
         &game_state.world,
@@ -579,7 +1260,10 @@ fn perform_attack_roll(
         &action_data.context,
     );
 
-    let armor_class = systems::loadout::armor_class(&game_state.world, target);
+    let mut armor_class = attack_armor_class(&game_state.world, action_data, target, payload);
+    if let Some((source, bonus)) = resolution.armor_class_bonus() {
+        armor_class.add_modifier(source, bonus);
+    }
 
     let attack_event = Event::new(EventKind::D20CheckPerformed(
         action_data.actor,
@@ -594,6 +1278,7 @@ fn perform_attack_roll(
         let attack_roll = attack_roll.clone();
         let payload = payload.clone();
         let damage_on_miss = damage_on_miss.clone();
+        let resolution = resolution.clone();
 
         move |game_state, event| match &event.kind {
             EventKind::D20CheckResolved(_, result, dc) => {
@@ -603,7 +1288,11 @@ fn perform_attack_roll(
                 };
 
                 let hit = result.is_success(dc);
-                let is_crit = result.d20_result().is_crit;
+                let mut attack_roll = attack_roll.clone();
+                if hit {
+                    attack_roll.confirm_hit();
+                }
+                let is_crit = result.d20_result().is_crit || attack_roll.roll_result.is_crit;
 
                 // Decide effect application
                 let effect_result: Option<EffectOutcome> = if hit {
@@ -617,8 +1306,13 @@ fn perform_attack_roll(
                 } else {
                     None
                 };
+                let disarmed = if hit {
+                    get_disarm_outcome(&mut game_state.world, target, &action_data, &payload)
+                } else {
+                    None
+                };
 
-                let damage_roll = get_damage_roll(
+                let mut damage_roll = get_damage_roll(
                     &game_state.world,
                     action_data.actor,
                     &action_data.action_id,
@@ -628,8 +1322,32 @@ fn perform_attack_roll(
                     &action_data.context,
                     hit,
                     is_crit,
+                    &resolution,
                 );
 
+                if hit {
+                    apply_on_hit_riders(
+                        &mut game_state.world,
+                        target,
+                        &action_data,
+                        is_crit,
+                        damage_roll.as_mut(),
+                    );
+                }
+
+                // Thrown weapons leave the hand whether they hit or not, but
+                // only once everything that depends on the weapon is rolled
+                if payload.throw()
+                    && let ActionContext::Weapon { slot } = action_data.context.base()
+                {
+                    systems::inventory::throw_weapon(
+                        &mut game_state.world,
+                        action_data.actor,
+                        slot,
+                        target,
+                    );
+                }
+
                 // If no damage or not hit, return immediately.
                 if damage_roll.is_none() || !hit {
                     let result = ActionKindResult::Standard(ActionOutcomeBundle {
@@ -640,8 +1358,15 @@ fn perform_attack_roll(
                             attack_roll.clone(),
                             armor_class,
                         )),
+                        saving_throw: None,
                         effect: effect_result.clone(),
                         healing: None,
+                        stabilized: None,
+                        revived: None,
+                        cured: Vec::new(),
+                        conjured: Vec::new(),
+                        disarmed,
+                        sundered: None,
                     });
 
                     return CallbackResult::Event(Event::action_performed_event(
@@ -654,53 +1379,68 @@ fn perform_attack_roll(
                 let damage_event = Event::new(EventKind::DamageRollPerformed(
                     action_data.actor,
                     damage_roll.unwrap(),
+                    target,
                 ));
 
-                CallbackResult::EventWithCallback(
-                    damage_event,
-                    Arc::new({
-                        let action_data = action_data.clone();
-                        let attack_roll = attack_roll.clone();
-                        let armor_class = armor_class.clone();
-                        let hit = hit;
-                        let effect_result = effect_result.clone();
-
-                        move |game_state, event| match &event.kind {
-                            EventKind::DamageRollResolved(_, damage_roll_result) => {
-                                let (damage_taken, new_life_state) = if hit {
-                                    systems::health::damage(
-                                        game_state,
-                                        target,
-                                        damage_roll_result,
-                                        Some(&attack_roll),
-                                    )
-                                } else {
-                                    (None, None)
-                                };
-
-                                let damage_outcome = DamageOutcome::attack_roll(
-                                    Some(damage_roll_result.clone()),
-                                    damage_taken,
-                                    new_life_state,
-                                    attack_roll.clone(),
-                                    armor_class.clone(),
-                                );
-
-                                let result = ActionKindResult::Standard(ActionOutcomeBundle {
-                                    damage: Some(damage_outcome),
-                                    effect: effect_result.clone(),
-                                    healing: None,
-                                });
-
-                                CallbackResult::Event(Event::action_performed_event(
+                let damage_callback: EventCallback = Arc::new({
+                    let action_data = action_data.clone();
+                    let attack_roll = attack_roll.clone();
+                    let armor_class = armor_class.clone();
+                    let hit = hit;
+                    let effect_result = effect_result.clone();
+                    let payload = payload.clone();
+
+                    move |game_state, event| match &event.kind {
+                        EventKind::DamageRollResolved(_, damage_roll_result) => {
+                            let (damage_taken, new_life_state, sundered) = if hit {
+                                apply_damage(
                                     game_state,
                                     &action_data,
-                                    vec![(target, result)],
-                                ))
-                            }
-                            _ => panic!("Unexpected event kind in damage callback: {:?}", event),
+                                    target,
+                                    &payload,
+                                    damage_roll_result,
+                                    Some(&attack_roll),
+                                )
+                            } else {
+                                (None, None, None)
+                            };
+
+                            let damage_outcome = DamageOutcome::attack_roll(
+                                Some(damage_roll_result.clone()),
+                                damage_taken,
+                                new_life_state,
+                                attack_roll.clone(),
+                                armor_class.clone(),
+                            );
+
+                            let result = ActionKindResult::Standard(ActionOutcomeBundle {
+                                damage: Some(damage_outcome),
+                                saving_throw: None,
+                                effect: effect_result.clone(),
+                                healing: None,
+                                stabilized: None,
+                                revived: None,
+                                cured: Vec::new(),
+                                conjured: Vec::new(),
+                                disarmed: disarmed.clone(),
+                                sundered,
+                            });
+
+                            CallbackResult::Event(Event::action_performed_event(
+                                game_state,
+                                &action_data,
+                                vec![(target, result)],
+                            ))
                         }
-                    }),
+                        _ => panic!("Unexpected event kind in damage callback: {:?}", event),
+                    }
+                });
+
+                decide_on_hit_reactions(
+                    game_state,
+                    action_data.actor,
+                    damage_event,
+                    damage_callback,
                 )
             }
             _ => panic!("Unexpected event kind in attack roll callback: {:?}", event),
@@ -710,27 +1450,101 @@ fn perform_attack_roll(
     game_state.process_event_with_callback(attack_event, callback)
 }
 
+/// The attacker's own reactions to the damage of its hit, e.g. Divine Smite,
+/// are decided on right after the attack hits, before the damage roll is
+/// processed. The chosen reaction is applied to the damage roll directly, and
+/// the attacker isn't offered the reactions again when the damage is rolled.
+fn decide_on_hit_reactions(
+    game_state: &mut GameState,
+    attacker: Entity,
+    damage_event: Event,
+    damage_callback: EventCallback,
+) -> CallbackResult {
+    let reactions = available_reactions_to_event(
+        &game_state.world,
+        &game_state.geometry,
+        attacker,
+        &damage_event,
+    );
+    game_state
+        .event_log
+        .record_reaction(damage_event.id, attacker);
+    if reactions.is_empty() {
+        return CallbackResult::EventWithCallback(damage_event, damage_callback);
+    }
+
+    let options = std::iter::once("Don't react".to_string())
+        .chain(
+            reactions
+                .iter()
+                .map(|reaction| match reaction.context.base() {
+                    ActionContext::Spell { level, .. } => {
+                        format!("{} (level {})", reaction.reaction_id, level)
+                    }
+                    _ => reaction.reaction_id.to_string(),
+                }),
+        )
+        .collect();
+
+    CallbackResult::Decision(
+        DecisionRequest::new(attacker, "The attack hits", options),
+        Arc::new(move |game_state, option| {
+            let mut damage_event = damage_event.clone();
+            // The first option is not to react
+            if let Some(reaction) = option.checked_sub(1).and_then(|index| reactions.get(index)) {
+                match game_state.validate_action(&ActionData::from(reaction), true) {
+                    Ok(()) => {
+                        systems::scripts::apply_reaction_to_event(
+                            &game_state.world,
+                            reaction,
+                            &mut damage_event,
+                        );
+                        systems::effects::end_effects_triggered_by(
+                            &mut game_state.world,
+                            &ActionData::from(reaction),
+                        );
+                    }
+                    Err(error) => warn!(
+                        "Reaction {:?} chosen after a hit can't be used: {:?}",
+                        reaction.reaction_id, error
+                    ),
+                }
+            }
+            CallbackResult::EventWithCallback(damage_event, damage_callback.clone())
+        }),
+    )
+}
+
 fn perform_saving_throw(
     game_state: &mut GameState,
     action_data: &ActionData,
-    target: Entity,
-    saving_throw_function: &Arc<SavingThrowFunction>,
+    resolution: &TargetResolution,
+    saving_throw_function: &Arc<TargetSavingThrowFunction>,
     payload: &ActionPayload,
     damage_on_save: &Option<DamageOnFailure>,
 ) -> Result<(), ActionError> {
-    let saving_throw_dc =
-        saving_throw_function(&game_state.world, action_data.actor, &action_data.context);
+    let target = resolution.target;
+    let saving_throw_dc = saving_throw_function(
+        &game_state.world,
+        action_data.actor,
+        &action_data.context,
+        resolution,
+    );
 
-    let saving_throw_event = systems::d20::check(
+    let mut saving_throw_event = systems::d20::check(
         game_state,
         target,
         &D20CheckDCKind::SavingThrow(saving_throw_dc.clone()),
     );
+    if let Some((source, bonus)) = resolution.saving_throw_bonus(&saving_throw_dc.key) {
+        EventModification::D20Bonus { source, bonus }.apply(&mut saving_throw_event);
+    }
 
     let callback: EventCallback = Arc::new({
         let action_data = action_data.clone();
         let payload = payload.clone();
         let damage_on_save = damage_on_save.clone();
+        let resolution = resolution.clone();
 
         move |game_state, event| match &event.kind {
             EventKind::D20CheckResolved(_, result, dc) => {
@@ -740,10 +1554,19 @@ fn perform_saving_throw(
                 };
 
                 let save_success = result.is_success(dc);
+                let saving_throw_outcome = SavingThrowOutcome {
+                    dc: saving_throw_dc.clone(),
+                    result: result.d20_result().clone(),
+                    success: save_success,
+                };
 
-                // Decide effect application
+                // Decide effect application. A successful save resists the effect.
                 let effect_result: Option<EffectOutcome> = if save_success {
-                    None
+                    payload.effect().map(|effect| EffectOutcome {
+                        effect: effect.effect_id.clone(),
+                        applied: false,
+                        rule: EffectApplyRule::OnFailedSave,
+                    })
                 } else {
                     get_effect_outcome(
                         &mut game_state.world,
@@ -765,11 +1588,19 @@ fn perform_saving_throw(
                     &action_data.context,
                     !save_success,
                     false,
+                    &resolution,
                 ) else {
                     let result = ActionKindResult::Standard(ActionOutcomeBundle {
                         damage: None,
+                        saving_throw: Some(saving_throw_outcome),
                         effect: effect_result.clone(),
                         healing: None,
+                        stabilized: None,
+                        revived: None,
+                        cured: Vec::new(),
+                        conjured: Vec::new(),
+                        disarmed: None,
+                        sundered: None,
                     });
 
                     return CallbackResult::Event(Event::action_performed_event(
@@ -782,21 +1613,25 @@ fn perform_saving_throw(
                 let damage_event = Event::new(EventKind::DamageRollPerformed(
                     action_data.actor,
                     damage_roll,
+                    target,
                 ));
 
                 CallbackResult::EventWithCallback(
                     damage_event,
                     Arc::new({
                         let action_data = action_data.clone();
+                        let payload = payload.clone();
                         let saving_throw_result = result.clone();
                         let saving_throw_dc = saving_throw_dc.clone();
                         let effect_result = effect_result.clone();
 
                         move |game_state, event| match &event.kind {
                             EventKind::DamageRollResolved(_, damage_roll_result) => {
-                                let (damage_taken, new_life_state) = systems::health::damage(
+                                let (damage_taken, new_life_state, sundered) = apply_damage(
                                     game_state,
+                                    &action_data,
                                     target,
+                                    &payload,
                                     damage_roll_result,
                                     None,
                                 );
@@ -811,8 +1646,15 @@ fn perform_saving_throw(
 
                                 let result = ActionKindResult::Standard(ActionOutcomeBundle {
                                     damage: Some(damage_outcome),
+                                    saving_throw: Some(saving_throw_outcome.clone()),
                                     effect: effect_result.clone(),
                                     healing: None,
+                                    stabilized: None,
+                                    revived: None,
+                                    cured: Vec::new(),
+                                    conjured: Vec::new(),
+                                    disarmed: None,
+                                    sundered,
                                 });
 
                                 CallbackResult::Event(Event::action_performed_event(
@@ -836,6 +1678,84 @@ fn perform_saving_throw(
     game_state.process_event_with_callback(saving_throw_event, callback)
 }
 
+fn perform_skill_check(
+    game_state: &mut GameState,
+    action_data: &ActionData,
+    resolution: &TargetResolution,
+    skill_check_function: &Arc<SkillCheckFunction>,
+    payload: &ActionPayload,
+) -> Result<(), ActionError> {
+    let skill_check_dc =
+        skill_check_function(&game_state.world, action_data.actor, &action_data.context);
+    perform_skill_check_against(game_state, action_data, resolution, skill_check_dc, payload)
+}
+
+fn perform_contest(
+    game_state: &mut GameState,
+    action_data: &ActionData,
+    resolution: &TargetResolution,
+    contest: &SkillContest,
+    payload: &ActionPayload,
+) -> Result<(), ActionError> {
+    // The target rolls first, and the performer has to beat its result
+    let (_, skill_check_dc) =
+        systems::d20::contest_dc(&game_state.world, resolution.target, contest);
+    perform_skill_check_against(game_state, action_data, resolution, skill_check_dc, payload)
+}
+
+fn perform_skill_check_against(
+    game_state: &mut GameState,
+    action_data: &ActionData,
+    resolution: &TargetResolution,
+    skill_check_dc: SkillCheckDC,
+    payload: &ActionPayload,
+) -> Result<(), ActionError> {
+    let target = resolution.target;
+    // Unlike saving throws, it's the performer who makes the check
+    let skill_check_event = systems::d20::check(
+        game_state,
+        action_data.actor,
+        &D20CheckDCKind::Skill(skill_check_dc),
+    );
+
+    let callback: EventCallback = Arc::new({
+        let action_data = action_data.clone();
+        let payload = payload.clone();
+        let resolution = resolution.clone();
+
+        move |game_state, event| match &event.kind {
+            EventKind::D20CheckResolved(_, result, dc) => {
+                if result.is_success(dc) {
+                    let _ = perform_unconditional(game_state, &action_data, &resolution, &payload);
+                    return CallbackResult::None;
+                }
+
+                let result = ActionKindResult::Standard(ActionOutcomeBundle {
+                    damage: None,
+                    saving_throw: None,
+                    effect: None,
+                    healing: None,
+                    stabilized: None,
+                    revived: None,
+                    cured: Vec::new(),
+                    conjured: Vec::new(),
+                    disarmed: None,
+                    sundered: None,
+                });
+
+                CallbackResult::Event(Event::action_performed_event(
+                    game_state,
+                    &action_data,
+                    vec![(target, result)],
+                ))
+            }
+            _ => panic!("Unexpected event kind in skill check callback: {:?}", event),
+        }
+    });
+
+    game_state.process_event_with_callback(skill_check_event, callback)
+}
+
 // TODO: Doesn't seem like the cleanest solution
 fn get_damage_roll(
     world: &World,
@@ -847,6 +1767,7 @@ fn get_damage_roll(
     context: &ActionContext,
     success: bool,
     crit: bool,
+    resolution: &TargetResolution,
 ) -> Option<DamageRollResult> {
     let damage_function = if let Some(damage_on_failure) = &damage_on_failure
         && !success
@@ -862,25 +1783,34 @@ fn get_damage_roll(
     let Some(damage_function) = damage_function else {
         return None;
     };
+    // Without any damage on a failure, e.g. a successful save against Acid
+    // Splash, there's nothing to roll
+    if !success && damage_on_failure.is_none() {
+        return None;
+    }
 
-    let mut damage_roll =
-        systems::damage::damage_roll_fn(damage_function.as_ref(), world, entity, context, crit);
-    damage_roll.action = Some((entity, action.clone()));
+    // The shared roll is only for the regular damage, custom damage on a
+    // failure is still rolled for each target
+    let custom_failure = matches!(damage_on_failure, Some(DamageOnFailure::Custom(_))) && !success;
+    let damage_roll = match &resolution.shared_damage {
+        Some(shared_damage) if !custom_failure => shared_damage.clone(),
+        _ => {
+            let mut damage_roll = systems::damage::damage_roll_fn(
+                damage_function.as_ref(),
+                world,
+                entity,
+                context,
+                resolution,
+                crit,
+            );
+            damage_roll.action = Some((entity, action.clone()));
+            damage_roll
+        }
+    };
 
     if let Some(damage_on_failure) = damage_on_failure {
         match damage_on_failure {
-            DamageOnFailure::Half if !success => {
-                let mut half_damage_roll = damage_roll.clone();
-                for component in half_damage_roll.components.iter_mut() {
-                    let total = component.result.subtotal;
-                    component.result.modifiers.add_modifier(
-                        ModifierSource::Custom(failure_label.clone()),
-                        -(total as f32 / 2.0).ceil() as i32,
-                    );
-                }
-                half_damage_roll.recalculate_total();
-                Some(half_damage_roll)
-            }
+            DamageOnFailure::Half if !success => Some(halve_damage(&damage_roll, failure_label)),
             _ => Some(damage_roll),
         }
     } else {
@@ -888,6 +1818,62 @@ fn get_damage_roll(
     }
 }
 
+fn halve_damage(damage_roll: &DamageRollResult, label: String) -> DamageRollResult {
+    let mut half_damage_roll = damage_roll.clone();
+    for component in half_damage_roll.components.iter_mut() {
+        let total = component.result.subtotal;
+        component.result.modifiers.add_modifier(
+            ModifierSource::Custom(label.clone()),
+            -(total as f32 / 2.0).ceil() as i32,
+        );
+    }
+    half_damage_roll.recalculate_total();
+    half_damage_roll
+}
+
+/// Apply the on-hit riders of the weapon used for the attack and whatever it's
+/// coated in, if any. Extra damage is added to the damage roll so it shows up in
+/// the mitigation breakdown.
+fn apply_on_hit_riders(
+    world: &mut World,
+    target: Entity,
+    action_data: &ActionData,
+    crit: bool,
+    mut damage_roll: Option<&mut DamageRollResult>,
+) {
+    let ActionContext::Weapon { slot } = action_data.context.base() else {
+        return;
+    };
+    systems::coating::on_hit(
+        world,
+        action_data.actor,
+        target,
+        slot,
+        &action_data.context,
+        damage_roll.as_deref_mut(),
+    );
+    let Some((item_id, on_hit)) = systems::loadout::on_hit(world, action_data.actor, slot) else {
+        return;
+    };
+
+    if let Some(damage_roll) = damage_roll {
+        for component in on_hit.roll_damage(crit) {
+            damage_roll.add_component(component);
+        }
+    }
+
+    for effect in &on_hit.effects {
+        systems::effects::add_effect_template(
+            world,
+            action_data.actor,
+            target,
+            ModifierSource::Item(item_id.clone()),
+            effect,
+            Some(&action_data.context),
+        );
+    }
+}
+
 fn get_effect_outcome(
     world: &mut World,
     target: Entity,
@@ -928,3 +1914,142 @@ fn get_effect_outcome(
         }
     })
 }
+
+fn get_stabilize_outcome(
+    world: &mut World,
+    target: Entity,
+    action_data: &ActionData,
+    payload: &ActionPayload,
+) -> Option<LifeState> {
+    if !payload.stabilize() {
+        return None;
+    }
+    let new_life_state = systems::health::stabilize(world, target)?;
+
+    // Someone with the Healer feat also restores a hit point to the creatures
+    // they stabilize with a Healer's Kit
+    let healer = world
+        .get::<&Vec<FeatId>>(action_data.actor)
+        .is_ok_and(|feats| feats.contains(&FeatId::new("nat20_core", "feat.healer")));
+    if healer && systems::inventory::is_tool_action(&action_data.action_id) {
+        return systems::health::heal(world, target, 1).or(Some(new_life_state));
+    }
+
+    Some(new_life_state)
+}
+
+fn get_conjured(world: &mut World, target: Entity, payload: &ActionPayload) -> Vec<ItemId> {
+    payload
+        .conjures()
+        .iter()
+        .filter(|item_id| {
+            let Some(item) = ItemsRegistry::get(item_id) else {
+                warn!("Cannot conjure unknown item {}", item_id);
+                return false;
+            };
+            systems::inventory::add_item(world, target, item.clone());
+            true
+        })
+        .cloned()
+        .collect()
+}
+
+/// The item of the target the action is aimed at, e.g. the weapon someone is
+/// trying to knock out of its hands
+fn item_target(action_data: &ActionData, target: Entity) -> Option<ItemTarget> {
+    action_data
+        .targets
+        .iter()
+        .find_map(|instance| match instance {
+            TargetInstance::Item { owner, item } if *owner == target => Some(item.clone()),
+            _ => None,
+        })
+}
+
+/// Sundering attacks are made against the armor class of the targeted item
+/// rather than the one of its owner
+fn attack_armor_class(
+    world: &World,
+    action_data: &ActionData,
+    target: Entity,
+    payload: &ActionPayload,
+) -> ArmorClass {
+    if payload.sunder()
+        && let Some(item) = item_target(action_data, target)
+            .and_then(|item| systems::inventory::target_item(world, target, &item))
+    {
+        return ArmorClass::new(
+            item.item().armor_class(),
+            ModifierSource::Item(item.id().clone()),
+            ArmorDexterityBonus::Limited(0),
+        );
+    }
+    systems::loadout::armor_class(world, target)
+}
+
+/// Deal the damage to the target, or to the targeted item if the payload
+/// sunders it
+fn apply_damage(
+    game_state: &mut GameState,
+    action_data: &ActionData,
+    target: Entity,
+    payload: &ActionPayload,
+    damage_roll_result: &DamageRollResult,
+    attack_roll: Option<&AttackRollResult>,
+) -> (
+    Option<DamageMitigationResult>,
+    Option<LifeState>,
+    Option<ItemDamageOutcome>,
+) {
+    if !payload.sunder() {
+        let (damage_taken, new_life_state) =
+            systems::health::damage(game_state, target, damage_roll_result, attack_roll);
+        return (damage_taken, new_life_state, None);
+    }
+
+    let sundered = item_target(action_data, target).and_then(|item| {
+        systems::inventory::damage_item(
+            &mut game_state.world,
+            target,
+            &item,
+            damage_roll_result.total.max(0) as u32,
+        )
+    });
+    (None, None, sundered)
+}
+
+fn get_disarm_outcome(
+    world: &mut World,
+    target: Entity,
+    action_data: &ActionData,
+    payload: &ActionPayload,
+) -> Option<ItemId> {
+    if !payload.disarm() {
+        return None;
+    }
+    systems::inventory::disarm(world, target, item_target(action_data, target).as_ref())
+}
+
+fn get_revive_outcome(
+    game_state: &mut GameState,
+    target: Entity,
+    action_data: &ActionData,
+    payload: &ActionPayload,
+) -> Option<LifeState> {
+    let resurrection = payload.revive()?;
+    match systems::health::resurrect(game_state, target, resurrection) {
+        Ok(new_life_state) => {
+            systems::spells::consume_material_component(
+                &mut game_state.world,
+                action_data.actor,
+                &action_data.context,
+                MaterialConsumption::OnSuccess,
+            );
+            Some(new_life_state)
+        }
+        Err(error) => {
+            debug!("Failed to bring {:?} back to life: {:?}", target, error);
+            None
+        }
+    }
+}
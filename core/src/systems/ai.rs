@@ -17,13 +17,31 @@ pub fn is_player_controlled(world: &World, entity: Entity) -> bool {
     world.get::<&PlayerControlledTag>(entity).is_ok()
 }
 
+/// Whether a player decides what the entity does on its turn. Usually the same
+/// as [`is_player_controlled`], unless someone else has taken control of it,
+/// e.g. with Dominate Person.
+pub fn is_decided_by_player(world: &World, entity: Entity) -> bool {
+    let decider = systems::control::controller_of(world, entity).unwrap_or(entity);
+    is_player_controlled(world, decider)
+}
+
 pub fn decide_action(
     game_state: &mut GameState,
     prompt: &ActionPrompt,
     actor: Entity,
 ) -> AIDecision {
-    let controller_id =
-        systems::helpers::get_component_clone::<AIControllerId>(&game_state.world, actor);
+    // A controlled creature is played by the brain of its controller
+    let controller_id = systems::control::controller_of(&game_state.world, actor)
+        .and_then(|controller| {
+            game_state
+                .world
+                .get::<&AIControllerId>(controller)
+                .ok()
+                .map(|id| id.clone())
+        })
+        .unwrap_or_else(|| {
+            systems::helpers::get_component_clone::<AIControllerId>(&game_state.world, actor)
+        });
 
     registry::ai::AI_CONTROLLER_REGISTRY
         .get(&controller_id)
@@ -41,7 +59,7 @@ pub fn recommeneded_target_attitude(
             if payload.damage().is_some() {
                 return Attitude::Hostile;
             }
-            if payload.healing().is_some() {
+            if payload.healing().is_some() || payload.stabilize() || payload.revive().is_some() {
                 return Attitude::Friendly;
             }
             if let Some(effect) = payload.effect() {
@@ -57,8 +75,8 @@ pub fn recommeneded_target_attitude(
             // TODO: Hopefully there's never a mix of friendly and hostile sub-actions?
             // If any sub-action is hostile, be hostile; else friendly
             let mut best = Attitude::Friendly;
-            for sub_action in actions {
-                let attitude = recommeneded_target_attitude(world, actor, sub_action);
+            for step in actions {
+                let attitude = recommeneded_target_attitude(world, actor, &step.kind);
                 best = best.max(attitude);
                 if best == Attitude::Hostile {
                     break;
@@ -67,12 +85,20 @@ pub fn recommeneded_target_attitude(
             best
         }
 
-        ActionKind::Variant { variants } => {
-            todo!()
-        }
+        ActionKind::Variant { variants } => variants
+            .iter()
+            .filter_map(systems::actions::get_action)
+            .map(|variant| recommeneded_target_attitude(world, actor, &variant.kind))
+            .max()
+            .unwrap_or(Attitude::Neutral),
+
+        // Utility actions are aimed at doors and levers rather than creatures
+        ActionKind::Utility { .. } => Attitude::Neutral,
 
-        ActionKind::Custom(_) => todo!(),
+        // Illusions are placed on the ground rather than on a creature
+        ActionKind::Illusion { .. } => Attitude::Neutral,
 
-        ActionKind::Reaction { .. } => todo!(),
+        // No way of telling what these do to their targets
+        ActionKind::Custom { .. } | ActionKind::Reaction { .. } => Attitude::Neutral,
     }
 }
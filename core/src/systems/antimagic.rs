@@ -0,0 +1,163 @@
+use hecs::{Entity, World};
+use strum::IntoEnumIterator;
+use tracing::debug;
+
+use crate::{
+    components::{
+        damage::{AttackRoll, DamageRoll, DamageTag},
+        effects::effect::EffectInstance,
+        id::{EffectId, ItemId, SpellId},
+        items::{
+            equipment::{loadout::Loadout, slots::EquipmentSlot},
+            inventory::ItemContainer,
+            item::ItemRarity,
+        },
+        modifier::{Modifiable, ModifierSource},
+        surface::SurfaceKind,
+    },
+    engine::game_state::GameState,
+    registry::registry::{ItemsRegistry, SpellsRegistry},
+    systems,
+};
+
+/// Marks an entity as being inside an antimagic field
+pub fn antimagic_field() -> EffectId {
+    EffectId::new("nat20_core", "effect.antimagic_field")
+}
+
+fn suppressor() -> ModifierSource {
+    ModifierSource::Custom("Antimagic Field".to_string())
+}
+
+pub fn in_antimagic_field(world: &World, entity: Entity) -> bool {
+    systems::effects::has_effect(world, entity, &antimagic_field())
+}
+
+/// Effects which come from spells or magic items. Items of common rarity are
+/// mundane, e.g. the Stealth penalty of heavy armor keeps working.
+pub fn is_magical(world: &World, entity: Entity, effect: &EffectInstance) -> bool {
+    match &effect.source {
+        ModifierSource::Action(action_id) => {
+            let spell_id: SpellId = action_id.into();
+            SpellsRegistry::get(&spell_id).is_some()
+        }
+        ModifierSource::Item(item_id) => {
+            item_rarity(world, entity, item_id).is_some_and(|rarity| rarity != ItemRarity::Common)
+        }
+        _ => false,
+    }
+}
+
+/// Items can be changed at runtime, e.g. by enchanting them, so the equipped
+/// item takes precedence over the one in the registry
+fn item_rarity(world: &World, entity: Entity, item_id: &ItemId) -> Option<ItemRarity> {
+    if let Ok(loadout) = world.get::<&Loadout>(entity)
+        && let Some(item) = EquipmentSlot::iter()
+            .filter_map(|slot| loadout.item_in_slot(&slot))
+            .find(|item| item.item().id == *item_id)
+    {
+        return Some(item.item().rarity.clone());
+    }
+    ItemsRegistry::get(item_id).map(|item| item.item().rarity.clone())
+}
+
+/// The source suppressing the effect if it were added to the entity right now,
+/// i.e. if it's magical and the entity is inside an antimagic field
+pub fn suppressed_by(
+    world: &World,
+    entity: Entity,
+    effect: &EffectInstance,
+) -> Option<ModifierSource> {
+    (in_antimagic_field(world, entity) && is_magical(world, entity, effect)).then(suppressor)
+}
+
+/// Check whether the entity is standing in an antimagic field, suppressing its
+/// magical effects when it enters one and restoring them when it leaves.
+pub fn update(game_state: &mut GameState, entity: Entity) {
+    if game_state
+        .world
+        .get::<&Vec<EffectInstance>>(entity)
+        .is_err()
+    {
+        return;
+    }
+    let inside_now =
+        systems::geometry::get_foot_position(&game_state.world, entity).is_some_and(|position| {
+            systems::surfaces::surfaces_at(game_state, &position)
+                .iter()
+                .any(|surface| surface.kind == SurfaceKind::Antimagic)
+        });
+    let inside_before = in_antimagic_field(&game_state.world, entity);
+
+    if inside_now && !inside_before {
+        debug!("{:?} enters an antimagic field", entity);
+        systems::effects::add_permanent_effect(
+            &mut game_state.world,
+            entity,
+            antimagic_field(),
+            &suppressor(),
+            None,
+        );
+        let magical: Vec<EffectId> = systems::effects::effects(&game_state.world, entity)
+            .iter()
+            .filter(|effect| is_magical(&game_state.world, entity, effect))
+            .map(|effect| effect.effect_id.clone())
+            .collect();
+        systems::effects::suppress_effects(
+            &mut game_state.world,
+            entity,
+            &suppressor(),
+            |effect| magical.contains(&effect.effect_id),
+        );
+    } else if !inside_now && inside_before {
+        debug!("{:?} leaves an antimagic field", entity);
+        systems::effects::remove_effect(&mut game_state.world, entity, &antimagic_field());
+        systems::effects::unsuppress_effects(&mut game_state.world, entity, &suppressor());
+    }
+}
+
+/// Update every entity, e.g. after an antimagic field has been created or has
+/// ended
+pub fn update_all(game_state: &mut GameState) {
+    let entities: Vec<Entity> = game_state
+        .world
+        .query::<&Vec<EffectInstance>>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        update(game_state, entity);
+    }
+}
+
+/// Inside an antimagic field a magic weapon works like a mundane one
+pub fn suppress_attack_roll(attack_roll: &mut AttackRoll) {
+    attack_roll
+        .d20_check
+        .remove_modifier(&ModifierSource::Custom("Enchantment".to_string()));
+}
+
+pub fn suppress_damage_roll(damage_roll: &mut DamageRoll) {
+    damage_roll
+        .primary
+        .dice_roll
+        .modifiers
+        .remove_modifier(&ModifierSource::Custom("Enchantment".to_string()));
+    damage_roll.remove_tag(DamageTag::Magical);
+}
+
+/// End the spells affecting the entity, e.g. with Dispel Magic. Unlike an
+/// antimagic field, this doesn't affect magic items. Returns the effects that
+/// were removed.
+pub fn dispel(world: &mut World, entity: Entity) -> Vec<EffectId> {
+    let dispelled: Vec<EffectId> = systems::effects::effects(world, entity)
+        .iter()
+        .filter(|effect| {
+            matches!(effect.source, ModifierSource::Action(_)) && is_magical(world, entity, effect)
+        })
+        .map(|effect| effect.effect_id.clone())
+        .collect();
+    debug!("Dispelling {:?} from {:?}", dispelled, entity);
+    systems::effects::remove_effects(world, entity, &dispelled);
+    dispelled
+}
@@ -2,11 +2,8 @@ use hecs::{Entity, World};
 
 use crate::{
     components::{
-        id::BackgroundId,
-        level_up::LevelUpPrompt,
-        modifier::ModifierSource,
-        proficiency::{Proficiency, ProficiencyLevel},
-        skill::SkillSet,
+        id::BackgroundId, level_up::LevelUpPrompt, modifier::ModifierSource,
+        proficiency::ProficiencyLevel, skill::SkillSet,
     },
     registry::registry::BackgroundsRegistry,
     systems,
@@ -41,12 +38,10 @@ pub fn set_background(
 
     let mut skill_set = systems::helpers::get_component_mut::<SkillSet>(world, entity);
     for skill in background.skill_proficiencies {
-        skill_set.set_proficiency(
+        skill_set.add_proficiency(
             &skill,
-            Proficiency::new(
-                ProficiencyLevel::Proficient,
-                ModifierSource::Background(background_id.clone()),
-            ),
+            ProficiencyLevel::Proficient,
+            ModifierSource::Background(background_id.clone()),
         );
     }
 
@@ -0,0 +1,196 @@
+use hecs::Entity;
+use rerecast::Config;
+use tracing::{debug, info, warn};
+
+use crate::{
+    components::{
+        campaign::{Adventure, CampaignState, Quest, QuestStatus},
+        id::AdventureId,
+        items::inventory::{Inventory, ItemInstance},
+    },
+    engine::game_state::GameState,
+    registry::registry::AdventuresRegistry,
+    systems::{
+        self,
+        mapgen::{self, MapGenConfig, MapGenError},
+        preset::{self, PresetError},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CampaignError {
+    UnknownAdventure(AdventureId),
+    UnknownMap(String),
+    Preset(PresetError),
+    MapGen(MapGenError),
+}
+
+/// Start playing the adventure from the beginning and spawn its NPCs. Returns
+/// the spawned NPCs.
+pub fn start(
+    game_state: &mut GameState,
+    adventure_id: &AdventureId,
+) -> Result<Vec<Entity>, CampaignError> {
+    let Some(adventure) = AdventuresRegistry::get(adventure_id) else {
+        return Err(CampaignError::UnknownAdventure(adventure_id.clone()));
+    };
+
+    let npcs = preset::spawn_creatures(game_state, &adventure.name, &adventure.npcs)
+        .map_err(CampaignError::Preset)?;
+    let mut campaign = CampaignState::new(adventure_id.clone());
+    campaign.location = adventure
+        .world_map
+        .start()
+        .map(|location| location.id.clone());
+    // Quests without requirements start right away
+    for quest in &adventure.quests {
+        if campaign.quest_status(quest) == QuestStatus::Active {
+            start_quest(&mut campaign, quest);
+        }
+    }
+    game_state.campaign = Some(campaign);
+
+    info!("Started adventure '{}'", adventure.name);
+    Ok(npcs)
+}
+
+/// The adventure currently being played
+pub fn adventure(game_state: &GameState) -> Option<&'static Adventure> {
+    game_state
+        .campaign
+        .as_ref()
+        .and_then(|campaign| AdventuresRegistry::get(&campaign.adventure))
+}
+
+pub fn has_flag(game_state: &GameState, flag: &str) -> bool {
+    game_state
+        .campaign
+        .as_ref()
+        .is_some_and(|campaign| campaign.has_flag(flag))
+}
+
+/// Set a progress flag. Quests started by it queue up their map to be loaded,
+/// and quests completed by it hand out their rewards to the party. Returns the
+/// completed quests.
+pub fn set_flag(game_state: &mut GameState, flag: &str) -> Vec<&'static Quest> {
+    let Some(adventure) = adventure(game_state) else {
+        warn!("Cannot set flag '{}': no adventure is being played", flag);
+        return Vec::new();
+    };
+    let campaign = game_state.campaign.as_mut().unwrap();
+
+    let before = adventure
+        .quests
+        .iter()
+        .map(|quest| campaign.quest_status(quest))
+        .collect::<Vec<_>>();
+    if !campaign.flags.insert(flag.to_string()) {
+        return Vec::new();
+    }
+    debug!("Set flag '{}' in adventure '{}'", flag, adventure.name);
+
+    let mut completed = Vec::new();
+    for (quest, before) in adventure.quests.iter().zip(before) {
+        match (before, campaign.quest_status(quest)) {
+            (QuestStatus::Locked, QuestStatus::Active) => start_quest(campaign, quest),
+            (QuestStatus::Locked | QuestStatus::Active, QuestStatus::Completed) => {
+                info!("Completed quest '{}'", quest.name);
+                completed.push(quest);
+            }
+            _ => {}
+        }
+    }
+
+    for quest in &completed {
+        grant_rewards(game_state, quest);
+    }
+    completed
+}
+
+fn start_quest(campaign: &mut CampaignState, quest: &Quest) {
+    info!("Started quest '{}'", quest.name);
+    if let Some(map) = &quest.map {
+        campaign.pending_map = Some(map.clone());
+    }
+}
+
+/// Roll the rewards of the quest and share them out between the living
+/// player-controlled characters, one item each in turn
+fn grant_rewards(game_state: &mut GameState, quest: &Quest) {
+    let mut party = game_state
+        .world
+        .query::<&Inventory>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| {
+            systems::ai::is_player_controlled(&game_state.world, *entity)
+                && systems::health::is_alive(&game_state.world, *entity)
+        })
+        .collect::<Vec<_>>();
+    if party.is_empty() {
+        warn!("No one is there to receive the rewards of '{}'", quest.name);
+        return;
+    }
+    party.sort();
+
+    for (item, entity) in quest_rewards(quest).into_iter().zip(party.iter().cycle()) {
+        debug!(
+            "{:?} receives {} for '{}'",
+            entity,
+            item.item().name,
+            quest.name
+        );
+        systems::inventory::add_item(&mut game_state.world, *entity, item);
+    }
+}
+
+/// Load the map of the quest which most recently started, if it hasn't been
+/// loaded yet. The map's geometry replaces the current one, and the doors,
+/// traps and light sources on it are spawned. Returns the config the map was
+/// generated from along with the spawned entities.
+pub fn load_pending_map(
+    game_state: &mut GameState,
+    navmesh_config: &Config,
+) -> Option<Result<(MapGenConfig, Vec<Entity>), CampaignError>> {
+    let name = game_state.campaign.as_mut()?.pending_map.take()?;
+    Some(load_map(game_state, &name, navmesh_config))
+}
+
+pub fn load_map(
+    game_state: &mut GameState,
+    name: &str,
+    navmesh_config: &Config,
+) -> Result<(MapGenConfig, Vec<Entity>), CampaignError> {
+    let Some(adventure_map) = adventure(game_state).and_then(|adventure| adventure.map(name))
+    else {
+        return Err(CampaignError::UnknownMap(name.to_string()));
+    };
+
+    let map = mapgen::generate(&adventure_map.config).map_err(CampaignError::MapGen)?;
+    game_state.geometry = map.to_geometry(navmesh_config);
+    let entities = mapgen::spawn_features(game_state, &map);
+    info!("Loaded map '{}'", name);
+    Ok((adventure_map.config.clone(), entities))
+}
+
+pub fn clear_flag(game_state: &mut GameState, flag: &str) {
+    if let Some(campaign) = &mut game_state.campaign {
+        campaign.flags.remove(flag);
+    }
+}
+
+/// Every quest of the adventure along with how far the party has come
+pub fn quests(game_state: &GameState) -> Vec<(&'static Quest, QuestStatus)> {
+    let (Some(adventure), Some(campaign)) = (adventure(game_state), &game_state.campaign) else {
+        return Vec::new();
+    };
+    adventure
+        .quests
+        .iter()
+        .map(|quest| (quest, campaign.quest_status(quest)))
+        .collect()
+}
+
+pub fn quest_rewards(quest: &Quest) -> Vec<ItemInstance> {
+    systems::loot::roll(&quest.rewards)
+}
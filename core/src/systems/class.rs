@@ -1,15 +1,20 @@
+use std::fmt::Display;
+
 use crate::{
     components::{
-        class::{ClassAndSubclass, ClassBase},
+        ability::AbilityScoreMap,
+        class::{ClassAndSubclass, ClassBase, MulticlassPrerequisite},
         id::{ClassId, SubclassId},
         items::equipment::{armor::ArmorTrainingSet, weapon::WeaponProficiencyMap},
         proficiency::ProficiencyLevel,
         resource::ResourceMap,
         saving_throw::SavingThrowKind,
+        spells::spellbook::Spellbook,
     },
     registry::registry::ClassesRegistry,
 };
 use hecs::{Entity, World};
+use tracing::warn;
 
 use crate::{
     components::{
@@ -35,6 +40,65 @@ impl ClassIdentifier {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum MulticlassError {
+    RegistryMissing(ClassId),
+    /// Either the class being multiclassed into, or one of the classes the
+    /// character already has, has a prerequisite which isn't met
+    PrerequisiteNotMet {
+        class_id: ClassId,
+        prerequisite: MulticlassPrerequisite,
+    },
+}
+
+impl Display for MulticlassError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MulticlassError::RegistryMissing(class_id) => {
+                write!(f, "Class {} not found in the registry", class_id)
+            }
+            MulticlassError::PrerequisiteNotMet {
+                class_id,
+                prerequisite,
+            } => write!(f, "{} requires {}", class_id, prerequisite),
+        }
+    }
+}
+
+/// Check if the entity can take a level in the class. Taking the first level,
+/// or another level in a class the entity already has, is always allowed.
+/// Otherwise the multiclass prerequisites of both the new class and all the
+/// existing classes have to be met.
+pub fn can_multiclass(
+    world: &World,
+    entity: Entity,
+    class_id: &ClassId,
+) -> Result<(), MulticlassError> {
+    let levels = systems::helpers::get_component::<CharacterLevels>(world, entity);
+    if levels.total_level() == 0 || levels.class_level(class_id).is_some() {
+        return Ok(());
+    }
+
+    let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
+    for class_id in levels.all_classes().keys().chain([class_id]) {
+        let Some(class) = ClassesRegistry::get(class_id) else {
+            return Err(MulticlassError::RegistryMissing(class_id.clone()));
+        };
+        if let Some(prerequisite) = class
+            .multiclass_prerequisites
+            .iter()
+            .find(|prerequisite| !prerequisite.is_met(&ability_scores))
+        {
+            return Err(MulticlassError::PrerequisiteNotMet {
+                class_id: class_id.clone(),
+                prerequisite: prerequisite.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
 pub fn class_level(world: &World, entity: Entity, class_id: &ClassId) -> u8 {
     if let Ok(character_levels) = world.get::<&CharacterLevels>(entity) {
         if let Some(class_level) = character_levels.class_level(class_id) {
@@ -68,12 +132,10 @@ pub fn increment_class_level(
 
     // TODO: Do we need to do this every time?
     for ability in class.saving_throw_proficiencies.iter() {
-        systems::helpers::get_component_mut::<SavingThrowSet>(world, entity).set_proficiency(
+        systems::helpers::get_component_mut::<SavingThrowSet>(world, entity).add_proficiency(
             &SavingThrowKind::Ability(*ability),
-            Proficiency::new(
-                ProficiencyLevel::Proficient,
-                ModifierSource::ClassFeature(class_id.clone()),
-            ),
+            ProficiencyLevel::Proficient,
+            ModifierSource::ClassFeature(class_id.clone()),
         );
     }
 
@@ -98,17 +160,32 @@ pub fn increment_class_level(
             new_level,
         ));
     }
+    systems::resources::update_pools(world, entity);
 
+    let class_and_subclass = ClassAndSubclass {
+        class: class_id.clone(),
+        subclass: subclass.as_ref().map(|s| s.id.clone()),
+    };
     prompts.extend(systems::spells::update_spellbook(
         world,
         entity,
-        ClassAndSubclass {
-            class: class_id.clone(),
-            subclass: subclass.as_ref().map(|s| s.id.clone()),
-        },
+        class_and_subclass.clone(),
         new_level,
     ));
 
+    // The spellbook has to be updated first, since that's where the class
+    // spellcasting state is created
+    add_always_prepared_spells(world, entity, &class_and_subclass, &class.base, new_level);
+    if let Some(subclass) = subclass {
+        add_always_prepared_spells(
+            world,
+            entity,
+            &class_and_subclass,
+            subclass.base(),
+            new_level,
+        );
+    }
+
     // Feats need special handling since they can have prerequisites and
     // can (or can't) be repeatable.
     if class.feat_levels.contains(&new_level) {
@@ -144,13 +221,47 @@ pub fn set_subclass(
         (subclass, level)
     };
 
-    apply_class_base(
+    let prompts = apply_class_base(
         world,
         entity,
         subclass.base(),
         ClassIdentifier::Subclass(subclass_id.clone()),
         level,
-    )
+    );
+    add_always_prepared_spells(
+        world,
+        entity,
+        &ClassAndSubclass {
+            class: class_id.clone(),
+            subclass: Some(subclass_id.clone()),
+        },
+        subclass.base(),
+        level,
+    );
+
+    prompts
+}
+
+fn add_always_prepared_spells(
+    world: &mut World,
+    entity: Entity,
+    class_and_subclass: &ClassAndSubclass,
+    class_base: &ClassBase,
+    level: u8,
+) {
+    let Some(spells_for_level) = class_base.spells_by_level.get(&level) else {
+        return;
+    };
+
+    let mut spellbook = systems::helpers::get_component_mut::<Spellbook>(world, entity);
+    for spell_id in spells_for_level {
+        if let Err(error) = spellbook.add_always_prepared(class_and_subclass, spell_id) {
+            warn!(
+                "Failed to add always prepared spell {} for {:?}: {:?}",
+                spell_id, class_and_subclass, error
+            );
+        }
+    }
 }
 
 fn apply_class_base(
@@ -212,12 +323,19 @@ fn apply_class_base(
         }
     }
 
-    // Return any additional prompts that should be presented to the player
+    // Return any additional prompts that should be presented to the player.
+    // Options the entity already has, e.g. maneuvers picked at an earlier
+    // level, are shown as disabled
     let mut new_prompts = class_base
         .prompts_by_level
         .get(&level)
         .cloned()
         .unwrap_or_default();
+    for prompt in new_prompts.iter_mut() {
+        if let LevelUpPrompt::Choice(spec) = prompt {
+            spec.disable_known_options(world, entity);
+        }
+    }
 
     new_prompts
 }
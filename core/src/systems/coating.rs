@@ -0,0 +1,184 @@
+use hecs::{Entity, World};
+use tracing::debug;
+
+use crate::{
+    components::{
+        actions::action::ActionContext,
+        damage::DamageRollResult,
+        id::ItemId,
+        items::{
+            coating::AppliedCoating,
+            equipment::{loadout::Loadout, slots::EquipmentSlot},
+            inventory::{Inventory, ItemInstance},
+        },
+        modifier::ModifierSource,
+        time::{TimeStep, TurnBoundary},
+    },
+    systems::{self, d20::D20CheckDCKind},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoatingError {
+    /// The item is either not in the inventory or not something that can be
+    /// applied to a weapon
+    NotACoating(ItemId),
+    NoWeaponInSlot(EquipmentSlot),
+}
+
+/// Apply a poison or oil from the inventory of the entity to the weapon in the
+/// slot, using up the item. Any previous coating on the weapon is replaced.
+pub fn apply(
+    world: &mut World,
+    entity: Entity,
+    item_id: &ItemId,
+    slot: &EquipmentSlot,
+) -> Result<(), CoatingError> {
+    if !systems::loadout::loadout(world, entity).has_weapon_in_hand(slot) {
+        return Err(CoatingError::NoWeaponInSlot(*slot));
+    }
+
+    let index = systems::helpers::get_component::<Inventory>(world, entity)
+        .items()
+        .iter()
+        .position(
+            |item| matches!(item, ItemInstance::Coating(coating) if coating.item.id == *item_id),
+        )
+        .ok_or_else(|| CoatingError::NotACoating(item_id.clone()))?;
+    let Some(ItemInstance::Coating(coating_item)) =
+        systems::inventory::remove_item(world, entity, index)
+    else {
+        unreachable!("Item at index {} is a coating", index);
+    };
+
+    let mut loadout = systems::loadout::loadout_mut(world, entity);
+    let weapon = loadout.weapon_in_hand_mut(slot).unwrap();
+    debug!(
+        "{:?} coats {} with {}",
+        entity,
+        weapon.item().name,
+        coating_item.item.name
+    );
+    weapon.coat(AppliedCoating::new(&coating_item));
+    Ok(())
+}
+
+/// The coating of the weapon in the slot, if any
+pub fn coating(world: &World, entity: Entity, slot: &EquipmentSlot) -> Option<AppliedCoating> {
+    systems::loadout::loadout(world, entity)
+        .weapon_in_hand(slot)
+        .and_then(|weapon| weapon.coating().cloned())
+}
+
+/// Resolve the coating of the weapon used for an attack that hit. The target can
+/// shrug off the coating with a saving throw if the coating allows one, and the
+/// coating wears off once it has been used up.
+pub fn on_hit(
+    world: &mut World,
+    entity: Entity,
+    target: Entity,
+    slot: &EquipmentSlot,
+    context: &ActionContext,
+    damage_roll: Option<&mut DamageRollResult>,
+) {
+    let Some(coating) = spend_hit(world, entity, slot) else {
+        return;
+    };
+
+    let saved = coating
+        .coating
+        .saving_throw_dc(&coating.source)
+        .is_some_and(|dc| {
+            let dc = D20CheckDCKind::SavingThrow(dc);
+            systems::d20::check_no_event(world, target, &dc).is_success(&dc)
+        });
+    let half_damage = coating
+        .coating
+        .save
+        .as_ref()
+        .is_some_and(|save| save.half_damage);
+    if saved {
+        debug!("{:?} resists the {} coating", target, coating.name);
+        if !half_damage {
+            return;
+        }
+    }
+
+    if let Some(damage_roll) = damage_roll {
+        for mut component in coating.coating.roll_damage() {
+            if saved {
+                let total = component.result.subtotal;
+                component.result.modifiers.add_modifier(
+                    ModifierSource::Custom("Saving Throw".to_string()),
+                    -(total as f32 / 2.0).ceil() as i32,
+                );
+                component.result.recalculate_total();
+            }
+            damage_roll.add_component(component);
+        }
+    }
+
+    if saved {
+        return;
+    }
+    for effect in &coating.coating.effects {
+        systems::effects::add_effect_template(
+            world,
+            entity,
+            target,
+            ModifierSource::Item(coating.source.clone()),
+            effect,
+            Some(context),
+        );
+    }
+}
+
+/// Use up a hit of the coating on the weapon in the slot, returning the coating
+/// as it was before the hit. The coating is removed once it runs out of hits.
+fn spend_hit(world: &mut World, entity: Entity, slot: &EquipmentSlot) -> Option<AppliedCoating> {
+    let mut loadout = systems::loadout::loadout_mut(world, entity);
+    let weapon = loadout.weapon_in_hand_mut(slot)?;
+    let coating = weapon.coating_mut()?;
+    let before = coating.clone();
+    coating.spend_hit();
+    if coating.is_expired() {
+        debug!(
+            "The {} coating on {} wears off",
+            before.name,
+            weapon.item().name
+        );
+        weapon.remove_coating();
+    }
+    Some(before)
+}
+
+/// Coatings dry up over time. In combat they're counted down at the end of the
+/// turns of the entity wielding the weapon.
+pub fn advance_time(world: &mut World, entity: Entity, time_step: &TimeStep) {
+    match time_step {
+        TimeStep::TurnBoundary {
+            entity: turn_entity,
+            boundary: TurnBoundary::End,
+        } if *turn_entity == entity => {}
+        TimeStep::RealTime { .. } => {}
+        _ => return,
+    }
+
+    let Ok(mut loadout) = world.get::<&mut Loadout>(entity) else {
+        return;
+    };
+    for (_, weapon) in loadout.weapons_mut() {
+        let Some(coating) = weapon.coating_mut() else {
+            continue;
+        };
+        coating.advance_time(time_step);
+        if coating.is_expired()
+            && let Some(coating) = weapon.remove_coating()
+        {
+            debug!(
+                "The {} coating on {} dries up",
+                coating.name,
+                weapon.item().name
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use hecs::Entity;
+use tracing::debug;
+
+use crate::{
+    components::actions::action::{ActionKind, ActionKindResult, CompositeStep, DamageRollPolicy},
+    engine::{
+        event::{ActionData, Event, EventKind},
+        game_state::GameState,
+    },
+};
+
+/// A composite action which is in the middle of performing its steps. Since the
+/// steps of the action can be paused by reactions, the results of each step are
+/// collected as they come in, and the next step is only performed once every
+/// target of the current step has a result.
+#[derive(Debug, Clone)]
+pub struct CompositeResolution {
+    action: ActionData,
+    steps: Vec<CompositeStep>,
+    damage_roll: DamageRollPolicy,
+    /// The results of the steps performed so far for each target
+    results: Vec<(Entity, Vec<ActionKindResult>)>,
+    next_step: usize,
+    /// The targets of the current step which are still waiting for a result
+    waiting_for: Vec<Entity>,
+    /// Whether the current step is still being performed, in which case moving
+    /// on to the next step is left to whoever is performing it
+    performing: bool,
+}
+
+/// Perform the steps of a composite action in order. Once all steps are done
+/// the results are combined into a single `ActionPerformed` event, where each
+/// target gets an `ActionKindResult::Composite` with the results of the steps
+/// which were performed on it.
+pub fn perform(
+    game_state: &mut GameState,
+    steps: &[CompositeStep],
+    action_data: &ActionData,
+    targets: &[Entity],
+    damage_roll: DamageRollPolicy,
+) {
+    game_state.composite_actions.push(CompositeResolution {
+        action: action_data.clone(),
+        steps: steps.to_vec(),
+        damage_roll,
+        results: targets.iter().map(|target| (*target, Vec::new())).collect(),
+        next_step: 0,
+        waiting_for: Vec::new(),
+        performing: false,
+    });
+    let index = game_state.composite_actions.len() - 1;
+    advance(game_state, index);
+}
+
+fn advance(game_state: &mut GameState, index: usize) {
+    loop {
+        let resolution = &mut game_state.composite_actions[index];
+        let Some(step) = resolution.steps.get(resolution.next_step).cloned() else {
+            let resolution = game_state.composite_actions.remove(index);
+            finish(game_state, resolution);
+            return;
+        };
+        resolution.next_step += 1;
+
+        // Scripted reactions are performed through `perform_reaction`
+        if matches!(step.kind, ActionKind::Reaction { .. }) {
+            continue;
+        }
+
+        let targets: Vec<_> = resolution
+            .results
+            .iter()
+            .filter(|(_, results)| step.only_if.is_met(results.last()))
+            .map(|(target, _)| *target)
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
+
+        debug!(
+            "Performing step {} of {:?} on {:?}",
+            resolution.next_step, resolution.action.action_id, targets
+        );
+        resolution.waiting_for = targets.clone();
+        resolution.performing = true;
+        let action_data = resolution.action.clone();
+        let damage_roll = resolution.damage_roll;
+        step.kind
+            .perform(game_state, &action_data, &targets, damage_roll);
+
+        let resolution = &mut game_state.composite_actions[index];
+        resolution.performing = false;
+        if !resolution.waiting_for.is_empty() {
+            // The step is waiting for a reaction, so the next step is performed
+            // once the results come in
+            return;
+        }
+    }
+}
+
+fn finish(game_state: &mut GameState, resolution: CompositeResolution) {
+    if resolution.results.is_empty() {
+        return;
+    }
+    let results = resolution
+        .results
+        .into_iter()
+        .map(|(target, actions)| (target, ActionKindResult::Composite { actions }))
+        .collect();
+    let _ = game_state.process_event(Event::action_performed_event(
+        game_state,
+        &resolution.action,
+        results,
+    ));
+}
+
+/// The steps of a composite action report their results like any other action,
+/// but instead of being logged on their own they're collected into the result
+/// of the composite action. Returns whether the event was absorbed this way.
+pub(crate) fn absorb_result(game_state: &mut GameState, event: &Event) -> bool {
+    let EventKind::ActionPerformed { action, results } = &event.kind else {
+        return false;
+    };
+    // Nested composite actions share the action data of the outermost one, and
+    // the innermost is the one currently performing a step
+    let Some(index) = game_state
+        .composite_actions
+        .iter()
+        .rposition(|resolution| resolution.action.instance_id == action.instance_id)
+    else {
+        return false;
+    };
+
+    let resolution = &mut game_state.composite_actions[index];
+    let mut absorbed = false;
+    for result in results {
+        let Some(target) = result.target.entity() else {
+            continue;
+        };
+        let Some(position) = resolution
+            .waiting_for
+            .iter()
+            .position(|waiting| *waiting == target)
+        else {
+            continue;
+        };
+        resolution.waiting_for.remove(position);
+        if let Some((_, target_results)) = resolution
+            .results
+            .iter_mut()
+            .find(|(entity, _)| *entity == target)
+        {
+            target_results.push(result.kind.clone());
+        }
+        absorbed = true;
+    }
+
+    if absorbed && resolution.waiting_for.is_empty() && !resolution.performing {
+        advance(game_state, index);
+    }
+    absorbed
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use hecs::{Entity, World};
+use uom::si::{f32::Length, length::meter};
+
+use crate::{
+    components::{
+        actions::action::ActionKind,
+        d20::{AdvantageType, D20Check},
+        effects::effect::EffectTag,
+        faction::Attitude,
+        fear::FearSight,
+        id::EffectId,
+        modifier::ModifierSource,
+    },
+    engine::{game_state::GameState, geometry::WorldPath},
+    systems,
+};
+
+/// How much closer a frightened creature can get before it counts as moving
+/// towards the source of its fear, so walking around it isn't ruled out
+const FEAR_TOLERANCE: f32 = 0.05;
+
+pub fn exhaustion() -> EffectId {
+    EffectId::new("nat20_core", "effect.condition.exhaustion")
+}
+
+/// Finishing a long rest removes one level of exhaustion
+pub fn recover_from_exhaustion(world: &mut World, entity: Entity) {
+    systems::effects::remove_stack(world, entity, &exhaustion());
+}
+
+/// The creatures that applied an effect with the tag to the entity, along with
+/// the effect itself, e.g. whoever the entity is charmed by
+fn sources_of(world: &World, entity: Entity, tag: EffectTag) -> Vec<(EffectId, Entity)> {
+    systems::effects::active_effects(world, entity)
+        .iter()
+        .filter(|effect| effect.effect().has_tag(&tag))
+        .filter_map(|effect| Some((effect.effect_id.clone(), effect.applier?)))
+        .filter(|(_, applier)| *applier != entity)
+        .collect()
+}
+
+/// Whether the entity has an active effect which keeps it from taking any
+/// actions, e.g. Stunned
+pub fn is_incapacitated(world: &World, entity: Entity) -> bool {
+    systems::effects::active_effects(world, entity)
+        .iter()
+        .any(|effect| effect.effect().has_tag(&EffectTag::Incapacitated))
+}
+
+/// The creatures that have charmed the entity
+pub fn charmed_by(world: &World, entity: Entity) -> Vec<Entity> {
+    sources_of(world, entity, EffectTag::Charmed)
+        .into_iter()
+        .map(|(_, charmer)| charmer)
+        .collect()
+}
+
+pub fn is_charmed_by(world: &World, entity: Entity, other: Entity) -> bool {
+    charmed_by(world, entity).contains(&other)
+}
+
+/// The creatures the entity is frightened of, as long as they're still alive
+pub fn frightened_of(world: &World, entity: Entity) -> Vec<Entity> {
+    sources_of(world, entity, EffectTag::Frightened)
+        .into_iter()
+        .map(|(_, source)| source)
+        .filter(|source| systems::health::is_alive(world, *source))
+        .collect()
+}
+
+/// Charmed creatures can't attack their charmer or target it with anything
+/// harmful. Returns the charmer if the action isn't allowed against the target.
+pub fn prevents_harm(
+    world: &World,
+    actor: Entity,
+    target: Entity,
+    action_kind: &ActionKind,
+) -> Option<Entity> {
+    let harmful = action_kind.is_attack()
+        || systems::ai::recommeneded_target_attitude(world, actor, action_kind)
+            == Attitude::Hostile;
+    (harmful && is_charmed_by(world, actor, target)).then_some(target)
+}
+
+/// Work out which of the creatures the entity is frightened of are out of its
+/// line of sight, so [`apply_fear`] can leave them out. Called whenever the
+/// entity is about to act or roll.
+pub fn update_fear_sight(game_state: &mut GameState, entity: Entity) {
+    let out_of_sight: HashSet<Entity> = frightened_of(&game_state.world, entity)
+        .into_iter()
+        .filter(|source| {
+            !systems::geometry::line_of_sight_entity_entity(
+                &game_state.world,
+                &game_state.geometry,
+                entity,
+                *source,
+            )
+            .has_line_of_sight
+        })
+        .collect();
+
+    if out_of_sight.is_empty() {
+        let _ = game_state.world.remove_one::<FearSight>(entity);
+    } else {
+        let _ = game_state
+            .world
+            .insert_one(entity, FearSight { out_of_sight });
+    }
+}
+
+/// Frightened creatures have Disadvantage on attack rolls and ability checks
+/// while the source of their fear is within sight, i.e. it isn't invisible and
+/// nothing blocks the line of sight to it
+pub fn apply_fear(world: &World, entity: Entity, d20_check: &mut D20Check) {
+    for (effect_id, source) in sources_of(world, entity, EffectTag::Frightened) {
+        let out_of_sight = world
+            .get::<&FearSight>(entity)
+            .is_ok_and(|sight| sight.out_of_sight.contains(&source));
+        if systems::health::is_alive(world, source)
+            && systems::visibility::can_see(world, entity, source)
+            && !out_of_sight
+        {
+            d20_check.advantage_tracker_mut().add(
+                AdvantageType::Disadvantage,
+                ModifierSource::Effect(effect_id),
+            );
+        }
+    }
+}
+
+/// Frightened creatures can't willingly move closer to the source of their
+/// fear than where they started. Backing off and coming partway back is fine.
+/// Returns the source of the fear and how far along the path the entity can
+/// move before it would get closer, if the path takes it closer at all.
+pub fn fear_limit(world: &World, entity: Entity, path: &WorldPath) -> Option<(Entity, Length)> {
+    let sources = frightened_of(world, entity);
+    if sources.is_empty() {
+        return None;
+    }
+    let start = *path.start()?;
+
+    // How far away from each source the entity started out
+    let starting: Vec<_> = sources
+        .iter()
+        .filter_map(|source| {
+            let position = systems::geometry::get_foot_position(world, *source)?;
+            Some((*source, position, (position - start).magnitude()))
+        })
+        .collect();
+
+    let mut travelled = 0.0;
+    for (sample, sample_travelled) in path.samples() {
+        for (source, position, distance) in &starting {
+            if (*position - sample).magnitude() < *distance - FEAR_TOLERANCE {
+                return Some((*source, Length::new::<meter>(travelled)));
+            }
+        }
+        travelled = sample_travelled;
+    }
+    None
+}
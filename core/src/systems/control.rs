@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use hecs::{Entity, World};
+use tracing::debug;
+use uom::si::length::meter;
+
+use crate::{
+    components::{
+        ability::{Ability, AbilityScoreMap},
+        actions::action::ActionContext,
+        control::{ControlTemplate, Controlled},
+        id::{ActionId, EffectId},
+        modifier::{Modifiable, ModifierSet, ModifierSource},
+        proficiency::ProficiencyLevel,
+        saving_throw::{BASE_SAVE_DC, SavingThrowDC, SavingThrowKind},
+        speed::Speed,
+    },
+    engine::{
+        event::{CallbackResult, EventCallback, EventKind},
+        game_state::GameState,
+    },
+    systems::{self, d20::D20CheckDCKind},
+};
+
+/// Hand control of the entity to the applier of an effect with a
+/// [`ControlTemplate`]. Called when the effect is applied.
+pub fn take_control(
+    world: &mut World,
+    entity: Entity,
+    controller: Entity,
+    effect_id: &EffectId,
+    template: &ControlTemplate,
+    context: Option<&ActionContext>,
+) {
+    if controller == entity {
+        return;
+    }
+
+    // Breaking free is a save against the DC of whatever took control
+    let save_on_damage = template.save_on_damage.map(|ability| SavingThrowDC {
+        key: SavingThrowKind::Ability(ability),
+        dc: match context.map(ActionContext::base) {
+            Some(ActionContext::Spell { source, .. }) => {
+                systems::spells::spell_dc(world, controller, source)
+            }
+            _ => charisma_dc(world, controller),
+        },
+    });
+
+    debug!(
+        "{:?} takes control of {:?} through {:?}",
+        controller, entity, effect_id
+    );
+    let _ = world.insert_one(
+        entity,
+        Controlled {
+            controller,
+            effect: effect_id.clone(),
+            actions: template.actions.clone(),
+            immobile: template.immobile,
+            flee: template.flee,
+            save_on_damage,
+        },
+    );
+}
+
+/// DC of breaking free from control which doesn't come from a spell, e.g. a
+/// monster's charm, which is based on the Charisma of the controller
+fn charisma_dc(world: &World, controller: Entity) -> ModifierSet {
+    let mut dc = ModifierSet::from(ModifierSource::Base, BASE_SAVE_DC);
+    dc.add_modifier(
+        ModifierSource::Ability(Ability::Charisma),
+        systems::helpers::get_component::<AbilityScoreMap>(world, controller)
+            .ability_modifier(&Ability::Charisma)
+            .total(),
+    );
+    if let Some(level) = systems::helpers::level(world, controller) {
+        dc.add_modifier(
+            ModifierSource::Proficiency(ProficiencyLevel::Proficient),
+            level.proficiency_bonus() as i32,
+        );
+    }
+    dc
+}
+
+/// The control over the entity, as long as the effect granting it is active
+pub fn controlled(world: &World, entity: Entity) -> Option<Controlled> {
+    let controlled = world.get::<&Controlled>(entity).ok()?;
+    systems::effects::is_effect_active(world, entity, &controlled.effect)
+        .then(|| controlled.clone())
+}
+
+/// The creature currently deciding the turns of the entity, if it's not
+/// deciding them itself
+pub fn controller_of(world: &World, entity: Entity) -> Option<Entity> {
+    controlled(world, entity).map(|controlled| controlled.controller)
+}
+
+/// Returns the controller if it doesn't allow the entity to take the action
+pub fn restricts_action(world: &World, entity: Entity, action_id: &ActionId) -> Option<Entity> {
+    controlled(world, entity)
+        .filter(|controlled| !controlled.allows(action_id))
+        .map(|controlled| controlled.controller)
+}
+
+/// Returns the controller if it doesn't allow the entity to move
+pub fn holds_in_place(world: &World, entity: Entity) -> Option<Entity> {
+    controlled(world, entity)
+        .filter(|controlled| controlled.immobile)
+        .map(|controlled| controlled.controller)
+}
+
+/// End the control over the entity, along with the effect granting it
+pub fn release(world: &mut World, entity: Entity) {
+    let Ok(controlled) = world.remove_one::<Controlled>(entity) else {
+        return;
+    };
+    debug!(
+        "{:?} is no longer controlled by {:?}",
+        entity, controlled.controller
+    );
+    if systems::effects::has_effect(world, entity, &controlled.effect) {
+        systems::effects::remove_effect(world, entity, &controlled.effect);
+    }
+}
+
+/// Control ends with the effect granting it, however the effect ends
+pub fn on_effect_removed(world: &mut World, entity: Entity, effect_id: &EffectId) {
+    if world
+        .get::<&Controlled>(entity)
+        .is_ok_and(|controlled| controlled.effect == *effect_id)
+    {
+        let _ = world.remove_one::<Controlled>(entity);
+    }
+}
+
+/// Creatures commanded to flee spend their turn moving as far away from the
+/// controller as their speed allows
+pub fn on_turn_start(game_state: &mut GameState, entity: Entity) {
+    let Some(controlled) = controlled(&game_state.world, entity).filter(|c| c.flee) else {
+        return;
+    };
+    let (Some(position), Some(controller_position)) = (
+        systems::geometry::get_foot_position(&game_state.world, entity),
+        systems::geometry::get_foot_position(&game_state.world, controlled.controller),
+    ) else {
+        return;
+    };
+
+    let mut away = position - controller_position;
+    away.y = 0.0;
+    if away.magnitude() < f32::EPSILON {
+        return;
+    }
+    let distance = systems::helpers::get_component::<Speed>(&game_state.world, entity)
+        .remaining_movement()
+        .get::<meter>();
+
+    // Run straight away if possible, otherwise settle for a shorter distance
+    // in the same direction, e.g. with a wall in the way
+    for fraction in [1.0, 0.75, 0.5, 0.25] {
+        let goal = position + away.normalize() * distance * fraction;
+        let goal = systems::geometry::ground_position(&game_state.geometry, &goal).unwrap_or(goal);
+        if let Ok(result) = systems::movement::path(game_state, entity, &goal, true, true, true) {
+            debug!(
+                "{:?} flees from {:?} to {:?}",
+                entity,
+                controlled.controller,
+                result.taken_path.end()
+            );
+            return;
+        }
+    }
+}
+
+/// Some control can be shaken off when the controlled creature gets hurt, e.g.
+/// Dominate Person lets the target repeat the save each time it takes damage
+pub fn on_damage(game_state: &mut GameState, entity: Entity) {
+    let Some(saving_throw_dc) =
+        controlled(&game_state.world, entity).and_then(|controlled| controlled.save_on_damage)
+    else {
+        return;
+    };
+
+    let saving_throw_event = systems::d20::check(
+        game_state,
+        entity,
+        &D20CheckDCKind::SavingThrow(saving_throw_dc),
+    );
+    let callback: EventCallback = Arc::new(move |game_state, event| {
+        if let EventKind::D20CheckResolved(_, check_result, dc) = &event.kind
+            && check_result.is_success(dc)
+        {
+            release(&mut game_state.world, entity);
+        }
+        CallbackResult::None
+    });
+    let _ = game_state.process_event_with_callback(saving_throw_event, callback);
+}
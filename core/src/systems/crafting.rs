@@ -0,0 +1,283 @@
+use hecs::Entity;
+use tracing::debug;
+use uom::si::{f32::Mass, mass::pound};
+
+use crate::{
+    components::{
+        actions::action::{ActionContext, ActionProvider},
+        id::{ActionId, IdProvider, ItemId, SpellId},
+        items::{
+            inventory::ItemInstance,
+            item::{Item, ItemRarity},
+            money::{Currency, MonetaryValue},
+            spell_item::{Charges, SpellItem},
+        },
+        resource::RechargeRule,
+        spells::spellbook::{SpellSource, Spellbook},
+        time::TimeDuration,
+    },
+    engine::game_state::GameState,
+    entities::object::ObjectMaterial,
+    registry::registry::{ItemsRegistry, SpellsRegistry},
+    systems::{
+        self,
+        time::{PassTimeError, TimeActivity},
+    },
+};
+
+/// Wands hold a fixed number of charges which are restored every dawn
+const WAND_CHARGES: u8 = 7;
+
+#[derive(Debug, Clone)]
+pub enum CraftingError {
+    UnknownSpell(SpellId),
+    /// The crafter has to be able to cast the spell at the level it's crafted at
+    CannotCastSpell {
+        spell: SpellId,
+        level: u8,
+    },
+    InsufficientFunds {
+        cost: MonetaryValue,
+    },
+    PassTime(PassTimeError),
+}
+
+/// Gold and time it takes to scribe a Spell Scroll of the given level
+pub fn scroll_cost(level: u8) -> (MonetaryValue, TimeDuration) {
+    let (gold, days) = match level {
+        0 => (15, 1),
+        1 => (25, 1),
+        2 => (100, 3),
+        3 => (150, 5),
+        4 => (1_000, 10),
+        5 => (1_500, 25),
+        6 => (10_000, 40),
+        7 => (12_500, 50),
+        8 => (15_000, 60),
+        _ => (50_000, 120),
+    };
+    (gold_pieces(gold), TimeDuration::from_days(days))
+}
+
+/// Gold and time it takes to craft a wand of the given spell level, based on
+/// the rarity of the wand
+pub fn wand_cost(level: u8) -> (MonetaryValue, TimeDuration) {
+    let (gold, days) = match wand_rarity(level) {
+        ItemRarity::Common => (50, 5),
+        ItemRarity::Uncommon => (200, 10),
+        ItemRarity::Rare => (2_000, 50),
+        ItemRarity::VeryRare => (20_000, 125),
+        ItemRarity::Legendary => (100_000, 250),
+    };
+    (gold_pieces(gold), TimeDuration::from_days(days))
+}
+
+/// Rarity of a Spell Scroll of the given spell level
+pub fn scroll_rarity(level: u8) -> ItemRarity {
+    match level {
+        0..=1 => ItemRarity::Common,
+        2..=3 => ItemRarity::Uncommon,
+        4..=5 => ItemRarity::Rare,
+        6..=8 => ItemRarity::VeryRare,
+        _ => ItemRarity::Legendary,
+    }
+}
+
+/// Rarity of a wand of the given spell level
+pub fn wand_rarity(level: u8) -> ItemRarity {
+    match level {
+        0..=2 => ItemRarity::Uncommon,
+        3..=4 => ItemRarity::Rare,
+        5..=6 => ItemRarity::VeryRare,
+        _ => ItemRarity::Legendary,
+    }
+}
+
+fn gold_pieces(amount: u32) -> MonetaryValue {
+    let mut value = MonetaryValue::new();
+    value.add(Currency::Gold, amount);
+    value
+}
+
+/// Human readable name of the spell, e.g. "Magic Missile" for
+/// `nat20_core::spell.magic_missile`
+fn spell_name(spell_id: &SpellId) -> String {
+    spell_id
+        .id()
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn spell_slug(spell_id: &SpellId) -> &str {
+    spell_id.id().rsplit('.').next().unwrap_or_default()
+}
+
+/// A Spell Scroll of the spell at the given level. If the item registry has a
+/// definition for the scroll it's used as is, so crafted scrolls are the same
+/// as the ones found as loot.
+pub fn scroll(spell_id: &SpellId, level: u8) -> SpellItem {
+    let id = ItemId::new(
+        spell_id.namespace(),
+        format!("item.spell_scroll.{}.level_{}", spell_slug(spell_id), level),
+    );
+    if let Some(ItemInstance::Spell(scroll)) = ItemsRegistry::get(&id) {
+        return scroll.clone();
+    }
+
+    SpellItem {
+        item: Item {
+            id,
+            name: format!("Spell Scroll ({}, level {})", spell_name(spell_id), level),
+            description: format!(
+                "A scroll bearing the words of the {} spell. Reading it casts the spell, \
+                after which the scroll crumbles to dust.",
+                spell_name(spell_id)
+            ),
+            weight: Mass::new::<pound>(0.0),
+            value: scroll_cost(level).0,
+            rarity: scroll_rarity(level),
+            material: ObjectMaterial::Cloth,
+            damage_taken: 0,
+        },
+        spell: spell_id.clone(),
+        level,
+        charges: Charges::consumable(),
+    }
+}
+
+/// A wand of the spell at the given level. Like scrolls, registry definitions
+/// take precedence over the generated item.
+pub fn wand(spell_id: &SpellId, level: u8) -> SpellItem {
+    let id = ItemId::new(
+        spell_id.namespace(),
+        format!("item.wand.{}.level_{}", spell_slug(spell_id), level),
+    );
+    if let Some(ItemInstance::Spell(wand)) = ItemsRegistry::get(&id) {
+        return wand.clone();
+    }
+
+    SpellItem {
+        item: Item {
+            id,
+            name: format!("Wand of {} (level {})", spell_name(spell_id), level),
+            description: format!(
+                "This wand has {} charges. While holding it, you can expend a charge to cast \
+                the {} spell from it. The wand regains all expended charges daily at dawn.",
+                WAND_CHARGES,
+                spell_name(spell_id)
+            ),
+            weight: Mass::new::<pound>(1.0),
+            value: wand_cost(level).0,
+            rarity: wand_rarity(level),
+            material: ObjectMaterial::Wood,
+            damage_taken: 0,
+        },
+        spell: spell_id.clone(),
+        level,
+        charges: Charges::new(WAND_CHARGES, Some(RechargeRule::Daily)),
+    }
+}
+
+/// Spend downtime scribing a Spell Scroll of a spell the entity can cast
+pub fn scribe_scroll(
+    game_state: &mut GameState,
+    entity: Entity,
+    spell_id: &SpellId,
+    level: u8,
+) -> Result<ItemId, CraftingError> {
+    craft(
+        game_state,
+        entity,
+        spell_id,
+        level,
+        scroll(spell_id, level),
+        scroll_cost(level),
+    )
+}
+
+/// Spend downtime crafting a wand of a spell the entity can cast
+pub fn craft_wand(
+    game_state: &mut GameState,
+    entity: Entity,
+    spell_id: &SpellId,
+    level: u8,
+) -> Result<ItemId, CraftingError> {
+    craft(
+        game_state,
+        entity,
+        spell_id,
+        level,
+        wand(spell_id, level),
+        wand_cost(level),
+    )
+}
+
+fn craft(
+    game_state: &mut GameState,
+    entity: Entity,
+    spell_id: &SpellId,
+    level: u8,
+    item: SpellItem,
+    (cost, duration): (MonetaryValue, TimeDuration),
+) -> Result<ItemId, CraftingError> {
+    let Some(spell) = SpellsRegistry::get(spell_id) else {
+        return Err(CraftingError::UnknownSpell(spell_id.clone()));
+    };
+
+    if !can_cast_at_level(game_state, entity, spell.action().id(), level) {
+        return Err(CraftingError::CannotCastSpell {
+            spell: spell_id.clone(),
+            level,
+        });
+    }
+
+    systems::inventory::remove_money(&mut game_state.world, entity, cost.clone())
+        .map_err(|_| CraftingError::InsufficientFunds { cost: cost.clone() })?;
+
+    if let Err(error) = systems::time::pass_time(game_state, duration, TimeActivity::Downtime) {
+        // Nothing was crafted, so the materials aren't used up
+        systems::inventory::add_money(&mut game_state.world, entity, cost);
+        return Err(CraftingError::PassTime(error));
+    }
+
+    let item_id = item.id().clone();
+    debug!("{:?} crafted {} for {}", entity, item_id, cost);
+    systems::inventory::add_item(&mut game_state.world, entity, item);
+    Ok(item_id)
+}
+
+/// Whether the entity can cast the spell at the level with its own spellcasting,
+/// i.e. not from an item
+fn can_cast_at_level(
+    game_state: &GameState,
+    entity: Entity,
+    action_id: &ActionId,
+    level: u8,
+) -> bool {
+    let world = &game_state.world;
+    let actions =
+        systems::helpers::get_component::<Spellbook>(world, entity).actions(world, entity);
+    actions.get(action_id).is_some_and(|contexts| {
+        contexts.iter().any(|(context, _)| {
+            matches!(
+                context,
+                ActionContext::Spell {
+                    source: SpellSource::Class(_),
+                    level: cast_level,
+                    ..
+                } if *cast_level == level
+            )
+        })
+    })
+}
@@ -2,12 +2,12 @@ use hecs::{Entity, World};
 
 use crate::{
     components::{
-        d20::{D20CheckDC, D20CheckResult},
+        d20::{D20CheckDC, D20CheckExplanation, D20CheckResult, RollMode},
         damage::AttackRollResult,
         items::equipment::armor::ArmorClass,
-        modifier::Modifiable,
+        modifier::{Modifiable, ModifierSet, ModifierSource},
         saving_throw::{SavingThrowKind, SavingThrowSet},
-        skill::{Skill, SkillSet},
+        skill::{Skill, SkillCheckDC, SkillContest, SkillSet},
     },
     engine::{
         event::{Event, EventKind},
@@ -56,9 +56,7 @@ impl D20ResultKind {
                 result.is_success(dc)
             }
             (D20ResultKind::AttackRoll { result }, D20CheckDCKind::AttackRoll(_, armor_class)) => {
-                let result = &result.roll_result;
-                !result.is_crit_fail
-                    && (result.is_crit || result.total() >= armor_class.total() as u32)
+                result.hits(armor_class)
             }
             _ => false,
         }
@@ -81,6 +79,31 @@ impl D20ResultKind {
     }
 }
 
+/// The would-be breakdown of a check for the entity without rolling it, e.g.
+/// to show live bonuses in the GUI. Attack rolls depend on the weapon and the
+/// target, so they can't be explained this way.
+pub fn explain(
+    world: &World,
+    entity: Entity,
+    check_kind: &D20CheckKind,
+) -> Option<D20CheckExplanation> {
+    match check_kind {
+        D20CheckKind::SavingThrow(kind) => Some(
+            world
+                .get::<&SavingThrowSet>(entity)
+                .ok()?
+                .explain(kind, world, entity),
+        ),
+        D20CheckKind::Skill(skill) => Some(
+            world
+                .get::<&SkillSet>(entity)
+                .ok()?
+                .explain(skill, world, entity),
+        ),
+        D20CheckKind::AttackRoll => None,
+    }
+}
+
 pub fn check_no_event(world: &World, entity: Entity, dc: &D20CheckDCKind) -> D20ResultKind {
     match dc {
         D20CheckDCKind::SavingThrow(dc) => D20ResultKind::SavingThrow {
@@ -102,8 +125,80 @@ pub fn check_no_event(world: &World, entity: Entity, dc: &D20CheckDCKind) -> D20
     }
 }
 
+/// The skill the defender of a contest is best at, which is the one it
+/// resists with
+pub fn contest_defense(world: &World, defender: Entity, contest: &SkillContest) -> Skill {
+    let skills = systems::helpers::get_component::<SkillSet>(world, defender);
+    contest
+        .opposed_by
+        .iter()
+        .max_by_key(|skill| skills.explain(skill, world, defender).total_modifier())
+        .copied()
+        .unwrap_or(contest.skill)
+}
+
+/// Roll the defender's side of a contest. The result is the DC the initiator
+/// has to meet, which is one higher than the defender's total since a tie
+/// leaves the situation as it was.
+pub fn contest_dc(
+    world: &World,
+    defender: Entity,
+    contest: &SkillContest,
+) -> (D20ResultKind, SkillCheckDC) {
+    let defense = contest_defense(world, defender, contest);
+    let result = check_no_event(
+        world,
+        defender,
+        &D20CheckDCKind::Skill(D20CheckDC {
+            key: defense,
+            dc: ModifierSet::new(),
+        }),
+    );
+    let dc = SkillCheckDC {
+        key: contest.skill,
+        dc: ModifierSet::from(
+            ModifierSource::Custom(format!("Contested by {}", defense)),
+            result.d20_result().total() as i32 + 1,
+        ),
+    };
+    (result, dc)
+}
+
+/// The chance that the initiator wins the contest, without rolling either
+/// side of it
+pub fn contest_success_probability(
+    world: &World,
+    actor: Entity,
+    defender: Entity,
+    contest: &SkillContest,
+) -> f64 {
+    let defense = contest_defense(world, defender, contest);
+    let explanation = systems::helpers::get_component::<SkillSet>(world, defender)
+        .explain(&defense, world, defender);
+    let actor_skills = systems::helpers::get_component::<SkillSet>(world, actor);
+
+    (1..=20)
+        .map(|roll: i32| {
+            let chance = match explanation.roll_mode() {
+                RollMode::Normal => 1.0 / 20.0,
+                RollMode::Advantage => (2 * roll - 1) as f64 / 400.0,
+                RollMode::Disadvantage => (41 - 2 * roll) as f64 / 400.0,
+            };
+            let dc = SkillCheckDC {
+                key: contest.skill,
+                dc: ModifierSet::from(
+                    ModifierSource::Custom(format!("Contested by {}", defense)),
+                    roll + explanation.total_modifier() + 1,
+                ),
+            };
+            chance * actor_skills.success_probability(&dc, world, actor)
+        })
+        .sum()
+}
+
 #[must_use]
 pub fn check(game_state: &mut GameState, entity: Entity, dc: &D20CheckDCKind) -> Event {
+    systems::conditions::update_fear_sight(game_state, entity);
     Event::new(EventKind::D20CheckPerformed(
         entity,
         check_no_event(&game_state.world, entity, dc),
@@ -1,27 +1,32 @@
 use hecs::{Entity, World};
+use uom::si::{f32::Length, length::foot};
 
 use crate::{
     components::{
-        actions::action::{ActionContext, AttackRollFunction, DamageFunction},
+        actions::{
+            action::{ActionContext, AttackRollFunction, TargetDamageFunction},
+            resolution::TargetResolution,
+        },
+        d20::AdvantageType,
         damage::{AttackRoll, AttackRollResult, DamageRoll, DamageRollResult},
+        health::life_state::LifeState,
         items::equipment::slots::EquipmentSlot,
+        modifier::ModifierSource,
     },
     systems,
 };
 
 pub fn damage_roll(
-    mut damage_roll: DamageRoll,
+    damage_roll: DamageRoll,
     world: &World,
     entity: Entity,
     crit: bool,
 ) -> DamageRollResult {
-    for effect in systems::effects::effects(world, entity).iter() {
-        (effect.effect().pre_damage_roll)(world, entity, &mut damage_roll);
-    }
-
-    let mut result = damage_roll.roll(crit);
+    let policy = systems::helpers::roll_policy(world, entity);
+    let mut result =
+        prepare_damage_roll(damage_roll, world, entity).roll_with_policy(crit, &policy);
 
-    for effect in systems::effects::effects(world, entity).iter() {
+    for effect in systems::effects::active_effects(world, entity).iter() {
         (effect.effect().post_damage_roll)(world, entity, &mut result);
     }
 
@@ -29,28 +34,86 @@ pub fn damage_roll(
 }
 
 pub fn damage_roll_fn(
-    damage_roll_fn: &DamageFunction,
+    damage_roll_fn: &TargetDamageFunction,
     world: &World,
     entity: Entity,
     context: &ActionContext,
+    resolution: &TargetResolution,
     crit: bool,
 ) -> DamageRollResult {
-    let roll = damage_roll_fn(world, entity, context);
+    let roll = damage_roll_fn(world, entity, context, resolution);
     damage_roll(roll, world, entity, crit)
 }
 
-pub fn attack_roll(mut attack_roll: AttackRoll, world: &World, entity: Entity) -> AttackRollResult {
-    for effect in systems::effects::effects(world, entity).iter() {
+/// Apply the effects of both the attacker and the target to the attack roll,
+/// without rolling it
+pub fn prepare_attack_roll(
+    mut attack_roll: AttackRoll,
+    world: &World,
+    entity: Entity,
+    target: Entity,
+) -> AttackRoll {
+    for effect in systems::effects::active_effects(world, entity).iter() {
         (effect.effect().pre_attack_roll)(world, entity, &mut attack_roll);
     }
+    systems::conditions::apply_fear(world, entity, &mut attack_roll.d20_check);
+
+    // Effects on the target can also affect the attack roll, e.g. attacks
+    // against a paralyzed creature have advantage
+    for effect in systems::effects::active_effects(world, target).iter() {
+        (effect.effect().pre_incoming_attack_roll)(world, target, entity, &mut attack_roll);
+    }
+
+    // Attacks against unconscious creatures have advantage, and any hit from
+    // within 5 feet is a critical hit
+    if world
+        .get::<&LifeState>(target)
+        .is_ok_and(|life_state| life_state.is_unconscious())
+    {
+        let source = ModifierSource::Custom("Unconscious".to_string());
+        attack_roll
+            .d20_check
+            .advantage_tracker_mut()
+            .add(AdvantageType::Advantage, source.clone());
+        if systems::geometry::distance_between_entities(world, entity, target)
+            .is_some_and(|distance| distance <= Length::new::<foot>(5.0))
+        {
+            attack_roll.set_crit_on_hit(source);
+        }
+    }
+
+    attack_roll
+}
+
+/// Apply the effects of the entity to the damage roll, without rolling it
+pub fn prepare_damage_roll(
+    mut damage_roll: DamageRoll,
+    world: &World,
+    entity: Entity,
+) -> DamageRoll {
+    for effect in systems::effects::active_effects(world, entity).iter() {
+        (effect.effect().pre_damage_roll)(world, entity, &mut damage_roll);
+    }
+
+    damage_roll
+}
+
+pub fn attack_roll(
+    attack_roll: AttackRoll,
+    world: &World,
+    entity: Entity,
+    target: Entity,
+) -> AttackRollResult {
+    let attack_roll = prepare_attack_roll(attack_roll, world, entity, target);
 
     let mut result = {
         let level =
             systems::helpers::level(world, entity).expect("Entity must have a level component");
-        attack_roll.roll_raw(level.proficiency_bonus())
+        let policy = systems::helpers::roll_policy(world, entity);
+        attack_roll.roll_raw_with_policy(level.proficiency_bonus(), &policy)
     };
 
-    for effect in systems::effects::effects(world, entity).iter() {
+    for effect in systems::effects::active_effects(world, entity).iter() {
         (effect.effect().post_attack_roll)(world, entity, &mut result);
     }
 
@@ -65,7 +128,7 @@ pub fn attack_roll_fn(
     context: &ActionContext,
 ) -> AttackRollResult {
     let roll = attack_roll_fn(world, entity, target, context);
-    attack_roll(roll, world, entity)
+    attack_roll(roll, world, entity, target)
 }
 
 pub fn damage_roll_weapon(
@@ -92,5 +155,6 @@ pub fn attack_roll_weapon(
         systems::loadout::weapon_attack_roll(world, entity, target, slot),
         world,
         entity,
+        target,
     )
 }
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use hecs::{Entity, World};
+use parry3d::na::Point3;
+use tracing::debug;
+use uom::si::{f32::Length, length::foot};
+
+use crate::{
+    components::{
+        d20::D20CheckDC,
+        damage::DamageThreshold,
+        health::hit_points::HitPoints,
+        hidden::{HiddenFeature, HiddenFeatureKind},
+        id::Name,
+        modifier::{ModifierSet, ModifierSource},
+        skill::{Skill, SkillSet},
+        species::CreatureSize,
+    },
+    engine::{
+        event::{CallbackResult, Event, EventCallback, EventKind},
+        game_state::GameState,
+    },
+    entities::{
+        character::CharacterTag,
+        object::{Object, ObjectMaterial},
+    },
+    systems::{self, d20::D20CheckDCKind},
+};
+
+/// How close a character has to get to a hidden feature to notice it without
+/// looking for it
+const NOTICE_RANGE: f32 = 10.0;
+/// How far around itself a creature looks when it searches
+const SEARCH_RANGE: f32 = 30.0;
+
+/// Place a small hidden object on the ground, e.g. a lever or a clue. Secret
+/// doors are regular doors with a [`HiddenFeature`] added to them.
+pub fn spawn(
+    game_state: &mut GameState,
+    name: &str,
+    feature: HiddenFeature,
+    position: &Point3<f32>,
+) -> Entity {
+    let material = match feature.kind {
+        HiddenFeatureKind::SecretDoor => ObjectMaterial::Wood,
+        HiddenFeatureKind::Lever | HiddenFeatureKind::Trap => ObjectMaterial::Iron,
+        HiddenFeatureKind::Clue => ObjectMaterial::Cloth,
+    };
+    let entity = game_state.world.spawn(Object::new(
+        Name::new(name),
+        material,
+        CreatureSize::Tiny,
+        HitPoints::new(5),
+        DamageThreshold(0),
+    ));
+    let _ = game_state.world.insert_one(entity, feature);
+    systems::geometry::teleport_to_ground(
+        &mut game_state.world,
+        &game_state.geometry,
+        entity,
+        position,
+    );
+    entity
+}
+
+/// Whether the entity is a hidden feature which hasn't been found yet
+pub fn is_hidden(world: &World, entity: Entity) -> bool {
+    world
+        .get::<&HiddenFeature>(entity)
+        .is_ok_and(|feature| !feature.revealed)
+}
+
+/// Hidden features within range of the entity which haven't been found yet
+pub fn hidden_features_in_range(
+    world: &World,
+    entity: Entity,
+    range: Length,
+) -> Vec<(Entity, HiddenFeature)> {
+    world
+        .query::<&HiddenFeature>()
+        .iter()
+        .filter(|(_, feature)| !feature.revealed)
+        .filter(|(feature, _)| {
+            systems::geometry::distance_between_entities(world, entity, *feature)
+                .is_some_and(|distance| distance <= range)
+        })
+        .map(|(entity, feature)| (entity, feature.clone()))
+        .collect()
+}
+
+/// The passive score of the entity for the skill, e.g. its passive Perception
+pub fn passive_score(world: &World, entity: Entity, skill: Skill) -> Option<i32> {
+    let skills = world.get::<&SkillSet>(entity).ok()?;
+    Some(skills.passive_score(&skill, world, entity))
+}
+
+/// Reveal a hidden feature to everyone, e.g. because it was found or the GM
+/// decided it was time for the party to see it
+pub fn reveal(game_state: &mut GameState, feature: Entity, by: Option<Entity>) {
+    let kind = {
+        let Ok(mut hidden_feature) = game_state.world.get::<&mut HiddenFeature>(feature) else {
+            return;
+        };
+        if hidden_feature.revealed {
+            return;
+        }
+        hidden_feature.revealed = true;
+        hidden_feature.kind
+    };
+
+    debug!("{:?} ({}) was found by {:?}", feature, kind, by);
+    let _ = game_state.process_event(Event::new(EventKind::HiddenFeatureRevealed {
+        feature,
+        kind,
+        by,
+    }));
+}
+
+/// Characters notice hidden features close to them if their passive score is at
+/// least the DC of the feature, returning the features they noticed. Monsters
+/// are left out, since whatever they stumble upon doesn't help the party.
+pub fn notice(game_state: &mut GameState, entity: Entity) -> Vec<Entity> {
+    let world = &game_state.world;
+    if world.get::<&CharacterTag>(entity).is_err() {
+        return Vec::new();
+    }
+
+    let noticed = hidden_features_in_range(world, entity, Length::new::<foot>(NOTICE_RANGE))
+        .into_iter()
+        .filter(|(_, feature)| {
+            passive_score(world, entity, feature.skill()).is_some_and(|score| score >= feature.dc)
+        })
+        .map(|(feature, _)| feature)
+        .collect::<Vec<_>>();
+
+    for feature in &noticed {
+        reveal(game_state, *feature, Some(entity));
+    }
+    noticed
+}
+
+/// Search the surroundings for hidden features. The entity makes a single check
+/// for each skill needed to find the features nearby, and finds every feature
+/// whose DC the check meets.
+pub fn search(game_state: &mut GameState, entity: Entity) {
+    let features =
+        hidden_features_in_range(&game_state.world, entity, Length::new::<foot>(SEARCH_RANGE));
+
+    for skill in [Skill::Perception, Skill::Investigation] {
+        let candidates = features
+            .iter()
+            .filter(|(_, feature)| feature.skill() == skill)
+            .map(|(feature, hidden_feature)| (*feature, hidden_feature.dc))
+            .collect::<Vec<_>>();
+        // The check is made against the easiest feature, and the result is then
+        // compared with the rest of them
+        let Some(lowest_dc) = candidates.iter().map(|(_, dc)| *dc).min() else {
+            continue;
+        };
+
+        let check = systems::d20::check(game_state, entity, &skill_dc(skill, lowest_dc));
+        let callback: EventCallback = Arc::new(move |game_state, event| {
+            let EventKind::D20CheckResolved(_, result, _) = &event.kind else {
+                return CallbackResult::None;
+            };
+            for (feature, dc) in &candidates {
+                if result.is_success(&skill_dc(skill, *dc)) {
+                    reveal(game_state, *feature, Some(entity));
+                }
+            }
+            CallbackResult::None
+        });
+        let _ = game_state.process_event_with_callback(check, callback);
+    }
+}
+
+fn skill_dc(skill: Skill, dc: i32) -> D20CheckDCKind {
+    D20CheckDCKind::Skill(D20CheckDC {
+        key: skill,
+        dc: ModifierSet::from(ModifierSource::Base, dc),
+    })
+}
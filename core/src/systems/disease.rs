@@ -0,0 +1,137 @@
+use hecs::{Entity, World};
+use strum::IntoEnumIterator;
+use tracing::debug;
+
+use crate::{
+    components::{
+        d20::D20CheckDC,
+        disease::{Disease, DiseaseOutcome, DiseaseProgress, Diseases},
+        effects::effect::EffectTag,
+        id::EffectId,
+        modifier::{ModifierSet, ModifierSource},
+    },
+    registry::registry::EffectsRegistry,
+    systems::{self, d20::D20CheckDCKind},
+};
+
+fn source(disease: &Disease) -> ModifierSource {
+    ModifierSource::Effect(disease.effect())
+}
+
+/// Infect the entity with the disease. The symptoms don't show until the next
+/// dawn.
+pub fn infect(world: &mut World, entity: Entity, disease: Disease, source: &ModifierSource) {
+    debug!("{:?} is infected with {}", entity, disease);
+    systems::effects::add_permanent_effect(world, entity, disease.effect(), source, None);
+}
+
+pub fn diseases(world: &World, entity: Entity) -> Vec<Disease> {
+    Disease::iter()
+        .filter(|disease| systems::effects::has_effect(world, entity, &disease.effect()))
+        .collect()
+}
+
+/// Finishing a long rest counts towards recovering from a disease, as long as
+/// the saving throw at the next dawn succeeds
+pub fn on_long_rest(world: &mut World, entity: Entity) {
+    if let Ok(mut diseases) = world.get::<&mut Diseases>(entity) {
+        for progress in diseases.values_mut() {
+            progress.rested = true;
+        }
+    }
+}
+
+/// Let a day pass for the diseases of the entity. Diseases which have been
+/// cured in the meantime, e.g. by Lesser Restoration, are forgotten.
+pub fn on_dawn(world: &mut World, entity: Entity) -> Vec<(Disease, DiseaseOutcome)> {
+    let infected = diseases(world, entity);
+    let mut diseases = world
+        .get::<&Diseases>(entity)
+        .map(|diseases| (*diseases).clone())
+        .unwrap_or_default();
+    diseases.retain(|disease, _| infected.contains(disease));
+
+    let mut outcomes = Vec::new();
+    for disease in infected {
+        let outcome = match diseases.get_mut(&disease) {
+            Some(progress) => progress_disease(world, entity, &disease, progress),
+            None => {
+                diseases.insert(disease, DiseaseProgress::default());
+                apply_stage(world, entity, &disease, 0);
+                DiseaseOutcome::SymptomsAppear
+            }
+        };
+        debug!("{} of {:?} at dawn: {:?}", disease, entity, outcome);
+        if outcome == DiseaseOutcome::Recovered {
+            diseases.remove(&disease);
+        }
+        outcomes.push((disease, outcome));
+    }
+
+    for progress in diseases.values_mut() {
+        progress.rested = false;
+    }
+    if diseases.is_empty() {
+        let _ = world.remove_one::<Diseases>(entity);
+    } else {
+        let _ = world.insert_one(entity, diseases);
+    }
+
+    outcomes
+}
+
+fn progress_disease(
+    world: &mut World,
+    entity: Entity,
+    disease: &Disease,
+    progress: &mut DiseaseProgress,
+) -> DiseaseOutcome {
+    let dc = D20CheckDCKind::SavingThrow(D20CheckDC {
+        key: disease.saving_throw(),
+        dc: ModifierSet::from(source(disease), disease.saving_throw_dc()),
+    });
+    let success = systems::d20::check_no_event(world, entity, &dc).is_success(&dc);
+
+    if !success {
+        progress.streak = 0;
+        progress.stage = (progress.stage + 1).min(disease.stages().len() - 1);
+        apply_stage(world, entity, disease, progress.stage);
+        return DiseaseOutcome::Worsened {
+            stage: progress.stage,
+        };
+    }
+
+    // Only days spent resting count towards the streak
+    progress.streak = if progress.rested {
+        progress.streak + 1
+    } else {
+        0
+    };
+    if progress.streak >= disease.recovery_streak() {
+        cure(world, entity, disease);
+        return DiseaseOutcome::Recovered;
+    }
+    DiseaseOutcome::Resisted {
+        streak: progress.streak,
+    }
+}
+
+/// Stages of the same disease replace each other, except for general conditions
+/// like exhaustion which stack up instead
+fn apply_stage(world: &mut World, entity: Entity, disease: &Disease, stage: usize) {
+    let effect = disease.stages()[stage].clone();
+    systems::effects::add_permanent_effect(world, entity, effect, &source(disease), None);
+}
+
+/// End the disease along with its symptoms. General conditions it has caused,
+/// e.g. exhaustion, have to be recovered from separately.
+pub fn cure(world: &mut World, entity: Entity, disease: &Disease) {
+    let cured: Vec<EffectId> = std::iter::once(disease.effect())
+        .chain(disease.stages().into_iter().filter(|effect| {
+            EffectsRegistry::get(effect).is_some_and(|effect| effect.has_tag(&EffectTag::Disease))
+        }))
+        .filter(|effect| systems::effects::has_effect(world, entity, effect))
+        .collect();
+    debug!("{:?} recovers from {}", entity, disease);
+    systems::effects::remove_effects(world, entity, &cured);
+}
@@ -4,10 +4,13 @@ use tracing::debug;
 use crate::{
     components::{
         actions::action::ActionContext,
-        effects::effect::{EffectInstance, EffectInstanceTemplate},
+        effects::effect::{EffectInstance, EffectInstanceTemplate, EffectTag, StackingPolicy},
+        faction::Attitude,
+        health::life_state::LifeState,
         id::EffectId,
         modifier::ModifierSource,
     },
+    engine::event::ActionData,
     registry::registry::EffectsRegistry,
     systems,
 };
@@ -21,6 +24,42 @@ pub fn effects_mut(world: &mut World, entity: Entity) -> hecs::RefMut<'_, Vec<Ef
     systems::helpers::get_component_mut::<Vec<EffectInstance>>(world, entity)
 }
 
+/// The effects on the entity which aren't suppressed
+pub struct ActiveEffects<'a>(Ref<'a, Vec<EffectInstance>>);
+
+impl ActiveEffects<'_> {
+    pub fn iter(&self) -> impl Iterator<Item = &EffectInstance> {
+        self.0.iter().filter(|effect| !effect.is_suppressed())
+    }
+}
+
+/// The effects whose hooks should run for the entity. Suppressed effects are
+/// still on the entity, but they shouldn't do anything.
+pub fn active_effects(world: &World, entity: Entity) -> ActiveEffects<'_> {
+    ActiveEffects(effects(world, entity))
+}
+
+pub fn has_effect(world: &World, entity: Entity, effect_id: &EffectId) -> bool {
+    world
+        .get::<&Vec<EffectInstance>>(entity)
+        .is_ok_and(|effects| effects.iter().any(|effect| effect.effect_id == *effect_id))
+}
+
+/// How many times the effect has been stacked on the entity, or 0 if the
+/// entity doesn't have it
+pub fn stacks(world: &World, entity: Entity, effect_id: &EffectId) -> u32 {
+    world
+        .get::<&Vec<EffectInstance>>(entity)
+        .ok()
+        .and_then(|effects| {
+            effects
+                .iter()
+                .find(|effect| effect.effect_id == *effect_id)
+                .map(|effect| effect.stacks)
+        })
+        .unwrap_or(0)
+}
+
 pub fn add_effect_template(
     world: &mut World,
     applier: Entity,
@@ -45,8 +84,7 @@ pub fn add_permanent_effect(
     context: Option<&ActionContext>,
 ) {
     let effect_instance = EffectInstance::permanent(effect_id.clone(), source.clone());
-    apply_and_replace(world, entity, &effect_instance, context);
-    effects_mut(world, entity).push(effect_instance);
+    add_effect_instance(world, entity, effect_instance, context);
 }
 
 pub fn add_permanent_effects(
@@ -61,27 +99,126 @@ pub fn add_permanent_effects(
     }
 }
 
+/// Add the effect instance, following the stacking policy of the effect if the
+/// entity already has it
 fn add_effect_instance(
     world: &mut World,
     entity: Entity,
-    effect_instance: EffectInstance,
+    mut effect_instance: EffectInstance,
     context: Option<&ActionContext>,
 ) {
-    apply_and_replace(world, entity, &effect_instance, context);
-    effects_mut(world, entity).push(effect_instance);
-}
+    if let Some(suppressor) = systems::antimagic::suppressed_by(world, entity, &effect_instance) {
+        effect_instance.suppressed_by.push(suppressor);
+    }
 
-fn apply_and_replace(
-    world: &mut World,
-    entity: Entity,
-    effect_instance: &EffectInstance,
-    context: Option<&ActionContext>,
-) {
     let effect = effect_instance.effect();
-    (effect.on_apply)(world, entity, context);
-    if let Some(replaces) = &effect.replaces {
+    let stacking = effect.stacking;
+    let on_apply = effect.on_apply.clone();
+    let replaces = effect.replaces.clone();
+    let control = effect.control.clone();
+    let effect_id = effect_instance.effect_id.clone();
+    let applier = effect_instance.applier;
+
+    let mut lethal_stacks = false;
+    let applied = {
+        let mut effects = effects_mut(world, entity);
+        match effects
+            .iter_mut()
+            .find(|existing| existing.effect_id == effect_instance.effect_id)
+        {
+            Some(existing) => {
+                let stacked = match stacking {
+                    StackingPolicy::Unique => false,
+                    StackingPolicy::Stack { max, lethal } => {
+                        if max.is_none_or(|max| existing.stacks < max) {
+                            existing.stacks += 1;
+                            lethal_stacks = lethal && max == Some(existing.stacks);
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                };
+                if effect_instance.outlasts(existing) {
+                    existing.lifetime = effect_instance.lifetime;
+                    existing.source = effect_instance.source;
+                    existing.applier = effect_instance.applier;
+                }
+                debug!(
+                    "Entity {:?} already has effect {:?}, now with {} stack(s)",
+                    entity, existing.effect_id, existing.stacks
+                );
+                // A suppressed effect is applied when the suppression is lifted
+                stacked && !existing.is_suppressed()
+            }
+            None => {
+                let suppressed = effect_instance.is_suppressed();
+                effects.push(effect_instance);
+                !suppressed
+            }
+        }
+    };
+
+    if applied {
+        on_apply(world, entity, context);
+        if let Some(control) = &control
+            && let Some(applier) = applier
+        {
+            systems::control::take_control(world, entity, applier, &effect_id, control, context);
+        }
+    }
+    if let Some(replaces) = &replaces
+        && has_effect(world, entity, replaces)
+    {
         remove_effect(world, entity, replaces);
     }
+    if lethal_stacks {
+        debug!(
+            "Entity {:?} dies from reaching the maximum stacks of {:?}",
+            entity, effect_id
+        );
+        systems::health::set_life_state(world, entity, LifeState::Dead);
+    }
+}
+
+/// Take a single stack off the effect, e.g. a level of exhaustion recovered by
+/// finishing a long rest. The effect ends when its last stack is removed.
+pub fn remove_stack(world: &mut World, entity: Entity, effect_id: &EffectId) {
+    let Some((stacks, suppressed)) = effects(world, entity)
+        .iter()
+        .find(|effect| effect.effect_id == *effect_id)
+        .map(|effect| (effect.stacks, effect.is_suppressed()))
+    else {
+        return;
+    };
+    if stacks <= 1 {
+        remove_effect(world, entity, effect_id);
+        return;
+    }
+
+    // The modifiers scale with the number of stacks, so the effect is applied
+    // again from scratch for the stacks which are left
+    let effect = EffectsRegistry::get(effect_id)
+        .expect(format!("Effect definition not found for ID `{}`", effect_id).as_str());
+    let applied_stacks = if suppressed { 0 } else { stacks };
+    for _ in 0..applied_stacks {
+        (effect.on_unapply)(world, entity);
+    }
+    if let Some(existing) = effects_mut(world, entity)
+        .iter_mut()
+        .find(|effect| effect.effect_id == *effect_id)
+    {
+        existing.stacks -= 1;
+    }
+    debug!(
+        "Entity {:?} has effect {:?} with {} stack(s) left",
+        entity,
+        effect_id,
+        stacks - 1
+    );
+    for _ in 1..applied_stacks {
+        (effect.on_apply)(world, entity, None);
+    }
 }
 
 pub fn remove_effect(world: &mut World, entity: Entity, effect_id: &EffectId) {
@@ -89,8 +226,30 @@ pub fn remove_effect(world: &mut World, entity: Entity, effect_id: &EffectId) {
     // TODO: Is this all we need to do here?
     let effect = EffectsRegistry::get(effect_id)
         .expect(format!("Effect definition not found for ID `{}`", effect_id).as_str());
-    (effect.on_unapply)(world, entity);
+    let instance = effects(world, entity)
+        .iter()
+        .find(|e| e.effect_id == *effect_id)
+        .map(|e| (e.applier, if e.is_suppressed() { 0 } else { e.stacks }));
+    let applied_stacks = instance.map_or(1, |(_, stacks)| stacks);
+    for _ in 0..applied_stacks {
+        (effect.on_unapply)(world, entity);
+    }
     effects_mut(world, entity).retain(|e| e.effect_id != *effect_id);
+    systems::control::on_effect_removed(world, entity, effect_id);
+
+    // Whatever follows the effect only happens if the entity actually had it
+    if let Some(followed_by) = &effect.followed_by
+        && let Some((applier, _)) = instance
+    {
+        add_effect_template(
+            world,
+            applier.unwrap_or(entity),
+            entity,
+            ModifierSource::Effect(effect_id.clone()),
+            followed_by,
+            None,
+        );
+    }
 }
 
 pub fn remove_effects(world: &mut World, entity: Entity, effects: &[EffectId]) {
@@ -98,3 +257,122 @@ pub fn remove_effects(world: &mut World, entity: Entity, effects: &[EffectId]) {
         remove_effect(world, entity, effect);
     }
 }
+
+/// Suppress the effects matching `filter` on behalf of `suppressor`. A
+/// suppressed effect is unapplied, but stays on the entity and keeps counting
+/// down its duration, until it's unsuppressed again.
+pub fn suppress_effects(
+    world: &mut World,
+    entity: Entity,
+    suppressor: &ModifierSource,
+    filter: impl Fn(&EffectInstance) -> bool,
+) {
+    let mut unapply = Vec::new();
+    for effect in effects_mut(world, entity).iter_mut() {
+        if !filter(effect) || effect.suppressed_by.contains(suppressor) {
+            continue;
+        }
+        if !effect.is_suppressed() {
+            unapply.push((effect.effect().on_unapply.clone(), effect.stacks));
+        }
+        debug!(
+            "Effect {:?} on entity {:?} is suppressed by {:?}",
+            effect.effect_id, entity, suppressor
+        );
+        effect.suppressed_by.push(suppressor.clone());
+    }
+
+    for (on_unapply, stacks) in unapply {
+        for _ in 0..stacks {
+            on_unapply(world, entity);
+        }
+    }
+}
+
+/// Lift the suppression by `suppressor`. Effects which are no longer
+/// suppressed by anything are applied again.
+pub fn unsuppress_effects(world: &mut World, entity: Entity, suppressor: &ModifierSource) {
+    let mut apply = Vec::new();
+    for effect in effects_mut(world, entity).iter_mut() {
+        if !effect.suppressed_by.contains(suppressor) {
+            continue;
+        }
+        effect.suppressed_by.retain(|source| source != suppressor);
+        if !effect.is_suppressed() {
+            debug!(
+                "Effect {:?} on entity {:?} is no longer suppressed",
+                effect.effect_id, entity
+            );
+            apply.push((effect.effect().on_apply.clone(), effect.stacks));
+        }
+    }
+
+    for (on_apply, stacks) in apply {
+        for _ in 0..stacks {
+            on_apply(world, entity, None);
+        }
+    }
+}
+
+/// Whether the entity has the effect and it isn't suppressed
+pub fn is_effect_active(world: &World, entity: Entity, effect_id: &EffectId) -> bool {
+    world
+        .get::<&Vec<EffectInstance>>(entity)
+        .is_ok_and(|effects| {
+            effects
+                .iter()
+                .any(|effect| effect.effect_id == *effect_id && !effect.is_suppressed())
+        })
+}
+
+/// End every effect on the entity with one of the tags, e.g. all curses for
+/// Remove Curse. Returns the effects that were removed.
+pub fn remove_effects_with_tags(
+    world: &mut World,
+    entity: Entity,
+    tags: &[EffectTag],
+) -> Vec<EffectId> {
+    let removed: Vec<EffectId> = effects(world, entity)
+        .iter()
+        .filter(|effect| tags.iter().any(|tag| effect.effect().has_tag(tag)))
+        .map(|effect| effect.effect_id.clone())
+        .collect();
+    remove_effects(world, entity, &removed);
+    removed
+}
+
+/// End the effects on the actor which end when it performs the action, e.g.
+/// Invisibility ending when the invisible creature attacks. This happens after
+/// the action is performed, so the effect still applies to the action itself.
+pub fn end_effects_triggered_by(world: &mut World, action_data: &ActionData) {
+    let ended: Vec<EffectId> = effects(world, action_data.actor)
+        .iter()
+        .filter(|effect| {
+            effect
+                .effect()
+                .end_on
+                .iter()
+                .any(|trigger| trigger.matches(action_data))
+        })
+        .map(|effect| effect.effect_id.clone())
+        .collect();
+    remove_effects(world, action_data.actor, &ended);
+}
+
+/// End the effects on the target which don't survive being hurt by whoever
+/// applied them, or by one of their allies, e.g. Charm Person
+pub fn end_effects_on_damage(world: &mut World, target: Entity, attacker: Entity) {
+    let ended: Vec<EffectId> = effects(world, target)
+        .iter()
+        .filter(|effect| effect.effect().end_on_damage)
+        .filter(|effect| {
+            effect.applier.is_some_and(|applier| {
+                applier == attacker
+                    || systems::factions::attitude_from_to(world, applier, attacker)
+                        == Attitude::Friendly
+            })
+        })
+        .map(|effect| effect.effect_id.clone())
+        .collect();
+    remove_effects(world, target, &ended);
+}
@@ -8,6 +8,7 @@ use crate::{
         id::FactionId,
     },
     registry::registry::FactionsRegistry,
+    systems,
 };
 
 pub fn get_faction(faction_id: &FactionId) -> &Faction {
@@ -18,6 +19,15 @@ pub fn get_faction(faction_id: &FactionId) -> &Faction {
 }
 
 pub fn attitude_from_to(world: &World, source: Entity, destination: Entity) -> Attitude {
+    // A controlled creature sides with its controller for as long as it lasts,
+    // and everyone else treats it the way they treat the controller
+    let source = systems::control::controller_of(world, source).unwrap_or(source);
+    let destination = systems::control::controller_of(world, destination).unwrap_or(destination);
+    own_attitude_from_to(world, source, destination)
+}
+
+/// The attitude of the source based on its own factions and overrides
+fn own_attitude_from_to(world: &World, source: Entity, destination: Entity) -> Attitude {
     if source == destination {
         return Attitude::Friendly;
     }
@@ -91,3 +101,12 @@ pub fn perceived_threat(world: &World, viewer: Entity, other: Entity) -> Attitud
 pub fn mutual_attitude(world: &World, a: Entity, b: Entity) -> Attitude {
     attitude_from_to(world, a, b).max(attitude_from_to(world, b, a))
 }
+
+/// The entities among `others` that the entity considers its allies
+pub fn allies(world: &World, entity: Entity, others: &[Entity]) -> Vec<Entity> {
+    others
+        .iter()
+        .copied()
+        .filter(|other| attitude_from_to(world, entity, *other) == Attitude::Friendly)
+        .collect()
+}
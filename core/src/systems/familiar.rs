@@ -0,0 +1,282 @@
+use hecs::{Entity, World};
+use parry3d::na::Point3;
+use tracing::debug;
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    components::{
+        actions::{
+            action::ActionContext,
+            targeting::{TargetInstance, TargetingRange},
+        },
+        faction::FactionSet,
+        familiar::{Familiar, Steed},
+        id::{ActionId, EffectId, MonsterId, ResourceId},
+        resource::{ResourceAmount, ResourceAmountMap},
+    },
+    engine::geometry::WorldGeometry,
+    entities::monster::MonsterTemplate,
+    registry::registry::MonstersRegistry,
+    systems::{self, geometry::CreaturePose},
+};
+
+/// How far away the familiar can be from its owner and still deliver touch
+/// spells for it
+const DELIVERY_RANGE_FEET: f32 = 100.0;
+/// How far away from its owner a dismissed familiar can reappear
+const RECALL_RANGE_FEET: f32 = 30.0;
+/// Spells with a range up to this are considered touch spells
+const TOUCH_RANGE_FEET: f32 = 5.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FamiliarError {
+    UnknownForm(MonsterId),
+    NotAFamiliar(Entity),
+    AlreadyDismissed(Entity),
+    NotDismissed(Entity),
+    OutOfRange { distance: Length, max_range: Length },
+}
+
+pub fn shared_senses_action() -> ActionId {
+    ActionId::new("nat20_core", "action.familiar.shared_senses")
+}
+
+pub fn shared_senses_effect() -> EffectId {
+    EffectId::new("nat20_core", "effect.familiar.shared_senses")
+}
+
+/// Summon a familiar for the owner at the given position. A creature can only
+/// have one familiar at a time, so any previous familiar disappears.
+pub fn summon(
+    world: &mut World,
+    owner: Entity,
+    form: &MonsterId,
+    position: &Point3<f32>,
+) -> Result<Entity, FamiliarError> {
+    let Some(template) = MonstersRegistry::get(form) else {
+        return Err(FamiliarError::UnknownForm(form.clone()));
+    };
+
+    if let Some(previous) = familiar(world, owner) {
+        debug!("{:?} replaces its familiar {:?}", owner, previous);
+        let _ = world.despawn(previous);
+    }
+
+    let familiar = spawn_companion(world, owner, template, position);
+    systems::helpers::set_component(world, familiar, Familiar::new(owner));
+
+    systems::actions::add_actions(world, owner, &[shared_senses_action()]);
+
+    debug!("{:?} summons {:?} as its familiar", owner, familiar);
+    Ok(familiar)
+}
+
+/// Summon a steed for the owner at the given position. Like familiars, a
+/// creature can only have one steed at a time.
+pub fn summon_steed(
+    world: &mut World,
+    owner: Entity,
+    form: &MonsterId,
+    position: &Point3<f32>,
+) -> Result<Entity, FamiliarError> {
+    let Some(template) = MonstersRegistry::get(form) else {
+        return Err(FamiliarError::UnknownForm(form.clone()));
+    };
+
+    if let Some(previous) = steed(world, owner) {
+        debug!("{:?} replaces its steed {:?}", owner, previous);
+        let _ = world.despawn(previous);
+    }
+
+    let steed = spawn_companion(world, owner, template, position);
+    systems::helpers::set_component(world, steed, Steed { owner });
+
+    debug!("{:?} summons {:?} as its steed", owner, steed);
+    Ok(steed)
+}
+
+/// Companions are on the same side as their owner
+fn spawn_companion(
+    world: &mut World,
+    owner: Entity,
+    template: &MonsterTemplate,
+    position: &Point3<f32>,
+) -> Entity {
+    let companion = systems::generator::spawn_monster(world, template).id();
+    let factions = systems::helpers::get_component_clone::<FactionSet>(world, owner);
+    systems::helpers::set_component(world, companion, factions);
+    systems::geometry::teleport_to(world, companion, position);
+    companion
+}
+
+/// The steed of the owner, if it has one
+pub fn steed(world: &World, owner: Entity) -> Option<Entity> {
+    world
+        .query::<&Steed>()
+        .iter()
+        .find(|(_, steed)| steed.owner == owner)
+        .map(|(entity, _)| entity)
+}
+
+/// The familiar of the owner, if it has one
+pub fn familiar(world: &World, owner: Entity) -> Option<Entity> {
+    world
+        .query::<&Familiar>()
+        .iter()
+        .find(|(_, familiar)| familiar.owner == owner)
+        .map(|(entity, _)| entity)
+}
+
+/// The owner of the familiar, or `None` if the entity isn't a familiar
+pub fn owner(world: &World, familiar: Entity) -> Option<Entity> {
+    world
+        .get::<&Familiar>(familiar)
+        .ok()
+        .map(|familiar| familiar.owner)
+}
+
+pub fn is_familiar(world: &World, entity: Entity) -> bool {
+    world.get::<&Familiar>(entity).is_ok()
+}
+
+pub fn is_dismissed(world: &World, entity: Entity) -> bool {
+    world
+        .get::<&Familiar>(entity)
+        .is_ok_and(|familiar| familiar.dismissed)
+}
+
+/// Send the familiar to a pocket dimension, removing it from the world until
+/// it's recalled
+pub fn dismiss(world: &mut World, familiar: Entity) -> Result<(), FamiliarError> {
+    {
+        let mut component = world
+            .get::<&mut Familiar>(familiar)
+            .map_err(|_| FamiliarError::NotAFamiliar(familiar))?;
+        if component.dismissed {
+            return Err(FamiliarError::AlreadyDismissed(familiar));
+        }
+        component.dismissed = true;
+    }
+
+    let _ = world.remove_one::<CreaturePose>(familiar);
+    debug!("{:?} is dismissed to a pocket dimension", familiar);
+    Ok(())
+}
+
+/// Bring a dismissed familiar back at the given position, which has to be
+/// within 30 feet of its owner
+pub fn recall(
+    world: &mut World,
+    familiar: Entity,
+    position: &Point3<f32>,
+) -> Result<(), FamiliarError> {
+    let owner = {
+        let component = world
+            .get::<&Familiar>(familiar)
+            .map_err(|_| FamiliarError::NotAFamiliar(familiar))?;
+        if !component.dismissed {
+            return Err(FamiliarError::NotDismissed(familiar));
+        }
+        component.owner
+    };
+
+    if let Some(owner_position) = systems::geometry::get_foot_position(world, owner) {
+        let distance = Length::new::<meter>((position - owner_position).norm());
+        let max_range = Length::new::<foot>(RECALL_RANGE_FEET);
+        if distance > max_range {
+            return Err(FamiliarError::OutOfRange {
+                distance,
+                max_range,
+            });
+        }
+    }
+
+    systems::helpers::get_component_mut::<Familiar>(world, familiar).dismissed = false;
+    systems::helpers::set_component(
+        world,
+        familiar,
+        CreaturePose::translation(position.x, position.y, position.z),
+    );
+    debug!("{:?} is recalled from its pocket dimension", familiar);
+    Ok(())
+}
+
+/// Familiars can't attack, but they can take other actions
+pub fn can_take_action(world: &World, entity: Entity, action_id: &ActionId) -> bool {
+    if !is_familiar(world, entity) {
+        return true;
+    }
+
+    match systems::actions::get_action(action_id) {
+        Some(action) => !action.kind.is_attack(),
+        None => true,
+    }
+}
+
+/// The familiar which can deliver a touch spell for its owner. This is the case
+/// when the owner can't reach the targets itself, but its familiar is within
+/// 100 feet of it, can reach the targets and has its reaction available, which
+/// is spent to deliver the spell.
+pub fn touch_spell_deliverer(
+    world: &World,
+    world_geometry: &WorldGeometry,
+    owner: Entity,
+    action_id: &ActionId,
+    context: &ActionContext,
+    targets: &[TargetInstance],
+) -> Option<Entity> {
+    if !matches!(context.base(), ActionContext::Spell { .. }) {
+        return None;
+    }
+
+    let targeting = systems::actions::targeting_context(world, owner, action_id, context);
+    if targeting.range.max() > TargetingRange::new::<foot>(TOUCH_RANGE_FEET).max()
+        || targeting
+            .validate_targets(world, world_geometry, owner, targets)
+            .is_ok()
+    {
+        return None;
+    }
+
+    let familiar = familiar(world, owner)?;
+    if is_dismissed(world, familiar) || !systems::health::is_alive(world, familiar) {
+        return None;
+    }
+
+    let distance = systems::geometry::distance_between_entities(world, owner, familiar)?;
+    if distance > Length::new::<foot>(DELIVERY_RANGE_FEET) {
+        return None;
+    }
+
+    if !systems::resources::can_afford(world, familiar, &delivery_cost()).0 {
+        return None;
+    }
+
+    targeting
+        .validate_targets(world, world_geometry, familiar, targets)
+        .ok()?;
+
+    Some(familiar)
+}
+
+pub fn delivery_cost() -> ResourceAmountMap {
+    ResourceAmountMap::from([(
+        ResourceId::new("nat20_core", "resource.reaction"),
+        ResourceAmount::Flat(1),
+    )])
+}
+
+/// The familiar the owner is currently seeing through, if any
+pub fn shared_senses(world: &World, owner: Entity) -> Option<Entity> {
+    let sharing = systems::effects::effects(world, owner)
+        .iter()
+        .any(|effect| effect.effect_id == shared_senses_effect());
+    if !sharing {
+        return None;
+    }
+
+    familiar(world, owner).filter(|familiar| !is_dismissed(world, *familiar))
+}
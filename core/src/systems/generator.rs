@@ -0,0 +1,205 @@
+use hecs::{Entity, World};
+use rand::seq::{IndexedRandom, IteratorRandom};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    components::{
+        health::hit_points::HitPoints,
+        id::{ClassId, EntityIdentifier, ItemId, Name, SpeciesId},
+        items::{
+            equipment::{
+                armor::ArmorTrainingSet, loadout::TryEquipError, weapon::WeaponProficiencyMap,
+            },
+            inventory::ItemInstance,
+        },
+        level::Level,
+        modifier::ModifierSource,
+        personality::Personality,
+        proficiency::{Proficiency, ProficiencyLevel},
+        species::{CreatureSize, CreatureType},
+    },
+    entities::monster::{Monster, MonsterTemplate},
+    registry::{
+        self,
+        registry::{ItemsRegistry, MonstersRegistry},
+    },
+    systems::{self, level_up::LevelUpError},
+};
+
+/// Number of personality tags rolled for each generated monster
+const PERSONALITY_TAGS: usize = 2;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonsterFilter {
+    pub min_challenge_rating: Option<u8>,
+    pub max_challenge_rating: Option<u8>,
+    pub creature_type: Option<CreatureType>,
+    pub size: Option<CreatureSize>,
+}
+
+impl MonsterFilter {
+    /// A single monster of a challenge rating between half the party level and
+    /// the party level makes for a reasonable fight.
+    pub fn for_party_level(party_level: u8) -> Self {
+        Self {
+            min_challenge_rating: Some((party_level / 2).max(1)),
+            max_challenge_rating: Some(party_level.max(1)),
+            creature_type: None,
+            size: None,
+        }
+    }
+
+    pub fn matches(&self, template: &MonsterTemplate) -> bool {
+        let challenge_rating = template.challenge_rating.total_level();
+        self.min_challenge_rating
+            .is_none_or(|min| challenge_rating >= min)
+            && self
+                .max_challenge_rating
+                .is_none_or(|max| challenge_rating <= max)
+            && self
+                .creature_type
+                .as_ref()
+                .is_none_or(|creature_type| template.creature_type == *creature_type)
+            && self.size.as_ref().is_none_or(|size| template.size == *size)
+    }
+}
+
+/// NPCs which are built like player characters, with a species and class
+/// levels, rather than from a monster stat block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NpcFilter {
+    /// The species of the NPC, or a random one if not set
+    pub species: Option<SpeciesId>,
+    /// The class of the NPC, or a random one if not set
+    pub class: Option<ClassId>,
+    pub level: u8,
+}
+
+impl Default for NpcFilter {
+    fn default() -> Self {
+        Self {
+            species: None,
+            class: None,
+            level: 1,
+        }
+    }
+}
+
+pub fn random_template(filter: &MonsterFilter) -> Option<&'static MonsterTemplate> {
+    MonstersRegistry::values()
+        .filter(|template| filter.matches(template))
+        .choose(&mut rand::rng())
+}
+
+/// Generate a random monster matching the filter and spawn it into the world
+pub fn generate_monster(world: &mut World, filter: &MonsterFilter) -> Option<EntityIdentifier> {
+    let Some(template) = random_template(filter) else {
+        warn!("No monster templates match filter {:?}", filter);
+        return None;
+    };
+    Some(spawn_monster(world, template))
+}
+
+/// Generate an NPC with class levels matching the filter and spawn it into the
+/// world. See `quick_build` for how the choices along the way are made.
+pub fn generate_npc(
+    world: &mut World,
+    name: Name,
+    filter: &NpcFilter,
+) -> Result<EntityIdentifier, LevelUpError> {
+    let character = systems::quick_build::quick_build(
+        name.clone(),
+        filter.class.clone(),
+        filter.species.clone(),
+        filter.level.max(1),
+    )?;
+    let entity = world.spawn(character);
+    debug!("Generated NPC {:?} from {:?}", entity, filter);
+    Ok(EntityIdentifier::new(entity, name))
+}
+
+pub fn spawn_monster(world: &mut World, template: &MonsterTemplate) -> EntityIdentifier {
+    let mut rng = rand::rng();
+
+    let name = match template.names.choose(&mut rng) {
+        Some(name) => Name::new(format!("{} ({})", name, template.name.as_str())),
+        None => template.name.clone(),
+    };
+
+    let hit_points = template.hit_points.roll().subtotal.max(1) as u32;
+
+    let mut monster = Monster::new(
+        name.clone(),
+        registry::ai::RANDOM_CONTROLLER_ID.clone(),
+        template.challenge_rating.clone(),
+        HitPoints::new(hit_points),
+        template.size.clone(),
+        template.creature_type.clone(),
+        template.speed.clone(),
+        template.abilities.clone(),
+        template.factions.clone(),
+    );
+    monster.personality = Personality::new(
+        template
+            .personality
+            .choose_multiple(&mut rng, PERSONALITY_TAGS)
+            .cloned()
+            .collect(),
+    );
+    monster.images = template.images.clone();
+    if let Some(innate_spellcasting) = &template.innate_spellcasting {
+        monster
+            .spellbook
+            .set_innate_spellcasting(innate_spellcasting.clone());
+    }
+
+    let entity = world.spawn(monster);
+    // Remember the template, so e.g. encounter presets can spawn it again
+    systems::helpers::set_component(world, entity, template.id.clone());
+    debug!("Spawned {:?} from template {}", entity, template.id);
+
+    if let Err(error) = equip_monster(world, entity, &template.equipment) {
+        warn!(
+            "Failed to equip monster generated from {}: {:?}",
+            template.id, error
+        );
+    }
+
+    for item in systems::loot::roll(&template.loot) {
+        systems::inventory::add_item(world, entity, item);
+    }
+
+    EntityIdentifier::new(entity, name)
+}
+
+pub fn equip_monster(
+    world: &mut World,
+    entity: Entity,
+    item_ids: &[ItemId],
+) -> Result<(), TryEquipError> {
+    for item_id in item_ids {
+        let item = ItemsRegistry::get(item_id).unwrap().clone();
+        // Monsters are considered proficient with all their equipment
+        // so we can add proficiency for what they equip
+        match &item {
+            ItemInstance::Armor(armor) => {
+                systems::helpers::get_component_mut::<ArmorTrainingSet>(world, entity)
+                    .insert(armor.armor_type.clone());
+            }
+            ItemInstance::Weapon(weapon) => {
+                systems::helpers::get_component_mut::<WeaponProficiencyMap>(world, entity)
+                    .set_proficiency(
+                        weapon.category().clone(),
+                        Proficiency::new(ProficiencyLevel::Proficient, ModifierSource::None),
+                    );
+            }
+            _ => {}
+        }
+
+        systems::loadout::equip(world, entity, item)?;
+    }
+    Ok(())
+}
@@ -11,8 +11,13 @@ use polyanya::Coords;
 use uom::si::f32::Length;
 
 use crate::{
-    components::species::CreatureSize,
+    components::{actions::resolution::Cover, illusion::Illusion, species::CreatureSize},
     engine::geometry::{WorldGeometry, WorldPath},
+    entities::{
+        ground_item::GroundItemTag,
+        object::{ObjectMaterial, ObjectTag},
+    },
+    systems,
 };
 
 pub static EPSILON: f32 = 1e-4;
@@ -343,12 +348,14 @@ pub fn line_of_sight_entity_point(
     entity: Entity,
     point: Point3<f32>,
 ) -> LineOfSightResult {
+    let mut excluded = see_through_entities(world, entity);
+    excluded.push(entity);
     line_of_sight_entity_point_filter(
         world,
         world_geometry,
         entity,
         point,
-        &RaycastFilter::ExcludeCreatures(vec![entity]),
+        &RaycastFilter::ExcludeCreatures(excluded),
     )
 }
 
@@ -384,6 +391,10 @@ pub fn line_of_sight_entity_entity(
         };
     }
 
+    let mut excluded = see_through_entities(world, from_entity);
+    excluded.retain(|entity| *entity != to_entity);
+    excluded.push(from_entity);
+
     if let Some(from_eye_pos) = get_eye_position(world, from_entity)
         && let Some(to_eye_pos) = get_eye_position(world, to_entity)
         && let Some(result) = raycast_point_point(
@@ -391,7 +402,7 @@ pub fn line_of_sight_entity_entity(
             world_geometry,
             from_eye_pos,
             to_eye_pos,
-            &RaycastFilter::ExcludeCreatures(vec![from_entity]),
+            &RaycastFilter::ExcludeCreatures(excluded),
         )
         && let Some(closest) = result.closest()
     {
@@ -407,6 +418,149 @@ pub fn line_of_sight_entity_entity(
     }
 }
 
+/// Entities the viewer can see through, i.e. items on the ground, transparent
+/// objects such as windows and illusions the viewer knows are fake.
+fn see_through_entities(world: &World, viewer: Entity) -> Vec<Entity> {
+    world
+        .query::<(
+            Option<&GroundItemTag>,
+            Option<&ObjectMaterial>,
+            Option<&Illusion>,
+        )>()
+        .with::<&CreaturePose>()
+        .iter()
+        .filter(|(_, (ground_item, material, illusion))| {
+            ground_item.is_some()
+                || material.is_some_and(|material| material.is_transparent())
+                || illusion.is_some_and(|illusion| illusion.is_disbelieved_by(viewer))
+        })
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Entities which don't block line of effect. Creatures might provide cover,
+/// but only objects (including transparent ones) actually block the path.
+/// Illusions never do, whether anyone believes in them or not.
+fn non_solid_entities(world: &World) -> Vec<Entity> {
+    world
+        .query::<(
+            Option<&GroundItemTag>,
+            Option<&ObjectTag>,
+            Option<&Illusion>,
+        )>()
+        .with::<&CreaturePose>()
+        .iter()
+        .filter(|(_, (ground_item, object, illusion))| {
+            ground_item.is_some() || object.is_none() || illusion.is_some()
+        })
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Whether there's an unobstructed path between two points or entities. Unlike
+/// line of sight, a window doesn't let effects through, even though you can see
+/// what's on the other side of it.
+#[derive(Debug, Clone)]
+pub struct LineOfEffectResult {
+    pub has_line_of_effect: bool,
+    pub raycast_result: Option<RaycastResult>,
+}
+
+pub fn line_of_effect_entity_point(
+    world: &World,
+    world_geometry: &WorldGeometry,
+    entity: Entity,
+    point: Point3<f32>,
+) -> LineOfEffectResult {
+    let mut excluded = non_solid_entities(world);
+    excluded.push(entity);
+    let result = line_of_sight_entity_point_filter(
+        world,
+        world_geometry,
+        entity,
+        point,
+        &RaycastFilter::ExcludeCreatures(excluded),
+    );
+    LineOfEffectResult {
+        has_line_of_effect: result.has_line_of_sight,
+        raycast_result: result.raycast_result,
+    }
+}
+
+pub fn line_of_effect_entity_entity(
+    world: &World,
+    world_geometry: &WorldGeometry,
+    from_entity: Entity,
+    to_entity: Entity,
+) -> LineOfEffectResult {
+    if from_entity == to_entity {
+        return LineOfEffectResult {
+            has_line_of_effect: true,
+            raycast_result: None,
+        };
+    }
+
+    let mut excluded = non_solid_entities(world);
+    excluded.retain(|entity| *entity != to_entity);
+    excluded.push(from_entity);
+
+    if let Some(from_eye_pos) = get_eye_position(world, from_entity)
+        && let Some(to_eye_pos) = get_eye_position(world, to_entity)
+        && let Some(result) = raycast_point_point(
+            world,
+            world_geometry,
+            from_eye_pos,
+            to_eye_pos,
+            &RaycastFilter::ExcludeCreatures(excluded),
+        )
+        && let Some(closest) = result.closest()
+    {
+        LineOfEffectResult {
+            has_line_of_effect: closest.kind == RaycastHitKind::Creature(to_entity),
+            raycast_result: Some(result),
+        }
+    } else {
+        LineOfEffectResult {
+            has_line_of_effect: false,
+            raycast_result: None,
+        }
+    }
+}
+
+/// How much of the target is hidden from the point, e.g. the center of a
+/// Fireball or the eyes of an archer. Like line of effect, only the world and
+/// objects get in the way, and the target is checked at a few heights from its
+/// feet to the top of its head, so a low wall only covers its legs.
+pub fn cover(
+    world: &World,
+    world_geometry: &WorldGeometry,
+    from: Point3<f32>,
+    target: Entity,
+) -> Cover {
+    let (Some(foot_position), Some(height)) =
+        (get_foot_position(world, target), get_height(world, target))
+    else {
+        return Cover::None;
+    };
+
+    let filter = RaycastFilter::ExcludeCreatures(non_solid_entities(world));
+    let samples = 4;
+    let blocked = (0..samples)
+        .filter(|sample| {
+            let sample_height = height * (*sample as f32 + 0.5) / samples as f32;
+            let point = foot_position + Vector3::y() * sample_height;
+            !line_of_sight_point_point(world, world_geometry, from, point, &filter)
+                .has_line_of_sight
+        })
+        .count();
+
+    match blocked * 4 / samples {
+        0 | 1 => Cover::None,
+        2 => Cover::Half,
+        _ => Cover::ThreeQuarters,
+    }
+}
+
 pub fn ground_position(
     world_geometry: &WorldGeometry,
     position: &Point3<f32>,
@@ -432,8 +586,11 @@ pub fn distance_between_entities(
 ) -> Option<Length> {
     let pos_a = get_foot_position(world, entity_a)?;
     let pos_b = get_foot_position(world, entity_b)?;
-    let distance = (pos_b - pos_a).magnitude();
-    Some(Length::new::<uom::si::length::meter>(distance))
+    let distance = Length::new::<uom::si::length::meter>((pos_b - pos_a).magnitude());
+    // Creatures larger than a single square can be reached from further away
+    let offset =
+        systems::size::space_offset(world, entity_a) + systems::size::space_offset(world, entity_b);
+    Some((distance - offset).max(Length::new::<uom::si::length::meter>(0.0)))
 }
 
 pub fn teleport_to(world: &mut World, entity: Entity, new_position: &Point3<f32>) {
@@ -510,7 +667,12 @@ pub fn entities_in_shape(
 ) -> Vec<Entity> {
     let mut entities = vec![];
 
-    for (entity, _) in world.query::<&CreaturePose>().iter() {
+    // Items on the ground can't be caught in areas or react to anything
+    for (entity, _) in world
+        .query::<&CreaturePose>()
+        .without::<&GroundItemTag>()
+        .iter()
+    {
         if let Some((creature_shape, creature_shape_pose)) = get_shape(world, entity) {
             let intersects = parry3d::query::intersection_test(
                 shape_pose,
@@ -0,0 +1,266 @@
+use std::collections::BTreeSet;
+
+use hecs::{Entity, World};
+use tracing::info;
+use uom::si::{f32::Length, length::foot};
+
+use crate::{
+    components::{
+        damage::{
+            DamageComponentResult, DamageMitigationResult, DamageRollResult, DamageSource,
+            DamageType,
+        },
+        dice::{DiceSetRollResult, DieSize},
+        health::life_state::LifeState,
+        id::{EffectId, ItemId},
+        items::{
+            inventory::{ItemContainer, ItemInstance},
+            item::ItemRarity,
+        },
+        modifier::{ModifierSet, ModifierSource},
+        surface::{SURFACE_DC_DEFAULT, Surface, SurfaceKind},
+    },
+    engine::game_state::GameState,
+    registry::registry::{EffectsRegistry, ItemsRegistry},
+    systems,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GmError {
+    UnknownItem(ItemId),
+    UnknownEffect(EffectId),
+    /// Only weapons can be enchanted
+    NotAWeapon(ItemId),
+    EffectNotActive(EffectId),
+    NoMagicItems(ItemRarity),
+    /// The entity isn't anywhere in the world, e.g. a dismissed familiar
+    NoPosition(Entity),
+}
+
+/// Radius of the surfaces created by the GM, in feet
+const SURFACE_RADIUS: f32 = 10.0;
+
+/// Source of everything the GM adds to a creature. The GM overrides bypass the
+/// rules and the action economy, e.g. when used from the GM console.
+pub fn source() -> ModifierSource {
+    ModifierSource::Custom("GM".to_string())
+}
+
+/// Deal a flat amount of damage of a single type, which still goes through the
+/// resistances and effects of the target
+pub fn damage(
+    game_state: &mut GameState,
+    target: Entity,
+    amount: u32,
+    damage_type: DamageType,
+) -> (Option<DamageMitigationResult>, Option<LifeState>) {
+    let mut modifiers = ModifierSet::new();
+    modifiers.add_modifier(source(), amount as i32);
+    let damage_roll_result = DamageRollResult {
+        components: vec![DamageComponentResult {
+            damage_type,
+            result: DiceSetRollResult {
+                die_size: DieSize::D4,
+                rolls: Vec::new(),
+                modifiers,
+                subtotal: amount as i32,
+            },
+            tags: BTreeSet::new(),
+        }],
+        source: DamageSource::default(),
+        total: amount as i32,
+        action: None,
+        crit: false,
+    };
+
+    info!("GM deals {} {} damage to {:?}", amount, damage_type, target);
+    systems::health::damage(game_state, target, &damage_roll_result, None)
+}
+
+/// Put a copy of the item from the registry into the inventory of the entity,
+/// optionally as a +N weapon. Returns the name of the item that was given.
+pub fn give_item(
+    world: &mut World,
+    entity: Entity,
+    item_id: &ItemId,
+    enchantment: Option<u32>,
+) -> Result<String, GmError> {
+    let Some(item) = ItemsRegistry::get(item_id) else {
+        return Err(GmError::UnknownItem(item_id.clone()));
+    };
+
+    let item = match (item.clone(), enchantment) {
+        (item, None) => item,
+        (ItemInstance::Weapon(weapon), Some(level)) => {
+            ItemInstance::Weapon(weapon.with_enchantment(level))
+        }
+        (_, Some(_)) => return Err(GmError::NotAWeapon(item_id.clone())),
+    };
+
+    let name = item.item().name.clone();
+    info!("GM gives {} to {:?}", name, entity);
+    systems::inventory::add_item(world, entity, item);
+    Ok(name)
+}
+
+/// Roll a random magic item of the rarity and put it into the inventory of the
+/// entity. Returns the name of the item that was given.
+pub fn give_magic_item(
+    world: &mut World,
+    entity: Entity,
+    rarity: &ItemRarity,
+) -> Result<String, GmError> {
+    let Some(item) = systems::loot::roll_magic_item(rarity) else {
+        return Err(GmError::NoMagicItems(rarity.clone()));
+    };
+
+    let name = item.item().name.clone();
+    info!("GM gives {} to {:?}", name, entity);
+    systems::inventory::add_item(world, entity, item);
+    Ok(name)
+}
+
+pub fn add_effect(world: &mut World, entity: Entity, effect_id: &EffectId) -> Result<(), GmError> {
+    if EffectsRegistry::get(effect_id).is_none() {
+        return Err(GmError::UnknownEffect(effect_id.clone()));
+    }
+
+    info!("GM adds effect {} to {:?}", effect_id, entity);
+    systems::effects::add_permanent_effect(world, entity, effect_id.clone(), &source(), None);
+    Ok(())
+}
+
+pub fn remove_effect(
+    world: &mut World,
+    entity: Entity,
+    effect_id: &EffectId,
+) -> Result<(), GmError> {
+    if EffectsRegistry::get(effect_id).is_none() {
+        return Err(GmError::UnknownEffect(effect_id.clone()));
+    }
+    if !systems::effects::effects(world, entity)
+        .iter()
+        .any(|effect| effect.effect_id == *effect_id)
+    {
+        return Err(GmError::EffectNotActive(effect_id.clone()));
+    }
+
+    info!("GM removes effect {} from {:?}", effect_id, entity);
+    systems::effects::remove_effect(world, entity, effect_id);
+    Ok(())
+}
+
+/// Cover the ground around the feet of the entity in a surface, e.g. to set up
+/// an environmental hazard. The surface lasts until it's cleared.
+pub fn create_surface(
+    game_state: &mut GameState,
+    entity: Entity,
+    kind: SurfaceKind,
+) -> Result<(), GmError> {
+    let Some(position) = systems::geometry::get_foot_position(&game_state.world, entity) else {
+        return Err(GmError::NoPosition(entity));
+    };
+
+    info!("GM creates {} surface at {:?}", kind, position);
+    systems::surfaces::create(
+        game_state,
+        Surface::new(
+            kind,
+            position,
+            Length::new::<foot>(SURFACE_RADIUS),
+            SURFACE_DC_DEFAULT,
+            None,
+            None,
+        ),
+    );
+    Ok(())
+}
+
+/// Remove every surface under the feet of the entity, returning how many were
+/// removed
+pub fn clear_surfaces(game_state: &mut GameState, entity: Entity) -> Result<usize, GmError> {
+    let Some(position) = systems::geometry::get_foot_position(&game_state.world, entity) else {
+        return Err(GmError::NoPosition(entity));
+    };
+
+    info!("GM clears surfaces at {:?}", position);
+    Ok(systems::surfaces::remove_at(game_state, &position, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{components::health::hit_points::HitPoints, test_utils::fixtures};
+
+    use super::*;
+
+    #[test]
+    fn gm_damage() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let hp_before =
+            systems::helpers::get_component::<HitPoints>(&game_state.world, fighter).current();
+
+        let (mitigation, _) = damage(&mut game_state, fighter, 5, DamageType::Fire);
+
+        assert_eq!(mitigation.unwrap().total, 5);
+        assert_eq!(
+            systems::helpers::get_component::<HitPoints>(&game_state.world, fighter).current(),
+            hp_before - 5
+        );
+    }
+
+    #[test]
+    fn gm_give_enchanted_weapon() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+
+        let longsword = ItemId::new("nat20_core", "item.longsword");
+        let name = give_item(&mut game_state.world, fighter, &longsword, Some(1)).unwrap();
+        assert_eq!(name, "Longsword +1");
+
+        let robe = ItemId::new("nat20_core", "item.robe");
+        assert_eq!(
+            give_item(&mut game_state.world, fighter, &robe, Some(1)),
+            Err(GmError::NotAWeapon(robe))
+        );
+
+        let unknown = ItemId::new("nat20_core", "item.does_not_exist");
+        assert_eq!(
+            give_item(&mut game_state.world, fighter, &unknown, None),
+            Err(GmError::UnknownItem(unknown))
+        );
+    }
+
+    #[test]
+    fn gm_add_and_remove_effect() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let poisoned = EffectId::new("nat20_core", "effect.condition.poisoned");
+
+        add_effect(&mut game_state.world, fighter, &poisoned).unwrap();
+        assert!(
+            systems::effects::effects(&game_state.world, fighter)
+                .iter()
+                .any(|effect| effect.effect_id == poisoned)
+        );
+
+        remove_effect(&mut game_state.world, fighter, &poisoned).unwrap();
+        assert_eq!(
+            remove_effect(&mut game_state.world, fighter, &poisoned),
+            Err(GmError::EffectNotActive(poisoned))
+        );
+    }
+
+    #[test]
+    fn gm_create_and_clear_surface() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+
+        create_surface(&mut game_state, fighter, SurfaceKind::Acid).unwrap();
+        assert_eq!(game_state.surfaces.len(), 1);
+        assert_eq!(game_state.surfaces[0].kind, SurfaceKind::Acid);
+
+        assert_eq!(clear_surfaces(&mut game_state, fighter), Ok(1));
+        assert!(game_state.surfaces.is_empty());
+    }
+}
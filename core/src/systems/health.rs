@@ -1,42 +1,157 @@
 use std::{cmp::max, ops::Deref, sync::Arc};
 
 use hecs::{Entity, World};
+use rand::Rng;
 use tracing::debug;
 
 use crate::{
     components::{
         ability::{Ability, AbilityScoreMap},
+        ai::PlayerControlledTag,
         d20::D20CheckDC,
-        damage::{AttackRollResult, DamageMitigationResult, DamageResistances, DamageRollResult},
+        damage::{
+            AttackRollResult, DamageMitigationResult, DamageResistances, DamageRollResult,
+            DamageSource, DamageThreshold,
+        },
         effects::{
-            effect::{EffectInstance, EffectLifetime},
+            effect::{
+                EffectInstance, EffectInstanceTemplate, EffectLifetime, EffectLifetimeEntiy,
+                EffectLifetimeTemplate,
+            },
             hooks::DeathHook,
         },
-        health::{hit_points::HitPoints, life_state::LifeState},
+        health::{
+            healing::{HealingModifiers, HealingResult},
+            hit_points::HitPoints,
+            life_state::{LifeState, NonLethalIntent},
+            massive_damage::{MassiveDamageOutcome, SYSTEM_SHOCK_SAVING_THROW_DC, SystemShock},
+            resurrection::{
+                Corpse, DeathTimer, RETURN_FROM_DEATH_EFFECTS, Resurrection, ResurrectionError,
+                RevivedHitPoints,
+            },
+        },
+        id::{EffectId, ResourceId},
+        items::equipment::weapon::WeaponKind,
         level::CharacterLevels,
         modifier::{Modifiable, ModifierSet, ModifierSource},
+        resource::{ResourceAmount, ResourceAmountMap},
         saving_throw::SavingThrowKind,
         spells::{spell::CONCENTRATION_SAVING_THROW_DC_DEFAULT, spellbook::Spellbook},
+        time::{TimeDuration, TurnBoundary},
     },
     engine::{
-        event::{CallbackResult, EventCallback, EventKind},
+        event::{CallbackResult, Event, EventCallback, EventKind},
         game_state::GameState,
+        rules::OptionalRule,
     },
-    entities::{character::CharacterTag, monster::MonsterTag},
+    entities::{character::CharacterTag, monster::MonsterTag, object::ObjectTag},
     registry::registry::ClassesRegistry,
     systems::{self, d20::D20CheckDCKind},
 };
 
 pub fn heal(world: &mut World, target: Entity, amount: u32) -> Option<LifeState> {
-    if let Ok(mut hit_points) = world.get::<&mut HitPoints>(target) {
+    receive_healing(world, target, amount).1
+}
+
+/// Move the entity into the given life state, and keep the death timer and the
+/// dropped loot of dead creatures up to date. The state is written as given, so
+/// death saving throws only start over if the caller passes fresh ones, e.g.
+/// `LifeState::unconscious()`. Returns the new life state, or `None` if it
+/// didn't change.
+pub fn set_life_state(
+    world: &mut World,
+    entity: Entity,
+    new_state: LifeState,
+) -> Option<LifeState> {
+    let mut life_state = world.get::<&mut LifeState>(entity).ok()?;
+    if *life_state == new_state {
+        return None;
+    }
+    debug!(
+        "Entity {:?} life state changed: {:?} -> {:?}",
+        entity, *life_state, new_state
+    );
+    let was_dead = is_dead_state(&life_state);
+    *life_state = new_state;
+    drop(life_state);
+
+    // Keep track of how long the creature has been dead for, since most
+    // resurrection magic only works within a certain time of death
+    match (was_dead, is_dead_state(&new_state)) {
+        (false, true) => {
+            let _ = world.insert_one(entity, DeathTimer::new());
+        }
+        (true, false) => {
+            let _ = world.remove_one::<DeathTimer>(entity);
+        }
+        _ => {}
+    }
+
+    // Dead monsters drop everything they had on them. Defeated characters
+    // hold on to their gear, since the party is usually quick to revive them.
+    if new_state == LifeState::Dead && !was_dead {
+        systems::inventory::drop_all(world, entity);
+    }
+
+    Some(new_state)
+}
+
+fn is_dead_state(life_state: &LifeState) -> bool {
+    matches!(life_state, LifeState::Dead | LifeState::Defeated)
+}
+
+/// Stabilize a dying creature, so it stops making death saving throws. Returns
+/// the new life state, or `None` if the creature wasn't dying.
+pub fn stabilize(world: &mut World, target: Entity) -> Option<LifeState> {
+    if !world
+        .get::<&LifeState>(target)
+        .is_ok_and(|life_state| matches!(*life_state, LifeState::Unconscious(_)))
+    {
+        return None;
+    }
+    set_life_state(world, target, LifeState::Stable)
+}
+
+/// Heal the target, taking its healing modifiers into account (e.g. Chill Touch
+/// blocking all healing). Returns the healing that was actually received along
+/// with the new life state, if it changed.
+pub fn receive_healing(
+    world: &mut World,
+    target: Entity,
+    amount: u32,
+) -> (HealingResult, Option<LifeState>) {
+    let healing = if let Ok(modifiers) = world.get::<&HealingModifiers>(target) {
+        modifiers.apply(amount)
+    } else {
+        HealingResult::unmodified(amount)
+    };
+    if !healing.modifiers.is_empty() {
+        debug!("Healing for entity {:?} modified: {}", target, healing);
+    }
+    let new_life_state = restore_hit_points(world, target, healing.amount);
+    (healing, new_life_state)
+}
+
+fn restore_hit_points(world: &mut World, target: Entity, amount: u32) -> Option<LifeState> {
+    // Healing can't bring back the dead, that takes resurrection magic
+    let can_regain = world
+        .get::<&LifeState>(target)
+        .is_ok_and(|life_state| !matches!(*life_state, LifeState::Dead | LifeState::Defeated));
+    if !can_regain {
+        return None;
+    }
+
+    let regained_consciousness = {
+        let mut hit_points = world.get::<&mut HitPoints>(target).ok()?;
         let hit_points_before = hit_points.current();
         hit_points.heal(amount);
-        if let Ok(mut life_state) = world.get::<&mut LifeState>(target) {
-            if hit_points.current() > 0 && hit_points_before == 0 {
-                *life_state = LifeState::Normal;
-                return Some(LifeState::Normal);
-            }
-        }
+        hit_points.current() > 0 && hit_points_before == 0
+    };
+
+    // A creature at 0 hit points that regains any hit points wakes up with
+    // the healed amount, and its death saving throws start over
+    if regained_consciousness {
+        return set_life_state(world, target, LifeState::Normal);
     }
     None
 }
@@ -48,102 +163,317 @@ pub fn heal_full(world: &mut World, target: Entity) -> Option<LifeState> {
     } else {
         None
     };
+    // Regaining all hit points (e.g. from a long rest) ignores healing modifiers
     if let Some(max) = hit_point_max {
-        return heal(world, target, max);
+        return restore_hit_points(world, target, max);
     }
     None
 }
 
-pub fn damage(
+/// Reduce the hit point maximum of the target, e.g. from a Wight's Life Drain.
+/// The reduction lasts until it is restored, typically by a long rest or Greater
+/// Restoration. A creature whose hit point maximum is reduced to 0 dies.
+pub fn reduce_max_hit_points(world: &mut World, target: Entity, amount: u32) -> Option<LifeState> {
+    let max = {
+        let mut hit_points = world.get::<&mut HitPoints>(target).ok()?;
+        hit_points.reduce_max(amount);
+        hit_points.max()
+    };
+
+    if max > 0 {
+        return None;
+    }
+
+    set_life_state(world, target, LifeState::Dead)
+}
+
+pub fn restore_max_hit_points(world: &mut World, target: Entity) {
+    if let Ok(mut hit_points) = world.get::<&mut HitPoints>(target) {
+        hit_points.restore_max();
+    }
+}
+
+/// Undo part of a reduction of the hit point maximum, e.g. when the effect which
+/// reduced it ends
+pub fn restore_max_hit_points_by(world: &mut World, target: Entity, amount: u32) {
+    if let Ok(mut hit_points) = world.get::<&mut HitPoints>(target) {
+        hit_points.restore_max_by(amount);
+    }
+}
+
+/// Bring a dead creature back to life. Fails if the creature has been dead for
+/// longer than the resurrection allows, or if death is permanent.
+pub fn resurrect(
     game_state: &mut GameState,
     target: Entity,
-    damage_roll_result: &DamageRollResult,
-    attack_roll: Option<&AttackRollResult>,
-) -> (Option<DamageMitigationResult>, Option<LifeState>) {
-    let resistances =
-        if let Ok(resistances) = game_state.world.get::<&mut DamageResistances>(target) {
-            resistances.deref().clone()
-        } else {
-            DamageResistances::new()
+    resurrection: &Resurrection,
+) -> Result<LifeState, ResurrectionError> {
+    let permadeath = game_state.rule_enabled(OptionalRule::Permadeath);
+    let world = &mut game_state.world;
+    if !world
+        .get::<&LifeState>(target)
+        .is_ok_and(|life_state| is_dead_state(&life_state))
+    {
+        return Err(ResurrectionError::NotDead);
+    }
+
+    if permadeath || world.get::<&Corpse>(target).is_ok() {
+        return Err(ResurrectionError::Permanent);
+    }
+
+    if let Ok(timer) = world.get::<&DeathTimer>(target)
+        && timer.elapsed().as_seconds() > resurrection.time_limit.as_seconds()
+    {
+        return Err(ResurrectionError::TimeLimitExceeded {
+            elapsed: timer.elapsed(),
+            limit: resurrection.time_limit,
+        });
+    }
+
+    if let Ok(mut hit_points) = world.get::<&mut HitPoints>(target) {
+        let amount = match resurrection.hit_points {
+            RevivedHitPoints::One => 1,
+            RevivedHitPoints::Full => hit_points.max(),
         };
+        hit_points.heal(amount);
+    }
+    set_life_state(world, target, LifeState::Normal);
 
-    let mut damage_roll_result = damage_roll_result.clone();
-    for effect in systems::effects::effects(&game_state.world, target).iter() {
-        (effect.effect().pre_damage_mitigation)(
-            &game_state.world,
+    if resurrection.penalty {
+        systems::effects::add_permanent_effect(
+            world,
             target,
-            effect,
-            &mut damage_roll_result,
+            EffectId::new(
+                "nat20_core",
+                RETURN_FROM_DEATH_EFFECTS[RETURN_FROM_DEATH_EFFECTS.len() - 1],
+            ),
+            &ModifierSource::Custom("Returned from Death".to_string()),
+            None,
         );
     }
 
+    debug!("Entity {:?} was brought back to life", target);
+    Ok(LifeState::Normal)
+}
+
+/// Count the time the dead have spent dead
+pub fn advance_death_timers(world: &mut World, duration: &TimeDuration) {
+    for (_, timer) in world.query_mut::<&mut DeathTimer>() {
+        timer.advance(duration);
+    }
+}
+
+/// The penalty from returning from death is reduced by one with every long rest
+pub fn recover_from_death(world: &mut World, entity: Entity) {
+    let Some(index) = RETURN_FROM_DEATH_EFFECTS.iter().position(|effect| {
+        systems::effects::has_effect(world, entity, &EffectId::new("nat20_core", *effect))
+    }) else {
+        return;
+    };
+
+    let current = EffectId::new("nat20_core", RETURN_FROM_DEATH_EFFECTS[index]);
+    if index == 0 {
+        systems::effects::remove_effect(world, entity, &current);
+        return;
+    }
+
+    // The smaller penalty replaces the current one
+    let source = systems::effects::effects(world, entity)
+        .iter()
+        .find(|effect| effect.effect_id == current)
+        .map(|effect| effect.source.clone())
+        .unwrap_or(ModifierSource::Custom("Returned from Death".to_string()));
+    systems::effects::add_permanent_effect(
+        world,
+        entity,
+        EffectId::new("nat20_core", RETURN_FROM_DEATH_EFFECTS[index - 1]),
+        &source,
+        None,
+    );
+}
+
+/// Under the permadeath rule the dead stay dead. Their remains become corpse
+/// objects, and characters leave the party. Returns the entities that were
+/// turned into corpses.
+pub fn convert_to_corpses(world: &mut World) -> Vec<Entity> {
+    let dead = world
+        .query::<&LifeState>()
+        .without::<&Corpse>()
+        .iter()
+        .filter(|(_, life_state)| is_dead_state(life_state))
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    for entity in &dead {
+        debug!("Entity {:?} is permanently dead", entity);
+        let _ = world.remove_one::<PlayerControlledTag>(*entity);
+        let _ = world.insert(*entity, (Corpse, ObjectTag));
+    }
+
+    dead
+}
+
+/// Declare whether the melee attacks of the entity knock its targets out
+/// instead of killing them
+pub fn set_non_lethal(world: &mut World, entity: Entity, non_lethal: bool) {
+    if non_lethal {
+        let _ = world.insert_one(entity, NonLethalIntent);
+    } else {
+        let _ = world.remove_one::<NonLethalIntent>(entity);
+    }
+}
+
+pub fn is_non_lethal(world: &World, entity: Entity) -> bool {
+    world.get::<&NonLethalIntent>(entity).is_ok()
+}
+
+/// Whether the damage comes from a melee attack made with non-lethal intent.
+/// Objects can't be knocked out, so they're destroyed as usual.
+fn is_knockout(world: &World, target: Entity, damage_roll_result: &DamageRollResult) -> bool {
+    matches!(
+        damage_roll_result.source,
+        DamageSource::Weapon(WeaponKind::Melee)
+    ) && world.get::<&ObjectTag>(target).is_err()
+        && damage_roll_result
+            .action
+            .as_ref()
+            .is_some_and(|(actor, _)| is_non_lethal(world, *actor))
+}
+
+/// Apply the resistances, effects and damage threshold of the target to the
+/// damage, without actually dealing it
+pub fn mitigate(
+    world: &World,
+    target: Entity,
+    damage_roll_result: &DamageRollResult,
+) -> DamageMitigationResult {
+    let resistances = if let Ok(resistances) = world.get::<&DamageResistances>(target) {
+        resistances.deref().clone()
+    } else {
+        DamageResistances::new()
+    };
+
+    let mut damage_roll_result = damage_roll_result.clone();
+    for effect in systems::effects::active_effects(world, target).iter() {
+        (effect.effect().pre_damage_mitigation)(world, target, effect, &mut damage_roll_result);
+    }
+
     let mut mitigation_result = resistances.apply(&damage_roll_result);
 
-    for effect in systems::effects::effects(&game_state.world, target).iter() {
-        (effect.effect().post_damage_mitigation)(&game_state.world, target, &mut mitigation_result);
-    }
-
-    let (damage_taken, killed_by_damage, mut new_life_state, removed_temp_hp_source) =
-        if let Ok((hit_points, life_state)) = game_state
-            .world
-            .query_one_mut::<(&mut HitPoints, &mut LifeState)>(target)
-        {
-            // Track any changes to the life state of the target
-            let mut new_life_state = None;
-            // Check if the target is already at 0 HP
-            let hp_before_damage = hit_points.current();
-            if hit_points.current() == 0 {
-                match life_state {
-                    LifeState::Stable => {
-                        new_life_state = Some(LifeState::unconscious());
-                    }
+    for effect in systems::effects::active_effects(world, target).iter() {
+        (effect.effect().post_damage_mitigation)(world, target, &mut mitigation_result);
+    }
 
-                    LifeState::Unconscious(death_saving_throws) => {
-                        if let Some(attack_roll) = attack_roll {
-                            if attack_roll.roll_result.is_crit {
-                                death_saving_throws.record_failure(2);
-                            } else {
-                                death_saving_throws.record_failure(1);
-                            }
+    if let Ok(damage_threshold) = world.get::<&DamageThreshold>(target) {
+        damage_threshold.apply(&mut mitigation_result);
+    }
+
+    mitigation_result
+}
+
+pub fn damage(
+    game_state: &mut GameState,
+    target: Entity,
+    damage_roll_result: &DamageRollResult,
+    attack_roll: Option<&AttackRollResult>,
+) -> (Option<DamageMitigationResult>, Option<LifeState>) {
+    let mitigation_result = mitigate(&game_state.world, target, damage_roll_result);
+
+    let (
+        damage_taken,
+        excess,
+        killed_by_damage,
+        instant_death,
+        mut new_life_state,
+        removed_temp_hp_source,
+    ) = if let Ok((hit_points, life_state)) = game_state
+        .world
+        .query_one_mut::<(&mut HitPoints, &mut LifeState)>(target)
+    {
+        // Track any changes to the life state of the target
+        let mut new_life_state = None;
+        // Check if the target is already at 0 HP
+        let hp_before_damage = hit_points.current();
+        if hit_points.current() == 0 {
+            match life_state {
+                LifeState::Stable => {
+                    new_life_state = Some(LifeState::unconscious());
+                }
+
+                LifeState::Unconscious(death_saving_throws) => {
+                    if let Some(attack_roll) = attack_roll {
+                        if attack_roll.roll_result.is_crit {
+                            death_saving_throws.record_failure(2);
                         } else {
                             death_saving_throws.record_failure(1);
                         }
-
-                        let next_state = death_saving_throws.next_state();
-                        if !matches!(next_state, LifeState::Unconscious(_)) {
-                            // If the next state is not still unconscious, we need to update it
-                            new_life_state = Some(next_state);
-                        }
+                    } else {
+                        death_saving_throws.record_failure(1);
                     }
 
-                    _ => {
-                        // Other valid states where HP would be zero are some form of
-                        // dead, so no-op
-                        // TODO: Validate that this is the case?
+                    let next_state = death_saving_throws.next_state();
+                    if !matches!(next_state, LifeState::Unconscious(_)) {
+                        // If the next state is not still unconscious, we need to update it
+                        new_life_state = Some(next_state);
                     }
                 }
+
+                _ => {
+                    // Other valid states where HP would be zero are some form of
+                    // dead, so no-op
+                    // TODO: Validate that this is the case?
+                }
             }
+        }
 
-            let damage_taken = mitigation_result.total.max(0) as u32;
+        let damage_taken = mitigation_result.total.max(0) as u32;
+        let excess = damage_taken
+            .saturating_sub(hp_before_damage + hit_points.temp().map_or(0, |temp| temp.amount()));
 
-            let removed_temp_hp = hit_points.damage(damage_taken);
-            debug!(
-                "Entity {:?} took {} damage (HP: {} -> {})",
-                target,
-                damage_taken,
-                hp_before_damage,
-                hit_points.current()
-            );
+        let removed_temp_hp = hit_points.damage(damage_taken);
+        debug!(
+            "Entity {:?} took {} damage (HP: {} -> {})",
+            target,
+            damage_taken,
+            hp_before_damage,
+            hit_points.current()
+        );
 
-            (
-                damage_taken,
-                hp_before_damage > 0 && hit_points.current() == 0,
-                new_life_state,
-                removed_temp_hp,
-            )
-        } else {
-            return (None, None);
-        };
+        // Damage left over after dropping to 0 hit points, including any
+        // damage taken while already at 0 hit points, kills outright if it's
+        // at least the hit point maximum
+        let instant_death = matches!(
+            life_state,
+            LifeState::Normal | LifeState::Unconscious(_) | LifeState::Stable
+        ) && hit_points.current() == 0
+            && excess >= hit_points.max();
+
+        (
+            damage_taken,
+            excess,
+            hp_before_damage > 0 && hit_points.current() == 0,
+            instant_death,
+            new_life_state,
+            removed_temp_hp,
+        )
+    } else {
+        return (None, None);
+    };
+
+    // A shapechanged creature reverts to its original form instead of dropping
+    // to 0 hit points, and any excess damage carries over to that form
+    let reverted =
+        killed_by_damage && systems::shapechange::revert(&mut game_state.world, target).is_some();
+    let killed_by_damage = if reverted {
+        let mut hit_points =
+            systems::helpers::get_component_mut::<HitPoints>(&mut game_state.world, target);
+        hit_points.damage(excess);
+        hit_points.current() == 0
+    } else {
+        killed_by_damage
+    };
+    // The original form is what matters for massive damage
+    let instant_death = instant_death && !reverted;
 
     if killed_by_damage {
         // Monsters and Characters 'die' differently
@@ -155,8 +485,18 @@ pub fn damage(
             new_life_state = Some(LifeState::unconscious());
         }
 
+        // Objects are destroyed outright
+        if game_state.world.get::<&ObjectTag>(target).is_ok() {
+            new_life_state = Some(LifeState::Dead);
+        }
+
+        if is_knockout(&game_state.world, target, &damage_roll_result) {
+            debug!("Entity {:?} was knocked out", target);
+            new_life_state = Some(LifeState::Stable);
+        }
+
         // Trigger death hooks and remove effects that are not permanent
-        let hooks = systems::effects::effects(&game_state.world, target)
+        let hooks = systems::effects::active_effects(&game_state.world, target)
             .iter()
             .map(|e| (e.clone(), e.effect().on_death.clone()))
             .collect::<Vec<(EffectInstance, DeathHook)>>();
@@ -193,9 +533,26 @@ pub fn damage(
         }
     }
 
-    if let Some(new_life_state) = new_life_state {
-        if let Ok(mut life_state) = game_state.world.get::<&mut LifeState>(target) {
-            *life_state = new_life_state;
+    // Creatures that are knocked out are spared, even by a massive blow
+    if instant_death && new_life_state != Some(LifeState::Stable) {
+        debug!("Entity {:?} was killed outright by massive damage", target);
+        new_life_state = if game_state.world.get::<&CharacterTag>(target).is_ok() {
+            Some(LifeState::Defeated)
+        } else {
+            Some(LifeState::Dead)
+        };
+        let _ = game_state.process_event(Event::new(EventKind::MassiveDamage {
+            entity: target,
+            outcome: MassiveDamageOutcome::InstantDeath,
+        }));
+    }
+
+    if let Some(state) = new_life_state {
+        set_life_state(&mut game_state.world, target, state);
+        // Breaking down a locked door is another way of getting through it
+        if state == LifeState::Dead && game_state.world.get::<&ObjectTag>(target).is_ok() {
+            let actor = damage_roll_result.action.as_ref().map(|(actor, _)| *actor);
+            systems::locks::on_destroyed(game_state, target, actor);
         }
     }
 
@@ -212,10 +569,11 @@ pub fn damage(
         }
     }
 
-    let is_concentrating = {
-        let spellbook = systems::helpers::get_component::<Spellbook>(&game_state.world, target);
-        spellbook.concentration_tracker().is_concentrating()
-    };
+    // Not every damageable entity can cast spells (e.g. objects)
+    let is_concentrating = game_state
+        .world
+        .get::<&Spellbook>(target)
+        .is_ok_and(|spellbook| spellbook.concentration_tracker().is_concentrating());
 
     if is_concentrating {
         debug!(
@@ -252,9 +610,133 @@ pub fn damage(
         game_state.process_event_with_callback(saving_throw_event, callback);
     }
 
+    if damage_taken > 0 {
+        systems::control::on_damage(game_state, target);
+        if let Some((attacker, _)) = &damage_roll_result.action {
+            systems::effects::end_effects_on_damage(&mut game_state.world, target, *attacker);
+        }
+    }
+
+    // Creatures that survive a massive blow can still go into shock
+    let max_hit_points =
+        systems::helpers::get_component::<HitPoints>(&game_state.world, target).max();
+    if game_state.rule_enabled(OptionalRule::MassiveDamage)
+        && damage_taken > 0
+        && damage_taken * 2 >= max_hit_points
+        && is_alive(&game_state.world, target)
+        && game_state.world.get::<&ObjectTag>(target).is_err()
+    {
+        system_shock_check(game_state, target);
+    }
+
     (Some(mitigation_result), new_life_state)
 }
 
+fn system_shock_check(game_state: &mut GameState, target: Entity) {
+    debug!(
+        "Entity {:?} took massive damage; checking for system shock",
+        target
+    );
+
+    let saving_throw_dc = D20CheckDC {
+        key: SavingThrowKind::Ability(Ability::Constitution),
+        dc: ModifierSet::from(ModifierSource::Base, SYSTEM_SHOCK_SAVING_THROW_DC),
+    };
+    let saving_throw_event = systems::d20::check(
+        game_state,
+        target,
+        &D20CheckDCKind::SavingThrow(saving_throw_dc),
+    );
+    let callback: EventCallback = Arc::new({
+        move |game_state, event| {
+            let EventKind::D20CheckResolved(_, check_result, dc) = &event.kind else {
+                return CallbackResult::None;
+            };
+            let outcome = if check_result.is_success(dc) {
+                MassiveDamageOutcome::SystemShockResisted
+            } else {
+                let roll = rand::rng().random_range(1..=10);
+                let result = SystemShock::from_roll(roll);
+                apply_system_shock(game_state, target, result);
+                MassiveDamageOutcome::SystemShock { roll, result }
+            };
+            CallbackResult::Event(Event::new(EventKind::MassiveDamage {
+                entity: target,
+                outcome,
+            }))
+        }
+    });
+    game_state.process_event_with_callback(saving_throw_event, callback);
+}
+
+fn apply_system_shock(game_state: &mut GameState, target: Entity, shock: SystemShock) {
+    debug!("Entity {:?} {}", target, shock);
+
+    let new_life_state = match shock {
+        SystemShock::DropToZero => {
+            if game_state.world.get::<&CharacterTag>(target).is_ok() {
+                Some(LifeState::unconscious())
+            } else {
+                Some(LifeState::Dead)
+            }
+        }
+        SystemShock::DropToZeroStable => Some(LifeState::Stable),
+        SystemShock::Stunned => {
+            add_system_shock_effect(game_state, target, "effect.condition.stunned");
+            None
+        }
+        SystemShock::Reeling => {
+            add_system_shock_effect(game_state, target, "effect.system_shock");
+            None
+        }
+        SystemShock::Dazed => None,
+    };
+
+    if let Some(new_life_state) = new_life_state {
+        let mut hit_points =
+            systems::helpers::get_component_mut::<HitPoints>(&mut game_state.world, target);
+        let remaining = hit_points.current() + hit_points.temp().map_or(0, |temp| temp.amount());
+        hit_points.damage(remaining);
+        drop(hit_points);
+
+        set_life_state(&mut game_state.world, target, new_life_state);
+        let _ = game_state.process_event(Event::new(EventKind::LifeStateChanged {
+            entity: target,
+            new_state: new_life_state,
+            actor: None,
+        }));
+        return;
+    }
+
+    // Reactions are only regained at the start of a turn, so spending the
+    // reaction now keeps the creature from taking one until then
+    let _ = systems::resources::spend(
+        &mut game_state.world,
+        target,
+        &ResourceAmountMap::from([(
+            ResourceId::new("nat20_core", "resource.reaction"),
+            ResourceAmount::Flat(1),
+        )]),
+    );
+}
+
+fn add_system_shock_effect(game_state: &mut GameState, target: Entity, effect: &str) {
+    systems::effects::add_effect_template(
+        &mut game_state.world,
+        target,
+        target,
+        ModifierSource::Custom("System Shock".to_string()),
+        &EffectInstanceTemplate {
+            effect_id: EffectId::new("nat20_core", effect),
+            lifetime: EffectLifetimeTemplate::UntilNextTurn {
+                entity: EffectLifetimeEntiy::Target,
+                boundary: TurnBoundary::End,
+            },
+        },
+        None,
+    );
+}
+
 pub fn is_alive(world: &World, entity: Entity) -> bool {
     if let Ok(hit_points) = world.get::<&HitPoints>(entity) {
         hit_points.current() > 0
@@ -3,7 +3,10 @@ use std::{any::type_name, ops::Deref};
 use hecs::{Entity, Ref, World};
 use tracing::error;
 
-use crate::components::level::{ChallengeRating, CharacterLevels, Level};
+use crate::components::{
+    dice::RollPolicy,
+    level::{ChallengeRating, CharacterLevels, Level},
+};
 
 pub fn get_component<'a, T: hecs::Component + 'static>(
     world: &'a World,
@@ -62,3 +65,12 @@ pub fn level(world: &World, entity: Entity) -> Option<Ref<'_, dyn Level>> {
 
     None
 }
+
+/// How the dice of the entity are rolled. Creatures without a policy roll as
+/// usual.
+pub fn roll_policy(world: &World, entity: Entity) -> RollPolicy {
+    world
+        .get::<&RollPolicy>(entity)
+        .map(|policy| *policy)
+        .unwrap_or_default()
+}
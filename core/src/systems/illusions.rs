@@ -0,0 +1,220 @@
+use std::sync::Arc;
+
+use hecs::{Entity, World};
+use parry3d::na::Point3;
+use tracing::debug;
+
+use crate::{
+    components::{
+        actions::{action::ActionContext, targeting::TargetInstance},
+        ai::PlayerControlledTag,
+        d20::D20CheckDC,
+        damage::DamageThreshold,
+        health::hit_points::HitPoints,
+        id::Name,
+        illusion::{Illusion, IllusionTemplate},
+        modifier::{ModifierSet, ModifierSource},
+        skill::Skill,
+        spells::{
+            spell::{ConcentrationInstance, SpellFlag},
+            spellbook::Spellbook,
+        },
+        time::{TimeDuration, TimeStep},
+    },
+    engine::{
+        event::{ActionData, CallbackResult, Event, EventCallback, EventKind},
+        game_state::GameState,
+    },
+    entities::object::{Object, ObjectMaterial},
+    registry::registry::SpellsRegistry,
+    systems::{self, d20::D20CheckDCKind},
+};
+
+/// DC to see through an illusion which isn't tied to a spellcaster
+const ILLUSION_DC_DEFAULT: i32 = 13;
+/// DC used when investigating something real. There's nothing to find, but the
+/// check is still rolled so investigating doesn't give away what's real.
+const INVESTIGATE_DC_REAL: i32 = 10;
+
+/// Place an illusion on the ground. To everyone but the caster it looks like
+/// any other object until they see through it.
+pub fn spawn(
+    game_state: &mut GameState,
+    template: &IllusionTemplate,
+    caster: Option<Entity>,
+    dc: i32,
+    position: &Point3<f32>,
+) -> Entity {
+    let entity = game_state.world.spawn(Object::new(
+        Name::new(template.name.clone()),
+        ObjectMaterial::Cloth,
+        template.size.clone(),
+        HitPoints::new(1),
+        DamageThreshold(0),
+    ));
+    let _ = game_state
+        .world
+        .insert_one(entity, Illusion::new(caster, dc, template.duration));
+    systems::geometry::teleport_to_ground(
+        &mut game_state.world,
+        &game_state.geometry,
+        entity,
+        position,
+    );
+    entity
+}
+
+/// Create the illusion at each of the targeted points. Images of concentration
+/// spells, e.g. Major Image, disappear when the caster's concentration ends.
+pub fn create(game_state: &mut GameState, template: &IllusionTemplate, action_data: &ActionData) {
+    let concentration = SpellsRegistry::get(&action_data.action_id.clone().into())
+        .is_some_and(|spell| spell.has_flag(SpellFlag::Concentration));
+
+    let dc = template
+        .dc
+        .unwrap_or_else(|| match action_data.context.base() {
+            ActionContext::Spell { source, .. } => {
+                systems::spells::spell_dc(&game_state.world, action_data.actor, source).total()
+            }
+            _ => ILLUSION_DC_DEFAULT,
+        });
+
+    for target in &action_data.targets {
+        let TargetInstance::Point(point) = target else {
+            continue;
+        };
+        let position =
+            systems::geometry::ground_position(&game_state.geometry, point).unwrap_or(*point);
+        let illusion = spawn(game_state, template, Some(action_data.actor), dc, &position);
+        debug!(
+            "{:?} created an illusion of a {} ({:?})",
+            action_data.actor, template.name, illusion
+        );
+        if concentration {
+            systems::spells::add_concentration_instance(
+                &mut game_state.world,
+                action_data.actor,
+                ConcentrationInstance::Entity { entity: illusion },
+                &action_data.instance_id,
+            );
+        }
+    }
+}
+
+pub fn is_illusion(world: &World, entity: Entity) -> bool {
+    world.get::<&Illusion>(entity).is_ok()
+}
+
+/// Whether the observer knows the entity is an illusion. Always false for
+/// things that are actually real.
+pub fn is_disbelieved_by(world: &World, entity: Entity, observer: Entity) -> bool {
+    world
+        .get::<&Illusion>(entity)
+        .is_ok_and(|illusion| illusion.is_disbelieved_by(observer))
+}
+
+/// Whether any of the player controlled creatures have seen through the
+/// illusion, in which case it's shown as faint to the players
+pub fn disbelieved_by_players(world: &World, entity: Entity) -> bool {
+    let Ok(illusion) = world.get::<&Illusion>(entity) else {
+        return false;
+    };
+    world
+        .query::<&PlayerControlledTag>()
+        .iter()
+        .any(|(player, _)| illusion.is_disbelieved_by(player))
+}
+
+/// Let the observer in on the illusion. Everyone else still believes in it.
+pub fn disbelieve(game_state: &mut GameState, illusion: Entity, observer: Entity) {
+    {
+        let Ok(mut component) = game_state.world.get::<&mut Illusion>(illusion) else {
+            return;
+        };
+        if component.is_disbelieved_by(observer) {
+            return;
+        }
+        component.disbelievers.insert(observer);
+    }
+
+    debug!("{:?} saw through the illusion {:?}", observer, illusion);
+    let _ = game_state.process_event(Event::new(EventKind::IllusionDisbelieved {
+        illusion,
+        by: observer,
+    }));
+}
+
+/// Study the target with an Investigation check. If the target is an illusion
+/// and the check beats its DC, the actor sees through it.
+pub fn investigate(game_state: &mut GameState, actor: Entity, target: Entity) {
+    let illusion_dc = game_state
+        .world
+        .get::<&Illusion>(target)
+        .ok()
+        .filter(|illusion| !illusion.is_disbelieved_by(actor))
+        .map(|illusion| illusion.dc);
+    let dc = investigation_dc(illusion_dc.unwrap_or(INVESTIGATE_DC_REAL));
+
+    let check = systems::d20::check(game_state, actor, &dc);
+    let callback: EventCallback = Arc::new(move |game_state, event| {
+        let EventKind::D20CheckResolved(_, result, _) = &event.kind else {
+            return CallbackResult::None;
+        };
+        if illusion_dc.is_some() && result.is_success(&dc) {
+            disbelieve(game_state, target, actor);
+        }
+        CallbackResult::None
+    });
+    let _ = game_state.process_event_with_callback(check, callback);
+}
+
+/// Attacks go straight through illusions, which gives them away to the
+/// attacker. Returns the targets which are actually real.
+pub fn pass_through(
+    game_state: &mut GameState,
+    attacker: Entity,
+    targets: Vec<Entity>,
+) -> Vec<Entity> {
+    let (illusions, real): (Vec<Entity>, Vec<Entity>) = targets
+        .into_iter()
+        .partition(|target| is_illusion(&game_state.world, *target));
+    for illusion in illusions {
+        disbelieve(game_state, illusion, attacker);
+    }
+    real
+}
+
+/// Illusions with a duration fade once it runs out
+pub fn advance_time(game_state: &mut GameState, duration: &TimeDuration) {
+    let time_step = TimeStep::RealTime {
+        delta_seconds: duration.as_seconds(),
+    };
+    let expired: Vec<(Entity, Option<Entity>)> = game_state
+        .world
+        .query_mut::<&mut Illusion>()
+        .into_iter()
+        .filter_map(|(entity, illusion)| {
+            illusion.advance_time(&time_step);
+            illusion.is_expired().then_some((entity, illusion.caster))
+        })
+        .collect();
+    for (illusion, caster) in expired {
+        debug!("Illusion {:?} fades", illusion);
+        // The caster no longer has anything to concentrate on
+        if let Some(caster) = caster
+            && let Ok(mut spellbook) = game_state.world.get::<&mut Spellbook>(caster)
+        {
+            spellbook
+                .concentration_tracker_mut()
+                .remove_instances_by_entity(illusion);
+        }
+        let _ = game_state.world.despawn(illusion);
+    }
+}
+
+fn investigation_dc(dc: i32) -> D20CheckDCKind {
+    D20CheckDCKind::Skill(D20CheckDC {
+        key: Skill::Investigation,
+        dc: ModifierSet::from(ModifierSource::Base, dc),
+    })
+}
@@ -1,15 +1,36 @@
+use std::f32::consts::TAU;
+
 use hecs::{Entity, World};
+use parry3d::na::Vector3;
+use strum::IntoEnumIterator;
+use tracing::{debug, warn};
+use uom::si::f32::Length;
 
 use crate::{
-    components::items::{
-        equipment::{
-            loadout::{EquipmentInstance, TryEquipError},
-            slots::EquipmentSlot,
+    components::{
+        actions::{
+            action::{ActionContext, ItemDamageOutcome},
+            targeting::{ItemFilter, ItemTarget},
+        },
+        id::{ActionId, IdProvider, ItemId},
+        items::{
+            equipment::{
+                loadout::{EquipmentInstance, Loadout, TryEquipError},
+                slots::EquipmentSlot,
+            },
+            inventory::{Inventory, ItemContainer, ItemInstance},
+            money::{MonetaryValue, MonetaryValueError},
         },
-        inventory::{Inventory, ItemInstance},
-        money::{MonetaryValue, MonetaryValueError},
+        resource::RechargeRule,
+        spells::spellbook::{GrantedSpellSource, SpellSource},
     },
-    systems,
+    engine::{
+        event::{Event, EventKind},
+        game_state::GameState,
+    },
+    entities::ground_item::{GroundItem, GroundItemTag},
+    registry::registry::ItemsRegistry,
+    systems::{self, geometry::CreaturePose},
 };
 
 pub fn equip<T>(
@@ -47,6 +68,253 @@ pub fn remove_item(world: &mut World, entity: Entity, index: usize) -> Option<It
     systems::helpers::get_component_mut::<Inventory>(world, entity).remove_item(index)
 }
 
+/// The item targeted by an action, if the owner still carries it
+pub fn target_item(world: &World, owner: Entity, target: &ItemTarget) -> Option<ItemInstance> {
+    match target {
+        ItemTarget::Equipped(slot) => world
+            .get::<&Loadout>(owner)
+            .ok()?
+            .item_in_slot(slot)
+            .cloned()
+            .map(Into::into),
+        ItemTarget::Inventory(index) => world
+            .get::<&Inventory>(owner)
+            .ok()?
+            .items()
+            .get(*index)
+            .cloned(),
+    }
+}
+
+/// The items of the owner which an action with the filter can target.
+/// Equipped items come first.
+pub fn item_targets(world: &World, owner: Entity, filter: &ItemFilter) -> Vec<ItemTarget> {
+    let equipped = EquipmentSlot::iter().map(ItemTarget::Equipped);
+    let carried = world
+        .get::<&Inventory>(owner)
+        .map(|inventory| inventory.items().len())
+        .unwrap_or_default();
+    equipped
+        .chain((0..carried).map(ItemTarget::Inventory))
+        .filter(|target| filter.matches(world, owner, target))
+        .collect()
+}
+
+/// How far apart the belongings of a fallen creature are scattered around it,
+/// so they don't all end up in the same spot
+const SCATTER_RADIUS: f32 = 0.5;
+
+/// Drop the item at the feet of the entity, where anyone can pick it up.
+/// Returns the item lying on the ground.
+pub fn drop_item<T>(world: &mut World, entity: Entity, item: T) -> Entity
+where
+    T: Into<ItemInstance>,
+{
+    let pose = world
+        .get::<&CreaturePose>(entity)
+        .map(|pose| *pose)
+        .unwrap_or_else(|_| CreaturePose::identity());
+    world.spawn(GroundItem::new(item.into(), pose))
+}
+
+/// Drop everything the entity is carrying and wearing around it, e.g. when it
+/// dies. Cursed items stay on the entity.
+pub fn drop_all(world: &mut World, entity: Entity) -> Vec<Entity> {
+    let mut items = Vec::new();
+    if world.get::<&Loadout>(entity).is_ok() {
+        for slot in EquipmentSlot::iter() {
+            items.extend(unequip(world, entity, &slot));
+        }
+    }
+    if let Ok(mut inventory) = world.get::<&mut Inventory>(entity) {
+        while let Some(item) = inventory.remove_item(0) {
+            items.push(item);
+        }
+    }
+
+    let count = items.len();
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let ground_item = drop_item(world, entity, item);
+            let angle = TAU * index as f32 / count as f32;
+            let offset = Vector3::new(angle.cos(), 0.0, angle.sin()) * SCATTER_RADIUS;
+            if let Ok(mut pose) = world.get::<&mut CreaturePose>(ground_item) {
+                pose.translation.vector += offset;
+            }
+            ground_item
+        })
+        .collect()
+}
+
+/// Drop an item the entity is carrying or wearing. Returns the item lying on
+/// the ground, or `None` if there's nothing there or the item is cursed.
+pub fn drop_target(
+    game_state: &mut GameState,
+    entity: Entity,
+    target: &ItemTarget,
+) -> Option<Entity> {
+    let item = match target {
+        ItemTarget::Equipped(slot) => unequip(&mut game_state.world, entity, slot),
+        ItemTarget::Inventory(index) => remove_item(&mut game_state.world, entity, *index),
+    }?;
+    let item_id = item.id().clone();
+    let ground_item = drop_item(&mut game_state.world, entity, item);
+
+    let _ = game_state.process_event(Event::new(EventKind::ItemDropped {
+        entity,
+        item: item_id,
+        ground_item,
+    }));
+    Some(ground_item)
+}
+
+/// Pick up an item lying on the ground and put it in the inventory of the
+/// entity. Returns the item that was picked up, if it's still there.
+pub fn pick_up(game_state: &mut GameState, entity: Entity, ground_item: Entity) -> Option<ItemId> {
+    if !game_state
+        .world
+        .satisfies::<&GroundItemTag>(ground_item)
+        .unwrap_or(false)
+    {
+        return None;
+    }
+    let item = game_state
+        .world
+        .remove_one::<ItemInstance>(ground_item)
+        .ok()?;
+    let _ = game_state.world.despawn(ground_item);
+    let item_id = item.id().clone();
+    add_item(&mut game_state.world, entity, item);
+
+    let _ = game_state.process_event(Event::new(EventKind::ItemPickedUp {
+        entity,
+        item: item_id.clone(),
+    }));
+    Some(item_id)
+}
+
+/// The items lying on the ground within reach of the entity
+pub fn ground_items_in_reach(world: &World, entity: Entity, reach: Length) -> Vec<Entity> {
+    world
+        .query::<&GroundItemTag>()
+        .iter()
+        .map(|(ground_item, _)| ground_item)
+        .filter(|ground_item| {
+            systems::geometry::distance_between_entities(world, entity, *ground_item)
+                .is_some_and(|distance| distance <= reach)
+        })
+        .collect()
+}
+
+/// Knock a weapon out of the hands of the entity. If no weapon is targeted, the
+/// first weapon it's holding is dropped. Returns the dropped weapon, if any.
+pub fn disarm(world: &mut World, entity: Entity, target: Option<&ItemTarget>) -> Option<ItemId> {
+    let slot = match target {
+        Some(ItemTarget::Equipped(slot)) => Some(*slot),
+        Some(ItemTarget::Inventory(_)) => None,
+        None => item_targets(world, entity, &ItemFilter::Weapons)
+            .into_iter()
+            .find_map(|target| match target {
+                ItemTarget::Equipped(slot) => Some(slot),
+                ItemTarget::Inventory(_) => None,
+            }),
+    }?;
+    if systems::loadout::loadout(world, entity)
+        .weapon_in_hand(&slot)
+        .is_none()
+    {
+        return None;
+    }
+
+    let weapon = unequip(world, entity, &slot)?;
+    let item_id = weapon.id().clone();
+    drop_item(world, entity, weapon);
+    Some(item_id)
+}
+
+/// Throw the weapon in `slot` at the target, where it lands at the feet of the
+/// target. Returning weapons fly back to the hand of the thrower instead.
+/// Returns the weapon lying on the ground, if any.
+pub fn throw_weapon(
+    world: &mut World,
+    thrower: Entity,
+    slot: &EquipmentSlot,
+    target: Entity,
+) -> Option<Entity> {
+    let item_id = systems::loadout::loadout(world, thrower)
+        .weapon_in_hand(slot)?
+        .item()
+        .id
+        .clone();
+    if returns_when_thrown(world, thrower, &item_id) {
+        debug!("{} returns to the hand of {:?}", item_id, thrower);
+        return None;
+    }
+
+    let weapon = unequip(world, thrower, slot)?;
+    Some(drop_item(world, target, weapon))
+}
+
+/// Whether any of the effects on the thrower bring the item back after it's
+/// thrown
+pub fn returns_when_thrown(world: &World, thrower: Entity, item: &ItemId) -> bool {
+    systems::effects::active_effects(world, thrower)
+        .iter()
+        .any(|effect| (effect.effect().on_throw)(world, thrower, item))
+}
+
+/// Deal damage to an item the entity is carrying or wearing. The item is
+/// destroyed if it breaks.
+pub fn damage_item(
+    world: &mut World,
+    owner: Entity,
+    target: &ItemTarget,
+    damage: u32,
+) -> Option<ItemDamageOutcome> {
+    let (item_id, remaining_hit_points, broken) = {
+        let item = match target {
+            ItemTarget::Equipped(slot) => {
+                let mut loadout = systems::loadout::loadout_mut(world, owner);
+                let equipment = loadout.item_in_slot_mut(slot)?;
+                equipment.item_mut().damage_taken += damage;
+                equipment.item().clone()
+            }
+            ItemTarget::Inventory(index) => {
+                let mut inventory = systems::helpers::get_component_mut::<Inventory>(world, owner);
+                let item = inventory.item_mut(*index)?;
+                item.item_mut().damage_taken += damage;
+                item.item().clone()
+            }
+        };
+        let remaining_hit_points = item.hit_points().saturating_sub(item.damage_taken);
+        (item.id, remaining_hit_points, item.is_broken())
+    };
+
+    if broken {
+        match target {
+            ItemTarget::Equipped(slot) => {
+                // Not even a curse keeps a broken item on
+                let equipment = systems::loadout::loadout_mut(world, owner).unequip(slot);
+                if let Some(equipment) = equipment {
+                    systems::effects::remove_effects(world, owner, equipment.effects());
+                }
+            }
+            ItemTarget::Inventory(index) => {
+                remove_item(world, owner, *index);
+            }
+        }
+    }
+
+    Some(ItemDamageOutcome {
+        item: item_id,
+        damage,
+        remaining_hit_points,
+        broken,
+    })
+}
+
 pub fn add_money(world: &mut World, entity: Entity, amount: MonetaryValue) {
     systems::helpers::get_component_mut::<Inventory>(world, entity).add_money(amount);
 }
@@ -58,3 +326,48 @@ pub fn remove_money(
 ) -> Result<(), MonetaryValueError> {
     systems::helpers::get_component_mut::<Inventory>(world, entity).remove_money(amount)
 }
+
+/// Spend a charge of the item the spell is cast from, if it's cast from one
+pub fn spend_item_charge(world: &mut World, entity: Entity, context: &ActionContext) {
+    if let ActionContext::Spell {
+        source:
+            SpellSource::Granted {
+                source: GrantedSpellSource::Item(item_id),
+                ..
+            },
+        ..
+    } = context.base()
+        && !systems::helpers::get_component_mut::<Inventory>(world, entity).spend_charge(item_id)
+    {
+        warn!("{:?} has no charges left on {}", entity, item_id);
+    }
+}
+
+/// Spend a charge of the tool the action is performed with, if it's performed
+/// with one, e.g. a Healer's Kit
+pub fn spend_tool_charge(world: &mut World, entity: Entity, action_id: &ActionId) {
+    let Ok(mut inventory) = world.get::<&mut Inventory>(entity) else {
+        return;
+    };
+    if let Some(item_id) = inventory
+        .tool_for_action(action_id)
+        .map(|tool| tool.id().clone())
+    {
+        inventory.spend_charge(&item_id);
+    }
+}
+
+/// Whether the action can only be performed with a tool, e.g. stabilizing a
+/// creature with a Healer's Kit
+pub fn is_tool_action(action_id: &ActionId) -> bool {
+    ItemsRegistry::values().any(|item| match item {
+        ItemInstance::Tool(tool) => tool.actions.contains(action_id),
+        _ => false,
+    })
+}
+
+pub fn recharge_items(world: &mut World, entity: Entity, rest_type: &RechargeRule) {
+    if let Ok(mut inventory) = world.get::<&mut Inventory>(entity) {
+        inventory.recharge_items(rest_type);
+    }
+}
@@ -14,18 +14,19 @@ use crate::{
         ability::{Ability, AbilityScore, AbilityScoreDistribution, AbilityScoreMap},
         class::ClassAndSubclass,
         health::hit_points::HitPoints,
-        id::{ActionId, ClassId, EffectId, Name, ResourceId, SpellId, SubclassId},
+        id::{ActionId, ClassId, EffectId, ItemId, Name, ResourceId, SpellId, SubclassId},
         items::{equipment::loadout::EquipmentInstance, money::MonetaryValue},
         level::CharacterLevels,
         level_up::{ChoiceItem, LevelUpPrompt},
-        modifier::{KeyedModifiable, ModifierSource},
-        proficiency::{Proficiency, ProficiencyLevel},
+        modifier::{KeyedModifiable, Modifiable, ModifierSource},
+        proficiency::ProficiencyLevel,
         resource::{ResourceBudgetKind, ResourceMap},
         skill::{Skill, SkillSet},
         spells::spellbook::{SpellSource, Spellbook},
     },
+    entities::character::Character,
     registry::registry::{ClassesRegistry, ItemsRegistry},
-    systems,
+    systems::{self, what_if::WhatIfReport},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -117,6 +118,10 @@ pub struct LevelUpSession {
     character: Entity,
     pending_prompts: Vec<LevelUpPrompt>,
     decisions: Vec<LevelUpDecision>,
+    /// Equipment picked during the session. It's only handed out once the
+    /// session is complete, so armor and weapons are equipped after all the
+    /// proficiencies of the class and background have been applied.
+    equipment: Vec<(Vec<(u8, ItemId)>, String)>,
 }
 
 impl LevelUpSession {
@@ -132,12 +137,13 @@ impl LevelUpSession {
                 });
         }
 
-        pending_prompts.push(LevelUpPrompt::class());
+        pending_prompts.push(LevelUpPrompt::class(world, character));
 
         LevelUpSession {
             character,
             pending_prompts,
             decisions: Vec::new(),
+            equipment: Vec::new(),
         }
     }
 
@@ -167,8 +173,13 @@ impl LevelUpSession {
                 continue;
             }
 
-            let next_prompts =
-                resolve_level_up_prompt(world, self.character, prompt.clone(), decision.clone())?;
+            let next_prompts = resolve_level_up_prompt(
+                world,
+                self.character,
+                prompt.clone(),
+                decision.clone(),
+                &mut self.equipment,
+            )?;
             new_prompts.extend(next_prompts);
             resolved_prompt = Some(prompt.clone());
             break;
@@ -189,6 +200,12 @@ impl LevelUpSession {
 
         self.pending_prompts.sort_by_key(|p| p.priority());
 
+        if self.is_complete() {
+            for (items, money) in self.equipment.drain(..) {
+                add_equipment(world, self.character, &items, &money);
+            }
+        }
+
         Ok(())
     }
 
@@ -210,6 +227,7 @@ fn resolve_level_up_prompt(
     entity: Entity,
     prompt: LevelUpPrompt,
     decision: LevelUpDecision,
+    equipment: &mut Vec<(Vec<(u8, ItemId)>, String)>,
 ) -> Result<Vec<LevelUpPrompt>, LevelUpError> {
     let mut prompts = Vec::new();
 
@@ -243,6 +261,13 @@ fn resolve_level_up_prompt(
                         )),
                     });
                 }
+                if let Some(reason) = spec.disabled_reason(item) {
+                    return Err(LevelUpError::InvalidDecision {
+                        prompt: prompt.clone(),
+                        decision: decision.clone(),
+                        message: Some(reason.to_string()),
+                    });
+                }
                 let count = seen.entry(item).or_insert(0);
                 *count += 1;
                 if !spec.allow_duplicates && *count > 1 {
@@ -313,31 +338,17 @@ fn resolve_level_up_prompt(
                     ChoiceItem::Subspecies(subspecies_id) => {
                         systems::species::set_subspecies(world, entity, subspecies_id);
                     }
-                    ChoiceItem::Equipment { items, money } => {
-                        for (count, item_id) in items {
-                            // TODO: Not the most elegant solution
-                            for _ in 0..*count {
-                                let item = ItemsRegistry::get(item_id).unwrap().clone();
-                                if item.equipable() {
-                                    let equipment: EquipmentInstance = item.clone().into();
-                                    if systems::loadout::can_equip(world, entity, &equipment) {
-                                        let result =
-                                            systems::loadout::equip(world, entity, equipment);
-                                        if let Err(e) = result {
-                                            error!("Failed to equip item {}: {:?}", item_id, e);
-                                        } else {
-                                            // If the item is successfully equipped,
-                                            // we don't need to add it to inventory
-                                            continue;
-                                        }
-                                    }
-                                }
-                                systems::inventory::add_item(world, entity, item);
-                            }
-                        }
-                        if !money.is_empty() {
-                            let money = MonetaryValue::from_str(money).unwrap();
-                            systems::inventory::add_money(world, entity, money);
+                    ChoiceItem::Equipment {
+                        items,
+                        money,
+                        any_weapon,
+                    } => {
+                        equipment.push((items.clone(), money.clone()));
+                        for (i, category) in any_weapon.iter().enumerate() {
+                            prompts.push(LevelUpPrompt::any_weapon(
+                                format!("{}.weapon.{}", spec.id, i),
+                                category,
+                            ));
                         }
                     }
                     ChoiceItem::Spell(spell_id, source) => {
@@ -391,9 +402,10 @@ fn resolve_level_up_prompt(
                     });
                 }
                 // TODO: Expertise handling
-                systems::helpers::get_component_mut::<SkillSet>(world, entity).set_proficiency(
+                systems::helpers::get_component_mut::<SkillSet>(world, entity).add_proficiency(
                     skill,
-                    Proficiency::new(ProficiencyLevel::Proficient, source.clone()),
+                    ProficiencyLevel::Proficient,
+                    source.clone(),
                 );
             }
         }
@@ -481,8 +493,11 @@ fn resolve_level_up_prompt(
                         message: None,
                     });
                 }
-                let current_score = ability_score_set.get(ability).total() as u8;
-                if current_score + bonus > *max_score {
+                // Improvements apply to the score underneath any overrides, and
+                // some features let the score go above the usual maximum
+                let ability_score = ability_score_set.get(ability);
+                let current_score = ability_score.unset_total();
+                if current_score + *bonus as i32 > ability_score.max_score(*max_score as i32) {
                     return Err(LevelUpError::InvalidDecision {
                         prompt,
                         decision,
@@ -602,6 +617,33 @@ fn resolve_level_up_prompt(
     Ok(prompts)
 }
 
+fn add_equipment(world: &mut World, entity: Entity, items: &[(u8, ItemId)], money: &str) {
+    for (count, item_id) in items {
+        // TODO: Not the most elegant solution
+        for _ in 0..*count {
+            let item = ItemsRegistry::get(item_id).unwrap().clone();
+            if item.equipable() {
+                let equipment: EquipmentInstance = item.clone().into();
+                if systems::loadout::can_equip(world, entity, &equipment) {
+                    let result = systems::loadout::equip(world, entity, equipment);
+                    if let Err(e) = result {
+                        error!("Failed to equip item {}: {:?}", item_id, e);
+                    } else {
+                        // If the item is successfully equipped,
+                        // we don't need to add it to inventory
+                        continue;
+                    }
+                }
+            }
+            systems::inventory::add_item(world, entity, item);
+        }
+    }
+    if !money.is_empty() {
+        let money = MonetaryValue::from_str(money).unwrap();
+        systems::inventory::add_money(world, entity, money);
+    }
+}
+
 pub fn apply_level_up_decision(
     world: &mut World,
     entity: Entity,
@@ -661,6 +703,9 @@ pub struct LevelUpGains {
     pub actions: Vec<ActionId>,
     pub effects: Vec<EffectId>,
     pub resources: Vec<(ResourceId, ResourceBudgetKind, bool)>,
+    /// Full before/after comparison of the character, if a preview has been
+    /// computed for the pending decisions
+    pub diff: Option<WhatIfReport>,
 }
 
 pub fn level_up_gains(
@@ -712,5 +757,26 @@ pub fn level_up_gains(
         actions,
         effects,
         resources,
+        diff: None,
     }
 }
+
+/// Preview a level up by applying the decisions to a clone of the character in
+/// a scratch world, so the actual character is left untouched. The decisions
+/// don't have to complete the level up, in which case the diff only covers the
+/// decisions made so far.
+pub fn preview_level_up(
+    character: &Character,
+    decisions: &[LevelUpDecision],
+) -> Result<WhatIfReport, LevelUpError> {
+    let mut world = World::new();
+    let entity = world.spawn(character.clone());
+
+    systems::what_if::compare_in_scratch(&mut world, entity, |world, entity| {
+        let mut session = LevelUpSession::new(world, entity);
+        for decision in decisions {
+            session.advance(world, decision)?;
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,17 @@
+use hecs::{Entity, World};
+use tracing::debug;
+
+use crate::components::lever::Lever;
+
+pub fn is_active(world: &World, entity: Entity) -> Option<bool> {
+    world.get::<&Lever>(entity).ok().map(|lever| lever.active)
+}
+
+/// Pull the lever, switching it to the other position. Returns the new
+/// position, or `None` if the entity isn't a lever.
+pub fn pull(world: &mut World, entity: Entity) -> Option<bool> {
+    let mut lever = world.get::<&mut Lever>(entity).ok()?;
+    lever.active = !lever.active;
+    debug!("Lever {:?} pulled (active: {})", entity, lever.active);
+    Some(lever.active)
+}
@@ -0,0 +1,109 @@
+use hecs::{Entity, World};
+use parry3d::na::Point3;
+use tracing::debug;
+use uom::si::f32::Length;
+
+use crate::{
+    components::{
+        id::{IdProvider, ItemId},
+        items::inventory::Inventory,
+        time::{TimeStep, TurnBoundary},
+    },
+    systems,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LightError {
+    /// There's no light at the index in the inventory
+    NotALight(usize),
+    /// The light has burned out and there's nothing left to fuel it with
+    NoFuel(ItemId),
+}
+
+/// Light shed by a lit light carried by a creature
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmittedLight {
+    pub carrier: Entity,
+    pub position: Point3<f32>,
+    pub bright_light: Length,
+    pub dim_light: Length,
+}
+
+/// Light or put out the light at the index in the inventory of the entity,
+/// returning whether it's lit afterwards. A light which has burned out uses up
+/// some fuel from the inventory before it can be lit again.
+pub fn toggle(world: &mut World, entity: Entity, index: usize) -> Result<bool, LightError> {
+    let mut inventory = systems::helpers::get_component_mut::<Inventory>(world, entity);
+    let light = inventory
+        .light_mut(index)
+        .ok_or(LightError::NotALight(index))?;
+
+    if light.lit {
+        light.lit = false;
+        debug!("{:?} puts out {}", entity, light.item.name);
+        return Ok(false);
+    }
+
+    let mut index = index;
+    if light.is_burned_out() {
+        let fuel = light
+            .fuel
+            .clone()
+            .ok_or_else(|| LightError::NoFuel(light.item.id.clone()))?;
+        let fuel_index = inventory
+            .items()
+            .iter()
+            .position(|item| *item.id() == fuel)
+            .ok_or_else(|| LightError::NoFuel(fuel.clone()))?;
+        inventory.remove_item(fuel_index);
+        // Using up the fuel moves everything after it
+        if fuel_index < index {
+            index -= 1;
+        }
+        inventory.light_mut(index).unwrap().refuel();
+    }
+
+    let light = inventory.light_mut(index).unwrap();
+    light.lit = true;
+    debug!("{:?} lights {}", entity, light.item.name);
+    Ok(true)
+}
+
+/// The light shed by everything lit that's carried by creatures
+pub fn emitted_light(world: &World) -> Vec<EmittedLight> {
+    world
+        .query::<&Inventory>()
+        .iter()
+        .flat_map(|(entity, inventory)| {
+            let position = systems::geometry::get_eye_position(world, entity);
+            inventory
+                .lit_lights()
+                .filter_map(move |light| {
+                    Some(EmittedLight {
+                        carrier: entity,
+                        position: position?,
+                        bright_light: light.bright_light,
+                        dim_light: light.dim_light(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Lights burn down as time passes. In combat they burn at the end of the turns
+/// of the creature carrying them.
+pub fn advance_time(world: &mut World, entity: Entity, time_step: &TimeStep) {
+    match time_step {
+        TimeStep::TurnBoundary {
+            entity: turn_entity,
+            boundary: TurnBoundary::End,
+        } if *turn_entity == entity => {}
+        TimeStep::RealTime { .. } => {}
+        _ => return,
+    }
+
+    if let Ok(mut inventory) = world.get::<&mut Inventory>(entity) {
+        inventory.burn_lights(time_step);
+    }
+}
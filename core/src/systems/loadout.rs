@@ -1,11 +1,15 @@
 use hecs::{Entity, Ref, World};
+use tracing::debug;
 
 use crate::{
     components::{
         damage::{AttackRoll, DamageRoll},
+        effects::effect::EffectTag,
+        id::ItemId,
         items::{
             equipment::{
                 armor::ArmorClass,
+                equipment::OnHit,
                 loadout::{EquipmentInstance, Loadout, TryEquipError},
                 slots::EquipmentSlot,
             },
@@ -13,6 +17,7 @@ use crate::{
         },
         modifier::ModifierSource,
     },
+    registry::registry::EffectsRegistry,
     systems,
 };
 
@@ -35,7 +40,9 @@ where
 {
     let equipment = equipment.into();
     let item_id = equipment.item().id.clone();
+    let previous_loadout = loadout(world, entity).clone();
     let unequipped_items = loadout_mut(world, entity).equip_in_slot(slot, equipment)?;
+    revert_if_cursed(world, entity, previous_loadout, &unequipped_items)?;
     for item in &unequipped_items {
         systems::effects::remove_effects(world, entity, item.effects());
     }
@@ -67,7 +74,9 @@ where
     let item_id = equipment.item().id.clone();
     // TODO: Slightly less performant than calling `equip_in_slot` directly
     let effects = equipment.effects().clone();
+    let previous_loadout = loadout(world, entity).clone();
     let unequipped_items = loadout_mut(world, entity).equip(equipment)?;
+    revert_if_cursed(world, entity, previous_loadout, &unequipped_items)?;
     for item in &unequipped_items {
         systems::effects::remove_effects(world, entity, item.effects());
     }
@@ -81,11 +90,19 @@ where
     Ok(unequipped_items)
 }
 
+/// Remove the item in the slot. Nothing is unequipped if the item is bound to
+/// the entity by a curse.
 pub fn unequip(
     world: &mut World,
     entity: Entity,
     slot: &EquipmentSlot,
 ) -> Option<EquipmentInstance> {
+    if let Some(item) = loadout(world, entity).item_in_slot(slot)
+        && is_bound_by_curse(world, entity, item)
+    {
+        debug!("{:?} can't unequip the cursed {}", entity, item.item().name);
+        return None;
+    }
     let unequipped_item = loadout_mut(world, entity).unequip(slot);
     if let Some(item) = &unequipped_item {
         systems::effects::remove_effects(world, entity, item.effects());
@@ -93,7 +110,44 @@ pub fn unequip(
     unequipped_item
 }
 
+/// Cursed items can't be removed as long as the curse they placed on the
+/// entity lasts. The curse is only revealed once the item is equipped.
+pub fn is_bound_by_curse(world: &World, entity: Entity, equipment: &EquipmentInstance) -> bool {
+    equipment.effects().iter().any(|effect_id| {
+        EffectsRegistry::get(effect_id).is_some_and(|effect| effect.has_tag(&EffectTag::Curse))
+            && systems::effects::has_effect(world, entity, effect_id)
+    })
+}
+
+/// Put the loadout back the way it was if equipping something would take off
+/// a cursed item
+fn revert_if_cursed(
+    world: &mut World,
+    entity: Entity,
+    previous_loadout: Loadout,
+    unequipped_items: &[EquipmentInstance],
+) -> Result<(), TryEquipError> {
+    let Some(cursed) = unequipped_items
+        .iter()
+        .find(|item| is_bound_by_curse(world, entity, item))
+        .cloned()
+    else {
+        return Ok(());
+    };
+    debug!(
+        "{:?} can't unequip the cursed {}",
+        entity,
+        cursed.item().name
+    );
+    systems::helpers::set_component(world, entity, previous_loadout);
+    Err(TryEquipError::BoundByCurse(cursed))
+}
+
 pub fn armor_class(world: &World, entity: Entity) -> ArmorClass {
+    // Objects don't have a loadout, so their armor class is stored directly
+    if let Ok(armor_class) = world.get::<&ArmorClass>(entity) {
+        return armor_class.clone();
+    }
     loadout(world, entity).armor_class(world, entity)
 }
 
@@ -102,7 +156,11 @@ pub fn can_equip(world: &World, entity: Entity, equipment: &EquipmentInstance) -
 }
 
 pub fn weapon_damage_roll(world: &World, entity: Entity, slot: &EquipmentSlot) -> DamageRoll {
-    loadout(world, entity).damage_roll(world, entity, slot)
+    let mut damage_roll = loadout(world, entity).damage_roll(world, entity, slot);
+    if systems::antimagic::in_antimagic_field(world, entity) {
+        systems::antimagic::suppress_damage_roll(&mut damage_roll);
+    }
+    damage_roll
 }
 
 pub fn weapon_attack_roll(
@@ -111,5 +169,46 @@ pub fn weapon_attack_roll(
     target: Entity,
     slot: &EquipmentSlot,
 ) -> AttackRoll {
-    loadout(world, entity).attack_roll(world, entity, target, slot)
+    let mut attack_roll = loadout(world, entity).attack_roll(world, entity, target, slot);
+    if systems::antimagic::in_antimagic_field(world, entity) {
+        systems::antimagic::suppress_attack_roll(&mut attack_roll);
+    }
+    attack_roll
+}
+
+pub fn thrown_damage_roll(world: &World, entity: Entity, slot: &EquipmentSlot) -> DamageRoll {
+    let mut damage_roll = loadout(world, entity).thrown_damage_roll(world, entity, slot);
+    if systems::antimagic::in_antimagic_field(world, entity) {
+        systems::antimagic::suppress_damage_roll(&mut damage_roll);
+    }
+    damage_roll
+}
+
+pub fn thrown_attack_roll(
+    world: &World,
+    entity: Entity,
+    target: Entity,
+    slot: &EquipmentSlot,
+) -> AttackRoll {
+    let mut attack_roll = loadout(world, entity).thrown_attack_roll(world, entity, target, slot);
+    if systems::antimagic::in_antimagic_field(world, entity) {
+        systems::antimagic::suppress_attack_roll(&mut attack_roll);
+    }
+    attack_roll
+}
+
+/// The on-hit riders of the item in `slot`, along with the ID of the item so the
+/// riders can be attributed to it. The riders don't work inside an antimagic
+/// field.
+pub fn on_hit(world: &World, entity: Entity, slot: &EquipmentSlot) -> Option<(ItemId, OnHit)> {
+    if systems::antimagic::in_antimagic_field(world, entity) {
+        return None;
+    }
+    let loadout = loadout(world, entity);
+    let equipment = loadout.item_in_slot(slot)?;
+    let on_hit = equipment.on_hit()?;
+    if on_hit.is_empty() {
+        return None;
+    }
+    Some((equipment.item().id.clone(), on_hit.clone()))
 }
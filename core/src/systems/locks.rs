@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use hecs::{Entity, World};
+use tracing::debug;
+
+use crate::{
+    components::{
+        d20::D20CheckDC,
+        id::{IdProvider, ItemId},
+        items::inventory::{Inventory, ItemInstance},
+        lock::{Lock, UnlockMethod},
+        modifier::{ModifierSet, ModifierSource},
+        skill::Skill,
+    },
+    engine::{
+        event::{CallbackResult, Event, EventCallback, EventKind},
+        game_state::GameState,
+    },
+    systems::{self, d20::D20CheckDCKind},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockError {
+    NoLock(Entity),
+    NotLocked(Entity),
+    /// The lock can't be opened without a check, e.g. because no one has the
+    /// key
+    Locked(Entity),
+    NoThievesTools,
+    NotAContainer(Entity),
+}
+
+pub fn thieves_tools() -> ItemId {
+    ItemId::new("nat20_core", "item.thieves_tools")
+}
+
+pub fn lock(world: &World, entity: Entity) -> Option<Lock> {
+    world.get::<&Lock>(entity).ok().map(|lock| lock.clone())
+}
+
+pub fn is_locked(world: &World, entity: Entity) -> bool {
+    lock(world, entity).is_some_and(|lock| lock.locked)
+}
+
+/// The closest locked door or container to the entity, e.g. the one it's
+/// standing in front of
+pub fn nearest_locked(world: &World, entity: Entity) -> Option<Entity> {
+    world
+        .query::<&Lock>()
+        .iter()
+        .filter(|(_, lock)| lock.locked)
+        .filter_map(|(target, _)| {
+            systems::geometry::distance_between_entities(world, entity, target)
+                .map(|distance| (target, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.value.total_cmp(&b.value))
+        .map(|(target, _)| target)
+}
+
+fn carries(world: &World, entity: Entity, item_id: &ItemId) -> bool {
+    world
+        .get::<&Inventory>(entity)
+        .is_ok_and(|inventory| inventory.items().iter().any(|item| item.id() == item_id))
+}
+
+/// Whether the entity carries the key to the lock of the target
+pub fn has_key(world: &World, entity: Entity, target: Entity) -> bool {
+    lock(world, target)
+        .and_then(|lock| lock.key)
+        .is_some_and(|key| carries(world, entity, &key))
+}
+
+fn locked(world: &World, target: Entity) -> Result<Lock, LockError> {
+    let lock = lock(world, target).ok_or(LockError::NoLock(target))?;
+    if !lock.locked {
+        return Err(LockError::NotLocked(target));
+    }
+    Ok(lock)
+}
+
+fn unlock(world: &mut World, actor: Option<Entity>, target: Entity, method: UnlockMethod) -> Event {
+    debug!("Lock on {:?} opened ({}) by {:?}", target, method, actor);
+    if let Ok(mut lock) = world.get::<&mut Lock>(target) {
+        lock.locked = false;
+    }
+    Event::new(EventKind::LockOpened {
+        target,
+        method,
+        actor,
+    })
+}
+
+/// Make sure the target can be opened by the entity. Locks open on their own
+/// for anyone carrying the key, otherwise they have to be picked or forced
+/// open first.
+pub fn open(game_state: &mut GameState, entity: Entity, target: Entity) -> Result<(), LockError> {
+    if !is_locked(&game_state.world, target) {
+        return Ok(());
+    }
+    if !has_key(&game_state.world, entity, target) {
+        return Err(LockError::Locked(target));
+    }
+    let event = unlock(
+        &mut game_state.world,
+        Some(entity),
+        target,
+        UnlockMethod::Key,
+    );
+    let _ = game_state.process_event(event);
+    Ok(())
+}
+
+/// Make a check against the lock, opening it on a success
+fn attempt(
+    game_state: &mut GameState,
+    entity: Entity,
+    target: Entity,
+    skill: Skill,
+    dc: i32,
+    method: UnlockMethod,
+) {
+    let check = systems::d20::check(
+        game_state,
+        entity,
+        &D20CheckDCKind::Skill(D20CheckDC {
+            key: skill,
+            dc: ModifierSet::from(ModifierSource::Base, dc),
+        }),
+    );
+    let callback: EventCallback = Arc::new(move |game_state, event| {
+        let EventKind::D20CheckResolved(_, result, dc) = &event.kind else {
+            return CallbackResult::None;
+        };
+        if !result.is_success(dc) {
+            debug!("{:?} failed to open the lock on {:?}", entity, target);
+            return CallbackResult::None;
+        }
+        CallbackResult::Event(unlock(&mut game_state.world, Some(entity), target, method))
+    });
+    let _ = game_state.process_event_with_callback(check, callback);
+}
+
+/// Try to pick the lock of the target with a Dexterity (Sleight of Hand)
+/// check, which requires a set of thieves' tools
+pub fn pick(game_state: &mut GameState, entity: Entity, target: Entity) -> Result<(), LockError> {
+    let lock = locked(&game_state.world, target)?;
+    if !carries(&game_state.world, entity, &thieves_tools()) {
+        return Err(LockError::NoThievesTools);
+    }
+    attempt(
+        game_state,
+        entity,
+        target,
+        Skill::SleightOfHand,
+        lock.dc,
+        UnlockMethod::Picked,
+    );
+    Ok(())
+}
+
+/// Try to force the target open with a Strength (Athletics) check
+pub fn force(game_state: &mut GameState, entity: Entity, target: Entity) -> Result<(), LockError> {
+    let lock = locked(&game_state.world, target)?;
+    attempt(
+        game_state,
+        entity,
+        target,
+        Skill::Athletics,
+        lock.force_dc,
+        UnlockMethod::Forced,
+    );
+    Ok(())
+}
+
+/// Whatever the lock was on has been destroyed, so it doesn't keep anyone out
+/// anymore
+pub fn on_destroyed(game_state: &mut GameState, target: Entity, actor: Option<Entity>) {
+    if !is_locked(&game_state.world, target) {
+        return;
+    }
+    let event = unlock(&mut game_state.world, actor, target, UnlockMethod::Broken);
+    let _ = game_state.process_event(event);
+}
+
+/// Take everything out of a container, e.g. a chest, opening it with a key
+/// if it's locked
+pub fn loot(
+    game_state: &mut GameState,
+    entity: Entity,
+    container: Entity,
+) -> Result<Vec<ItemInstance>, LockError> {
+    if game_state.world.get::<&Inventory>(container).is_err() {
+        return Err(LockError::NotAContainer(container));
+    }
+    open(game_state, entity, container)?;
+
+    let (items, money) = {
+        let mut inventory =
+            systems::helpers::get_component_mut::<Inventory>(&mut game_state.world, container);
+        let items = inventory.take_all_items();
+        let money = inventory.money().clone();
+        let _ = inventory.remove_money(money.clone());
+        (items, money)
+    };
+
+    debug!(
+        "{:?} takes {} items and {} from {:?}",
+        entity,
+        items.len(),
+        money,
+        container
+    );
+    for item in &items {
+        systems::inventory::add_item(&mut game_state.world, entity, item.clone());
+    }
+    systems::inventory::add_money(&mut game_state.world, entity, money);
+    Ok(items)
+}
@@ -0,0 +1,252 @@
+use rand::{Rng, seq::IndexedRandom};
+use tracing::{debug, warn};
+
+use crate::{
+    components::{
+        damage::DamageType,
+        dice::{DiceSet, DieSize},
+        id::IdProvider,
+        items::{
+            equipment::weapon::{Weapon, WeaponProperties},
+            inventory::{ItemContainer, ItemInstance},
+            item::ItemRarity,
+            loot::{LootItem, LootTable},
+        },
+    },
+    registry::registry::{ItemsRegistry, SpellsRegistry},
+    systems,
+};
+
+/// Chance for a generated +N weapon to also have a minor property
+const RIDER_CHANCE: f32 = 0.25;
+
+/// Minor properties which can be rolled onto generated weapons, as the suffix
+/// added to the name of the weapon and the extra damage it deals on a hit
+const RIDERS: [(&str, DamageType); 5] = [
+    ("Acid", DamageType::Acid),
+    ("Flames", DamageType::Fire),
+    ("Frost", DamageType::Cold),
+    ("Lightning", DamageType::Lightning),
+    ("Thunder", DamageType::Thunder),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MagicItemKind {
+    /// One of the magic items defined in the item registry
+    Registry,
+    /// A mundane weapon turned into a +N weapon
+    Weapon,
+    SpellScroll,
+    Wand,
+}
+
+/// The weights of each kind of magic item for each rarity, out of 100
+fn magic_item_table(rarity: &ItemRarity) -> &'static [(u32, MagicItemKind)] {
+    match rarity {
+        ItemRarity::Common => &[
+            (70, MagicItemKind::SpellScroll),
+            (30, MagicItemKind::Registry),
+        ],
+        ItemRarity::Uncommon => &[
+            (30, MagicItemKind::SpellScroll),
+            (40, MagicItemKind::Weapon),
+            (20, MagicItemKind::Registry),
+            (10, MagicItemKind::Wand),
+        ],
+        ItemRarity::Rare | ItemRarity::VeryRare => &[
+            (25, MagicItemKind::SpellScroll),
+            (35, MagicItemKind::Weapon),
+            (30, MagicItemKind::Registry),
+            (10, MagicItemKind::Wand),
+        ],
+        ItemRarity::Legendary => &[
+            (30, MagicItemKind::SpellScroll),
+            (70, MagicItemKind::Registry),
+        ],
+    }
+}
+
+/// Enchantment of the +N weapons of the given rarity
+fn weapon_enchantment(rarity: &ItemRarity) -> Option<u32> {
+    match rarity {
+        ItemRarity::Uncommon => Some(1),
+        ItemRarity::Rare => Some(2),
+        ItemRarity::VeryRare => Some(3),
+        _ => None,
+    }
+}
+
+/// Roll every entry in the loot table, rolling magic items on the magic item
+/// tables
+pub fn roll(table: &LootTable) -> Vec<ItemInstance> {
+    table
+        .roll()
+        .into_iter()
+        .filter_map(|item| match item {
+            LootItem::Item(item_id) => Some(ItemsRegistry::get(&item_id).unwrap().clone()),
+            LootItem::MagicItem { rarity } => {
+                let item = roll_magic_item(&rarity);
+                if item.is_none() {
+                    warn!("No magic items of rarity {} to roll", rarity);
+                }
+                item
+            }
+        })
+        .collect()
+}
+
+/// Roll a random magic item of the given rarity. The kind of item is rolled
+/// first, e.g. a Spell Scroll or a +N weapon, and then an item of that kind.
+/// Returns `None` if there are no items of the rarity at all.
+pub fn roll_magic_item(rarity: &ItemRarity) -> Option<ItemInstance> {
+    let mut rng = rand::rng();
+
+    // Kinds without any items of the rarity are skipped, so the roll only
+    // fails if the whole table is empty
+    let mut candidates = magic_item_table(rarity)
+        .iter()
+        .map(|(weight, kind)| (*weight, *kind, magic_items(rarity, *kind)))
+        .filter(|(_, _, items)| !items.is_empty())
+        .collect::<Vec<_>>();
+    let total = candidates.iter().map(|(weight, _, _)| weight).sum::<u32>();
+    if total == 0 {
+        return None;
+    }
+
+    let mut roll = rng.random_range(0..total);
+    let index = candidates
+        .iter()
+        .position(|(weight, _, _)| {
+            if roll < *weight {
+                return true;
+            }
+            roll -= weight;
+            false
+        })
+        .unwrap();
+    let (_, kind, items) = candidates.swap_remove(index);
+
+    let item = match items.choose(&mut rng).cloned()? {
+        ItemInstance::Weapon(weapon) if kind == MagicItemKind::Weapon => {
+            ItemInstance::Weapon(with_random_rider(weapon))
+        }
+        item => item,
+    };
+    debug!("Rolled {} magic item: {}", rarity, item.item().name);
+    Some(item)
+}
+
+fn magic_items(rarity: &ItemRarity, kind: MagicItemKind) -> Vec<ItemInstance> {
+    match kind {
+        MagicItemKind::Registry => ItemsRegistry::values()
+            .filter(|item| item.item().rarity == *rarity && is_magical(item))
+            .cloned()
+            .collect(),
+
+        MagicItemKind::Weapon => {
+            let Some(enchantment) = weapon_enchantment(rarity) else {
+                return Vec::new();
+            };
+            ItemsRegistry::values()
+                .filter_map(|item| match item {
+                    ItemInstance::Weapon(weapon) if !is_magical(item) => {
+                        Some(ItemInstance::Weapon(
+                            weapon
+                                .clone()
+                                .with_enchantment(enchantment)
+                                .with_rarity(rarity.clone()),
+                        ))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+
+        MagicItemKind::SpellScroll => SpellsRegistry::values()
+            .filter(|spell| systems::crafting::scroll_rarity(spell.base_level()) == *rarity)
+            .map(|spell| {
+                ItemInstance::Spell(systems::crafting::scroll(spell.id(), spell.base_level()))
+            })
+            .collect(),
+
+        MagicItemKind::Wand => SpellsRegistry::values()
+            .filter(|spell| {
+                spell.base_level() > 0
+                    && systems::crafting::wand_rarity(spell.base_level()) == *rarity
+            })
+            .map(|spell| {
+                ItemInstance::Spell(systems::crafting::wand(spell.id(), spell.base_level()))
+            })
+            .collect(),
+    }
+}
+
+/// Items in the registry are considered mundane if they're common and don't
+/// have any magical properties, e.g. a plain longsword
+fn is_magical(item: &ItemInstance) -> bool {
+    match item {
+        ItemInstance::Spell(_) => true,
+        ItemInstance::Weapon(weapon) => {
+            weapon.item().rarity != ItemRarity::Common
+                || !weapon.on_hit().is_empty()
+                || !weapon.effects().is_empty()
+                || weapon.properties().iter().any(|property| {
+                    matches!(
+                        property,
+                        WeaponProperties::Enchantment(_) | WeaponProperties::Magical
+                    )
+                })
+        }
+        _ => item.item().rarity != ItemRarity::Common,
+    }
+}
+
+/// Generated weapons have a chance of getting a random minor property
+fn with_random_rider(weapon: Weapon) -> Weapon {
+    let mut rng = rand::rng();
+    if rng.random::<f32>() < RIDER_CHANCE
+        && let Some((name, damage_type)) = RIDERS.choose(&mut rng)
+    {
+        return weapon.with_rider(name, DiceSet::new(1, DieSize::D4), *damage_type);
+    }
+    weapon
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use crate::components::items::loot::LootEntry;
+
+    use super::*;
+
+    #[test]
+    fn roll_magic_item_of_rarity() {
+        for rarity in ItemRarity::iter() {
+            for _ in 0..20 {
+                let item = roll_magic_item(&rarity).unwrap();
+                assert_eq!(item.item().rarity, rarity);
+                assert!(is_magical(&item));
+            }
+        }
+    }
+
+    #[test]
+    fn roll_loot_table_with_magic_item() {
+        let table = LootTable::new(vec![LootEntry {
+            item: LootItem::MagicItem {
+                rarity: ItemRarity::Uncommon,
+            },
+            chance: 1.0,
+            count: 2,
+        }]);
+
+        let items = roll(&table);
+        assert_eq!(items.len(), 2);
+        assert!(
+            items
+                .iter()
+                .all(|item| item.item().rarity == ItemRarity::Uncommon)
+        );
+    }
+}
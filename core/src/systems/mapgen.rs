@@ -0,0 +1,634 @@
+use hecs::Entity;
+use parry3d::na::Point3;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IteratorRandom};
+use rerecast::Config;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use uom::si::{f32::Length, length::foot};
+
+use crate::{
+    components::{
+        damage::{DamageThreshold, DamageType},
+        dice::{DiceSet, DieSize},
+        health::hit_points::HitPoints,
+        hidden::{HiddenFeature, HiddenFeatureKind, SECRET_DOOR_DC_DEFAULT, TRAP_DC_DEFAULT, Trap},
+        id::{ItemId, Name},
+        items::inventory::{Inventory, ItemInstance},
+        lock::Lock,
+        species::CreatureSize,
+    },
+    engine::{game_state::GameState, geometry::WorldGeometry},
+    entities::object::{Object, ObjectMaterial},
+    registry::registry::ItemsRegistry,
+    systems::{self, generator::MonsterFilter, time::RestKind},
+};
+
+/// Each tile is a 5 ft. square, matching the usual battle map grid
+pub const TILE_SIZE: f32 = 1.524;
+pub const WALL_HEIGHT: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tile {
+    Wall,
+    Floor,
+    Door,
+    Trap,
+}
+
+impl Tile {
+    pub fn is_walkable(&self) -> bool {
+        !matches!(self, Tile::Wall)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TilePosition {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl TilePosition {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Room {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Room {
+    pub fn center(&self) -> TilePosition {
+        TilePosition::new(self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    pub fn contains(&self, position: &TilePosition) -> bool {
+        position.x >= self.x
+            && position.x < self.x + self.width
+            && position.y >= self.y
+            && position.y < self.y + self.height
+    }
+
+    /// Rooms are considered intersecting if they are less than one tile apart,
+    /// so there is always a wall between two rooms.
+    pub fn intersects(&self, other: &Room) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = TilePosition> {
+        let (x, y, width, height) = (self.x, self.y, self.width, self.height);
+        (y..y + height).flat_map(move |y| (x..x + width).map(move |x| TilePosition::new(x, y)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightSource {
+    pub position: TilePosition,
+    /// Radius of bright light. Dim light extends for the same distance beyond it.
+    pub radius: Length,
+}
+
+#[derive(Debug, Clone)]
+pub struct EncounterPlacement {
+    /// Index of the room the encounter is placed in
+    pub room: usize,
+    pub filter: MonsterFilter,
+    /// One monster is spawned at each position
+    pub positions: Vec<TilePosition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapGenConfig {
+    pub width: usize,
+    pub height: usize,
+    pub max_rooms: usize,
+    pub min_room_size: usize,
+    pub max_room_size: usize,
+    pub party_level: u8,
+    pub party_size: usize,
+    /// Chance for each room (except the first) to contain an encounter
+    pub encounter_chance: f32,
+    /// Chance for each corridor tile to be trapped
+    pub trap_chance: f32,
+    /// Chance for each door to be locked
+    #[serde(default)]
+    pub lock_chance: f32,
+    /// Chance for each door to be a secret door, which has to be found first
+    #[serde(default)]
+    pub secret_door_chance: f32,
+    /// Generating twice with the same seed produces the same map
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapGenError {
+    /// The smallest rooms are larger than the largest ones, or have no floor
+    InvalidRoomSize { min: usize, max: usize },
+    /// Not even the smallest room fits inside the walls around the edge of the
+    /// map
+    RoomTooLarge {
+        room_size: usize,
+        width: usize,
+        height: usize,
+    },
+    /// Chances have to be between 0 and 1
+    InvalidChance { name: &'static str, chance: f32 },
+}
+
+impl MapGenConfig {
+    pub fn validate(&self) -> Result<(), MapGenError> {
+        if self.min_room_size == 0 || self.min_room_size > self.max_room_size {
+            return Err(MapGenError::InvalidRoomSize {
+                min: self.min_room_size,
+                max: self.max_room_size,
+            });
+        }
+        // Keep a border of walls around the edge of the map
+        if self.min_room_size + 2 > self.width || self.min_room_size + 2 > self.height {
+            return Err(MapGenError::RoomTooLarge {
+                room_size: self.min_room_size,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        for (name, chance) in [
+            ("encounter_chance", self.encounter_chance),
+            ("trap_chance", self.trap_chance),
+            ("lock_chance", self.lock_chance),
+            ("secret_door_chance", self.secret_door_chance),
+        ] {
+            if !(0.0..=1.0).contains(&chance) {
+                return Err(MapGenError::InvalidChance { name, chance });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for MapGenConfig {
+    fn default() -> Self {
+        Self {
+            width: 40,
+            height: 30,
+            max_rooms: 10,
+            min_room_size: 4,
+            max_room_size: 8,
+            party_level: 1,
+            party_size: 4,
+            encounter_chance: 0.5,
+            trap_chance: 0.05,
+            lock_chance: 0.1,
+            secret_door_chance: 0.05,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TileMap {
+    pub width: usize,
+    pub height: usize,
+    /// Seed the map was generated from, so the same map can be generated again
+    pub seed: u64,
+    /// Level of the party the map was generated for, which decides how
+    /// dangerous its encounters and traps are
+    pub party_level: u8,
+    tiles: Vec<Tile>,
+    /// The first room is where the party starts, so it never has an encounter
+    pub rooms: Vec<Room>,
+    pub light_sources: Vec<LightSource>,
+    pub encounters: Vec<EncounterPlacement>,
+    pub locked_doors: Vec<TilePosition>,
+    pub secret_doors: Vec<TilePosition>,
+}
+
+impl TileMap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            seed: 0,
+            party_level: 1,
+            tiles: vec![Tile::Wall; width * height],
+            rooms: Vec::new(),
+            light_sources: Vec::new(),
+            encounters: Vec::new(),
+            locked_doors: Vec::new(),
+            secret_doors: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, position: &TilePosition) -> Option<Tile> {
+        if position.x >= self.width || position.y >= self.height {
+            return None;
+        }
+        Some(self.tiles[position.y * self.width + position.x])
+    }
+
+    pub fn set(&mut self, position: &TilePosition, tile: Tile) {
+        self.tiles[position.y * self.width + position.x] = tile;
+    }
+
+    pub fn is_walkable(&self, position: &TilePosition) -> bool {
+        self.get(position).is_some_and(|tile| tile.is_walkable())
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = TilePosition> {
+        let (width, height) = (self.width, self.height);
+        (0..height).flat_map(move |y| (0..width).map(move |x| TilePosition::new(x, y)))
+    }
+
+    pub fn positions_of(&self, tile: Tile) -> impl Iterator<Item = TilePosition> {
+        self.positions()
+            .filter(move |position| self.get(position) == Some(tile))
+    }
+
+    /// Center of the tile on the ground in world coordinates. The map is laid
+    /// out on the xz-plane, with the tile y coordinate along the z-axis.
+    pub fn tile_center(&self, position: &TilePosition) -> Point3<f32> {
+        Point3::new(
+            (position.x as f32 + 0.5) * TILE_SIZE,
+            0.0,
+            (position.y as f32 + 0.5) * TILE_SIZE,
+        )
+    }
+
+    fn neighbours(&self, position: &TilePosition) -> [Option<TilePosition>; 4] {
+        [
+            (position.x + 1 < self.width).then(|| TilePosition::new(position.x + 1, position.y)),
+            (position.x > 0).then(|| TilePosition::new(position.x - 1, position.y)),
+            (position.y + 1 < self.height).then(|| TilePosition::new(position.x, position.y + 1)),
+            (position.y > 0).then(|| TilePosition::new(position.x, position.y - 1)),
+        ]
+    }
+
+    /// Build the world geometry for the map. Walkable tiles become floor and
+    /// walls are only built where they border a walkable tile.
+    pub fn to_geometry(&self, config: &Config) -> WorldGeometry {
+        let mut points = Vec::new();
+        let mut indices = Vec::new();
+
+        let mut add_quad = |quad: [[f32; 3]; 4]| {
+            let start = points.len() as u32;
+            points.extend(quad);
+            indices.push([start, start + 1, start + 2]);
+            indices.push([start, start + 2, start + 3]);
+        };
+
+        for position in self.positions() {
+            let x0 = position.x as f32 * TILE_SIZE;
+            let x1 = x0 + TILE_SIZE;
+            let z0 = position.y as f32 * TILE_SIZE;
+            let z1 = z0 + TILE_SIZE;
+
+            if self.is_walkable(&position) {
+                add_quad([[x0, 0.0, z0], [x0, 0.0, z1], [x1, 0.0, z1], [x1, 0.0, z0]]);
+                continue;
+            }
+
+            let [east, west, south, north] = self
+                .neighbours(&position)
+                .map(|neighbour| neighbour.is_some_and(|n| self.is_walkable(&n)));
+            if !(east || west || south || north) {
+                continue;
+            }
+
+            let h = WALL_HEIGHT;
+            add_quad([[x0, h, z0], [x0, h, z1], [x1, h, z1], [x1, h, z0]]);
+            // Only the faces pointing towards walkable tiles are visible
+            if east {
+                add_quad([[x1, 0.0, z0], [x1, h, z0], [x1, h, z1], [x1, 0.0, z1]]);
+            }
+            if west {
+                add_quad([[x0, 0.0, z1], [x0, h, z1], [x0, h, z0], [x0, 0.0, z0]]);
+            }
+            if south {
+                add_quad([[x1, 0.0, z1], [x1, h, z1], [x0, h, z1], [x0, 0.0, z1]]);
+            }
+            if north {
+                add_quad([[x0, 0.0, z0], [x0, h, z0], [x1, h, z0], [x1, 0.0, z0]]);
+            }
+        }
+
+        WorldGeometry::new(points, indices, config)
+    }
+}
+
+pub fn generate(config: &MapGenConfig) -> Result<TileMap, MapGenError> {
+    config.validate()?;
+
+    let seed = config.seed.unwrap_or_else(|| rand::rng().random());
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut map = TileMap::new(config.width, config.height);
+    map.seed = seed;
+    map.party_level = config.party_level;
+
+    // Keep a border of walls around the edge of the map
+    let max_width = config.max_room_size.min(config.width - 2);
+    let max_height = config.max_room_size.min(config.height - 2);
+
+    for _ in 0..config.max_rooms {
+        let width = rng.random_range(config.min_room_size..=max_width);
+        let height = rng.random_range(config.min_room_size..=max_height);
+        let room = Room {
+            x: rng.random_range(1..=config.width - width - 1),
+            y: rng.random_range(1..=config.height - height - 1),
+            width,
+            height,
+        };
+
+        if map.rooms.iter().any(|other| room.intersects(other)) {
+            continue;
+        }
+
+        for position in room.tiles() {
+            map.set(&position, Tile::Floor);
+        }
+
+        if let Some(previous) = map.rooms.last() {
+            carve_corridor(&mut map, &previous.center(), &room.center(), rng.random());
+        }
+
+        map.rooms.push(room);
+    }
+
+    place_doors(&mut map);
+    place_traps(&mut map, config.trap_chance, &mut rng);
+
+    for room in &map.rooms {
+        // Torches are mounted in the corner of each room
+        map.light_sources.push(LightSource {
+            position: TilePosition::new(room.x, room.y),
+            radius: Length::new::<foot>(20.0),
+        });
+    }
+
+    place_encounters(&mut map, config, &mut rng);
+    place_locks(&mut map, config.lock_chance, &mut rng);
+    place_secret_doors(&mut map, config.secret_door_chance, &mut rng);
+
+    debug!(
+        "Generated {}x{} map with {} rooms and {} encounters",
+        map.width,
+        map.height,
+        map.rooms.len(),
+        map.encounters.len()
+    );
+
+    Ok(map)
+}
+
+fn carve_corridor(
+    map: &mut TileMap,
+    from: &TilePosition,
+    to: &TilePosition,
+    horizontal_first: bool,
+) {
+    let corner = if horizontal_first {
+        TilePosition::new(to.x, from.y)
+    } else {
+        TilePosition::new(from.x, to.y)
+    };
+
+    for (start, end) in [(from, &corner), (&corner, to)] {
+        for x in start.x.min(end.x)..=start.x.max(end.x) {
+            for y in start.y.min(end.y)..=start.y.max(end.y) {
+                map.set(&TilePosition::new(x, y), Tile::Floor);
+            }
+        }
+    }
+}
+
+/// A door is placed wherever a corridor enters a room through a one tile wide
+/// gap in the wall.
+fn place_doors(map: &mut TileMap) {
+    let mut doors = Vec::new();
+
+    for room in &map.rooms {
+        let (left, right) = (room.x - 1, room.x + room.width);
+        let (top, bottom) = (room.y - 1, room.y + room.height);
+
+        let horizontal = (room.x..room.x + room.width)
+            .flat_map(|x| [TilePosition::new(x, top), TilePosition::new(x, bottom)])
+            .filter(|position| {
+                map.get(&TilePosition::new(position.x - 1, position.y)) == Some(Tile::Wall)
+                    && map.get(&TilePosition::new(position.x + 1, position.y)) == Some(Tile::Wall)
+            });
+        let vertical = (room.y..room.y + room.height)
+            .flat_map(|y| [TilePosition::new(left, y), TilePosition::new(right, y)])
+            .filter(|position| {
+                map.get(&TilePosition::new(position.x, position.y - 1)) == Some(Tile::Wall)
+                    && map.get(&TilePosition::new(position.x, position.y + 1)) == Some(Tile::Wall)
+            });
+
+        doors.extend(
+            horizontal
+                .chain(vertical)
+                .filter(|position| map.get(position) == Some(Tile::Floor)),
+        );
+    }
+
+    for door in doors {
+        map.set(&door, Tile::Door);
+    }
+}
+
+fn place_traps(map: &mut TileMap, trap_chance: f32, rng: &mut StdRng) {
+    let corridors = map
+        .positions_of(Tile::Floor)
+        .filter(|position| !map.rooms.iter().any(|room| room.contains(position)))
+        .collect::<Vec<_>>();
+
+    for position in corridors {
+        if rng.random::<f32>() < trap_chance {
+            map.set(&position, Tile::Trap);
+        }
+    }
+}
+
+fn place_locks(map: &mut TileMap, lock_chance: f32, rng: &mut StdRng) {
+    let doors = map.positions_of(Tile::Door).collect::<Vec<_>>();
+    for position in doors {
+        if rng.random::<f32>() < lock_chance {
+            map.locked_doors.push(position);
+        }
+    }
+}
+
+fn place_secret_doors(map: &mut TileMap, secret_door_chance: f32, rng: &mut StdRng) {
+    let doors = map.positions_of(Tile::Door).collect::<Vec<_>>();
+    for position in doors {
+        if rng.random::<f32>() < secret_door_chance {
+            map.secret_doors.push(position);
+        }
+    }
+}
+
+/// Each encounter is a group of monsters with a challenge rating appropriate
+/// for the party level, with one monster for every two party members.
+fn place_encounters(map: &mut TileMap, config: &MapGenConfig, rng: &mut StdRng) {
+    let monsters = (config.party_size / 2).max(1);
+
+    for (index, room) in map.rooms.iter().enumerate().skip(1) {
+        if rng.random::<f32>() >= config.encounter_chance {
+            continue;
+        }
+
+        let positions = room
+            .tiles()
+            .filter(|position| {
+                !map.light_sources
+                    .iter()
+                    .any(|light| light.position == *position)
+            })
+            .choose_multiple(rng, monsters);
+
+        map.encounters.push(EncounterPlacement {
+            room: index,
+            filter: MonsterFilter::for_party_level(config.party_level),
+            positions,
+        });
+    }
+}
+
+/// Spawn the doors, traps, light sources and monsters of the map into the
+/// world. The world geometry is expected to already have been built from the
+/// map.
+pub fn populate(game_state: &mut GameState, map: &TileMap) -> Vec<Entity> {
+    let mut entities = spawn_features(game_state, map);
+
+    let mut monsters = Vec::new();
+    for encounter in &map.encounters {
+        for position in &encounter.positions {
+            let Some(monster) =
+                systems::generator::generate_monster(&mut game_state.world, &encounter.filter)
+            else {
+                continue;
+            };
+            systems::geometry::teleport_to_ground(
+                &mut game_state.world,
+                &game_state.geometry,
+                monster.id(),
+                &map.tile_center(position),
+            );
+            monsters.push(monster.id());
+        }
+    }
+    // Ensure all resources are fully recharged
+    systems::time::on_rest_end(&mut game_state.world, &monsters, &RestKind::Long);
+    entities.extend(monsters);
+
+    entities
+}
+
+/// Spawn everything on the map except the monsters, e.g. when the monsters come
+/// from somewhere else
+pub fn spawn_features(game_state: &mut GameState, map: &TileMap) -> Vec<Entity> {
+    let mut entities = spawn_doors(game_state, map);
+    entities.extend(spawn_traps(game_state, map));
+    entities.extend(spawn_light_sources(game_state, map));
+    entities
+}
+
+/// Some of the doors might be locked or hidden
+fn spawn_doors(game_state: &mut GameState, map: &TileMap) -> Vec<Entity> {
+    let mut entities = Vec::new();
+
+    for position in map.positions_of(Tile::Door) {
+        let door = game_state.world.spawn(Object::new(
+            Name::new("Wooden Door"),
+            ObjectMaterial::Wood,
+            CreatureSize::Large,
+            HitPoints::new(18),
+            DamageThreshold(5),
+        ));
+        systems::geometry::teleport_to_ground(
+            &mut game_state.world,
+            &game_state.geometry,
+            door,
+            &map.tile_center(&position),
+        );
+        if map.locked_doors.contains(&position) {
+            let _ = game_state.world.insert_one(door, Lock::default());
+        }
+        if map.secret_doors.contains(&position) {
+            let _ = game_state.world.insert_one(
+                door,
+                HiddenFeature::new(HiddenFeatureKind::SecretDoor, SECRET_DOOR_DC_DEFAULT, ""),
+            );
+        }
+        entities.push(door);
+    }
+
+    entities
+}
+
+/// Traps get more dangerous as the party levels up, going from a setback at the
+/// lowest levels to a dangerous trap later on
+fn trap_damage(party_level: u8) -> DiceSet {
+    let dice = match party_level {
+        0..=4 => 1,
+        5..=10 => 2,
+        11..=16 => 4,
+        _ => 10,
+    };
+    DiceSet::new(dice, DieSize::D10)
+}
+
+fn spawn_traps(game_state: &mut GameState, map: &TileMap) -> Vec<Entity> {
+    map.positions_of(Tile::Trap)
+        .map(|position| {
+            systems::traps::spawn(
+                game_state,
+                Trap {
+                    damage: (trap_damage(map.party_level), DamageType::Piercing),
+                    dc: TRAP_DC_DEFAULT,
+                },
+                &map.tile_center(&position),
+            )
+        })
+        .collect()
+}
+
+/// Each light source is a lit torch mounted on the wall
+fn spawn_light_sources(game_state: &mut GameState, map: &TileMap) -> Vec<Entity> {
+    let Some(ItemInstance::Light(torch)) =
+        ItemsRegistry::get(&ItemId::new("nat20_core", "item.torch"))
+    else {
+        return Vec::new();
+    };
+
+    let mut entities = Vec::new();
+    for light_source in &map.light_sources {
+        let entity = game_state.world.spawn(Object::new(
+            Name::new("Wall Torch"),
+            ObjectMaterial::Wood,
+            CreatureSize::Tiny,
+            HitPoints::new(2),
+            DamageThreshold(0),
+        ));
+        let mut torch = torch.clone();
+        torch.bright_light = light_source.radius;
+        let mut inventory = Inventory::new();
+        inventory.add_item(ItemInstance::Light(torch));
+        let _ = game_state.world.insert_one(entity, inventory);
+        let _ = systems::light::toggle(&mut game_state.world, entity, 0);
+
+        systems::geometry::teleport_to_ground(
+            &mut game_state.world,
+            &game_state.geometry,
+            entity,
+            &map.tile_center(&light_source.position),
+        );
+        entities.push(entity);
+    }
+    entities
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+
+use hecs::Entity;
+use tracing::debug;
+
+use crate::{
+    components::{
+        actions::{
+            action::{ActionContext, ActionKindResult, DamageResolutionKind},
+            targeting::{EntityFilter, TargetInstance},
+        },
+        faction::Attitude,
+        health::life_state::LifeState,
+        id::{ActionId, ResourceId},
+        items::equipment::{loadout::Loadout, slots::EquipmentSlot},
+        resource::{ResourceAmount, ResourceAmountMap},
+    },
+    engine::{
+        encounter::EncounterId,
+        event::{ActionData, ActionExecutionInstanceId, EncounterEvent, Event, EventKind},
+        game_state::GameState,
+    },
+    systems,
+};
+
+/// The combined attack of a mob against a single target
+#[derive(Debug, Clone, PartialEq)]
+pub struct MobAttack {
+    pub target: Entity,
+    pub attackers: usize,
+    pub hits: usize,
+    pub damage: i32,
+}
+
+/// A mob attack whose members are still attacking. Each attack is resolved
+/// like any other, but the results are tallied here instead of being logged
+/// one by one.
+#[derive(Debug, Clone)]
+pub struct MobResolution {
+    attack: MobAttack,
+    attacks: HashSet<ActionExecutionInstanceId>,
+}
+
+/// Take the turns of all members of the current mob in one go. The mob decides
+/// on a single target, every member moves towards it, and the members within
+/// reach attack together. Every attack is resolved on its own, so reactions and
+/// effects apply as usual, but the attacks are logged as a single entry.
+/// Returns the attack, if the mob was able to make one. Attacks which are still
+/// waiting for a reaction when the turn ends are logged on their own.
+pub fn take_turn(game_state: &mut GameState, encounter_id: &EncounterId) -> Option<MobAttack> {
+    let members = game_state.encounter(encounter_id)?.mob_turns();
+    let leader = *members.first()?;
+
+    let attack = target(game_state, encounter_id, leader)
+        .and_then(|target| attack(game_state, encounter_id, &members, target));
+
+    // Members who are down have their turns skipped automatically
+    for member in members {
+        let is_current = game_state
+            .encounter(encounter_id)
+            .is_some_and(|encounter| encounter.current_entity() == member);
+        if is_current {
+            game_state.end_turn(member);
+        }
+    }
+
+    attack
+}
+
+/// The mob goes for the closest hostile creature that's still standing
+fn target(game_state: &GameState, encounter_id: &EncounterId, leader: Entity) -> Option<Entity> {
+    let encounter = game_state.encounter(encounter_id)?;
+    encounter
+        .participants(
+            &game_state.world,
+            EntityFilter::LifeStates(HashSet::from([LifeState::Normal])),
+        )
+        .into_iter()
+        .filter(|other| {
+            systems::factions::perceived_threat(&game_state.world, leader, *other)
+                == Attitude::Hostile
+        })
+        .filter_map(|other| {
+            systems::geometry::distance_between_entities(&game_state.world, leader, other)
+                .map(|distance| (other, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.value.total_cmp(&b.value))
+        .map(|(other, _)| other)
+}
+
+fn attack(
+    game_state: &mut GameState,
+    encounter_id: &EncounterId,
+    members: &[Entity],
+    target: Entity,
+) -> Option<MobAttack> {
+    let target_position = systems::geometry::get_foot_position(&game_state.world, target)?;
+    let action_cost = ResourceAmountMap::from([(
+        ResourceId::new("nat20_core", "resource.action"),
+        ResourceAmount::Flat(1),
+    )]);
+
+    game_state.mob_attack = Some(MobResolution {
+        attack: MobAttack {
+            target,
+            attackers: 0,
+            hits: 0,
+            damage: 0,
+        },
+        attacks: HashSet::new(),
+    });
+
+    for member in members {
+        let member = *member;
+        let has_weapon = game_state
+            .world
+            .get::<&Loadout>(member)
+            .is_ok_and(|loadout| {
+                loadout
+                    .weapon_in_hand(&EquipmentSlot::MeleeMainHand)
+                    .is_some()
+            });
+        if !has_weapon {
+            continue;
+        }
+
+        let reach = systems::movement::melee_reach(&game_state.world, member);
+        let _ = systems::movement::path_in_range_of_point(
+            game_state,
+            member,
+            target_position,
+            reach,
+            true,
+            true,
+            false,
+            true,
+        );
+        let in_reach =
+            systems::geometry::distance_between_entities(&game_state.world, member, target)
+                .is_some_and(|distance| distance <= reach);
+        if !in_reach
+            || systems::resources::spend(&mut game_state.world, member, &action_cost).is_err()
+        {
+            continue;
+        }
+
+        let action_data = ActionData::new(
+            member,
+            ActionId::new("nat20_core", "action.weapon_attack"),
+            ActionContext::Weapon {
+                slot: EquipmentSlot::MeleeMainHand,
+            },
+            action_cost.clone(),
+            vec![TargetInstance::Entity(target)],
+        );
+        if let Some(resolution) = &mut game_state.mob_attack {
+            resolution.attack.attackers += 1;
+            resolution.attacks.insert(action_data.instance_id);
+        }
+        systems::actions::perform_action(game_state, &action_data);
+    }
+
+    let mob_attack = game_state.mob_attack.take()?.attack;
+    if mob_attack.attackers == 0 {
+        return None;
+    }
+
+    debug!(
+        "Mob of {} attacks {:?}: {} hits for {} damage",
+        mob_attack.attackers, target, mob_attack.hits, mob_attack.damage
+    );
+    if let Some(encounter) = game_state.encounter_mut(encounter_id) {
+        encounter.log_event(Event::encounter_event(EncounterEvent::MobAttack(
+            encounter_id.clone(),
+            mob_attack.clone(),
+        )));
+    }
+    Some(mob_attack)
+}
+
+/// The attacks of the members of a mob report their results like any other
+/// action, but instead of being logged on their own they're tallied into the
+/// mob attack. Returns whether the event was absorbed this way.
+pub(crate) fn absorb_result(game_state: &mut GameState, event: &Event) -> bool {
+    let EventKind::ActionPerformed { action, results } = &event.kind else {
+        return false;
+    };
+    let Some(resolution) = &mut game_state.mob_attack else {
+        return false;
+    };
+    if !resolution.attacks.remove(&action.instance_id) {
+        return false;
+    }
+
+    for result in results {
+        if result.target.entity() != Some(resolution.attack.target) {
+            continue;
+        }
+        let ActionKindResult::Standard(outcome) = &result.kind else {
+            continue;
+        };
+        let Some(damage) = &outcome.damage else {
+            continue;
+        };
+        if let DamageResolutionKind::AttackRoll {
+            attack_roll,
+            armor_class,
+        } = &damage.kind
+            && attack_roll.hits(armor_class)
+        {
+            resolution.attack.hits += 1;
+        }
+        if let Some(damage_taken) = &damage.damage_taken {
+            resolution.attack.damage += damage_taken.total;
+        }
+    }
+    true
+}
@@ -11,6 +11,10 @@ use uom::si::{f32::Length, length::meter};
 use crate::{
     components::{
         actions::targeting::{TargetInstance, TargetingError},
+        faction::Attitude,
+        health::life_state::LifeState,
+        id::EffectId,
+        items::equipment::{loadout::Loadout, slots::EquipmentSlot, weapon::MELEE_RANGE_DEFAULT},
         speed::Speed,
     },
     engine::{
@@ -25,6 +29,13 @@ use crate::{
 pub enum MovementError {
     InsufficientSpeed,
     NoPathFound,
+    /// A hostile creature is in the way
+    Blocked(Entity),
+    /// The path leads closer to the creature the entity is frightened of
+    Frightened(Entity),
+    /// The entity is controlled by another creature which doesn't allow it to
+    /// move
+    Controlled(Entity),
     // Strictly speaking this isn't a movement error, but it makes it easier to
     // handle in the game state if we put it here ;)
     NotYourTurn,
@@ -69,15 +80,52 @@ pub fn path(
             .remaining_movement()
             .clone();
 
-    let taken_path = if full_path.length > remaining_movement && spend_movement {
+    let mut taken_path = if spend_movement
+        && systems::surfaces::movement_cost(game_state, &full_path) > remaining_movement
+    {
         if !allow_partial {
             return Err(MovementError::InsufficientSpeed);
         }
-        full_path.trim_to_length(remaining_movement)
+        full_path.trim_to_length(systems::surfaces::reachable_length(
+            game_state,
+            &full_path,
+            remaining_movement,
+        ))
     } else {
         full_path.clone()
     };
 
+    if let Some((blocker, distance)) =
+        systems::size::blocked_at(&game_state.world, entity, &taken_path)
+    {
+        if !allow_partial {
+            return Err(MovementError::Blocked(blocker));
+        }
+        trace!("Path of {:?} is blocked by {:?}", entity, blocker);
+        taken_path = taken_path.trim_to_length(distance);
+    }
+
+    if let Some((source, distance)) =
+        systems::conditions::fear_limit(&game_state.world, entity, &taken_path)
+    {
+        if !allow_partial {
+            return Err(MovementError::Frightened(source));
+        }
+        trace!(
+            "{:?} is too frightened of {:?} to go further",
+            entity, source
+        );
+        taken_path = taken_path.trim_to_length(distance);
+    }
+
+    if let Some(controller) = systems::control::holds_in_place(&game_state.world, entity) {
+        if !allow_partial {
+            return Err(MovementError::Controlled(controller));
+        }
+        trace!("{:?} is held in place by {:?}", entity, controller);
+        taken_path = taken_path.trim_to_length(Length::new::<meter>(0.0));
+    }
+
     if move_entity {
         // TODO: Actually make them move along the path rather than teleporting to the end
         systems::geometry::teleport_to_ground(
@@ -86,9 +134,15 @@ pub fn path(
             entity,
             taken_path.end().unwrap(),
         );
+        systems::surfaces::on_enter(game_state, entity, &taken_path);
+        systems::traps::on_enter(game_state, entity, &taken_path);
+        systems::antimagic::update(game_state, entity);
+        systems::size::update_squeezing(game_state, entity);
+        systems::discovery::notice(game_state, entity);
         if spend_movement {
+            let cost = systems::surfaces::movement_cost(game_state, &taken_path);
             systems::helpers::get_component_mut::<Speed>(&mut game_state.world, entity)
-                .record_movement(taken_path.length);
+                .record_movement(cost);
         }
     }
 
@@ -98,6 +152,106 @@ pub fn path(
     })
 }
 
+#[derive(Debug, Clone)]
+pub struct MovementPreview {
+    pub path: PathResult,
+    /// Movement spent by taking the path. Movement is only spent in encounters.
+    pub cost: Length,
+    /// Movement left after taking the path
+    pub remaining_movement: Length,
+    /// Hostile creatures whose reach the entity would leave along the path
+    pub opportunity_attackers: Vec<Entity>,
+}
+
+/// Determine what would happen if the entity moved towards the goal, without
+/// actually moving it.
+pub fn preview_movement(
+    game_state: &mut GameState,
+    entity: Entity,
+    goal: &Point3<f32>,
+) -> Result<MovementPreview, MovementError> {
+    let in_combat = game_state.in_combat.contains_key(&entity);
+    let path = path(game_state, entity, goal, true, false, in_combat)?;
+
+    let remaining_movement =
+        systems::helpers::get_component::<Speed>(&game_state.world, entity).remaining_movement();
+    let (cost, opportunity_attackers) = if in_combat {
+        (
+            systems::surfaces::movement_cost(game_state, &path.taken_path),
+            opportunity_attackers(&game_state.world, entity, &path.taken_path),
+        )
+    } else {
+        (Length::new::<meter>(0.0), Vec::new())
+    };
+
+    Ok(MovementPreview {
+        path,
+        cost,
+        remaining_movement: remaining_movement - cost,
+        opportunity_attackers,
+    })
+}
+
+/// The distance at which a creature can make melee attacks, which is the
+/// range of its main hand melee weapon or 5 ft. if it doesn't have one.
+pub fn melee_reach(world: &World, entity: Entity) -> Length {
+    world
+        .get::<&Loadout>(entity)
+        .ok()
+        .and_then(|loadout| {
+            loadout
+                .weapon_in_hand(&EquipmentSlot::MeleeMainHand)
+                .map(|weapon| weapon.range().normal())
+        })
+        .unwrap_or_else(|| MELEE_RANGE_DEFAULT.normal())
+}
+
+/// Hostile creatures that could make an opportunity attack against the entity
+/// if it moved along the path, i.e. creatures whose reach the entity leaves.
+/// Creatures that have taken the Disengage action don't provoke any.
+pub fn opportunity_attackers(world: &World, entity: Entity, path: &WorldPath) -> Vec<Entity> {
+    if systems::effects::is_effect_active(
+        world,
+        entity,
+        &EffectId::new("nat20_core", "effect.disengage"),
+    ) {
+        return Vec::new();
+    }
+
+    let samples: Vec<_> = path
+        .start()
+        .into_iter()
+        .copied()
+        .chain(path.samples().into_iter().map(|(sample, _)| sample))
+        .collect();
+
+    let mut attackers = Vec::new();
+    for (other, life_state) in world.query::<&LifeState>().iter() {
+        if other == entity
+            || *life_state != LifeState::Normal
+            || systems::factions::perceived_threat(world, entity, other) != Attitude::Hostile
+        {
+            continue;
+        }
+        let Some(other_position) = systems::geometry::get_foot_position(world, other) else {
+            continue;
+        };
+
+        let reach = melee_reach(world, other).get::<meter>();
+        let mut in_reach = false;
+        for sample in &samples {
+            let sample_in_reach = (sample - other_position).magnitude() <= reach;
+            if in_reach && !sample_in_reach {
+                attackers.push(other);
+                break;
+            }
+            in_reach = sample_in_reach;
+        }
+    }
+
+    attackers
+}
+
 pub fn path_in_range_of_point(
     game_state: &mut GameState,
     entity: Entity,
@@ -290,7 +444,9 @@ pub fn path_to_target(
             | TargetingError::NoLineOfSight { target } = targeting_error
         {
             let target_position = match target {
-                TargetInstance::Entity(entity) => {
+                TargetInstance::Entity(entity)
+                | TargetInstance::Object(entity)
+                | TargetInstance::Item { owner: entity, .. } => {
                     let (_, shape_pose) =
                         systems::geometry::get_shape(&game_state.world, *entity).unwrap();
                     shape_pose.translation.vector.into()
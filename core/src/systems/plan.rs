@@ -0,0 +1,261 @@
+use hecs::Entity;
+use parry3d::na::Point3;
+use tracing::debug;
+
+use crate::{
+    components::{
+        actions::action::{ActionCooldownMap, ActionsUsedThisTurn},
+        resource::ResourceMap,
+        speed::Speed,
+    },
+    engine::{
+        event::{ActionData, ActionDecision, ActionDecisionKind, ActionError, ActionPromptKind},
+        game_state::GameState,
+    },
+    systems::{
+        self,
+        geometry::CreaturePose,
+        movement::{MovementError, PathResult},
+    },
+};
+
+/// A single intent in a turn plan
+#[derive(Debug, Clone)]
+pub enum PlannedStep {
+    Move(Point3<f32>),
+    Action(ActionData),
+}
+
+/// A sequence of intents queued up by an entity, which are validated as a
+/// whole and then executed one after another
+#[derive(Debug, Clone)]
+pub struct TurnPlan {
+    pub actor: Entity,
+    pub steps: Vec<PlannedStep>,
+}
+
+impl TurnPlan {
+    pub fn new(actor: Entity) -> Self {
+        Self {
+            actor,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, step: PlannedStep) {
+        self.steps.push(step);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub enum PlanError {
+    EmptyPlan,
+    NotYourTurn,
+    /// The actor in one of the actions isn't the one making the plan
+    WrongActor {
+        step: usize,
+        actor: Entity,
+    },
+    Movement {
+        step: usize,
+        error: MovementError,
+    },
+    Action {
+        step: usize,
+        error: ActionError,
+    },
+    /// Something happened while executing the plan which stopped the actor
+    /// from carrying out the rest of it, e.g. a reaction or being knocked out
+    Interrupted {
+        step: usize,
+    },
+}
+
+/// Simulate the plan from the current state of the actor, without changing
+/// anything. Each step is checked against the position, movement, resources and
+/// cooldowns the actor would have after the steps before it, so e.g. the range
+/// of an attack is measured from where the actor will be standing, and an
+/// action that can only be taken once per turn can't be planned twice. The
+/// outcomes of the actions themselves aren't simulated. Returns the reasons for
+/// all the steps that would fail.
+pub fn validate(game_state: &mut GameState, plan: &TurnPlan) -> Result<(), Vec<PlanError>> {
+    if plan.is_empty() {
+        return Err(vec![PlanError::EmptyPlan]);
+    }
+    if !is_turn_of(game_state, plan.actor) {
+        return Err(vec![PlanError::NotYourTurn]);
+    }
+
+    let actor = plan.actor;
+    let in_combat = game_state.in_combat.contains_key(&actor);
+
+    // Snapshot everything the simulation touches, so it can be restored after
+    let pose = systems::helpers::get_component_clone::<CreaturePose>(&game_state.world, actor);
+    let speed = systems::helpers::get_component_clone::<Speed>(&game_state.world, actor);
+    let resources = systems::helpers::get_component_clone::<ResourceMap>(&game_state.world, actor);
+    let cooldowns =
+        systems::helpers::get_component_clone::<ActionCooldownMap>(&game_state.world, actor);
+    let used_this_turn = game_state
+        .world
+        .get::<&ActionsUsedThisTurn>(actor)
+        .map(|used| (*used).clone())
+        .ok();
+
+    let mut errors = Vec::new();
+
+    for (step, planned_step) in plan.steps.iter().enumerate() {
+        match planned_step {
+            PlannedStep::Move(goal) => {
+                match systems::movement::path(game_state, actor, goal, false, false, in_combat) {
+                    Ok(result) => {
+                        systems::geometry::teleport_to_ground(
+                            &mut game_state.world,
+                            &game_state.geometry,
+                            actor,
+                            result.taken_path.end().unwrap(),
+                        );
+                        if in_combat {
+                            systems::helpers::get_component_mut::<Speed>(
+                                &mut game_state.world,
+                                actor,
+                            )
+                            .record_movement(result.taken_path.length);
+                        }
+                    }
+                    Err(error) => errors.push(PlanError::Movement { step, error }),
+                }
+            }
+
+            PlannedStep::Action(action) => {
+                if action.actor != actor {
+                    errors.push(PlanError::WrongActor {
+                        step,
+                        actor: action.actor,
+                    });
+                    continue;
+                }
+
+                let result = systems::actions::action_usable_on_targets(
+                    &game_state.world,
+                    &game_state.geometry,
+                    actor,
+                    &action.action_id,
+                    &action.context,
+                    &action.resource_cost,
+                    &action.targets,
+                )
+                .map_err(ActionError::Usability)
+                .and_then(|_| {
+                    systems::resources::spend(&mut game_state.world, actor, &action.resource_cost)
+                        .map_err(ActionError::Resource)
+                });
+
+                match result {
+                    Ok(()) => {
+                        if let Some(definition) = systems::actions::get_action(&action.action_id) {
+                            systems::actions::record_use(
+                                &mut game_state.world,
+                                actor,
+                                &action.action_id,
+                                definition,
+                            );
+                        }
+                    }
+                    Err(error) => errors.push(PlanError::Action { step, error }),
+                }
+            }
+        }
+    }
+
+    systems::helpers::set_component(&mut game_state.world, actor, pose);
+    systems::helpers::set_component(&mut game_state.world, actor, speed);
+    systems::helpers::set_component(&mut game_state.world, actor, resources);
+    systems::helpers::set_component(&mut game_state.world, actor, cooldowns);
+    match used_this_turn {
+        Some(used) => systems::helpers::set_component(&mut game_state.world, actor, used),
+        None => {
+            let _ = game_state.world.remove_one::<ActionsUsedThisTurn>(actor);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validate the plan and carry it out step by step. Nothing is executed if the
+/// plan isn't valid. If something interrupts the plan while it's being carried
+/// out, the remaining steps are dropped and the step that couldn't be executed
+/// is reported.
+pub fn execute(game_state: &mut GameState, plan: TurnPlan) -> Result<(), Vec<PlanError>> {
+    validate(game_state, &plan)?;
+
+    let actor = plan.actor;
+
+    for (step, planned_step) in plan.steps.into_iter().enumerate() {
+        if !can_continue(game_state, actor) {
+            debug!("Plan of {:?} interrupted before step {}", actor, step);
+            return Err(vec![PlanError::Interrupted { step }]);
+        }
+
+        match planned_step {
+            PlannedStep::Move(goal) => {
+                let result = game_state
+                    .submit_movement(actor, goal)
+                    .map_err(|error| vec![PlanError::Movement { step, error }])?;
+                if !reaches_goal(&result) {
+                    return Err(vec![PlanError::Interrupted { step }]);
+                }
+            }
+
+            PlannedStep::Action(action) => {
+                let kind = ActionDecisionKind::Action { action };
+                let decision = match game_state.next_prompt_entity(actor) {
+                    Some(prompt) if prompt.actors().contains(&actor) => ActionDecision {
+                        response_to: prompt.id,
+                        kind,
+                    },
+                    _ => ActionDecision::without_response_to(kind),
+                };
+                game_state
+                    .submit_decision(decision)
+                    .map_err(|error| vec![PlanError::Action { step, error }])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_turn_of(game_state: &GameState, actor: Entity) -> bool {
+    match game_state.in_combat.get(&actor) {
+        Some(encounter_id) => game_state
+            .encounter(encounter_id)
+            .is_some_and(|encounter| encounter.current_entity() == actor),
+        None => true,
+    }
+}
+
+/// The actor can keep going as long as it's still their turn, they're still
+/// standing, and nobody else has been prompted to make a decision first
+fn can_continue(game_state: &GameState, actor: Entity) -> bool {
+    if !is_turn_of(game_state, actor) || !systems::health::is_alive(&game_state.world, actor) {
+        return false;
+    }
+    match game_state.next_prompt_entity(actor) {
+        Some(prompt) => {
+            matches!(prompt.kind, ActionPromptKind::Action { actor: prompted } if prompted == actor)
+        }
+        None => true,
+    }
+}
+
+fn reaches_goal(result: &PathResult) -> bool {
+    result.is_empty() || result.reaches_goal()
+}
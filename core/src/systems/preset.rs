@@ -0,0 +1,168 @@
+use std::cmp::Ordering;
+
+use hecs::Entity;
+use parry3d::na::Point3;
+use rerecast::Config;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    components::{
+        biography::Biography,
+        id::{MonsterId, Name},
+    },
+    engine::game_state::GameState,
+    registry::registry::MonstersRegistry,
+    systems::{
+        self,
+        geometry::CreaturePose,
+        mapgen::{self, MapGenConfig, MapGenError},
+        time::RestKind,
+    },
+};
+
+/// A creature placed in an encounter preset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetCreature {
+    /// Template the creature is spawned from
+    pub template: MonsterId,
+    pub name: String,
+    pub position: [f32; 3],
+    /// Notes on the creature, e.g. the backstory of a named villain
+    #[serde(default, skip_serializing_if = "Biography::is_empty")]
+    pub biography: Biography,
+}
+
+/// A saved encounter, i.e. the monsters and where they stand, optionally on
+/// a generated map. Player characters are never part of a preset, so the same
+/// encounter can be run with different parties.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncounterPreset {
+    pub name: String,
+    /// Map the encounter takes place on. If not set, the preset is placed on
+    /// whatever map is currently loaded.
+    #[serde(default)]
+    pub map: Option<MapGenConfig>,
+    pub creatures: Vec<PresetCreature>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetError {
+    UnknownMonster(MonsterId),
+    MapGen(MapGenError),
+}
+
+/// Capture every monster in the world which was spawned from a template and
+/// isn't player-controlled. The map config should have its seed set, otherwise
+/// a different map is generated when the preset is restored.
+pub fn capture(
+    game_state: &GameState,
+    name: impl Into<String>,
+    map: Option<MapGenConfig>,
+) -> EncounterPreset {
+    let world = &game_state.world;
+
+    let mut creatures = world
+        .query::<(&MonsterId, &Name, &CreaturePose, Option<&Biography>)>()
+        .iter()
+        .filter(|(entity, _)| !systems::ai::is_player_controlled(world, *entity))
+        .map(|(_, (template, name, pose, biography))| PresetCreature {
+            template: template.clone(),
+            name: name.to_string(),
+            position: pose.translation.vector.into(),
+            biography: biography.cloned().unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    // Keep the file stable between saves of the same encounter
+    creatures.sort_by(|a, b| {
+        a.name.cmp(&b.name).then_with(|| {
+            a.position
+                .iter()
+                .zip(&b.position)
+                .fold(Ordering::Equal, |ordering, (a, b)| {
+                    ordering.then(a.total_cmp(b))
+                })
+        })
+    });
+
+    EncounterPreset {
+        name: name.into(),
+        map,
+        creatures,
+    }
+}
+
+/// Spawn the creatures of the preset, generating its map first if it has one.
+/// Returns every entity spawned for the preset, including the doors, traps and
+/// light sources of the map, so they can be despawned again later.
+pub fn restore(
+    game_state: &mut GameState,
+    preset: &EncounterPreset,
+    navmesh_config: &Config,
+) -> Result<Vec<Entity>, PresetError> {
+    // Check the templates up front so nothing is spawned for a broken preset
+    check_templates(&preset.name, &preset.creatures)?;
+
+    let mut entities = Vec::new();
+
+    if let Some(map_config) = &preset.map {
+        let map = mapgen::generate(map_config).map_err(PresetError::MapGen)?;
+        game_state.geometry = map.to_geometry(navmesh_config);
+        entities.extend(mapgen::spawn_features(game_state, &map));
+    }
+
+    entities.extend(spawn_creatures(
+        game_state,
+        &preset.name,
+        &preset.creatures,
+    )?);
+    Ok(entities)
+}
+
+fn check_templates(name: &str, creatures: &[PresetCreature]) -> Result<(), PresetError> {
+    for creature in creatures {
+        if MonstersRegistry::get(&creature.template).is_none() {
+            warn!("'{}' uses unknown monster {}", name, creature.template);
+            return Err(PresetError::UnknownMonster(creature.template.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Spawn the creatures where they were placed, without touching the map. The
+/// name is only used for logging.
+pub fn spawn_creatures(
+    game_state: &mut GameState,
+    name: &str,
+    creatures: &[PresetCreature],
+) -> Result<Vec<Entity>, PresetError> {
+    check_templates(name, creatures)?;
+
+    let mut monsters = Vec::new();
+    for creature in creatures {
+        // The templates were checked above
+        let template = MonstersRegistry::get(&creature.template).unwrap();
+        let monster = systems::generator::spawn_monster(&mut game_state.world, template);
+        systems::helpers::set_component(
+            &mut game_state.world,
+            monster.id(),
+            Name::new(creature.name.clone()),
+        );
+        systems::geometry::teleport_to(
+            &mut game_state.world,
+            monster.id(),
+            &Point3::from(creature.position),
+        );
+        if !creature.biography.is_empty() {
+            let _ = game_state
+                .world
+                .insert_one(monster.id(), creature.biography.clone());
+        }
+        monsters.push(monster.id());
+    }
+    systems::time::on_rest_end(&mut game_state.world, &monsters, &RestKind::Long);
+
+    debug!("Spawned {} creatures for '{}'", monsters.len(), name);
+
+    Ok(monsters)
+}
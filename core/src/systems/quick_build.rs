@@ -0,0 +1,230 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use hecs::{Entity, World};
+use rand::seq::{IteratorRandom, SliceRandom};
+use strum::IntoEnumIterator;
+use tracing::debug;
+
+use crate::{
+    components::{
+        ability::{Ability, AbilityScoreMap},
+        class::Class,
+        id::{ClassId, FeatId, Name, SpeciesId},
+        level::CharacterLevels,
+        level_up::{ChoiceItem, ChoiceSpec, LevelUpPrompt},
+        proficiency::ProficiencyLevel,
+        skill::{SkillSet, skill_ability},
+        spells::spellbook::SpellSource,
+    },
+    entities::character::Character,
+    registry::registry::{ClassesRegistry, SpeciesRegistry, SpellListsRegistry},
+    systems::{
+        self,
+        level_up::{LevelUpDecision, LevelUpError, LevelUpSession},
+    },
+};
+
+/// Build a character of the given class and species (or random ones) at the
+/// given level, without asking for any decisions. See `level_up_to` for how the
+/// prompts are resolved.
+pub fn quick_build(
+    name: Name,
+    class_id: Option<ClassId>,
+    species_id: Option<SpeciesId>,
+    level: u8,
+) -> Result<Character, LevelUpError> {
+    let class_id = match class_id {
+        Some(class_id) => class_id,
+        None => ClassesRegistry::keys()
+            .choose(&mut rand::rng())
+            .cloned()
+            .ok_or(LevelUpError::RegistryMissing("class".to_string()))?,
+    };
+
+    let mut world = World::new();
+    let entity = world.spawn(Character::new(name));
+    level_up_to(&mut world, entity, &class_id, species_id.as_ref(), level)?;
+
+    Ok(Character::from_world(&world, entity))
+}
+
+/// Level up the entity in the class until it reaches the given total level,
+/// resolving every prompt automatically. Ability scores follow the recommended
+/// distribution of the class, and ability score improvements go to its main
+/// abilities. Skills are picked to match the highest abilities, the species is
+/// the given one if any, spells are picked from the ones recommended by the
+/// spell list of the class, and everything else (background, ...) is picked at
+/// random.
+pub fn level_up_to(
+    world: &mut World,
+    entity: Entity,
+    class_id: &ClassId,
+    species_id: Option<&SpeciesId>,
+    level: u8,
+) -> Result<(), LevelUpError> {
+    let Some(class) = ClassesRegistry::get(class_id) else {
+        return Err(LevelUpError::RegistryMissing(class_id.to_string()));
+    };
+    if let Some(species_id) = species_id
+        && SpeciesRegistry::get(species_id).is_none()
+    {
+        return Err(LevelUpError::RegistryMissing(species_id.to_string()));
+    }
+
+    while systems::helpers::get_component::<CharacterLevels>(world, entity).total_level() < level {
+        let mut session = LevelUpSession::new(world, entity);
+        while let Some(prompt) = session.pending_prompts().first().cloned() {
+            let decision = decide(world, entity, &prompt, class, species_id);
+            debug!("Quick build decision for {:?}: {:?}", prompt, decision);
+            session.advance(world, &decision)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn decide(
+    world: &World,
+    entity: Entity,
+    prompt: &LevelUpPrompt,
+    class: &Class,
+    species_id: Option<&SpeciesId>,
+) -> LevelUpDecision {
+    match prompt {
+        LevelUpPrompt::Choice(spec) => LevelUpDecision::from_choice(
+            spec.id.clone(),
+            choose_options(spec, &class.id, species_id),
+        ),
+
+        LevelUpPrompt::AbilityScores(_, _) => {
+            LevelUpDecision::AbilityScores(class.default_abilities.clone())
+        }
+
+        LevelUpPrompt::AbilityScoreImprovement {
+            budget,
+            abilities,
+            max_score,
+            ..
+        } => {
+            let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
+            let mut points = HashMap::new();
+            let mut remaining = *budget;
+            for ability in ability_priority(class)
+                .into_iter()
+                .filter(|ability| abilities.contains(ability))
+            {
+                let ability_score = ability_scores.get(&ability);
+                let room = (ability_score.max_score(*max_score as i32)
+                    - ability_score.unset_total())
+                .max(0) as u8;
+                let bonus = room.min(remaining);
+                if bonus > 0 {
+                    points.insert(ability, bonus);
+                    remaining -= bonus;
+                }
+                if remaining == 0 {
+                    break;
+                }
+            }
+            LevelUpDecision::AbilityScoreImprovement(points)
+        }
+
+        LevelUpPrompt::SkillProficiency(skills, count, _) => {
+            let skill_set = systems::helpers::get_component::<SkillSet>(world, entity);
+            let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
+
+            // Skills the entity isn't already proficient in come first, and
+            // within those the ones using the highest abilities
+            let mut candidates = skills.iter().cloned().collect::<Vec<_>>();
+            candidates.sort_by_key(|skill| {
+                let proficient =
+                    skill_set.get(skill).proficiency().level() != &ProficiencyLevel::None;
+                let score = skill_ability(skill)
+                    .map(|ability| ability_scores.get(&ability).total())
+                    .unwrap_or(0);
+                (proficient, Reverse(score))
+            });
+
+            LevelUpDecision::SkillProficiency(
+                candidates.into_iter().take(*count as usize).collect(),
+            )
+        }
+
+        // Keep the spells which were picked when they were learned
+        LevelUpPrompt::ReplaceSpells { .. } => {
+            LevelUpDecision::ReplaceSpells { spells: Vec::new() }
+        }
+    }
+}
+
+fn choose_options(
+    spec: &ChoiceSpec,
+    class_id: &ClassId,
+    species_id: Option<&SpeciesId>,
+) -> Vec<ChoiceItem> {
+    let class = ChoiceItem::Class(class_id.clone());
+    if spec.options.contains(&class) {
+        return vec![class];
+    }
+
+    if let Some(species_id) = species_id {
+        let species = ChoiceItem::Species(species_id.clone());
+        if spec.options.contains(&species) {
+            return vec![species];
+        }
+    }
+
+    let ability_score_improvement =
+        ChoiceItem::Feat(FeatId::new("nat20_core", "feat.ability_score_improvement"));
+    if spec.options.contains(&ability_score_improvement)
+        && spec.disabled_reason(&ability_score_improvement).is_none()
+    {
+        return vec![ability_score_improvement];
+    }
+
+    // Shuffle first so options without a priority are still picked at random
+    let mut options = spec
+        .options
+        .iter()
+        .filter(|option| spec.disabled_reason(option).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    options.shuffle(&mut rand::rng());
+    options.sort_by_key(recommendation);
+    options.truncate(spec.picks as usize);
+    options
+}
+
+/// Where the option is on the list of recommended spells of the spell list it
+/// would be learned from. Anything which isn't recommended comes last.
+fn recommendation(option: &ChoiceItem) -> usize {
+    let ChoiceItem::Spell(spell_id, SpellSource::Class(class_and_subclass)) = option else {
+        return usize::MAX;
+    };
+    ClassesRegistry::get(&class_and_subclass.class)
+        .and_then(|class| class.spellcasting_rules(&class_and_subclass.subclass))
+        .and_then(|rules| SpellListsRegistry::get(&rules.spell_list))
+        .and_then(|spell_list| {
+            spell_list
+                .recommended
+                .iter()
+                .position(|recommended| recommended == spell_id)
+        })
+        .unwrap_or(usize::MAX)
+}
+
+/// The abilities of the class ordered by how much the class benefits from
+/// them, i.e. the ones getting the background bonuses first, then the rest by
+/// their recommended score
+fn ability_priority(class: &Class) -> Vec<Ability> {
+    let distribution = &class.default_abilities;
+    let mut abilities = Ability::iter().collect::<Vec<_>>();
+    abilities.sort_by_key(|ability| {
+        (
+            *ability != distribution.plus_2_bonus,
+            *ability != distribution.plus_1_bonus,
+            Reverse(distribution.scores.get(ability).copied().unwrap_or(0)),
+        )
+    });
+    abilities
+}
@@ -1,10 +1,14 @@
 use hecs::{Entity, World};
+use tracing::warn;
 
 use crate::{
     components::{
         actions::action::ActionCooldownMap,
         id::ResourceId,
-        resource::{RechargeRule, ResourceAmountMap, ResourceError, ResourceMap},
+        resource::{
+            RechargeRule, ResourceAmount, ResourceAmountMap, ResourceBudgetKind,
+            ResourceDefinitionKind, ResourceError, ResourceMap,
+        },
     },
     registry::registry::ResourcesRegistry,
     systems,
@@ -30,6 +34,9 @@ pub fn recharge(world: &mut World, entity: Entity, rest_type: &RechargeRule) {
 
     systems::helpers::get_component_mut::<ActionCooldownMap>(world, entity)
         .retain(|_, recharge_rule| !recharge_rule.is_recharged_by(rest_type));
+
+    systems::inventory::recharge_items(world, entity, rest_type);
+    systems::spells::recharge_innate(world, entity, rest_type);
 }
 
 pub fn can_afford(
@@ -55,3 +62,54 @@ pub fn restore(
 ) -> Result<(), ResourceError> {
     systems::helpers::get_component_mut::<ResourceMap>(world, entity).restore_all(restoration)
 }
+
+/// The size of a pool resource for the entity, e.g. 5 Hit Points per Paladin
+/// level for Lay on Hands. Returns `None` if the resource isn't a pool.
+pub fn pool_size(world: &World, entity: Entity, resource: &ResourceId) -> Option<u8> {
+    let ResourceDefinitionKind::Pool { per_level, class } = &ResourcesRegistry::get(resource)?.kind
+    else {
+        return None;
+    };
+    let level = systems::class::class_level(world, entity, class);
+    Some(per_level.saturating_mul(level))
+}
+
+/// Give the entity a full pool resource sized after its class level
+pub fn add_pool(world: &mut World, entity: Entity, resource: &ResourceId) {
+    let Some(size) = pool_size(world, entity, resource) else {
+        warn!("Resource {} is not a pool", resource);
+        return;
+    };
+    systems::helpers::get_component_mut::<ResourceMap>(world, entity).add(
+        resource.clone(),
+        ResourceBudgetKind::from(ResourceAmount::Flat(size)),
+        true,
+    );
+}
+
+/// Resize the pools of the entity after its level has changed. A pool which
+/// grows gains the new uses right away, while one which shrinks loses its
+/// uses above the new maximum.
+pub fn update_pools(world: &mut World, entity: Entity) {
+    let pools = systems::helpers::get_component::<ResourceMap>(world, entity)
+        .iter()
+        .filter_map(|(resource, _)| {
+            pool_size(world, entity, resource).map(|size| (resource.clone(), size))
+        })
+        .collect::<Vec<_>>();
+
+    let mut resources = systems::helpers::get_component_mut::<ResourceMap>(world, entity);
+    for (resource, size) in pools {
+        let Some(ResourceBudgetKind::Flat(budget)) = resources.get_mut(&resource) else {
+            continue;
+        };
+        let result = if size > budget.max_uses {
+            budget.add_uses(size - budget.max_uses)
+        } else {
+            budget.remove_uses(budget.max_uses - size)
+        };
+        if let Err(error) = result {
+            warn!("Failed to resize pool {}: {:?}", resource, error);
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{HashMap, HashSet};
+
+use hecs::{Entity, World};
+use parry3d::na::Point3;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    components::{
+        ai::PlayerControlledTag,
+        id::{MonsterId, Name},
+    },
+    engine::game_state::GameState,
+    registry::registry::MonstersRegistry,
+    systems::{self, geometry::CreaturePose, time::RestKind},
+};
+
+/// A point in the world, either fixed or following an actor of the scene
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScenePoint {
+    Position([f32; 3]),
+    Actor(String),
+}
+
+/// A single step of a scene. Spawning and starting combat happen instantly,
+/// while the other steps hold up the scene until they're done.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SceneStep {
+    /// Move the camera to look at a point over the given time
+    Camera {
+        target: ScenePoint,
+        /// Distance from the camera to the target. If not set, the camera
+        /// keeps its current distance.
+        #[serde(default)]
+        distance: Option<f32>,
+        #[serde(default)]
+        seconds: f32,
+    },
+    /// Show a line of dialogue until the player continues the scene
+    Dialogue {
+        speaker: String,
+        line: String,
+    },
+    /// Spawn a monster, which later steps can refer to as `actor`
+    Spawn {
+        actor: String,
+        template: MonsterId,
+        /// Defaults to the name of the template
+        #[serde(default)]
+        name: Option<String>,
+        position: [f32; 3],
+    },
+    /// Start an encounter between the given actors and, unless disabled, every
+    /// player-controlled creature in the world
+    StartCombat {
+        actors: Vec<String>,
+        #[serde(default = "default_include_players")]
+        include_players: bool,
+    },
+    /// Set a progress flag of the adventure being played, e.g. to complete a
+    /// quest objective once the party has heard someone out
+    SetFlag {
+        flag: String,
+    },
+    Wait {
+        seconds: f32,
+    },
+}
+
+fn default_include_players() -> bool {
+    true
+}
+
+/// A scripted sequence of steps, e.g. an ambush where the camera pans to the
+/// goblins, their leader taunts the party and then combat starts. Scenes are
+/// plain data, so they can be written without touching any code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub name: String,
+    pub steps: Vec<SceneStep>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneError {
+    UnknownMonster(MonsterId),
+    /// An actor is used before the step which spawns it
+    UnknownActor(String),
+    DuplicateActor(String),
+}
+
+/// Plays a scene one step at a time. The game state is only changed by the
+/// steps themselves, so presenting the camera moves and dialogue is left to
+/// whoever drives the player.
+#[derive(Debug, Clone)]
+pub struct ScenePlayer {
+    scene: Scene,
+    step: usize,
+    /// Time spent on the current step, in seconds
+    elapsed: f32,
+    actors: HashMap<String, Entity>,
+}
+
+impl ScenePlayer {
+    /// Check the scene up front, so nothing is spawned for a broken scene
+    pub fn new(scene: Scene) -> Result<Self, SceneError> {
+        let mut actors = HashSet::new();
+        let check_actor = |actors: &HashSet<&String>, actor: &String| {
+            if actors.contains(actor) {
+                Ok(())
+            } else {
+                Err(SceneError::UnknownActor(actor.clone()))
+            }
+        };
+
+        for step in &scene.steps {
+            match step {
+                SceneStep::Camera {
+                    target: ScenePoint::Actor(actor),
+                    ..
+                } => check_actor(&actors, actor)?,
+                SceneStep::Spawn {
+                    actor, template, ..
+                } => {
+                    if MonstersRegistry::get(template).is_none() {
+                        warn!("Scene '{}' uses unknown monster {}", scene.name, template);
+                        return Err(SceneError::UnknownMonster(template.clone()));
+                    }
+                    if !actors.insert(actor) {
+                        return Err(SceneError::DuplicateActor(actor.clone()));
+                    }
+                }
+                SceneStep::StartCombat {
+                    actors: fighters, ..
+                } => {
+                    for actor in fighters {
+                        check_actor(&actors, actor)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            scene,
+            step: 0,
+            elapsed: 0.0,
+            actors: HashMap::new(),
+        })
+    }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    pub fn step_index(&self) -> usize {
+        self.step
+    }
+
+    pub fn current_step(&self) -> Option<&SceneStep> {
+        self.scene.steps.get(self.step)
+    }
+
+    pub fn step_elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.scene.steps.len()
+    }
+
+    /// The entity spawned for an actor, if it has been spawned yet
+    pub fn actor(&self, actor: &str) -> Option<Entity> {
+        self.actors.get(actor).copied()
+    }
+
+    pub fn actors(&self) -> &HashMap<String, Entity> {
+        &self.actors
+    }
+
+    /// Where the point currently is. Actors which have been despawned don't
+    /// have a position.
+    pub fn position(&self, world: &World, point: &ScenePoint) -> Option<Point3<f32>> {
+        match point {
+            ScenePoint::Position(position) => Some(Point3::from(*position)),
+            ScenePoint::Actor(actor) => {
+                let entity = self.actor(actor)?;
+                world
+                    .get::<&CreaturePose>(entity)
+                    .ok()
+                    .map(|pose| Point3::from(pose.translation.vector))
+            }
+        }
+    }
+
+    /// Move on from the current step, e.g. when the player has read a line of
+    /// dialogue or wants to skip a camera move
+    pub fn advance(&mut self) {
+        if !self.is_finished() {
+            self.step += 1;
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// Play the scene until it reaches a step which has to wait, either for
+    /// time to pass or for the player to continue
+    pub fn update(&mut self, game_state: &mut GameState, delta_time: f32) {
+        self.elapsed += delta_time;
+
+        while let Some(step) = self.current_step().cloned() {
+            match step {
+                SceneStep::Camera { seconds, .. } | SceneStep::Wait { seconds } => {
+                    if self.elapsed < seconds {
+                        return;
+                    }
+                }
+                SceneStep::Dialogue { .. } => return,
+                SceneStep::Spawn {
+                    actor,
+                    template,
+                    name,
+                    position,
+                } => self.spawn(game_state, actor, &template, name, position),
+                SceneStep::StartCombat {
+                    actors,
+                    include_players,
+                } => self.start_combat(game_state, &actors, include_players),
+                SceneStep::SetFlag { flag } => {
+                    systems::campaign::set_flag(game_state, &flag);
+                }
+            }
+            self.advance();
+        }
+    }
+
+    fn spawn(
+        &mut self,
+        game_state: &mut GameState,
+        actor: String,
+        template: &MonsterId,
+        name: Option<String>,
+        position: [f32; 3],
+    ) {
+        // The templates were checked when the player was created
+        let template = MonstersRegistry::get(template).unwrap();
+        let monster = systems::generator::spawn_monster(&mut game_state.world, template);
+        if let Some(name) = name {
+            systems::helpers::set_component(&mut game_state.world, monster.id(), Name::new(name));
+        }
+        systems::geometry::teleport_to(
+            &mut game_state.world,
+            monster.id(),
+            &Point3::from(position),
+        );
+        systems::time::on_rest_end(&mut game_state.world, &[monster.id()], &RestKind::Long);
+
+        debug!(
+            "Scene '{}' spawned {:?} as '{}'",
+            self.scene.name,
+            monster.id(),
+            actor
+        );
+        self.actors.insert(actor, monster.id());
+    }
+
+    fn start_combat(&self, game_state: &mut GameState, actors: &[String], include_players: bool) {
+        let mut participants = actors
+            .iter()
+            .filter_map(|actor| self.actor(actor))
+            .filter(|entity| game_state.world.contains(*entity))
+            .collect::<HashSet<_>>();
+        if include_players {
+            participants.extend(
+                game_state
+                    .world
+                    .query::<&PlayerControlledTag>()
+                    .iter()
+                    .map(|(entity, _)| entity),
+            );
+        }
+
+        if let Some(entity) = participants
+            .iter()
+            .find(|entity| game_state.in_combat.contains_key(entity))
+        {
+            warn!(
+                "Scene '{}' cannot start combat: {:?} is already in an encounter",
+                self.scene.name, entity
+            );
+            return;
+        }
+
+        let encounter_id = game_state.start_encounter(participants);
+        debug!(
+            "Scene '{}' started encounter {}",
+            self.scene.name, encounter_id
+        );
+    }
+}
@@ -1,26 +1,35 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
-use hecs::World;
+use hecs::{Entity, World};
 use tracing::error;
 
 use crate::{
     components::{
-        actions::action::{ActionKindResult, ReactionResult},
+        actions::{
+            action::{ActionKind, ActionKindResult, ReactionResult},
+            modification::EventModification,
+        },
+        damage::DamageComponentResult,
+        dice::DiceSet,
         id::ScriptId,
-        modifier::{Modifiable, ModifierSource},
+        modifier::{DiceModifier, ModifierSource},
         resource::ResourceAmountMap,
+        species::CreatureType,
     },
     engine::{
         event::{ActionData, CallbackResult, Event, EventCallback, EventKind, ReactionData},
         game_state::GameState,
     },
-    registry::registry::ScriptsRegistry,
+    registry::{
+        registry::ScriptsRegistry,
+        serialize::{dice::DamageEquation, parser::Evaluable, variables::PARSER_VARIABLES},
+    },
     scripts::{
         script_api::{
-            ScriptActionView, ScriptDamageMitigationResult, ScriptDamageRollResult,
-            ScriptEffectView, ScriptEntityRole, ScriptEntityView, ScriptEventRef,
-            ScriptOptionalEntityView, ScriptReactionBodyContext, ScriptReactionPlan,
-            ScriptReactionTriggerContext,
+            ScriptActionView, ScriptAttackRoll, ScriptDamageMitigationResult,
+            ScriptDamageRollResult, ScriptEffectView, ScriptEntityRole, ScriptEntityView,
+            ScriptEventRef, ScriptOptionalEntityView, ScriptReactionBodyContext,
+            ScriptReactionPlan, ScriptReactionTriggerContext,
         },
         script_engine::SCRIPT_ENGINES,
     },
@@ -160,6 +169,35 @@ pub fn evaluate_armor_class_hook(
     }
 }
 
+pub fn evaluate_incoming_attack_roll_hook(
+    incoming_attack_roll_hook: &ScriptId,
+    target_view: &ScriptEntityView,
+    attacker_view: &ScriptEntityView,
+    attack_roll: &ScriptAttackRoll,
+) {
+    let script = ScriptsRegistry::get(incoming_attack_roll_hook).expect(
+        format!(
+            "Incoming attack roll hook script not found in registry: {:?}",
+            incoming_attack_roll_hook
+        )
+        .as_str(),
+    );
+    let mut engine_lock = SCRIPT_ENGINES.lock().unwrap();
+    let engine = engine_lock
+        .get_mut(&script.language)
+        .expect(format!("No script engine found for language: {:?}", script.language).as_str());
+    match engine.evaluate_incoming_attack_roll_hook(script, target_view, attacker_view, attack_roll)
+    {
+        Ok(()) => {}
+        Err(err) => {
+            error!(
+                "Error evaluating incoming attack roll hook script {:?} for entity {:?}: {:?}",
+                incoming_attack_roll_hook, target_view.entity, err
+            );
+        }
+    }
+}
+
 pub fn evaluate_damage_roll_result_hook(
     damage_roll_result_hook: &ScriptId,
     entity_view: &ScriptEntityView,
@@ -291,112 +329,68 @@ pub fn apply_reaction_plan(
         }
 
         ScriptReactionPlan::ModifyD20Result { bonus } => {
-            let bonus_value = bonus.evaluate(
+            let bonus = bonus.evaluate(
                 &game_state.world,
                 reaction_data.reactor,
                 &reaction_data.context,
             );
-
-            let result = ReactionResult::ModifyEvent {
-                modification: Arc::new({
-                    let action_id = reaction_data.reaction_id.clone();
-                    move |_world: &World, event: &mut Event| {
-                        if let EventKind::D20CheckPerformed(_, ref mut existing_result, _) =
-                            event.kind
-                        {
-                            match existing_result {
-                                D20ResultKind::Skill { result, .. }
-                                | D20ResultKind::SavingThrow { result, .. } => {
-                                    result.add_bonus(
-                                        ModifierSource::Action(action_id.clone()),
-                                        bonus_value,
-                                    );
-                                }
-                                D20ResultKind::AttackRoll { result } => {
-                                    result.roll_result.add_bonus(
-                                        ModifierSource::Action(action_id.clone()),
-                                        bonus_value,
-                                    );
-                                }
-                            }
-                        } else {
-                            panic!("ModifyD20Result applied to wrong event type: {:?}", event);
-                        }
-                    }
-                }),
-            };
-
-            let process_event_result = game_state.process_event(Event::action_performed_event(
+            modify_trigger_event(
                 game_state,
-                &ActionData::from(reaction_data),
-                vec![(reaction_data.reactor, ActionKindResult::Reaction { result })],
-            ));
+                reaction_data,
+                EventModification::D20Bonus {
+                    source: ModifierSource::Action(reaction_data.reaction_id.clone()),
+                    bonus,
+                },
+            );
+        }
 
-            match process_event_result {
-                Ok(_) => {}
+        ScriptReactionPlan::PenalizeD20Result { penalty } => {
+            let penalty = match penalty
+                .evaluate(
+                    &game_state.world,
+                    reaction_data.reactor,
+                    &reaction_data.context,
+                    &PARSER_VARIABLES,
+                )
+                .map_err(|err| format!("{:?}", err))
+                .and_then(|(num_dice, size, _)| {
+                    DiceSet::from_str(format!("{}d{}", num_dice, size).as_str())
+                }) {
+                Ok(penalty) => penalty,
                 Err(err) => {
                     error!(
-                        "Error processing ModifyD20Result reaction for reactor {:?}: {:?}",
-                        reaction_data.reactor, err
+                        "Error evaluating penalty of reaction {:?} for reactor {:?}: {}",
+                        reaction_data.reaction_id, reaction_data.reactor, err
                     );
+                    return;
                 }
-            }
+            };
+            modify_trigger_event(
+                game_state,
+                reaction_data,
+                EventModification::D20Dice {
+                    source: ModifierSource::Action(reaction_data.reaction_id.clone()),
+                    roll: DiceModifier::penalty(penalty).roll_with_policy(
+                        &systems::helpers::roll_policy(&game_state.world, reaction_data.reactor),
+                    ),
+                },
+            );
         }
 
         ScriptReactionPlan::ModifyD20DC { modifier } => {
-            let modifier_value = modifier.evaluate(
+            let bonus = modifier.evaluate(
                 &game_state.world,
                 reaction_data.reactor,
                 &reaction_data.context,
             );
-
-            let result = ReactionResult::ModifyEvent {
-                modification: Arc::new({
-                    let action = reaction_data.reaction_id.clone();
-                    move |_world: &World, event: &mut Event| {
-                        if let EventKind::D20CheckPerformed(_, _, ref mut dc_kind) = event.kind {
-                            match dc_kind {
-                                D20CheckDCKind::SavingThrow(d20_check_dc) => {
-                                    d20_check_dc.dc.add_modifier(
-                                        ModifierSource::Action(action.clone()),
-                                        modifier_value,
-                                    );
-                                }
-                                D20CheckDCKind::Skill(d20_check_dc) => {
-                                    d20_check_dc.dc.add_modifier(
-                                        ModifierSource::Action(action.clone()),
-                                        modifier_value,
-                                    );
-                                }
-                                D20CheckDCKind::AttackRoll(_, armor_class) => {
-                                    armor_class.add_modifier(
-                                        ModifierSource::Action(action.clone()),
-                                        modifier_value,
-                                    );
-                                }
-                            }
-                        } else {
-                            panic!("ModifyD20DC applied to wrong event type: {:?}", event);
-                        }
-                    }
-                }),
-            };
-
-            let process_event_result = game_state.process_event(Event::action_performed_event(
+            modify_trigger_event(
                 game_state,
-                &ActionData::from(reaction_data),
-                vec![(reaction_data.reactor, ActionKindResult::Reaction { result })],
-            ));
-
-            match process_event_result {
-                Ok(_) => {}
-                Err(err) => {
-                    error!(
-                        "Error processing ModifyD20DC reaction for reactor {:?}: {:?}",
-                        reaction_data.reactor, err
-                    );
-                }
-            }
+                reaction_data,
+                EventModification::DCBonus {
+                    source: ModifierSource::Action(reaction_data.reaction_id.clone()),
+                    bonus,
+                },
+            );
         }
 
         ScriptReactionPlan::RerollD20Result {
@@ -413,54 +407,70 @@ pub fn apply_reaction_plan(
                 0
             };
 
-            let result = ReactionResult::ModifyEvent {
-                modification: Arc::new({
-                    let actor = reaction_data.event.actor().unwrap();
-                    let action_id = reaction_data.reaction_id.clone();
-                    move |world: &World, event: &mut Event| {
-                        if let EventKind::D20CheckPerformed(
-                            _,
-                            ref mut existing_result,
-                            ref dc_kind,
-                        ) = event.kind
-                        {
-                            let mut new_roll = systems::d20::check_no_event(world, actor, dc_kind);
-                            new_roll
-                                .d20_result_mut()
-                                .add_bonus(ModifierSource::Action(action_id.clone()), bonus_value);
-
-                            if force_use_new {
-                                *existing_result = new_roll;
-                            } else {
-                                // Choose the better of the two rolls
-                                let existing_total = existing_result.d20_result().total();
-                                let new_total = new_roll.d20_result().total();
-                                if new_total > existing_total {
-                                    *existing_result = new_roll;
-                                }
-                            }
-                        } else {
-                            panic!("RerollD20Result applied to wrong event type: {:?}", event);
-                        }
-                    }
-                }),
+            let trigger = pending_trigger_event(game_state, reaction_data);
+            let EventKind::D20CheckPerformed(actor, _, dc_kind) = &trigger.kind else {
+                error!(
+                    "RerollD20Result reaction {:?} triggered by wrong event type: {:?}",
+                    reaction_data.reaction_id, trigger
+                );
+                return;
             };
 
-            let process_event_result = game_state.process_event(Event::action_performed_event(
+            let mut new_roll = systems::d20::check_no_event(&game_state.world, *actor, dc_kind);
+            new_roll.d20_result_mut().add_bonus(
+                ModifierSource::Action(reaction_data.reaction_id.clone()),
+                bonus_value,
+            );
+
+            modify_trigger_event(
                 game_state,
-                &ActionData::from(reaction_data),
-                vec![(reaction_data.reactor, ActionKindResult::Reaction { result })],
-            ));
+                reaction_data,
+                EventModification::RerollD20 {
+                    new_roll,
+                    force_use_new,
+                },
+            );
+        }
 
-            match process_event_result {
-                Ok(_) => {}
-                Err(err) => {
-                    error!(
-                        "Error processing RerollD20Result reaction for reactor {:?}: {:?}",
-                        reaction_data.reactor, err
-                    );
-                }
-            }
+        ScriptReactionPlan::AddDamage { damage, against } => {
+            let trigger = pending_trigger_event(game_state, reaction_data);
+            let EventKind::DamageRollPerformed(_, damage_roll, target) = &trigger.kind else {
+                error!(
+                    "AddDamage reaction {:?} triggered by wrong event type: {:?}",
+                    reaction_data.reaction_id, trigger
+                );
+                return;
+            };
+
+            let components = roll_added_damage(
+                &game_state.world,
+                reaction_data,
+                *target,
+                damage_roll.crit,
+                &damage,
+                &against,
+            );
+            modify_trigger_event(
+                game_state,
+                reaction_data,
+                EventModification::AddDamage { components },
+            );
+        }
+
+        ScriptReactionPlan::ReduceDamage { reduction } => {
+            let amount = reduction.evaluate(
+                &game_state.world,
+                reaction_data.reactor,
+                &reaction_data.context,
+            );
+            modify_trigger_event(
+                game_state,
+                reaction_data,
+                EventModification::ReduceDamage {
+                    source: ModifierSource::Action(reaction_data.reaction_id.clone()),
+                    amount,
+                },
+            );
         }
 
         ScriptReactionPlan::CancelEvent {
@@ -567,3 +577,137 @@ pub fn apply_reaction_plan(
         }
     }
 }
+
+/// Roll the damage a reaction adds to a damage roll against the target. The
+/// damage for specific creature types is used instead if the target is one of
+/// them. The dice are doubled if the damage roll was for a critical hit.
+fn roll_added_damage(
+    world: &World,
+    reaction_data: &ReactionData,
+    target: Entity,
+    crit: bool,
+    damage: &DamageEquation,
+    against: &Option<(Vec<CreatureType>, DamageEquation)>,
+) -> Vec<DamageComponentResult> {
+    let equation = match (against, world.get::<&CreatureType>(target)) {
+        (Some((creature_types, equation)), Ok(creature_type))
+            if creature_types.contains(&*creature_type) =>
+        {
+            equation
+        }
+        _ => damage,
+    };
+
+    (equation.function)(world, reaction_data.reactor, &reaction_data.context)
+        .roll(crit)
+        .components
+}
+
+/// Apply a reaction to its trigger event before the event is processed, for
+/// reactions which are decided on while the event is being resolved, e.g.
+/// smiting right after an attack hits. Only reactions which add damage can be
+/// applied this way.
+pub fn apply_reaction_to_event(world: &World, reaction_data: &ReactionData, event: &mut Event) {
+    let Some(ActionKind::Reaction { reaction }) =
+        systems::actions::get_action(&reaction_data.reaction_id).map(|action| &action.kind)
+    else {
+        error!(
+            "Reaction {:?} isn't scripted and can't be applied to an event",
+            reaction_data.reaction_id
+        );
+        return;
+    };
+    let plan = evaluate_reaction_body(reaction, &ScriptReactionBodyContext::from(reaction_data));
+    apply_reaction_plan_to_event(world, reaction_data, plan, event);
+}
+
+fn apply_reaction_plan_to_event(
+    world: &World,
+    reaction_data: &ReactionData,
+    plan: ScriptReactionPlan,
+    event: &mut Event,
+) {
+    match plan {
+        ScriptReactionPlan::None => {}
+
+        ScriptReactionPlan::Sequence(plans) => {
+            for plan in plans {
+                apply_reaction_plan_to_event(world, reaction_data, plan, event);
+            }
+        }
+
+        ScriptReactionPlan::AddDamage { damage, against } => {
+            let EventKind::DamageRollPerformed(_, damage_roll, target) = &event.kind else {
+                error!(
+                    "AddDamage reaction {:?} applied to wrong event type: {:?}",
+                    reaction_data.reaction_id, event
+                );
+                return;
+            };
+            let components = roll_added_damage(
+                world,
+                reaction_data,
+                *target,
+                damage_roll.crit,
+                &damage,
+                &against,
+            );
+            let _ = EventModification::AddDamage { components }.apply(event);
+        }
+
+        _ => error!(
+            "Reaction {:?} can only add damage when applied to an event before it's processed",
+            reaction_data.reaction_id
+        ),
+    }
+}
+
+fn pending_trigger_event(game_state: &GameState, reaction_data: &ReactionData) -> Event {
+    game_state
+        .session_for_entity(reaction_data.reactor)
+        .and_then(|session| {
+            session
+                .pending_events()
+                .iter()
+                .find(|event| event.id == reaction_data.event.id)
+        })
+        .cloned()
+        .unwrap_or_else(|| (*reaction_data.event).clone())
+}
+
+fn modify_trigger_event(
+    game_state: &mut GameState,
+    reaction_data: &ReactionData,
+    modification: EventModification,
+) {
+    let trigger = pending_trigger_event(game_state, reaction_data);
+    let Some(delta) = modification.preview(&trigger) else {
+        error!(
+            "Reaction {:?} can't modify event {:?} with {:?}",
+            reaction_data.reaction_id, trigger, modification
+        );
+        return;
+    };
+
+    let result = ReactionResult::ModifyEvent {
+        event: trigger.id,
+        modification,
+        delta,
+    };
+
+    let process_event_result = game_state.process_event(Event::action_performed_event(
+        game_state,
+        &ActionData::from(reaction_data),
+        vec![(reaction_data.reactor, ActionKindResult::Reaction { result })],
+    ));
+
+    match process_event_result {
+        Ok(_) => {}
+        Err(err) => {
+            error!(
+                "Error processing ModifyEvent reaction for reactor {:?}: {:?}",
+                reaction_data.reactor, err
+            );
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::cmp::Reverse;
+
+use hecs::{Entity, World};
+use rand::seq::IteratorRandom;
+use tracing::debug;
+
+use crate::{
+    components::{
+        ability::AbilityScoreMap,
+        effects::effect::{EffectInstanceTemplate, EffectLifetimeEntiy, EffectLifetimeTemplate},
+        health::hit_points::HitPoints,
+        id::{ClassId, EffectId, MonsterId},
+        level::Level,
+        modifier::ModifierSource,
+        shapechange::{OriginalForm, SHAPECHANGE_ABILITIES, Shapechanged},
+        species::{CreatureSize, CreatureType},
+        speed::Speed,
+        time::{TimeDuration, TurnBoundary},
+    },
+    entities::monster::MonsterTemplate,
+    registry::registry::MonstersRegistry,
+    systems::{self, generator::MonsterFilter},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapechangeError {
+    UnknownForm(MonsterId),
+    NoMatchingForm,
+    /// The creature has to revert before it can take another form
+    AlreadyShapechanged(MonsterId),
+}
+
+/// The forms matching the filter, e.g. beasts up to a certain challenge rating
+/// for Wild Shape, ordered from the highest challenge rating down
+pub fn forms(filter: &MonsterFilter) -> Vec<&'static MonsterTemplate> {
+    let mut forms = MonstersRegistry::values()
+        .filter(|template| filter.matches(template))
+        .collect::<Vec<_>>();
+    forms.sort_by_key(|template| {
+        (
+            Reverse(template.challenge_rating.total_level()),
+            template.name.as_str().to_string(),
+        )
+    });
+    forms
+}
+
+/// Take one of the strongest forms matching the filter
+pub fn shapechange_by_filter(
+    world: &mut World,
+    entity: Entity,
+    filter: &MonsterFilter,
+    source: ModifierSource,
+) -> Result<(), ShapechangeError> {
+    let forms = forms(filter);
+    let Some(strongest) = forms
+        .first()
+        .map(|template| template.challenge_rating.total_level())
+    else {
+        return Err(ShapechangeError::NoMatchingForm);
+    };
+
+    let template = forms
+        .into_iter()
+        .filter(|template| template.challenge_rating.total_level() == strongest)
+        .choose(&mut rand::rng())
+        .unwrap();
+    shapechange(world, entity, &template.id, source)
+}
+
+pub fn wild_shape_effect() -> EffectId {
+    EffectId::new("nat20_core", "effect.druid.wild_shape")
+}
+
+/// Take the chosen beast form with Wild Shape. The form lasts for a number of
+/// hours equal to half the druid level of the entity (at least one hour), or
+/// until the entity drops to 0 hit points.
+pub fn wild_shape(
+    world: &mut World,
+    entity: Entity,
+    form: &MonsterId,
+) -> Result<(), ShapechangeError> {
+    let effect_id = wild_shape_effect();
+    shapechange(
+        world,
+        entity,
+        form,
+        ModifierSource::Effect(effect_id.clone()),
+    )?;
+
+    let druid_level =
+        systems::class::class_level(world, entity, &ClassId::new("nat20_core", "class.druid"));
+    systems::effects::add_effect_template(
+        world,
+        entity,
+        entity,
+        ModifierSource::Effect(effect_id.clone()),
+        &EffectInstanceTemplate {
+            effect_id,
+            lifetime: EffectLifetimeTemplate::AtTurnBoundary {
+                entity: EffectLifetimeEntiy::Applier,
+                boundary: TurnBoundary::Start,
+                duration: TimeDuration::from_hours((druid_level as u32 / 2).max(1)),
+            },
+        },
+        None,
+    );
+    Ok(())
+}
+
+/// Replace the physical stats of the entity with the ones of the form. The
+/// mental abilities, proficiencies, equipment and effects of the entity are
+/// kept.
+pub fn shapechange(
+    world: &mut World,
+    entity: Entity,
+    form: &MonsterId,
+    source: ModifierSource,
+) -> Result<(), ShapechangeError> {
+    if let Ok(shapechanged) = world.get::<&Shapechanged>(entity) {
+        return Err(ShapechangeError::AlreadyShapechanged(
+            shapechanged.form.clone(),
+        ));
+    }
+    let Some(template) = MonstersRegistry::get(form) else {
+        return Err(ShapechangeError::UnknownForm(form.clone()));
+    };
+
+    let original = {
+        let abilities = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
+        OriginalForm {
+            hit_points: systems::helpers::get_component::<HitPoints>(world, entity).clone(),
+            abilities: SHAPECHANGE_ABILITIES
+                .iter()
+                .map(|ability| (*ability, abilities.get(ability).base))
+                .collect(),
+            speed: systems::helpers::get_component::<Speed>(world, entity).base(),
+            size: systems::helpers::get_component::<CreatureSize>(world, entity).clone(),
+            creature_type: systems::helpers::get_component::<CreatureType>(world, entity).clone(),
+        }
+    };
+
+    {
+        let mut abilities = systems::helpers::get_component_mut::<AbilityScoreMap>(world, entity);
+        for ability in SHAPECHANGE_ABILITIES {
+            let mut score = abilities.get(&ability).clone();
+            score.base = template.abilities.get(&ability).base;
+            abilities.set(ability, score);
+        }
+    }
+    let hit_points = template.hit_points.roll().subtotal.max(1) as u32;
+    systems::helpers::set_component(world, entity, HitPoints::new(hit_points));
+    systems::helpers::get_component_mut::<Speed>(world, entity)
+        .set_base(template.speed.get_total_speed());
+    systems::helpers::set_component(world, entity, template.size.clone());
+    systems::helpers::set_component(world, entity, template.creature_type.clone());
+
+    debug!("{:?} takes the form of {}", entity, template.id);
+    systems::helpers::set_component(
+        world,
+        entity,
+        Shapechanged {
+            form: template.id.clone(),
+            source,
+            original,
+        },
+    );
+    Ok(())
+}
+
+/// Revert the entity to its original form. If the shapechange came from an
+/// effect, the effect is removed as well. Returns the form the entity was in,
+/// or `None` if it wasn't shapechanged.
+pub fn revert(world: &mut World, entity: Entity) -> Option<MonsterId> {
+    let shapechanged = restore_original_form(world, entity)?;
+
+    if let ModifierSource::Effect(effect_id) = &shapechanged.source
+        && systems::effects::effects(world, entity)
+            .iter()
+            .any(|effect| effect.effect_id == *effect_id)
+    {
+        systems::effects::remove_effect(world, entity, effect_id);
+    }
+
+    Some(shapechanged.form)
+}
+
+/// Restore the original stats of the entity without touching whatever caused
+/// the shapechange, e.g. when the effect causing it is being removed
+pub fn restore_original_form(world: &mut World, entity: Entity) -> Option<Shapechanged> {
+    let shapechanged = world.remove_one::<Shapechanged>(entity).ok()?;
+    let original = shapechanged.original.clone();
+
+    {
+        let mut abilities = systems::helpers::get_component_mut::<AbilityScoreMap>(world, entity);
+        for (ability, base) in original.abilities {
+            let mut score = abilities.get(&ability).clone();
+            score.base = base;
+            abilities.set(ability, score);
+        }
+    }
+    systems::helpers::set_component(world, entity, original.hit_points);
+    systems::helpers::get_component_mut::<Speed>(world, entity).set_base(original.speed);
+    systems::helpers::set_component(world, entity, original.size);
+    systems::helpers::set_component(world, entity, original.creature_type);
+
+    debug!(
+        "{:?} reverts from the form of {}",
+        entity, shapechanged.form
+    );
+    Some(shapechanged)
+}
+
+pub fn is_shapechanged(world: &World, entity: Entity) -> bool {
+    world.get::<&Shapechanged>(entity).is_ok()
+}
@@ -0,0 +1,170 @@
+use hecs::{Entity, World};
+use parry3d::{
+    na::{Isometry3, Point3},
+    shape::Cylinder,
+};
+use tracing::debug;
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    components::{
+        faction::Attitude, health::life_state::LifeState, id::EffectId, modifier::ModifierSource,
+        species::CreatureSize,
+    },
+    engine::{
+        game_state::GameState,
+        geometry::{WorldGeometry, WorldPath},
+    },
+    systems,
+};
+
+/// Creatures can brush against walls and furniture without having to squeeze,
+/// so only this fraction of their space has to be free
+const SQUEEZE_TOLERANCE: f32 = 0.8;
+/// Geometry below this height, e.g. steps and slopes, doesn't make a creature
+/// squeeze
+const SQUEEZE_STEP_HEIGHT: f32 = 0.3;
+
+fn squeezing() -> EffectId {
+    EffectId::new("nat20_core", "effect.squeezing")
+}
+
+pub fn size(world: &World, entity: Entity) -> Option<CreatureSize> {
+    world
+        .get::<&CreatureSize>(entity)
+        .ok()
+        .map(|size| size.clone())
+}
+
+/// Width of the space the creature controls in combat. Creatures without a
+/// size take up a single 5 ft. square.
+pub fn space(world: &World, entity: Entity) -> Length {
+    size(world, entity)
+        .map(|size| size.space())
+        .unwrap_or_else(|| Length::new::<foot>(5.0))
+}
+
+/// How much further the space of the creature extends from its center than a
+/// single 5 ft. square, e.g. 2.5 ft. for a Large creature. Distances to the
+/// creature are measured to the edge of its space.
+pub fn space_offset(world: &World, entity: Entity) -> Length {
+    let offset = (space(world, entity) - Length::new::<foot>(5.0)) / 2.0;
+    offset.max(Length::new::<meter>(0.0))
+}
+
+/// Whether the mover can move through the space of the other creature. Only
+/// hostile creatures block movement, and only if they're able to and within
+/// one size of the mover.
+pub fn can_move_through(world: &World, mover: Entity, other: Entity) -> bool {
+    if systems::factions::perceived_threat(world, mover, other) != Attitude::Hostile {
+        return true;
+    }
+    if world
+        .get::<&LifeState>(other)
+        .is_ok_and(|life_state| *life_state != LifeState::Normal)
+    {
+        return true;
+    }
+    match (size(world, mover), size(world, other)) {
+        (Some(mover_size), Some(other_size)) => {
+            other_size == CreatureSize::Tiny || mover_size.difference(&other_size).abs() >= 2
+        }
+        _ => true,
+    }
+}
+
+/// The first creature whose space blocks the path of the entity, and how far
+/// the entity can move along the path before entering it. Creatures whose space
+/// the entity is already in don't block it, so it can always move away.
+pub fn blocked_at(world: &World, entity: Entity, path: &WorldPath) -> Option<(Entity, Length)> {
+    let start = path.start()?;
+
+    let blockers = world
+        .query::<&CreatureSize>()
+        .iter()
+        .filter(|(other, _)| *other != entity && !can_move_through(world, entity, *other))
+        .filter_map(|(other, size)| {
+            let position = systems::geometry::get_foot_position(world, other)?;
+            let radius = size.space().get::<meter>() / 2.0;
+            let height = systems::geometry::get_height(world, other)?;
+            Some((other, position, radius, height))
+        })
+        .filter(|(_, position, radius, height)| !in_space(start, position, *radius, *height))
+        .collect::<Vec<_>>();
+    if blockers.is_empty() {
+        return None;
+    }
+
+    // The entity can move up to the last sample outside every blocker's space
+    let mut allowed = 0.0;
+    for (sample, distance) in path.samples() {
+        if let Some((blocker, _, _, _)) = blockers
+            .iter()
+            .find(|(_, position, radius, height)| in_space(&sample, position, *radius, *height))
+        {
+            return Some((*blocker, Length::new::<meter>(allowed)));
+        }
+        allowed = distance;
+    }
+
+    None
+}
+
+fn in_space(point: &Point3<f32>, position: &Point3<f32>, radius: f32, height: f32) -> bool {
+    let horizontal_distance =
+        ((point.x - position.x).powi(2) + (point.z - position.z).powi(2)).sqrt();
+    horizontal_distance < radius && (point.y - position.y).abs() < height
+}
+
+/// Whether the world geometry leaves too little room for the space of the
+/// entity at its current position, forcing it to squeeze
+pub fn is_squeezing(world: &World, world_geometry: &WorldGeometry, entity: Entity) -> bool {
+    let Some(position) = systems::geometry::get_foot_position(world, entity) else {
+        return false;
+    };
+    let Some(height) = systems::geometry::get_height(world, entity) else {
+        return false;
+    };
+
+    let radius = space(world, entity).get::<meter>() / 2.0 * SQUEEZE_TOLERANCE;
+    let half_height = (height - SQUEEZE_STEP_HEIGHT).max(0.0) / 2.0;
+    let shape = Cylinder::new(half_height, radius);
+    let shape_pose = Isometry3::translation(
+        position.x,
+        position.y + SQUEEZE_STEP_HEIGHT + half_height,
+        position.z,
+    );
+
+    parry3d::query::intersection_test(
+        &shape_pose,
+        &shape,
+        &Isometry3::identity(),
+        &world_geometry.trimesh,
+    )
+    .unwrap_or(false)
+}
+
+/// Apply or remove the squeezing penalties after the entity has moved
+pub fn update_squeezing(game_state: &mut GameState, entity: Entity) {
+    let squeezing_now = is_squeezing(&game_state.world, &game_state.geometry, entity);
+    let squeezing_before = systems::effects::effects(&game_state.world, entity)
+        .iter()
+        .any(|effect| effect.effect_id == squeezing());
+
+    if squeezing_now && !squeezing_before {
+        debug!("{:?} squeezes into a tight space", entity);
+        systems::effects::add_permanent_effect(
+            &mut game_state.world,
+            entity,
+            squeezing(),
+            &ModifierSource::Custom("Tight Space".to_string()),
+            None,
+        );
+    } else if !squeezing_now && squeezing_before {
+        debug!("{:?} is no longer squeezing", entity);
+        systems::effects::remove_effect(&mut game_state.world, entity, &squeezing());
+    }
+}
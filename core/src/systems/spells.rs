@@ -1,25 +1,38 @@
 use std::{cmp::max, collections::HashMap, sync::LazyLock};
 
 use hecs::{Entity, World};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::{
     components::{
+        ability::{Ability, AbilityScoreMap},
+        actions::action::ActionContext,
         class::{
             ClassAndSubclass, SpellAccessModel, SpellReplacementModel, SpellcastingProgression,
         },
-        id::ResourceId,
+        id::{ActionId, ResourceId, SpellId},
+        items::inventory::Inventory,
         level::CharacterLevels,
         level_up::LevelUpPrompt,
-        resource::{ResourceAmount, ResourceBudgetKind, ResourceMap},
+        modifier::{Modifiable, ModifierSet, ModifierSource},
+        proficiency::ProficiencyLevel,
+        resource::{
+            RechargeRule, ResourceAmount, ResourceAmountMap, ResourceBudgetKind, ResourceMap,
+        },
+        saving_throw::BASE_SAVE_DC,
         spells::{
-            spell::ConcentrationInstance,
-            spellbook::{ClassSpellcastingState, Spellbook},
+            innate::InnateSpellcasting,
+            metamagic::{KnownMetamagic, Metamagic, sorcery_point_id},
+            spell::{ConcentrationInstance, MaterialComponent, MaterialConsumption, SpellFlag},
+            spellbook::{
+                ClassSpellcastingState, GrantedSpellSource, SpellSource, Spellbook, SpellbookError,
+                SpellcastingAbility,
+            },
         },
     },
-    engine::event::ActionExecutionInstanceId,
-    registry::registry::ClassesRegistry,
-    systems,
+    engine::event::{ActionData, ActionExecutionInstanceId},
+    registry::registry::{ClassesRegistry, SpellsRegistry},
+    systems::{self, actions::ActionUsabilityError},
 };
 
 pub fn spellcaster_levels(world: &World, entity: Entity) -> u8 {
@@ -125,6 +138,7 @@ pub fn update_spellbook(
                     spellbook.insert_class_state(
                         class_and_subclass.clone(),
                         ClassSpellcastingState::new(
+                            spellcasting_rules.spellcasting_ability,
                             *max_cantrips,
                             *max_learned_spells,
                             *max_prepared_spells,
@@ -184,14 +198,18 @@ pub fn update_spellbook(
             ));
         }
         if new_spells > 0 {
-            prompts.push(LevelUpPrompt::spells(
+            let prompt = LevelUpPrompt::spells(
                 world,
                 entity,
                 &class_and_subclass,
                 false,
                 new_spells as u8,
                 max_spell_level,
-            ));
+            );
+            // Nothing to pick if every spell on the list has been picked already
+            if !matches!(&prompt, LevelUpPrompt::Choice(spec) if spec.picks == 0) {
+                prompts.push(prompt);
+            }
         }
         if let Some(replacement_model) = replacement_model
             && matches!(replacement_model, SpellReplacementModel::LevelUp)
@@ -250,3 +268,346 @@ pub fn break_concentration(world: &mut World, target: Entity) {
         instance.break_concentration(world);
     }
 }
+
+/// The metamagic options the entity knows which would have an effect on the
+/// given spell. Options which are already applied to the context are excluded.
+pub fn available_metamagic(
+    world: &World,
+    entity: Entity,
+    action_id: &ActionId,
+    context: &ActionContext,
+    cost: &ResourceAmountMap,
+) -> Vec<Metamagic> {
+    let ActionContext::Spell { metamagic, .. } = context.base() else {
+        return Vec::new();
+    };
+    let Ok(known_metamagic) = world.get::<&KnownMetamagic>(entity) else {
+        return Vec::new();
+    };
+
+    let targeting = systems::actions::targeting_context(world, entity, action_id, context);
+
+    known_metamagic
+        .0
+        .iter()
+        .filter(|option| !metamagic.contains(option) && option.applicable(&targeting, cost))
+        .cloned()
+        .collect()
+}
+
+/// Apply a metamagic option to a spell. The Sorcery Point cost of the option,
+/// and any changes it makes to the action economy, are added to the cost of the
+/// action.
+pub fn apply_metamagic(action_data: &mut ActionData, option: Metamagic) {
+    let ActionContext::Spell {
+        level, metamagic, ..
+    } = action_data.context.base_mut()
+    else {
+        warn!(
+            "Can't apply metamagic to {:?}, since it isn't a spell",
+            action_data.action_id
+        );
+        return;
+    };
+    option.apply_to_cost(&mut action_data.resource_cost, *level);
+    metamagic.push(option);
+}
+
+/// The metamagic applied to a spell has to be known by the caster, and its
+/// Sorcery Point cost has to be part of the cost of the action
+pub fn validate_metamagic(
+    world: &World,
+    entity: Entity,
+    context: &ActionContext,
+    cost: &ResourceAmountMap,
+) -> Result<(), ActionUsabilityError> {
+    let ActionContext::Spell {
+        level, metamagic, ..
+    } = context.base()
+    else {
+        return Ok(());
+    };
+    if metamagic.is_empty() {
+        return Ok(());
+    }
+
+    let known_metamagic = world.get::<&KnownMetamagic>(entity).ok();
+    if let Some(unknown) = metamagic.iter().find(|option| {
+        !known_metamagic
+            .as_ref()
+            .is_some_and(|known| known.0.contains(option))
+    }) {
+        return Err(ActionUsabilityError::UnknownMetamagic(*unknown));
+    }
+
+    let sorcery_points: u8 = metamagic
+        .iter()
+        .map(|option| option.sorcery_point_cost(*level))
+        .sum();
+    match cost.get(&sorcery_point_id()) {
+        Some(ResourceAmount::Flat(paid)) if *paid >= sorcery_points => Ok(()),
+        _ => Err(ActionUsabilityError::MetamagicNotPaid(sorcery_points)),
+    }
+}
+
+/// How much longer it takes to cast a spell as a ritual
+pub const RITUAL_CASTING_MINUTES: u32 = 10;
+
+/// Whether the spell is cast as a ritual, i.e. a ritual spell cast from a class
+/// without spending any of the class' spellcasting resource, see
+/// [`Spellbook`]'s actions
+pub fn is_ritual_casting(context: &ActionContext, cost: &ResourceAmountMap) -> bool {
+    let ActionContext::Spell {
+        id,
+        source: SpellSource::Class(class_and_subclass),
+        level,
+        ..
+    } = context.base()
+    else {
+        return false;
+    };
+    if *level == 0
+        || !SpellsRegistry::get(id).is_some_and(|spell| spell.has_flag(SpellFlag::Ritual))
+    {
+        return false;
+    }
+
+    ClassesRegistry::get(&class_and_subclass.class)
+        .and_then(|class| class.spellcasting_rules(&class_and_subclass.subclass))
+        .is_some_and(|rules| !cost.contains_key(&rules.spellcasting_resource))
+}
+
+/// The costly material component the spell needs, if any. Spells cast from
+/// items, e.g. a Spell Scroll, don't need material components.
+pub fn material_component(context: &ActionContext) -> Option<MaterialComponent> {
+    let ActionContext::Spell { id, source, .. } = context.base() else {
+        return None;
+    };
+    if matches!(
+        source,
+        SpellSource::Granted {
+            source: GrantedSpellSource::Item(_),
+            ..
+        }
+    ) {
+        return None;
+    }
+    SpellsRegistry::get(id)?.material().cloned()
+}
+
+/// The material component the entity can't pay for, if any
+pub fn missing_material_component(
+    world: &World,
+    entity: Entity,
+    context: &ActionContext,
+) -> Option<MaterialComponent> {
+    let material = material_component(context)?;
+    let affordable = world
+        .get::<&Inventory>(entity)
+        .is_ok_and(|inventory| inventory.money().covers(&material.cost));
+    (!affordable).then_some(material)
+}
+
+/// Use up the material component of the spell, if it's consumed at the given
+/// point of the casting
+pub fn consume_material_component(
+    world: &mut World,
+    entity: Entity,
+    context: &ActionContext,
+    consumption: MaterialConsumption,
+) {
+    let Some(material) = material_component(context) else {
+        return;
+    };
+    if material.consumed != consumption {
+        return;
+    }
+    debug!(
+        "Entity {:?} consumed material component: {}",
+        entity, material.description
+    );
+    let _ = systems::inventory::remove_money(world, entity, material.cost);
+}
+
+/// The classes of the entity which have the spell on their spell list
+pub fn classes_with_spell(
+    world: &World,
+    entity: Entity,
+    spell_id: &SpellId,
+) -> Vec<ClassAndSubclass> {
+    let Ok(class_levels) = world.get::<&CharacterLevels>(entity) else {
+        return Vec::new();
+    };
+    let spellbook = world.get::<&Spellbook>(entity).ok();
+
+    class_levels
+        .all_classes()
+        .iter()
+        .filter_map(|(class_id, level_progression)| {
+            let class_and_subclass = ClassAndSubclass {
+                class: class_id.clone(),
+                subclass: level_progression.subclass().cloned(),
+            };
+            let on_spell_list = match &spellbook {
+                Some(spellbook) => spellbook.is_on_spell_list(&class_and_subclass, spell_id),
+                None => ClassesRegistry::get(class_id)?
+                    .spell_list(&class_and_subclass.subclass)
+                    .contains(spell_id),
+            };
+            on_spell_list.then_some(class_and_subclass)
+        })
+        .collect()
+}
+
+/// Learn a spell for the first of the entity's classes which can learn it.
+/// Cantrips are chosen instead of learned.
+pub fn learn_spell(
+    world: &mut World,
+    entity: Entity,
+    spell_id: &SpellId,
+) -> Result<(), SpellbookError> {
+    let spell = SpellsRegistry::get(spell_id).ok_or(SpellbookError::NotFound)?;
+    let classes = classes_with_spell(world, entity, spell_id);
+    let resources = systems::helpers::get_component_clone::<ResourceMap>(world, entity);
+    let mut spellbook = systems::helpers::get_component_mut::<Spellbook>(world, entity);
+
+    let mut result = Err(SpellbookError::SpellNotOnClassList);
+    for class_and_subclass in &classes {
+        result = if spell.is_cantrip() {
+            spellbook.try_choose_cantrip(class_and_subclass, spell_id)
+        } else {
+            spellbook.try_learn_spell(class_and_subclass, spell_id, &resources)
+        };
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+/// Prepare a spell for the first of the entity's classes which can prepare it
+pub fn prepare_spell(
+    world: &mut World,
+    entity: Entity,
+    spell_id: &SpellId,
+) -> Result<(), SpellbookError> {
+    let classes = classes_with_spell(world, entity, spell_id);
+    let resources = systems::helpers::get_component_clone::<ResourceMap>(world, entity);
+    let mut spellbook = systems::helpers::get_component_mut::<Spellbook>(world, entity);
+
+    let mut result = Err(SpellbookError::SpellNotOnClassList);
+    for class_and_subclass in &classes {
+        result = spellbook.try_prepare_spell(class_and_subclass, spell_id, &resources);
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+/// The ability the entity casts spells from the source with, see
+/// [`SpellcastingAbility`]. Overrides in the entity's spellbook take priority
+/// over the defaults of the source.
+pub fn spellcasting_ability(world: &World, entity: Entity, source: &SpellSource) -> Ability {
+    let spellcasting_ability = world
+        .get::<&Spellbook>(entity)
+        .ok()
+        .and_then(|spellbook| spellbook.spellcasting_ability(source))
+        .unwrap_or_else(|| SpellcastingAbility::default_for(source));
+    spellcasting_ability.resolve(&systems::helpers::get_component::<AbilityScoreMap>(
+        world, entity,
+    ))
+}
+
+/// The spellcasting ability modifier and proficiency bonus of the entity, which
+/// are added to the attack rolls of its spells from the source
+pub fn spell_attack_bonus(world: &World, entity: Entity, source: &SpellSource) -> ModifierSet {
+    if let Some(attack_bonus) = innate_spellcasting(world, entity, source)
+        .and_then(|spellcasting| spellcasting.attack_bonus)
+    {
+        return ModifierSet::from(ModifierSource::Base, attack_bonus);
+    }
+
+    let spellcasting_ability = spellcasting_ability(world, entity, source);
+    let proficiency_bonus = systems::helpers::level(world, entity)
+        .map(|level| level.proficiency_bonus())
+        .unwrap_or(0);
+
+    let mut bonus = ModifierSet::new();
+    bonus.add_modifier(
+        ModifierSource::Ability(spellcasting_ability),
+        systems::helpers::get_component::<AbilityScoreMap>(world, entity)
+            .ability_modifier(&spellcasting_ability)
+            .total(),
+    );
+    // TODO: Not sure if Proficiency is the correct modifier source here, since I don't think
+    // you can have e.g. Expertise in spell save DCs.
+    bonus.add_modifier(
+        ModifierSource::Proficiency(ProficiencyLevel::Proficient),
+        proficiency_bonus as i32,
+    );
+    bonus
+}
+
+/// The save DC of the entity's spells from the source. Every action with a
+/// saving throw against a spell should use this rather than computing the DC.
+pub fn spell_dc(world: &World, entity: Entity, source: &SpellSource) -> ModifierSet {
+    if let Some(dc) =
+        innate_spellcasting(world, entity, source).and_then(|spellcasting| spellcasting.dc)
+    {
+        return ModifierSet::from(ModifierSource::Base, dc);
+    }
+
+    let mut dc = ModifierSet::from(ModifierSource::Base, BASE_SAVE_DC);
+    dc.add_modifier_set(&spell_attack_bonus(world, entity, source));
+    dc
+}
+
+/// The innate spellcasting of the entity, if the spell source is innate
+fn innate_spellcasting(
+    world: &World,
+    entity: Entity,
+    source: &SpellSource,
+) -> Option<InnateSpellcasting> {
+    if !matches!(
+        source,
+        SpellSource::Granted {
+            source: GrantedSpellSource::Innate,
+            ..
+        }
+    ) {
+        return None;
+    }
+    world
+        .get::<&Spellbook>(entity)
+        .ok()?
+        .innate_spellcasting()
+        .map(|innate| innate.spellcasting.clone())
+}
+
+/// Spend a use of the innate spell, if the spell is cast innately
+pub fn spend_innate_use(world: &mut World, entity: Entity, context: &ActionContext) {
+    if let ActionContext::Spell {
+        id,
+        source:
+            SpellSource::Granted {
+                source: GrantedSpellSource::Innate,
+                ..
+            },
+        ..
+    } = context.base()
+        && !systems::helpers::get_component_mut::<Spellbook>(world, entity)
+            .innate_spellcasting_mut()
+            .is_some_and(|innate| innate.spend(id))
+    {
+        warn!("{:?} has no innate uses left of {}", entity, id);
+    }
+}
+
+pub fn recharge_innate(world: &mut World, entity: Entity, rest_type: &RechargeRule) {
+    if let Ok(mut spellbook) = world.get::<&mut Spellbook>(entity)
+        && let Some(innate) = spellbook.innate_spellcasting_mut()
+    {
+        innate.recharge(rest_type);
+    }
+}
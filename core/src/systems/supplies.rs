@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use hecs::{Entity, World};
+use strum::IntoEnumIterator;
+use tracing::{debug, info};
+
+use crate::{
+    components::{
+        ability::{Ability, AbilityScoreMap},
+        id::IdProvider,
+        items::{inventory::Inventory, supply::SupplyKind},
+        modifier::{Modifiable, ModifierSource},
+        sustenance::Sustenance,
+    },
+    engine::{game_state::GameState, rules::OptionalRule},
+    systems,
+};
+
+/// The creatures who have to eat and drink, i.e. the living members of the
+/// party. No one does unless the supplies rule is enabled.
+pub fn party(game_state: &GameState) -> Vec<Entity> {
+    if !game_state.rule_enabled(OptionalRule::Supplies) {
+        return Vec::new();
+    }
+    let mut party = game_state
+        .world
+        .query::<&Inventory>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| {
+            systems::ai::is_player_controlled(&game_state.world, *entity)
+                && systems::health::is_alive(&game_state.world, *entity)
+        })
+        .collect::<Vec<_>>();
+    party.sort();
+    party
+}
+
+/// How many days' worth of each kind of supply the party carries between them
+pub fn party_supplies(game_state: &GameState) -> HashMap<SupplyKind, u32> {
+    let party = party(game_state);
+    SupplyKind::iter()
+        .map(|kind| {
+            let days = party
+                .iter()
+                .filter_map(|entity| game_state.world.get::<&Inventory>(*entity).ok())
+                .map(|inventory| inventory.supply_days(&kind))
+                .sum();
+            (kind, days)
+        })
+        .collect()
+}
+
+pub fn sustenance(world: &World, entity: Entity) -> Sustenance {
+    world
+        .get::<&Sustenance>(entity)
+        .map(|sustenance| *sustenance)
+        .unwrap_or_default()
+}
+
+/// Eat and drink a day's worth of whatever the creature hasn't had yet. The
+/// creature takes from its own pack first, and then from those of the rest of
+/// the party.
+pub fn eat_and_drink(world: &mut World, entity: Entity, party: &[Entity]) {
+    let mut sustenance = sustenance(world, entity);
+
+    for kind in SupplyKind::iter() {
+        if sustenance.has(&kind) {
+            continue;
+        }
+
+        let carriers = std::iter::once(entity)
+            .chain(party.iter().copied().filter(|carrier| *carrier != entity));
+        let Some((carrier, supply)) = carriers.find_map(|carrier| {
+            let inventory = world.get::<&Inventory>(carrier).ok()?;
+            let supply = inventory.supply_for(&kind)?.clone();
+            Some((carrier, supply))
+        }) else {
+            debug!("{:?} has no {} left", entity, kind);
+            continue;
+        };
+
+        world
+            .get::<&mut Inventory>(carrier)
+            .unwrap()
+            .spend_charge(supply.id());
+        debug!("{:?} used a day's worth of {}", entity, supply.item.name);
+        for kind in &supply.provides {
+            sustenance.provide(kind);
+        }
+        if supply.healing > 0 {
+            systems::health::heal(world, entity, supply.healing);
+        }
+    }
+
+    let _ = world.insert_one(entity, sustenance);
+}
+
+/// Someone else takes care of the food and drink for the day, e.g. an inn
+pub fn provide_meals(game_state: &mut GameState, entities: &[Entity]) {
+    let party = party(game_state);
+    for entity in entities.iter().filter(|entity| party.contains(entity)) {
+        let mut sustenance = sustenance(&game_state.world, *entity);
+        for kind in SupplyKind::iter() {
+            sustenance.provide(&kind);
+        }
+        let _ = game_state.world.insert_one(*entity, sustenance);
+    }
+}
+
+/// Fill up the waterskins of the party, e.g. at the well in a town
+pub fn refill_water(game_state: &mut GameState) {
+    for entity in party(game_state) {
+        if let Ok(mut inventory) = game_state.world.get::<&mut Inventory>(entity) {
+            inventory.refill_supplies(&SupplyKind::Water);
+        }
+    }
+}
+
+/// The party has a meal when they settle down for a long rest
+pub fn on_long_rest(game_state: &mut GameState, participants: &[Entity]) {
+    let party = party(game_state);
+    for entity in participants {
+        if party.contains(entity) {
+            eat_and_drink(&mut game_state.world, *entity, &party);
+        }
+    }
+}
+
+/// A creature can go without food for 3 days plus its Constitution modifier,
+/// but always at least 1 day
+fn days_before_starving(world: &World, entity: Entity) -> u32 {
+    let constitution = systems::helpers::get_component::<AbilityScoreMap>(world, entity)
+        .ability_modifier(&Ability::Constitution)
+        .total();
+    (3 + constitution).max(1) as u32
+}
+
+/// Settle the day which has passed. Anyone who hasn't eaten or had anything to
+/// drink yet does so from the supplies at hand, e.g. while on the road.
+/// Starving creatures gain a level of exhaustion every day, and a day without
+/// water causes one right away, or two if the creature is already exhausted.
+pub fn on_dawn(game_state: &mut GameState) {
+    let party = party(game_state);
+    for &entity in &party {
+        eat_and_drink(&mut game_state.world, entity, &party);
+
+        let world = &mut game_state.world;
+        let mut sustenance = sustenance(world, entity);
+        let mut deprivation = Vec::new();
+        if sustenance.fed {
+            sustenance.days_without_food = 0;
+        } else {
+            sustenance.days_without_food += 1;
+            if sustenance.days_without_food > days_before_starving(world, entity) {
+                deprivation.push("Starvation");
+            }
+        }
+        if !sustenance.watered {
+            deprivation.push("Dehydration");
+            if systems::effects::has_effect(world, entity, &systems::conditions::exhaustion()) {
+                deprivation.push("Dehydration");
+            }
+        }
+
+        for cause in deprivation {
+            info!("{:?} suffers from {}", entity, cause.to_lowercase());
+            systems::effects::add_permanent_effect(
+                world,
+                entity,
+                systems::conditions::exhaustion(),
+                &ModifierSource::Custom(cause.to_string()),
+                None,
+            );
+        }
+
+        sustenance.fed = false;
+        sustenance.watered = false;
+        let _ = world.insert_one(entity, sustenance);
+    }
+}
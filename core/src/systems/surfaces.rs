@@ -0,0 +1,359 @@
+use std::collections::HashSet;
+
+use hecs::Entity;
+use parry3d::na::Point3;
+use tracing::debug;
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    components::{
+        actions::{
+            action::{Action, ActionCondition, ActionKind},
+            resolution::TargetResolution,
+            targeting::{AreaShape, TargetInstance, TargetingKind},
+        },
+        d20::D20CheckDC,
+        damage::{DamageRoll, DamageSource},
+        effects::effect::{EffectInstanceTemplate, EffectLifetimeEntiy, EffectLifetimeTemplate},
+        id::EffectId,
+        modifier::{Modifiable, ModifierSet, ModifierSource},
+        surface::{SURFACE_DC_DEFAULT, Surface, SurfaceCheck, SurfaceInteraction, SurfaceKind},
+        time::{TimeDuration, TimeStep, TurnBoundary},
+    },
+    engine::{
+        event::{ActionData, Event, EventKind},
+        game_state::GameState,
+        geometry::WorldPath,
+    },
+    systems::{self, d20::D20CheckDCKind},
+};
+
+/// Radius of surfaces created by actions that don't target an area
+const SURFACE_RADIUS_DEFAULT: f32 = 5.0;
+
+fn prone() -> EffectId {
+    EffectId::new("nat20_core", "effect.condition.prone")
+}
+
+/// Create a surface, letting it interact with the surfaces it's created on top
+/// of, e.g. fire igniting a pool of grease.
+pub fn create(game_state: &mut GameState, surface: Surface) {
+    debug!(
+        "Creating {} surface at {:?} with radius {:?}",
+        surface.kind, surface.center, surface.radius
+    );
+
+    game_state.surfaces.retain_mut(|existing| {
+        if !existing.overlaps(&surface) {
+            return true;
+        }
+        match surface.kind.interact(&existing.kind) {
+            SurfaceInteraction::None => true,
+            SurfaceInteraction::Transform(kind) => {
+                debug!("{} surface turns into {}", existing.kind, kind);
+                existing.kind = kind;
+                existing.dc = surface.dc;
+                true
+            }
+            SurfaceInteraction::Remove => {
+                debug!("{} surface is removed by {}", existing.kind, surface.kind);
+                false
+            }
+        }
+    });
+
+    game_state.surfaces.push(surface);
+    systems::antimagic::update_all(game_state);
+}
+
+/// Create the surface left behind by the action, if any, covering the area
+/// targeted by the action.
+pub fn create_from_action(game_state: &mut GameState, action: &Action, action_data: &ActionData) {
+    let Some(template) = &action.surface else {
+        return;
+    };
+
+    let targeting = systems::actions::targeting_context(
+        &game_state.world,
+        action_data.actor,
+        &action_data.action_id,
+        &action_data.context,
+    );
+    let (radius, fixed_on_actor) = match &targeting.kind {
+        TargetingKind::Area {
+            shape,
+            fixed_on_actor,
+        } => (footprint_radius(shape), *fixed_on_actor),
+        _ => (Length::new::<foot>(SURFACE_RADIUS_DEFAULT), false),
+    };
+
+    let dc = template
+        .dc
+        .or_else(|| action_saving_throw_dc(game_state, action, action_data))
+        .unwrap_or(SURFACE_DC_DEFAULT);
+
+    for target in &action_data.targets {
+        let center = if fixed_on_actor {
+            systems::geometry::get_foot_position(&game_state.world, action_data.actor)
+        } else {
+            match target {
+                TargetInstance::Entity(entity)
+                | TargetInstance::Object(entity)
+                | TargetInstance::Item { owner: entity, .. } => {
+                    systems::geometry::get_foot_position(&game_state.world, *entity)
+                }
+                // Surfaces cover the ground, even if e.g. a Fireball explodes
+                // in mid-air
+                TargetInstance::Point(point) => {
+                    systems::geometry::ground_position(&game_state.geometry, point).or(Some(*point))
+                }
+            }
+        };
+        let Some(center) = center else {
+            continue;
+        };
+
+        create(
+            game_state,
+            Surface::new(
+                template.kind,
+                center,
+                radius,
+                dc,
+                template.duration,
+                Some(action_data.actor),
+            ),
+        );
+    }
+}
+
+/// Cones and lines originate at the actor rather than the target point, so
+/// their surfaces are approximated by a circle around the target point.
+fn footprint_radius(shape: &AreaShape) -> Length {
+    match shape {
+        AreaShape::Sphere { radius } | AreaShape::Cylinder { radius, .. } => *radius,
+        AreaShape::Cube { side_length } => *side_length / 2.0,
+        AreaShape::Arc { .. } | AreaShape::Line { .. } => {
+            Length::new::<foot>(SURFACE_RADIUS_DEFAULT)
+        }
+    }
+}
+
+fn action_saving_throw_dc(
+    game_state: &GameState,
+    action: &Action,
+    action_data: &ActionData,
+) -> Option<i32> {
+    let mut kinds = vec![&action.kind];
+    while let Some(kind) = kinds.pop() {
+        match kind {
+            ActionKind::Standard {
+                condition: ActionCondition::SavingThrow { saving_throw, .. },
+                ..
+            } => {
+                // Surfaces aren't aimed at anyone in particular
+                let saving_throw_dc = saving_throw(
+                    &game_state.world,
+                    action_data.actor,
+                    &action_data.context,
+                    &TargetResolution::new(action_data.actor),
+                );
+                return Some(saving_throw_dc.dc.total());
+            }
+            ActionKind::Composite { actions } => {
+                kinds.extend(actions.iter().map(|step| &step.kind))
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+pub fn surfaces_at(game_state: &GameState, point: &Point3<f32>) -> Vec<Surface> {
+    game_state
+        .surfaces
+        .iter()
+        .filter(|surface| surface.contains(point))
+        .cloned()
+        .collect()
+}
+
+fn is_difficult_terrain(game_state: &GameState, point: &Point3<f32>) -> bool {
+    game_state
+        .surfaces
+        .iter()
+        .any(|surface| surface.kind.is_difficult_terrain() && surface.contains(point))
+}
+
+/// The movement it takes to move along the path. Every foot moved through
+/// difficult terrain costs an extra foot of movement.
+pub fn movement_cost(game_state: &GameState, path: &WorldPath) -> Length {
+    let mut difficult_terrain = 0.0;
+    let mut previous = 0.0;
+    for (sample, travelled) in path.samples() {
+        if is_difficult_terrain(game_state, &sample) {
+            difficult_terrain += travelled - previous;
+        }
+        previous = travelled;
+    }
+    path.length + Length::new::<meter>(difficult_terrain)
+}
+
+/// How far along the path the movement takes the entity, taking difficult
+/// terrain into account
+pub fn reachable_length(game_state: &GameState, path: &WorldPath, movement: Length) -> Length {
+    let movement = movement.get::<meter>();
+    let mut cost = 0.0;
+    let mut previous = 0.0;
+    for (sample, travelled) in path.samples() {
+        let step = travelled - previous;
+        let step_cost = if is_difficult_terrain(game_state, &sample) {
+            2.0 * step
+        } else {
+            step
+        };
+        if cost + step_cost > movement {
+            return Length::new::<meter>(previous + step * (movement - cost) / step_cost);
+        }
+        cost += step_cost;
+        previous = travelled;
+    }
+    path.length
+}
+
+/// Resolve the surfaces the entity entered by moving along the path. Surfaces
+/// the entity was already standing in don't count, and each kind of surface
+/// only affects the entity once per move.
+pub fn on_enter(game_state: &mut GameState, entity: Entity, path: &WorldPath) {
+    let Some(start) = path.start() else {
+        return;
+    };
+
+    let samples = path.samples();
+    let entered = game_state
+        .surfaces
+        .iter()
+        .filter(|surface| {
+            !surface.contains(start) && samples.iter().any(|(sample, _)| surface.contains(sample))
+        })
+        .cloned()
+        .collect();
+    trigger(game_state, entity, entered);
+}
+
+/// Creatures starting their turn in a surface are affected by it
+pub fn on_turn_start(game_state: &mut GameState, entity: Entity) {
+    let Some(position) = systems::geometry::get_foot_position(&game_state.world, entity) else {
+        return;
+    };
+    let surfaces = surfaces_at(game_state, &position);
+    trigger(game_state, entity, surfaces);
+}
+
+fn trigger(game_state: &mut GameState, entity: Entity, surfaces: Vec<Surface>) {
+    let mut triggered = HashSet::new();
+    for surface in surfaces {
+        if triggered.insert(surface.kind) {
+            resolve_hazard(game_state, entity, &surface);
+        }
+    }
+}
+
+fn resolve_hazard(game_state: &mut GameState, entity: Entity, surface: &Surface) {
+    let Some(hazard) = surface.kind.hazard() else {
+        return;
+    };
+    let source = ModifierSource::Custom(format!("{} Surface", surface.kind));
+
+    let dc = ModifierSet::from(source.clone(), surface.dc);
+    let dc = match hazard.check {
+        SurfaceCheck::SavingThrow(saving_throw) => D20CheckDCKind::SavingThrow(D20CheckDC {
+            key: saving_throw,
+            dc,
+        }),
+        SurfaceCheck::Skill(skill) => D20CheckDCKind::Skill(D20CheckDC { key: skill, dc }),
+    };
+    let success = systems::d20::check_no_event(&game_state.world, entity, &dc).is_success(&dc);
+    debug!(
+        "{:?} {} the {} surface",
+        entity,
+        if success { "resists" } else { "is affected by" },
+        surface.kind
+    );
+
+    if let Some((dice, damage_type)) = hazard.damage {
+        let mut damage_roll =
+            DamageRoll::new(dice, damage_type, DamageSource::Environment).roll(false);
+        if success {
+            for component in damage_roll.components.iter_mut() {
+                let total = component.result.subtotal;
+                component
+                    .result
+                    .modifiers
+                    .add_modifier(source.clone(), -(total as f32 / 2.0).ceil() as i32);
+            }
+            damage_roll.recalculate_total();
+        }
+
+        let (_, new_life_state) = systems::health::damage(game_state, entity, &damage_roll, None);
+        if let Some(new_state) = new_life_state {
+            let _ = game_state.process_event(Event::new(EventKind::LifeStateChanged {
+                entity,
+                new_state,
+                actor: surface.creator,
+            }));
+        }
+    }
+
+    if hazard.prone && !success {
+        // There's no action for standing up, so creatures get back up at the
+        // start of their next turn
+        systems::effects::add_effect_template(
+            &mut game_state.world,
+            surface.creator.unwrap_or(entity),
+            entity,
+            source,
+            &EffectInstanceTemplate {
+                effect_id: prone(),
+                lifetime: EffectLifetimeTemplate::UntilNextTurn {
+                    entity: EffectLifetimeEntiy::Target,
+                    boundary: TurnBoundary::Start,
+                },
+            },
+            None,
+        );
+    }
+}
+
+/// Surfaces with a duration fade as time passes in the world
+pub fn advance_time(game_state: &mut GameState, duration: &TimeDuration) {
+    let time_step = TimeStep::RealTime {
+        delta_seconds: duration.as_seconds(),
+    };
+    game_state.surfaces.retain_mut(|surface| {
+        surface.advance_time(&time_step);
+        if surface.is_expired() {
+            debug!("{} surface at {:?} fades", surface.kind, surface.center);
+        }
+        !surface.is_expired()
+    });
+    systems::antimagic::update_all(game_state);
+}
+
+/// Remove the surfaces containing the point, optionally only those of the
+/// given kind, e.g. when a fire is put out. Returns how many were removed.
+pub fn remove_at(
+    game_state: &mut GameState,
+    point: &Point3<f32>,
+    kind: Option<SurfaceKind>,
+) -> usize {
+    let before = game_state.surfaces.len();
+    game_state.surfaces.retain(|surface| {
+        !(surface.contains(point) && kind.is_none_or(|kind| surface.kind == kind))
+    });
+    systems::antimagic::update_all(game_state);
+    before - game_state.surfaces.len()
+}
@@ -1,19 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use hecs::{Entity, World};
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::field::debug;
 
 use crate::{
     components::{
+        actions::action::ActionsUsedThisTurn,
+        effects::effect::{EffectInstance, EffectTag},
         health::hit_points::HitPoints,
-        resource::RechargeRule,
-        time::{EntityClock, TimeMode, TimeStep},
+        resource::{RechargeRule, ResourceMap},
+        time::{EntityClock, TimeDuration, TimeMode, TimeStep, TurnBoundary},
     },
     engine::{
         event::{ActionError, Event, EventKind},
         game_state::GameState,
+        rules::OptionalRule,
     },
     systems,
 };
@@ -56,6 +59,17 @@ pub fn advance_time(world: &mut World, entity: Entity, time_step: TimeStep) {
         _ => { /* no special logging for other time steps */ }
     }
 
+    // Once per turn means once during anyone's turn, not just the entity's own
+    if matches!(
+        time_step,
+        TimeStep::TurnBoundary {
+            boundary: TurnBoundary::Start,
+            ..
+        } | TimeStep::RealTime { .. }
+    ) {
+        let _ = world.remove_one::<ActionsUsedThisTurn>(entity);
+    }
+
     let mut expired_effects = Vec::new();
     for effect in systems::effects::effects_mut(world, entity).iter_mut() {
         effect.advance_time(time_step);
@@ -65,6 +79,33 @@ pub fn advance_time(world: &mut World, entity: Entity, time_step: TimeStep) {
     }
 
     systems::effects::remove_effects(world, entity, &expired_effects);
+    systems::coating::advance_time(world, entity, &time_step);
+    systems::light::advance_time(world, entity, &time_step);
+}
+
+/// Effect lifetimes are counted on the turns of their anchor entity. If the
+/// anchor is not taking part in the encounter its turns never come up, so the
+/// lifetime is moved onto the turns of the entity carrying the effect instead.
+pub fn anchor_effects_to_encounter(
+    world: &mut World,
+    entity: Entity,
+    participants: &HashSet<Entity>,
+) {
+    let Ok(mut effects) = world.get::<&mut Vec<EffectInstance>>(entity) else {
+        return;
+    };
+
+    for effect in effects.iter_mut() {
+        if let Some(anchor) = effect.lifetime.anchor()
+            && !participants.contains(&anchor)
+        {
+            debug!(
+                "Re-anchoring effect {} on entity {:?} from {:?} to itself",
+                effect.effect_id, entity, anchor
+            );
+            effect.lifetime.set_anchor(entity);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -74,6 +115,123 @@ pub enum RestKind {
     Long,
 }
 
+impl RestKind {
+    pub fn duration(&self) -> TimeDuration {
+        match self {
+            RestKind::Short => TimeDuration::from_hours(1),
+            RestKind::Long => TimeDuration::from_hours(8),
+        }
+    }
+
+    /// The longest kind of rest which can be completed in the given time, if
+    /// any
+    pub fn completed_within(duration: &TimeDuration) -> Option<RestKind> {
+        [RestKind::Long, RestKind::Short]
+            .into_iter()
+            .find(|kind| kind.duration().as_seconds() <= duration.as_seconds())
+    }
+}
+
+/// Activities that let time pass outside of combat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeActivity {
+    Rest(RestKind),
+    Travel,
+    Downtime,
+}
+
+#[derive(Debug, Clone)]
+pub enum PassTimeError {
+    InCombat { entities: Vec<Entity> },
+}
+
+/// Advance the world clock, letting surfaces fade and recharging the daily
+/// resources of everyone who is not in combat whenever dawn comes around. Each
+/// dawn also lets a day pass for the diseases of the living, and for the food
+/// and water of the party. The dead keep track of how long they've been dead
+/// for, unless the permadeath rule turns them into corpses.
+pub fn advance_world_clock(game_state: &mut GameState, duration: &TimeDuration) {
+    systems::surfaces::advance_time(game_state, duration);
+    systems::illusions::advance_time(game_state, duration);
+    systems::health::advance_death_timers(&mut game_state.world, duration);
+    if game_state.rule_enabled(OptionalRule::Permadeath) {
+        systems::health::convert_to_corpses(&mut game_state.world);
+    }
+
+    let dawns = game_state.clock.advance(duration);
+    if dawns == 0 {
+        return;
+    }
+
+    info!("Dawn of day {}", game_state.clock.day());
+    let entities: Vec<Entity> = game_state
+        .world
+        .query::<&ResourceMap>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| !game_state.in_combat.contains_key(entity))
+        .collect();
+    for entity in entities {
+        systems::resources::recharge(&mut game_state.world, entity, &RechargeRule::Daily);
+    }
+
+    let living: Vec<Entity> = game_state
+        .world
+        .query::<&Vec<EffectInstance>>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| systems::health::is_alive(&game_state.world, *entity))
+        .collect();
+    for _ in 0..dawns {
+        for &entity in &living {
+            systems::disease::on_dawn(&mut game_state.world, entity);
+        }
+        systems::supplies::on_dawn(game_state);
+    }
+}
+
+/// Skip ahead in time outside of combat, e.g. while resting, travelling or
+/// during downtime. Effects run out and daily resources recharge as if the time
+/// had passed in real time.
+pub fn pass_time(
+    game_state: &mut GameState,
+    duration: TimeDuration,
+    activity: TimeActivity,
+) -> Result<(), PassTimeError> {
+    if !game_state.in_combat.is_empty() {
+        let entities: Vec<Entity> = game_state.in_combat.keys().cloned().collect();
+        error!(
+            "Time cannot pass while entities are in combat: {:?}",
+            entities
+        );
+        return Err(PassTimeError::InCombat { entities });
+    }
+
+    info!(
+        "Passing {} seconds of time ({:?})",
+        duration.as_seconds(),
+        activity
+    );
+
+    advance_world_clock(game_state, &duration);
+
+    let time_step = TimeStep::RealTime {
+        delta_seconds: duration.as_seconds(),
+    };
+    let entities: Vec<Entity> = game_state
+        .world
+        .query::<&EntityClock>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        advance_time(&mut game_state.world, entity, time_step);
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub enum RestError {
     InCombat { entities: Vec<Entity> },
@@ -120,16 +278,85 @@ pub fn start_rest(
         game_state.resting.insert(entity, *kind);
     });
 
+    if *kind == RestKind::Long {
+        systems::supplies::on_long_rest(game_state, &participants);
+    }
+
     result
 }
 
 pub fn finish_rest(game_state: &mut GameState, participants: Vec<Entity>) -> Result<(), RestError> {
     info!("Finishing rest for entities {:?}", participants);
 
+    let kind = stop_resting(game_state, &participants)?;
+
+    let event = Event::new(EventKind::RestFinished {
+        kind,
+        participants: participants.clone(),
+    });
+    game_state
+        .process_event(event)
+        .map_err(RestError::ActionError)?;
+
+    // Time passes for everyone, not just the participants, so it can only pass
+    // if no one else is fighting in the meantime
+    if let Err(error) = pass_time(game_state, kind.duration(), TimeActivity::Rest(kind)) {
+        warn!("Rest finished without advancing time: {:?}", error);
+    }
+
+    on_rest_end(&mut game_state.world, &participants, &kind);
+
+    Ok(())
+}
+
+/// Wake the participants before their rest is over, e.g. because of an
+/// encounter during the night. Time passes for the hours they managed to rest,
+/// and they get the benefits of the longest rest which fits within those hours.
+/// Returns the kind of rest they got the benefits of, if any.
+pub fn interrupt_rest(
+    game_state: &mut GameState,
+    participants: Vec<Entity>,
+    hours: u32,
+) -> Result<Option<RestKind>, RestError> {
+    info!(
+        "Interrupting rest for entities {:?} after {} hours",
+        participants, hours
+    );
+
+    let kind = stop_resting(game_state, &participants)?;
+    let rested = TimeDuration::from_hours(hours);
+
+    let event = Event::new(EventKind::RestInterrupted {
+        kind,
+        participants: participants.clone(),
+        hours,
+    });
+    game_state
+        .process_event(event)
+        .map_err(RestError::ActionError)?;
+
+    if let Err(error) = pass_time(game_state, rested, TimeActivity::Rest(kind)) {
+        warn!("Rest interrupted without advancing time: {:?}", error);
+    }
+
+    let completed = RestKind::completed_within(&rested);
+    if let Some(completed) = &completed {
+        on_rest_end(&mut game_state.world, &participants, completed);
+    }
+
+    Ok(completed)
+}
+
+/// Check that all participants are resting, and that they're all taking the
+/// same kind of rest. They're no longer considered to be resting afterwards.
+fn stop_resting(
+    game_state: &mut GameState,
+    participants: &[Entity],
+) -> Result<RestKind, RestError> {
     // Check that all participants are actually resting and of the same kind
     let mut not_resting_entities: Vec<Entity> = Vec::new();
     let mut rest_kinds = HashMap::new();
-    for &entity in &participants {
+    for &entity in participants {
         if let Some(kind) = game_state.resting.remove(&entity) {
             rest_kinds.insert(entity, kind);
         } else {
@@ -156,17 +383,7 @@ pub fn finish_rest(game_state: &mut GameState, participants: Vec<Entity>) -> Res
         });
     }
 
-    let event = Event::new(EventKind::RestFinished {
-        kind: *first_kind,
-        participants: participants.clone(),
-    });
-    game_state
-        .process_event(event)
-        .map_err(RestError::ActionError)?;
-
-    on_rest_end(&mut game_state.world, &participants, first_kind);
-
-    Ok(())
+    Ok(*first_kind)
 }
 
 fn entities_in_combat(game_state: &GameState, participants: &[Entity]) -> Vec<Entity> {
@@ -192,7 +409,17 @@ pub fn on_rest_end(world: &mut World, participants: &[Entity], kind: &RestKind)
 
             RestKind::Long => {
                 systems::resources::recharge(world, entity, &RechargeRule::Rest(RestKind::Long));
+                // Reductions of the hit point maximum, e.g. from Life Drain,
+                // last until the creature finishes a long rest
+                systems::effects::remove_effects_with_tags(
+                    world,
+                    entity,
+                    &[EffectTag::MaxHitPointReduction],
+                );
                 systems::health::heal_full(world, entity);
+                systems::health::recover_from_death(world, entity);
+                systems::conditions::recover_from_exhaustion(world, entity);
+                systems::disease::on_long_rest(world, entity);
                 // TODO: Remove non-permanent effects?
             }
         }
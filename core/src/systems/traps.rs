@@ -0,0 +1,119 @@
+use hecs::Entity;
+use parry3d::na::Point3;
+use tracing::debug;
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    components::{
+        ability::Ability,
+        d20::D20CheckDC,
+        damage::{DamageRoll, DamageSource},
+        hidden::{HiddenFeature, HiddenFeatureKind, Trap},
+        modifier::{Modifiable, ModifierSet, ModifierSource},
+        saving_throw::SavingThrowKind,
+    },
+    engine::{
+        event::{Event, EventKind},
+        game_state::GameState,
+        geometry::WorldPath,
+    },
+    systems::{self, d20::D20CheckDCKind},
+};
+
+/// How close to a trap a creature has to step to set it off, i.e. anywhere in
+/// the 5 ft. square of the trap
+const TRIGGER_RADIUS: f32 = 2.5;
+
+/// Hide a trap on the ground, which goes off the first time someone walks over
+/// it without having found it first. It's spotted with the same DC as it takes
+/// to avoid some of its damage.
+pub fn spawn(game_state: &mut GameState, trap: Trap, position: &Point3<f32>) -> Entity {
+    let entity = systems::discovery::spawn(
+        game_state,
+        "Trap",
+        HiddenFeature::new(HiddenFeatureKind::Trap, trap.dc, ""),
+        position,
+    );
+    let _ = game_state.world.insert_one(entity, trap);
+    entity
+}
+
+/// Set off the hidden traps the entity walked over by moving along the path.
+/// The trap the entity was already standing on doesn't count.
+pub fn on_enter(game_state: &mut GameState, entity: Entity, path: &WorldPath) {
+    let Some(start) = path.start() else {
+        return;
+    };
+
+    let samples = path.samples();
+    let triggered = game_state
+        .world
+        .query::<(&Trap, &HiddenFeature)>()
+        .iter()
+        .filter(|(_, (_, feature))| !feature.revealed)
+        .filter_map(|(trap, _)| {
+            let position = systems::geometry::get_foot_position(&game_state.world, trap)?;
+            let within = |point: &Point3<f32>| {
+                let horizontal_distance =
+                    ((point.x - position.x).powi(2) + (point.z - position.z).powi(2)).sqrt();
+                Length::new::<meter>(horizontal_distance) <= Length::new::<foot>(TRIGGER_RADIUS)
+            };
+            (!within(start) && samples.iter().any(|(sample, _)| within(sample))).then_some(trap)
+        })
+        .collect::<Vec<_>>();
+
+    for trap in triggered {
+        trigger(game_state, entity, trap);
+    }
+}
+
+fn trigger(game_state: &mut GameState, entity: Entity, trap: Entity) {
+    let Some(Trap { damage, dc }) = game_state
+        .world
+        .get::<&Trap>(trap)
+        .ok()
+        .map(|t| (*t).clone())
+    else {
+        return;
+    };
+    // Once it has gone off, the trap is out in the open
+    systems::discovery::reveal(game_state, trap, Some(entity));
+
+    let source = ModifierSource::Custom("Trap".to_string());
+    let dc = D20CheckDCKind::SavingThrow(D20CheckDC {
+        key: SavingThrowKind::Ability(Ability::Dexterity),
+        dc: ModifierSet::from(source.clone(), dc),
+    });
+    let success = systems::d20::check_no_event(&game_state.world, entity, &dc).is_success(&dc);
+    debug!(
+        "{:?} sets off trap {:?} and {} the saving throw",
+        entity,
+        trap,
+        if success { "succeeds" } else { "fails" }
+    );
+
+    let (dice, damage_type) = damage;
+    let mut damage_roll = DamageRoll::new(dice, damage_type, DamageSource::Environment).roll(false);
+    if success {
+        for component in damage_roll.components.iter_mut() {
+            let total = component.result.subtotal;
+            component
+                .result
+                .modifiers
+                .add_modifier(source.clone(), -(total as f32 / 2.0).ceil() as i32);
+        }
+        damage_roll.recalculate_total();
+    }
+
+    let (_, new_life_state) = systems::health::damage(game_state, entity, &damage_roll, None);
+    if let Some(new_state) = new_life_state {
+        let _ = game_state.process_event(Event::new(EventKind::LifeStateChanged {
+            entity,
+            new_state,
+            actor: None,
+        }));
+    }
+}
@@ -0,0 +1,224 @@
+use hecs::Entity;
+use tracing::{debug, info, warn};
+
+use crate::{
+    components::{
+        id::ItemId,
+        items::{inventory::ItemContainer, money::MonetaryValue},
+        time::TimeDuration,
+        travel::{Location, LocationKind},
+    },
+    engine::game_state::GameState,
+    registry::registry::ItemsRegistry,
+    systems::{
+        self,
+        time::{PassTimeError, RestError, RestKind, TimeActivity},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub enum TravelError {
+    /// Travel only makes sense within an adventure
+    NoCampaign,
+    UnknownLocation(String),
+    NoRoute {
+        from: String,
+        to: String,
+    },
+    PassTime(PassTimeError),
+    NoService {
+        location: String,
+    },
+    NotForSale(ItemId),
+    InsufficientFunds {
+        cost: MonetaryValue,
+    },
+    Rest(RestError),
+}
+
+/// Where the party ended up after travelling. The journey is cut short if an
+/// encounter happens along the way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TravelOutcome {
+    pub location: String,
+    pub hours: u32,
+    /// Name of the encounter in the adventure waiting for the party
+    pub encounter: Option<String>,
+}
+
+/// How a night of camping went
+#[derive(Debug, Clone, PartialEq)]
+pub struct CampOutcome {
+    /// How many hours the party got to rest before waking up
+    pub hours: u32,
+    /// The rest the party got the benefits of, if any
+    pub rest: Option<RestKind>,
+    /// Name of the encounter in the adventure which woke the party
+    pub encounter: Option<String>,
+    /// Who was keeping watch when the encounter happened
+    pub sentry: Option<Entity>,
+}
+
+/// Where the party currently is on the world map of the adventure
+pub fn current_location(game_state: &GameState) -> Option<&'static Location> {
+    let adventure = systems::campaign::adventure(game_state)?;
+    let location = game_state.campaign.as_ref()?.location.as_ref()?;
+    adventure.world_map.location(location)
+}
+
+/// Travel along the quickest route to the destination. Time passes for every
+/// leg of the journey, and the encounter table of each location on the way is
+/// rolled when the party arrives there.
+pub fn travel(game_state: &mut GameState, destination: &str) -> Result<TravelOutcome, TravelError> {
+    let adventure = systems::campaign::adventure(game_state).ok_or(TravelError::NoCampaign)?;
+    let world_map = &adventure.world_map;
+    if world_map.location(destination).is_none() {
+        return Err(TravelError::UnknownLocation(destination.to_string()));
+    }
+    let from = current_location(game_state)
+        .or(world_map.start())
+        .ok_or(TravelError::NoCampaign)?;
+    let path = world_map
+        .path(&from.id, destination)
+        .ok_or_else(|| TravelError::NoRoute {
+            from: from.id.clone(),
+            to: destination.to_string(),
+        })?;
+
+    let mut outcome = TravelOutcome {
+        location: from.id.clone(),
+        hours: 0,
+        encounter: None,
+    };
+    for (location, hours) in path {
+        systems::time::pass_time(
+            game_state,
+            TimeDuration::from_hours(hours),
+            TimeActivity::Travel,
+        )
+        .map_err(TravelError::PassTime)?;
+
+        game_state.campaign.as_mut().unwrap().location = Some(location.id.clone());
+        outcome.location = location.id.clone();
+        outcome.hours += hours;
+        debug!("Party arrived at {} after {} hours", location.name, hours);
+        if location.kind == LocationKind::Town {
+            systems::supplies::refill_water(game_state);
+        }
+
+        if let Some(encounter) = location.encounters.roll() {
+            info!("Encounter '{}' at {}", encounter, location.name);
+            outcome.encounter = Some(encounter.to_string());
+            break;
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Make camp at the current location and take a long rest, with the party
+/// taking turns keeping watch in the given order. The night is split evenly
+/// between the watches, and the encounter table of the location is rolled for
+/// every hour of it. An encounter wakes the party, who only get the benefits of
+/// the hours they managed to rest.
+pub fn camp(
+    game_state: &mut GameState,
+    party: Vec<Entity>,
+    watch_order: &[Entity],
+) -> Result<CampOutcome, TravelError> {
+    let location = current_location(game_state).ok_or(TravelError::NoCampaign)?;
+    let night = RestKind::Long.duration().as_hours();
+
+    systems::time::start_rest(game_state, party.clone(), &RestKind::Long)
+        .map_err(TravelError::Rest)?;
+
+    let interruption = (0..night).find_map(|hour| {
+        let encounter = location.encounters.roll_during_rest()?;
+        let sentry = if watch_order.is_empty() {
+            None
+        } else {
+            Some(watch_order[hour as usize * watch_order.len() / night as usize])
+        };
+        Some((hour, encounter, sentry))
+    });
+
+    let Some((hours, encounter, sentry)) = interruption else {
+        systems::time::finish_rest(game_state, party).map_err(TravelError::Rest)?;
+        return Ok(CampOutcome {
+            hours: night,
+            rest: Some(RestKind::Long),
+            encounter: None,
+            sentry: None,
+        });
+    };
+
+    info!(
+        "Encounter '{}' at {} after {} hours of rest, with {:?} on watch",
+        encounter, location.name, hours, sentry
+    );
+    let rest =
+        systems::time::interrupt_rest(game_state, party, hours).map_err(TravelError::Rest)?;
+    Ok(CampOutcome {
+        hours,
+        rest,
+        encounter: Some(encounter.to_string()),
+        sentry,
+    })
+}
+
+/// Let the party take a long rest at the inn of the current location, paid for
+/// by one of them
+pub fn rest_at_inn(
+    game_state: &mut GameState,
+    payer: Entity,
+    party: Vec<Entity>,
+) -> Result<(), TravelError> {
+    let location = current_location(game_state).ok_or(TravelError::NoCampaign)?;
+    let price = location
+        .inn_price()
+        .ok_or_else(|| TravelError::NoService {
+            location: location.id.clone(),
+        })?
+        .clone();
+
+    systems::inventory::remove_money(&mut game_state.world, payer, price.clone()).map_err(
+        |_| TravelError::InsufficientFunds {
+            cost: price.clone(),
+        },
+    )?;
+
+    // A hot meal is included in the price
+    systems::supplies::provide_meals(game_state, &party);
+    let result = systems::time::start_rest(game_state, party.clone(), &RestKind::Long)
+        .and_then(|_| systems::time::finish_rest(game_state, party));
+    if let Err(error) = result {
+        // No one got any rest, so the room isn't paid for
+        warn!(
+            "Failed to rest at the inn of {}: {:?}",
+            location.name, error
+        );
+        systems::inventory::add_money(&mut game_state.world, payer, price);
+        return Err(TravelError::Rest(error));
+    }
+
+    Ok(())
+}
+
+/// Buy an item from a shop at the current location
+pub fn buy(game_state: &mut GameState, buyer: Entity, item_id: &ItemId) -> Result<(), TravelError> {
+    let location = current_location(game_state).ok_or(TravelError::NoCampaign)?;
+    if !location.shop_stock().any(|stock| stock == item_id) {
+        return Err(TravelError::NotForSale(item_id.clone()));
+    }
+    let Some(item) = ItemsRegistry::get(item_id) else {
+        return Err(TravelError::NotForSale(item_id.clone()));
+    };
+
+    let cost = item.item().value.clone();
+    systems::inventory::remove_money(&mut game_state.world, buyer, cost.clone())
+        .map_err(|_| TravelError::InsufficientFunds { cost })?;
+    systems::inventory::add_item(&mut game_state.world, buyer, item.clone());
+
+    debug!("{:?} bought {} in {}", buyer, item_id, location.name);
+    Ok(())
+}
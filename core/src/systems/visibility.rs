@@ -0,0 +1,77 @@
+use hecs::{Entity, World};
+use parry3d::na::{Point3, Vector2};
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    components::{
+        ai::PlayerControlledTag, effects::effect::EffectTag, faction::Attitude, id::EffectId,
+    },
+    entities::ground_item::GroundItemTag,
+    systems::{self, geometry::CreaturePose},
+};
+
+/// Half the width of the 5 ft. square a creature occupies, which is how close
+/// a guess has to be to hit an unseen creature
+const HALF_SQUARE_FEET: f32 = 2.5;
+
+/// Whether the entity has an active effect which makes it Invisible
+pub fn is_invisible(world: &World, entity: Entity) -> bool {
+    systems::effects::active_effects(world, entity)
+        .iter()
+        .any(|effect| effect.effect().has_tag(&EffectTag::Invisible))
+}
+
+pub fn hidden() -> EffectId {
+    EffectId::new("nat20_core", "effect.hidden")
+}
+
+/// Whether the entity is hidden, which also makes it Invisible until it gives
+/// itself away
+pub fn is_hiding(world: &World, entity: Entity) -> bool {
+    systems::effects::has_effect(world, entity, &hidden())
+}
+
+/// Whether the viewer knows where the target is. Invisible creatures can't be
+/// seen by anyone but their allies, who are assumed to know where they are.
+pub fn can_see(world: &World, viewer: Entity, target: Entity) -> bool {
+    viewer == target
+        || !is_invisible(world, target)
+        || systems::factions::attitude_from_to(world, viewer, target) == Attitude::Friendly
+}
+
+/// Whether the entity should be hidden from the players, i.e. none of the
+/// player controlled creatures can see it. Without any players around, e.g.
+/// when the GM is setting up an encounter, everything is shown.
+pub fn hidden_from_players(world: &World, entity: Entity) -> bool {
+    let players: Vec<Entity> = world
+        .query::<&PlayerControlledTag>()
+        .iter()
+        .map(|(player, _)| player)
+        .collect();
+    !players.is_empty() && !players.iter().any(|player| can_see(world, *player, entity))
+}
+
+/// The creature occupying the square at the point, if any. Creatures which
+/// can't be seen have to be attacked by guessing which square they're in, so
+/// the guess doesn't have to hit the creature itself.
+pub fn creature_in_square(world: &World, point: Point3<f32>) -> Option<Entity> {
+    let half_square = Length::new::<foot>(HALF_SQUARE_FEET).get::<meter>();
+
+    world
+        .query::<&CreaturePose>()
+        .without::<&GroundItemTag>()
+        .iter()
+        .filter_map(|(entity, pose)| {
+            let height = systems::geometry::get_height(world, entity)?;
+            let foot_position = pose.translation.vector;
+            let offset = Vector2::new(point.x - foot_position.x, point.z - foot_position.z).norm();
+            let within_height =
+                point.y >= foot_position.y - half_square && point.y <= foot_position.y + height;
+            (offset <= half_square && within_height).then_some((entity, offset))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
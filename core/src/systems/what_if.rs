@@ -0,0 +1,385 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::Infallible,
+    fmt,
+};
+
+use hecs::{Entity, World};
+use strum::IntoEnumIterator;
+use uom::si::length::foot;
+
+use crate::{
+    components::{
+        ability::{Ability, AbilityScoreMap},
+        actions::preview::DamageRange,
+        d20::D20CheckExplanation,
+        health::hit_points::HitPoints,
+        id::{ActionId, EffectId, ResourceId},
+        items::equipment::{loadout::EquipmentInstance, slots::EquipmentSlot},
+        modifier::{Modifiable, ModifierSource},
+        resource::{ResourceAmount, ResourceMap},
+        saving_throw::{SavingThrowKind, SavingThrowSet},
+        skill::{Skill, SkillSet},
+        speed::Speed,
+    },
+    entities::{
+        character::{Character, CharacterTag},
+        monster::{Monster, MonsterTag},
+    },
+    systems,
+};
+
+/// A hypothetical change to a creature
+#[derive(Debug, Clone)]
+pub enum WhatIf {
+    /// The creature is affected by an effect, e.g. "what does Bless do for me?"
+    Effect(EffectId),
+    /// The creature equips an item, replacing whatever is in its slot
+    Equip(EquipmentInstance),
+    Unequip(EquipmentSlot),
+}
+
+/// The attack bonus and damage of the weapon in a slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeaponStats {
+    /// The expected bonus to the attack roll, counting dice modifiers like
+    /// Bless at their average
+    pub attack_bonus: f32,
+    /// The damage before the target's resistances
+    pub damage: DamageRange,
+}
+
+/// The stats of a creature at a point in time, for comparing what a change would
+/// do to it, e.g. equipping an item or levelling up. Bonuses to d20 checks count
+/// dice modifiers at their average.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatSnapshot {
+    pub ability_scores: BTreeMap<Ability, i32>,
+    pub saving_throws: BTreeMap<Ability, f32>,
+    pub skills: HashMap<Skill, f32>,
+    pub armor_class: i32,
+    pub max_hit_points: u32,
+    /// Speed in feet
+    pub speed: f32,
+    pub weapons: Vec<(EquipmentSlot, WeaponStats)>,
+    /// Maximum uses of each resource, e.g. one entry per spell slot level
+    pub resources: BTreeMap<ResourceId, Vec<ResourceAmount>>,
+    pub actions: BTreeSet<ActionId>,
+    pub effects: BTreeSet<EffectId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stat {
+    AbilityScore(Ability),
+    SavingThrow(Ability),
+    Skill(Skill),
+    ArmorClass,
+    MaxHitPoints,
+    Speed,
+    AttackBonus(EquipmentSlot),
+    MinDamage(EquipmentSlot),
+    MaxDamage(EquipmentSlot),
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stat::AbilityScore(ability) => write!(f, "{}", ability),
+            Stat::SavingThrow(ability) => write!(f, "{} Saving Throw", ability),
+            Stat::Skill(skill) => write!(f, "{}", skill),
+            Stat::ArmorClass => write!(f, "Armor Class"),
+            Stat::MaxHitPoints => write!(f, "Max Hit Points"),
+            Stat::Speed => write!(f, "Speed"),
+            Stat::AttackBonus(slot) => write!(f, "Attack Bonus ({})", slot),
+            Stat::MinDamage(slot) => write!(f, "Min Damage ({})", slot),
+            Stat::MaxDamage(slot) => write!(f, "Max Damage ({})", slot),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatChange {
+    pub stat: Stat,
+    pub before: f32,
+    pub after: f32,
+}
+
+impl StatChange {
+    pub fn delta(&self) -> f32 {
+        self.after - self.before
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIfReport {
+    pub before: StatSnapshot,
+    pub after: StatSnapshot,
+}
+
+impl WhatIfReport {
+    /// Every stat which would be different after the changes
+    pub fn changes(&self) -> Vec<StatChange> {
+        let before = self.before.flatten();
+        let after = self.after.flatten();
+
+        let mut changes = Vec::new();
+        for (stat, before_value) in &before {
+            let after_value = after
+                .iter()
+                .find(|(other, _)| other == stat)
+                .map(|(_, value)| *value);
+            match after_value {
+                Some(after_value) if after_value == *before_value => {}
+                // A weapon which is no longer there is treated as a zero
+                _ => changes.push(StatChange {
+                    stat: *stat,
+                    before: *before_value,
+                    after: after_value.unwrap_or(0.0),
+                }),
+            }
+        }
+        for (stat, after_value) in &after {
+            if !before.iter().any(|(other, _)| other == stat) {
+                changes.push(StatChange {
+                    stat: *stat,
+                    before: 0.0,
+                    after: *after_value,
+                });
+            }
+        }
+        changes
+    }
+
+    /// Every resource the creature has before or after the changes, along with
+    /// its maximum uses before and after. Resources the creature didn't have
+    /// before have no uses in `before`.
+    pub fn resources(&self) -> Vec<(ResourceId, Vec<ResourceAmount>, Vec<ResourceAmount>)> {
+        let ids = self
+            .before
+            .resources
+            .keys()
+            .chain(self.after.resources.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+
+        ids.into_iter()
+            .map(|id| {
+                let before = self.before.resources.get(&id).cloned().unwrap_or_default();
+                let after = self.after.resources.get(&id).cloned().unwrap_or_default();
+                (id, before, after)
+            })
+            .collect()
+    }
+
+    pub fn gained_actions(&self) -> impl Iterator<Item = &ActionId> {
+        self.after.actions.difference(&self.before.actions)
+    }
+
+    pub fn lost_actions(&self) -> impl Iterator<Item = &ActionId> {
+        self.before.actions.difference(&self.after.actions)
+    }
+
+    pub fn gained_effects(&self) -> impl Iterator<Item = &EffectId> {
+        self.after.effects.difference(&self.before.effects)
+    }
+
+    pub fn lost_effects(&self) -> impl Iterator<Item = &EffectId> {
+        self.before.effects.difference(&self.after.effects)
+    }
+}
+
+impl StatSnapshot {
+    fn flatten(&self) -> Vec<(Stat, f32)> {
+        let mut stats = Vec::new();
+        stats.extend(
+            self.ability_scores
+                .iter()
+                .map(|(ability, score)| (Stat::AbilityScore(*ability), *score as f32)),
+        );
+        stats.extend(
+            self.saving_throws
+                .iter()
+                .map(|(ability, bonus)| (Stat::SavingThrow(*ability), *bonus)),
+        );
+        for skill in Skill::iter() {
+            if let Some(bonus) = self.skills.get(&skill) {
+                stats.push((Stat::Skill(skill), *bonus));
+            }
+        }
+        stats.push((Stat::ArmorClass, self.armor_class as f32));
+        stats.push((Stat::MaxHitPoints, self.max_hit_points as f32));
+        stats.push((Stat::Speed, self.speed));
+        for (slot, weapon) in &self.weapons {
+            stats.push((Stat::AttackBonus(*slot), weapon.attack_bonus));
+            stats.push((Stat::MinDamage(*slot), weapon.damage.min as f32));
+            stats.push((Stat::MaxDamage(*slot), weapon.damage.max as f32));
+        }
+        stats
+    }
+}
+
+/// Copy a creature into a new world of its own, so it can be changed without
+/// touching the original. Only the components of the character or monster
+/// bundle are copied, and anything the creature refers to in the original
+/// world, e.g. the applier of an effect, isn't there. Returns `None` if the
+/// entity isn't a character or a monster.
+pub fn scratch_copy(world: &World, entity: Entity) -> Option<(World, Entity)> {
+    let mut scratch = World::new();
+    let copy = if world.get::<&CharacterTag>(entity).is_ok() {
+        scratch.spawn(Character::from_world(world, entity))
+    } else if world.get::<&MonsterTag>(entity).is_ok() {
+        scratch.spawn(Monster::from_world(world, entity))
+    } else {
+        return None;
+    };
+    Some((scratch, copy))
+}
+
+pub fn snapshot(world: &World, entity: Entity) -> StatSnapshot {
+    let ability_scores = systems::helpers::get_component::<AbilityScoreMap>(world, entity);
+    let saving_throws = systems::helpers::get_component::<SavingThrowSet>(world, entity);
+    let skills = systems::helpers::get_component::<SkillSet>(world, entity);
+
+    StatSnapshot {
+        ability_scores: Ability::iter()
+            .map(|ability| (ability, ability_scores.get(&ability).total()))
+            .collect(),
+        saving_throws: Ability::iter()
+            .map(|ability| {
+                let explanation =
+                    saving_throws.explain(&SavingThrowKind::Ability(ability), world, entity);
+                (ability, expected_bonus(&explanation))
+            })
+            .collect(),
+        skills: Skill::iter()
+            .map(|skill| {
+                (
+                    skill,
+                    expected_bonus(&skills.explain(&skill, world, entity)),
+                )
+            })
+            .collect(),
+        armor_class: systems::loadout::armor_class(world, entity).total(),
+        max_hit_points: systems::helpers::get_component::<HitPoints>(world, entity).max(),
+        speed: systems::helpers::get_component::<Speed>(world, entity)
+            .get_total_speed()
+            .get::<foot>(),
+        weapons: weapon_stats(world, entity),
+        resources: systems::helpers::get_component::<ResourceMap>(world, entity)
+            .iter()
+            .map(|(id, budget)| (id.clone(), budget.max_uses()))
+            .collect(),
+        actions: systems::actions::all_actions(world, entity)
+            .keys()
+            .cloned()
+            .collect(),
+        effects: systems::effects::effects(world, entity)
+            .iter()
+            .map(|effect| effect.effect_id.clone())
+            .collect(),
+    }
+}
+
+/// The bonus to the check, counting dice modifiers like Bless at their average
+fn expected_bonus(explanation: &D20CheckExplanation) -> f32 {
+    explanation.total_modifier() as f32 + explanation.modifiers.dice_average()
+}
+
+fn weapon_stats(world: &World, entity: Entity) -> Vec<(EquipmentSlot, WeaponStats)> {
+    let proficiency_bonus = systems::helpers::level(world, entity)
+        .map(|level| level.proficiency_bonus())
+        .unwrap_or(0);
+    let slots = EquipmentSlot::weapon_slots()
+        .iter()
+        .filter(|slot| {
+            systems::loadout::loadout(world, entity)
+                .weapon_in_hand(slot)
+                .is_some()
+        })
+        .copied()
+        .collect::<Vec<_>>();
+
+    slots
+        .into_iter()
+        .map(|slot| {
+            // The creature attacks itself, since there's no target to compare with
+            let attack_roll = systems::damage::prepare_attack_roll(
+                systems::loadout::weapon_attack_roll(world, entity, entity, &slot),
+                world,
+                entity,
+                entity,
+            );
+            let attack_bonus = expected_bonus(&attack_roll.d20_check.explain(proficiency_bonus));
+
+            let damage_roll = systems::damage::prepare_damage_roll(
+                systems::loadout::weapon_damage_roll(world, entity, &slot),
+                world,
+                entity,
+            );
+            let (min, max) = damage_roll.min_max_results(false);
+            (
+                slot,
+                WeaponStats {
+                    attack_bonus,
+                    damage: DamageRange {
+                        min: min.total,
+                        max: max.total,
+                    },
+                },
+            )
+        })
+        .collect()
+}
+
+/// Apply the changes to a copy of the creature and compare its stats before and
+/// after, without changing anything in the world. Returns `None` if the entity
+/// isn't a character or a monster.
+pub fn compare(world: &World, entity: Entity, changes: &[WhatIf]) -> Option<WhatIfReport> {
+    let (mut scratch, copy) = scratch_copy(world, entity)?;
+
+    let source = ModifierSource::Custom("What If".to_string());
+    compare_in_scratch(&mut scratch, copy, |scratch, copy| {
+        for change in changes {
+            match change {
+                WhatIf::Effect(effect_id) => {
+                    systems::effects::add_permanent_effects(
+                        scratch,
+                        copy,
+                        vec![effect_id.clone()],
+                        &source,
+                        None,
+                    );
+                }
+                WhatIf::Equip(equipment) => {
+                    // Items that can't be equipped just don't change anything
+                    let _ = systems::loadout::equip(scratch, copy, equipment.clone());
+                }
+                WhatIf::Unequip(slot) => {
+                    let _ = systems::loadout::unequip(scratch, copy, slot);
+                }
+            }
+        }
+        Ok::<_, Infallible>(())
+    })
+    .ok()
+}
+
+/// Snapshot a creature in a scratch world, apply the change to it and snapshot
+/// it again, see `scratch_copy`. If the change fails, the error is returned
+/// instead.
+pub fn compare_in_scratch<E>(
+    scratch: &mut World,
+    entity: Entity,
+    change: impl FnOnce(&mut World, Entity) -> Result<(), E>,
+) -> Result<WhatIfReport, E> {
+    systems::health::update_hit_points(scratch, entity);
+    let before = snapshot(scratch, entity);
+
+    change(scratch, entity)?;
+    systems::health::update_hit_points(scratch, entity);
+
+    Ok(WhatIfReport {
+        before,
+        after: snapshot(scratch, entity),
+    })
+}
@@ -3,13 +3,16 @@ pub mod equipment {
 
     use uom::si::{f32::Mass, mass::pound};
 
-    use crate::components::{
-        id::ItemId,
-        items::{
-            equipment::equipment::{EquipmentItem, EquipmentKind},
-            item::{Item, ItemRarity},
-            money::MonetaryValue,
+    use crate::{
+        components::{
+            id::ItemId,
+            items::{
+                equipment::equipment::{EquipmentItem, EquipmentKind},
+                item::{Item, ItemRarity},
+                money::MonetaryValue,
+            },
         },
+        entities::object::ObjectMaterial,
     };
 
     pub fn boots() -> EquipmentItem {
@@ -21,6 +24,8 @@ pub mod equipment {
                 weight: Mass::new::<pound>(1.8),
                 value: MonetaryValue::from_str("10 GP").unwrap(),
                 rarity: ItemRarity::Common,
+                material: ObjectMaterial::Cloth,
+                damage_taken: 0,
             },
             kind: EquipmentKind::Boots,
             effects: Vec::new(),
@@ -36,6 +41,8 @@ pub mod equipment {
                 weight: Mass::new::<pound>(0.5),
                 value: MonetaryValue::from_str("5 GP").unwrap(),
                 rarity: ItemRarity::Common,
+                material: ObjectMaterial::Cloth,
+                damage_taken: 0,
             },
             kind: EquipmentKind::Gloves,
             effects: Vec::new(),
@@ -135,6 +142,7 @@ pub mod creatures {
                                 (8, ItemId::new("nat20_core", "item.javelin")),
                             ],
                             money: "4 GP".to_string(),
+                            any_weapon: Vec::new(),
                         },
                     ),
                     LevelUpDecision::single_choice_with_id(
@@ -142,6 +150,7 @@ pub mod creatures {
                         ChoiceItem::Equipment {
                             items: Vec::new(),
                             money: "50 GP".to_string(),
+                            any_weapon: Vec::new(),
                         },
                     ),
                     // Level 2
@@ -283,6 +292,7 @@ pub mod creatures {
                                 (1, ItemId::new("nat20_core", "item.robe")),
                             ],
                             money: "8 GP".to_string(),
+                            any_weapon: Vec::new(),
                         },
                     ),
                     LevelUpDecision::spells(
@@ -419,6 +429,7 @@ pub mod creatures {
                         ChoiceItem::Equipment {
                             items: vec![(1, ItemId::new("nat20_core", "item.robe"))],
                             money: "8 GP".to_string(),
+                            any_weapon: Vec::new(),
                         },
                     ),
                     LevelUpDecision::spells(
@@ -499,20 +510,12 @@ pub mod creatures {
                 faction::FactionSet,
                 health::hit_points::HitPoints,
                 id::{EntityIdentifier, FactionId, ItemId, Name},
-                items::{
-                    equipment::{
-                        armor::ArmorTrainingSet, loadout::TryEquipError,
-                        weapon::WeaponProficiencyMap,
-                    },
-                    inventory::ItemInstance,
-                },
                 level::ChallengeRating,
-                proficiency::{Proficiency, ProficiencyLevel},
                 species::{CreatureSize, CreatureType},
                 speed::Speed,
             },
             entities::monster::Monster,
-            registry::{self, registry::ItemsRegistry},
+            registry,
         };
 
         use super::*;
@@ -538,7 +541,7 @@ pub mod creatures {
                 FactionSet::from([FactionId::new("nat20_core", "faction.goblins")]),
             );
             let entity = world.spawn(monster);
-            let _ = monster_equipment(
+            let _ = systems::generator::equip_monster(
                 world,
                 entity,
                 &[
@@ -552,45 +555,19 @@ pub mod creatures {
 
             EntityIdentifier::new(entity, name)
         }
-
-        fn monster_equipment(
-            world: &mut World,
-            entity: Entity,
-            item_ids: &[ItemId],
-        ) -> Result<(), TryEquipError> {
-            for item_id in item_ids {
-                let item = ItemsRegistry::get(item_id).unwrap().clone();
-                // Monsters are considered proficient with all their equipment
-                // so we can add proficiency for what they equip
-                match &item {
-                    ItemInstance::Armor(armor) => {
-                        systems::helpers::get_component_mut::<ArmorTrainingSet>(world, entity)
-                            .insert(armor.armor_type.clone());
-                    }
-                    ItemInstance::Weapon(weapon) => {
-                        systems::helpers::get_component_mut::<WeaponProficiencyMap>(world, entity)
-                            .set_proficiency(
-                                weapon.category().clone(),
-                                Proficiency::new(
-                                    ProficiencyLevel::Proficient,
-                                    ModifierSource::None,
-                                ),
-                            );
-                    }
-                    _ => {}
-                }
-
-                systems::loadout::equip(world, entity, item)?;
-            }
-            Ok(())
-        }
     }
 }
 
 pub mod engine {
+    use hecs::{Entity, World};
+    use parry3d::na::Point3;
     use rerecast::ConfigBuilder;
 
-    use crate::engine::{game_state::GameState, geometry::WorldGeometry};
+    use crate::{
+        components::id::EntityIdentifier,
+        engine::{game_state::GameState, geometry::WorldGeometry},
+        systems,
+    };
 
     pub fn game_state() -> GameState {
         GameState::new(WorldGeometry::from_obj_path(
@@ -598,4 +575,23 @@ pub mod engine {
             &ConfigBuilder::default().build(),
         ))
     }
+
+    /// A game state with the first creature at the origin and the second
+    /// `distance` away from it along the x-axis
+    pub fn game_state_with_pair(
+        first: fn(&mut World) -> EntityIdentifier,
+        second: fn(&mut World) -> EntityIdentifier,
+        distance: f32,
+    ) -> (GameState, Entity, Entity) {
+        let mut game_state = game_state();
+        let first = first(&mut game_state.world).id();
+        let second = second(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, first, &Point3::origin());
+        systems::geometry::teleport_to(
+            &mut game_state.world,
+            second,
+            &Point3::new(distance, 0.0, 0.0),
+        );
+        (game_state, first, second)
+    }
 }
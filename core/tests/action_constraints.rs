@@ -0,0 +1,258 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            actions::{
+                action::{Action, ActionContext},
+                constraint::ActionConstraint,
+                targeting::TargetInstance,
+            },
+            id::{ActionId, EffectId, ItemId},
+            items::equipment::{slots::EquipmentSlot, weapon::WeaponKind},
+            modifier::ModifierSource,
+            resource::ResourceAmountMap,
+            time::{TimeMode, TimeStep, TurnBoundary},
+        },
+        registry::registry::ItemsRegistry,
+        systems::{self, actions::ActionUsabilityError},
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+    use uom::si::{f32::Length, length::foot};
+
+    fn grapple(world: &World, entity: Entity) -> Result<(), ActionUsabilityError> {
+        systems::actions::action_usable(
+            world,
+            entity,
+            &ActionId::new("nat20_core", "action.grapple"),
+            &ActionContext::Other,
+            &ResourceAmountMap::new(),
+        )
+    }
+
+    fn attack() -> ActionId {
+        ActionId::new("nat20_core", "action.weapon_attack")
+    }
+
+    #[test]
+    fn grapple_requires_free_hand() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+
+        // One hand can let go of the greatsword
+        assert!(grapple(&world, fighter).is_ok());
+
+        let dagger = ItemsRegistry::get(&ItemId::new("nat20_core", "item.dagger"))
+            .unwrap()
+            .clone();
+        for slot in [EquipmentSlot::MeleeMainHand, EquipmentSlot::MeleeOffHand] {
+            let _ = systems::loadout::equip_in_slot(&mut world, fighter, &slot, dagger.clone());
+        }
+        assert_eq!(
+            grapple(&world, fighter),
+            Err(ActionUsabilityError::ConstraintNotMet(
+                ActionConstraint::FreeHand
+            ))
+        );
+        assert!(
+            !systems::actions::available_actions(&world, fighter)
+                .contains_key(&ActionId::new("nat20_core", "action.grapple"))
+        );
+    }
+
+    #[test]
+    fn weapon_constraint_checks_context() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let melee_only = ActionConstraint::Weapon(WeaponKind::Melee);
+
+        assert!(melee_only.is_met(
+            &world,
+            fighter,
+            &attack(),
+            &ActionContext::Weapon {
+                slot: EquipmentSlot::MeleeMainHand
+            }
+        ));
+        assert!(!melee_only.is_met(
+            &world,
+            fighter,
+            &attack(),
+            &ActionContext::Weapon {
+                slot: EquipmentSlot::RangedMainHand
+            }
+        ));
+        assert!(!melee_only.is_met(&world, fighter, &attack(), &ActionContext::Other));
+    }
+
+    #[test]
+    fn effect_constraints() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let dash = EffectId::new("nat20_core", "effect.dash");
+        let while_dashing = ActionConstraint::RequiresEffect(dash.clone());
+        let not_while_dashing = ActionConstraint::ForbidsEffect(dash.clone());
+
+        assert!(!while_dashing.is_met(&world, fighter, &attack(), &ActionContext::Other));
+        assert!(not_while_dashing.is_met(&world, fighter, &attack(), &ActionContext::Other));
+
+        systems::effects::add_permanent_effects(
+            &mut world,
+            fighter,
+            vec![dash],
+            &ModifierSource::Base,
+            None,
+        );
+        assert!(while_dashing.is_met(&world, fighter, &attack(), &ActionContext::Other));
+        assert!(!not_while_dashing.is_met(&world, fighter, &attack(), &ActionContext::Other));
+    }
+
+    #[test]
+    fn target_within_constraint() {
+        let mut game_state = fixtures::engine::game_state();
+        let world = &mut game_state.world;
+        let fighter = fixtures::creatures::heroes::fighter(world).id();
+        let near = fixtures::creatures::monsters::goblin_warrior(world).id();
+        let far = fixtures::creatures::monsters::goblin_warrior(world).id();
+        systems::geometry::teleport_to(world, fighter, &Point3::origin());
+        systems::geometry::teleport_to(world, near, &Point3::new(1.0, 0.0, 0.0));
+        systems::geometry::teleport_to(world, far, &Point3::new(10.0, 0.0, 0.0));
+
+        let within = ActionConstraint::TargetWithin(Length::new::<foot>(5.0));
+        assert!(within.allows_target(world, fighter, &TargetInstance::Entity(near)));
+        assert!(!within.allows_target(world, fighter, &TargetInstance::Entity(far)));
+        assert!(within.allows_target(
+            world,
+            fighter,
+            &TargetInstance::Point(Point3::new(0.5, 0.0, 0.0))
+        ));
+        // Constraints on the targets don't stop the action from being available
+        assert!(within.is_met(world, fighter, &attack(), &ActionContext::Other));
+    }
+
+    #[test]
+    fn constraints_are_parsed_from_definition() {
+        let action: Action = serde_json::from_str(
+            r#"{
+                "id": "nat20_core::action.test",
+                "description": "",
+                "kind": {
+                    "standard": {
+                        "payload": {
+                            "search": true
+                        }
+                    }
+                },
+                "targeting": "self",
+                "resource_cost": {},
+                "constraints": [
+                    "once_per_turn",
+                    { "requires_effect": "nat20_core::effect.dash" },
+                    { "weapon": "melee" },
+                    { "target_within": "5 feet" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            action.constraints(),
+            &[
+                ActionConstraint::OncePerTurn,
+                ActionConstraint::RequiresEffect(EffectId::new("nat20_core", "effect.dash")),
+                ActionConstraint::Weapon(WeaponKind::Melee),
+                ActionConstraint::TargetWithin(Length::new::<foot>(5.0)),
+            ]
+        );
+        // Once per turn doesn't put the action on cooldown until the actor's
+        // next turn
+        assert_eq!(action.cooldown, None);
+    }
+
+    #[test]
+    fn constraint_distance_cannot_depend_on_variables() {
+        let action = serde_json::from_str::<Action>(
+            r#"{
+                "id": "nat20_core::action.test",
+                "description": "",
+                "kind": {
+                    "standard": {
+                        "payload": {
+                            "search": true
+                        }
+                    }
+                },
+                "targeting": "self",
+                "resource_cost": {},
+                "constraints": [
+                    { "target_within": "spell_level * 5 feet" }
+                ]
+            }"#,
+        );
+        assert!(action.is_err());
+    }
+
+    #[test]
+    fn once_per_turn_recharges_on_every_turn() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut world).id();
+        systems::time::set_time_mode(
+            &mut world,
+            fighter,
+            TimeMode::TurnBased { encounter_id: None },
+        );
+        let reckless_attack = ActionId::new("nat20_core", "action.barbarian.reckless_attack");
+        let usable = |world: &World| {
+            systems::actions::action_usable(
+                world,
+                fighter,
+                &reckless_attack,
+                &ActionContext::Other,
+                &ResourceAmountMap::new(),
+            )
+        };
+        assert!(usable(&world).is_ok());
+
+        let action = systems::actions::get_action(&reckless_attack)
+            .unwrap()
+            .clone();
+        systems::actions::record_use(&mut world, fighter, &reckless_attack, &action);
+        assert_eq!(
+            usable(&world),
+            Err(ActionUsabilityError::ConstraintNotMet(
+                ActionConstraint::OncePerTurn
+            ))
+        );
+
+        // The goblin's turn is a new turn, even though it isn't the fighter's
+        systems::time::advance_time(
+            &mut world,
+            fighter,
+            TimeStep::TurnBoundary {
+                entity: goblin,
+                boundary: TurnBoundary::Start,
+            },
+        );
+        assert!(usable(&world).is_ok());
+    }
+
+    #[test]
+    fn hidden_constraint() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let hidden = ActionConstraint::Hidden;
+        assert!(!hidden.is_met(&world, fighter, &attack(), &ActionContext::Other));
+
+        systems::effects::add_permanent_effects(
+            &mut world,
+            fighter,
+            vec![systems::visibility::hidden()],
+            &ModifierSource::Base,
+            None,
+        );
+        assert!(hidden.is_met(&world, fighter, &attack(), &ActionContext::Other));
+    }
+}
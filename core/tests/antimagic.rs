@@ -0,0 +1,135 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::action::ActionContext,
+            class::ClassAndSubclass,
+            effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate},
+            id::{ActionId, ClassId, EffectId, SpellId},
+            modifier::{Modifiable, ModifierSource},
+            resource::ResourceAmountMap,
+            spells::spellbook::SpellSource,
+            surface::{SURFACE_DC_DEFAULT, Surface, SurfaceKind},
+            time::TimeDuration,
+        },
+        engine::game_state::GameState,
+        systems::{self, actions::ActionUsabilityError},
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+    use uom::si::{f32::Length, length::meter};
+
+    fn shield() -> EffectId {
+        EffectId::new("nat20_core", "effect.spell.shield")
+    }
+
+    fn armor_class(game_state: &GameState, entity: Entity) -> i32 {
+        systems::loadout::armor_class(&game_state.world, entity).total()
+    }
+
+    fn create_antimagic_field(game_state: &mut GameState) {
+        systems::surfaces::create(
+            game_state,
+            Surface::new(
+                SurfaceKind::Antimagic,
+                Point3::origin(),
+                Length::new::<meter>(3.0),
+                SURFACE_DC_DEFAULT,
+                None,
+                None,
+            ),
+        );
+    }
+
+    #[test]
+    fn antimagic_field_suppresses_spells() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::origin());
+
+        let base_armor_class = armor_class(&game_state, fighter);
+        systems::effects::add_effect_template(
+            &mut game_state.world,
+            fighter,
+            fighter,
+            ModifierSource::Action(ActionId::new("nat20_core", "action.shield")),
+            &EffectInstanceTemplate {
+                effect_id: shield(),
+                lifetime: EffectLifetimeTemplate::Duration(TimeDuration::from_minutes(1)),
+            },
+            None,
+        );
+        assert_eq!(armor_class(&game_state, fighter), base_armor_class + 5);
+
+        create_antimagic_field(&mut game_state);
+        assert!(systems::antimagic::in_antimagic_field(
+            &game_state.world,
+            fighter
+        ));
+        assert!(systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &shield()
+        ));
+        assert_eq!(armor_class(&game_state, fighter), base_armor_class);
+
+        systems::surfaces::remove_at(&mut game_state, &Point3::origin(), None);
+        assert!(!systems::antimagic::in_antimagic_field(
+            &game_state.world,
+            fighter
+        ));
+        assert_eq!(armor_class(&game_state, fighter), base_armor_class + 5);
+    }
+
+    #[test]
+    fn no_spellcasting_in_antimagic_field() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+        create_antimagic_field(&mut game_state);
+
+        let result = systems::actions::action_usable(
+            &game_state.world,
+            wizard,
+            &ActionId::new("nat20_core", "action.fire_bolt"),
+            &ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.fire_bolt"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 0,
+                metamagic: Vec::new(),
+            },
+            &ResourceAmountMap::new(),
+        );
+        assert!(matches!(result, Err(ActionUsabilityError::AntimagicField)));
+    }
+
+    #[test]
+    fn dispel_ends_spells() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::effects::add_effect_template(
+            &mut game_state.world,
+            fighter,
+            fighter,
+            ModifierSource::Action(ActionId::new("nat20_core", "action.shield")),
+            &EffectInstanceTemplate {
+                effect_id: shield(),
+                lifetime: EffectLifetimeTemplate::Duration(TimeDuration::from_minutes(1)),
+            },
+            None,
+        );
+
+        let dispelled = systems::antimagic::dispel(&mut game_state.world, fighter);
+        assert_eq!(dispelled, vec![shield()]);
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &shield()
+        ));
+    }
+}
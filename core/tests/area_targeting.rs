@@ -0,0 +1,207 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            actions::{
+                action::{ActionContext, ActionResult},
+                targeting::{AreaShape, TargetInstance},
+            },
+            class::ClassAndSubclass,
+            dice::RollPolicy,
+            id::{ActionId, ClassId, SpellId},
+            resource::ResourceAmountMap,
+            spells::spellbook::SpellSource,
+        },
+        engine::{
+            event::{ActionData, EventKind},
+            game_state::GameState,
+        },
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+    use uom::si::{
+        angle::degree,
+        f32::{Angle, Length},
+        length::foot,
+    };
+
+    fn spawn_at(world: &mut World, x: f32, z: f32) -> Entity {
+        let entity = fixtures::creatures::monsters::goblin_warrior(world).id();
+        systems::geometry::teleport_to(world, entity, &Point3::new(x, 0.0, z));
+        entity
+    }
+
+    fn entities_in_area(
+        world: &World,
+        actor: Entity,
+        shape: &AreaShape,
+        target: Point3<f32>,
+    ) -> Vec<Entity> {
+        let (shape, pose) = shape.parry3d_shape(world, actor, true, &target);
+        systems::geometry::entities_in_shape(world, shape, &pose)
+    }
+
+    fn fireball_at(wizard: Entity, point: Point3<f32>) -> ActionData {
+        ActionData::new(
+            wizard,
+            ActionId::new("nat20_core", "action.fireball"),
+            ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.fireball"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 3,
+                metamagic: Vec::new(),
+            },
+            ResourceAmountMap::new(),
+            vec![TargetInstance::Point(point)],
+        )
+    }
+
+    #[test]
+    fn cone_points_towards_target() {
+        let mut world = World::new();
+        let caster = fixtures::creatures::heroes::wizard(&mut world).id();
+        let front = spawn_at(&mut world, 3.0, 0.0);
+        let behind = spawn_at(&mut world, -3.0, 0.0);
+        let side = spawn_at(&mut world, 0.0, 3.0);
+
+        let cone = AreaShape::Arc {
+            angle: Angle::new::<degree>(53.0),
+            length: Length::new::<foot>(15.0),
+        };
+
+        let affected = entities_in_area(&world, caster, &cone, Point3::new(5.0, 0.0, 0.0));
+        assert!(affected.contains(&front));
+        assert!(!affected.contains(&behind));
+        assert!(!affected.contains(&side));
+
+        let affected = entities_in_area(&world, caster, &cone, Point3::new(0.0, 0.0, 5.0));
+        assert!(!affected.contains(&front));
+        assert!(affected.contains(&side));
+    }
+
+    #[test]
+    fn line_points_towards_target() {
+        let mut world = World::new();
+        let caster = fixtures::creatures::heroes::wizard(&mut world).id();
+        let front = spawn_at(&mut world, 0.0, -6.0);
+        let behind = spawn_at(&mut world, 0.0, 6.0);
+
+        let line = AreaShape::Line {
+            length: Length::new::<foot>(100.0),
+            width: Length::new::<foot>(5.0),
+        };
+
+        let affected = entities_in_area(&world, caster, &line, Point3::new(0.0, 0.0, -10.0));
+        assert!(affected.contains(&front));
+        assert!(!affected.contains(&behind));
+    }
+
+    #[test]
+    fn fireball_rolls_damage_once_for_all_targets() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+        let goblins = [
+            spawn_at(&mut game_state.world, 8.0, 0.0),
+            spawn_at(&mut game_state.world, 8.0, 1.0),
+            spawn_at(&mut game_state.world, 9.0, 0.0),
+        ];
+        // The first goblin fails its save, the others succeed
+        for (goblin, d20) in goblins.iter().zip([1, 20, 20]) {
+            game_state
+                .world
+                .insert_one(
+                    *goblin,
+                    RollPolicy {
+                        average_damage: false,
+                        fixed_d20: Some(d20),
+                    },
+                )
+                .unwrap();
+        }
+
+        let fireball = fireball_at(wizard, Point3::new(8.0, 0.0, 0.0));
+        systems::actions::perform_action(&mut game_state, &fireball);
+
+        let damage_rolls: Vec<_> = game_state
+            .event_log
+            .events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::DamageRollResolved(_, damage_roll) => Some(damage_roll.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(damage_rolls.len(), goblins.len());
+
+        // Every goblin is hit by the same dice, halved for those who saved
+        let rolls = &damage_rolls[0].components[0].result.rolls;
+        assert!(
+            damage_rolls
+                .iter()
+                .all(|damage_roll| damage_roll.components[0].result.rolls == *rolls)
+        );
+        let full = rolls.iter().sum::<u32>() as i32;
+        let mut totals: Vec<i32> = damage_rolls.iter().map(|roll| roll.total).collect();
+        totals.sort();
+        assert_eq!(totals, vec![full / 2, full / 2, full]);
+    }
+
+    fn performed_results(game_state: &GameState) -> Vec<Vec<ActionResult>> {
+        game_state
+            .event_log
+            .events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::ActionPerformed { results, .. } => Some(results.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn area_results_reference_the_point() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+        let goblins = [
+            spawn_at(&mut game_state.world, 8.0, 0.0),
+            spawn_at(&mut game_state.world, 9.0, 1.0),
+        ];
+        let point = Point3::new(8.0, 0.0, 0.0);
+
+        systems::actions::perform_action(&mut game_state, &fireball_at(wizard, point));
+
+        let results: Vec<_> = performed_results(&game_state)
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(results.len(), goblins.len());
+        for result in &results {
+            assert!(goblins.contains(&result.target.entity().unwrap()));
+            assert_eq!(result.point, Some(point));
+        }
+    }
+
+    #[test]
+    fn area_affecting_no_one_is_still_performed() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+
+        systems::actions::perform_action(
+            &mut game_state,
+            &fireball_at(wizard, Point3::new(8.0, 0.0, 0.0)),
+        );
+
+        let performed = performed_results(&game_state);
+        assert_eq!(performed.len(), 1);
+        assert!(performed[0].is_empty());
+    }
+}
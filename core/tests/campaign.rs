@@ -0,0 +1,133 @@
+extern crate nat20_core;
+
+mod tests {
+    use nat20_core::{
+        components::{
+            ai::PlayerControlledTag,
+            campaign::QuestStatus,
+            id::{AdventureId, ItemId, Name},
+            items::inventory::{Inventory, ItemContainer},
+        },
+        engine::game_state::GameState,
+        systems::{self, campaign::CampaignError, scene::ScenePlayer},
+        test_utils::fixtures,
+    };
+    use rerecast::ConfigBuilder;
+
+    fn goblin_road() -> AdventureId {
+        AdventureId::new("nat20_core", "adventure.goblin_road")
+    }
+
+    fn status(game_state: &GameState) -> QuestStatus {
+        systems::campaign::quests(game_state)[0].1
+    }
+
+    #[test]
+    fn adventure_loaded_from_registry() {
+        let mut game_state = fixtures::engine::game_state();
+        let npcs = systems::campaign::start(&mut game_state, &goblin_road()).unwrap();
+
+        assert_eq!(npcs.len(), 1);
+        assert_eq!(
+            game_state.world.get::<&Name>(npcs[0]).unwrap().as_str(),
+            "Captain Hale"
+        );
+
+        let adventure = systems::campaign::adventure(&game_state).unwrap();
+        assert!(adventure.map("Goblin Den").is_some());
+        assert!(adventure.encounter("Goblin Den").is_some());
+        assert!(adventure.loot.contains_key("goblin_stash"));
+    }
+
+    #[test]
+    fn adventure_unknown() {
+        let mut game_state = fixtures::engine::game_state();
+        let adventure = AdventureId::new("nat20_core", "adventure.does_not_exist");
+        assert_eq!(
+            systems::campaign::start(&mut game_state, &adventure),
+            Err(CampaignError::UnknownAdventure(adventure))
+        );
+        assert!(game_state.campaign.is_none());
+    }
+
+    #[test]
+    fn quest_completed_by_flags() {
+        let mut game_state = fixtures::engine::game_state();
+        systems::campaign::start(&mut game_state, &goblin_road()).unwrap();
+        assert_eq!(status(&game_state), QuestStatus::Locked);
+
+        // Talking to the captain sets the flag which unlocks the quest
+        let adventure = systems::campaign::adventure(&game_state).unwrap();
+        let mut player =
+            ScenePlayer::new(adventure.scene("Captain Hale").unwrap().clone()).unwrap();
+        player.update(&mut game_state, 0.0);
+        player.advance();
+        player.update(&mut game_state, 0.0);
+        assert!(player.is_finished());
+        assert!(systems::campaign::has_flag(&game_state, "met_captain"));
+        assert_eq!(status(&game_state), QuestStatus::Active);
+
+        assert!(systems::campaign::set_flag(&mut game_state, "grukk_defeated").is_empty());
+        let completed = systems::campaign::set_flag(&mut game_state, "reported_to_captain");
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].id, "clear_the_road");
+        assert_eq!(status(&game_state), QuestStatus::Completed);
+        assert_eq!(systems::campaign::quest_rewards(completed[0]).len(), 1);
+
+        // Setting the same flag again doesn't complete the quest twice
+        assert!(systems::campaign::set_flag(&mut game_state, "reported_to_captain").is_empty());
+    }
+
+    #[test]
+    fn quest_map_loaded_when_started() {
+        let mut game_state = fixtures::engine::game_state();
+        systems::campaign::start(&mut game_state, &goblin_road()).unwrap();
+        let navmesh_config = ConfigBuilder::default().build();
+        // The quest is still locked, so there's no map to load yet
+        assert!(systems::campaign::load_pending_map(&mut game_state, &navmesh_config).is_none());
+
+        let vertices = game_state.geometry.trimesh.vertices().len();
+        systems::campaign::set_flag(&mut game_state, "met_captain");
+        assert_eq!(status(&game_state), QuestStatus::Active);
+        let (map_config, _) = systems::campaign::load_pending_map(&mut game_state, &navmesh_config)
+            .unwrap()
+            .unwrap();
+
+        let adventure = systems::campaign::adventure(&game_state).unwrap();
+        assert_eq!(map_config, adventure.map("Goblin Den").unwrap().config);
+        assert_ne!(game_state.geometry.trimesh.vertices().len(), vertices);
+        // The map is only loaded once
+        assert!(systems::campaign::load_pending_map(&mut game_state, &navmesh_config).is_none());
+    }
+
+    #[test]
+    fn quest_rewards_granted_to_party() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        game_state
+            .world
+            .insert_one(fighter, PlayerControlledTag)
+            .unwrap();
+        systems::campaign::start(&mut game_state, &goblin_road()).unwrap();
+
+        let dagger = ItemId::new("nat20_core", "item.dagger");
+        let daggers = |game_state: &GameState| {
+            systems::helpers::get_component::<Inventory>(&game_state.world, fighter)
+                .items()
+                .iter()
+                .filter(|item| item.item().id == dagger)
+                .count()
+        };
+        let before = daggers(&game_state);
+
+        for flag in ["met_captain", "grukk_defeated", "reported_to_captain"] {
+            systems::campaign::set_flag(&mut game_state, flag);
+        }
+        assert_eq!(status(&game_state), QuestStatus::Completed);
+        assert_eq!(daggers(&game_state), before + 1);
+
+        // Setting the flag again doesn't hand out the rewards twice
+        systems::campaign::set_flag(&mut game_state, "reported_to_captain");
+        assert_eq!(daggers(&game_state), before + 1);
+    }
+}
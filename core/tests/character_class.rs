@@ -8,19 +8,29 @@ mod tests {
     use nat20_core::{
         components::{
             ability::Ability,
+            class::ClassAndSubclass,
+            health::hit_points::HitPoints,
             id::{
-                BackgroundId, ClassId, EffectId, FeatId, ItemId, SpeciesId, SubclassId,
-                SubspeciesId,
+                BackgroundId, ClassId, EffectId, FeatId, ItemId, Name, SpeciesId, SpellId,
+                SubclassId, SubspeciesId,
+            },
+            items::{
+                equipment::{slots::EquipmentSlot, weapon::WeaponCategory},
+                inventory::ItemInstance,
             },
             level::CharacterLevels,
-            level_up::ChoiceItem,
+            level_up::{ChoiceItem, ChoiceSpec, LevelUpPrompt},
             proficiency::ProficiencyLevel,
             saving_throw::{SavingThrowKind, SavingThrowSet},
             skill::{Skill, SkillSet},
         },
         entities::character::Character,
-        registry::registry::ClassesRegistry,
-        systems::{self, level_up::LevelUpDecision},
+        registry::registry::{ClassesRegistry, ItemsRegistry},
+        systems::{
+            self,
+            level_up::{LevelUpDecision, LevelUpError, LevelUpSession},
+        },
+        test_utils::fixtures,
     };
 
     #[test]
@@ -78,6 +88,7 @@ mod tests {
                             (8, ItemId::new("nat20_core", "item.javelin")),
                         ],
                         money: "4 GP".to_string(),
+                        any_weapon: Vec::new(),
                     },
                 ),
                 LevelUpDecision::single_choice_with_id(
@@ -85,6 +96,7 @@ mod tests {
                     ChoiceItem::Equipment {
                         items: Vec::new(),
                         money: "50 GP".to_string(),
+                        any_weapon: Vec::new(),
                     },
                 ),
                 // Level 2
@@ -163,4 +175,248 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn character_level_up_preview() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let character = Character::from_world(&world, fighter);
+        let max_hit_points = systems::helpers::get_component::<HitPoints>(&world, fighter).max();
+
+        let diff = systems::level_up::preview_level_up(
+            &character,
+            &[LevelUpDecision::single_choice(ChoiceItem::Class(
+                ClassId::new("nat20_core", "class.fighter"),
+            ))],
+        )
+        .unwrap();
+
+        assert_eq!(diff.before.max_hit_points, max_hit_points);
+        assert!(diff.after.max_hit_points > diff.before.max_hit_points);
+        assert_eq!(diff.before.saving_throws.len(), 6);
+        assert!(diff.lost_actions().next().is_none());
+
+        // The preview runs against a clone, so the character itself is untouched
+        assert_eq!(
+            systems::helpers::get_component::<HitPoints>(&world, fighter).max(),
+            max_hit_points
+        );
+        assert_eq!(
+            systems::helpers::get_component::<CharacterLevels>(&world, fighter).total_level(),
+            9
+        );
+    }
+
+    #[test]
+    fn character_multiclass_prerequisites() {
+        let mut world = World::new();
+        // Intelligence 8 and Charisma 12, so neither Wizard nor Warlock is allowed
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let fighter_class = ClassId::new("nat20_core", "class.fighter");
+        let wizard_class = ClassId::new("nat20_core", "class.wizard");
+
+        assert!(systems::class::can_multiclass(&world, fighter, &fighter_class).is_ok());
+        assert!(systems::class::can_multiclass(&world, fighter, &wizard_class).is_err());
+
+        let mut session = LevelUpSession::new(&world, fighter);
+        let LevelUpPrompt::Choice(spec) = &session.pending_prompts()[0] else {
+            panic!("Expected the class prompt");
+        };
+        assert!(
+            spec.disabled_reason(&ChoiceItem::Class(fighter_class.clone()))
+                .is_none()
+        );
+        assert!(
+            spec.disabled_reason(&ChoiceItem::Class(wizard_class.clone()))
+                .is_some()
+        );
+
+        let result = session.advance(
+            &mut world,
+            &LevelUpDecision::single_choice(ChoiceItem::Class(wizard_class)),
+        );
+        assert!(matches!(result, Err(LevelUpError::InvalidDecision { .. })));
+        assert_eq!(
+            systems::helpers::get_component::<CharacterLevels>(&world, fighter).total_level(),
+            9
+        );
+
+        session
+            .advance(
+                &mut world,
+                &LevelUpDecision::single_choice(ChoiceItem::Class(fighter_class)),
+            )
+            .unwrap();
+        assert_eq!(
+            systems::helpers::get_component::<CharacterLevels>(&world, fighter).total_level(),
+            10
+        );
+    }
+
+    #[test]
+    fn character_quick_build() {
+        for class_id in ClassesRegistry::keys() {
+            let character = systems::quick_build::quick_build(
+                Name::new("Quick Builder"),
+                Some(class_id.clone()),
+                None,
+                5,
+            )
+            .unwrap();
+
+            assert_eq!(character.levels.total_level(), 5);
+            assert_eq!(character.levels.class_level(class_id).unwrap().level(), 5);
+            assert!(character.hit_points.max() > 0);
+        }
+
+        let character =
+            systems::quick_build::quick_build(Name::new("Random Builder"), None, None, 1).unwrap();
+        assert_eq!(character.levels.total_level(), 1);
+    }
+
+    #[test]
+    fn character_quick_build_picks_recommended_spells() {
+        let wizard = ClassId::new("nat20_core", "class.wizard");
+        let character = systems::quick_build::quick_build(
+            Name::new("Quick Wizard"),
+            Some(wizard.clone()),
+            None,
+            1,
+        )
+        .unwrap();
+
+        let known_spells = character
+            .spellbook
+            .known_spells_for_class(
+                &ClassAndSubclass {
+                    class: wizard,
+                    subclass: None,
+                },
+                &character.resources,
+            )
+            .unwrap();
+        for cantrip in ["spell.fire_bolt", "spell.ray_of_frost", "spell.minor_image"] {
+            assert!(known_spells.contains(&SpellId::new("nat20_core", cantrip)));
+        }
+    }
+
+    #[test]
+    fn character_starting_equipment_granted_on_completion() {
+        let mut world = World::new();
+        let character = world.spawn(Character::default());
+        let fighter = ClassId::new("nat20_core", "class.fighter");
+
+        let decisions = vec![
+            LevelUpDecision::single_choice(ChoiceItem::Species(SpeciesId::new(
+                "nat20_core",
+                "species.dragonborn",
+            ))),
+            LevelUpDecision::single_choice(ChoiceItem::Subspecies(SubspeciesId::new(
+                "nat20_core",
+                "subspecies.dragonborn.white",
+            ))),
+            LevelUpDecision::single_choice(ChoiceItem::Background(BackgroundId::new(
+                "nat20_core",
+                "background.soldier",
+            ))),
+            LevelUpDecision::single_choice(ChoiceItem::Class(fighter.clone())),
+            LevelUpDecision::AbilityScores(
+                ClassesRegistry::get(&fighter)
+                    .unwrap()
+                    .default_abilities
+                    .clone(),
+            ),
+            LevelUpDecision::single_choice_with_id(
+                "choice.fighting_style",
+                ChoiceItem::Feat(FeatId::new(
+                    "nat20_core",
+                    "feat.fighting_style.great_weapon_fighting",
+                )),
+            ),
+            LevelUpDecision::SkillProficiency(HashSet::from([
+                Skill::Acrobatics,
+                Skill::Perception,
+            ])),
+            LevelUpDecision::single_choice_with_id(
+                "choice.starting_equipment.fighter",
+                ChoiceItem::Equipment {
+                    items: vec![
+                        (1, ItemId::new("nat20_core", "item.chainmail")),
+                        (1, ItemId::new("nat20_core", "item.greatsword")),
+                    ],
+                    money: "4 GP".to_string(),
+                    any_weapon: Vec::new(),
+                },
+            ),
+            LevelUpDecision::single_choice_with_id(
+                "choice.starting_equipment.soldier",
+                ChoiceItem::Equipment {
+                    items: Vec::new(),
+                    money: "50 GP".to_string(),
+                    any_weapon: Vec::new(),
+                },
+            ),
+        ];
+
+        let mut session = LevelUpSession::new(&world, character);
+        for decision in &decisions {
+            assert!(
+                systems::loadout::loadout(&world, character)
+                    .item_in_slot(&EquipmentSlot::Armor)
+                    .is_none()
+            );
+            session.advance(&mut world, decision).unwrap();
+        }
+
+        assert!(session.is_complete());
+        assert!(
+            systems::loadout::loadout(&world, character)
+                .item_in_slot(&EquipmentSlot::Armor)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn character_starting_equipment_any_weapon() {
+        let LevelUpPrompt::Choice(spec) =
+            LevelUpPrompt::any_weapon("choice.any_martial_weapon", &WeaponCategory::Martial)
+        else {
+            panic!("Expected a choice prompt");
+        };
+
+        assert_eq!(spec.id, "choice.any_martial_weapon");
+        assert!(!spec.options.is_empty());
+        for option in &spec.options {
+            let ChoiceItem::Equipment { items, .. } = option else {
+                panic!("Expected equipment, got {:?}", option);
+            };
+            let Some(ItemInstance::Weapon(weapon)) = ItemsRegistry::get(&items[0].1) else {
+                panic!("Expected a weapon, got {:?}", items[0].1);
+            };
+            assert_eq!(weapon.category(), &WeaponCategory::Martial);
+        }
+    }
+
+    #[test]
+    fn character_choice_known_options_disabled() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let great_weapon_fighting = ChoiceItem::Feat(FeatId::new(
+            "nat20_core",
+            "feat.fighting_style.great_weapon_fighting",
+        ));
+        let archery = ChoiceItem::Feat(FeatId::new("nat20_core", "feat.fighting_style.archery"));
+
+        let mut spec = ChoiceSpec::single(
+            "Fighting Style",
+            vec![great_weapon_fighting.clone(), archery.clone()],
+        );
+        spec.disable_known_options(&world, fighter);
+
+        assert_eq!(
+            spec.disabled_reason(&great_weapon_fighting),
+            Some("Already taken")
+        );
+        assert_eq!(spec.disabled_reason(&archery), None);
+    }
 }
@@ -6,11 +6,12 @@ mod tests {
     use nat20_core::{
         components::{
             ability::{Ability, AbilityScore, AbilityScoreMap},
-            d20::RollMode,
-            id::ItemId,
-            modifier::{KeyedModifiable, Modifiable, ModifierSource},
+            d20::{D20CheckOverride, D20CheckResult, RollMode},
+            health::life_state::LifeState,
+            id::{EffectId, ItemId},
+            modifier::{KeyedModifiable, Modifiable, ModifierSet, ModifierSource},
             proficiency::{Proficiency, ProficiencyLevel},
-            saving_throw::{SavingThrowKind, SavingThrowSet},
+            saving_throw::{SavingThrowDC, SavingThrowKind, SavingThrowSet},
             skill::{Skill, SkillSet},
         },
         entities::character::Character,
@@ -113,4 +114,59 @@ mod tests {
         );
         assert!(result.advantage_tracker.roll_mode() == RollMode::Disadvantage);
     }
+
+    fn saving_throw(world: &World, entity: hecs::Entity, ability: Ability) -> D20CheckResult {
+        let dc = SavingThrowDC {
+            key: SavingThrowKind::Ability(ability),
+            dc: ModifierSet::from(ModifierSource::Base, 1),
+        };
+        systems::helpers::get_component::<SavingThrowSet>(world, entity)
+            .check_dc(&dc, world, entity)
+    }
+
+    #[test]
+    fn character_unconscious_auto_fails_saving_throws() {
+        let mut world = World::new();
+        let entity = world.spawn(Character::default());
+        systems::helpers::set_component(&mut world, entity, LifeState::unconscious());
+
+        for ability in [Ability::Strength, Ability::Dexterity] {
+            let result = saving_throw(&world, entity, ability);
+            assert!(!result.success);
+            assert_eq!(
+                result.forced_outcome.unwrap().kind,
+                D20CheckOverride::AutoFailure
+            );
+        }
+
+        // Other saving throws are rolled as usual
+        let result = saving_throw(&world, entity, Ability::Constitution);
+        assert!(result.forced_outcome.is_none());
+    }
+
+    #[test]
+    fn character_paralyzed_auto_fails_saving_throws() {
+        let mut world = World::new();
+        let entity = world.spawn(Character::default());
+
+        let paralyzed = EffectId::new("nat20_core", "effect.condition.paralyzed");
+        systems::effects::add_permanent_effect(
+            &mut world,
+            entity,
+            paralyzed.clone(),
+            &ModifierSource::Custom("Test".to_string()),
+            None,
+        );
+
+        let result = saving_throw(&world, entity, Ability::Dexterity);
+        assert!(!result.success);
+        assert_eq!(
+            result.forced_outcome.unwrap().source,
+            ModifierSource::Effect(paralyzed.clone())
+        );
+
+        systems::effects::remove_effect(&mut world, entity, &paralyzed);
+        let result = saving_throw(&world, entity, Ability::Dexterity);
+        assert!(result.forced_outcome.is_none());
+    }
 }
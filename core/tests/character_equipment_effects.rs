@@ -4,10 +4,10 @@ mod tests {
 
     use std::str::FromStr;
 
-    use hecs::World;
+    use hecs::{Entity, World};
     use nat20_core::{
         components::{
-            ability::Ability,
+            ability::{Ability, AbilityScoreMap},
             d20::RollMode,
             id::{EffectId, ItemId},
             items::{
@@ -19,10 +19,11 @@ mod tests {
                 item::{Item, ItemRarity},
                 money::MonetaryValue,
             },
+            proficiency::ProficiencyLevel,
             saving_throw::{SavingThrowKind, SavingThrowSet},
             skill::{Skill, SkillSet},
         },
-        entities::character::Character,
+        entities::{character::Character, object::ObjectMaterial},
         registry::registry::ItemsRegistry,
         systems,
         test_utils::fixtures,
@@ -42,6 +43,8 @@ mod tests {
                 weight: Mass::new::<pound>(0.1),
                 value: MonetaryValue::from_str("1000 GP").unwrap(),
                 rarity: ItemRarity::Rare,
+                material: ObjectMaterial::Iron,
+                damage_taken: 0,
             },
             kind: EquipmentKind::Ring,
             effects: vec![EffectId::new("nat20_core", "effect.item.ring_of_attacking")],
@@ -112,6 +115,8 @@ mod tests {
                 weight: Mass::new::<pound>(0.5),
                 value: MonetaryValue::from_str("500 GP").unwrap(),
                 rarity: ItemRarity::Rare,
+                material: ObjectMaterial::Cloth,
+                damage_taken: 0,
             },
             12,
             vec![EffectId::new("nat20_core", "effect.item.armor_of_sneaking")],
@@ -150,6 +155,8 @@ mod tests {
                 weight: Mass::new::<pound>(10.0),
                 value: MonetaryValue::from_str("1500 GP").unwrap(),
                 rarity: ItemRarity::VeryRare,
+                material: ObjectMaterial::Steel,
+                damage_taken: 0,
             },
             18,
             vec![EffectId::new(
@@ -176,4 +183,109 @@ mod tests {
         );
         assert_eq!(throw.advantage_tracker.roll_mode(), RollMode::Normal);
     }
+
+    fn cloak_of_resilience() -> EquipmentItem {
+        EquipmentItem {
+            item: Item {
+                id: ItemId::new("nat20_core", "item.cloak_of_resilience"),
+                name: "Cloak of Resilience".to_string(),
+                description: "A magical cloak that hardens its wearer.".to_string(),
+                weight: Mass::new::<pound>(1.0),
+                value: MonetaryValue::from_str("500 GP").unwrap(),
+                rarity: ItemRarity::Uncommon,
+                material: ObjectMaterial::Cloth,
+                damage_taken: 0,
+            },
+            kind: EquipmentKind::Cloak,
+            effects: vec![EffectId::new(
+                "nat20_core",
+                "effect.item.cloak_of_resilience",
+            )],
+        }
+    }
+
+    fn constitution_save_proficiency(world: &World, entity: Entity) -> ProficiencyLevel {
+        *systems::helpers::get_component::<SavingThrowSet>(world, entity)
+            .get(&SavingThrowKind::Ability(Ability::Constitution))
+            .proficiency()
+            .level()
+    }
+
+    #[test]
+    fn character_saving_throw_proficiency_effect() {
+        let mut world = World::new();
+        let entity = world.spawn(Character::default());
+        assert_eq!(
+            constitution_save_proficiency(&world, entity),
+            ProficiencyLevel::None
+        );
+
+        let _ = systems::loadout::equip(&mut world, entity, cloak_of_resilience());
+        assert_eq!(
+            constitution_save_proficiency(&world, entity),
+            ProficiencyLevel::Proficient
+        );
+
+        systems::loadout::unequip(&mut world, entity, &EquipmentSlot::Cloak)
+            .expect("Failed to unequip cloak");
+        assert_eq!(
+            constitution_save_proficiency(&world, entity),
+            ProficiencyLevel::None
+        );
+    }
+
+    #[test]
+    fn removing_proficiency_source_keeps_other_sources() {
+        let mut world = World::new();
+        // Fighters are proficient in Constitution saving throws
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let saving_throw_sources = |world: &World| {
+            systems::helpers::get_component::<SavingThrowSet>(world, fighter)
+                .get(&SavingThrowKind::Ability(Ability::Constitution))
+                .proficiency()
+                .sources()
+                .filter(|(_, level)| **level != ProficiencyLevel::None)
+                .count()
+        };
+        assert_eq!(saving_throw_sources(&world), 1);
+
+        let _ = systems::loadout::equip(&mut world, fighter, cloak_of_resilience());
+        assert_eq!(saving_throw_sources(&world), 2);
+
+        systems::loadout::unequip(&mut world, fighter, &EquipmentSlot::Cloak)
+            .expect("Failed to unequip cloak");
+        assert_eq!(saving_throw_sources(&world), 1);
+        assert_eq!(
+            constitution_save_proficiency(&world, fighter),
+            ProficiencyLevel::Proficient
+        );
+    }
+
+    #[test]
+    fn character_ability_score_set_by_item() {
+        let mut world = World::new();
+        let entity = world.spawn(Character::default());
+        let intelligence = |world: &World| {
+            systems::helpers::get_component::<AbilityScoreMap>(world, entity)
+                .get(&Ability::Intelligence)
+                .total()
+        };
+        assert_eq!(intelligence(&world), 10);
+
+        let headband = ItemsRegistry::get(&ItemId::new("nat20_core", "item.headband_of_intellect"))
+            .unwrap()
+            .clone();
+        let _ = systems::loadout::equip(&mut world, entity, headband);
+        assert_eq!(intelligence(&world), 19);
+        assert_eq!(
+            systems::helpers::get_component::<AbilityScoreMap>(&world, entity)
+                .ability_modifier(&Ability::Intelligence)
+                .total(),
+            4
+        );
+
+        systems::loadout::unequip(&mut world, entity, &EquipmentSlot::Headwear)
+            .expect("Failed to unequip headband");
+        assert_eq!(intelligence(&world), 10);
+    }
 }
@@ -0,0 +1,485 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::World;
+    use nat20_core::{
+        components::{
+            ability::{Ability, AbilityScoreMap},
+            actions::targeting::TargetInstance,
+            damage::DamageType,
+            effects::effect::EffectTag,
+            health::{
+                hit_points::HitPoints,
+                life_state::LifeState,
+                resurrection::{
+                    Corpse, RETURN_FROM_DEATH_EFFECTS, Resurrection, ResurrectionError,
+                    RevivedHitPoints,
+                },
+            },
+            id::{ActionId, EffectId, FeatId, IdProvider, ItemId, MonsterId},
+            items::{equipment::slots::EquipmentSlot, inventory::Inventory},
+            modifier::ModifierSource,
+            shapechange::Shapechanged,
+            species::CreatureType,
+            time::TimeDuration,
+        },
+        engine::{
+            event::{ActionData, ActionDecision, ActionDecisionKind},
+            rules::OptionalRule,
+        },
+        entities::character::Character,
+        registry::registry::ItemsRegistry,
+        systems::{self, helpers, time::RestKind},
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn wounded_character(world: &mut World) -> hecs::Entity {
+        let entity = world.spawn(Character::default());
+        helpers::set_component(world, entity, HitPoints::with_current(5, 20));
+        entity
+    }
+
+    #[test]
+    fn character_healing_blocked_by_effect() {
+        let mut world = World::new();
+        let entity = wounded_character(&mut world);
+
+        let chill_touch = EffectId::new("nat20_core", "effect.spell.chill_touch");
+        let source = ModifierSource::Custom("Test".to_string());
+        systems::effects::add_permanent_effect(
+            &mut world,
+            entity,
+            chill_touch.clone(),
+            &source,
+            None,
+        );
+
+        let (healing, _) = systems::health::receive_healing(&mut world, entity, 10);
+        assert_eq!(healing.original, 10);
+        assert_eq!(healing.amount, 0);
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&world, entity).current(),
+            5
+        );
+
+        systems::effects::remove_effect(&mut world, entity, &chill_touch);
+
+        let (healing, _) = systems::health::receive_healing(&mut world, entity, 10);
+        assert_eq!(healing.amount, 10);
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&world, entity).current(),
+            15
+        );
+    }
+
+    #[test]
+    fn character_max_hit_points_reduction() {
+        let mut world = World::new();
+        let entity = wounded_character(&mut world);
+
+        assert_eq!(
+            systems::health::reduce_max_hit_points(&mut world, entity, 4),
+            None
+        );
+        systems::health::heal_full(&mut world, entity);
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&world, entity).current(),
+            16
+        );
+
+        systems::health::restore_max_hit_points(&mut world, entity);
+        systems::health::heal_full(&mut world, entity);
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&world, entity).current(),
+            20
+        );
+
+        // Reducing the maximum to 0 kills the creature outright
+        assert_eq!(
+            systems::health::reduce_max_hit_points(&mut world, entity, 20),
+            Some(LifeState::Dead)
+        );
+    }
+
+    #[test]
+    fn character_max_hit_points_reduced_by_effect() {
+        let mut world = World::new();
+        let entity = wounded_character(&mut world);
+
+        systems::effects::add_permanent_effect(
+            &mut world,
+            entity,
+            EffectId::new("nat20_core", "effect.monster.life_drain"),
+            &ModifierSource::Custom("Test".to_string()),
+            None,
+        );
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&world, entity).max(),
+            15
+        );
+
+        // Same as the payload of Greater Restoration
+        systems::effects::remove_effects_with_tags(
+            &mut world,
+            entity,
+            &[EffectTag::MaxHitPointReduction],
+        );
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&world, entity).max(),
+            20
+        );
+    }
+
+    #[test]
+    fn character_max_hit_points_reduction_ends_on_long_rest() {
+        let mut world = World::new();
+        let entity = wounded_character(&mut world);
+        let life_drain = EffectId::new("nat20_core", "effect.monster.life_drain");
+
+        systems::effects::add_permanent_effect(
+            &mut world,
+            entity,
+            life_drain.clone(),
+            &ModifierSource::Custom("Test".to_string()),
+            None,
+        );
+        systems::time::on_rest_end(&mut world, &[entity], &RestKind::Short);
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&world, entity).max(),
+            15
+        );
+
+        systems::time::on_rest_end(&mut world, &[entity], &RestKind::Long);
+        assert!(!systems::effects::has_effect(&world, entity, &life_drain));
+        let hit_points = helpers::get_component::<HitPoints>(&world, entity);
+        assert_eq!(hit_points.max(), 20);
+        assert_eq!(hit_points.current(), 20);
+    }
+
+    #[test]
+    fn character_wild_shape_reverts_at_zero_hit_points() {
+        let mut game_state = fixtures::engine::game_state();
+        let entity = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let hit_points_before =
+            helpers::get_component::<HitPoints>(&game_state.world, entity).current();
+
+        let wild_shape = EffectId::new("nat20_core", "effect.druid.wild_shape");
+        systems::effects::add_permanent_effect(
+            &mut game_state.world,
+            entity,
+            wild_shape.clone(),
+            &ModifierSource::Custom("Test".to_string()),
+            None,
+        );
+
+        assert_eq!(
+            helpers::get_component::<Shapechanged>(&game_state.world, entity).form,
+            MonsterId::new("nat20_core", "monster.wolf")
+        );
+        assert_eq!(
+            *helpers::get_component::<CreatureType>(&game_state.world, entity),
+            CreatureType::Beast
+        );
+        {
+            let abilities = helpers::get_component::<AbilityScoreMap>(&game_state.world, entity);
+            assert_eq!(abilities.get(&Ability::Dexterity).base, 15);
+            // Mental abilities are kept
+            assert_eq!(abilities.get(&Ability::Intelligence).base, 8);
+        }
+        let beast_hit_points =
+            helpers::get_component::<HitPoints>(&game_state.world, entity).current();
+        assert!((4..=18).contains(&beast_hit_points));
+
+        // Dropping to 0 hit points reverts the form, and the excess damage
+        // carries over
+        let (_, life_state) = systems::gm::damage(
+            &mut game_state,
+            entity,
+            beast_hit_points + 5,
+            DamageType::Bludgeoning,
+        );
+        assert_eq!(life_state, None);
+        assert!(!systems::shapechange::is_shapechanged(
+            &game_state.world,
+            entity
+        ));
+        assert!(
+            !systems::effects::effects(&game_state.world, entity)
+                .iter()
+                .any(|effect| effect.effect_id == wild_shape)
+        );
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&game_state.world, entity).current(),
+            hit_points_before - 5
+        );
+        assert_eq!(
+            *helpers::get_component::<CreatureType>(&game_state.world, entity),
+            CreatureType::Humanoid
+        );
+    }
+
+    #[test]
+    fn non_lethal_melee_attack_knocks_out() {
+        for (non_lethal, expected) in [(true, LifeState::Stable), (false, LifeState::Dead)] {
+            let mut game_state = fixtures::engine::game_state();
+            let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+            let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+            helpers::set_component(&mut game_state.world, goblin, HitPoints::new(1));
+            systems::health::set_non_lethal(&mut game_state.world, fighter, non_lethal);
+
+            let mut damage_roll = systems::damage::damage_roll_weapon(
+                &game_state.world,
+                fighter,
+                &EquipmentSlot::MeleeMainHand,
+                false,
+            );
+            damage_roll.action =
+                Some((fighter, ActionId::new("nat20_core", "action.weapon_attack")));
+
+            let (_, life_state) =
+                systems::health::damage(&mut game_state, goblin, &damage_roll, None);
+            assert_eq!(life_state, Some(expected));
+        }
+    }
+
+    #[test]
+    fn character_massive_damage_instant_death() {
+        for (amount, expected) in [(24, LifeState::unconscious()), (25, LifeState::Defeated)] {
+            let mut game_state = fixtures::engine::game_state();
+            let entity = wounded_character(&mut game_state.world);
+
+            // The damage left over after dropping to 0 hit points has to be at
+            // least the hit point maximum
+            let (_, life_state) =
+                systems::gm::damage(&mut game_state, entity, amount, DamageType::Bludgeoning);
+            assert_eq!(life_state, Some(expected));
+        }
+    }
+
+    #[test]
+    fn character_healed_at_zero_hit_points_regains_consciousness() {
+        let mut game_state = fixtures::engine::game_state();
+        let entity = wounded_character(&mut game_state.world);
+
+        let (_, life_state) =
+            systems::gm::damage(&mut game_state, entity, 5, DamageType::Bludgeoning);
+        assert_eq!(life_state, Some(LifeState::unconscious()));
+        // Taking damage while dying counts as a failed death saving throw
+        systems::gm::damage(&mut game_state, entity, 1, DamageType::Bludgeoning);
+        assert!(matches!(
+            *helpers::get_component::<LifeState>(&game_state.world, entity),
+            LifeState::Unconscious(death_saving_throws) if death_saving_throws.failures() == 1
+        ));
+
+        assert_eq!(
+            systems::health::heal(&mut game_state.world, entity, 3),
+            Some(LifeState::Normal)
+        );
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&game_state.world, entity).current(),
+            3
+        );
+
+        // Dropping to 0 hit points again starts the death saving throws over
+        let (_, life_state) =
+            systems::gm::damage(&mut game_state, entity, 3, DamageType::Bludgeoning);
+        assert_eq!(life_state, Some(LifeState::unconscious()));
+
+        // Spare the Dying stabilizes without restoring any hit points
+        assert_eq!(
+            systems::health::stabilize(&mut game_state.world, entity),
+            Some(LifeState::Stable)
+        );
+        assert_eq!(
+            systems::health::stabilize(&mut game_state.world, entity),
+            None
+        );
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&game_state.world, entity).current(),
+            0
+        );
+
+        // The dead don't get back up from healing alone
+        systems::health::set_life_state(&mut game_state.world, entity, LifeState::Dead);
+        assert_eq!(
+            systems::health::heal(&mut game_state.world, entity, 3),
+            None
+        );
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&game_state.world, entity).current(),
+            0
+        );
+    }
+
+    #[test]
+    fn healers_kit_stabilizes_dying_creature() {
+        let healers_kit = ItemId::new("nat20_core", "item.healers_kit");
+        let stabilize = ActionId::new("nat20_core", "action.healers_kit.stabilize");
+
+        // With the Healer feat the creature also regains a hit point
+        for (healer, expected) in [(false, LifeState::Stable), (true, LifeState::Normal)] {
+            let mut game_state = fixtures::engine::game_state();
+            let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+            let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+            systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::origin());
+            systems::geometry::teleport_to(
+                &mut game_state.world,
+                wizard,
+                &Point3::new(1.0, 0.0, 0.0),
+            );
+            if healer {
+                systems::feats::feats_mut(&mut game_state.world, wizard)
+                    .push(FeatId::new("nat20_core", "feat.healer"));
+            }
+
+            let hit_points =
+                helpers::get_component::<HitPoints>(&game_state.world, fighter).current();
+            let (_, life_state) = systems::gm::damage(
+                &mut game_state,
+                fighter,
+                hit_points,
+                DamageType::Bludgeoning,
+            );
+            assert_eq!(life_state, Some(LifeState::unconscious()));
+
+            // The action is only available while carrying a Healer's Kit
+            assert!(
+                !systems::actions::available_actions(&game_state.world, wizard)
+                    .contains_key(&stabilize)
+            );
+            systems::inventory::add_item(
+                &mut game_state.world,
+                wizard,
+                ItemsRegistry::get(&healers_kit).unwrap().clone(),
+            );
+            let available_actions = systems::actions::available_actions(&game_state.world, wizard);
+            let (context, cost) = available_actions.get(&stabilize).unwrap()[0].clone();
+
+            let _ = game_state.submit_decision(ActionDecision::without_response_to(
+                ActionDecisionKind::Action {
+                    action: ActionData::new(
+                        wizard,
+                        stabilize.clone(),
+                        context,
+                        cost,
+                        vec![TargetInstance::Entity(fighter)],
+                    ),
+                },
+            ));
+
+            assert_eq!(
+                *helpers::get_component::<LifeState>(&game_state.world, fighter),
+                expected
+            );
+            let inventory = helpers::get_component::<Inventory>(&game_state.world, wizard);
+            let kit = inventory
+                .items()
+                .iter()
+                .find(|item| *item.id() == healers_kit)
+                .unwrap();
+            assert_eq!(kit.charges().unwrap().current, 9);
+        }
+    }
+
+    #[test]
+    fn character_resurrection() {
+        let mut game_state = fixtures::engine::game_state();
+        let entity = wounded_character(&mut game_state.world);
+        let revivify = Resurrection {
+            time_limit: TimeDuration::from_minutes(1),
+            hit_points: RevivedHitPoints::One,
+            penalty: false,
+        };
+        let raise_dead = Resurrection {
+            time_limit: TimeDuration::from_days(10),
+            hit_points: RevivedHitPoints::One,
+            penalty: true,
+        };
+
+        assert_eq!(
+            systems::health::resurrect(&mut game_state, entity, &revivify),
+            Err(ResurrectionError::NotDead)
+        );
+
+        let (_, life_state) =
+            systems::gm::damage(&mut game_state, entity, 25, DamageType::Bludgeoning);
+        assert_eq!(life_state, Some(LifeState::Defeated));
+
+        // Revivify only works within a minute of death
+        systems::time::advance_world_clock(&mut game_state, &TimeDuration::from_minutes(2));
+        assert!(matches!(
+            systems::health::resurrect(&mut game_state, entity, &revivify),
+            Err(ResurrectionError::TimeLimitExceeded { .. })
+        ));
+
+        assert_eq!(
+            systems::health::resurrect(&mut game_state, entity, &raise_dead),
+            Ok(LifeState::Normal)
+        );
+        assert_eq!(
+            helpers::get_component::<HitPoints>(&game_state.world, entity).current(),
+            1
+        );
+
+        // The penalty wears off one long rest at a time
+        let penalty = |index: usize| EffectId::new("nat20_core", RETURN_FROM_DEATH_EFFECTS[index]);
+        for index in (0..RETURN_FROM_DEATH_EFFECTS.len()).rev() {
+            assert!(systems::effects::has_effect(
+                &game_state.world,
+                entity,
+                &penalty(index)
+            ));
+            systems::time::on_rest_end(&mut game_state.world, &[entity], &RestKind::Long);
+            assert!(!systems::effects::has_effect(
+                &game_state.world,
+                entity,
+                &penalty(index)
+            ));
+        }
+        assert!(systems::effects::effects(&game_state.world, entity).is_empty());
+    }
+
+    #[test]
+    fn permadeath_turns_the_dead_into_corpses() {
+        let mut game_state = fixtures::engine::game_state();
+        game_state.set_rule(OptionalRule::Permadeath, true);
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        assert!(systems::ai::is_player_controlled(
+            &game_state.world,
+            fighter
+        ));
+
+        let max_hit_points = helpers::get_component::<HitPoints>(&game_state.world, fighter).max();
+        systems::gm::damage(
+            &mut game_state,
+            fighter,
+            max_hit_points * 2,
+            DamageType::Bludgeoning,
+        );
+        systems::time::advance_world_clock(&mut game_state, &TimeDuration::from_rounds(1));
+
+        // The fighter leaves the party, and can't be brought back
+        assert!(game_state.world.get::<&Corpse>(fighter).is_ok());
+        assert!(!systems::ai::is_player_controlled(
+            &game_state.world,
+            fighter
+        ));
+        assert_eq!(
+            TargetInstance::from_entity(&game_state.world, fighter),
+            TargetInstance::Object(fighter)
+        );
+        assert_eq!(
+            systems::health::resurrect(
+                &mut game_state,
+                fighter,
+                &Resurrection {
+                    time_limit: TimeDuration::from_days(36500),
+                    hit_points: RevivedHitPoints::Full,
+                    penalty: true,
+                },
+            ),
+            Err(ResurrectionError::Permanent)
+        );
+    }
+}
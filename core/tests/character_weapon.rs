@@ -7,8 +7,11 @@ mod tests {
     use nat20_core::{
         components::{
             ability::{Ability, AbilityScore, AbilityScoreMap},
+            actions::action::ActionContext,
+            d20::RollMode,
             damage::DamageType,
             dice::DieSize,
+            health::life_state::LifeState,
             id::ItemId,
             items::{
                 equipment::{
@@ -16,16 +19,17 @@ mod tests {
                     slots::EquipmentSlot,
                     weapon::{Weapon, WeaponCategory, WeaponKind, WeaponProperties},
                 },
-                inventory::ItemInstance,
+                inventory::{Inventory, ItemInstance},
                 item::{Item, ItemRarity},
                 money::MonetaryValue,
             },
             modifier::ModifierSource,
             proficiency::ProficiencyLevel,
+            time::TimeStep,
         },
-        entities::character::Character,
+        entities::{character::Character, object::ObjectMaterial},
         registry::registry::ItemsRegistry,
-        systems::{self, helpers},
+        systems::{self, coating::CoatingError, helpers},
         test_utils::fixtures,
     };
     use uom::si::{f32::Mass, mass::pound};
@@ -198,6 +202,8 @@ mod tests {
                 weight: Mass::new::<pound>(3.0),
                 value: MonetaryValue::from_str("15 GP").unwrap(),
                 rarity: ItemRarity::Common,
+                material: ObjectMaterial::Steel,
+                damage_taken: 0,
             },
             WeaponKind::Melee,
             WeaponCategory::Martial,
@@ -235,4 +241,139 @@ mod tests {
                 .contains_key(&ModifierSource::Custom("Enchantment".to_string()))
         );
     }
+
+    #[test]
+    fn character_attack_roll_against_unconscious_target() {
+        for (distance, crit) in [(1.5, true), (6.0, false)] {
+            let (mut game_state, fighter, goblin) = fixtures::engine::game_state_with_pair(
+                fixtures::creatures::heroes::fighter,
+                fixtures::creatures::monsters::goblin_warrior,
+                distance,
+            );
+            systems::helpers::set_component(
+                &mut game_state.world,
+                goblin,
+                LifeState::unconscious(),
+            );
+
+            let roll = systems::damage::prepare_attack_roll(
+                systems::loadout::weapon_attack_roll(
+                    &game_state.world,
+                    fighter,
+                    goblin,
+                    &EquipmentSlot::MeleeMainHand,
+                ),
+                &game_state.world,
+                fighter,
+                goblin,
+            );
+
+            assert_eq!(
+                roll.d20_check.advantage_tracker().roll_mode(),
+                RollMode::Advantage
+            );
+            // Only hits from within 5 feet are critical hits
+            assert_eq!(roll.roll_raw(2).crit_on_hit.is_some(), crit);
+        }
+    }
+
+    #[test]
+    fn character_weapon_on_hit_riders() {
+        let mut world = World::new();
+        let entity = world.spawn(Character::default());
+
+        let flame_tongue = ItemsRegistry::get(&ItemId::new("nat20_core", "item.flame_tongue"))
+            .unwrap()
+            .clone();
+        systems::loadout::equip(&mut world, entity, flame_tongue).unwrap();
+
+        let (item_id, on_hit) =
+            systems::loadout::on_hit(&world, entity, &EquipmentSlot::MeleeMainHand).unwrap();
+        assert_eq!(item_id, ItemId::new("nat20_core", "item.flame_tongue"));
+        assert_eq!(on_hit.damage.len(), 1);
+        assert_eq!(on_hit.damage[0].1, DamageType::Fire);
+
+        // Regular weapons have no riders
+        let longsword = ItemsRegistry::get(&ItemId::new("nat20_core", "item.longsword"))
+            .unwrap()
+            .clone();
+        systems::loadout::equip(&mut world, entity, longsword).unwrap();
+        assert!(systems::loadout::on_hit(&world, entity, &EquipmentSlot::MeleeMainHand).is_none());
+    }
+
+    #[test]
+    fn character_weapon_coating() {
+        let mut world = World::new();
+        let entity = world.spawn(Character::default());
+        let target = world.spawn(Character::default());
+        let slot = EquipmentSlot::MeleeMainHand;
+
+        let dagger = ItemsRegistry::get(&ItemId::new("nat20_core", "item.dagger"))
+            .unwrap()
+            .clone();
+        systems::loadout::equip(&mut world, entity, dagger).unwrap();
+
+        let basic_poison = ItemId::new("nat20_core", "item.basic_poison");
+        assert_eq!(
+            systems::coating::apply(&mut world, entity, &basic_poison, &slot),
+            Err(CoatingError::NotACoating(basic_poison.clone()))
+        );
+        for _ in 0..2 {
+            systems::inventory::add_item(
+                &mut world,
+                entity,
+                ItemsRegistry::get(&basic_poison).unwrap().clone(),
+            );
+        }
+        assert_eq!(
+            systems::coating::apply(
+                &mut world,
+                entity,
+                &basic_poison,
+                &EquipmentSlot::MeleeOffHand
+            ),
+            Err(CoatingError::NoWeaponInSlot(EquipmentSlot::MeleeOffHand))
+        );
+
+        systems::coating::apply(&mut world, entity, &basic_poison, &slot).unwrap();
+        assert_eq!(
+            helpers::get_component::<Inventory>(&world, entity)
+                .items()
+                .len(),
+            1
+        );
+        assert_eq!(
+            systems::coating::coating(&world, entity, &slot)
+                .unwrap()
+                .hits_remaining,
+            3
+        );
+
+        // The poison is used up after three hits
+        let context = ActionContext::Weapon { slot };
+        for _ in 0..3 {
+            let mut damage_roll =
+                systems::loadout::weapon_damage_roll(&world, entity, &slot).roll(false);
+            systems::coating::on_hit(
+                &mut world,
+                entity,
+                target,
+                &slot,
+                &context,
+                Some(&mut damage_roll),
+            );
+        }
+        assert!(systems::coating::coating(&world, entity, &slot).is_none());
+
+        // ...or dries up after a minute
+        systems::coating::apply(&mut world, entity, &basic_poison, &slot).unwrap();
+        systems::time::advance_time(
+            &mut world,
+            entity,
+            TimeStep::RealTime {
+                delta_seconds: 60.0,
+            },
+        );
+        assert!(systems::coating::coating(&world, entity, &slot).is_none());
+    }
 }
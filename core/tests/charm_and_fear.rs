@@ -0,0 +1,302 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{action::ActionContext, targeting::TargetInstance},
+            class::ClassAndSubclass,
+            d20::RollMode,
+            damage::{DamageRoll, DamageSource, DamageType},
+            effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate},
+            id::{ActionId, ClassId, EffectId, SpellId},
+            illusion::IllusionTemplate,
+            items::equipment::slots::EquipmentSlot,
+            modifier::ModifierSource,
+            skill::{Skill, SkillSet},
+            species::CreatureSize,
+            spells::spellbook::SpellSource,
+        },
+        engine::{game_state::GameState, geometry::WorldPath},
+        systems::{self, actions::ActionUsabilityError},
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            1.5,
+        )
+    }
+
+    fn add_condition(game_state: &mut GameState, applier: Entity, target: Entity, effect: &str) {
+        systems::effects::add_effect_template(
+            &mut game_state.world,
+            applier,
+            target,
+            ModifierSource::Custom("Test".to_string()),
+            &EffectInstanceTemplate {
+                effect_id: EffectId::new("nat20_core", effect),
+                lifetime: EffectLifetimeTemplate::Permanent,
+            },
+            None,
+        );
+    }
+
+    fn attack(
+        game_state: &GameState,
+        attacker: Entity,
+        target: Entity,
+    ) -> Result<(), ActionUsabilityError> {
+        systems::actions::action_usable_on_targets(
+            &game_state.world,
+            &game_state.geometry,
+            attacker,
+            &ActionId::new("nat20_core", "action.weapon_attack"),
+            &ActionContext::Weapon {
+                slot: EquipmentSlot::MeleeMainHand,
+            },
+            &Default::default(),
+            &[TargetInstance::Entity(target)],
+        )
+    }
+
+    fn attack_roll_mode(game_state: &GameState, attacker: Entity, target: Entity) -> RollMode {
+        systems::damage::prepare_attack_roll(
+            systems::loadout::weapon_attack_roll(
+                &game_state.world,
+                attacker,
+                target,
+                &EquipmentSlot::MeleeMainHand,
+            ),
+            &game_state.world,
+            attacker,
+            target,
+        )
+        .d20_check
+        .advantage_tracker()
+        .roll_mode()
+    }
+
+    #[test]
+    fn charmed_creatures_cant_attack_their_charmer() {
+        let (mut game_state, fighter, goblin) = setup();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::new(1.5, 0.0, 1.0));
+        assert_eq!(attack(&game_state, goblin, fighter), Ok(()));
+
+        add_condition(&mut game_state, fighter, goblin, "effect.condition.charmed");
+        assert_eq!(
+            attack(&game_state, goblin, fighter),
+            Err(ActionUsabilityError::Charmed(fighter))
+        );
+        // The rest of the party is still fair game
+        assert_eq!(attack(&game_state, goblin, wizard), Ok(()));
+    }
+
+    fn damage_from(game_state: &mut GameState, attacker: Entity, target: Entity) {
+        let mut damage = DamageRoll::new(
+            "1d4".parse().unwrap(),
+            DamageType::Force,
+            DamageSource::Spell(SpellId::new("nat20_core", "test.spell")),
+        )
+        .roll(false);
+        damage.action = Some((attacker, ActionId::new("nat20_core", "test.action")));
+        systems::health::damage(game_state, target, &damage, None);
+    }
+
+    #[test]
+    fn charm_person_only_works_on_humanoids() {
+        let (mut game_state, fighter, goblin) = setup();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+
+        let targeting = systems::actions::targeting_context(
+            &game_state.world,
+            wizard,
+            &ActionId::new("nat20_core", "action.charm_person"),
+            &ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.charm_person"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 1,
+                metamagic: Vec::new(),
+            },
+        );
+        assert!(
+            targeting
+                .allowed_targets
+                .matches(&game_state.world, &fighter)
+        );
+        // Goblins are Fey
+        assert!(
+            !targeting
+                .allowed_targets
+                .matches(&game_state.world, &goblin)
+        );
+    }
+
+    #[test]
+    fn charm_person_ends_when_the_caster_or_its_allies_cause_harm() {
+        let (mut game_state, fighter, goblin) = setup();
+        let charm_person = EffectId::new("nat20_core", "effect.spell.charm_person");
+        add_condition(
+            &mut game_state,
+            goblin,
+            fighter,
+            "effect.spell.charm_person",
+        );
+
+        // Someone the charmer doesn't side with can't break the charm
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        damage_from(&mut game_state, wizard, fighter);
+        assert!(systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &charm_person
+        ));
+
+        damage_from(&mut game_state, goblin, fighter);
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &charm_person
+        ));
+    }
+
+    #[test]
+    fn charmed_creatures_can_still_help_their_charmer() {
+        let (mut game_state, fighter, goblin) = setup();
+        add_condition(&mut game_state, fighter, goblin, "effect.condition.charmed");
+
+        let stabilize =
+            systems::actions::get_action(&ActionId::new("nat20_core", "action.stabilize")).unwrap();
+        assert_eq!(
+            systems::conditions::prevents_harm(&game_state.world, goblin, fighter, &stabilize.kind),
+            None
+        );
+    }
+
+    #[test]
+    fn frightened_creatures_have_disadvantage_while_they_see_the_source() {
+        let (mut game_state, fighter, goblin) = setup();
+        add_condition(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.condition.frightened",
+        );
+
+        assert_eq!(
+            attack_roll_mode(&game_state, goblin, fighter),
+            RollMode::Disadvantage
+        );
+        assert_eq!(
+            systems::helpers::get_component::<SkillSet>(&game_state.world, goblin)
+                .check(&Skill::Athletics, &game_state.world, goblin)
+                .advantage_tracker
+                .roll_mode(),
+            RollMode::Disadvantage
+        );
+        // The fear doesn't go the other way
+        assert_eq!(
+            attack_roll_mode(&game_state, fighter, goblin),
+            RollMode::Normal
+        );
+
+        // Out of sight, out of mind
+        systems::effects::add_permanent_effect(
+            &mut game_state.world,
+            fighter,
+            EffectId::new("nat20_core", "effect.condition.invisible"),
+            &ModifierSource::Base,
+            None,
+        );
+        assert_eq!(
+            systems::helpers::get_component::<SkillSet>(&game_state.world, goblin)
+                .check(&Skill::Athletics, &game_state.world, goblin)
+                .advantage_tracker
+                .roll_mode(),
+            RollMode::Normal
+        );
+    }
+
+    #[test]
+    fn frightened_creatures_arent_bothered_by_what_they_cant_see() {
+        let (mut game_state, fighter, goblin) = fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            5.0,
+        );
+        add_condition(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.condition.frightened",
+        );
+        systems::conditions::update_fear_sight(&mut game_state, goblin);
+        assert_eq!(
+            attack_roll_mode(&game_state, goblin, fighter),
+            RollMode::Disadvantage
+        );
+
+        // A wall between them, or at least something that looks like one
+        systems::illusions::spawn(
+            &mut game_state,
+            &IllusionTemplate {
+                name: "Wall".to_string(),
+                size: CreatureSize::Large,
+                dc: None,
+                duration: None,
+            },
+            None,
+            15,
+            &Point3::new(2.5, 0.0, 0.0),
+        );
+        systems::conditions::update_fear_sight(&mut game_state, goblin);
+        assert_eq!(
+            attack_roll_mode(&game_state, goblin, fighter),
+            RollMode::Normal
+        );
+    }
+
+    #[test]
+    fn frightened_creatures_cant_move_closer() {
+        let (mut game_state, fighter, goblin) = setup();
+        add_condition(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.condition.frightened",
+        );
+
+        let away = WorldPath::new(vec![Point3::new(1.5, 0.0, 0.0), Point3::new(5.0, 0.0, 0.0)]);
+        assert!(systems::conditions::fear_limit(&game_state.world, goblin, &away).is_none());
+
+        // Backing off and coming partway back is fine, but not any closer than
+        // where the goblin started
+        let back = WorldPath::new(vec![
+            Point3::new(1.5, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+        ]);
+        assert!(systems::conditions::fear_limit(&game_state.world, goblin, &back).is_none());
+        let past = WorldPath::new(vec![
+            Point3::new(1.5, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            Point3::new(0.5, 0.0, 0.0),
+        ]);
+        let (source, distance) =
+            systems::conditions::fear_limit(&game_state.world, goblin, &past).unwrap();
+        assert_eq!(source, fighter);
+        assert!(distance.value > 4.5 && distance.value < past.length.value);
+
+        let towards = WorldPath::new(vec![Point3::new(1.5, 0.0, 0.0), Point3::origin()]);
+        let (_, distance) =
+            systems::conditions::fear_limit(&game_state.world, goblin, &towards).unwrap();
+        assert_eq!(distance.value, 0.0);
+    }
+}
@@ -0,0 +1,89 @@
+extern crate nat20_core;
+
+mod tests {
+
+    use nat20_core::{
+        components::{
+            actions::targeting::TargetInstance,
+            effects::effect::EffectLifetime,
+            id::{ActionId, ClassId, MonsterId, Name, ResourceId},
+            resource::{ResourceAmount, ResourceMap},
+            shapechange::Shapechanged,
+            species::CreatureSize,
+            time::TimeDuration,
+        },
+        engine::event::ActionData,
+        entities::character::Character,
+        systems,
+        test_utils::fixtures,
+    };
+
+    #[test]
+    fn druid_wild_shape_takes_the_chosen_form() {
+        let mut game_state = fixtures::engine::game_state();
+        let druid = game_state
+            .world
+            .spawn(Character::new(Name::new("Jenny Druid")));
+        systems::quick_build::level_up_to(
+            &mut game_state.world,
+            druid,
+            &ClassId::new("nat20_core", "class.druid"),
+            None,
+            4,
+        )
+        .unwrap();
+
+        // Check that the druid has the Wild Shape action
+        let available_actions = systems::actions::available_actions(&game_state.world, druid);
+        let action_id = ActionId::new("nat20_core", "action.druid.wild_shape");
+        assert!(
+            available_actions.contains_key(&action_id),
+            "Druid should have Wild Shape action"
+        );
+        let contexts_and_costs = available_actions.get(&action_id).unwrap();
+
+        // Check that the druid has two uses of Wild Shape
+        let wild_shape_uses = ResourceId::new("nat20_core", "resource.druid.wild_shape");
+        assert!(
+            systems::helpers::get_component::<ResourceMap>(&game_state.world, druid)
+                .can_afford(&wild_shape_uses, &ResourceAmount::Flat(2))
+        );
+
+        let cat_form = ActionData::new(
+            druid,
+            ActionId::new("nat20_core", "action.druid.wild_shape.cat"),
+            contexts_and_costs[0].0.clone(),
+            contexts_and_costs[0].1.clone(),
+            vec![TargetInstance::Entity(druid)],
+        );
+        game_state.validate_action(&cat_form, true).unwrap();
+        systems::actions::perform_action(&mut game_state, &cat_form);
+
+        assert_eq!(
+            systems::helpers::get_component::<Shapechanged>(&game_state.world, druid).form,
+            MonsterId::new("nat20_core", "monster.cat")
+        );
+        assert_eq!(
+            *systems::helpers::get_component::<CreatureSize>(&game_state.world, druid),
+            CreatureSize::Tiny
+        );
+        {
+            let resources =
+                systems::helpers::get_component::<ResourceMap>(&game_state.world, druid);
+            assert!(resources.can_afford(&wild_shape_uses, &ResourceAmount::Flat(1)));
+            assert!(!resources.can_afford(&wild_shape_uses, &ResourceAmount::Flat(2)));
+        }
+
+        // The form lasts for half the druid level in hours
+        let effects = systems::effects::effects(&game_state.world, druid);
+        let wild_shape = effects
+            .iter()
+            .find(|effect| effect.effect_id == systems::shapechange::wild_shape_effect())
+            .unwrap();
+        assert!(matches!(
+            wild_shape.lifetime,
+            EffectLifetime::AtTurnBoundary { duration, .. }
+                if duration == TimeDuration::from_hours(2)
+        ));
+    }
+}
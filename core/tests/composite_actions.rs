@@ -0,0 +1,169 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::collections::HashMap;
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{
+                action::{
+                    ActionContext, ActionKind, ActionKindResult, CompositeStep,
+                    CompositeStepCondition,
+                },
+                targeting::TargetInstance,
+            },
+            dice::RollPolicy,
+            health::hit_points::HitPoints,
+            id::{ActionId, EffectId, ResourceId},
+            items::equipment::slots::EquipmentSlot,
+            resource::{ResourceAmount, ResourceAmountMap},
+        },
+        engine::{
+            event::{ActionData, EventKind},
+            game_state::GameState,
+        },
+        systems,
+        test_utils::fixtures,
+    };
+
+    fn topple() -> ActionId {
+        ActionId::new("nat20_core", "action.topple")
+    }
+
+    fn prone() -> EffectId {
+        EffectId::new("nat20_core", "effect.condition.prone")
+    }
+
+    fn setup(fighter_d20: u8, goblin_d20: u8) -> (GameState, Entity, Entity) {
+        let (mut game_state, fighter, goblin) = fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            1.0,
+        );
+        for (entity, d20) in [(fighter, fighter_d20), (goblin, goblin_d20)] {
+            game_state
+                .world
+                .insert_one(
+                    entity,
+                    RollPolicy {
+                        average_damage: false,
+                        fixed_d20: Some(d20),
+                    },
+                )
+                .unwrap();
+        }
+        // Tough enough to still be standing after the attack
+        game_state
+            .world
+            .insert_one(goblin, HitPoints::new(100))
+            .unwrap();
+        (game_state, fighter, goblin)
+    }
+
+    /// Topple the goblin, and return the results of the steps performed on it
+    fn perform_topple(
+        game_state: &mut GameState,
+        fighter: Entity,
+        goblin: Entity,
+    ) -> Vec<ActionKindResult> {
+        let action = ActionData::new(
+            fighter,
+            topple(),
+            ActionContext::Weapon {
+                slot: EquipmentSlot::MeleeMainHand,
+            },
+            ResourceAmountMap::new(),
+            vec![TargetInstance::Entity(goblin)],
+        );
+        systems::actions::perform_action(game_state, &action);
+
+        let mut performed =
+            game_state
+                .event_log
+                .events
+                .iter()
+                .filter_map(|event| match &event.kind {
+                    EventKind::ActionPerformed { results, .. } => Some(results),
+                    _ => None,
+                });
+        let results = performed.next().expect("The action should be performed");
+        // The steps are logged as part of the whole action
+        assert!(performed.next().is_none());
+
+        assert_eq!(results.len(), 1);
+        match &results[0].kind {
+            ActionKindResult::Composite { actions } => actions.clone(),
+            other => panic!("Expected a composite result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn later_steps_depend_on_earlier_ones() {
+        let (mut game_state, fighter, goblin) = setup(1, 1);
+
+        let steps = perform_topple(&mut game_state, fighter, goblin);
+
+        // The attack missed, so there's nothing to save against
+        assert_eq!(steps.len(), 1);
+        assert!(!steps[0].succeeded());
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            goblin,
+            &prone()
+        ));
+    }
+
+    #[test]
+    fn steps_are_performed_in_sequence() {
+        for (goblin_d20, prone_expected) in [(1, true), (20, false)] {
+            let (mut game_state, fighter, goblin) = setup(20, goblin_d20);
+
+            let steps = perform_topple(&mut game_state, fighter, goblin);
+
+            assert_eq!(steps.len(), 2);
+            let ActionKindResult::Standard(attack) = &steps[0] else {
+                panic!("Expected the attack first, got {:?}", steps[0]);
+            };
+            assert!(attack.damage.is_some());
+            assert!(steps[0].succeeded());
+            let ActionKindResult::Standard(save) = &steps[1] else {
+                panic!("Expected the saving throw second, got {:?}", steps[1]);
+            };
+            assert_eq!(save.saving_throw.as_ref().unwrap().success, !prone_expected);
+            assert_eq!(
+                systems::effects::has_effect(&game_state.world, goblin, &prone()),
+                prone_expected
+            );
+        }
+    }
+
+    #[test]
+    fn composite_costs_include_every_step() {
+        let action = ResourceId::new("nat20_core", "resource.action");
+        let bonus_action = ResourceId::new("nat20_core", "resource.bonus_action");
+        let step = |cost: ResourceAmountMap| CompositeStep {
+            kind: ActionKind::Composite {
+                actions: Vec::new(),
+            },
+            only_if: CompositeStepCondition::Always,
+            resource_cost: cost,
+        };
+        let kind = ActionKind::Composite {
+            actions: vec![
+                step(HashMap::from([(
+                    bonus_action.clone(),
+                    ResourceAmount::Flat(1),
+                )])),
+                step(HashMap::new()),
+                step(HashMap::from([(action.clone(), ResourceAmount::Flat(1))])),
+            ],
+        };
+
+        let cost = kind.total_cost(HashMap::from([(action.clone(), ResourceAmount::Flat(1))]));
+
+        assert_eq!(cost.len(), 2);
+        assert_eq!(cost[&action], ResourceAmount::Flat(2));
+        assert_eq!(cost[&bonus_action], ResourceAmount::Flat(1));
+    }
+}
@@ -0,0 +1,266 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::action::ActionContext,
+            ai::PlayerControlledTag,
+            class::ClassAndSubclass,
+            control::Controlled,
+            effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate},
+            faction::Attitude,
+            id::{ActionId, ClassId, EffectId, SpellId},
+            items::equipment::slots::EquipmentSlot,
+            modifier::ModifierSource,
+            spells::spellbook::SpellSource,
+        },
+        engine::game_state::GameState,
+        systems::{self, actions::ActionUsabilityError},
+        test_utils::fixtures,
+    };
+
+    fn setup() -> (GameState, Entity, Entity) {
+        let (mut game_state, fighter, goblin) = fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            1.5,
+        );
+        game_state
+            .world
+            .insert_one(fighter, PlayerControlledTag)
+            .unwrap();
+        (game_state, fighter, goblin)
+    }
+
+    fn apply(game_state: &mut GameState, applier: Entity, target: Entity, effect: &str) {
+        systems::effects::add_effect_template(
+            &mut game_state.world,
+            applier,
+            target,
+            ModifierSource::Custom("Test".to_string()),
+            &EffectInstanceTemplate {
+                effect_id: EffectId::new("nat20_core", effect),
+                lifetime: EffectLifetimeTemplate::Permanent,
+            },
+            None,
+        );
+    }
+
+    fn usable(
+        game_state: &GameState,
+        entity: Entity,
+        action: &str,
+        context: ActionContext,
+    ) -> Result<(), ActionUsabilityError> {
+        let action_id = ActionId::new("nat20_core", action);
+        let cost = systems::actions::get_action(&action_id)
+            .unwrap()
+            .resource_cost()
+            .clone();
+        systems::actions::action_usable(&game_state.world, entity, &action_id, &context, &cost)
+    }
+
+    #[test]
+    fn dominated_creatures_are_decided_by_their_controller() {
+        let (mut game_state, fighter, goblin) = setup();
+        assert!(!systems::ai::is_decided_by_player(
+            &game_state.world,
+            goblin
+        ));
+
+        apply(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.spell.dominate_person",
+        );
+
+        assert_eq!(
+            systems::control::controller_of(&game_state.world, goblin),
+            Some(fighter)
+        );
+        assert!(systems::ai::is_decided_by_player(&game_state.world, goblin));
+        // Being controlled doesn't make it part of the party
+        assert!(!systems::ai::is_player_controlled(
+            &game_state.world,
+            goblin
+        ));
+    }
+
+    #[test]
+    fn dominated_creatures_side_with_their_controller() {
+        let (mut game_state, fighter, goblin) = setup();
+        let other_goblin =
+            fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        assert_eq!(
+            systems::factions::attitude_from_to(&game_state.world, goblin, other_goblin),
+            Attitude::Friendly
+        );
+
+        apply(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.spell.dominate_person",
+        );
+
+        assert_eq!(
+            systems::factions::attitude_from_to(&game_state.world, goblin, fighter),
+            Attitude::Friendly
+        );
+        assert_eq!(
+            systems::factions::attitude_from_to(&game_state.world, other_goblin, goblin),
+            Attitude::Hostile
+        );
+    }
+
+    #[test]
+    fn commands_restrict_what_the_target_can_do() {
+        let (mut game_state, fighter, goblin) = setup();
+        let weapon_attack = ActionContext::Weapon {
+            slot: EquipmentSlot::MeleeMainHand,
+        };
+        assert!(
+            usable(
+                &game_state,
+                goblin,
+                "action.weapon_attack",
+                weapon_attack.clone()
+            )
+            .is_ok()
+        );
+
+        apply(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.spell.command.flee",
+        );
+        assert_eq!(
+            usable(&game_state, goblin, "action.weapon_attack", weapon_attack),
+            Err(ActionUsabilityError::Controlled(fighter))
+        );
+        assert!(usable(&game_state, goblin, "action.dash", ActionContext::Other).is_ok());
+        assert_eq!(
+            systems::control::holds_in_place(&game_state.world, goblin),
+            None
+        );
+    }
+
+    #[test]
+    fn fleeing_creatures_move_away_from_their_controller() {
+        let (mut game_state, fighter, goblin) = setup();
+        apply(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.spell.command.flee",
+        );
+
+        let distance = |game_state: &GameState| {
+            let fighter = systems::geometry::get_foot_position(&game_state.world, fighter).unwrap();
+            let goblin = systems::geometry::get_foot_position(&game_state.world, goblin).unwrap();
+            (goblin - fighter).magnitude()
+        };
+        let before = distance(&game_state);
+        systems::control::on_turn_start(&mut game_state, goblin);
+        assert!(distance(&game_state) > before + 1.0);
+    }
+
+    #[test]
+    fn control_without_a_spell_can_still_be_shaken_off() {
+        let (mut game_state, fighter, goblin) = setup();
+        apply(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.spell.dominate_person",
+        );
+
+        assert!(
+            systems::control::controlled(&game_state.world, goblin)
+                .unwrap()
+                .save_on_damage
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn dominate_person_only_works_on_humanoids() {
+        let (mut game_state, fighter, goblin) = setup();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+
+        let targeting = systems::actions::targeting_context(
+            &game_state.world,
+            wizard,
+            &ActionId::new("nat20_core", "action.dominate_person"),
+            &ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.dominate_person"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 5,
+                metamagic: Vec::new(),
+            },
+        );
+        assert!(
+            targeting
+                .allowed_targets
+                .matches(&game_state.world, &fighter)
+        );
+        assert!(
+            !targeting
+                .allowed_targets
+                .matches(&game_state.world, &goblin)
+        );
+    }
+
+    #[test]
+    fn halted_creatures_cant_move_or_act() {
+        let (mut game_state, fighter, goblin) = setup();
+        apply(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.spell.command.halt",
+        );
+
+        assert_eq!(
+            systems::control::holds_in_place(&game_state.world, goblin),
+            Some(fighter)
+        );
+        assert_eq!(
+            usable(&game_state, goblin, "action.dash", ActionContext::Other),
+            Err(ActionUsabilityError::Controlled(fighter))
+        );
+    }
+
+    #[test]
+    fn control_ends_with_the_effect() {
+        let (mut game_state, fighter, goblin) = setup();
+        apply(
+            &mut game_state,
+            fighter,
+            goblin,
+            "effect.spell.dominate_person",
+        );
+
+        systems::effects::remove_effect(
+            &mut game_state.world,
+            goblin,
+            &EffectId::new("nat20_core", "effect.spell.dominate_person"),
+        );
+
+        assert_eq!(
+            systems::control::controller_of(&game_state.world, goblin),
+            None
+        );
+        assert!(game_state.world.get::<&Controlled>(goblin).is_err());
+        assert!(!systems::ai::is_decided_by_player(
+            &game_state.world,
+            goblin
+        ));
+    }
+}
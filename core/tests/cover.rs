@@ -0,0 +1,161 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            ability::Ability,
+            actions::{
+                action::ActionContext, preview::CheckPreview, resolution::Cover,
+                targeting::TargetInstance,
+            },
+            class::ClassAndSubclass,
+            damage::DamageThreshold,
+            health::hit_points::HitPoints,
+            id::{ActionId, ClassId, Name, SpellId},
+            resource::ResourceAmountMap,
+            saving_throw::SavingThrowKind,
+            species::CreatureSize,
+            spells::spellbook::SpellSource,
+        },
+        engine::{
+            event::{ActionData, EventKind},
+            game_state::GameState,
+        },
+        entities::object::{Object, ObjectMaterial},
+        systems::{self, d20::D20ResultKind},
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn spawn_at(world: &mut World, x: f32, z: f32) -> Entity {
+        let entity = fixtures::creatures::monsters::goblin_warrior(world).id();
+        systems::geometry::teleport_to(world, entity, &Point3::new(x, 0.0, z));
+        entity
+    }
+
+    /// A crate half as tall as a goblin
+    fn crate_at(game_state: &mut GameState, x: f32, z: f32) -> Entity {
+        let object = game_state.world.spawn(Object::new(
+            Name::new("Crate"),
+            ObjectMaterial::Wood,
+            CreatureSize::Tiny,
+            HitPoints::new(5),
+            DamageThreshold(0),
+        ));
+        systems::geometry::teleport_to(&mut game_state.world, object, &Point3::new(x, 0.0, z));
+        object
+    }
+
+    fn fireball_at(wizard: Entity, point: Point3<f32>) -> ActionData {
+        ActionData::new(
+            wizard,
+            ActionId::new("nat20_core", "action.fireball"),
+            ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.fireball"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 3,
+                metamagic: Vec::new(),
+            },
+            ResourceAmountMap::new(),
+            vec![TargetInstance::Point(point)],
+        )
+    }
+
+    /// The cover bonus to the saving throw the entity made, if any
+    fn save_cover_bonus(game_state: &GameState, entity: Entity) -> Option<i32> {
+        game_state
+            .event_log
+            .events
+            .iter()
+            .find_map(|event| match &event.kind {
+                EventKind::D20CheckPerformed(
+                    performer,
+                    D20ResultKind::SavingThrow { kind, result },
+                    _,
+                ) if *performer == entity => {
+                    assert_eq!(*kind, SavingThrowKind::Ability(Ability::Dexterity));
+                    Some(
+                        [Cover::Half, Cover::ThreeQuarters]
+                            .iter()
+                            .find_map(|cover| {
+                                result.modifier_breakdown.get(&cover.modifier_source())
+                            }),
+                    )
+                }
+                _ => None,
+            })
+            .expect("Expected a saving throw")
+    }
+
+    #[test]
+    fn low_objects_give_half_cover() {
+        let mut game_state = fixtures::engine::game_state();
+        let goblin = spawn_at(&mut game_state.world, 10.0, 0.0);
+        crate_at(&mut game_state, 9.75, 0.0);
+
+        let explosion = Point3::new(8.0, 0.1, 0.0);
+        assert_eq!(
+            systems::geometry::cover(&game_state.world, &game_state.geometry, explosion, goblin),
+            Cover::Half
+        );
+        // From right above the goblin the crate doesn't get in the way
+        assert_eq!(
+            systems::geometry::cover(
+                &game_state.world,
+                &game_state.geometry,
+                Point3::new(10.0, 5.0, 0.0),
+                goblin
+            ),
+            Cover::None
+        );
+    }
+
+    #[test]
+    fn each_target_of_an_area_gets_its_own_cover() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+        let behind_crate = spawn_at(&mut game_state.world, 10.0, 0.0);
+        let in_the_open = spawn_at(&mut game_state.world, 8.0, 2.0);
+        crate_at(&mut game_state, 9.75, 0.0);
+
+        systems::actions::perform_action(
+            &mut game_state,
+            &fireball_at(wizard, Point3::new(8.0, 0.0, 0.0)),
+        );
+
+        assert_eq!(
+            save_cover_bonus(&game_state, behind_crate),
+            Some(Cover::Half.bonus())
+        );
+        assert_eq!(save_cover_bonus(&game_state, in_the_open), None);
+    }
+
+    #[test]
+    fn previews_account_for_cover() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+        let behind_crate = spawn_at(&mut game_state.world, 10.0, 0.0);
+        let in_the_open = spawn_at(&mut game_state.world, 8.0, 2.0);
+        crate_at(&mut game_state, 9.75, 0.0);
+
+        let fireball = fireball_at(wizard, Point3::new(8.0, 0.0, 0.0));
+        let save_chance = |target| {
+            let preview =
+                systems::actions::preview_on_target(&game_state, &fireball, target).unwrap();
+            match preview.check {
+                Some(CheckPreview::SavingThrow { save_chance, .. }) => save_chance,
+                check => panic!("Expected a saving throw, got {:?}", check),
+            }
+        };
+
+        // Half cover is a +2 bonus to the save, i.e. 10% on a d20
+        let difference = save_chance(behind_crate) - save_chance(in_the_open);
+        assert!((difference - 0.1).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,156 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::str::FromStr;
+
+    use nat20_core::{
+        components::{
+            actions::action::ActionContext,
+            id::{ActionId, IdProvider, ItemId, SpellId},
+            items::{
+                inventory::{Inventory, ItemContainer, ItemInstance},
+                money::MonetaryValue,
+            },
+            resource::RechargeRule,
+            spells::spellbook::{GrantedSpellSource, SpellSource},
+        },
+        registry::registry::ItemsRegistry,
+        systems::{self, crafting::CraftingError},
+        test_utils::fixtures,
+    };
+
+    fn magic_missile() -> SpellId {
+        SpellId::new("nat20_core", "spell.magic_missile")
+    }
+
+    fn item_context(item_id: &ItemId, level: u8) -> ActionContext {
+        ActionContext::Spell {
+            id: magic_missile(),
+            source: SpellSource::Granted {
+                source: GrantedSpellSource::Item(item_id.clone()),
+                level,
+            },
+            level,
+            metamagic: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn scribe_scroll() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+
+        // The wizard starts out with only 8 GP
+        assert!(matches!(
+            systems::crafting::scribe_scroll(&mut game_state, wizard, &magic_missile(), 1),
+            Err(CraftingError::InsufficientFunds { .. })
+        ));
+
+        systems::inventory::add_money(
+            &mut game_state.world,
+            wizard,
+            MonetaryValue::from_str("30 GP").unwrap(),
+        );
+        let scroll_id =
+            systems::crafting::scribe_scroll(&mut game_state, wizard, &magic_missile(), 1).unwrap();
+
+        assert_eq!(game_state.clock.to_string(), "Day 2, 06:00:00");
+        {
+            let inventory = systems::helpers::get_component::<Inventory>(&game_state.world, wizard);
+            assert_eq!(inventory.money().total_in_gold(), 13.0);
+            assert!(inventory.items().iter().any(|item| *item.id() == scroll_id));
+        }
+
+        let context = item_context(&scroll_id, 1);
+        assert!(
+            systems::actions::all_actions(&game_state.world, wizard)
+                .get(&ActionId::new("nat20_core", "action.magic_missile"))
+                .unwrap()
+                .iter()
+                .any(|(action_context, _)| *action_context == context)
+        );
+
+        // Reading the scroll destroys it
+        systems::inventory::spend_item_charge(&mut game_state.world, wizard, &context);
+        assert!(
+            !systems::helpers::get_component::<Inventory>(&game_state.world, wizard)
+                .items()
+                .iter()
+                .any(|item| *item.id() == scroll_id)
+        );
+    }
+
+    #[test]
+    fn scribe_scroll_above_known_level() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::inventory::add_money(
+            &mut game_state.world,
+            wizard,
+            MonetaryValue::from_str("100000 GP").unwrap(),
+        );
+
+        // A level 5 wizard doesn't have any level 9 spell slots
+        assert!(matches!(
+            systems::crafting::scribe_scroll(&mut game_state, wizard, &magic_missile(), 9),
+            Err(CraftingError::CannotCastSpell { level: 9, .. })
+        ));
+        // Only spells the wizard knows can be scribed
+        assert!(matches!(
+            systems::crafting::scribe_scroll(
+                &mut game_state,
+                wizard,
+                &SpellId::new("nat20_core", "spell.eldritch_blast"),
+                0
+            ),
+            Err(CraftingError::CannotCastSpell { .. })
+        ));
+    }
+
+    #[test]
+    fn craft_wand_recharges() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        systems::inventory::add_money(
+            &mut game_state.world,
+            wizard,
+            MonetaryValue::from_str("200 GP").unwrap(),
+        );
+
+        let wand_id =
+            systems::crafting::craft_wand(&mut game_state, wizard, &magic_missile(), 1).unwrap();
+        // The registry has a definition for this wand, so that one is crafted
+        assert_eq!(
+            ItemsRegistry::get(&wand_id).map(|item| item.item().name.clone()),
+            Some("Wand of Magic Missiles".to_string())
+        );
+
+        let context = item_context(&wand_id, 1);
+        for _ in 0..7 {
+            systems::inventory::spend_item_charge(&mut game_state.world, wizard, &context);
+        }
+        let charges = |game_state: &nat20_core::engine::game_state::GameState| {
+            systems::helpers::get_component::<Inventory>(&game_state.world, wizard)
+                .items()
+                .iter()
+                .find_map(|item| match item {
+                    ItemInstance::Spell(wand) if wand.item.id == wand_id => {
+                        Some(wand.charges.current)
+                    }
+                    _ => None,
+                })
+        };
+        // Wands aren't destroyed when they run out of charges
+        assert_eq!(charges(&game_state), Some(0));
+        assert!(
+            !systems::actions::all_actions(&game_state.world, wizard)
+                .get(&ActionId::new("nat20_core", "action.magic_missile"))
+                .unwrap()
+                .iter()
+                .any(|(action_context, _)| *action_context == context)
+        );
+
+        systems::inventory::recharge_items(&mut game_state.world, wizard, &RechargeRule::Daily);
+        assert_eq!(charges(&game_state), Some(7));
+    }
+}
@@ -0,0 +1,72 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            effects::effect::EffectTag,
+            id::{EffectId, ItemId},
+            items::equipment::{loadout::TryEquipError, slots::EquipmentSlot},
+        },
+        engine::game_state::GameState,
+        registry::registry::ItemsRegistry,
+        systems,
+        test_utils::fixtures,
+    };
+
+    fn equip(game_state: &mut GameState, entity: Entity, item: &str) -> Result<(), TryEquipError> {
+        systems::loadout::equip(
+            &mut game_state.world,
+            entity,
+            ItemsRegistry::get(&ItemId::new("nat20_core", item))
+                .unwrap()
+                .clone(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn cursed_armor_cannot_be_removed() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let curse = EffectId::new("nat20_core", "effect.item.armor_of_vulnerability_curse");
+
+        equip(&mut game_state, fighter, "item.armor_of_vulnerability").unwrap();
+        assert!(systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &curse
+        ));
+
+        assert!(
+            systems::loadout::unequip(&mut game_state.world, fighter, &EquipmentSlot::Armor)
+                .is_none()
+        );
+        assert!(matches!(
+            equip(&mut game_state, fighter, "item.chainmail"),
+            Err(TryEquipError::BoundByCurse(_))
+        ));
+        assert!(systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &curse
+        ));
+
+        let removed = systems::effects::remove_effects_with_tags(
+            &mut game_state.world,
+            fighter,
+            &[EffectTag::Curse],
+        );
+        assert_eq!(removed, vec![curse.clone()]);
+
+        assert!(
+            systems::loadout::unequip(&mut game_state.world, fighter, &EquipmentSlot::Armor)
+                .is_some()
+        );
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &curse
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{
+                action::{ActionContext, ActionKind, ActionKindResult, DamageRollPolicy},
+                custom::{CustomAction, CustomActionResult},
+                targeting::TargetInstance,
+            },
+            health::hit_points::HitPoints,
+            id::{ActionId, CustomActionId},
+            resource::ResourceAmountMap,
+        },
+        engine::{
+            event::{ActionData, EventKind},
+            game_state::GameState,
+        },
+        registry::{self, serialize::action::ActionKindDefinition},
+        test_utils::fixtures,
+    };
+    use serde_json::json;
+
+    /// Homebrew: the target is left hanging on by a thread
+    struct HangingByAThread;
+
+    impl CustomAction for HangingByAThread {
+        fn perform(
+            &self,
+            game_state: &mut GameState,
+            _: &ActionData,
+            target: Entity,
+        ) -> CustomActionResult {
+            let previous = game_state
+                .world
+                .get::<&HitPoints>(target)
+                .map(|hit_points| hit_points.current())
+                .unwrap_or_default();
+            game_state
+                .world
+                .insert_one(target, HitPoints::new(1))
+                .unwrap();
+            CustomActionResult::new(hanging_by_a_thread(), true, "is hanging by a thread")
+                .with_payload(json!({ "previous_hit_points": previous }))
+        }
+    }
+
+    fn hanging_by_a_thread() -> CustomActionId {
+        CustomActionId::new("nat20_core", "custom_action.test.hanging_by_a_thread")
+    }
+
+    fn perform(game_state: &mut GameState, kind: &ActionKind) -> (Entity, Vec<ActionKindResult>) {
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        let action = ActionData::new(
+            wizard,
+            ActionId::new("nat20_core", "action.test.custom"),
+            ActionContext::Other,
+            ResourceAmountMap::new(),
+            vec![TargetInstance::Entity(goblin)],
+        );
+        kind.perform(game_state, &action, &[goblin], DamageRollPolicy::PerTarget);
+
+        let results = game_state
+            .event_log
+            .events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::ActionPerformed { results, .. } => Some(results),
+                _ => None,
+            })
+            .flatten()
+            .map(|result| result.kind.clone())
+            .collect();
+        (goblin, results)
+    }
+
+    #[test]
+    fn custom_actions_are_performed_by_their_registered_implementation() {
+        registry::custom_action::register(hanging_by_a_thread(), HangingByAThread);
+        let mut game_state = fixtures::engine::game_state();
+        let kind: ActionKind = serde_json::from_value::<ActionKindDefinition>(json!({
+            "custom": {
+                "action": hanging_by_a_thread().to_string()
+            }
+        }))
+        .unwrap()
+        .into();
+
+        let (goblin, results) = perform(&mut game_state, &kind);
+
+        assert_eq!(
+            game_state
+                .world
+                .get::<&HitPoints>(goblin)
+                .unwrap()
+                .current(),
+            1
+        );
+        assert_eq!(results.len(), 1);
+        let ActionKindResult::Custom(result) = &results[0] else {
+            panic!("Expected a custom result, got {:?}", results[0]);
+        };
+        assert_eq!(result.action, hanging_by_a_thread());
+        assert!(results[0].succeeded());
+        assert!(result.payload["previous_hit_points"].as_u64().unwrap() > 1);
+    }
+
+    #[test]
+    fn unregistered_custom_actions_do_nothing() {
+        let mut game_state = fixtures::engine::game_state();
+        let kind = ActionKind::Custom {
+            action: CustomActionId::new("nat20_core", "custom_action.test.unregistered"),
+        };
+
+        let (_, results) = perform(&mut game_state, &kind);
+
+        assert!(results.is_empty());
+    }
+}
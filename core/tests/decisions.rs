@@ -0,0 +1,232 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            ability::Ability,
+            actions::{action::ActionContext, targeting::TargetInstance},
+            class::ClassAndSubclass,
+            damage::{DamageRollResult, DamageType},
+            dice::RollPolicy,
+            id::{ActionId, ClassId, ItemId, SpellId},
+            items::equipment::slots::EquipmentSlot,
+            modifier::{ModifierSet, ModifierSource},
+            saving_throw::{SavingThrowDC, SavingThrowKind},
+            spells::spellbook::Spellbook,
+        },
+        engine::{
+            event::{
+                ActionData, ActionDecision, ActionDecisionKind, ActionPromptKind, CallbackResult,
+                DecisionRequest, EventKind,
+            },
+            game_state::GameState,
+        },
+        registry::registry::ItemsRegistry,
+        systems::{self, d20::D20CheckDCKind},
+        test_utils::fixtures,
+    };
+
+    #[test]
+    fn resolution_waits_for_decision() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+
+        let event = systems::d20::check(
+            &mut game_state,
+            fighter,
+            &D20CheckDCKind::SavingThrow(SavingThrowDC {
+                key: SavingThrowKind::Ability(Ability::Strength),
+                dc: ModifierSet::from(ModifierSource::Base, 10),
+            }),
+        );
+
+        // Once the check has been resolved, the fighter has to make a choice
+        // before the resolution can continue
+        let chosen = Arc::new(AtomicUsize::new(usize::MAX));
+        let _ = game_state.process_event_with_callback(
+            event,
+            Arc::new({
+                let chosen = chosen.clone();
+                move |_, _| {
+                    let chosen = chosen.clone();
+                    CallbackResult::Decision(
+                        DecisionRequest::new(
+                            fighter,
+                            "Pick one",
+                            vec!["First".to_string(), "Second".to_string()],
+                        ),
+                        Arc::new(move |_, option| {
+                            chosen.store(option, Ordering::SeqCst);
+                            CallbackResult::None
+                        }),
+                    )
+                }
+            }),
+        );
+
+        let prompt = game_state.next_prompt_entity(fighter).unwrap().clone();
+        let ActionPromptKind::Decision { request } = &prompt.kind else {
+            panic!("Expected a decision prompt, got {:?}", prompt.kind);
+        };
+        assert_eq!(request.chooser, fighter);
+        assert_eq!(chosen.load(Ordering::SeqCst), usize::MAX);
+
+        // Options that don't exist are rejected
+        assert!(
+            game_state
+                .submit_decision(ActionDecision {
+                    response_to: prompt.id,
+                    kind: ActionDecisionKind::Decision {
+                        chooser: fighter,
+                        option: 2,
+                    },
+                })
+                .is_err()
+        );
+
+        game_state
+            .submit_decision(ActionDecision {
+                response_to: prompt.id,
+                kind: ActionDecisionKind::Decision {
+                    chooser: fighter,
+                    option: 1,
+                },
+            })
+            .unwrap();
+        assert_eq!(chosen.load(Ordering::SeqCst), 1);
+        assert!(game_state.next_prompt_entity(fighter).is_none());
+    }
+
+    /// A wizard who can smite attacks a goblin with a dagger, rolling the given
+    /// number on the d20
+    fn smiting_attack(d20: u8) -> (GameState, Entity) {
+        let (mut game_state, wizard, goblin) = fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::wizard,
+            fixtures::creatures::monsters::goblin_warrior,
+            1.5,
+        );
+        let wizard_class = ClassAndSubclass {
+            class: ClassId::new("nat20_core", "class.wizard"),
+            subclass: None,
+        };
+        systems::helpers::get_component_mut::<Spellbook>(&mut game_state.world, wizard)
+            .add_always_prepared(
+                &wizard_class,
+                &SpellId::new("nat20_core", "spell.divine_smite"),
+            )
+            .unwrap();
+        let dagger = ItemsRegistry::get(&ItemId::new("nat20_core", "item.dagger"))
+            .unwrap()
+            .clone();
+        let _ = systems::loadout::equip_in_slot(
+            &mut game_state.world,
+            wizard,
+            &EquipmentSlot::MeleeMainHand,
+            dagger,
+        );
+        game_state
+            .world
+            .insert_one(
+                wizard,
+                RollPolicy {
+                    average_damage: false,
+                    fixed_d20: Some(d20),
+                },
+            )
+            .unwrap();
+
+        let weapon_attack = ActionId::new("nat20_core", "action.weapon_attack");
+        let cost = systems::actions::get_action(&weapon_attack)
+            .unwrap()
+            .resource_cost()
+            .clone();
+        systems::actions::perform_action(
+            &mut game_state,
+            &ActionData::new(
+                wizard,
+                weapon_attack,
+                ActionContext::Weapon {
+                    slot: EquipmentSlot::MeleeMainHand,
+                },
+                cost,
+                vec![TargetInstance::Entity(goblin)],
+            ),
+        );
+        (game_state, wizard)
+    }
+
+    fn damage_rolls(game_state: &GameState, performer: Entity) -> Vec<DamageRollResult> {
+        game_state
+            .event_log
+            .events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::DamageRollPerformed(entity, damage_roll, _) if *entity == performer => {
+                    Some(damage_roll.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn smite(game_state: &mut GameState, wizard: Entity) {
+        let prompt = game_state.next_prompt_entity(wizard).unwrap().clone();
+        game_state
+            .submit_decision(ActionDecision {
+                response_to: prompt.id,
+                kind: ActionDecisionKind::Decision {
+                    chooser: wizard,
+                    option: 1,
+                },
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn smiting_is_decided_right_after_the_hit() {
+        let (mut game_state, wizard) = smiting_attack(19);
+
+        // The damage isn't rolled until the wizard has decided whether to smite
+        let prompt = game_state.next_prompt_entity(wizard).unwrap().clone();
+        let ActionPromptKind::Decision { request } = &prompt.kind else {
+            panic!("Expected a decision prompt, got {:?}", prompt.kind);
+        };
+        assert!(request.options.len() > 1);
+        assert!(damage_rolls(&game_state, wizard).is_empty());
+
+        smite(&mut game_state, wizard);
+
+        let damage_rolls = damage_rolls(&game_state, wizard);
+        assert_eq!(damage_rolls.len(), 1);
+        assert!(
+            damage_rolls[0]
+                .components
+                .iter()
+                .any(|component| component.damage_type == DamageType::Radiant)
+        );
+        // The wizard isn't offered the smite again when the damage is rolled
+        assert!(game_state.next_prompt_entity(wizard).is_none());
+    }
+    #[test]
+    fn smite_dice_are_doubled_on_a_critical_hit() {
+        for (d20, smite_dice) in [(19, 2), (20, 4)] {
+            let (mut game_state, wizard) = smiting_attack(d20);
+            smite(&mut game_state, wizard);
+
+            let damage_rolls = damage_rolls(&game_state, wizard);
+            assert_eq!(damage_rolls[0].crit, d20 == 20);
+            let radiant = damage_rolls[0]
+                .components
+                .iter()
+                .find(|component| component.damage_type == DamageType::Radiant)
+                .unwrap();
+            assert_eq!(radiant.result.rolls.len(), smite_dice);
+        }
+    }
+}
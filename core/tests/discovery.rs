@@ -0,0 +1,128 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            damage::DamageType,
+            dice::{DiceSet, DieSize},
+            health::hit_points::HitPoints,
+            hidden::{HiddenFeature, HiddenFeatureKind, Trap},
+            skill::Skill,
+        },
+        engine::{game_state::GameState, geometry::WorldPath},
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup() -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::origin());
+        (game_state, fighter)
+    }
+
+    fn feature(game_state: &mut GameState, kind: HiddenFeatureKind, dc: i32, x: f32) -> Entity {
+        systems::discovery::spawn(
+            game_state,
+            "Hidden Thing",
+            HiddenFeature::new(kind, dc, ""),
+            &Point3::new(x, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn passive_perception_notices_nearby_features() {
+        let (mut game_state, fighter) = setup();
+        let passive_perception =
+            systems::discovery::passive_score(&game_state.world, fighter, Skill::Perception)
+                .unwrap();
+
+        let obvious = feature(
+            &mut game_state,
+            HiddenFeatureKind::SecretDoor,
+            passive_perception,
+            2.0,
+        );
+        let subtle = feature(
+            &mut game_state,
+            HiddenFeatureKind::Lever,
+            passive_perception + 1,
+            2.0,
+        );
+        let far_away = feature(&mut game_state, HiddenFeatureKind::SecretDoor, 0, 15.0);
+        assert!(systems::discovery::is_hidden(&game_state.world, obvious));
+
+        assert_eq!(
+            systems::discovery::notice(&mut game_state, fighter),
+            vec![obvious]
+        );
+        assert!(!systems::discovery::is_hidden(&game_state.world, obvious));
+        assert!(systems::discovery::is_hidden(&game_state.world, subtle));
+        assert!(systems::discovery::is_hidden(&game_state.world, far_away));
+
+        // Features are only found once
+        assert!(systems::discovery::notice(&mut game_state, fighter).is_empty());
+    }
+
+    #[test]
+    fn search_finds_features_in_range() {
+        let (mut game_state, fighter) = setup();
+        let lever = feature(&mut game_state, HiddenFeatureKind::Lever, 0, 6.0);
+        let clue = feature(&mut game_state, HiddenFeatureKind::Clue, 0, 6.0);
+        let far_away = feature(&mut game_state, HiddenFeatureKind::Clue, 0, 15.0);
+
+        // Natural 1s always fail, so it might take a few tries
+        for _ in 0..100 {
+            if !systems::discovery::is_hidden(&game_state.world, lever)
+                && !systems::discovery::is_hidden(&game_state.world, clue)
+            {
+                break;
+            }
+            systems::discovery::search(&mut game_state, fighter);
+        }
+        assert!(!systems::discovery::is_hidden(&game_state.world, lever));
+        assert!(!systems::discovery::is_hidden(&game_state.world, clue));
+        assert!(systems::discovery::is_hidden(&game_state.world, far_away));
+    }
+
+    #[test]
+    fn monsters_do_not_notice_features() {
+        let mut game_state = fixtures::engine::game_state();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, goblin, &Point3::origin());
+        let door = feature(&mut game_state, HiddenFeatureKind::SecretDoor, 0, 2.0);
+
+        assert!(systems::discovery::notice(&mut game_state, goblin).is_empty());
+        assert!(systems::discovery::is_hidden(&game_state.world, door));
+    }
+
+    #[test]
+    fn hidden_trap_goes_off_once() {
+        let (mut game_state, fighter) = setup();
+        let trap = systems::traps::spawn(
+            &mut game_state,
+            Trap {
+                damage: (DiceSet::new(2, DieSize::D10), DamageType::Piercing),
+                dc: 100,
+            },
+            &Point3::new(3.0, 0.0, 0.0),
+        );
+        let hit_points = |game_state: &GameState| {
+            systems::helpers::get_component::<HitPoints>(&game_state.world, fighter).current()
+        };
+        let hp_before = hit_points(&game_state);
+
+        let path = WorldPath::new(vec![Point3::origin(), Point3::new(6.0, 0.0, 0.0)]);
+        systems::traps::on_enter(&mut game_state, fighter, &path);
+        assert!(hit_points(&game_state) < hp_before);
+        assert!(!systems::discovery::is_hidden(&game_state.world, trap));
+
+        // Once it's been found, the trap can be stepped around
+        let hp_after = hit_points(&game_state);
+        let path = WorldPath::new(vec![Point3::new(6.0, 0.0, 0.0), Point3::origin()]);
+        systems::traps::on_enter(&mut game_state, fighter, &path);
+        assert_eq!(hit_points(&game_state), hp_after);
+    }
+}
@@ -0,0 +1,179 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            ability::Ability,
+            d20::D20CheckOverride,
+            disease::{Disease, DiseaseOutcome, Diseases},
+            effects::effect::EffectTag,
+            id::EffectId,
+            modifier::ModifierSource,
+            saving_throw::{SavingThrowKind, SavingThrowSet},
+            time::TimeDuration,
+        },
+        engine::game_state::GameState,
+        systems::{
+            self,
+            time::{RestKind, TimeActivity},
+        },
+        test_utils::fixtures,
+    };
+
+    fn setup(disease: Disease) -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::disease::infect(
+            &mut game_state.world,
+            fighter,
+            disease,
+            &ModifierSource::Custom("Test".to_string()),
+        );
+        (game_state, fighter)
+    }
+
+    /// Make the saving throws against the disease always succeed or fail
+    fn rig_saving_throws(game_state: &mut GameState, entity: Entity, outcome: D20CheckOverride) {
+        let mut saving_throws =
+            systems::helpers::get_component_mut::<SavingThrowSet>(&mut game_state.world, entity);
+        let constitution = SavingThrowKind::Ability(Ability::Constitution);
+        let source = ModifierSource::Custom("Rigged".to_string());
+        saving_throws.remove_override(&constitution, &source);
+        saving_throws.add_override(&constitution, outcome, source);
+    }
+
+    fn has_effect(game_state: &GameState, entity: Entity, effect: &str) -> bool {
+        systems::effects::has_effect(
+            &game_state.world,
+            entity,
+            &EffectId::new("nat20_core", effect),
+        )
+    }
+
+    fn pass_hours(game_state: &mut GameState, hours: u32) {
+        systems::time::pass_time(
+            game_state,
+            TimeDuration::from_hours(hours),
+            TimeActivity::Downtime,
+        )
+        .unwrap();
+    }
+
+    fn long_rest(game_state: &mut GameState, entity: Entity) {
+        systems::time::start_rest(game_state, vec![entity], &RestKind::Long).unwrap();
+        systems::time::finish_rest(game_state, vec![entity]).unwrap();
+    }
+
+    #[test]
+    fn sight_rot_worsens_on_failed_saves() {
+        let (mut game_state, fighter) = setup(Disease::SightRot);
+        assert!(!has_effect(
+            &game_state,
+            fighter,
+            "effect.disease.sight_rot.symptoms_1"
+        ));
+
+        pass_hours(&mut game_state, 24);
+        assert!(has_effect(
+            &game_state,
+            fighter,
+            "effect.disease.sight_rot.symptoms_1"
+        ));
+
+        rig_saving_throws(&mut game_state, fighter, D20CheckOverride::AutoFailure);
+        pass_hours(&mut game_state, 24);
+        assert!(!has_effect(
+            &game_state,
+            fighter,
+            "effect.disease.sight_rot.symptoms_1"
+        ));
+        assert!(has_effect(
+            &game_state,
+            fighter,
+            "effect.disease.sight_rot.symptoms_2"
+        ));
+    }
+
+    #[test]
+    fn lesser_restoration_cures_disease_and_symptoms() {
+        let (mut game_state, fighter) = setup(Disease::SightRot);
+        pass_hours(&mut game_state, 24);
+
+        // Same as the payload of Lesser Restoration
+        systems::effects::remove_effects_with_tags(
+            &mut game_state.world,
+            fighter,
+            &[EffectTag::Disease],
+        );
+        assert!(systems::disease::diseases(&game_state.world, fighter).is_empty());
+        assert!(!has_effect(
+            &game_state,
+            fighter,
+            "effect.disease.sight_rot.symptoms_1"
+        ));
+
+        pass_hours(&mut game_state, 24);
+        assert!(game_state.world.get::<&Diseases>(fighter).is_err());
+    }
+
+    #[test]
+    fn rest_streak_cures_sewer_plague() {
+        let (mut game_state, fighter) = setup(Disease::SewerPlague);
+        let outcomes = systems::disease::on_dawn(&mut game_state.world, fighter);
+        assert_eq!(
+            outcomes,
+            vec![(Disease::SewerPlague, DiseaseOutcome::SymptomsAppear)]
+        );
+        assert!(has_effect(
+            &game_state,
+            fighter,
+            "effect.condition.exhaustion"
+        ));
+
+        // Every failed save adds another level of exhaustion
+        rig_saving_throws(&mut game_state, fighter, D20CheckOverride::AutoFailure);
+        for _ in 0..2 {
+            let outcomes = systems::disease::on_dawn(&mut game_state.world, fighter);
+            assert_eq!(
+                outcomes,
+                vec![(Disease::SewerPlague, DiseaseOutcome::Worsened { stage: 0 })]
+            );
+        }
+
+        rig_saving_throws(&mut game_state, fighter, D20CheckOverride::AutoSuccess);
+
+        // Succeeding without resting doesn't count towards recovering
+        let outcomes = systems::disease::on_dawn(&mut game_state.world, fighter);
+        assert_eq!(
+            outcomes,
+            vec![(Disease::SewerPlague, DiseaseOutcome::Resisted { streak: 0 })]
+        );
+
+        long_rest(&mut game_state, fighter);
+        let outcomes = systems::disease::on_dawn(&mut game_state.world, fighter);
+        assert_eq!(
+            outcomes,
+            vec![(Disease::SewerPlague, DiseaseOutcome::Resisted { streak: 1 })]
+        );
+
+        long_rest(&mut game_state, fighter);
+        let outcomes = systems::disease::on_dawn(&mut game_state.world, fighter);
+        assert_eq!(
+            outcomes,
+            vec![(Disease::SewerPlague, DiseaseOutcome::Recovered)]
+        );
+        assert!(systems::disease::diseases(&game_state.world, fighter).is_empty());
+
+        // The exhaustion has to be recovered from separately, one level for
+        // each long rest
+        assert_eq!(
+            systems::effects::stacks(
+                &game_state.world,
+                fighter,
+                &EffectId::new("nat20_core", "effect.condition.exhaustion")
+            ),
+            1
+        );
+    }
+}
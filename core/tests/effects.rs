@@ -0,0 +1,258 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            ability::Ability,
+            d20::RollMode,
+            effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate},
+            health::life_state::LifeState,
+            id::EffectId,
+            modifier::{Modifiable, ModifierSource},
+            saving_throw::{SavingThrowKind, SavingThrowSet},
+            skill::{Skill, SkillSet},
+            speed::Speed,
+            time::TimeDuration,
+        },
+        systems::{self, time::RestKind},
+        test_utils::fixtures,
+    };
+    use uom::si::length::foot;
+
+    fn add_effect(world: &mut World, entity: Entity, effect_id: &EffectId, duration: TimeDuration) {
+        systems::effects::add_effect_template(
+            world,
+            entity,
+            entity,
+            ModifierSource::Custom("Test".to_string()),
+            &EffectInstanceTemplate {
+                effect_id: effect_id.clone(),
+                lifetime: EffectLifetimeTemplate::Duration(duration),
+            },
+            None,
+        );
+    }
+
+    fn athletics_roll_mode(world: &World, entity: Entity) -> RollMode {
+        systems::helpers::get_component::<SkillSet>(world, entity)
+            .check(&Skill::Athletics, world, entity)
+            .advantage_tracker
+            .roll_mode()
+    }
+
+    #[test]
+    fn same_effect_keeps_longest_duration() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let poisoned = EffectId::new("nat20_core", "effect.condition.poisoned");
+
+        add_effect(
+            &mut world,
+            fighter,
+            &poisoned,
+            TimeDuration::from_minutes(1),
+        );
+        add_effect(&mut world, fighter, &poisoned, TimeDuration::from_hours(1));
+        add_effect(
+            &mut world,
+            fighter,
+            &poisoned,
+            TimeDuration::from_minutes(1),
+        );
+
+        let effects = systems::effects::effects(&world, fighter);
+        let instances: Vec<_> = effects
+            .iter()
+            .filter(|effect| effect.effect_id == poisoned)
+            .collect();
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].stacks, 1);
+        assert_eq!(
+            instances[0].lifetime.remaining(),
+            Some(&TimeDuration::from_hours(1))
+        );
+    }
+
+    #[test]
+    fn exhaustion_stacks_up_to_max() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let exhaustion = EffectId::new("nat20_core", "effect.condition.exhaustion");
+
+        for _ in 0..8 {
+            add_effect(
+                &mut world,
+                fighter,
+                &exhaustion,
+                TimeDuration::from_hours(1),
+            );
+        }
+
+        let effects = systems::effects::effects(&world, fighter);
+        let instances: Vec<_> = effects
+            .iter()
+            .filter(|effect| effect.effect_id == exhaustion)
+            .collect();
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].stacks, 6);
+        drop(effects);
+
+        // The sixth level of exhaustion is fatal
+        assert_eq!(
+            *systems::helpers::get_component::<LifeState>(&world, fighter),
+            LifeState::Dead
+        );
+    }
+
+    #[test]
+    fn exhaustion_penalties_grow_with_each_level() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let exhaustion = EffectId::new("nat20_core", "effect.condition.exhaustion");
+        let penalties = |world: &World| {
+            let athletics = systems::helpers::get_component::<SkillSet>(world, fighter)
+                .get(&Skill::Athletics)
+                .modifiers()
+                .total();
+            let strength_save = systems::helpers::get_component::<SavingThrowSet>(world, fighter)
+                .get(&SavingThrowKind::Ability(Ability::Strength))
+                .modifiers()
+                .total();
+            let speed = systems::helpers::get_component::<Speed>(world, fighter)
+                .get_total_speed()
+                .get::<foot>()
+                .round() as i32;
+            (athletics, strength_save, speed)
+        };
+        let (athletics, strength_save, speed) = penalties(&world);
+
+        for _ in 0..3 {
+            add_effect(
+                &mut world,
+                fighter,
+                &exhaustion,
+                TimeDuration::from_hours(1),
+            );
+        }
+        assert_eq!(
+            penalties(&world),
+            (athletics - 6, strength_save - 6, speed - 15)
+        );
+
+        // Every long rest removes one level
+        systems::time::on_rest_end(&mut world, &[fighter], &RestKind::Long);
+        assert_eq!(systems::effects::stacks(&world, fighter, &exhaustion), 2);
+        assert_eq!(
+            penalties(&world),
+            (athletics - 4, strength_save - 4, speed - 10)
+        );
+
+        systems::time::on_rest_end(&mut world, &[fighter], &RestKind::Long);
+        systems::time::on_rest_end(&mut world, &[fighter], &RestKind::Long);
+        assert!(!systems::effects::has_effect(&world, fighter, &exhaustion));
+        assert_eq!(penalties(&world), (athletics, strength_save, speed));
+    }
+
+    #[test]
+    fn suppressed_effect_does_nothing() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let poisoned = EffectId::new("nat20_core", "effect.condition.poisoned");
+        let antimagic = ModifierSource::Custom("Antimagic".to_string());
+
+        add_effect(
+            &mut world,
+            fighter,
+            &poisoned,
+            TimeDuration::from_minutes(1),
+        );
+        assert_eq!(athletics_roll_mode(&world, fighter), RollMode::Disadvantage);
+
+        systems::effects::suppress_effects(&mut world, fighter, &antimagic, |effect| {
+            effect.effect_id == poisoned
+        });
+        assert!(systems::effects::has_effect(&world, fighter, &poisoned));
+        assert!(!systems::effects::is_effect_active(
+            &world, fighter, &poisoned
+        ));
+        assert_eq!(athletics_roll_mode(&world, fighter), RollMode::Normal);
+
+        systems::effects::unsuppress_effects(&mut world, fighter, &antimagic);
+        assert!(systems::effects::is_effect_active(
+            &world, fighter, &poisoned
+        ));
+        assert_eq!(athletics_roll_mode(&world, fighter), RollMode::Disadvantage);
+    }
+
+    #[test]
+    fn bless_and_bane_roll_dice_modifiers() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let save = SavingThrowKind::Ability(Ability::Wisdom);
+        let save_dice_rolls = |world: &World| {
+            systems::helpers::get_component::<SavingThrowSet>(world, fighter)
+                .check(&save, world, fighter)
+                .dice_rolls
+                .into_values()
+                .map(|roll| roll.value())
+                .collect::<Vec<_>>()
+        };
+        assert!(save_dice_rolls(&world).is_empty());
+
+        add_effect(
+            &mut world,
+            fighter,
+            &EffectId::new("nat20_core", "effect.spell.bless"),
+            TimeDuration::from_minutes(1),
+        );
+        let rolls = save_dice_rolls(&world);
+        assert_eq!(rolls.len(), 1);
+        assert!((1..=4).contains(&rolls[0]));
+
+        systems::effects::remove_effect(
+            &mut world,
+            fighter,
+            &EffectId::new("nat20_core", "effect.spell.bless"),
+        );
+        add_effect(
+            &mut world,
+            fighter,
+            &EffectId::new("nat20_core", "effect.spell.bane"),
+            TimeDuration::from_minutes(1),
+        );
+        let rolls = save_dice_rolls(&world);
+        assert_eq!(rolls.len(), 1);
+        assert!((-4..=-1).contains(&rolls[0]));
+    }
+
+    #[test]
+    fn guidance_adds_dice_to_skill_checks() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        add_effect(
+            &mut world,
+            fighter,
+            &EffectId::new("nat20_core", "effect.spell.guidance"),
+            TimeDuration::from_minutes(1),
+        );
+
+        let skills = systems::helpers::get_component::<SkillSet>(&world, fighter);
+        let result = skills.check(&Skill::Athletics, &world, fighter);
+        assert_eq!(result.dice_rolls.len(), 1);
+        let bonus = result.dice_rolls.values().next().unwrap().value();
+        assert!((1..=4).contains(&bonus));
+        assert_eq!(
+            result.total() as i32,
+            result.selected_roll as i32 + result.modifier_breakdown.total() + bonus
+        );
+        // The dice are listed apart from the flat bonuses before rolling
+        let explanation = skills.explain(&Skill::Athletics, &world, fighter);
+        assert!(
+            explanation
+                .modifiers
+                .dice_modifiers()
+                .any(|(_, dice)| dice.to_string() == "+1d4")
+        );
+    }
+}
@@ -0,0 +1,26 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::collections::HashSet;
+
+    use nat20_core::test_utils::fixtures;
+
+    #[test]
+    fn add_to_running_encounter() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+
+        let encounter_id = game_state.start_encounter(HashSet::from([fighter]));
+        game_state.add_to_encounter(&encounter_id, goblin);
+
+        assert_eq!(
+            game_state.encounter_for_entity(&goblin),
+            Some(&encounter_id)
+        );
+        let encounter = game_state.encounter(&encounter_id).unwrap();
+        assert_eq!(encounter.initiative_order().len(), 2);
+        // Adding a participant doesn't change whose turn it is
+        assert_eq!(encounter.current_entity(), fighter);
+    }
+}
@@ -0,0 +1,394 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::str::FromStr;
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{
+                action::{ActionContext, ActionKindResult, ReactionResult},
+                modification::{EventModification, ModificationDelta, ModifiedValue},
+                targeting::TargetInstance,
+            },
+            dice::{DiceSet, DieSize, RollPolicy},
+            health::hit_points::HitPoints,
+            id::{ActionId, ClassId, Name},
+            items::equipment::slots::EquipmentSlot,
+            modifier::{DiceModifier, DiceModifierRoll, Modifiable, ModifierSource},
+        },
+        engine::{
+            event::{
+                ActionData, ActionDecision, ActionDecisionKind, ActionPromptKind, Event, EventKind,
+            },
+            game_state::GameState,
+        },
+        entities::character::Character,
+        systems::{
+            self,
+            d20::{D20CheckDCKind, D20ResultKind},
+        },
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            1.5,
+        )
+    }
+
+    fn attack_event(game_state: &GameState, attacker: Entity, target: Entity) -> Event {
+        let result = systems::damage::attack_roll_weapon(
+            &game_state.world,
+            attacker,
+            target,
+            &EquipmentSlot::MeleeMainHand,
+        );
+        Event::new(EventKind::D20CheckPerformed(
+            attacker,
+            D20ResultKind::AttackRoll { result },
+            D20CheckDCKind::AttackRoll(
+                target,
+                systems::loadout::armor_class(&game_state.world, target),
+            ),
+        ))
+    }
+
+    fn damage_event(game_state: &GameState, attacker: Entity, target: Entity) -> Event {
+        let damage_roll = systems::damage::damage_roll_weapon(
+            &game_state.world,
+            attacker,
+            &EquipmentSlot::MeleeMainHand,
+            false,
+        );
+        Event::new(EventKind::DamageRollPerformed(
+            attacker,
+            damage_roll,
+            target,
+        ))
+    }
+
+    fn source(reaction: &str) -> ModifierSource {
+        ModifierSource::Action(ActionId::new("nat20_core", reaction))
+    }
+
+    fn d20_total(event: &Event) -> i32 {
+        match &event.kind {
+            EventKind::D20CheckPerformed(_, result, _) => result.d20_result().total() as i32,
+            _ => panic!("Expected a d20 check, got {:?}", event),
+        }
+    }
+
+    fn damage_total(event: &Event) -> i32 {
+        match &event.kind {
+            EventKind::DamageRollPerformed(_, damage_roll, _) => damage_roll.total,
+            _ => panic!("Expected a damage roll, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn armor_class_bonus_only_applies_to_the_modified_attack() {
+        let (game_state, fighter, goblin) = setup();
+        let mut event = attack_event(&game_state, fighter, goblin);
+        let armor_class = systems::loadout::armor_class(&game_state.world, goblin).total();
+
+        let delta = EventModification::DCBonus {
+            source: source("action.test.shield"),
+            bonus: 5,
+        }
+        .apply(&mut event);
+
+        assert_eq!(
+            delta,
+            Some(ModificationDelta {
+                value: ModifiedValue::ArmorClass,
+                before: armor_class,
+                after: armor_class + 5,
+            })
+        );
+        let EventKind::D20CheckPerformed(_, _, D20CheckDCKind::AttackRoll(_, rolled_against)) =
+            &event.kind
+        else {
+            panic!("Expected an attack roll, got {:?}", event);
+        };
+        assert_eq!(rolled_against.total(), armor_class + 5);
+        assert_eq!(
+            systems::loadout::armor_class(&game_state.world, goblin).total(),
+            armor_class
+        );
+    }
+
+    #[test]
+    fn penalty_dice_are_subtracted_from_the_roll() {
+        let (game_state, fighter, goblin) = setup();
+        let mut event = attack_event(&game_state, fighter, goblin);
+        let before = d20_total(&event);
+
+        let delta = EventModification::D20Dice {
+            source: source("action.test.cutting_words"),
+            roll: DiceModifierRoll {
+                modifier: DiceModifier::penalty(DiceSet::from_str("1d6").unwrap()),
+                rolls: vec![4],
+            },
+        }
+        .apply(&mut event)
+        .unwrap();
+
+        assert_eq!(delta.value, ModifiedValue::AttackRoll);
+        assert_eq!(delta.before, before);
+        assert_eq!(delta.after, (before - 4).max(0));
+        assert_eq!(d20_total(&event), delta.after);
+    }
+
+    #[test]
+    fn damage_reduction_never_goes_below_zero() {
+        let (game_state, fighter, goblin) = setup();
+        let mut event = damage_event(&game_state, fighter, goblin);
+        let before = damage_total(&event);
+
+        let delta = EventModification::ReduceDamage {
+            source: source("action.test.cutting_words"),
+            amount: 1,
+        }
+        .apply(&mut event)
+        .unwrap();
+        assert_eq!((delta.before, delta.after), (before, before - 1));
+
+        let delta = EventModification::ReduceDamage {
+            source: source("action.test.cutting_words"),
+            amount: 100,
+        }
+        .apply(&mut event)
+        .unwrap();
+        assert_eq!(delta.after, 0);
+        assert_eq!(damage_total(&event), 0);
+        let EventKind::DamageRollPerformed(_, damage_roll, _) = &event.kind else {
+            unreachable!();
+        };
+        assert!(
+            damage_roll
+                .components
+                .iter()
+                .all(|component| component.result.subtotal == 0)
+        );
+    }
+
+    #[test]
+    fn modifications_only_apply_to_matching_events() {
+        let (game_state, fighter, goblin) = setup();
+        let event = attack_event(&game_state, fighter, goblin);
+        let extra_damage = damage_event(&game_state, fighter, goblin);
+        let EventKind::DamageRollPerformed(_, extra_damage, _) = extra_damage.kind else {
+            unreachable!();
+        };
+
+        let add_damage = EventModification::AddDamage {
+            components: extra_damage.components,
+        };
+        let mut modified = event.clone();
+        assert_eq!(add_damage.apply(&mut modified), None);
+        assert_eq!(modified, event);
+
+        // Previewing a modification leaves the event as it was
+        let bonus = EventModification::D20Bonus {
+            source: source("action.test.tactical_mind"),
+            bonus: 3,
+        };
+        let delta = bonus.preview(&event).unwrap();
+        assert_eq!(delta.after, delta.before + 3);
+        assert_eq!(d20_total(&event), delta.before);
+    }
+
+    fn fix_d20(game_state: &mut GameState, entity: Entity, d20: u8) {
+        game_state
+            .world
+            .insert_one(
+                entity,
+                RollPolicy {
+                    average_damage: false,
+                    fixed_d20: Some(d20),
+                },
+            )
+            .unwrap();
+    }
+
+    /// Make the attacker's next attack against the target beat its AC by
+    /// exactly the margin
+    fn fix_attack_roll(game_state: &mut GameState, attacker: Entity, target: Entity, margin: i32) {
+        let attack_bonus = systems::damage::attack_roll_weapon(
+            &game_state.world,
+            attacker,
+            target,
+            &EquipmentSlot::MeleeMainHand,
+        )
+        .roll_result
+        .total_modifier();
+        let armor_class = systems::loadout::armor_class(&game_state.world, target).total();
+        let d20 = armor_class + margin - attack_bonus;
+        assert!((2..20).contains(&d20), "Can't hit by {} with a d20", margin);
+        fix_d20(game_state, attacker, d20 as u8);
+    }
+
+    fn weapon_attack(game_state: &mut GameState, attacker: Entity, target: Entity) {
+        let weapon_attack = ActionId::new("nat20_core", "action.weapon_attack");
+        let cost = systems::actions::get_action(&weapon_attack)
+            .unwrap()
+            .resource_cost()
+            .clone();
+        systems::actions::perform_action(
+            game_state,
+            &ActionData::new(
+                attacker,
+                weapon_attack,
+                ActionContext::Weapon {
+                    slot: EquipmentSlot::MeleeMainHand,
+                },
+                cost,
+                vec![TargetInstance::Entity(target)],
+            ),
+        );
+    }
+
+    fn reaction_options(game_state: &GameState, reactor: Entity) -> Vec<ActionId> {
+        match game_state
+            .next_prompt_entity(reactor)
+            .map(|prompt| &prompt.kind)
+        {
+            Some(ActionPromptKind::Reactions { options, .. }) => options
+                .get(&reactor)
+                .into_iter()
+                .flatten()
+                .map(|reaction| reaction.reaction_id.clone())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Answer the pending reaction prompt, with the reactor using the reaction
+    /// (if any) and everyone else passing
+    fn react(game_state: &mut GameState, reactor: Entity, reaction: Option<&str>) {
+        let prompt = game_state.next_prompt_entity(reactor).unwrap().clone();
+        let ActionPromptKind::Reactions { event, options } = &prompt.kind else {
+            panic!("Expected a reaction prompt, got {:?}", prompt.kind);
+        };
+        for (entity, reactions) in options {
+            let choice = reaction.filter(|_| *entity == reactor).map(|reaction| {
+                reactions
+                    .iter()
+                    .find(|data| data.reaction_id == ActionId::new("nat20_core", reaction))
+                    .cloned()
+                    .expect("The reactor should be able to use the reaction")
+            });
+            game_state
+                .submit_decision(ActionDecision {
+                    response_to: prompt.id,
+                    kind: ActionDecisionKind::Reaction {
+                        event: event.clone(),
+                        reactor: *entity,
+                        choice,
+                    },
+                })
+                .unwrap();
+        }
+    }
+
+    fn hit_points(game_state: &GameState, entity: Entity) -> u32 {
+        systems::helpers::get_component::<HitPoints>(&game_state.world, entity).current()
+    }
+
+    #[test]
+    fn shield_turns_a_hit_into_a_miss() {
+        for shield in [false, true] {
+            let (mut game_state, wizard, goblin) = fixtures::engine::game_state_with_pair(
+                fixtures::creatures::heroes::wizard,
+                fixtures::creatures::monsters::goblin_warrior,
+                1.5,
+            );
+            fix_attack_roll(&mut game_state, goblin, wizard, 0);
+            let hit_points_before = hit_points(&game_state, wizard);
+
+            weapon_attack(&mut game_state, goblin, wizard);
+            assert!(
+                reaction_options(&game_state, wizard)
+                    .contains(&ActionId::new("nat20_core", "action.shield"))
+            );
+            react(&mut game_state, wizard, shield.then_some("action.shield"));
+
+            assert_eq!(hit_points(&game_state, wizard) == hit_points_before, shield);
+        }
+    }
+
+    /// A bard of the given level, who gets the average of every die
+    fn bard(game_state: &mut GameState, level: u8, position: Point3<f32>) -> Entity {
+        let bard = game_state
+            .world
+            .spawn(Character::new(Name::new("Johnny Bard")));
+        systems::quick_build::level_up_to(
+            &mut game_state.world,
+            bard,
+            &ClassId::new("nat20_core", "class.bard"),
+            None,
+            level,
+        )
+        .unwrap();
+        systems::geometry::teleport_to(&mut game_state.world, bard, &position);
+        fix_d20(game_state, bard, 10);
+        bard
+    }
+
+    fn cutting_words() -> ActionId {
+        ActionId::new("nat20_core", "action.bard.cutting_words")
+    }
+
+    #[test]
+    fn cutting_words_turns_a_hit_into_a_miss() {
+        // The Bardic Inspiration die grows with the bard
+        for (level, die_size) in [(3, DieSize::D6), (5, DieSize::D8)] {
+            let (mut game_state, fighter, goblin) = setup();
+            let bard = bard(&mut game_state, level, Point3::new(0.0, 0.0, 3.0));
+            fix_attack_roll(&mut game_state, goblin, fighter, 1);
+            let hit_points_before = hit_points(&game_state, fighter);
+
+            weapon_attack(&mut game_state, goblin, fighter);
+            assert!(reaction_options(&game_state, bard).contains(&cutting_words()));
+            react(&mut game_state, bard, Some("action.bard.cutting_words"));
+
+            let penalty = game_state
+                .event_log
+                .events
+                .iter()
+                .find_map(|event| match &event.kind {
+                    EventKind::ActionPerformed { results, .. } => {
+                        results.iter().find_map(|result| match &result.kind {
+                            ActionKindResult::Reaction {
+                                result:
+                                    ReactionResult::ModifyEvent {
+                                        modification: EventModification::D20Dice { roll, .. },
+                                        ..
+                                    },
+                            } => Some(roll.clone()),
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                })
+                .expect("Cutting Words should penalize the attack roll");
+            assert_eq!(penalty.modifier.dice.die_size, die_size);
+            assert!(penalty.modifier.penalty);
+            assert_eq!(hit_points(&game_state, fighter), hit_points_before);
+        }
+    }
+
+    #[test]
+    fn cutting_words_needs_the_creature_within_60_feet() {
+        let (mut game_state, fighter, goblin) = setup();
+        let bard = bard(&mut game_state, 3, Point3::new(0.0, 0.0, 30.0));
+        fix_attack_roll(&mut game_state, goblin, fighter, 1);
+
+        weapon_attack(&mut game_state, goblin, fighter);
+        assert!(!reaction_options(&game_state, bard).contains(&cutting_words()));
+    }
+}
@@ -0,0 +1,24 @@
+extern crate nat20_core;
+
+mod tests {
+    use nat20_core::{
+        systems::{self, time::RestKind},
+        test_utils::fixtures,
+    };
+
+    #[test]
+    fn rest_events_reach_subscribers() {
+        let mut game_state = fixtures::engine::game_state();
+        let entity = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let events = game_state.subscribe_events();
+
+        systems::time::start_rest(&mut game_state, vec![entity], &RestKind::Short).unwrap();
+        systems::time::finish_rest(&mut game_state, vec![entity]).unwrap();
+
+        let kinds = events
+            .try_iter()
+            .map(|event| event.kind.name())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["RestStarted", "RestFinished"]);
+    }
+}
@@ -0,0 +1,91 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            actions::{action::ActionContext, targeting::TargetInstance},
+            class::ClassAndSubclass,
+            faction::{Attitude, FactionSet},
+            id::{ActionId, ClassId, FactionId, SpellId},
+            resource::ResourceAmountMap,
+            spells::spellbook::SpellSource,
+        },
+        engine::event::ActionData,
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn guard(world: &mut World) -> Entity {
+        let guard = fixtures::creatures::monsters::goblin_warrior(world).id();
+        systems::helpers::set_component(
+            world,
+            guard,
+            FactionSet::from([FactionId::new("nat20_core", "faction.town_guard")]),
+        );
+        guard
+    }
+
+    #[test]
+    fn town_guard_attitudes() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut world).id();
+        let guard = guard(&mut world);
+
+        assert_eq!(
+            systems::factions::attitude_from_to(&world, guard, goblin),
+            Attitude::Hostile
+        );
+        assert_eq!(
+            systems::factions::attitude_from_to(&world, guard, fighter),
+            Attitude::Friendly
+        );
+        assert_eq!(
+            systems::factions::mutual_attitude(&world, fighter, guard),
+            Attitude::Friendly
+        );
+        assert_eq!(
+            systems::factions::allies(&world, guard, &[fighter, goblin]),
+            vec![fighter]
+        );
+    }
+
+    #[test]
+    fn fireball_warns_about_friendly_fire() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::new(8.0, 0.0, 1.0));
+        systems::geometry::teleport_to(&mut game_state.world, goblin, &Point3::new(8.0, 0.0, 0.0));
+
+        let fireball = |target: Point3<f32>| {
+            ActionData::new(
+                wizard,
+                ActionId::new("nat20_core", "action.fireball"),
+                ActionContext::Spell {
+                    id: SpellId::new("nat20_core", "spell.fireball"),
+                    source: SpellSource::Class(ClassAndSubclass {
+                        class: ClassId::new("nat20_core", "class.wizard"),
+                        subclass: None,
+                    }),
+                    level: 3,
+                    metamagic: Vec::new(),
+                },
+                ResourceAmountMap::new(),
+                vec![TargetInstance::Point(target)],
+            )
+        };
+
+        let friendly_fire =
+            systems::actions::friendly_fire(&game_state, &fireball(Point3::new(8.0, 0.0, 0.0)));
+        assert_eq!(friendly_fire, vec![fighter]);
+
+        let friendly_fire =
+            systems::actions::friendly_fire(&game_state, &fireball(Point3::new(30.0, 0.0, 0.0)));
+        assert!(friendly_fire.is_empty());
+    }
+}
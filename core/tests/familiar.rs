@@ -0,0 +1,229 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            actions::{
+                action::{ActionContext, ActionKindResult, UtilityOutcome},
+                targeting::TargetInstance,
+            },
+            class::ClassAndSubclass,
+            faction::FactionSet,
+            id::{ActionId, ClassId, MonsterId, Name, SpellId},
+            items::equipment::slots::EquipmentSlot,
+            resource::ResourceAmountMap,
+            spells::spellbook::SpellSource,
+        },
+        engine::{
+            event::{ActionData, EventKind},
+            game_state::GameState,
+        },
+        systems::{
+            self, actions::ActionUsabilityError, familiar::FamiliarError, geometry::CreaturePose,
+        },
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        let wizard = fixtures::creatures::heroes::wizard(&mut world).id();
+        let familiar = systems::familiar::summon(
+            &mut world,
+            wizard,
+            &MonsterId::new("nat20_core", "monster.wolf"),
+            &Point3::new(1.0, 0.0, 0.0),
+        )
+        .unwrap();
+        (world, wizard, familiar)
+    }
+
+    #[test]
+    fn summon_familiar() {
+        let (world, wizard, familiar) = setup();
+
+        assert_eq!(systems::familiar::familiar(&world, wizard), Some(familiar));
+        assert_eq!(systems::familiar::owner(&world, familiar), Some(wizard));
+        assert_eq!(
+            *systems::helpers::get_component::<FactionSet>(&world, familiar),
+            *systems::helpers::get_component::<FactionSet>(&world, wizard)
+        );
+        assert!(
+            systems::actions::all_actions(&world, wizard)
+                .contains_key(&systems::familiar::shared_senses_action())
+        );
+    }
+
+    #[test]
+    fn summon_replaces_previous_familiar() {
+        let (mut world, wizard, familiar) = setup();
+        let new_familiar = systems::familiar::summon(
+            &mut world,
+            wizard,
+            &MonsterId::new("nat20_core", "monster.wolf"),
+            &Point3::new(-1.0, 0.0, 0.0),
+        )
+        .unwrap();
+
+        assert!(!world.contains(familiar));
+        assert_eq!(
+            systems::familiar::familiar(&world, wizard),
+            Some(new_familiar)
+        );
+    }
+
+    #[test]
+    fn familiar_cannot_attack() {
+        let (world, _, familiar) = setup();
+
+        assert_eq!(
+            systems::actions::action_usable(
+                &world,
+                familiar,
+                &ActionId::new("nat20_core", "action.weapon_attack"),
+                &ActionContext::Weapon {
+                    slot: EquipmentSlot::MeleeMainHand
+                },
+                &ResourceAmountMap::new(),
+            ),
+            Err(ActionUsabilityError::FamiliarCannotAttack)
+        );
+        assert!(
+            systems::actions::action_usable(
+                &world,
+                familiar,
+                &ActionId::new("nat20_core", "action.dash"),
+                &ActionContext::Other,
+                &ResourceAmountMap::new(),
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn dismiss_and_recall_familiar() {
+        let (mut world, _, familiar) = setup();
+
+        systems::familiar::dismiss(&mut world, familiar).unwrap();
+        assert!(world.get::<&CreaturePose>(familiar).is_err());
+        assert!(matches!(
+            systems::actions::action_usable(
+                &world,
+                familiar,
+                &ActionId::new("nat20_core", "action.dash"),
+                &ActionContext::Other,
+                &ResourceAmountMap::new(),
+            ),
+            Err(ActionUsabilityError::Dismissed(_))
+        ));
+
+        // Familiars have to reappear within 30 feet of their owner
+        assert!(matches!(
+            systems::familiar::recall(&mut world, familiar, &Point3::new(20.0, 0.0, 0.0)),
+            Err(FamiliarError::OutOfRange { .. })
+        ));
+
+        systems::familiar::recall(&mut world, familiar, &Point3::new(2.0, 0.0, 0.0)).unwrap();
+        assert_eq!(
+            systems::geometry::get_foot_position(&world, familiar),
+            Some(Point3::new(2.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            systems::familiar::recall(&mut world, familiar, &Point3::new(2.0, 0.0, 0.0)),
+            Err(FamiliarError::NotDismissed(familiar))
+        );
+    }
+
+    fn cast(
+        game_state: &mut GameState,
+        caster: Entity,
+        spell: &str,
+        level: u8,
+    ) -> ActionKindResult {
+        let spell_id = SpellId::new("nat20_core", &format!("spell.{}", spell));
+        let action_id = ActionId::new("nat20_core", &format!("action.{}", spell));
+        let cost = systems::actions::get_action(&action_id)
+            .unwrap()
+            .resource_cost()
+            .clone();
+        let context = ActionContext::Spell {
+            id: spell_id,
+            source: SpellSource::Class(ClassAndSubclass {
+                class: ClassId::new("nat20_core", "class.wizard"),
+                subclass: None,
+            }),
+            level,
+            metamagic: Vec::new(),
+        };
+        let action = ActionData::new(
+            caster,
+            action_id,
+            context,
+            cost,
+            vec![TargetInstance::Entity(caster)],
+        );
+
+        systems::actions::perform_action(game_state, &action);
+        game_state
+            .event_log
+            .events
+            .iter()
+            .rev()
+            .find_map(|event| match &event.kind {
+                EventKind::ActionPerformed { results, .. } => {
+                    results.first().map(|result| result.kind.clone())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn find_familiar_summons_the_chosen_form() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+
+        let result = cast(&mut game_state, wizard, "find_familiar.owl", 1);
+        assert_eq!(
+            result,
+            ActionKindResult::Utility(UtilityOutcome::Summoned(MonsterId::new(
+                "nat20_core",
+                "monster.owl"
+            )))
+        );
+        let owl = systems::familiar::familiar(&game_state.world, wizard).unwrap();
+        assert_eq!(
+            systems::helpers::get_component::<Name>(&game_state.world, owl).as_str(),
+            "Owl"
+        );
+
+        // Casting it again replaces the familiar
+        cast(&mut game_state, wizard, "find_familiar.cat", 1);
+        let cat = systems::familiar::familiar(&game_state.world, wizard).unwrap();
+        assert!(!game_state.world.contains(owl));
+        assert_eq!(
+            systems::helpers::get_component::<Name>(&game_state.world, cat).as_str(),
+            "Cat"
+        );
+    }
+
+    #[test]
+    fn find_steed_summons_a_steed_which_can_attack() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+
+        cast(&mut game_state, wizard, "find_steed", 2);
+        let steed = systems::familiar::steed(&game_state.world, wizard).unwrap();
+        assert!(!systems::familiar::is_familiar(&game_state.world, steed));
+        assert_eq!(
+            *systems::helpers::get_component::<FactionSet>(&game_state.world, steed),
+            *systems::helpers::get_component::<FactionSet>(&game_state.world, wizard)
+        );
+        assert!(systems::familiar::can_take_action(
+            &game_state.world,
+            steed,
+            &ActionId::new("nat20_core", "action.weapon_attack"),
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::World;
+    use nat20_core::{
+        components::{
+            health::hit_points::HitPoints,
+            id::{ClassId, Name, SpeciesId},
+            level::{ChallengeRating, CharacterLevels},
+            personality::Personality,
+            species::{CreatureSize, CreatureType},
+        },
+        registry::registry::MonstersRegistry,
+        systems::{
+            self,
+            generator::{MonsterFilter, NpcFilter},
+        },
+    };
+
+    #[test]
+    fn generate_humanoid_npc() {
+        let mut world = World::new();
+        let filter = MonsterFilter {
+            creature_type: Some(CreatureType::Humanoid),
+            ..Default::default()
+        };
+
+        let monster = systems::generator::generate_monster(&mut world, &filter).unwrap();
+        let entity = monster.id();
+
+        assert!(
+            systems::helpers::get_component::<Name>(&world, entity)
+                .as_str()
+                .ends_with("(Bandit)")
+        );
+        assert_eq!(
+            *systems::helpers::get_component::<CreatureType>(&world, entity),
+            CreatureType::Humanoid
+        );
+        let hit_points = systems::helpers::get_component::<HitPoints>(&world, entity).max();
+        assert!((4..=18).contains(&hit_points));
+        assert_eq!(
+            systems::helpers::get_component::<Personality>(&world, entity)
+                .tags
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn generate_for_party_level() {
+        let mut world = World::new();
+        let monster =
+            systems::generator::generate_monster(&mut world, &MonsterFilter::for_party_level(1))
+                .unwrap();
+
+        assert_eq!(
+            systems::helpers::get_component::<ChallengeRating>(&world, monster.id()).total_level(),
+            1
+        );
+    }
+
+    #[test]
+    fn generate_no_matching_template() {
+        let mut world = World::new();
+        let filter = MonsterFilter {
+            min_challenge_rating: Some(5),
+            ..Default::default()
+        };
+
+        assert!(MonstersRegistry::values().all(|template| !filter.matches(template)));
+        assert!(systems::generator::generate_monster(&mut world, &filter).is_none());
+    }
+
+    #[test]
+    fn filter_by_size() {
+        let filter = MonsterFilter {
+            size: Some(CreatureSize::Small),
+            ..Default::default()
+        };
+
+        let templates = MonstersRegistry::values()
+            .filter(|template| filter.matches(template))
+            .collect::<Vec<_>>();
+        assert!(!templates.is_empty());
+        assert!(
+            templates
+                .iter()
+                .all(|template| template.size == CreatureSize::Small)
+        );
+    }
+
+    #[test]
+    fn generate_npc_with_species_and_class() {
+        let mut world = World::new();
+        let species = SpeciesId::new("nat20_core", "species.dragonborn");
+        let class = ClassId::new("nat20_core", "class.wizard");
+        let filter = NpcFilter {
+            species: Some(species.clone()),
+            class: Some(class.clone()),
+            level: 3,
+        };
+
+        let npc =
+            systems::generator::generate_npc(&mut world, Name::new("Apprentice"), &filter).unwrap();
+        let entity = npc.id();
+
+        assert_eq!(
+            *systems::helpers::get_component::<SpeciesId>(&world, entity),
+            species
+        );
+        let levels = systems::helpers::get_component::<CharacterLevels>(&world, entity);
+        assert_eq!(levels.total_level(), 3);
+        assert_eq!(levels.class_level(&class).unwrap().level(), 3);
+    }
+
+    #[test]
+    fn generate_npc_unknown_species() {
+        let mut world = World::new();
+        let filter = NpcFilter {
+            species: Some(SpeciesId::new("nat20_core", "species.unknown")),
+            ..Default::default()
+        };
+
+        assert!(
+            systems::generator::generate_npc(&mut world, Name::new("Nobody"), &filter).is_err()
+        );
+        assert_eq!(world.len(), 0);
+    }
+}
@@ -0,0 +1,200 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::action::ActionContext,
+            effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate},
+            id::{ActionId, EffectId, ResourceId},
+            modifier::ModifierSource,
+            resource::ResourceMap,
+            time::TimeDuration,
+        },
+        engine::game_state::GameState,
+        systems::{self, time::TimeActivity},
+        test_utils::fixtures,
+    };
+
+    fn setup() -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        (game_state, fighter)
+    }
+
+    fn haste() -> EffectId {
+        EffectId::new("nat20_core", "effect.spell.haste")
+    }
+
+    fn hasted_action() -> ActionId {
+        ActionId::new("nat20_core", "action.hasted_action")
+    }
+
+    fn hasted_attack() -> ActionId {
+        ActionId::new("nat20_core", "action.hasted_action.attack")
+    }
+
+    fn add_effect(game_state: &mut GameState, entity: Entity, effect_id: EffectId) {
+        systems::effects::add_effect_template(
+            &mut game_state.world,
+            entity,
+            entity,
+            ModifierSource::Custom("Test".to_string()),
+            &EffectInstanceTemplate {
+                effect_id,
+                lifetime: EffectLifetimeTemplate::Duration(TimeDuration::from_minutes(1)),
+            },
+            None,
+        );
+    }
+
+    fn add_haste(game_state: &mut GameState, entity: Entity) {
+        add_effect(game_state, entity, haste());
+    }
+
+    fn can_take(game_state: &GameState, entity: Entity, action_id: &ActionId) -> bool {
+        systems::actions::available_actions(&game_state.world, entity).contains_key(action_id)
+    }
+
+    #[test]
+    fn haste_grants_its_action_until_it_expires() {
+        let (mut game_state, fighter) = setup();
+        assert!(!can_take(&game_state, fighter, &hasted_action()));
+
+        add_haste(&mut game_state, fighter);
+        assert!(game_state.actions(fighter).contains_key(&hasted_action()));
+        assert!(can_take(&game_state, fighter, &hasted_action()));
+
+        systems::time::pass_time(
+            &mut game_state,
+            TimeDuration::from_minutes(1),
+            TimeActivity::Downtime,
+        )
+        .unwrap();
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &haste()
+        ));
+        assert!(!can_take(&game_state, fighter, &hasted_action()));
+    }
+
+    #[test]
+    fn suppressed_effects_dont_grant_actions() {
+        let (mut game_state, fighter) = setup();
+        let antimagic = ModifierSource::Custom("Antimagic".to_string());
+        add_haste(&mut game_state, fighter);
+
+        systems::effects::suppress_effects(&mut game_state.world, fighter, &antimagic, |effect| {
+            effect.effect_id == haste()
+        });
+        assert!(!can_take(&game_state, fighter, &hasted_action()));
+
+        systems::effects::unsuppress_effects(&mut game_state.world, fighter, &antimagic);
+        assert!(can_take(&game_state, fighter, &hasted_action()));
+    }
+
+    #[test]
+    fn actions_are_only_revoked_by_their_last_grant() {
+        let (mut game_state, fighter) = setup();
+        let first = ModifierSource::Custom("First".to_string());
+        let second = ModifierSource::Custom("Second".to_string());
+
+        systems::actions::grant_action(
+            &mut game_state.world,
+            fighter,
+            &hasted_action(),
+            first.clone(),
+        );
+        systems::actions::grant_action(
+            &mut game_state.world,
+            fighter,
+            &hasted_action(),
+            second.clone(),
+        );
+        systems::actions::revoke_action(&mut game_state.world, fighter, &hasted_action(), &first);
+        assert!(can_take(&game_state, fighter, &hasted_action()));
+
+        systems::actions::revoke_action(&mut game_state.world, fighter, &hasted_action(), &second);
+        assert!(!can_take(&game_state, fighter, &hasted_action()));
+
+        // Revoking a grant doesn't take away actions the entity knows itself
+        let dash = ActionId::new("nat20_core", "action.dash");
+        systems::actions::grant_action(&mut game_state.world, fighter, &dash, first.clone());
+        systems::actions::revoke_action(&mut game_state.world, fighter, &dash, &first);
+        assert!(can_take(&game_state, fighter, &dash));
+    }
+
+    #[test]
+    fn hasted_attacks_are_made_with_weapons() {
+        let (mut game_state, fighter) = setup();
+        let hasted_action_resource = ResourceId::new("nat20_core", "resource.hasted_action");
+        add_haste(&mut game_state, fighter);
+
+        let contexts =
+            &systems::actions::available_actions(&game_state.world, fighter)[&hasted_attack()];
+        assert!(!contexts.is_empty());
+        assert!(
+            contexts
+                .iter()
+                .all(|(context, _)| matches!(context, ActionContext::Weapon { .. }))
+        );
+        assert!(
+            systems::helpers::get_component::<ResourceMap>(&game_state.world, fighter)
+                .get(&hasted_action_resource)
+                .is_some()
+        );
+
+        systems::effects::remove_effect(&mut game_state.world, fighter, &haste());
+        assert!(!can_take(&game_state, fighter, &hasted_attack()));
+        assert!(
+            systems::helpers::get_component::<ResourceMap>(&game_state.world, fighter)
+                .get(&hasted_action_resource)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn haste_is_followed_by_lethargy() {
+        let (mut game_state, fighter) = setup();
+        let lethargy = EffectId::new("nat20_core", "effect.spell.haste_lethargy");
+        assert!(!systems::conditions::is_incapacitated(
+            &game_state.world,
+            fighter
+        ));
+
+        add_haste(&mut game_state, fighter);
+        systems::effects::remove_effect(&mut game_state.world, fighter, &haste());
+        assert!(systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &lethargy
+        ));
+        assert!(systems::conditions::is_incapacitated(
+            &game_state.world,
+            fighter
+        ));
+
+        // Removing an effect the entity doesn't have isn't followed by anything
+        systems::effects::remove_effect(&mut game_state.world, fighter, &lethargy);
+        systems::effects::remove_effect(&mut game_state.world, fighter, &haste());
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &lethargy
+        ));
+    }
+
+    #[test]
+    fn rage_grants_reckless_attack() {
+        let (mut game_state, fighter) = setup();
+        let rage = EffectId::new("nat20_core", "effect.barbarian.rage");
+        let reckless_attack = ActionId::new("nat20_core", "action.barbarian.reckless_attack");
+
+        add_effect(&mut game_state, fighter, rage.clone());
+        assert!(can_take(&game_state, fighter, &reckless_attack));
+
+        systems::effects::remove_effect(&mut game_state.world, fighter, &rage);
+        assert!(!can_take(&game_state, fighter, &reckless_attack));
+    }
+}
@@ -0,0 +1,181 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::targeting::{ItemTarget, TargetInstance},
+            health::life_state::LifeState,
+            id::{ActionId, ItemId, ResourceId},
+            items::{
+                equipment::slots::EquipmentSlot,
+                inventory::{Inventory, ItemContainer, ItemInstance},
+            },
+            resource::{RechargeRule, ResourceAmount, ResourceMap},
+        },
+        engine::{event::ActionData, game_state::GameState},
+        registry::registry::ItemsRegistry,
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+    use uom::si::{f32::Length, length::foot};
+
+    fn torch() -> ItemId {
+        ItemId::new("nat20_core", "item.torch")
+    }
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            10.0,
+        )
+    }
+
+    fn action(
+        game_state: &GameState,
+        actor: Entity,
+        action_id: &str,
+        target: TargetInstance,
+    ) -> ActionData {
+        let action_id = ActionId::new("nat20_core", action_id);
+        let (context, cost) = systems::actions::all_actions(&game_state.world, actor)
+            .get(&action_id)
+            .unwrap()[0]
+            .clone();
+        ActionData::new(actor, action_id, context, cost, vec![target])
+    }
+
+    fn perform(game_state: &mut GameState, action: &ActionData) {
+        game_state.validate_action(action, true).unwrap();
+        systems::actions::perform_action(game_state, action);
+    }
+
+    fn nearby_items(game_state: &GameState, entity: Entity) -> Vec<ItemId> {
+        systems::inventory::ground_items_in_reach(
+            &game_state.world,
+            entity,
+            Length::new::<foot>(5.0),
+        )
+        .into_iter()
+        .map(|ground_item| {
+            game_state
+                .world
+                .get::<&ItemInstance>(ground_item)
+                .unwrap()
+                .id()
+                .clone()
+        })
+        .collect()
+    }
+
+    fn carries(game_state: &GameState, entity: Entity, item: &ItemId) -> bool {
+        systems::helpers::get_component::<Inventory>(&game_state.world, entity)
+            .items()
+            .iter()
+            .any(|carried| carried.id() == item)
+    }
+
+    fn has_free_interaction(game_state: &GameState, entity: Entity) -> bool {
+        systems::helpers::get_component::<ResourceMap>(&game_state.world, entity).can_afford(
+            &ResourceId::new("nat20_core", "resource.free_interaction"),
+            &ResourceAmount::Flat(1),
+        )
+    }
+
+    #[test]
+    fn drop_and_pick_up() {
+        let (mut game_state, fighter, _) = setup();
+        let item = ItemsRegistry::get(&torch()).unwrap().clone();
+        systems::inventory::add_item(&mut game_state.world, fighter, item);
+        let index = systems::helpers::get_component::<Inventory>(&game_state.world, fighter)
+            .items()
+            .len()
+            - 1;
+
+        let drop_torch = action(
+            &game_state,
+            fighter,
+            "action.drop_item",
+            TargetInstance::Item {
+                owner: fighter,
+                item: ItemTarget::Inventory(index),
+            },
+        );
+        perform(&mut game_state, &drop_torch);
+        assert!(!carries(&game_state, fighter, &torch()));
+        assert_eq!(nearby_items(&game_state, fighter), vec![torch()]);
+        assert!(!has_free_interaction(&game_state, fighter));
+
+        systems::resources::recharge(&mut game_state.world, fighter, &RechargeRule::Turn);
+        let ground_item = systems::inventory::ground_items_in_reach(
+            &game_state.world,
+            fighter,
+            Length::new::<foot>(5.0),
+        )[0];
+        let pick_up = action(
+            &game_state,
+            fighter,
+            "action.pick_up",
+            TargetInstance::Entity(ground_item),
+        );
+        perform(&mut game_state, &pick_up);
+        assert!(carries(&game_state, fighter, &torch()));
+        assert!(nearby_items(&game_state, fighter).is_empty());
+        assert!(!game_state.world.contains(ground_item));
+        assert!(!has_free_interaction(&game_state, fighter));
+    }
+
+    #[test]
+    fn items_out_of_reach_cant_be_picked_up() {
+        let (mut game_state, fighter, _) = setup();
+        let item = ItemsRegistry::get(&torch()).unwrap().clone();
+        let ground_item = systems::inventory::drop_item(&mut game_state.world, fighter, item);
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::new(5.0, 0.0, 0.0));
+
+        let pick_up = action(
+            &game_state,
+            fighter,
+            "action.pick_up",
+            TargetInstance::Entity(ground_item),
+        );
+        assert!(game_state.validate_action(&pick_up, false).is_err());
+    }
+
+    #[test]
+    fn dead_monsters_drop_their_gear() {
+        let (mut game_state, _, goblin) = setup();
+
+        systems::health::set_life_state(&mut game_state.world, goblin, LifeState::Dead);
+
+        let dropped = nearby_items(&game_state, goblin);
+        for item in [
+            "item.studded_leather_armor",
+            "item.scimitar",
+            "item.shortbow",
+        ] {
+            assert!(dropped.contains(&ItemId::new("nat20_core", item)));
+        }
+        let loadout = systems::loadout::loadout(&game_state.world, goblin);
+        assert!(
+            loadout
+                .weapon_in_hand(&EquipmentSlot::MeleeMainHand)
+                .is_none()
+        );
+        assert!(loadout.armor().is_none());
+    }
+
+    #[test]
+    fn defeated_characters_keep_their_gear() {
+        let (mut game_state, fighter, _) = setup();
+        systems::health::set_life_state(&mut game_state.world, fighter, LifeState::Defeated);
+
+        assert!(nearby_items(&game_state, fighter).is_empty());
+        assert!(
+            systems::loadout::loadout(&game_state.world, fighter)
+                .weapon_in_hand(&EquipmentSlot::MeleeMainHand)
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,315 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{action::ActionContext, targeting::TargetInstance},
+            class::ClassAndSubclass,
+            id::{ActionId, ClassId, Name, SpellId},
+            illusion::{Illusion, IllusionTemplate},
+            items::equipment::slots::EquipmentSlot,
+            resource::ResourceAmountMap,
+            species::CreatureSize,
+            spells::spellbook::{SpellSource, Spellbook},
+            time::TimeDuration,
+        },
+        engine::{event::ActionData, game_state::GameState},
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::wizard,
+            fixtures::creatures::monsters::goblin_warrior,
+            5.0,
+        )
+    }
+
+    fn place_illusion(game_state: &mut GameState, dc: i32, position: Point3<f32>) -> Entity {
+        systems::illusions::spawn(
+            game_state,
+            &IllusionTemplate {
+                name: "Boulder".to_string(),
+                size: CreatureSize::Large,
+                dc: Some(dc),
+                duration: Some(TimeDuration::from_minutes(1)),
+            },
+            None,
+            dc,
+            &position,
+        )
+    }
+
+    fn perform(
+        game_state: &mut GameState,
+        actor: Entity,
+        action_id: ActionId,
+        context: ActionContext,
+        target: TargetInstance,
+    ) {
+        let cost = systems::actions::get_action(&action_id)
+            .unwrap()
+            .resource_cost()
+            .clone();
+        let action = ActionData::new(actor, action_id, context, cost, vec![target]);
+        systems::actions::perform_action(game_state, &action);
+    }
+
+    fn cast(
+        game_state: &mut GameState,
+        wizard: Entity,
+        spell: &str,
+        image: &str,
+        level: u8,
+        point: Point3<f32>,
+    ) {
+        perform(
+            game_state,
+            wizard,
+            ActionId::new("nat20_core", &format!("action.{}.{}", spell, image)),
+            ActionContext::Spell {
+                id: SpellId::new("nat20_core", &format!("spell.{}", spell)),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level,
+                metamagic: Vec::new(),
+            },
+            TargetInstance::Point(point),
+        );
+    }
+
+    fn illusions(game_state: &GameState) -> Vec<Entity> {
+        game_state
+            .world
+            .query::<&Illusion>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    fn is_concentrating(game_state: &GameState, entity: Entity) -> bool {
+        systems::helpers::get_component::<Spellbook>(&game_state.world, entity)
+            .concentration_tracker()
+            .is_concentrating()
+    }
+
+    fn can_see(game_state: &GameState, from: Entity, to: Entity) -> bool {
+        systems::geometry::line_of_sight_entity_entity(
+            &game_state.world,
+            &game_state.geometry,
+            from,
+            to,
+        )
+        .has_line_of_sight
+    }
+
+    #[test]
+    fn minor_image_creates_an_illusion() {
+        let (mut game_state, wizard, goblin) = setup();
+
+        cast(
+            &mut game_state,
+            wizard,
+            "minor_image",
+            "barrel",
+            0,
+            Point3::new(2.5, 0.0, 0.0),
+        );
+
+        let illusions = illusions(&game_state);
+        assert_eq!(illusions.len(), 1);
+        let illusion = illusions[0];
+        assert_eq!(
+            systems::helpers::get_component::<Name>(&game_state.world, illusion).as_str(),
+            "Barrel"
+        );
+
+        // The caster knows it's fake, the goblin doesn't
+        assert!(systems::illusions::is_disbelieved_by(
+            &game_state.world,
+            illusion,
+            wizard
+        ));
+        assert!(!systems::illusions::is_disbelieved_by(
+            &game_state.world,
+            illusion,
+            goblin
+        ));
+    }
+
+    #[test]
+    fn major_image_lasts_as_long_as_the_concentration() {
+        let (mut game_state, wizard, _) = setup();
+
+        cast(
+            &mut game_state,
+            wizard,
+            "major_image",
+            "ogre",
+            3,
+            Point3::new(2.5, 0.0, 0.0),
+        );
+        let illusion = illusions(&game_state)[0];
+        assert!(is_concentrating(&game_state, wizard));
+
+        systems::spells::break_concentration(&mut game_state.world, wizard);
+        assert!(!game_state.world.contains(illusion));
+    }
+
+    #[test]
+    fn major_image_ends_the_concentration_when_it_fades() {
+        let (mut game_state, wizard, _) = setup();
+
+        cast(
+            &mut game_state,
+            wizard,
+            "major_image",
+            "wolf",
+            3,
+            Point3::new(2.5, 0.0, 0.0),
+        );
+        let illusion = illusions(&game_state)[0];
+
+        systems::time::advance_world_clock(&mut game_state, &TimeDuration::from_minutes(10));
+        assert!(!game_state.world.contains(illusion));
+        assert!(!is_concentrating(&game_state, wizard));
+    }
+
+    #[test]
+    fn area_spells_dont_affect_illusions() {
+        let (mut game_state, wizard, goblin) = setup();
+        let illusion = place_illusion(&mut game_state, 15, Point3::new(6.0, 0.0, 0.0));
+
+        let fireball = ActionData::new(
+            wizard,
+            ActionId::new("nat20_core", "action.fireball"),
+            ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.fireball"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 3,
+                metamagic: Vec::new(),
+            },
+            ResourceAmountMap::new(),
+            vec![TargetInstance::Point(Point3::new(5.0, 0.0, 0.0))],
+        );
+        let targets = systems::actions::get_targeted_entities(&game_state, &fireball);
+        assert!(targets.contains(&goblin));
+        assert!(!targets.contains(&illusion));
+    }
+
+    #[test]
+    fn illusions_block_sight_until_seen_through() {
+        let (mut game_state, wizard, goblin) = setup();
+        let illusion = place_illusion(&mut game_state, 15, Point3::new(2.5, 0.0, 0.0));
+
+        assert!(!can_see(&game_state, wizard, goblin));
+        assert!(!can_see(&game_state, goblin, wizard));
+
+        systems::illusions::disbelieve(&mut game_state, illusion, wizard);
+        assert!(can_see(&game_state, wizard, goblin));
+        assert!(!can_see(&game_state, goblin, wizard));
+    }
+
+    #[test]
+    fn illusions_dont_block_line_of_effect() {
+        let (mut game_state, wizard, goblin) = setup();
+        place_illusion(&mut game_state, 15, Point3::new(2.5, 0.0, 0.0));
+
+        assert!(
+            systems::geometry::line_of_effect_entity_entity(
+                &game_state.world,
+                &game_state.geometry,
+                wizard,
+                goblin
+            )
+            .has_line_of_effect
+        );
+    }
+
+    #[test]
+    fn attacks_pass_through_illusions() {
+        let (mut game_state, wizard, goblin) = setup();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::new(1.0, 0.0, 0.0));
+        let illusion = place_illusion(&mut game_state, 15, Point3::new(2.5, 0.0, 0.0));
+
+        perform(
+            &mut game_state,
+            fighter,
+            ActionId::new("nat20_core", "action.weapon_attack"),
+            ActionContext::Weapon {
+                slot: EquipmentSlot::MeleeMainHand,
+            },
+            TargetInstance::Entity(illusion),
+        );
+
+        // Only the fighter found out, and the illusion is still there for the rest
+        assert!(game_state.world.contains(illusion));
+        assert!(systems::illusions::is_disbelieved_by(
+            &game_state.world,
+            illusion,
+            fighter
+        ));
+        assert!(!systems::illusions::is_disbelieved_by(
+            &game_state.world,
+            illusion,
+            wizard
+        ));
+        assert!(!systems::illusions::is_disbelieved_by(
+            &game_state.world,
+            illusion,
+            goblin
+        ));
+    }
+
+    #[test]
+    fn investigating_reveals_illusions() {
+        let (mut game_state, wizard, goblin) = setup();
+        let illusion = place_illusion(&mut game_state, 0, Point3::new(2.5, 0.0, 0.0));
+
+        // Anything but a natural 1 beats DC 0
+        for _ in 0..20 {
+            perform(
+                &mut game_state,
+                goblin,
+                ActionId::new("nat20_core", "action.investigate"),
+                ActionContext::Other,
+                TargetInstance::Object(illusion),
+            );
+            if systems::illusions::is_disbelieved_by(&game_state.world, illusion, goblin) {
+                break;
+            }
+        }
+
+        assert!(systems::illusions::is_disbelieved_by(
+            &game_state.world,
+            illusion,
+            goblin
+        ));
+        assert!(!systems::illusions::is_disbelieved_by(
+            &game_state.world,
+            illusion,
+            wizard
+        ));
+    }
+
+    #[test]
+    fn illusions_fade_when_their_duration_runs_out() {
+        let (mut game_state, _, _) = setup();
+        let illusion = place_illusion(&mut game_state, 15, Point3::new(2.5, 0.0, 0.0));
+
+        systems::time::advance_world_clock(&mut game_state, &TimeDuration::from_seconds(30.0));
+        assert!(game_state.world.contains(illusion));
+
+        systems::time::advance_world_clock(&mut game_state, &TimeDuration::from_minutes(1));
+        assert!(!game_state.world.contains(illusion));
+    }
+}
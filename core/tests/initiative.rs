@@ -0,0 +1,182 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::collections::HashSet;
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{d20::D20CheckResult, modifier::ModifierSource},
+        engine::{
+            encounter::{EncounterId, InitiativeError},
+            game_state::GameState,
+            rules::InitiativeMode,
+        },
+        test_utils::fixtures,
+    };
+
+    struct Setup {
+        game_state: GameState,
+        encounter_id: EncounterId,
+        party: Vec<Entity>,
+        goblins: Vec<Entity>,
+    }
+
+    fn setup(mode: InitiativeMode) -> Setup {
+        let mut game_state = fixtures::engine::game_state();
+        game_state.initiative_mode = mode;
+        let party = vec![
+            fixtures::creatures::heroes::fighter(&mut game_state.world).id(),
+            fixtures::creatures::heroes::wizard(&mut game_state.world).id(),
+        ];
+        let goblins = (0..2)
+            .map(|_| fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id())
+            .collect::<Vec<_>>();
+
+        let participants = party
+            .iter()
+            .chain(goblins.iter())
+            .copied()
+            .collect::<HashSet<_>>();
+        let encounter_id = game_state.start_encounter(participants);
+        Setup {
+            game_state,
+            encounter_id,
+            party,
+            goblins,
+        }
+    }
+
+    fn turn_order(setup: &Setup) -> Vec<Entity> {
+        setup
+            .game_state
+            .encounter(&setup.encounter_id)
+            .unwrap()
+            .initiative_order()
+            .iter()
+            .map(|(entity, _)| *entity)
+            .collect()
+    }
+
+    fn current_entity(setup: &Setup) -> Entity {
+        setup
+            .game_state
+            .encounter(&setup.encounter_id)
+            .unwrap()
+            .current_entity()
+    }
+
+    fn end_turn(setup: &mut Setup) {
+        let entity = current_entity(setup);
+        setup.game_state.end_turn(entity);
+    }
+
+    #[test]
+    fn side_initiative_groups_sides() {
+        let setup = setup(InitiativeMode::Side);
+        let order = turn_order(&setup);
+
+        // One side takes all its turns before the other
+        let (first, second) = order.split_at(2);
+        let first = first.iter().copied().collect::<HashSet<_>>();
+        let second = second.iter().copied().collect::<HashSet<_>>();
+        let party = setup.party.iter().copied().collect::<HashSet<_>>();
+        let goblins = setup.goblins.iter().copied().collect::<HashSet<_>>();
+        assert!((first == party && second == goblins) || (first == goblins && second == party));
+
+        // Everyone on a side shares the roll of their side
+        let encounter = setup.game_state.encounter(&setup.encounter_id).unwrap();
+        let rolls = encounter.initiative_order();
+        assert_eq!(rolls[0].1.total(), rolls[1].1.total());
+        assert_eq!(rolls[2].1.total(), rolls[3].1.total());
+    }
+
+    #[test]
+    fn popcorn_initiative_picks_next_actor() {
+        let mut setup = setup(InitiativeMode::Popcorn);
+        let order = turn_order(&setup);
+        let first = order[0];
+        let last = order[3];
+
+        let encounter_id = setup.encounter_id;
+        let encounter = setup.game_state.encounter_mut(&encounter_id).unwrap();
+        assert_eq!(
+            encounter.choose_next(first),
+            Err(InitiativeError::AlreadyActed(first))
+        );
+        encounter.choose_next(last).unwrap();
+        end_turn(&mut setup);
+        assert_eq!(current_entity(&setup), last);
+
+        // The last creature of the round can pick anyone to start the next one,
+        // even itself
+        end_turn(&mut setup);
+        end_turn(&mut setup);
+        let round_ender = current_entity(&setup);
+        setup
+            .game_state
+            .encounter_mut(&encounter_id)
+            .unwrap()
+            .choose_next(round_ender)
+            .unwrap();
+        end_turn(&mut setup);
+
+        let encounter = setup.game_state.encounter(&encounter_id).unwrap();
+        assert_eq!(encounter.round(), 2);
+        assert_eq!(encounter.current_entity(), round_ender);
+    }
+
+    #[test]
+    fn choose_next_requires_popcorn_initiative() {
+        let mut setup = setup(InitiativeMode::Individual);
+        let goblin = setup.goblins[0];
+        let encounter_id = setup.encounter_id;
+        assert_eq!(
+            setup
+                .game_state
+                .encounter_mut(&encounter_id)
+                .unwrap()
+                .choose_next(goblin),
+            Err(InitiativeError::NotPopcorn)
+        );
+    }
+
+    #[test]
+    fn speed_factor_initiative_is_rerolled_every_round() {
+        let mut setup = setup(InitiativeMode::SpeedFactor);
+        let speed_factor = ModifierSource::Custom("Speed Factor".to_string());
+        let rolls = |setup: &Setup| {
+            setup
+                .game_state
+                .encounter(&setup.encounter_id)
+                .unwrap()
+                .initiative_order()
+                .clone()
+        };
+
+        // Goblins are small, so they're a bit quicker than the party
+        let goblins = setup.goblins.clone();
+        let goblins_are_quick = |rolls: &Vec<(Entity, D20CheckResult)>| {
+            rolls
+                .iter()
+                .filter(|(entity, _)| goblins.contains(entity))
+                .all(|(_, roll)| roll.modifier_breakdown.get(&speed_factor).unwrap() >= 2)
+        };
+        assert!(goblins_are_quick(&rolls(&setup)));
+
+        for _ in 0..4 {
+            end_turn(&mut setup);
+        }
+        assert_eq!(
+            setup
+                .game_state
+                .encounter(&setup.encounter_id)
+                .unwrap()
+                .round(),
+            2
+        );
+        // Everyone rolled initiative again for the new round
+        let second_round = rolls(&setup);
+        assert_eq!(second_round.len(), 4);
+        assert!(goblins_are_quick(&second_round));
+    }
+}
@@ -0,0 +1,97 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            actions::action::ActionContext,
+            id::{ActionId, MonsterId, SpellId},
+            modifier::Modifiable,
+            resource::RechargeRule,
+            spells::spellbook::{GrantedSpellSource, SpellSource},
+        },
+        registry::registry::{MonstersRegistry, SpellsRegistry},
+        systems::{self, time::RestKind},
+    };
+
+    fn hexer(world: &mut World) -> Entity {
+        let template =
+            MonstersRegistry::get(&MonsterId::new("nat20_core", "monster.goblin_hexer")).unwrap();
+        systems::generator::spawn_monster(world, template).id()
+    }
+
+    fn spell_action(spell: &str) -> ActionId {
+        SpellsRegistry::get(&SpellId::new("nat20_core", spell))
+            .unwrap()
+            .action()
+            .id()
+            .clone()
+    }
+
+    fn innate_context(spell: &str, level: u8) -> ActionContext {
+        ActionContext::Spell {
+            id: SpellId::new("nat20_core", spell),
+            source: SpellSource::Granted {
+                source: GrantedSpellSource::Innate,
+                level,
+            },
+            level,
+            metamagic: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn innate_spells_are_castable_without_slots() {
+        let mut world = World::new();
+        let hexer = hexer(&mut world);
+
+        let actions = systems::actions::available_actions(&world, hexer);
+        for spell in ["spell.fire_bolt", "spell.hex", "spell.burning_hands"] {
+            assert!(actions.contains_key(&spell_action(spell)), "{}", spell);
+        }
+        assert_eq!(
+            actions[&spell_action("spell.hex")][0].0,
+            innate_context("spell.hex", 1)
+        );
+    }
+
+    #[test]
+    fn innate_spells_use_fixed_dc_and_attack_bonus() {
+        let mut world = World::new();
+        let hexer = hexer(&mut world);
+        let source = SpellSource::Granted {
+            source: GrantedSpellSource::Innate,
+            level: 1,
+        };
+
+        assert_eq!(
+            systems::spells::spell_dc(&world, hexer, &source).total(),
+            12
+        );
+        assert_eq!(
+            systems::spells::spell_attack_bonus(&world, hexer, &source).total(),
+            4
+        );
+    }
+
+    #[test]
+    fn per_day_spells_recharge_on_long_rest() {
+        let mut world = World::new();
+        let hexer = hexer(&mut world);
+        let hex = spell_action("spell.hex");
+        let fire_bolt = spell_action("spell.fire_bolt");
+
+        systems::spells::spend_innate_use(&mut world, hexer, &innate_context("spell.hex", 1));
+        systems::spells::spend_innate_use(&mut world, hexer, &innate_context("spell.fire_bolt", 0));
+        let actions = systems::actions::available_actions(&world, hexer);
+        assert!(!actions.contains_key(&hex));
+        // At will spells never run out
+        assert!(actions.contains_key(&fire_bolt));
+
+        systems::resources::recharge(&mut world, hexer, &RechargeRule::Rest(RestKind::Short));
+        assert!(!systems::actions::available_actions(&world, hexer).contains_key(&hex));
+
+        systems::resources::recharge(&mut world, hexer, &RechargeRule::Rest(RestKind::Long));
+        assert!(systems::actions::available_actions(&world, hexer).contains_key(&hex));
+    }
+}
@@ -0,0 +1,189 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{
+                action::ActionContext,
+                targeting::{TargetInstance, TargetingError},
+            },
+            d20::RollMode,
+            id::{ActionId, EffectId},
+            items::equipment::slots::EquipmentSlot,
+            modifier::ModifierSource,
+        },
+        engine::{event::ActionData, game_state::GameState},
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn weapon_attack() -> ActionId {
+        ActionId::new("nat20_core", "action.weapon_attack")
+    }
+
+    fn main_hand() -> ActionContext {
+        ActionContext::Weapon {
+            slot: EquipmentSlot::MeleeMainHand,
+        }
+    }
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            1.5,
+        )
+    }
+
+    fn turn_invisible(game_state: &mut GameState, entity: Entity, effect: &str) {
+        systems::effects::add_permanent_effect(
+            &mut game_state.world,
+            entity,
+            EffectId::new("nat20_core", effect),
+            &ModifierSource::Base,
+            None,
+        );
+    }
+
+    fn attack(game_state: &GameState, attacker: Entity, target: TargetInstance) -> ActionData {
+        let cost = systems::actions::get_action(&weapon_attack())
+            .unwrap()
+            .resource_cost()
+            .clone();
+        ActionData::new(attacker, weapon_attack(), main_hand(), cost, vec![target])
+    }
+
+    fn validate(game_state: &GameState, action: &ActionData) -> Result<(), TargetingError> {
+        systems::actions::targeting_context(
+            &game_state.world,
+            action.actor,
+            &action.action_id,
+            &action.context,
+        )
+        .validate_targets(
+            &game_state.world,
+            &game_state.geometry,
+            action.actor,
+            &action.targets,
+        )
+    }
+
+    fn roll_mode(game_state: &GameState, attacker: Entity, target: Entity) -> RollMode {
+        systems::damage::prepare_attack_roll(
+            systems::loadout::weapon_attack_roll(
+                &game_state.world,
+                attacker,
+                target,
+                &EquipmentSlot::MeleeMainHand,
+            ),
+            &game_state.world,
+            attacker,
+            target,
+        )
+        .d20_check
+        .advantage_tracker()
+        .roll_mode()
+    }
+
+    #[test]
+    fn invisible_creatures_cant_be_targeted_directly() {
+        let (mut game_state, fighter, goblin) = setup();
+        turn_invisible(&mut game_state, goblin, "effect.condition.invisible");
+
+        let action = attack(&game_state, fighter, TargetInstance::Entity(goblin));
+        assert_eq!(
+            validate(&game_state, &action),
+            Err(TargetingError::NotVisible {
+                target: TargetInstance::Entity(goblin)
+            })
+        );
+        assert!(!systems::visibility::can_see(
+            &game_state.world,
+            fighter,
+            goblin
+        ));
+        assert!(systems::visibility::can_see(
+            &game_state.world,
+            goblin,
+            goblin
+        ));
+    }
+
+    #[test]
+    fn guessing_the_right_square() {
+        let (mut game_state, fighter, goblin) = setup();
+        turn_invisible(&mut game_state, goblin, "effect.condition.invisible");
+
+        let action = attack(
+            &game_state,
+            fighter,
+            TargetInstance::Point(Point3::new(1.7, 0.0, 0.2)),
+        );
+        assert_eq!(validate(&game_state, &action), Ok(()));
+        assert_eq!(
+            systems::actions::get_targeted_entities(&game_state, &action),
+            vec![goblin]
+        );
+    }
+
+    #[test]
+    fn guessing_the_wrong_square() {
+        let (mut game_state, fighter, goblin) = setup();
+        turn_invisible(&mut game_state, goblin, "effect.condition.invisible");
+
+        let action = attack(
+            &game_state,
+            fighter,
+            TargetInstance::Point(Point3::new(0.5, 0.0, -1.0)),
+        );
+        assert_eq!(validate(&game_state, &action), Ok(()));
+        assert!(systems::actions::get_targeted_entities(&game_state, &action).is_empty());
+    }
+
+    #[test]
+    fn invisibility_affects_attack_rolls() {
+        let (mut game_state, fighter, goblin) = setup();
+        assert_eq!(roll_mode(&game_state, fighter, goblin), RollMode::Normal);
+
+        turn_invisible(&mut game_state, goblin, "effect.condition.invisible");
+        assert_eq!(
+            roll_mode(&game_state, fighter, goblin),
+            RollMode::Disadvantage
+        );
+        assert_eq!(roll_mode(&game_state, goblin, fighter), RollMode::Advantage);
+    }
+
+    #[test]
+    fn invisibility_ends_on_attack() {
+        let (mut game_state, fighter, goblin) = setup();
+        turn_invisible(&mut game_state, fighter, "effect.spell.invisibility");
+        assert!(systems::visibility::is_invisible(
+            &game_state.world,
+            fighter
+        ));
+
+        let action = attack(&game_state, fighter, TargetInstance::Entity(goblin));
+        systems::actions::perform_action(&mut game_state, &action);
+
+        assert!(!systems::visibility::is_invisible(
+            &game_state.world,
+            fighter
+        ));
+    }
+
+    #[test]
+    fn greater_invisibility_lasts_through_attacks() {
+        let (mut game_state, fighter, goblin) = setup();
+        turn_invisible(&mut game_state, fighter, "effect.condition.invisible");
+
+        let action = attack(&game_state, fighter, TargetInstance::Entity(goblin));
+        systems::actions::perform_action(&mut game_state, &action);
+
+        assert!(systems::visibility::is_invisible(
+            &game_state.world,
+            fighter
+        ));
+    }
+}
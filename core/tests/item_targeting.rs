@@ -0,0 +1,195 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            actions::{
+                action::ActionContext,
+                targeting::{ItemFilter, ItemTarget, TargetInstance, TargetingError},
+            },
+            health::hit_points::HitPoints,
+            id::{ActionId, ItemId, SpellId},
+            items::{equipment::slots::EquipmentSlot, inventory::ItemContainer},
+            spells::spellbook::{GrantedSpellSource, SpellSource},
+        },
+        engine::{event::ActionData, game_state::GameState},
+        registry::registry::ItemsRegistry,
+        systems,
+        test_utils::fixtures,
+    };
+
+    fn heat_metal() -> ActionId {
+        ActionId::new("nat20_core", "action.heat_metal")
+    }
+
+    fn heat_metal_context() -> ActionContext {
+        ActionContext::Spell {
+            id: SpellId::new("nat20_core", "spell.heat_metal"),
+            source: SpellSource::Granted {
+                source: GrantedSpellSource::Item(ItemId::new("nat20_core", "item.wand")),
+                level: 2,
+            },
+            level: 2,
+            metamagic: Vec::new(),
+        }
+    }
+
+    fn validate(
+        game_state: &GameState,
+        caster: Entity,
+        target: TargetInstance,
+    ) -> Result<(), TargetingError> {
+        systems::actions::targeting_context(
+            &game_state.world,
+            caster,
+            &heat_metal(),
+            &heat_metal_context(),
+        )
+        .validate_targets(&game_state.world, &game_state.geometry, caster, &[target])
+    }
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::wizard,
+            fixtures::creatures::heroes::fighter,
+            2.0,
+        )
+    }
+
+    #[test]
+    fn item_filters() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let torch = ItemsRegistry::get(&ItemId::new("nat20_core", "item.torch"))
+            .unwrap()
+            .clone();
+        systems::inventory::add_item(&mut world, fighter, torch);
+
+        let weapons = systems::inventory::item_targets(&world, fighter, &ItemFilter::Weapons);
+        assert!(!weapons.is_empty());
+        assert!(weapons.iter().all(|target| matches!(
+            target,
+            ItemTarget::Equipped(slot) if EquipmentSlot::weapon_slots().contains(slot)
+        )));
+        assert_eq!(
+            systems::inventory::item_targets(&world, fighter, &ItemFilter::Armor),
+            vec![ItemTarget::Equipped(EquipmentSlot::Armor)]
+        );
+
+        // Only items which are actually there can be targeted
+        let carried = systems::inventory::item_targets(&world, fighter, &ItemFilter::Any);
+        let ItemTarget::Inventory(index) = carried.last().unwrap() else {
+            panic!("Carried items come after the equipped ones");
+        };
+        assert_eq!(
+            systems::inventory::target_item(&world, fighter, &ItemTarget::Inventory(*index))
+                .unwrap()
+                .item()
+                .name,
+            "Torch"
+        );
+        assert!(!ItemFilter::Any.matches(
+            &world,
+            fighter,
+            &ItemTarget::Equipped(EquipmentSlot::Cloak)
+        ));
+        assert!(!ItemFilter::Equipped.matches(&world, fighter, &ItemTarget::Inventory(*index)));
+
+        // Chainmail is metal, the torch isn't
+        assert!(ItemFilter::Metal.matches(
+            &world,
+            fighter,
+            &ItemTarget::Equipped(EquipmentSlot::Armor)
+        ));
+        assert!(!ItemFilter::Metal.matches(&world, fighter, &ItemTarget::Inventory(*index)));
+    }
+
+    #[test]
+    fn heat_metal_only_targets_metal_items() {
+        let (game_state, wizard, fighter) = setup();
+
+        assert!(matches!(
+            validate(&game_state, wizard, TargetInstance::Entity(fighter)),
+            Err(TargetingError::InvalidTarget { .. })
+        ));
+        assert!(matches!(
+            validate(
+                &game_state,
+                wizard,
+                TargetInstance::Item {
+                    owner: fighter,
+                    item: ItemTarget::Equipped(EquipmentSlot::Cloak),
+                }
+            ),
+            Err(TargetingError::InvalidTarget { .. })
+        ));
+
+        let targeting = systems::actions::targeting_context(
+            &game_state.world,
+            wizard,
+            &heat_metal(),
+            &heat_metal_context(),
+        );
+        let target =
+            systems::actions::target_for_entity(&game_state.world, &targeting, fighter).unwrap();
+        assert!(matches!(
+            target,
+            TargetInstance::Item {
+                item: ItemTarget::Equipped(_),
+                ..
+            }
+        ));
+        assert_eq!(validate(&game_state, wizard, target), Ok(()));
+
+        // The chainmail and the weapons can all be picked, but the wizard's robe
+        // and quarterstaff can't
+        let metal_items =
+            systems::inventory::item_targets(&game_state.world, fighter, &ItemFilter::Metal);
+        assert!(metal_items.len() > 1);
+        assert!(metal_items.contains(&ItemTarget::Equipped(EquipmentSlot::Armor)));
+        assert!(
+            systems::inventory::item_targets(&game_state.world, wizard, &ItemFilter::Metal)
+                .is_empty()
+        );
+        assert!(matches!(
+            validate(
+                &game_state,
+                wizard,
+                TargetInstance::Item {
+                    owner: wizard,
+                    item: ItemTarget::Equipped(EquipmentSlot::Armor),
+                }
+            ),
+            Err(TargetingError::InvalidTarget { .. })
+        ));
+    }
+
+    #[test]
+    fn heat_metal_burns_the_owner() {
+        let (mut game_state, wizard, fighter) = setup();
+        let max_hit_points =
+            systems::helpers::get_component::<HitPoints>(&game_state.world, fighter).max();
+
+        let action = ActionData::new(
+            wizard,
+            heat_metal(),
+            heat_metal_context(),
+            Default::default(),
+            vec![TargetInstance::Item {
+                owner: fighter,
+                item: ItemTarget::Equipped(EquipmentSlot::Armor),
+            }],
+        );
+        assert_eq!(
+            systems::actions::get_targeted_entities(&game_state, &action),
+            vec![fighter]
+        );
+        systems::actions::perform_action(&mut game_state, &action);
+
+        assert!(
+            systems::helpers::get_component::<HitPoints>(&game_state.world, fighter).current()
+                < max_hit_points
+        );
+    }
+}
@@ -0,0 +1,178 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::targeting::TargetInstance,
+            health::hit_points::HitPoints,
+            id::{ActionId, ClassId, EffectId, Name, ResourceId},
+            modifier::ModifierSource,
+            resource::{ResourceAmount, ResourceMap},
+        },
+        engine::{event::ActionData, game_state::GameState},
+        entities::character::Character,
+        systems,
+        test_utils::fixtures,
+    };
+
+    fn pool() -> ResourceId {
+        ResourceId::new("nat20_core", "resource.lay_on_hands.pool")
+    }
+
+    fn heal() -> ActionId {
+        ActionId::new("nat20_core", "action.lay_on_hands.heal")
+    }
+
+    fn cure() -> ActionId {
+        ActionId::new("nat20_core", "action.lay_on_hands.cure")
+    }
+
+    fn pool_uses(game_state: &GameState, entity: Entity) -> (ResourceAmount, ResourceAmount) {
+        let resources = systems::helpers::get_component::<ResourceMap>(&game_state.world, entity);
+        let budget = resources.get(&pool()).unwrap();
+        (
+            budget.current_uses()[0].clone(),
+            budget.max_uses()[0].clone(),
+        )
+    }
+
+    fn paladin_class() -> ClassId {
+        ClassId::new("nat20_core", "class.paladin")
+    }
+
+    fn level(game_state: &GameState, entity: Entity) -> u8 {
+        systems::class::class_level(&game_state.world, entity, &paladin_class())
+    }
+
+    fn setup() -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let paladin = game_state
+            .world
+            .spawn(Character::new(Name::new("Pally McPaladin")));
+        systems::quick_build::level_up_to(
+            &mut game_state.world,
+            paladin,
+            &paladin_class(),
+            None,
+            3,
+        )
+        .unwrap();
+        (game_state, paladin)
+    }
+
+    fn action(game_state: &GameState, actor: Entity, action_id: ActionId) -> ActionData {
+        let (context, cost) = systems::actions::available_actions(&game_state.world, actor)
+            .get(&action_id)
+            .unwrap()[0]
+            .clone();
+        ActionData::new(
+            actor,
+            action_id,
+            context,
+            cost,
+            vec![TargetInstance::Entity(actor)],
+        )
+    }
+
+    fn perform(game_state: &mut GameState, action: &ActionData) {
+        game_state.validate_action(action, true).unwrap();
+        systems::actions::perform_action(game_state, action);
+    }
+
+    #[test]
+    fn pool_is_five_per_level() {
+        let (mut game_state, paladin) = setup();
+        assert_eq!(level(&game_state, paladin), 3);
+        assert_eq!(
+            pool_uses(&game_state, paladin),
+            (ResourceAmount::Flat(15), ResourceAmount::Flat(15))
+        );
+
+        // Spent points stay spent, but the new ones are there right away
+        let mut action = action(&game_state, paladin, heal());
+        systems::actions::choose_amount(&mut action, 3).unwrap();
+        perform(&mut game_state, &action);
+
+        systems::class::increment_class_level(&mut game_state.world, paladin, &paladin_class());
+        assert_eq!(
+            pool_uses(&game_state, paladin),
+            (ResourceAmount::Flat(17), ResourceAmount::Flat(20))
+        );
+
+        // Levels in other classes don't grow the pool
+        systems::class::increment_class_level(
+            &mut game_state.world,
+            paladin,
+            &ClassId::new("nat20_core", "class.fighter"),
+        );
+        assert_eq!(
+            pool_uses(&game_state, paladin),
+            (ResourceAmount::Flat(17), ResourceAmount::Flat(20))
+        );
+    }
+
+    #[test]
+    fn lay_on_hands_heals_chosen_amount() {
+        let (mut game_state, paladin) = setup();
+        let size = 5 * level(&game_state, paladin);
+        {
+            let mut hit_points =
+                systems::helpers::get_component_mut::<HitPoints>(&mut game_state.world, paladin);
+            *hit_points = HitPoints::with_current(1, hit_points.max());
+        }
+
+        let mut action = action(&game_state, paladin, heal());
+        assert_eq!(
+            systems::actions::variable_amount_range(
+                &game_state.world,
+                paladin,
+                &action.resource_cost
+            ),
+            Some((1, size))
+        );
+        systems::actions::choose_amount(&mut action, 7).unwrap();
+        perform(&mut game_state, &action);
+
+        assert_eq!(
+            systems::helpers::get_component::<HitPoints>(&game_state.world, paladin).current(),
+            8
+        );
+        assert_eq!(
+            pool_uses(&game_state, paladin).0,
+            ResourceAmount::Flat(size - 7)
+        );
+    }
+
+    #[test]
+    fn lay_on_hands_cures_poison() {
+        let (mut game_state, paladin) = setup();
+        let size = 5 * level(&game_state, paladin);
+        let poisoned = EffectId::new("nat20_core", "effect.condition.poisoned");
+        systems::effects::add_permanent_effects(
+            &mut game_state.world,
+            paladin,
+            vec![poisoned.clone()],
+            &ModifierSource::Base,
+            None,
+        );
+        assert!(systems::effects::has_effect(
+            &game_state.world,
+            paladin,
+            &poisoned
+        ));
+
+        let action = action(&game_state, paladin, cure());
+        perform(&mut game_state, &action);
+
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            paladin,
+            &poisoned
+        ));
+        assert_eq!(
+            pool_uses(&game_state, paladin).0,
+            ResourceAmount::Flat(size - 5)
+        );
+    }
+}
@@ -0,0 +1,120 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            id::ItemId,
+            items::inventory::{Inventory, ItemContainer, ItemInstance},
+            time::TimeDuration,
+        },
+        engine::game_state::GameState,
+        registry::registry::ItemsRegistry,
+        systems::{self, light::LightError, time::TimeActivity},
+        test_utils::fixtures,
+    };
+
+    fn setup() -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        (game_state, fighter)
+    }
+
+    fn give(game_state: &mut GameState, entity: Entity, item: &str) -> usize {
+        let item = ItemsRegistry::get(&ItemId::new("nat20_core", item))
+            .unwrap()
+            .clone();
+        systems::inventory::add_item(&mut game_state.world, entity, item);
+        inventory(game_state, entity).items().len() - 1
+    }
+
+    fn inventory(game_state: &GameState, entity: Entity) -> Inventory {
+        systems::helpers::get_component_clone::<Inventory>(&game_state.world, entity)
+    }
+
+    fn count(game_state: &GameState, entity: Entity, name: &str) -> usize {
+        inventory(game_state, entity)
+            .items()
+            .iter()
+            .filter(|item| item.item().name == name)
+            .count()
+    }
+
+    fn is_lit(game_state: &GameState, entity: Entity, name: &str) -> bool {
+        inventory(game_state, entity)
+            .items()
+            .iter()
+            .any(|item| matches!(item, ItemInstance::Light(light) if light.item.name == name && light.lit))
+    }
+
+    fn pass_minutes(game_state: &mut GameState, minutes: u32) {
+        systems::time::pass_time(
+            game_state,
+            TimeDuration::from_minutes(minutes),
+            TimeActivity::Downtime,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn torch_lights_up_the_surroundings() {
+        let (mut game_state, fighter) = setup();
+        let torch = give(&mut game_state, fighter, "item.torch");
+
+        assert!(systems::light::emitted_light(&game_state.world).is_empty());
+
+        assert_eq!(
+            systems::light::toggle(&mut game_state.world, fighter, torch),
+            Ok(true)
+        );
+        let lights = systems::light::emitted_light(&game_state.world);
+        assert_eq!(lights.len(), 1);
+        assert_eq!(lights[0].carrier, fighter);
+        assert!(lights[0].dim_light > lights[0].bright_light);
+
+        // Putting the torch out saves what's left of it for later
+        pass_minutes(&mut game_state, 30);
+        assert_eq!(
+            systems::light::toggle(&mut game_state.world, fighter, torch),
+            Ok(false)
+        );
+        pass_minutes(&mut game_state, 60);
+        assert_eq!(count(&game_state, fighter, "Torch"), 1);
+
+        systems::light::toggle(&mut game_state.world, fighter, torch).unwrap();
+        pass_minutes(&mut game_state, 30);
+        assert_eq!(count(&game_state, fighter, "Torch"), 0);
+        assert!(systems::light::emitted_light(&game_state.world).is_empty());
+    }
+
+    #[test]
+    fn lantern_burns_oil() {
+        let (mut game_state, fighter) = setup();
+        let lantern = give(&mut game_state, fighter, "item.lantern");
+        give(&mut game_state, fighter, "item.oil_flask");
+
+        systems::light::toggle(&mut game_state.world, fighter, lantern).unwrap();
+        pass_minutes(&mut game_state, 6 * 60);
+        assert!(is_lit(&game_state, fighter, "Hooded Lantern"));
+        assert_eq!(count(&game_state, fighter, "Flask of Oil"), 0);
+
+        // Without any more oil the lantern goes out, but it's not thrown away
+        pass_minutes(&mut game_state, 6 * 60);
+        assert!(!is_lit(&game_state, fighter, "Hooded Lantern"));
+        assert_eq!(count(&game_state, fighter, "Hooded Lantern"), 1);
+        assert_eq!(
+            systems::light::toggle(&mut game_state.world, fighter, lantern),
+            Err(LightError::NoFuel(ItemId::new(
+                "nat20_core",
+                "item.oil_flask"
+            )))
+        );
+
+        give(&mut game_state, fighter, "item.oil_flask");
+        assert_eq!(
+            systems::light::toggle(&mut game_state.world, fighter, lantern),
+            Ok(true)
+        );
+        assert_eq!(count(&game_state, fighter, "Flask of Oil"), 0);
+    }
+}
@@ -0,0 +1,195 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{
+                action::ActionContext,
+                targeting::{TargetInstance, TargetingError},
+            },
+            class::ClassAndSubclass,
+            damage::DamageThreshold,
+            health::hit_points::HitPoints,
+            id::{ActionId, ClassId, Name, SpellId},
+            items::equipment::slots::EquipmentSlot,
+            species::CreatureSize,
+            spells::spellbook::SpellSource,
+        },
+        engine::game_state::GameState,
+        entities::object::{Object, ObjectMaterial},
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::wizard,
+            fixtures::creatures::monsters::goblin_warrior,
+            5.0,
+        )
+    }
+
+    fn place_between(game_state: &mut GameState, material: ObjectMaterial) -> Entity {
+        let object = game_state.world.spawn(Object::new(
+            Name::new("Window"),
+            material,
+            CreatureSize::Large,
+            HitPoints::new(3),
+            DamageThreshold(0),
+        ));
+        systems::geometry::teleport_to(&mut game_state.world, object, &Point3::new(2.5, 0.0, 0.0));
+        object
+    }
+
+    fn fire_bolt() -> (ActionId, ActionContext) {
+        (
+            ActionId::new("nat20_core", "action.fire_bolt"),
+            ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.fire_bolt"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 0,
+                metamagic: Vec::new(),
+            },
+        )
+    }
+
+    fn validate(
+        game_state: &GameState,
+        actor: Entity,
+        action_id: &ActionId,
+        context: &ActionContext,
+        target: Entity,
+    ) -> Result<(), TargetingError> {
+        systems::actions::targeting_context(&game_state.world, actor, action_id, context)
+            .validate_targets(
+                &game_state.world,
+                &game_state.geometry,
+                actor,
+                &[TargetInstance::Entity(target)],
+            )
+    }
+
+    #[test]
+    fn windows_block_line_of_effect_but_not_sight() {
+        let (mut game_state, wizard, goblin) = setup();
+        place_between(&mut game_state, ObjectMaterial::Glass);
+
+        assert!(
+            systems::geometry::line_of_sight_entity_entity(
+                &game_state.world,
+                &game_state.geometry,
+                wizard,
+                goblin
+            )
+            .has_line_of_sight
+        );
+        assert!(
+            !systems::geometry::line_of_effect_entity_entity(
+                &game_state.world,
+                &game_state.geometry,
+                wizard,
+                goblin
+            )
+            .has_line_of_effect
+        );
+    }
+
+    #[test]
+    fn opaque_objects_block_both() {
+        let (mut game_state, wizard, goblin) = setup();
+        place_between(&mut game_state, ObjectMaterial::Wood);
+
+        assert!(
+            !systems::geometry::line_of_sight_entity_entity(
+                &game_state.world,
+                &game_state.geometry,
+                wizard,
+                goblin
+            )
+            .has_line_of_sight
+        );
+        assert!(
+            !systems::geometry::line_of_effect_entity_entity(
+                &game_state.world,
+                &game_state.geometry,
+                wizard,
+                goblin
+            )
+            .has_line_of_effect
+        );
+    }
+
+    #[test]
+    fn creatures_dont_block_line_of_effect() {
+        let (mut game_state, wizard, goblin) = setup();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::new(2.5, 0.0, 0.0));
+
+        assert!(
+            systems::geometry::line_of_effect_entity_entity(
+                &game_state.world,
+                &game_state.geometry,
+                wizard,
+                goblin
+            )
+            .has_line_of_effect
+        );
+    }
+
+    #[test]
+    fn spells_cant_be_cast_through_windows() {
+        let (mut game_state, wizard, goblin) = setup();
+        let (action_id, context) = fire_bolt();
+        assert_eq!(
+            validate(&game_state, wizard, &action_id, &context, goblin),
+            Ok(())
+        );
+
+        place_between(&mut game_state, ObjectMaterial::Glass);
+        assert_eq!(
+            validate(&game_state, wizard, &action_id, &context, goblin),
+            Err(TargetingError::NoLineOfEffect {
+                target: TargetInstance::Entity(goblin)
+            })
+        );
+    }
+
+    #[test]
+    fn windows_can_be_targeted() {
+        let (mut game_state, wizard, _) = setup();
+        let window = place_between(&mut game_state, ObjectMaterial::Glass);
+        let (action_id, context) = fire_bolt();
+
+        assert_eq!(
+            validate(&game_state, wizard, &action_id, &context, window),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn weapon_attacks_need_line_of_effect() {
+        let (mut game_state, wizard, goblin) = setup();
+        place_between(&mut game_state, ObjectMaterial::Glass);
+
+        // The goblin can see the wizard, but can't shoot through the window
+        assert_eq!(
+            validate(
+                &game_state,
+                goblin,
+                &ActionId::new("nat20_core", "action.weapon_attack"),
+                &ActionContext::Weapon {
+                    slot: EquipmentSlot::RangedMainHand
+                },
+                wizard,
+            ),
+            Err(TargetingError::NoLineOfEffect {
+                target: TargetInstance::Entity(wizard)
+            })
+        );
+    }
+}
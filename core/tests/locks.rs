@@ -0,0 +1,152 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            damage::{DamageRoll, DamageSource, DamageThreshold, DamageType},
+            health::hit_points::HitPoints,
+            id::{IdProvider, ItemId, Name, SpellId},
+            items::{
+                inventory::{Inventory, ItemInstance},
+                item::Item,
+            },
+            lock::Lock,
+            species::CreatureSize,
+        },
+        engine::game_state::GameState,
+        entities::object::{Object, ObjectMaterial},
+        registry::registry::ItemsRegistry,
+        systems::{self, locks::LockError},
+        test_utils::fixtures,
+    };
+
+    fn setup() -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        (game_state, fighter)
+    }
+
+    fn key() -> ItemInstance {
+        ItemInstance::Item(Item {
+            id: ItemId::new("nat20_core", "test.iron_key"),
+            name: "Iron Key".to_string(),
+            ..Item::default()
+        })
+    }
+
+    fn locked_door(game_state: &mut GameState, lock: Lock) -> Entity {
+        game_state.world.spawn((
+            Object::new(
+                Name::new("Wooden Door"),
+                ObjectMaterial::Wood,
+                CreatureSize::Large,
+                HitPoints::new(18),
+                DamageThreshold(5),
+            ),
+            lock,
+        ))
+    }
+
+    fn locked_chest(game_state: &mut GameState, lock: Lock) -> Entity {
+        let chest = game_state.world.spawn((Inventory::new(), lock));
+        let dagger = ItemsRegistry::get(&ItemId::new("nat20_core", "item.dagger"))
+            .unwrap()
+            .clone();
+        systems::inventory::add_item(&mut game_state.world, chest, dagger);
+        chest
+    }
+
+    /// Keep trying until the lock opens. Natural 1s always fail, so a single
+    /// attempt isn't guaranteed to succeed even against the lowest DC.
+    fn attempt_until_open(
+        game_state: &mut GameState,
+        target: Entity,
+        mut attempt: impl FnMut(&mut GameState) -> Result<(), LockError>,
+    ) {
+        for _ in 0..100 {
+            if !systems::locks::is_locked(&game_state.world, target) {
+                return;
+            }
+            attempt(game_state).unwrap();
+        }
+        panic!("Failed to open the lock on {:?}", target);
+    }
+
+    #[test]
+    fn key_opens_lock() {
+        let (mut game_state, fighter) = setup();
+        let chest = locked_chest(&mut game_state, Lock::new(40, 40, Some(key().id().clone())));
+
+        assert_eq!(
+            systems::locks::loot(&mut game_state, fighter, chest),
+            Err(LockError::Locked(chest))
+        );
+
+        systems::inventory::add_item(&mut game_state.world, fighter, key());
+        assert!(systems::locks::has_key(&game_state.world, fighter, chest));
+        let items = systems::locks::loot(&mut game_state, fighter, chest).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(!systems::locks::is_locked(&game_state.world, chest));
+        assert!(
+            systems::helpers::get_component::<Inventory>(&game_state.world, chest)
+                .items()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn pick_lock_requires_thieves_tools() {
+        let (mut game_state, fighter) = setup();
+        let door = locked_door(&mut game_state, Lock::new(0, 40, None));
+
+        assert_eq!(
+            systems::locks::pick(&mut game_state, fighter, door),
+            Err(LockError::NoThievesTools)
+        );
+
+        let tools = ItemsRegistry::get(&systems::locks::thieves_tools())
+            .unwrap()
+            .clone();
+        systems::inventory::add_item(&mut game_state.world, fighter, tools);
+        attempt_until_open(&mut game_state, door, |game_state| {
+            systems::locks::pick(game_state, fighter, door)
+        });
+
+        assert_eq!(
+            systems::locks::pick(&mut game_state, fighter, door),
+            Err(LockError::NotLocked(door))
+        );
+    }
+
+    #[test]
+    fn force_lock_open() {
+        let (mut game_state, fighter) = setup();
+        let door = locked_door(&mut game_state, Lock::new(40, 0, None));
+
+        attempt_until_open(&mut game_state, door, |game_state| {
+            systems::locks::force(game_state, fighter, door)
+        });
+        // Opening a lock doesn't need the key once it's unlocked
+        assert_eq!(systems::locks::open(&mut game_state, fighter, door), Ok(()));
+    }
+
+    #[test]
+    fn breaking_door_opens_lock() {
+        let (mut game_state, _) = setup();
+        let door = locked_door(&mut game_state, Lock::default());
+
+        systems::health::damage(
+            &mut game_state,
+            door,
+            &DamageRoll::new(
+                "10d6".parse().unwrap(),
+                DamageType::Fire,
+                DamageSource::Spell(SpellId::new("nat20_core", "test.spell")),
+            )
+            .roll(false),
+            None,
+        );
+        assert!(!systems::locks::is_locked(&game_state.world, door));
+    }
+}
@@ -0,0 +1,236 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{
+                action::ActionContext,
+                preview::CheckPreview,
+                targeting::{ItemTarget, TargetInstance},
+            },
+            d20::D20CheckOverride,
+            id::{ActionId, ItemId},
+            items::{
+                equipment::slots::EquipmentSlot,
+                inventory::{Inventory, ItemContainer, ItemInstance},
+            },
+            modifier::{Modifiable, ModifierSource},
+            resource::ResourceAmountMap,
+            skill::{Skill, SkillContest, SkillSet},
+        },
+        engine::{
+            event::{ActionData, ActionError},
+            game_state::GameState,
+            rules::OptionalRule,
+        },
+        systems,
+        test_utils::fixtures,
+    };
+    use uom::si::{f32::Length, length::foot};
+
+    fn disarm() -> ActionId {
+        ActionId::new("nat20_core", "action.disarm")
+    }
+
+    fn sunder() -> ActionId {
+        ActionId::new("nat20_core", "action.sunder")
+    }
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            1.0,
+        )
+    }
+
+    fn maneuver(
+        game_state: &GameState,
+        actor: Entity,
+        action_id: ActionId,
+        target: TargetInstance,
+    ) -> ActionData {
+        let (context, cost) = systems::actions::all_actions(&game_state.world, actor)
+            .get(&action_id)
+            .unwrap()[0]
+            .clone();
+        ActionData::new(actor, action_id, context, cost, vec![target])
+    }
+
+    fn carries(game_state: &GameState, entity: Entity, item: &str) -> bool {
+        systems::helpers::get_component::<Inventory>(&game_state.world, entity)
+            .items()
+            .iter()
+            .any(|carried| carried.item().id == ItemId::new("nat20_core", item))
+    }
+
+    #[test]
+    fn maneuvers_require_optional_rules() {
+        let (mut game_state, fighter, goblin) = setup();
+        let called_shot = ActionId::new("nat20_core", "action.called_shot");
+
+        let actions = game_state.actions(fighter);
+        assert!(!actions.contains_key(&disarm()));
+        assert!(!actions.contains_key(&called_shot));
+
+        game_state.set_rule(OptionalRule::AttackManeuvers, true);
+        let actions = game_state.actions(fighter);
+        assert!(actions.contains_key(&disarm()));
+        assert!(actions.contains_key(&sunder()));
+        assert!(!actions.contains_key(&called_shot));
+
+        // The variants belong to the same rule as the called shot itself
+        let head_shot = ActionData::new(
+            fighter,
+            ActionId::new("nat20_core", "action.called_shot.head"),
+            ActionContext::Weapon {
+                slot: EquipmentSlot::MeleeMainHand,
+            },
+            ResourceAmountMap::new(),
+            vec![TargetInstance::Entity(goblin)],
+        );
+        assert!(matches!(
+            game_state.validate_action(&head_shot, false),
+            Err(ActionError::RuleDisabled(OptionalRule::CalledShots))
+        ));
+
+        game_state.set_rule(OptionalRule::CalledShots, true);
+        assert!(game_state.actions(fighter).contains_key(&called_shot));
+    }
+
+    #[test]
+    fn contest_ties_go_to_the_defender() {
+        let (game_state, fighter, goblin) = setup();
+        let contest = SkillContest {
+            skill: Skill::Athletics,
+            opposed_by: vec![Skill::Athletics, Skill::Acrobatics],
+        };
+
+        // The goblin is more nimble than it is strong
+        assert_eq!(
+            systems::d20::contest_defense(&game_state.world, goblin, &contest),
+            Skill::Acrobatics
+        );
+        let (result, dc) = systems::d20::contest_dc(&game_state.world, goblin, &contest);
+        assert_eq!(dc.key, Skill::Athletics);
+        assert_eq!(dc.dc.total(), result.d20_result().total() as i32 + 1);
+
+        let chance =
+            systems::d20::contest_success_probability(&game_state.world, fighter, goblin, &contest);
+        assert!(chance > 0.0 && chance < 1.0);
+    }
+
+    #[test]
+    fn disarm_drops_the_weapon() {
+        let (mut game_state, fighter, goblin) = setup();
+        systems::helpers::get_component_mut::<SkillSet>(&mut game_state.world, fighter)
+            .add_override(
+                &Skill::Athletics,
+                D20CheckOverride::AutoSuccess,
+                ModifierSource::Custom("Rigged".to_string()),
+            );
+
+        let action = maneuver(
+            &game_state,
+            fighter,
+            disarm(),
+            TargetInstance::Item {
+                owner: goblin,
+                item: ItemTarget::Equipped(EquipmentSlot::MeleeMainHand),
+            },
+        );
+        systems::actions::perform_action(&mut game_state, &action);
+
+        let loadout = systems::loadout::loadout(&game_state.world, goblin);
+        assert!(
+            loadout
+                .weapon_in_hand(&EquipmentSlot::MeleeMainHand)
+                .is_none()
+        );
+        // Only the targeted weapon is dropped
+        assert!(
+            loadout
+                .weapon_in_hand(&EquipmentSlot::RangedMainHand)
+                .is_some()
+        );
+        drop(loadout);
+        assert!(!carries(&game_state, goblin, "item.scimitar"));
+
+        // The weapon ends up on the ground, where anyone can pick it up
+        let reach = systems::inventory::ground_items_in_reach(
+            &game_state.world,
+            goblin,
+            Length::new::<foot>(5.0),
+        );
+        assert_eq!(reach.len(), 1);
+        assert_eq!(
+            game_state
+                .world
+                .get::<&ItemInstance>(reach[0])
+                .unwrap()
+                .item()
+                .id,
+            ItemId::new("nat20_core", "item.scimitar")
+        );
+    }
+
+    #[test]
+    fn sunder_attacks_the_item() {
+        let (game_state, fighter, goblin) = setup();
+        let item_armor_class = |slot: EquipmentSlot| {
+            let action = maneuver(
+                &game_state,
+                fighter,
+                sunder(),
+                TargetInstance::Item {
+                    owner: goblin,
+                    item: ItemTarget::Equipped(slot),
+                },
+            );
+            let preview = systems::actions::preview(&game_state, &action);
+            let Some(CheckPreview::AttackRoll { armor_class, .. }) = &preview[0].check else {
+                panic!("Expected an attack roll, got {:?}", preview[0].check);
+            };
+            armor_class.total()
+        };
+
+        // The armor class of the item depends on its material, i.e. the
+        // studded leather armor is as easy to hit as cloth, but the scimitar is
+        // made of steel
+        assert_eq!(item_armor_class(EquipmentSlot::Armor), 11);
+        assert_eq!(item_armor_class(EquipmentSlot::MeleeMainHand), 19);
+    }
+
+    #[test]
+    fn damaged_items_break() {
+        let (mut game_state, _, goblin) = setup();
+        let armor = ItemTarget::Equipped(EquipmentSlot::Armor);
+        let unarmored = systems::loadout::armor_class(&game_state.world, goblin).total();
+        let hit_points = systems::inventory::target_item(&game_state.world, goblin, &armor)
+            .unwrap()
+            .item()
+            .hit_points();
+        // Studded leather armor is a small, fragile object
+        assert_eq!(hit_points, 3);
+
+        let outcome =
+            systems::inventory::damage_item(&mut game_state.world, goblin, &armor, hit_points - 1)
+                .unwrap();
+        assert!(!outcome.broken);
+        assert_eq!(outcome.remaining_hit_points, 1);
+        assert_eq!(
+            systems::inventory::target_item(&game_state.world, goblin, &armor)
+                .unwrap()
+                .item()
+                .damage_taken,
+            hit_points - 1
+        );
+
+        let outcome =
+            systems::inventory::damage_item(&mut game_state.world, goblin, &armor, 1).unwrap();
+        assert!(outcome.broken);
+        assert!(systems::inventory::target_item(&game_state.world, goblin, &armor).is_none());
+        assert!(systems::loadout::armor_class(&game_state.world, goblin).total() < unarmored);
+    }
+}
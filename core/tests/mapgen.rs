@@ -0,0 +1,176 @@
+extern crate nat20_core;
+
+mod tests {
+    use nat20_core::{
+        components::{hidden::Trap, id::Name},
+        engine::game_state::GameState,
+        entities::{monster::MonsterTag, object::ObjectTag},
+        systems::{
+            self,
+            mapgen::{self, MapGenConfig, MapGenError, Tile},
+        },
+    };
+    use rerecast::ConfigBuilder;
+
+    fn config(seed: u64) -> MapGenConfig {
+        MapGenConfig {
+            seed: Some(seed),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mapgen_is_deterministic() {
+        let a = mapgen::generate(&config(42)).unwrap();
+        let b = mapgen::generate(&config(42)).unwrap();
+
+        assert_eq!(a.rooms, b.rooms);
+        assert_eq!(a.light_sources, b.light_sources);
+        assert!(
+            a.positions()
+                .all(|position| a.get(&position) == b.get(&position))
+        );
+    }
+
+    #[test]
+    fn mapgen_layout() {
+        let config = config(7);
+        let map = mapgen::generate(&config).unwrap();
+
+        assert!(!map.rooms.is_empty());
+        assert!(map.rooms.len() <= config.max_rooms);
+        for (i, room) in map.rooms.iter().enumerate() {
+            for other in &map.rooms[i + 1..] {
+                assert!(!room.intersects(other));
+            }
+            assert!(room.tiles().all(|position| map.is_walkable(&position)));
+        }
+
+        // The map is surrounded by walls
+        for position in map.positions() {
+            if position.x == 0
+                || position.y == 0
+                || position.x == map.width - 1
+                || position.y == map.height - 1
+            {
+                assert_eq!(map.get(&position), Some(Tile::Wall));
+            }
+        }
+
+        assert_eq!(map.light_sources.len(), map.rooms.len());
+    }
+
+    #[test]
+    fn mapgen_encounters() {
+        let config = MapGenConfig {
+            encounter_chance: 1.0,
+            party_size: 4,
+            ..config(3)
+        };
+        let map = mapgen::generate(&config).unwrap();
+
+        assert_eq!(map.encounters.len(), map.rooms.len() - 1);
+        for encounter in &map.encounters {
+            assert_ne!(encounter.room, 0);
+            assert_eq!(encounter.positions.len(), 2);
+            let room = &map.rooms[encounter.room];
+            assert!(
+                encounter
+                    .positions
+                    .iter()
+                    .all(|position| room.contains(position))
+            );
+        }
+    }
+
+    #[test]
+    fn mapgen_populate() {
+        let config = MapGenConfig {
+            width: 20,
+            height: 20,
+            max_rooms: 4,
+            encounter_chance: 1.0,
+            trap_chance: 0.5,
+            ..config(11)
+        };
+        let map = mapgen::generate(&config).unwrap();
+        let mut game_state = GameState::new(map.to_geometry(&ConfigBuilder::default().build()));
+
+        mapgen::populate(&mut game_state, &map);
+
+        let doors = game_state
+            .world
+            .query::<(&ObjectTag, &Name)>()
+            .iter()
+            .filter(|(_, (_, name))| name.as_str() == "Wooden Door")
+            .count();
+        assert_eq!(doors, map.positions_of(Tile::Door).count());
+
+        let traps = game_state.world.query::<&Trap>().iter().count();
+        assert_eq!(traps, map.positions_of(Tile::Trap).count());
+
+        // Every torch on the walls is lit
+        let lights = systems::light::emitted_light(&game_state.world).len();
+        assert_eq!(lights, map.light_sources.len());
+
+        let monsters = game_state.world.query::<&MonsterTag>().iter().count();
+        let expected = map
+            .encounters
+            .iter()
+            .map(|encounter| encounter.positions.len())
+            .sum::<usize>();
+        assert_eq!(monsters, expected);
+    }
+
+    #[test]
+    fn mapgen_invalid_config() {
+        let invalid = MapGenConfig {
+            min_room_size: 8,
+            max_room_size: 4,
+            ..config(1)
+        };
+        assert_eq!(
+            mapgen::generate(&invalid).unwrap_err(),
+            MapGenError::InvalidRoomSize { min: 8, max: 4 }
+        );
+
+        let invalid = MapGenConfig {
+            width: 5,
+            ..config(1)
+        };
+        assert!(matches!(
+            mapgen::generate(&invalid).unwrap_err(),
+            MapGenError::RoomTooLarge { width: 5, .. }
+        ));
+
+        let invalid = MapGenConfig {
+            trap_chance: 1.5,
+            ..config(1)
+        };
+        assert!(matches!(
+            mapgen::generate(&invalid).unwrap_err(),
+            MapGenError::InvalidChance {
+                name: "trap_chance",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn mapgen_rooms_larger_than_the_map() {
+        // Rooms are capped to fit inside the walls around the map
+        let config = MapGenConfig {
+            width: 8,
+            height: 8,
+            min_room_size: 4,
+            max_room_size: 20,
+            ..config(5)
+        };
+        let map = mapgen::generate(&config).unwrap();
+        assert!(
+            map.rooms
+                .iter()
+                .all(|room| room.x + room.width < map.width && room.y + room.height < map.height)
+        );
+    }
+}
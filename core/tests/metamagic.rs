@@ -0,0 +1,129 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::targeting::TargetInstance,
+            id::{ActionId, ClassId, EffectId, ResourceId},
+            level_up::{ChoiceItem, LevelUpPrompt},
+            modifier::ModifierSource,
+            resource::{ResourceAmount, ResourceBudget, ResourceBudgetKind, ResourceMap},
+            spells::metamagic::{Metamagic, sorcery_point_id},
+        },
+        engine::{
+            event::{ActionData, ActionError},
+            game_state::GameState,
+        },
+        registry::registry::ClassesRegistry,
+        systems::{self, actions::ActionUsabilityError},
+        test_utils::fixtures,
+    };
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::wizard,
+            fixtures::creatures::monsters::goblin_warrior,
+            5.0,
+        )
+    }
+
+    fn fire_bolt(game_state: &GameState, wizard: Entity, goblin: Entity) -> ActionData {
+        let action_id = ActionId::new("nat20_core", "action.fire_bolt");
+        let (context, cost) = systems::actions::available_actions(&game_state.world, wizard)
+            [&action_id]
+            .first()
+            .cloned()
+            .unwrap();
+        ActionData::new(
+            wizard,
+            action_id,
+            context,
+            cost,
+            vec![TargetInstance::Entity(goblin)],
+        )
+    }
+
+    fn can_afford(game_state: &GameState, entity: Entity, resource: &str, amount: u8) -> bool {
+        systems::helpers::get_component::<ResourceMap>(&game_state.world, entity).can_afford(
+            &ResourceId::new("nat20_core", resource),
+            &ResourceAmount::Flat(amount),
+        )
+    }
+
+    #[test]
+    fn sorcerers_pick_metamagic_at_levels_two_and_ten() {
+        let sorcerer = ClassesRegistry::get(&ClassId::new("nat20_core", "class.sorcerer")).unwrap();
+        let metamagic_choice = |level: u8| {
+            sorcerer.base.prompts_by_level[&level]
+                .iter()
+                .find_map(|prompt| match prompt {
+                    LevelUpPrompt::Choice(choice) if choice.id.starts_with("choice.metamagic") => {
+                        Some(choice)
+                    }
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("Sorcerers should pick metamagic at level {}", level))
+        };
+
+        let level_2 = metamagic_choice(2);
+        assert_eq!(level_2.picks, 2);
+        assert!(level_2.options.contains(&ChoiceItem::Effect(EffectId::new(
+            "nat20_core",
+            "effect.sorcerer.metamagic.quickened"
+        ))));
+
+        // Each choice needs its own id, so the decisions don't overwrite each
+        // other
+        let level_10 = metamagic_choice(10);
+        assert_eq!(level_10.picks, 1);
+        assert_ne!(level_2.id, level_10.id);
+    }
+
+    #[test]
+    fn metamagic_is_validated_and_charged() {
+        let (mut game_state, wizard, goblin) = setup();
+        let mut quickened = fire_bolt(&game_state, wizard, goblin);
+        systems::spells::apply_metamagic(&mut quickened, Metamagic::Quickened);
+
+        assert!(matches!(
+            game_state.validate_action(&quickened, false),
+            Err(ActionError::Usability(
+                ActionUsabilityError::UnknownMetamagic(Metamagic::Quickened)
+            ))
+        ));
+
+        systems::effects::add_permanent_effect(
+            &mut game_state.world,
+            wizard,
+            EffectId::new("nat20_core", "effect.sorcerer.metamagic.quickened"),
+            &ModifierSource::Custom("Test".to_string()),
+            None,
+        );
+        systems::helpers::get_component_mut::<ResourceMap>(&mut game_state.world, wizard).add(
+            sorcery_point_id(),
+            ResourceBudgetKind::Flat(ResourceBudget::with_max_uses(2).unwrap()),
+            true,
+        );
+
+        // The Sorcery Points can't be left out of the cost
+        let mut unpaid = quickened.clone();
+        unpaid.resource_cost.remove(&sorcery_point_id());
+        assert!(matches!(
+            game_state.validate_action(&unpaid, false),
+            Err(ActionError::Usability(
+                ActionUsabilityError::MetamagicNotPaid(2)
+            ))
+        ));
+
+        game_state.validate_action(&quickened, true).unwrap();
+        assert!(!can_afford(
+            &game_state,
+            wizard,
+            "resource.sorcerer.sorcery_point",
+            1
+        ));
+        assert!(can_afford(&game_state, wizard, "resource.action", 1));
+        assert!(!can_afford(&game_state, wizard, "resource.bonus_action", 1));
+    }
+}
@@ -0,0 +1,172 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::collections::HashSet;
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{health::hit_points::HitPoints, id::MonsterId},
+        engine::{
+            encounter::{EncounterId, MOB_SIZE_MIN},
+            event::{EncounterEvent, EventKind},
+            game_state::GameState,
+        },
+        systems::{self, d20::D20ResultKind},
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn goblins(game_state: &mut GameState, count: usize) -> Vec<Entity> {
+        (0..count)
+            .map(|i| {
+                let goblin =
+                    fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+                systems::helpers::set_component(
+                    &mut game_state.world,
+                    goblin,
+                    MonsterId::new("nat20_core", "monster.goblin_warrior"),
+                );
+                // Surround the origin, within reach of whoever is standing there
+                let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                systems::geometry::teleport_to(
+                    &mut game_state.world,
+                    goblin,
+                    &Point3::new(angle.cos(), 0.0, angle.sin()),
+                );
+                goblin
+            })
+            .collect()
+    }
+
+    fn setup(count: usize) -> (GameState, EncounterId, Entity, Vec<Entity>) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::origin());
+        let goblins = goblins(&mut game_state, count);
+
+        let mut participants = HashSet::from([fighter]);
+        participants.extend(goblins.iter().copied());
+        let encounter_id = game_state.start_encounter(participants);
+        (game_state, encounter_id, fighter, goblins)
+    }
+
+    #[test]
+    fn identical_monsters_form_mob() {
+        let (game_state, encounter_id, fighter, goblins) = setup(MOB_SIZE_MIN);
+        let encounter = game_state.encounter(&encounter_id).unwrap();
+
+        assert_eq!(encounter.mobs().len(), 1);
+        assert!(encounter.mob(fighter).is_none());
+        let mob = encounter.mob(goblins[0]).unwrap();
+        assert_eq!(mob.members.len(), MOB_SIZE_MIN);
+
+        // The members share a single initiative roll, and act back to back
+        let positions = encounter
+            .initiative_order()
+            .iter()
+            .enumerate()
+            .filter(|(_, (entity, _))| goblins.contains(entity))
+            .map(|(index, (_, roll))| (index, roll.total()))
+            .collect::<Vec<_>>();
+        assert!(positions.iter().all(|(_, total)| *total == positions[0].1));
+        assert!(
+            positions
+                .windows(2)
+                .all(|window| window[1].0 == window[0].0 + 1)
+        );
+    }
+
+    #[test]
+    fn small_groups_do_not_form_mob() {
+        let (game_state, encounter_id, _, _) = setup(MOB_SIZE_MIN - 1);
+        let encounter = game_state.encounter(&encounter_id).unwrap();
+        assert!(encounter.mobs().is_empty());
+    }
+
+    #[test]
+    fn reinforcements_join_mob() {
+        let (mut game_state, encounter_id, _, goblins) = setup(MOB_SIZE_MIN);
+        let reinforcement = self::goblins(&mut game_state, 1)[0];
+        game_state.add_to_encounter(&encounter_id, reinforcement);
+
+        let encounter = game_state.encounter(&encounter_id).unwrap();
+        let mob = encounter.mob(goblins[0]).unwrap();
+        assert!(mob.members.contains(&reinforcement));
+    }
+
+    #[test]
+    fn mob_takes_turns_together() {
+        let (mut game_state, encounter_id, fighter, goblins) = setup(MOB_SIZE_MIN);
+        // Make sure the fighter survives the onslaught, and gets to take a turn
+        systems::helpers::set_component(&mut game_state.world, fighter, HitPoints::new(1000));
+        if game_state
+            .encounter(&encounter_id)
+            .unwrap()
+            .current_entity()
+            == fighter
+        {
+            game_state.end_turn(fighter);
+        }
+
+        let attack = systems::mob::take_turn(&mut game_state, &encounter_id).unwrap();
+        assert_eq!(attack.target, fighter);
+        assert_eq!(attack.attackers, goblins.len());
+        assert!(attack.hits <= attack.attackers);
+
+        // All members have acted, so it's the fighter's turn again
+        let encounter = game_state.encounter(&encounter_id).unwrap();
+        assert_eq!(encounter.current_entity(), fighter);
+    }
+
+    #[test]
+    fn mob_attacks_are_resolved_one_by_one_and_logged_together() {
+        let (mut game_state, encounter_id, fighter, _) = setup(MOB_SIZE_MIN);
+        systems::helpers::set_component(&mut game_state.world, fighter, HitPoints::new(1000));
+        if game_state
+            .encounter(&encounter_id)
+            .unwrap()
+            .current_entity()
+            == fighter
+        {
+            game_state.end_turn(fighter);
+        }
+
+        let attack = systems::mob::take_turn(&mut game_state, &encounter_id).unwrap();
+
+        // Every attack is rolled on its own, so reactions and effects can
+        // respond to it, but only the mob attack as a whole is logged
+        let events = &game_state
+            .encounter(&encounter_id)
+            .unwrap()
+            .combat_log()
+            .events;
+        let attack_rolls = events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    &event.kind,
+                    EventKind::D20CheckPerformed(_, D20ResultKind::AttackRoll { .. }, _)
+                )
+            })
+            .count();
+        assert_eq!(attack_rolls, attack.attackers);
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(&event.kind, EventKind::ActionPerformed { .. }))
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(
+                    &event.kind,
+                    EventKind::Encounter(EncounterEvent::MobAttack(..))
+                ))
+                .count(),
+            1
+        );
+
+        let hit_points = systems::helpers::get_component::<HitPoints>(&game_state.world, fighter);
+        assert_eq!(1000 - hit_points.current() as i32, attack.damage);
+    }
+}
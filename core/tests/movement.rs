@@ -0,0 +1,57 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{engine::geometry::WorldPath, systems, test_utils::fixtures};
+    use parry3d::na::Point3;
+    use uom::si::length::foot;
+
+    fn setup() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut world).id();
+        systems::geometry::teleport_to(&mut world, goblin, &Point3::new(1.0, 0.0, 0.0));
+        (world, fighter, goblin)
+    }
+
+    #[test]
+    fn melee_reach() {
+        let (world, _, goblin) = setup();
+        // Goblins wield a scimitar, which doesn't have reach
+        let reach = systems::movement::melee_reach(&world, goblin).get::<foot>();
+        assert!((reach - 5.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn leaving_reach_provokes_opportunity_attack() {
+        let (world, fighter, goblin) = setup();
+        let path = WorldPath::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(-5.0, 0.0, 0.0),
+        ]);
+
+        assert_eq!(
+            systems::movement::opportunity_attackers(&world, fighter, &path),
+            vec![goblin]
+        );
+    }
+
+    #[test]
+    fn staying_in_reach_does_not_provoke() {
+        let (world, fighter, _) = setup();
+        let path = WorldPath::new(vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 1.0)]);
+
+        assert!(systems::movement::opportunity_attackers(&world, fighter, &path).is_empty());
+    }
+
+    #[test]
+    fn entering_reach_does_not_provoke() {
+        let (world, fighter, _) = setup();
+        let path = WorldPath::new(vec![
+            Point3::new(-5.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 0.0),
+        ]);
+
+        assert!(systems::movement::opportunity_attackers(&world, fighter, &path).is_empty());
+    }
+}
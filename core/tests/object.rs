@@ -0,0 +1,90 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::targeting::TargetInstance,
+            damage::{DamageRoll, DamageSource, DamageThreshold, DamageType},
+            health::{hit_points::HitPoints, life_state::LifeState},
+            id::{Name, SpellId},
+            modifier::Modifiable,
+            species::CreatureSize,
+        },
+        engine::game_state::GameState,
+        entities::object::{Object, ObjectMaterial},
+        systems,
+        test_utils::fixtures,
+    };
+
+    fn wooden_door(game_state: &mut GameState) -> Entity {
+        game_state.world.spawn(Object::new(
+            Name::new("Wooden Door"),
+            ObjectMaterial::Wood,
+            CreatureSize::Large,
+            HitPoints::new(18),
+            DamageThreshold(5),
+        ))
+    }
+
+    fn damage(game_state: &mut GameState, target: Entity, dice: &str, damage_type: DamageType) {
+        systems::health::damage(
+            game_state,
+            target,
+            &DamageRoll::new(
+                dice.parse().unwrap(),
+                damage_type,
+                DamageSource::Spell(SpellId::new("nat20_core", "test.spell")),
+            )
+            .roll(false),
+            None,
+        );
+    }
+
+    fn current_hit_points(game_state: &GameState, entity: Entity) -> u32 {
+        systems::helpers::get_component::<HitPoints>(&game_state.world, entity).current()
+    }
+
+    #[test]
+    fn object_armor_class_and_targeting() {
+        let mut game_state = fixtures::engine::game_state();
+        let door = wooden_door(&mut game_state);
+
+        assert_eq!(
+            systems::loadout::armor_class(&game_state.world, door).total(),
+            15
+        );
+        assert_eq!(
+            TargetInstance::from_entity(&game_state.world, door),
+            TargetInstance::Object(door)
+        );
+    }
+
+    #[test]
+    fn object_damage_threshold() {
+        let mut game_state = fixtures::engine::game_state();
+        let door = wooden_door(&mut game_state);
+
+        // 1d4 is always below the damage threshold
+        damage(&mut game_state, door, "1d4", DamageType::Bludgeoning);
+        assert_eq!(current_hit_points(&game_state, door), 18);
+
+        // 10d6 always meets the threshold and destroys the door
+        damage(&mut game_state, door, "10d6", DamageType::Fire);
+        assert_eq!(current_hit_points(&game_state, door), 0);
+        assert_eq!(
+            *systems::helpers::get_component::<LifeState>(&game_state.world, door),
+            LifeState::Dead
+        );
+    }
+
+    #[test]
+    fn object_poison_and_psychic_immunity() {
+        let mut game_state = fixtures::engine::game_state();
+        let door = wooden_door(&mut game_state);
+
+        damage(&mut game_state, door, "10d6", DamageType::Poison);
+        damage(&mut game_state, door, "10d6", DamageType::Psychic);
+        assert_eq!(current_hit_points(&game_state, door), 18);
+    }
+}
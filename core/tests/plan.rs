@@ -0,0 +1,147 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::targeting::TargetInstance,
+            id::{ActionId, ResourceId},
+            modifier::ModifierSource,
+            resource::{ResourceAmount, ResourceMap},
+        },
+        engine::{
+            event::{ActionData, ActionError},
+            game_state::GameState,
+        },
+        systems::{
+            self,
+            actions::ActionUsabilityError,
+            plan::{PlanError, PlannedStep, TurnPlan},
+        },
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup(goblin_position: Point3<f32>) -> (GameState, Entity, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::origin());
+        systems::geometry::teleport_to(&mut game_state.world, goblin, &goblin_position);
+        (game_state, fighter, goblin)
+    }
+
+    fn attack(game_state: &GameState, fighter: Entity, goblin: Entity) -> PlannedStep {
+        let action_id = ActionId::new("nat20_core", "action.weapon_attack");
+        let available_actions = systems::actions::available_actions(&game_state.world, fighter);
+        let (context, cost) = available_actions.get(&action_id).unwrap()[0].clone();
+        PlannedStep::Action(ActionData::new(
+            fighter,
+            action_id,
+            context,
+            cost,
+            vec![TargetInstance::Entity(goblin)],
+        ))
+    }
+
+    fn has_action(game_state: &GameState, fighter: Entity) -> bool {
+        systems::helpers::get_component::<ResourceMap>(&game_state.world, fighter).can_afford(
+            &ResourceId::new("nat20_core", "resource.action"),
+            &ResourceAmount::Flat(1),
+        )
+    }
+
+    #[test]
+    fn empty_plan_is_rejected() {
+        let (mut game_state, fighter, _) = setup(Point3::new(1.0, 0.0, 0.0));
+        let errors = systems::plan::validate(&mut game_state, &TurnPlan::new(fighter)).unwrap_err();
+        assert!(matches!(errors[..], [PlanError::EmptyPlan]));
+    }
+
+    #[test]
+    fn plan_exceeding_resources_is_rejected() {
+        let (mut game_state, fighter, goblin) = setup(Point3::new(1.0, 0.0, 0.0));
+        let mut plan = TurnPlan::new(fighter);
+        plan.push(attack(&game_state, fighter, goblin));
+        plan.push(attack(&game_state, fighter, goblin));
+
+        let errors = systems::plan::validate(&mut game_state, &plan).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            PlanError::Action {
+                step: 1,
+                error: ActionError::Usability(ActionUsabilityError::NotEnoughResources(_))
+            }
+        ));
+
+        // Validating the plan doesn't spend anything
+        assert!(has_action(&game_state, fighter));
+    }
+
+    #[test]
+    fn plan_out_of_range_is_rejected() {
+        let (mut game_state, fighter, goblin) = setup(Point3::new(10.0, 0.0, 0.0));
+        let mut plan = TurnPlan::new(fighter);
+        plan.push(attack(&game_state, fighter, goblin));
+
+        let errors = systems::plan::validate(&mut game_state, &plan).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [PlanError::Action {
+                step: 0,
+                error: ActionError::Usability(ActionUsabilityError::TargetingError(_))
+            }]
+        ));
+    }
+
+    #[test]
+    fn valid_plan_is_executed() {
+        let (mut game_state, fighter, goblin) = setup(Point3::new(1.0, 0.0, 0.0));
+        let mut plan = TurnPlan::new(fighter);
+        plan.push(attack(&game_state, fighter, goblin));
+
+        assert!(systems::plan::validate(&mut game_state, &plan).is_ok());
+        assert!(game_state.submit_plan(plan).is_ok());
+        assert!(!has_action(&game_state, fighter));
+    }
+
+    #[test]
+    fn plan_repeating_once_per_turn_action_is_rejected() {
+        let (mut game_state, fighter, _) = setup(Point3::new(1.0, 0.0, 0.0));
+        let reckless_attack = ActionId::new("nat20_core", "action.barbarian.reckless_attack");
+        systems::actions::grant_action(
+            &mut game_state.world,
+            fighter,
+            &reckless_attack,
+            ModifierSource::Custom("Test".to_string()),
+        );
+        let (context, cost) = systems::actions::available_actions(&game_state.world, fighter)
+            [&reckless_attack][0]
+            .clone();
+        let step = PlannedStep::Action(ActionData::new(
+            fighter,
+            reckless_attack.clone(),
+            context,
+            cost,
+            vec![TargetInstance::Entity(fighter)],
+        ));
+        let mut plan = TurnPlan::new(fighter);
+        plan.push(step.clone());
+        plan.push(step);
+
+        let errors = systems::plan::validate(&mut game_state, &plan).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [PlanError::Action {
+                step: 1,
+                error: ActionError::Usability(ActionUsabilityError::OnCooldown(_))
+            }]
+        ));
+
+        // Validating the plan doesn't put anything on cooldown
+        assert!(
+            systems::actions::on_cooldown(&game_state.world, fighter, &reckless_attack).is_none()
+        );
+    }
+}
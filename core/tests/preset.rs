@@ -0,0 +1,119 @@
+extern crate nat20_core;
+
+mod tests {
+    use nat20_core::{
+        components::{
+            biography::Biography,
+            id::{MonsterId, Name},
+        },
+        engine::game_state::GameState,
+        entities::{monster::MonsterTag, object::ObjectTag},
+        systems::{
+            mapgen::{self, MapGenConfig, Tile},
+            preset::{self, EncounterPreset, PresetCreature, PresetError},
+        },
+        test_utils::fixtures,
+    };
+    use rerecast::ConfigBuilder;
+
+    fn map_config() -> MapGenConfig {
+        MapGenConfig {
+            width: 20,
+            height: 20,
+            max_rooms: 4,
+            encounter_chance: 1.0,
+            seed: Some(11),
+            ..Default::default()
+        }
+    }
+
+    fn game_state() -> GameState {
+        let map = mapgen::generate(&map_config()).unwrap();
+        GameState::new(map.to_geometry(&ConfigBuilder::default().build()))
+    }
+
+    #[test]
+    fn preset_capture_and_restore() {
+        let mut game_state = game_state();
+        let map = mapgen::generate(&map_config()).unwrap();
+        mapgen::populate(&mut game_state, &map);
+        // Player characters are not part of the preset
+        fixtures::creatures::heroes::fighter(&mut game_state.world);
+
+        // The notes on a monster are saved along with it
+        let (villain, _) = game_state
+            .world
+            .query::<&MonsterTag>()
+            .iter()
+            .next()
+            .unwrap();
+        let biography = Biography {
+            backstory: "Exiled from the tribe for cowardice".to_string(),
+            ..Default::default()
+        };
+        game_state
+            .world
+            .insert_one(villain, biography.clone())
+            .unwrap();
+
+        let preset = preset::capture(&game_state, "Goblin Ambush", Some(map_config()));
+        let monsters = game_state.world.query::<&MonsterTag>().iter().count();
+        assert_eq!(preset.creatures.len(), monsters);
+
+        let json = serde_json::to_string(&preset).unwrap();
+        let loaded: EncounterPreset = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, preset);
+
+        let mut restored_state = game_state();
+        let entities = preset::restore(
+            &mut restored_state,
+            &loaded,
+            &ConfigBuilder::default().build(),
+        )
+        .unwrap();
+
+        // Doors, traps and light sources
+        let objects = restored_state.world.query::<&ObjectTag>().iter().count();
+        assert_eq!(
+            objects,
+            map.positions_of(Tile::Door).count()
+                + map.positions_of(Tile::Trap).count()
+                + map.light_sources.len()
+        );
+        assert_eq!(entities.len(), objects + preset.creatures.len());
+
+        let restored = preset::capture(&restored_state, "Goblin Ambush", Some(map_config()));
+        assert_eq!(restored, preset);
+        assert_eq!(
+            restored_state
+                .world
+                .query::<&Biography>()
+                .iter()
+                .map(|(_, biography)| biography.clone())
+                .collect::<Vec<_>>(),
+            vec![biography]
+        );
+    }
+
+    #[test]
+    fn preset_unknown_monster() {
+        let mut game_state = game_state();
+        let template = MonsterId::new("nat20_core", "monster.does_not_exist");
+        let preset = EncounterPreset {
+            name: "Broken".to_string(),
+            map: None,
+            creatures: vec![PresetCreature {
+                template: template.clone(),
+                name: "Nobody".to_string(),
+                position: [0.0, 0.0, 0.0],
+                biography: Biography::default(),
+            }],
+        };
+
+        assert_eq!(
+            preset::restore(&mut game_state, &preset, &ConfigBuilder::default().build()),
+            Err(PresetError::UnknownMonster(template))
+        );
+        assert_eq!(game_state.world.query::<&Name>().iter().count(), 0);
+    }
+}
@@ -0,0 +1,105 @@
+extern crate nat20_core;
+
+mod tests {
+    use nat20_core::{
+        components::{
+            actions::{
+                action::ActionContext,
+                preview::{CheckPreview, DamageRange},
+                targeting::TargetInstance,
+            },
+            class::ClassAndSubclass,
+            id::{ActionId, ClassId, SpellId},
+            resource::ResourceAmountMap,
+            spells::spellbook::SpellSource,
+        },
+        engine::event::ActionData,
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    #[test]
+    fn attack_preview() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, goblin, &Point3::new(1.0, 0.0, 0.0));
+
+        let action_id = ActionId::new("nat20_core", "action.weapon_attack");
+        let available_actions = systems::actions::available_actions(&game_state.world, fighter);
+        let (context, cost) = available_actions.get(&action_id).unwrap()[0].clone();
+        let action = ActionData::new(
+            fighter,
+            action_id,
+            context,
+            cost,
+            vec![TargetInstance::Entity(goblin)],
+        );
+
+        let preview = systems::actions::preview(&game_state, &action);
+        assert_eq!(preview.len(), 1);
+        let preview = &preview[0];
+        assert_eq!(preview.target, goblin);
+
+        let Some(CheckPreview::AttackRoll {
+            armor_class,
+            hit_chance,
+            crit_chance,
+            ..
+        }) = &preview.check
+        else {
+            panic!("Expected an attack roll, got {:?}", preview.check);
+        };
+        assert_eq!(
+            *armor_class,
+            systems::loadout::armor_class(&game_state.world, goblin)
+        );
+        assert!(*hit_chance > 0.0 && *hit_chance <= 1.0);
+        assert!(*crit_chance > 0.0 && crit_chance <= hit_chance);
+        assert_eq!(preview.success_chance, *hit_chance);
+
+        let damage = preview.damage.as_ref().unwrap();
+        assert!(damage.full.min > 0 && damage.full.min <= damage.full.max);
+        let critical = damage.critical.unwrap();
+        assert!(critical.max > damage.full.max);
+        assert!(damage.reduced.is_none());
+    }
+
+    #[test]
+    fn saving_throw_preview() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        systems::geometry::teleport_to(&mut game_state.world, wizard, &Point3::origin());
+        systems::geometry::teleport_to(&mut game_state.world, goblin, &Point3::new(8.0, 0.0, 0.0));
+
+        let fireball = ActionData::new(
+            wizard,
+            ActionId::new("nat20_core", "action.fireball"),
+            ActionContext::Spell {
+                id: SpellId::new("nat20_core", "spell.fireball"),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level: 3,
+                metamagic: Vec::new(),
+            },
+            ResourceAmountMap::new(),
+            vec![TargetInstance::Point(Point3::new(8.0, 0.0, 0.0))],
+        );
+
+        let preview = systems::actions::preview_on_target(&game_state, &fireball, goblin).unwrap();
+        let Some(CheckPreview::SavingThrow { save_chance, .. }) = &preview.check else {
+            panic!("Expected a saving throw, got {:?}", preview.check);
+        };
+        assert!((preview.success_chance - (1.0 - save_chance)).abs() < f64::EPSILON);
+
+        // Fireball deals 8d6 fire damage, and half as much on a successful save
+        let damage = preview.damage.unwrap();
+        assert_eq!(damage.full, DamageRange { min: 8, max: 48 });
+        assert_eq!(damage.reduced, Some(DamageRange { min: 4, max: 24 }));
+        assert!(damage.critical.is_none());
+    }
+}
@@ -0,0 +1,179 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            ability::Ability,
+            actions::{
+                action::{ActionContext, ActionKindResult, ActionOutcomeBundle},
+                targeting::TargetInstance,
+            },
+            class::ClassAndSubclass,
+            dice::RollPolicy,
+            id::{ActionId, ClassId, EffectId, SpellId},
+            modifier::ModifierSource,
+            resource::ResourceAmountMap,
+            saving_throw::SavingThrowKind,
+            spells::spellbook::SpellSource,
+        },
+        engine::{
+            event::{ActionData, EventKind},
+            game_state::GameState,
+        },
+        registry::serialize::d20::SavingThrowProvider,
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+
+    fn setup(goblin_d20: u8) -> (GameState, Entity, Entity) {
+        let (mut game_state, wizard, goblin) = fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::wizard,
+            fixtures::creatures::monsters::goblin_warrior,
+            3.0,
+        );
+        game_state
+            .world
+            .insert_one(
+                goblin,
+                RollPolicy {
+                    average_damage: false,
+                    fixed_d20: Some(goblin_d20),
+                },
+            )
+            .unwrap();
+        (game_state, wizard, goblin)
+    }
+
+    fn cast(
+        game_state: &mut GameState,
+        wizard: Entity,
+        spell: &str,
+        level: u8,
+        target: TargetInstance,
+    ) -> ActionOutcomeBundle {
+        let action = ActionData::new(
+            wizard,
+            ActionId::new("nat20_core", &format!("action.{}", spell)),
+            ActionContext::Spell {
+                id: SpellId::new("nat20_core", &format!("spell.{}", spell)),
+                source: SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+                level,
+                metamagic: Vec::new(),
+            },
+            ResourceAmountMap::new(),
+            vec![target],
+        );
+        systems::actions::perform_action(game_state, &action);
+
+        game_state
+            .event_log
+            .events
+            .iter()
+            .rev()
+            .find_map(|event| match &event.kind {
+                EventKind::ActionPerformed { results, .. } => match &results.first()?.kind {
+                    ActionKindResult::Standard(outcome) => Some(outcome.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn successful_save_without_half_damage_takes_no_damage() {
+        let (mut game_state, wizard, _) = setup(20);
+
+        let outcome = cast(
+            &mut game_state,
+            wizard,
+            "acid_splash",
+            0,
+            TargetInstance::Point(Point3::new(3.0, 0.0, 0.0)),
+        );
+
+        assert!(outcome.damage.is_none());
+        let saving_throw = outcome.saving_throw.unwrap();
+        assert!(saving_throw.success);
+        assert_eq!(
+            saving_throw.dc.key,
+            SavingThrowKind::Ability(Ability::Dexterity)
+        );
+        assert_eq!(
+            saving_throw.dc.dc.total(),
+            systems::spells::spell_dc(
+                &game_state.world,
+                wizard,
+                &SpellSource::Class(ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                }),
+            )
+            .total()
+        );
+    }
+
+    #[test]
+    fn failed_save_takes_full_damage() {
+        let (mut game_state, wizard, _) = setup(1);
+
+        let outcome = cast(
+            &mut game_state,
+            wizard,
+            "acid_splash",
+            0,
+            TargetInstance::Point(Point3::new(3.0, 0.0, 0.0)),
+        );
+
+        assert!(!outcome.saving_throw.unwrap().success);
+        let damage = outcome.damage.unwrap();
+        assert!(damage.damage_roll.unwrap().total > 0);
+    }
+
+    #[test]
+    fn effects_are_resisted_on_a_successful_save() {
+        let charmed = EffectId::new("nat20_core", "effect.condition.charmed");
+        for (d20, charmed_expected) in [(20, false), (1, true)] {
+            let (mut game_state, wizard, goblin) = setup(d20);
+
+            let outcome = cast(
+                &mut game_state,
+                wizard,
+                "charm_person",
+                1,
+                TargetInstance::Entity(goblin),
+            );
+
+            let saving_throw = outcome.saving_throw.unwrap();
+            assert_eq!(saving_throw.success, !charmed_expected);
+            let effect = outcome.effect.unwrap();
+            assert_eq!(effect.effect, charmed);
+            assert_eq!(effect.applied, charmed_expected);
+            assert_eq!(
+                systems::effects::has_effect(&game_state.world, goblin, &charmed),
+                charmed_expected
+            );
+        }
+    }
+
+    #[test]
+    fn weapon_save_dc_uses_best_of_strength_and_dexterity() {
+        let (game_state, _, goblin) = setup(10);
+        let provider: SavingThrowProvider = "weapon_save_dc;constitution".parse().unwrap();
+
+        let dc = (provider.function)(&game_state.world, goblin, &ActionContext::Other);
+
+        assert_eq!(dc.key, SavingThrowKind::Ability(Ability::Constitution));
+        // Goblins are better with Dexterity than Strength
+        assert!(
+            dc.dc
+                .iter()
+                .any(|(source, _)| *source == ModifierSource::Ability(Ability::Dexterity))
+        );
+    }
+}
@@ -0,0 +1,87 @@
+extern crate nat20_core;
+
+mod tests {
+    use nat20_core::{
+        components::id::{MonsterId, Name},
+        systems::scene::{Scene, SceneError, ScenePlayer, SceneStep},
+        test_utils::fixtures,
+    };
+
+    fn ambush() -> Scene {
+        serde_json::from_str(
+            r#"{
+                "name": "Ambush",
+                "steps": [
+                    {
+                        "kind": "spawn",
+                        "actor": "boss",
+                        "template": "nat20_core::monster.goblin_warrior",
+                        "name": "Grukk",
+                        "position": [2.0, 0.0, 2.0]
+                    },
+                    { "kind": "camera", "target": "boss", "seconds": 1.0 },
+                    { "kind": "dialogue", "speaker": "Grukk", "line": "Pay the toll!" },
+                    { "kind": "start_combat", "actors": ["boss"] }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn scene_plays_steps_in_order() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let mut player = ScenePlayer::new(ambush()).unwrap();
+
+        // The monster is spawned right away, and the camera move takes a second
+        player.update(&mut game_state, 0.5);
+        let boss = player.actor("boss").unwrap();
+        assert_eq!(
+            game_state.world.get::<&Name>(boss).unwrap().as_str(),
+            "Grukk"
+        );
+        assert!(matches!(
+            player.current_step(),
+            Some(SceneStep::Camera { .. })
+        ));
+
+        // Dialogue waits for the player, no matter how much time passes
+        player.update(&mut game_state, 0.5);
+        player.update(&mut game_state, 10.0);
+        assert!(matches!(
+            player.current_step(),
+            Some(SceneStep::Dialogue { .. })
+        ));
+        assert!(game_state.in_combat.is_empty());
+
+        player.advance();
+        player.update(&mut game_state, 0.1);
+        assert!(player.is_finished());
+        assert!(game_state.in_combat.contains_key(&boss));
+        assert!(game_state.in_combat.contains_key(&fighter));
+    }
+
+    #[test]
+    fn scene_checked_before_playing() {
+        let mut scene = ambush();
+        scene.steps.remove(0);
+        assert_eq!(
+            ScenePlayer::new(scene).unwrap_err(),
+            SceneError::UnknownActor("boss".to_string())
+        );
+
+        let mut scene = ambush();
+        let template = MonsterId::new("nat20_core", "monster.does_not_exist");
+        if let SceneStep::Spawn {
+            template: spawned, ..
+        } = &mut scene.steps[0]
+        {
+            *spawned = template.clone();
+        }
+        assert_eq!(
+            ScenePlayer::new(scene).unwrap_err(),
+            SceneError::UnknownMonster(template)
+        );
+    }
+}
@@ -0,0 +1,80 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{actions::action::ActionContext, id::ActionId, species::CreatureSize},
+        engine::geometry::WorldPath,
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+    use uom::si::length::{foot, meter};
+
+    fn setup(goblin_size: CreatureSize) -> (World, Entity, Entity) {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut world).id();
+        systems::helpers::set_component::<CreatureSize>(&mut world, goblin, goblin_size);
+        systems::geometry::teleport_to(&mut world, goblin, &Point3::new(3.0, 0.0, 0.0));
+        (world, fighter, goblin)
+    }
+
+    #[test]
+    fn creature_size_space() {
+        assert_eq!(CreatureSize::Medium.space().get::<foot>(), 5.0);
+        assert_eq!(CreatureSize::Large.space().get::<foot>(), 10.0);
+        assert_eq!(CreatureSize::Large.larger(), Some(CreatureSize::Huge));
+        assert_eq!(CreatureSize::Tiny.smaller(), None);
+        assert_eq!(CreatureSize::Small.difference(&CreatureSize::Huge), 3);
+    }
+
+    #[test]
+    fn distance_is_measured_to_edge_of_space() {
+        let (world, fighter, goblin) = setup(CreatureSize::Medium);
+        let distance = systems::geometry::distance_between_entities(&world, fighter, goblin);
+        assert!((distance.unwrap().get::<meter>() - 3.0).abs() < 0.01);
+
+        let (world, fighter, goblin) = setup(CreatureSize::Huge);
+        let distance = systems::geometry::distance_between_entities(&world, fighter, goblin);
+        // The space of a Huge creature extends 5 ft. further than a single square
+        assert!((distance.unwrap().get::<foot>() - (3.0 / 0.3048 - 5.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn hostile_creature_of_similar_size_blocks_path() {
+        let (world, fighter, goblin) = setup(CreatureSize::Small);
+        let path = WorldPath::new(vec![Point3::new(0.0, 0.0, 0.0), Point3::new(6.0, 0.0, 0.0)]);
+
+        let (blocker, distance) = systems::size::blocked_at(&world, fighter, &path).unwrap();
+        assert_eq!(blocker, goblin);
+        let radius = CreatureSize::Small.space().get::<meter>() / 2.0;
+        assert!(distance.get::<meter>() <= 3.0 - radius);
+    }
+
+    #[test]
+    fn much_smaller_or_larger_creature_does_not_block_path() {
+        let path = WorldPath::new(vec![Point3::new(0.0, 0.0, 0.0), Point3::new(6.0, 0.0, 0.0)]);
+
+        for size in [CreatureSize::Tiny, CreatureSize::Huge] {
+            let (world, fighter, _) = setup(size);
+            assert!(systems::size::blocked_at(&world, fighter, &path).is_none());
+        }
+    }
+
+    #[test]
+    fn cannot_grapple_much_larger_creature() {
+        let grapple = ActionId::new("nat20_core", "action.grapple");
+
+        for (size, allowed) in [(CreatureSize::Large, true), (CreatureSize::Huge, false)] {
+            let (world, fighter, goblin) = setup(size);
+            let targeting = systems::actions::targeting_context(
+                &world,
+                fighter,
+                &grapple,
+                &ActionContext::Other,
+            );
+            assert_eq!(targeting.allowed_targets.matches(&world, &goblin), allowed);
+        }
+    }
+}
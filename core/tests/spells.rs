@@ -0,0 +1,412 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::collections::HashSet;
+
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            ability::{Ability, AbilityScoreMap},
+            actions::{action::ActionContext, targeting::TargetInstance},
+            class::ClassAndSubclass,
+            damage::DamageType,
+            health::life_state::LifeState,
+            id::{ActionId, ClassId, ItemId, ResourceId, SpellId, SpellListId},
+            items::{
+                inventory::{Inventory, ItemInstance},
+                money::MonetaryValue,
+            },
+            level::{CharacterLevels, Level},
+            modifier::Modifiable,
+            resource::{ResourceAmountMap, ResourceMap},
+            spells::spellbook::{
+                GrantedSpellSource, SpellSource, Spellbook, SpellbookError, SpellcastingAbility,
+            },
+            time::TimeDuration,
+        },
+        engine::{
+            event::{ActionData, ActionError},
+            game_state::GameState,
+        },
+        registry::registry::{ClassesRegistry, SpellListsRegistry},
+        systems::{self, actions::ActionUsabilityError},
+        test_utils::fixtures,
+    };
+
+    #[test]
+    fn classes_with_spell() {
+        let mut world = World::new();
+        let wizard = fixtures::creatures::heroes::wizard(&mut world).id();
+
+        let classes = systems::spells::classes_with_spell(
+            &world,
+            wizard,
+            &SpellId::new("nat20_core", "spell.fireball"),
+        );
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].class, ClassId::new("nat20_core", "class.wizard"));
+
+        assert!(
+            systems::spells::classes_with_spell(
+                &world,
+                wizard,
+                &SpellId::new("nat20_core", "spell.hex"),
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn learn_spell_not_on_class_list() {
+        let mut world = World::new();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+
+        assert_eq!(
+            systems::spells::learn_spell(
+                &mut world,
+                fighter,
+                &SpellId::new("nat20_core", "spell.fire_bolt"),
+            ),
+            Err(SpellbookError::SpellNotOnClassList)
+        );
+    }
+
+    #[test]
+    fn class_spell_list_from_registry() {
+        let wizard = ClassesRegistry::get(&ClassId::new("nat20_core", "class.wizard")).unwrap();
+        let spell_list =
+            SpellListsRegistry::get(&SpellListId::new("nat20_core", "spell_list.wizard")).unwrap();
+
+        assert_eq!(wizard.spell_list(&None), spell_list.spells);
+        assert!(spell_list.contains(&SpellId::new("nat20_core", "spell.fireball")));
+        assert!(!spell_list.contains(&SpellId::new("nat20_core", "spell.bless")));
+
+        let fighter = ClassesRegistry::get(&ClassId::new("nat20_core", "class.fighter")).unwrap();
+        assert!(fighter.spell_list(&None).is_empty());
+    }
+
+    #[test]
+    fn expanded_spell_list() {
+        let mut world = World::new();
+        let wizard = fixtures::creatures::heroes::wizard(&mut world).id();
+        let class_and_subclass = ClassAndSubclass {
+            class: ClassId::new("nat20_core", "class.wizard"),
+            subclass: None,
+        };
+        let bless = SpellId::new("nat20_core", "spell.bless");
+        assert!(systems::spells::classes_with_spell(&world, wizard, &bless).is_empty());
+
+        // E.g. a feat which lets the wizard pick spells from the cleric list
+        systems::helpers::get_component_mut::<Spellbook>(&mut world, wizard)
+            .expand_spell_list(&class_and_subclass, [bless.clone()])
+            .unwrap();
+
+        assert!(
+            systems::helpers::get_component::<Spellbook>(&world, wizard)
+                .is_on_spell_list(&class_and_subclass, &bless)
+        );
+        assert_eq!(
+            systems::spells::classes_with_spell(&world, wizard, &bless),
+            vec![class_and_subclass]
+        );
+    }
+
+    #[test]
+    fn scrolls_require_spell_on_class_list() {
+        let mut world = World::new();
+        let wizard = fixtures::creatures::heroes::wizard(&mut world).id();
+        let fighter = fixtures::creatures::heroes::fighter(&mut world).id();
+        let magic_missile = SpellId::new("nat20_core", "spell.magic_missile");
+        let action_id = ActionId::new("nat20_core", "action.magic_missile");
+
+        let scroll = systems::crafting::scroll(&magic_missile, 1);
+        let wand = systems::crafting::wand(&magic_missile, 1);
+        let scroll_context = ActionContext::Spell {
+            id: magic_missile.clone(),
+            source: SpellSource::Granted {
+                source: GrantedSpellSource::Item(scroll.item.id.clone()),
+                level: 1,
+            },
+            level: 1,
+            metamagic: Vec::new(),
+        };
+        for entity in [wizard, fighter] {
+            systems::inventory::add_item(&mut world, entity, ItemInstance::Spell(scroll.clone()));
+            systems::inventory::add_item(&mut world, entity, ItemInstance::Spell(wand.clone()));
+        }
+
+        let can_read_scroll = |entity| {
+            systems::actions::all_actions(&world, entity)
+                .get(&action_id)
+                .is_some_and(|contexts| {
+                    contexts
+                        .iter()
+                        .any(|(context, _)| *context == scroll_context)
+                })
+        };
+        assert!(can_read_scroll(wizard));
+        assert!(!can_read_scroll(fighter));
+
+        // Anyone can use a wand
+        assert!(systems::actions::all_actions(&world, fighter).contains_key(&action_id));
+    }
+
+    #[test]
+    fn subclass_always_prepared_spells() {
+        let mut world = World::new();
+        let warlock = fixtures::creatures::heroes::warlock(&mut world).id();
+        let class_and_subclass = ClassAndSubclass {
+            class: ClassId::new("nat20_core", "class.warlock"),
+            subclass: None,
+        };
+
+        let spellbook = systems::helpers::get_component::<Spellbook>(&world, warlock);
+        let resources = systems::helpers::get_component::<ResourceMap>(&world, warlock);
+        let castable = spellbook
+            .castable_spells_for_class(&class_and_subclass, &resources)
+            .unwrap();
+
+        // Fiend spells for levels 3 and 5, but not 7
+        for (spell, expected) in [
+            ("spell.burning_hands", true),
+            ("spell.scorching_ray", true),
+            ("spell.fireball", false),
+        ] {
+            let spell_id = SpellId::new("nat20_core", spell);
+            assert_eq!(
+                spellbook
+                    .class_state(&class_and_subclass)
+                    .unwrap()
+                    .selections
+                    .always_prepared
+                    .contains(&spell_id),
+                expected
+            );
+            assert_eq!(castable.contains(&spell_id), expected);
+        }
+    }
+
+    #[test]
+    fn material_component_consumed_on_success() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        let revivify = SpellId::new("nat20_core", "spell.revivify");
+        let action_id: ActionId = revivify.clone().into();
+        let context = ActionContext::Spell {
+            id: revivify.clone(),
+            source: SpellSource::Class(ClassAndSubclass {
+                class: ClassId::new("nat20_core", "class.wizard"),
+                subclass: None,
+            }),
+            level: 3,
+            metamagic: Vec::new(),
+        };
+        let usable = |world: &World| {
+            systems::actions::action_usable(
+                world,
+                wizard,
+                &action_id,
+                &context,
+                &ResourceAmountMap::new(),
+            )
+        };
+
+        assert!(matches!(
+            usable(&game_state.world),
+            Err(ActionUsabilityError::MissingMaterialComponent(_))
+        ));
+
+        systems::inventory::add_money(
+            &mut game_state.world,
+            wizard,
+            "350 GP".parse::<MonetaryValue>().unwrap(),
+        );
+        assert_eq!(usable(&game_state.world), Ok(()));
+
+        let gold = |game_state: &GameState| {
+            systems::helpers::get_component::<Inventory>(&game_state.world, wizard)
+                .money()
+                .total_in_gold()
+        };
+        let gold_before = gold(&game_state);
+        let dead_fighter = |game_state: &mut GameState| {
+            let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+            systems::gm::damage(game_state, fighter, 1000, DamageType::Bludgeoning);
+            fighter
+        };
+        let cast = |game_state: &mut GameState, target: Entity| {
+            let action = ActionData::new(
+                wizard,
+                action_id.clone(),
+                context.clone(),
+                ResourceAmountMap::new(),
+                vec![TargetInstance::Entity(target)],
+            );
+            game_state.validate_action(&action, true).unwrap();
+            systems::actions::perform_action(game_state, &action);
+            *systems::helpers::get_component::<LifeState>(&game_state.world, target)
+        };
+
+        // The diamond isn't lost if the spell fails to bring the target back
+        let long_dead = dead_fighter(&mut game_state);
+        systems::time::advance_world_clock(&mut game_state, &TimeDuration::from_minutes(2));
+        assert_ne!(cast(&mut game_state, long_dead), LifeState::Normal);
+        assert_eq!(gold(&game_state), gold_before);
+
+        // but it's used up when the spell works
+        let recently_dead = dead_fighter(&mut game_state);
+        assert_eq!(cast(&mut game_state, recently_dead), LifeState::Normal);
+        assert_eq!(gold(&game_state), gold_before - 300.0);
+    }
+
+    fn expected_spell_dc(world: &World, entity: Entity, ability: Ability) -> i32 {
+        let proficiency_bonus =
+            systems::helpers::get_component::<CharacterLevels>(world, entity).proficiency_bonus();
+        8 + systems::helpers::get_component::<AbilityScoreMap>(world, entity)
+            .ability_modifier(&ability)
+            .total()
+            + proficiency_bonus as i32
+    }
+
+    #[test]
+    fn spell_dc_uses_class_spellcasting_ability() {
+        let mut world = World::new();
+        let wizard = fixtures::creatures::heroes::wizard(&mut world).id();
+        let warlock = fixtures::creatures::heroes::warlock(&mut world).id();
+
+        for (entity, class, ability) in [
+            (wizard, "class.wizard", Ability::Intelligence),
+            (warlock, "class.warlock", Ability::Charisma),
+        ] {
+            let source = SpellSource::Class(ClassAndSubclass {
+                class: ClassId::new("nat20_core", class),
+                subclass: None,
+            });
+            assert_eq!(
+                systems::spells::spellcasting_ability(&world, entity, &source),
+                ability
+            );
+            assert_eq!(
+                systems::spells::spell_dc(&world, entity, &source).total(),
+                expected_spell_dc(&world, entity, ability)
+            );
+            assert_eq!(
+                systems::spells::spell_attack_bonus(&world, entity, &source).total(),
+                expected_spell_dc(&world, entity, ability) - 8
+            );
+        }
+    }
+
+    #[test]
+    fn spellcasting_ability_override() {
+        let mut world = World::new();
+        let wizard = fixtures::creatures::heroes::wizard(&mut world).id();
+        let class_source = SpellSource::Class(ClassAndSubclass {
+            class: ClassId::new("nat20_core", "class.wizard"),
+            subclass: None,
+        });
+        let granted_source = SpellSource::Granted {
+            source: GrantedSpellSource::Item(ItemId::new("nat20_core", "item.wand")),
+            level: 1,
+        };
+
+        // Granted spells use the highest spellcasting ability by default
+        let highest = systems::helpers::get_component::<AbilityScoreMap>(&world, wizard)
+            .get_max_score(&[Ability::Intelligence, Ability::Wisdom, Ability::Charisma])
+            .0;
+        assert_eq!(
+            systems::spells::spellcasting_ability(&world, wizard, &granted_source),
+            highest
+        );
+
+        {
+            let mut spellbook =
+                systems::helpers::get_component_mut::<Spellbook>(&mut world, wizard);
+            for source in [&class_source, &granted_source] {
+                spellbook
+                    .set_spellcasting_ability(source, SpellcastingAbility::Fixed(Ability::Wisdom))
+                    .unwrap();
+            }
+        }
+
+        for source in [&class_source, &granted_source] {
+            assert_eq!(
+                systems::spells::spellcasting_ability(&world, wizard, source),
+                Ability::Wisdom
+            );
+            assert_eq!(
+                systems::spells::spell_dc(&world, wizard, source).total(),
+                expected_spell_dc(&world, wizard, Ability::Wisdom)
+            );
+        }
+
+        // Classes the character doesn't have can't be overridden
+        assert_eq!(
+            systems::helpers::get_component_mut::<Spellbook>(&mut world, wizard)
+                .set_spellcasting_ability(
+                    &SpellSource::Class(ClassAndSubclass {
+                        class: ClassId::new("nat20_core", "class.warlock"),
+                        subclass: None,
+                    }),
+                    SpellcastingAbility::Highest,
+                ),
+            Err(SpellbookError::ClassNotFound)
+        );
+    }
+
+    #[test]
+    fn ritual_spells_are_cast_without_a_spell_slot() {
+        let mut game_state = fixtures::engine::game_state();
+        let wizard = fixtures::creatures::heroes::wizard(&mut game_state.world).id();
+        let find_familiar = SpellId::new("nat20_core", "spell.find_familiar");
+        systems::helpers::get_component_mut::<Spellbook>(&mut game_state.world, wizard)
+            .add_always_prepared(
+                &ClassAndSubclass {
+                    class: ClassId::new("nat20_core", "class.wizard"),
+                    subclass: None,
+                },
+                &find_familiar,
+            )
+            .unwrap();
+        systems::inventory::add_money(
+            &mut game_state.world,
+            wizard,
+            "10 GP".parse::<MonetaryValue>().unwrap(),
+        );
+
+        let action_id: ActionId = find_familiar.into();
+        let (context, cost) = systems::actions::available_actions(&game_state.world, wizard)
+            [&action_id]
+            .iter()
+            .find(|(context, cost)| systems::spells::is_ritual_casting(context, cost))
+            .cloned()
+            .unwrap();
+        assert!(matches!(context, ActionContext::Spell { level: 1, .. }));
+        let action = ActionData::new(
+            wizard,
+            ActionId::new("nat20_core", "action.find_familiar.owl"),
+            context,
+            cost,
+            vec![TargetInstance::Entity(wizard)],
+        );
+
+        // Rituals take too long to cast in combat
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        let encounter = game_state.start_encounter(HashSet::from([wizard, goblin]));
+        assert!(matches!(
+            game_state.validate_action(&action, false),
+            Err(ActionError::RitualInCombat)
+        ));
+        game_state.end_encounter(&encounter);
+
+        let spell_slots = |game_state: &GameState| {
+            systems::helpers::get_component::<ResourceMap>(&game_state.world, wizard)
+                .get(&ResourceId::new("nat20_core", "resource.spell_slot"))
+                .cloned()
+        };
+        let spell_slots_before = spell_slots(&game_state);
+        game_state.validate_action(&action, true).unwrap();
+        assert_eq!(spell_slots(&game_state), spell_slots_before);
+        assert_eq!(game_state.clock.to_string(), "Day 1, 06:10:00");
+    }
+}
@@ -0,0 +1,87 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::collections::HashSet;
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            ability::Ability,
+            dice::{RollPolicy, TAKE_10},
+            items::equipment::slots::EquipmentSlot,
+            saving_throw::{SavingThrowKind, SavingThrowSet},
+        },
+        engine::{encounter::EncounterId, game_state::GameState},
+        systems,
+        test_utils::fixtures,
+    };
+
+    fn setup(policy: RollPolicy) -> (GameState, EncounterId, Entity, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        game_state.npc_roll_policy = policy;
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+        let encounter_id = game_state.start_encounter(HashSet::from([fighter, goblin]));
+        (game_state, encounter_id, fighter, goblin)
+    }
+
+    fn saving_throw(game_state: &GameState, entity: Entity) -> u8 {
+        systems::helpers::get_component::<SavingThrowSet>(&game_state.world, entity)
+            .check(
+                &SavingThrowKind::Ability(Ability::Dexterity),
+                &game_state.world,
+                entity,
+            )
+            .selected_roll
+    }
+
+    #[test]
+    fn npcs_use_static_rolls() {
+        let (game_state, _, fighter, goblin) = setup(RollPolicy::STATIC);
+        assert_eq!(
+            systems::helpers::roll_policy(&game_state.world, goblin),
+            RollPolicy::STATIC
+        );
+        // The party always rolls their own dice
+        assert!(systems::helpers::roll_policy(&game_state.world, fighter).is_random());
+
+        for _ in 0..10 {
+            assert_eq!(saving_throw(&game_state, goblin), TAKE_10);
+
+            let damage = systems::damage::damage_roll_weapon(
+                &game_state.world,
+                goblin,
+                &EquipmentSlot::MeleeMainHand,
+                false,
+            );
+            let (min, max) = systems::loadout::weapon_damage_roll(
+                &game_state.world,
+                goblin,
+                &EquipmentSlot::MeleeMainHand,
+            )
+            .min_max_results(false);
+            assert_eq!(damage.total, (min.total + max.total) / 2);
+        }
+    }
+
+    #[test]
+    fn roll_policy_is_set_per_encounter() {
+        let (mut game_state, encounter_id, _, goblin) = setup(RollPolicy::RANDOM);
+        assert!(systems::helpers::roll_policy(&game_state.world, goblin).is_random());
+
+        let take_10 = RollPolicy {
+            average_damage: false,
+            fixed_d20: Some(TAKE_10),
+        };
+        game_state.set_roll_policy(&encounter_id, take_10);
+        assert_eq!(
+            game_state.encounter(&encounter_id).unwrap().roll_policy(),
+            take_10
+        );
+        assert_eq!(saving_throw(&game_state, goblin), TAKE_10);
+
+        // NPCs roll their own dice again once the fight is over
+        game_state.end_encounter(&encounter_id);
+        assert!(systems::helpers::roll_policy(&game_state.world, goblin).is_random());
+    }
+}
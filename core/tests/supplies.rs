@@ -0,0 +1,146 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::str::FromStr;
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            health::hit_points::HitPoints,
+            id::{AdventureId, EffectId, ItemId},
+            items::{inventory::Inventory, money::MonetaryValue, supply::SupplyKind},
+            time::TimeDuration,
+        },
+        engine::{game_state::GameState, rules::OptionalRule},
+        registry::registry::ItemsRegistry,
+        systems::{self, time::TimeActivity, travel},
+        test_utils::fixtures,
+    };
+
+    fn setup() -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        game_state.set_rule(OptionalRule::Supplies, true);
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        (game_state, fighter)
+    }
+
+    fn give(game_state: &mut GameState, entity: Entity, item: &str) {
+        let item = ItemsRegistry::get(&ItemId::new("nat20_core", item))
+            .unwrap()
+            .clone();
+        systems::inventory::add_item(&mut game_state.world, entity, item);
+    }
+
+    fn days(game_state: &GameState, kind: SupplyKind) -> u32 {
+        systems::supplies::party_supplies(game_state)[&kind]
+    }
+
+    fn exhaustion(game_state: &GameState, entity: Entity) -> u32 {
+        let exhaustion = EffectId::new("nat20_core", "effect.condition.exhaustion");
+        systems::effects::effects(&game_state.world, entity)
+            .iter()
+            .find(|effect| effect.effect_id == exhaustion)
+            .map(|effect| effect.stacks)
+            .unwrap_or(0)
+    }
+
+    fn pass_days(game_state: &mut GameState, days: u32) {
+        systems::time::pass_time(
+            game_state,
+            TimeDuration::from_days(days),
+            TimeActivity::Downtime,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn supplies_only_matter_with_the_rule() {
+        let (mut game_state, fighter) = setup();
+        game_state.set_rule(OptionalRule::Supplies, false);
+
+        pass_days(&mut game_state, 3);
+        assert_eq!(exhaustion(&game_state, fighter), 0);
+    }
+
+    #[test]
+    fn party_lives_off_their_supplies() {
+        let (mut game_state, fighter) = setup();
+        give(&mut game_state, fighter, "item.rations");
+        give(&mut game_state, fighter, "item.rations");
+        give(&mut game_state, fighter, "item.waterskin");
+        assert_eq!(days(&game_state, SupplyKind::Food), 2);
+        assert_eq!(days(&game_state, SupplyKind::Water), 1);
+
+        pass_days(&mut game_state, 1);
+        assert_eq!(days(&game_state, SupplyKind::Food), 1);
+        assert_eq!(days(&game_state, SupplyKind::Water), 0);
+        assert_eq!(exhaustion(&game_state, fighter), 0);
+
+        // The waterskin is empty, but it's not thrown away
+        let waterskin = ItemId::new("nat20_core", "item.waterskin");
+        assert!(
+            systems::helpers::get_component::<Inventory>(&game_state.world, fighter)
+                .items()
+                .iter()
+                .any(|item| item.item().id == waterskin)
+        );
+
+        pass_days(&mut game_state, 1);
+        assert_eq!(days(&game_state, SupplyKind::Food), 0);
+        assert_eq!(exhaustion(&game_state, fighter), 1);
+
+        // Going without water is worse for those already exhausted, while it
+        // takes a few days to starve
+        pass_days(&mut game_state, 1);
+        assert_eq!(exhaustion(&game_state, fighter), 3);
+        assert_eq!(
+            systems::supplies::sustenance(&game_state.world, fighter).days_without_food,
+            1
+        );
+    }
+
+    #[test]
+    fn towns_refill_waterskins_and_inns_feed_the_party() {
+        let (mut game_state, fighter) = setup();
+        systems::campaign::start(
+            &mut game_state,
+            &AdventureId::new("nat20_core", "adventure.goblin_road"),
+        )
+        .unwrap();
+        give(&mut game_state, fighter, "item.waterskin");
+
+        travel::travel(&mut game_state, "east_road").unwrap();
+        pass_days(&mut game_state, 1);
+        assert_eq!(days(&game_state, SupplyKind::Water), 0);
+
+        travel::travel(&mut game_state, "millbrook").unwrap();
+        assert_eq!(days(&game_state, SupplyKind::Water), 1);
+
+        systems::inventory::add_money(
+            &mut game_state.world,
+            fighter,
+            MonetaryValue::from_str("5 SP").unwrap(),
+        );
+        travel::rest_at_inn(&mut game_state, fighter, vec![fighter]).unwrap();
+        let sustenance = systems::supplies::sustenance(&game_state.world, fighter);
+        assert!(sustenance.fed && sustenance.watered);
+        assert_eq!(days(&game_state, SupplyKind::Water), 1);
+    }
+
+    #[test]
+    fn goodberries_heal_and_nourish() {
+        let (mut game_state, fighter) = setup();
+        let max = systems::helpers::get_component::<HitPoints>(&game_state.world, fighter).max();
+        *systems::helpers::get_component_mut::<HitPoints>(&mut game_state.world, fighter) =
+            HitPoints::with_current(1, max);
+        give(&mut game_state, fighter, "item.goodberry");
+
+        systems::supplies::eat_and_drink(&mut game_state.world, fighter, &[fighter]);
+        assert!(systems::supplies::sustenance(&game_state.world, fighter).fed);
+        assert_eq!(days(&game_state, SupplyKind::Food), 9);
+        assert_eq!(
+            systems::helpers::get_component::<HitPoints>(&game_state.world, fighter).current(),
+            2
+        );
+    }
+}
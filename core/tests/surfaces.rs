@@ -0,0 +1,151 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            health::hit_points::HitPoints,
+            id::EffectId,
+            modifier::ModifierSource,
+            surface::{SURFACE_DC_DEFAULT, Surface, SurfaceKind},
+            time::TimeDuration,
+        },
+        engine::{game_state::GameState, geometry::WorldPath},
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+    use uom::si::{f32::Length, length::meter};
+
+    fn prone() -> EffectId {
+        EffectId::new("nat20_core", "effect.condition.prone")
+    }
+
+    fn surface(kind: SurfaceKind, x: f32, duration: Option<TimeDuration>) -> Surface {
+        Surface::new(
+            kind,
+            Point3::new(x, 0.0, 0.0),
+            Length::new::<meter>(1.5),
+            SURFACE_DC_DEFAULT,
+            duration,
+            None,
+        )
+    }
+
+    /// Paralyzed creatures automatically fail Dexterity saving throws, so the
+    /// outcome of the hazards is deterministic
+    fn paralyzed_fighter(game_state: &mut GameState) -> Entity {
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::effects::add_permanent_effect(
+            &mut game_state.world,
+            fighter,
+            EffectId::new("nat20_core", "effect.condition.paralyzed"),
+            &ModifierSource::Custom("Test".to_string()),
+            None,
+        );
+        fighter
+    }
+
+    fn has_effect(game_state: &GameState, entity: Entity, effect_id: &EffectId) -> bool {
+        systems::effects::effects(&game_state.world, entity)
+            .iter()
+            .any(|effect| effect.effect_id == *effect_id)
+    }
+
+    fn hit_points(game_state: &GameState, entity: Entity) -> u32 {
+        systems::helpers::get_component::<HitPoints>(&game_state.world, entity).current()
+    }
+
+    #[test]
+    fn grease_knocks_prone_at_turn_start() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = paralyzed_fighter(&mut game_state);
+        systems::geometry::teleport_to(&mut game_state.world, fighter, &Point3::origin());
+
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Grease, 0.0, None));
+        systems::surfaces::on_turn_start(&mut game_state, fighter);
+
+        assert!(has_effect(&game_state, fighter, &prone()));
+    }
+
+    #[test]
+    fn fire_ignites_grease() {
+        let mut game_state = fixtures::engine::game_state();
+
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Grease, 0.0, None));
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Fire, 2.0, None));
+
+        assert_eq!(game_state.surfaces.len(), 2);
+        assert!(
+            game_state
+                .surfaces
+                .iter()
+                .all(|surface| surface.kind == SurfaceKind::Fire)
+        );
+
+        // Ice melts in the fire, and puts it out
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Ice, 0.0, None));
+        assert_eq!(game_state.surfaces.len(), 1);
+    }
+
+    #[test]
+    fn entering_fire_deals_damage() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = paralyzed_fighter(&mut game_state);
+        let hp_before = hit_points(&game_state, fighter);
+
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Fire, 5.0, None));
+        let path = WorldPath::new(vec![Point3::origin(), Point3::new(5.0, 0.0, 0.0)]);
+        systems::surfaces::on_enter(&mut game_state, fighter, &path);
+
+        assert!(hit_points(&game_state, fighter) < hp_before);
+    }
+
+    #[test]
+    fn leaving_surface_does_not_trigger_it() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = paralyzed_fighter(&mut game_state);
+
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Grease, 0.0, None));
+        let path = WorldPath::new(vec![Point3::origin(), Point3::new(5.0, 0.0, 0.0)]);
+        systems::surfaces::on_enter(&mut game_state, fighter, &path);
+
+        assert!(!has_effect(&game_state, fighter, &prone()));
+    }
+
+    #[test]
+    fn surfaces_fade_over_time() {
+        let mut game_state = fixtures::engine::game_state();
+
+        systems::surfaces::create(
+            &mut game_state,
+            surface(SurfaceKind::Acid, 0.0, Some(TimeDuration::from_minutes(1))),
+        );
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Grease, 10.0, None));
+
+        systems::time::advance_world_clock(&mut game_state, &TimeDuration::from_minutes(1));
+
+        assert_eq!(game_state.surfaces.len(), 1);
+        assert_eq!(game_state.surfaces[0].kind, SurfaceKind::Grease);
+    }
+
+    #[test]
+    fn grease_is_difficult_terrain() {
+        let mut game_state = fixtures::engine::game_state();
+        let path = WorldPath::new(vec![
+            Point3::new(-5.0, 0.0, 0.0),
+            Point3::new(5.0, 0.0, 0.0),
+        ]);
+        let clear_cost = systems::surfaces::movement_cost(&game_state, &path).get::<meter>();
+        assert!((clear_cost - 10.0).abs() < 0.01);
+
+        // Crossing the 3 m wide puddle costs an extra meter for every meter in it
+        systems::surfaces::create(&mut game_state, surface(SurfaceKind::Grease, 0.0, None));
+        let greasy_cost = systems::surfaces::movement_cost(&game_state, &path).get::<meter>();
+        assert!(
+            (12.5..=13.5).contains(&greasy_cost),
+            "Crossing the grease should cost about 13 m of movement, got {}",
+            greasy_cost
+        );
+    }
+}
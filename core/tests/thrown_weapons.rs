@@ -0,0 +1,188 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{action::ActionContext, targeting::TargetInstance},
+            d20::RollMode,
+            damage::{DamageSource, DamageType},
+            dice::DieSize,
+            id::{ActionId, ItemId},
+            items::{
+                equipment::{slots::EquipmentSlot, weapon::WeaponKind},
+                inventory::{ItemContainer, ItemInstance},
+            },
+            proficiency::ProficiencyLevel,
+        },
+        engine::{event::ActionData, game_state::GameState},
+        registry::registry::ItemsRegistry,
+        systems,
+        test_utils::fixtures,
+    };
+    use parry3d::na::Point3;
+    use uom::si::{f32::Length, length::foot};
+
+    fn throw() -> ActionId {
+        ActionId::new("nat20_core", "action.throw")
+    }
+
+    fn main_hand() -> ActionContext {
+        ActionContext::Weapon {
+            slot: EquipmentSlot::MeleeMainHand,
+        }
+    }
+
+    fn setup() -> (GameState, Entity, Entity) {
+        fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            fixtures::creatures::monsters::goblin_warrior,
+            3.0,
+        )
+    }
+
+    fn wield(game_state: &mut GameState, entity: Entity, item: &str) {
+        let weapon = ItemsRegistry::get(&ItemId::new("nat20_core", item))
+            .unwrap()
+            .clone();
+        let _ = systems::loadout::equip_in_slot(
+            &mut game_state.world,
+            entity,
+            &EquipmentSlot::MeleeMainHand,
+            weapon,
+        );
+    }
+
+    fn throw_at(game_state: &mut GameState, thrower: Entity, target: Entity) {
+        let cost = systems::actions::get_action(&throw())
+            .unwrap()
+            .resource_cost()
+            .clone();
+        let action = ActionData::new(
+            thrower,
+            throw(),
+            main_hand(),
+            cost,
+            vec![TargetInstance::Entity(target)],
+        );
+        systems::actions::perform_action(game_state, &action);
+    }
+
+    fn items_near(game_state: &GameState, entity: Entity) -> Vec<ItemId> {
+        systems::inventory::ground_items_in_reach(
+            &game_state.world,
+            entity,
+            Length::new::<foot>(5.0),
+        )
+        .into_iter()
+        .map(|ground_item| {
+            game_state
+                .world
+                .get::<&ItemInstance>(ground_item)
+                .unwrap()
+                .id()
+                .clone()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn thrown_weapons_use_their_thrown_range() {
+        let (mut game_state, fighter, _) = setup();
+        wield(&mut game_state, fighter, "item.javelin");
+
+        let targeting =
+            systems::actions::targeting_context(&game_state.world, fighter, &throw(), &main_hand());
+        assert_eq!(targeting.range.normal().get::<foot>().round(), 30.0);
+        assert_eq!(targeting.range.max().get::<foot>().round(), 120.0);
+    }
+
+    #[test]
+    fn improvised_throws_deal_1d4() {
+        let (game_state, fighter, goblin) = setup();
+
+        // The fighter's greatsword isn't made for throwing
+        let damage_roll = systems::loadout::thrown_damage_roll(
+            &game_state.world,
+            fighter,
+            &EquipmentSlot::MeleeMainHand,
+        );
+        assert_eq!(damage_roll.primary.dice_roll.dice.num_dice, 1);
+        assert_eq!(damage_roll.primary.dice_roll.dice.die_size, DieSize::D4);
+        assert_eq!(damage_roll.primary.damage_type, DamageType::Bludgeoning);
+        assert_eq!(damage_roll.source, DamageSource::Weapon(WeaponKind::Ranged));
+
+        let attack_roll = systems::loadout::thrown_attack_roll(
+            &game_state.world,
+            fighter,
+            goblin,
+            &EquipmentSlot::MeleeMainHand,
+        );
+        assert_eq!(
+            attack_roll.d20_check.proficiency().level(),
+            &ProficiencyLevel::None
+        );
+    }
+
+    #[test]
+    fn throws_beyond_normal_range_have_disadvantage() {
+        let (mut game_state, fighter, goblin) = setup();
+        wield(&mut game_state, fighter, "item.dagger");
+
+        let roll_mode = |game_state: &GameState| {
+            systems::loadout::thrown_attack_roll(
+                &game_state.world,
+                fighter,
+                goblin,
+                &EquipmentSlot::MeleeMainHand,
+            )
+            .d20_check
+            .advantage_tracker()
+            .roll_mode()
+        };
+        assert_eq!(roll_mode(&game_state), RollMode::Normal);
+
+        systems::geometry::teleport_to(&mut game_state.world, goblin, &Point3::new(10.0, 0.0, 0.0));
+        assert_eq!(roll_mode(&game_state), RollMode::Disadvantage);
+    }
+
+    #[test]
+    fn thrown_weapons_land_next_to_the_target() {
+        let (mut game_state, fighter, goblin) = setup();
+        wield(&mut game_state, fighter, "item.dagger");
+
+        throw_at(&mut game_state, fighter, goblin);
+
+        assert!(
+            systems::loadout::loadout(&game_state.world, fighter)
+                .weapon_in_hand(&EquipmentSlot::MeleeMainHand)
+                .is_none()
+        );
+        // The goblin might drop its own gear if the dagger kills it
+        assert!(
+            items_near(&game_state, goblin).contains(&ItemId::new("nat20_core", "item.dagger"))
+        );
+        assert!(items_near(&game_state, fighter).is_empty());
+    }
+
+    #[test]
+    fn returning_weapons_come_back() {
+        let (mut game_state, fighter, goblin) = setup();
+        wield(&mut game_state, fighter, "item.dwarven_thrower");
+
+        throw_at(&mut game_state, fighter, goblin);
+
+        assert_eq!(
+            systems::loadout::loadout(&game_state.world, fighter)
+                .weapon_in_hand(&EquipmentSlot::MeleeMainHand)
+                .unwrap()
+                .item()
+                .id,
+            ItemId::new("nat20_core", "item.dwarven_thrower")
+        );
+        assert!(
+            !items_near(&game_state, goblin)
+                .contains(&ItemId::new("nat20_core", "item.dwarven_thrower"))
+        );
+    }
+}
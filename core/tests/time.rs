@@ -0,0 +1,168 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::collections::HashSet;
+
+    use nat20_core::{
+        components::{
+            effects::effect::{EffectInstanceTemplate, EffectLifetimeTemplate},
+            health::hit_points::HitPoints,
+            id::EffectId,
+            modifier::ModifierSource,
+            time::TimeDuration,
+        },
+        systems::{
+            self,
+            time::{PassTimeError, RestKind, TimeActivity},
+        },
+        test_utils::fixtures,
+    };
+
+    #[test]
+    fn pass_time_expires_effects() {
+        let mut game_state = fixtures::engine::game_state();
+        let entity = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+
+        let chill_touch = EffectId::new("nat20_core", "effect.spell.chill_touch");
+        systems::effects::add_effect_template(
+            &mut game_state.world,
+            entity,
+            entity,
+            ModifierSource::Custom("Test".to_string()),
+            &EffectInstanceTemplate {
+                effect_id: chill_touch.clone(),
+                lifetime: EffectLifetimeTemplate::Duration(TimeDuration::from_hours(1)),
+            },
+            None,
+        );
+
+        let has_effect = |game_state: &nat20_core::engine::game_state::GameState| {
+            systems::effects::effects(&game_state.world, entity)
+                .iter()
+                .any(|effect| effect.effect_id == chill_touch)
+        };
+
+        systems::time::pass_time(
+            &mut game_state,
+            TimeDuration::from_minutes(30),
+            TimeActivity::Downtime,
+        )
+        .unwrap();
+        assert!(has_effect(&game_state));
+
+        systems::time::pass_time(
+            &mut game_state,
+            TimeDuration::from_minutes(30),
+            TimeActivity::Travel,
+        )
+        .unwrap();
+        assert!(!has_effect(&game_state));
+        assert_eq!(game_state.clock.to_string(), "Day 1, 07:00:00");
+    }
+
+    #[test]
+    fn long_rest_advances_world_clock() {
+        let mut game_state = fixtures::engine::game_state();
+        let entity = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+
+        systems::time::start_rest(&mut game_state, vec![entity], &RestKind::Long).unwrap();
+        systems::time::finish_rest(&mut game_state, vec![entity]).unwrap();
+
+        assert_eq!(game_state.clock.to_string(), "Day 1, 14:00:00");
+    }
+
+    #[test]
+    fn pass_time_in_combat() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+
+        game_state.start_encounter(HashSet::from([fighter, goblin]));
+
+        let result = systems::time::pass_time(
+            &mut game_state,
+            TimeDuration::from_hours(1),
+            TimeActivity::Downtime,
+        );
+        assert!(matches!(result, Err(PassTimeError::InCombat { .. })));
+        assert_eq!(game_state.clock.to_string(), "Day 1, 06:00:00");
+    }
+
+    #[test]
+    fn world_clock_advances_once_per_round() {
+        let mut game_state = fixtures::engine::game_state();
+        let encounters = (0..2)
+            .map(|_| {
+                let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+                let goblin =
+                    fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+                game_state.start_encounter(HashSet::from([fighter, goblin]))
+            })
+            .collect::<Vec<_>>();
+
+        let end_round = |game_state: &mut nat20_core::engine::game_state::GameState,
+                         index: usize| {
+            for _ in 0..2 {
+                let entity = game_state
+                    .encounter(&encounters[index])
+                    .unwrap()
+                    .current_entity();
+                game_state.end_turn(entity);
+            }
+        };
+
+        end_round(&mut game_state, 0);
+        assert_eq!(game_state.clock.to_string(), "Day 1, 06:00:06");
+
+        // The second encounter is fought at the same time as the first one
+        end_round(&mut game_state, 1);
+        assert_eq!(game_state.clock.to_string(), "Day 1, 06:00:06");
+
+        end_round(&mut game_state, 1);
+        assert_eq!(game_state.clock.to_string(), "Day 1, 06:00:12");
+    }
+
+    #[test]
+    fn interrupted_rest_grants_partial_benefits() {
+        let mut game_state = fixtures::engine::game_state();
+        let entity = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let max = systems::helpers::get_component::<HitPoints>(&game_state.world, entity).max();
+        *systems::helpers::get_component_mut::<HitPoints>(&mut game_state.world, entity) =
+            HitPoints::with_current(1, max);
+        let events = game_state.subscribe_events();
+        let start_hour = game_state.clock.hour();
+
+        // Woken up straight away, so no rest at all
+        systems::time::start_rest(&mut game_state, vec![entity], &RestKind::Long).unwrap();
+        let rest = systems::time::interrupt_rest(&mut game_state, vec![entity], 0).unwrap();
+        assert_eq!(rest, None);
+        assert_eq!(
+            systems::helpers::get_component::<HitPoints>(&game_state.world, entity).current(),
+            1
+        );
+
+        // A few hours is enough for a short rest, but not a long one
+        systems::time::start_rest(&mut game_state, vec![entity], &RestKind::Long).unwrap();
+        let rest = systems::time::interrupt_rest(&mut game_state, vec![entity], 3).unwrap();
+        assert_eq!(rest, Some(RestKind::Short));
+        assert_eq!(
+            systems::helpers::get_component::<HitPoints>(&game_state.world, entity).current(),
+            1 + max / 2
+        );
+        assert_eq!(game_state.clock.hour(), start_hour + 3);
+
+        let kinds = events
+            .try_iter()
+            .map(|event| event.kind.name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "RestStarted",
+                "RestInterrupted",
+                "RestStarted",
+                "RestInterrupted"
+            ]
+        );
+    }
+}
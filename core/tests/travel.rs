@@ -0,0 +1,147 @@
+extern crate nat20_core;
+
+mod tests {
+    use std::str::FromStr;
+
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            id::{AdventureId, ItemId},
+            items::{
+                inventory::{Inventory, ItemContainer},
+                money::{Currency, MonetaryValue},
+            },
+        },
+        engine::game_state::GameState,
+        systems::{
+            self,
+            time::RestKind,
+            travel::{self, TravelError},
+        },
+        test_utils::fixtures,
+    };
+
+    fn setup() -> (GameState, Entity) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        systems::campaign::start(
+            &mut game_state,
+            &AdventureId::new("nat20_core", "adventure.goblin_road"),
+        )
+        .unwrap();
+        (game_state, fighter)
+    }
+
+    fn money(amount: &str) -> MonetaryValue {
+        MonetaryValue::from_str(amount).unwrap()
+    }
+
+    fn inventory(game_state: &GameState, entity: Entity) -> Inventory {
+        systems::helpers::get_component_clone::<Inventory>(&game_state.world, entity)
+    }
+
+    fn coins(game_state: &GameState, entity: Entity, currency: Currency) -> u32 {
+        inventory(game_state, entity)
+            .money()
+            .values
+            .get(&currency)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn travel_along_quickest_route() {
+        let (mut game_state, _) = setup();
+        let world_map = &systems::campaign::adventure(&game_state).unwrap().world_map;
+        let path = world_map.path("millbrook", "goblin_den").unwrap();
+        assert_eq!(
+            path.iter()
+                .map(|(location, hours)| (location.id.as_str(), *hours))
+                .collect::<Vec<_>>(),
+            vec![("east_road", 4), ("goblin_den", 3)]
+        );
+        assert_eq!(
+            travel::current_location(&game_state).unwrap().id,
+            "millbrook"
+        );
+        let start_hour = game_state.clock.hour();
+
+        // Wolves might interrupt the journey on the road, but the goblins are
+        // always waiting in their den
+        let mut hours = 0;
+        let outcome = loop {
+            let outcome = travel::travel(&mut game_state, "goblin_den").unwrap();
+            hours += outcome.hours;
+            if outcome.location == "goblin_den" {
+                break outcome;
+            }
+            assert_eq!(outcome.encounter.as_deref(), Some("Wolf Pack"));
+        };
+        assert_eq!(outcome.encounter.as_deref(), Some("Goblin Den"));
+        assert_eq!(hours, 7);
+        assert_eq!(game_state.clock.hour(), start_hour + 7);
+        assert_eq!(
+            travel::current_location(&game_state).unwrap().id,
+            "goblin_den"
+        );
+    }
+
+    #[test]
+    fn inn_and_shop_services() {
+        let (mut game_state, fighter) = setup();
+
+        assert!(matches!(
+            travel::rest_at_inn(&mut game_state, fighter, vec![fighter]),
+            Err(TravelError::InsufficientFunds { .. })
+        ));
+
+        let start_hour = game_state.clock.hour();
+        systems::inventory::add_money(&mut game_state.world, fighter, money("7 SP, 2 GP"));
+        travel::rest_at_inn(&mut game_state, fighter, vec![fighter]).unwrap();
+        assert_eq!(game_state.clock.hour(), start_hour + 8);
+        assert_eq!(coins(&game_state, fighter, Currency::Silver), 2);
+
+        let gold = coins(&game_state, fighter, Currency::Gold);
+        let dagger = ItemId::new("nat20_core", "item.dagger");
+        travel::buy(&mut game_state, fighter, &dagger).unwrap();
+        assert_eq!(coins(&game_state, fighter, Currency::Gold), gold - 2);
+        assert!(
+            inventory(&game_state, fighter)
+                .items()
+                .iter()
+                .any(|item| item.item().id == dagger)
+        );
+
+        let greatsword = ItemId::new("nat20_core", "item.greatsword");
+        assert!(matches!(
+            travel::buy(&mut game_state, fighter, &greatsword),
+            Err(TravelError::NotForSale(_))
+        ));
+    }
+
+    #[test]
+    fn camping_with_night_watch() {
+        let (mut game_state, fighter) = setup();
+        let start_hour = game_state.clock.hour();
+
+        // Nothing disturbs the night in town
+        let outcome = travel::camp(&mut game_state, vec![fighter], &[fighter]).unwrap();
+        assert_eq!(outcome.hours, 8);
+        assert_eq!(outcome.rest, Some(RestKind::Long));
+        assert_eq!(outcome.encounter, None);
+        assert_eq!(game_state.clock.hour(), (start_hour + 8) % 24);
+
+        // The goblins never let anyone get any sleep in their den
+        while travel::travel(&mut game_state, "goblin_den")
+            .unwrap()
+            .location
+            != "goblin_den"
+        {}
+        let outcome = travel::camp(&mut game_state, vec![fighter], &[fighter]).unwrap();
+        assert_eq!(outcome.hours, 0);
+        assert_eq!(outcome.rest, None);
+        assert_eq!(outcome.encounter.as_deref(), Some("Goblin Den"));
+        assert_eq!(outcome.sentry, Some(fighter));
+        assert!(game_state.resting.is_empty());
+    }
+}
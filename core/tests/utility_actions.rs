@@ -0,0 +1,145 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::{Entity, World};
+    use nat20_core::{
+        components::{
+            actions::{
+                action::{ActionContext, ActionKindResult, UtilityOutcome},
+                targeting::TargetInstance,
+            },
+            damage::DamageThreshold,
+            dice::RollPolicy,
+            health::hit_points::HitPoints,
+            id::{ActionId, EntityIdentifier, Name},
+            lever::Lever,
+            lock::{Lock, UnlockMethod},
+            resource::ResourceAmountMap,
+            species::CreatureSize,
+        },
+        engine::{
+            event::{ActionData, EventKind},
+            game_state::GameState,
+        },
+        entities::object::{Object, ObjectMaterial},
+        registry::registry::ItemsRegistry,
+        systems,
+        test_utils::fixtures,
+    };
+
+    /// A rogue who never misses a roll, standing next to the object
+    fn setup(object: fn(&mut World) -> EntityIdentifier) -> (GameState, Entity, Entity) {
+        let (mut game_state, rogue, object) = fixtures::engine::game_state_with_pair(
+            fixtures::creatures::heroes::fighter,
+            object,
+            1.0,
+        );
+        game_state
+            .world
+            .insert_one(
+                rogue,
+                RollPolicy {
+                    average_damage: false,
+                    fixed_d20: Some(20),
+                },
+            )
+            .unwrap();
+        (game_state, rogue, object)
+    }
+
+    fn object(world: &mut World, name: &str) -> Entity {
+        world.spawn(Object::new(
+            Name::new(name),
+            ObjectMaterial::Wood,
+            CreatureSize::Large,
+            HitPoints::new(18),
+            DamageThreshold(5),
+        ))
+    }
+
+    fn locked_door(world: &mut World) -> EntityIdentifier {
+        let door = object(world, "Wooden Door");
+        world.insert_one(door, Lock::new(10, 40, None)).unwrap();
+        EntityIdentifier::from_world(world, door)
+    }
+
+    fn lever(world: &mut World) -> EntityIdentifier {
+        let lever = object(world, "Rusty Lever");
+        world.insert_one(lever, Lever::default()).unwrap();
+        EntityIdentifier::from_world(world, lever)
+    }
+
+    /// Perform the utility action on the target, and return what it did
+    fn perform(
+        game_state: &mut GameState,
+        actor: Entity,
+        action: &str,
+        target: Entity,
+    ) -> UtilityOutcome {
+        let action = ActionData::new(
+            actor,
+            ActionId::new("nat20_core", action),
+            ActionContext::Other,
+            ResourceAmountMap::new(),
+            vec![TargetInstance::from_entity(&game_state.world, target)],
+        );
+        systems::actions::perform_action(game_state, &action);
+
+        game_state
+            .event_log
+            .events
+            .iter()
+            .rev()
+            .find_map(|event| match &event.kind {
+                EventKind::ActionPerformed { results, .. } => match &results.first()?.kind {
+                    ActionKindResult::Utility(outcome) => Some(outcome.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .expect("The utility action should be performed")
+    }
+
+    #[test]
+    fn picking_a_lock_opens_it() {
+        let (mut game_state, rogue, door) = setup(locked_door);
+
+        assert_eq!(
+            perform(&mut game_state, rogue, "action.pick_lock", door),
+            UtilityOutcome::StillLocked
+        );
+        assert!(systems::locks::is_locked(&game_state.world, door));
+
+        let tools = ItemsRegistry::get(&systems::locks::thieves_tools())
+            .unwrap()
+            .clone();
+        systems::inventory::add_item(&mut game_state.world, rogue, tools);
+        assert_eq!(
+            perform(&mut game_state, rogue, "action.pick_lock", door),
+            UtilityOutcome::Unlocked(UnlockMethod::Picked)
+        );
+        assert!(!systems::locks::is_locked(&game_state.world, door));
+
+        // There's nothing left to pick
+        assert_eq!(
+            perform(&mut game_state, rogue, "action.pick_lock", door),
+            UtilityOutcome::NoEffect
+        );
+    }
+
+    #[test]
+    fn pulling_a_lever_switches_it() {
+        let (mut game_state, rogue, lever) = setup(lever);
+
+        for active in [true, false] {
+            assert_eq!(
+                perform(&mut game_state, rogue, "action.pull_lever", lever),
+                UtilityOutcome::LeverPulled { active }
+            );
+            assert_eq!(
+                systems::levers::is_active(&game_state.world, lever),
+                Some(active)
+            );
+        }
+    }
+}
@@ -0,0 +1,117 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::Entity;
+    use nat20_core::{
+        components::{
+            actions::{action::ActionContext, targeting::TargetInstance},
+            id::{ActionId, ResourceId},
+            resource::{ResourceAmount, ResourceAmountMap, ResourceError, ResourceMap},
+        },
+        engine::{
+            event::{ActionData, ActionError},
+            game_state::GameState,
+        },
+        systems,
+        test_utils::fixtures,
+    };
+
+    fn second_wind_uses() -> ResourceId {
+        ResourceId::new("nat20_core", "resource.fighter.second_wind")
+    }
+
+    fn current_uses(game_state: &GameState, fighter: Entity) -> ResourceAmount {
+        systems::helpers::get_component::<ResourceMap>(&game_state.world, fighter)
+            .get(&second_wind_uses())
+            .unwrap()
+            .current_uses()[0]
+            .clone()
+    }
+
+    /// Second Wind, but the fighter decides how many uses to spend on it
+    fn variable_second_wind(fighter: Entity, max: u8) -> ActionData {
+        ActionData::new(
+            fighter,
+            ActionId::new("nat20_core", "action.fighter.second_wind"),
+            ActionContext::Other,
+            ResourceAmountMap::from([(
+                second_wind_uses(),
+                ResourceAmount::Variable { min: 1, max },
+            )]),
+            vec![TargetInstance::Entity(fighter)],
+        )
+    }
+
+    fn setup() -> (GameState, Entity, u8) {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let ResourceAmount::Flat(uses) = current_uses(&game_state, fighter) else {
+            panic!("Second Wind should be a flat resource");
+        };
+        assert!(uses >= 2);
+        (game_state, fighter, uses)
+    }
+
+    #[test]
+    fn variable_amount_is_limited_by_resources() {
+        let (game_state, fighter, uses) = setup();
+
+        let action = variable_second_wind(fighter, u8::MAX);
+        assert_eq!(
+            systems::actions::variable_amount_range(
+                &game_state.world,
+                fighter,
+                &action.resource_cost
+            ),
+            Some((1, uses))
+        );
+
+        let action = variable_second_wind(fighter, 1);
+        assert_eq!(
+            systems::actions::variable_amount_range(
+                &game_state.world,
+                fighter,
+                &action.resource_cost
+            ),
+            Some((1, 1))
+        );
+
+        let fixed = ResourceAmountMap::from([(second_wind_uses(), ResourceAmount::Flat(1))]);
+        assert_eq!(
+            systems::actions::variable_amount_range(&game_state.world, fighter, &fixed),
+            None
+        );
+    }
+
+    #[test]
+    fn chosen_amount_is_spent() {
+        let (mut game_state, fighter, uses) = setup();
+
+        // The amount has to be chosen before the action can be performed
+        let mut action = variable_second_wind(fighter, u8::MAX);
+        assert!(matches!(
+            game_state.validate_action(&action, true),
+            Err(ActionError::Resource(ResourceError::UnresolvedAmount(_)))
+        ));
+        assert_eq!(
+            current_uses(&game_state, fighter),
+            ResourceAmount::Flat(uses)
+        );
+
+        assert!(systems::actions::choose_amount(&mut action, 0).is_err());
+        systems::actions::choose_amount(&mut action, 2).unwrap();
+        // The amount is carried next to the context the action was chosen in
+        assert_eq!(action.context.amount(), Some(2));
+        assert_eq!(action.context.base(), &ActionContext::Other);
+        assert_eq!(
+            action.resource_cost[&second_wind_uses()],
+            ResourceAmount::Flat(2)
+        );
+
+        game_state.validate_action(&action, true).unwrap();
+        assert_eq!(
+            current_uses(&game_state, fighter),
+            ResourceAmount::Flat(uses - 2)
+        );
+    }
+}
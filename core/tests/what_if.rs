@@ -0,0 +1,97 @@
+extern crate nat20_core;
+
+mod tests {
+    use hecs::World;
+    use nat20_core::{
+        components::{
+            ability::{Ability, AbilityScoreMap},
+            id::{EffectId, ItemId},
+            items::equipment::slots::EquipmentSlot,
+        },
+        registry::registry::ItemsRegistry,
+        systems::{
+            self,
+            what_if::{Stat, WhatIf},
+        },
+        test_utils::fixtures,
+    };
+
+    #[test]
+    fn bless_adds_expected_bonus() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let bless = EffectId::new("nat20_core", "effect.spell.bless");
+
+        let report =
+            systems::what_if::compare(&game_state.world, fighter, &[WhatIf::Effect(bless.clone())])
+                .unwrap();
+        let changes = report.changes();
+
+        // +1d4 on every saving throw and attack roll, and nothing else
+        for ability in [Ability::Strength, Ability::Wisdom] {
+            let change = changes
+                .iter()
+                .find(|change| change.stat == Stat::SavingThrow(ability))
+                .unwrap();
+            assert_eq!(change.delta(), 2.5);
+        }
+        assert!(
+            changes
+                .iter()
+                .all(|change| matches!(change.stat, Stat::SavingThrow(_) | Stat::AttackBonus(_)))
+        );
+        assert!(
+            changes
+                .iter()
+                .any(|change| change.stat == Stat::AttackBonus(EquipmentSlot::RangedMainHand))
+        );
+
+        // The fighter itself isn't blessed
+        assert!(!systems::effects::has_effect(
+            &game_state.world,
+            fighter,
+            &bless
+        ));
+    }
+
+    #[test]
+    fn equipping_item_in_scratch_world() {
+        let mut game_state = fixtures::engine::game_state();
+        let fighter = fixtures::creatures::heroes::fighter(&mut game_state.world).id();
+        let intelligence = |world: &World| {
+            systems::helpers::get_component::<AbilityScoreMap>(world, fighter)
+                .get(&Ability::Intelligence)
+                .total()
+        };
+        let before = intelligence(&game_state.world);
+
+        let headband = ItemsRegistry::get(&ItemId::new("nat20_core", "item.headband_of_intellect"))
+            .unwrap()
+            .clone();
+        let report = systems::what_if::compare(
+            &game_state.world,
+            fighter,
+            &[WhatIf::Equip(headband.into())],
+        )
+        .unwrap();
+
+        let change = report
+            .changes()
+            .into_iter()
+            .find(|change| change.stat == Stat::AbilityScore(Ability::Intelligence))
+            .unwrap();
+        assert_eq!(change.before, before as f32);
+        assert_eq!(change.after, 19.0);
+        assert_eq!(intelligence(&game_state.world), before);
+    }
+
+    #[test]
+    fn no_changes_no_differences() {
+        let mut game_state = fixtures::engine::game_state();
+        let goblin = fixtures::creatures::monsters::goblin_warrior(&mut game_state.world).id();
+
+        let report = systems::what_if::compare(&game_state.world, goblin, &[]).unwrap();
+        assert!(report.changes().is_empty());
+        assert_eq!(report.before, report.after);
+    }
+}
@@ -0,0 +1,321 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use hecs::Entity;
+use nat20_core::{
+    components::{
+        actions::action::{ActionContext, ActionKindResult, DamageResolutionKind},
+        health::life_state::LifeState,
+    },
+    engine::{
+        encounter::EncounterId,
+        event::{Event, EventKind},
+        game_state::GameState,
+    },
+};
+use strum::{Display, EnumIter};
+
+use crate::state::{self, events::EventSubscription, settings::GuiSettings};
+
+/// Sound files are looked up in here, e.g. `assets/audio/sfx/critical_hit.wav`
+/// or `assets/audio/music/combat.wav`. Missing files are skipped.
+const AUDIO_DIRECTORY: &str = "assets/audio";
+
+/// Short sounds played when something happens in the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum SoundCue {
+    Hit,
+    CriticalHit,
+    Miss,
+    SpellCast,
+    Death,
+    TurnStart,
+}
+
+impl SoundCue {
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("{}/sfx/{}.wav", AUDIO_DIRECTORY, self))
+    }
+}
+
+/// Background music, which fades between the tracks when combat starts or ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum MusicTrack {
+    Exploration,
+    Combat,
+}
+
+impl MusicTrack {
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("{}/music/{}.wav", AUDIO_DIRECTORY, self))
+    }
+}
+
+/// The sounds an event should trigger. An attack hitting several targets only
+/// plays its sound once.
+pub fn event_cues(event: &Event) -> Vec<SoundCue> {
+    let mut cues = Vec::new();
+
+    match &event.kind {
+        EventKind::ActionPerformed { action, results } => {
+            if matches!(action.context, ActionContext::Spell { .. }) {
+                cues.push(SoundCue::SpellCast);
+            }
+
+            let mut kinds = results
+                .iter()
+                .map(|result| &result.kind)
+                .collect::<Vec<_>>();
+            while let Some(kind) = kinds.pop() {
+                match kind {
+                    ActionKindResult::Standard(outcome) => {
+                        if let Some(damage) = &outcome.damage
+                            && let DamageResolutionKind::AttackRoll { attack_roll, .. } =
+                                &damage.kind
+                        {
+                            let cue = if damage.damage_taken.is_none() {
+                                SoundCue::Miss
+                            } else if attack_roll.roll_result.is_crit {
+                                SoundCue::CriticalHit
+                            } else {
+                                SoundCue::Hit
+                            };
+                            if !cues.contains(&cue) {
+                                cues.push(cue);
+                            }
+                        }
+                    }
+                    ActionKindResult::Composite { actions } => kinds.extend(actions),
+                    _ => {}
+                }
+            }
+        }
+
+        EventKind::LifeStateChanged {
+            new_state: LifeState::Dead | LifeState::Defeated,
+            ..
+        } => cues.push(SoundCue::Death),
+
+        _ => {}
+    }
+
+    cues
+}
+
+/// Move the music mix towards the target, taking `crossfade_seconds` to fade
+/// all the way from one track to the other
+fn crossfade(current: f32, target: f32, delta_time: f32, crossfade_seconds: f32) -> f32 {
+    if crossfade_seconds <= 0.0 {
+        return target;
+    }
+    let step = delta_time / crossfade_seconds;
+    if current < target {
+        (current + step).min(target)
+    } else {
+        (current - step).max(target)
+    }
+}
+
+/// Plays sound effects for the events logged in the game state, and the
+/// background music. Without the `audio` feature nothing is played.
+pub struct AudioPlayer {
+    events: EventSubscription,
+    /// Last seen turns, so a sound is only played when the turn changes
+    current_turns: HashMap<EncounterId, Entity>,
+    /// How far the music has faded towards the combat track, from 0 (only
+    /// exploration music) to 1 (only combat music)
+    combat_mix: f32,
+    /// Opened the first time something is played. `Some(None)` means there's
+    /// no audio output, so it isn't opened again every frame.
+    #[cfg(feature = "audio")]
+    output: Option<Option<playback::Output>>,
+}
+
+impl AudioPlayer {
+    pub fn new() -> Self {
+        Self {
+            events: EventSubscription::new(),
+            current_turns: HashMap::new(),
+            combat_mix: 0.0,
+            #[cfg(feature = "audio")]
+            output: None,
+        }
+    }
+
+    pub fn update(&mut self, settings: &GuiSettings, game_state: &mut GameState, delta_time: f32) {
+        if !*settings.get::<bool>(state::parameters::AUDIO_ENABLED) {
+            // Stop listening, so the events don't pile up while muted
+            self.events.unsubscribe();
+            self.stop();
+            return;
+        }
+
+        let events = self.events.poll(game_state);
+
+        let mut cues = events.iter().flat_map(event_cues).collect::<Vec<_>>();
+
+        self.current_turns
+            .retain(|encounter_id, _| game_state.encounters.contains_key(encounter_id));
+        for (encounter_id, encounter) in &game_state.encounters {
+            let current_entity = encounter.current_entity();
+            if self
+                .current_turns
+                .insert(encounter_id.clone(), current_entity)
+                != Some(current_entity)
+                && !cues.contains(&SoundCue::TurnStart)
+            {
+                cues.push(SoundCue::TurnStart);
+            }
+        }
+
+        let target = if game_state.in_combat.is_empty() {
+            0.0
+        } else {
+            1.0
+        };
+        self.combat_mix = crossfade(
+            self.combat_mix,
+            target,
+            delta_time,
+            *settings.get::<f32>(state::parameters::AUDIO_CROSSFADE_SECONDS),
+        );
+
+        self.play(settings, &cues);
+    }
+
+    #[cfg(feature = "audio")]
+    fn play(&mut self, settings: &GuiSettings, cues: &[SoundCue]) {
+        let Some(output) = self.output.get_or_insert_with(playback::Output::new) else {
+            return;
+        };
+
+        let master = *settings.get::<f32>(state::parameters::AUDIO_MASTER_VOLUME);
+        let music = master * *settings.get::<f32>(state::parameters::AUDIO_MUSIC_VOLUME);
+        let sound_effects = master * *settings.get::<f32>(state::parameters::AUDIO_SFX_VOLUME);
+
+        output.set_music_volume(MusicTrack::Exploration, music * (1.0 - self.combat_mix));
+        output.set_music_volume(MusicTrack::Combat, music * self.combat_mix);
+        for cue in cues {
+            output.play(*cue, sound_effects);
+        }
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn play(&mut self, _settings: &GuiSettings, _cues: &[SoundCue]) {}
+
+    fn stop(&mut self) {
+        #[cfg(feature = "audio")]
+        {
+            self.output = None;
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod playback {
+    use std::{collections::HashMap, fs, io::Cursor};
+
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+    use strum::IntoEnumIterator;
+    use tracing::{debug, warn};
+
+    use super::{MusicTrack, SoundCue};
+
+    /// Dropping the output stops everything that's playing
+    pub struct Output {
+        // Nothing plays once the stream is dropped, so it's kept around
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        music: HashMap<MusicTrack, Sink>,
+        /// Sound files are read the first time they're played. Files which
+        /// couldn't be read are remembered, so they're only reported once.
+        sounds: HashMap<SoundCue, Option<Vec<u8>>>,
+    }
+
+    impl Output {
+        pub fn new() -> Option<Self> {
+            let (stream, handle) = match OutputStream::try_default() {
+                Ok(output) => output,
+                Err(error) => {
+                    warn!("Failed to open audio output: {}", error);
+                    return None;
+                }
+            };
+
+            // Every track plays all the time, and the crossfade only changes
+            // their volumes, so the music picks up where it left off
+            let music = MusicTrack::iter()
+                .filter_map(|track| Some((track, start_music(&handle, track)?)))
+                .collect();
+
+            Some(Self {
+                _stream: stream,
+                handle,
+                music,
+                sounds: HashMap::new(),
+            })
+        }
+
+        pub fn set_music_volume(&self, track: MusicTrack, volume: f32) {
+            if let Some(sink) = self.music.get(&track) {
+                sink.set_volume(volume);
+            }
+        }
+
+        pub fn play(&mut self, cue: SoundCue, volume: f32) {
+            let Some(bytes) = self.sounds.entry(cue).or_insert_with(|| read(cue)) else {
+                return;
+            };
+            let source = match Decoder::new(Cursor::new(bytes.clone())) {
+                Ok(source) => source,
+                Err(error) => {
+                    warn!("Failed to decode {}: {}", cue.path().display(), error);
+                    return;
+                }
+            };
+            if let Err(error) = self
+                .handle
+                .play_raw(source.amplify(volume).convert_samples())
+            {
+                warn!("Failed to play {}: {}", cue, error);
+            }
+        }
+    }
+
+    fn read(cue: SoundCue) -> Option<Vec<u8>> {
+        let path = cue.path();
+        match fs::read(&path) {
+            Ok(bytes) => Some(bytes),
+            Err(error) => {
+                debug!("No sound for {}: {} ({})", cue, path.display(), error);
+                None
+            }
+        }
+    }
+
+    fn start_music(handle: &OutputStreamHandle, track: MusicTrack) -> Option<Sink> {
+        let path = track.path();
+        let source = fs::read(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| Decoder::new(Cursor::new(bytes)).map_err(|error| error.to_string()));
+        let source = match source {
+            Ok(source) => source,
+            Err(error) => {
+                debug!("No {} music: {} ({})", track, path.display(), error);
+                return None;
+            }
+        };
+
+        let sink = match Sink::try_new(handle) {
+            Ok(sink) => sink,
+            Err(error) => {
+                warn!("Failed to create sink for {} music: {}", track, error);
+                return None;
+            }
+        };
+        sink.set_volume(0.0);
+        sink.append(source.repeat_infinite());
+        Some(sink)
+    }
+}
@@ -3,6 +3,7 @@ use std::{
     time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
+mod audio;
 mod render;
 mod state;
 mod utils;
@@ -16,7 +17,8 @@ use tracing_subscriber::{
 };
 
 use crate::{
-    render::ui::utils::ImguiRenderableMut, state::gui_state::GuiState,
+    render::ui::utils::ImguiRenderableMut,
+    state::{gui_state::GuiState, keybindings::KeyAction},
     windows::main_menu::MainMenuWindow,
 };
 
@@ -28,6 +30,9 @@ fn main() {
     let gl = utils::glow_context(&context);
 
     let mut gui_state = GuiState::new(gl, &mut imgui_context);
+    let base_style = imgui_context.style().clone();
+    let mut applied_ui_scale = ui_scale(&gui_state, winit_platform.hidpi_factor());
+    let mut applied_theme = None;
     let mut show_settings = false;
 
     let mut last_frame = Instant::now();
@@ -50,6 +55,23 @@ fn main() {
                     .prepare_frame(imgui_context.io_mut(), &window)
                     .unwrap();
 
+                let gamepad_enabled = *gui_state
+                    .settings
+                    .get::<bool>(state::parameters::GAMEPAD_ENABLED);
+                let cursor_speed = *gui_state
+                    .settings
+                    .get::<f32>(state::parameters::GAMEPAD_CURSOR_SPEED);
+                if let Some([x, y]) =
+                    gui_state
+                        .gamepad
+                        .update(imgui_context.io_mut(), gamepad_enabled, cursor_speed)
+                {
+                    let hidpi_factor = winit_platform.hidpi_factor() as f32;
+                    gui_state
+                        .camera
+                        .set_cursor(x * hidpi_factor, y * hidpi_factor);
+                }
+
                 window.request_redraw();
             }
 
@@ -57,13 +79,27 @@ fn main() {
                 event: winit::event::WindowEvent::RedrawRequested,
                 ..
             } => {
+                // Changing the theme, the font size or moving to a monitor with a
+                // different scale factor requires rebuilding the style and font atlas
+                let current_ui_scale = ui_scale(&gui_state, winit_platform.hidpi_factor());
+                let current_theme = gui_state.themes.revision();
+                if current_ui_scale != applied_ui_scale || applied_theme != Some(current_theme) {
+                    let mut style = base_style.clone();
+                    gui_state.themes.current().apply_colors(&mut style);
+                    utils::apply_ui_scale(&mut imgui_context, &style, &current_ui_scale);
+                    gui_state
+                        .reload_font_texture(utils::glow_context(&context), &mut imgui_context);
+                    applied_ui_scale = current_ui_scale;
+                    applied_theme = Some(current_theme);
+                }
+
                 let ui = imgui_context.frame();
 
                 gui_state.new_frame(&window);
 
                 let prev_show_settings = show_settings;
 
-                if ui.is_key_pressed(imgui::Key::Escape) {
+                if gui_state.keybindings.pressed(ui, KeyAction::ToggleSettings) {
                     show_settings = !show_settings;
                 }
 
@@ -78,6 +114,14 @@ fn main() {
                                 ui.set_keyboard_focus_here();
                             }
                             gui_state.settings.render_mut(&ui);
+
+                            if ui.collapsing_header("Theme", imgui::TreeNodeFlags::empty()) {
+                                gui_state.themes.render_mut(&ui);
+                            }
+
+                            if ui.collapsing_header("Key Bindings", imgui::TreeNodeFlags::empty()) {
+                                gui_state.keybindings.render_mut(&ui);
+                            }
                         });
                 }
 
@@ -127,28 +171,23 @@ fn main() {
                 winit_platform.handle_event(imgui_context.io_mut(), &window, &event);
             }
 
-            // winit::event::Event::WindowEvent {
-            //     event: winit::event::WindowEvent::ScaleFactorChanged { new_inner_size, .. },
-            //     ..
-            // } => {
-            //     // new_inner_size is &mut; read and resize the surface + viewport
-            //     let (w, h) = (new_inner_size.width, new_inner_size.height);
-            //     if w > 0 && h > 0 {
-            //         surface.resize(
-            //             &context,
-            //             NonZeroU32::new(w).unwrap(),
-            //             NonZeroU32::new(h).unwrap(),
-            //         );
-            //         unsafe {
-            //             gl.viewport(0, 0, w as i32, h as i32);
-            //         }
-            //     }
-            //     winit_platform.handle_event(imgui_context.io_mut(), &window, &event);
-            // }
+            winit::event::Event::WindowEvent {
+                event: winit::event::WindowEvent::ScaleFactorChanged { .. },
+                ..
+            } => {
+                // The platform updates the scale factor, and the fonts are rebuilt
+                // for it before the next frame. The surface is resized by the
+                // Resized event that follows.
+                winit_platform.handle_event(imgui_context.io_mut(), &window, &event);
+            }
             winit::event::Event::WindowEvent {
                 event: ref window_event,
                 ..
             } => {
+                if let winit::event::WindowEvent::CursorMoved { .. } = window_event {
+                    // The mouse takes over from the gamepad cursor
+                    imgui_context.io_mut().mouse_draw_cursor = false;
+                }
                 let wants_mouse = imgui_context.io().want_capture_mouse;
                 gui_state.camera.handle_event(&window_event, wants_mouse);
                 winit_platform.handle_event(imgui_context.io_mut(), &window, &event);
@@ -163,6 +202,16 @@ fn main() {
         .expect("EventLoop error");
 }
 
+fn ui_scale(gui_state: &GuiState, hidpi_factor: f64) -> utils::UiScale {
+    utils::UiScale::new(
+        *gui_state
+            .settings
+            .get::<f32>(state::parameters::UI_FONT_SIZE),
+        *gui_state.settings.get::<f32>(state::parameters::UI_SCALE),
+        hidpi_factor,
+    )
+}
+
 fn render_imgui_windows(gui_state: &mut GuiState, ui: &mut imgui::Ui) {
     let show_demo = gui_state
         .settings
@@ -1,6 +1,10 @@
 pub mod components;
+pub mod custom;
 pub mod engine;
 pub mod entities;
+pub mod icons;
+pub mod images;
 pub mod inventory;
 pub mod text;
+pub mod theme;
 pub mod utils;
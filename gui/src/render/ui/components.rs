@@ -1,4 +1,4 @@
-use std::{collections::HashMap, ops::Deref, vec};
+use std::{collections::HashMap, ops::Deref, path::PathBuf, vec};
 
 use hecs::{Entity, World};
 use nat20_core::{
@@ -7,10 +7,14 @@ use nat20_core::{
         actions::{
             action::{
                 ActionCondition, ActionContext, ActionKind, ActionKindResult, ActionResult,
-                DamageResolutionKind, ReactionResult,
+                DamageOutcome, DamageResolutionKind, ReactionResult, SavingThrowOutcome,
+                UtilityOutcome,
             },
+            modification::ModificationDelta,
+            resolution::TargetResolution,
             targeting::{AreaShape, TargetInstance, TargetingKind, TargetingRange},
         },
+        biography::Biography,
         d20::{D20CheckDC, D20CheckResult, RollMode},
         damage::{
             AttackRollResult, DamageComponentMitigation, DamageComponentResult,
@@ -18,31 +22,46 @@ use nat20_core::{
             DamageRollResult, MitigationOperation,
         },
         effects::effect::{EffectInstance, EffectLifetime},
-        health::{hit_points::HitPoints, life_state::LifeState},
+        health::{
+            hit_points::HitPoints, life_state::LifeState, massive_damage::MassiveDamageOutcome,
+        },
         id::{ActionId, FeatId, Name, ResourceId, SpeciesId, SpellId, SubspeciesId},
+        images::CreatureImages,
         items::{
+            coating::CoatingItem,
             equipment::{
                 armor::{Armor, ArmorClass, ArmorDexterityBonus, ArmorType},
                 loadout::Loadout,
                 weapon::{MELEE_RANGE_DEFAULT, Weapon},
             },
+            inventory::ItemContainer,
             item::{Item, ItemRarity},
+            light::LightItem,
             money::MonetaryValue,
+            spell_item::SpellItem,
+            supply::SupplyItem,
+            tool::ToolItem,
         },
         level::{ChallengeRating, CharacterLevels, Level},
+        lock::Lock,
         modifier::{Modifiable, ModifierSet},
+        personality::Personality,
         proficiency::{Proficiency, ProficiencyLevel},
         resource::{ResourceAmount, ResourceAmountMap, ResourceBudgetKind, ResourceMap},
         saving_throw::{SavingThrowKind, SavingThrowSet},
         skill::{Skill, SkillSet, skill_ability},
         species::{CreatureSize, CreatureType},
         speed::Speed,
-        spells::spellbook::Spellbook, time::{TimeDuration, TimeMode},
+        spells::{spell::MaterialConsumption, spellbook::Spellbook}, time::{TimeDuration, TimeMode, TurnBoundary},
+    },
+    registry::{
+        self,
+        registry::{ItemsRegistry, MonstersRegistry, SpellsRegistry},
     },
-    registry::{self, registry::SpellsRegistry},
     systems::{
         self,
         d20::{D20CheckDCKind, D20ResultKind},
+        what_if::WhatIfReport,
     },
 };
 use std::collections::HashSet;
@@ -51,12 +70,13 @@ use uom::si::{angle::degree, length::meter, mass::kilogram};
 
 use crate::{
     render::ui::{
+        custom::render_custom_result,
         engine::render_event_description,
         text::{TextKind, TextSegment, TextSegments, indent_text, item_rarity_color},
         utils::{
-            ImguiRenderable, ImguiRenderableMutWithContext, ImguiRenderableWithContext,
-            ProgressBarColor, SELECTED_BUTTON_COLOR, render_empty_button, render_progress_bar,
-            roman_numeral,
+            ImguiRenderable, ImguiRenderableMut, ImguiRenderableMutWithContext,
+            ImguiRenderableWithContext, ProgressBarColor, SELECTED_BUTTON_COLOR,
+            render_empty_button, render_progress_bar, roman_numeral,
         },
     },
     table_with_columns,
@@ -90,6 +110,10 @@ impl ImguiRenderableWithContext<ModifierSetRenderMode> for ModifierSet {
                     ));
                     segments.push((format!("({})", source), TextKind::Details));
                 }
+                for (source, dice) in self.dice_modifiers() {
+                    segments.push((dice.to_string(), TextKind::Normal));
+                    segments.push((format!("({})", source), TextKind::Details));
+                }
                 TextSegments::new(segments).render(ui);
             }
 
@@ -105,10 +129,21 @@ impl ImguiRenderableWithContext<ModifierSetRenderMode> for ModifierSet {
                     .with_indent(indent_level)
                     .render(ui);
                 }
+                for (source, dice) in self.dice_modifiers() {
+                    TextSegments::new(vec![
+                        (dice.to_string(), TextKind::Normal),
+                        (source.to_string(), TextKind::Details),
+                    ])
+                    .with_indent(indent_level)
+                    .render(ui);
+                }
             }
 
             ModifierSetRenderMode::Hoverable => {
-                let total = format!("{}{}", sign(self.total()), self.total().abs());
+                let mut total = format!("{}{}", sign(self.total()), self.total().abs());
+                for (_, dice) in self.dice_modifiers() {
+                    total.push_str(&format!(" {}", dice));
+                }
                 ui.text(total);
                 if self.is_empty() {
                     return;
@@ -155,6 +190,72 @@ impl ImguiRenderable for ChallengeRating {
     }
 }
 
+impl ImguiRenderable for Personality {
+    fn render(&self, ui: &imgui::Ui) {
+        if self.is_empty() {
+            return;
+        }
+        ui.text_disabled(self.to_string());
+    }
+}
+
+impl ImguiRenderable for Biography {
+    fn render(&self, ui: &imgui::Ui) {
+        if self.is_empty() {
+            ui.text_disabled("Nothing has been written about this character yet");
+            return;
+        }
+        for (label, text) in [
+            ("Appearance", &self.appearance),
+            ("Backstory", &self.backstory),
+            ("Trinket", &self.trinket),
+            ("Notes", &self.notes),
+        ] {
+            if text.is_empty() {
+                continue;
+            }
+            ui.separator_with_text(label);
+            ui.text_wrapped(text);
+        }
+    }
+}
+
+impl ImguiRenderableMut for Biography {
+    fn render_mut(&mut self, ui: &imgui::Ui) {
+        let size = [ui.content_region_avail()[0], ui.text_line_height() * 5.0];
+        for (label, text) in [
+            ("Appearance", &mut self.appearance),
+            ("Backstory", &mut self.backstory),
+            ("Notes", &mut self.notes),
+        ] {
+            ui.separator_with_text(label);
+            ui.input_text_multiline(format!("##{}", label), text, size).build();
+        }
+
+        ui.separator_with_text("Trinket");
+        ui.input_text("##Trinket", &mut self.trinket).build();
+    }
+}
+
+impl ImguiRenderableMut for CreatureImages {
+    fn render_mut(&mut self, ui: &imgui::Ui) {
+        for (label, image) in [("Portrait", &mut self.portrait), ("Token", &mut self.token)] {
+            ui.separator_with_text(label);
+            let mut path = image
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default();
+            if ui
+                .input_text(format!("##{}", label), &mut path)
+                .hint("Path to an image")
+                .build()
+            {
+                *image = (!path.is_empty()).then(|| PathBuf::from(path));
+            }
+        }
+    }
+}
+
 // TODO: Store all colors in one place?
 pub static FULL_HEALTH_COLOR: [f32; 4] = [0.0, 0.7, 0.0, 1.0];
 pub static FULL_HEALTH_BG_COLOR: [f32; 4] = [0.0, 0.2, 0.0, 1.0];
@@ -210,6 +311,23 @@ impl ImguiRenderable for LifeState {
     }
 }
 
+impl ImguiRenderable for Lock {
+    fn render(&self, ui: &imgui::Ui) {
+        if !self.locked {
+            TextSegment::new("Unlocked", TextKind::Details).render(ui);
+            return;
+        }
+        TextSegments::new(vec![
+            ("Locked".to_string(), TextKind::Red),
+            (
+                format!("(pick DC {}, force DC {})", self.dc, self.force_dc),
+                TextKind::Details,
+            ),
+        ])
+        .render(ui);
+    }
+}
+
 fn proficiency_icon(proficiency: &ProficiencyLevel) -> &'static str {
     match proficiency {
         ProficiencyLevel::None => "",
@@ -269,6 +387,21 @@ impl ImguiRenderable for AbilityScore {
 
         self.modifiers
             .render_with_context(ui, ModifierSetRenderMode::List(1));
+
+        if let Some((source, value)) = self.active_override() {
+            TextSegments::new(vec![
+                (format!("Set to {}", value), TextKind::Normal),
+                (format!("({})", source), TextKind::Details),
+            ])
+            .render(ui);
+        }
+        for (source, value) in &self.maximums {
+            TextSegments::new(vec![
+                (format!("Maximum {}", value), TextKind::Normal),
+                (format!("({})", source), TextKind::Details),
+            ])
+            .render(ui);
+        }
     }
 }
 
@@ -297,24 +430,32 @@ impl ImguiRenderableWithContext<(&World, Entity)> for AbilityScoreMap {
 
                     ui.separator_with_text("Saving Throw");
 
-                    if saving_throw_proficiency.level() != &ProficiencyLevel::None {
+                    // Everything granting the proficiency, not just the best source
+                    for (source, level) in saving_throw_proficiency.sources() {
+                        if level == &ProficiencyLevel::None {
+                            continue;
+                        }
                         TextSegments::new(vec![
-                            (
-                                format!("{}", saving_throw_proficiency.level()),
-                                TextKind::Normal,
-                            ),
-                            (
-                                format!("({})", saving_throw_proficiency.source()),
-                                TextKind::Details,
-                            ),
+                            (format!("{}", level), TextKind::Normal),
+                            (format!("({})", source), TextKind::Details),
                         ])
                         .render(ui);
                     }
-                    let result = saving_throws.check(&saving_throw_kind, world, entity);
-                    let modifiers = &result.modifier_breakdown;
+                    let explanation = saving_throws.explain(&saving_throw_kind, world, entity);
+                    let modifiers = &explanation.modifiers;
                     let total = modifiers.total();
                     ui.text(format!("Bonus: {}{}", sign(total), total.abs()));
                     modifiers.render_with_context(ui, ModifierSetRenderMode::List(1));
+                    for (source, kind) in explanation.advantage_tracker.summary() {
+                        TextSegments::new(vec![
+                            (format!("{:?}", kind), TextKind::Normal),
+                            (format!("({})", source), TextKind::Details),
+                        ])
+                        .render(ui);
+                    }
+                    if let Some(forced_outcome) = &explanation.forced_outcome {
+                        ui.text(forced_outcome.to_string());
+                    }
                 });
             }
         }
@@ -356,10 +497,8 @@ impl ImguiRenderableWithContext<(&World, Entity)> for SkillSet {
                 ui.text(skill.to_string());
                 // Bonus column
                 ui.table_next_column();
-                // TODO: Avoid doing an actual skill check here every time
-                let result = self.check(&skill, world, entity);
-                result
-                    .modifier_breakdown
+                self.explain(&skill, world, entity)
+                    .modifiers
                     .render_with_context(ui, ModifierSetRenderMode::Hoverable);
             }
 
@@ -609,7 +748,7 @@ impl ImguiRenderableWithContext<&TimeMode> for Vec<EffectInstance> {
             for effect in &temporary_effects {
                 // Effect ID column
                 ui.table_next_column();
-                ui.text(effect.effect_id.to_string());
+                ui.text(effect_label(effect));
                 // Source column
                 ui.table_next_column();
                 ui.text(effect.source.to_string());
@@ -625,7 +764,7 @@ impl ImguiRenderableWithContext<&TimeMode> for Vec<EffectInstance> {
             for effect in &permanent_effects {
                 // Effect ID column
                 ui.table_next_column();
-                ui.text(effect.effect_id.to_string());
+                ui.text(effect_label(effect));
                 // Source column
                 ui.table_next_column();
                 ui.text(effect.source.to_string());
@@ -635,6 +774,17 @@ impl ImguiRenderableWithContext<&TimeMode> for Vec<EffectInstance> {
     }
 }
 
+fn effect_label(effect: &EffectInstance) -> String {
+    let mut label = effect.effect_id.to_string();
+    if effect.stacks > 1 {
+        label.push_str(&format!(" x{}", effect.stacks));
+    }
+    if effect.is_suppressed() {
+        label.push_str(" (suppressed)");
+    }
+    label
+}
+
 impl ImguiRenderable for Vec<FeatId> {
     fn render(&self, ui: &imgui::Ui) {
         if let Some(table) = table_with_columns!(ui, "Feats", "Feat") {
@@ -679,6 +829,16 @@ impl ImguiRenderableWithContext<(&World, Entity)> for Weapon {
         let (world, entity) = context;
         render_item_name(ui, self.item());
         self.item().rarity.render(ui);
+        if let Some(coating) = self.coating() {
+            TextSegment::new(
+                format!(
+                    "Coated in {} ({} hits left)",
+                    coating.name, coating.hits_remaining
+                ),
+                TextKind::Green,
+            )
+            .render(ui);
+        }
         let damage_roll = self.damage_roll(
             systems::helpers::get_component::<AbilityScoreMap>(world, entity).deref(),
             systems::helpers::get_component::<Loadout>(world, entity)
@@ -717,6 +877,137 @@ impl ImguiRenderableWithContext<(&World, Entity)> for Armor {
     }
 }
 
+impl ImguiRenderable for SpellItem {
+    fn render(&self, ui: &imgui::Ui) {
+        render_item_name(ui, &self.item);
+        self.item.rarity.render(ui);
+        TextSegments::new(vec![
+            ("Casts".to_string(), TextKind::Details),
+            (self.spell.to_string(), TextKind::Action),
+            (format!("at level {}", self.level), TextKind::Details),
+        ])
+        .render(ui);
+        let charges = if self.charges.consumable {
+            "Consumed on use".to_string()
+        } else {
+            format!("Charges: {}/{}", self.charges.current, self.charges.max)
+        };
+        TextSegment::new(charges, TextKind::Details).render(ui);
+        ui.separator();
+        render_item_misc(ui, &self.item);
+    }
+}
+
+impl ImguiRenderable for ToolItem {
+    fn render(&self, ui: &imgui::Ui) {
+        render_item_name(ui, &self.item);
+        self.item.rarity.render(ui);
+        for action in &self.actions {
+            TextSegments::new(vec![
+                ("Grants".to_string(), TextKind::Details),
+                (action.to_string(), TextKind::Action),
+            ])
+            .render(ui);
+        }
+        TextSegment::new(
+            format!("Uses: {}/{}", self.charges.current, self.charges.max),
+            TextKind::Details,
+        )
+        .render(ui);
+        ui.separator();
+        render_item_misc(ui, &self.item);
+    }
+}
+
+impl ImguiRenderable for SupplyItem {
+    fn render(&self, ui: &imgui::Ui) {
+        render_item_name(ui, &self.item);
+        self.item.rarity.render(ui);
+        for kind in &self.provides {
+            TextSegment::new(format!("Provides {}", kind), TextKind::Details).render(ui);
+        }
+        if self.healing > 0 {
+            TextSegment::new(format!("Heals {} HP", self.healing), TextKind::Healing).render(ui);
+        }
+        TextSegment::new(
+            format!("Days left: {}/{}", self.charges.current, self.charges.max),
+            TextKind::Details,
+        )
+        .render(ui);
+        ui.separator();
+        render_item_misc(ui, &self.item);
+    }
+}
+
+impl ImguiRenderable for LightItem {
+    fn render(&self, ui: &imgui::Ui) {
+        render_item_name(ui, &self.item);
+        self.item.rarity.render(ui);
+        TextSegment::new(
+            format!(
+                "Bright light: {:.1} meters, dim light: {:.1} meters",
+                self.bright_light.get::<meter>(),
+                self.dim_light().get::<meter>()
+            ),
+            TextKind::Details,
+        )
+        .render(ui);
+        if let Some(fuel) = &self.fuel {
+            TextSegment::new(format!("Fuel: {}", fuel), TextKind::Details).render(ui);
+        }
+        let status = if self.lit {
+            "Lit"
+        } else if self.is_burned_out() {
+            "Burned out"
+        } else {
+            "Not lit"
+        };
+        TextSegment::new(
+            format!(
+                "{}, {} of {} left",
+                status,
+                time_duration_text(&self.time_remaining()),
+                time_duration_text(&self.burn_time)
+            ),
+            TextKind::Details,
+        )
+        .render(ui);
+        ui.separator();
+        render_item_misc(ui, &self.item);
+    }
+}
+
+impl ImguiRenderable for CoatingItem {
+    fn render(&self, ui: &imgui::Ui) {
+        render_item_name(ui, &self.item);
+        self.item.rarity.render(ui);
+        for (dice, damage_type) in &self.coating.damage {
+            TextSegment::new(
+                format!("{} {}", dice, damage_type),
+                TextKind::Damage(*damage_type),
+            )
+            .render(ui);
+        }
+        for effect in &self.coating.effects {
+            TextSegment::new(effect.effect_id.to_string(), TextKind::Effect).render(ui);
+        }
+        if let Some(save) = &self.coating.save {
+            TextSegment::new(
+                format!("DC {} {} saving throw", save.dc, save.saving_throw),
+                TextKind::Details,
+            )
+            .render(ui);
+        }
+        TextSegment::new(
+            format!("Lasts for {} hits", self.coating.hits),
+            TextKind::Details,
+        )
+        .render(ui);
+        ui.separator();
+        render_item_misc(ui, &self.item);
+    }
+}
+
 impl ImguiRenderable for ArmorDexterityBonus {
     fn render(&self, ui: &imgui::Ui) {
         if self != &ArmorDexterityBonus::Unlimited {
@@ -881,14 +1172,34 @@ impl ImguiRenderable for D20CheckResult {
             self.modifier_breakdown
                 .render_with_context(ui, ModifierSetRenderMode::Line);
         }
+        for (source, roll) in &self.dice_rolls {
+            ui.same_line();
+            TextSegments::new(vec![
+                (
+                    format!("{} {}", sign(roll.value()), roll.value().abs()),
+                    TextKind::Normal,
+                ),
+                (format!("({}, {})", roll.modifier, source), TextKind::Details),
+            ])
+            .render(ui);
+        }
         ui.same_line();
         ui.text(format!("= {}", self.total()));
+        if let Some(forced_outcome) = &self.forced_outcome {
+            ui.same_line();
+            TextSegment::new(format!("({})", forced_outcome), TextKind::Details).render(ui);
+        }
     }
 }
 
 impl ImguiRenderable for AttackRollResult {
     fn render(&self, ui: &imgui::Ui) {
         self.roll_result.render(ui);
+        if let Some(source) = &self.crit_on_hit {
+            ui.same_line();
+            TextSegment::new(format!("(Critical on Hit: {})", source), TextKind::Details)
+                .render(ui);
+        }
     }
 }
 
@@ -928,11 +1239,15 @@ impl ImguiRenderable for DamageComponentMitigation {
 impl ImguiRenderableWithContext<(&World, u8)> for ActionResult {
     fn render_with_context(&self, ui: &imgui::Ui, (world, indent_level): (&World, u8)) {
         let target_name = match &self.target {
-            TargetInstance::Entity(entity) => {
+            TargetInstance::Entity(entity)
+            | TargetInstance::Object(entity)
+            | TargetInstance::Item { owner: entity, .. } => {
                 let character_name = systems::helpers::get_component::<Name>(world, *entity);
                 character_name.as_str().to_string()
             }
-            TargetInstance::Point(point) => todo!(),
+            TargetInstance::Point(point) => {
+                format!("point ({:.1}, {:.1}, {:.1})", point.x, point.y, point.z)
+            }
         };
 
         match &self.kind {
@@ -961,73 +1276,31 @@ impl ImguiRenderableWithContext<(&World, u8)> for ActionResult {
 
                     if ui.is_item_hovered() {
                         ui.tooltip(|| {
-                            match damage.kind {
-                                DamageResolutionKind::Unconditional => {
-                                    (&damage.damage_roll, &damage.damage_taken)
-                                        .render(ui);
-                                }
-
-                                DamageResolutionKind::AttackRoll {
-                                    ref attack_roll,
-                                    ref armor_class,
-                                } => {
-                                    TextSegment::new(
-                                        format!("{}'s", target_name),
-                                        TextKind::Target,
-                                    )
-                                    .render(ui);
-                                    ui.same_line();
-                                    ui.text("Armor Class:");
-                                    ui.same_line();
-                                    armor_class.render(ui);
-
-                                    ui.text("");
-                                    ui.text("Attack Roll:");
-                                    ui.same_line();
-                                    attack_roll.render(ui);
-
-                                    if let Some(damage_taken) = &damage.damage_taken {
-                                        ui.text("");
-                                        ui.text("Damage Roll:");
-                                        ui.same_line();
-                                        damage.damage_roll.as_ref().unwrap().render(ui);
-
-                                        ui.text("");
-                                        ui.text("Damage Taken:");
-                                        ui.same_line();
-                                        damage_taken.render(ui);
-                                    } else {
-                                        ui.text(format!("Attack did not hit. Attack roll ({}) was less than Armor Class ({})", 
-                                            attack_roll.roll_result.total(), armor_class.total()));
-                                    }
-                                }
+                            render_damage_outcome_breakdown(ui, &target_name, damage);
+                        });
+                    }
+                } else if let Some(saving_throw) = &action_outcome.saving_throw {
+                    // Saves without damage are only shown here, otherwise
+                    // they're part of the damage breakdown
+                    let outcome = if saving_throw.success {
+                        "succeeded on"
+                    } else {
+                        "failed"
+                    };
+                    TextSegments::new(vec![
+                        (target_name.as_str(), TextKind::Target),
+                        (outcome, TextKind::Normal),
+                        (
+                            &format!("a {} saving throw", saving_throw.dc.key),
+                            TextKind::Normal,
+                        ),
+                    ])
+                    .with_indent(indent_level + 1)
+                    .render(ui);
 
-                                DamageResolutionKind::SavingThrow {
-                                    ref saving_throw_dc,
-                                    ref saving_throw_result,
-                                } => {
-                                    ui.text("Saving Throw DC:");
-                                    ui.same_line();
-                                    saving_throw_dc.render(ui);
-
-                                    ui.text("");
-                                    ui.text("Saving Throw:");
-                                    ui.same_line();
-                                    saving_throw_result.render(ui);
-
-                                    ui.same_line();
-                                    let label = if saving_throw_result.is_success(saving_throw_dc) {
-                                        "(Success)" } else {
-                                            "(Failure)"
-                                        };
-                                    TextSegment::new(label, TextKind::Details)
-                                        .render(ui);
-                                    
-                                    ui.text("");
-                                    (&damage.damage_roll, &damage.damage_taken)
-                                        .render(ui);
-                                }
-                            }
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            render_saving_throw_breakdown(ui, saving_throw);
                         });
                     }
                 }
@@ -1037,7 +1310,10 @@ impl ImguiRenderableWithContext<(&World, u8)> for ActionResult {
                         TextSegments::new(vec![
                             (target_name.as_str(), TextKind::Target),
                             ("was healed for", TextKind::Normal),
-                            (&format!("{} HP", healing.healing.subtotal), TextKind::Healing),
+                            (
+                                &format!("{} HP", healing.received.amount),
+                                TextKind::Healing,
+                            ),
                         ])
                         .with_indent(indent_level + 1)
                         .render(ui);
@@ -1055,16 +1331,117 @@ impl ImguiRenderableWithContext<(&World, u8)> for ActionResult {
                         ui.tooltip(|| {
                             ui.text("Healing:");
                             ui.same_line();
-                            TextSegment::new(
-                                &format!("{} HP", healing.healing),
-                                TextKind::Healing,
-                            ).render(ui);
+                            TextSegment::new(&format!("{} HP", healing.healing), TextKind::Healing)
+                                .render(ui);
+                            if !healing.received.modifiers.is_empty() {
+                                ui.text("Received:");
+                                ui.same_line();
+                                TextSegment::new(
+                                    &format!("{} HP", healing.received),
+                                    TextKind::Healing,
+                                )
+                                .render(ui);
+                            }
                         });
                     }
                 }
 
+                action_outcome.stabilized.render_with_context(
+                    ui,
+                    (
+                        &target_name,
+                        Some(self.performer.name().as_str()),
+                        indent_level + 1,
+                    ),
+                );
+
+                action_outcome.revived.render_with_context(
+                    ui,
+                    (
+                        &target_name,
+                        Some(self.performer.name().as_str()),
+                        indent_level + 1,
+                    ),
+                );
+
+                for effect in &action_outcome.cured {
+                    TextSegments::new(vec![
+                        (target_name.as_str(), TextKind::Target),
+                        ("is no longer affected by", TextKind::Normal),
+                        (&effect.to_string(), TextKind::Effect),
+                    ])
+                    .with_indent(indent_level + 1)
+                    .render(ui);
+                }
+
+                for item_id in &action_outcome.conjured {
+                    let Some(item) = ItemsRegistry::get(item_id) else {
+                        continue;
+                    };
+                    TextSegments::new(vec![
+                        (target_name.as_str(), TextKind::Target),
+                        ("receives", TextKind::Normal),
+                        (
+                            &item.item().name,
+                            TextKind::Item(item.item().rarity.clone()),
+                        ),
+                    ])
+                    .with_indent(indent_level + 1)
+                    .render(ui);
+                }
+
+                if let Some(item) = action_outcome
+                    .disarmed
+                    .as_ref()
+                    .and_then(|item_id| ItemsRegistry::get(item_id))
+                {
+                    TextSegments::new(vec![
+                        (target_name.as_str(), TextKind::Target),
+                        ("drops", TextKind::Normal),
+                        (
+                            &item.item().name,
+                            TextKind::Item(item.item().rarity.clone()),
+                        ),
+                    ])
+                    .with_indent(indent_level + 1)
+                    .render(ui);
+                }
+
+                if let Some(sundered) = &action_outcome.sundered
+                    && let Some(item) = ItemsRegistry::get(&sundered.item)
+                {
+                    let result = if sundered.broken {
+                        "which breaks".to_string()
+                    } else {
+                        format!("({} HP left)", sundered.remaining_hit_points)
+                    };
+                    TextSegments::new(vec![
+                        (&format!("{} damage to", sundered.damage), TextKind::Normal),
+                        (
+                            &item.item().name,
+                            TextKind::Item(item.item().rarity.clone()),
+                        ),
+                        (&result, TextKind::Details),
+                    ])
+                    .with_indent(indent_level + 1)
+                    .render(ui);
+                }
+
                 if let Some(effect) = &action_outcome.effect {
                     if !effect.applied {
+                        if action_outcome
+                            .saving_throw
+                            .as_ref()
+                            .is_some_and(|saving_throw| saving_throw.success)
+                        {
+                            TextSegments::new(vec![
+                                (target_name.as_str(), TextKind::Target),
+                                ("resisted", TextKind::Normal),
+                                (&effect.effect.to_string(), TextKind::Effect),
+                            ])
+                            .with_indent(indent_level + 1)
+                            .render(ui);
+                        }
                         return;
                     }
 
@@ -1078,16 +1455,39 @@ impl ImguiRenderableWithContext<(&World, u8)> for ActionResult {
                 }
             }
 
-            ActionKindResult::Utility => todo!(),
+            ActionKindResult::Utility(outcome) => {
+                TextSegments::new(utility_outcome_text(&target_name, outcome))
+                    .with_indent(indent_level + 1)
+                    .render(ui);
+            }
 
-            ActionKindResult::Composite { actions } => todo!(),
+            ActionKindResult::Composite { actions } => {
+                for action in actions {
+                    // Each step reads like the result of a standalone action,
+                    // with nested composite actions indented further
+                    let indent_level = match action {
+                        ActionKindResult::Composite { .. } => indent_level + 1,
+                        _ => indent_level,
+                    };
+                    ActionResult {
+                        performer: self.performer.clone(),
+                        target: self.target.clone(),
+                        point: self.point,
+                        kind: action.clone(),
+                    }
+                    .render_with_context(ui, (world, indent_level));
+                }
+            }
 
-            ActionKindResult::Custom {} => todo!(),
+            ActionKindResult::Custom(custom) => {
+                render_custom_result(ui, world, self, custom, &target_name, indent_level);
+            }
 
             ActionKindResult::Reaction { result } => match result {
-                ReactionResult::ModifyEvent { modification } => {
-                    // TODO: No idea how to render this yet
-                    return;
+                ReactionResult::ModifyEvent { delta, .. } => {
+                    TextSegments::new(modification_delta_text(delta))
+                        .with_indent(indent_level + 1)
+                        .render(ui);
                 }
 
                 ReactionResult::CancelEvent {
@@ -1109,6 +1509,90 @@ impl ImguiRenderableWithContext<(&World, u8)> for ActionResult {
     }
 }
 
+/// Full breakdown of how damage was resolved against a target, i.e. the attack
+/// roll or saving throw along with the damage roll and mitigation.
+pub fn render_damage_outcome_breakdown(
+    ui: &imgui::Ui,
+    target_name: &str,
+    damage: &DamageOutcome,
+) {
+    match damage.kind {
+        DamageResolutionKind::Unconditional => {
+            (&damage.damage_roll, &damage.damage_taken).render(ui);
+        }
+
+        DamageResolutionKind::AttackRoll {
+            ref attack_roll,
+            ref armor_class,
+        } => {
+            TextSegment::new(format!("{}'s", target_name), TextKind::Target).render(ui);
+            ui.same_line();
+            ui.text("Armor Class:");
+            ui.same_line();
+            armor_class.render(ui);
+
+            ui.text("");
+            ui.text("Attack Roll:");
+            ui.same_line();
+            attack_roll.render(ui);
+
+            if let Some(damage_taken) = &damage.damage_taken {
+                ui.text("");
+                ui.text("Damage Roll:");
+                ui.same_line();
+                damage.damage_roll.as_ref().unwrap().render(ui);
+
+                ui.text("");
+                ui.text("Damage Taken:");
+                ui.same_line();
+                damage_taken.render(ui);
+            } else {
+                ui.text(format!(
+                    "Attack did not hit. Attack roll ({}) was less than Armor Class ({})",
+                    attack_roll.roll_result.total(),
+                    armor_class.total()
+                ));
+            }
+        }
+
+        DamageResolutionKind::SavingThrow {
+            ref saving_throw_dc,
+            ref saving_throw_result,
+        } => {
+            render_saving_throw_breakdown(
+                ui,
+                &SavingThrowOutcome {
+                    dc: saving_throw_dc.clone(),
+                    result: saving_throw_result.clone(),
+                    success: saving_throw_result.is_success(saving_throw_dc),
+                },
+            );
+
+            ui.text("");
+            (&damage.damage_roll, &damage.damage_taken).render(ui);
+        }
+    }
+}
+
+pub fn render_saving_throw_breakdown(ui: &imgui::Ui, saving_throw: &SavingThrowOutcome) {
+    ui.text("Saving Throw DC:");
+    ui.same_line();
+    saving_throw.dc.render(ui);
+
+    ui.text("");
+    ui.text("Saving Throw:");
+    ui.same_line();
+    saving_throw.result.render(ui);
+
+    ui.same_line();
+    let label = if saving_throw.success {
+        "(Success)"
+    } else {
+        "(Failure)"
+    };
+    TextSegment::new(label, TextKind::Details).render(ui);
+}
+
 impl ImguiRenderableWithContext<(&str, u8, &Option<AttackRollResult>)>
     for DamageComponentMitigation
 {
@@ -1184,6 +1668,39 @@ impl ImguiRenderable for DamageMitigationResult {
     }
 }
 
+pub fn utility_outcome_text(target: &str, outcome: &UtilityOutcome) -> Vec<(String, TextKind)> {
+    let what = match outcome {
+        UtilityOutcome::Unlocked(_) => "was unlocked".to_string(),
+        UtilityOutcome::StillLocked => "is still locked".to_string(),
+        UtilityOutcome::LeverPulled { active } => {
+            format!("was switched {}", if *active { "on" } else { "off" })
+        }
+        UtilityOutcome::Summoned(form) => match MonstersRegistry::get(form) {
+            Some(template) => format!("was joined by {}", template.name.as_str()),
+            None => format!("was joined by {}", form),
+        },
+        UtilityOutcome::Shapechanged(form) => match MonstersRegistry::get(form) {
+            Some(template) => format!("took the form of {}", template.name.as_str()),
+            None => format!("took the form of {}", form),
+        },
+        UtilityOutcome::NoEffect => "was not affected".to_string(),
+    };
+    vec![
+        (target.to_string(), TextKind::Target),
+        (what, TextKind::Normal),
+    ]
+}
+
+/// e.g. "AC 15 → 20" when Shield is cast against an attack
+pub fn modification_delta_text(delta: &ModificationDelta) -> Vec<(String, TextKind)> {
+    vec![
+        (delta.value.to_string(), TextKind::Normal),
+        (delta.before.to_string(), TextKind::Details),
+        ("→".to_string(), TextKind::Normal),
+        (delta.after.to_string(), TextKind::Details),
+    ]
+}
+
 pub fn new_life_state_text(
     entity: &str,
     new_state: &LifeState,
@@ -1267,6 +1784,29 @@ pub fn new_life_state_text(
     }
 }
 
+pub fn massive_damage_text(
+    entity: &str,
+    outcome: &MassiveDamageOutcome,
+) -> Vec<(String, TextKind)> {
+    let entity_component = (entity.to_string(), TextKind::Target);
+
+    match outcome {
+        MassiveDamageOutcome::InstantDeath => vec![
+            entity_component,
+            ("was killed outright by massive damage".to_string(), TextKind::Normal),
+        ],
+        MassiveDamageOutcome::SystemShockResisted => vec![
+            entity_component,
+            ("resisted the system shock".to_string(), TextKind::Normal),
+        ],
+        MassiveDamageOutcome::SystemShock { roll, result } => vec![
+            entity_component,
+            (format!("went into system shock ({}):", roll), TextKind::Normal),
+            (result.to_string(), TextKind::Details),
+        ],
+    }
+}
+
 impl ImguiRenderableWithContext<(&str, Option<&str>, u8)> for Option<LifeState> {
     // This is used to render a LifeState which is being transitioned to
     fn render_with_context(&self, ui: &imgui::Ui, context: (&str, Option<&str>, u8)) {
@@ -1421,6 +1961,16 @@ impl ImguiRenderable for DamageMitigationEffect {
         if ui.is_item_hovered() {
             ui.tooltip(|| {
                 TextSegment::new(format!("{}", self.source), TextKind::Details).render(ui);
+                if !self.bypassed_by.is_empty() {
+                    let tags = self
+                        .bypassed_by
+                        .iter()
+                        .map(|tag| tag.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    TextSegment::new(format!("Bypassed by: {}", tags), TextKind::Details)
+                        .render(ui);
+                }
             });
         }
     }
@@ -1436,6 +1986,24 @@ impl ImguiRenderableWithContext<&TimeMode> for EffectLifetime {
                 ui.same_line();
                 duration.render_with_context(ui, time_mode);
             }
+            EffectLifetime::UntilNextTurn {
+                boundary,
+                remaining,
+                ..
+            } => match time_mode {
+                TimeMode::TurnBased { .. } => {
+                    let boundary = match boundary {
+                        TurnBoundary::Start => "start",
+                        TurnBoundary::End => "end",
+                    };
+                    TextSegment::new(
+                        format!("Until {} of next turn", boundary),
+                        TextKind::Details,
+                    )
+                    .render(ui);
+                }
+                _ => remaining.render_with_context(ui, time_mode),
+            },
             // TODO: Does it make sense to render the other durations?
             _ => {}
         }
@@ -1463,6 +2031,21 @@ impl ImguiRenderableWithContext<&TimeMode> for TimeDuration {
     }
 }
 
+/// The duration in the largest unit it spans, e.g. "10 days"
+fn time_duration_text(duration: &TimeDuration) -> String {
+    let seconds = duration.as_seconds() as u32;
+    let (amount, unit) = [(86_400, "day"), (3_600, "hour"), (60, "minute")]
+        .into_iter()
+        .find(|(unit_seconds, _)| seconds >= *unit_seconds)
+        .map(|(unit_seconds, unit)| (seconds / unit_seconds, unit))
+        .unwrap_or((seconds, "second"));
+    if amount == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", amount, unit)
+    }
+}
+
 fn render_seconds(ui: &imgui::Ui, seconds: &f32) {
     let seconds = *seconds as u32;
     let minutes = seconds / 60;
@@ -1540,7 +2123,13 @@ impl ImguiRenderableWithContext<(&World, Entity, &ActionContext)> for ActionKind
         match self {
             ActionKind::Standard { payload, .. } => {
                 if let Some(damage) = &payload.damage() {
-                    damage(world, entity, action_context).render(ui);
+                    damage(
+                        world,
+                        entity,
+                        action_context,
+                        &TargetResolution::new(entity),
+                    )
+                    .render(ui);
                 }
 
                 if let Some(healing) = &payload.healing() {
@@ -1556,11 +2145,27 @@ impl ImguiRenderableWithContext<(&World, Entity, &ActionContext)> for ActionKind
                 if let Some(effect) = &payload.effect() {
                     TextSegment::new(format!("{}", effect.effect_id), TextKind::Effect).render(ui);
                 }
+
+                if payload.stabilize() {
+                    TextSegment::new("Stabilizes a dying creature", TextKind::Healing).render(ui);
+                }
+
+                if let Some(resurrection) = payload.revive() {
+                    TextSegment::new(
+                        format!(
+                            "Revives a creature that died within {}",
+                            time_duration_text(&resurrection.time_limit)
+                        ),
+                        TextKind::Healing,
+                    )
+                    .render(ui);
+                }
             }
 
             ActionKind::Composite { actions } => {
-                for action in actions {
-                    action.render_with_context(ui, (world, entity, action_context));
+                for step in actions {
+                    step.kind
+                        .render_with_context(ui, (world, entity, action_context));
                 }
             }
 
@@ -1580,6 +2185,8 @@ impl ImguiRenderable for ResourceAmountMap {
             let amount_text = match amount {
                 ResourceAmount::Flat(amount) => amount.to_string(),
                 ResourceAmount::Tiered { tier, amount } => format!("{} Level {}", amount, tier),
+                ResourceAmount::Variable { min, max } if *max == u8::MAX => format!("{}+", min),
+                ResourceAmount::Variable { min, max } => format!("{}-{}", min, max),
             };
             ui.text(format!("{} {}", amount_text, resource));
         }
@@ -1626,17 +2233,47 @@ impl ImguiRenderableWithContext<(&World, Entity)>
                             TextSegment::new("Attack Roll", TextKind::Details).render(ui);
                         }
                         ActionCondition::SavingThrow { saving_throw, .. } => {
-                            let saving_throw = saving_throw(world, entity, &context);
-                            let saving_throw_ability = match saving_throw.key {
-                                SavingThrowKind::Ability(ability) => ability,
-                                _ => todo!()
+                            let saving_throw = saving_throw(
+                                world,
+                                entity,
+                                &context,
+                                &TargetResolution::new(entity),
+                            );
+                            // Death and Concentration saves aren't tied to an ability
+                            let text_kind = match saving_throw.key {
+                                SavingThrowKind::Ability(_) => TextKind::Ability,
+                                SavingThrowKind::Death | SavingThrowKind::Concentration => {
+                                    TextKind::Details
+                                }
                             };
                             TextSegments::new(vec![
-                                (saving_throw_ability.to_string(), TextKind::Ability),
+                                (saving_throw.key.to_string(), text_kind),
                                 ("Saving Throw".to_string(), TextKind::Details),
                             ])
                             .render(ui);
                         }
+                        ActionCondition::SkillCheck { skill_check } => {
+                            let skill_check = skill_check(world, entity, &context);
+                            TextSegments::new(vec![
+                                (skill_check.key.to_string(), TextKind::Skill),
+                                (format!("Check (DC {})", skill_check.dc.total()), TextKind::Details),
+                            ])
+                            .render(ui);
+                        }
+                        ActionCondition::Contest { contest } => {
+                            let opposed_by = contest
+                                .opposed_by
+                                .iter()
+                                .map(|skill| skill.to_string())
+                                .collect::<Vec<_>>()
+                                .join(" or ");
+                            TextSegments::new(vec![
+                                (contest.skill.to_string(), TextKind::Skill),
+                                ("Check contested by".to_string(), TextKind::Details),
+                                (opposed_by, TextKind::Skill),
+                            ])
+                            .render(ui);
+                        }
                         _ => {}
                     },
                     _ => {}
@@ -1647,6 +2284,19 @@ impl ImguiRenderableWithContext<(&World, Entity)>
                     for flag in spell.flags() {
                         TextSegment::new(format!("{:?}", flag), TextKind::Details).render(ui);
                     }
+                    if let Some(material) = spell.material() {
+                        let consumed = match material.consumed {
+                            MaterialConsumption::Never => "",
+                            MaterialConsumption::OnCast => ", consumed",
+                            MaterialConsumption::OnSuccess => ", consumed on success",
+                        };
+                        TextSegment::new(
+                            format!("Material: {} ({}{})", material.description, material.cost, consumed),
+                            TextKind::Details,
+                        )
+                        .wrap_text(true)
+                        .render(ui);
+                    }
                 }
 
                 ui.separator();
@@ -1746,3 +2396,32 @@ impl ImguiRenderable for TargetingKind {
         TextSegment::new(text, TextKind::Details).render(ui);
     }
 }
+
+impl ImguiRenderable for WhatIfReport {
+    fn render(&self, ui: &imgui::Ui) {
+        let changes = self.changes();
+        if changes.is_empty() {
+            TextSegment::new("No changes", TextKind::Details).render(ui);
+            return;
+        }
+        for change in changes {
+            let delta = change.delta();
+            TextSegments::new(vec![
+                (change.stat.to_string(), TextKind::Normal),
+                (
+                    format!("{} -> {}", change.before, change.after),
+                    TextKind::Details,
+                ),
+                (
+                    format!("({}{})", if delta >= 0.0 { "+" } else { "-" }, delta.abs()),
+                    if delta >= 0.0 {
+                        TextKind::Green
+                    } else {
+                        TextKind::Red
+                    },
+                ),
+            ])
+            .render(ui);
+        }
+    }
+}
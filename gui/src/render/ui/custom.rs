@@ -0,0 +1,54 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
+
+use hecs::World;
+use nat20_core::components::{
+    actions::{action::ActionResult, custom::CustomActionResult},
+    id::CustomActionId,
+};
+
+use crate::render::ui::{
+    text::{TextKind, TextSegments},
+    utils::ImguiRenderable,
+};
+
+/// Renders the result of a custom action on its target at the given indent
+/// level, e.g. to show the details stored in the payload of the result
+pub type CustomResultRenderer = fn(&imgui::Ui, &World, &ActionResult, &CustomActionResult, u8);
+
+static RENDERERS: LazyLock<RwLock<HashMap<CustomActionId, CustomResultRenderer>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Use the renderer for the results of the custom action instead of the summary
+/// the action provides
+pub fn register_renderer(action: CustomActionId, renderer: CustomResultRenderer) {
+    RENDERERS.write().unwrap().insert(action, renderer);
+}
+
+pub fn render_custom_result(
+    ui: &imgui::Ui,
+    world: &World,
+    result: &ActionResult,
+    custom: &CustomActionResult,
+    target_name: &str,
+    indent_level: u8,
+) {
+    let renderer = RENDERERS.read().unwrap().get(&custom.action).copied();
+    if let Some(renderer) = renderer {
+        renderer(ui, world, result, custom, indent_level);
+        return;
+    }
+
+    TextSegments::new(custom_result_text(target_name, custom))
+        .with_indent(indent_level + 1)
+        .render(ui);
+}
+
+pub fn custom_result_text(target: &str, custom: &CustomActionResult) -> Vec<(String, TextKind)> {
+    vec![
+        (target.to_string(), TextKind::Target),
+        (custom.summary.clone(), TextKind::Normal),
+    ]
+}
@@ -1,9 +1,20 @@
 use chrono::format;
-use hecs::World;
+use hecs::{Entity, World};
 use imgui::TreeNodeFlags;
 use nat20_core::{
-    components::{actions::targeting::TargetInstance, id::Name},
+    components::{
+        actions::{
+            action::{ActionKindResult, ActionOutcomeBundle, DamageResolutionKind, ReactionResult},
+            targeting::TargetInstance,
+        },
+        hidden::{HiddenFeature, HiddenFeatureKind},
+        id::{ItemId, Name},
+        items::{inventory::ItemContainer, item::ItemRarity},
+        lock::UnlockMethod,
+        modifier::Modifiable,
+    },
     engine::event::{ActionData, EncounterEvent, Event, EventKind, EventLog},
+    registry::registry::ItemsRegistry,
     systems::{
         self,
         d20::{D20CheckDCKind, D20ResultKind},
@@ -12,7 +23,11 @@ use nat20_core::{
 use strum::{Display, EnumIter};
 
 use crate::render::ui::{
-    components::new_life_state_text,
+    components::{
+        massive_damage_text, modification_delta_text, new_life_state_text,
+        render_damage_outcome_breakdown, utility_outcome_text,
+    },
+    custom::custom_result_text,
     text::{TextKind, TextSegment, TextSegments},
     utils::{ImguiRenderable, ImguiRenderableWithContext},
 };
@@ -41,15 +56,163 @@ pub fn event_log_level(event: &Event) -> LogLevel {
         EventKind::ActionPerformed { .. } => LogLevel::Info,
         EventKind::ReactionTriggered { .. } => LogLevel::Info,
         EventKind::LifeStateChanged { .. } => LogLevel::Info,
+        EventKind::MassiveDamage { .. } => LogLevel::Info,
         EventKind::D20CheckPerformed(_, result_kind, _)
         | EventKind::D20CheckResolved(_, result_kind, _) => match result_kind {
             D20ResultKind::SavingThrow { .. } | D20ResultKind::Skill { .. } => LogLevel::Info,
             systems::d20::D20ResultKind::AttackRoll { .. } => LogLevel::Debug,
         },
-        EventKind::DamageRollPerformed(_, _) => LogLevel::Debug,
+        EventKind::DamageRollPerformed(_, _, _) => LogLevel::Debug,
         EventKind::DamageRollResolved(_, _) => LogLevel::Debug,
         EventKind::RestStarted { .. } => LogLevel::Info,
         EventKind::RestFinished { .. } => LogLevel::Info,
+        EventKind::RestInterrupted { .. } => LogLevel::Info,
+        EventKind::LockOpened { .. } => LogLevel::Info,
+        EventKind::HiddenFeatureRevealed { .. } => LogLevel::Info,
+        EventKind::IllusionDisbelieved { .. } => LogLevel::Info,
+        EventKind::ItemDropped { .. } | EventKind::ItemPickedUp { .. } => LogLevel::Info,
+    }
+}
+
+/// Filters for which events are shown in the combat log
+#[derive(Debug, Clone, Default)]
+pub struct CombatLogFilter {
+    /// Only show events where damage was rolled or dealt
+    pub damage_only: bool,
+    /// Only show events where this entity is the actor or a target
+    pub entity: Option<Entity>,
+    /// Also show the individual attack and damage rolls, which are otherwise
+    /// only visible at the 'Debug' log level
+    pub show_rolls: bool,
+}
+
+impl CombatLogFilter {
+    pub fn matches(&self, event: &Event, log_level: &LogLevel) -> bool {
+        if event_log_level(event) > *log_level && !(self.show_rolls && is_roll_event(event)) {
+            return false;
+        }
+
+        if self.damage_only && !involves_damage(event) {
+            return false;
+        }
+
+        if let Some(entity) = self.entity
+            && !involves_entity(event, entity)
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+pub fn is_roll_event(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::D20CheckPerformed(..)
+            | EventKind::D20CheckResolved(..)
+            | EventKind::DamageRollPerformed(..)
+            | EventKind::DamageRollResolved(..)
+    )
+}
+
+pub fn involves_damage(event: &Event) -> bool {
+    match &event.kind {
+        EventKind::DamageRollPerformed(..) | EventKind::DamageRollResolved(..) => true,
+        EventKind::ActionPerformed { results, .. } => results
+            .iter()
+            .any(|result| action_result_has_damage(&result.kind)),
+        _ => false,
+    }
+}
+
+fn action_result_has_damage(result: &ActionKindResult) -> bool {
+    match result {
+        ActionKindResult::Standard(outcome) => outcome.damage.is_some(),
+        ActionKindResult::Composite { actions } => actions.iter().any(action_result_has_damage),
+        _ => false,
+    }
+}
+
+/// The outcomes of a result, including those of every step of a composite action
+fn standard_outcomes(result: &ActionKindResult) -> Vec<&ActionOutcomeBundle> {
+    match result {
+        ActionKindResult::Standard(outcome) => vec![outcome],
+        ActionKindResult::Composite { actions } => {
+            actions.iter().flat_map(standard_outcomes).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+pub fn involves_entity(event: &Event, entity: Entity) -> bool {
+    if event.actor() == Some(entity) {
+        return true;
+    }
+
+    match &event.kind {
+        EventKind::ActionRequested { action } | EventKind::ActionPerformed { action, .. } => {
+            action.entity_targets().contains(&entity)
+        }
+        EventKind::ReactionTriggered { reactors, .. } => reactors.contains(&entity),
+        EventKind::LifeStateChanged { entity: target, .. }
+        | EventKind::MassiveDamage { entity: target, .. } => *target == entity,
+        EventKind::D20CheckPerformed(_, _, D20CheckDCKind::AttackRoll(target, _))
+        | EventKind::D20CheckResolved(_, _, D20CheckDCKind::AttackRoll(target, _)) => {
+            *target == entity
+        }
+        EventKind::RestStarted { participants, .. }
+        | EventKind::RestFinished { participants, .. }
+        | EventKind::RestInterrupted { participants, .. } => participants.contains(&entity),
+        EventKind::LockOpened { target, .. } => *target == entity,
+        EventKind::HiddenFeatureRevealed { feature, by, .. } => {
+            *feature == entity || *by == Some(entity)
+        }
+        EventKind::IllusionDisbelieved { illusion, by } => *illusion == entity || *by == entity,
+        EventKind::ItemDropped { entity: actor, .. }
+        | EventKind::ItemPickedUp { entity: actor, .. } => *actor == entity,
+        _ => false,
+    }
+}
+
+/// Whether the event has a roll breakdown that can be expanded in the log
+pub fn has_breakdown(event: &Event) -> bool {
+    is_roll_event(event) || involves_damage(event)
+}
+
+/// Renders the full roll breakdown of an event, i.e. what would otherwise
+/// only be visible when hovering over the event.
+pub fn render_event_breakdown(ui: &imgui::Ui, event: &Event, world: &World) {
+    match &event.kind {
+        EventKind::D20CheckPerformed(_, result_kind, dc_kind)
+        | EventKind::D20CheckResolved(_, result_kind, dc_kind) => {
+            ui.text("DC:");
+            ui.same_line();
+            dc_kind.render(ui);
+            ui.text("D20 Check:");
+            ui.same_line();
+            result_kind.render(ui);
+        }
+        EventKind::DamageRollPerformed(_, damage_roll_result, _)
+        | EventKind::DamageRollResolved(_, damage_roll_result) => {
+            damage_roll_result.render(ui);
+        }
+        EventKind::ActionPerformed { results, .. } => {
+            for result in results {
+                let Some(target) = result.target.entity() else {
+                    continue;
+                };
+                let target_name = systems::helpers::get_component::<Name>(world, target);
+                for outcome in standard_outcomes(&result.kind) {
+                    let Some(damage) = &outcome.damage else {
+                        continue;
+                    };
+                    ui.separator_with_text(target_name.as_str());
+                    render_damage_outcome_breakdown(ui, target_name.as_str(), damage);
+                }
+            }
+        }
+        _ => {}
     }
 }
 
@@ -104,7 +267,9 @@ pub fn events_match(event1: &Event, event2: &Event) -> bool {
 
         (EventKind::D20CheckPerformed(e1, _, _), EventKind::D20CheckResolved(e2, _, _)) => e1 == e2,
 
-        (EventKind::DamageRollPerformed(e1, _), EventKind::DamageRollResolved(e2, _)) => e1 == e2,
+        (EventKind::DamageRollPerformed(e1, _, _), EventKind::DamageRollResolved(e2, _)) => {
+            e1 == e2
+        }
 
         _ => false,
     }
@@ -160,6 +325,18 @@ impl ImguiRenderableWithContext<&(&World, &LogLevel)> for Event {
                 EncounterEvent::NewRound(encounter_id, round) => {
                     ui.separator_with_text(format!("Round {}", round));
                 }
+                EncounterEvent::MobAttack(_, mob_attack) => {
+                    TextSegments::new(vec![
+                        (format!("Mob of {}", mob_attack.attackers), TextKind::Actor),
+                        ("attacked".to_string(), TextKind::Normal),
+                        (name_of(world, mob_attack.target), TextKind::Target),
+                        (
+                            format!("({} hits, {} damage)", mob_attack.hits, mob_attack.damage),
+                            TextKind::Details,
+                        ),
+                    ])
+                    .render(ui);
+                }
             },
             EventKind::ActionRequested { action } => {
                 action.render_with_context(ui, world);
@@ -185,19 +362,15 @@ impl ImguiRenderableWithContext<&(&World, &LogLevel)> for Event {
                 if action.targets.len() == 1
                     && action.targets[0] != TargetInstance::Entity(action.actor)
                 {
-                    let target = match &action.targets[0] {
-                        TargetInstance::Entity(entity) => {
-                            systems::helpers::get_component::<Name>(world, *entity).to_string()
-                        }
-                        TargetInstance::Point(point) => {
-                            format!("point ({:.1}, {:.1}, {:.1})", point.x, point.y, point.z)
-                                .to_string()
-                        }
+                    let target = target_plain_text(world, &action.targets[0]);
+                    let preposition = match action.targets[0] {
+                        TargetInstance::Point(_) => "at",
+                        _ => "on",
                     };
 
                     ui.same_line();
                     TextSegments::new(vec![
-                        ("on".to_string(), TextKind::Normal),
+                        (preposition.to_string(), TextKind::Normal),
                         (target, TextKind::Target),
                     ])
                     .render(ui);
@@ -206,6 +379,10 @@ impl ImguiRenderableWithContext<&(&World, &LogLevel)> for Event {
                 for result in results {
                     result.render_with_context(ui, (&world, 0));
                 }
+
+                if results.is_empty() && !action.point_targets().is_empty() {
+                    TextSegment::new("\tNo one was affected", TextKind::Details).render(ui);
+                }
             }
             EventKind::ReactionTriggered {
                 trigger_event,
@@ -238,6 +415,11 @@ impl ImguiRenderableWithContext<&(&World, &LogLevel)> for Event {
                 let segments = new_life_state_text(&entity_name, new_state, actor_name.as_deref());
                 TextSegments::new(segments).render(ui);
             }
+            EventKind::MassiveDamage { entity, outcome } => {
+                let entity_name =
+                    systems::helpers::get_component::<Name>(world, *entity).to_string();
+                TextSegments::new(massive_damage_text(&entity_name, outcome)).render(ui);
+            }
             EventKind::D20CheckResolved(entity, result_kind, dc_kind)
             | EventKind::D20CheckPerformed(entity, result_kind, dc_kind) => {
                 let dc_text_segments = get_dc_description(world, dc_kind);
@@ -278,7 +460,7 @@ impl ImguiRenderableWithContext<&(&World, &LogLevel)> for Event {
                     });
                 }
             }
-            EventKind::DamageRollPerformed(entity, damage_roll_result)
+            EventKind::DamageRollPerformed(entity, damage_roll_result, _)
             | EventKind::DamageRollResolved(entity, damage_roll_result) => {
                 TextSegments::new(vec![
                     (
@@ -328,6 +510,47 @@ impl ImguiRenderableWithContext<&(&World, &LogLevel)> for Event {
                     .collect::<Vec<_>>()
                     .render_with_context(ui, &world);
             }
+            EventKind::RestInterrupted {
+                kind,
+                participants,
+                hours,
+            } => {
+                TextSegments::new(vec![
+                    ("Interrupted".to_string(), TextKind::Normal),
+                    (
+                        format!("{:?} rest after {} hours for", kind, hours),
+                        TextKind::Normal,
+                    ),
+                ])
+                .render(ui);
+
+                ui.same_line();
+
+                participants
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .render_with_context(ui, &world);
+            }
+            EventKind::LockOpened {
+                target,
+                method,
+                actor,
+            } => {
+                TextSegments::new(lock_opened_text(world, *target, method, *actor)).render(ui);
+            }
+            EventKind::HiddenFeatureRevealed { feature, kind, by } => {
+                TextSegments::new(feature_revealed_text(world, *feature, kind, *by)).render(ui);
+            }
+            EventKind::IllusionDisbelieved { illusion, by } => {
+                TextSegments::new(illusion_disbelieved_text(world, *illusion, *by)).render(ui);
+            }
+            EventKind::ItemDropped { entity, item, .. } => {
+                TextSegments::new(item_moved_text(world, *entity, "dropped", item)).render(ui);
+            }
+            EventKind::ItemPickedUp { entity, item } => {
+                TextSegments::new(item_moved_text(world, *entity, "picked up", item)).render(ui);
+            }
         }
 
         group_token.end();
@@ -393,3 +616,472 @@ impl ImguiRenderableWithContext<&World> for ActionData {
         }
     }
 }
+
+/// Joins text segments into a single line of plain text, dropping the colors
+pub fn segments_plain_text(segments: &[(String, TextKind)]) -> String {
+    segments
+        .iter()
+        .map(|(text, _)| text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn name_of(world: &World, entity: Entity) -> String {
+    systems::helpers::get_component::<Name>(world, entity).to_string()
+}
+
+fn names_of(world: &World, entities: impl IntoIterator<Item = Entity>) -> String {
+    entities
+        .into_iter()
+        .map(|entity| name_of(world, entity))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn target_plain_text(world: &World, target: &TargetInstance) -> String {
+    match target {
+        TargetInstance::Entity(entity) | TargetInstance::Object(entity) => name_of(world, *entity),
+        TargetInstance::Point(point) => {
+            format!("point ({:.1}, {:.1}, {:.1})", point.x, point.y, point.z)
+        }
+        TargetInstance::Item { owner, item } => {
+            let item_name = systems::inventory::target_item(world, *owner, item)
+                .map(|item| item.item().name.clone())
+                .unwrap_or_else(|| "item".to_string());
+            format!("{}'s {}", name_of(world, *owner), item_name)
+        }
+    }
+}
+
+fn action_plain_text(world: &World, action: &ActionData, verb: &str) -> String {
+    let mut text = format!(
+        "{} {} {}",
+        name_of(world, action.actor),
+        verb,
+        action.action_id
+    );
+    let self_target =
+        action.targets.len() == 1 && action.targets[0] == TargetInstance::Entity(action.actor);
+    if !self_target && !action.targets.is_empty() {
+        let targets = action
+            .targets
+            .iter()
+            .map(|target| target_plain_text(world, target))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let preposition = if action
+            .targets
+            .iter()
+            .all(|target| matches!(target, TargetInstance::Point(_)))
+        {
+            "at"
+        } else {
+            "on"
+        };
+        text.push_str(&format!(" {} {}", preposition, targets));
+    }
+    text
+}
+
+fn dc_plain_text(world: &World, dc_kind: &D20CheckDCKind) -> String {
+    match dc_kind {
+        D20CheckDCKind::SavingThrow(dc) => {
+            format!("{} saving throw against DC {}", dc.key, dc.dc.total())
+        }
+        D20CheckDCKind::Skill(dc) => format!("{} check against DC {}", dc.key, dc.dc.total()),
+        D20CheckDCKind::AttackRoll(target, armor_class) => format!(
+            "attack roll against {} (Armor Class {})",
+            name_of(world, *target),
+            armor_class.total()
+        ),
+    }
+}
+
+/// Describes the outcome of an action on a single target. Success, failure
+/// and damage types are spelled out, since they are otherwise only conveyed
+/// by color.
+fn outcome_plain_text(performer: &str, target: &str, outcome: &ActionOutcomeBundle) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(damage) = &outcome.damage {
+        match &damage.kind {
+            DamageResolutionKind::Unconditional => {}
+            DamageResolutionKind::AttackRoll {
+                attack_roll,
+                armor_class,
+            } => {
+                let roll = &attack_roll.roll_result;
+                let result = if roll.is_crit && damage.damage_taken.is_some() {
+                    "critical hit"
+                } else if damage.damage_taken.is_some() {
+                    "hit"
+                } else {
+                    "miss"
+                };
+                lines.push(format!(
+                    "Attack roll {} against {}'s Armor Class {}: {}.",
+                    roll.total(),
+                    target,
+                    armor_class.total(),
+                    result
+                ));
+            }
+            DamageResolutionKind::SavingThrow {
+                saving_throw_dc,
+                saving_throw_result,
+            } => {
+                lines.push(format!(
+                    "{} {} a {} saving throw, rolling {} against DC {}.",
+                    target,
+                    if saving_throw_result.is_success(saving_throw_dc) {
+                        "succeeded"
+                    } else {
+                        "failed"
+                    },
+                    saving_throw_dc.key,
+                    saving_throw_result.total(),
+                    saving_throw_dc.dc.total()
+                ));
+            }
+        }
+
+        if let Some(damage_taken) = &damage.damage_taken {
+            let components = damage_taken
+                .components
+                .iter()
+                .map(|component| format!("{} {}", component.after_mods, component.damage_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!(
+                "{} took {} damage ({}).",
+                target, damage_taken.total, components
+            ));
+        }
+
+        if let Some(new_life_state) = &damage.new_life_state {
+            lines.push(format!(
+                "{}.",
+                segments_plain_text(&new_life_state_text(
+                    target,
+                    new_life_state,
+                    Some(performer)
+                ))
+            ));
+        }
+    }
+
+    if let Some(healing) = &outcome.healing {
+        lines.push(format!(
+            "{} regained {} hit points.",
+            target, healing.received.amount
+        ));
+        if let Some(new_life_state) = &healing.new_life_state {
+            lines.push(format!(
+                "{}.",
+                segments_plain_text(&new_life_state_text(
+                    target,
+                    new_life_state,
+                    Some(performer)
+                ))
+            ));
+        }
+    }
+
+    for new_life_state in [&outcome.stabilized, &outcome.revived]
+        .into_iter()
+        .flatten()
+    {
+        lines.push(format!(
+            "{}.",
+            segments_plain_text(&new_life_state_text(
+                target,
+                new_life_state,
+                Some(performer)
+            ))
+        ));
+    }
+
+    if let Some(effect) = &outcome.effect
+        && effect.applied
+    {
+        lines.push(format!("{} is affected by {}.", target, effect.effect));
+    }
+
+    for effect in &outcome.cured {
+        lines.push(format!("{} is no longer affected by {}.", target, effect));
+    }
+
+    for item in &outcome.conjured {
+        lines.push(format!("{} receives {}.", target, item));
+    }
+
+    if let Some(item) = &outcome.disarmed {
+        lines.push(format!("{} drops {}.", target, item));
+    }
+
+    if let Some(sundered) = &outcome.sundered {
+        if sundered.broken {
+            lines.push(format!("{} breaks.", sundered.item));
+        } else {
+            lines.push(format!(
+                "{} takes {} damage ({} HP left).",
+                sundered.item, sundered.damage, sundered.remaining_hit_points
+            ));
+        }
+    }
+
+    lines
+}
+
+fn result_plain_text(
+    performer: &str,
+    target: &str,
+    result: &ActionKindResult,
+    lines: &mut Vec<String>,
+) {
+    match result {
+        ActionKindResult::Standard(outcome) => {
+            lines.extend(outcome_plain_text(performer, target, outcome));
+        }
+        ActionKindResult::Composite { actions } => {
+            for action in actions {
+                result_plain_text(performer, target, action, lines);
+            }
+        }
+        ActionKindResult::Utility(outcome) => {
+            lines.push(format!(
+                "{}.",
+                segments_plain_text(&utility_outcome_text(target, outcome))
+            ));
+        }
+        ActionKindResult::Custom(custom) => {
+            lines.push(format!(
+                "{}.",
+                segments_plain_text(&custom_result_text(target, custom))
+            ));
+        }
+        ActionKindResult::Reaction {
+            result: ReactionResult::ModifyEvent { delta, .. },
+        } => {
+            lines.push(format!(
+                "{}.",
+                segments_plain_text(&modification_delta_text(delta))
+            ));
+        }
+        ActionKindResult::Reaction { .. } => {}
+    }
+}
+
+/// Describes an event as plain, linear text without relying on color, e.g.
+/// for the accessible log or a screen reader. Returns `None` for events which
+/// are too noisy to announce on their own, like the individual dice rolls
+/// that are already covered by the action they belong to.
+pub fn event_plain_text(world: &World, event: &Event) -> Option<String> {
+    let text = match &event.kind {
+        EventKind::Encounter(encounter_event) => match encounter_event {
+            EncounterEvent::EncounterStarted(encounter_id) => {
+                format!("Encounter {} started.", encounter_id)
+            }
+            EncounterEvent::EncounterEnded(encounter_id, _) => {
+                format!("Encounter {} ended.", encounter_id)
+            }
+            EncounterEvent::NewRound(_, round) => format!("Round {} begins.", round),
+            EncounterEvent::MobAttack(_, mob_attack) => format!(
+                "A mob of {} attacked {}: {} hits for {} damage.",
+                mob_attack.attackers,
+                name_of(world, mob_attack.target),
+                mob_attack.hits,
+                mob_attack.damage
+            ),
+        },
+        // Only announced once the action is performed
+        EventKind::ActionRequested { .. } => return None,
+        EventKind::ReactionRequested { reaction } => format!(
+            "{}, as a response to {}.",
+            action_plain_text(world, &ActionData::from(reaction), "can react with"),
+            reaction.event.actor().map_or_else(
+                || reaction.event.kind.name().to_string(),
+                |actor| { format!("{}'s {}", name_of(world, actor), reaction.event.kind.name()) }
+            )
+        ),
+        EventKind::ActionPerformed { action, results } => {
+            let performer = name_of(world, action.actor);
+            let mut lines = vec![format!("{}.", action_plain_text(world, action, "used"))];
+            for result in results {
+                let target = target_plain_text(world, &result.target);
+                result_plain_text(&performer, &target, &result.kind, &mut lines);
+            }
+            if results.is_empty() && !action.point_targets().is_empty() {
+                lines.push("No one was affected.".to_string());
+            }
+            lines.join(" ")
+        }
+        EventKind::ReactionTriggered { reactors, .. } => format!(
+            "Reaction available for {}.",
+            names_of(world, reactors.iter().cloned())
+        ),
+        EventKind::LifeStateChanged {
+            entity,
+            new_state,
+            actor,
+        } => {
+            let entity_name = name_of(world, *entity);
+            let actor_name = actor.map(|actor| name_of(world, actor));
+            format!(
+                "{}.",
+                segments_plain_text(&new_life_state_text(
+                    &entity_name,
+                    new_state,
+                    actor_name.as_deref()
+                ))
+            )
+        }
+        EventKind::MassiveDamage { entity, outcome } => format!(
+            "{}.",
+            segments_plain_text(&massive_damage_text(&name_of(world, *entity), outcome))
+        ),
+        // Attack rolls are described as part of the action
+        EventKind::D20CheckResolved(_, _, D20CheckDCKind::AttackRoll(..)) => return None,
+        EventKind::D20CheckResolved(entity, result_kind, dc_kind) => format!(
+            "{} {} a {}, rolling {}.",
+            name_of(world, *entity),
+            if result_kind.is_success(dc_kind) {
+                "succeeded"
+            } else {
+                "failed"
+            },
+            dc_plain_text(world, dc_kind),
+            result_kind.d20_result().total()
+        ),
+        EventKind::D20CheckPerformed(..)
+        | EventKind::DamageRollPerformed(..)
+        | EventKind::DamageRollResolved(..) => return None,
+        EventKind::RestStarted { kind, participants } => format!(
+            "{:?} rest started for {}.",
+            kind,
+            names_of(world, participants.iter().cloned())
+        ),
+        EventKind::RestFinished { kind, participants } => format!(
+            "{:?} rest finished for {}.",
+            kind,
+            names_of(world, participants.iter().cloned())
+        ),
+        EventKind::RestInterrupted {
+            kind,
+            participants,
+            hours,
+        } => format!(
+            "{:?} rest interrupted after {} hours for {}.",
+            kind,
+            hours,
+            names_of(world, participants.iter().cloned())
+        ),
+        EventKind::LockOpened {
+            target,
+            method,
+            actor,
+        } => format!(
+            "{}.",
+            segments_plain_text(&lock_opened_text(world, *target, method, *actor))
+        ),
+        EventKind::HiddenFeatureRevealed { feature, kind, by } => format!(
+            "{}.",
+            segments_plain_text(&feature_revealed_text(world, *feature, kind, *by))
+        ),
+        EventKind::IllusionDisbelieved { illusion, by } => format!(
+            "{}.",
+            segments_plain_text(&illusion_disbelieved_text(world, *illusion, *by))
+        ),
+        EventKind::ItemDropped { entity, item, .. } => format!(
+            "{}.",
+            segments_plain_text(&item_moved_text(world, *entity, "dropped", item))
+        ),
+        EventKind::ItemPickedUp { entity, item } => format!(
+            "{}.",
+            segments_plain_text(&item_moved_text(world, *entity, "picked up", item))
+        ),
+    };
+
+    Some(text)
+}
+
+fn lock_opened_text(
+    world: &World,
+    target: Entity,
+    method: &UnlockMethod,
+    actor: Option<Entity>,
+) -> Vec<(String, TextKind)> {
+    let how = match method {
+        UnlockMethod::Key => "unlocked",
+        UnlockMethod::Picked => "picked the lock of",
+        UnlockMethod::Forced => "forced open",
+        UnlockMethod::Broken => "broke open",
+    };
+    match actor {
+        Some(actor) => vec![
+            (name_of(world, actor), TextKind::Actor),
+            (how.to_string(), TextKind::Normal),
+            (name_of(world, target), TextKind::Target),
+        ],
+        None => vec![
+            (name_of(world, target), TextKind::Target),
+            ("was broken open".to_string(), TextKind::Normal),
+        ],
+    }
+}
+
+fn feature_revealed_text(
+    world: &World,
+    feature: Entity,
+    kind: &HiddenFeatureKind,
+    by: Option<Entity>,
+) -> Vec<(String, TextKind)> {
+    let what = match kind {
+        HiddenFeatureKind::SecretDoor => "a secret door",
+        HiddenFeatureKind::Lever => "a hidden lever",
+        HiddenFeatureKind::Clue => "a clue",
+        HiddenFeatureKind::Trap => "a trap",
+    };
+    let mut segments = match by {
+        Some(by) => vec![
+            (name_of(world, by), TextKind::Actor),
+            (format!("found {}", what), TextKind::Normal),
+        ],
+        None => vec![(format!("Revealed {}", what), TextKind::Normal)],
+    };
+    if let Ok(feature) = world.get::<&HiddenFeature>(feature)
+        && !feature.description.is_empty()
+    {
+        segments.push((format!("({})", feature.description), TextKind::Details));
+    }
+    segments
+}
+
+fn illusion_disbelieved_text(
+    world: &World,
+    illusion: Entity,
+    by: Entity,
+) -> Vec<(String, TextKind)> {
+    vec![
+        (name_of(world, by), TextKind::Actor),
+        ("saw through".to_string(), TextKind::Normal),
+        (name_of(world, illusion), TextKind::Target),
+        ("(illusion)".to_string(), TextKind::Details),
+    ]
+}
+
+fn item_moved_text(
+    world: &World,
+    entity: Entity,
+    verb: &str,
+    item_id: &ItemId,
+) -> Vec<(String, TextKind)> {
+    let (name, rarity) = ItemsRegistry::get(item_id)
+        .map(|item| (item.item().name.clone(), item.item().rarity.clone()))
+        .unwrap_or_else(|| (item_id.to_string(), ItemRarity::Common));
+    vec![
+        (name_of(world, entity), TextKind::Actor),
+        (verb.to_string(), TextKind::Normal),
+        (name, TextKind::Item(rarity)),
+    ]
+}
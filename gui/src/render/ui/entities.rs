@@ -2,11 +2,15 @@ use hecs::{Entity, World};
 use nat20_core::{
     components::{
         ability::AbilityScoreMap,
+        biography::Biography,
         damage::DamageResistances,
         effects::effect::{Effect, EffectInstance, EffectLifetime},
         health::{hit_points::HitPoints, life_state::LifeState},
         id::{FeatId, Name, SpeciesId, SubspeciesId},
+        images::CreatureImages,
         level::{ChallengeRating, CharacterLevels},
+        lock::Lock,
+        personality::Personality,
         resource::ResourceMap,
         skill::SkillSet,
         species::{CreatureSize, CreatureType},
@@ -20,8 +24,12 @@ use strum::{Display, EnumIter};
 
 use crate::{
     render::ui::{
+        icons,
         inventory::{render_loadout, render_loadout_inventory},
-        utils::{ImguiRenderable, ImguiRenderableMutWithContext, ImguiRenderableWithContext},
+        utils::{
+            ImguiRenderable, ImguiRenderableMut, ImguiRenderableMutWithContext,
+            ImguiRenderableWithContext,
+        },
     },
     table_with_columns,
 };
@@ -88,6 +96,13 @@ impl ImguiRenderableWithContext<(&World, &CreatureRenderMode)> for Entity {
                         tab.end();
                     }
 
+                    if let Ok(biography) = world.get::<&Biography>(entity)
+                        && let Some(tab) = ui.tab_item("Bio")
+                    {
+                        biography.render(ui);
+                        tab.end();
+                    }
+
                     tab_bar.end();
                 }
             }
@@ -118,6 +133,7 @@ impl ImguiRenderableWithContext<(&World, &CreatureRenderMode)> for Entity {
                 render_if_present::<ChallengeRating>(ui, world, *self);
                 render_if_present::<LifeState>(ui, world, *self);
                 render_if_present::<HitPoints>(ui, world, *self);
+                icons::render_effect_icons(ui, world, *self);
                 render_effects_compact(ui, world, *self);
             }
         }
@@ -155,6 +171,9 @@ fn render_overview(ui: &imgui::Ui, world: &World, entity: Entity, mode: &Creatur
             render_if_present::<ChallengeRating>(ui, world, entity);
             render_if_present::<LifeState>(ui, world, entity);
             render_if_present::<HitPoints>(ui, world, entity);
+            render_if_present::<Lock>(ui, world, entity);
+            icons::render_effect_icons(ui, world, entity);
+            render_if_present::<Personality>(ui, world, entity);
 
             render_if_present::<Speed>(ui, world, entity);
 
@@ -240,6 +259,18 @@ impl ImguiRenderableMutWithContext<&mut World> for Entity {
                 tab.end();
             }
 
+            if let Some(tab) = ui.tab_item("Bio") {
+                if let Ok(mut biography) = world.get::<&mut Biography>(entity) {
+                    biography.render_mut(ui);
+                } else if ui.button("Add biography") {
+                    let _ = world.insert_one(entity, Biography::default());
+                }
+                if let Ok(mut images) = world.get::<&mut CreatureImages>(entity) {
+                    images.render_mut(ui);
+                }
+                tab.end();
+            }
+
             tab_bar.end();
         }
     }
@@ -0,0 +1,150 @@
+use hecs::{Entity, World};
+use nat20_core::{
+    components::{
+        effects::effect::{EffectInstance, EffectLifetime},
+        id::{EffectId, Name},
+        time::EntityClock,
+    },
+    systems,
+};
+
+use crate::render::ui::{
+    text::{TextKind, TextSegment},
+    utils::{ImguiRenderable, ImguiRenderableWithContext},
+};
+
+/// Size of the effect icons in pixels
+pub const ICON_SIZE: f32 = 18.0;
+
+const ICON_ROUNDING: f32 = 3.0;
+
+const DEFAULT_ICON_COLOR: [f32; 4] = [0.45, 0.45, 0.45, 1.0];
+
+/// Small square icon with a one or two letter glyph, used to show effects
+/// without taking up the space of a full table row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Icon {
+    pub glyph: String,
+    pub color: [f32; 4],
+}
+
+/// Icons for known effects. The first entry whose key is contained in the
+/// effect ID is used, so more specific keys should come first.
+static ICON_ATLAS: &[(&str, &str, [f32; 4])] = &[
+    ("condition.paralyzed", "Pa", [0.85, 0.75, 0.15, 1.0]),
+    ("condition.poisoned", "Po", [0.3, 0.65, 0.2, 1.0]),
+    ("spell.hex", "Hx", [0.55, 0.25, 0.7, 1.0]),
+    ("spell.shield", "Sh", [0.25, 0.5, 0.9, 1.0]),
+    ("spell.chill_touch", "Ch", [0.35, 0.3, 0.5, 1.0]),
+    ("spell.ray_of_frost", "Fr", [0.4, 0.8, 0.9, 1.0]),
+    ("spell.expeditious_retreat", "Er", [0.9, 0.55, 0.2, 1.0]),
+    ("spell.longstrider", "Ls", [0.6, 0.75, 0.3, 1.0]),
+    ("false_life", "FL", [0.3, 0.3, 0.3, 1.0]),
+    ("action_surge", "AS", [0.85, 0.3, 0.2, 1.0]),
+    ("dash", "Da", [0.2, 0.7, 0.6, 1.0]),
+];
+
+impl Icon {
+    pub fn for_effect(effect_id: &EffectId) -> Self {
+        if let Some((_, glyph, color)) = ICON_ATLAS
+            .iter()
+            .find(|(key, _, _)| effect_id.id().contains(key))
+        {
+            return Self {
+                glyph: glyph.to_string(),
+                color: *color,
+            };
+        }
+
+        // Fall back to the first letter of the last part of the ID, e.g.
+        // 'effect.spell.bless' -> 'B'
+        let glyph = effect_id
+            .id()
+            .rsplit('.')
+            .next()
+            .and_then(|name| name.chars().next())
+            .map(|c| c.to_ascii_uppercase().to_string())
+            .unwrap_or_else(|| "?".to_string());
+
+        Self {
+            glyph,
+            color: DEFAULT_ICON_COLOR,
+        }
+    }
+}
+
+impl ImguiRenderable for Icon {
+    fn render(&self, ui: &imgui::Ui) {
+        let min = ui.cursor_screen_pos();
+        let max = [min[0] + ICON_SIZE, min[1] + ICON_SIZE];
+        let draw_list = ui.get_window_draw_list();
+
+        draw_list
+            .add_rect(min, max, self.color)
+            .rounding(ICON_ROUNDING)
+            .filled(true)
+            .build();
+        draw_list
+            .add_rect(min, max, [0.0, 0.0, 0.0, 0.8])
+            .rounding(ICON_ROUNDING)
+            .build();
+
+        let text_size = ui.calc_text_size(&self.glyph);
+        draw_list.add_text(
+            [
+                min[0] + (ICON_SIZE - text_size[0]) / 2.0,
+                min[1] + (ICON_SIZE - text_size[1]) / 2.0,
+            ],
+            [1.0, 1.0, 1.0, 1.0],
+            &self.glyph,
+        );
+
+        // Reserve the space so the icon can be hovered like any other item
+        ui.dummy([ICON_SIZE, ICON_SIZE]);
+    }
+}
+
+/// Render the temporary effects on an entity as a row of icons, with a
+/// tooltip showing the remaining duration and source of each effect.
+pub fn render_effect_icons(ui: &imgui::Ui, world: &World, entity: Entity) {
+    let Ok(effects) = world.get::<&Vec<EffectInstance>>(entity) else {
+        return;
+    };
+    let time_mode = systems::helpers::get_component::<EntityClock>(world, entity).mode();
+
+    let mut first = true;
+    for effect in effects
+        .iter()
+        .filter(|effect| !matches!(effect.lifetime, EffectLifetime::Permanent))
+    {
+        if !first {
+            ui.same_line_with_spacing(0.0, 2.0);
+        }
+        first = false;
+
+        let min = ui.cursor_screen_pos();
+        Icon::for_effect(&effect.effect_id).render(ui);
+
+        // Check the rect directly instead of the item, since e.g. the labels
+        // on the battle map don't take mouse inputs
+        if ui.is_mouse_hovering_rect(min, [min[0] + ICON_SIZE, min[1] + ICON_SIZE]) {
+            ui.tooltip(|| {
+                ui.text(effect.effect_id.to_string());
+                TextSegment::new(&effect.effect().description, TextKind::Details)
+                    .wrap_text(true)
+                    .render(ui);
+
+                ui.separator();
+                ui.text(format!("Source: {}", effect.source));
+                if let Some(applier) = effect.applier
+                    && let Ok(name) = world.get::<&Name>(applier)
+                {
+                    ui.text(format!("Applied by: {}", name.as_str()));
+                }
+                ui.text("Remaining:");
+                ui.same_line();
+                effect.lifetime.render_with_context(ui, &time_mode);
+            });
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use glow::HasContext;
+use hecs::{Entity, World};
+use nat20_core::components::images::CreatureImages;
+use tracing::{debug, warn};
+
+use crate::state::gui_state::GuiState;
+
+/// An image uploaded to the GPU, which imgui can draw
+#[derive(Debug, Clone, Copy)]
+pub struct Texture {
+    pub id: imgui::TextureId,
+    pub size: [f32; 2],
+}
+
+impl Texture {
+    /// Scale the texture to fit within a square, keeping its aspect ratio
+    pub fn fit(&self, size: f32) -> [f32; 2] {
+        let scale = size / self.size[0].max(self.size[1]);
+        [self.size[0] * scale, self.size[1] * scale]
+    }
+}
+
+/// Images loaded from disk, e.g. portraits and tokens. Images are loaded the
+/// first time they're drawn. Images which fail to load are remembered as well,
+/// so they aren't loaded again every frame.
+pub struct TextureCache {
+    textures: HashMap<PathBuf, Option<Texture>>,
+}
+
+impl TextureCache {
+    pub fn new() -> Self {
+        Self {
+            textures: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, gl: &glow::Context, path: &Path) -> Option<Texture> {
+        *self
+            .textures
+            .entry(path.to_path_buf())
+            .or_insert_with(|| load_texture(gl, path))
+    }
+}
+
+fn load_texture(gl: &glow::Context, path: &Path) -> Option<Texture> {
+    let image = match image::open(path) {
+        Ok(image) => image.to_rgba8(),
+        Err(error) => {
+            warn!("Failed to load image {}: {}", path.display(), error);
+            return None;
+        }
+    };
+    let (width, height) = image.dimensions();
+
+    let texture = unsafe {
+        let texture = match gl.create_texture() {
+            Ok(texture) => texture,
+            Err(error) => {
+                warn!("Failed to create texture for {}: {}", path.display(), error);
+                return None;
+            }
+        };
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA as i32,
+            width as i32,
+            height as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelUnpackData::Slice(Some(&image.into_raw())),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        texture
+    };
+    debug!("Loaded image {} ({}x{})", path.display(), width, height);
+
+    // The imgui renderer uses the OpenGL texture names as texture IDs
+    Some(Texture {
+        id: imgui::TextureId::new(texture.0.get() as usize),
+        size: [width as f32, height as f32],
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreatureImageKind {
+    Portrait,
+    Token,
+}
+
+/// Draw the portrait or token of the creature, if it has one. Returns whether
+/// anything was drawn, so callers know whether to continue on the same line.
+pub fn render_creature_image(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    world: &World,
+    entity: Entity,
+    kind: CreatureImageKind,
+    size: f32,
+) -> bool {
+    let Ok(images) = world.get::<&CreatureImages>(entity) else {
+        return false;
+    };
+    let path = match kind {
+        CreatureImageKind::Portrait => images.portrait.as_ref(),
+        CreatureImageKind::Token => images.token_or_portrait(),
+    };
+    let Some(texture) = path.and_then(|path| {
+        gui_state
+            .textures
+            .get(gui_state.ig_renderer.gl_context(), path)
+    }) else {
+        return false;
+    };
+
+    imgui::Image::new(texture.id, texture.fit(size)).build(ui);
+    true
+}
@@ -1,11 +1,14 @@
 use hecs::{Entity, World};
 use nat20_core::{
-    components::items::{
-        equipment::slots::EquipmentSlot,
-        inventory::{Inventory, ItemContainer, ItemInstance},
-        item::Item,
+    components::{
+        id::IdProvider,
+        items::{
+            equipment::slots::EquipmentSlot,
+            inventory::{Inventory, ItemContainer, ItemInstance},
+            item::Item,
+        },
     },
-    systems,
+    systems::{self, what_if::WhatIf},
 };
 use strum::IntoEnumIterator;
 use tracing::info;
@@ -119,6 +122,17 @@ pub fn render_inventory(
             if ui.is_item_hovered() {
                 ui.tooltip(|| {
                     items[i].render_with_context(ui, (world, entity));
+                    // Compare with what the creature is currently wearing
+                    if items[i].equipable()
+                        && let Some(report) = systems::what_if::compare(
+                            world,
+                            entity,
+                            &[WhatIf::Equip(items[i].clone().into())],
+                        )
+                    {
+                        ui.separator_with_text("If Equipped");
+                        report.render(ui);
+                    }
                 });
             }
 
@@ -230,7 +244,34 @@ pub fn render_loadout_inventory(ui: &imgui::Ui, world: &mut World, entity: Entit
                 info!("Right-clicked on inventory item: {:?}", item.item().name);
             }
 
-            InteractMode::DoubleClick => {
+            InteractMode::DoubleClick if matches!(item, ItemInstance::Coating(_)) => {
+                // Coat the first weapon in hand
+                let slot = {
+                    let loadout = systems::loadout::loadout(world, entity);
+                    [
+                        EquipmentSlot::MeleeMainHand,
+                        EquipmentSlot::MeleeOffHand,
+                        EquipmentSlot::RangedMainHand,
+                    ]
+                    .into_iter()
+                    .find(|slot| loadout.has_weapon_in_hand(slot))
+                };
+                let Some(slot) = slot else {
+                    info!("No weapon to coat with {}", item.item().name);
+                    return;
+                };
+                if let Err(err) = systems::coating::apply(world, entity, item.id(), &slot) {
+                    info!("Failed to apply coating: {:?}", err);
+                }
+            }
+
+            InteractMode::DoubleClick if matches!(item, ItemInstance::Light(_)) => {
+                if let Err(err) = systems::light::toggle(world, entity, index) {
+                    info!("Failed to light {}: {:?}", item.item().name, err);
+                }
+            }
+
+            InteractMode::DoubleClick if item.equipable() => {
                 // Try to equip the item
                 let result = systems::inventory::equip(world, entity, item);
                 match result {
@@ -249,6 +290,10 @@ pub fn render_loadout_inventory(ui: &imgui::Ui, world: &mut World, entity: Entit
                 }
             }
 
+            InteractMode::DoubleClick => {
+                info!("Double-clicked on inventory item: {:?}", item.item().name);
+            }
+
             InteractMode::Drag => {
                 // Handle drag on inventory item
                 info!("Dragging inventory item: {:?}", item.item().name);
@@ -266,6 +311,21 @@ impl ImguiRenderableWithContext<(&World, Entity)> for ItemInstance {
             ItemInstance::Armor(armor) => {
                 armor.render_with_context(ui, context);
             }
+            ItemInstance::Spell(spell_item) => {
+                spell_item.render(ui);
+            }
+            ItemInstance::Coating(coating_item) => {
+                coating_item.render(ui);
+            }
+            ItemInstance::Tool(tool) => {
+                tool.render(ui);
+            }
+            ItemInstance::Supply(supply) => {
+                supply.render(ui);
+            }
+            ItemInstance::Light(light) => {
+                light.render(ui);
+            }
             _ => {
                 ui.text("Placeholder tooltip :^)");
             }
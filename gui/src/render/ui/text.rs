@@ -2,34 +2,14 @@ use std::{borrow::Cow, fmt::Display};
 
 use nat20_core::components::{damage::DamageType, items::item::ItemRarity};
 
-use crate::render::ui::utils::ImguiRenderable;
+use crate::render::ui::{theme, utils::ImguiRenderable};
 
 pub fn damage_type_color(damage_type: &DamageType) -> [f32; 4] {
-    match damage_type {
-        DamageType::Bludgeoning | DamageType::Piercing | DamageType::Slashing => {
-            [0.8, 0.8, 0.8, 1.0]
-        }
-        DamageType::Fire => [1.0, 0.5, 0.0, 1.0],
-        DamageType::Cold => [0.0, 1.0, 1.0, 1.0],
-        DamageType::Lightning => [0.25, 0.25, 1.0, 1.0],
-        DamageType::Acid => [0.0, 1.0, 0.0, 1.0],
-        DamageType::Poison => [0.5, 0.9, 0.0, 1.0],
-        DamageType::Force => [0.9, 0.0, 0.0, 1.0],
-        DamageType::Necrotic => [0.25, 0.7, 0.25, 1.0],
-        DamageType::Psychic => [1.0, 0.5, 1.0, 1.0],
-        DamageType::Radiant => [1.0, 0.9, 0.0, 1.0],
-        DamageType::Thunder => [0.5, 0.0, 1.0, 1.0],
-    }
+    theme::palette().damage.color(damage_type)
 }
 
 pub fn item_rarity_color(rarity: &ItemRarity) -> [f32; 4] {
-    match rarity {
-        ItemRarity::Common => [1.0, 1.0, 1.0, 1.0],
-        ItemRarity::Uncommon => [0.12, 1.0, 0.0, 1.0],
-        ItemRarity::Rare => [0.2, 0.4, 1.0, 1.0],
-        ItemRarity::VeryRare => [0.64, 0.21, 0.93, 1.0],
-        ItemRarity::Legendary => [1.0, 0.5, 0.0, 1.0],
-    }
+    theme::palette().rarity.color(rarity)
 }
 
 pub fn indent_text(ui: &imgui::Ui, indent_level: u8) {
@@ -58,20 +38,21 @@ pub enum TextKind {
 
 impl TextKind {
     pub fn color(&self) -> [f32; 4] {
+        let palette = theme::palette();
         match self {
-            TextKind::Actor => [0.8, 1.0, 0.8, 1.0],
-            TextKind::Target => [1.0, 0.8, 0.8, 1.0],
-            TextKind::Action => [1.0, 1.0, 0.8, 1.0],
-            TextKind::Normal => [1.0, 1.0, 1.0, 1.0],
-            TextKind::Damage(damage_type) => damage_type_color(damage_type),
-            TextKind::Healing => [0.5, 1.0, 0.5, 1.0],
-            TextKind::Effect => [1.0, 0.8, 0.5, 1.0],
-            TextKind::Details => [0.75, 0.75, 0.75, 1.0],
-            TextKind::Ability => [0.75, 0.5, 1.0, 1.0],
-            TextKind::Skill => [0.5, 0.75, 1.0, 1.0],
-            TextKind::Item(item_rarity) => item_rarity_color(item_rarity),
-            TextKind::Green => [0.0, 1.0, 0.0, 1.0],
-            TextKind::Red => [1.0, 0.0, 0.0, 1.0],
+            TextKind::Actor => palette.text.actor,
+            TextKind::Target => palette.text.target,
+            TextKind::Action => palette.text.action,
+            TextKind::Normal => palette.text.normal,
+            TextKind::Damage(damage_type) => palette.damage.color(damage_type),
+            TextKind::Healing => palette.text.healing,
+            TextKind::Effect => palette.text.effect,
+            TextKind::Details => palette.text.details,
+            TextKind::Ability => palette.text.ability,
+            TextKind::Skill => palette.text.skill,
+            TextKind::Item(item_rarity) => palette.rarity.color(item_rarity),
+            TextKind::Green => palette.text.green,
+            TextKind::Red => palette.text.red,
         }
     }
 }
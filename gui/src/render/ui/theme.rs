@@ -0,0 +1,435 @@
+use std::{collections::HashMap, fs, path::Path, sync::RwLock};
+
+use imgui::StyleColor;
+use nat20_core::components::{damage::DamageType, items::item::ItemRarity};
+use serde::Deserialize;
+use strum::Display;
+use tracing::{error, info};
+
+use crate::render::ui::utils::ImguiRenderableMut;
+
+/// Directory with user-defined themes. Every `.json` file in it is loaded as
+/// a theme on top of the built-in ones.
+pub static THEME_DIRECTORY: &str = "assets/themes";
+
+type Color = [f32; 4];
+
+/// Colors used for the different kinds of text, see `TextKind`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TextPalette {
+    pub actor: Color,
+    pub target: Color,
+    pub action: Color,
+    pub normal: Color,
+    pub healing: Color,
+    pub effect: Color,
+    pub details: Color,
+    pub ability: Color,
+    pub skill: Color,
+    pub green: Color,
+    pub red: Color,
+}
+
+impl TextPalette {
+    pub const DARK: Self = Self {
+        actor: [0.8, 1.0, 0.8, 1.0],
+        target: [1.0, 0.8, 0.8, 1.0],
+        action: [1.0, 1.0, 0.8, 1.0],
+        normal: [1.0, 1.0, 1.0, 1.0],
+        healing: [0.5, 1.0, 0.5, 1.0],
+        effect: [1.0, 0.8, 0.5, 1.0],
+        details: [0.75, 0.75, 0.75, 1.0],
+        ability: [0.75, 0.5, 1.0, 1.0],
+        skill: [0.5, 0.75, 1.0, 1.0],
+        green: [0.0, 1.0, 0.0, 1.0],
+        red: [1.0, 0.0, 0.0, 1.0],
+    };
+
+    pub const LIGHT: Self = Self {
+        actor: [0.1, 0.45, 0.1, 1.0],
+        target: [0.6, 0.1, 0.1, 1.0],
+        action: [0.5, 0.4, 0.0, 1.0],
+        normal: [0.0, 0.0, 0.0, 1.0],
+        healing: [0.0, 0.5, 0.0, 1.0],
+        effect: [0.65, 0.35, 0.0, 1.0],
+        details: [0.35, 0.35, 0.35, 1.0],
+        ability: [0.4, 0.15, 0.7, 1.0],
+        skill: [0.1, 0.3, 0.7, 1.0],
+        green: [0.0, 0.55, 0.0, 1.0],
+        red: [0.8, 0.0, 0.0, 1.0],
+    };
+
+    pub const HIGH_CONTRAST: Self = Self {
+        actor: [0.4, 1.0, 0.4, 1.0],
+        target: [1.0, 0.5, 0.5, 1.0],
+        action: [1.0, 1.0, 0.0, 1.0],
+        normal: [1.0, 1.0, 1.0, 1.0],
+        healing: [0.0, 1.0, 0.0, 1.0],
+        effect: [1.0, 0.65, 0.0, 1.0],
+        details: [0.9, 0.9, 0.9, 1.0],
+        ability: [0.85, 0.6, 1.0, 1.0],
+        skill: [0.4, 0.85, 1.0, 1.0],
+        green: [0.0, 1.0, 0.0, 1.0],
+        red: [1.0, 0.2, 0.2, 1.0],
+    };
+}
+
+impl Default for TextPalette {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DamagePalette {
+    pub acid: Color,
+    pub bludgeoning: Color,
+    pub cold: Color,
+    pub fire: Color,
+    pub force: Color,
+    pub lightning: Color,
+    pub necrotic: Color,
+    pub piercing: Color,
+    pub poison: Color,
+    pub psychic: Color,
+    pub radiant: Color,
+    pub slashing: Color,
+    pub thunder: Color,
+}
+
+impl DamagePalette {
+    pub const DEFAULT: Self = Self {
+        acid: [0.0, 1.0, 0.0, 1.0],
+        bludgeoning: [0.8, 0.8, 0.8, 1.0],
+        cold: [0.0, 1.0, 1.0, 1.0],
+        fire: [1.0, 0.5, 0.0, 1.0],
+        force: [0.9, 0.0, 0.0, 1.0],
+        lightning: [0.25, 0.25, 1.0, 1.0],
+        necrotic: [0.25, 0.7, 0.25, 1.0],
+        piercing: [0.8, 0.8, 0.8, 1.0],
+        poison: [0.5, 0.9, 0.0, 1.0],
+        psychic: [1.0, 0.5, 1.0, 1.0],
+        radiant: [1.0, 0.9, 0.0, 1.0],
+        slashing: [0.8, 0.8, 0.8, 1.0],
+        thunder: [0.5, 0.0, 1.0, 1.0],
+    };
+
+    /// Based on the Okabe-Ito palette, which stays distinguishable for the
+    /// common kinds of color blindness. There are more damage types than
+    /// colors, so the least common ones share a color with a related type.
+    pub const COLORBLIND_SAFE: Self = Self {
+        acid: [0.0, 0.62, 0.45, 1.0],
+        bludgeoning: [0.8, 0.8, 0.8, 1.0],
+        cold: [0.34, 0.71, 0.91, 1.0],
+        fire: [0.9, 0.62, 0.0, 1.0],
+        force: [0.84, 0.37, 0.0, 1.0],
+        lightning: [0.0, 0.45, 0.7, 1.0],
+        necrotic: [0.0, 0.62, 0.45, 1.0],
+        piercing: [0.8, 0.8, 0.8, 1.0],
+        poison: [0.0, 0.62, 0.45, 1.0],
+        psychic: [0.8, 0.47, 0.65, 1.0],
+        radiant: [0.94, 0.89, 0.26, 1.0],
+        slashing: [0.8, 0.8, 0.8, 1.0],
+        thunder: [0.0, 0.45, 0.7, 1.0],
+    };
+
+    pub fn color(&self, damage_type: &DamageType) -> Color {
+        match damage_type {
+            DamageType::Acid => self.acid,
+            DamageType::Bludgeoning => self.bludgeoning,
+            DamageType::Cold => self.cold,
+            DamageType::Fire => self.fire,
+            DamageType::Force => self.force,
+            DamageType::Lightning => self.lightning,
+            DamageType::Necrotic => self.necrotic,
+            DamageType::Piercing => self.piercing,
+            DamageType::Poison => self.poison,
+            DamageType::Psychic => self.psychic,
+            DamageType::Radiant => self.radiant,
+            DamageType::Slashing => self.slashing,
+            DamageType::Thunder => self.thunder,
+        }
+    }
+}
+
+impl Default for DamagePalette {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RarityPalette {
+    pub common: Color,
+    pub uncommon: Color,
+    pub rare: Color,
+    pub very_rare: Color,
+    pub legendary: Color,
+}
+
+impl RarityPalette {
+    pub const DEFAULT: Self = Self {
+        common: [1.0, 1.0, 1.0, 1.0],
+        uncommon: [0.12, 1.0, 0.0, 1.0],
+        rare: [0.2, 0.4, 1.0, 1.0],
+        very_rare: [0.64, 0.21, 0.93, 1.0],
+        legendary: [1.0, 0.5, 0.0, 1.0],
+    };
+
+    /// Common items can't be white on a light background
+    pub const LIGHT: Self = Self {
+        common: [0.0, 0.0, 0.0, 1.0],
+        uncommon: [0.05, 0.55, 0.0, 1.0],
+        rare: [0.1, 0.25, 0.85, 1.0],
+        very_rare: [0.5, 0.1, 0.75, 1.0],
+        legendary: [0.8, 0.35, 0.0, 1.0],
+    };
+
+    pub fn color(&self, rarity: &ItemRarity) -> Color {
+        match rarity {
+            ItemRarity::Common => self.common,
+            ItemRarity::Uncommon => self.uncommon,
+            ItemRarity::Rare => self.rare,
+            ItemRarity::VeryRare => self.very_rare,
+            ItemRarity::Legendary => self.legendary,
+        }
+    }
+}
+
+impl Default for RarityPalette {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// All the colors used for text in the UI
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    pub text: TextPalette,
+    pub damage: DamagePalette,
+    pub rarity: RarityPalette,
+}
+
+impl Palette {
+    const DARK: Self = Self {
+        text: TextPalette::DARK,
+        damage: DamagePalette::DEFAULT,
+        rarity: RarityPalette::DEFAULT,
+    };
+}
+
+/// The palette of the active theme. Text colors are looked up all over the
+/// place, so it's global instead of being passed around with the GUI state.
+static PALETTE: RwLock<Palette> = RwLock::new(Palette::DARK);
+
+pub fn palette() -> Palette {
+    *PALETTE.read().unwrap()
+}
+
+/// The imgui color preset a theme starts from
+#[derive(Debug, Clone, Copy, PartialEq, Default, Display, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StyleBase {
+    #[default]
+    Dark,
+    Light,
+    Classic,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    #[serde(default)]
+    pub base: StyleBase,
+    /// Overrides for individual imgui style colors, using the names of the
+    /// `imgui::StyleColor` variants, e.g. "WindowBg"
+    #[serde(default)]
+    pub style_colors: HashMap<String, Color>,
+    #[serde(flatten)]
+    pub palette: Palette,
+}
+
+impl Theme {
+    fn built_in() -> Vec<Theme> {
+        vec![
+            Theme {
+                name: "Dark".to_string(),
+                base: StyleBase::Dark,
+                style_colors: HashMap::new(),
+                palette: Palette::DARK,
+            },
+            Theme {
+                name: "Light".to_string(),
+                base: StyleBase::Light,
+                style_colors: HashMap::new(),
+                palette: Palette {
+                    text: TextPalette::LIGHT,
+                    damage: DamagePalette::DEFAULT,
+                    rarity: RarityPalette::LIGHT,
+                },
+            },
+            Theme {
+                name: "High Contrast".to_string(),
+                base: StyleBase::Dark,
+                style_colors: HashMap::from([
+                    ("Text".to_string(), [1.0, 1.0, 1.0, 1.0]),
+                    ("WindowBg".to_string(), [0.0, 0.0, 0.0, 1.0]),
+                    ("PopupBg".to_string(), [0.0, 0.0, 0.0, 1.0]),
+                    ("ChildBg".to_string(), [0.0, 0.0, 0.0, 1.0]),
+                    ("Border".to_string(), [1.0, 1.0, 1.0, 1.0]),
+                    ("FrameBg".to_string(), [0.15, 0.15, 0.15, 1.0]),
+                    ("Button".to_string(), [0.2, 0.2, 0.2, 1.0]),
+                    ("ButtonHovered".to_string(), [0.0, 0.4, 0.8, 1.0]),
+                    ("ButtonActive".to_string(), [0.0, 0.55, 1.0, 1.0]),
+                ]),
+                palette: Palette {
+                    text: TextPalette::HIGH_CONTRAST,
+                    damage: DamagePalette::DEFAULT,
+                    rarity: RarityPalette::DEFAULT,
+                },
+            },
+            Theme {
+                name: "Dark (Colorblind Safe)".to_string(),
+                base: StyleBase::Dark,
+                style_colors: HashMap::new(),
+                palette: Palette {
+                    damage: DamagePalette::COLORBLIND_SAFE,
+                    ..Palette::DARK
+                },
+            },
+        ]
+    }
+
+    /// Set the colors of the style to the ones of this theme
+    pub fn apply_colors(&self, style: &mut imgui::Style) {
+        match self.base {
+            StyleBase::Dark => style.use_dark_colors(),
+            StyleBase::Light => style.use_light_colors(),
+            StyleBase::Classic => style.use_classic_colors(),
+        };
+
+        for (name, color) in &self.style_colors {
+            match StyleColor::VARIANTS
+                .iter()
+                .find(|style_color| format!("{:?}", style_color) == *name)
+            {
+                Some(style_color) => style[*style_color] = *color,
+                None => error!("Theme '{}' has unknown style color '{}'", self.name, name),
+            }
+        }
+    }
+}
+
+/// Loads the available themes and keeps track of which one is active
+pub struct Themes {
+    themes: Vec<Theme>,
+    selected: usize,
+    /// Bumped whenever the active theme changes
+    revision: u32,
+}
+
+impl Themes {
+    pub fn new() -> Self {
+        let mut themes = Self {
+            themes: Vec::new(),
+            selected: 0,
+            revision: 0,
+        };
+        themes.reload();
+        themes
+    }
+
+    pub fn current(&self) -> &Theme {
+        &self.themes[self.selected]
+    }
+
+    /// Used to detect when the theme has changed and the style has to be
+    /// updated.
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.themes.len() {
+            self.selected = index;
+            self.revision += 1;
+            *PALETTE.write().unwrap() = self.current().palette;
+        }
+    }
+
+    /// Reload the themes from disk, keeping the active theme if it still exists
+    pub fn reload(&mut self) {
+        let current_name = self
+            .themes
+            .get(self.selected)
+            .map(|theme| theme.name.clone());
+
+        self.themes = Theme::built_in();
+        self.themes.extend(load_themes(Path::new(THEME_DIRECTORY)));
+
+        let index = current_name
+            .and_then(|name| self.themes.iter().position(|theme| theme.name == name))
+            .unwrap_or(0);
+        self.select(index);
+    }
+}
+
+fn load_themes(directory: &Path) -> Vec<Theme> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let result = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<Theme>(&json).map_err(|error| error.to_string())
+                });
+            match result {
+                Ok(theme) => {
+                    info!("Loaded theme '{}' from {:?}", theme.name, path);
+                    Some(theme)
+                }
+                Err(error) => {
+                    error!("Failed to load theme from {:?}: {}", path, error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+impl ImguiRenderableMut for Themes {
+    fn render_mut(&mut self, ui: &imgui::Ui) {
+        let mut selected = self.selected;
+        let width_token = ui.push_item_width(200.0);
+        if ui.combo("Theme", &mut selected, &self.themes, |theme| {
+            theme.name.as_str().into()
+        }) {
+            self.select(selected);
+        }
+        width_token.end();
+
+        if ui.button("Reload Theme Files") {
+            self.reload();
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(format!("Themes are loaded from '{}'", THEME_DIRECTORY));
+        }
+    }
+}
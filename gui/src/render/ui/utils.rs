@@ -179,6 +179,27 @@ pub fn render_button_disabled_conditionally(
     clicked
 }
 
+/// Combo box where the first option is "Any". Returns the new value if it
+/// was changed.
+pub fn render_option_combo<T: ToString + PartialEq + Clone>(
+    ui: &imgui::Ui,
+    label: &str,
+    options: &[T],
+    current: Option<&T>,
+) -> Option<Option<T>> {
+    let mut labels = vec!["Any".to_string()];
+    labels.extend(options.iter().map(|option| option.to_string()));
+    let mut index = current
+        .and_then(|current| options.iter().position(|option| option == current))
+        .map_or(0, |index| index + 1);
+
+    if ui.combo_simple_string(label, &mut index, &labels) {
+        Some(index.checked_sub(1).map(|index| options[index].clone()))
+    } else {
+        None
+    }
+}
+
 pub fn render_empty_button(ui: &imgui::Ui, label: &str) {
     let disabled = ui.begin_disabled(true);
     let color = ui.push_style_color(imgui::StyleColor::Button, [0.3, 0.3, 0.3, 1.0]);
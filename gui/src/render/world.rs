@@ -1,4 +1,6 @@
+pub mod battle_map;
 pub mod camera;
+pub mod combat_feedback;
 pub mod frame_uniforms;
 pub mod grid;
 pub mod line;
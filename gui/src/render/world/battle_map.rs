@@ -0,0 +1,620 @@
+use hecs::Entity;
+use nat20_core::{
+    components::{
+        faction::Attitude,
+        health::{hit_points::HitPoints, life_state::LifeState},
+        hidden::HiddenFeature,
+        id::Name,
+        items::inventory::{ItemContainer, ItemInstance},
+        speed::Speed,
+        surface::SurfaceKind,
+    },
+    engine::{game_state::GameState, geometry::WorldPath},
+    entities::{ground_item::GroundItemTag, object::ObjectTag},
+    systems::{
+        self,
+        geometry::{CreaturePose, RaycastHitKind},
+        movement::MovementPreview,
+    },
+};
+use parry3d::{
+    na::{Isometry3, Matrix4, Point3},
+    shape::Shape,
+};
+use uom::si::{
+    f32::Length,
+    length::{foot, meter},
+};
+
+use crate::{
+    render::{
+        ui::{
+            entities::render_if_present,
+            icons,
+            images::{self, CreatureImageKind},
+            text::{TextKind, item_rarity_color},
+            utils::ImguiRenderable,
+        },
+        world::{
+            line::LineRenderer,
+            mesh::{Mesh, MeshRenderMode},
+            shapes,
+        },
+    },
+    state::{self, gui_state::GuiState},
+};
+
+// TODO: Store all colors in one place?
+pub static PLAYER_TOKEN_COLOR: [f32; 3] = [0.2, 0.5, 1.0];
+pub static FRIENDLY_TOKEN_COLOR: [f32; 3] = [0.2, 0.8, 0.2];
+pub static NEUTRAL_TOKEN_COLOR: [f32; 3] = [0.9, 0.8, 0.2];
+pub static HOSTILE_TOKEN_COLOR: [f32; 3] = [0.9, 0.2, 0.2];
+pub static MOVEMENT_RANGE_COLOR: [f32; 3] = [0.3, 0.7, 1.0];
+pub static FIRE_SURFACE_COLOR: [f32; 3] = [1.0, 0.45, 0.1];
+pub static ICE_SURFACE_COLOR: [f32; 3] = [0.6, 0.9, 1.0];
+pub static GREASE_SURFACE_COLOR: [f32; 3] = [0.55, 0.45, 0.2];
+pub static ACID_SURFACE_COLOR: [f32; 3] = [0.5, 1.0, 0.2];
+pub static ANTIMAGIC_SURFACE_COLOR: [f32; 3] = [0.6, 0.4, 0.9];
+pub static BRIGHT_LIGHT_COLOR: [f32; 3] = [1.0, 0.85, 0.2];
+pub static DIM_LIGHT_COLOR: [f32; 3] = [0.6, 0.5, 0.2];
+pub static REVEALED_FEATURE_COLOR: [f32; 3] = [0.8, 0.4, 1.0];
+pub static GROUND_ITEM_COLOR: [f32; 3] = [1.0, 0.8, 0.3];
+
+/// Number of subdivisions used when drawing the outline of round shapes
+const OUTLINE_SUBDIVISIONS: u32 = 32;
+/// How fast tokens slide along their path when moving, in meters per second
+const TOKEN_SLIDE_SPEED: f32 = 6.0;
+/// How far the cursor has to move before the movement preview is recomputed
+const MOVEMENT_PREVIEW_TOLERANCE: f32 = 0.1;
+
+/// A token sliding along the path its creature just moved. The creature has
+/// already moved in the world, this only affects where it is drawn.
+pub struct MovementAnimation {
+    path: WorldPath,
+    elapsed: f32,
+}
+
+impl MovementAnimation {
+    pub fn new(path: WorldPath) -> Self {
+        Self { path, elapsed: 0.0 }
+    }
+
+    pub fn advance(&mut self, delta_time: f32) {
+        self.elapsed += delta_time;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed * TOKEN_SLIDE_SPEED >= self.path.length.get::<meter>()
+    }
+
+    pub fn position(&self) -> Option<Point3<f32>> {
+        self.path
+            .trim_to_length(Length::new::<meter>(self.elapsed * TOKEN_SLIDE_SPEED))
+            .end()
+            .copied()
+    }
+}
+
+/// Draw the battle map in the GL viewport beneath the imgui UI: the grid, the
+/// world geometry, creature tokens and overlays for the selected creature.
+pub fn render_battle_map(ui: &imgui::Ui, gui_state: &mut GuiState, game_state: &mut GameState) {
+    if *gui_state
+        .settings
+        .get::<bool>(state::parameters::RENDER_GRID)
+    {
+        gui_state
+            .grid_renderer
+            .draw(gui_state.ig_renderer.gl_context());
+    }
+
+    let delta_time = ui.io().delta_time;
+    gui_state.movement_animations.retain(|_, animation| {
+        animation.advance(delta_time);
+        !animation.is_finished()
+    });
+    gui_state.combat_feedback.update(game_state, delta_time);
+
+    let mesh_cache = &mut gui_state.mesh_cache;
+    // TODO: Do something less "hardcoded" with the mesh cache
+    if let Some(mesh) = mesh_cache.get("world") {
+        mesh.draw(
+            gui_state.ig_renderer.gl_context(),
+            &gui_state.program,
+            &Matrix4::identity(),
+            [0.75, 0.75, 0.75, 1.0],
+            &MeshRenderMode::MeshOnly,
+        );
+    } else {
+        let mesh = Mesh::from_parry_trimesh(
+            gui_state.ig_renderer.gl_context(),
+            &game_state.geometry.trimesh,
+        );
+        mesh_cache.insert("world".to_string(), mesh);
+    }
+
+    if let Some(mesh) = mesh_cache.get("navmesh") {
+        if *gui_state
+            .settings
+            .get_mut::<bool>(state::parameters::RENDER_NAVIGATION_NAVMESH)
+        {
+            mesh.draw(
+                gui_state.ig_renderer.gl_context(),
+                &gui_state.program,
+                &Matrix4::identity(),
+                [0.2, 0.8, 0.2, 0.5],
+                &MeshRenderMode::MeshWithWireFrame {
+                    color: [0.0, 0.5, 0.0, 0.5],
+                    width: 2.0,
+                },
+            );
+        }
+    } else {
+        let mesh = Mesh::from_poly_navmesh(
+            gui_state.ig_renderer.gl_context(),
+            &game_state.geometry.poly_navmesh,
+        );
+        mesh_cache.insert("navmesh".to_string(), mesh);
+    }
+
+    // TODO: I feel like this should be somewhere else
+    for (entity, pose) in game_state.world.query::<&CreaturePose>().iter() {
+        // Secret doors and the like aren't shown until someone finds them, and
+        // invisible creatures aren't shown unless the players can see them
+        if systems::discovery::is_hidden(&game_state.world, entity)
+            || systems::visibility::hidden_from_players(&game_state.world, entity)
+        {
+            continue;
+        }
+        systems::geometry::get_shape(&game_state.world, entity).map(|(shape, mut shape_pose)| {
+            // Draw the token along its path while it's sliding into place
+            if let Some(animation) = gui_state.movement_animations.get(&entity)
+                && let Some(position) = animation.position()
+            {
+                shape_pose.translation.vector += position.coords - pose.translation.vector;
+            }
+
+            let key = format!("{:#?}", shape);
+            if let Some(mesh) = mesh_cache.get(&key) {
+                if let Some(current_entity) = gui_state.selected_entity
+                    && current_entity == entity
+                {
+                    // Render a ring around the feet of the currently selected entity
+                    gui_state.line_renderer.add_circle(
+                        [
+                            pose.translation.vector.x,
+                            pose.translation.vector.y + 0.1,
+                            pose.translation.vector.z,
+                        ],
+                        shape.radius + 0.1,
+                        [1.0, 1.0, 1.0],
+                    );
+                }
+
+                // Highlight if mouse is over the creature
+                let mode = if let Some(raycast) = &gui_state.cursor_ray_result
+                    && let Some(closest) = raycast.closest()
+                    && let RaycastHitKind::Creature(e) = &closest.kind
+                    && *e == entity
+                {
+                    &MeshRenderMode::MeshWithWireFrame {
+                        color: [1.0, 1.0, 1.0, 1.0],
+                        width: 2.0,
+                    }
+                } else if systems::illusions::disbelieved_by_players(&game_state.world, entity) {
+                    // Illusions the players have seen through are only faintly visible
+                    &MeshRenderMode::WireFrameOnly {
+                        color: [0.7, 0.7, 1.0, 0.5],
+                        width: 1.0,
+                    }
+                } else {
+                    gui_state
+                        .creature_render_mode
+                        .get(&entity)
+                        .unwrap_or(&MeshRenderMode::MeshOnly)
+                };
+
+                mesh.draw(
+                    gui_state.ig_renderer.gl_context(),
+                    &gui_state.program,
+                    &shape_pose.to_homogeneous(),
+                    gui_state
+                        .combat_feedback
+                        .token_color(entity, [0.8, 0.8, 0.8, 1.0]),
+                    mode,
+                );
+
+                // TEMP
+                gui_state.path_cache.get(&entity).map(|path| {
+                    [
+                        (&path.taken_path, [1.0, 1.0, 1.0]),
+                        (&path.full_path, [1.0, 0.0, 0.0]),
+                    ]
+                    .iter()
+                    .for_each(|(path, color)| {
+                        gui_state.line_renderer.add_polyline(
+                            &path
+                                .points
+                                .iter()
+                                .map(|p| [p.x, p.y, p.z])
+                                .collect::<Vec<[f32; 3]>>(),
+                            *color,
+                        );
+                    });
+                });
+            } else {
+                let mesh = shapes::build_capsule_mesh(
+                    gui_state.ig_renderer.gl_context(),
+                    8,
+                    16,
+                    shape.radius,
+                    shape.half_height(),
+                );
+                mesh_cache.insert(key, mesh);
+            }
+        });
+    }
+
+    render_surfaces(gui_state, game_state);
+    render_hidden_features(gui_state, game_state);
+    render_ground_items(ui, gui_state, game_state);
+
+    if *gui_state
+        .settings
+        .get::<bool>(state::parameters::RENDER_LIGHTS)
+    {
+        render_lights(gui_state, game_state);
+    }
+
+    if *gui_state
+        .settings
+        .get::<bool>(state::parameters::RENDER_TOKENS)
+    {
+        render_token_rings(gui_state, game_state);
+    }
+
+    if *gui_state
+        .settings
+        .get::<bool>(state::parameters::RENDER_MOVEMENT_RANGE)
+    {
+        render_movement_range(gui_state, game_state);
+    }
+
+    render_creature_labels(ui, gui_state, game_state);
+    gui_state
+        .combat_feedback
+        .render(ui, game_state, &gui_state.camera);
+
+    // TODO: Not sure where to put this?
+    gui_state.line_renderer.draw(
+        gui_state.ig_renderer.gl_context(),
+        &Matrix4::identity(),
+        2.0,
+    );
+}
+
+/// Draw a ring around the feet of every creature, colored by how it relates to
+/// the selected creature. Player controlled creatures are always blue.
+fn render_token_rings(gui_state: &mut GuiState, game_state: &GameState) {
+    for (entity, pose) in game_state
+        .world
+        .query::<&CreaturePose>()
+        .without::<&ObjectTag>()
+        .without::<&GroundItemTag>()
+        .iter()
+    {
+        if systems::visibility::hidden_from_players(&game_state.world, entity) {
+            continue;
+        }
+
+        // Rings show the space the creature controls, which is larger than
+        // the creature itself
+        let radius = systems::size::space(&game_state.world, entity).get::<meter>() / 2.0;
+
+        let color = if systems::ai::is_player_controlled(&game_state.world, entity) {
+            PLAYER_TOKEN_COLOR
+        } else if let Some(selected) = gui_state.selected_entity {
+            match systems::factions::perceived_threat(&game_state.world, selected, entity) {
+                Attitude::Friendly => FRIENDLY_TOKEN_COLOR,
+                Attitude::Neutral => NEUTRAL_TOKEN_COLOR,
+                Attitude::Hostile => HOSTILE_TOKEN_COLOR,
+            }
+        } else {
+            NEUTRAL_TOKEN_COLOR
+        };
+
+        gui_state.line_renderer.add_circle(
+            [
+                pose.translation.vector.x,
+                pose.translation.vector.y + 0.05,
+                pose.translation.vector.z,
+            ],
+            radius,
+            color,
+        );
+    }
+}
+
+/// Outline the surfaces covering the ground, e.g. fire or grease
+fn render_surfaces(gui_state: &mut GuiState, game_state: &GameState) {
+    for surface in &game_state.surfaces {
+        let color = match surface.kind {
+            SurfaceKind::Fire => FIRE_SURFACE_COLOR,
+            SurfaceKind::Ice => ICE_SURFACE_COLOR,
+            SurfaceKind::Grease => GREASE_SURFACE_COLOR,
+            SurfaceKind::Acid => ACID_SURFACE_COLOR,
+            SurfaceKind::Antimagic => ANTIMAGIC_SURFACE_COLOR,
+        };
+        gui_state.line_renderer.add_circle(
+            [surface.center.x, surface.center.y + 0.05, surface.center.z],
+            surface.radius.get::<meter>(),
+            color,
+        );
+    }
+}
+
+/// Mark the hidden features which have been found, so they're easy to spot
+/// among the rest of the map
+fn render_hidden_features(gui_state: &mut GuiState, game_state: &GameState) {
+    for (entity, feature) in game_state.world.query::<&HiddenFeature>().iter() {
+        if !feature.revealed {
+            continue;
+        }
+        let Some(position) = systems::geometry::get_foot_position(&game_state.world, entity) else {
+            continue;
+        };
+        let radius = systems::size::space(&game_state.world, entity).get::<meter>() / 2.0;
+        gui_state.line_renderer.add_circle(
+            [position.x, position.y + 0.05, position.z],
+            radius,
+            REVEALED_FEATURE_COLOR,
+        );
+    }
+}
+
+/// Mark the items lying on the ground, and show what an item is when the
+/// cursor is over it
+fn render_ground_items(ui: &imgui::Ui, gui_state: &mut GuiState, game_state: &GameState) {
+    let hovered = if let Some(raycast) = &gui_state.cursor_ray_result
+        && let Some(closest) = raycast.closest()
+        && let RaycastHitKind::Creature(entity) = &closest.kind
+    {
+        Some(*entity)
+    } else {
+        None
+    };
+
+    for (entity, (_, item)) in game_state
+        .world
+        .query::<(&GroundItemTag, &ItemInstance)>()
+        .iter()
+    {
+        let Some(position) = systems::geometry::get_foot_position(&game_state.world, entity) else {
+            continue;
+        };
+        let radius = systems::size::space(&game_state.world, entity).get::<meter>() / 2.0;
+        gui_state.line_renderer.add_circle(
+            [position.x, position.y + 0.05, position.z],
+            radius,
+            GROUND_ITEM_COLOR,
+        );
+
+        if hovered == Some(entity) {
+            let item = item.item();
+            ui.tooltip(|| {
+                ui.text_colored(item_rarity_color(&item.rarity), &item.name);
+                ui.text_colored(TextKind::Details.color(), "Lying on the ground");
+            });
+        }
+    }
+}
+
+/// Outline the bright and dim light around creatures carrying a lit torch or
+/// lantern
+fn render_lights(gui_state: &mut GuiState, game_state: &GameState) {
+    for light in systems::light::emitted_light(&game_state.world) {
+        let Some(position) = systems::geometry::get_foot_position(&game_state.world, light.carrier)
+        else {
+            continue;
+        };
+        let center = [position.x, position.y + 0.05, position.z];
+        gui_state.line_renderer.add_circle(
+            center,
+            light.bright_light.get::<meter>(),
+            BRIGHT_LIGHT_COLOR,
+        );
+        gui_state
+            .line_renderer
+            .add_circle(center, light.dim_light.get::<meter>(), DIM_LIGHT_COLOR);
+    }
+}
+
+/// Show how far the selected creature can still move this turn. Movement is
+/// only limited during encounters, so the overlay is hidden outside of them.
+fn render_movement_range(gui_state: &mut GuiState, game_state: &GameState) {
+    let Some(entity) = gui_state.selected_entity else {
+        return;
+    };
+    if !game_state.in_combat.contains_key(&entity) {
+        return;
+    }
+
+    let Ok(speed) = game_state.world.get::<&Speed>(entity) else {
+        return;
+    };
+    let Some(position) = systems::geometry::get_foot_position(&game_state.world, entity) else {
+        return;
+    };
+
+    let remaining = speed.remaining_movement().get::<meter>();
+    if remaining > 0.0 {
+        gui_state.line_renderer.add_circle(
+            [position.x, position.y + 0.05, position.z],
+            remaining,
+            MOVEMENT_RANGE_COLOR,
+        );
+    }
+}
+
+fn render_creature_labels(ui: &imgui::Ui, gui_state: &mut GuiState, game_state: &GameState) {
+    for (entity, name) in game_state.world.query::<&Name>().iter() {
+        if systems::discovery::is_hidden(&game_state.world, entity)
+            || systems::visibility::hidden_from_players(&game_state.world, entity)
+        {
+            continue;
+        }
+        if let Some(pose) = game_state.world.get::<&CreaturePose>(entity).ok() {
+            let translation = pose.translation.vector;
+            let pos = gui_state.camera.world_to_screen(&Point3::new(
+                translation.x,
+                translation.y
+                    + systems::geometry::get_height(&game_state.world, entity).unwrap() * 1.5,
+                translation.z,
+            ));
+
+            if let Some((x, y)) = pos {
+                let height = ui.calc_text_size(name.as_str())[1] * 2.0;
+                let width = ui.calc_text_size("HP:")[0] + 150.0; // rough estimate for width
+                let window_pos = [x - width / 2.0, y - height];
+                ui.window(&format!("Label##{:?}", entity))
+                    .always_auto_resize(true)
+                    .position(window_pos, imgui::Condition::Always)
+                    .bg_alpha(0.5)
+                    .title_bar(false)
+                    .resizable(false)
+                    .movable(false)
+                    .scrollable(false)
+                    .focus_on_appearing(false)
+                    .collapsed(false, imgui::Condition::Always)
+                    .mouse_inputs(false)
+                    .build(|| {
+                        if images::render_creature_image(
+                            ui,
+                            gui_state,
+                            &game_state.world,
+                            entity,
+                            CreatureImageKind::Token,
+                            height,
+                        ) {
+                            ui.same_line();
+                        }
+                        ui.group(|| {
+                            name.render(ui);
+                            render_if_present::<HitPoints>(ui, &game_state.world, entity);
+                            ui.same_line();
+                            render_if_present::<LifeState>(ui, &game_state.world, entity);
+                            icons::render_effect_icons(ui, &game_state.world, entity);
+                        });
+                    });
+            }
+        }
+    }
+}
+
+/// Draw the wireframe outline of an area of effect, e.g. the cone of Burning
+/// Hands or the sphere of Fireball.
+pub fn render_area_outline(
+    line_renderer: &mut LineRenderer,
+    shape: &dyn Shape,
+    pose: &Isometry3<f32>,
+    color: [f32; 3],
+) {
+    let (points, indices) = if let Some(ball) = shape.as_ball() {
+        ball.to_outline(OUTLINE_SUBDIVISIONS)
+    } else if let Some(cuboid) = shape.as_cuboid() {
+        cuboid.to_outline()
+    } else if let Some(cone) = shape.as_cone() {
+        cone.to_outline(OUTLINE_SUBDIVISIONS)
+    } else if let Some(cylinder) = shape.as_cylinder() {
+        cylinder.to_outline(OUTLINE_SUBDIVISIONS)
+    } else {
+        return;
+    };
+
+    for [a, b] in indices {
+        let a = pose * points[a as usize];
+        let b = pose * points[b as usize];
+        line_renderer.add_line([a.x, a.y, a.z], [b.x, b.y, b.z], color);
+    }
+}
+
+/// Whether the entity is allowed to move right now, i.e. it's either not in
+/// an encounter or it's the entity's turn.
+pub fn can_move(game_state: &GameState, entity: Entity) -> bool {
+    match game_state.in_combat.get(&entity) {
+        Some(encounter_id) => game_state
+            .encounters
+            .get(encounter_id)
+            .is_some_and(|encounter| encounter.current_entity() == entity),
+        None => true,
+    }
+}
+
+/// Show the path the entity would take to reach the goal along with how much
+/// movement it costs and whether it provokes any opportunity attacks.
+pub fn render_movement_preview(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    game_state: &mut GameState,
+    entity: Entity,
+    goal: Point3<f32>,
+) {
+    let is_cached =
+        gui_state
+            .movement_preview
+            .as_ref()
+            .is_some_and(|(cached_entity, cached_goal, _)| {
+                *cached_entity == entity
+                    && (cached_goal - goal).magnitude() < MOVEMENT_PREVIEW_TOLERANCE
+            });
+    if !is_cached {
+        gui_state.movement_preview = systems::movement::preview_movement(game_state, entity, &goal)
+            .ok()
+            .map(|preview| (entity, goal, preview));
+    }
+
+    let Some((_, _, preview)) = &gui_state.movement_preview else {
+        return;
+    };
+
+    for (path, color) in [
+        (&preview.path.full_path, HOSTILE_TOKEN_COLOR),
+        (&preview.path.taken_path, MOVEMENT_RANGE_COLOR),
+    ] {
+        gui_state.line_renderer.add_polyline(
+            &path
+                .points
+                .iter()
+                .map(|p| [p.x, p.y + 0.05, p.z])
+                .collect::<Vec<[f32; 3]>>(),
+            color,
+        );
+    }
+
+    ui.tooltip(|| {
+        if game_state.in_combat.contains_key(&entity) {
+            ui.text(format!(
+                "Movement: {:.0} ft. ({:.0} ft. left)",
+                preview.cost.get::<foot>(),
+                preview.remaining_movement.get::<foot>()
+            ));
+        } else {
+            ui.text(format!(
+                "Distance: {:.0} ft.",
+                preview.path.taken_path.length.get::<foot>()
+            ));
+        }
+
+        if !preview.path.reaches_goal() {
+            ui.text_colored(TextKind::Red.color(), "Not enough movement to reach");
+        }
+
+        if !preview.opportunity_attackers.is_empty() {
+            ui.text_colored(TextKind::Red.color(), "Provokes opportunity attacks from:");
+            for attacker in &preview.opportunity_attackers {
+                if let Ok(name) = game_state.world.get::<&Name>(*attacker) {
+                    ui.bullet_text(name.as_str());
+                }
+            }
+        }
+    });
+}
@@ -6,13 +6,14 @@ use parry3d::{
     na::{Isometry3, Perspective3, Point3, Vector3},
     query::Ray,
 };
-use winit::{
-    event::{MouseButton, MouseScrollDelta, WindowEvent},
-    keyboard::PhysicalKey,
-};
+use winit::event::{MouseButton, MouseScrollDelta, WindowEvent};
 
 use crate::{
     render::ui::utils::ImguiRenderableMutWithContext,
+    state::{
+        gamepad::Gamepad,
+        keybindings::{KeyAction, KeyBindings},
+    },
     windows::anchor::{self, AUTO_RESIZE, WindowManager},
 };
 
@@ -24,6 +25,11 @@ static DISTANCE: f32 = 30.0;
 static YAW: f32 = (-135.0f32).to_radians();
 static PITCH: f32 = (-45.0f32).to_radians();
 
+// Keyboard controls, per second
+static KEY_PAN_SPEED: f32 = 1.0; // fraction of the distance to the target
+static KEY_ROTATE_SPEED: f32 = 1.5; // radians
+static KEY_ZOOM_SPEED: f32 = 2.0; // scalar
+
 pub struct OrbitCamera {
     pub target: Point3<f32>,
     pub radius: f32, // distance from target
@@ -35,7 +41,7 @@ pub struct OrbitCamera {
     zoom_sens: f32,   // scalar per wheel tick
     // state
     mmb_down: bool,
-    shift_down: bool,
+    pan_held: bool,
 
     last_cursor: Option<(f32, f32)>,
     last_viewport: Option<(f32, f32)>,
@@ -53,7 +59,7 @@ impl OrbitCamera {
             pan_sens: 0.0015,
             zoom_sens: 1.1,
             mmb_down: false,
-            shift_down: false,
+            pan_held: false,
             last_cursor: None,
             last_viewport: None,
             last_proj: None,
@@ -113,6 +119,58 @@ impl OrbitCamera {
         Some(Ray::new(origin.into(), dir.normalize()))
     }
 
+    /// Move the camera with the keys bound to the camera controls
+    pub fn handle_keybindings(&mut self, ui: &imgui::Ui, keybindings: &KeyBindings) {
+        self.pan_held = keybindings.down(ui, KeyAction::CameraPan);
+
+        if ui.io().want_capture_keyboard {
+            return;
+        }
+
+        let delta_time = ui.io().delta_time;
+        let axis = |positive: KeyAction, negative: KeyAction| {
+            keybindings.down(ui, positive) as i32 as f32
+                - keybindings.down(ui, negative) as i32 as f32
+        };
+
+        let dir = Self::spherical_dir(self.yaw, self.pitch);
+        let forward = Vector3::new(dir.x, 0.0, dir.z).normalize();
+        let right = Vector3::new(-dir.z, 0.0, dir.x).normalize();
+        let pan_step = self.radius * KEY_PAN_SPEED * delta_time;
+        self.target += forward * axis(KeyAction::CameraForward, KeyAction::CameraBack) * pan_step;
+        self.target += right * axis(KeyAction::CameraRight, KeyAction::CameraLeft) * pan_step;
+
+        self.yaw += axis(KeyAction::CameraRotateRight, KeyAction::CameraRotateLeft)
+            * KEY_ROTATE_SPEED
+            * delta_time;
+
+        let zoom = axis(KeyAction::CameraZoomIn, KeyAction::CameraZoomOut);
+        if zoom != 0.0 {
+            self.radius = (self.radius / KEY_ZOOM_SPEED.powf(zoom * delta_time)).clamp(0.5, 200.0);
+        }
+    }
+
+    /// Orbit and zoom the camera with the right stick. The D-pad and left
+    /// stick are left to the window navigation and the map cursor.
+    pub fn handle_gamepad(&mut self, ui: &imgui::Ui, gamepad: &Gamepad) {
+        if !gamepad.active() || ui.io().nav_active {
+            return;
+        }
+
+        let delta_time = ui.io().delta_time;
+        let [x, y] = gamepad.right_stick();
+        self.yaw += x * KEY_ROTATE_SPEED * delta_time;
+        if y != 0.0 {
+            self.radius = (self.radius * KEY_ZOOM_SPEED.powf(y * delta_time)).clamp(0.5, 200.0);
+        }
+    }
+
+    /// Move the cursor used for picking, e.g. when it's moved with a gamepad
+    /// instead of the mouse. The position is in physical pixels.
+    pub fn set_cursor(&mut self, x: f32, y: f32) {
+        self.last_cursor = Some((x, y));
+    }
+
     fn spherical_dir(yaw: f32, pitch: f32) -> Vector3<f32> {
         let cp = pitch.clamp(-1.5533, 1.5533); // ~±89°
         let cy = yaw.cos();
@@ -156,17 +214,6 @@ impl OrbitCamera {
                 }
             }
 
-            WindowEvent::KeyboardInput { event, .. } => match event.physical_key {
-                PhysicalKey::Code(key_code) => {
-                    if key_code == winit::keyboard::KeyCode::ShiftLeft
-                        || key_code == winit::keyboard::KeyCode::ShiftRight
-                    {
-                        self.shift_down = event.state == winit::event::ElementState::Pressed;
-                    }
-                }
-                PhysicalKey::Unidentified(_) => {}
-            },
-
             WindowEvent::CursorMoved { position, .. } => {
                 let (position_x, position_y) = (position.x as f32, position.y as f32);
                 if imgui_wants_mouse {
@@ -180,7 +227,7 @@ impl OrbitCamera {
 
                     if self.mmb_down {
                         // PAN when MMB (and Shift)
-                        if self.shift_down {
+                        if self.pan_held {
                             // pan along camera right/up
                             let dir = Self::spherical_dir(self.yaw, self.pitch);
                             let right = Vector3::new(dir.z, 0.0, -dir.x).normalize(); // Y-up right
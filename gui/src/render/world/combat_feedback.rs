@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use hecs::Entity;
+use nat20_core::{
+    components::{
+        actions::{
+            action::{ActionKindResult, ActionResult, DamageResolutionKind},
+            targeting::TargetInstance,
+        },
+        hidden::HiddenFeatureKind,
+    },
+    engine::{event::EventKind, game_state::GameState},
+    systems,
+};
+use parry3d::na::Point3;
+
+use crate::{
+    render::{
+        ui::{text::damage_type_color, utils::interpolate_color},
+        world::camera::OrbitCamera,
+    },
+    state::events::EventSubscription,
+};
+
+/// How long floating text stays on screen, in seconds
+const FLOATER_DURATION: f32 = 1.5;
+/// How far floating text rises before it disappears, in pixels
+const FLOATER_RISE: f32 = 40.0;
+/// Vertical spacing between floaters on the same creature, in pixels
+const FLOATER_SPACING: f32 = 16.0;
+/// How long a token flashes after being hit, in seconds
+const FLASH_DURATION: f32 = 0.3;
+
+const FLASH_COLOR: [f32; 4] = [1.0, 0.25, 0.25, 1.0];
+const MISS_COLOR: [f32; 4] = [0.7, 0.7, 0.7, 1.0];
+const CRIT_COLOR: [f32; 4] = [1.0, 0.85, 0.1, 1.0];
+const REVEAL_COLOR: [f32; 4] = [0.8, 0.4, 1.0, 1.0];
+
+/// Text floating up from a creature, e.g. the damage it just took
+struct Floater {
+    entity: Entity,
+    text: String,
+    color: [f32; 4],
+    /// Floaters spawned at the same time are stacked on top of each other
+    slot: usize,
+    elapsed: f32,
+}
+
+/// Short-lived visual feedback on the battle map when creatures are attacked or
+/// something hidden is found.
+/// The feedback is driven by the events logged in the game state, so anything
+/// that deals damage shows up here without having to render the action results.
+pub struct CombatFeedback {
+    events: EventSubscription,
+    floaters: Vec<Floater>,
+    flashes: HashMap<Entity, f32>,
+}
+
+impl CombatFeedback {
+    pub fn new() -> Self {
+        Self {
+            events: EventSubscription::new(),
+            floaters: Vec::new(),
+            flashes: HashMap::new(),
+        }
+    }
+
+    /// Handle new events and advance the current animations
+    pub fn update(&mut self, game_state: &mut GameState, delta_time: f32) {
+        let events = self.events.poll(game_state);
+
+        for event in events {
+            match &event.kind {
+                EventKind::ActionPerformed { results, .. } => {
+                    for result in results {
+                        self.handle_result(result);
+                    }
+                }
+                EventKind::HiddenFeatureRevealed { feature, kind, .. } => {
+                    let text = match kind {
+                        HiddenFeatureKind::SecretDoor => "Secret door!",
+                        HiddenFeatureKind::Lever => "Hidden lever!",
+                        HiddenFeatureKind::Clue => "Clue!",
+                        HiddenFeatureKind::Trap => "Trap!",
+                    };
+                    self.add_floater(*feature, text.to_string(), REVEAL_COLOR);
+                }
+                EventKind::IllusionDisbelieved { illusion, .. } => {
+                    self.add_floater(*illusion, "Illusion!".to_string(), REVEAL_COLOR);
+                }
+                _ => {}
+            }
+        }
+
+        self.floaters.retain_mut(|floater| {
+            floater.elapsed += delta_time;
+            floater.elapsed < FLOATER_DURATION
+        });
+        self.flashes.retain(|_, remaining| {
+            *remaining -= delta_time;
+            *remaining > 0.0
+        });
+    }
+
+    fn handle_result(&mut self, result: &ActionResult) {
+        let (TargetInstance::Entity(target) | TargetInstance::Object(target)) = result.target
+        else {
+            return;
+        };
+
+        let mut results = vec![&result.kind];
+        while let Some(kind) = results.pop() {
+            match kind {
+                ActionKindResult::Standard(outcome) => {
+                    let Some(damage) = &outcome.damage else {
+                        continue;
+                    };
+
+                    if let DamageResolutionKind::AttackRoll { attack_roll, .. } = &damage.kind {
+                        if damage.damage_taken.is_none() {
+                            self.add_floater(target, "MISS".to_string(), MISS_COLOR);
+                            continue;
+                        }
+                        if attack_roll.roll_result.is_crit {
+                            self.add_floater(target, "CRIT!".to_string(), CRIT_COLOR);
+                        }
+                    }
+
+                    if let Some(damage_taken) = &damage.damage_taken {
+                        for component in &damage_taken.components {
+                            self.add_floater(
+                                target,
+                                component.after_mods.to_string(),
+                                damage_type_color(&component.damage_type),
+                            );
+                        }
+                        if damage_taken.total > 0 {
+                            self.flashes.insert(target, FLASH_DURATION);
+                        }
+                    }
+                }
+                ActionKindResult::Composite { actions } => results.extend(actions),
+                _ => {}
+            }
+        }
+    }
+
+    fn add_floater(&mut self, entity: Entity, text: String, color: [f32; 4]) {
+        let slot = self
+            .floaters
+            .iter()
+            .filter(|floater| floater.entity == entity && floater.elapsed == 0.0)
+            .count();
+        self.floaters.push(Floater {
+            entity,
+            text,
+            color,
+            slot,
+            elapsed: 0.0,
+        });
+    }
+
+    /// The color a token should be drawn with, flashing if it was just hit
+    pub fn token_color(&self, entity: Entity, base_color: [f32; 4]) -> [f32; 4] {
+        match self.flashes.get(&entity) {
+            Some(remaining) => {
+                interpolate_color(FLASH_COLOR, base_color, remaining / FLASH_DURATION)
+            }
+            None => base_color,
+        }
+    }
+
+    pub fn render(&self, ui: &imgui::Ui, game_state: &GameState, camera: &OrbitCamera) {
+        let draw_list = ui.get_background_draw_list();
+
+        for floater in &self.floaters {
+            let Some(position) =
+                systems::geometry::get_foot_position(&game_state.world, floater.entity)
+            else {
+                continue;
+            };
+            let height =
+                systems::geometry::get_height(&game_state.world, floater.entity).unwrap_or(2.0);
+            let Some((x, y)) =
+                camera.world_to_screen(&Point3::new(position.x, position.y + height, position.z))
+            else {
+                continue;
+            };
+
+            let progress = floater.elapsed / FLOATER_DURATION;
+            let mut color = floater.color;
+            // Fade out during the second half of the animation
+            color[3] *= (2.0 - 2.0 * progress).min(1.0);
+
+            let text_size = ui.calc_text_size(&floater.text);
+            draw_list.add_text(
+                [
+                    x - text_size[0] / 2.0,
+                    y - progress * FLOATER_RISE - floater.slot as f32 * FLOATER_SPACING,
+                ],
+                color,
+                &floater.text,
+            );
+        }
+    }
+}
@@ -1,3 +1,6 @@
+pub mod events;
+pub mod gamepad;
 pub mod gui_state;
+pub mod keybindings;
 pub mod parameters;
 pub mod settings;
@@ -0,0 +1,43 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use nat20_core::engine::{event::Event, game_state::GameState};
+
+/// Subscription to the events logged in the game state, which subscribes again
+/// if the game state is replaced, e.g. by loading a new map
+pub struct EventSubscription {
+    receiver: Option<Receiver<Event>>,
+}
+
+impl EventSubscription {
+    pub fn new() -> Self {
+        Self { receiver: None }
+    }
+
+    /// Events logged since the last call. The first call only subscribes, so
+    /// it never returns anything.
+    pub fn poll(&mut self, game_state: &mut GameState) -> Vec<Event> {
+        let mut events = Vec::new();
+        if let Some(receiver) = &self.receiver {
+            loop {
+                match receiver.try_recv() {
+                    Ok(event) => events.push(event),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.receiver = None;
+                        break;
+                    }
+                }
+            }
+        }
+        if self.receiver.is_none() {
+            self.receiver = Some(game_state.subscribe_events());
+        }
+        events
+    }
+
+    /// Stop listening until the next poll, so events don't pile up in the
+    /// meantime
+    pub fn unsubscribe(&mut self) {
+        self.receiver = None;
+    }
+}
@@ -0,0 +1,243 @@
+use gilrs::{Axis, Button, EventType, Gilrs};
+use imgui::{BackendFlags, ConfigFlags, Key, MouseButton};
+use tracing::{info, warn};
+
+/// Stick deflection below this is treated as the stick being centered
+pub const STICK_DEADZONE: f32 = 0.2;
+/// How far an analog trigger has to be pulled to count as pressed
+const TRIGGER_THRESHOLD: f32 = 0.5;
+
+fn button_key(button: Button) -> Option<Key> {
+    let key = match button {
+        Button::South => Key::GamepadFaceDown,
+        Button::East => Key::GamepadFaceRight,
+        Button::North => Key::GamepadFaceUp,
+        Button::West => Key::GamepadFaceLeft,
+        Button::LeftTrigger => Key::GamepadL1,
+        Button::RightTrigger => Key::GamepadR1,
+        Button::LeftTrigger2 => Key::GamepadL2,
+        Button::RightTrigger2 => Key::GamepadR2,
+        Button::LeftThumb => Key::GamepadL3,
+        Button::RightThumb => Key::GamepadR3,
+        Button::Select => Key::GamepadBack,
+        Button::Start => Key::GamepadStart,
+        Button::DPadUp => Key::GamepadDpadUp,
+        Button::DPadDown => Key::GamepadDpadDown,
+        Button::DPadLeft => Key::GamepadDpadLeft,
+        Button::DPadRight => Key::GamepadDpadRight,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Feeds controller input into imgui, so the UI can be navigated with a
+/// gamepad, and moves a virtual mouse cursor around the map with the left
+/// stick when no window has navigation focus.
+///
+/// Sticks are stored in screen space, i.e. positive Y is down.
+pub struct Gamepad {
+    gilrs: Option<Gilrs>,
+    enabled: bool,
+    connected: usize,
+    left_stick: [f32; 2],
+    right_stick: [f32; 2],
+    left_trigger: f32,
+}
+
+impl Gamepad {
+    pub fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(error) => {
+                warn!("Gamepad support unavailable: {}", error);
+                None
+            }
+        };
+        let connected = gilrs.as_ref().map_or(0, |gilrs| gilrs.gamepads().count());
+
+        Self {
+            gilrs,
+            enabled: true,
+            connected,
+            left_stick: [0.0, 0.0],
+            right_stick: [0.0, 0.0],
+            left_trigger: 0.0,
+        }
+    }
+
+    /// Whether a gamepad is connected and gamepad input is enabled
+    pub fn active(&self) -> bool {
+        self.enabled && self.connected > 0
+    }
+
+    pub fn left_stick(&self) -> [f32; 2] {
+        self.left_stick
+    }
+
+    pub fn right_stick(&self) -> [f32; 2] {
+        self.right_stick
+    }
+
+    /// Whether the radial action menu is being held open
+    pub fn radial_menu_held(&self) -> bool {
+        self.left_trigger > TRIGGER_THRESHOLD
+    }
+
+    /// Forward the controller events since the last frame to imgui. Returns
+    /// the new position of the virtual cursor, in logical pixels, if the left
+    /// stick moved it.
+    pub fn update(
+        &mut self,
+        io: &mut imgui::Io,
+        enabled: bool,
+        cursor_speed: f32,
+    ) -> Option<[f32; 2]> {
+        // Taken out while handling the events, since they need to update self
+        let Some(mut gilrs) = self.gilrs.take() else {
+            return None;
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            if !enabled {
+                continue;
+            }
+
+            match event.event {
+                EventType::Connected => {
+                    self.connected += 1;
+                    info!("Gamepad connected: {}", gilrs.gamepad(event.id).name());
+                }
+                EventType::Disconnected => {
+                    self.connected = self.connected.saturating_sub(1);
+                    info!("Gamepad disconnected");
+                }
+                EventType::ButtonPressed(button, _) => {
+                    self.button_event(io, button, true);
+                }
+                EventType::ButtonReleased(button, _) => {
+                    self.button_event(io, button, false);
+                }
+                EventType::ButtonChanged(button, value, _) => {
+                    // Analog triggers
+                    if let Some(key) = button_key(button) {
+                        io.add_key_analog_event(key, value > TRIGGER_THRESHOLD, value);
+                    }
+                    if button == Button::LeftTrigger2 {
+                        self.left_trigger = value;
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    self.axis_event(io, axis, value);
+                }
+                _ => {}
+            }
+        }
+        self.gilrs = Some(gilrs);
+
+        self.enabled = enabled;
+        if !enabled {
+            self.left_stick = [0.0, 0.0];
+            self.right_stick = [0.0, 0.0];
+            self.left_trigger = 0.0;
+        }
+
+        if !self.active() {
+            io.backend_flags.remove(BackendFlags::HAS_GAMEPAD);
+            io.config_flags.remove(ConfigFlags::NAV_ENABLE_GAMEPAD);
+            return None;
+        }
+        io.backend_flags.insert(BackendFlags::HAS_GAMEPAD);
+        io.config_flags.insert(ConfigFlags::NAV_ENABLE_GAMEPAD);
+
+        self.move_cursor(io, cursor_speed)
+    }
+
+    fn button_event(&mut self, io: &mut imgui::Io, button: Button, down: bool) {
+        if let Some(key) = button_key(button) {
+            io.add_key_event(key, down);
+        }
+
+        if button == Button::LeftTrigger2 {
+            self.left_trigger = if down { 1.0 } else { 0.0 };
+        }
+
+        // Outside of the windows the face buttons act as the mouse buttons, so
+        // the map can be clicked with the virtual cursor
+        if !io.nav_active {
+            match button {
+                Button::South => io.add_mouse_button_event(MouseButton::Left, down),
+                Button::East => io.add_mouse_button_event(MouseButton::Right, down),
+                _ => {}
+            }
+        }
+    }
+
+    fn axis_event(&mut self, io: &mut imgui::Io, axis: Axis, value: f32) {
+        let (stick, index, negative_key, positive_key) = match axis {
+            Axis::LeftStickX => (
+                &mut self.left_stick,
+                0,
+                Key::GamepadLStickLeft,
+                Key::GamepadLStickRight,
+            ),
+            Axis::LeftStickY => (
+                &mut self.left_stick,
+                1,
+                Key::GamepadLStickUp,
+                Key::GamepadLStickDown,
+            ),
+            Axis::RightStickX => (
+                &mut self.right_stick,
+                0,
+                Key::GamepadRStickLeft,
+                Key::GamepadRStickRight,
+            ),
+            Axis::RightStickY => (
+                &mut self.right_stick,
+                1,
+                Key::GamepadRStickUp,
+                Key::GamepadRStickDown,
+            ),
+            _ => return,
+        };
+
+        // gilrs reports up as positive Y
+        let value = if index == 1 { -value } else { value };
+        let value = if value.abs() < STICK_DEADZONE {
+            0.0
+        } else {
+            value
+        };
+        stick[index] = value;
+
+        io.add_key_analog_event(negative_key, value < 0.0, (-value).max(0.0));
+        io.add_key_analog_event(positive_key, value > 0.0, value.max(0.0));
+    }
+
+    fn move_cursor(&self, io: &mut imgui::Io, cursor_speed: f32) -> Option<[f32; 2]> {
+        // The stick is used for navigating the windows and picking from the
+        // radial menu, so only move the cursor when it isn't doing either
+        let [x, y] = self.left_stick;
+        if (x == 0.0 && y == 0.0) || io.nav_active || self.radial_menu_held() {
+            return None;
+        }
+
+        let [width, height] = io.display_size;
+        let [mouse_x, mouse_y] = io.mouse_pos;
+        let (start_x, start_y) = if mouse_x.is_finite() && mouse_x > -f32::MAX / 2.0 {
+            (mouse_x, mouse_y)
+        } else {
+            (width / 2.0, height / 2.0)
+        };
+
+        let step = cursor_speed * io.delta_time;
+        let cursor = [
+            (start_x + x * step).clamp(0.0, width),
+            (start_y + y * step).clamp(0.0, height),
+        ];
+        io.add_mouse_pos_event(cursor);
+        io.mouse_draw_cursor = true;
+
+        Some(cursor)
+    }
+}
@@ -3,20 +3,32 @@ use std::collections::{BTreeMap, HashMap};
 use glow::HasContext;
 use hecs::Entity;
 use imgui_glow_renderer::AutoRenderer;
-use nat20_core::systems::{geometry::RaycastResult, movement::PathResult};
-use parry3d::na::Vector3;
+use nat20_core::{
+    components::id::ActionId,
+    systems::{
+        geometry::RaycastResult,
+        movement::{MovementPreview, PathResult},
+    },
+};
+use parry3d::na::{Point3, Vector3};
 use winit::window::Window;
 
 use crate::{
-    render::world::{
-        camera::OrbitCamera,
-        frame_uniforms::FrameUniforms,
-        grid::GridRenderer,
-        line::LineRenderer,
-        mesh::{Mesh, MeshRenderMode},
-        program::BasicProgram,
+    audio::AudioPlayer,
+    render::{
+        ui::{images::TextureCache, theme::Themes},
+        world::{
+            battle_map::MovementAnimation,
+            camera::OrbitCamera,
+            combat_feedback::CombatFeedback,
+            frame_uniforms::FrameUniforms,
+            grid::GridRenderer,
+            line::LineRenderer,
+            mesh::{Mesh, MeshRenderMode},
+            program::BasicProgram,
+        },
     },
-    state::settings::GuiSettings,
+    state::{gamepad::Gamepad, keybindings::KeyBindings, settings::GuiSettings},
     windows::anchor::WindowManager,
 };
 
@@ -34,6 +46,15 @@ pub struct GuiState {
     /// GUI settings, mostly used to configure various rendering options.
     pub settings: GuiSettings,
 
+    /// Which keys trigger which actions in the GUI.
+    pub keybindings: KeyBindings,
+
+    /// Controller input, forwarded to imgui every frame.
+    pub gamepad: Gamepad,
+
+    /// The available themes and which one is active.
+    pub themes: Themes,
+
     /// Manages the positioning of anchored windows.
     pub window_manager: WindowManager,
 
@@ -44,6 +65,9 @@ pub struct GuiState {
     // TODO: Everything involving the mesh cache seems like a mess right now.
     pub mesh_cache: BTreeMap<String, Mesh>,
 
+    /// Portraits and tokens loaded from disk.
+    pub textures: TextureCache,
+
     /// The result of a raycast from the cursor into the 3D world.
     /// This is updated every frame, and can be used by various UI components
     /// to determine what the cursor is pointing at.
@@ -63,6 +87,23 @@ pub struct GuiState {
     /// The render mode for each creature entity. Mopstly used for highlighting
     /// creatures within an AoE, etc.
     pub creature_render_mode: HashMap<Entity, MeshRenderMode>,
+
+    /// Preview of moving an entity to the point under the cursor. Pathfinding
+    /// is fairly expensive, so the preview is only updated when the cursor moves.
+    pub movement_preview: Option<(Entity, Point3<f32>, MovementPreview)>,
+
+    /// Tokens currently sliding along the path they just moved.
+    pub movement_animations: HashMap<Entity, MovementAnimation>,
+
+    /// The order of the actions in each entity's action bar. The order is kept
+    /// here so it survives the action bar being closed and reopened.
+    pub hotbar_order: HashMap<Entity, Vec<ActionId>>,
+
+    /// Damage numbers, hit/miss popups and token flashes on the battle map.
+    pub combat_feedback: CombatFeedback,
+
+    /// Sound effects and background music.
+    pub audio: AudioPlayer,
 }
 
 impl GuiState {
@@ -104,15 +145,32 @@ impl GuiState {
             grid_renderer,
             camera: OrbitCamera::new(),
             settings: GuiSettings::default(),
+            keybindings: KeyBindings::default(),
+            gamepad: Gamepad::new(),
+            themes: Themes::new(),
             window_manager: WindowManager::new(),
             path_cache: HashMap::new(),
             mesh_cache: BTreeMap::new(),
+            textures: TextureCache::new(),
             cursor_ray_result: None,
             selected_entity: None,
             creature_render_mode: HashMap::default(),
+            movement_preview: None,
+            movement_animations: HashMap::new(),
+            hotbar_order: HashMap::new(),
+            combat_feedback: CombatFeedback::new(),
+            audio: AudioPlayer::new(),
         }
     }
 
+    /// Recreate the imgui renderer so it uploads the rebuilt font atlas. The
+    /// new GL context shares its resources with the old one, so everything
+    /// else (meshes, programs, etc.) stays valid.
+    pub fn reload_font_texture(&mut self, gl: glow::Context, imgui_context: &mut imgui::Context) {
+        self.ig_renderer =
+            AutoRenderer::new(gl, imgui_context).expect("failed to recreate imgui OpenGL renderer");
+    }
+
     pub fn gl_context(&self) -> &glow::Context {
         self.ig_renderer.gl_context()
     }
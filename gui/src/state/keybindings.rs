@@ -0,0 +1,358 @@
+use std::{collections::BTreeMap, fmt};
+
+use imgui::{Key, TableFlags};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::render::ui::{
+    text::TextKind,
+    utils::{ImguiRenderableMut, render_button_selectable},
+};
+
+/// Everything in the GUI that can be triggered from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumIter)]
+pub enum KeyAction {
+    #[strum(to_string = "Toggle Settings")]
+    ToggleSettings,
+    #[strum(to_string = "End Turn")]
+    EndTurn,
+    #[strum(to_string = "Character Sheet")]
+    CharacterSheet,
+    #[strum(to_string = "Hotbar 1")]
+    Hotbar1,
+    #[strum(to_string = "Hotbar 2")]
+    Hotbar2,
+    #[strum(to_string = "Hotbar 3")]
+    Hotbar3,
+    #[strum(to_string = "Hotbar 4")]
+    Hotbar4,
+    #[strum(to_string = "Hotbar 5")]
+    Hotbar5,
+    #[strum(to_string = "Hotbar 6")]
+    Hotbar6,
+    #[strum(to_string = "Hotbar 7")]
+    Hotbar7,
+    #[strum(to_string = "Hotbar 8")]
+    Hotbar8,
+    #[strum(to_string = "Hotbar 9")]
+    Hotbar9,
+    #[strum(to_string = "Camera Forward")]
+    CameraForward,
+    #[strum(to_string = "Camera Back")]
+    CameraBack,
+    #[strum(to_string = "Camera Left")]
+    CameraLeft,
+    #[strum(to_string = "Camera Right")]
+    CameraRight,
+    #[strum(to_string = "Camera Rotate Left")]
+    CameraRotateLeft,
+    #[strum(to_string = "Camera Rotate Right")]
+    CameraRotateRight,
+    #[strum(to_string = "Camera Zoom In")]
+    CameraZoomIn,
+    #[strum(to_string = "Camera Zoom Out")]
+    CameraZoomOut,
+    /// Held while dragging with the middle mouse button to pan instead of orbit
+    #[strum(to_string = "Camera Pan (hold)")]
+    CameraPan,
+}
+
+impl KeyAction {
+    const HOTBAR: [KeyAction; 9] = [
+        KeyAction::Hotbar1,
+        KeyAction::Hotbar2,
+        KeyAction::Hotbar3,
+        KeyAction::Hotbar4,
+        KeyAction::Hotbar5,
+        KeyAction::Hotbar6,
+        KeyAction::Hotbar7,
+        KeyAction::Hotbar8,
+        KeyAction::Hotbar9,
+    ];
+
+    /// The action for the given hotbar slot, starting from zero
+    pub fn hotbar(slot: usize) -> Option<KeyAction> {
+        Self::HOTBAR.get(slot).copied()
+    }
+
+    fn default_binding(&self) -> KeyBinding {
+        let key = match self {
+            KeyAction::ToggleSettings => Key::Escape,
+            KeyAction::EndTurn => Key::Space,
+            KeyAction::CharacterSheet => Key::C,
+            KeyAction::Hotbar1 => Key::Alpha1,
+            KeyAction::Hotbar2 => Key::Alpha2,
+            KeyAction::Hotbar3 => Key::Alpha3,
+            KeyAction::Hotbar4 => Key::Alpha4,
+            KeyAction::Hotbar5 => Key::Alpha5,
+            KeyAction::Hotbar6 => Key::Alpha6,
+            KeyAction::Hotbar7 => Key::Alpha7,
+            KeyAction::Hotbar8 => Key::Alpha8,
+            KeyAction::Hotbar9 => Key::Alpha9,
+            KeyAction::CameraForward => Key::W,
+            KeyAction::CameraBack => Key::S,
+            KeyAction::CameraLeft => Key::A,
+            KeyAction::CameraRight => Key::D,
+            KeyAction::CameraRotateLeft => Key::Q,
+            KeyAction::CameraRotateRight => Key::E,
+            KeyAction::CameraZoomIn => Key::Equal,
+            KeyAction::CameraZoomOut => Key::Minus,
+            KeyAction::CameraPan => Key::LeftShift,
+        };
+        KeyBinding::new(key)
+    }
+}
+
+/// A key and the modifiers that have to be held for it to trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    fn modifiers_held(&self, ui: &imgui::Ui) -> bool {
+        let io = ui.io();
+        // Modifier keys bound on their own shouldn't require themselves to
+        // *not* be held
+        if is_modifier(self.key) {
+            return true;
+        }
+        io.key_ctrl == self.ctrl && io.key_shift == self.shift && io.key_alt == self.alt
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", key_name(self.key))
+    }
+}
+
+fn is_modifier(key: Key) -> bool {
+    matches!(
+        key,
+        Key::LeftCtrl
+            | Key::RightCtrl
+            | Key::LeftShift
+            | Key::RightShift
+            | Key::LeftAlt
+            | Key::RightAlt
+            | Key::LeftSuper
+            | Key::RightSuper
+            | Key::ModCtrl
+            | Key::ModShift
+            | Key::ModAlt
+            | Key::ModSuper
+    )
+}
+
+/// Keys that can be bound. Mouse buttons, gamepad inputs and the combined
+/// modifier keys are left out, since they aren't really "keys".
+fn is_bindable(key: Key) -> bool {
+    let name = format!("{:?}", key);
+    !(name.starts_with("Mouse")
+        || name.starts_with("Gamepad")
+        || name.starts_with("Mod")
+        || name.starts_with("Reserved"))
+}
+
+fn key_name(key: Key) -> String {
+    let name = format!("{:?}", key);
+    match name.strip_prefix("Alpha") {
+        Some(digit) => digit.to_string(),
+        None => name,
+    }
+}
+
+/// Maps the keyboard to actions in the GUI, so the keys can be rebound
+/// instead of being hard-coded in every window.
+pub struct KeyBindings {
+    bindings: BTreeMap<KeyAction, KeyBinding>,
+    /// Action currently waiting for a new key to be pressed
+    rebinding: Option<KeyAction>,
+}
+
+impl KeyBindings {
+    pub fn binding(&self, action: KeyAction) -> &KeyBinding {
+        &self.bindings[&action]
+    }
+
+    pub fn set(&mut self, action: KeyAction, binding: KeyBinding) {
+        self.bindings.insert(action, binding);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Actions which share a binding with at least one other action
+    pub fn conflicts(&self) -> Vec<(KeyAction, KeyAction)> {
+        let mut conflicts = Vec::new();
+        for (i, (action, binding)) in self.bindings.iter().enumerate() {
+            for (other_action, other_binding) in self.bindings.iter().skip(i + 1) {
+                if binding == other_binding {
+                    conflicts.push((*action, *other_action));
+                }
+            }
+        }
+        conflicts
+    }
+
+    fn is_conflicting(&self, action: KeyAction) -> bool {
+        let binding = self.binding(action);
+        self.bindings
+            .iter()
+            .any(|(other, other_binding)| *other != action && other_binding == binding)
+    }
+
+    fn can_trigger(&self, ui: &imgui::Ui) -> bool {
+        // Typing in a text field or picking a new key shouldn't trigger anything
+        self.rebinding.is_none() && !ui.io().want_text_input
+    }
+
+    /// Whether the key bound to the action was pressed this frame
+    pub fn pressed(&self, ui: &imgui::Ui, action: KeyAction) -> bool {
+        let binding = self.binding(action);
+        self.can_trigger(ui)
+            && ui.is_key_pressed_no_repeat(binding.key)
+            && binding.modifiers_held(ui)
+    }
+
+    /// Whether the key bound to the action is currently held down
+    pub fn down(&self, ui: &imgui::Ui, action: KeyAction) -> bool {
+        let binding = self.binding(action);
+        self.can_trigger(ui) && ui.is_key_down(binding.key) && binding.modifiers_held(ui)
+    }
+
+    fn capture_binding(&mut self, ui: &imgui::Ui) {
+        let Some(action) = self.rebinding else {
+            return;
+        };
+
+        let Some(key) = Key::VARIANTS
+            .iter()
+            .copied()
+            .filter(|key| is_bindable(*key))
+            .find(|key| ui.is_key_pressed_no_repeat(*key))
+        else {
+            return;
+        };
+
+        // Wait for a non-modifier key so e.g. Ctrl+E can be bound. Held actions
+        // like panning the camera are the exception.
+        if is_modifier(key) && action != KeyAction::CameraPan {
+            return;
+        }
+
+        self.rebinding = None;
+        if key == Key::Escape && action != KeyAction::ToggleSettings {
+            // Cancel
+            return;
+        }
+
+        let io = ui.io();
+        let binding = if is_modifier(key) {
+            KeyBinding::new(key)
+        } else {
+            KeyBinding {
+                key,
+                ctrl: io.key_ctrl,
+                shift: io.key_shift,
+                alt: io.key_alt,
+            }
+        };
+        self.set(action, binding);
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: KeyAction::iter()
+                .map(|action| (action, action.default_binding()))
+                .collect(),
+            rebinding: None,
+        }
+    }
+}
+
+impl ImguiRenderableMut for KeyBindings {
+    fn render_mut(&mut self, ui: &imgui::Ui) {
+        self.capture_binding(ui);
+
+        if let Some(action) = self.rebinding {
+            ui.text(format!("Press a key for '{}' (Escape to cancel)", action));
+        }
+
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() {
+            ui.text_colored(
+                TextKind::Red.color(),
+                format!("{} conflicting key binding(s)", conflicts.len()),
+            );
+        }
+
+        if let Some(_table) = ui.begin_table_with_flags(
+            "Key Bindings",
+            3,
+            TableFlags::SIZING_FIXED_FIT | TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H,
+        ) {
+            for action in KeyAction::iter() {
+                let _id = ui.push_id(action.to_string());
+                ui.table_next_row();
+
+                ui.table_next_column();
+                if self.is_conflicting(action) {
+                    ui.text_colored(TextKind::Red.color(), action.to_string());
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Another action uses the same key");
+                    }
+                } else {
+                    ui.text(action.to_string());
+                }
+
+                ui.table_next_column();
+                let rebinding = self.rebinding == Some(action);
+                let label = if rebinding {
+                    "...".to_string()
+                } else {
+                    self.binding(action).to_string()
+                };
+                if render_button_selectable(ui, label, [120.0, 0.0], rebinding) {
+                    self.rebinding = Some(action);
+                }
+
+                ui.table_next_column();
+                let default_binding = action.default_binding();
+                let disabled_token = ui.begin_disabled(*self.binding(action) == default_binding);
+                if ui.small_button("Reset") {
+                    self.set(action, default_binding);
+                }
+                disabled_token.end();
+            }
+        }
+
+        if ui.button("Reset All") {
+            self.reset();
+        }
+    }
+}
@@ -1,9 +1,36 @@
+pub static ACCESSIBILITY_ECHO_TO_STDOUT: &str = "accessibility.echo_to_stdout";
+pub static ACCESSIBILITY_TEXT_LOG: &str = "accessibility.text_log";
+pub static AUDIO_CROSSFADE_SECONDS: &str = "audio.music.crossfade_seconds";
+pub static AUDIO_ENABLED: &str = "audio.enabled";
+pub static AUDIO_MASTER_VOLUME: &str = "audio.volume.master";
+pub static AUDIO_MUSIC_VOLUME: &str = "audio.volume.music";
+pub static AUDIO_SFX_VOLUME: &str = "audio.volume.sound_effects";
+pub static GAMEPAD_CURSOR_SPEED: &str = "input.gamepad.cursor_speed";
+pub static GAMEPAD_ENABLED: &str = "input.gamepad.enabled";
+pub static REACTION_AUTO_DECLINE_TIMEOUT: &str = "gameplay.reactions.auto_decline_timeout";
+pub static RENDER_BESTIARY: &str = "render.ui.tools.bestiary_window";
+pub static RENDER_CALENDAR: &str = "render.ui.time.calendar_window";
 pub static RENDER_CAMERA_DEBUG: &str = "render.ui.camera.debug_window";
+pub static RENDER_COMBAT_LOG: &str = "render.ui.combat.combat_log_window";
+pub static RENDER_DICE_ROLLER: &str = "render.ui.tools.dice_roller_window";
+pub static RENDER_ENCOUNTER_PRESETS: &str = "render.ui.world.encounter_presets_window";
+pub static RENDER_GM_CONSOLE: &str = "render.ui.tools.gm_console_window";
 pub static RENDER_GRID: &str = "render.ui.world.render_grid";
 pub static RENDER_IMGUI_ABOUT: &str = "render.ui.imgui.show_about_window";
 pub static RENDER_IMGUI_DEMO: &str = "render.ui.imgui.show_demo_window";
 pub static RENDER_IMGUI_METRICS: &str = "render.ui.imgui.show_metrics_window";
 pub static RENDER_IMGUI_USER_GUIDE: &str = "render.ui.imgui.show_user_guide";
+pub static RENDER_ITEM_CATALOG: &str = "render.ui.tools.item_catalog_window";
+pub static RENDER_LIGHTS: &str = "render.ui.world.render_lights";
 pub static RENDER_LINE_OF_SIGHT_DEBUG: &str = "render.ui.line_of_sight.debug_window";
+pub static RENDER_MAP_GENERATOR: &str = "render.ui.world.map_generator_window";
+pub static RENDER_MOVEMENT_RANGE: &str = "render.ui.world.render_movement_range";
 pub static RENDER_NAVIGATION_DEBUG: &str = "render.ui.navigation.debug_window";
 pub static RENDER_NAVIGATION_NAVMESH: &str = "render.ui.navigation.render_navmesh";
+pub static RENDER_PARTY: &str = "render.ui.party.party_window";
+pub static RENDER_SCENES: &str = "render.ui.world.scenes_window";
+pub static RENDER_SPELL_COMPENDIUM: &str = "render.ui.tools.spell_compendium_window";
+pub static RENDER_TOKENS: &str = "render.ui.world.render_tokens";
+pub static RENDER_WORLD_MAP: &str = "render.ui.world.world_map_window";
+pub static UI_FONT_SIZE: &str = "render.ui.display.font_size";
+pub static UI_SCALE: &str = "render.ui.display.scale";
@@ -5,6 +5,7 @@ use imgui::{InputTextFlags, TreeNodeFlags};
 use crate::{
     render::ui::utils::{ImguiRenderableMut, ImguiRenderableMutWithContext},
     state::{self},
+    utils::UiScale,
 };
 
 #[derive(Clone, Debug)]
@@ -286,6 +287,111 @@ impl Default for GuiSettings {
                 state::parameters::RENDER_LINE_OF_SIGHT_DEBUG.to_string(),
                 Setting::Bool(false),
             ),
+            (
+                state::parameters::RENDER_CALENDAR.to_string(),
+                Setting::Bool(true),
+            ),
+            (
+                state::parameters::RENDER_MAP_GENERATOR.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_ENCOUNTER_PRESETS.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_SCENES.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_WORLD_MAP.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_BESTIARY.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_DICE_ROLLER.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_SPELL_COMPENDIUM.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_ITEM_CATALOG.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_GM_CONSOLE.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_PARTY.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::RENDER_COMBAT_LOG.to_string(),
+                Setting::Bool(true),
+            ),
+            (
+                state::parameters::REACTION_AUTO_DECLINE_TIMEOUT.to_string(),
+                Setting::F32(0.0),
+            ),
+            (
+                state::parameters::RENDER_TOKENS.to_string(),
+                Setting::Bool(true),
+            ),
+            (
+                state::parameters::RENDER_MOVEMENT_RANGE.to_string(),
+                Setting::Bool(true),
+            ),
+            (
+                state::parameters::RENDER_LIGHTS.to_string(),
+                Setting::Bool(true),
+            ),
+            (
+                state::parameters::UI_FONT_SIZE.to_string(),
+                Setting::F32(UiScale::DEFAULT_FONT_SIZE),
+            ),
+            (state::parameters::UI_SCALE.to_string(), Setting::F32(1.0)),
+            (
+                state::parameters::ACCESSIBILITY_TEXT_LOG.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::ACCESSIBILITY_ECHO_TO_STDOUT.to_string(),
+                Setting::Bool(false),
+            ),
+            (
+                state::parameters::GAMEPAD_ENABLED.to_string(),
+                Setting::Bool(true),
+            ),
+            (
+                state::parameters::GAMEPAD_CURSOR_SPEED.to_string(),
+                Setting::F32(600.0),
+            ),
+            (
+                state::parameters::AUDIO_ENABLED.to_string(),
+                Setting::Bool(true),
+            ),
+            (
+                state::parameters::AUDIO_MASTER_VOLUME.to_string(),
+                Setting::F32(1.0),
+            ),
+            (
+                state::parameters::AUDIO_MUSIC_VOLUME.to_string(),
+                Setting::F32(0.5),
+            ),
+            (
+                state::parameters::AUDIO_SFX_VOLUME.to_string(),
+                Setting::F32(0.8),
+            ),
+            (
+                state::parameters::AUDIO_CROSSFADE_SECONDS.to_string(),
+                Setting::F32(2.0),
+            ),
         ]))
     }
 }
@@ -89,11 +89,71 @@ pub fn imgui_init(window: &Window) -> (WinitPlatform, imgui::Context) {
         imgui_winit_support::HiDpiMode::Rounded,
     );
 
-    imgui_context
-        .fonts()
-        .add_font(&[imgui::FontSource::DefaultFontData { config: None }]);
-
-    imgui_context.io_mut().font_global_scale = (1.0 / winit_platform.hidpi_factor()) as f32;
+    let base_style = imgui_context.style().clone();
+    apply_ui_scale(
+        &mut imgui_context,
+        &base_style,
+        &UiScale {
+            hidpi_factor: winit_platform.hidpi_factor(),
+            ..UiScale::default()
+        },
+    );
 
     (winit_platform, imgui_context)
 }
+
+/// Size of the text and widgets in the UI. The font is rasterized at the
+/// physical pixel size, so text stays sharp on high-DPI displays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiScale {
+    /// Font size in logical pixels, before scaling
+    pub font_size: f32,
+    /// User-facing scale applied to both the font and the widgets
+    pub scale: f32,
+    /// Scale factor of the monitor the window is on
+    pub hidpi_factor: f64,
+}
+
+impl UiScale {
+    pub const DEFAULT_FONT_SIZE: f32 = 13.0;
+
+    pub fn new(font_size: f32, scale: f32, hidpi_factor: f64) -> Self {
+        Self {
+            font_size: font_size.clamp(6.0, 48.0),
+            scale: scale.clamp(0.5, 4.0),
+            hidpi_factor,
+        }
+    }
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_FONT_SIZE, 1.0, 1.0)
+    }
+}
+
+/// Rebuild the font atlas and scale the style. `base_style` is the unscaled
+/// style, since scaling the current one repeatedly would compound. Note that
+/// the renderer has to reload the font texture afterwards.
+pub fn apply_ui_scale(
+    imgui_context: &mut imgui::Context,
+    base_style: &imgui::Style,
+    ui_scale: &UiScale,
+) {
+    let font_pixels = ui_scale.font_size * ui_scale.scale * ui_scale.hidpi_factor as f32;
+
+    let fonts = imgui_context.fonts();
+    fonts.clear();
+    fonts.add_font(&[imgui::FontSource::DefaultFontData {
+        config: Some(imgui::FontConfig {
+            size_pixels: font_pixels,
+            ..imgui::FontConfig::default()
+        }),
+    }]);
+
+    imgui_context.io_mut().font_global_scale = (1.0 / ui_scale.hidpi_factor) as f32;
+
+    let style = imgui_context.style_mut();
+    *style = base_style.clone();
+    style.scale_all_sizes(ui_scale.scale);
+}
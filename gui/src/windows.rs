@@ -1,11 +1,25 @@
+pub mod accessible_log;
 pub mod action_bar;
 pub mod anchor;
+pub mod bestiary;
+pub mod calendar;
+pub mod combat_log;
 pub mod creature_debug;
 pub mod creature_right_click;
+pub mod decisions;
+pub mod dice_roller;
 pub mod encounter;
+pub mod encounter_presets;
+pub mod gm_console;
+pub mod item_catalog;
 pub mod level_up;
 pub mod line_of_sight_debug;
 pub mod main_menu;
+pub mod map_generator;
 pub mod navigation_debug;
+pub mod party;
 pub mod reactions;
+pub mod scenes;
 pub mod spawn_predefined;
+pub mod spell_compendium;
+pub mod world_map;
@@ -0,0 +1,176 @@
+use std::collections::{HashMap, VecDeque};
+
+use hecs::Entity;
+use imgui::ChildFlags;
+use nat20_core::{
+    components::id::Name,
+    engine::{encounter::EncounterId, game_state::GameState},
+    systems,
+};
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            engine,
+            text::{TextKind, TextSegment},
+            utils::ImguiRenderable,
+        },
+    },
+    state::{self, events::EventSubscription, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+/// Oldest lines are dropped once the log grows past this
+const MAX_LINES: usize = 500;
+
+/// Plain, linear text version of everything happening in the game, meant for
+/// players using a screen reader or who can't rely on colors. Combat events
+/// come from the structured event stream, and changes in the UI state (like
+/// the selected creature or whose turn it is) are announced alongside them.
+///
+/// The lines can optionally be echoed to stdout, so a terminal screen reader
+/// can pick them up.
+pub struct AccessibleLogWindow {
+    events: EventSubscription,
+    lines: VecDeque<String>,
+    auto_scroll: bool,
+    /// Last announced selection and turns, so only changes are announced
+    selected_entity: Option<Entity>,
+    current_turns: HashMap<EncounterId, Entity>,
+}
+
+impl AccessibleLogWindow {
+    pub fn new() -> Self {
+        Self {
+            events: EventSubscription::new(),
+            lines: VecDeque::new(),
+            auto_scroll: true,
+            selected_entity: None,
+            current_turns: HashMap::new(),
+        }
+    }
+
+    pub fn announce(&mut self, text: String, echo: bool) {
+        if echo {
+            println!("{}", text);
+        }
+
+        self.lines.push_back(text);
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    fn update(&mut self, gui_state: &GuiState, game_state: &mut GameState, echo: bool) {
+        let events = self.events.poll(game_state);
+
+        for event in &events {
+            if let Some(text) = engine::event_plain_text(&game_state.world, event) {
+                self.announce(text, echo);
+            }
+        }
+
+        let name = |entity: Entity| {
+            systems::helpers::get_component::<Name>(&game_state.world, entity).to_string()
+        };
+
+        let selected_entity = gui_state
+            .selected_entity
+            .filter(|entity| game_state.world.contains(*entity));
+        if selected_entity != self.selected_entity {
+            self.selected_entity = selected_entity;
+            let text = match selected_entity {
+                Some(entity) => format!("Selected {}.", name(entity)),
+                None => "Selection cleared.".to_string(),
+            };
+            self.announce(text, echo);
+        }
+
+        self.current_turns
+            .retain(|encounter_id, _| game_state.encounters.contains_key(encounter_id));
+        let mut turn_changes = game_state
+            .encounters
+            .iter()
+            .filter(|(encounter_id, encounter)| {
+                self.current_turns.get(*encounter_id) != Some(&encounter.current_entity())
+            })
+            .map(|(encounter_id, encounter)| (encounter_id.clone(), encounter.current_entity()))
+            .collect::<Vec<_>>();
+        turn_changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (encounter_id, entity) in turn_changes {
+            self.current_turns.insert(encounter_id, entity);
+            self.announce(format!("It is {}'s turn.", name(entity)), echo);
+        }
+    }
+
+    fn text(&self) -> String {
+        self.lines.iter().cloned().collect::<Vec<_>>().join("\n")
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for AccessibleLogWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut log_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::ACCESSIBILITY_TEXT_LOG);
+        let echo = *gui_state
+            .settings
+            .get::<bool>(state::parameters::ACCESSIBILITY_ECHO_TO_STDOUT);
+
+        if !log_open && !echo {
+            // Drop the subscription so events don't pile up while disabled
+            self.events.unsubscribe();
+            return;
+        }
+
+        self.update(gui_state, game_state, echo);
+
+        if !log_open {
+            return;
+        }
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Accessible Log",
+            &anchor::BOTTOM_RIGHT,
+            AUTO_RESIZE,
+            &mut log_open,
+            || {
+                ui.child_window("Accessible Log Content")
+                    .child_flags(ChildFlags::BORDERS)
+                    .size([400.0, 300.0])
+                    .build(|| {
+                        for line in &self.lines {
+                            TextSegment::new(line, TextKind::Normal)
+                                .wrap_text(true)
+                                .render(ui);
+                        }
+
+                        if self.auto_scroll && ui.scroll_y() >= ui.scroll_max_y() - 5.0 {
+                            ui.set_scroll_here_y_with_ratio(1.0);
+                        }
+                    });
+
+                ui.checkbox("Auto-scroll", &mut self.auto_scroll);
+                ui.same_line();
+                if ui.button("Copy to clipboard") {
+                    ui.set_clipboard_text(self.text());
+                }
+                ui.same_line();
+                if ui.button("Clear") {
+                    self.lines.clear();
+                }
+            },
+        );
+
+        gui_state
+            .settings
+            .set(state::parameters::ACCESSIBILITY_TEXT_LOG, log_open);
+    }
+}
@@ -1,18 +1,21 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, f32::consts::TAU};
 
 use hecs::Entity;
-use imgui::{ChildFlags, MouseButton};
+use imgui::{ChildFlags, Key, MouseButton, StyleColor};
 use nat20_core::{
     components::{
         actions::{
-            action::{ActionCondition, ActionContext, ActionKind, ActionMap},
-            targeting::{AreaShape, TargetInstance, TargetingContext, TargetingKind},
+            action::{ActionContext, ActionKind, ActionMap},
+            preview::{ActionPreview, CheckPreview, DamageRange},
+            targeting::{ItemTarget, TargetInstance, TargetingContext, TargetingKind},
         },
         d20::RollMode,
         id::{ActionId, Name, ResourceId},
+        items::inventory::ItemContainer,
         modifier::Modifiable,
         resource::{RechargeRule, ResourceAmount, ResourceAmountMap, ResourceMap},
         speed::Speed,
+        spells::metamagic::Metamagic,
     },
     engine::{
         event::{ActionData, ActionDecision, ActionDecisionKind, ActionPromptKind},
@@ -21,6 +24,7 @@ use nat20_core::{
     registry::registry::ResourcesRegistry,
     systems::{
         self,
+        actions::ActionUsabilityError,
         geometry::{RaycastHit, RaycastHitKind},
         movement::{PathResult, TargetPathFindingResult},
     },
@@ -34,16 +38,21 @@ use crate::{
         common::utils::RenderableMutWithContext,
         ui::{
             components::{LOW_HEALTH_BG_COLOR, LOW_HEALTH_COLOR, SPEED_COLOR, SPEED_COLOR_BG},
-            text::{TextKind, TextSegments},
+            text::{TextKind, TextSegment, TextSegments},
             utils::{
                 ImguiRenderable, ImguiRenderableWithContext, ProgressBarColor,
-                render_button_disabled_conditionally, render_button_with_padding,
-                render_capacity_meter, render_progress_bar, roman_numeral,
+                SELECTED_BUTTON_COLOR, render_button_disabled_conditionally,
+                render_button_selectable, render_button_with_padding, render_capacity_meter,
+                render_progress_bar, roman_numeral,
             },
         },
-        world::mesh::MeshRenderMode,
+        world::{battle_map, mesh::MeshRenderMode},
+    },
+    state::{
+        gamepad::Gamepad,
+        gui_state::GuiState,
+        keybindings::{KeyAction, KeyBindings},
     },
-    state::gui_state::GuiState,
     windows::anchor::{AUTO_RESIZE, BOTTOM_CENTER, WindowManager},
 };
 
@@ -59,24 +68,43 @@ pub enum ActionBarState {
         action: ActionId,
         contexts_and_costs: Vec<(ActionContext, ResourceAmountMap)>,
     },
+    Amount {
+        /// The action with the chosen context, before the variable cost is resolved
+        action: ActionData,
+        amount: u8,
+    },
+    Metamagic {
+        /// The action with the chosen context, before any metamagic is applied
+        action: ActionData,
+        options: Vec<Metamagic>,
+        selected: Option<Metamagic>,
+    },
     Targets {
         action: ActionData,
         potential_target: Option<(TargetInstance, TargetPathFindingResult)>,
     },
+    Item {
+        /// The action with every other target already chosen
+        action: ActionData,
+        owner: Entity,
+        items: Vec<ItemTarget>,
+    },
 }
 
 pub struct ActionBarWindow {
     pub state: ActionBarState,
     pub entity: Entity,
+    gamepad_hotbar: GamepadHotbar,
 }
 
 impl ActionBarWindow {
     pub fn new(game_state: &mut GameState, entity: Entity) -> Self {
         Self {
             state: ActionBarState::Action {
-                actions: systems::actions::all_actions(&game_state.world, entity),
+                actions: game_state.actions(entity),
             },
             entity,
+            gamepad_hotbar: GamepadHotbar::default(),
         }
     }
 
@@ -88,7 +116,10 @@ impl ActionBarWindow {
                 return true;
             }
             if let Some(prompt) = game_state.next_prompt_entity(self.entity)
-                && matches!(prompt.kind, ActionPromptKind::Reactions { .. })
+                && matches!(
+                    prompt.kind,
+                    ActionPromptKind::Reactions { .. } | ActionPromptKind::Decision { .. }
+                )
             {
                 return true;
             }
@@ -126,13 +157,33 @@ impl RenderableMutWithContext<&mut GameState> for ActionBarWindow {
 
                 match &mut self.state {
                     ActionBarState::Action { actions } => {
-                        render_actions(ui, game_state, self.entity, &mut new_state, actions);
+                        render_actions(
+                            ui,
+                            game_state,
+                            self.entity,
+                            &mut new_state,
+                            actions,
+                            &gui_state.keybindings,
+                            gui_state.hotbar_order.entry(self.entity).or_default(),
+                            &gui_state.gamepad,
+                            &mut self.gamepad_hotbar,
+                        );
                         ui.same_line();
                         render_resources(ui, game_state, self.entity);
                     }
 
                     ActionBarState::Variant { variants } => {
-                        render_actions(ui, game_state, self.entity, &mut new_state, variants);
+                        render_actions(
+                            ui,
+                            game_state,
+                            self.entity,
+                            &mut new_state,
+                            variants,
+                            &gui_state.keybindings,
+                            gui_state.hotbar_order.entry(self.entity).or_default(),
+                            &gui_state.gamepad,
+                            &mut self.gamepad_hotbar,
+                        );
                         ui.separator();
                         right_click_cancel(ui, gui_state, game_state, &mut new_state, self.entity);
                     }
@@ -152,6 +203,33 @@ impl RenderableMutWithContext<&mut GameState> for ActionBarWindow {
                         );
                     }
 
+                    ActionBarState::Amount { action, amount } => {
+                        render_amount_selection(
+                            ui,
+                            gui_state,
+                            game_state,
+                            &mut new_state,
+                            action,
+                            amount,
+                        );
+                    }
+
+                    ActionBarState::Metamagic {
+                        action,
+                        options,
+                        selected,
+                    } => {
+                        render_metamagic_selection(
+                            ui,
+                            gui_state,
+                            game_state,
+                            &mut new_state,
+                            action,
+                            options,
+                            selected,
+                        );
+                    }
+
                     ActionBarState::Targets {
                         action,
                         potential_target,
@@ -165,6 +243,22 @@ impl RenderableMutWithContext<&mut GameState> for ActionBarWindow {
                             potential_target,
                         );
                     }
+
+                    ActionBarState::Item {
+                        action,
+                        owner,
+                        items,
+                    } => {
+                        render_item_selection(
+                            ui,
+                            gui_state,
+                            game_state,
+                            &mut new_state,
+                            action,
+                            *owner,
+                            items,
+                        );
+                    }
                 }
 
                 if let Some(state) = new_state {
@@ -181,57 +275,242 @@ impl RenderableMutWithContext<&mut GameState> for ActionBarWindow {
     }
 }
 
+const HOTBAR_DRAG_DROP: &str = "HotbarAction";
+
+/// Distance from the center of the screen to the actions in the radial menu
+const RADIAL_MENU_RADIUS: f32 = 140.0;
+
+/// Hotbar selection with a gamepad. The bumpers move between the slots and the
+/// right trigger uses the selected action. Holding the left trigger opens a
+/// radial menu instead, where the action is picked with the left stick and
+/// used when the trigger is released.
+#[derive(Debug, Default)]
+struct GamepadHotbar {
+    slot: usize,
+    radial_selection: Option<usize>,
+}
+
+impl GamepadHotbar {
+    /// Returns the slot of the action to use this frame, if any
+    fn update(&mut self, ui: &imgui::Ui, gamepad: &Gamepad, labels: &[String]) -> Option<usize> {
+        let slots = labels.len();
+        if slots == 0 {
+            self.radial_selection = None;
+            return None;
+        }
+
+        self.slot = self.slot.min(slots - 1);
+        if ui.is_key_pressed(Key::GamepadR1) {
+            self.slot = (self.slot + 1) % slots;
+        }
+        if ui.is_key_pressed(Key::GamepadL1) {
+            self.slot = (self.slot + slots - 1) % slots;
+        }
+
+        if gamepad.radial_menu_held() {
+            let [x, y] = gamepad.left_stick();
+            if x != 0.0 || y != 0.0 {
+                // The first slot is straight up, and the rest follow clockwise
+                let angle = x.atan2(-y).rem_euclid(TAU);
+                let sector = TAU / slots as f32;
+                self.radial_selection = Some((angle / sector).round() as usize % slots);
+            }
+            render_radial_menu(ui, labels, self.radial_selection);
+            return None;
+        }
+
+        if let Some(selection) = self.radial_selection.take() {
+            self.slot = selection;
+            return Some(selection);
+        }
+
+        if ui.is_key_pressed_no_repeat(Key::GamepadR2) {
+            return Some(self.slot);
+        }
+
+        None
+    }
+}
+
+fn render_radial_menu(ui: &imgui::Ui, labels: &[String], selection: Option<usize>) {
+    let [width, height] = ui.io().display_size;
+    let center = [width / 2.0, height / 2.0];
+    let draw_list = ui.get_foreground_draw_list();
+
+    draw_list
+        .add_circle(center, RADIAL_MENU_RADIUS * 1.4, [0.0, 0.0, 0.0, 0.6])
+        .filled(true)
+        .build();
+
+    for (i, label) in labels.iter().enumerate() {
+        let angle = i as f32 / labels.len() as f32 * TAU;
+        let text_size = ui.calc_text_size(label);
+        let min = [
+            center[0] + angle.sin() * RADIAL_MENU_RADIUS - text_size[0] / 2.0,
+            center[1] - angle.cos() * RADIAL_MENU_RADIUS - text_size[1] / 2.0,
+        ];
+
+        if selection == Some(i) {
+            draw_list
+                .add_rect(
+                    [min[0] - 4.0, min[1] - 2.0],
+                    [min[0] + text_size[0] + 4.0, min[1] + text_size[1] + 2.0],
+                    SELECTED_BUTTON_COLOR,
+                )
+                .rounding(4.0)
+                .filled(true)
+                .build();
+        }
+
+        draw_list.add_text(min, ui.style_color(StyleColor::Text), label);
+    }
+}
+
+/// Actions in the action bar are grouped by which part of the action economy
+/// they use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ActionCategory {
+    Action,
+    BonusAction,
+    Reaction,
+    Other,
+}
+
+impl ActionCategory {
+    fn from_cost(cost: &ResourceAmountMap) -> Self {
+        if cost.contains_key(&ResourceId::new("nat20_core", "resource.action")) {
+            ActionCategory::Action
+        } else if cost.contains_key(&ResourceId::new("nat20_core", "resource.bonus_action")) {
+            ActionCategory::BonusAction
+        } else if cost.contains_key(&ResourceId::new("nat20_core", "resource.reaction")) {
+            ActionCategory::Reaction
+        } else {
+            ActionCategory::Other
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ActionCategory::Action => "Actions",
+            ActionCategory::BonusAction => "Bonus Actions",
+            ActionCategory::Reaction => "Reactions",
+            ActionCategory::Other => "Other",
+        }
+    }
+}
+
+fn usability_error_text(error: &ActionUsabilityError) -> String {
+    match error {
+        ActionUsabilityError::EntityNotAlive(_) => "Cannot act while incapacitated".to_string(),
+        ActionUsabilityError::Dismissed(_) => "Cannot act while dismissed".to_string(),
+        ActionUsabilityError::FamiliarCannotAttack => "Familiars cannot attack".to_string(),
+        ActionUsabilityError::OnCooldown(recharge) => {
+            format!("On cooldown, recharges on {}", recharge)
+        }
+        ActionUsabilityError::NotEnoughResources(cost) => format!(
+            "Not enough resources: {}",
+            cost.keys()
+                .map(|resource| resource.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ActionUsabilityError::ResourceNotFound(resource) => {
+            format!("Missing resource: {}", resource)
+        }
+        ActionUsabilityError::MissingMaterialComponent(material) => format!(
+            "Missing material component: {} ({})",
+            material.description, material.cost
+        ),
+        ActionUsabilityError::TargetingError(error) => format!("{:?}", error),
+        ActionUsabilityError::AntimagicField => {
+            "Cannot cast spells inside an antimagic field".to_string()
+        }
+        ActionUsabilityError::ConstraintNotMet(constraint) => constraint.to_string(),
+        ActionUsabilityError::Charmed(_) => "Cannot harm the creature that charmed you".to_string(),
+        ActionUsabilityError::Controlled(_) => {
+            "Not allowed by the creature controlling you".to_string()
+        }
+        ActionUsabilityError::UnknownMetamagic(metamagic) => {
+            format!("Unknown metamagic: {}", metamagic)
+        }
+        ActionUsabilityError::MetamagicNotPaid(sorcery_points) => {
+            format!("Metamagic costs {} Sorcery Points", sorcery_points)
+        }
+    }
+}
+
 fn render_actions(
     ui: &imgui::Ui,
     game_state: &mut GameState,
     entity: Entity,
     new_state: &mut Option<ActionBarState>,
     actions: &mut ActionMap,
+    keybindings: &KeyBindings,
+    hotbar_order: &mut Vec<ActionId>,
+    gamepad: &Gamepad,
+    gamepad_hotbar: &mut GamepadHotbar,
 ) {
     ui.child_window("Actions")
         .child_flags(
             ChildFlags::ALWAYS_AUTO_RESIZE | ChildFlags::AUTO_RESIZE_X | ChildFlags::AUTO_RESIZE_Y,
         )
         .build(|| {
-            ui.separator_with_text("Actions");
-
-            for (action_id, contexts_and_costs) in actions {
-                // Don't render reactions
-                if matches!(
+            // New actions are added to the end of the hotbar
+            let mut new_actions = actions
+                .keys()
+                .filter(|action_id| !hotbar_order.contains(action_id))
+                .cloned()
+                .collect::<Vec<_>>();
+            new_actions.sort();
+            hotbar_order.extend(new_actions);
+
+            let mut entries = Vec::new();
+
+            for (action_id, contexts_and_costs) in actions.iter_mut() {
+                let is_reaction = matches!(
                     systems::actions::get_action(action_id).unwrap().kind(),
                     ActionKind::Reaction { .. }
-                ) {
-                    continue;
-                }
-                // Don't render actions that either:
-                // 1. Can only be used as reactions
-                // 2. Cost a resource which never recharges and which is the entity
-                //    currently doesn't have any of. This probably means the action
-                //    is only usable under certain conditions which aren't currently
-                //    met.
+                );
+                // Don't render actions that cost a resource which never recharges
+                // and which the entity currently doesn't have any of. This probably
+                // means the action is only usable under certain conditions which
+                // aren't currently met.
 
                 // TODO: This works for now to hide stuff like Reapply Hex, but it's
                 // definitely not ideal. Probably better to "mark" these actions somehow
                 if contexts_and_costs.iter().all(|(_, cost)| {
-                    cost.contains_key(&ResourceId::new("nat20_core", "resource.reaction"))
-                        || cost.iter().any(|(res_id, amount)| {
-                            let resources = systems::helpers::get_component::<ResourceMap>(
-                                &game_state.world,
-                                entity,
-                            );
-                            !resources.can_afford(res_id, amount)
-                                && ResourcesRegistry::get(res_id).unwrap().recharge
-                                    == RechargeRule::Never
-                        })
+                    cost.iter().any(|(res_id, amount)| {
+                        let resources = systems::helpers::get_component::<ResourceMap>(
+                            &game_state.world,
+                            entity,
+                        );
+                        !resources.can_afford(res_id, amount)
+                            && ResourcesRegistry::get(res_id).unwrap().recharge
+                                == RechargeRule::Never
+                    })
                 }) {
                     continue;
                 }
 
-                for (_, cost) in contexts_and_costs.iter() {}
+                let category = if is_reaction {
+                    ActionCategory::Reaction
+                } else {
+                    ActionCategory::from_cost(&contexts_and_costs[0].1)
+                };
+
+                if category == ActionCategory::Reaction {
+                    entries.push((
+                        action_id.clone(),
+                        category,
+                        Err("Can only be used as a reaction".to_string()),
+                    ));
+                    continue;
+                }
 
-                let mut action_usable = false;
+                let mut usability = Err(String::new());
                 for (context, cost) in contexts_and_costs.iter_mut() {
-                    for effect in systems::effects::effects(&game_state.world, entity).iter() {
+                    for effect in systems::effects::active_effects(&game_state.world, entity).iter()
+                    {
                         (effect.effect().on_resource_cost)(
                             &game_state.world,
                             entity,
@@ -240,26 +519,85 @@ fn render_actions(
                             cost,
                         );
                     }
-                    if systems::actions::action_usable(
+                    // Note to self: *don't* break here! We need to update
+                    // the costs for all contexts even if one is usable
+                    match systems::actions::action_usable(
                         &game_state.world,
                         entity,
                         action_id,
                         context,
                         cost,
-                    )
-                    .is_ok()
-                    {
-                        // Note to self: *don't* break here! We need to update
-                        // the costs for all contexts even if one is usable
-                        action_usable = true;
-                    } else {
-                        continue;
+                    ) {
+                        Ok(()) => usability = Ok(()),
+                        Err(error) => {
+                            if let Err(reason) = &mut usability
+                                && reason.is_empty()
+                            {
+                                *reason = usability_error_text(&error);
+                            }
+                        }
                     }
                 }
 
-                let disabled_token = ui.begin_disabled(!action_usable);
+                entries.push((action_id.clone(), category, usability));
+            }
+
+            entries.sort_by_key(|(action_id, category, _)| {
+                (
+                    *category,
+                    hotbar_order.iter().position(|id| id == action_id),
+                )
+            });
+
+            let gamepad_action = if gamepad.active() {
+                let labels = entries
+                    .iter()
+                    .filter(|(_, category, _)| *category != ActionCategory::Reaction)
+                    .map(|(action_id, _, _)| action_id.to_string())
+                    .collect::<Vec<_>>();
+                gamepad_hotbar.update(ui, gamepad, &labels)
+            } else {
+                None
+            };
+
+            let mut current_category = None;
+            let mut slot = 0;
+            let mut reorder = None;
+
+            for (action_id, category, usability) in &entries {
+                if current_category != Some(*category) {
+                    ui.separator_with_text(category.label());
+                    current_category = Some(*category);
+                } else {
+                    ui.same_line();
+                }
+
+                let hotbar_slot = if *category != ActionCategory::Reaction {
+                    slot += 1;
+                    Some(slot - 1)
+                } else {
+                    None
+                };
+                let hotkey = hotbar_slot.and_then(KeyAction::hotbar);
+
+                let label = if let Some(hotkey) = hotkey {
+                    format!("{} {}", keybindings.binding(hotkey), action_id)
+                } else {
+                    action_id.to_string()
+                };
+
+                let gamepad_selected = gamepad.active() && hotbar_slot == Some(gamepad_hotbar.slot);
+
+                let disabled_token = ui.begin_disabled(usability.is_err());
+                let clicked = render_button_selectable(ui, label, [0.0, 0.0], gamepad_selected);
+                disabled_token.end();
+
+                let hotkey_pressed = usability.is_ok()
+                    && (hotkey.is_some_and(|hotkey| keybindings.pressed(ui, hotkey))
+                        || (hotbar_slot.is_some() && hotbar_slot == gamepad_action));
 
-                if ui.button(&action_id.to_string()) {
+                if clicked || hotkey_pressed {
+                    let contexts_and_costs = actions.get_mut(action_id).unwrap();
                     let action = systems::actions::get_action(action_id).unwrap();
 
                     match action.kind() {
@@ -276,26 +614,70 @@ fn render_actions(
                         }
 
                         _ => {
-                            select_action(entity, new_state, action_id, contexts_and_costs);
+                            select_action(
+                                game_state,
+                                entity,
+                                new_state,
+                                action_id,
+                                contexts_and_costs,
+                            );
                         }
                     }
                 }
 
-                disabled_token.end();
+                // Actions can be dragged onto each other to reorder the hotbar
+                if let Some(tooltip) = ui
+                    .drag_drop_source_config(HOTBAR_DRAG_DROP)
+                    .begin_payload(hotbar_order.iter().position(|id| id == action_id))
+                {
+                    ui.text(action_id.to_string());
+                    tooltip.end();
+                }
+                if let Some(target) = ui.drag_drop_target() {
+                    if let Some(Ok(payload)) = target.accept_payload::<Option<usize>, _>(
+                        HOTBAR_DRAG_DROP,
+                        imgui::DragDropFlags::empty(),
+                    ) && let Some(source) = payload.data
+                    {
+                        reorder = Some((source, action_id.clone()));
+                    }
+                    target.pop();
+                }
 
-                if ui.is_item_hovered() {
+                if ui.is_item_hovered_with_flags(imgui::HoveredFlags::ALLOW_WHEN_DISABLED) {
                     ui.tooltip(|| {
-                        let (context, cost) = &contexts_and_costs[0];
+                        let (context, cost) = &actions[action_id][0];
                         (action_id, context, cost)
                             .render_with_context(ui, (&game_state.world, entity));
+
+                        if let Err(reason) = usability
+                            && !reason.is_empty()
+                        {
+                            ui.separator();
+                            ui.text_colored(TextKind::Red.color(), reason);
+                        }
                     });
                 }
             }
 
+            if let Some((source, target)) = reorder {
+                let action_id = hotbar_order.remove(source);
+                let index = hotbar_order
+                    .iter()
+                    .position(|id| *id == target)
+                    .unwrap_or(hotbar_order.len());
+                hotbar_order.insert(index, action_id);
+            }
+
             ui.separator();
 
             if game_state.in_combat.contains_key(&entity) {
-                if ui.button("End Turn") {
+                if ui.button(format!(
+                    "End Turn ({})",
+                    keybindings.binding(KeyAction::EndTurn)
+                )) || keybindings.pressed(ui, KeyAction::EndTurn)
+                    || (gamepad.active() && ui.is_key_pressed_no_repeat(Key::GamepadStart))
+                {
                     game_state.end_turn(entity);
                 }
             }
@@ -303,22 +685,24 @@ fn render_actions(
 }
 
 fn select_action(
+    game_state: &GameState,
     entity: Entity,
     new_state: &mut Option<ActionBarState>,
     action_id: &ActionId,
     contexts_and_costs: &mut Vec<(ActionContext, HashMap<ResourceId, ResourceAmount>)>,
 ) {
     if contexts_and_costs.len() == 1 {
-        *new_state = Some(ActionBarState::Targets {
-            action: ActionData::new(
+        select_context(
+            game_state,
+            new_state,
+            ActionData::new(
                 entity,
                 action_id.clone(),
                 contexts_and_costs[0].0.clone(),
                 contexts_and_costs[0].1.clone(),
                 Vec::new(),
             ),
-            potential_target: None,
-        });
+        );
     } else {
         *new_state = Some(ActionBarState::Context {
             action: action_id.clone(),
@@ -327,6 +711,47 @@ fn select_action(
     }
 }
 
+/// Once the context is known the action either moves on to metamagic selection
+/// (if the caster has any options for the spell) or straight to targeting
+fn select_context(
+    game_state: &GameState,
+    new_state: &mut Option<ActionBarState>,
+    action: ActionData,
+) {
+    if let Some((min, _)) = systems::actions::variable_amount_range(
+        &game_state.world,
+        action.actor,
+        &action.resource_cost,
+    ) {
+        *new_state = Some(ActionBarState::Amount {
+            action,
+            amount: min,
+        });
+        return;
+    }
+
+    let options = systems::spells::available_metamagic(
+        &game_state.world,
+        action.actor,
+        &action.action_id,
+        &action.context,
+        &action.resource_cost,
+    );
+
+    *new_state = Some(if options.is_empty() {
+        ActionBarState::Targets {
+            action,
+            potential_target: None,
+        }
+    } else {
+        ActionBarState::Metamagic {
+            action,
+            options,
+            selected: None,
+        }
+    });
+}
+
 fn render_resources(ui: &imgui::Ui, game_state: &mut GameState, entity: Entity) {
     ui.child_window("Resources")
         .child_flags(
@@ -357,6 +782,17 @@ fn render_resources(ui: &imgui::Ui, game_state: &mut GameState, entity: Entity)
                     color_empty_bg: LOW_HEALTH_BG_COLOR,
                 }),
             );
+
+            ui.separator_with_text("Attacks");
+            let mut non_lethal = systems::health::is_non_lethal(&game_state.world, entity);
+            if ui.checkbox("Non-lethal", &mut non_lethal) {
+                systems::health::set_non_lethal(&mut game_state.world, entity, non_lethal);
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text(
+                    "Creatures reduced to 0 HP by your melee attacks are knocked out instead of killed",
+                );
+            }
         });
 }
 
@@ -384,6 +820,10 @@ fn render_context_selection(
                 render_button_with_padding(ui, format!("{}", slot).as_str(), [10.0, 10.0])
             }
 
+            ActionContext::Spell { .. } if systems::spells::is_ritual_casting(context, cost) => {
+                render_button_with_padding(ui, "Ritual", [10.0, 10.0])
+            }
+
             ActionContext::Spell { level, .. } => {
                 let style = ui.push_style_var(imgui::StyleVar::ButtonTextAlign([0.5, 0.5]));
                 let clicked = ui.button_with_size(roman_numeral(*level), [30.0, 30.0]);
@@ -391,20 +831,25 @@ fn render_context_selection(
                 clicked
             }
 
+            ActionContext::Variable { amount, .. } => {
+                render_button_with_padding(ui, amount.to_string().as_str(), [10.0, 10.0])
+            }
+
             ActionContext::Other => render_button_with_padding(ui, "Other", [10.0, 10.0]),
         };
 
         if clicked {
-            *new_state = Some(ActionBarState::Targets {
-                action: ActionData::new(
+            select_context(
+                game_state,
+                new_state,
+                ActionData::new(
                     actor,
                     action.clone(),
                     context.clone(),
                     cost.clone(),
                     Vec::new(),
                 ),
-                potential_target: None,
-            });
+            );
         }
 
         disabled_token.end();
@@ -421,6 +866,111 @@ fn render_context_selection(
     right_click_cancel(ui, gui_state, game_state, new_state, actor);
 }
 
+fn render_amount_selection(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    game_state: &mut GameState,
+    new_state: &mut Option<ActionBarState>,
+    action: &ActionData,
+    amount: &mut u8,
+) {
+    ui.text(format!("Select amount for: {}", action.action_id));
+
+    let Some((min, max)) = systems::actions::variable_amount_range(
+        &game_state.world,
+        action.actor,
+        &action.resource_cost,
+    ) else {
+        right_click_cancel(ui, gui_state, game_state, new_state, action.actor);
+        return;
+    };
+
+    ui.slider("Amount", min, max, amount);
+
+    if ui.button("Confirm") {
+        let mut action = action.clone();
+        if systems::actions::choose_amount(&mut action, *amount).is_ok() {
+            select_context(game_state, new_state, action);
+        }
+    }
+
+    ui.separator();
+
+    right_click_cancel(ui, gui_state, game_state, new_state, action.actor);
+}
+
+fn with_metamagic(action: &ActionData, metamagic: Option<Metamagic>) -> ActionData {
+    let mut action = action.clone();
+    if let Some(metamagic) = metamagic {
+        systems::spells::apply_metamagic(&mut action, metamagic);
+    }
+    action
+}
+
+fn render_metamagic_selection(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    game_state: &mut GameState,
+    new_state: &mut Option<ActionBarState>,
+    action: &ActionData,
+    options: &[Metamagic],
+    selected: &mut Option<Metamagic>,
+) {
+    ui.text(format!("Select metamagic for: {}", action.action_id));
+
+    let level = match &action.context {
+        ActionContext::Spell { level, .. } => *level,
+        _ => 0,
+    };
+
+    ui.radio_button("None", selected, None);
+    for option in options {
+        let can_afford = systems::resources::can_afford(
+            &game_state.world,
+            action.actor,
+            &with_metamagic(action, Some(*option)).resource_cost,
+        )
+        .0;
+
+        let disabled_token = ui.begin_disabled(!can_afford);
+        ui.radio_button(
+            format!("{} ({} SP)", option, option.sorcery_point_cost(level)),
+            selected,
+            Some(*option),
+        );
+        disabled_token.end();
+
+        if !can_afford && ui.is_item_hovered_with_flags(imgui::HoveredFlags::ALLOW_WHEN_DISABLED) {
+            ui.tooltip_text("Not enough Sorcery Points");
+        }
+    }
+
+    let action_with_metamagic = with_metamagic(action, *selected);
+    let can_afford = systems::resources::can_afford(
+        &game_state.world,
+        action.actor,
+        &action_with_metamagic.resource_cost,
+    )
+    .0;
+
+    if render_button_disabled_conditionally(
+        ui,
+        "Confirm",
+        [0.0, 0.0],
+        !can_afford,
+        "Cannot afford the selected metamagic",
+    ) {
+        *new_state = Some(ActionBarState::Targets {
+            action: action_with_metamagic,
+            potential_target: None,
+        });
+    }
+
+    ui.separator();
+
+    right_click_cancel(ui, gui_state, game_state, new_state, action.actor);
+}
+
 fn right_click_cancel(
     ui: &imgui::Ui,
     gui_state: &mut GuiState,
@@ -438,7 +988,7 @@ fn right_click_cancel(
 
     if ui.button("Cancel") || right_click_cancel {
         *new_state = Some(ActionBarState::Action {
-            actions: systems::actions::all_actions(&game_state.world, actor),
+            actions: game_state.actions(actor),
         });
     }
 }
@@ -461,6 +1011,7 @@ fn render_target_selection(
     );
 
     render_range_preview(gui_state, game_state, action, &targeting_context);
+    render_selected_targets(gui_state, action);
 
     let mut submit_action = false;
 
@@ -498,54 +1049,14 @@ fn render_target_selection(
             None
         };
 
-        match systems::actions::get_action(&action.action_id)
-            .unwrap()
-            .kind()
+        if let Some(potential_target) = &potential_target_instance
+            && let Some(target) = potential_target.entity()
+            && let Some(preview) = systems::actions::preview_on_target(game_state, action, target)
         {
-            ActionKind::Standard { condition, .. } => match condition {
-                ActionCondition::AttackRoll { attack_roll, .. } => {
-                    if let Some(potential_target) = &potential_target_instance
-                        && let TargetInstance::Entity(target) = potential_target
-                    {
-                        let mut attack_roll =
-                            attack_roll(&game_state.world, action.actor, *target, &action.context);
-                        for effect in
-                            systems::effects::effects(&game_state.world, action.actor).iter()
-                        {
-                            (effect.effect().pre_attack_roll)(
-                                &game_state.world,
-                                action.actor,
-                                &mut attack_roll,
-                            );
-                        }
-                        let target_ac = systems::loadout::armor_class(&game_state.world, *target);
-                        ui.tooltip(|| {
-                            ui.separator();
-
-                            let hitchance = attack_roll.hit_chance(
-                                &game_state.world,
-                                action.actor,
-                                target_ac.total() as u32,
-                            ) * 100.0;
-
-                            let text_kind =
-                                match attack_roll.d20_check.advantage_tracker().roll_mode() {
-                                    RollMode::Normal => TextKind::Normal,
-                                    RollMode::Advantage => TextKind::Green,
-                                    RollMode::Disadvantage => TextKind::Red,
-                                };
-
-                            TextSegments::new(vec![
-                                ("Hit chance:", TextKind::Normal),
-                                (&format!("{:.0}%", hitchance), text_kind),
-                            ])
-                            .render(ui);
-                        });
-                    }
-                }
-                _ => {}
-            },
-            _ => {}
+            ui.tooltip(|| {
+                ui.separator();
+                render_action_preview(ui, &preview);
+            });
         }
 
         match targeting_context.kind {
@@ -562,8 +1073,23 @@ fn render_target_selection(
                 if let Some(potential_target) = potential_target_instance {
                     if ui.is_mouse_clicked(MouseButton::Left) {
                         action.targets.clear();
-                        action.targets.push(potential_target.clone());
                         gui_state.cursor_ray_result.take();
+                        // Let the player pick which of the creature's items to target
+                        if let TargetInstance::Item { owner, .. } = potential_target
+                            && let Some(filter) = &targeting_context.allowed_items
+                        {
+                            let items =
+                                systems::inventory::item_targets(&game_state.world, *owner, filter);
+                            if items.len() > 1 {
+                                *new_state = Some(ActionBarState::Item {
+                                    action: action.clone(),
+                                    owner: *owner,
+                                    items,
+                                });
+                                return;
+                            }
+                        }
+                        action.targets.push(potential_target.clone());
                         submit_action = true;
                     }
                 }
@@ -608,40 +1134,66 @@ fn render_target_selection(
                 if let Some(potential_target) = potential_target_instance {
                     // 1. Render the area shape at the potential target location
                     let point = match &potential_target {
-                        TargetInstance::Entity(entity) => {
+                        TargetInstance::Entity(entity)
+                        | TargetInstance::Object(entity)
+                        | TargetInstance::Item { owner: entity, .. } => {
                             systems::geometry::get_foot_position(&game_state.world, *entity)
                                 .unwrap()
                         }
                         TargetInstance::Point(point) => *point,
                     };
-                    match &shape {
-                        AreaShape::Sphere { radius } => {
-                            gui_state.line_renderer.add_circle(
-                                [point.x, point.y, point.z],
-                                radius.get::<meter>(),
-                                [1.0, 1.0, 1.0],
-                            );
-                        }
-                        _ => { /* TODO other shapes */ }
-                    }
-                    // 2. Highlight entities within the area
                     let (shape, shape_pose) = shape.parry3d_shape(
                         &game_state.world,
                         action.actor,
                         fixed_on_actor,
                         &point,
                     );
+                    battle_map::render_area_outline(
+                        &mut gui_state.line_renderer,
+                        shape.as_ref(),
+                        &shape_pose,
+                        [1.0, 1.0, 1.0],
+                    );
+                    // 2. Highlight the entities that would be affected
+                    let mut potential_action = action.clone();
+                    potential_action.targets = vec![potential_target.clone()];
                     let affected_entities =
-                        systems::geometry::entities_in_shape(&game_state.world, shape, &shape_pose);
-                    for entity in affected_entities {
+                        systems::actions::get_targeted_entities(game_state, &potential_action);
+                    let friendly_fire =
+                        systems::actions::friendly_fire(game_state, &potential_action);
+                    for entity in &affected_entities {
+                        let color = if friendly_fire.contains(entity) {
+                            [1.0, 0.0, 0.0, 0.5]
+                        } else {
+                            [0.0, 1.0, 0.0, 0.5]
+                        };
                         gui_state.creature_render_mode.insert(
-                            entity,
-                            MeshRenderMode::MeshWithWireFrame {
-                                color: [0.0, 1.0, 0.0, 0.5],
-                                width: 3.0,
-                            },
+                            *entity,
+                            MeshRenderMode::MeshWithWireFrame { color, width: 3.0 },
                         );
                     }
+                    ui.tooltip(|| {
+                        ui.separator();
+                        ui.text(format!("Affected targets: {}", affected_entities.len()));
+                        if !friendly_fire.is_empty() {
+                            let allies = friendly_fire
+                                .iter()
+                                .map(|entity| {
+                                    systems::helpers::get_component::<Name>(
+                                        &game_state.world,
+                                        *entity,
+                                    )
+                                    .to_string()
+                                })
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            TextSegment::new(
+                                format!("Warning: this will hit allies ({})", allies),
+                                TextKind::Red,
+                            )
+                            .render(ui);
+                        }
+                    });
                     // 3. On left click, select all entities within the area as targets
                     if ui.is_mouse_clicked(MouseButton::Left) {
                         action.targets.clear();
@@ -665,29 +1217,7 @@ fn render_target_selection(
     }
 
     if submit_action {
-        let response_to = if let Some(prompt) = game_state.next_prompt_entity(action.actor)
-            && prompt.actors().contains(&action.actor)
-        {
-            info!("Submitting action in response to prompt: {:#?}", prompt);
-            Some(prompt.id)
-        } else {
-            None
-        };
-
-        let action_kind = ActionDecisionKind::Action {
-            action: action.clone(),
-        };
-
-        let result = if let Some(response_to) = response_to {
-            game_state.submit_decision(ActionDecision {
-                response_to,
-                kind: action_kind,
-            })
-        } else {
-            game_state.submit_decision(ActionDecision::without_response_to(action_kind))
-        };
-
-        info!("Submitted action decision: {:#?}", result);
+        submit(game_state, action);
     }
 
     // TODO: gui_state util function to handle checking for Some and taking?
@@ -703,11 +1233,186 @@ fn render_target_selection(
 
     if ui.button("Cancel") || right_click_cancel || submit_action {
         *new_state = Some(ActionBarState::Action {
-            actions: systems::actions::all_actions(&game_state.world, action.actor),
+            actions: game_state.actions(action.actor),
         });
     }
 }
 
+fn submit(game_state: &mut GameState, action: &ActionData) {
+    let response_to = if let Some(prompt) = game_state.next_prompt_entity(action.actor)
+        && prompt.actors().contains(&action.actor)
+    {
+        info!("Submitting action in response to prompt: {:#?}", prompt);
+        Some(prompt.id)
+    } else {
+        None
+    };
+
+    let action_kind = ActionDecisionKind::Action {
+        action: action.clone(),
+    };
+
+    let result = if let Some(response_to) = response_to {
+        game_state.submit_decision(ActionDecision {
+            response_to,
+            kind: action_kind,
+        })
+    } else {
+        game_state.submit_decision(ActionDecision::without_response_to(action_kind))
+    };
+
+    info!("Submitted action decision: {:#?}", result);
+}
+
+fn render_item_selection(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    game_state: &mut GameState,
+    new_state: &mut Option<ActionBarState>,
+    action: &ActionData,
+    owner: Entity,
+    items: &[ItemTarget],
+) {
+    ui.text(format!(
+        "Select item of {} for: {}",
+        systems::helpers::get_component::<Name>(&game_state.world, owner).as_str(),
+        action.action_id
+    ));
+
+    for (index, item) in items.iter().enumerate() {
+        let Some(instance) = systems::inventory::target_item(&game_state.world, owner, item) else {
+            continue;
+        };
+        if ui.button(format!("{}##{}", instance.item().name, index)) {
+            let mut action = action.clone();
+            action.targets.push(TargetInstance::Item {
+                owner,
+                item: item.clone(),
+            });
+            submit(game_state, &action);
+            *new_state = Some(ActionBarState::Action {
+                actions: game_state.actions(action.actor),
+            });
+        }
+    }
+
+    ui.separator();
+
+    right_click_cancel(ui, gui_state, game_state, new_state, action.actor);
+}
+
+fn render_action_preview(ui: &imgui::Ui, preview: &ActionPreview) {
+    let percentage = |chance: f64| format!("{:.0}%", chance * 100.0);
+
+    match &preview.check {
+        Some(CheckPreview::AttackRoll {
+            attack_roll,
+            armor_class,
+            hit_chance,
+            crit_chance,
+        }) => {
+            let text_kind = match attack_roll.d20_check.advantage_tracker().roll_mode() {
+                RollMode::Normal => TextKind::Normal,
+                RollMode::Advantage => TextKind::Green,
+                RollMode::Disadvantage => TextKind::Red,
+            };
+            TextSegments::new(vec![
+                ("Hit chance:", TextKind::Normal),
+                (&percentage(*hit_chance), text_kind),
+                (
+                    &format!("({} vs AC {})", attack_roll.d20_check, armor_class.total()),
+                    TextKind::Details,
+                ),
+            ])
+            .render(ui);
+            TextSegments::new(vec![
+                ("Critical chance:", TextKind::Normal),
+                (&percentage(*crit_chance), TextKind::Normal),
+            ])
+            .render(ui);
+        }
+
+        Some(CheckPreview::SavingThrow {
+            saving_throw_dc,
+            save_chance,
+        }) => {
+            TextSegments::new(vec![
+                ("Chance to fail save:", TextKind::Normal),
+                (&percentage(1.0 - save_chance), TextKind::Normal),
+                (
+                    &format!(
+                        "(DC {} {})",
+                        saving_throw_dc.dc.total(),
+                        saving_throw_dc.key
+                    ),
+                    TextKind::Details,
+                ),
+            ])
+            .render(ui);
+        }
+
+        Some(CheckPreview::SkillCheck {
+            skill_check_dc,
+            success_chance,
+        }) => {
+            TextSegments::new(vec![
+                ("Success chance:", TextKind::Normal),
+                (&percentage(*success_chance), TextKind::Normal),
+                (
+                    &format!("(DC {} {})", skill_check_dc.dc.total(), skill_check_dc.key),
+                    TextKind::Details,
+                ),
+            ])
+            .render(ui);
+        }
+
+        Some(CheckPreview::Contest {
+            contest,
+            defense,
+            success_chance,
+        }) => {
+            TextSegments::new(vec![
+                ("Success chance:", TextKind::Normal),
+                (&percentage(*success_chance), TextKind::Normal),
+                (
+                    &format!("({} vs {})", contest.skill, defense),
+                    TextKind::Details,
+                ),
+            ])
+            .render(ui);
+        }
+
+        None => {}
+    }
+
+    if let Some(damage) = &preview.damage {
+        let range = |range: &DamageRange| format!("{}-{}", range.min, range.max);
+        TextSegments::new(vec![
+            ("Damage:", TextKind::Normal),
+            (&range(&damage.full), TextKind::Normal),
+        ])
+        .render(ui);
+        if let Some(critical) = &damage.critical {
+            TextSegments::new(vec![
+                ("Critical damage:", TextKind::Normal),
+                (&range(critical), TextKind::Normal),
+            ])
+            .render(ui);
+        }
+        if let Some(reduced) = &damage.reduced {
+            let label = match preview.check {
+                Some(CheckPreview::AttackRoll { .. }) => "Damage on miss:",
+                _ => "Damage on save:",
+            };
+            TextSegments::new(vec![
+                (label, TextKind::Normal),
+                (&range(reduced), TextKind::Normal),
+            ])
+            .render(ui);
+        }
+    }
+}
+
 fn should_render_target_preview(targeting_context: &TargetingContext) -> bool {
     match &targeting_context.kind {
         TargetingKind::SelfTarget => false,
@@ -779,7 +1484,9 @@ fn render_target_path_preview(
         }
 
         let line_end = match target {
-            TargetInstance::Entity(entity) => {
+            TargetInstance::Entity(entity)
+            | TargetInstance::Object(entity)
+            | TargetInstance::Item { owner: entity, .. } => {
                 systems::geometry::get_shape(&game_state.world, *entity)
                     .map(|(_, shape_pose)| shape_pose.translation.vector.into())
                     .unwrap()
@@ -813,6 +1520,32 @@ fn render_range_preview(
     }
 }
 
+/// Highlight the targets that have already been selected for the action
+fn render_selected_targets(gui_state: &mut GuiState, action: &ActionData) {
+    for target in &action.targets {
+        match target {
+            TargetInstance::Entity(entity)
+            | TargetInstance::Object(entity)
+            | TargetInstance::Item { owner: entity, .. } => {
+                gui_state.creature_render_mode.insert(
+                    *entity,
+                    MeshRenderMode::MeshWithWireFrame {
+                        color: [1.0, 0.8, 0.0, 0.75],
+                        width: 3.0,
+                    },
+                );
+            }
+            TargetInstance::Point(point) => {
+                gui_state.line_renderer.add_circle(
+                    [point.x, point.y, point.z],
+                    0.2,
+                    [1.0, 0.8, 0.0],
+                );
+            }
+        }
+    }
+}
+
 fn update_potential_target(
     potential_target: &mut Option<(TargetInstance, TargetPathFindingResult)>,
     game_state: &mut GameState,
@@ -820,7 +1553,18 @@ fn update_potential_target(
     closest: &RaycastHit,
 ) {
     let closest_target = match &closest.kind {
-        RaycastHitKind::Creature(entity) => TargetInstance::Entity(*entity),
+        RaycastHitKind::Creature(entity) => {
+            // Actions which target items preview the creature's first matching
+            // item, and the player picks the item once the creature is chosen
+            let targeting = systems::actions::targeting_context(
+                &game_state.world,
+                action.actor,
+                &action.action_id,
+                &action.context,
+            );
+            systems::actions::target_for_entity(&game_state.world, &targeting, *entity)
+                .unwrap_or_else(|| TargetInstance::from_entity(&game_state.world, *entity))
+        }
         RaycastHitKind::World => TargetInstance::Point(closest.poi),
     };
 
@@ -0,0 +1,301 @@
+use hecs::{Entity, World};
+use imgui::{ChildFlags, MouseButton};
+use nat20_core::{
+    components::{
+        id::MonsterId,
+        level::Level,
+        species::{CreatureSize, CreatureType},
+    },
+    engine::{encounter::EncounterId, game_state::GameState},
+    entities::monster::MonsterTemplate,
+    registry::registry::MonstersRegistry,
+    systems::{self, generator::MonsterFilter, time::RestKind},
+};
+use parry3d::na::Point3;
+use strum::IntoEnumIterator;
+use tracing::info;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            entities::CreatureRenderMode,
+            utils::{
+                ImguiRenderableWithContext, render_button_disabled_conditionally,
+                render_option_combo,
+            },
+        },
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+/// Creature being placed in the world, and the encounter it should join once
+/// it has been placed
+struct Placement {
+    entity: Entity,
+    encounter: Option<EncounterId>,
+}
+
+pub struct BestiaryWindow {
+    filter: MonsterFilter,
+    selected: Option<MonsterId>,
+    /// World used to show the stat block of the selected creature, so the
+    /// existing renderables can be used without touching the game world.
+    preview_world: World,
+    preview: Option<(MonsterId, Entity)>,
+    /// Encounter to add creatures to
+    encounter: Option<EncounterId>,
+    placement: Option<Placement>,
+}
+
+impl BestiaryWindow {
+    pub fn new() -> Self {
+        Self {
+            filter: MonsterFilter::default(),
+            selected: None,
+            preview_world: World::new(),
+            preview: None,
+            encounter: None,
+            placement: None,
+        }
+    }
+
+    fn templates(&self) -> Vec<&'static MonsterTemplate> {
+        let mut templates = MonstersRegistry::values()
+            .filter(|template| self.filter.matches(template))
+            .collect::<Vec<_>>();
+        templates.sort_by(|a, b| {
+            a.challenge_rating
+                .total_level()
+                .cmp(&b.challenge_rating.total_level())
+                .then_with(|| a.name.as_str().cmp(b.name.as_str()))
+        });
+        templates
+    }
+
+    fn preview_entity(&mut self, template: &MonsterTemplate) -> Entity {
+        if let Some((id, entity)) = &self.preview
+            && *id == template.id
+        {
+            return *entity;
+        }
+
+        if let Some((_, entity)) = self.preview.take() {
+            let _ = self.preview_world.despawn(entity);
+        }
+        let entity = systems::generator::spawn_monster(&mut self.preview_world, template).id();
+        systems::time::on_rest_end(&mut self.preview_world, &[entity], &RestKind::Long);
+        self.preview = Some((template.id.clone(), entity));
+        entity
+    }
+
+    fn render_filters(&mut self, ui: &imgui::Ui) {
+        let width_token = ui.push_item_width(80.0);
+
+        let mut min_challenge_rating = self.filter.min_challenge_rating.unwrap_or(0) as i32;
+        if ui.input_int("Min CR", &mut min_challenge_rating).build() {
+            self.filter.min_challenge_rating =
+                Some(min_challenge_rating.clamp(0, 30) as u8).filter(|cr| *cr > 0);
+        }
+        ui.same_line();
+        let mut max_challenge_rating = self.filter.max_challenge_rating.unwrap_or(0) as i32;
+        if ui.input_int("Max CR", &mut max_challenge_rating).build() {
+            self.filter.max_challenge_rating =
+                Some(max_challenge_rating.clamp(0, 30) as u8).filter(|cr| *cr > 0);
+        }
+
+        width_token.end();
+        let width_token = ui.push_item_width(120.0);
+
+        let creature_types = CreatureType::iter().collect::<Vec<_>>();
+        if let Some(creature_type) = render_option_combo(
+            ui,
+            "Type",
+            &creature_types,
+            self.filter.creature_type.as_ref(),
+        ) {
+            self.filter.creature_type = creature_type;
+        }
+        ui.same_line();
+        let sizes = CreatureSize::iter().collect::<Vec<_>>();
+        if let Some(size) = render_option_combo(ui, "Size", &sizes, self.filter.size.as_ref()) {
+            self.filter.size = size;
+        }
+
+        width_token.end();
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for BestiaryWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut bestiary_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_BESTIARY);
+
+        if !bestiary_open {
+            if let Some(placement) = self.placement.take() {
+                let _ = game_state.world.despawn(placement.entity);
+            }
+            return;
+        }
+
+        // The encounter might have ended since the last frame
+        if let Some(encounter) = &self.encounter
+            && !game_state.encounters.contains_key(encounter)
+        {
+            self.encounter = None;
+        }
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Bestiary",
+            &anchor::CENTER_LEFT,
+            AUTO_RESIZE,
+            &mut bestiary_open,
+            || {
+                self.render_filters(ui);
+
+                let templates = self.templates();
+
+                ui.child_window("Bestiary List")
+                    .child_flags(ChildFlags::BORDERS | ChildFlags::AUTO_RESIZE_X)
+                    .size([0.0, 150.0])
+                    .build(|| {
+                        if templates.is_empty() {
+                            ui.text_disabled("No creatures match the filters");
+                        }
+
+                        for template in &templates {
+                            let selected = self.selected.as_ref() == Some(&template.id);
+                            if ui
+                                .selectable_config(format!(
+                                    "CR {} - {} ({} {})##{}",
+                                    template.challenge_rating.total_level(),
+                                    template.name.as_str(),
+                                    template.size,
+                                    template.creature_type,
+                                    template.id
+                                ))
+                                .selected(selected)
+                                .build()
+                            {
+                                self.selected = Some(template.id.clone());
+                            }
+                        }
+                    });
+
+                let Some(template) = self
+                    .selected
+                    .as_ref()
+                    .and_then(|id| MonstersRegistry::get(id))
+                else {
+                    return;
+                };
+
+                ui.separator_with_text(template.name.as_str());
+
+                let mut encounter_ids = game_state.encounters.keys().cloned().collect::<Vec<_>>();
+                encounter_ids.sort();
+
+                let spawn_clicked = ui.button("Spawn into world");
+                ui.same_line();
+
+                let mut encounter_index = self
+                    .encounter
+                    .as_ref()
+                    .and_then(|encounter| encounter_ids.iter().position(|id| id == encounter))
+                    .unwrap_or(0);
+                let add_clicked = render_button_disabled_conditionally(
+                    ui,
+                    "Add to encounter",
+                    [0.0, 0.0],
+                    encounter_ids.is_empty(),
+                    "There are no active encounters",
+                );
+                if !encounter_ids.is_empty() {
+                    ui.same_line();
+                    let width_token = ui.push_item_width(150.0);
+                    if ui.combo("##Encounter", &mut encounter_index, &encounter_ids, |id| {
+                        format!("Encounter {}", id).into()
+                    }) {
+                        self.encounter = encounter_ids.get(encounter_index).cloned();
+                    }
+                    width_token.end();
+                }
+
+                if (spawn_clicked || add_clicked) && self.placement.is_none() {
+                    let entity =
+                        systems::generator::spawn_monster(&mut game_state.world, template).id();
+                    systems::time::on_rest_end(&mut game_state.world, &[entity], &RestKind::Long);
+                    // Spawn it somewhere we can't see it, it follows the cursor
+                    // until it has been placed
+                    systems::geometry::teleport_to(
+                        &mut game_state.world,
+                        entity,
+                        &Point3::new(f32::MAX, f32::MAX, f32::MAX),
+                    );
+                    self.placement = Some(Placement {
+                        entity,
+                        encounter: add_clicked
+                            .then(|| encounter_ids.get(encounter_index).cloned())
+                            .flatten(),
+                    });
+                }
+
+                let preview = self.preview_entity(template);
+                preview
+                    .render_with_context(ui, (&self.preview_world, &CreatureRenderMode::Inspect));
+            },
+        );
+
+        if let Some(placement) = self.placement.take() {
+            ui.tooltip(|| {
+                ui.text("LEFT-CLICK: Place here");
+                ui.text("RIGHT-CLICK: Cancel");
+            });
+
+            let mut placed = false;
+            if ui.is_mouse_clicked(MouseButton::Right) {
+                gui_state.cursor_ray_result.take();
+                let _ = game_state.world.despawn(placement.entity);
+                placed = true;
+            } else if let Some(raycast) = &gui_state.cursor_ray_result
+                && let Some(raycast_world) = raycast.world_hit()
+                && let Some(navmesh_point) = systems::geometry::navmesh_nearest_point(
+                    &game_state.geometry,
+                    raycast_world.poi,
+                )
+            {
+                systems::geometry::teleport_to_ground(
+                    &mut game_state.world,
+                    &game_state.geometry,
+                    placement.entity,
+                    &navmesh_point,
+                );
+
+                if ui.is_mouse_clicked(MouseButton::Left) {
+                    gui_state.cursor_ray_result.take();
+                    if let Some(encounter) = &placement.encounter {
+                        info!("Adding {:?} to encounter {}", placement.entity, encounter);
+                        game_state.add_to_encounter(encounter, placement.entity);
+                    }
+                    placed = true;
+                }
+            }
+
+            if !placed {
+                self.placement = Some(placement);
+            }
+        }
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_BESTIARY, bestiary_open);
+    }
+}
@@ -0,0 +1,90 @@
+use nat20_core::{
+    components::time::TimeDuration,
+    engine::game_state::GameState,
+    systems::{self, time::TimeActivity},
+};
+use tracing::warn;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext, ui::utils::render_button_disabled_conditionally,
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+pub struct CalendarWindow {
+    pub hours: i32,
+    pub activity: TimeActivity,
+}
+
+impl CalendarWindow {
+    pub fn new() -> Self {
+        Self {
+            hours: 1,
+            activity: TimeActivity::Travel,
+        }
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for CalendarWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut calendar_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_CALENDAR);
+
+        if !calendar_open {
+            return;
+        }
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Calendar",
+            &anchor::TOP_RIGHT,
+            AUTO_RESIZE,
+            &mut calendar_open,
+            || {
+                ui.text(game_state.clock.to_string());
+
+                ui.separator_with_text("Pass Time");
+
+                for (label, activity) in [
+                    ("Travel", TimeActivity::Travel),
+                    ("Downtime", TimeActivity::Downtime),
+                ] {
+                    ui.radio_button(label, &mut self.activity, activity);
+                    ui.same_line();
+                }
+                ui.new_line();
+
+                let width_token = ui.push_item_width(100.0);
+                ui.input_int("Hours", &mut self.hours).build();
+                width_token.end();
+                self.hours = self.hours.max(1);
+
+                if render_button_disabled_conditionally(
+                    ui,
+                    "Pass Time",
+                    [0.0, 0.0],
+                    !game_state.in_combat.is_empty(),
+                    "Time cannot pass while an encounter is running",
+                ) && let Err(error) = systems::time::pass_time(
+                    game_state,
+                    TimeDuration::from_hours(self.hours as u32),
+                    self.activity,
+                ) {
+                    warn!("Failed to pass time: {:?}", error);
+                }
+            },
+        );
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_CALENDAR, calendar_open);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use imgui::ChildFlags;
+use nat20_core::engine::{
+    encounter::EncounterId,
+    event::{Event, EventId},
+    game_state::GameState,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            engine::{self, CombatLogFilter, LogLevel},
+            text::{TextKind, TextSegment},
+            utils::{ImguiRenderable, ImguiRenderableWithContext},
+        },
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+pub struct CombatLogWindow {
+    pub filter: CombatLogFilter,
+    pub log_level: LogLevel,
+    pub auto_scroll: bool,
+    /// Only show events involving the selected entity
+    pub selected_only: bool,
+    /// Encounter whose log is shown. If not set the world log is shown
+    pub encounter: Option<EncounterId>,
+    /// Events whose full roll breakdown is expanded
+    expanded: HashSet<EventId>,
+}
+
+impl CombatLogWindow {
+    pub fn new() -> Self {
+        Self {
+            filter: CombatLogFilter::default(),
+            log_level: LogLevel::Info,
+            auto_scroll: true,
+            selected_only: false,
+            encounter: None,
+            expanded: HashSet::new(),
+        }
+    }
+
+    fn render_events(&mut self, ui: &imgui::Ui, game_state: &GameState, events: &[Event]) {
+        let events = events
+            .iter()
+            .filter(|event| self.filter.matches(event, &self.log_level))
+            .collect::<Vec<_>>();
+
+        for (i, event) in events.iter().enumerate() {
+            // Same as the event log, collapse e.g. 'ActionRequested' and
+            // 'ActionPerformed' into one entry at 'Info' level
+            if self.log_level == LogLevel::Info
+                && i < events.len() - 1
+                && engine::events_match(event, events[i + 1])
+            {
+                continue;
+            }
+
+            event.render_with_context(ui, &(&game_state.world, &self.log_level));
+
+            if !engine::has_breakdown(event) {
+                continue;
+            }
+
+            if ui.is_item_clicked() && !self.expanded.remove(&event.id) {
+                self.expanded.insert(event.id);
+            }
+
+            if self.expanded.contains(&event.id) {
+                ui.indent();
+                engine::render_event_breakdown(ui, event, &game_state.world);
+                ui.unindent();
+            } else if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    TextSegment::new("Click to show the full breakdown", TextKind::Details)
+                        .render(ui);
+                });
+            }
+        }
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for CombatLogWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut combat_log_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_COMBAT_LOG);
+
+        if !combat_log_open {
+            return;
+        }
+
+        // The encounter might have ended since the last frame
+        if let Some(encounter) = &self.encounter
+            && !game_state.encounters.contains_key(encounter)
+        {
+            self.encounter = None;
+        }
+
+        self.filter.entity = if self.selected_only {
+            gui_state.selected_entity
+        } else {
+            None
+        };
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Combat Log",
+            &anchor::BOTTOM_LEFT,
+            AUTO_RESIZE,
+            &mut combat_log_open,
+            || {
+                let mut encounter_ids = game_state.encounters.keys().cloned().collect::<Vec<_>>();
+                encounter_ids.sort();
+
+                let mut log_sources = vec!["World".to_string()];
+                log_sources.extend(encounter_ids.iter().map(|id| format!("Encounter {}", id)));
+                let mut log_source = self
+                    .encounter
+                    .as_ref()
+                    .and_then(|encounter| encounter_ids.iter().position(|id| id == encounter))
+                    .map_or(0, |index| index + 1);
+
+                let width_token = ui.push_item_width(150.0);
+                if ui.combo("Log source", &mut log_source, &log_sources[..], |s| {
+                    s.to_string().into()
+                }) {
+                    self.encounter = log_source
+                        .checked_sub(1)
+                        .and_then(|index| encounter_ids.get(index).cloned());
+                }
+                width_token.end();
+
+                ui.checkbox("Damage only", &mut self.filter.damage_only);
+                ui.same_line();
+                ui.checkbox("Selected character only", &mut self.selected_only);
+                ui.same_line();
+                ui.checkbox("Rolls", &mut self.filter.show_rolls);
+
+                let events = match &self.encounter {
+                    Some(encounter) => &game_state.encounters[encounter].combat_log().events,
+                    None => &game_state.event_log.events,
+                };
+
+                ui.child_window("Combat Log Content")
+                    .child_flags(
+                        ChildFlags::ALWAYS_AUTO_RESIZE
+                            | ChildFlags::AUTO_RESIZE_X
+                            | ChildFlags::BORDERS,
+                    )
+                    .size([0.0, 300.0])
+                    .build(|| {
+                        self.render_events(ui, game_state, events);
+
+                        if self.auto_scroll && ui.scroll_y() >= ui.scroll_max_y() - 5.0 {
+                            ui.set_scroll_here_y_with_ratio(1.0);
+                        }
+                    });
+
+                ui.checkbox("Auto-scroll", &mut self.auto_scroll);
+                ui.same_line();
+
+                let mut current_log_level = self.log_level.clone() as usize;
+                let width_token = ui.push_item_width(60.0);
+                if ui.combo(
+                    "Log level",
+                    &mut current_log_level,
+                    &LogLevel::iter().collect::<Vec<_>>()[..],
+                    |lvl| lvl.to_string().into(),
+                ) {
+                    self.log_level = current_log_level.into();
+                }
+                width_token.end();
+            },
+        );
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_COMBAT_LOG, combat_log_open);
+    }
+}
@@ -0,0 +1,111 @@
+use nat20_core::{
+    components::id::Name,
+    engine::{
+        event::{ActionDecision, ActionDecisionKind, ActionPromptId, DecisionRequest},
+        game_state::GameState,
+    },
+    systems,
+};
+use tracing::{error, info};
+
+use crate::{render::common::utils::RenderableMutWithContext, state::gui_state::GuiState};
+
+static DECISIONS_POPUP: &str = "Decision";
+
+pub enum DecisionWindowState {
+    Active {
+        prompt_id: ActionPromptId,
+        request: DecisionRequest,
+    },
+    Pending,
+}
+
+/// Lets the player answer the choices that come up while an action is being
+/// resolved
+pub struct DecisionsWindow {
+    state: DecisionWindowState,
+    open_popup: bool,
+}
+
+impl DecisionsWindow {
+    pub fn new() -> Self {
+        Self {
+            state: DecisionWindowState::Pending,
+            open_popup: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, DecisionWindowState::Active { .. })
+    }
+
+    pub fn activate(&mut self, prompt_id: ActionPromptId, request: &DecisionRequest) {
+        info!("Activating decisions window for prompt {:?}", prompt_id);
+        self.state = DecisionWindowState::Active {
+            prompt_id,
+            request: request.clone(),
+        };
+        self.open_popup = true;
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for DecisionsWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        _gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let DecisionWindowState::Active { prompt_id, request } = &self.state else {
+            return;
+        };
+
+        if self.open_popup {
+            ui.open_popup(DECISIONS_POPUP);
+            self.open_popup = false;
+        }
+
+        let mut chosen = None;
+
+        let popup = ui
+            .modal_popup_config(DECISIONS_POPUP)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.separator_with_text(
+                    systems::helpers::get_component_clone::<Name>(
+                        &game_state.world,
+                        request.chooser,
+                    )
+                    .as_str(),
+                );
+                ui.text(&request.description);
+
+                for (index, option) in request.options.iter().enumerate() {
+                    if ui.button(format!("{}##{}", option, index)) {
+                        chosen = Some(index);
+                    }
+                }
+
+                if chosen.is_some() {
+                    ui.close_current_popup();
+                }
+            });
+
+        if let Some(option) = chosen {
+            let result = game_state.submit_decision(ActionDecision {
+                response_to: *prompt_id,
+                kind: ActionDecisionKind::Decision {
+                    chooser: request.chooser,
+                    option,
+                },
+            });
+            if let Err(action_error) = result {
+                error!("Failed to submit decision: {:#?}", action_error);
+            }
+            self.state = DecisionWindowState::Pending;
+        } else if popup.is_none() {
+            // Make sure the prompt can't get stuck if the popup was closed
+            self.open_popup = true;
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::str::FromStr;
+
+use hecs::Entity;
+use imgui::ChildFlags;
+use nat20_core::{
+    components::{
+        d20::D20CheckResult,
+        dice::{CompositeRoll, CompositeRollResult, DieSize},
+        id::Name,
+        skill::{Skill, SkillSet},
+    },
+    engine::game_state::GameState,
+    systems,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            text::{TextKind, TextSegment},
+            utils::{ImguiRenderable, render_button_disabled_conditionally},
+        },
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+/// How long the newest result "tumbles" before settling, in seconds
+const ROLL_ANIMATION_DURATION: f32 = 0.6;
+/// Maximum number of rolls kept in the history
+const HISTORY_LENGTH: usize = 50;
+
+const DIE_SIZES: [DieSize; 7] = [
+    DieSize::D4,
+    DieSize::D6,
+    DieSize::D8,
+    DieSize::D10,
+    DieSize::D12,
+    DieSize::D20,
+    DieSize::D100,
+];
+
+enum DiceRollKind {
+    Expression {
+        roll: CompositeRoll,
+        result: CompositeRollResult,
+    },
+    Skill {
+        skill: Skill,
+        result: D20CheckResult,
+    },
+}
+
+struct DiceRollEntry {
+    /// Name of the character the roll was made for, if any
+    roller: Option<String>,
+    kind: DiceRollKind,
+}
+
+impl DiceRollEntry {
+    fn total(&self) -> i32 {
+        match &self.kind {
+            DiceRollKind::Expression { result, .. } => result.total,
+            DiceRollKind::Skill { result, .. } => result.total() as i32,
+        }
+    }
+
+    fn range(&self) -> (i32, i32) {
+        match &self.kind {
+            DiceRollKind::Expression { roll, .. } => (roll.min_roll(), roll.max_roll()),
+            DiceRollKind::Skill { result, .. } => {
+                (1 + result.total_modifier(), 20 + result.total_modifier())
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        let label = match &self.kind {
+            DiceRollKind::Expression { roll, .. } => roll.to_string(),
+            DiceRollKind::Skill { skill, .. } => format!("{} check", skill),
+        };
+        match &self.roller {
+            Some(roller) => format!("{}: {}", roller, label),
+            None => label,
+        }
+    }
+}
+
+pub struct DiceRollerWindow {
+    expression: String,
+    error: Option<String>,
+    /// Attach rolls to the selected character
+    roll_for_selected: bool,
+    skill: Skill,
+    /// Newest roll first
+    history: Vec<DiceRollEntry>,
+    /// Time since the newest roll was made
+    elapsed: f32,
+}
+
+impl DiceRollerWindow {
+    pub fn new() -> Self {
+        Self {
+            expression: "1d20".to_string(),
+            error: None,
+            roll_for_selected: false,
+            skill: Skill::Perception,
+            history: Vec::new(),
+            elapsed: ROLL_ANIMATION_DURATION,
+        }
+    }
+
+    fn push_entry(&mut self, entry: DiceRollEntry) {
+        self.history.insert(0, entry);
+        self.history.truncate(HISTORY_LENGTH);
+        self.elapsed = 0.0;
+    }
+
+    fn roll_expression(&mut self, roller: Option<String>) {
+        match CompositeRoll::from_str(&self.expression) {
+            Ok(roll) => {
+                self.error = None;
+                let result = roll.roll();
+                self.push_entry(DiceRollEntry {
+                    roller,
+                    kind: DiceRollKind::Expression { roll, result },
+                });
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn roll_skill(&mut self, game_state: &GameState, entity: Entity) {
+        let result = systems::helpers::get_component::<SkillSet>(&game_state.world, entity).check(
+            &self.skill,
+            &game_state.world,
+            entity,
+        );
+        self.push_entry(DiceRollEntry {
+            roller: Some(
+                systems::helpers::get_component::<Name>(&game_state.world, entity)
+                    .as_str()
+                    .to_string(),
+            ),
+            kind: DiceRollKind::Skill {
+                skill: self.skill,
+                result,
+            },
+        });
+    }
+
+    fn render_builder(&mut self, ui: &imgui::Ui) {
+        for (i, die_size) in DIE_SIZES.iter().enumerate() {
+            if i > 0 {
+                ui.same_line();
+            }
+            if ui.button(format!("d{}", *die_size as u32)) {
+                if !self.expression.trim().is_empty() {
+                    self.expression.push_str(" + ");
+                }
+                self.expression.push_str(&format!("1d{}", *die_size as u32));
+            }
+        }
+
+        for (i, (label, term)) in [("+1", " + 1"), ("-1", " - 1")].iter().enumerate() {
+            if i > 0 {
+                ui.same_line();
+            }
+            if ui.button(label) {
+                self.expression.push_str(term);
+            }
+        }
+        ui.same_line();
+        if ui.button("Clear") {
+            self.expression.clear();
+            self.error = None;
+        }
+    }
+
+    fn render_history(&self, ui: &imgui::Ui) {
+        ui.child_window("Dice Roll History")
+            .child_flags(ChildFlags::BORDERS)
+            .size([300.0, 200.0])
+            .build(|| {
+                if self.history.is_empty() {
+                    ui.text_disabled("No rolls yet");
+                }
+
+                for (i, entry) in self.history.iter().enumerate() {
+                    let animating = i == 0 && self.elapsed < ROLL_ANIMATION_DURATION;
+                    let total = if animating {
+                        tumbling_value(entry.range(), self.elapsed)
+                    } else {
+                        entry.total()
+                    };
+
+                    TextSegment::new(format!("{:>4}", total), TextKind::Action).render(ui);
+                    ui.same_line();
+                    ui.text(entry.label());
+
+                    if !animating && ui.is_item_hovered() {
+                        ui.tooltip(|| match &entry.kind {
+                            DiceRollKind::Expression { result, .. } => {
+                                for component in &result.components {
+                                    ui.text(component.to_string());
+                                }
+                            }
+                            DiceRollKind::Skill { result, .. } => result.render(ui),
+                        });
+                    }
+                }
+            });
+    }
+}
+
+/// Value shown while the dice are still "tumbling". It only has to look random,
+/// so it cycles through the possible results based on the time since the roll.
+fn tumbling_value((min, max): (i32, i32), elapsed: f32) -> i32 {
+    let range = (max - min + 1).max(1);
+    let step = (elapsed * 30.0) as i32;
+    min + (step * 7919).rem_euclid(range)
+}
+
+impl RenderableMutWithContext<&mut GameState> for DiceRollerWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut dice_roller_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_DICE_ROLLER);
+
+        if !dice_roller_open {
+            return;
+        }
+
+        self.elapsed += ui.io().delta_time;
+
+        let selected_entity = gui_state.selected_entity;
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Dice Roller",
+            &anchor::CENTER_RIGHT,
+            AUTO_RESIZE,
+            &mut dice_roller_open,
+            || {
+                self.render_builder(ui);
+
+                let width_token = ui.push_item_width(200.0);
+                let submitted = ui
+                    .input_text("##Expression", &mut self.expression)
+                    .enter_returns_true(true)
+                    .build();
+                width_token.end();
+                ui.same_line();
+                if ui.button("Roll") || submitted {
+                    let roller = selected_entity
+                        .filter(|_| self.roll_for_selected)
+                        .map(|entity| {
+                            systems::helpers::get_component::<Name>(&game_state.world, entity)
+                                .as_str()
+                                .to_string()
+                        });
+                    self.roll_expression(roller);
+                }
+
+                if let Some(error) = &self.error {
+                    TextSegment::new(error, TextKind::Red).render(ui);
+                }
+
+                ui.separator_with_text("Character");
+
+                ui.checkbox("Roll for selected character", &mut self.roll_for_selected);
+
+                let mut skill_index = Skill::iter()
+                    .position(|skill| skill == self.skill)
+                    .unwrap_or(0);
+                let width_token = ui.push_item_width(150.0);
+                if ui.combo(
+                    "##Skill",
+                    &mut skill_index,
+                    &Skill::iter().collect::<Vec<_>>()[..],
+                    |skill| skill.to_string().into(),
+                ) && let Some(skill) = Skill::iter().nth(skill_index)
+                {
+                    self.skill = skill;
+                }
+                width_token.end();
+                ui.same_line();
+                if render_button_disabled_conditionally(
+                    ui,
+                    "Roll Check",
+                    [0.0, 0.0],
+                    selected_entity.is_none(),
+                    "Select a character to roll a skill check for",
+                ) && let Some(entity) = selected_entity
+                {
+                    self.roll_skill(game_state, entity);
+                }
+
+                ui.separator_with_text("History");
+                self.render_history(ui);
+                if ui.button("Clear History") {
+                    self.history.clear();
+                }
+            },
+        );
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_DICE_ROLLER, dice_roller_open);
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use hecs::Entity;
 use nat20_core::{
-    components::id::Name,
+    components::{health::life_state::LifeState, id::Name},
     engine::{
         encounter::{Encounter, EncounterId},
         game_state::GameState,
@@ -16,6 +16,7 @@ use crate::{
         common::utils::RenderableMutWithContext,
         ui::{
             entities::CreatureRenderMode,
+            images::{self, CreatureImageKind},
             utils::{
                 ImguiRenderable, ImguiRenderableWithContext, SELECTED_BUTTON_COLOR,
                 render_button_disabled_conditionally, render_button_selectable,
@@ -158,7 +159,16 @@ impl RenderableMutWithContext<&mut GameState> for Encounter {
         let current_entity = self.current_entity();
 
         if let Some(table) = table_with_columns!(ui, "Initiative Order", "", "Participant",) {
+            let mut rendered_mobs = HashSet::new();
             for (entity, initiative) in initiative_order {
+                // Mobs share a single row, since their members act together
+                let mob = self.mob(*entity);
+                if let Some(mob) = mob
+                    && !rendered_mobs.insert(mob.members[0])
+                {
+                    continue;
+                }
+
                 if let Ok(_) = &game_state.world.query_one_mut::<&Name>(*entity) {
                     // Initiative column
                     ui.table_next_column();
@@ -170,14 +180,62 @@ impl RenderableMutWithContext<&mut GameState> for Encounter {
                         });
                     }
 
-                    if current_entity == *entity {
+                    let is_current = match mob {
+                        Some(mob) => mob.members.contains(&current_entity),
+                        None => current_entity == *entity,
+                    };
+                    if is_current {
                         ui.table_set_bg_color(imgui::TableBgTarget::all(), SELECTED_BUTTON_COLOR);
                     }
 
                     // Participant column
                     ui.table_next_column();
-                    entity
-                        .render_with_context(ui, (&game_state.world, &CreatureRenderMode::Compact));
+                    if let Some(mob) = mob {
+                        let standing = mob
+                            .members
+                            .iter()
+                            .filter(|member| {
+                                game_state
+                                    .world
+                                    .get::<&LifeState>(**member)
+                                    .is_ok_and(|life_state| *life_state == LifeState::Normal)
+                            })
+                            .count();
+                        ui.text(format!(
+                            "{} mob ({}/{})",
+                            systems::helpers::get_component::<Name>(&game_state.world, *entity)
+                                .as_str(),
+                            standing,
+                            mob.members.len()
+                        ));
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                for member in &mob.members {
+                                    member.render_with_context(
+                                        ui,
+                                        (&game_state.world, &CreatureRenderMode::Compact),
+                                    );
+                                }
+                            });
+                        }
+                    } else {
+                        if images::render_creature_image(
+                            ui,
+                            gui_state,
+                            &game_state.world,
+                            *entity,
+                            CreatureImageKind::Token,
+                            ui.text_line_height() * 2.0,
+                        ) {
+                            ui.same_line();
+                        }
+                        ui.group(|| {
+                            entity.render_with_context(
+                                ui,
+                                (&game_state.world, &CreatureRenderMode::Compact),
+                            );
+                        });
+                    }
                 }
             }
 
@@ -188,7 +246,10 @@ impl RenderableMutWithContext<&mut GameState> for Encounter {
         ui.text(format!("Round: {}", self.round()));
 
         // TODO: No idea where to put this
-        if !systems::ai::is_player_controlled(&game_state.world, self.current_entity())
+        if self.mob(self.current_entity()).is_some() {
+            let result = systems::mob::take_turn(game_state, &self.id().clone());
+            info!("Mob turn taken: {:?}", result);
+        } else if !systems::ai::is_decided_by_player(&game_state.world, self.current_entity())
             && let Some(prompt) = &game_state.next_promt_encounter(self.id()).cloned()
             && prompt.kind.actors().contains(&self.current_entity())
         {
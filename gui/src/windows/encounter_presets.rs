@@ -0,0 +1,296 @@
+use std::{fs, path::Path};
+
+use nat20_core::{
+    engine::game_state::GameState,
+    entities::monster::MonsterTag,
+    systems::{
+        self,
+        preset::{self, EncounterPreset},
+    },
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext, ui::utils::render_button_disabled_conditionally,
+    },
+    state::{self, gui_state::GuiState},
+    windows::{
+        anchor::{self, AUTO_RESIZE},
+        map_generator::MapGeneratorWindow,
+    },
+};
+
+pub static PRESET_DIRECTORY: &str = "assets/presets/encounters";
+
+/// Lets the GM save the monsters currently in the world (and optionally the
+/// generated map they're on) as a named preset, and load it again later
+pub struct EncounterPresetsWindow {
+    presets: Vec<EncounterPreset>,
+    selected: Option<usize>,
+    name: String,
+    include_map: bool,
+    /// Despawn the monsters already in the world when loading a preset
+    replace_monsters: bool,
+    loaded: bool,
+}
+
+impl EncounterPresetsWindow {
+    pub fn new() -> Self {
+        Self {
+            presets: Vec::new(),
+            selected: None,
+            name: String::new(),
+            include_map: true,
+            replace_monsters: true,
+            loaded: false,
+        }
+    }
+
+    /// Reload the presets from disk, keeping the selected preset if it still
+    /// exists
+    pub fn reload(&mut self) {
+        let selected_name = self
+            .selected
+            .and_then(|index| self.presets.get(index))
+            .map(|preset| preset.name.clone());
+
+        self.presets = load_presets(Path::new(PRESET_DIRECTORY));
+        self.selected = selected_name
+            .and_then(|name| self.presets.iter().position(|preset| preset.name == name));
+        self.loaded = true;
+    }
+
+    fn save(&mut self, game_state: &GameState, map_generator: &MapGeneratorWindow) {
+        let map = if self.include_map {
+            map_generator.map_config().cloned()
+        } else {
+            None
+        };
+        let preset = preset::capture(game_state, self.name.trim(), map);
+
+        let path = Path::new(PRESET_DIRECTORY).join(format!("{}.json", file_stem(&preset.name)));
+        let result = fs::create_dir_all(PRESET_DIRECTORY)
+            .map_err(|error| error.to_string())
+            .and_then(|_| serde_json::to_string_pretty(&preset).map_err(|error| error.to_string()))
+            .and_then(|json| fs::write(&path, json).map_err(|error| error.to_string()));
+        match result {
+            Ok(_) => info!(
+                "Saved encounter preset '{}' with {} creatures to {:?}",
+                preset.name,
+                preset.creatures.len(),
+                path
+            ),
+            Err(error) => error!(
+                "Failed to save encounter preset '{}' to {:?}: {}",
+                preset.name, path, error
+            ),
+        }
+
+        self.reload();
+    }
+
+    fn load(
+        &self,
+        preset: &EncounterPreset,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+        map_generator: &mut MapGeneratorWindow,
+    ) {
+        if self.replace_monsters {
+            let monsters = game_state
+                .world
+                .query::<&MonsterTag>()
+                .iter()
+                .map(|(entity, _)| entity)
+                .filter(|entity| !systems::ai::is_player_controlled(&game_state.world, *entity))
+                .collect::<Vec<_>>();
+            for entity in monsters {
+                let _ = game_state.world.despawn(entity);
+            }
+        }
+
+        let navmesh_config = map_generator.navmesh_config.clone().build();
+        match preset::restore(game_state, preset, &navmesh_config) {
+            Ok(entities) => {
+                if let Some(map_config) = &preset.map {
+                    map_generator.set_map(gui_state, game_state, map_config, entities);
+                }
+            }
+            Err(error) => warn!(
+                "Failed to load encounter preset '{}': {:?}",
+                preset.name, error
+            ),
+        }
+    }
+}
+
+/// Turn the preset name into something which is safe to use as a file name
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn load_presets(directory: &Path) -> Vec<EncounterPreset> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let result = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<EncounterPreset>(&json)
+                        .map_err(|error| error.to_string())
+                });
+            match result {
+                Ok(preset) => Some(preset),
+                Err(error) => {
+                    error!("Failed to load encounter preset from {:?}: {}", path, error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+impl RenderableMutWithContext<(&mut GameState, &mut MapGeneratorWindow)>
+    for EncounterPresetsWindow
+{
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        (game_state, map_generator): (&mut GameState, &mut MapGeneratorWindow),
+    ) {
+        let mut presets_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_ENCOUNTER_PRESETS);
+
+        if !presets_open {
+            return;
+        }
+
+        if !self.loaded {
+            self.reload();
+        }
+
+        let mut save = false;
+        let mut load = None;
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Encounter Presets",
+            &anchor::CENTER_RIGHT,
+            AUTO_RESIZE,
+            &mut presets_open,
+            || {
+                let in_combat = !game_state.in_combat.is_empty();
+
+                ui.separator_with_text("Save");
+                let width_token = ui.push_item_width(200.0);
+                ui.input_text("Name", &mut self.name).build();
+                width_token.end();
+
+                if map_generator.map_config().is_some() {
+                    ui.checkbox("Include current map", &mut self.include_map);
+                } else {
+                    ui.text_disabled("No generated map to include");
+                }
+
+                let name_taken = self
+                    .presets
+                    .iter()
+                    .any(|preset| preset.name == self.name.trim());
+                save = render_button_disabled_conditionally(
+                    ui,
+                    if name_taken { "Overwrite" } else { "Save" },
+                    [0.0, 0.0],
+                    self.name.trim().is_empty(),
+                    "The preset needs a name",
+                );
+
+                ui.separator_with_text("Load");
+                if self.presets.is_empty() {
+                    ui.text_disabled(format!("No presets in '{}'", PRESET_DIRECTORY));
+                }
+                for (index, preset) in self.presets.iter().enumerate() {
+                    let label = format!(
+                        "{} ({} creatures{})",
+                        preset.name,
+                        preset.creatures.len(),
+                        if preset.map.is_some() { ", map" } else { "" }
+                    );
+                    if ui
+                        .selectable_config(&label)
+                        .selected(self.selected == Some(index))
+                        .build()
+                    {
+                        self.selected = Some(index);
+                        self.name = preset.name.clone();
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            for creature in &preset.creatures {
+                                ui.text(format!("{} ({})", creature.name, creature.template));
+                            }
+                        });
+                    }
+                }
+
+                ui.checkbox("Replace current monsters", &mut self.replace_monsters);
+
+                let disabled_reason = if in_combat {
+                    "Cannot load a preset while an encounter is running"
+                } else {
+                    "Select a preset to load"
+                };
+                if render_button_disabled_conditionally(
+                    ui,
+                    "Load",
+                    [0.0, 0.0],
+                    in_combat || self.selected.is_none(),
+                    disabled_reason,
+                ) {
+                    load = self.selected;
+                }
+                ui.same_line();
+                if ui.button("Refresh") {
+                    self.reload();
+                }
+            },
+        );
+
+        if save {
+            self.save(game_state, map_generator);
+        }
+
+        if let Some(index) = load
+            && let Some(preset) = self.presets.get(index)
+        {
+            self.load(preset, gui_state, game_state, map_generator);
+        }
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_ENCOUNTER_PRESETS, presets_open);
+    }
+}
@@ -0,0 +1,771 @@
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
+
+use hecs::Entity;
+use imgui::{ChildFlags, HistoryDirection, InputTextCallback, InputTextCallbackHandler};
+use nat20_core::{
+    components::{
+        damage::DamageType,
+        dice::{CompositeRoll, KeepRoll, RollPolicy, TAKE_10},
+        id::{AdventureId, EffectId, ItemId, Name},
+        items::item::ItemRarity,
+        surface::SurfaceKind,
+    },
+    engine::{
+        game_state::GameState,
+        rules::{InitiativeMode, OptionalRule},
+    },
+    registry::registry::{AdventuresRegistry, EffectsRegistry, ItemsRegistry},
+    systems,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            text::{TextKind, TextSegment},
+            utils::ImguiRenderable,
+        },
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+/// Oldest lines are dropped once the output grows past this
+const MAX_OUTPUT_LINES: usize = 200;
+/// Maximum number of suggestions shown below the input
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Refers to the currently selected creature instead of a creature name
+const SELECTED: &str = "selected";
+
+const COMMANDS: [(&str, &str); 14] = [
+    ("/damage", "/damage <target> <amount> <type>"),
+    ("/heal", "/heal <target> <amount>"),
+    ("/give", "/give <target> <item>[+N]"),
+    ("/loot", "/loot <target> <rarity>"),
+    ("/effect", "/effect add|remove <effect> <target>"),
+    ("/surface", "/surface <kind>|clear <target>"),
+    ("/lock", "/lock open|pick|force"),
+    ("/roll", "/roll <dice>, e.g. 2d6+3 or 4d6kh3"),
+    ("/rule", "/rule <rule> on|off"),
+    ("/initiative", "/initiative <mode>|next <target>"),
+    ("/static", "/static off|damage|d20|all [target]"),
+    ("/adventure", "/adventure <adventure>"),
+    ("/flag", "/flag set|clear <flag>"),
+    ("/help", "/help"),
+];
+
+/// Creature names can contain spaces, so in commands they're written in lower
+/// case with underscores instead, e.g. "johnny_fighter"
+fn command_name(name: &str) -> String {
+    name.trim().to_lowercase().replace(' ', "_")
+}
+
+fn item_name(id: &ItemId) -> String {
+    id.id().trim_start_matches("item.").to_string()
+}
+
+fn rarity_name(rarity: &ItemRarity) -> String {
+    rarity.to_string().to_lowercase()
+}
+
+fn effect_name(id: &EffectId) -> String {
+    id.id().trim_start_matches("effect.").to_string()
+}
+
+fn adventure_name(id: &AdventureId) -> String {
+    id.id().trim_start_matches("adventure.").to_string()
+}
+
+/// Find the registry key matching the token, either by its full ID, its short
+/// name or the last part of its short name (e.g. "poisoned" for
+/// "condition.poisoned"), as long as the latter is unambiguous
+fn find_id<K>(
+    keys: impl Iterator<Item = &'static K>,
+    short_name: fn(&K) -> String,
+    token: &str,
+    kind: &str,
+) -> Result<K, String>
+where
+    K: Clone + Display + FromStr + 'static,
+{
+    if let Ok(id) = K::from_str(token) {
+        return Ok(id);
+    }
+
+    let token = token.to_lowercase();
+    let mut partial_matches = Vec::new();
+    for key in keys {
+        let name = short_name(key);
+        if name == token {
+            return Ok(key.clone());
+        }
+        if name.rsplit('.').next() == Some(token.as_str()) {
+            partial_matches.push(key.clone());
+        }
+    }
+
+    match partial_matches.len() {
+        0 => Err(format!("Unknown {}: {}", kind, token)),
+        1 => Ok(partial_matches.remove(0)),
+        _ => Err(format!(
+            "Ambiguous {} '{}', could be any of: {}",
+            kind,
+            token,
+            partial_matches
+                .iter()
+                .map(|key| key.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+enum ConsoleLineKind {
+    Command,
+    Output,
+    Error,
+}
+
+struct ConsoleLine {
+    text: String,
+    kind: ConsoleLineKind,
+}
+
+impl ConsoleLine {
+    fn text_kind(&self) -> TextKind {
+        match self.kind {
+            ConsoleLineKind::Command => TextKind::Details,
+            ConsoleLineKind::Output => TextKind::Normal,
+            ConsoleLineKind::Error => TextKind::Red,
+        }
+    }
+}
+
+/// Input callbacks for completing the current word with Tab and browsing the
+/// previous commands with the arrow keys
+struct ConsoleCallbacks<'a> {
+    suggestions: &'a [String],
+    history: &'a [String],
+    history_index: &'a mut Option<usize>,
+}
+
+impl InputTextCallbackHandler for ConsoleCallbacks<'_> {
+    fn on_completion(&mut self, mut data: imgui::TextCallbackData) {
+        let Some(suggestion) = self.suggestions.first() else {
+            return;
+        };
+        let text = data.str().to_string();
+        let prefix = match text.rfind(' ') {
+            Some(index) => &text[..=index],
+            None => "",
+        };
+        let completed = format!("{}{} ", prefix, suggestion);
+        data.clear();
+        data.push_str(&completed);
+    }
+
+    fn on_history(&mut self, direction: HistoryDirection, mut data: imgui::TextCallbackData) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match (direction, *self.history_index) {
+            (HistoryDirection::Up, None) => Some(self.history.len() - 1),
+            (HistoryDirection::Up, Some(index)) => Some(index.saturating_sub(1)),
+            (HistoryDirection::Down, Some(index)) if index + 1 < self.history.len() => {
+                Some(index + 1)
+            }
+            (HistoryDirection::Down, _) => None,
+        };
+        *self.history_index = index;
+
+        data.clear();
+        if let Some(index) = index {
+            data.push_str(&self.history[index]);
+        }
+    }
+}
+
+/// Console for the GM to change the world directly with commands like
+/// `/damage goblin 5 fire` or `/give johnny_fighter longsword+1`, which are
+/// dispatched to the GM overrides in the core
+pub struct GmConsoleWindow {
+    input: String,
+    output: VecDeque<ConsoleLine>,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    refocus: bool,
+}
+
+impl GmConsoleWindow {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            output: VecDeque::new(),
+            history: Vec::new(),
+            history_index: None,
+            refocus: false,
+        }
+    }
+
+    fn print(&mut self, text: impl Into<String>, kind: ConsoleLineKind) {
+        self.output.push_back(ConsoleLine {
+            text: text.into(),
+            kind,
+        });
+        while self.output.len() > MAX_OUTPUT_LINES {
+            self.output.pop_front();
+        }
+    }
+
+    fn submit(&mut self, gui_state: &GuiState, game_state: &mut GameState) {
+        let command = self.input.trim().to_string();
+        self.input.clear();
+        self.history_index = None;
+        if command.is_empty() {
+            return;
+        }
+
+        self.print(format!("> {}", command), ConsoleLineKind::Command);
+        if self.history.last() != Some(&command) {
+            self.history.push(command.clone());
+        }
+
+        match execute(&command, gui_state.selected_entity, game_state) {
+            Ok(output) => {
+                for line in output {
+                    self.print(line, ConsoleLineKind::Output);
+                }
+            }
+            Err(error) => self.print(error, ConsoleLineKind::Error),
+        }
+    }
+}
+
+fn find_target(
+    token: &str,
+    selected_entity: Option<Entity>,
+    game_state: &GameState,
+) -> Result<Entity, String> {
+    let world = &game_state.world;
+    if token == SELECTED {
+        return selected_entity
+            .filter(|entity| world.contains(*entity))
+            .ok_or_else(|| "No creature is selected".to_string());
+    }
+
+    let token = token.to_lowercase();
+    let matches = world
+        .query::<&Name>()
+        .iter()
+        .filter(|(_, name)| command_name(name.as_str()) == token)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    match matches.as_slice() {
+        [] => Err(format!("No creature named '{}'", token)),
+        [entity] => Ok(*entity),
+        _ => {
+            // Several goblins can share a name, so prefer the selected one
+            if let Some(entity) = selected_entity
+                && matches.contains(&entity)
+            {
+                return Ok(entity);
+            }
+            Err(format!(
+                "{} creatures are named '{}', select one and use '{}' instead",
+                matches.len(),
+                token,
+                SELECTED
+            ))
+        }
+    }
+}
+
+fn parse_amount(token: &str) -> Result<u32, String> {
+    token
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid amount: {}", token))
+}
+
+fn name_of(game_state: &GameState, entity: Entity) -> String {
+    systems::helpers::get_component::<Name>(&game_state.world, entity).to_string()
+}
+
+/// Run a single console command, returning the lines to print
+fn execute(
+    command: &str,
+    selected_entity: Option<Entity>,
+    game_state: &mut GameState,
+) -> Result<Vec<String>, String> {
+    let tokens = command.split_whitespace().collect::<Vec<_>>();
+    let usage = |name: &str| {
+        let (_, usage) = COMMANDS
+            .iter()
+            .find(|(command, _)| *command == name)
+            .unwrap();
+        Err(format!("Usage: {}", usage))
+    };
+
+    match tokens.as_slice() {
+        ["/help"] => Ok(COMMANDS
+            .iter()
+            .map(|(_, usage)| usage.to_string())
+            .chain([format!(
+                "Use '{}' to target the selected creature",
+                SELECTED
+            )])
+            .collect()),
+
+        ["/damage", target, amount, damage_type] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let amount = parse_amount(amount)?;
+            let damage_type = DamageType::from_str(damage_type)?;
+            let (mitigation, life_state) =
+                systems::gm::damage(game_state, target, amount, damage_type);
+            let Some(mitigation) = mitigation else {
+                return Err(format!("{} can't take damage", name_of(game_state, target)));
+            };
+            let mut output = vec![format!(
+                "{} took {} {} damage",
+                name_of(game_state, target),
+                mitigation.total.max(0),
+                damage_type
+            )];
+            if let Some(life_state) = life_state {
+                output.push(format!(
+                    "{} is now {:?}",
+                    name_of(game_state, target),
+                    life_state
+                ));
+            }
+            Ok(output)
+        }
+        ["/damage", ..] => usage("/damage"),
+
+        ["/heal", target, amount] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let amount = parse_amount(amount)?;
+            systems::health::heal(&mut game_state.world, target, amount);
+            Ok(vec![format!(
+                "Healed {} for {}",
+                name_of(game_state, target),
+                amount
+            )])
+        }
+        ["/heal", ..] => usage("/heal"),
+
+        ["/give", target, item] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let (item, enchantment) = match item.rsplit_once('+') {
+                Some((item, level)) => (
+                    item,
+                    Some(
+                        level
+                            .parse::<u32>()
+                            .map_err(|_| format!("Invalid enchantment: +{}", level))?,
+                    ),
+                ),
+                None => (*item, None),
+            };
+            let item_id = find_id(ItemsRegistry::keys(), item_name, item, "item")?;
+            let name = systems::gm::give_item(&mut game_state.world, target, &item_id, enchantment)
+                .map_err(|error| format!("{:?}", error))?;
+            Ok(vec![format!(
+                "Gave {} to {}",
+                name,
+                name_of(game_state, target)
+            )])
+        }
+        ["/give", ..] => usage("/give"),
+
+        ["/loot", target, rarity] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let rarity = ItemRarity::iter()
+                .find(|candidate| rarity_name(candidate) == rarity.to_lowercase())
+                .ok_or_else(|| format!("Unknown rarity '{}'", rarity))?;
+            let name = systems::gm::give_magic_item(&mut game_state.world, target, &rarity)
+                .map_err(|error| format!("{:?}", error))?;
+            Ok(vec![format!(
+                "Gave {} to {}",
+                name,
+                name_of(game_state, target)
+            )])
+        }
+        ["/loot", ..] => usage("/loot"),
+
+        ["/effect", operation @ ("add" | "remove"), effect, target] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let effect_id = find_id(EffectsRegistry::keys(), effect_name, effect, "effect")?;
+            let (result, verb) = if *operation == "add" {
+                (
+                    systems::gm::add_effect(&mut game_state.world, target, &effect_id),
+                    "Added",
+                )
+            } else {
+                (
+                    systems::gm::remove_effect(&mut game_state.world, target, &effect_id),
+                    "Removed",
+                )
+            };
+            result.map_err(|error| format!("{:?}", error))?;
+            Ok(vec![format!(
+                "{} {} on {}",
+                verb,
+                effect_id,
+                name_of(game_state, target)
+            )])
+        }
+        ["/effect", ..] => usage("/effect"),
+
+        ["/surface", "clear", target] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let removed = systems::gm::clear_surfaces(game_state, target)
+                .map_err(|error| format!("{:?}", error))?;
+            Ok(vec![format!(
+                "Cleared {} surface(s) around {}",
+                removed,
+                name_of(game_state, target)
+            )])
+        }
+        ["/surface", kind, target] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let kind = SurfaceKind::iter()
+                .find(|candidate| candidate.to_string().to_lowercase() == kind.to_lowercase())
+                .ok_or_else(|| format!("Unknown surface '{}'", kind))?;
+            systems::gm::create_surface(game_state, target, kind)
+                .map_err(|error| format!("{:?}", error))?;
+            Ok(vec![format!(
+                "Covered the ground around {} in {}",
+                name_of(game_state, target),
+                kind.to_string().to_lowercase()
+            )])
+        }
+        ["/surface", ..] => usage("/surface"),
+
+        // The selected creature tries the closest lock
+        ["/lock", operation @ ("open" | "pick" | "force")] => {
+            let actor = find_target(SELECTED, selected_entity, game_state)?;
+            let target = systems::locks::nearest_locked(&game_state.world, actor)
+                .ok_or_else(|| "There are no locks nearby".to_string())?;
+            let result = match *operation {
+                "open" => systems::locks::open(game_state, actor, target),
+                "pick" => systems::locks::pick(game_state, actor, target),
+                _ => systems::locks::force(game_state, actor, target),
+            };
+            result.map_err(|error| format!("{:?}", error))?;
+            let outcome = if systems::locks::is_locked(&game_state.world, target) {
+                "is still locked"
+            } else {
+                "is unlocked"
+            };
+            Ok(vec![format!("{} {}", name_of(game_state, target), outcome)])
+        }
+        ["/lock", ..] => usage("/lock"),
+
+        ["/roll", expression @ ..] if !expression.is_empty() => {
+            let expression = expression.join(" ");
+            if expression.contains('k') {
+                let roll = KeepRoll::from_str(&expression)?;
+                Ok(vec![format!("{}: {}", roll, roll.roll())])
+            } else {
+                let roll = CompositeRoll::from_str(&expression)?;
+                let result = roll.roll();
+                Ok(vec![format!("{}: {}= {}", roll, result, result.total)])
+            }
+        }
+        ["/roll", ..] => usage("/roll"),
+
+        ["/rule", rule, toggle @ ("on" | "off")] => {
+            let rule = OptionalRule::iter()
+                .find(|candidate| candidate.to_string() == rule.to_lowercase())
+                .ok_or_else(|| format!("Unknown rule '{}'", rule))?;
+            game_state.set_rule(rule, *toggle == "on");
+            Ok(vec![format!("Turned {} optional rule {}", toggle, rule)])
+        }
+        ["/rule", ..] => usage("/rule"),
+
+        ["/initiative", "next", target] => {
+            let target = find_target(target, selected_entity, game_state)?;
+            let encounter_id = game_state
+                .in_combat
+                .get(&target)
+                .cloned()
+                .ok_or_else(|| format!("{} is not in combat", name_of(game_state, target)))?;
+            game_state
+                .encounters
+                .get_mut(&encounter_id)
+                .ok_or_else(|| "Encounter not found".to_string())?
+                .choose_next(target)
+                .map_err(|error| format!("{:?}", error))?;
+            Ok(vec![format!("{} goes next", name_of(game_state, target))])
+        }
+        ["/initiative", mode] => {
+            let mode = InitiativeMode::iter()
+                .find(|candidate| candidate.to_string() == mode.to_lowercase())
+                .ok_or_else(|| format!("Unknown initiative mode '{}'", mode))?;
+            game_state.initiative_mode = mode;
+            Ok(vec![format!("New encounters use {} initiative", mode)])
+        }
+        ["/initiative", ..] => usage("/initiative"),
+
+        // Without a target the policy applies to new encounters, otherwise to
+        // the encounter the target is fighting in
+        [
+            "/static",
+            policy @ ("off" | "damage" | "d20" | "all"),
+            target @ ..,
+        ] if target.len() <= 1 => {
+            let policy = RollPolicy {
+                average_damage: matches!(*policy, "damage" | "all"),
+                fixed_d20: matches!(*policy, "d20" | "all").then_some(TAKE_10),
+            };
+            let description = match (policy.average_damage, policy.fixed_d20) {
+                (false, None) => "roll their dice",
+                (true, None) => "deal average damage",
+                (false, Some(_)) => "take 10 on d20 rolls",
+                (true, Some(_)) => "deal average damage and take 10 on d20 rolls",
+            };
+            let Some(target) = target.first() else {
+                game_state.npc_roll_policy = policy;
+                return Ok(vec![format!("NPCs in new encounters {}", description)]);
+            };
+            let target = find_target(target, selected_entity, game_state)?;
+            let encounter_id = game_state
+                .in_combat
+                .get(&target)
+                .cloned()
+                .ok_or_else(|| format!("{} is not in combat", name_of(game_state, target)))?;
+            game_state.set_roll_policy(&encounter_id, policy);
+            Ok(vec![format!(
+                "NPCs in the encounter of {} {}",
+                name_of(game_state, target),
+                description
+            )])
+        }
+        ["/static", ..] => usage("/static"),
+
+        ["/adventure", adventure] => {
+            let adventure_id = find_id(
+                AdventuresRegistry::keys(),
+                adventure_name,
+                adventure,
+                "adventure",
+            )?;
+            let npcs = systems::campaign::start(game_state, &adventure_id)
+                .map_err(|error| format!("Failed to start {}: {:?}", adventure_id, error))?;
+            Ok(vec![format!(
+                "Started {} with {} NPCs",
+                adventure_id,
+                npcs.len()
+            )])
+        }
+        ["/adventure", ..] => usage("/adventure"),
+
+        ["/flag", operation @ ("set" | "clear"), flag] => {
+            if game_state.campaign.is_none() {
+                return Err("No adventure is being played".to_string());
+            }
+            if *operation == "clear" {
+                systems::campaign::clear_flag(game_state, flag);
+                return Ok(vec![format!("Cleared flag '{}'", flag)]);
+            }
+            let completed = systems::campaign::set_flag(game_state, flag);
+            Ok(std::iter::once(format!("Set flag '{}'", flag))
+                .chain(
+                    completed
+                        .iter()
+                        .map(|quest| format!("Completed quest '{}'", quest.name)),
+                )
+                .collect())
+        }
+        ["/flag", ..] => usage("/flag"),
+
+        [command, ..] => Err(format!(
+            "Unknown command '{}', try /help",
+            command.trim_start_matches('/')
+        )),
+        [] => Ok(Vec::new()),
+    }
+}
+
+/// Suggestions for the word currently being typed, taken from the commands,
+/// the creatures in the world and the registries
+fn complete(input: &str, game_state: &GameState) -> Vec<String> {
+    let tokens = input.split(' ').collect::<Vec<_>>();
+    let current = tokens.last().copied().unwrap_or_default().to_lowercase();
+    let position = tokens.len() - 1;
+
+    let creatures = || {
+        let mut names = game_state
+            .world
+            .query::<&Name>()
+            .iter()
+            .map(|(_, name)| command_name(name.as_str()))
+            .chain([SELECTED.to_string()])
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    };
+
+    let candidates = match (tokens[0], position) {
+        (_, 0) => COMMANDS
+            .iter()
+            .map(|(command, _)| command.to_string())
+            .collect(),
+        ("/damage" | "/heal" | "/give" | "/loot", 1) => creatures(),
+        ("/damage", 3) => DamageType::iter()
+            .map(|damage_type| damage_type.to_string().to_lowercase())
+            .collect(),
+        ("/give", 2) => {
+            let mut items = ItemsRegistry::keys().map(item_name).collect::<Vec<_>>();
+            items.sort();
+            items
+        }
+        ("/loot", 2) => ItemRarity::iter()
+            .map(|rarity| rarity_name(&rarity))
+            .collect(),
+        ("/effect", 1) => vec!["add".to_string(), "remove".to_string()],
+        ("/effect", 2) => {
+            let mut effects = EffectsRegistry::keys().map(effect_name).collect::<Vec<_>>();
+            effects.sort();
+            effects
+        }
+        ("/effect", 3) => creatures(),
+        ("/surface", 1) => SurfaceKind::iter()
+            .map(|kind| kind.to_string().to_lowercase())
+            .chain(["clear".to_string()])
+            .collect(),
+        ("/surface", 2) => creatures(),
+        ("/lock", 1) => vec!["open".to_string(), "pick".to_string(), "force".to_string()],
+        ("/rule", 1) => OptionalRule::iter().map(|rule| rule.to_string()).collect(),
+        ("/rule", 2) => vec!["on".to_string(), "off".to_string()],
+        ("/initiative", 1) => InitiativeMode::iter()
+            .map(|mode| mode.to_string())
+            .chain(["next".to_string()])
+            .collect(),
+        ("/initiative", 2) => creatures(),
+        ("/static", 1) => ["off", "damage", "d20", "all"]
+            .iter()
+            .map(|policy| policy.to_string())
+            .collect(),
+        ("/static", 2) => creatures(),
+        ("/adventure", 1) => {
+            let mut adventures = AdventuresRegistry::keys()
+                .map(adventure_name)
+                .collect::<Vec<_>>();
+            adventures.sort();
+            adventures
+        }
+        ("/flag", 1) => vec!["set".to_string(), "clear".to_string()],
+        ("/flag", 2) => {
+            // Suggest the flags the quests of the adventure are waiting for
+            let mut flags = systems::campaign::adventure(game_state)
+                .into_iter()
+                .flat_map(|adventure| &adventure.quests)
+                .flat_map(|quest| {
+                    quest
+                        .requires
+                        .iter()
+                        .chain(quest.objectives.iter().map(|objective| &objective.flag))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            flags.sort();
+            flags.dedup();
+            flags
+        }
+        _ => Vec::new(),
+    };
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(&current) && *candidate != current)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+impl RenderableMutWithContext<&mut GameState> for GmConsoleWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut console_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_GM_CONSOLE);
+
+        if !console_open {
+            return;
+        }
+
+        let mut submitted = false;
+
+        gui_state.window_manager.render_window(
+            ui,
+            "GM Console",
+            &anchor::BOTTOM_CENTER,
+            AUTO_RESIZE,
+            &mut console_open,
+            || {
+                ui.child_window("GM Console Output")
+                    .child_flags(ChildFlags::BORDERS)
+                    .size([500.0, 200.0])
+                    .build(|| {
+                        for line in &self.output {
+                            TextSegment::new(&line.text, line.text_kind())
+                                .wrap_text(true)
+                                .render(ui);
+                        }
+                        if ui.scroll_y() >= ui.scroll_max_y() - 5.0 {
+                            ui.set_scroll_here_y_with_ratio(1.0);
+                        }
+                    });
+
+                let suggestions = complete(&self.input, game_state);
+
+                if self.refocus {
+                    ui.set_keyboard_focus_here();
+                    self.refocus = false;
+                }
+                let width_token = ui.push_item_width(500.0);
+                submitted = ui
+                    .input_text("##GM Command", &mut self.input)
+                    .hint("/help")
+                    .enter_returns_true(true)
+                    .callback(
+                        InputTextCallback::COMPLETION | InputTextCallback::HISTORY,
+                        ConsoleCallbacks {
+                            suggestions: &suggestions,
+                            history: &self.history,
+                            history_index: &mut self.history_index,
+                        },
+                    )
+                    .build();
+                width_token.end();
+
+                if !suggestions.is_empty() && !self.input.is_empty() {
+                    TextSegment::new(
+                        format!("Tab: {}", suggestions.join("  ")),
+                        TextKind::Details,
+                    )
+                    .wrap_text(true)
+                    .render(ui);
+                }
+            },
+        );
+
+        if submitted {
+            self.submit(gui_state, game_state);
+            // Enter drops the focus, but the GM usually wants to keep typing
+            self.refocus = true;
+        }
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_GM_CONSOLE, console_open);
+    }
+}
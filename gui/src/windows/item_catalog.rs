@@ -0,0 +1,287 @@
+use hecs::{Entity, World};
+use imgui::{ChildFlags, StyleColor};
+use nat20_core::{
+    components::{
+        id::{IdProvider, ItemId, Name},
+        items::{
+            inventory::{Inventory, ItemContainer, ItemInstance},
+            item::ItemRarity,
+        },
+    },
+    engine::game_state::GameState,
+    registry::registry::ItemsRegistry,
+    systems,
+    test_utils::fixtures,
+};
+use strum::{Display, EnumIter, IntoEnumIterator};
+use tracing::info;
+use uom::si::mass::kilogram;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            text::{TextKind, TextSegment, item_rarity_color},
+            utils::{
+                ImguiRenderable, ImguiRenderableWithContext, render_button_disabled_conditionally,
+                render_option_combo,
+            },
+        },
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter)]
+enum ItemCategory {
+    Item,
+    Weapon,
+    Armor,
+    Equipment,
+    Spell,
+    Coating,
+    Tool,
+    Supply,
+    Light,
+}
+
+impl ItemCategory {
+    fn of(item: &ItemInstance) -> Self {
+        match item {
+            ItemInstance::Item(_) => ItemCategory::Item,
+            ItemInstance::Weapon(_) => ItemCategory::Weapon,
+            ItemInstance::Armor(_) => ItemCategory::Armor,
+            ItemInstance::Equipment(_) => ItemCategory::Equipment,
+            ItemInstance::Spell(_) => ItemCategory::Spell,
+            ItemInstance::Coating(_) => ItemCategory::Coating,
+            ItemInstance::Tool(_) => ItemCategory::Tool,
+            ItemInstance::Supply(_) => ItemCategory::Supply,
+            ItemInstance::Light(_) => ItemCategory::Light,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ItemFilter {
+    search: String,
+    category: Option<ItemCategory>,
+    rarity: Option<ItemRarity>,
+}
+
+impl ItemFilter {
+    fn matches(&self, item: &ItemInstance) -> bool {
+        let search = self.search.to_lowercase();
+        (search.is_empty()
+            || item.item().name.to_lowercase().contains(&search)
+            || item.id().to_string().to_lowercase().contains(&search))
+            && self
+                .category
+                .is_none_or(|category| ItemCategory::of(item) == category)
+            && self
+                .rarity
+                .as_ref()
+                .is_none_or(|rarity| item.item().rarity == *rarity)
+    }
+}
+
+pub struct ItemCatalogWindow {
+    filter: ItemFilter,
+    selected: Option<ItemId>,
+    /// Equip the item straight away instead of putting it in the inventory
+    equip_on_grant: bool,
+    /// Creature used to render the item details when no character is
+    /// selected, since e.g. weapon damage depends on the wielder
+    preview_world: World,
+    preview_holder: Entity,
+    /// Result of the last grant
+    message: Option<Result<String, String>>,
+}
+
+impl ItemCatalogWindow {
+    pub fn new() -> Self {
+        let mut preview_world = World::new();
+        let preview_holder = fixtures::creatures::heroes::fighter(&mut preview_world).id();
+
+        Self {
+            filter: ItemFilter::default(),
+            selected: None,
+            equip_on_grant: false,
+            preview_world,
+            preview_holder,
+            message: None,
+        }
+    }
+
+    fn render_filters(&mut self, ui: &imgui::Ui) {
+        ui.input_text("Search", &mut self.filter.search).build();
+
+        let width_token = ui.push_item_width(120.0);
+
+        let categories = ItemCategory::iter().collect::<Vec<_>>();
+        if let Some(category) =
+            render_option_combo(ui, "Category", &categories, self.filter.category.as_ref())
+        {
+            self.filter.category = category;
+        }
+        ui.same_line();
+        let rarities = ItemRarity::iter().collect::<Vec<_>>();
+        if let Some(rarity) =
+            render_option_combo(ui, "Rarity", &rarities, self.filter.rarity.as_ref())
+        {
+            self.filter.rarity = rarity;
+        }
+
+        width_token.end();
+    }
+
+    fn grant(&mut self, world: &mut World, entity: Entity, item: &ItemInstance) {
+        let name = systems::helpers::get_component_clone::<Name>(world, entity);
+        let item_name = &item.item().name;
+
+        if !self.equip_on_grant || !item.equipable() {
+            systems::inventory::add_item(world, entity, item.clone());
+            info!("Granted {} to {}", item_name, name.as_str());
+            self.message = Some(Ok(format!("Added {} to {}", item_name, name.as_str())));
+            return;
+        }
+
+        match systems::inventory::equip(world, entity, item.clone()) {
+            Ok(unequipped_items) => {
+                for unequipped_item in unequipped_items {
+                    systems::inventory::add_item(world, entity, unequipped_item);
+                }
+                info!("Granted and equipped {} on {}", item_name, name.as_str());
+                self.message = Some(Ok(format!("{} equipped {}", name.as_str(), item_name)));
+            }
+            Err(error) => {
+                self.message = Some(Err(format!(
+                    "{} could not equip {}: {:?}",
+                    name.as_str(),
+                    item_name,
+                    error
+                )));
+            }
+        }
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for ItemCatalogWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut catalog_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_ITEM_CATALOG);
+
+        if !catalog_open {
+            return;
+        }
+
+        let selected_entity = gui_state
+            .selected_entity
+            .filter(|entity| game_state.world.get::<&Inventory>(*entity).is_ok());
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Item Catalog",
+            &anchor::CENTER,
+            AUTO_RESIZE,
+            &mut catalog_open,
+            || {
+                self.render_filters(ui);
+
+                let mut items = ItemsRegistry::values()
+                    .filter(|item| self.filter.matches(item))
+                    .collect::<Vec<_>>();
+                items.sort_by(|a, b| a.item().name.cmp(&b.item().name));
+
+                ui.child_window("Item List")
+                    .child_flags(ChildFlags::BORDERS)
+                    .size([250.0, 400.0])
+                    .build(|| {
+                        if items.is_empty() {
+                            ui.text_disabled("No items match the filters");
+                        }
+
+                        for item in &items {
+                            let color_token = ui.push_style_color(
+                                StyleColor::Text,
+                                item_rarity_color(&item.item().rarity),
+                            );
+                            let clicked = ui
+                                .selectable_config(format!("{}##{}", item.item().name, item.id()))
+                                .selected(self.selected.as_ref() == Some(item.id()))
+                                .build();
+                            color_token.pop();
+
+                            if clicked {
+                                self.selected = Some(item.id().clone());
+                                self.message = None;
+                            }
+                        }
+                    });
+
+                let Some(item) = self.selected.as_ref().and_then(ItemsRegistry::get) else {
+                    return;
+                };
+
+                ui.same_line();
+                ui.group(|| {
+                    TextSegment::new(
+                        &item.item().name,
+                        TextKind::Item(item.item().rarity.clone()),
+                    )
+                    .render(ui);
+                    TextSegment::new(
+                        format!("{} {}", item.item().rarity, ItemCategory::of(item)),
+                        TextKind::Details,
+                    )
+                    .render(ui);
+                    TextSegment::new(&item.item().description, TextKind::Details)
+                        .wrap_text(true)
+                        .render(ui);
+                    ui.text(format!(
+                        "Weight: {} kg, Value: {}",
+                        item.item().weight.get::<kilogram>(),
+                        item.item().value
+                    ));
+
+                    if item.equipable() {
+                        ui.separator();
+                        let (world, holder) = match selected_entity {
+                            Some(entity) => (&game_state.world, entity),
+                            None => (&self.preview_world, self.preview_holder),
+                        };
+                        item.render_with_context(ui, (world, holder));
+                    }
+
+                    ui.separator();
+                    ui.checkbox("Equip when granted", &mut self.equip_on_grant);
+                    if render_button_disabled_conditionally(
+                        ui,
+                        "Grant to selected",
+                        [0.0, 0.0],
+                        selected_entity.is_none(),
+                        "Select a creature with an inventory to grant the item to",
+                    ) && let Some(entity) = selected_entity
+                    {
+                        self.grant(&mut game_state.world, entity, item);
+                    }
+
+                    match &self.message {
+                        Some(Ok(message)) => TextSegment::new(message, TextKind::Green).render(ui),
+                        Some(Err(message)) => TextSegment::new(message, TextKind::Red).render(ui),
+                        None => {}
+                    }
+                });
+            },
+        );
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_ITEM_CATALOG, catalog_open);
+    }
+}
@@ -11,6 +11,7 @@ use nat20_core::{
         level::CharacterLevels,
         level_up::{ChoiceItem, ChoiceSpec, LevelUpPrompt},
         proficiency::{Proficiency, ProficiencyLevel},
+        resource::ResourceAmount,
         skill::{Skill, SkillSet},
         spells::spellbook::SpellSource,
     },
@@ -19,6 +20,7 @@ use nat20_core::{
     systems::{
         self,
         level_up::{LevelUpDecision, LevelUpGains, LevelUpSession},
+        what_if::{Stat, WhatIfReport},
     },
 };
 use strum::IntoEnumIterator;
@@ -305,6 +307,9 @@ pub struct LevelUpWindow {
     initial_character: Option<Character>,
     level_up_session: Option<LevelUpSession>,
     pending_decisions: Vec<LevelUpPromptWithProgress>,
+    /// Before/after comparison of the decisions made so far, computed against
+    /// a clone of the initial character
+    preview: Option<WhatIfReport>,
     level_up_complete: bool,
 }
 
@@ -321,6 +326,7 @@ impl LevelUpWindow {
             initial_character,
             level_up_session: None,
             pending_decisions: Vec::new(),
+            preview: None,
             level_up_complete: false,
         }
     }
@@ -391,6 +397,17 @@ impl LevelUpWindow {
                 ));
             }
         }
+
+        self.preview = match systems::level_up::preview_level_up(
+            self.initial_character.as_ref().unwrap(),
+            self.level_up_session.as_ref().unwrap().decisions(),
+        ) {
+            Ok(diff) => Some(diff),
+            Err(error) => {
+                debug!("Failed to preview level up: {:?}", error);
+                None
+            }
+        };
     }
 }
 
@@ -430,13 +447,14 @@ impl ImguiRenderableMutWithContext<&mut World> for LevelUpWindow {
                 // TODO: Include species and subspecies gains
                 if let Some(level_up_session) = &self.level_up_session {
                     if let Some(class) = level_up_session.chosen_class() {
-                        systems::level_up::level_up_gains(
+                        let mut gains = systems::level_up::level_up_gains(
                             &world,
                             self.character.unwrap(),
                             &class,
                             levels.class_level(&class).unwrap().level(),
-                        )
-                        .render(ui);
+                        );
+                        gains.diff = self.preview.clone();
+                        gains.render(ui);
                     }
                 }
                 ui.separator();
@@ -565,7 +583,18 @@ impl ImguiRenderableMut for LevelUpPromptWithProgress {
 
                     for (i, option) in spec.options.iter().enumerate() {
                         let selected = decisions.contains(option);
-                        if render_button_selectable(ui, option.to_string(), button_size, selected) {
+                        let clicked = if let Some(reason) = spec.disabled_reason(option) {
+                            render_button_disabled_conditionally(
+                                ui,
+                                &option.to_string(),
+                                button_size,
+                                true,
+                                reason,
+                            )
+                        } else {
+                            render_button_selectable(ui, option.to_string(), button_size, selected)
+                        };
+                        if clicked {
                             // Special case for only one allowed choice
                             if required == &1 {
                                 decisions.clear();
@@ -954,5 +983,102 @@ impl ImguiRenderable for LevelUpGains {
                 ui.bullet_text(format!("Resource: {}", resource));
             }
         }
+
+        if let Some(diff) = &self.diff {
+            render_level_up_diff(ui, diff);
+        }
+    }
+}
+
+fn render_level_up_diff(ui: &imgui::Ui, diff: &WhatIfReport) {
+    ui.separator_with_text("Before / After");
+
+    if let Some(table) = table_with_columns!(ui, "LevelUpDiff", "", "Before", "After") {
+        render_diff_row(
+            ui,
+            "Hit Points",
+            diff.before.max_hit_points as f32,
+            diff.after.max_hit_points as f32,
+            false,
+        );
+        render_diff_row(
+            ui,
+            "Armor Class",
+            diff.before.armor_class as f32,
+            diff.after.armor_class as f32,
+            false,
+        );
+        for change in diff.changes() {
+            let label = match change.stat {
+                Stat::SavingThrow(ability) => format!("{} Save", ability.acronym()),
+                Stat::Skill(skill) => skill.to_string(),
+                _ => continue,
+            };
+            render_diff_row(ui, &label, change.before, change.after, true);
+        }
+        for (resource, before, after) in diff.resources() {
+            if before == after {
+                continue;
+            }
+            ui.table_next_column();
+            ui.text(resource.to_string());
+            ui.table_next_column();
+            ui.text(format_resource_uses(&before));
+            ui.table_next_column();
+            ui.text_colored(TextKind::Green.color(), format_resource_uses(&after));
+        }
+        table.end();
+    }
+
+    for action in diff.gained_actions() {
+        ui.text_colored(TextKind::Green.color(), format!("+ Action: {}", action));
+    }
+    for action in diff.lost_actions() {
+        ui.text_colored(TextKind::Red.color(), format!("- Action: {}", action));
+    }
+    for effect in diff.gained_effects() {
+        ui.text_colored(TextKind::Green.color(), format!("+ Effect: {}", effect));
+    }
+    for effect in diff.lost_effects() {
+        ui.text_colored(TextKind::Red.color(), format!("- Effect: {}", effect));
+    }
+}
+
+/// Render a row of the before/after table, highlighting the new value if it
+/// changed. Unchanged bonuses are skipped to keep the table short.
+fn render_diff_row(ui: &imgui::Ui, label: &str, before: f32, after: f32, is_bonus: bool) {
+    if is_bonus && before == after {
+        return;
+    }
+
+    let format = |value: f32| {
+        if is_bonus {
+            format!("{:+}", value)
+        } else {
+            value.to_string()
+        }
+    };
+
+    ui.table_next_column();
+    ui.text(label);
+    ui.table_next_column();
+    ui.text(format(before));
+    ui.table_next_column();
+    if after > before {
+        ui.text_colored(TextKind::Green.color(), format(after));
+    } else if after < before {
+        ui.text_colored(TextKind::Red.color(), format(after));
+    } else {
+        ui.text(format(after));
+    }
+}
+
+fn format_resource_uses(uses: &[ResourceAmount]) -> String {
+    if uses.is_empty() {
+        return "-".to_string();
     }
+    uses.iter()
+        .map(|amount| String::from(amount.clone()))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
@@ -2,17 +2,13 @@ use std::{fs::File, io::BufReader};
 
 use imgui::{ChildFlags, MouseButton};
 use nat20_core::{
-    components::{
-        health::{hit_points::HitPoints, life_state::LifeState},
-        id::Name,
-    },
+    components::id::Name,
     engine::{event::ActionPromptKind, game_state::GameState, geometry::WorldGeometry},
     systems::{
         self,
         geometry::{CreaturePose, RaycastFilter, RaycastHitKind},
     },
 };
-use parry3d::na::{Matrix4, Point3};
 use strum::IntoEnumIterator;
 use tracing::error;
 
@@ -21,30 +17,40 @@ use crate::{
         common::utils::RenderableMutWithContext,
         ui::{
             engine::LogLevel,
-            entities::render_if_present,
+            entities::CreatureRenderMode,
             utils::{
-                ImguiRenderable, ImguiRenderableMutWithContext, ImguiRenderableWithContext,
+                ImguiRenderableMutWithContext, ImguiRenderableWithContext,
                 render_button_disabled_conditionally, render_uniform_buttons_with_padding,
             },
         },
-        world::{
-            camera::OrbitCamera,
-            mesh::{Mesh, MeshRenderMode},
-            shapes::{self},
-        },
+        world::battle_map,
     },
-    state::{self, gui_state::GuiState},
+    state::{self, gui_state::GuiState, keybindings::KeyAction},
     windows::{
+        accessible_log::AccessibleLogWindow,
         action_bar::ActionBarWindow,
         anchor::{self, AUTO_RESIZE, WindowManager},
+        bestiary::BestiaryWindow,
+        calendar::CalendarWindow,
+        combat_log::CombatLogWindow,
         creature_debug::CreatureDebugWindow,
-        creature_right_click::CreatureRightClickWindow,
+        creature_right_click::{CreatureRightClickState, CreatureRightClickWindow},
+        decisions::DecisionsWindow,
+        dice_roller::DiceRollerWindow,
         encounter::EncounterWindow,
+        encounter_presets::EncounterPresetsWindow,
+        gm_console::GmConsoleWindow,
+        item_catalog::ItemCatalogWindow,
         level_up::LevelUpWindow,
         line_of_sight_debug::LineOfSightDebugWindow,
+        map_generator::MapGeneratorWindow,
         navigation_debug::NavigationDebugWindow,
+        party::PartyWindow,
         reactions::ReactionsWindow,
+        scenes::ScenesWindow,
         spawn_predefined::SpawnPredefinedWindow,
+        spell_compendium::SpellCompendiumWindow,
+        world_map::WorldMapWindow,
     },
 };
 
@@ -61,8 +67,22 @@ pub enum MainMenuState {
         creature_right_click: Option<CreatureRightClickWindow>,
         action_bar: Option<ActionBarWindow>,
         reactions: ReactionsWindow,
+        decisions: DecisionsWindow,
         navigation_debug: NavigationDebugWindow,
         line_of_sight_debug: LineOfSightDebugWindow,
+        calendar: CalendarWindow,
+        bestiary: BestiaryWindow,
+        combat_log: CombatLogWindow,
+        accessible_log: AccessibleLogWindow,
+        dice_roller: DiceRollerWindow,
+        spell_compendium: SpellCompendiumWindow,
+        item_catalog: ItemCatalogWindow,
+        party: PartyWindow,
+        map_generator: MapGeneratorWindow,
+        encounter_presets: EncounterPresetsWindow,
+        scenes: ScenesWindow,
+        world_map: WorldMapWindow,
+        gm_console: GmConsoleWindow,
     },
 }
 
@@ -114,8 +134,22 @@ impl MainMenuWindow {
                 creature_right_click: None,
                 action_bar: None,
                 reactions: ReactionsWindow::new(),
+                decisions: DecisionsWindow::new(),
                 navigation_debug: NavigationDebugWindow::new(&initial_config),
                 line_of_sight_debug: LineOfSightDebugWindow::new(),
+                calendar: CalendarWindow::new(),
+                bestiary: BestiaryWindow::new(),
+                combat_log: CombatLogWindow::new(),
+                accessible_log: AccessibleLogWindow::new(),
+                dice_roller: DiceRollerWindow::new(),
+                spell_compendium: SpellCompendiumWindow::new(),
+                item_catalog: ItemCatalogWindow::new(),
+                party: PartyWindow::new(),
+                map_generator: MapGeneratorWindow::new(&initial_config),
+                encounter_presets: EncounterPresetsWindow::new(),
+                scenes: ScenesWindow::new(),
+                world_map: WorldMapWindow::new(),
+                gm_console: GmConsoleWindow::new(),
             },
         }
     }
@@ -134,14 +168,56 @@ impl MainMenuWindow {
                 creature_right_click,
                 action_bar,
                 reactions,
+                decisions,
                 navigation_debug,
                 line_of_sight_debug,
+                calendar,
+                bestiary,
+                combat_log,
+                accessible_log,
+                dice_roller,
+                spell_compendium,
+                item_catalog,
+                party,
+                map_generator,
+                encounter_presets,
+                scenes,
+                world_map,
+                gm_console,
             } => {
                 game_state.update(ui.io().delta_time);
+                gui_state
+                    .audio
+                    .update(&gui_state.settings, game_state, ui.io().delta_time);
 
                 navigation_debug.render_mut_with_context(ui, gui_state, game_state);
                 line_of_sight_debug.render_mut_with_context(ui, gui_state, game_state);
+                calendar.render_mut_with_context(ui, gui_state, game_state);
+                bestiary.render_mut_with_context(ui, gui_state, game_state);
+                combat_log.render_mut_with_context(ui, gui_state, game_state);
+                accessible_log.render_mut_with_context(ui, gui_state, game_state);
+                dice_roller.render_mut_with_context(ui, gui_state, game_state);
+                spell_compendium.render_mut_with_context(ui, gui_state, game_state);
+                item_catalog.render_mut_with_context(ui, gui_state, game_state);
+                party.render_mut_with_context(ui, gui_state, game_state);
+                map_generator.render_mut_with_context(ui, gui_state, game_state);
+                encounter_presets.render_mut_with_context(
+                    ui,
+                    gui_state,
+                    (&mut *game_state, &mut *map_generator),
+                );
+                scenes.render_mut_with_context(ui, gui_state, game_state);
+                world_map.render_mut_with_context(
+                    ui,
+                    gui_state,
+                    (&mut *game_state, &mut *map_generator),
+                );
+                gm_console.render_mut_with_context(ui, gui_state, game_state);
 
+                gui_state
+                    .camera
+                    .handle_keybindings(ui, &gui_state.keybindings);
+                gui_state.camera.handle_gamepad(ui, &gui_state.gamepad);
                 gui_state.camera.render_mut_with_context(
                     ui,
                     (
@@ -159,11 +235,22 @@ impl MainMenuWindow {
                 gui_state.cursor_ray_result = if ui.io().want_capture_mouse {
                     None
                 } else if let Some(ray_from_cursor) = gui_state.camera.ray_from_cursor() {
+                    // Creatures the players can't see can't be clicked either,
+                    // so attacking them means guessing where they are
+                    let unseen = game_state
+                        .world
+                        .query::<&CreaturePose>()
+                        .iter()
+                        .map(|(entity, _)| entity)
+                        .filter(|entity| {
+                            systems::visibility::hidden_from_players(&game_state.world, *entity)
+                        })
+                        .collect();
                     systems::geometry::raycast(
                         &game_state.world,
                         &game_state.geometry,
                         &ray_from_cursor,
-                        &RaycastFilter::All,
+                        &RaycastFilter::ExcludeCreatures(unseen),
                     )
                 } else {
                     None
@@ -175,25 +262,62 @@ impl MainMenuWindow {
                     {
                         action_bar.replace(ActionBarWindow::new(game_state, entity));
                     }
+                } else {
+                    *action_bar = None;
+                }
 
-                    if !reactions.is_active()
-                        && let Some(prompt) = game_state.next_prompt_entity(entity)
-                    {
-                        match &prompt.kind {
-                            ActionPromptKind::Reactions { event, options } => {
-                                reactions.activate(prompt.id, &event, &options);
-                            }
-                            _ => {}
+                // Reactions can be triggered during anyone's turn, so check every
+                // encounter for reactions the player has to decide on
+                if !reactions.is_active() {
+                    let prompts = game_state
+                        .encounters
+                        .keys()
+                        .filter_map(|encounter_id| game_state.next_promt_encounter(encounter_id))
+                        .chain(
+                            gui_state
+                                .selected_entity
+                                .and_then(|entity| game_state.next_prompt_entity(entity)),
+                        );
+
+                    for prompt in prompts {
+                        if let ActionPromptKind::Reactions { event, options } = &prompt.kind
+                            && options.keys().any(|reactor| {
+                                systems::ai::is_decided_by_player(&game_state.world, *reactor)
+                            })
+                        {
+                            reactions.activate(prompt.id, &event, &options);
+                            break;
+                        }
+                    }
+                }
+
+                // Choices can come up in the middle of resolving an action
+                if !decisions.is_active() {
+                    let prompts = game_state
+                        .encounters
+                        .keys()
+                        .filter_map(|encounter_id| game_state.next_promt_encounter(encounter_id))
+                        .chain(
+                            gui_state
+                                .selected_entity
+                                .and_then(|entity| game_state.next_prompt_entity(entity)),
+                        );
+
+                    for prompt in prompts {
+                        if let ActionPromptKind::Decision { request } = &prompt.kind
+                            && systems::ai::is_decided_by_player(&game_state.world, request.chooser)
+                        {
+                            decisions.activate(prompt.id, request);
+                            break;
                         }
                     }
-                } else {
-                    *action_bar = None;
                 }
 
                 if let Some(action_bar) = action_bar {
                     action_bar.render_mut_with_context(ui, gui_state, game_state);
                 }
                 reactions.render_mut_with_context(ui, gui_state, game_state);
+                decisions.render_mut_with_context(ui, gui_state, game_state);
 
                 let window_manager_ptr =
                     unsafe { &mut *(&mut gui_state.window_manager as *mut WindowManager) };
@@ -241,10 +365,13 @@ impl MainMenuWindow {
 
                 // If the raycast result was not taken by anyone, we can fallback
                 // to using it for inspecting entities or for movement
-                if let Some(raycast) = &gui_state.cursor_ray_result
-                    && let Some(closest) = raycast.closest()
-                {
-                    match &closest.kind {
+                let closest_hit = gui_state
+                    .cursor_ray_result
+                    .as_ref()
+                    .and_then(|raycast| raycast.closest())
+                    .map(|closest| (closest.kind.clone(), closest.poi));
+                if let Some((kind, poi)) = closest_hit {
+                    match &kind {
                         RaycastHitKind::Creature(entity) => {
                             if ui.is_mouse_clicked(MouseButton::Right) {
                                 ui.open_popup("CreatureRightClick");
@@ -253,7 +380,7 @@ impl MainMenuWindow {
                             }
 
                             if ui.is_mouse_clicked(MouseButton::Left)
-                                && systems::ai::is_player_controlled(&game_state.world, *entity)
+                                && systems::ai::is_decided_by_player(&game_state.world, *entity)
                             {
                                 if gui_state.selected_entity.is_some()
                                     && gui_state.selected_entity.unwrap() == *entity
@@ -266,13 +393,28 @@ impl MainMenuWindow {
                         }
 
                         RaycastHitKind::World => {
+                            if let Some(entity) = gui_state.selected_entity
+                                && battle_map::can_move(game_state, entity)
+                            {
+                                battle_map::render_movement_preview(
+                                    ui, gui_state, game_state, entity, poi,
+                                );
+                            }
+
                             if ui.is_mouse_clicked(MouseButton::Left)
                                 && let Some(entity) = gui_state.selected_entity
                             {
-                                let result = game_state.submit_movement(entity, closest.poi);
+                                let result = game_state.submit_movement(entity, poi);
 
                                 match result {
                                     Ok(path_result) => {
+                                        gui_state.movement_preview = None;
+                                        gui_state.movement_animations.insert(
+                                            entity,
+                                            battle_map::MovementAnimation::new(
+                                                path_result.taken_path.clone(),
+                                            ),
+                                        );
                                         gui_state.path_cache.insert(entity, path_result);
                                     }
                                     Err(err) => {
@@ -284,13 +426,23 @@ impl MainMenuWindow {
                     }
                 }
 
+                if let Some(entity) = gui_state.selected_entity
+                    && gui_state.keybindings.pressed(ui, KeyAction::CharacterSheet)
+                {
+                    ui.open_popup("CreatureRightClick");
+                    creature_right_click.replace(CreatureRightClickWindow {
+                        state: CreatureRightClickState::InspectCreature(CreatureRenderMode::Full),
+                        entity,
+                    });
+                }
+
                 if let Some(creature_right_click) = creature_right_click {
                     ui.popup("CreatureRightClick", || {
                         creature_right_click.render_mut_with_context(ui, game_state);
                     });
                 }
 
-                Self::render_world(ui, gui_state, game_state);
+                battle_map::render_battle_map(ui, gui_state, game_state);
             }
         }
     }
@@ -482,179 +634,4 @@ impl MainMenuWindow {
             },
         );
     }
-
-    fn render_world(ui: &imgui::Ui, gui_state: &mut GuiState, game_state: &mut GameState) {
-        if *gui_state
-            .settings
-            .get::<bool>(state::parameters::RENDER_GRID)
-        {
-            gui_state
-                .grid_renderer
-                .draw(gui_state.ig_renderer.gl_context());
-        }
-
-        let mesh_cache = &mut gui_state.mesh_cache;
-        // TODO: Do something less "hardcoded" with the mesh cache
-        if let Some(mesh) = mesh_cache.get("world") {
-            mesh.draw(
-                gui_state.ig_renderer.gl_context(),
-                &gui_state.program,
-                &Matrix4::identity(),
-                [0.75, 0.75, 0.75, 1.0],
-                &MeshRenderMode::MeshOnly,
-            );
-        } else {
-            let mesh = Mesh::from_parry_trimesh(
-                gui_state.ig_renderer.gl_context(),
-                &game_state.geometry.trimesh,
-            );
-            mesh_cache.insert("world".to_string(), mesh);
-        }
-
-        if let Some(mesh) = mesh_cache.get("navmesh") {
-            if *gui_state
-                .settings
-                .get_mut::<bool>(state::parameters::RENDER_NAVIGATION_NAVMESH)
-            {
-                mesh.draw(
-                    gui_state.ig_renderer.gl_context(),
-                    &gui_state.program,
-                    &Matrix4::identity(),
-                    [0.2, 0.8, 0.2, 0.5],
-                    &MeshRenderMode::MeshWithWireFrame {
-                        color: [0.0, 0.5, 0.0, 0.5],
-                        width: 2.0,
-                    },
-                );
-            }
-        } else {
-            let mesh = Mesh::from_poly_navmesh(
-                gui_state.ig_renderer.gl_context(),
-                &game_state.geometry.poly_navmesh,
-            );
-            mesh_cache.insert("navmesh".to_string(), mesh);
-        }
-
-        // TODO: I feel like this should be somewhere else
-        for (entity, pose) in game_state.world.query::<&CreaturePose>().iter() {
-            systems::geometry::get_shape(&game_state.world, entity).map(|(shape, shape_pose)| {
-                let key = format!("{:#?}", shape);
-                if let Some(mesh) = mesh_cache.get(&key) {
-                    if let Some(current_entity) = gui_state.selected_entity
-                        && current_entity == entity
-                    {
-                        // Render a ring around the feet of the currently selected entity
-                        gui_state.line_renderer.add_circle(
-                            [
-                                pose.translation.vector.x,
-                                pose.translation.vector.y + 0.1,
-                                pose.translation.vector.z,
-                            ],
-                            shape.radius + 0.1,
-                            [1.0, 1.0, 1.0],
-                        );
-                    }
-
-                    // Highlight if mouse is over the creature
-                    let mode = if let Some(raycast) = &gui_state.cursor_ray_result
-                        && let Some(closest) = raycast.closest()
-                        && let RaycastHitKind::Creature(e) = &closest.kind
-                        && *e == entity
-                    {
-                        &MeshRenderMode::MeshWithWireFrame {
-                            color: [1.0, 1.0, 1.0, 1.0],
-                            width: 2.0,
-                        }
-                    } else {
-                        gui_state
-                            .creature_render_mode
-                            .get(&entity)
-                            .unwrap_or(&MeshRenderMode::MeshOnly)
-                    };
-
-                    mesh.draw(
-                        gui_state.ig_renderer.gl_context(),
-                        &gui_state.program,
-                        &shape_pose.to_homogeneous(),
-                        [0.8, 0.8, 0.8, 1.0],
-                        mode,
-                    );
-
-                    // TEMP
-                    gui_state.path_cache.get(&entity).map(|path| {
-                        [
-                            (&path.taken_path, [1.0, 1.0, 1.0]),
-                            (&path.full_path, [1.0, 0.0, 0.0]),
-                        ]
-                        .iter()
-                        .for_each(|(path, color)| {
-                            gui_state.line_renderer.add_polyline(
-                                &path
-                                    .points
-                                    .iter()
-                                    .map(|p| [p.x, p.y, p.z])
-                                    .collect::<Vec<[f32; 3]>>(),
-                                *color,
-                            );
-                        });
-                    });
-                } else {
-                    let mesh = shapes::build_capsule_mesh(
-                        gui_state.ig_renderer.gl_context(),
-                        8,
-                        16,
-                        shape.radius,
-                        shape.half_height(),
-                    );
-                    mesh_cache.insert(key, mesh);
-                }
-            });
-        }
-
-        Self::render_creature_labels(ui, game_state, &gui_state.camera);
-
-        // TODO: Not sure where to put this?
-        gui_state.line_renderer.draw(
-            gui_state.ig_renderer.gl_context(),
-            &Matrix4::identity(),
-            2.0,
-        );
-    }
-
-    fn render_creature_labels(ui: &imgui::Ui, game_state: &GameState, camera: &OrbitCamera) {
-        for (entity, name) in game_state.world.query::<&Name>().iter() {
-            if let Some(pose) = game_state.world.get::<&CreaturePose>(entity).ok() {
-                let translation = pose.translation.vector;
-                let pos = camera.world_to_screen(&Point3::new(
-                    translation.x,
-                    translation.y
-                        + systems::geometry::get_height(&game_state.world, entity).unwrap() * 1.5,
-                    translation.z,
-                ));
-
-                if let Some((x, y)) = pos {
-                    let height = ui.calc_text_size(name.as_str())[1] * 2.0;
-                    let width = ui.calc_text_size("HP:")[0] + 150.0; // rough estimate for width
-                    let window_pos = [x - width / 2.0, y - height];
-                    ui.window(&format!("Label##{:?}", entity))
-                        .always_auto_resize(true)
-                        .position(window_pos, imgui::Condition::Always)
-                        .bg_alpha(0.5)
-                        .title_bar(false)
-                        .resizable(false)
-                        .movable(false)
-                        .scrollable(false)
-                        .focus_on_appearing(false)
-                        .collapsed(false, imgui::Condition::Always)
-                        .mouse_inputs(false)
-                        .build(|| {
-                            name.render(ui);
-                            render_if_present::<HitPoints>(ui, &game_state.world, entity);
-                            ui.same_line();
-                            render_if_present::<LifeState>(ui, &game_state.world, entity);
-                        });
-                }
-            }
-        }
-    }
 }
@@ -0,0 +1,306 @@
+use hecs::Entity;
+use nat20_core::{
+    engine::game_state::GameState,
+    entities::character::CharacterTag,
+    systems::{
+        self,
+        mapgen::{self, MapGenConfig, Tile, TileMap},
+    },
+};
+use rerecast::ConfigBuilder;
+use tracing::warn;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext, ui::utils::render_button_disabled_conditionally,
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+/// Size of each tile in the map preview in pixels
+const PREVIEW_TILE_SIZE: f32 = 6.0;
+
+pub struct MapGeneratorWindow {
+    pub config: MapGenConfig,
+    pub navmesh_config: ConfigBuilder,
+    pub use_seed: bool,
+    pub seed: u64,
+    pub map: Option<TileMap>,
+    /// Config the current map was generated from, with the seed filled in
+    map_config: Option<MapGenConfig>,
+    /// Doors, traps, light sources and monsters spawned for the current map,
+    /// which are despawned when a new map is generated
+    populated: Vec<Entity>,
+}
+
+impl MapGeneratorWindow {
+    pub fn new(navmesh_config: &ConfigBuilder) -> Self {
+        Self {
+            config: MapGenConfig::default(),
+            navmesh_config: navmesh_config.clone(),
+            use_seed: false,
+            seed: 0,
+            map: None,
+            map_config: None,
+            populated: Vec::new(),
+        }
+    }
+
+    fn generate(&mut self, gui_state: &mut GuiState, game_state: &mut GameState) {
+        self.config.seed = self.use_seed.then_some(self.seed);
+        let map = match mapgen::generate(&self.config) {
+            Ok(map) => map,
+            Err(error) => {
+                warn!("Failed to generate map: {:?}", error);
+                return;
+            }
+        };
+
+        game_state.geometry = map.to_geometry(&self.navmesh_config.clone().build());
+        gui_state.mesh_cache.remove("world");
+        gui_state.mesh_cache.remove("navmesh");
+
+        self.clear_populated(game_state);
+        self.populated = mapgen::populate(game_state, &map);
+
+        Self::move_party_to_start(game_state, &map);
+        self.map_config = Some(MapGenConfig {
+            seed: Some(map.seed),
+            ..self.config.clone()
+        });
+        self.map = Some(map);
+    }
+
+    /// Config which generates the current map again, if a map has been
+    /// generated
+    pub fn map_config(&self) -> Option<&MapGenConfig> {
+        self.map_config.as_ref()
+    }
+
+    /// Despawn everything spawned for the current map
+    pub fn clear_populated(&mut self, game_state: &mut GameState) {
+        for entity in self.populated.drain(..) {
+            let _ = game_state.world.despawn(entity);
+        }
+    }
+
+    /// Take over a map which was generated and populated elsewhere, e.g. by
+    /// loading an encounter preset. The world geometry is expected to already
+    /// have been built from the map.
+    pub fn set_map(
+        &mut self,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+        config: &MapGenConfig,
+        populated: Vec<Entity>,
+    ) {
+        let map = match mapgen::generate(config) {
+            Ok(map) => map,
+            Err(error) => {
+                warn!("Failed to generate map: {:?}", error);
+                return;
+            }
+        };
+        gui_state.mesh_cache.remove("world");
+        gui_state.mesh_cache.remove("navmesh");
+
+        self.clear_populated(game_state);
+        self.populated = populated;
+
+        Self::move_party_to_start(game_state, &map);
+        self.config = config.clone();
+        self.use_seed = true;
+        self.seed = map.seed;
+        self.map_config = Some(config.clone());
+        self.map = Some(map);
+    }
+
+    /// Move the party to the first room so they aren't left outside the map
+    fn move_party_to_start(game_state: &mut GameState, map: &TileMap) {
+        if let Some(room) = map.rooms.first() {
+            let characters = game_state
+                .world
+                .query::<&CharacterTag>()
+                .iter()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+            for (entity, position) in characters.into_iter().zip(room.tiles()) {
+                systems::geometry::teleport_to_ground(
+                    &mut game_state.world,
+                    &game_state.geometry,
+                    entity,
+                    &map.tile_center(&position),
+                );
+            }
+        }
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for MapGeneratorWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut map_generator_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_MAP_GENERATOR);
+
+        if !map_generator_open {
+            return;
+        }
+
+        let mut generate = false;
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Map Generator",
+            &anchor::CENTER_RIGHT,
+            AUTO_RESIZE,
+            &mut map_generator_open,
+            || {
+                let width_token = ui.push_item_width(100.0);
+
+                let mut parameters_usize = [
+                    ("Width", &mut self.config.width),
+                    ("Height", &mut self.config.height),
+                    ("Max Rooms", &mut self.config.max_rooms),
+                    ("Min Room Size", &mut self.config.min_room_size),
+                    ("Max Room Size", &mut self.config.max_room_size),
+                    ("Party Size", &mut self.config.party_size),
+                ];
+                for (label, value) in &mut parameters_usize {
+                    let mut input = **value as i32;
+                    ui.input_int(*label, &mut input).build();
+                    **value = input.max(1) as usize;
+                }
+                // Make sure there is always room for at least one room
+                self.config.max_room_size =
+                    self.config.max_room_size.max(self.config.min_room_size);
+                self.config.width = self.config.width.max(self.config.max_room_size + 2);
+                self.config.height = self.config.height.max(self.config.max_room_size + 2);
+
+                let mut party_level = self.config.party_level as i32;
+                ui.input_int("Party Level", &mut party_level).build();
+                self.config.party_level = party_level.clamp(1, 20) as u8;
+
+                ui.slider(
+                    "Encounter Chance",
+                    0.0,
+                    1.0,
+                    &mut self.config.encounter_chance,
+                );
+                ui.slider("Trap Chance", 0.0, 1.0, &mut self.config.trap_chance);
+                ui.slider("Lock Chance", 0.0, 1.0, &mut self.config.lock_chance);
+                ui.slider(
+                    "Secret Door Chance",
+                    0.0,
+                    1.0,
+                    &mut self.config.secret_door_chance,
+                );
+
+                ui.checkbox("Use Seed", &mut self.use_seed);
+                if self.use_seed {
+                    ui.same_line();
+                    ui.input_scalar("Seed", &mut self.seed).build();
+                }
+
+                width_token.end();
+
+                generate = render_button_disabled_conditionally(
+                    ui,
+                    "Generate",
+                    [0.0, 0.0],
+                    !game_state.in_combat.is_empty(),
+                    "Cannot generate a new map while an encounter is running",
+                );
+
+                if let Some(map) = &self.map {
+                    ui.separator_with_text("Map");
+                    ui.text(format!(
+                        "Rooms: {}, Encounters: {}, Traps: {}",
+                        map.rooms.len(),
+                        map.encounters.len(),
+                        map.positions_of(Tile::Trap).count()
+                    ));
+                    render_map_preview(ui, map);
+                }
+            },
+        );
+
+        if generate {
+            self.generate(gui_state, game_state);
+        }
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_MAP_GENERATOR, map_generator_open);
+    }
+}
+
+fn render_map_preview(ui: &imgui::Ui, map: &TileMap) {
+    let origin = ui.cursor_screen_pos();
+    let draw_list = ui.get_window_draw_list();
+
+    let tile_corner = |x: usize, y: usize| {
+        [
+            origin[0] + x as f32 * PREVIEW_TILE_SIZE,
+            origin[1] + y as f32 * PREVIEW_TILE_SIZE,
+        ]
+    };
+    let tile_center = |x: usize, y: usize| {
+        let [x, y] = tile_corner(x, y);
+        [x + PREVIEW_TILE_SIZE / 2.0, y + PREVIEW_TILE_SIZE / 2.0]
+    };
+
+    for position in map.positions() {
+        let color = match map.get(&position).unwrap() {
+            Tile::Wall => [0.15, 0.15, 0.15, 1.0],
+            Tile::Floor => [0.6, 0.6, 0.6, 1.0],
+            Tile::Door if map.secret_doors.contains(&position) => [0.3, 0.3, 0.3, 1.0],
+            Tile::Door if map.locked_doors.contains(&position) => [0.35, 0.2, 0.1, 1.0],
+            Tile::Door => [0.55, 0.35, 0.15, 1.0],
+            Tile::Trap => [0.8, 0.2, 0.8, 1.0],
+        };
+        let [x0, y0] = tile_corner(position.x, position.y);
+        draw_list
+            .add_rect(
+                [x0, y0],
+                [x0 + PREVIEW_TILE_SIZE, y0 + PREVIEW_TILE_SIZE],
+                color,
+            )
+            .filled(true)
+            .build();
+    }
+
+    for light in &map.light_sources {
+        draw_list
+            .add_circle(
+                tile_center(light.position.x, light.position.y),
+                PREVIEW_TILE_SIZE / 3.0,
+                [1.0, 0.85, 0.2, 1.0],
+            )
+            .filled(true)
+            .build();
+    }
+
+    for encounter in &map.encounters {
+        for position in &encounter.positions {
+            draw_list
+                .add_circle(
+                    tile_center(position.x, position.y),
+                    PREVIEW_TILE_SIZE / 2.0,
+                    [0.9, 0.1, 0.1, 1.0],
+                )
+                .filled(true)
+                .build();
+        }
+    }
+
+    ui.dummy([
+        map.width as f32 * PREVIEW_TILE_SIZE,
+        map.height as f32 * PREVIEW_TILE_SIZE,
+    ]);
+}
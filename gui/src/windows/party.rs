@@ -0,0 +1,351 @@
+use hecs::Entity;
+use imgui::{DragDropFlags, TableFlags};
+use nat20_core::{
+    components::{
+        health::{hit_points::HitPoints, life_state::LifeState},
+        id::Name,
+        resource::ResourceMap,
+        time::TimeDuration,
+    },
+    engine::game_state::GameState,
+    systems::{
+        self,
+        time::{RestKind, TimeActivity},
+    },
+};
+use parry3d::na::{Point3, Vector3};
+use strum::{Display, EnumIter, IntoEnumIterator};
+use tracing::warn;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            images::{self, CreatureImageKind},
+            utils::{ImguiRenderable, render_button_disabled_conditionally},
+        },
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+const PARTY_DRAG_DROP: &str = "PartyMember";
+
+/// Distance between party members when gathering in formation, in meters
+const FORMATION_SPACING: f32 = 1.5;
+
+/// How the party lines up behind the leader, i.e. the first in the marching
+/// order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
+pub enum Formation {
+    #[strum(to_string = "Single File")]
+    SingleFile,
+    #[strum(to_string = "Double File")]
+    DoubleFile,
+    Wedge,
+}
+
+impl Formation {
+    /// Offset of the n-th member in the marching order from the leader, as
+    /// (meters to the right, meters behind)
+    pub fn offset(&self, index: usize) -> (f32, f32) {
+        let (right, behind) = match self {
+            Formation::SingleFile => (0.0, index as f32),
+            Formation::DoubleFile => ((index % 2) as f32 - 0.5, (index / 2) as f32),
+            Formation::Wedge => {
+                if index == 0 {
+                    (0.0, 0.0)
+                } else {
+                    // Alternate left and right, one row further back each pair
+                    let row = index.div_ceil(2) as f32;
+                    let side = if index % 2 == 1 { -1.0 } else { 1.0 };
+                    (side * row, row)
+                }
+            }
+        };
+        (right * FORMATION_SPACING, behind * FORMATION_SPACING)
+    }
+}
+
+pub struct PartyWindow {
+    /// Marching order of the party. The first member leads the way.
+    order: Vec<Entity>,
+    formation: Formation,
+    travel_hours: i32,
+}
+
+impl PartyWindow {
+    pub fn new() -> Self {
+        Self {
+            order: Vec::new(),
+            formation: Formation::SingleFile,
+            travel_hours: 1,
+        }
+    }
+
+    /// Keep the marching order in sync with the player-controlled entities in
+    /// the world. New members join at the back.
+    fn update_members(&mut self, game_state: &GameState) {
+        let world = &game_state.world;
+        self.order.retain(|entity| {
+            world.contains(*entity) && systems::ai::is_player_controlled(world, *entity)
+        });
+
+        let mut new_members = world
+            .query::<&Name>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .filter(|entity| {
+                systems::ai::is_player_controlled(world, *entity) && !self.order.contains(entity)
+            })
+            .collect::<Vec<_>>();
+        new_members.sort();
+        self.order.extend(new_members);
+    }
+
+    fn render_members(&mut self, ui: &imgui::Ui, gui_state: &mut GuiState, game_state: &GameState) {
+        let mut reorder = None;
+
+        if let Some(_table) = ui.begin_table_with_flags(
+            "Party Members",
+            4,
+            TableFlags::SIZING_FIXED_FIT | TableFlags::ROW_BG | TableFlags::BORDERS_INNER_H,
+        ) {
+            ui.table_setup_column("#");
+            ui.table_setup_column("Name");
+            ui.table_setup_column("HP");
+            ui.table_setup_column("Resources");
+            ui.table_headers_row();
+
+            for (index, entity) in self.order.iter().enumerate() {
+                let _id = ui.push_id(format!("{:?}", entity));
+                let name = systems::helpers::get_component::<Name>(&game_state.world, *entity);
+                ui.table_next_row();
+
+                ui.table_next_column();
+                ui.text(format!("{}", index + 1));
+
+                ui.table_next_column();
+                if images::render_creature_image(
+                    ui,
+                    gui_state,
+                    &game_state.world,
+                    *entity,
+                    CreatureImageKind::Portrait,
+                    ui.text_line_height() * 2.0,
+                ) {
+                    ui.same_line();
+                }
+                if ui
+                    .selectable_config(name.as_str())
+                    .selected(gui_state.selected_entity == Some(*entity))
+                    .build()
+                {
+                    gui_state.selected_entity = Some(*entity);
+                }
+
+                // Members can be dragged onto each other to change the
+                // marching order
+                if let Some(tooltip) = ui
+                    .drag_drop_source_config(PARTY_DRAG_DROP)
+                    .begin_payload(index)
+                {
+                    ui.text(name.as_str());
+                    tooltip.end();
+                }
+                if let Some(target) = ui.drag_drop_target() {
+                    if let Some(Ok(payload)) =
+                        target.accept_payload::<usize, _>(PARTY_DRAG_DROP, DragDropFlags::empty())
+                    {
+                        reorder = Some((payload.data, index));
+                    }
+                    target.pop();
+                }
+
+                ui.table_next_column();
+                if let Ok(hit_points) = game_state.world.get::<&HitPoints>(*entity) {
+                    hit_points.render(ui);
+                }
+                if let Ok(life_state) = game_state.world.get::<&LifeState>(*entity)
+                    && *life_state != LifeState::Normal
+                {
+                    ui.same_line();
+                    life_state.render(ui);
+                }
+
+                ui.table_next_column();
+                if let Ok(resources) = game_state.world.get::<&ResourceMap>(*entity) {
+                    let total = resources.iter().count();
+                    let available = resources
+                        .iter()
+                        .filter(|(_, budget)| !budget.is_empty())
+                        .count();
+                    ui.text(format!("{} / {} available", available, total));
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| resources.render(ui));
+                    }
+                }
+            }
+        }
+
+        if let Some((source, target)) = reorder {
+            let entity = self.order.remove(source);
+            self.order.insert(target.min(self.order.len()), entity);
+        }
+    }
+
+    fn rest(&self, game_state: &mut GameState, kind: RestKind) {
+        if let Err(error) = systems::time::start_rest(game_state, self.order.clone(), &kind) {
+            warn!("Party failed to start {:?} rest: {:?}", kind, error);
+            return;
+        }
+        if let Err(error) = systems::time::finish_rest(game_state, self.order.clone()) {
+            warn!("Party failed to finish {:?} rest: {:?}", kind, error);
+        }
+    }
+
+    /// Line up the party behind the leader, facing away from the camera
+    fn gather(&self, gui_state: &GuiState, game_state: &mut GameState) {
+        let Some(leader) = self.order.first() else {
+            return;
+        };
+        let Some(leader_position) =
+            systems::geometry::get_foot_position(&game_state.world, *leader)
+        else {
+            return;
+        };
+
+        let view = gui_state.camera.target - gui_state.camera.eye();
+        let forward = Vector3::new(view.x, 0.0, view.z)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vector3::z());
+        let right = Vector3::new(-forward.z, 0.0, forward.x);
+
+        for (index, entity) in self.order.iter().enumerate().skip(1) {
+            let (offset_right, offset_behind) = self.formation.offset(index);
+            let position: Point3<f32> =
+                leader_position + right * offset_right - forward * offset_behind;
+            systems::geometry::teleport_to_ground(
+                &mut game_state.world,
+                &game_state.geometry,
+                *entity,
+                &position,
+            );
+        }
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for PartyWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut party_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_PARTY);
+
+        if !party_open {
+            return;
+        }
+
+        self.update_members(game_state);
+
+        let window_manager_ptr =
+            unsafe { &mut *(&mut gui_state.window_manager as *mut anchor::WindowManager) };
+
+        window_manager_ptr.render_window(
+            ui,
+            "Party",
+            &anchor::CENTER_LEFT,
+            AUTO_RESIZE,
+            &mut party_open,
+            || {
+                if self.order.is_empty() {
+                    ui.text_disabled("No player-controlled characters");
+                    return;
+                }
+
+                self.render_members(ui, gui_state, game_state);
+
+                let party_in_combat = self
+                    .order
+                    .iter()
+                    .any(|entity| game_state.in_combat.contains_key(entity));
+                let anyone_in_combat = !game_state.in_combat.is_empty();
+
+                ui.separator_with_text("Formation");
+                let width_token = ui.push_item_width(120.0);
+                let formations = Formation::iter().collect::<Vec<_>>();
+                let mut formation_index = formations
+                    .iter()
+                    .position(|formation| *formation == self.formation)
+                    .unwrap_or(0);
+                if ui.combo(
+                    "##Formation",
+                    &mut formation_index,
+                    &formations,
+                    |formation| formation.to_string().into(),
+                ) {
+                    self.formation = formations[formation_index];
+                }
+                width_token.end();
+                ui.same_line();
+                if render_button_disabled_conditionally(
+                    ui,
+                    "Gather",
+                    [0.0, 0.0],
+                    party_in_combat,
+                    "The party can't regroup during combat",
+                ) {
+                    self.gather(gui_state, game_state);
+                }
+
+                ui.separator_with_text("Party Actions");
+                if render_button_disabled_conditionally(
+                    ui,
+                    "Short Rest",
+                    [0.0, 0.0],
+                    party_in_combat,
+                    "The party can't rest during combat",
+                ) {
+                    self.rest(game_state, RestKind::Short);
+                }
+                ui.same_line();
+                if render_button_disabled_conditionally(
+                    ui,
+                    "Long Rest",
+                    [0.0, 0.0],
+                    party_in_combat,
+                    "The party can't rest during combat",
+                ) {
+                    self.rest(game_state, RestKind::Long);
+                }
+
+                let width_token = ui.push_item_width(100.0);
+                ui.input_int("Hours", &mut self.travel_hours).build();
+                width_token.end();
+                self.travel_hours = self.travel_hours.max(1);
+                ui.same_line();
+                if render_button_disabled_conditionally(
+                    ui,
+                    "Travel",
+                    [0.0, 0.0],
+                    anyone_in_combat,
+                    "Time cannot pass while an encounter is running",
+                ) && let Err(error) = systems::time::pass_time(
+                    game_state,
+                    TimeDuration::from_hours(self.travel_hours as u32),
+                    TimeActivity::Travel,
+                ) {
+                    warn!("Party failed to travel: {:?}", error);
+                }
+            },
+        );
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_PARTY, party_open);
+    }
+}
@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use hecs::Entity;
 use nat20_core::{
-    components::id::Name,
+    components::{
+        id::{ActionId, Name},
+        resource::{ResourceAmount, ResourceAmountMap},
+    },
     engine::{
         event::{ActionDecision, ActionDecisionKind, ActionPromptId, Event, ReactionData},
         game_state::GameState,
@@ -19,27 +22,36 @@ use crate::{
             utils::{ImguiRenderableWithContext, render_button_selectable},
         },
     },
-    state::gui_state::GuiState,
-    windows::anchor::{AUTO_RESIZE, BOTTOM_CENTER, CENTER},
+    state::{self, gui_state::GuiState},
 };
 
+static REACTIONS_POPUP: &str = "Reactions";
+
 pub enum ReactionWindowState {
     Active {
         prompt_id: ActionPromptId,
         event: Event,
         options: HashMap<Entity, Vec<ReactionData>>,
+        /// Time since the prompt was opened, used for the auto-decline timeout
+        elapsed: f32,
     },
     Pending,
 }
 
 pub struct ReactionsWindow {
     state: ReactionWindowState,
+    /// Reactions which are used automatically whenever they're offered, e.g.
+    /// always using Shield when it's available
+    always_use: HashSet<(Entity, ActionId)>,
+    open_popup: bool,
 }
 
 impl ReactionsWindow {
     pub fn new() -> Self {
         Self {
             state: ReactionWindowState::Pending,
+            always_use: HashSet::new(),
+            open_popup: false,
         }
     }
 
@@ -58,8 +70,29 @@ impl ReactionsWindow {
             prompt_id,
             event: event.clone(),
             options: options.clone(),
+            elapsed: 0.0,
         };
+        self.open_popup = true;
+    }
+}
+
+fn cost_summary(cost: &ResourceAmountMap) -> String {
+    if cost.is_empty() {
+        return "No cost".to_string();
     }
+
+    cost.iter()
+        .map(|(resource, amount)| match amount {
+            ResourceAmount::Flat(amount) => format!("{} {}", amount, resource),
+            ResourceAmount::Tiered { tier, amount } => {
+                format!("{} Level {} {}", amount, tier, resource)
+            }
+            ResourceAmount::Variable { .. } => {
+                format!("{} {}", String::from(amount.clone()), resource)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl RenderableMutWithContext<&mut GameState> for ReactionsWindow {
@@ -69,130 +102,192 @@ impl RenderableMutWithContext<&mut GameState> for ReactionsWindow {
         gui_state: &mut GuiState,
         game_state: &mut GameState,
     ) {
+        let auto_decline_timeout = *gui_state
+            .settings
+            .get::<f32>(state::parameters::REACTION_AUTO_DECLINE_TIMEOUT);
+
         let mut new_state = None;
 
-        match &self.state {
+        match &mut self.state {
             ReactionWindowState::Pending => return,
             ReactionWindowState::Active {
                 prompt_id,
                 event,
                 options,
-            } => gui_state.window_manager.render_window(
-                ui,
-                "Reactions",
-                &CENTER,
-                AUTO_RESIZE,
-                &mut true,
-                || {
-                    if options.is_empty() {
-                        ui.text("No reactions available.");
-                    }
+                elapsed,
+            } => {
+                *elapsed += ui.io().delta_time;
 
-                    event.render_with_context(ui, &(&game_state.world, &LogLevel::Info));
+                let decided = game_state
+                    .session_for_entity(*options.keys().next().unwrap())
+                    .and_then(|session| session.decisions_for_prompt(prompt_id))
+                    .cloned()
+                    .unwrap_or_default();
 
-                    ui.text("Choose how to react:");
+                let undecided_reactors = options
+                    .keys()
+                    .filter(|reactor| {
+                        systems::ai::is_decided_by_player(&game_state.world, **reactor)
+                            && !decided.contains_key(reactor)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
 
-                    let decisions = game_state
-                        .session_for_entity(*options.keys().next().unwrap())
-                        .and_then(|session| session.decisions_for_prompt(prompt_id));
+                let mut submissions = Vec::new();
 
-                    let (mut button_clicked, mut entity, mut choice) = (false, None, None);
+                for reactor in &undecided_reactors {
+                    if let Some(option) = options[reactor].iter().find(|option| {
+                        self.always_use
+                            .contains(&(*reactor, option.reaction_id.clone()))
+                    }) {
+                        info!(
+                            "Automatically using {} for reactor {:?}",
+                            option.reaction_id, reactor
+                        );
+                        submissions.push((*reactor, Some(option.clone())));
+                    } else if auto_decline_timeout > 0.0 && *elapsed >= auto_decline_timeout {
+                        info!("Reaction prompt timed out for reactor {:?}", reactor);
+                        submissions.push((*reactor, None));
+                    }
+                }
 
-                    for (reactor, options) in options {
-                        if !systems::ai::is_player_controlled(&game_state.world, *reactor) {
-                            continue;
+                if self.open_popup {
+                    ui.open_popup(REACTIONS_POPUP);
+                    self.open_popup = false;
+                }
+
+                let popup = ui
+                    .modal_popup_config(REACTIONS_POPUP)
+                    .always_auto_resize(true)
+                    .build(|| {
+                        if options.is_empty() {
+                            ui.text("No reactions available.");
                         }
 
-                        ui.separator_with_text(
-                            systems::helpers::get_component_clone::<Name>(
-                                &game_state.world,
-                                *reactor,
-                            )
-                            .as_str(),
-                        );
+                        event.render_with_context(ui, &(&game_state.world, &LogLevel::Info));
+
+                        ui.text("Choose how to react:");
+
+                        for (reactor, options) in options.iter() {
+                            if !systems::ai::is_decided_by_player(&game_state.world, *reactor) {
+                                continue;
+                            }
+
+                            ui.separator_with_text(
+                                systems::helpers::get_component_clone::<Name>(
+                                    &game_state.world,
+                                    *reactor,
+                                )
+                                .as_str(),
+                            );
 
-                        for option in options {
-                            let option_selected = if let Some(decisions) = decisions
-                                && let Some(decision) = decisions.get(reactor)
-                            {
-                                match &decision.kind {
-                                    ActionDecisionKind::Reaction { choice, .. } => {
-                                        choice.as_ref() == Some(option)
+                            for option in options {
+                                let option_selected = match decided.get(reactor) {
+                                    Some(ActionDecision {
+                                        kind: ActionDecisionKind::Reaction { choice, .. },
+                                        ..
+                                    }) => choice.as_ref() == Some(option),
+                                    _ => false,
+                                };
+
+                                if render_button_selectable(
+                                    ui,
+                                    format!(
+                                        "{}##{:?}{:?}",
+                                        option.reaction_id, option.resource_cost, reactor
+                                    ),
+                                    [0., 0.],
+                                    option_selected,
+                                ) {
+                                    submissions.push((*reactor, Some(option.clone())));
+                                }
+
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        (
+                                            &option.reaction_id,
+                                            &option.context,
+                                            &option.resource_cost,
+                                        )
+                                            .render_with_context(ui, (&game_state.world, *reactor));
+                                    });
+                                }
+
+                                ui.same_line();
+                                ui.text_disabled(cost_summary(&option.resource_cost));
+
+                                ui.same_line();
+                                let key = (*reactor, option.reaction_id.clone());
+                                let mut always_use = self.always_use.contains(&key);
+                                if ui.checkbox(
+                                    format!("Always use##{:?}{:?}", option.reaction_id, reactor),
+                                    &mut always_use,
+                                ) {
+                                    if always_use {
+                                        self.always_use.insert(key);
+                                    } else {
+                                        self.always_use.remove(&key);
                                     }
-                                    ActionDecisionKind::Action { .. } => false,
                                 }
-                            } else {
-                                false
-                            };
-
-                            if render_button_selectable(
-                                ui,
-                                format!(
-                                    "{}##{:?}{:?}",
-                                    option.reaction_id, option.resource_cost, reactor
-                                ),
-                                [0., 0.],
-                                option_selected,
-                            ) {
-                                (button_clicked, entity, choice) =
-                                    (true, Some(reactor), Some(option.clone()));
                             }
 
-                            if ui.is_item_hovered() {
-                                ui.tooltip(|| {
-                                    (&option.reaction_id, &option.context, &option.resource_cost)
-                                        .render_with_context(ui, (&game_state.world, *reactor));
-                                });
+                            if options.len() > 0 {
+                                ui.separator();
+                                if ui.button(format!("Don't react##{:?}", reactor)) {
+                                    submissions.push((*reactor, None));
+                                }
                             }
                         }
 
-                        if options.len() > 0 {
+                        if auto_decline_timeout > 0.0 {
                             ui.separator();
-                            if ui.button(format!("Don't react##{:?}", reactor)) {
-                                (button_clicked, entity, choice) = (true, Some(reactor), None);
-                            }
+                            ui.text_disabled(format!(
+                                "Declining automatically in {:.0} s",
+                                (auto_decline_timeout - *elapsed).max(0.0)
+                            ));
                         }
-                    }
 
-                    ui.separator();
-
-                    let mut decision_keys = decisions
-                        .map(|decisions| decisions.keys().cloned().collect::<Vec<Entity>>());
-
-                    if button_clicked && let Some(reactor) = entity {
-                        info!("Submitting reaction decision for reactor {:?}...", reactor);
-                        let result = game_state.submit_decision(ActionDecision {
-                            response_to: *prompt_id,
-                            kind: ActionDecisionKind::Reaction {
-                                event: event.clone(),
-                                reactor: *reactor,
-                                choice,
-                            },
-                        });
-                        match result {
-                            Ok(()) => {
-                                if let Some(keys) = &mut decision_keys {
+                        let mut decided_reactors = decided.keys().cloned().collect::<HashSet<_>>();
+
+                        for (reactor, choice) in submissions {
+                            info!("Submitting reaction decision for reactor {:?}...", reactor);
+                            let result = game_state.submit_decision(ActionDecision {
+                                response_to: *prompt_id,
+                                kind: ActionDecisionKind::Reaction {
+                                    event: event.clone(),
+                                    reactor,
+                                    choice,
+                                },
+                            });
+                            match result {
+                                Ok(()) => {
                                     info!("Submitted reaction decision for reactor {:?}", reactor);
-                                    keys.push(*reactor);
+                                    decided_reactors.insert(reactor);
+                                }
+                                Err(action_error) => {
+                                    error!(
+                                        "Failed to submit reaction decision: {:#?}",
+                                        action_error
+                                    );
                                 }
-                            }
-                            Err(action_error) => {
-                                error!("Failed to submit reaction decision: {:#?}", action_error);
                             }
                         }
-                    }
 
-                    if let Some(decisions) = decision_keys
-                        && options.keys().all(|entity| {
-                            !systems::ai::is_player_controlled(&game_state.world, *entity)
-                                || decisions.contains(entity)
-                        })
-                    {
-                        info!("All reactions submitted, closing window.");
-                        new_state = Some(ReactionWindowState::Pending);
-                    }
-                },
-            ),
+                        if options.keys().all(|entity| {
+                            !systems::ai::is_decided_by_player(&game_state.world, *entity)
+                                || decided_reactors.contains(entity)
+                        }) {
+                            info!("All reactions submitted, closing window.");
+                            new_state = Some(ReactionWindowState::Pending);
+                            ui.close_current_popup();
+                        }
+                    });
+
+                // Make sure the prompt can't get stuck if the popup was closed
+                if popup.is_none() {
+                    self.open_popup = true;
+                }
+            }
         }
 
         if let Some(state) = new_state {
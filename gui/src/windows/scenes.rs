@@ -0,0 +1,277 @@
+use std::{fs, path::Path};
+
+use nat20_core::{
+    engine::game_state::GameState,
+    systems::scene::{Scene, ScenePlayer, SceneStep},
+};
+use parry3d::na::Point3;
+use tracing::{error, warn};
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext, ui::utils::render_button_disabled_conditionally,
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+pub static SCENE_DIRECTORY: &str = "assets/scenes";
+
+/// Where the camera was when a camera step started, so it can be moved
+/// smoothly from there
+struct CameraMove {
+    step: usize,
+    target: Point3<f32>,
+    radius: f32,
+}
+
+/// Lists the scenes written by content authors and plays them. While a scene
+/// is playing it controls the camera and shows its dialogue.
+pub struct ScenesWindow {
+    scenes: Vec<Scene>,
+    selected: Option<usize>,
+    loaded: bool,
+    player: Option<ScenePlayer>,
+    camera_move: Option<CameraMove>,
+}
+
+impl ScenesWindow {
+    pub fn new() -> Self {
+        Self {
+            scenes: Vec::new(),
+            selected: None,
+            loaded: false,
+            player: None,
+            camera_move: None,
+        }
+    }
+
+    pub fn reload(&mut self) {
+        let selected_name = self
+            .selected
+            .and_then(|index| self.scenes.get(index))
+            .map(|scene| scene.name.clone());
+
+        self.scenes = load_scenes(Path::new(SCENE_DIRECTORY));
+        self.selected =
+            selected_name.and_then(|name| self.scenes.iter().position(|scene| scene.name == name));
+        self.loaded = true;
+    }
+
+    fn play(&mut self, scene: Scene) {
+        match ScenePlayer::new(scene) {
+            Ok(player) => {
+                self.player = Some(player);
+                self.camera_move = None;
+            }
+            Err(error) => warn!("Failed to play scene: {:?}", error),
+        }
+    }
+
+    fn update(&mut self, ui: &imgui::Ui, gui_state: &mut GuiState, game_state: &mut GameState) {
+        let Some(player) = &mut self.player else {
+            return;
+        };
+
+        player.update(game_state, ui.io().delta_time);
+        if player.is_finished() {
+            self.player = None;
+            self.camera_move = None;
+            return;
+        }
+
+        if let Some(SceneStep::Camera {
+            target,
+            distance,
+            seconds,
+        }) = player.current_step()
+        {
+            let camera = &mut gui_state.camera;
+            if self
+                .camera_move
+                .as_ref()
+                .is_none_or(|camera_move| camera_move.step != player.step_index())
+            {
+                self.camera_move = Some(CameraMove {
+                    step: player.step_index(),
+                    target: camera.target,
+                    radius: camera.radius,
+                });
+            }
+            let start = self.camera_move.as_ref().unwrap();
+
+            if let Some(end) = player.position(&game_state.world, target) {
+                let t = if *seconds > 0.0 {
+                    (player.step_elapsed() / seconds).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+                camera.target = start.target + (end - start.target) * t;
+                if let Some(distance) = distance {
+                    camera.radius = start.radius + (distance - start.radius) * t;
+                }
+            }
+        }
+
+        let mut advance = false;
+        let mut skip = false;
+        if let Some(SceneStep::Dialogue { speaker, line }) = player.current_step() {
+            gui_state.window_manager.render_window(
+                ui,
+                &player.scene().name,
+                &anchor::TOP_CENTER,
+                AUTO_RESIZE,
+                &mut true,
+                || {
+                    ui.text_colored([1.0, 0.85, 0.4, 1.0], speaker);
+                    let wrap_token = ui.push_text_wrap_pos_with_pos(400.0);
+                    ui.text(line);
+                    wrap_token.end();
+                    ui.separator();
+                    advance = ui.button("Continue");
+                    ui.same_line();
+                    skip = ui.button("Skip scene");
+                },
+            );
+        }
+
+        if skip {
+            // The rest of the scene still plays out, just without waiting for
+            // the camera or the dialogue
+            while !player.is_finished() {
+                player.advance();
+                player.update(game_state, 0.0);
+            }
+        } else if advance {
+            player.advance();
+        }
+    }
+}
+
+fn load_scenes(directory: &Path) -> Vec<Scene> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let result = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<Scene>(&json).map_err(|error| error.to_string())
+                });
+            match result {
+                Ok(scene) => Some(scene),
+                Err(error) => {
+                    error!("Failed to load scene from {:?}: {}", path, error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+impl RenderableMutWithContext<&mut GameState> for ScenesWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        // Scenes keep playing when the window is closed
+        self.update(ui, gui_state, game_state);
+
+        let mut scenes_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_SCENES);
+
+        if !scenes_open {
+            return;
+        }
+
+        if !self.loaded {
+            self.reload();
+        }
+
+        let mut play = None;
+        let mut stop = false;
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Scenes",
+            &anchor::CENTER_RIGHT,
+            AUTO_RESIZE,
+            &mut scenes_open,
+            || {
+                if self.scenes.is_empty() {
+                    ui.text_disabled(format!("No scenes in '{}'", SCENE_DIRECTORY));
+                }
+                for (index, scene) in self.scenes.iter().enumerate() {
+                    let label = format!("{} ({} steps)", scene.name, scene.steps.len());
+                    if ui
+                        .selectable_config(&label)
+                        .selected(self.selected == Some(index))
+                        .build()
+                    {
+                        self.selected = Some(index);
+                    }
+                }
+
+                if let Some(player) = &self.player {
+                    ui.text(format!(
+                        "Playing '{}' ({}/{})",
+                        player.scene().name,
+                        player.step_index() + 1,
+                        player.scene().steps.len()
+                    ));
+                    stop = ui.button("Stop");
+                } else {
+                    let in_combat = !game_state.in_combat.is_empty();
+                    let disabled_reason = if in_combat {
+                        "Cannot play a scene while an encounter is running"
+                    } else {
+                        "Select a scene to play"
+                    };
+                    if render_button_disabled_conditionally(
+                        ui,
+                        "Play",
+                        [0.0, 0.0],
+                        in_combat || self.selected.is_none(),
+                        disabled_reason,
+                    ) {
+                        play = self.selected;
+                    }
+                }
+                ui.same_line();
+                if ui.button("Refresh") {
+                    self.reload();
+                }
+            },
+        );
+
+        if stop {
+            self.player = None;
+            self.camera_move = None;
+        }
+
+        if let Some(index) = play
+            && let Some(scene) = self.scenes.get(index)
+        {
+            self.play(scene.clone());
+        }
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_SCENES, scenes_open);
+    }
+}
@@ -3,22 +3,30 @@ use core::f32;
 use hecs::{Entity, World};
 use imgui::MouseButton;
 use nat20_core::{
-    components::id::Name,
+    components::id::{ClassId, Name, SpeciesId},
     engine::game_state::GameState,
     entities::{
         character::{Character, CharacterTag},
         monster::{Monster, MonsterTag},
     },
-    systems::{self, time::RestKind},
+    registry::registry::{ClassesRegistry, SpeciesRegistry},
+    systems::{
+        self,
+        generator::{self, MonsterFilter},
+        time::RestKind,
+    },
     test_utils::fixtures,
 };
 use parry3d::na::Point3;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     render::{
         common::utils::RenderableMutWithContext,
-        ui::{entities::CreatureRenderMode, utils::ImguiRenderableWithContext},
+        ui::{
+            entities::CreatureRenderMode,
+            utils::{ImguiRenderableWithContext, render_option_combo},
+        },
     },
     state::gui_state::GuiState,
     windows::anchor::{AUTO_RESIZE, TOP_LEFT},
@@ -31,6 +39,13 @@ pub struct SpawnPredefinedWindow {
     entity_to_spawn: Option<Entity>,
     current_entity: Option<Entity>,
     spawning_completed: bool,
+    /// Party level used to pick the challenge rating of generated monsters
+    party_level: i32,
+    /// Class of quick built characters, or a random class if not set
+    quick_build_class: Option<ClassId>,
+    /// Species of quick built characters, or a random species if not set
+    quick_build_species: Option<SpeciesId>,
+    quick_build_level: i32,
 }
 
 impl SpawnPredefinedWindow {
@@ -56,6 +71,10 @@ impl SpawnPredefinedWindow {
             entity_to_spawn: None,
             current_entity: None,
             spawning_completed: false,
+            party_level: 1,
+            quick_build_class: None,
+            quick_build_species: None,
+            quick_build_level: 1,
         }
     }
 
@@ -105,6 +124,54 @@ impl RenderableMutWithContext<&mut GameState> for SpawnPredefinedWindow {
                         }
                     });
 
+                ui.separator_with_text("Generate");
+                let width_token = ui.push_item_width(100.0);
+                ui.input_int("Party Level", &mut self.party_level).build();
+                width_token.end();
+                self.party_level = self.party_level.clamp(1, 20);
+                if ui.button("Generate")
+                    && let Some(monster) = generator::generate_monster(
+                        &mut self.world,
+                        &MonsterFilter::for_party_level(self.party_level as u8),
+                    )
+                {
+                    systems::time::on_rest_end(&mut self.world, &[monster.id()], &RestKind::Long);
+                }
+
+                ui.separator_with_text("Quick Build");
+                let mut classes = ClassesRegistry::keys().cloned().collect::<Vec<_>>();
+                classes.sort();
+                let width_token = ui.push_item_width(200.0);
+                if let Some(class) =
+                    render_option_combo(ui, "Class", &classes, self.quick_build_class.as_ref())
+                {
+                    self.quick_build_class = class;
+                }
+                let mut species = SpeciesRegistry::keys().cloned().collect::<Vec<_>>();
+                species.sort();
+                if let Some(species) =
+                    render_option_combo(ui, "Species", &species, self.quick_build_species.as_ref())
+                {
+                    self.quick_build_species = species;
+                }
+                ui.input_int("Level", &mut self.quick_build_level).build();
+                width_token.end();
+                self.quick_build_level = self.quick_build_level.clamp(1, 20);
+                if ui.button("Quick Build") {
+                    match systems::quick_build::quick_build(
+                        Name::new("Quick Build"),
+                        self.quick_build_class.clone(),
+                        self.quick_build_species.clone(),
+                        self.quick_build_level as u8,
+                    ) {
+                        Ok(character) => {
+                            let entity = self.world.spawn(character);
+                            systems::time::on_rest_end(&mut self.world, &[entity], &RestKind::Long);
+                        }
+                        Err(error) => warn!("Failed to quick build character: {:?}", error),
+                    }
+                }
+
                 if let Some(entity) = self.entity_to_spawn {
                     if self.current_entity.is_none() {
                         let spawned_entity = if let Ok(_) = self.world.get::<&CharacterTag>(entity)
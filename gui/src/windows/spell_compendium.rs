@@ -0,0 +1,319 @@
+use hecs::{Entity, World};
+use imgui::ChildFlags;
+use nat20_core::{
+    components::{
+        actions::action::ActionContext,
+        id::{ClassId, Name, SpellId},
+        spells::{
+            spell::{MagicSchool, Spell, SpellFlag},
+            spellbook::{GrantedSpellSource, SpellSource, Spellbook},
+        },
+    },
+    engine::game_state::GameState,
+    registry::registry::{ClassesRegistry, SpellsRegistry},
+    systems::{self, time::RestKind},
+    test_utils::fixtures,
+};
+use strum::IntoEnumIterator;
+use tracing::info;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext,
+        ui::{
+            text::{TextKind, TextSegment},
+            utils::{ImguiRenderable, ImguiRenderableWithContext},
+        },
+    },
+    state::{self, gui_state::GuiState},
+    windows::anchor::{self, AUTO_RESIZE},
+};
+
+#[derive(Default)]
+struct SpellFilter {
+    search: String,
+    level: Option<u8>,
+    school: Option<MagicSchool>,
+    class: Option<ClassId>,
+    concentration_only: bool,
+    ritual_only: bool,
+}
+
+impl SpellFilter {
+    fn matches(&self, spell: &Spell) -> bool {
+        let search = self.search.to_lowercase();
+        (search.is_empty() || spell.id().to_string().to_lowercase().contains(&search))
+            && self.level.is_none_or(|level| spell.base_level() == level)
+            && self.school.is_none_or(|school| spell.school() == school)
+            && self.class.as_ref().is_none_or(|class_id| {
+                ClassesRegistry::get(class_id)
+                    .is_some_and(|class| class.spell_list(&None).contains(spell.id()))
+            })
+            && (!self.concentration_only || spell.has_flag(SpellFlag::Concentration))
+            && (!self.ritual_only || spell.has_flag(SpellFlag::Ritual))
+    }
+}
+
+pub struct SpellCompendiumWindow {
+    filter: SpellFilter,
+    selected: Option<SpellId>,
+    /// Caster used to render the spell details when no character is selected
+    preview_world: World,
+    preview_caster: Entity,
+    /// Result of the last learn/prepare attempt
+    message: Option<Result<String, String>>,
+}
+
+impl SpellCompendiumWindow {
+    pub fn new() -> Self {
+        let mut preview_world = World::new();
+        let preview_caster = fixtures::creatures::heroes::wizard(&mut preview_world).id();
+        systems::time::on_rest_end(&mut preview_world, &[preview_caster], &RestKind::Long);
+
+        Self {
+            filter: SpellFilter::default(),
+            selected: None,
+            preview_world,
+            preview_caster,
+            message: None,
+        }
+    }
+
+    fn render_filters(&mut self, ui: &imgui::Ui) {
+        ui.input_text("Search", &mut self.filter.search).build();
+
+        let width_token = ui.push_item_width(120.0);
+
+        let mut levels = vec!["Any".to_string(), "Cantrip".to_string()];
+        levels
+            .extend((1..=systems::spells::MAX_SPELL_LEVEL).map(|level| format!("Level {}", level)));
+        let mut level_index = self.filter.level.map_or(0, |level| level as usize + 1);
+        if ui.combo_simple_string("Level", &mut level_index, &levels) {
+            self.filter.level = level_index.checked_sub(1).map(|level| level as u8);
+        }
+        ui.same_line();
+
+        let schools = MagicSchool::iter().collect::<Vec<_>>();
+        let mut school_labels = vec!["Any".to_string()];
+        school_labels.extend(schools.iter().map(|school| school.to_string()));
+        let mut school_index = self
+            .filter
+            .school
+            .and_then(|school| schools.iter().position(|s| *s == school))
+            .map_or(0, |index| index + 1);
+        if ui.combo_simple_string("School", &mut school_index, &school_labels) {
+            self.filter.school = school_index.checked_sub(1).map(|index| schools[index]);
+        }
+
+        let mut classes = ClassesRegistry::values()
+            .filter(|class| class.spellcasting_rules(&None).is_some())
+            .map(|class| class.id.clone())
+            .collect::<Vec<_>>();
+        classes.sort_by_key(|class| class.to_string());
+        let mut class_labels = vec!["Any".to_string()];
+        class_labels.extend(classes.iter().map(|class| class.to_string()));
+        let mut class_index = self
+            .filter
+            .class
+            .as_ref()
+            .and_then(|class| classes.iter().position(|c| c == class))
+            .map_or(0, |index| index + 1);
+        if ui.combo_simple_string("Class List", &mut class_index, &class_labels) {
+            self.filter.class = class_index
+                .checked_sub(1)
+                .map(|index| classes[index].clone());
+        }
+
+        width_token.end();
+
+        ui.checkbox("Concentration", &mut self.filter.concentration_only);
+        ui.same_line();
+        ui.checkbox("Ritual", &mut self.filter.ritual_only);
+    }
+
+    fn render_learn_buttons(
+        &mut self,
+        ui: &imgui::Ui,
+        game_state: &mut GameState,
+        entity: Entity,
+        spell: &Spell,
+    ) {
+        let name = systems::helpers::get_component_clone::<Name>(&game_state.world, entity);
+        ui.separator_with_text(name.as_str());
+
+        if let Some(status) = spell_status(&game_state.world, entity, spell.id()) {
+            TextSegment::new(status, TextKind::Green).render(ui);
+        }
+
+        let learn_label = if spell.is_cantrip() {
+            "Learn Cantrip"
+        } else {
+            "Learn"
+        };
+        let result = if ui.button(learn_label) {
+            Some((
+                "learned",
+                systems::spells::learn_spell(&mut game_state.world, entity, spell.id()),
+            ))
+        } else if !spell.is_cantrip() && {
+            ui.same_line();
+            ui.button("Prepare")
+        } {
+            Some((
+                "prepared",
+                systems::spells::prepare_spell(&mut game_state.world, entity, spell.id()),
+            ))
+        } else {
+            None
+        };
+
+        if let Some((verb, result)) = result {
+            self.message = Some(match result {
+                Ok(()) => {
+                    let message = format!("{} {} {}", name.as_str(), verb, spell.id());
+                    info!("{}", message);
+                    Ok(message)
+                }
+                Err(error) => Err(format!("{} could not be {}: {:?}", spell.id(), verb, error)),
+            });
+        }
+
+        match &self.message {
+            Some(Ok(message)) => TextSegment::new(message, TextKind::Green).render(ui),
+            Some(Err(message)) => TextSegment::new(message, TextKind::Red).render(ui),
+            None => {}
+        }
+    }
+}
+
+/// Whether the spell is already prepared or known by one of the entity's classes
+fn spell_status(world: &World, entity: Entity, spell_id: &SpellId) -> Option<&'static str> {
+    let spellbook = world.get::<&Spellbook>(entity).ok()?;
+    let mut status = None;
+    for class_and_subclass in systems::spells::classes_with_spell(world, entity, spell_id) {
+        let Some(state) = spellbook.class_state(&class_and_subclass) else {
+            continue;
+        };
+        let selections = &state.selections;
+        if selections.prepared_spells.contains(spell_id)
+            || selections.always_prepared.contains(spell_id)
+        {
+            return Some("Prepared");
+        }
+        if selections.cantrips.contains(spell_id) || selections.learned_spells.contains(spell_id) {
+            status = Some("Known");
+        }
+    }
+    status
+}
+
+/// Context used to show what the spell does when cast by the caster at its
+/// base level
+fn preview_context(world: &World, caster: Entity, spell: &Spell) -> ActionContext {
+    let source = systems::spells::classes_with_spell(world, caster, spell.id())
+        .into_iter()
+        .next()
+        .map(SpellSource::Class)
+        .unwrap_or_else(|| SpellSource::Granted {
+            source: GrantedSpellSource::ParentSpell(spell.id().clone()),
+            level: spell.base_level(),
+        });
+
+    ActionContext::Spell {
+        id: spell.id().clone(),
+        source,
+        level: spell.base_level(),
+        metamagic: Vec::new(),
+    }
+}
+
+impl RenderableMutWithContext<&mut GameState> for SpellCompendiumWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+    ) {
+        let mut compendium_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_SPELL_COMPENDIUM);
+
+        if !compendium_open {
+            return;
+        }
+
+        let selected_entity = gui_state
+            .selected_entity
+            .filter(|entity| game_state.world.get::<&Spellbook>(*entity).is_ok());
+
+        gui_state.window_manager.render_window(
+            ui,
+            "Spell Compendium",
+            &anchor::CENTER,
+            AUTO_RESIZE,
+            &mut compendium_open,
+            || {
+                self.render_filters(ui);
+
+                let mut spells = SpellsRegistry::values()
+                    .filter(|spell| self.filter.matches(spell))
+                    .collect::<Vec<_>>();
+                spells.sort_by_key(|spell| (spell.base_level(), spell.id().to_string()));
+
+                ui.child_window("Spell List")
+                    .child_flags(ChildFlags::BORDERS)
+                    .size([250.0, 400.0])
+                    .build(|| {
+                        if spells.is_empty() {
+                            ui.text_disabled("No spells match the filters");
+                        }
+
+                        for spell in &spells {
+                            let label = if spell.is_cantrip() {
+                                format!("{} (Cantrip)", spell.id())
+                            } else {
+                                format!("{} ({})", spell.id(), spell.base_level())
+                            };
+                            if ui
+                                .selectable_config(&label)
+                                .selected(self.selected.as_ref() == Some(spell.id()))
+                                .build()
+                            {
+                                self.selected = Some(spell.id().clone());
+                                self.message = None;
+                            }
+                        }
+                    });
+
+                let Some(spell) = self.selected.as_ref().and_then(SpellsRegistry::get) else {
+                    return;
+                };
+
+                ui.same_line();
+                ui.group(|| {
+                    let (world, caster) = match selected_entity {
+                        Some(entity) => (&game_state.world, entity),
+                        None => (&self.preview_world, self.preview_caster),
+                    };
+                    let context = preview_context(world, caster, spell);
+                    (&spell.action().id, &context, &spell.action().resource_cost)
+                        .render_with_context(ui, (world, caster));
+
+                    if let Some(entity) = selected_entity {
+                        self.render_learn_buttons(ui, game_state, entity, spell);
+                    } else {
+                        TextSegment::new(
+                            "Select a spellcaster to learn or prepare spells",
+                            TextKind::Details,
+                        )
+                        .render(ui);
+                    }
+                });
+            },
+        );
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_SPELL_COMPENDIUM, compendium_open);
+    }
+}
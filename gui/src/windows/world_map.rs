@@ -0,0 +1,439 @@
+use hecs::Entity;
+use nat20_core::{
+    components::{
+        id::Name,
+        items::{inventory::ItemContainer, supply::SupplyKind},
+        travel::{Location, LocationKind, WorldMap},
+    },
+    engine::{game_state::GameState, rules::OptionalRule},
+    registry::registry::ItemsRegistry,
+    systems::{self, preset, travel},
+};
+use tracing::warn;
+
+use crate::{
+    render::{
+        common::utils::RenderableMutWithContext, ui::utils::render_button_disabled_conditionally,
+    },
+    state::{self, gui_state::GuiState},
+    windows::{
+        anchor::{self, AUTO_RESIZE},
+        map_generator::MapGeneratorWindow,
+    },
+};
+
+const CANVAS_SIZE: [f32; 2] = [420.0, 280.0];
+/// Space between the locations furthest out and the edge of the canvas
+const CANVAS_MARGIN: f32 = 30.0;
+const LOCATION_RADIUS: f32 = 8.0;
+
+fn location_color(kind: &LocationKind) -> [f32; 4] {
+    match kind {
+        LocationKind::Town => [0.9, 0.75, 0.3, 1.0],
+        LocationKind::Dungeon => [0.75, 0.2, 0.2, 1.0],
+        LocationKind::Wilderness => [0.3, 0.7, 0.3, 1.0],
+    }
+}
+
+/// Overworld map of the adventure being played. Clicking a location travels
+/// there, and the services of the current location can be used from here.
+pub struct WorldMapWindow {
+    /// The encounter the party ran into on their last journey or night of
+    /// camping, shown until they do something else
+    last_encounter: Option<String>,
+}
+
+impl WorldMapWindow {
+    pub fn new() -> Self {
+        Self {
+            last_encounter: None,
+        }
+    }
+
+    fn travel(
+        &mut self,
+        destination: &str,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+        map_generator: &mut MapGeneratorWindow,
+    ) {
+        let outcome = match travel::travel(game_state, destination) {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                warn!("Failed to travel to {}: {:?}", destination, error);
+                return;
+            }
+        };
+
+        self.last_encounter = outcome.encounter.map(|encounter| {
+            spawn_encounter(&encounter, gui_state, game_state, map_generator);
+            format!("Encounter after {} hours: {}", outcome.hours, encounter)
+        });
+    }
+
+    fn camp(
+        &mut self,
+        gui_state: &mut GuiState,
+        game_state: &mut GameState,
+        map_generator: &mut MapGeneratorWindow,
+    ) {
+        // The party keeps watch in the same order as they're listed in
+        let party = party(game_state);
+        if party.is_empty() {
+            warn!("There's no party to make camp");
+            return;
+        }
+        let outcome = match travel::camp(game_state, party.clone(), &party) {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                warn!("Party failed to make camp: {:?}", error);
+                return;
+            }
+        };
+
+        self.last_encounter = outcome.encounter.map(|encounter| {
+            spawn_encounter(&encounter, gui_state, game_state, map_generator);
+            let sentry = outcome
+                .sentry
+                .and_then(|sentry| game_state.world.get::<&Name>(sentry).ok())
+                .map(|name| name.as_str().to_string())
+                .unwrap_or_else(|| "no one".to_string());
+            format!(
+                "Woken after {} hours by {}, with {} on watch",
+                outcome.hours, encounter, sentry
+            )
+        });
+    }
+}
+
+/// Spawn an encounter from the adventure, along with its map
+fn spawn_encounter(
+    encounter: &str,
+    gui_state: &mut GuiState,
+    game_state: &mut GameState,
+    map_generator: &mut MapGeneratorWindow,
+) {
+    let Some(preset) = systems::campaign::adventure(game_state)
+        .and_then(|adventure| adventure.encounter(encounter))
+    else {
+        warn!("Unknown encounter '{}'", encounter);
+        return;
+    };
+
+    let navmesh_config = map_generator.navmesh_config.clone().build();
+    match preset::restore(game_state, preset, &navmesh_config) {
+        Ok(entities) => {
+            if let Some(map_config) = &preset.map {
+                map_generator.set_map(gui_state, game_state, map_config, entities);
+            }
+        }
+        Err(error) => warn!("Failed to spawn encounter '{}': {:?}", encounter, error),
+    }
+}
+
+/// Load the map of a quest which has just started
+fn load_quest_map(
+    gui_state: &mut GuiState,
+    game_state: &mut GameState,
+    map_generator: &mut MapGeneratorWindow,
+) {
+    if game_state
+        .campaign
+        .as_ref()
+        .is_none_or(|campaign| campaign.pending_map.is_none())
+    {
+        return;
+    }
+
+    let navmesh_config = map_generator.navmesh_config.clone().build();
+    match systems::campaign::load_pending_map(game_state, &navmesh_config) {
+        Some(Ok((map_config, entities))) => {
+            map_generator.set_map(gui_state, game_state, &map_config, entities);
+        }
+        Some(Err(error)) => warn!("Failed to load quest map: {:?}", error),
+        None => {}
+    }
+}
+
+/// The player-controlled creatures, who travel and rest together
+fn party(game_state: &GameState) -> Vec<Entity> {
+    let mut party = game_state
+        .world
+        .query::<&Name>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| systems::ai::is_player_controlled(&game_state.world, *entity))
+        .collect::<Vec<_>>();
+    party.sort();
+    party
+}
+
+/// Draw the locations and routes, returning the location which was clicked
+fn render_canvas<'a>(
+    ui: &imgui::Ui,
+    world_map: &'a WorldMap,
+    current: Option<&Location>,
+) -> Option<&'a Location> {
+    let origin = ui.cursor_screen_pos();
+    let (min, max) = world_map.locations.iter().fold(
+        ([f32::MAX, f32::MAX], [f32::MIN, f32::MIN]),
+        |(min, max), location| {
+            let [x, y] = location.position;
+            (
+                [min[0].min(x), min[1].min(y)],
+                [max[0].max(x), max[1].max(y)],
+            )
+        },
+    );
+    let scale = ((CANVAS_SIZE[0] - 2.0 * CANVAS_MARGIN) / (max[0] - min[0]).max(f32::EPSILON))
+        .min((CANVAS_SIZE[1] - 2.0 * CANVAS_MARGIN) / (max[1] - min[1]).max(f32::EPSILON));
+    let to_screen = |location: &Location| {
+        let [x, y] = location.position;
+        [
+            origin[0] + CANVAS_MARGIN + (x - min[0]) * scale,
+            origin[1] + CANVAS_MARGIN + (y - min[1]) * scale,
+        ]
+    };
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list
+        .add_rect(
+            origin,
+            [origin[0] + CANVAS_SIZE[0], origin[1] + CANVAS_SIZE[1]],
+            [0.12, 0.12, 0.1, 1.0],
+        )
+        .filled(true)
+        .build();
+
+    for route in &world_map.routes {
+        let (Some(from), Some(to)) = (
+            world_map.location(&route.from),
+            world_map.location(&route.to),
+        ) else {
+            continue;
+        };
+        let (from, to) = (to_screen(from), to_screen(to));
+        draw_list
+            .add_line(from, to, [0.6, 0.55, 0.45, 1.0])
+            .thickness(2.0)
+            .build();
+        let label = format!("{}h", route.hours);
+        draw_list.add_text(
+            [(from[0] + to[0]) / 2.0, (from[1] + to[1]) / 2.0],
+            [0.8, 0.8, 0.8, 1.0],
+            &label,
+        );
+    }
+
+    let mouse = ui.io().mouse_pos;
+    let mut hovered = None;
+    for location in &world_map.locations {
+        let center = to_screen(location);
+        draw_list
+            .add_circle(center, LOCATION_RADIUS, location_color(&location.kind))
+            .filled(true)
+            .build();
+        if current.is_some_and(|current| current.id == location.id) {
+            draw_list
+                .add_circle(center, LOCATION_RADIUS + 4.0, [1.0, 1.0, 1.0, 1.0])
+                .thickness(2.0)
+                .build();
+        }
+        draw_list.add_text(
+            [
+                center[0] + LOCATION_RADIUS + 2.0,
+                center[1] - LOCATION_RADIUS,
+            ],
+            [1.0, 1.0, 1.0, 1.0],
+            &location.name,
+        );
+
+        let (dx, dy) = (mouse[0] - center[0], mouse[1] - center[1]);
+        if dx * dx + dy * dy <= (LOCATION_RADIUS + 4.0).powi(2) {
+            hovered = Some(location);
+        }
+    }
+
+    // Reserve the space so clicks on the canvas don't fall through to the world
+    ui.invisible_button("World Map Canvas", CANVAS_SIZE);
+    let clicked = ui.is_item_clicked();
+    let hovered = hovered.filter(|_| ui.is_item_hovered());
+
+    if let Some(location) = hovered {
+        ui.tooltip(|| {
+            ui.text(&location.name);
+            ui.text_disabled(location.kind.to_string());
+            if !location.description.is_empty() {
+                ui.text(&location.description);
+            }
+            if let Some(current) = current
+                && current.id != location.id
+            {
+                match world_map.path(&current.id, &location.id) {
+                    Some(path) => ui.text(format!(
+                        "{} hours away",
+                        path.iter().map(|(_, hours)| hours).sum::<u32>()
+                    )),
+                    None => ui.text_disabled("No route"),
+                }
+            }
+        });
+    }
+
+    if clicked { hovered } else { None }
+}
+
+impl RenderableMutWithContext<(&mut GameState, &mut MapGeneratorWindow)> for WorldMapWindow {
+    fn render_mut_with_context(
+        &mut self,
+        ui: &imgui::Ui,
+        gui_state: &mut GuiState,
+        (game_state, map_generator): (&mut GameState, &mut MapGeneratorWindow),
+    ) {
+        // Quests can start from anywhere, e.g. a scene or the GM console, so
+        // their maps are picked up here even while the window is closed
+        load_quest_map(gui_state, game_state, map_generator);
+
+        let mut world_map_open = *gui_state
+            .settings
+            .get::<bool>(state::parameters::RENDER_WORLD_MAP);
+
+        if !world_map_open {
+            return;
+        }
+
+        let mut destination = None;
+        let mut rest = false;
+        let mut camp = false;
+        let mut buy = None;
+
+        gui_state.window_manager.render_window(
+            ui,
+            "World Map",
+            &anchor::CENTER,
+            AUTO_RESIZE,
+            &mut world_map_open,
+            || {
+                let Some(adventure) = systems::campaign::adventure(game_state) else {
+                    ui.text_disabled("No adventure is being played");
+                    return;
+                };
+                if adventure.world_map.locations.is_empty() {
+                    ui.text_disabled(format!("'{}' has no world map", adventure.name));
+                    return;
+                }
+
+                let current = travel::current_location(game_state);
+                let in_combat = !game_state.in_combat.is_empty();
+                if let Some(location) = render_canvas(ui, &adventure.world_map, current) {
+                    if in_combat {
+                        warn!("Cannot travel while an encounter is running");
+                    } else {
+                        destination = Some(location.id.clone());
+                    }
+                }
+
+                if let Some(encounter) = &self.last_encounter {
+                    ui.text_colored([1.0, 0.4, 0.3, 1.0], encounter);
+                }
+
+                let Some(location) = current else {
+                    return;
+                };
+                ui.separator_with_text(&location.name);
+                if !location.description.is_empty() {
+                    ui.text_wrapped(&location.description);
+                }
+                if game_state.rule_enabled(OptionalRule::Supplies) {
+                    let supplies = systems::supplies::party_supplies(game_state);
+                    ui.text(format!(
+                        "Supplies: {} days of food, {} days of water",
+                        supplies[&SupplyKind::Food],
+                        supplies[&SupplyKind::Water]
+                    ));
+                }
+
+                if let Some(price) = location.inn_price() {
+                    rest = render_button_disabled_conditionally(
+                        ui,
+                        &format!("Rest at the inn ({})", price),
+                        [0.0, 0.0],
+                        in_combat,
+                        "The party can't rest during combat",
+                    );
+                }
+                camp = render_button_disabled_conditionally(
+                    ui,
+                    "Make camp",
+                    [0.0, 0.0],
+                    in_combat,
+                    "The party can't rest during combat",
+                );
+                if ui.is_item_hovered() && !in_combat {
+                    ui.tooltip_text(
+                        "Take a long rest here. The party takes turns keeping watch, \
+                        and might be woken by an encounter.",
+                    );
+                }
+
+                let stock = location.shop_stock().collect::<Vec<_>>();
+                if !stock.is_empty() {
+                    ui.separator_with_text("Shop");
+                    let buyer = gui_state.selected_entity.filter(|entity| {
+                        systems::ai::is_player_controlled(&game_state.world, *entity)
+                    });
+                    for item_id in stock {
+                        let Some(item) = ItemsRegistry::get(item_id) else {
+                            continue;
+                        };
+                        let _id = ui.push_id(item_id.to_string());
+                        ui.text(format!("{} ({})", item.item().name, item.item().value));
+                        ui.same_line();
+                        if render_button_disabled_conditionally(
+                            ui,
+                            "Buy",
+                            [0.0, 0.0],
+                            buyer.is_none(),
+                            "Select a party member to buy for",
+                        ) {
+                            buy = buyer.map(|buyer| (buyer, item_id.clone()));
+                        }
+                    }
+                }
+            },
+        );
+
+        if let Some(destination) = destination {
+            self.travel(&destination, gui_state, game_state, map_generator);
+        }
+
+        if rest {
+            let party = party(game_state);
+            // Whoever is selected pays for the rooms, otherwise the party
+            // leader does
+            let payer = gui_state
+                .selected_entity
+                .filter(|entity| party.contains(entity))
+                .or(party.first().copied());
+            if let Some(payer) = payer
+                && let Err(error) = travel::rest_at_inn(game_state, payer, party)
+            {
+                warn!("Party failed to rest at the inn: {:?}", error);
+            }
+        }
+
+        if camp {
+            self.camp(gui_state, game_state, map_generator);
+        }
+
+        if let Some((buyer, item_id)) = buy
+            && let Err(error) = travel::buy(game_state, buyer, &item_id)
+        {
+            warn!("Failed to buy {}: {:?}", item_id, error);
+        }
+
+        gui_state
+            .settings
+            .set(state::parameters::RENDER_WORLD_MAP, world_map_open);
+    }
+}